@@ -5,49 +5,100 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use axum::Router;
+use axum::{http::HeaderValue, Router};
 use tokio::sync::RwLock;
 use tower_http::{
-    cors::{Any, CorsLayer},
+    cors::{AllowOrigin, Any, CorsLayer},
     trace::TraceLayer,
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::CONFIG;
+use crate::config::{validation, CONFIG};
 use crate::db;
 use crate::endpoints;
+use crate::services::diagnostics;
+use crate::services::log_buffer::LogBufferLayer;
+use crate::services::notification::events as notification_events;
+use crate::services::tls;
+use crate::services::updates;
 use crate::services::{
-    init_jwt_keys, scheduler, start_network_broadcaster, AppCatalog, AuditService,
-    ChartSyncService, K8sClient, NotificationService,
+    checksums, crypto, init_jwt_keys, runtime_config, scheduler, start_network_broadcaster,
+    trust_store, AppCatalog, AuditService, ChartSyncService, K8sClient, NotificationService,
 };
 use crate::state::AppState;
 
 /// Bootstrap and run the application
 pub async fn run() -> anyhow::Result<()> {
     init_tracing();
+    diagnostics::install_panic_hook();
+    if let Some(crash) = diagnostics::load_previous_crash() {
+        tracing::warn!(
+            "Previous run crashed at {}: {}",
+            crash.timestamp,
+            crash.message
+        );
+    }
 
     tracing::info!("Starting Kubarr backend v{}", env!("CARGO_PKG_VERSION"));
 
+    // Refuse to start on critical misconfiguration instead of running on defaults
+    let report = validation::validate();
+    report.log();
+    if report.has_errors() {
+        let keys: Vec<&str> = report.errors().map(|i| i.key).collect();
+        anyhow::bail!(
+            "Invalid configuration ({}); run `kubarr check-config` for details",
+            keys.join(", ")
+        );
+    }
+
     let state = init_services().await?;
 
     // Start background network metrics broadcaster
     start_network_broadcaster(state.clone());
     tracing::info!("Network metrics broadcaster started");
 
+    // Check for a new Kubarr version and finish verifying a pending upgrade
+    updates::resume(state.clone());
+
     let app = create_app(state);
 
     serve(app).await
 }
 
 /// Initialize tracing/logging
+///
+/// Unless `RUST_LOG` pins the filter, the level follows the runtime
+/// `log_level` setting.
 fn init_tracing() {
+    let pinned = tracing_subscriber::EnvFilter::try_from_default_env().ok();
+    let dynamic = pinned.is_none();
+    let filter = pinned.unwrap_or_else(|| log_filter(&runtime_config::current().log_level));
+    let (filter, handle) = tracing_subscriber::reload::Layer::new(filter);
+
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| format!("kubarr={}", CONFIG.log_level).into()),
-        )
+        .with(filter)
         .with(tracing_subscriber::fmt::layer().with_ansi(false))
+        .with(LogBufferLayer)
         .init();
+
+    if dynamic {
+        let mut changes = runtime_config::subscribe();
+        tokio::spawn(async move {
+            while changes.changed().await.is_ok() {
+                let level = changes.borrow_and_update().log_level.clone();
+                if let Err(e) = handle.reload(log_filter(&level)) {
+                    tracing::warn!("Failed to apply log level {}: {}", level, e);
+                } else {
+                    tracing::info!("Log level set to {}", level);
+                }
+            }
+        });
+    }
+}
+
+fn log_filter(level: &str) -> tracing_subscriber::EnvFilter {
+    format!("kubarr={}", level).into()
 }
 
 /// Initialize all application services
@@ -55,9 +106,19 @@ async fn init_services() -> anyhow::Result<AppState> {
     let k8s_client = init_kubernetes().await;
     let catalog = init_catalog();
 
+    // Stored credentials cannot be read, nor safely written, without the key
+    crypto::load_key(k8s_client.read().await.as_ref())
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load the encryption key: {}", e))?;
+    if crypto::enabled() {
+        tracing::info!("Stored credentials are encrypted");
+    }
+
     // Create chart sync service and run initial sync
     let chart_sync = Arc::new(ChartSyncService::new(catalog.clone()));
-    if let Err(e) = chart_sync.sync().await {
+    if CONFIG.outbound.offline {
+        tracing::info!("Offline mode: skipping chart sync, charts come from imported bundles");
+    } else if let Err(e) = chart_sync.sync().await {
         tracing::warn!("Initial chart sync failed: {}", e);
     }
 
@@ -65,15 +126,43 @@ async fn init_services() -> anyhow::Result<AppState> {
     let conn = init_database(&k8s_client).await;
 
     let audit = AuditService::new();
+    audit
+        .set_integrity_key(CONFIG.audit.integrity_key.clone())
+        .await;
     let notification = NotificationService::new();
 
     // If database is available, initialize services that need it
     if let Some(ref db) = conn {
         audit.set_db(db.clone()).await;
         notification.set_db(db.clone()).await;
+        if let Err(e) = runtime_config::reload(db).await {
+            tracing::warn!("Failed to load runtime configuration: {}", e);
+        }
+        if let Err(e) = trust_store::load(db).await {
+            tracing::warn!("Failed to load trusted certificates: {}", e);
+        }
+        match crypto::encrypt_existing(db).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Encrypted the stored credentials of {} rows", n),
+            Err(e) => tracing::warn!("Failed to encrypt stored credentials: {}", e),
+        }
         if let Err(e) = notification.init_providers().await {
             tracing::warn!("Failed to initialize notification providers: {}", e);
         }
+        match notification_events::sync_catalog(db).await {
+            Ok((0, 0)) => {}
+            Ok((added, removed)) => tracing::info!(
+                "Notification event catalog synced ({} added, {} removed)",
+                added,
+                removed
+            ),
+            Err(e) => tracing::warn!("Failed to sync notification event catalog: {}", e),
+        }
+        match checksums::fail_interrupted(db).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Marked {} interrupted checksum jobs as failed", n),
+            Err(e) => tracing::warn!("Failed to clean up checksum jobs: {}", e),
+        }
 
         // Initialize JWT keys from database
         if let Err(e) = init_jwt_keys(db).await {
@@ -83,7 +172,14 @@ async fn init_services() -> anyhow::Result<AppState> {
         }
 
         // Start periodic task scheduler
-        scheduler::start_scheduler(Arc::new(db.clone()), chart_sync.clone());
+        scheduler::start_scheduler(
+            Arc::new(db.clone()),
+            chart_sync.clone(),
+            notification.clone(),
+            audit.clone(),
+            k8s_client.clone(),
+            catalog.clone(),
+        );
     } else {
         tracing::info!("Database not available - running in setup mode");
     }
@@ -171,8 +267,18 @@ fn init_catalog() -> Arc<RwLock<AppCatalog>> {
 
 /// Create the main application router
 fn create_app(state: AppState) -> Router {
+    let origins: Vec<HeaderValue> = CONFIG
+        .server
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| origin.parse().ok())
+        .collect();
     let cors = CorsLayer::new()
-        .allow_origin(Any)
+        .allow_origin(if origins.is_empty() {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(origins)
+        })
         .allow_methods(Any)
         .allow_headers(Any);
 
@@ -187,7 +293,19 @@ async fn serve(app: Router) -> anyhow::Result<()> {
     tracing::info!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let Some(acceptor) = tls::TlsAcceptor::from_config(&CONFIG.server)? else {
+        axum::serve(listener, app).await?;
+        return Ok(());
+    };
 
-    Ok(())
+    if let Some(port) = CONFIG.server.http_redirect_port {
+        let https_port = tls::public_https_port(&CONFIG.server);
+        tokio::spawn(async move {
+            if let Err(e) = tls::serve_redirect(port, https_port).await {
+                tracing::error!("HTTP to HTTPS redirect stopped: {}", e);
+            }
+        });
+    }
+    tracing::info!("Serving HTTPS with HTTP/2 on {}", addr);
+    tls::serve(listener, app, acceptor).await
 }
@@ -0,0 +1,17 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    /// HMAC key for tamper-evident audit chaining; unset disables integrity mode
+    pub integrity_key: Option<String>,
+}
+
+impl AuditConfig {
+    pub fn from_env() -> Self {
+        Self {
+            integrity_key: env::var("KUBARR_AUDIT_INTEGRITY_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+        }
+    }
+}
@@ -1,19 +1,125 @@
 use std::env;
 
+use super::server;
+
+/// Default base name of the session cookies
+pub const DEFAULT_SESSION_COOKIE: &str = "kubarr_session";
+
+/// Default lifetime of session access tokens in seconds (15 minutes)
+pub const DEFAULT_ACCESS_TOKEN_TTL: i64 = 15 * 60;
+
+/// Longest allowed lifetime of session access tokens in seconds (1 day)
+pub const MAX_ACCESS_TOKEN_TTL: i64 = 24 * 60 * 60;
+
+/// `SameSite` attribute of the session cookies, set with
+/// `KUBARR_SESSION_COOKIE_SAMESITE`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    #[default]
+    Lax,
+    /// Sent on cross-site requests too; browsers require `Secure` with it
+    None,
+}
+
+impl SameSite {
+    /// Accepted values of `KUBARR_SESSION_COOKIE_SAMESITE`
+    pub const VALUES: &'static [&'static str] = &["strict", "lax", "none"];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "strict" => Some(Self::Strict),
+            "lax" => Some(Self::Lax),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
     pub oauth2_enabled: bool,
     pub oauth2_issuer_url: String,
+    /// Header the ingress forwards the verified client certificate in, e.g.
+    /// `ssl-client-cert`; client certificate auth is off when unset
+    pub client_cert_header: Option<String>,
+    /// Header carrying the ingress's verification result, which must be
+    /// `SUCCESS` for the certificate to be used
+    pub client_cert_verify_header: String,
+    /// Base name of the session cookies; sessions are stored as
+    /// `{name}_0`, `{name}_1`, etc. next to the legacy `{name}` cookie
+    pub session_cookie_name: String,
+    /// Name of the cookie holding the active session slot
+    pub active_session_cookie_name: String,
+    /// Send the session cookies over HTTPS only; defaults to on when the
+    /// external URL is HTTPS
+    pub session_cookie_secure: bool,
+    pub session_cookie_same_site: SameSite,
+    /// `Domain` of the session cookie the app proxy reads; the
+    /// `session_cookie_domain` setting takes precedence
+    pub session_cookie_domain: Option<String>,
+    /// Seconds a session access token is valid before it has to be renewed
+    /// with the session's refresh token
+    pub access_token_ttl: i64,
 }
 
 impl AuthConfig {
     pub fn from_env() -> Self {
+        let oauth2_issuer_url = env::var("KUBARR_OAUTH2_ISSUER_URL")
+            .unwrap_or_else(|_| "http://kubarr:8000/auth".to_string());
+        let session_cookie_name = env::var("KUBARR_SESSION_COOKIE_NAME")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_SESSION_COOKIE.to_string());
+        // Renamed cookies get a matching active slot cookie, so two instances
+        // on one domain keep their sessions apart
+        let active_session_cookie_name = if session_cookie_name == DEFAULT_SESSION_COOKIE {
+            "kubarr_active".to_string()
+        } else {
+            format!("{}_active", session_cookie_name)
+        };
+        let session_cookie_secure = env::var("KUBARR_SESSION_COOKIE_SECURE")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or_else(|_| {
+                server::external_url_from(|key| env::var(key).ok())
+                    .is_some_and(|url| url.starts_with("https://"))
+            });
         Self {
             oauth2_enabled: env::var("KUBARR_OAUTH2_ENABLED")
                 .map(|v| v.to_lowercase() == "true")
                 .unwrap_or(false),
-            oauth2_issuer_url: env::var("KUBARR_OAUTH2_ISSUER_URL")
-                .unwrap_or_else(|_| "http://kubarr:8000/auth".to_string()),
+            oauth2_issuer_url,
+            client_cert_header: env::var("KUBARR_CLIENT_CERT_HEADER")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            client_cert_verify_header: env::var("KUBARR_CLIENT_CERT_VERIFY_HEADER")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "ssl-client-verify".to_string()),
+            session_cookie_name,
+            active_session_cookie_name,
+            session_cookie_secure,
+            session_cookie_same_site: env::var("KUBARR_SESSION_COOKIE_SAMESITE")
+                .ok()
+                .and_then(|v| SameSite::parse(&v))
+                .unwrap_or_default(),
+            session_cookie_domain: env::var("KUBARR_SESSION_COOKIE_DOMAIN")
+                .ok()
+                .map(|d| d.trim().trim_matches('.').to_lowercase())
+                .filter(|d| !d.is_empty()),
+            access_token_ttl: env::var("KUBARR_ACCESS_TOKEN_TTL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|ttl| (1..=MAX_ACCESS_TOKEN_TTL).contains(ttl))
+                .unwrap_or(DEFAULT_ACCESS_TOKEN_TTL),
         }
     }
 }
@@ -0,0 +1,21 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    /// Directory scheduled backups are written to
+    pub dir: String,
+    /// Passphrase that encrypts the secrets in scheduled backups
+    pub passphrase: Option<String>,
+}
+
+impl BackupConfig {
+    pub fn from_env() -> Self {
+        Self {
+            dir: env::var("KUBARR_BACKUP_DIR")
+                .unwrap_or_else(|_| "/var/lib/kubarr/backups".to_string()),
+            passphrase: env::var("KUBARR_BACKUP_PASSPHRASE")
+                .ok()
+                .filter(|p| !p.is_empty()),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::env;
+
+/// Helm backend selected with `KUBARR_HELM_ENGINE`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HelmEngineKind {
+    /// Run the helm binary from `KUBARR_HELM_BINARY`
+    #[default]
+    Subprocess,
+    /// Embedded Helm implementation, for environments without the binary.
+    /// Not available in this build yet; startup validation rejects it.
+    Library,
+}
+
+impl HelmEngineKind {
+    /// Accepted values of `KUBARR_HELM_ENGINE`
+    pub const VALUES: &'static [&'static str] = &["subprocess", "library"];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "subprocess" => Some(Self::Subprocess),
+            "library" => Some(Self::Library),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HelmConfig {
+    /// Which Helm backend deploys apps
+    pub engine: HelmEngineKind,
+    /// Path or name of the helm binary used by the subprocess engine
+    pub binary: String,
+}
+
+impl HelmConfig {
+    pub fn from_env() -> Self {
+        Self {
+            engine: env::var("KUBARR_HELM_ENGINE")
+                .ok()
+                .and_then(|v| HelmEngineKind::parse(&v))
+                .unwrap_or_default(),
+            binary: env::var("KUBARR_HELM_BINARY").unwrap_or_else(|_| "helm".to_string()),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct LogArchiveConfig {
+    /// Directory archives are written to when no S3 bucket is configured
+    pub local_dir: String,
+    /// S3-compatible endpoint, e.g. `https://s3.us-west-000.backblazeb2.com`
+    pub s3_endpoint: Option<String>,
+    /// Bucket archives are written to; setting it selects the S3 store
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    /// Key prefix inside the bucket
+    pub s3_prefix: String,
+}
+
+impl LogArchiveConfig {
+    pub fn from_env() -> Self {
+        let non_empty = |key: &str| env::var(key).ok().filter(|v| !v.is_empty());
+        Self {
+            local_dir: env::var("KUBARR_LOG_ARCHIVE_DIR")
+                .unwrap_or_else(|_| "/var/lib/kubarr/log-archive".to_string()),
+            s3_endpoint: non_empty("KUBARR_LOG_ARCHIVE_S3_ENDPOINT"),
+            s3_bucket: non_empty("KUBARR_LOG_ARCHIVE_S3_BUCKET"),
+            s3_region: non_empty("KUBARR_LOG_ARCHIVE_S3_REGION")
+                .unwrap_or_else(|| "us-east-1".to_string()),
+            s3_access_key_id: non_empty("KUBARR_LOG_ARCHIVE_S3_ACCESS_KEY_ID"),
+            s3_secret_access_key: non_empty("KUBARR_LOG_ARCHIVE_S3_SECRET_ACCESS_KEY"),
+            s3_prefix: env::var("KUBARR_LOG_ARCHIVE_S3_PREFIX")
+                .unwrap_or_else(|_| "kubarr-logs".to_string()),
+        }
+    }
+}
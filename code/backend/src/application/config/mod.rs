@@ -1,8 +1,17 @@
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod charts;
 pub mod database;
+pub mod helm;
 pub mod kubernetes;
+pub mod log_archive;
+pub mod outbound;
+pub mod previews;
+pub mod security;
 pub mod server;
+pub mod updates;
+pub mod validation;
 
 use once_cell::sync::Lazy;
 use std::env;
@@ -14,8 +23,16 @@ pub struct Config {
     pub server: server::ServerConfig,
     pub database: database::DatabaseConfig,
     pub kubernetes: kubernetes::KubernetesConfig,
+    pub log_archive: log_archive::LogArchiveConfig,
+    pub outbound: outbound::OutboundConfig,
     pub auth: auth::AuthConfig,
+    pub audit: audit::AuditConfig,
+    pub backup: backup::BackupConfig,
     pub charts: charts::ChartsConfig,
+    pub helm: helm::HelmConfig,
+    pub previews: previews::PreviewsConfig,
+    pub security: security::SecurityConfig,
+    pub updates: updates::UpdatesConfig,
 
     // Build info
     pub commit_hash: String,
@@ -36,8 +53,16 @@ impl Config {
             server: server::ServerConfig::from_env(),
             database: database::DatabaseConfig::from_env(),
             kubernetes: kubernetes::KubernetesConfig::from_env(),
+            log_archive: log_archive::LogArchiveConfig::from_env(),
+            outbound: outbound::OutboundConfig::from_env(),
             auth: auth::AuthConfig::from_env(),
+            audit: audit::AuditConfig::from_env(),
+            backup: backup::BackupConfig::from_env(),
             charts: charts::ChartsConfig::from_env(),
+            helm: helm::HelmConfig::from_env(),
+            previews: previews::PreviewsConfig::from_env(),
+            security: security::SecurityConfig::from_env(),
+            updates: updates::UpdatesConfig::from_env(),
 
             // Build info
             commit_hash: env::var("COMMIT_HASH").unwrap_or_else(|_| "unknown".to_string()),
@@ -0,0 +1,18 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct OutboundConfig {
+    /// Air-gapped mode: never reach out to the internet. Chart sync, update
+    /// checks and WAN probes are skipped; charts come from imported bundles.
+    pub offline: bool,
+}
+
+impl OutboundConfig {
+    pub fn from_env() -> Self {
+        Self {
+            offline: env::var("KUBARR_OFFLINE")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(false),
+        }
+    }
+}
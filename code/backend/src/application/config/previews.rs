@@ -0,0 +1,30 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct PreviewsConfig {
+    /// Path or name of the ffmpeg binary that renders thumbnails
+    pub ffmpeg_binary: String,
+    /// Path or name of the ffprobe binary used to pick a video frame
+    pub ffprobe_binary: String,
+    /// Directory generated thumbnails are cached in
+    pub cache_dir: String,
+    /// Seconds a single ffmpeg or ffprobe run may take
+    pub timeout: u64,
+}
+
+impl PreviewsConfig {
+    pub fn from_env() -> Self {
+        Self {
+            ffmpeg_binary: env::var("KUBARR_FFMPEG_BINARY")
+                .unwrap_or_else(|_| "ffmpeg".to_string()),
+            ffprobe_binary: env::var("KUBARR_FFPROBE_BINARY")
+                .unwrap_or_else(|_| "ffprobe".to_string()),
+            cache_dir: env::var("KUBARR_PREVIEW_CACHE_DIR")
+                .unwrap_or_else(|_| "/tmp/kubarr-previews".to_string()),
+            timeout: env::var("KUBARR_PREVIEW_TIMEOUT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct SecurityConfig {
+    /// File failed sign-ins are appended to for Fail2ban or CrowdSec
+    pub auth_failure_log: Option<String>,
+    /// Bouncer key for the CrowdSec Local API
+    pub crowdsec_lapi_key: Option<String>,
+    /// Key stored credentials are encrypted with, base64 or hex
+    pub encryption_key: Option<String>,
+    /// Kubernetes secret holding the key, as `[namespace/]name`
+    pub encryption_key_secret: Option<String>,
+}
+
+impl SecurityConfig {
+    pub fn from_env() -> Self {
+        let non_empty = |key: &str| env::var(key).ok().filter(|v| !v.is_empty());
+        Self {
+            auth_failure_log: non_empty("KUBARR_AUTH_FAILURE_LOG"),
+            crowdsec_lapi_key: non_empty("KUBARR_CROWDSEC_LAPI_KEY"),
+            encryption_key: non_empty("KUBARR_ENCRYPTION_KEY"),
+            encryption_key_secret: non_empty("KUBARR_ENCRYPTION_KEY_SECRET"),
+        }
+    }
+}
@@ -1,9 +1,108 @@
 use std::env;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Directory of bundled assets served under `/auth/assets`
+    pub static_dir: PathBuf,
+    /// Origins browsers may call the API from; empty allows any
+    pub cors_allowed_origins: Vec<String>,
+    /// URL users reach Kubarr at, without a trailing slash; absolute links
+    /// are built from it unless the `external_url` setting overrides it
+    pub external_url: String,
+    /// Largest request body in bytes for most routes; `None` disables the limit
+    pub max_body_size: Option<usize>,
+    /// Largest request body for sign-in and setup routes
+    pub max_auth_body_size: Option<usize>,
+    /// Largest request body for WebDAV uploads and proxied apps
+    pub max_upload_size: Option<usize>,
+    /// Response encodings offered to clients, from `KUBARR_COMPRESSION`
+    pub compression: Vec<String>,
+    /// Smallest response body in bytes that is compressed
+    pub compression_min_size: u16,
+    /// PEM certificate chain served on the API port, with `tls_key_file`
+    pub tls_cert_file: Option<PathBuf>,
+    /// PEM private key of `tls_cert_file`
+    pub tls_key_file: Option<PathBuf>,
+    /// Domains to get a Let's Encrypt certificate for; empty disables ACME
+    pub acme_domains: Vec<String>,
+    /// Contact address given to Let's Encrypt
+    pub acme_email: Option<String>,
+    /// Where the ACME account and certificates are kept between restarts
+    pub acme_cache_dir: PathBuf,
+    /// Use Let's Encrypt's staging environment, for testing
+    pub acme_staging: bool,
+    /// Plain HTTP port redirecting to HTTPS when TLS is on
+    pub http_redirect_port: Option<u16>,
+}
+
+/// How the API port terminates TLS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Plain HTTP, e.g. behind an ingress
+    Off,
+    /// Certificate and key read from files
+    Files { cert: PathBuf, key: PathBuf },
+    /// Certificate from Let's Encrypt through the TLS-ALPN-01 challenge
+    Acme,
+}
+
+/// External URL used when neither it nor the issuer URL is configured
+pub const DEFAULT_EXTERNAL_URL: &str = "http://kubarr:8000";
+
+/// `KUBARR_EXTERNAL_URL`, falling back to `KUBARR_OAUTH2_ISSUER_URL`, which
+/// links were built from before the external URL could be set
+pub fn external_url_from(get: impl Fn(&str) -> Option<String>) -> Option<String> {
+    get("KUBARR_EXTERNAL_URL")
+        .or_else(|| get("KUBARR_OAUTH2_ISSUER_URL"))
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Defaults of `KUBARR_MAX_BODY_SIZE`, `KUBARR_MAX_AUTH_BODY_SIZE` and
+/// `KUBARR_MAX_UPLOAD_SIZE`
+pub const DEFAULT_MAX_BODY_SIZE: &str = "2m";
+pub const DEFAULT_MAX_AUTH_BODY_SIZE: &str = "64k";
+pub const DEFAULT_MAX_UPLOAD_SIZE: &str = "100g";
+
+/// Response encodings `KUBARR_COMPRESSION` accepts
+pub const COMPRESSION_ALGORITHMS: &[&str] = &["br", "gzip"];
+
+/// Default of `KUBARR_COMPRESSION_MIN_SIZE`
+pub const DEFAULT_COMPRESSION_MIN_SIZE: u16 = 1024;
+
+/// Encodings listed in `KUBARR_COMPRESSION`, lowercased; `none` or an
+/// empty value turns compression off
+pub fn parse_compression(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty() && a != "none")
+        .collect()
+}
+
+/// Parse a size such as `512`, `64k`, `2m` or `100g` (binary units, as in
+/// nginx's `client_max_body_size`); `0` means no limit
+pub fn parse_size(value: &str) -> Option<Option<usize>> {
+    let value = value.trim().to_lowercase();
+    let (number, multiplier) = match value.chars().last()? {
+        'k' => (&value[..value.len() - 1], 1usize << 10),
+        'm' => (&value[..value.len() - 1], 1 << 20),
+        'g' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value.as_str(), 1),
+    };
+    let bytes = number.parse::<usize>().ok()?.checked_mul(multiplier)?;
+    Some((bytes > 0).then_some(bytes))
+}
+
+/// Size limit from `key`, falling back to `default` when unset or malformed
+fn size_from_env(key: &str, default: &str) -> Option<usize> {
+    env::var(key)
+        .ok()
+        .and_then(|v| parse_size(&v))
+        .unwrap_or_else(|| parse_size(default).flatten())
 }
 
 impl ServerConfig {
@@ -14,6 +113,94 @@ impl ServerConfig {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(8000),
+            static_dir: PathBuf::from(
+                env::var("KUBARR_STATIC_DIR").unwrap_or_else(|_| "/app/static".to_string()),
+            ),
+            cors_allowed_origins: env::var("KUBARR_CORS_ALLOWED_ORIGINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty())
+                .collect(),
+            external_url: external_url_from(|key| env::var(key).ok())
+                .unwrap_or_else(|| DEFAULT_EXTERNAL_URL.to_string()),
+            max_body_size: size_from_env("KUBARR_MAX_BODY_SIZE", DEFAULT_MAX_BODY_SIZE),
+            max_auth_body_size: size_from_env(
+                "KUBARR_MAX_AUTH_BODY_SIZE",
+                DEFAULT_MAX_AUTH_BODY_SIZE,
+            ),
+            max_upload_size: size_from_env("KUBARR_MAX_UPLOAD_SIZE", DEFAULT_MAX_UPLOAD_SIZE),
+            compression: parse_compression(
+                &env::var("KUBARR_COMPRESSION").unwrap_or_else(|_| "br,gzip".to_string()),
+            ),
+            compression_min_size: env::var("KUBARR_COMPRESSION_MIN_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_COMPRESSION_MIN_SIZE),
+            tls_cert_file: path_from_env("KUBARR_TLS_CERT_FILE"),
+            tls_key_file: path_from_env("KUBARR_TLS_KEY_FILE"),
+            acme_domains: parse_domains(&env::var("KUBARR_ACME_DOMAINS").unwrap_or_default()),
+            acme_email: env::var("KUBARR_ACME_EMAIL")
+                .ok()
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty()),
+            acme_cache_dir: PathBuf::from(
+                env::var("KUBARR_ACME_CACHE_DIR")
+                    .unwrap_or_else(|_| "/var/lib/kubarr/acme".to_string()),
+            ),
+            acme_staging: env::var("KUBARR_ACME_STAGING")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(false),
+            http_redirect_port: env::var("KUBARR_HTTP_REDIRECT_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .filter(|p| *p > 0),
+        }
+    }
+
+    /// How TLS is terminated; files win over ACME, which validation rejects
+    pub fn tls_mode(&self) -> TlsMode {
+        match (&self.tls_cert_file, &self.tls_key_file) {
+            (Some(cert), Some(key)) => TlsMode::Files {
+                cert: cert.clone(),
+                key: key.clone(),
+            },
+            _ if !self.acme_domains.is_empty() => TlsMode::Acme,
+            _ => TlsMode::Off,
         }
     }
 }
+
+/// Non-empty path from `key`
+fn path_from_env(key: &str) -> Option<PathBuf> {
+    env::var(key)
+        .ok()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Domains listed in `KUBARR_ACME_DOMAINS`, lowercased
+pub fn parse_domains(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|d| d.trim().trim_end_matches('.').to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+/// A DNS name Let's Encrypt can issue for: letters, digits and hyphens in
+/// at least two labels, with an optional leading `*.` wildcard
+pub fn is_valid_domain(domain: &str) -> bool {
+    let name = domain.strip_prefix("*.").unwrap_or(domain);
+    let labels: Vec<&str> = name.split('.').collect();
+    name.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
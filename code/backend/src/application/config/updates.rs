@@ -0,0 +1,33 @@
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct UpdatesConfig {
+    /// GitHub repository whose releases are checked for new versions
+    pub repo: String,
+    /// Chart Kubarr's own release is upgraded from
+    pub chart: String,
+    /// Name of Kubarr's own Helm release
+    pub release_name: String,
+    /// Namespace of Kubarr's own Helm release
+    pub namespace: String,
+    /// Seconds between update checks
+    pub check_interval: u64,
+}
+
+impl UpdatesConfig {
+    pub fn from_env() -> Self {
+        Self {
+            repo: env::var("KUBARR_UPDATE_REPO")
+                .unwrap_or_else(|_| "bmartensNL/Kubarr".to_string()),
+            chart: env::var("KUBARR_UPDATE_CHART")
+                .unwrap_or_else(|_| "oci://ghcr.io/bmartensnl/kubarr/charts/kubarr".to_string()),
+            release_name: env::var("KUBARR_RELEASE_NAME").unwrap_or_else(|_| "kubarr".to_string()),
+            namespace: env::var("KUBARR_RELEASE_NAMESPACE")
+                .unwrap_or_else(|_| "kubarr".to_string()),
+            check_interval: env::var("KUBARR_UPDATE_CHECK_INTERVAL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6 * 3600),
+        }
+    }
+}
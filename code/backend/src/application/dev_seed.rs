@@ -0,0 +1,465 @@
+//! Development data seeding
+//!
+//! Backs the `kubarr seed-dev-data` subcommand, which fills a database with
+//! representative users, audit history and notifications so the frontend can
+//! be worked on without a cluster. The same building blocks are used by the
+//! integration test fixtures.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde_json::json;
+
+use crate::config::validation::PRODUCTION_CHANNELS;
+use crate::config::CONFIG;
+use crate::endpoints::notifications::{default_event_severity, get_all_event_types};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{self, AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::{notification_event, role, user, user_notification, user_role};
+use crate::services::notification::NotificationService;
+use crate::services::security::hash_password;
+
+/// Password shared by every seeded user
+pub const DEV_PASSWORD: &str = "kubarr-dev";
+
+/// Seeded users as (username, role, approved)
+const DEV_USERS: &[(&str, &str, bool)] = &[
+    ("dev-admin", "admin", true),
+    ("alice", "viewer", true),
+    ("bob", "downloader", true),
+    ("carol", "viewer", false),
+];
+
+/// Apps referenced by the seeded audit history
+const DEV_APPS: &[&str] = &["sonarr", "radarr", "jellyfin", "qbittorrent"];
+
+/// Days of audit history to generate
+const HISTORY_DAYS: i64 = 14;
+
+/// What a seeding run created
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub audit_logs: usize,
+    pub notifications: usize,
+}
+
+/// Command line options of `seed-dev-data`
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SeedArgs {
+    database_url: Option<String>,
+    /// Seed even though `CHANNEL` is a production channel
+    force: bool,
+}
+
+/// Entry point for `kubarr seed-dev-data [--database-url <url>] [--force]`
+pub async fn run(args: &[String]) -> anyhow::Result<()> {
+    let args = parse_args(args)?;
+    check_channel(&CONFIG.channel, args.force)?;
+
+    let db = match args.database_url {
+        Some(url) => crate::db::connect_with_url(&url).await?,
+        None => crate::db::connect().await?,
+    };
+
+    match seed_dev_data(&db).await? {
+        Some(summary) => println!(
+            "Seeded {} users, {} audit log entries and {} notifications (password: {})",
+            summary.users, summary.audit_logs, summary.notifications, DEV_PASSWORD
+        ),
+        None => println!("Development data already present, nothing to do"),
+    }
+
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> anyhow::Result<SeedArgs> {
+    let mut iter = args.iter();
+    let mut parsed = SeedArgs::default();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--database-url" => match iter.next() {
+                Some(value) => parsed.database_url = Some(value.clone()),
+                None => anyhow::bail!("--database-url requires a value"),
+            },
+            "--force" => parsed.force = true,
+            other => anyhow::bail!("Unknown argument for seed-dev-data: {}", other),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Refuse to create the well-known dev accounts on a production channel
+/// unless `--force` was given
+fn check_channel(channel: &str, force: bool) -> anyhow::Result<()> {
+    if PRODUCTION_CHANNELS.contains(&channel) && !force {
+        anyhow::bail!(
+            "Refusing to seed development data on the '{}' channel; pass --force to seed anyway",
+            channel
+        );
+    }
+    Ok(())
+}
+
+/// Fill the database with development data
+///
+/// Returns `None` without touching anything when the seeded admin already
+/// exists, so running the command twice does not duplicate history.
+pub async fn seed_dev_data(db: &DatabaseConnection) -> Result<Option<SeedSummary>> {
+    if User::find()
+        .filter(user::Column::Username.eq(DEV_USERS[0].0))
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    let mut users = Vec::with_capacity(DEV_USERS.len());
+    for (username, role_name, approved) in DEV_USERS {
+        users.push(create_user(db, username, DEV_PASSWORD, role_name, *approved).await?);
+    }
+
+    let audit_logs = seed_audit_history(db, &users, Utc::now()).await?;
+
+    let notification = NotificationService::new();
+    notification.set_db(db.clone()).await;
+    enable_notification_events(db).await?;
+    let notifications = seed_notifications(db, &notification, &users).await?;
+
+    Ok(Some(SeedSummary {
+        users: users.len(),
+        audit_logs,
+        notifications,
+    }))
+}
+
+/// Create an active user with the given role
+pub async fn create_user(
+    db: &DatabaseConnection,
+    username: &str,
+    password: &str,
+    role_name: &str,
+    approved: bool,
+) -> Result<user::Model> {
+    let role = Role::find()
+        .filter(role::Column::Name.eq(role_name))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::BadRequest(format!("Role '{}' does not exist", role_name)))?;
+
+    let now = Utc::now();
+    let user = user::ActiveModel {
+        username: Set(username.to_string()),
+        email: Set(format!("{}@kubarr.local", username)),
+        hashed_password: Set(hash_password(password)?),
+        is_active: Set(true),
+        is_approved: Set(approved),
+        totp_secret: Set(None),
+        totp_enabled: Set(false),
+        totp_verified_at: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    user_role::ActiveModel {
+        user_id: Set(user.id),
+        role_id: Set(role.id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(user)
+}
+
+/// Write a couple of weeks of audit history for the given users
+///
+/// Every approved user logs in daily; admins also install and restart apps
+/// and change settings, with the occasional failed login mixed in. Returns
+/// the number of entries written.
+pub async fn seed_audit_history(
+    db: &DatabaseConnection,
+    users: &[user::Model],
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let admins = admin_ids(db, users).await?;
+    let mut written = 0;
+
+    for day in (0..HISTORY_DAYS).rev() {
+        let day_start = now - Duration::days(day);
+
+        for (index, u) in users.iter().enumerate() {
+            if !u.is_approved {
+                continue;
+            }
+            let at = day_start - Duration::hours(8 - index as i64);
+
+            if (day + index as i64) % 5 == 0 {
+                insert_audit(
+                    db,
+                    at - Duration::minutes(2),
+                    u,
+                    AuditAction::LoginFailed,
+                    ResourceType::Session,
+                    None,
+                    None,
+                    Some("Invalid username or password"),
+                )
+                .await?;
+                written += 1;
+            }
+            insert_audit(
+                db,
+                at,
+                u,
+                AuditAction::Login,
+                ResourceType::Session,
+                None,
+                None,
+                None,
+            )
+            .await?;
+            written += 1;
+
+            if !admins.contains(&u.id) {
+                continue;
+            }
+            let app = DEV_APPS[(day as usize) % DEV_APPS.len()];
+            let (action, resource, resource_id, details) = match day % 3 {
+                0 => (
+                    AuditAction::AppInstalled,
+                    ResourceType::App,
+                    Some(app),
+                    json!({"app_name": app}),
+                ),
+                1 => (
+                    AuditAction::AppRestarted,
+                    ResourceType::App,
+                    Some(app),
+                    json!({"app_name": app}),
+                ),
+                _ => (
+                    AuditAction::SystemSettingChanged,
+                    ResourceType::System,
+                    None,
+                    json!({"key": "registration_enabled", "value": day % 2 == 0}),
+                ),
+            };
+            insert_audit(
+                db,
+                at + Duration::minutes(15),
+                u,
+                action,
+                resource,
+                resource_id,
+                Some(details),
+                None,
+            )
+            .await?;
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn insert_audit(
+    db: &DatabaseConnection,
+    timestamp: DateTime<Utc>,
+    user: &user::Model,
+    action: AuditAction,
+    resource_type: ResourceType,
+    resource_id: Option<&str>,
+    details: Option<serde_json::Value>,
+    error_message: Option<&str>,
+) -> Result<()> {
+    audit_log::ActiveModel {
+        timestamp: Set(timestamp),
+        user_id: Set(Some(user.id)),
+        username: Set(Some(user.username.clone())),
+        action: Set(action.to_string()),
+        resource_type: Set(resource_type.to_string()),
+        resource_id: Set(resource_id.map(String::from)),
+        details: Set(details.map(|d| d.to_string())),
+        ip_address: Set(Some("192.168.1.20".to_string())),
+        user_agent: Set(Some(
+            "Mozilla/5.0 (X11; Linux x86_64) Firefox/131.0".to_string(),
+        )),
+        success: Set(error_message.is_none()),
+        error_message: Set(error_message.map(String::from)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Enable notifications for every event type at its default severity
+///
+/// Returns the number of event types that were changed.
+pub async fn enable_notification_events(db: &DatabaseConnection) -> Result<usize> {
+    let mut changed = 0;
+    for event_type in get_all_event_types() {
+        let existing = NotificationEvent::find()
+            .filter(notification_event::Column::EventType.eq(&event_type))
+            .one(db)
+            .await?;
+
+        match existing {
+            Some(event) if event.enabled => continue,
+            Some(event) => {
+                let mut active: notification_event::ActiveModel = event.into();
+                active.enabled = Set(true);
+                active.update(db).await?;
+            }
+            None => {
+                notification_event::ActiveModel {
+                    severity: Set(default_event_severity(&event_type).to_string()),
+                    event_type: Set(event_type),
+                    enabled: Set(true),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?;
+            }
+        }
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Send a representative set of notifications to each approved user
+///
+/// Goes through `NotificationService::notify_event`, so event types must be
+/// enabled first. The older half of each user's inbox is marked read.
+/// Returns the number of inbox entries created.
+pub async fn seed_notifications(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    users: &[user::Model],
+) -> Result<usize> {
+    let admins = admin_ids(db, users).await?;
+    let before = UserNotification::find().count(db).await?;
+
+    for u in users.iter().filter(|u| u.is_approved) {
+        let mut events = vec![
+            (AuditAction::Login, None),
+            (AuditAction::PasswordChanged, None),
+            (AuditAction::MediaRequestApproved, Some("Dune: Part Two")),
+        ];
+        if admins.contains(&u.id) {
+            events.extend([
+                (AuditAction::AppInstalled, Some("sonarr")),
+                (AuditAction::UptimeMonitorDown, Some("Jellyfin: HTTP 502")),
+                (
+                    AuditAction::MetricAnomaly,
+                    Some("qbittorrent CPU 4.2x baseline"),
+                ),
+                (AuditAction::UserCreated, Some("carol")),
+            ]);
+        }
+
+        for (action, details) in &events {
+            notification
+                .notify_event(action, Some(u.id), Some(&u.username), *details)
+                .await?;
+        }
+
+        let inbox = UserNotification::find()
+            .filter(user_notification::Column::UserId.eq(u.id))
+            .order_by_asc(user_notification::Column::Id)
+            .all(db)
+            .await?;
+        let read_count = inbox.len() / 2;
+        for entry in inbox.into_iter().take(read_count) {
+            let mut active: user_notification::ActiveModel = entry.into();
+            active.read = Set(true);
+            active.update(db).await?;
+        }
+    }
+
+    let after = UserNotification::find().count(db).await?;
+    Ok((after - before) as usize)
+}
+
+async fn admin_ids(db: &DatabaseConnection, users: &[user::Model]) -> Result<Vec<i64>> {
+    let Some(admin_role) = Role::find()
+        .filter(role::Column::Name.eq("admin"))
+        .one(db)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(UserRole::find()
+        .filter(user_role::Column::RoleId.eq(admin_role.id))
+        .filter(user_role::Column::UserId.is_in(users.iter().map(|u| u.id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|ur| ur.user_id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args_default() {
+        assert_eq!(parse_args(&[]).unwrap(), SeedArgs::default());
+    }
+
+    #[test]
+    fn test_parse_args_flags() {
+        let parsed = parse_args(&args(&[
+            "--database-url",
+            "sqlite://dev.db?mode=rwc",
+            "--force",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed.database_url.as_deref(),
+            Some("sqlite://dev.db?mode=rwc")
+        );
+        assert!(parsed.force);
+    }
+
+    #[test]
+    fn test_parse_args_missing_value() {
+        assert!(parse_args(&args(&["--database-url"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_unknown_argument() {
+        assert!(parse_args(&args(&["--yes"])).is_err());
+    }
+
+    #[test]
+    fn test_check_channel_refuses_production_without_force() {
+        assert!(check_channel("dev", false).is_ok());
+        assert!(check_channel("stable", false).is_err());
+        assert!(check_channel("release", false).is_err());
+        assert!(check_channel("stable", true).is_ok());
+    }
+
+    #[test]
+    fn test_dev_users_include_each_default_role() {
+        for role in ["admin", "viewer", "downloader"] {
+            assert!(DEV_USERS.iter().any(|(_, r, _)| *r == role));
+        }
+        assert!(DEV_USERS.iter().any(|(_, _, approved)| !approved));
+    }
+}
@@ -6,6 +6,9 @@ use axum::{
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::middleware::current_request_id;
+use crate::services::telemetry::METRICS;
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Not found: {0}")]
@@ -27,6 +30,12 @@ pub enum AppError {
     #[error("Internal server error: {0}")]
     Internal(String),
 
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
@@ -37,7 +46,7 @@ pub enum AppError {
     Database(#[from] sea_orm::DbErr),
 
     #[error("Kubernetes error: {0}")]
-    Kubernetes(#[from] kube::Error),
+    Kubernetes(#[source] kube::Error),
 
     #[error("Kubernetes config error: {0}")]
     KubeConfig(#[from] kube::config::KubeconfigError),
@@ -67,6 +76,18 @@ pub enum AppError {
 #[derive(Serialize)]
 struct ErrorResponse {
     detail: String,
+    /// ID of the failed request, for correlating with logs and audit entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+/// Every failed Kubernetes call passes through here, so this is where
+/// they are counted
+impl From<kube::Error> for AppError {
+    fn from(error: kube::Error) -> Self {
+        METRICS.record_kubernetes_error(&error);
+        AppError::Kubernetes(error)
+    }
 }
 
 impl IntoResponse for AppError {
@@ -78,6 +99,8 @@ impl IntoResponse for AppError {
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
             AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
             AppError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg.clone()),
             AppError::Database(e) => {
@@ -134,7 +157,14 @@ impl IntoResponse for AppError {
             }
         };
 
-        (status, Json(ErrorResponse { detail: message })).into_response()
+        (
+            status,
+            Json(ErrorResponse {
+                detail: message,
+                request_id: current_request_id(),
+            }),
+        )
+            .into_response()
     }
 }
 
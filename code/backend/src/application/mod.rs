@@ -1,5 +1,6 @@
 pub mod bootstrapper;
 pub mod config;
 pub mod database;
+pub mod dev_seed;
 pub mod error;
 pub mod state;
@@ -9,7 +9,8 @@ use crate::services::audit::AuditService;
 use crate::services::cadvisor::NamespaceNetworkMetrics;
 use crate::services::catalog::AppCatalog;
 use crate::services::chart_sync::ChartSyncService;
-use crate::services::k8s::K8sClient;
+use crate::services::helm::{self, HelmEngine};
+use crate::services::k8s::{K8sApi, K8sClient};
 use crate::services::notification::NotificationService;
 use crate::services::proxy::ProxyService;
 
@@ -248,6 +249,61 @@ impl EndpointCache {
     }
 }
 
+/// Cached JSON response from an app's API with expiration
+#[derive(Clone)]
+struct CachedResponse {
+    value: serde_json::Value,
+    expires_at: Instant,
+}
+
+/// Cache for app API responses fetched by integration adapters
+/// (avoids hitting Sonarr/Radarr/etc. on every dashboard refresh)
+#[derive(Clone, Default)]
+pub struct IntegrationCache {
+    cache: Arc<RwLock<HashMap<String, CachedResponse>>>,
+    ttl: Duration,
+}
+
+impl IntegrationCache {
+    pub fn new(ttl_seconds: u64) -> Self {
+        Self {
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            ttl: Duration::from_secs(ttl_seconds),
+        }
+    }
+
+    /// Get a cached response by key
+    pub async fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let cache = self.cache.read().await;
+        cache
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Cache a response under a key
+    pub async fn set(&self, key: &str, value: serde_json::Value) {
+        let mut cache = self.cache.write().await;
+        // Drop expired entries so the cache doesn't grow with old date ranges
+        let now = Instant::now();
+        cache.retain(|_, entry| entry.expires_at > now);
+        cache.insert(
+            key.to_string(),
+            CachedResponse {
+                value,
+                expires_at: now + self.ttl,
+            },
+        );
+    }
+
+    /// Invalidate all cached responses for an app (keys are prefixed "{app}:")
+    pub async fn invalidate_app(&self, app_name: &str) {
+        let prefix = format!("{}:", app_name);
+        let mut cache = self.cache.write().await;
+        cache.retain(|key, _| !key.starts_with(&prefix));
+    }
+}
+
 /// Shared K8s client state
 pub type SharedK8sClient = Arc<RwLock<Option<K8sClient>>>;
 
@@ -274,9 +330,14 @@ pub struct AppState {
     pub notification: NotificationService,
     pub proxy: ProxyService,
     pub endpoint_cache: EndpointCache,
+    pub integration_cache: IntegrationCache,
     pub network_metrics_cache: NetworkMetricsCache,
     pub network_metrics_tx: NetworkMetricsBroadcast,
     pub bootstrap_tx: BootstrapBroadcast,
+    /// Cluster API used instead of `k8s_client` when set (see `with_k8s_api`)
+    k8s_api: Option<Arc<dyn K8sApi>>,
+    /// Helm engine used for app deployments (see `with_helm_engine`)
+    helm: Arc<dyn HelmEngine>,
 }
 
 impl AppState {
@@ -302,10 +363,42 @@ impl AppState {
             notification,
             proxy: ProxyService::new(),
             endpoint_cache: EndpointCache::new(60), // Cache endpoints for 60 seconds
+            integration_cache: IntegrationCache::new(300), // Cache app API responses for 5 minutes
             network_metrics_cache: NetworkMetricsCache::new(),
             network_metrics_tx,
             bootstrap_tx,
+            k8s_api: None,
+            helm: helm::from_config(),
+        }
+    }
+
+    /// Use the given Helm engine for app deployments instead of the helm
+    /// binary, e.g. a `MockHelmEngine` in tests
+    pub fn with_helm_engine(mut self, engine: Arc<dyn HelmEngine>) -> Self {
+        self.helm = engine;
+        self
+    }
+
+    /// Get the Helm engine used for app deployments
+    pub fn helm(&self) -> Arc<dyn HelmEngine> {
+        self.helm.clone()
+    }
+
+    /// Use the given cluster API for app lifecycle operations instead of
+    /// the live Kubernetes client, e.g. a `FakeK8s` in tests
+    pub fn with_k8s_api(mut self, api: Arc<dyn K8sApi>) -> Self {
+        self.k8s_api = Some(api);
+        self
+    }
+
+    /// Get the cluster API, or `None` if no Kubernetes client is available
+    pub async fn k8s_api(&self) -> Option<Arc<dyn K8sApi>> {
+        if let Some(api) = &self.k8s_api {
+            return Some(api.clone());
         }
+        let k8s = self.k8s_client.read().await;
+        k8s.clone()
+            .map(|client| Arc::new(client) as Arc<dyn K8sApi>)
     }
 
     /// Set the database connection after PostgreSQL is installed
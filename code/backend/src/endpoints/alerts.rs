@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post, put},
+    Json, Router,
+};
+
+use crate::error::Result;
+use crate::middleware::permissions::{Authorized, MonitoringManage, MonitoringView};
+use crate::services::alerts::{
+    self, AcknowledgeAlertRequest, AlertDetail, AlertInfo, AlertListQuery, AssignAlertRequest,
+    SilenceAlertRequest,
+};
+use crate::state::AppState;
+
+/// Create alert routes
+pub fn alerts_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_alerts))
+        .route("/{id}", get(get_alert))
+        .route("/{id}/ack", post(acknowledge_alert))
+        .route("/{id}/silence", post(silence_alert).delete(unsilence_alert))
+        .route("/{id}/assign", put(assign_alert))
+        .with_state(state)
+}
+
+/// List alerts from all monitoring sources, newest first
+#[utoipa::path(
+    get,
+    path = "/api/alerts",
+    tag = "Alerts",
+    params(AlertListQuery),
+    responses(
+        (status = 200, body = Vec<AlertInfo>),
+        (status = 400, description = "Invalid state or source filter")
+    )
+)]
+async fn list_alerts(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Query(query): Query<AlertListQuery>,
+) -> Result<Json<Vec<AlertInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(alerts::list_alerts(&db, query).await?))
+}
+
+/// Get an alert with its history
+#[utoipa::path(
+    get,
+    path = "/api/alerts/{id}",
+    tag = "Alerts",
+    params(("id" = i64, Path, description = "Alert ID")),
+    responses(
+        (status = 200, body = AlertDetail),
+        (status = 404, description = "Alert not found")
+    )
+)]
+async fn get_alert(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Path(id): Path<i64>,
+) -> Result<Json<AlertDetail>> {
+    let db = state.get_db().await?;
+    Ok(Json(alerts::get_alert(&db, id).await?))
+}
+
+/// Acknowledge an alert
+#[utoipa::path(
+    post,
+    path = "/api/alerts/{id}/ack",
+    tag = "Alerts",
+    params(("id" = i64, Path, description = "Alert ID")),
+    request_body = AcknowledgeAlertRequest,
+    responses(
+        (status = 200, body = AlertDetail),
+        (status = 400, description = "Alert is resolved"),
+        (status = 404, description = "Alert not found")
+    )
+)]
+async fn acknowledge_alert(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+    request: Option<Json<AcknowledgeAlertRequest>>,
+) -> Result<Json<AlertDetail>> {
+    let db = state.get_db().await?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    Ok(Json(
+        alerts::acknowledge(&db, id, auth.user_id(), request).await?,
+    ))
+}
+
+/// Silence an alert for a while
+///
+/// Notifications for the alert's condition are suppressed until the silence
+/// expires, including when it resolves and fires again in the meantime.
+#[utoipa::path(
+    post,
+    path = "/api/alerts/{id}/silence",
+    tag = "Alerts",
+    params(("id" = i64, Path, description = "Alert ID")),
+    request_body = SilenceAlertRequest,
+    responses(
+        (status = 200, body = AlertDetail),
+        (status = 400, description = "Invalid duration or alert is resolved"),
+        (status = 404, description = "Alert not found")
+    )
+)]
+async fn silence_alert(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+    Json(request): Json<SilenceAlertRequest>,
+) -> Result<Json<AlertDetail>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        alerts::silence(&db, id, auth.user_id(), request).await?,
+    ))
+}
+
+/// End an alert's silence early
+#[utoipa::path(
+    delete,
+    path = "/api/alerts/{id}/silence",
+    tag = "Alerts",
+    params(("id" = i64, Path, description = "Alert ID")),
+    responses(
+        (status = 200, body = AlertDetail),
+        (status = 400, description = "Alert is not silenced"),
+        (status = 404, description = "Alert not found")
+    )
+)]
+async fn unsilence_alert(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<AlertDetail>> {
+    let db = state.get_db().await?;
+    Ok(Json(alerts::unsilence(&db, id, auth.user_id()).await?))
+}
+
+/// Assign an alert to a user, or unassign it
+///
+/// The assignee is notified unless they assigned the alert to themselves.
+#[utoipa::path(
+    put,
+    path = "/api/alerts/{id}/assign",
+    tag = "Alerts",
+    params(("id" = i64, Path, description = "Alert ID")),
+    request_body = AssignAlertRequest,
+    responses(
+        (status = 200, body = AlertDetail),
+        (status = 400, description = "User does not exist"),
+        (status = 404, description = "Alert not found")
+    )
+)]
+async fn assign_alert(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+    Json(request): Json<AssignAlertRequest>,
+) -> Result<Json<AlertDetail>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        alerts::assign(&db, &state.notification, id, auth.user_id(), request).await?,
+    ))
+}
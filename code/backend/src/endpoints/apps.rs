@@ -1,20 +1,44 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::header,
+    body::Bytes,
+    extract::{
+        ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade},
+        DefaultBodyLimit, Extension, Path, Query, State,
+    },
+    http::{header, HeaderMap},
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use sea_orm::EntityTrait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::config::CONFIG;
+use crate::endpoints::extractors::user_has_app_access;
 use crate::error::{AppError, Result};
 use crate::middleware::permissions::{
-    AppsDelete, AppsInstall, AppsRestart, AppsView, Authenticated, Authorized,
+    AppsDelete, AppsExec, AppsInstall, AppsRequest, AppsRestart, AppsStop, AppsView, Authenticated,
+    Authorized, Permission, SystemManage, TenantsManage,
 };
+use crate::middleware::AuthenticatedUser;
 use crate::models::audit_log::AuditAction;
 use crate::models::prelude::*;
+use crate::services::app_requests::{
+    self, AppInstallRequestInfo, CreateAppInstallRequest, ReviewAppInstallRequest,
+};
+use crate::services::app_routing::{self, AppRouting, UpdateAppRouting};
+use crate::services::app_values::{self, AppValues, UpdateAppValues};
+use crate::services::boot_order::{self, BootStatus, UpdateBootOrder};
+use crate::services::catalog::ArchCompatibility;
+use crate::services::catalog_bundle::{self, BundleImport, MAX_BUNDLE_BYTES};
+use crate::services::catalog_validation::{self, CatalogEntryRequest, CatalogValidation};
+use crate::services::installed_apps::{
+    self, InstalledAppInfo, UpdateIdleSuspend, UpdateInstalledAppMetadata,
+};
+use crate::services::integrations::{self, NativeStatus};
+use crate::services::maintenance::{self, AppRunState};
+use crate::services::tenants::{self, AssignAppTenant};
+use crate::services::{ip_bans, pod_exec};
 use crate::services::{AppConfig, DeploymentManager, DeploymentRequest, DeploymentStatus};
 use crate::state::AppState;
 
@@ -22,19 +46,54 @@ use crate::state::AppState;
 pub fn apps_routes(state: AppState) -> Router {
     Router::new()
         .route("/catalog", get(list_catalog))
+        .route("/catalog/validate", post(validate_catalog_entry))
+        .route(
+            "/catalog/import-bundle",
+            post(import_catalog_bundle).layer(DefaultBodyLimit::max(MAX_BUNDLE_BYTES)),
+        )
         .route("/catalog/{app_name}", get(get_app_from_catalog))
         .route("/catalog/{app_name}/icon", get(get_app_icon))
         .route("/installed", get(list_installed_apps))
         .route("/install", post(install_app))
+        .route(
+            "/requests",
+            get(list_install_requests).post(create_install_request),
+        )
+        .route(
+            "/requests/{request_id}/approve",
+            post(approve_install_request),
+        )
+        .route("/requests/{request_id}/deny", post(deny_install_request))
         .route("/sync", post(sync_charts))
         .route("/categories", get(list_categories))
         .route("/category/{category}", get(get_apps_by_category))
         .route("/{app_name}", delete(delete_app))
+        .route("/{app_name}/clone", post(clone_app))
         .route("/{app_name}/restart", post(restart_app))
+        .route("/{app_name}/upgrade", post(upgrade_app))
+        .route("/{app_name}/stop", post(stop_app))
+        .route("/{app_name}/start", post(start_app))
+        .route("/{app_name}/exec", get(exec_in_app))
         .route("/{app_name}/health", get(check_app_health))
         .route("/{app_name}/exists", get(check_app_exists))
         .route("/{app_name}/status", get(get_app_status))
         .route("/{app_name}/access", post(log_app_access))
+        .route("/{app_name}/metadata", put(update_app_metadata))
+        .route("/{app_name}/idle-suspend", put(update_idle_suspend))
+        .route("/{app_name}/tenant", put(update_app_tenant))
+        .route(
+            "/{app_name}/routing",
+            get(get_app_routing).put(update_app_routing),
+        )
+        .route(
+            "/{app_name}/boot-order",
+            get(get_boot_order).put(update_boot_order),
+        )
+        .route(
+            "/{app_name}/values",
+            get(get_app_values).put(update_app_values),
+        )
+        .route("/{app_name}/native-status", get(get_native_status))
         .with_state(state)
 }
 
@@ -47,27 +106,97 @@ pub struct NamespaceQuery {
     pub namespace: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct CatalogQuery {
+    /// Leave out apps that no node of the cluster can run
+    #[serde(default)]
+    pub compatible_only: bool,
+}
+
+/// A catalog app with its fit for the cluster's node architectures
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CatalogApp {
+    #[serde(flatten)]
+    pub app: AppConfig,
+    #[serde(flatten)]
+    pub compatibility: ArchCompatibility,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct InstalledAppsQuery {
+    /// Return notes, tags, install time and chart versions instead of plain
+    /// app names
+    #[serde(default)]
+    pub details: bool,
+    /// Only return apps carrying this tag (case-insensitive)
+    pub tag: Option<String>,
+}
+
+/// Request to install another instance of a catalog app
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CloneAppRequest {
+    /// Appended to the app name, e.g. `4k` installs `sonarr-4k`
+    pub suffix: String,
+    #[serde(default)]
+    pub custom_config: HashMap<String, String>,
+    /// Install even though no node matches the app's architectures
+    #[serde(default)]
+    pub allow_incompatible_arch: bool,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct InstallRequestsQuery {
+    /// Only return requests with this status (`pending`, `approved`, `denied`)
+    pub status: Option<String>,
+}
+
+/// Installed apps, as names or with their metadata when `details=true`
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(untagged)]
+pub enum InstalledAppsResponse {
+    Names(Vec<String>),
+    Details(Vec<InstalledAppInfo>),
+}
+
 // ============================================================================
 // Endpoint Handlers
 // ============================================================================
 
 /// List all apps in the catalog (excludes hidden apps)
+///
+/// Each app is flagged with whether the cluster's nodes can run it. Apps
+/// no node can run carry a warning and are refused at install time unless
+/// the install request sets `allow_incompatible_arch`.
 #[utoipa::path(
     get,
     path = "/api/apps/catalog",
     tag = "Apps",
-    responses((status = 200, body = serde_json::Value))
+    params(CatalogQuery),
+    responses((status = 200, body = Vec<CatalogApp>))
 )]
 async fn list_catalog(
     State(state): State<AppState>,
+    Query(query): Query<CatalogQuery>,
     _auth: Authorized<AppsView>,
-) -> Result<Json<Vec<AppConfig>>> {
+) -> Result<Json<Vec<CatalogApp>>> {
+    // Unknown architectures make every app compatible
+    let node_architectures = match state.k8s_api().await {
+        Some(k8s) => k8s.node_architectures().await.unwrap_or_else(|e| {
+            tracing::debug!("Failed to read node architectures: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
     let catalog = state.catalog.read().await;
-    let apps: Vec<AppConfig> = catalog
+    let apps: Vec<CatalogApp> = catalog
         .get_all_apps()
         .into_iter()
         .filter(|app| !app.is_hidden)
-        .cloned()
+        .map(|app| CatalogApp {
+            app: app.clone(),
+            compatibility: app.arch_compatibility(&node_architectures),
+        })
+        .filter(|entry| !query.compatible_only || entry.compatibility.compatible)
         .collect();
     Ok(Json(apps))
 }
@@ -93,6 +222,70 @@ async fn get_app_from_catalog(
     Ok(Json(app))
 }
 
+/// Validate a catalog entry before it is published
+#[utoipa::path(
+    post,
+    path = "/api/apps/catalog/validate",
+    tag = "Apps",
+    request_body = CatalogEntryRequest,
+    responses((status = 200, description = "Validation issues and the parsed app", body = CatalogValidation))
+)]
+async fn validate_catalog_entry(
+    _auth: Authorized<AppsInstall>,
+    Json(request): Json<CatalogEntryRequest>,
+) -> Result<Json<CatalogValidation>> {
+    Ok(Json(catalog_validation::validate_catalog_entry(&request)))
+}
+
+/// Import an offline catalog bundle
+///
+/// The body is a `.tar.gz` of chart directories. Every chart is validated
+/// first; one invalid chart rejects the whole bundle. Imported charts replace
+/// the ones of the same name and the catalog is reloaded.
+#[utoipa::path(
+    post,
+    path = "/api/apps/catalog/import-bundle",
+    tag = "Apps",
+    request_body(content = Vec<u8>, content_type = "application/gzip"),
+    responses(
+        (status = 200, description = "Imported charts and their warnings", body = BundleImport),
+        (status = 400, description = "The bundle is malformed or contains invalid charts")
+    )
+)]
+async fn import_catalog_bundle(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    body: Bytes,
+) -> Result<Json<BundleImport>> {
+    use crate::models::audit_log::ResourceType;
+
+    let charts_dir = CONFIG.charts.dir.clone();
+    let result = tokio::task::spawn_blocking(move || catalog_bundle::import(&body, &charts_dir))
+        .await
+        .map_err(|e| AppError::Internal(format!("Bundle import failed: {}", e)))??;
+    state.catalog.write().await.reload();
+
+    let charts: Vec<serde_json::Value> = result
+        .charts
+        .iter()
+        .map(|c| serde_json::json!({"name": c.name, "version": c.version}))
+        .collect();
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::SystemSettingChanged,
+            ResourceType::System,
+            Some("catalog".to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({"action": "bundle_imported", "charts": charts})),
+            None,
+            None,
+        )
+        .await;
+    Ok(Json(result))
+}
+
 /// Get the icon for an app (SVG)
 #[utoipa::path(
     get,
@@ -129,28 +322,58 @@ async fn get_app_icon(Path(app_name): Path<String>) -> Result<Response> {
         .into_response())
 }
 
-/// List installed apps
+/// List installed apps, optionally with their notes and tags
 #[utoipa::path(
     get,
     path = "/api/apps/installed",
     tag = "Apps",
-    responses((status = 200, body = Vec<String>))
+    params(InstalledAppsQuery),
+    responses((status = 200, body = InstalledAppsResponse))
 )]
 async fn list_installed_apps(
     State(state): State<AppState>,
-    _auth: Authorized<AppsView>,
-) -> Result<Json<Vec<String>>> {
-    let k8s = state.k8s_client.read().await;
+    auth: Authorized<AppsView>,
+    Query(query): Query<InstalledAppsQuery>,
+) -> Result<Json<InstalledAppsResponse>> {
+    let db = state.get_db().await?;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
-    let apps = if let Some(ref client) = *k8s {
-        let manager = DeploymentManager::new(client, &catalog);
+    let apps = if let Some(client) = k8s.as_deref() {
+        let helm = state.helm();
+        let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
         manager.get_deployed_apps().await
     } else {
         Vec::new()
     };
+    let hidden = tenants::hidden_apps(&db, auth.user_id()).await?;
+    let apps: Vec<String> = apps.into_iter().filter(|a| !hidden.contains(a)).collect();
 
-    Ok(Json(apps))
+    if !query.details && query.tag.is_none() {
+        return Ok(Json(InstalledAppsResponse::Names(apps)));
+    }
+
+    let mut apps = installed_apps::with_metadata(&db, apps).await?;
+    if let Some(tag) = query.tag.as_deref() {
+        apps.retain(|app| app.has_tag(tag));
+    }
+    if query.details {
+        for app in apps.iter_mut() {
+            if app.chart_version.is_none() {
+                app.chart_version = installed_chart_version(&state, &db, &app.name).await;
+            }
+        }
+    }
+    let apps: Vec<InstalledAppInfo> = apps
+        .into_iter()
+        .map(|app| app.with_catalog(&catalog))
+        .collect();
+
+    Ok(Json(if query.details {
+        InstalledAppsResponse::Details(apps)
+    } else {
+        InstalledAppsResponse::Names(apps.into_iter().map(|app| app.name).collect())
+    }))
 }
 
 /// Install an app
@@ -163,15 +386,25 @@ async fn list_installed_apps(
 )]
 async fn install_app(
     State(state): State<AppState>,
-    _auth: Authorized<AppsInstall>,
+    auth: Authorized<AppsInstall>,
     Json(request): Json<DeploymentRequest>,
 ) -> Result<Json<DeploymentStatus>> {
+    Ok(Json(deploy_app(&state, &request, auth.user_id()).await?))
+}
+
+/// Deploy a catalog app for a user, within their quota, and record it as
+/// installed
+async fn deploy_app(
+    state: &AppState,
+    request: &DeploymentRequest,
+    user_id: i64,
+) -> Result<DeploymentStatus> {
     let db = state.get_db().await?;
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
     let client = k8s
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
 
     // Get storage path from settings
@@ -179,17 +412,365 @@ async fn install_app(
     let storage_path = storage_setting.map(|s| s.value);
 
     // Use with_db to enable VPN support
-    let manager = DeploymentManager::with_db(client, &catalog, &db);
-    let status = manager
-        .deploy_app(&request, storage_path.as_deref())
-        .await?;
+    let helm = state.helm();
+    let manager =
+        DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db).for_user(user_id);
+    let status = manager.deploy_app(request, storage_path.as_deref()).await?;
 
     // Invalidate cache to ensure fresh lookup when app becomes ready
     state.endpoint_cache.invalidate(&request.app_name).await;
 
+    if let Err(e) = installed_apps::record_install(&db, &request.app_name, None).await {
+        tracing::warn!(
+            "Failed to record installed app '{}': {}",
+            request.app_name,
+            e
+        );
+    }
+
+    Ok(status)
+}
+
+/// Install another instance of a catalog app under a suffixed name
+///
+/// The clone gets its own release, namespace, ingress path and storage
+/// directory, and is addressed by its instance name (e.g. `sonarr-4k`) in
+/// permissions, the proxy and monitoring.
+#[utoipa::path(
+    post,
+    path = "/api/apps/{app_name}/clone",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "Catalog app to clone")),
+    request_body = CloneAppRequest,
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 400, description = "Invalid suffix"),
+        (status = 403, description = "System apps cannot be cloned"),
+        (status = 404, description = "App not found in catalog"),
+        (status = 409, description = "An app with the instance name already exists")
+    )
+)]
+async fn clone_app(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+    Json(request): Json<CloneAppRequest>,
+) -> Result<Json<DeploymentStatus>> {
+    use crate::models::audit_log::ResourceType;
+    use crate::services::catalog::{instance_name, is_valid_instance_suffix};
+
+    let db = state.get_db().await?;
+    let k8s = state.k8s_api().await;
+    let catalog = state.catalog.read().await;
+
+    let app = catalog
+        .get_app(&app_name)
+        .ok_or_else(|| AppError::NotFound(format!("App '{}' not found in catalog", app_name)))?;
+    if app.is_system {
+        return Err(AppError::Forbidden(format!(
+            "Cannot clone system app '{}'",
+            app.name
+        )));
+    }
+    if !is_valid_instance_suffix(&request.suffix) {
+        return Err(AppError::BadRequest(
+            "Suffix must be 1-20 lowercase letters, digits or inner hyphens".to_string(),
+        ));
+    }
+
+    let instance = instance_name(&app.name, &request.suffix);
+    // The name must not collide with another catalog app such as `sonarr-anime`
+    if catalog.get_app(&instance).is_some() {
+        return Err(AppError::Conflict(format!(
+            "'{}' is the name of another catalog app",
+            instance
+        )));
+    }
+
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    if client.namespace_exists(&instance).await.unwrap_or(false) {
+        return Err(AppError::Conflict(format!(
+            "App '{}' is already installed",
+            instance
+        )));
+    }
+
+    let storage_setting = SystemSetting::find_by_id("storage_path").one(&db).await?;
+    let storage_path = storage_setting.map(|s| s.value);
+
+    // Only recorded clones count as installed apps, so record it up front
+    installed_apps::record_install(&db, &instance, Some(&app.name)).await?;
+
+    let helm = state.helm();
+    let manager =
+        DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db).for_user(auth.user_id());
+    let deploy_request = DeploymentRequest {
+        app_name: instance.clone(),
+        custom_config: request.custom_config,
+        allow_incompatible_arch: request.allow_incompatible_arch,
+    };
+    let status = match manager
+        .deploy_clone(&app.name, &deploy_request, storage_path.as_deref())
+        .await
+    {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = installed_apps::forget(&db, &instance).await;
+            return Err(e);
+        }
+    };
+
+    state.endpoint_cache.invalidate(&instance).await;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppInstalled,
+            ResourceType::App,
+            Some(instance.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "cloned_from": app.name })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
     Ok(Json(status))
 }
 
+/// List app install requests
+///
+/// Holders of `apps.install` see the whole queue; everyone else sees only
+/// their own requests.
+#[utoipa::path(
+    get,
+    path = "/api/apps/requests",
+    tag = "Apps",
+    params(InstallRequestsQuery),
+    responses(
+        (status = 200, body = Vec<AppInstallRequestInfo>),
+        (status = 403, description = "apps.request or apps.install required")
+    )
+)]
+async fn list_install_requests(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Query(query): Query<InstallRequestsQuery>,
+) -> Result<Json<Vec<AppInstallRequestInfo>>> {
+    let reviewer = auth_user.has_permission(AppsInstall::NAME);
+    if !reviewer && !auth_user.has_permission(AppsRequest::NAME) {
+        return Err(AppError::Forbidden(format!(
+            "Permission denied: {} required",
+            AppsRequest::NAME
+        )));
+    }
+    let db = state.get_db().await?;
+    let requested_by = (!reviewer).then_some(auth_user.user.id);
+    Ok(Json(
+        app_requests::list(&db, requested_by, query.status.as_deref()).await?,
+    ))
+}
+
+/// Ask for an app to be installed
+#[utoipa::path(
+    post,
+    path = "/api/apps/requests",
+    tag = "Apps",
+    request_body = CreateAppInstallRequest,
+    responses(
+        (status = 200, body = AppInstallRequestInfo),
+        (status = 403, description = "System apps cannot be requested"),
+        (status = 404, description = "App not found in catalog"),
+        (status = 409, description = "App installed or already requested")
+    )
+)]
+async fn create_install_request(
+    State(state): State<AppState>,
+    auth: Authorized<AppsRequest>,
+    Json(request): Json<CreateAppInstallRequest>,
+) -> Result<Json<AppInstallRequestInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    let db = state.get_db().await?;
+    if let Some(client) = state.k8s_api().await.as_deref() {
+        if client
+            .namespace_exists(&request.app_name)
+            .await
+            .unwrap_or(false)
+        {
+            return Err(AppError::Conflict(format!(
+                "App '{}' is already installed",
+                request.app_name
+            )));
+        }
+    }
+
+    let created = {
+        let catalog = state.catalog.read().await;
+        app_requests::create(&db, &catalog, auth.user_id(), &request).await?
+    };
+
+    let username = auth.user().username.clone();
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AppInstallRequested,
+            ResourceType::App,
+            Some(created.app_name.clone()),
+            Some(auth.user_id()),
+            Some(username.clone()),
+            Some(serde_json::json!({
+                "request_id": created.id,
+                "comment": created.comment,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let _ = state
+        .notification
+        .notify_event(
+            &AuditAction::AppInstallRequested,
+            Some(auth.user_id()),
+            Some(&username),
+            Some(&created.app_name),
+        )
+        .await;
+
+    Ok(Json(app_requests::info(&db, created).await?))
+}
+
+/// Approve an install request and install the app
+///
+/// The install is audited under the requester. If it fails the request
+/// stays pending.
+#[utoipa::path(
+    post,
+    path = "/api/apps/requests/{request_id}/approve",
+    tag = "Apps",
+    params(("request_id" = i64, Path, description = "Install request ID")),
+    request_body = ReviewAppInstallRequest,
+    responses(
+        (status = 200, body = AppInstallRequestInfo),
+        (status = 404, description = "Request not found"),
+        (status = 409, description = "Request already reviewed")
+    )
+)]
+async fn approve_install_request(
+    State(state): State<AppState>,
+    Path(request_id): Path<i64>,
+    auth: Authorized<AppsInstall>,
+    review: Option<Json<ReviewAppInstallRequest>>,
+) -> Result<Json<AppInstallRequestInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    let review = review.map(|Json(r)| r).unwrap_or_default();
+    let db = state.get_db().await?;
+    let pending = app_requests::pending(&db, request_id).await?;
+    let deploy_request = app_requests::deployment_request(&pending)?;
+    deploy_app(&state, &deploy_request, pending.requested_by).await?;
+
+    let reviewed = app_requests::review(
+        &db,
+        pending,
+        auth.user_id(),
+        true,
+        review.comment.as_deref(),
+    )
+    .await?;
+    let info = app_requests::info(&db, reviewed).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AppInstalled,
+            ResourceType::App,
+            Some(info.app_name.clone()),
+            Some(info.requested_by),
+            info.requested_by_username.clone(),
+            Some(serde_json::json!({
+                "request_id": info.id,
+                "approved_by": auth.user().username,
+                "comment": info.review_comment,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
+/// Deny an install request
+#[utoipa::path(
+    post,
+    path = "/api/apps/requests/{request_id}/deny",
+    tag = "Apps",
+    params(("request_id" = i64, Path, description = "Install request ID")),
+    request_body = ReviewAppInstallRequest,
+    responses(
+        (status = 200, body = AppInstallRequestInfo),
+        (status = 404, description = "Request not found"),
+        (status = 409, description = "Request already reviewed")
+    )
+)]
+async fn deny_install_request(
+    State(state): State<AppState>,
+    Path(request_id): Path<i64>,
+    auth: Authorized<AppsInstall>,
+    review: Option<Json<ReviewAppInstallRequest>>,
+) -> Result<Json<AppInstallRequestInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    let review = review.map(|Json(r)| r).unwrap_or_default();
+    let db = state.get_db().await?;
+    let pending = app_requests::pending(&db, request_id).await?;
+    let reviewed = app_requests::review(
+        &db,
+        pending,
+        auth.user_id(),
+        false,
+        review.comment.as_deref(),
+    )
+    .await?;
+    let info = app_requests::info(&db, reviewed).await?;
+
+    let username = auth.user().username.clone();
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AppInstallDenied,
+            ResourceType::App,
+            Some(info.app_name.clone()),
+            Some(auth.user_id()),
+            Some(username.clone()),
+            Some(serde_json::json!({
+                "request_id": info.id,
+                "requested_by": info.requested_by_username,
+                "comment": info.review_comment,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let _ = state
+        .notification
+        .notify_event(
+            &AuditAction::AppInstallDenied,
+            Some(auth.user_id()),
+            Some(&username),
+            Some(&info.app_name),
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
 /// Delete an app
 #[utoipa::path(
     delete,
@@ -203,7 +784,7 @@ async fn delete_app(
     Path(app_name): Path<String>,
     _auth: Authorized<AppsDelete>,
 ) -> Result<Json<serde_json::Value>> {
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
     // Check if this is a system app
@@ -217,15 +798,21 @@ async fn delete_app(
     }
 
     let client = k8s
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
 
-    let manager = DeploymentManager::new(client, &catalog);
+    let helm = state.helm();
+    let manager = DeploymentManager::new(client, helm.as_ref(), &catalog);
     manager.remove_app(&app_name).await?;
 
     // Invalidate endpoint cache for deleted app
     state.endpoint_cache.invalidate(&app_name).await;
 
+    let db = state.get_db().await?;
+    if let Err(e) = installed_apps::forget(&db, &app_name).await {
+        tracing::warn!("Failed to remove metadata of app '{}': {}", app_name, e);
+    }
+
     Ok(Json(serde_json::json!({
         "success": true,
         "message": format!("App '{}' deletion initiated", app_name),
@@ -252,27 +839,18 @@ async fn restart_app(
 ) -> Result<Json<serde_json::Value>> {
     let namespace = query.namespace.unwrap_or_else(|| app_name.clone());
 
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let client = k8s
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
 
     // Get pods with app label
     let pods = client.get_pod_status(&namespace, Some(&app_name)).await?;
 
     // Delete each pod
-    use k8s_openapi::api::core::v1::Pod;
-    use kube::api::{Api, DeleteParams};
-
-    let pod_api: Api<Pod> = Api::namespaced(client.client().clone(), &namespace);
     let mut deleted_count = 0;
-
     for pod in &pods {
-        if pod_api
-            .delete(&pod.name, &DeleteParams::default())
-            .await
-            .is_ok()
-        {
+        if client.delete_pod(&namespace, &pod.name).await.is_ok() {
             deleted_count += 1;
         }
     }
@@ -286,32 +864,352 @@ async fn restart_app(
     })))
 }
 
-/// List all categories
+/// Upgrade an installed app to the chart version the catalog offers
+///
+/// The app is redeployed with its current settings; only the chart changes.
 #[utoipa::path(
-    get,
-    path = "/api/apps/categories",
+    post,
+    path = "/api/apps/{app_name}/upgrade",
     tag = "Apps",
-    responses((status = 200, body = Vec<String>))
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "App is not installed or not in the catalog"),
+        (status = 409, description = "App already runs the catalog's chart version")
+    )
 )]
-async fn list_categories(
+async fn upgrade_app(
     State(state): State<AppState>,
-    _auth: Authorized<AppsView>,
-) -> Result<Json<Vec<String>>> {
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+) -> Result<Json<DeploymentStatus>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
     let catalog = state.catalog.read().await;
-    Ok(Json(catalog.get_categories()))
-}
 
-/// Get apps by category
-#[utoipa::path(
-    get,
-    path = "/api/apps/category/{category}",
-    tag = "Apps",
-    params(("category" = String, Path, description = "Category name")),
-    responses((status = 200, body = serde_json::Value))
-)]
-async fn get_apps_by_category(
-    State(state): State<AppState>,
-    Path(category): Path<String>,
+    let installed = installed_chart_version(&state, &db, &app_name).await;
+    let catalog_app = installed_apps::clone_source(&db, &app_name)
+        .await?
+        .unwrap_or_else(|| app_name.clone());
+    let latest = catalog
+        .get_app(&catalog_app)
+        .map(|app| app.version.clone())
+        .ok_or_else(|| AppError::NotFound(format!("App '{}' not found in catalog", app_name)))?;
+    if let Some(installed) = installed.as_deref() {
+        if latest.is_empty() || !installed_apps::is_newer(&latest, installed) {
+            return Err(AppError::Conflict(format!(
+                "App '{}' already runs chart version {}",
+                app_name, installed
+            )));
+        }
+    }
+
+    let storage_setting = SystemSetting::find_by_id("storage_path").one(&db).await?;
+    let storage_path = storage_setting.map(|s| s.value);
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    let deploy_request = DeploymentRequest {
+        app_name: app_name.clone(),
+        custom_config: HashMap::new(),
+        allow_incompatible_arch: false,
+    };
+    let mut status = manager
+        .upgrade_app(&deploy_request, storage_path.as_deref())
+        .await?;
+    status.status = "upgrading".to_string();
+    state.endpoint_cache.invalidate(&app_name).await;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppUpgraded,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "from": installed, "to": latest })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(status))
+}
+
+/// Chart version an installed app runs, read back from its Helm release and
+/// recorded for apps installed before versions were tracked
+async fn installed_chart_version(
+    state: &AppState,
+    db: &sea_orm::DatabaseConnection,
+    app_name: &str,
+) -> Option<String> {
+    if let Ok(Some(version)) = installed_apps::chart_version(db, app_name).await {
+        return Some(version);
+    }
+    let version = match state.helm().release_info(app_name, app_name).await {
+        Ok(info) => info.and_then(|info| info.chart_version)?,
+        Err(e) => {
+            tracing::debug!("Failed to read the release of '{}': {}", app_name, e);
+            return None;
+        }
+    };
+    if let Err(e) = installed_apps::set_chart_version(db, app_name, &version).await {
+        tracing::warn!("Failed to record chart version of '{}': {}", app_name, e);
+    }
+    Some(version)
+}
+
+/// Stop an app by scaling its deployments to zero
+#[utoipa::path(
+    post,
+    path = "/api/apps/{app_name}/stop",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses((status = 200, body = serde_json::Value))
+)]
+async fn stop_app(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsStop>,
+) -> Result<Json<serde_json::Value>> {
+    set_app_running(&state, &app_name, false, &auth).await
+}
+
+/// Start a stopped app, restoring its replica counts
+#[utoipa::path(
+    post,
+    path = "/api/apps/{app_name}/start",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses((status = 200, body = serde_json::Value))
+)]
+async fn start_app(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsStop>,
+) -> Result<Json<serde_json::Value>> {
+    set_app_running(&state, &app_name, true, &auth).await
+}
+
+/// Scale an app up or down and audit the change
+async fn set_app_running(
+    state: &AppState,
+    app_name: &str,
+    running: bool,
+    auth: &Authorized<AppsStop>,
+) -> Result<Json<serde_json::Value>> {
+    use crate::models::audit_log::ResourceType;
+
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    let helm = state.helm();
+    let db = state.get_db().await?;
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+
+    let scaled = if running {
+        manager.start_app(app_name).await?
+    } else {
+        let scaled = manager.stop_app(app_name).await?;
+        // A stop by hand overrides a boot hold, so the app stays down
+        installed_apps::set_boot_held(&db, app_name, None).await?;
+        scaled
+    };
+
+    // The service keeps its endpoint, but cached targets may point at pods
+    // that are gone
+    state.endpoint_cache.invalidate(app_name).await;
+
+    let (action, verb) = if running {
+        (AuditAction::AppStarted, "Started")
+    } else {
+        (AuditAction::AppStopped, "Stopped")
+    };
+    let _ = state
+        .audit
+        .log(
+            action,
+            ResourceType::App,
+            Some(app_name.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "deployments": scaled })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("{} {} deployment(s) of app '{}'", verb, scaled, app_name),
+        "state": if running { "installing" } else { "stopped" }
+    })))
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ExecQuery {
+    /// Pod to open the shell in; the app's first running pod by default
+    pub pod: Option<String>,
+    /// Container in the pod; the pod's default container by default
+    pub container: Option<String>,
+    /// Command to run, split on spaces; `/bin/sh` by default
+    pub command: Option<String>,
+}
+
+/// Open a shell in one of an app's pods
+///
+/// Upgrades to a WebSocket bridged to the shell. Binary frames are stdin and
+/// stdout; text frames carry `{"type": "resize", "cols", "rows"}` and
+/// `{"type": "stdin", "data"}`, and the server sends
+/// `{"type": "exit", "exit_code"}` when the shell exits. Opening and closing
+/// the shell are both audit-logged.
+#[utoipa::path(
+    get,
+    path = "/api/apps/{app_name}/exec",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name"), ExecQuery),
+    responses(
+        (status = 101, description = "Switching to the WebSocket shell"),
+        (status = 400, description = "The app has no running pod"),
+        (status = 404, description = "The pod is not one of the app's")
+    )
+)]
+async fn exec_in_app(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    Query(query): Query<ExecQuery>,
+    headers: HeaderMap,
+    auth: Authorized<AppsExec>,
+    ws: std::result::Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response> {
+    use crate::models::audit_log::ResourceType;
+
+    let k8s = state.k8s_api().await;
+    let api = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let pods = api.get_pod_status(&app_name, None).await?;
+    let pod = pod_exec::select_pod(&pods, query.pod.as_deref())?;
+    let command = pod_exec::parse_command(query.command.as_deref());
+
+    let ws = match ws {
+        Ok(ws) => ws,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let client = state
+        .k8s_client
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+
+    let mut details = serde_json::json!({
+        "pod": pod,
+        "container": query.container,
+        "command": command.join(" "),
+    });
+    let ip_address = ip_bans::client_ip(&headers);
+    let user_id = auth.user_id();
+    let username = auth.user().username.clone();
+    let process = match client
+        .exec_tty(&app_name, &pod, query.container.as_deref(), command)
+        .await
+    {
+        Ok(process) => process,
+        Err(e) => {
+            let _ = state
+                .audit
+                .log_failure(
+                    AuditAction::AppShellOpened,
+                    ResourceType::App,
+                    Some(app_name.clone()),
+                    Some(user_id),
+                    Some(username),
+                    Some(details),
+                    ip_address,
+                    None,
+                    &e.to_string(),
+                )
+                .await;
+            return Err(e);
+        }
+    };
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AppShellOpened,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(user_id),
+            Some(username.clone()),
+            Some(details.clone()),
+            ip_address.clone(),
+            None,
+        )
+        .await;
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        let started = std::time::Instant::now();
+        let summary = pod_exec::run_session(socket, process).await;
+        details["duration_secs"] = started.elapsed().as_secs().into();
+        details["exit_code"] = summary.exit_code.into();
+        details["stdin_bytes"] = summary.stdin_bytes.into();
+        details["closed_by_client"] = summary.closed_by_client.into();
+        let _ = state
+            .audit
+            .log_success(
+                AuditAction::AppShellClosed,
+                ResourceType::App,
+                Some(app_name),
+                Some(user_id),
+                Some(username),
+                Some(details),
+                ip_address,
+                None,
+            )
+            .await;
+    }))
+}
+
+/// List all categories
+#[utoipa::path(
+    get,
+    path = "/api/apps/categories",
+    tag = "Apps",
+    responses((status = 200, body = Vec<String>))
+)]
+async fn list_categories(
+    State(state): State<AppState>,
+    _auth: Authorized<AppsView>,
+) -> Result<Json<Vec<String>>> {
+    let catalog = state.catalog.read().await;
+    Ok(Json(catalog.get_categories()))
+}
+
+/// Get apps by category
+#[utoipa::path(
+    get,
+    path = "/api/apps/category/{category}",
+    tag = "Apps",
+    params(("category" = String, Path, description = "Category name")),
+    responses((status = 200, body = serde_json::Value))
+)]
+async fn get_apps_by_category(
+    State(state): State<AppState>,
+    Path(category): Path<String>,
     _auth: Authorized<AppsView>,
 ) -> Result<Json<Vec<AppConfig>>> {
     let catalog = state.catalog.read().await;
@@ -336,14 +1234,15 @@ async fn check_app_health(
     Path(app_name): Path<String>,
     _auth: Authorized<AppsView>,
 ) -> Result<Json<serde_json::Value>> {
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
     let client = k8s
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
 
-    let manager = DeploymentManager::new(client, &catalog);
+    let helm = state.helm();
+    let manager = DeploymentManager::new(client, helm.as_ref(), &catalog);
     let health = manager.check_namespace_health(&app_name).await?;
 
     Ok(Json(health))
@@ -362,14 +1261,15 @@ async fn check_app_exists(
     Path(app_name): Path<String>,
     _auth: Authorized<AppsView>,
 ) -> Result<Json<serde_json::Value>> {
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
     let client = k8s
-        .as_ref()
+        .as_deref()
         .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
 
-    let manager = DeploymentManager::new(client, &catalog);
+    let helm = state.helm();
+    let manager = DeploymentManager::new(client, helm.as_ref(), &catalog);
     let exists = manager.check_namespace_exists(&app_name).await;
 
     Ok(Json(serde_json::json!({"exists": exists})))
@@ -388,10 +1288,10 @@ async fn get_app_status(
     Path(app_name): Path<String>,
     _auth: Authorized<AppsView>,
 ) -> Result<Json<serde_json::Value>> {
-    let k8s = state.k8s_client.read().await;
+    let k8s = state.k8s_api().await;
     let catalog = state.catalog.read().await;
 
-    let client = match k8s.as_ref() {
+    let client = match k8s.as_deref() {
         Some(c) => c,
         None => {
             return Ok(Json(serde_json::json!({
@@ -401,7 +1301,8 @@ async fn get_app_status(
         }
     };
 
-    let manager = DeploymentManager::new(client, &catalog);
+    let helm = state.helm();
+    let manager = DeploymentManager::new(client, helm.as_ref(), &catalog);
 
     // Check if namespace exists
     if !manager.check_namespace_exists(&app_name).await {
@@ -411,6 +1312,42 @@ async fn get_app_status(
         })));
     }
 
+    // Held back until the apps it depends on are up
+    if let Ok(db) = state.get_db().await {
+        let held = InstalledApp::find_by_id(&app_name)
+            .one(&db)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|r| r.boot_held_at.is_some());
+        if held {
+            let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+            if let Ok(boot) =
+                boot_order::boot_status(&db, client, &manager, &catalog, &app_name).await
+            {
+                return Ok(Json(serde_json::json!({
+                    "state": "waiting",
+                    "message": if boot.waiting_for.is_empty() {
+                        "Starting".to_string()
+                    } else {
+                        format!("Waiting for {}", boot.waiting_for.join(", "))
+                    },
+                    "boot": boot
+                })));
+            }
+        }
+    }
+
+    // Scaled to zero by a stop
+    if let Ok(run_status) = maintenance::app_run_status(client, &app_name).await {
+        if run_status.state == AppRunState::Stopped {
+            return Ok(Json(serde_json::json!({
+                "state": "stopped",
+                "message": "Stopped"
+            })));
+        }
+    }
+
     // Check health
     match manager.check_namespace_health(&app_name).await {
         Ok(health) => {
@@ -420,7 +1357,7 @@ async fn get_app_status(
                     "state": "installed",
                     "message": "Running"
                 }))),
-                "no_deployments" => Ok(Json(serde_json::json!({
+                "no_workloads" => Ok(Json(serde_json::json!({
                     "state": "idle",
                     "message": "No deployments found"
                 }))),
@@ -448,6 +1385,11 @@ async fn sync_charts(
     State(state): State<AppState>,
     _auth: Authorized<AppsInstall>,
 ) -> Result<Json<serde_json::Value>> {
+    if CONFIG.outbound.offline {
+        return Err(AppError::BadRequest(
+            "Chart sync is disabled in offline mode, import a chart bundle instead".to_string(),
+        ));
+    }
     state
         .chart_sync
         .sync()
@@ -497,3 +1439,469 @@ async fn log_app_access(
         "message": "Access logged"
     })))
 }
+
+/// Replace the notes and tags of an installed app
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/metadata",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateInstalledAppMetadata,
+    responses(
+        (status = 200, body = InstalledAppInfo),
+        (status = 400, description = "Too many or too long tags, or notes too long"),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn update_app_metadata(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+    Json(request): Json<UpdateInstalledAppMetadata>,
+) -> Result<Json<InstalledAppInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let info = installed_apps::update_metadata(&db, &app_name, request).await?;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "tags": info.tags, "notes_set": info.notes.is_some() })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
+/// Assign an app to a tenant, hiding it from everyone outside the tenant,
+/// or share it again with `tenant_id: null`
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/tenant",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = AssignAppTenant,
+    responses(
+        (status = 200, body = InstalledAppInfo),
+        (status = 404, description = "App is not installed, or tenant not found")
+    )
+)]
+async fn update_app_tenant(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<TenantsManage>,
+    Json(request): Json<AssignAppTenant>,
+) -> Result<Json<InstalledAppInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let info = tenants::assign_app(&db, &app_name, request.tenant_id).await?;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "tenant_id": info.tenant_id })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
+/// Exclude an app from idle auto-suspend, or include it again
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/idle-suspend",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateIdleSuspend,
+    responses(
+        (status = 200, body = InstalledAppInfo),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn update_idle_suspend(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsStop>,
+    Json(request): Json<UpdateIdleSuspend>,
+) -> Result<Json<InstalledAppInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let info = installed_apps::set_idle_suspend_exempt(&db, &app_name, request.exempt).await?;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "idle_suspend_exempt": info.idle_suspend_exempt })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
+/// Get an app's boot priority, dependencies and what it is waiting for
+#[utoipa::path(
+    get,
+    path = "/api/apps/{app_name}/boot-order",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = BootStatus),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn get_boot_order(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsView>,
+) -> Result<Json<BootStatus>> {
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    let db = state.get_db().await?;
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    Ok(Json(
+        boot_order::boot_status(&db, client, &manager, &catalog, &app_name).await?,
+    ))
+}
+
+/// Set the order an app starts in after a reboot
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/boot-order",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateBootOrder,
+    responses(
+        (status = 200, body = InstalledAppInfo),
+        (status = 400, description = "Invalid priority, unknown dependency or dependency cycle"),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn update_boot_order(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+    Json(request): Json<UpdateBootOrder>,
+) -> Result<Json<InstalledAppInfo>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    if catalog.get_app(&app_name).is_some_and(|app| app.is_system) {
+        return Err(AppError::BadRequest(
+            "System apps always start without waiting".to_string(),
+        ));
+    }
+    let db = state.get_db().await?;
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    let (priority, depends_on) =
+        boot_order::validate(&db, client, &manager, &catalog, &app_name, request).await?;
+    let info = installed_apps::set_boot_order(&db, &app_name, priority, &depends_on).await?;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "boot_priority": info.boot_priority,
+                "depends_on": info.depends_on,
+            })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(info))
+}
+
+/// Get how the proxy routes an app
+#[utoipa::path(
+    get,
+    path = "/api/apps/{app_name}/routing",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses((status = 200, body = AppRouting))
+)]
+async fn get_app_routing(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsView>,
+) -> Result<Json<AppRouting>> {
+    let db = state.get_db().await?;
+    Ok(Json(app_routing::get_routing(&db, &app_name).await?))
+}
+
+/// Switch an app between path prefix and subdomain routing
+///
+/// The app is redeployed so its ingress follows the new routing.
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/routing",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateAppRouting,
+    responses(
+        (status = 200, body = AppRouting),
+        (status = 400, description = "Invalid subdomain or app_base_domain not set"),
+        (status = 404, description = "App is not installed"),
+        (status = 409, description = "Subdomain already used by another app")
+    )
+)]
+async fn update_app_routing(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+    Json(request): Json<UpdateAppRouting>,
+) -> Result<Json<AppRouting>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let routing = app_routing::plan_routing(&db, &app_name, request).await?;
+
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    let storage_setting = SystemSetting::find_by_id("storage_path").one(&db).await?;
+    let storage_path = storage_setting.map(|s| s.value);
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    let deploy_request = DeploymentRequest {
+        app_name: app_name.clone(),
+        custom_config: HashMap::new(),
+        allow_incompatible_arch: false,
+    };
+    // Only store the new routing once the ingress actually follows it
+    manager
+        .redeploy_app(&deploy_request, storage_path.as_deref(), Some(&routing))
+        .await?;
+    app_routing::save_routing(&db, &routing).await?;
+    state.endpoint_cache.invalidate(&app_name).await;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "routing": routing.mode, "host": routing.host })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(routing))
+}
+
+/// Get the Helm values of an installed app: the overrides set on top of its
+/// chart's defaults, the effective values and the chart's values schema
+///
+/// Credential-like values are masked. Requires the install permission, as
+/// values can hold secrets masking does not recognize.
+#[utoipa::path(
+    get,
+    path = "/api/apps/{app_name}/values",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = AppValues),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn get_app_values(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsInstall>,
+) -> Result<Json<AppValues>> {
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let chart = installed_apps::clone_source(&db, &app_name)
+        .await?
+        .unwrap_or_else(|| app_name.clone());
+    Ok(Json(app_values::get(&db, &app_name, &chart).await?))
+}
+
+/// Replace the Helm value overrides of an installed app
+///
+/// The chart's defaults merged with the overrides are checked against the
+/// chart's values schema, then the app is upgraded in place with them.
+/// Masked values sent back unchanged keep their stored value.
+#[utoipa::path(
+    put,
+    path = "/api/apps/{app_name}/values",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateAppValues,
+    responses(
+        (status = 200, body = AppValues),
+        (status = 400, description = "Overrides are not an object or do not match the chart schema"),
+        (status = 404, description = "App is not installed")
+    )
+)]
+async fn update_app_values(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsInstall>,
+    Json(request): Json<UpdateAppValues>,
+) -> Result<Json<AppValues>> {
+    use crate::models::audit_log::ResourceType;
+
+    ensure_deployed(&state, &app_name).await?;
+
+    let db = state.get_db().await?;
+    let chart = installed_apps::clone_source(&db, &app_name)
+        .await?
+        .unwrap_or_else(|| app_name.clone());
+    let overrides = app_values::plan(&db, &app_name, &chart, request).await?;
+
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    let storage_setting = SystemSetting::find_by_id("storage_path").one(&db).await?;
+    let storage_path = storage_setting.map(|s| s.value);
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    let deploy_request = DeploymentRequest {
+        app_name: app_name.clone(),
+        custom_config: HashMap::new(),
+        allow_incompatible_arch: false,
+    };
+    manager
+        .update_values(&deploy_request, storage_path.as_deref(), &overrides)
+        .await?;
+    state.endpoint_cache.invalidate(&app_name).await;
+
+    let values = app_values::describe(&app_name, &chart, &overrides)?;
+    let _ = state
+        .audit
+        .log(
+            AuditAction::AppConfigured,
+            ResourceType::App,
+            Some(app_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "value_overrides": values.overrides })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(values))
+}
+
+/// Fail with 404 unless `app_name` is currently deployed
+async fn ensure_deployed(state: &AppState, app_name: &str) -> Result<()> {
+    let k8s = state.k8s_api().await;
+    let client = k8s
+        .as_deref()
+        .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+    let catalog = state.catalog.read().await;
+    let db = state.get_db().await?;
+    let helm = state.helm();
+    let manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
+    if !manager
+        .get_deployed_apps()
+        .await
+        .iter()
+        .any(|a| a == app_name)
+    {
+        return Err(AppError::NotFound(format!(
+            "App '{}' is not installed",
+            app_name
+        )));
+    }
+    Ok(())
+}
+
+/// Get app-native status (queue sizes, transfer rates, active streams, ...)
+/// from the app's own API using its stored integration credentials
+#[utoipa::path(
+    get,
+    path = "/api/apps/{app_name}/native-status",
+    tag = "Apps",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = NativeStatus),
+        (status = 403, description = "No access to this app"),
+        (status = 404, description = "No integration adapter for this app")
+    )
+)]
+async fn get_native_status(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<AppsView>,
+) -> Result<Json<NativeStatus>> {
+    let db = state.get_db().await?;
+    if !user_has_app_access(&db, auth.user_id(), &app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "No access to app '{}'",
+            app_name
+        )));
+    }
+
+    let status = integrations::fetch_native_status(&state, &app_name).await?;
+    Ok(Json(status))
+}
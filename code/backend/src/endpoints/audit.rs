@@ -4,11 +4,17 @@ use axum::{
     Json, Router,
 };
 
-use crate::error::Result;
-use crate::middleware::permissions::{AuditManage, AuditView, Authorized};
+use crate::endpoints::extractors::get_user_permissions;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    AuditManage, AuditView, AuditViewOwnApps, Authenticated, Authorized, Permission,
+};
 use crate::services::audit::{
-    clear_old_logs, get_audit_logs, get_audit_stats, AuditLogQuery, AuditLogResponse, AuditStats,
+    clear_old_logs, get_audit_stats, get_audit_timeseries, get_scoped_audit_logs,
+    resolve_app_admin_scope, AuditChainVerification, AuditExclusion, AuditLogQuery,
+    AuditLogResponse, AuditStats, AuditTimeseries, AuditTimeseriesQuery,
 };
+use crate::services::tenants;
 use crate::state::AppState;
 
 /// Create audit routes
@@ -16,6 +22,8 @@ pub fn audit_routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(list_audit_logs))
         .route("/stats", get(audit_stats))
+        .route("/stats/timeseries", get(audit_timeseries))
+        .route("/verify", get(verify_audit_log))
         .route("/clear", axum::routing::post(clear_audit_logs))
         .with_state(state)
 }
@@ -25,17 +33,42 @@ pub fn audit_routes(state: AppState) -> Router {
     path = "/api/audit",
     tag = "Audit",
     responses(
-        (status = 200, description = "Audit logs with pagination", body = serde_json::Value)
+        (status = 200, description = "Audit logs with pagination", body = serde_json::Value),
+        (status = 403, description = "Neither audit.view nor audit.view_own_apps granted")
     )
 )]
-/// List audit logs with filtering and pagination
+/// List audit logs with filtering and pagination.
+///
+/// Holders of `audit.view` see every entry; holders of `audit.view_own_apps`
+/// only see entries for the apps and users their roles manage, and tenant
+/// admins the entries for their tenants' apps and members. Entries about
+/// other tenants are hidden unless the caller holds `tenants.manage`.
 async fn list_audit_logs(
     State(state): State<AppState>,
-    _auth: Authorized<AuditView>,
+    auth: Authenticated,
     Query(query): Query<AuditLogQuery>,
 ) -> Result<Json<AuditLogResponse>> {
     let db = state.get_db().await?;
-    let logs = get_audit_logs(&db, query).await?;
+    let permissions = get_user_permissions(&db, auth.user_id()).await;
+
+    let scope = if permissions.iter().any(|p| p == AuditView::NAME) {
+        None
+    } else if permissions.iter().any(|p| p == AuditViewOwnApps::NAME) {
+        Some(resolve_app_admin_scope(&db, auth.user_id()).await?)
+    } else if let Some(scope) = tenants::admin_audit_scope(&db, auth.user_id()).await? {
+        Some(scope)
+    } else {
+        return Err(AppError::Forbidden(format!(
+            "Permission denied: {} required",
+            AuditView::NAME
+        )));
+    };
+
+    let hidden = AuditExclusion {
+        apps: tenants::hidden_apps(&db, auth.user_id()).await?,
+        user_ids: tenants::hidden_users(&db, auth.user_id()).await?,
+    };
+    let logs = get_scoped_audit_logs(&db, query, scope.as_ref(), &hidden).await?;
     Ok(Json(logs))
 }
 
@@ -57,6 +90,43 @@ async fn audit_stats(
     Ok(Json(stats))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/audit/stats/timeseries",
+    tag = "Audit",
+    params(AuditTimeseriesQuery),
+    responses(
+        (status = 200, description = "Audit event counts per time bucket", body = AuditTimeseries),
+        (status = 400, description = "Invalid range or grouping")
+    )
+)]
+/// Count audit events per hour or day, split by action, outcome and/or user
+async fn audit_timeseries(
+    State(state): State<AppState>,
+    _auth: Authorized<AuditView>,
+    Query(query): Query<AuditTimeseriesQuery>,
+) -> Result<Json<AuditTimeseries>> {
+    let db = state.get_db().await?;
+    Ok(Json(get_audit_timeseries(&db, query).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/audit/verify",
+    tag = "Audit",
+    responses(
+        (status = 200, description = "Hash chain validation result", body = AuditChainVerification)
+    )
+)]
+/// Validate the audit hash chain and report the first broken link
+async fn verify_audit_log(
+    State(state): State<AppState>,
+    _auth: Authorized<AuditView>,
+) -> Result<Json<AuditChainVerification>> {
+    let db = state.get_db().await?;
+    Ok(Json(state.audit.verify_chain(&db).await?))
+}
+
 /// Clear old audit logs (admin only)
 #[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct ClearLogsRequest {
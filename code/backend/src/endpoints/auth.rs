@@ -1,23 +1,28 @@
 use axum::{
-    extract::{Path, State},
-    http::{header, HeaderMap, HeaderValue},
-    response::{IntoResponse, Response},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
     routing::{delete, get, post},
-    Json, Router,
+    Form, Json, Router,
 };
 use chrono::{Duration, Utc};
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
+use tower_http::services::ServeDir;
 
 use crate::config::CONFIG;
 use crate::error::{AppError, Result};
 use crate::middleware::auth::{
-    ACTIVE_SESSION_COOKIE, MAX_SESSIONS, SESSION_COOKIE_BASE, SESSION_COOKIE_NAME,
+    active_session_cookie_name, refresh_cookie, session_cookie, session_cookie_name, MAX_SESSIONS,
+    SESSION_COOKIE_MAX_AGE,
 };
+use crate::models::audit_log::{AuditAction, ResourceType};
 use crate::models::prelude::*;
 use crate::models::{role, session, two_factor_recovery_code, user, user_role};
+use crate::services::access::unexpired_roles;
 use crate::services::{
-    create_session_token, decode_session_token, verify_password, verify_recovery_code, verify_totp,
+    app_routing, approvals, create_session_token, decode_session_token, ip_bans, lockout,
+    login_alerts, refresh_tokens, verify_password, verify_recovery_code, verify_totp,
 };
 use crate::state::AppState;
 
@@ -26,11 +31,18 @@ pub fn auth_routes(state: AppState) -> Router {
     Router::new()
         .route("/login", post(login))
         .route("/logout", post(logout))
+        .route("/session/refresh", post(refresh_session))
         .route("/sessions", get(list_sessions))
         .route("/sessions/{session_id}", delete(revoke_session))
+        .route(
+            "/sessions/revoke-link",
+            get(revoke_link_page).post(revoke_link_confirm),
+        )
         .route("/switch/{slot}", post(switch_session))
         .route("/accounts", get(list_accounts))
         .route("/2fa/recover", post(recover_with_code))
+        // Self-hosted CSS/JS so auth and docs pages work without third-party CDNs
+        .nest_service("/assets", ServeDir::new(&CONFIG.server.static_dir))
         .with_state(state)
 }
 
@@ -53,6 +65,13 @@ pub struct LoginResponse {
     pub session_slot: usize,
 }
 
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct RefreshResponse {
+    pub session_slot: usize,
+    /// Seconds until the new access token expires
+    pub expires_in: i64,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct AccountInfo {
     pub slot: usize,
@@ -84,56 +103,113 @@ pub struct RecoveryLoginRequest {
 // ============================================================================
 
 /// Create an indexed session cookie with the given token
-fn create_session_cookie_for_slot(slot: usize, token: &str, secure: bool) -> HeaderValue {
-    let cookie = format!(
-        "{}_{}={}; HttpOnly; SameSite=Lax; Path=/; Max-Age=604800{}",
-        SESSION_COOKIE_BASE,
-        slot,
-        token,
-        if secure { "; Secure" } else { "" }
-    );
-    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+fn create_session_cookie_for_slot(slot: usize, token: &str) -> HeaderValue {
+    let name = format!("{}_{}", session_cookie_name(), slot);
+    session_cookie(&name, token, true, SESSION_COOKIE_MAX_AGE, None)
+}
+
+/// Name of the refresh token cookie of a session slot
+fn refresh_cookie_name(slot: usize) -> String {
+    format!("{}_refresh_{}", session_cookie_name(), slot)
+}
+
+/// Create the refresh token cookie of a session slot
+fn create_refresh_cookie_for_slot(slot: usize, token: &str) -> HeaderValue {
+    refresh_cookie(&refresh_cookie_name(slot), token, SESSION_COOKIE_MAX_AGE)
+}
+
+/// Create a cookie that clears the refresh token of a session slot
+fn clear_refresh_cookie_for_slot(slot: usize) -> HeaderValue {
+    refresh_cookie(&refresh_cookie_name(slot), "", 0)
+}
+
+/// Refresh token sent for a session slot
+fn refresh_token_for_slot(headers: &HeaderMap, slot: usize) -> Option<String> {
+    let cookie_str = headers.get(header::COOKIE)?.to_str().ok()?;
+    let prefix = format!("{}=", refresh_cookie_name(slot));
+    cookie_str
+        .split(';')
+        .find_map(|cookie| cookie.trim().strip_prefix(&prefix))
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
 }
 
 /// Create the active session cookie
-fn create_active_session_cookie(slot: usize, secure: bool) -> HeaderValue {
-    let cookie = format!(
-        "{}={}; SameSite=Lax; Path=/; Max-Age=604800{}",
-        ACTIVE_SESSION_COOKIE,
-        slot,
-        if secure { "; Secure" } else { "" }
-    );
-    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+fn create_active_session_cookie(slot: usize) -> HeaderValue {
+    session_cookie(
+        active_session_cookie_name(),
+        &slot.to_string(),
+        false,
+        SESSION_COOKIE_MAX_AGE,
+        None,
+    )
 }
 
 /// Create a cookie that clears an indexed session
-#[allow(dead_code)]
 fn clear_session_cookie_for_slot(slot: usize) -> HeaderValue {
-    let cookie = format!(
-        "{}_{}=; HttpOnly; SameSite=Lax; Path=/; Max-Age=0",
-        SESSION_COOKIE_BASE, slot
-    );
-    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+    let name = format!("{}_{}", session_cookie_name(), slot);
+    session_cookie(&name, "", true, 0, None)
 }
 
 /// Legacy: Create a session cookie with the given token (for backwards compatibility)
-fn create_session_cookie(token: &str, secure: bool) -> HeaderValue {
-    let cookie = format!(
-        "{}={}; HttpOnly; SameSite=Lax; Path=/; Max-Age=604800{}",
-        SESSION_COOKIE_NAME,
+///
+/// This is the cookie the app proxy reads, so it carries `Domain` when a
+/// session cookie domain is configured for subdomain-routed apps.
+fn create_session_cookie(token: &str, domain: Option<&str>) -> HeaderValue {
+    session_cookie(
+        session_cookie_name(),
         token,
-        if secure { "; Secure" } else { "" }
-    );
-    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+        true,
+        SESSION_COOKIE_MAX_AGE,
+        domain,
+    )
 }
 
 /// Create a cookie that clears the session
-fn clear_session_cookie() -> HeaderValue {
-    let cookie = format!(
-        "{}=; HttpOnly; SameSite=Lax; Path=/; Max-Age=0",
-        SESSION_COOKIE_NAME
+fn clear_session_cookie(domain: Option<&str>) -> HeaderValue {
+    session_cookie(session_cookie_name(), "", true, 0, domain)
+}
+
+/// The configured session cookie domain, if any
+async fn cookie_domain(state: &AppState) -> Option<String> {
+    let db = state.get_db().await.ok()?;
+    app_routing::session_cookie_domain(&db).await.ok().flatten()
+}
+
+/// Cookies that sign a new session in: its indexed cookie, the active slot
+/// pointing at it and the legacy cookie for backwards compatibility
+async fn complete_session_login(
+    state: &AppState,
+    slot: usize,
+    token: &str,
+    refresh_token: &str,
+) -> HeaderMap {
+    let domain = cookie_domain(state).await;
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SET_COOKIE,
+        create_session_cookie_for_slot(slot, token),
     );
-    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+    headers.append(
+        header::SET_COOKIE,
+        create_refresh_cookie_for_slot(slot, refresh_token),
+    );
+    headers.append(header::SET_COOKIE, create_active_session_cookie(slot));
+    headers.append(
+        header::SET_COOKIE,
+        create_session_cookie(token, domain.as_deref()),
+    );
+    headers
+}
+
+/// Session slot selected by the active session cookie
+fn active_slot(headers: &HeaderMap) -> Option<usize> {
+    let cookie_str = headers.get(header::COOKIE)?.to_str().ok()?;
+    let prefix = format!("{}=", active_session_cookie_name());
+    cookie_str
+        .split(';')
+        .find_map(|cookie| cookie.trim().strip_prefix(&prefix))
+        .and_then(|value| value.parse().ok())
 }
 
 /// Parse existing session cookies from headers to find used slots and their user IDs
@@ -155,7 +231,7 @@ async fn get_existing_sessions(state: &AppState, headers: &HeaderMap) -> Vec<(us
     };
 
     for i in 0..MAX_SESSIONS {
-        let prefix = format!("{}_{}=", SESSION_COOKIE_BASE, i);
+        let prefix = format!("{}_{}=", session_cookie_name(), i);
         for cookie in cookie_str.split(';') {
             let cookie = cookie.trim();
             if let Some(token) = cookie.strip_prefix(&prefix) {
@@ -217,7 +293,9 @@ async fn login(
     headers: HeaderMap,
     Json(request): Json<LoginRequest>,
 ) -> Result<Response> {
+    const METHOD: &str = "password";
     let db = state.get_db().await?;
+    lockout::check_ip(&db, ip_bans::client_ip(&headers).as_deref(), Utc::now()).await?;
 
     // Find user by username or email
     let found_user = User::find()
@@ -226,9 +304,21 @@ async fn login(
                 .eq(&request.username)
                 .or(user::Column::Email.eq(&request.username)),
         )
+        .filter(user::Column::IsServiceAccount.eq(false))
         .one(&db)
-        .await?
-        .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
+        .await?;
+    let Some(found_user) = found_user else {
+        return Err(sign_in_failed(
+            &state,
+            &headers,
+            AuditAction::LoginFailed,
+            None,
+            &request.username,
+            METHOD,
+            "Invalid credentials",
+        )
+        .await);
+    };
 
     // Check if user is active and approved
     if !found_user.is_active {
@@ -239,10 +329,20 @@ async fn login(
             "Account is pending approval".to_string(),
         ));
     }
+    lockout::check_user(&found_user, Utc::now())?;
 
     // Verify password
     if !verify_password(&request.password, &found_user.hashed_password) {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+        return Err(sign_in_failed(
+            &state,
+            &headers,
+            AuditAction::LoginFailed,
+            Some(&found_user),
+            &found_user.username,
+            METHOD,
+            "Invalid credentials",
+        )
+        .await);
     }
 
     // If role requires 2FA but user hasn't set it up, block login
@@ -263,10 +363,21 @@ async fn login(
         })?;
 
         if !verify_totp(totp_secret, totp_code, &found_user.email)? {
-            return Err(AppError::Unauthorized("Invalid TOTP code".to_string()));
+            return Err(sign_in_failed(
+                &state,
+                &headers,
+                AuditAction::TwoFactorFailed,
+                Some(&found_user),
+                &found_user.username,
+                METHOD,
+                "Invalid TOTP code",
+            )
+            .await);
         }
     }
 
+    let found_user = lockout::reset(&db, &found_user).await?;
+
     // Create session record in database
     let session_id = uuid::Uuid::new_v4().to_string();
     let now = Utc::now();
@@ -278,12 +389,9 @@ async fn login(
         .and_then(|h| h.to_str().ok())
         .map(|s| s.chars().take(255).collect::<String>());
 
-    let ip_address = headers
-        .get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
+    let ip_address = ip_bans::client_ip(&headers);
 
+    let refresh = refresh_tokens::issue(&session_id);
     let session = session::ActiveModel {
         id: Set(session_id.clone()),
         user_id: Set(found_user.id),
@@ -293,11 +401,24 @@ async fn login(
         expires_at: Set(expires_at),
         last_accessed_at: Set(now),
         is_revoked: Set(false),
+        refresh_token_hash: Set(Some(refresh.hash)),
+        previous_refresh_token_hash: Set(None),
+        refreshed_at: Set(None),
     };
-    session.insert(&db).await?;
+    let session = session.insert(&db).await?;
+    audit_sign_in(
+        &state,
+        found_user.id,
+        &found_user.username,
+        serde_json::json!({ "method": METHOD }),
+        session.ip_address.clone(),
+        session.user_agent.clone(),
+    )
+    .await;
+    alert_new_device(&state, &found_user.username, &session).await;
 
     // Create minimal session token (JWT containing only session ID)
-    let session_token = create_session_token(&session_id)?;
+    let session_token = create_session_token(&session_id, None)?;
 
     // Find available slot for this session
     let existing_sessions = get_existing_sessions(&state, &headers).await;
@@ -311,9 +432,6 @@ async fn login(
         session_slot: slot,
     });
 
-    // Determine if we should set Secure flag (check if running behind HTTPS)
-    let secure = CONFIG.auth.oauth2_issuer_url.starts_with("https://");
-
     tracing::info!(
         user_id = found_user.id,
         username = found_user.username,
@@ -322,22 +440,8 @@ async fn login(
         slot
     );
 
-    // Set both the indexed session cookie and the active session cookie
-    let mut response_headers = axum::http::HeaderMap::new();
-    response_headers.insert(
-        header::SET_COOKIE,
-        create_session_cookie_for_slot(slot, &session_token, secure),
-    );
-    response_headers.append(
-        header::SET_COOKIE,
-        create_active_session_cookie(slot, secure),
-    );
-    // Also set legacy cookie for backwards compatibility
-    response_headers.append(
-        header::SET_COOKIE,
-        create_session_cookie(&session_token, secure),
-    );
-
+    let response_headers =
+        complete_session_login(&state, slot, &session_token, &refresh.token).await;
     Ok((response_headers, response).into_response())
 }
 
@@ -362,13 +466,67 @@ async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Result<Res
         }
     }
 
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::SET_COOKIE,
+        clear_session_cookie(cookie_domain(&state).await.as_deref()),
+    );
+    if let Some(slot) = active_slot(&headers) {
+        response_headers.append(header::SET_COOKIE, clear_session_cookie_for_slot(slot));
+        response_headers.append(header::SET_COOKIE, clear_refresh_cookie_for_slot(slot));
+    }
+
     Ok((
-        [(header::SET_COOKIE, clear_session_cookie())],
+        response_headers,
         Json(serde_json::json!({"message": "Logged out"})),
     )
         .into_response())
 }
 
+/// Renew the active session's access token with its refresh token
+///
+/// Both tokens are replaced. A refresh token that was already used revokes
+/// the session.
+#[utoipa::path(
+    post,
+    path = "/auth/session/refresh",
+    tag = "Auth",
+    responses(
+        (status = 200, body = RefreshResponse),
+        (status = 401, description = "Missing, expired or reused refresh token"),
+        (status = 409, description = "Another request just refreshed the session")
+    )
+)]
+async fn refresh_session(State(state): State<AppState>, headers: HeaderMap) -> Result<Response> {
+    let slot = active_slot(&headers).unwrap_or(0);
+    let token = refresh_token_for_slot(&headers, slot)
+        .ok_or_else(|| AppError::Unauthorized("Missing refresh token".to_string()))?;
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.chars().take(255).collect::<String>());
+    let rotation = refresh_tokens::rotate(
+        &state,
+        &token,
+        ip_bans::client_ip(&headers),
+        user_agent,
+        Utc::now(),
+    )
+    .await?;
+
+    let session_token = create_session_token(&rotation.session.id, None)?;
+    let response_headers =
+        complete_session_login(&state, slot, &session_token, &rotation.refresh_token).await;
+    Ok((
+        response_headers,
+        Json(RefreshResponse {
+            session_slot: slot,
+            expires_in: CONFIG.auth.access_token_ttl,
+        }),
+    )
+        .into_response())
+}
+
 /// List all active sessions for the current user
 #[utoipa::path(
     get,
@@ -480,11 +638,242 @@ async fn revoke_session(
     Ok(Json(serde_json::json!({"message": "Session revoked"})))
 }
 
+/// Send a new-device alert for a fresh session; a failed alert does not
+/// fail the login
+async fn alert_new_device(state: &AppState, username: &str, session: &session::Model) {
+    let Ok(db) = state.get_db().await else {
+        return;
+    };
+    if let Err(e) = login_alerts::check_login(&db, &state.notification, username, session).await {
+        tracing::warn!(
+            "Failed to send new-device alert to user {}: {}",
+            session.user_id,
+            e
+        );
+    }
+}
+
+/// Audit a successful sign-in with the session's address and user agent
+pub(crate) async fn audit_sign_in(
+    state: &AppState,
+    user_id: i64,
+    username: &str,
+    details: serde_json::Value,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+) {
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::Login,
+            ResourceType::Session,
+            None,
+            Some(user_id),
+            Some(username.to_string()),
+            Some(details),
+            ip_address,
+            user_agent,
+        )
+        .await;
+}
+
+/// Record a failed sign-in, which may ban the client's address and lock the
+/// user's account, and return the error to answer with
+async fn sign_in_failed(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: AuditAction,
+    user: Option<&user::Model>,
+    username: &str,
+    method: &str,
+    message: &str,
+) -> AppError {
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.chars().take(255).collect::<String>());
+    if let Err(e) = ip_bans::record_failure(
+        state,
+        action,
+        username,
+        ip_bans::client_ip(headers),
+        user_agent,
+        method,
+    )
+    .await
+    {
+        tracing::warn!("Failed to record failed sign-in: {}", e);
+    }
+    if let Some(user) = user {
+        if let Err(e) = lockout::record_failure(state, user).await {
+            tracing::warn!("Failed to count failed sign-in: {}", e);
+        }
+    }
+    AppError::Unauthorized(message.to_string())
+}
+
+/// Token of a "this wasn't me" sign-out link
+#[derive(Debug, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+pub struct RevokeLinkParams {
+    pub token: String,
+}
+
+/// Page for a failed sign-out link, with a status matching the error
+fn revoke_link_error(err: AppError) -> (StatusCode, Html<String>) {
+    let (status, message) = match err {
+        AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+        AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+        err => {
+            tracing::error!("Sign-out link failed: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Something went wrong, please try again".to_string(),
+            )
+        }
+    };
+    (
+        status,
+        Html(approvals::render_page("Sign-out failed", &message, None)),
+    )
+}
+
+/// Where and when a session was signed in, for the sign-out pages
+fn describe_session(session: &session::Model) -> String {
+    format!(
+        "Signed in on {} from IP {} using {}.",
+        session.created_at.format("%Y-%m-%d %H:%M UTC"),
+        session.ip_address.as_deref().unwrap_or("unknown"),
+        session
+            .user_agent
+            .as_deref()
+            .unwrap_or("an unknown browser")
+    )
+}
+
+/// Confirmation page for a new-device alert's sign-out link
+///
+/// Public: the signed token stands in for a session. Nothing changes until
+/// the page's form is submitted.
+#[utoipa::path(
+    get,
+    path = "/auth/sessions/revoke-link",
+    tag = "Auth",
+    params(RevokeLinkParams),
+    responses(
+        (status = 200, description = "HTML confirmation page", content_type = "text/html"),
+        (status = 401, description = "Invalid or expired link"),
+        (status = 404, description = "The session no longer exists")
+    )
+)]
+async fn revoke_link_page(
+    State(state): State<AppState>,
+    Query(params): Query<RevokeLinkParams>,
+) -> Result<(StatusCode, Html<String>)> {
+    let db = state.get_db().await?;
+    Ok(match login_alerts::resolve_link(&db, &params.token).await {
+        Ok(session) if !login_alerts::is_active(&session) => (
+            StatusCode::OK,
+            Html(approvals::render_page(
+                "Already signed out",
+                &format!(
+                    "This session has already ended. {}",
+                    describe_session(&session)
+                ),
+                None,
+            )),
+        ),
+        Ok(session) => (
+            StatusCode::OK,
+            Html(approvals::render_page(
+                "Sign this session out?",
+                &format!(
+                    "{} If this wasn't you, sign it out and change your password.",
+                    describe_session(&session)
+                ),
+                Some((&params.token, "Sign out")),
+            )),
+        ),
+        Err(e) => revoke_link_error(e),
+    })
+}
+
+/// Sign a session out through a new-device alert's sign-out link
+///
+/// The outcome is recorded in the audit log as `session_revoked`.
+#[utoipa::path(
+    post,
+    path = "/auth/sessions/revoke-link",
+    tag = "Auth",
+    request_body(content = RevokeLinkParams, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "HTML result page", content_type = "text/html"),
+        (status = 401, description = "Invalid or expired link"),
+        (status = 404, description = "The session no longer exists")
+    )
+)]
+async fn revoke_link_confirm(
+    State(state): State<AppState>,
+    Form(params): Form<RevokeLinkParams>,
+) -> Result<(StatusCode, Html<String>)> {
+    let db = state.get_db().await?;
+    let (session, revoked) = match login_alerts::revoke_with_link(&db, &params.token).await {
+        Ok(outcome) => outcome,
+        Err(e) => return Ok(revoke_link_error(e)),
+    };
+    let owner = User::find_by_id(session.user_id).one(&db).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::SessionRevoked,
+            ResourceType::Session,
+            Some(session.id.clone()),
+            Some(session.user_id),
+            owner.map(|u| u.username),
+            Some(serde_json::json!({
+                "method": "revoke_link",
+                "outcome": if revoked { "revoked" } else { "already_signed_out" },
+                "session_ip_address": session.ip_address,
+                "session_user_agent": session.user_agent,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    if !revoked {
+        return Ok((
+            StatusCode::OK,
+            Html(approvals::render_page(
+                "Already signed out",
+                &format!(
+                    "This session has already ended. {}",
+                    describe_session(&session)
+                ),
+                None,
+            )),
+        ));
+    }
+    tracing::info!(
+        session_id = session.id,
+        "Session revoked through a new-device alert"
+    );
+    Ok((
+        StatusCode::OK,
+        Html(approvals::render_page(
+            "Session signed out",
+            "The session has been signed out. If you did not sign in, change your password now.",
+            None,
+        )),
+    ))
+}
+
 /// Check if any of a user's roles require 2FA
-async fn role_requires_2fa(db: &sea_orm::DatabaseConnection, user_id: i64) -> bool {
+pub(crate) async fn role_requires_2fa(db: &sea_orm::DatabaseConnection, user_id: i64) -> bool {
     let roles: Vec<role::Model> = Role::find()
         .inner_join(UserRole)
         .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
         .all(db)
         .await
         .unwrap_or_default();
@@ -507,7 +896,9 @@ async fn recover_with_code(
     headers: HeaderMap,
     Json(request): Json<RecoveryLoginRequest>,
 ) -> Result<Response> {
+    const METHOD: &str = "recovery_code";
     let db = state.get_db().await?;
+    lockout::check_ip(&db, ip_bans::client_ip(&headers).as_deref(), Utc::now()).await?;
 
     // Find user by username or email
     let found_user = User::find()
@@ -516,9 +907,21 @@ async fn recover_with_code(
                 .eq(&request.username)
                 .or(user::Column::Email.eq(&request.username)),
         )
+        .filter(user::Column::IsServiceAccount.eq(false))
         .one(&db)
-        .await?
-        .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
+        .await?;
+    let Some(found_user) = found_user else {
+        return Err(sign_in_failed(
+            &state,
+            &headers,
+            AuditAction::LoginFailed,
+            None,
+            &request.username,
+            METHOD,
+            "Invalid credentials",
+        )
+        .await);
+    };
 
     if !found_user.is_active {
         return Err(AppError::Unauthorized("Account is disabled".to_string()));
@@ -528,10 +931,20 @@ async fn recover_with_code(
             "Account is pending approval".to_string(),
         ));
     }
+    lockout::check_user(&found_user, Utc::now())?;
 
     // Verify password
     if !verify_password(&request.password, &found_user.hashed_password) {
-        return Err(AppError::Unauthorized("Invalid credentials".to_string()));
+        return Err(sign_in_failed(
+            &state,
+            &headers,
+            AuditAction::LoginFailed,
+            Some(&found_user),
+            &found_user.username,
+            METHOD,
+            "Invalid credentials",
+        )
+        .await);
     }
 
     // Recovery only applies when 2FA is enabled
@@ -557,9 +970,20 @@ async fn recover_with_code(
         .iter()
         .find(|rc| verify_recovery_code(&request.recovery_code, &rc.code_hash));
 
-    let matched_code = matching
-        .ok_or_else(|| AppError::Unauthorized("Invalid recovery code".to_string()))?
-        .clone();
+    let Some(matched_code) = matching.cloned() else {
+        return Err(sign_in_failed(
+            &state,
+            &headers,
+            AuditAction::TwoFactorFailed,
+            Some(&found_user),
+            &username,
+            METHOD,
+            "Invalid recovery code",
+        )
+        .await);
+    };
+
+    let found_user = lockout::reset(&db, &found_user).await?;
 
     // Mark the code as used
     let now = Utc::now();
@@ -604,12 +1028,9 @@ async fn recover_with_code(
         .and_then(|h| h.to_str().ok())
         .map(|s| s.chars().take(255).collect::<String>());
 
-    let ip_address = headers
-        .get("x-forwarded-for")
-        .or_else(|| headers.get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string());
+    let ip_address = ip_bans::client_ip(&headers);
 
+    let refresh = refresh_tokens::issue(&session_id);
     let session = session::ActiveModel {
         id: Set(session_id.clone()),
         user_id: Set(user_id),
@@ -619,10 +1040,23 @@ async fn recover_with_code(
         expires_at: Set(expires_at),
         last_accessed_at: Set(now),
         is_revoked: Set(false),
+        refresh_token_hash: Set(Some(refresh.hash)),
+        previous_refresh_token_hash: Set(None),
+        refreshed_at: Set(None),
     };
-    session.insert(&db).await?;
+    let session = session.insert(&db).await?;
+    audit_sign_in(
+        &state,
+        user_id,
+        &username,
+        serde_json::json!({ "method": METHOD }),
+        session.ip_address.clone(),
+        session.user_agent.clone(),
+    )
+    .await;
+    alert_new_device(&state, &username, &session).await;
 
-    let session_token = create_session_token(&session_id)?;
+    let session_token = create_session_token(&session_id, None)?;
 
     let existing_sessions = get_existing_sessions(&state, &headers).await;
     let slot = find_available_slot(&existing_sessions, user_id);
@@ -634,8 +1068,6 @@ async fn recover_with_code(
         session_slot: slot,
     });
 
-    let secure = CONFIG.auth.oauth2_issuer_url.starts_with("https://");
-
     tracing::info!(
         user_id = user_id,
         username = username,
@@ -644,41 +1076,19 @@ async fn recover_with_code(
         slot
     );
 
-    let mut response_headers = axum::http::HeaderMap::new();
-    response_headers.insert(
-        header::SET_COOKIE,
-        create_session_cookie_for_slot(slot, &session_token, secure),
-    );
-    response_headers.append(
-        header::SET_COOKIE,
-        create_active_session_cookie(slot, secure),
-    );
-    response_headers.append(
-        header::SET_COOKIE,
-        create_session_cookie(&session_token, secure),
-    );
-
+    let response_headers =
+        complete_session_login(&state, slot, &session_token, &refresh.token).await;
     Ok((response_headers, response).into_response())
 }
 
 /// Extract session token from cookie header
-fn extract_session_token(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn extract_session_token(headers: &HeaderMap) -> Option<String> {
     let cookies = headers.get(header::COOKIE)?;
     let cookie_str = cookies.to_str().ok()?;
 
-    // First try to find active slot
-    let mut active_slot: Option<usize> = None;
-    for cookie in cookie_str.split(';') {
-        let cookie = cookie.trim();
-        if let Some(value) = cookie.strip_prefix(&format!("{}=", ACTIVE_SESSION_COOKIE)) {
-            active_slot = value.parse().ok();
-            break;
-        }
-    }
-
-    // Look for indexed session cookie
-    if let Some(slot) = active_slot {
-        let prefix = format!("{}_{}=", SESSION_COOKIE_BASE, slot);
+    // Look for the indexed cookie of the active slot
+    if let Some(slot) = active_slot(headers) {
+        let prefix = format!("{}_{}=", session_cookie_name(), slot);
         for cookie in cookie_str.split(';') {
             let cookie = cookie.trim();
             if let Some(value) = cookie.strip_prefix(&prefix) {
@@ -690,7 +1100,7 @@ fn extract_session_token(headers: &HeaderMap) -> Option<String> {
     // Fallback to legacy cookie
     for cookie in cookie_str.split(';') {
         let cookie = cookie.trim();
-        if let Some(value) = cookie.strip_prefix(&format!("{}=", SESSION_COOKIE_NAME)) {
+        if let Some(value) = cookie.strip_prefix(&format!("{}=", session_cookie_name())) {
             return Some(value.to_string());
         }
     }
@@ -729,15 +1139,10 @@ async fn switch_session(
         return Err(AppError::NotFound("No session in that slot".to_string()));
     }
 
-    let secure = CONFIG.auth.oauth2_issuer_url.starts_with("https://");
-
     tracing::info!(slot = slot, "User switched to session slot {}", slot);
 
     Ok((
-        [(
-            header::SET_COOKIE,
-            create_active_session_cookie(slot, secure),
-        )],
+        [(header::SET_COOKIE, create_active_session_cookie(slot))],
         Json(serde_json::json!({"message": "Switched session", "slot": slot})),
     )
         .into_response())
@@ -759,20 +1164,7 @@ async fn list_accounts(
     let db = state.get_db().await?;
     let existing_sessions = get_existing_sessions(&state, &headers).await;
 
-    // Get active slot
-    let cookies = headers.get(header::COOKIE);
-    let active_slot: usize = cookies
-        .and_then(|c| c.to_str().ok())
-        .and_then(|s| {
-            for cookie in s.split(';') {
-                let cookie = cookie.trim();
-                if let Some(value) = cookie.strip_prefix(&format!("{}=", ACTIVE_SESSION_COOKIE)) {
-                    return value.parse().ok();
-                }
-            }
-            None
-        })
-        .unwrap_or(0);
+    let active_slot = active_slot(&headers).unwrap_or(0);
 
     let mut accounts = Vec::new();
     for (slot, user_id, username) in &existing_sessions {
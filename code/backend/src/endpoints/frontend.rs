@@ -1,21 +1,34 @@
 //! Frontend proxy handler
 //!
 //! Proxies unmatched requests to the frontend service.
-//! Also handles app proxying for authenticated users at /{app_name}/* paths.
+//! Also handles app proxying for authenticated users at /{app_name}/* paths,
+//! and at the root of `{subdomain}.{app_base_domain}` for apps configured with
+//! subdomain routing. Apps that cannot be reached get the maintenance page
+//! from [`crate::services::maintenance`] instead of a bare 502.
 //! Implements SPA routing: returns index.html for non-asset 404s.
 
 use axum::{
     body::Body,
     extract::{Request, State},
-    http::{header, Method, Response, StatusCode},
+    http::{header, HeaderMap, Method, Response, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response as AxumResponse},
 };
 
-use crate::config::CONFIG;
 use crate::error::{AppError, Result};
+use crate::middleware::auth::session_cookie_name;
+use crate::middleware::permissions::{AppsRestart, Permission};
+use crate::models::audit_log::{AuditAction, ResourceType};
 use crate::models::prelude::*;
 use crate::models::session;
+use crate::services::app_routing;
+use crate::services::deployment::DeploymentManager;
+use crate::services::idle_suspend;
+use crate::services::installed_apps;
+use crate::services::maintenance::{self, AppRunState, MAINTENANCE_HEADER};
+use crate::services::runtime_config;
 use crate::services::security::decode_session_token;
-use crate::state::AppState;
+use crate::state::{AppState, DbConn};
 use chrono::Utc;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 
@@ -64,7 +77,10 @@ fn extract_session_token(headers: &axum::http::HeaderMap) -> Option<String> {
 
     for cookie in cookie_str.split(';') {
         let cookie = cookie.trim();
-        if let Some(value) = cookie.strip_prefix("kubarr_session=") {
+        if let Some(value) = cookie
+            .strip_prefix(session_cookie_name())
+            .and_then(|rest| rest.strip_prefix('='))
+        {
             return Some(value.to_string());
         }
     }
@@ -242,20 +258,50 @@ fn rewrite_js_paths(text: &str, prefix: &str) -> String {
     rewritten.replace(&double_prefix, &single_prefix)
 }
 
-/// Create a redirect response to the app error page
-#[allow(clippy::unwrap_used)]
-fn redirect_to_app_error(app_name: &str, reason: &str, details: &str) -> Response<Body> {
-    let encoded_details = urlencoding::encode(details);
-    let redirect_url = format!(
-        "/app-error?app={}&reason={}&details={}",
-        app_name, reason, encoded_details
-    );
+/// Middleware routing requests for subdomain-routed apps
+///
+/// Runs in front of every Kubarr route: an app served on its own subdomain
+/// owns every path on that host, including `/api/*` and `/auth/*`.
+pub async fn dispatch_app_subdomains(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> AxumResponse {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string);
+    let Some(host) = host else {
+        return next.run(request).await;
+    };
+    let Ok(db) = state.get_db().await else {
+        return next.run(request).await;
+    };
+    let Ok(Some(app_name)) = app_routing::app_for_host(&db, &host).await else {
+        return next.run(request).await;
+    };
 
-    Response::builder()
-        .status(StatusCode::FOUND)
-        .header(header::LOCATION, redirect_url)
-        .body(Body::empty())
-        .unwrap()
+    let path = request.uri().path().to_string();
+    let query = request
+        .uri()
+        .query()
+        .map(|q| format!("?{}", q))
+        .unwrap_or_default();
+    let method = request.method().clone();
+    let headers = request.headers().clone();
+    proxy_subdomain_app(
+        &state,
+        &db,
+        &app_name,
+        &path,
+        &query,
+        method,
+        headers,
+        request.into_body(),
+    )
+    .await
+    .into_response()
 }
 
 /// Proxy requests to the frontend service with SPA routing support
@@ -324,6 +370,21 @@ pub async fn proxy_frontend(
                         has_permission
                     );
                     if has_permission {
+                        // Apps moved to a subdomain are only served there
+                        if let Ok(routing) = app_routing::get_routing(&db, app_name).await {
+                            if let Some(host) = routing.host {
+                                return Ok(redirect_to_subdomain(
+                                    &headers, &host, app_name, &path, &query,
+                                ));
+                            }
+                        }
+                        // Status polls and start requests from the maintenance page
+                        if headers.contains_key(MAINTENANCE_HEADER) {
+                            return app_maintenance_response(
+                                &state, &db, user_id, app_name, &method, &headers,
+                            )
+                            .await;
+                        }
                         // Try to proxy to the app
                         match get_app_target_url(&state, app_name, &path, &query).await {
                             Ok((base_url, has_base_path, target_url)) => {
@@ -335,7 +396,14 @@ pub async fn proxy_frontend(
                                 );
                                 let body = request.into_body();
                                 let proxy = &state.proxy;
-                                match proxy.proxy_http(&target_url, method, headers, body).await {
+                                let mut forwarded = headers.clone();
+                                if let Ok(prefix) = format!("/{}", app_name).parse() {
+                                    forwarded.insert("x-forwarded-prefix", prefix);
+                                }
+                                match proxy
+                                    .proxy_http(&target_url, method.clone(), forwarded, body)
+                                    .await
+                                {
                                     Ok(response) => {
                                         // Rewrite Location headers for redirects
                                         let response = rewrite_app_response(
@@ -351,6 +419,7 @@ pub async fn proxy_frontend(
                                         } else {
                                             response
                                         };
+                                        idle_suspend::note_access(&db, app_name).await;
                                         return Ok(response);
                                     }
                                     Err(e) => {
@@ -359,11 +428,10 @@ pub async fn proxy_frontend(
                                             app_name,
                                             e
                                         );
-                                        return Ok(redirect_to_app_error(
-                                            app_name,
-                                            "connection_failed",
-                                            &e.to_string(),
-                                        ));
+                                        return app_maintenance_response(
+                                            &state, &db, user_id, app_name, &method, &headers,
+                                        )
+                                        .await;
                                     }
                                 }
                             }
@@ -394,7 +462,12 @@ pub async fn proxy_frontend(
 
     // For static assets, proxy directly
     if is_static_asset(&path) {
-        let target_url = format!("{}{}{}", CONFIG.frontend_url, path, query);
+        let target_url = format!(
+            "{}{}{}",
+            runtime_config::current().frontend_url,
+            path,
+            query
+        );
         tracing::debug!("Proxying static asset: {}", target_url);
 
         return proxy
@@ -407,7 +480,12 @@ pub async fn proxy_frontend(
     }
 
     // For non-asset paths, try the path first, fall back to index.html on 404
-    let target_url = format!("{}{}{}", CONFIG.frontend_url, path, query);
+    let target_url = format!(
+        "{}{}{}",
+        runtime_config::current().frontend_url,
+        path,
+        query
+    );
     tracing::debug!("Proxying to frontend: {}", target_url);
 
     let response = proxy
@@ -420,7 +498,7 @@ pub async fn proxy_frontend(
 
     // If 404, serve index.html for SPA routing
     if response.status() == StatusCode::NOT_FOUND {
-        let index_url = format!("{}/index.html", CONFIG.frontend_url);
+        let index_url = format!("{}/index.html", runtime_config::current().frontend_url);
         tracing::debug!("SPA fallback to index.html for path: {}", path);
 
         return proxy
@@ -435,6 +513,224 @@ pub async fn proxy_frontend(
     Ok(response)
 }
 
+/// Redirect a path-routed request for `app_name` to the app's subdomain
+#[allow(clippy::unwrap_used)]
+fn redirect_to_subdomain(
+    headers: &axum::http::HeaderMap,
+    host: &str,
+    app_name: &str,
+    path: &str,
+    query: &str,
+) -> Response<Body> {
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("http");
+    let rest = path
+        .trim_start_matches('/')
+        .strip_prefix(app_name)
+        .unwrap_or("")
+        .trim_start_matches('/');
+    let location = format!("{}://{}/{}{}", scheme, host, rest, query);
+
+    Response::builder()
+        .status(StatusCode::TEMPORARY_REDIRECT)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Look up the user of the request's session cookie
+async fn session_user_id(db: &DbConn, headers: &axum::http::HeaderMap) -> Option<i64> {
+    let token = extract_session_token(headers)?;
+    let claims = decode_session_token(&token).ok()?;
+    Session::find()
+        .filter(session::Column::Id.eq(&claims.sid))
+        .filter(session::Column::IsRevoked.eq(false))
+        .filter(session::Column::ExpiresAt.gt(Utc::now()))
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|s| s.user_id)
+}
+
+/// Proxy a request on an app's own subdomain
+///
+/// The whole path belongs to the app, so neither the path nor the response
+/// body is rewritten. The session cookie must be scoped to a parent domain
+/// shared with `app_base_domain` for the browser to send it here.
+#[allow(clippy::too_many_arguments)]
+async fn proxy_subdomain_app(
+    state: &AppState,
+    db: &DbConn,
+    app_name: &str,
+    path: &str,
+    query: &str,
+    method: Method,
+    headers: axum::http::HeaderMap,
+    body: Body,
+) -> Result<Response<Body>> {
+    let user_id = session_user_id(db, &headers)
+        .await
+        .ok_or_else(|| AppError::Unauthorized(format!("Sign in to Kubarr to open {}", app_name)))?;
+    if !check_app_permission(state, user_id, app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "No access to app: {}",
+            app_name
+        )));
+    }
+
+    if headers.contains_key(MAINTENANCE_HEADER) {
+        return app_maintenance_response(state, db, user_id, app_name, &method, &headers).await;
+    }
+
+    let Ok((base_url, base_path)) = resolve_app_endpoint(state, app_name).await else {
+        return app_maintenance_response(state, db, user_id, app_name, &method, &headers).await;
+    };
+    let target_url = subdomain_target_url(&base_url, base_path.as_deref(), path, query);
+
+    let mut forwarded = headers.clone();
+    if let Some(host) = headers.get(header::HOST).cloned() {
+        forwarded.insert("x-forwarded-host", host);
+    }
+    match state
+        .proxy
+        .proxy_http(&target_url, method.clone(), forwarded, body)
+        .await
+    {
+        // Only internal URLs need rewriting; absolute paths are already correct
+        Ok(response) => {
+            idle_suspend::note_access(db, app_name).await;
+            Ok(rewrite_app_response(response, app_name, &base_url, true))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to connect to app {}: {}", app_name, e);
+            app_maintenance_response(state, db, user_id, app_name, &method, &headers).await
+        }
+    }
+}
+
+/// Answer a request for an app that could not be reached, or a status poll
+/// or start request from its maintenance page
+///
+/// Browsers get the maintenance page, other clients the app's
+/// [`maintenance::AppRunStatus`] as JSON, both with a 503. Requests carrying
+/// the maintenance header get the status with a 200. Apps suspended for being
+/// idle are started by any request from a user with access to them.
+async fn app_maintenance_response(
+    state: &AppState,
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Result<Response<Body>> {
+    use crate::services::access::get_user_permissions;
+
+    let k8s = state
+        .k8s_api()
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("Kubernetes not available".to_string()))?;
+    let can_restart = get_user_permissions(db, user_id)
+        .await
+        .iter()
+        .any(|p| p == AppsRestart::NAME);
+    let action = headers
+        .get(MAINTENANCE_HEADER)
+        .and_then(|v| v.to_str().ok());
+
+    let source = if installed_apps::is_suspended(db, app_name).await? {
+        Some("idle_wake")
+    } else if action == Some("start") && method == Method::POST {
+        if !can_restart {
+            return Err(AppError::Forbidden(format!(
+                "Missing permission to start {}",
+                app_name
+            )));
+        }
+        Some("maintenance_page")
+    } else {
+        None
+    };
+
+    if let Some(source) = source {
+        let catalog = state.catalog.read().await;
+        let helm = state.helm();
+        let started = DeploymentManager::with_db(k8s.as_ref(), helm.as_ref(), &catalog, db)
+            .start_app(app_name)
+            .await?;
+        state.endpoint_cache.invalidate(app_name).await;
+        if started > 0 {
+            let username = User::find_by_id(user_id)
+                .one(db)
+                .await
+                .ok()
+                .flatten()
+                .map(|u| u.username);
+            let _ = state
+                .audit
+                .log(
+                    AuditAction::AppStarted,
+                    ResourceType::App,
+                    Some(app_name.to_string()),
+                    Some(user_id),
+                    username,
+                    Some(serde_json::json!({ "source": source })),
+                    None,
+                    None,
+                    true,
+                    None,
+                )
+                .await;
+        }
+    }
+
+    let mut status = maintenance::app_run_status(k8s.as_ref(), app_name).await?;
+    status.can_start = can_restart && status.state == AppRunState::Stopped;
+    if action.is_some() {
+        return Ok(Json(status).into_response());
+    }
+
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    let mut response = if wants_html {
+        (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            maintenance::render_page(&status),
+        )
+            .into_response()
+    } else {
+        Json(status).into_response()
+    };
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, maintenance::RETRY_AFTER_SECS.into());
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        header::HeaderValue::from_static("no-store"),
+    );
+    Ok(response)
+}
+
+/// Target URL for a subdomain request; apps with a URL base still expect it
+/// in front of every path
+fn subdomain_target_url(
+    base_url: &str,
+    base_path: Option<&str>,
+    path: &str,
+    query: &str,
+) -> String {
+    let base_path = base_path
+        .map(|p| p.trim_end_matches('/'))
+        .filter(|p| !p.is_empty() && !path.starts_with(*p))
+        .unwrap_or("");
+    format!("{}{}{}{}", base_url, base_path, path, query)
+}
+
 /// Check if user has permission to access the app
 async fn check_app_permission(state: &AppState, user_id: i64, app_name: &str) -> bool {
     use crate::endpoints::extractors::get_user_permissions;
@@ -445,8 +741,16 @@ async fn check_app_permission(state: &AppState, user_id: i64, app_name: &str) ->
     };
     let permissions = get_user_permissions(&db, user_id).await;
 
-    // Check for app.* wildcard or specific app.{name} permission
-    permissions.contains(&"app.*".to_string()) || permissions.contains(&format!("app.{}", app_name))
+    // Check for app.* wildcard or specific app.{name} permission, that the
+    // app does not belong to another tenant, and that its schedules allow it
+    (permissions.contains(&"app.*".to_string())
+        || permissions.contains(&format!("app.{}", app_name)))
+        && crate::services::tenants::can_see_app(&db, user_id, app_name)
+            .await
+            .unwrap_or(false)
+        && crate::services::app_schedules::is_allowed(&db, user_id, app_name, Utc::now())
+            .await
+            .unwrap_or(false)
 }
 
 /// Helper function to proxy to frontend
@@ -462,7 +766,12 @@ async fn proxy_to_frontend(
 
     // For static assets, proxy directly
     if is_static_asset(&path) {
-        let target_url = format!("{}{}{}", CONFIG.frontend_url, path, query);
+        let target_url = format!(
+            "{}{}{}",
+            runtime_config::current().frontend_url,
+            path,
+            query
+        );
         tracing::debug!("Proxying static asset: {}", target_url);
 
         return proxy
@@ -475,7 +784,12 @@ async fn proxy_to_frontend(
     }
 
     // For non-asset paths, try the path first, fall back to index.html on 404
-    let target_url = format!("{}{}{}", CONFIG.frontend_url, path, query);
+    let target_url = format!(
+        "{}{}{}",
+        runtime_config::current().frontend_url,
+        path,
+        query
+    );
     tracing::debug!("Proxying to frontend: {}", target_url);
 
     let response = proxy
@@ -488,7 +802,7 @@ async fn proxy_to_frontend(
 
     // If 404, serve index.html for SPA routing
     if response.status() == StatusCode::NOT_FOUND {
-        let index_url = format!("{}/index.html", CONFIG.frontend_url);
+        let index_url = format!("{}/index.html", runtime_config::current().frontend_url);
         tracing::debug!("SPA fallback to index.html for path: {}", path);
 
         return proxy
@@ -503,6 +817,93 @@ async fn proxy_to_frontend(
     Ok(response)
 }
 
+/// Resolve an app's internal base URL and URL base, returning (base_url, base_path)
+async fn resolve_app_endpoint(
+    state: &AppState,
+    app_name: &str,
+) -> Result<(String, Option<String>)> {
+    // Check cache first
+    if let Some(cached) = state.endpoint_cache.get(app_name).await {
+        return Ok(cached);
+    }
+
+    // Get K8s client
+    let k8s_guard = state.k8s_client.read().await;
+    let k8s = k8s_guard
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailable("Kubernetes not available".to_string()))?;
+
+    // Get service endpoints for the app
+    // Apps are deployed in namespaces named after the app
+    let endpoints = k8s.get_service_endpoints(app_name, app_name).await?;
+
+    if endpoints.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "App {} not found or not ready",
+            app_name
+        )));
+    }
+
+    // Use the first endpoint
+    let endpoint = &endpoints[0];
+
+    // Build the internal URL
+    let base_url = format!(
+        "http://{}.{}.svc.cluster.local:{}",
+        endpoint.name, endpoint.namespace, endpoint.port
+    );
+
+    let base_path = endpoint.base_path.clone();
+
+    // Cache the endpoint
+    state
+        .endpoint_cache
+        .set(app_name, base_url.clone(), base_path.clone())
+        .await;
+
+    Ok((base_url, base_path))
+}
+
+/// Get the target URL for an app, returning (base_url, has_base_path, full_target_url)
+async fn get_app_target_url(
+    state: &AppState,
+    app_name: &str,
+    path: &str,
+    query: &str,
+) -> Result<(String, bool, String)> {
+    let (base_url, base_path) = resolve_app_endpoint(state, app_name).await?;
+    let has_base_path = base_path.is_some();
+
+    let target_url = if has_base_path {
+        // App has a URL base (e.g., Sonarr at /sonarr) - keep the full path
+        let trimmed = path.trim_start_matches('/');
+        if trimmed.is_empty() && query.is_empty() {
+            format!("{}/", base_url)
+        } else if trimmed.is_empty() {
+            format!("{}/?{}", base_url, query.trim_start_matches('?'))
+        } else {
+            format!("{}/{}{}", base_url, trimmed, query)
+        }
+    } else {
+        // App has no URL base (e.g., qBittorrent) - strip the app name prefix
+        let app_path = path
+            .trim_start_matches('/')
+            .strip_prefix(app_name)
+            .unwrap_or("")
+            .trim_start_matches('/');
+
+        if app_path.is_empty() && query.is_empty() {
+            format!("{}/", base_url)
+        } else if app_path.is_empty() {
+            format!("{}/?{}", base_url, query.trim_start_matches('?'))
+        } else {
+            format!("{}/{}{}", base_url, app_path, query)
+        }
+    };
+
+    Ok((base_url, has_base_path, target_url))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -902,84 +1303,47 @@ mod tests {
         let result = rewrite_js_paths(js, "/sonarr");
         assert!(result.contains(".p='/sonarr/static/'"));
     }
-}
 
-/// Get the target URL for an app, returning (base_url, has_base_path, full_target_url)
-async fn get_app_target_url(
-    state: &AppState,
-    app_name: &str,
-    path: &str,
-    query: &str,
-) -> Result<(String, bool, String)> {
-    // Check cache first
-    let (base_url, base_path) = if let Some(cached) = state.endpoint_cache.get(app_name).await {
-        cached
-    } else {
-        // Get K8s client
-        let k8s_guard = state.k8s_client.read().await;
-        let k8s = k8s_guard
-            .as_ref()
-            .ok_or_else(|| AppError::ServiceUnavailable("Kubernetes not available".to_string()))?;
-
-        // Get service endpoints for the app
-        // Apps are deployed in namespaces named after the app
-        let endpoints = k8s.get_service_endpoints(app_name, app_name).await?;
-
-        if endpoints.is_empty() {
-            return Err(AppError::NotFound(format!(
-                "App {} not found or not ready",
-                app_name
-            )));
-        }
-
-        // Use the first endpoint
-        let endpoint = &endpoints[0];
+    // -------------------------------------------------------------------------
+    // Subdomain routing tests
+    // -------------------------------------------------------------------------
 
-        // Build the internal URL
-        let base_url = format!(
-            "http://{}.{}.svc.cluster.local:{}",
-            endpoint.name, endpoint.namespace, endpoint.port
+    #[test]
+    fn test_subdomain_target_url_without_base_path() {
+        assert_eq!(
+            subdomain_target_url(
+                "http://plex.plex.svc:32400",
+                None,
+                "/web/index.html",
+                "?a=1"
+            ),
+            "http://plex.plex.svc:32400/web/index.html?a=1"
         );
+    }
 
-        let base_path = endpoint.base_path.clone();
-
-        // Cache the endpoint
-        state
-            .endpoint_cache
-            .set(app_name, base_url.clone(), base_path.clone())
-            .await;
-
-        (base_url, base_path)
-    };
-
-    let has_base_path = base_path.is_some();
-
-    let target_url = if has_base_path {
-        // App has a URL base (e.g., Sonarr at /sonarr) - keep the full path
-        let trimmed = path.trim_start_matches('/');
-        if trimmed.is_empty() && query.is_empty() {
-            format!("{}/", base_url)
-        } else if trimmed.is_empty() {
-            format!("{}/?{}", base_url, query.trim_start_matches('?'))
-        } else {
-            format!("{}/{}{}", base_url, trimmed, query)
-        }
-    } else {
-        // App has no URL base (e.g., qBittorrent) - strip the app name prefix
-        let app_path = path
-            .trim_start_matches('/')
-            .strip_prefix(app_name)
-            .unwrap_or("")
-            .trim_start_matches('/');
-
-        if app_path.is_empty() && query.is_empty() {
-            format!("{}/", base_url)
-        } else if app_path.is_empty() {
-            format!("{}/?{}", base_url, query.trim_start_matches('?'))
-        } else {
-            format!("{}/{}{}", base_url, app_path, query)
-        }
-    };
+    #[test]
+    fn test_subdomain_target_url_adds_missing_base_path() {
+        let base = "http://sonarr.sonarr.svc:8989";
+        assert_eq!(
+            subdomain_target_url(base, Some("/sonarr"), "/", ""),
+            "http://sonarr.sonarr.svc:8989/sonarr/"
+        );
+        assert_eq!(
+            subdomain_target_url(base, Some("/sonarr/"), "/sonarr/api", ""),
+            "http://sonarr.sonarr.svc:8989/sonarr/api"
+        );
+    }
 
-    Ok((base_url, has_base_path, target_url))
+    #[test]
+    fn test_redirect_to_subdomain_keeps_rest_of_path() {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("x-forwarded-proto", "https".parse().unwrap());
+        let response =
+            redirect_to_subdomain(&headers, "plex.apps.lan", "plex", "/plex/web/", "?x=1");
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://plex.apps.lan/web/?x=1"
+        );
+    }
 }
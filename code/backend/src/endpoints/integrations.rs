@@ -0,0 +1,535 @@
+//! App integration configuration endpoints
+//!
+//! Manages the credentials Kubarr uses to talk to installed apps' own APIs and
+//! serves data aggregated across apps (download queue, release calendar,
+//! media requests). Per-app native status is served from
+//! `/api/apps/{app_name}/native-status`.
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::extractors::{
+    get_user_app_access, get_user_permissions, user_has_app_access,
+};
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{AppsInstall, AppsView, Authorized, RequestsManage};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::services::integrations::requests::{
+    self, CreateMediaRequest, LinkAccountRequest, MediaAccountLinkResponse, MediaRequest,
+    MediaRequestCounts, MediaRequestList,
+};
+use crate::services::integrations::seerr::SeerrUser;
+use crate::services::integrations::{
+    self, DownloadQueue, IntegrationResponse, MediaCalendar, UpdateIntegrationRequest,
+};
+use crate::services::notification::NotificationAction;
+use crate::state::AppState;
+
+/// Create the integration routes
+pub fn integrations_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_integrations))
+        .route("/downloads", get(get_downloads))
+        .route("/calendar", get(get_calendar))
+        .route(
+            "/requests",
+            get(list_media_requests).post(create_media_request),
+        )
+        .route("/requests/count", get(get_media_request_counts))
+        .route("/requests/users", get(list_request_manager_users))
+        .route("/requests/links", get(list_account_links))
+        .route(
+            "/requests/links/{user_id}",
+            put(set_account_link).delete(delete_account_links),
+        )
+        .route(
+            "/requests/{request_id}/approve",
+            post(approve_media_request),
+        )
+        .route(
+            "/requests/{request_id}/decline",
+            post(decline_media_request),
+        )
+        .route(
+            "/apps/{app_name}",
+            get(get_integration)
+                .put(update_integration)
+                .delete(delete_integration),
+        )
+        .with_state(state)
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct IntegrationListResponse {
+    /// Apps that have an integration adapter
+    pub supported_apps: Vec<String>,
+    /// Integrations that have been configured
+    pub integrations: Vec<IntegrationResponse>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+pub struct CalendarQuery {
+    /// First day to include (YYYY-MM-DD, default today)
+    pub start: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD, default start + 7 days)
+    pub end: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+pub struct MediaRequestQuery {
+    /// Upstream filter: all, pending, approved, processing, available, ...
+    #[serde(default = "default_request_filter")]
+    pub filter: String,
+    /// Include every user's requests (requires requests.manage)
+    #[serde(default)]
+    pub all: bool,
+    #[serde(default = "default_request_take")]
+    pub take: u64,
+    #[serde(default)]
+    pub skip: u64,
+}
+
+fn default_request_filter() -> String {
+    "all".to_string()
+}
+
+fn default_request_take() -> u64 {
+    20
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// List supported adapters and configured integrations (credentials masked)
+#[utoipa::path(
+    get,
+    path = "/api/integrations",
+    tag = "Integrations",
+    responses((status = 200, body = IntegrationListResponse))
+)]
+async fn list_integrations(
+    State(state): State<AppState>,
+    _auth: Authorized<AppsView>,
+) -> Result<Json<IntegrationListResponse>> {
+    let db = state.get_db().await?;
+    let configured = integrations::list_integrations(&db).await?;
+    Ok(Json(IntegrationListResponse {
+        supported_apps: integrations::supported_apps()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        integrations: configured,
+    }))
+}
+
+/// Get the integration for an app (credentials masked)
+#[utoipa::path(
+    get,
+    path = "/api/integrations/apps/{app_name}",
+    tag = "Integrations",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = IntegrationResponse),
+        (status = 404, description = "No integration configured")
+    )
+)]
+async fn get_integration(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsView>,
+) -> Result<Json<IntegrationResponse>> {
+    let db = state.get_db().await?;
+    let integration = integrations::get_integration(&db, &app_name).await?;
+    Ok(Json(integration))
+}
+
+/// Create or update the integration for an app
+#[utoipa::path(
+    put,
+    path = "/api/integrations/apps/{app_name}",
+    tag = "Integrations",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = UpdateIntegrationRequest,
+    responses(
+        (status = 200, body = IntegrationResponse),
+        (status = 400, description = "No adapter for this app or invalid base URL")
+    )
+)]
+async fn update_integration(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsInstall>,
+    Json(req): Json<UpdateIntegrationRequest>,
+) -> Result<Json<IntegrationResponse>> {
+    let db = state.get_db().await?;
+    let integration = integrations::upsert_integration(&db, &app_name, req).await?;
+    state.integration_cache.invalidate_app(&app_name).await;
+    Ok(Json(integration))
+}
+
+/// Delete the integration for an app
+#[utoipa::path(
+    delete,
+    path = "/api/integrations/apps/{app_name}",
+    tag = "Integrations",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "No integration configured")
+    )
+)]
+async fn delete_integration(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<AppsInstall>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    integrations::delete_integration(&db, &app_name).await?;
+    state.integration_cache.invalidate_app(&app_name).await;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Get active downloads merged across all configured download clients
+///
+/// Only clients the caller has app access to are included.
+#[utoipa::path(
+    get,
+    path = "/api/integrations/downloads",
+    tag = "Integrations",
+    responses((status = 200, body = DownloadQueue))
+)]
+async fn get_downloads(
+    State(state): State<AppState>,
+    auth: Authorized<AppsView>,
+) -> Result<Json<DownloadQueue>> {
+    let db = state.get_db().await?;
+    let allowed_apps = get_user_app_access(&db, auth.user_id()).await;
+    let queue = integrations::downloads::aggregate_downloads(&state, &allowed_apps).await?;
+    Ok(Json(queue))
+}
+
+/// Get upcoming episode and movie releases from Sonarr and Radarr
+///
+/// Only sources the caller has app access to are included.
+#[utoipa::path(
+    get,
+    path = "/api/integrations/calendar",
+    tag = "Integrations",
+    params(CalendarQuery),
+    responses(
+        (status = 200, body = MediaCalendar),
+        (status = 400, description = "Invalid date range")
+    )
+)]
+async fn get_calendar(
+    State(state): State<AppState>,
+    auth: Authorized<AppsView>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Json<MediaCalendar>> {
+    let (start, end) = integrations::calendar::resolve_range(
+        query.start,
+        query.end,
+        chrono::Utc::now().date_naive(),
+    )?;
+
+    let db = state.get_db().await?;
+    let allowed_apps = get_user_app_access(&db, auth.user_id()).await;
+    let calendar =
+        integrations::calendar::aggregate_calendar(&state, &allowed_apps, start, end).await?;
+    Ok(Json(calendar))
+}
+
+// ============================================================================
+// Media Requests
+// ============================================================================
+
+/// Require app access to the active request manager
+async fn require_request_manager_access(state: &AppState, user_id: i64) -> Result<String> {
+    let db = state.get_db().await?;
+    let app_name = requests::active_manager_name(&db).await?;
+    if !user_has_app_access(&db, user_id, &app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "You don't have access to {}",
+            app_name
+        )));
+    }
+    Ok(app_name)
+}
+
+/// Record a media request event in the audit log and the requester's inbox
+async fn record_request_event(
+    state: &AppState,
+    action: AuditAction,
+    actor_id: i64,
+    actor_name: &str,
+    request: &MediaRequest,
+) {
+    let detail = requests::describe_request(request);
+
+    if let Err(e) = state
+        .audit
+        .log_success(
+            action.clone(),
+            ResourceType::App,
+            Some(request.app_name.clone()),
+            Some(actor_id),
+            Some(actor_name.to_string()),
+            Some(serde_json::json!({
+                "request_id": request.id,
+                "media_type": request.media_type,
+                "tmdb_id": request.tmdb_id,
+                "status": request.status,
+            })),
+            None,
+            None,
+        )
+        .await
+    {
+        tracing::warn!("Failed to write audit log for media request: {}", e);
+    }
+
+    let recipient = request
+        .requested_by
+        .as_ref()
+        .and_then(|r| r.user_id)
+        .or(Some(actor_id));
+    if let Err(e) = state
+        .notification
+        .notify_event_with_actions(
+            &action,
+            recipient,
+            Some(actor_name),
+            Some(&detail),
+            &[NotificationAction::OpenApp(request.app_name.clone())],
+        )
+        .await
+    {
+        tracing::warn!("Failed to send media request notification: {}", e);
+    }
+}
+
+/// List media requests from Jellyseerr/Overseerr
+///
+/// Returns the caller's own requests (via their linked account) unless
+/// `all=true` is passed by a user with `requests.manage`.
+#[utoipa::path(
+    get,
+    path = "/api/integrations/requests",
+    tag = "Integrations",
+    params(MediaRequestQuery),
+    responses(
+        (status = 200, body = MediaRequestList),
+        (status = 404, description = "No request manager configured")
+    )
+)]
+async fn list_media_requests(
+    State(state): State<AppState>,
+    auth: Authorized<AppsView>,
+    Query(query): Query<MediaRequestQuery>,
+) -> Result<Json<MediaRequestList>> {
+    require_request_manager_access(&state, auth.user_id()).await?;
+
+    let requested_by = if query.all {
+        let db = state.get_db().await?;
+        let permissions = get_user_permissions(&db, auth.user_id()).await;
+        if !permissions.iter().any(|p| p == "requests.manage") {
+            return Err(AppError::Forbidden(
+                "Permission denied: requests.manage required".to_string(),
+            ));
+        }
+        None
+    } else {
+        Some(auth.user_id())
+    };
+
+    let list = requests::list_requests(&state, requested_by, &query.filter, query.take, query.skip)
+        .await?;
+    Ok(Json(list))
+}
+
+/// Get request counts from Jellyseerr/Overseerr
+#[utoipa::path(
+    get,
+    path = "/api/integrations/requests/count",
+    tag = "Integrations",
+    responses(
+        (status = 200, body = MediaRequestCounts),
+        (status = 404, description = "No request manager configured")
+    )
+)]
+async fn get_media_request_counts(
+    State(state): State<AppState>,
+    auth: Authorized<AppsView>,
+) -> Result<Json<MediaRequestCounts>> {
+    require_request_manager_access(&state, auth.user_id()).await?;
+    let counts = requests::request_counts(&state).await?;
+    Ok(Json(counts))
+}
+
+/// Request a movie or series on behalf of the caller's linked account
+#[utoipa::path(
+    post,
+    path = "/api/integrations/requests",
+    tag = "Integrations",
+    request_body = CreateMediaRequest,
+    responses(
+        (status = 200, body = MediaRequest),
+        (status = 400, description = "Invalid request or account not linked")
+    )
+)]
+async fn create_media_request(
+    State(state): State<AppState>,
+    auth: Authorized<AppsView>,
+    Json(req): Json<CreateMediaRequest>,
+) -> Result<Json<MediaRequest>> {
+    require_request_manager_access(&state, auth.user_id()).await?;
+    let created = requests::create_request(&state, auth.user_id(), &req).await?;
+    record_request_event(
+        &state,
+        AuditAction::MediaRequested,
+        auth.user_id(),
+        &auth.user().username,
+        &created,
+    )
+    .await;
+    Ok(Json(created))
+}
+
+/// Approve a pending media request
+#[utoipa::path(
+    post,
+    path = "/api/integrations/requests/{request_id}/approve",
+    tag = "Integrations",
+    params(("request_id" = i64, Path, description = "Request ID in the request manager")),
+    responses((status = 200, body = MediaRequest))
+)]
+async fn approve_media_request(
+    State(state): State<AppState>,
+    Path(request_id): Path<i64>,
+    auth: Authorized<RequestsManage>,
+) -> Result<Json<MediaRequest>> {
+    let updated = requests::set_request_status(&state, request_id, true).await?;
+    record_request_event(
+        &state,
+        AuditAction::MediaRequestApproved,
+        auth.user_id(),
+        &auth.user().username,
+        &updated,
+    )
+    .await;
+    Ok(Json(updated))
+}
+
+/// Decline a pending media request
+#[utoipa::path(
+    post,
+    path = "/api/integrations/requests/{request_id}/decline",
+    tag = "Integrations",
+    params(("request_id" = i64, Path, description = "Request ID in the request manager")),
+    responses((status = 200, body = MediaRequest))
+)]
+async fn decline_media_request(
+    State(state): State<AppState>,
+    Path(request_id): Path<i64>,
+    auth: Authorized<RequestsManage>,
+) -> Result<Json<MediaRequest>> {
+    let updated = requests::set_request_status(&state, request_id, false).await?;
+    record_request_event(
+        &state,
+        AuditAction::MediaRequestDeclined,
+        auth.user_id(),
+        &auth.user().username,
+        &updated,
+    )
+    .await;
+    Ok(Json(updated))
+}
+
+/// List users in the request manager (for account linking)
+#[utoipa::path(
+    get,
+    path = "/api/integrations/requests/users",
+    tag = "Integrations",
+    responses((status = 200, body = Vec<SeerrUser>))
+)]
+async fn list_request_manager_users(
+    State(state): State<AppState>,
+    _auth: Authorized<RequestsManage>,
+) -> Result<Json<Vec<SeerrUser>>> {
+    let users = requests::list_external_users(&state).await?;
+    Ok(Json(users))
+}
+
+/// List links between Kubarr users and request manager accounts
+#[utoipa::path(
+    get,
+    path = "/api/integrations/requests/links",
+    tag = "Integrations",
+    responses((status = 200, body = Vec<MediaAccountLinkResponse>))
+)]
+async fn list_account_links(
+    State(state): State<AppState>,
+    _auth: Authorized<RequestsManage>,
+) -> Result<Json<Vec<MediaAccountLinkResponse>>> {
+    let db = state.get_db().await?;
+    let links = requests::list_links(&db).await?;
+    Ok(Json(links))
+}
+
+/// Link a Kubarr user to a request manager account
+#[utoipa::path(
+    put,
+    path = "/api/integrations/requests/links/{user_id}",
+    tag = "Integrations",
+    params(("user_id" = i64, Path, description = "Kubarr user ID")),
+    request_body = LinkAccountRequest,
+    responses(
+        (status = 200, body = MediaAccountLinkResponse),
+        (status = 409, description = "Account already linked to another user")
+    )
+)]
+async fn set_account_link(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<RequestsManage>,
+    Json(req): Json<LinkAccountRequest>,
+) -> Result<Json<MediaAccountLinkResponse>> {
+    let db = state.get_db().await?;
+    let app_name = match &req.app_name {
+        Some(app_name) => app_name.clone(),
+        None => requests::active_manager_name(&db).await?,
+    };
+    let link = requests::set_link(&db, user_id, &app_name, &req).await?;
+    Ok(Json(link))
+}
+
+/// Remove a user's request manager account links
+#[utoipa::path(
+    delete,
+    path = "/api/integrations/requests/links/{user_id}",
+    tag = "Integrations",
+    params(("user_id" = i64, Path, description = "Kubarr user ID")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "User has no linked accounts")
+    )
+)]
+async fn delete_account_links(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<RequestsManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    requests::delete_links(&db, user_id).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
@@ -1,18 +1,26 @@
 use axum::{
     extract::{Path, Query, State},
-    routing::get,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
     Json, Router,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{stream, Stream};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::Infallible;
 
 use crate::error::{AppError, Result};
-use crate::middleware::permissions::{Authorized, LogsView};
+use crate::middleware::permissions::{Authorized, LogsView, SettingsManage};
+use crate::models::log_archive;
+use crate::services::log_archive::{self as archive, ArchiveRunSummary};
+use crate::services::log_buffer;
+use crate::services::outbound;
+use crate::services::pod_logs::{self, FollowEvent};
 use crate::state::AppState;
 
 // VictoriaLogs service URL inside the cluster
-const VICTORIALOGS_URL: &str = "http://victorialogs.victorialogs.svc.cluster.local:9428";
+pub const VICTORIALOGS_URL: &str = "http://victorialogs.victorialogs.svc.cluster.local:9428";
 
 pub fn logs_routes(state: AppState) -> Router {
     Router::new()
@@ -26,8 +34,13 @@ pub fn logs_routes(state: AppState) -> Router {
         .route("/loki/labels", get(get_vlogs_labels))
         .route("/loki/label/{label}/values", get(get_vlogs_label_values))
         .route("/loki/query", get(query_vlogs))
+        // Log archive endpoints
+        .route("/archive", get(list_archives))
+        .route("/archive/query", get(query_archive))
+        .route("/archive/run", post(run_archive))
         // Pod logs endpoints
         .route("/raw/{pod_name}", get(get_raw_pod_logs))
+        .route("/pods/{pod_name}/stream", get(stream_pod_logs))
         .route("/app/{app_name}", get(get_app_logs))
         .route("/{pod_name}", get(get_pod_logs))
         .with_state(state)
@@ -96,6 +109,36 @@ pub struct VLogsEntry {
     pub level: Option<String>,
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ArchiveListQuery {
+    pub app: Option<String>,
+    /// First day, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Last day, `YYYY-MM-DD`
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ArchiveQueryParams {
+    pub app: String,
+    /// RFC 3339 start time (inclusive)
+    pub start: String,
+    /// RFC 3339 end time (exclusive)
+    pub end: String,
+    /// Only lines whose message contains this text
+    pub filter: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i32,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ArchiveQueryResponse {
+    pub streams: Vec<VLogsStream>,
+    pub total_entries: i32,
+    /// More lines matched than `limit`
+    pub truncated: bool,
+}
+
 #[utoipa::path(
     get,
     path = "/api/logs/{pod_name}",
@@ -141,6 +184,83 @@ async fn get_pod_logs(
     Ok(Json(entries))
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PodLogStreamQuery {
+    /// Namespace of the pod (default media)
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+    /// Container to follow (default the pod's only or default container)
+    pub container: Option<String>,
+    /// Minimum level: error, warn, info, debug or trace (default all)
+    pub level: Option<String>,
+    /// Number of recent lines to start with (default 100)
+    #[serde(default = "default_tail")]
+    pub tail: i32,
+}
+
+/// Follow a pod's logs live
+///
+/// The response is a `text/event-stream` of `log` events, starting with the
+/// last `tail` lines. A client that falls behind gets a `dropped` event with
+/// the number of lines it missed, e.g. `{"count": 120}`.
+#[utoipa::path(
+    get,
+    path = "/api/logs/pods/{pod_name}/stream",
+    tag = "Logs",
+    params(
+        ("pod_name" = String, Path, description = "Name of the pod"),
+        PodLogStreamQuery
+    ),
+    responses(
+        (status = 200, description = "Stream of log events", body = pod_logs::FollowedLine, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid level")
+    )
+)]
+async fn stream_pod_logs(
+    State(state): State<AppState>,
+    Path(pod_name): Path<String>,
+    Query(params): Query<PodLogStreamQuery>,
+    _auth: Authorized<LogsView>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    let level = match params.level.as_deref().filter(|l| !l.is_empty()) {
+        Some(level) => Some(log_buffer::parse_level(level).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid level '{}', expected error, warn, info, debug or trace",
+                level
+            ))
+        })?),
+        None => None,
+    };
+
+    let reader = {
+        let k8s = state.k8s_client.read().await;
+        let client = k8s
+            .as_ref()
+            .ok_or_else(|| AppError::Internal("Kubernetes client not available".to_string()))?;
+        client
+            .follow_pod_logs(
+                &pod_name,
+                &params.namespace,
+                params.container.as_deref(),
+                params.tail,
+            )
+            .await?
+    };
+
+    let events = pod_logs::follow(reader, pod_name, params.container, level);
+    let events = stream::unfold(events, |mut events| async move {
+        let event = match events.recv().await? {
+            FollowEvent::Line(line) => Event::default().event("log").json_data(&line),
+            FollowEvent::Dropped(count) => Event::default()
+                .event("dropped")
+                .json_data(serde_json::json!({ "count": count })),
+        };
+        Some((Ok(event.unwrap_or_default()), events))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 #[utoipa::path(
     get,
     path = "/api/logs/app/{app_name}",
@@ -247,7 +367,7 @@ async fn get_raw_pod_logs(
 )]
 /// Get all namespaces that have logs in VictoriaLogs
 async fn get_vlogs_namespaces(_auth: Authorized<LogsView>) -> Result<Json<Vec<String>>> {
-    let client = reqwest::Client::builder()
+    let client = outbound::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
@@ -301,7 +421,7 @@ async fn get_vlogs_namespaces(_auth: Authorized<LogsView>) -> Result<Json<Vec<St
 )]
 /// Get all available labels (field names) from VictoriaLogs
 async fn get_vlogs_labels(_auth: Authorized<LogsView>) -> Result<Json<Vec<String>>> {
-    let client = reqwest::Client::builder()
+    let client = outbound::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
@@ -360,7 +480,7 @@ async fn get_vlogs_label_values(
     Path(label): Path<String>,
     _auth: Authorized<LogsView>,
 ) -> Result<Json<Vec<String>>> {
-    let client = reqwest::Client::builder()
+    let client = outbound::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
@@ -415,7 +535,7 @@ async fn query_vlogs(
     Query(params): Query<VLogsQueryParams>,
     _auth: Authorized<LogsView>,
 ) -> Result<Json<VLogsQueryResponse>> {
-    let client = reqwest::Client::builder()
+    let client = outbound::client_builder()
         .timeout(std::time::Duration::from_secs(60))
         .build()
         .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
@@ -460,11 +580,116 @@ async fn query_vlogs(
         .await
         .map_err(|e| AppError::Internal(format!("Failed to read VictoriaLogs response: {}", e)))?;
 
-    // Parse JSON Lines response
+    Ok(Json(collect_streams(text.lines())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/logs/archive",
+    tag = "Logs",
+    params(
+        ("app" = Option<String>, Query, description = "Only archives of this app"),
+        ("from" = Option<String>, Query, description = "First day (YYYY-MM-DD)"),
+        ("to" = Option<String>, Query, description = "Last day (YYYY-MM-DD)"),
+    ),
+    responses(
+        (status = 200, description = "Archived app log days, newest first", body = Vec<log_archive::Model>)
+    )
+)]
+/// List the log archives
+async fn list_archives(
+    State(state): State<AppState>,
+    Query(params): Query<ArchiveListQuery>,
+    _auth: Authorized<LogsView>,
+) -> Result<Json<Vec<log_archive::Model>>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        archive::list(
+            &db,
+            params.app.as_deref(),
+            params.from.as_deref(),
+            params.to.as_deref(),
+        )
+        .await?,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/logs/archive/query",
+    tag = "Logs",
+    params(
+        ("app" = String, Query, description = "App whose logs to read"),
+        ("start" = String, Query, description = "RFC 3339 start time (inclusive)"),
+        ("end" = String, Query, description = "RFC 3339 end time (exclusive)"),
+        ("filter" = Option<String>, Query, description = "Only lines whose message contains this text"),
+        ("limit" = Option<i32>, Query, description = "Most lines to return (default 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Archived log lines, oldest first", body = ArchiveQueryResponse)
+    )
+)]
+/// Read an app's archived logs for a time range
+async fn query_archive(
+    State(state): State<AppState>,
+    Query(params): Query<ArchiveQueryParams>,
+    _auth: Authorized<LogsView>,
+) -> Result<Json<ArchiveQueryResponse>> {
+    let parse = |name: &str, value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|_| AppError::BadRequest(format!("{} must be an RFC 3339 time", name)))
+    };
+    let start = parse("start", &params.start)?;
+    let end = parse("end", &params.end)?;
+    let limit = params.limit.clamp(1, 10000) as usize;
+
+    let db = state.get_db().await?;
+    let store = archive::store_from_config()?;
+    let (lines, truncated) = archive::query(
+        &db,
+        store.as_ref(),
+        &params.app,
+        start,
+        end,
+        params.filter.as_deref().filter(|f| !f.is_empty()),
+        limit,
+    )
+    .await?;
+    let collected = collect_streams(lines.iter().map(String::as_str));
+    Ok(Json(ArchiveQueryResponse {
+        streams: collected.streams,
+        total_entries: collected.total_entries,
+        truncated,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/logs/archive/run",
+    tag = "Logs",
+    responses(
+        (status = 200, description = "Archive run summary", body = ArchiveRunSummary)
+    )
+)]
+/// Archive the complete days not archived yet and apply the retention now
+async fn run_archive(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+) -> Result<Json<ArchiveRunSummary>> {
+    let db = state.get_db().await?;
+    let store = archive::store_from_config()?;
+    Ok(Json(
+        archive::run(&db, store.as_ref(), VICTORIALOGS_URL, Utc::now()).await?,
+    ))
+}
+
+/// Group VictoriaLogs JSON lines into streams per namespace, pod and container
+pub(crate) fn collect_streams<'a>(lines: impl IntoIterator<Item = &'a str>) -> VLogsQueryResponse {
     let mut streams_map: HashMap<String, VLogsStream> = HashMap::new();
     let mut total_entries = 0;
 
-    for line in text.lines() {
+    for line in lines {
         if line.is_empty() {
             continue;
         }
@@ -549,10 +774,10 @@ async fn query_vlogs(
 
     let streams: Vec<VLogsStream> = streams_map.into_values().collect();
 
-    Ok(Json(VLogsQueryResponse {
+    VLogsQueryResponse {
         streams,
         total_entries,
-    }))
+    }
 }
 
 /// Convert Loki LogQL query to VictoriaLogs LogsQL
@@ -1,9 +1,11 @@
+pub mod alerts;
 pub mod apps;
 pub mod audit;
 pub mod auth;
 pub mod cloudflare;
 pub mod extractors;
 pub mod frontend;
+pub mod integrations;
 pub mod logs;
 pub mod monitoring;
 pub mod networking;
@@ -11,21 +13,38 @@ pub mod notifications;
 pub mod oauth;
 pub mod proxy;
 pub mod roles;
+pub mod security;
 pub mod settings;
 pub mod setup;
 pub mod storage;
+pub mod system;
+pub mod tenants;
 pub mod users;
 pub mod vpn;
+pub mod webdav;
 
-use axum::{extract::State, middleware as axum_middleware, response::Html, Router};
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::header,
+    middleware as axum_middleware,
+    response::{Html, IntoResponse},
+    Router,
+};
 use sea_orm::{ColumnTrait, EntityTrait, JoinType, QueryFilter, QuerySelect, RelationTrait};
 use utoipa::OpenApi;
 
 use crate::config::CONFIG;
-use crate::middleware::require_auth;
+use crate::middleware::{
+    compression_layer, limit_request_body, record_access_denials, record_metrics,
+    reject_banned_ips, request_id, require_auth, require_tenant_app, track_server_errors,
+    Authorized, MonitoringView,
+};
 use crate::models::prelude::*;
 use crate::models::{role, user_role};
+use crate::services::telemetry::{self, prometheus};
+use crate::services::tls;
 use crate::state::AppState;
+use system::SecurityAddon;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -39,12 +58,42 @@ use crate::state::AppState;
         health_check,
         health_check_detailed,
         get_version,
+        get_metrics,
+        // System
+        system::get_permissions_map,
+        system::get_system_logs,
+        system::download_system_logs,
+        system::get_diagnostic_errors,
+        system::create_diagnostic_bundle,
+        system::create_backup,
+        system::list_backups,
+        system::download_backup,
+        system::restore_backup,
+        system::get_update_status,
+        system::apply_update,
+        system::get_changelog,
+        system::get_report_subscription,
+        system::update_report_subscription,
+        system::preview_report,
+        system::send_report,
+        system::get_security_report,
+        system::list_registries,
+        system::create_registry,
+        system::update_registry,
+        system::delete_registry,
+        system::test_registry,
+        system::list_outbound_targets,
+        system::test_outbound_target,
+        system::list_certificates,
+        system::create_certificates,
+        system::delete_certificate,
         // Setup
         setup::check_setup_required,
         setup::get_setup_status,
         setup::initialize_setup,
         setup::generate_credentials,
         setup::validate_path,
+        setup::browse_setup_directory,
         setup::get_bootstrap_status,
         setup::start_bootstrap,
         setup::retry_bootstrap_component,
@@ -53,12 +102,17 @@ use crate::state::AppState;
         // Auth
         auth::login,
         auth::logout,
+        auth::refresh_session,
         auth::list_sessions,
         auth::revoke_session,
+        auth::revoke_link_page,
+        auth::revoke_link_confirm,
         auth::switch_session,
         auth::list_accounts,
+        auth::recover_with_code,
         // Users
         users::list_users,
+        users::create_user,
         users::get_current_user_info,
         users::update_own_profile,
         users::delete_own_account,
@@ -69,34 +123,106 @@ use crate::state::AppState;
         users::enable_2fa,
         users::disable_2fa,
         users::get_2fa_status,
+        users::get_2fa_qr,
+        users::get_recovery_code_count,
         users::list_pending_users,
         users::list_invites,
         users::create_invite,
+        users::create_invite_batch,
+        users::invite_share_page,
         users::delete_invite,
+        users::get_invite_qr,
         users::get_user,
         users::update_user,
         users::delete_user,
         users::approve_user,
+        users::approve_link_page,
+        users::approve_link_confirm,
         users::reject_user,
         users::admin_reset_password,
+        users::unlock_user,
+        users::get_my_quota,
+        users::get_user_quota,
+        users::set_user_quota,
+        users::delete_user_quota,
+        users::assign_user_role,
+        users::unassign_user_role,
+        users::list_client_certificates,
+        users::create_client_certificate,
+        users::delete_client_certificate,
+        users::list_service_accounts,
+        users::create_service_account,
+        users::get_service_account,
+        users::update_service_account,
+        users::delete_service_account,
+        users::list_service_account_tokens,
+        users::create_service_account_token,
+        users::delete_service_account_token,
+        users::list_account_links,
+        users::set_account_link,
+        users::delete_account_link,
+        users::discover_account_links,
+        users::provision_user,
+        users::deprovision_user,
+        users::get_user_schedules,
+        users::set_user_schedules,
+        users::list_schedule_overrides,
+        users::grant_schedule_override,
+        users::revoke_schedule_override,
         // Roles
         roles::list_roles,
         roles::create_role,
         roles::list_all_permissions,
+        roles::evaluate_permission,
+        roles::list_access_reviews,
+        roles::start_access_review,
+        roles::get_access_review,
+        roles::decide_access_review,
         roles::get_role,
         roles::update_role,
         roles::delete_role,
         roles::set_role_apps,
         roles::get_role_permissions,
         roles::set_role_permissions,
+        roles::get_role_quota,
+        roles::set_role_quota,
+        roles::delete_role_quota,
+        roles::get_role_schedules,
+        roles::set_role_schedules,
+        roles::get_media_profile,
+        roles::set_media_profile,
+        roles::delete_media_profile,
+        roles::sync_media_profile,
+        roles::get_provisioning_profile,
+        roles::set_provisioning_profile,
+        roles::delete_provisioning_profile,
+        // Tenants
+        tenants::list_tenants,
+        tenants::create_tenant,
+        tenants::get_tenant,
+        tenants::update_tenant,
+        tenants::delete_tenant,
+        tenants::list_members,
+        tenants::set_member,
+        tenants::remove_member,
         // Apps
         apps::list_catalog,
         apps::get_app_from_catalog,
+        apps::validate_catalog_entry,
+        apps::import_catalog_bundle,
         apps::get_app_icon,
         apps::list_installed_apps,
         apps::install_app,
+        apps::list_install_requests,
+        apps::create_install_request,
+        apps::approve_install_request,
+        apps::deny_install_request,
         apps::delete_app,
         apps::restart_app,
+        apps::upgrade_app,
+        apps::stop_app,
+        apps::start_app,
+        apps::exec_in_app,
         apps::list_categories,
         apps::get_apps_by_category,
         apps::check_app_health,
@@ -104,6 +230,17 @@ use crate::state::AppState;
         apps::get_app_status,
         apps::sync_charts,
         apps::log_app_access,
+        apps::update_app_metadata,
+        apps::update_idle_suspend,
+        apps::update_app_tenant,
+        apps::get_boot_order,
+        apps::update_boot_order,
+        apps::clone_app,
+        apps::get_app_routing,
+        apps::update_app_routing,
+        apps::get_app_values,
+        apps::update_app_values,
+        apps::get_native_status,
         // Monitoring
         monitoring::get_app_metrics,
         monitoring::get_cluster_metrics,
@@ -116,21 +253,76 @@ use crate::state::AppState;
         monitoring::get_app_health,
         monitoring::get_endpoints,
         monitoring::check_metrics_available,
+        monitoring::get_app_transcodes,
+        monitoring::get_app_stability,
+        monitoring::get_auth_analytics,
+        monitoring::get_uptime,
+        monitoring::create_uptime_monitor,
+        monitoring::get_uptime_monitor,
+        monitoring::update_uptime_monitor,
+        monitoring::delete_uptime_monitor,
+        monitoring::check_uptime_monitor,
+        monitoring::list_dashboards,
+        monitoring::create_dashboard,
+        monitoring::get_dashboard,
+        monitoring::update_dashboard,
+        monitoring::delete_dashboard,
+        monitoring::get_dashboard_data,
+        monitoring::get_node_sensors,
+        monitoring::get_energy,
+        monitoring::get_node_power,
+        monitoring::set_node_power,
+        monitoring::delete_node_power,
+        monitoring::get_anomalies,
+        monitoring::get_anomaly_sensitivity,
+        monitoring::set_anomaly_sensitivity,
+        monitoring::delete_anomaly_sensitivity,
+        monitoring::get_monitoring_alerts,
+        monitoring::list_log_alert_rules,
+        monitoring::create_log_alert_rule,
+        monitoring::get_log_alert_rule,
+        monitoring::update_log_alert_rule,
+        monitoring::delete_log_alert_rule,
+        monitoring::evaluate_log_alert_rule,
+        // Alerts
+        alerts::list_alerts,
+        alerts::get_alert,
+        alerts::acknowledge_alert,
+        alerts::silence_alert,
+        alerts::unsilence_alert,
+        alerts::assign_alert,
         // Networking
         networking::get_network_topology,
         networking::get_network_stats,
+        networking::get_network_usage,
+        networking::list_network_quotas,
+        networking::set_network_quota,
+        networking::delete_network_quota,
+        networking::get_wan_health,
         // Logs
         logs::get_pod_logs,
         logs::get_app_logs,
         logs::get_raw_pod_logs,
+        logs::stream_pod_logs,
         logs::get_vlogs_namespaces,
         logs::get_vlogs_labels,
         logs::get_vlogs_label_values,
         logs::query_vlogs,
+        logs::list_archives,
+        logs::query_archive,
+        logs::run_archive,
         // Audit
         audit::list_audit_logs,
         audit::audit_stats,
+        audit::audit_timeseries,
+        audit::verify_audit_log,
         audit::clear_audit_logs,
+        // Security
+        security::list_bans,
+        security::create_ban,
+        security::delete_ban,
+        security::export_bans,
+        security::failed_logins,
         // Notifications
         notifications::get_inbox,
         notifications::get_unread_count,
@@ -140,21 +332,56 @@ use crate::state::AppState;
         notifications::list_channels,
         notifications::get_channel,
         notifications::update_channel,
+        notifications::channel_status,
+        notifications::reload_channels,
         notifications::test_channel,
+        notifications::telegram_callback,
         notifications::list_events,
         notifications::update_event,
+        notifications::get_event_template,
+        notifications::update_event_template,
+        notifications::preview_event_template,
+        notifications::list_event_catalog,
+        notifications::bulk_update_events,
+        notifications::reset_events,
+        notifications::list_routes,
+        notifications::create_route,
+        notifications::get_route,
+        notifications::update_route,
+        notifications::delete_route,
         notifications::get_preferences,
         notifications::update_preference,
+        notifications::test_preference,
+        notifications::send_verification_code,
+        notifications::verify_code,
         notifications::list_logs,
         // Storage
+        storage::list_roots,
         storage::browse_directory,
         storage::get_storage_stats,
         storage::get_file_info,
         storage::create_directory,
         storage::delete_path,
         storage::download_file,
+        storage::preview_file,
+        storage::request_checksum,
+        storage::get_checksums,
+        storage::list_trash,
+        storage::restore_trash_item,
+        storage::purge_trash_item,
+        storage::empty_trash,
+        storage::get_shares,
+        storage::create_share,
+        storage::delete_share,
+        storage::set_share_password,
+        storage::clear_share_password,
+        storage::force_share_sync,
+        storage::list_homes,
+        storage::scan_homes,
+        storage::set_home_quota,
         // Settings
         settings::list_settings,
+        settings::settings_schema,
         settings::get_setting,
         settings::update_setting,
         // OAuth
@@ -186,6 +413,22 @@ use crate::state::AppState;
         cloudflare::delete_config,
         cloudflare::get_status,
         cloudflare::validate_token,
+        // Integrations
+        integrations::list_integrations,
+        integrations::get_integration,
+        integrations::update_integration,
+        integrations::delete_integration,
+        integrations::get_downloads,
+        integrations::get_calendar,
+        integrations::list_media_requests,
+        integrations::get_media_request_counts,
+        integrations::create_media_request,
+        integrations::approve_media_request,
+        integrations::decline_media_request,
+        integrations::list_request_manager_users,
+        integrations::list_account_links,
+        integrations::set_account_link,
+        integrations::delete_account_links,
     ),
     tags(
         (name = "Health", description = "Health check and version endpoints"),
@@ -193,18 +436,24 @@ use crate::state::AppState;
         (name = "Auth", description = "Authentication and session management"),
         (name = "Users", description = "User management, preferences, and 2FA"),
         (name = "Roles", description = "Role-based access control"),
+        (name = "Tenants", description = "Tenants and their members"),
         (name = "Apps", description = "Application catalog and deployment"),
         (name = "Monitoring", description = "Metrics and cluster monitoring"),
+        (name = "Alerts", description = "Alert state, silences, and assignment"),
         (name = "Networking", description = "Network topology and statistics"),
         (name = "Logs", description = "Log viewing and VictoriaLogs integration"),
         (name = "Audit", description = "Audit log management"),
+        (name = "Security", description = "IP bans and failed sign-ins"),
         (name = "Notifications", description = "Notification channels, events, and inbox"),
         (name = "Storage", description = "File storage management"),
         (name = "Settings", description = "System settings"),
         (name = "OAuth", description = "OAuth provider configuration and login"),
         (name = "VPN", description = "VPN provider and app VPN configuration"),
         (name = "Cloudflare", description = "Cloudflare Tunnel configuration"),
-    )
+        (name = "Integrations", description = "App API integrations and native status"),
+        (name = "System", description = "Kubarr's own access rules, logs, diagnostics, updates and reports"),
+    ),
+    modifiers(&SecurityAddon)
 )]
 pub struct ApiDoc;
 
@@ -223,12 +472,42 @@ pub fn create_router(state: AppState) -> Router {
     // Public routes (no auth required) - these already have state applied internally
     let public_routes = Router::new()
         .nest("/auth", auth::auth_routes(state.clone()))
-        .nest("/api/setup", setup::setup_routes(state.clone()));
+        .nest("/api/setup", setup::setup_routes(state.clone()))
+        .nest(
+            "/api/notifications/telegram",
+            notifications::telegram_webhook_routes(state.clone()),
+        )
+        .nest(
+            "/api/users/approve-link",
+            users::approve_link_routes(state.clone()),
+        )
+        .nest(
+            "/api/invites/share",
+            users::invite_share_routes(state.clone()),
+        )
+        .merge(webdav::webdav_routes(state.clone()))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            reject_banned_ips,
+        ));
 
-    // Protected API routes (auth required)
-    let protected_api_routes = Router::new().nest("/api", api_routes(state.clone())).layer(
-        axum_middleware::from_fn_with_state(state.clone(), require_auth),
-    );
+    // Protected API routes (auth required); denials are audited once the
+    // user is known
+    let protected_api_routes = Router::new()
+        .nest("/api", api_routes(state.clone()))
+        .merge(
+            Router::new()
+                .route("/metrics", axum::routing::get(get_metrics))
+                .with_state(state.clone()),
+        )
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            record_access_denials,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            require_auth,
+        ));
 
     // Note: App proxy routes (e.g., /qbittorrent/) are handled by the frontend fallback
     // which checks if the path is an installed app and proxies to it if authenticated
@@ -236,7 +515,7 @@ pub fn create_router(state: AppState) -> Router {
     // Frontend fallback router (unauthenticated)
     let fallback_router = Router::new()
         .fallback(frontend::proxy_frontend)
-        .with_state(state);
+        .with_state(state.clone());
 
     // OpenAPI spec and Swagger UI routes (no auth required)
     let openapi_routes = Router::new()
@@ -245,32 +524,72 @@ pub fn create_router(state: AppState) -> Router {
 
     // Merge all routes, with frontend proxy as fallback
     // The frontend fallback handles app proxying (e.g., /qbittorrent/) for authenticated users
+    // Subdomain-routed apps are dispatched on Host before any Kubarr route runs
+    // Body limits are enforced by `limit_request_body` per route, so the
+    // extractors' own 2 MB default is lifted
+    // Compression covers app subdomains too, whose proxy asks apps for
+    // uncompressed responses
+    // The request ID layer wraps everything so auth failures are tagged too,
+    // and 5xx responses are recorded with their request ID
+    // Request metrics are recorded per route, so they sit on the routes
+    // rather than around the router
     health_routes
         .merge(public_routes)
         .merge(openapi_routes)
         .merge(protected_api_routes)
         .merge(fallback_router)
+        .layer(axum_middleware::from_fn(record_metrics))
+        .layer(DefaultBodyLimit::disable())
+        .layer(axum_middleware::from_fn(limit_request_body))
+        .layer(axum_middleware::from_fn_with_state(
+            state,
+            frontend::dispatch_app_subdomains,
+        ))
+        .layer(compression_layer(&CONFIG.server))
+        .layer(axum_middleware::from_fn(track_server_errors))
+        .layer(axum_middleware::from_fn(request_id))
 }
 
 /// API routes under /api/* (protected by auth middleware)
 fn api_routes(state: AppState) -> Router {
+    // Routes naming an app hide the apps of other tenants
+    let tenant_apps = |router: Router| {
+        router.route_layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            require_tenant_app,
+        ))
+    };
     Router::new()
         .nest("/users", users::users_routes(state.clone()))
         .nest("/roles", roles::roles_routes(state.clone()))
+        .nest("/tenants", tenants::tenants_routes(state.clone()))
         .nest("/settings", settings::settings_routes(state.clone()))
-        .nest("/monitoring", monitoring::monitoring_routes(state.clone()))
-        .nest("/networking", networking::networking_routes(state.clone()))
-        .nest("/apps", apps::apps_routes(state.clone()))
+        .nest(
+            "/monitoring",
+            tenant_apps(monitoring::monitoring_routes(state.clone())),
+        )
+        .nest("/alerts", alerts::alerts_routes(state.clone()))
+        .nest(
+            "/networking",
+            tenant_apps(networking::networking_routes(state.clone())),
+        )
+        .nest("/apps", tenant_apps(apps::apps_routes(state.clone())))
         .nest("/storage", storage::storage_routes(state.clone()))
-        .nest("/logs", logs::logs_routes(state.clone()))
+        .nest("/logs", tenant_apps(logs::logs_routes(state.clone())))
         .nest("/audit", audit::audit_routes(state.clone()))
+        .nest("/security", security::security_routes(state.clone()))
         .nest(
             "/notifications",
             notifications::notifications_routes(state.clone()),
         )
         .nest("/oauth", oauth::oauth_routes(state.clone()))
-        .nest("/vpn", vpn::vpn_routes(state.clone()))
+        .nest("/vpn", tenant_apps(vpn::vpn_routes(state.clone())))
         .nest("/cloudflare", cloudflare::cloudflare_routes(state.clone()))
+        .nest(
+            "/integrations",
+            tenant_apps(integrations::integrations_routes(state.clone())),
+        )
+        .nest("/system", system::system_routes(state.clone()))
 }
 
 #[utoipa::path(get, path = "/api/health", tag = "Health", responses((status = 200, description = "OK")))]
@@ -292,11 +611,16 @@ async fn health_check_detailed(State(state): State<AppState>) -> axum::Json<serd
         Err(_) => false,
     };
 
-    axum::Json(serde_json::json!({
+    let mut health = serde_json::json!({
         "status": "ok",
         "ready": admin_exists,
         "setup_required": !admin_exists
-    }))
+    });
+    // Only present when the backend terminates TLS itself
+    if let Some(tls) = tls::status_report(chrono::Utc::now()) {
+        health["tls"] = tls;
+    }
+    axum::Json(health)
 }
 
 #[utoipa::path(get, path = "/api/system/version", tag = "Health", responses((status = 200, body = serde_json::Value)))]
@@ -306,17 +630,35 @@ async fn get_version() -> axum::Json<serde_json::Value> {
         "channel": CONFIG.channel,
         "commit_hash": CONFIG.commit_hash,
         "build_time": CONFIG.build_time,
+        "offline": CONFIG.outbound.offline,
         "rust_version": "1.83",
         "backend": "rust"
     }))
 }
 
+/// Kubarr's own metrics in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "Monitoring",
+    responses((status = 200, description = "Prometheus text format", content_type = "text/plain"))
+)]
+async fn get_metrics(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::CONTENT_TYPE)],
+        telemetry::scrape(&state).await,
+    )
+}
+
 /// Serve the OpenAPI JSON spec
 async fn openapi_json() -> axum::Json<utoipa::openapi::OpenApi> {
     axum::Json(ApiDoc::openapi())
 }
 
-/// Serve Swagger UI
+/// Serve Swagger UI from the bundled assets
 async fn swagger_ui() -> Html<&'static str> {
     Html(
         r#"<!DOCTYPE html>
@@ -324,11 +666,11 @@ async fn swagger_ui() -> Html<&'static str> {
 <head>
     <meta charset="UTF-8">
     <title>Kubarr API - Swagger UI</title>
-    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+    <link rel="stylesheet" href="/auth/assets/swagger-ui/swagger-ui.css">
 </head>
 <body>
     <div id="swagger-ui"></div>
-    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script src="/auth/assets/swagger-ui/swagger-ui-bundle.js"></script>
     <script>
         SwaggerUIBundle({
             url: '/api/openapi.json',
@@ -1,18 +1,39 @@
 use axum::{
     extract::{Path, Query, State},
-    routing::get,
+    routing::{get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::error::Result;
-use crate::middleware::permissions::{Authorized, MonitoringView};
+use crate::endpoints::extractors::{get_user_permissions, user_has_app_access};
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    Authorized, MonitoringManage, MonitoringView, SecurityManage,
+};
+use crate::services::anomaly::{self, AnomalyReport, AppSensitivity, SensitivitySettings};
+use crate::services::auth_analytics::{self, AuthAnalytics, AuthAnalyticsQuery};
+use crate::services::dashboards::{
+    self, CreateDashboardRequest, DashboardData, DashboardResponse, UpdateDashboardRequest,
+};
+use crate::services::energy::{self, EnergyReport, NodePowerInfo, NodePowerSettings};
+use crate::services::hardware_sensors::{self, NodeSensors};
+use crate::services::installed_apps;
+use crate::services::integrations::transcodes::{self, AppTranscodes, NodeLoad};
 use crate::services::k8s::{PodMetrics, PodStatus, ServiceEndpoint};
+use crate::services::log_alerts::{
+    self, CreateLogAlertRuleRequest, LogAlertRuleInfo, MonitoringAlert, UpdateLogAlertRuleRequest,
+};
+use crate::services::outbound;
+use crate::services::pod_stability::{self, AppStability};
+use crate::services::uptime::{
+    self, CreateUptimeMonitorRequest, UpdateUptimeMonitorRequest, UptimeMonitorDetail,
+    UptimeMonitorSummary, UptimeOverview,
+};
+use crate::services::victoriametrics::{
+    query_vm, query_vm_range, query_vm_scalar, victoriametrics_url,
+};
 use crate::state::AppState;
 
-/// VictoriaMetrics URL (inside cluster)
-const VICTORIAMETRICS_URL: &str = "http://victoriametrics.victoriametrics.svc.cluster.local:8428";
-
 /// Create monitoring routes
 pub fn monitoring_routes(state: AppState) -> Router {
     Router::new()
@@ -33,6 +54,50 @@ pub fn monitoring_routes(state: AppState) -> Router {
         .route("/health/{app_name}", get(get_app_health))
         .route("/endpoints/{app_name}", get(get_endpoints))
         .route("/metrics-available", get(check_metrics_available))
+        .route("/apps/{app_name}/transcodes", get(get_app_transcodes))
+        .route("/apps/{app_name}/stability", get(get_app_stability))
+        .route("/auth", get(get_auth_analytics))
+        .route("/uptime", get(get_uptime).post(create_uptime_monitor))
+        .route(
+            "/uptime/{id}",
+            get(get_uptime_monitor)
+                .put(update_uptime_monitor)
+                .delete(delete_uptime_monitor),
+        )
+        .route("/uptime/{id}/check", post(check_uptime_monitor))
+        .route("/dashboards", get(list_dashboards).post(create_dashboard))
+        .route(
+            "/dashboards/{id}",
+            get(get_dashboard)
+                .put(update_dashboard)
+                .delete(delete_dashboard),
+        )
+        .route("/dashboards/{id}/data", get(get_dashboard_data))
+        .route("/nodes/{name}/sensors", get(get_node_sensors))
+        .route("/energy", get(get_energy))
+        .route("/energy/nodes", get(get_node_power))
+        .route(
+            "/energy/nodes/{node_name}",
+            put(set_node_power).delete(delete_node_power),
+        )
+        .route("/alerts", get(get_monitoring_alerts))
+        .route(
+            "/log-alerts",
+            get(list_log_alert_rules).post(create_log_alert_rule),
+        )
+        .route(
+            "/log-alerts/{id}",
+            get(get_log_alert_rule)
+                .put(update_log_alert_rule)
+                .delete(delete_log_alert_rule),
+        )
+        .route("/log-alerts/{id}/evaluate", post(evaluate_log_alert_rule))
+        .route("/anomalies", get(get_anomalies))
+        .route("/anomalies/sensitivity", get(get_anomaly_sensitivity))
+        .route(
+            "/anomalies/sensitivity/{app_name}",
+            put(set_anomaly_sensitivity).delete(delete_anomaly_sensitivity),
+        )
         .with_state(state)
 }
 
@@ -145,64 +210,49 @@ pub struct ClusterMetricsHistory {
     pub container_series: Vec<TimeSeriesPoint>,
 }
 
-// ============================================================================
-// VictoriaMetrics Query Helpers
-// ============================================================================
+/// Load on the node running an app: CPU from cAdvisor, GPU from the
+/// NVIDIA DCGM exporter (if installed)
+async fn query_node_load(state: &AppState, app_name: &str) -> NodeLoad {
+    let app_cpu = query_vm_scalar(&format!(
+        r#"sum(rate(container_cpu_usage_seconds_total{{namespace="{}",container!="",container!="POD"}}[5m]))"#,
+        app_name
+    ))
+    .await
+    .unwrap_or(0.0);
 
-async fn query_vm(query: &str) -> Vec<serde_json::Value> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/api/v1/query", VICTORIAMETRICS_URL);
+    let node = if let Some(client) = state.k8s_client.read().await.as_ref() {
+        client
+            .get_pod_status(app_name, Some(app_name))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|p| p.node)
+    } else {
+        None
+    };
 
-    match client
-        .get(&url)
-        .query(&[("query", query)])
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await
-    {
-        Ok(resp) => {
-            if let Ok(data) = resp.json::<serde_json::Value>().await {
-                if data.get("status") == Some(&serde_json::json!("success")) {
-                    return data["data"]["result"]
-                        .as_array()
-                        .cloned()
-                        .unwrap_or_default();
-                }
-            }
-            Vec::new()
+    let (node_cpu, gpu) = match &node {
+        Some(node) => {
+            let node_cpu = query_vm_scalar(&format!(
+                r#"sum(rate(container_cpu_usage_seconds_total{{id="/",instance="{0}"}}[5m])) / sum(machine_cpu_cores{{instance="{0}"}}) * 100"#,
+                node
+            ))
+            .await;
+            let gpu = query_vm_scalar(&format!(
+                r#"avg(DCGM_FI_DEV_GPU_UTIL{{Hostname="{}"}})"#,
+                node
+            ))
+            .await;
+            (node_cpu, gpu)
         }
-        Err(_) => Vec::new(),
-    }
-}
-
-async fn query_vm_range(query: &str, start: f64, end: f64, step: &str) -> Vec<serde_json::Value> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/api/v1/query_range", VICTORIAMETRICS_URL);
+        None => (None, None),
+    };
 
-    match client
-        .get(&url)
-        .query(&[
-            ("query", query),
-            ("start", &start.to_string()),
-            ("end", &end.to_string()),
-            ("step", step),
-        ])
-        .timeout(std::time::Duration::from_secs(15))
-        .send()
-        .await
-    {
-        Ok(resp) => {
-            if let Ok(data) = resp.json::<serde_json::Value>().await {
-                if data.get("status") == Some(&serde_json::json!("success")) {
-                    return data["data"]["result"]
-                        .as_array()
-                        .cloned()
-                        .unwrap_or_default();
-                }
-            }
-            Vec::new()
-        }
-        Err(_) => Vec::new(),
+    NodeLoad {
+        node,
+        app_cpu_usage_cores: (app_cpu * 10000.0).round() / 10000.0,
+        node_cpu_usage_percent: node_cpu.map(|v| (v * 100.0).round() / 100.0),
+        gpu_utilization_percent: gpu.map(|v| (v * 100.0).round() / 100.0),
     }
 }
 
@@ -237,6 +287,13 @@ async fn get_app_metrics(
     allowed_namespaces.insert("victorialogs".to_string());
     allowed_namespaces.insert("fluent-bit".to_string());
     allowed_namespaces.insert("grafana".to_string());
+    // Recorded clones live in their own namespaces, e.g. `sonarr-4k`
+    if let Ok(db) = state.get_db().await {
+        if let Ok(clones) = installed_apps::list_clones(&db).await {
+            allowed_namespaces.extend(clones.into_iter().map(|clone| clone.app_name));
+        }
+    }
+    let is_allowed = |namespace: &str| allowed_namespaces.contains(namespace);
 
     // Query CPU usage by namespace
     let cpu_query = r#"sum by (namespace) (rate(container_cpu_usage_seconds_total{container!="",container!="POD"}[5m]))"#;
@@ -262,7 +319,7 @@ async fn get_app_metrics(
     for result in &cpu_results {
         if let Some(namespace) = result["metric"]["namespace"].as_str() {
             // Only include namespaces in our allowed list
-            if !allowed_namespaces.contains(namespace) {
+            if !is_allowed(namespace) {
                 continue;
             }
             if let Some(value) = result["value"][1].as_str() {
@@ -289,7 +346,7 @@ async fn get_app_metrics(
     for result in &memory_results {
         if let Some(namespace) = result["metric"]["namespace"].as_str() {
             // Only include namespaces in our allowed list
-            if !allowed_namespaces.contains(namespace) {
+            if !is_allowed(namespace) {
                 continue;
             }
             if let Some(value) = result["value"][1].as_str() {
@@ -322,7 +379,7 @@ async fn get_app_metrics(
     // Process network receive results
     for result in &network_rx_results {
         if let Some(namespace) = result["metric"]["namespace"].as_str() {
-            if !allowed_namespaces.contains(namespace) {
+            if !is_allowed(namespace) {
                 continue;
             }
             if let Some(value) = result["value"][1].as_str() {
@@ -337,7 +394,7 @@ async fn get_app_metrics(
     // Process network transmit results
     for result in &network_tx_results {
         if let Some(namespace) = result["metric"]["namespace"].as_str() {
-            if !allowed_namespaces.contains(namespace) {
+            if !is_allowed(namespace) {
                 continue;
             }
             if let Some(value) = result["value"][1].as_str() {
@@ -892,9 +949,9 @@ async fn get_app_detail_metrics(
     )
 )]
 async fn check_vm_available(_auth: Authorized<MonitoringView>) -> Result<Json<serde_json::Value>> {
-    let client = reqwest::Client::new();
+    let client = outbound::client();
     // VictoriaMetrics uses /health endpoint for health checks
-    let url = format!("{}/health", VICTORIAMETRICS_URL);
+    let url = format!("{}/health", victoriametrics_url());
 
     let available = client
         .get(&url)
@@ -1099,3 +1156,859 @@ async fn check_metrics_available(
         "message": if available { "Metrics server is available" } else { "Metrics server not found" }
     })))
 }
+
+/// Get active transcode sessions for a media server
+///
+/// Reports codec and hardware-acceleration status per session, the load on
+/// the node the server runs on, and capacity alerts when transcoding is
+/// saturating the CPU or GPU.
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/apps/{app_name}/transcodes",
+    tag = "Monitoring",
+    params(("app_name" = String, Path, description = "Media server app name")),
+    responses(
+        (status = 200, body = AppTranscodes),
+        (status = 403, description = "No access to this app"),
+        (status = 404, description = "App is not a supported media server")
+    )
+)]
+async fn get_app_transcodes(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<AppTranscodes>> {
+    if !transcodes::media_servers().contains(&app_name.as_str()) {
+        return Err(AppError::NotFound(format!(
+            "'{}' is not a supported media server",
+            app_name
+        )));
+    }
+
+    let db = state.get_db().await?;
+    if !user_has_app_access(&db, auth.user_id(), &app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "You don't have access to {}",
+            app_name
+        )));
+    }
+
+    let (sessions, node_load) = tokio::join!(
+        transcodes::fetch_sessions(&state, &app_name),
+        query_node_load(&state, &app_name)
+    );
+    Ok(Json(transcodes::build_report(
+        &app_name, sessions, node_load,
+    )))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct StabilityQuery {
+    /// Comma-separated windows such as "24h,7d,30d" (default; max 45d)
+    pub windows: Option<String>,
+}
+
+/// Get restart, OOM-kill and exit-code statistics for an app
+///
+/// Each window is compared against the preceding window of the same length,
+/// so a `trend` of "improving" means fewer restarts than before.
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/apps/{app_name}/stability",
+    tag = "Monitoring",
+    params(
+        ("app_name" = String, Path, description = "Application name"),
+        StabilityQuery,
+    ),
+    responses(
+        (status = 200, body = AppStability),
+        (status = 400, description = "Invalid window"),
+        (status = 403, description = "No access to this app")
+    )
+)]
+async fn get_app_stability(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    Query(query): Query<StabilityQuery>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<AppStability>> {
+    let windows = pod_stability::parse_windows(query.windows.as_deref())?;
+
+    let db = state.get_db().await?;
+    if !user_has_app_access(&db, auth.user_id(), &app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "You don't have access to {}",
+            app_name
+        )));
+    }
+
+    let containers = if let Some(client) = state.k8s_client.read().await.as_ref() {
+        client
+            .get_container_restarts(Some(&app_name))
+            .await
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(
+        pod_stability::app_stability(&db, &app_name, &windows, containers, chrono::Utc::now())
+            .await?,
+    ))
+}
+
+// ============================================================================
+// Uptime Monitors
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct UptimeQuery {
+    /// Statistics window in hours (default 24, max 720)
+    #[serde(default = "default_uptime_hours")]
+    pub hours: i64,
+}
+
+fn default_uptime_hours() -> i64 {
+    24
+}
+
+/// Maximum statistics window for uptime monitors (the retention period)
+const MAX_UPTIME_HOURS: i64 = 24 * 30;
+
+fn uptime_window(hours: i64) -> Result<i64> {
+    if hours <= 0 {
+        return Err(AppError::BadRequest(
+            "hours must be greater than zero".to_string(),
+        ));
+    }
+    Ok(hours.min(MAX_UPTIME_HOURS))
+}
+
+/// List uptime monitors with status, uptime and response-time percentiles
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/uptime",
+    tag = "Monitoring",
+    params(UptimeQuery),
+    responses(
+        (status = 200, body = UptimeOverview)
+    )
+)]
+async fn get_uptime(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Query(query): Query<UptimeQuery>,
+) -> Result<Json<UptimeOverview>> {
+    let db = state.get_db().await?;
+    let hours = uptime_window(query.hours)?;
+    Ok(Json(
+        uptime::overview(&db, hours, chrono::Utc::now()).await?,
+    ))
+}
+
+/// Sign-ins per hour or day, failure ratio, sign-in methods, top failing
+/// addresses and 2FA adoption
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/auth",
+    tag = "Monitoring",
+    params(AuthAnalyticsQuery),
+    responses(
+        (status = 200, body = AuthAnalytics),
+        (status = 400, description = "Invalid range")
+    )
+)]
+async fn get_auth_analytics(
+    State(state): State<AppState>,
+    _auth: Authorized<SecurityManage>,
+    Query(query): Query<AuthAnalyticsQuery>,
+) -> Result<Json<AuthAnalytics>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        auth_analytics::summarize(&db, query, chrono::Utc::now()).await?,
+    ))
+}
+
+/// Register a URL or TCP endpoint to monitor
+///
+/// The creator is notified when the monitor goes down or comes back up.
+#[utoipa::path(
+    post,
+    path = "/api/monitoring/uptime",
+    tag = "Monitoring",
+    request_body = CreateUptimeMonitorRequest,
+    responses(
+        (status = 200, body = UptimeMonitorDetail),
+        (status = 400, description = "Invalid monitor definition")
+    )
+)]
+async fn create_uptime_monitor(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Json(request): Json<CreateUptimeMonitorRequest>,
+) -> Result<Json<UptimeMonitorDetail>> {
+    let db = state.get_db().await?;
+    let monitor = uptime::create_monitor(&db, request, auth.user_id()).await?;
+    Ok(Json(
+        uptime::monitor_detail(&db, monitor.id, default_uptime_hours(), chrono::Utc::now()).await?,
+    ))
+}
+
+/// Get a monitor with its check history
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/uptime/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Monitor ID"), UptimeQuery),
+    responses(
+        (status = 200, body = UptimeMonitorDetail),
+        (status = 404, description = "Monitor not found")
+    )
+)]
+async fn get_uptime_monitor(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Path(id): Path<i64>,
+    Query(query): Query<UptimeQuery>,
+) -> Result<Json<UptimeMonitorDetail>> {
+    let db = state.get_db().await?;
+    let hours = uptime_window(query.hours)?;
+    Ok(Json(
+        uptime::monitor_detail(&db, id, hours, chrono::Utc::now()).await?,
+    ))
+}
+
+/// Update a monitor
+#[utoipa::path(
+    put,
+    path = "/api/monitoring/uptime/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Monitor ID")),
+    request_body = UpdateUptimeMonitorRequest,
+    responses(
+        (status = 200, body = UptimeMonitorDetail),
+        (status = 400, description = "Invalid monitor definition"),
+        (status = 404, description = "Monitor not found")
+    )
+)]
+async fn update_uptime_monitor(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateUptimeMonitorRequest>,
+) -> Result<Json<UptimeMonitorDetail>> {
+    let db = state.get_db().await?;
+    let monitor = uptime::update_monitor(&db, id, request).await?;
+    Ok(Json(
+        uptime::monitor_detail(&db, monitor.id, default_uptime_hours(), chrono::Utc::now()).await?,
+    ))
+}
+
+/// Delete a monitor and its history
+#[utoipa::path(
+    delete,
+    path = "/api/monitoring/uptime/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Monitor ID")),
+    responses(
+        (status = 200, description = "Monitor deleted"),
+        (status = 404, description = "Monitor not found")
+    )
+)]
+async fn delete_uptime_monitor(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    uptime::delete_monitor(&db, id).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Check a monitor immediately
+#[utoipa::path(
+    post,
+    path = "/api/monitoring/uptime/{id}/check",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Monitor ID")),
+    responses(
+        (status = 200, body = UptimeMonitorSummary),
+        (status = 404, description = "Monitor not found")
+    )
+)]
+async fn check_uptime_monitor(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<UptimeMonitorSummary>> {
+    let db = state.get_db().await?;
+    Ok(Json(uptime::check_now(&db, &state.notification, id).await?))
+}
+
+// ============================================================================
+// Dashboards
+// ============================================================================
+
+/// Whether the user may share dashboards and edit shared ones
+async fn can_manage_dashboards(state: &AppState, user_id: i64) -> Result<bool> {
+    let db = state.get_db().await?;
+    let permissions = get_user_permissions(&db, user_id).await;
+    Ok(permissions.iter().any(|p| p == "monitoring.manage"))
+}
+
+/// List the user's own dashboards and all shared dashboards
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/dashboards",
+    tag = "Monitoring",
+    responses(
+        (status = 200, body = Vec<DashboardResponse>)
+    )
+)]
+async fn list_dashboards(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<Vec<DashboardResponse>>> {
+    let can_manage = can_manage_dashboards(&state, auth.user_id()).await?;
+    let db = state.get_db().await?;
+    Ok(Json(
+        dashboards::list_dashboards(&db, auth.user_id(), can_manage).await?,
+    ))
+}
+
+/// Create a dashboard
+///
+/// Panel queries are validated against the restricted PromQL subset.
+/// Creating a shared dashboard requires monitoring.manage.
+#[utoipa::path(
+    post,
+    path = "/api/monitoring/dashboards",
+    tag = "Monitoring",
+    request_body = CreateDashboardRequest,
+    responses(
+        (status = 200, body = DashboardResponse),
+        (status = 400, description = "Invalid name, panel or query"),
+        (status = 403, description = "Sharing requires monitoring.manage")
+    )
+)]
+async fn create_dashboard(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringView>,
+    Json(request): Json<CreateDashboardRequest>,
+) -> Result<Json<DashboardResponse>> {
+    let can_manage = can_manage_dashboards(&state, auth.user_id()).await?;
+    let db = state.get_db().await?;
+    Ok(Json(
+        dashboards::create_dashboard(&db, request, auth.user_id(), can_manage).await?,
+    ))
+}
+
+/// Get a dashboard
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/dashboards/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Dashboard ID")),
+    responses(
+        (status = 200, body = DashboardResponse),
+        (status = 404, description = "Dashboard not found")
+    )
+)]
+async fn get_dashboard(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<DashboardResponse>> {
+    let can_manage = can_manage_dashboards(&state, auth.user_id()).await?;
+    let db = state.get_db().await?;
+    Ok(Json(
+        dashboards::get_dashboard(&db, id, auth.user_id(), can_manage).await?,
+    ))
+}
+
+/// Update a dashboard
+///
+/// Owners can edit their dashboards; shared dashboards can also be edited by
+/// users with monitoring.manage. Panels are replaced as a whole.
+#[utoipa::path(
+    put,
+    path = "/api/monitoring/dashboards/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Dashboard ID")),
+    request_body = UpdateDashboardRequest,
+    responses(
+        (status = 200, body = DashboardResponse),
+        (status = 400, description = "Invalid name, panel or query"),
+        (status = 403, description = "Not allowed to modify this dashboard"),
+        (status = 404, description = "Dashboard not found")
+    )
+)]
+async fn update_dashboard(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    auth: Authorized<MonitoringView>,
+    Json(request): Json<UpdateDashboardRequest>,
+) -> Result<Json<DashboardResponse>> {
+    let can_manage = can_manage_dashboards(&state, auth.user_id()).await?;
+    let db = state.get_db().await?;
+    Ok(Json(
+        dashboards::update_dashboard(&db, id, request, auth.user_id(), can_manage).await?,
+    ))
+}
+
+/// Delete a dashboard
+#[utoipa::path(
+    delete,
+    path = "/api/monitoring/dashboards/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Dashboard ID")),
+    responses(
+        (status = 200, description = "Dashboard deleted"),
+        (status = 403, description = "Not allowed to delete this dashboard"),
+        (status = 404, description = "Dashboard not found")
+    )
+)]
+async fn delete_dashboard(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<serde_json::Value>> {
+    let can_manage = can_manage_dashboards(&state, auth.user_id()).await?;
+    let db = state.get_db().await?;
+    dashboards::delete_dashboard(&db, id, auth.user_id(), can_manage).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// Run a dashboard's panel queries and return their time series
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/dashboards/{id}/data",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Dashboard ID")),
+    responses(
+        (status = 200, body = DashboardData),
+        (status = 404, description = "Dashboard not found")
+    )
+)]
+async fn get_dashboard_data(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    auth: Authorized<MonitoringView>,
+) -> Result<Json<DashboardData>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        dashboards::dashboard_data(&db, id, auth.user_id()).await?,
+    ))
+}
+
+// ============================================================================
+// Hardware Sensors
+// ============================================================================
+
+/// Get CPU, disk and other temperature sensors of a node
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/nodes/{name}/sensors",
+    tag = "Monitoring",
+    params(("name" = String, Path, description = "Kubernetes node name")),
+    responses(
+        (status = 200, body = NodeSensors)
+    )
+)]
+async fn get_node_sensors(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    _auth: Authorized<MonitoringView>,
+) -> Result<Json<NodeSensors>> {
+    let db = state.get_db().await?;
+    Ok(Json(hardware_sensors::node_sensors(&db, &name).await?))
+}
+
+// ============================================================================
+// Energy and Cost
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct EnergyQuery {
+    /// Rollup granularity: "month" (default) or "day"
+    #[serde(default = "default_energy_period")]
+    pub period: String,
+    /// Number of periods to include (default 12 months or 30 days)
+    pub count: Option<u32>,
+}
+
+fn default_energy_period() -> String {
+    "month".to_string()
+}
+
+/// Maximum number of periods returned by the energy endpoint
+const MAX_ENERGY_PERIODS: u32 = 366;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SetNodePowerRequest {
+    /// Power draw when idle
+    pub idle_watts: f64,
+    /// Power draw at full CPU load
+    pub max_watts: f64,
+}
+
+/// Get estimated energy use and cost per app from the daily/monthly rollups
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/energy",
+    tag = "Monitoring",
+    params(EnergyQuery),
+    responses(
+        (status = 200, body = EnergyReport),
+        (status = 400, description = "Invalid period")
+    )
+)]
+async fn get_energy(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Query(query): Query<EnergyQuery>,
+) -> Result<Json<EnergyReport>> {
+    let db = state.get_db().await?;
+    let default_count = if query.period == "day" { 30 } else { 12 };
+    let count = query
+        .count
+        .unwrap_or(default_count)
+        .clamp(1, MAX_ENERGY_PERIODS);
+
+    Ok(Json(
+        energy::energy_report(&db, &query.period, count, chrono::Utc::now()).await?,
+    ))
+}
+
+/// List node wattage profiles and the defaults for other nodes
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/energy/nodes",
+    tag = "Monitoring",
+    responses(
+        (status = 200, body = NodePowerSettings)
+    )
+)]
+async fn get_node_power(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+) -> Result<Json<NodePowerSettings>> {
+    let db = state.get_db().await?;
+    Ok(Json(energy::node_power_settings(&db).await?))
+}
+
+/// Set a node's idle and full-load wattage
+#[utoipa::path(
+    put,
+    path = "/api/monitoring/energy/nodes/{node_name}",
+    tag = "Monitoring",
+    params(("node_name" = String, Path, description = "Kubernetes node name")),
+    request_body = SetNodePowerRequest,
+    responses(
+        (status = 200, body = NodePowerInfo),
+        (status = 400, description = "Invalid wattage")
+    )
+)]
+async fn set_node_power(
+    State(state): State<AppState>,
+    Path(node_name): Path<String>,
+    _auth: Authorized<MonitoringManage>,
+    Json(request): Json<SetNodePowerRequest>,
+) -> Result<Json<NodePowerInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        energy::set_node_power(&db, &node_name, request.idle_watts, request.max_watts).await?,
+    ))
+}
+
+/// Remove a node's wattage profile
+#[utoipa::path(
+    delete,
+    path = "/api/monitoring/energy/nodes/{node_name}",
+    tag = "Monitoring",
+    params(("node_name" = String, Path, description = "Kubernetes node name")),
+    responses(
+        (status = 200, description = "Profile removed"),
+        (status = 404, description = "No profile for this node")
+    )
+)]
+async fn delete_node_power(
+    State(state): State<AppState>,
+    Path(node_name): Path<String>,
+    _auth: Authorized<MonitoringManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    energy::delete_node_power(&db, &node_name).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+// ============================================================================
+// Anomaly Detection
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct AnomalyQuery {
+    /// Look back this many hours (default 24, max 720)
+    #[serde(default = "default_anomaly_hours")]
+    pub hours: i64,
+    /// Only anomalies of this app
+    pub app: Option<String>,
+}
+
+fn default_anomaly_hours() -> i64 {
+    24
+}
+
+/// Anomalies are kept for 30 days
+const MAX_ANOMALY_HOURS: i64 = 24 * 30;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SetSensitivityRequest {
+    /// "off", "low", "medium" or "high"
+    pub sensitivity: String,
+}
+
+/// List recently detected metric anomalies
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/anomalies",
+    tag = "Monitoring",
+    params(AnomalyQuery),
+    responses(
+        (status = 200, body = AnomalyReport),
+        (status = 400, description = "Invalid window")
+    )
+)]
+async fn get_anomalies(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Query(query): Query<AnomalyQuery>,
+) -> Result<Json<AnomalyReport>> {
+    if query.hours <= 0 {
+        return Err(AppError::BadRequest(
+            "hours must be greater than zero".to_string(),
+        ));
+    }
+    let db = state.get_db().await?;
+    Ok(Json(
+        anomaly::list_anomalies(
+            &db,
+            query.hours.min(MAX_ANOMALY_HOURS),
+            query.app.as_deref(),
+            chrono::Utc::now(),
+        )
+        .await?,
+    ))
+}
+
+/// Get the default and per-app anomaly detection sensitivity
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/anomalies/sensitivity",
+    tag = "Monitoring",
+    responses(
+        (status = 200, body = SensitivitySettings)
+    )
+)]
+async fn get_anomaly_sensitivity(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+) -> Result<Json<SensitivitySettings>> {
+    let db = state.get_db().await?;
+    Ok(Json(anomaly::sensitivity_settings(&db).await?))
+}
+
+/// Override anomaly detection sensitivity for an app
+#[utoipa::path(
+    put,
+    path = "/api/monitoring/anomalies/sensitivity/{app_name}",
+    tag = "Monitoring",
+    params(("app_name" = String, Path, description = "Application name")),
+    request_body = SetSensitivityRequest,
+    responses(
+        (status = 200, body = AppSensitivity),
+        (status = 400, description = "Invalid sensitivity")
+    )
+)]
+async fn set_anomaly_sensitivity(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<MonitoringManage>,
+    Json(request): Json<SetSensitivityRequest>,
+) -> Result<Json<AppSensitivity>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        anomaly::set_sensitivity(&db, &app_name, &request.sensitivity).await?,
+    ))
+}
+
+/// Remove an app's sensitivity override
+#[utoipa::path(
+    delete,
+    path = "/api/monitoring/anomalies/sensitivity/{app_name}",
+    tag = "Monitoring",
+    params(("app_name" = String, Path, description = "Application name")),
+    responses(
+        (status = 200, description = "Override removed"),
+        (status = 404, description = "No override for this app")
+    )
+)]
+async fn delete_anomaly_sensitivity(
+    State(state): State<AppState>,
+    Path(app_name): Path<String>,
+    _auth: Authorized<MonitoringManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    anomaly::delete_sensitivity(&db, &app_name).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+// ============================================================================
+// Alerts
+// ============================================================================
+
+/// List active alerts: firing log alert rules and recent metric anomalies
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/alerts",
+    tag = "Monitoring",
+    responses(
+        (status = 200, body = Vec<MonitoringAlert>)
+    )
+)]
+async fn get_monitoring_alerts(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+) -> Result<Json<Vec<MonitoringAlert>>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        log_alerts::monitoring_alerts(&db, chrono::Utc::now()).await?,
+    ))
+}
+
+/// List log alert rules with their current state
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/log-alerts",
+    tag = "Monitoring",
+    responses(
+        (status = 200, body = Vec<LogAlertRuleInfo>)
+    )
+)]
+async fn list_log_alert_rules(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+) -> Result<Json<Vec<LogAlertRuleInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(log_alerts::list_rules(&db).await?))
+}
+
+/// Create a log alert rule
+///
+/// The creator is notified when the rule fires or resolves.
+#[utoipa::path(
+    post,
+    path = "/api/monitoring/log-alerts",
+    tag = "Monitoring",
+    request_body = CreateLogAlertRuleRequest,
+    responses(
+        (status = 200, body = LogAlertRuleInfo),
+        (status = 400, description = "Invalid rule definition")
+    )
+)]
+async fn create_log_alert_rule(
+    State(state): State<AppState>,
+    auth: Authorized<MonitoringManage>,
+    Json(request): Json<CreateLogAlertRuleRequest>,
+) -> Result<Json<LogAlertRuleInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        log_alerts::create_rule(&db, request, auth.user_id()).await?,
+    ))
+}
+
+/// Get a log alert rule
+#[utoipa::path(
+    get,
+    path = "/api/monitoring/log-alerts/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Rule ID")),
+    responses(
+        (status = 200, body = LogAlertRuleInfo),
+        (status = 404, description = "Rule not found")
+    )
+)]
+async fn get_log_alert_rule(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringView>,
+    Path(id): Path<i64>,
+) -> Result<Json<LogAlertRuleInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(log_alerts::get_rule(&db, id).await?))
+}
+
+/// Update a log alert rule
+#[utoipa::path(
+    put,
+    path = "/api/monitoring/log-alerts/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Rule ID")),
+    request_body = UpdateLogAlertRuleRequest,
+    responses(
+        (status = 200, body = LogAlertRuleInfo),
+        (status = 400, description = "Invalid rule definition"),
+        (status = 404, description = "Rule not found")
+    )
+)]
+async fn update_log_alert_rule(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateLogAlertRuleRequest>,
+) -> Result<Json<LogAlertRuleInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(log_alerts::update_rule(&db, id, request).await?))
+}
+
+/// Delete a log alert rule
+#[utoipa::path(
+    delete,
+    path = "/api/monitoring/log-alerts/{id}",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Rule ID")),
+    responses(
+        (status = 200, description = "Rule deleted"),
+        (status = 404, description = "Rule not found")
+    )
+)]
+async fn delete_log_alert_rule(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    log_alerts::delete_rule(&db, id).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// Evaluate a log alert rule immediately
+#[utoipa::path(
+    post,
+    path = "/api/monitoring/log-alerts/{id}/evaluate",
+    tag = "Monitoring",
+    params(("id" = i64, Path, description = "Rule ID")),
+    responses(
+        (status = 200, body = LogAlertRuleInfo),
+        (status = 404, description = "Rule not found")
+    )
+)]
+async fn evaluate_log_alert_rule(
+    State(state): State<AppState>,
+    _auth: Authorized<MonitoringManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<LogAlertRuleInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        log_alerts::evaluate_now(&db, &state.notification, id).await?,
+    ))
+}
@@ -1,20 +1,23 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Path, Query, State,
     },
     response::IntoResponse,
-    routing::get,
+    routing::{get, put},
     Json, Router,
 };
 use futures_util::{SinkExt, StreamExt};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tracing::debug;
 
 use crate::error::Result;
-use crate::middleware::permissions::{Authorized, NetworkingView};
+use crate::middleware::permissions::{Authorized, NetworkingManage, NetworkingView};
+use crate::models::network_quota;
 use crate::services::cadvisor::{aggregate_by_namespace, fetch_cadvisor_metrics};
+use crate::services::network_usage::{self, NetworkUsageReport, PERIOD_DAY, PERIOD_MONTH};
+use crate::services::wan_health::{self, WanHealthReport};
 use crate::state::AppState;
 
 /// Create networking routes
@@ -23,6 +26,13 @@ pub fn networking_routes(state: AppState) -> Router {
         .route("/topology", get(get_network_topology))
         .route("/stats", get(get_network_stats))
         .route("/ws", get(ws_handler))
+        .route("/usage", get(get_network_usage))
+        .route("/usage/quotas", get(list_network_quotas))
+        .route(
+            "/usage/quotas/{app_name}",
+            put(set_network_quota).delete(delete_network_quota),
+        )
+        .route("/wan-health", get(get_wan_health))
         .with_state(state)
 }
 
@@ -77,6 +87,59 @@ pub struct NetworkStats {
     pub pod_count: i32,
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct NetworkUsageQuery {
+    /// Rollup granularity: "day" (default) or "month"
+    #[serde(default = "default_usage_period")]
+    pub period: String,
+    /// Number of periods to include (default 30 days or 12 months)
+    pub count: Option<u32>,
+}
+
+fn default_usage_period() -> String {
+    PERIOD_DAY.to_string()
+}
+
+/// Maximum number of periods returned by the usage endpoint
+const MAX_USAGE_PERIODS: u32 = 366;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SetNetworkQuotaRequest {
+    /// Monthly transfer limit (ingress + egress) in bytes
+    pub monthly_limit_bytes: i64,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct NetworkQuotaResponse {
+    pub app_name: String,
+    pub monthly_limit_bytes: i64,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<network_quota::Model> for NetworkQuotaResponse {
+    fn from(model: network_quota::Model) -> Self {
+        Self {
+            app_name: model.app_name,
+            monthly_limit_bytes: model.monthly_limit_bytes,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct WanHealthQuery {
+    /// History window in hours (default 24, max 720)
+    #[serde(default = "default_wan_health_hours")]
+    pub hours: i64,
+}
+
+fn default_wan_health_hours() -> i64 {
+    24
+}
+
+/// Maximum history window for WAN health (the retention period)
+const MAX_WAN_HEALTH_HOURS: i64 = 24 * 30;
+
 // ============================================================================
 // Namespace filtering
 // ============================================================================
@@ -591,6 +654,125 @@ async fn get_network_stats(
     Ok(Json(stats))
 }
 
+// ============================================================================
+// Bandwidth Accounting
+// ============================================================================
+
+#[utoipa::path(
+    get,
+    path = "/api/networking/usage",
+    tag = "Networking",
+    params(NetworkUsageQuery),
+    responses(
+        (status = 200, description = "Per-app transfer totals, history and quota warnings", body = NetworkUsageReport),
+        (status = 400, description = "Invalid period")
+    )
+)]
+/// Get per-app ingress/egress usage from the daily/monthly rollups
+async fn get_network_usage(
+    State(state): State<AppState>,
+    _auth: Authorized<NetworkingView>,
+    Query(query): Query<NetworkUsageQuery>,
+) -> Result<Json<NetworkUsageReport>> {
+    let db = state.get_db().await?;
+    let default_count = if query.period == PERIOD_MONTH { 12 } else { 30 };
+    let count = query
+        .count
+        .unwrap_or(default_count)
+        .clamp(1, MAX_USAGE_PERIODS);
+
+    let report = network_usage::usage_report(&db, &query.period, count, chrono::Utc::now()).await?;
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/networking/usage/quotas",
+    tag = "Networking",
+    responses(
+        (status = 200, description = "Configured bandwidth quotas", body = Vec<NetworkQuotaResponse>)
+    )
+)]
+/// List per-app monthly bandwidth quotas
+async fn list_network_quotas(
+    State(state): State<AppState>,
+    _auth: Authorized<NetworkingView>,
+) -> Result<Json<Vec<NetworkQuotaResponse>>> {
+    let db = state.get_db().await?;
+    let quotas = network_usage::list_quotas(&db).await?;
+    Ok(Json(quotas.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/networking/usage/quotas/{app_name}",
+    tag = "Networking",
+    params(("app_name" = String, Path, description = "App name")),
+    request_body = SetNetworkQuotaRequest,
+    responses(
+        (status = 200, body = NetworkQuotaResponse),
+        (status = 400, description = "Invalid limit")
+    )
+)]
+/// Set an app's monthly bandwidth quota
+async fn set_network_quota(
+    State(state): State<AppState>,
+    _auth: Authorized<NetworkingManage>,
+    Path(app_name): Path<String>,
+    Json(request): Json<SetNetworkQuotaRequest>,
+) -> Result<Json<NetworkQuotaResponse>> {
+    let db = state.get_db().await?;
+    let quota = network_usage::set_quota(&db, &app_name, request.monthly_limit_bytes).await?;
+    Ok(Json(quota.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/networking/usage/quotas/{app_name}",
+    tag = "Networking",
+    params(("app_name" = String, Path, description = "App name")),
+    responses(
+        (status = 200, description = "Quota removed"),
+        (status = 404, description = "No quota configured")
+    )
+)]
+/// Remove an app's monthly bandwidth quota
+async fn delete_network_quota(
+    State(state): State<AppState>,
+    _auth: Authorized<NetworkingManage>,
+    Path(app_name): Path<String>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    network_usage::delete_quota(&db, &app_name).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+// ============================================================================
+// WAN Health
+// ============================================================================
+
+#[utoipa::path(
+    get,
+    path = "/api/networking/wan-health",
+    tag = "Networking",
+    params(WanHealthQuery),
+    responses(
+        (status = 200, description = "WAN status, per-target latency/packet loss, history and speed tests", body = WanHealthReport),
+        (status = 400, description = "Invalid window")
+    )
+)]
+/// Get WAN health from the scheduled latency, packet-loss and speed checks
+async fn get_wan_health(
+    State(state): State<AppState>,
+    _auth: Authorized<NetworkingView>,
+    Query(query): Query<WanHealthQuery>,
+) -> Result<Json<WanHealthReport>> {
+    let db = state.get_db().await?;
+    let hours = query.hours.min(MAX_WAN_HEALTH_HOURS);
+    let report = wan_health::wan_health_report(&db, hours, chrono::Utc::now()).await?;
+    Ok(Json(report))
+}
+
 // ============================================================================
 // WebSocket Handler
 // ============================================================================
@@ -9,14 +9,29 @@ use sea_orm::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::endpoints::extractors::get_user_permissions;
 use crate::error::{AppError, Result};
 use crate::middleware::permissions::{
-    AuditView, Authenticated, Authorized, SettingsManage, SettingsView,
+    AuditView, Authenticated, Authorized, MonitoringManage, Permission, SettingsManage,
+    SettingsView,
 };
 use crate::models::{
     notification_channel, notification_event, notification_log, user_notification_pref,
 };
-use crate::services::notification::ChannelType;
+use crate::services::alerts::{self, AcknowledgeAlertRequest};
+use crate::services::crypto;
+use crate::services::notification::events::{self, BulkUpdateEventsRequest, EventCatalogEntry};
+use crate::services::notification::routing::{
+    self, CreateNotificationRouteRequest, NotificationRouteInfo, UpdateNotificationRouteRequest,
+};
+use crate::services::notification::templates::{
+    self, EventTemplates, PreviewTemplateRequest, RenderedTemplate, SetTemplateRequest,
+};
+use crate::services::notification::{ChannelFilter, ChannelType, ProviderStatus, TelegramAction};
+use crate::services::runtime_config;
+use crate::services::security::{
+    generate_verification_code, hash_verification_code, verify_verification_code,
+};
 use crate::state::AppState;
 
 pub fn notifications_routes(state: AppState) -> Router {
@@ -29,15 +44,39 @@ pub fn notifications_routes(state: AppState) -> Router {
         .route("/inbox/{id}", delete(delete_notification))
         // Admin: Channel configuration
         .route("/channels", get(list_channels))
+        .route("/channels/status", get(channel_status))
+        .route("/channels/reload", post(reload_channels))
         .route("/channels/{channel_type}", get(get_channel))
         .route("/channels/{channel_type}", put(update_channel))
         .route("/channels/{channel_type}/test", post(test_channel))
         // Admin: Event settings
-        .route("/events", get(list_events))
+        .route("/events", get(list_events).put(bulk_update_events))
+        .route("/events/catalog", get(list_event_catalog))
+        .route("/events/reset", post(reset_events))
         .route("/events/{event_type}", put(update_event))
+        .route(
+            "/events/{event_type}/template",
+            get(get_event_template).put(update_event_template),
+        )
+        .route(
+            "/events/{event_type}/template/preview",
+            post(preview_event_template),
+        )
+        // Admin: Routing rules
+        .route("/routes", get(list_routes).post(create_route))
+        .route(
+            "/routes/{id}",
+            get(get_route).put(update_route).delete(delete_route),
+        )
         // User preferences
         .route("/preferences", get(get_preferences))
         .route("/preferences/{channel_type}", put(update_preference))
+        .route("/preferences/{channel_type}/test", post(test_preference))
+        .route(
+            "/preferences/{channel_type}/send-code",
+            post(send_verification_code),
+        )
+        .route("/preferences/{channel_type}/verify-code", post(verify_code))
         // Admin: Logs
         .route("/logs", get(list_logs))
         .with_state(state)
@@ -213,6 +252,8 @@ pub struct ChannelDto {
     pub channel_type: String,
     pub enabled: bool,
     pub config: serde_json::Value,
+    /// Events delivered through this channel for every user
+    pub filter: ChannelFilter,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -253,6 +294,7 @@ async fn list_channels(
                 channel_type: channel.channel_type.clone(),
                 enabled: channel.enabled,
                 config: masked_config,
+                filter: ChannelFilter::from_channel(channel),
                 created_at: channel.created_at.to_rfc3339(),
                 updated_at: channel.updated_at.to_rfc3339(),
             });
@@ -261,6 +303,7 @@ async fn list_channels(
                 channel_type: channel_type.as_str().to_string(),
                 enabled: false,
                 config: serde_json::json!({}),
+                filter: ChannelFilter::default(),
                 created_at: "".to_string(),
                 updated_at: "".to_string(),
             });
@@ -299,6 +342,7 @@ async fn get_channel(
             let masked_config = mask_sensitive_config(&config);
 
             Ok(Json(ChannelDto {
+                filter: ChannelFilter::from_channel(&ch),
                 channel_type: ch.channel_type,
                 enabled: ch.enabled,
                 config: masked_config,
@@ -310,6 +354,7 @@ async fn get_channel(
             channel_type,
             enabled: false,
             config: serde_json::json!({}),
+            filter: ChannelFilter::default(),
             created_at: "".to_string(),
             updated_at: "".to_string(),
         })),
@@ -320,6 +365,7 @@ async fn get_channel(
 pub struct UpdateChannelRequest {
     pub enabled: Option<bool>,
     pub config: Option<serde_json::Value>,
+    pub filter: Option<ChannelFilter>,
 }
 
 #[utoipa::path(
@@ -349,6 +395,10 @@ async fn update_channel(
         )));
     }
 
+    let filter = req
+        .filter
+        .map(|f| f.validate(&get_all_event_types()))
+        .transpose()?;
     let now = chrono::Utc::now();
 
     let existing = notification_channel::Entity::find()
@@ -363,17 +413,28 @@ async fn update_channel(
             active.enabled = Set(enabled);
         }
         if let Some(config) = req.config {
-            active.config = Set(serde_json::to_string(&config).unwrap_or_default());
+            active.config = Set(crypto::encrypt_config(&config)?);
+        }
+        if let Some(filter) = &filter {
+            let (include, exclude) = filter.event_type_columns();
+            active.min_severity = Set(filter.min_severity.clone());
+            active.include_event_types = Set(include);
+            active.exclude_event_types = Set(exclude);
         }
         active.updated_at = Set(now);
 
         active.update(&db).await?
     } else {
         let config = req.config.unwrap_or(serde_json::json!({}));
+        let filter = filter.unwrap_or_default();
+        let (include, exclude) = filter.event_type_columns();
         let new_channel = notification_channel::ActiveModel {
             channel_type: Set(channel_type.clone()),
             enabled: Set(req.enabled.unwrap_or(false)),
-            config: Set(serde_json::to_string(&config).unwrap_or_default()),
+            config: Set(crypto::encrypt_config(&config)?),
+            min_severity: Set(filter.min_severity),
+            include_event_types: Set(include),
+            exclude_event_types: Set(exclude),
             created_at: Set(now),
             updated_at: Set(now),
             ..Default::default()
@@ -381,9 +442,9 @@ async fn update_channel(
         new_channel.insert(&db).await?
     };
 
-    // Reinitialize providers
-    if let Err(e) = state.notification.init_providers().await {
-        tracing::warn!("Failed to reinitialize notification providers: {}", e);
+    // Reload providers so the new configuration applies immediately
+    if let Err(e) = state.notification.reload_providers().await {
+        tracing::warn!("Failed to reload notification providers: {}", e);
     }
 
     let config: serde_json::Value =
@@ -391,6 +452,7 @@ async fn update_channel(
     let masked_config = mask_sensitive_config(&config);
 
     Ok(Json(ChannelDto {
+        filter: ChannelFilter::from_channel(&channel),
         channel_type: channel.channel_type,
         enabled: channel.enabled,
         config: masked_config,
@@ -399,6 +461,36 @@ async fn update_channel(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/notifications/channels/status",
+    tag = "Notifications",
+    responses(
+        (status = 200, body = Vec<ProviderStatus>)
+    )
+)]
+async fn channel_status(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsView>,
+) -> Result<Json<Vec<ProviderStatus>>> {
+    Ok(Json(state.notification.provider_status().await))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/notifications/channels/reload",
+    tag = "Notifications",
+    responses(
+        (status = 200, body = Vec<ProviderStatus>)
+    )
+)]
+async fn reload_channels(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+) -> Result<Json<Vec<ProviderStatus>>> {
+    Ok(Json(state.notification.reload_providers().await?))
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct TestChannelRequest {
     pub destination: String,
@@ -484,9 +576,9 @@ async fn list_events(
             });
         } else {
             result.push(EventSettingDto {
+                severity: default_event_severity(&event_type).to_string(),
                 event_type,
                 enabled: false,
-                severity: "info".to_string(),
             });
         }
     }
@@ -539,7 +631,9 @@ async fn update_event(
         let new_event = notification_event::ActiveModel {
             event_type: Set(event_type.clone()),
             enabled: Set(req.enabled.unwrap_or(false)),
-            severity: Set(req.severity.unwrap_or_else(|| "info".to_string())),
+            severity: Set(req
+                .severity
+                .unwrap_or_else(|| default_event_severity(&event_type).to_string())),
             ..Default::default()
         };
         new_event.insert(&db).await?
@@ -552,6 +646,264 @@ async fn update_event(
     }))
 }
 
+/// Get the subject and body templates of an event on every channel
+///
+/// Also lists the variables templates can use.
+#[utoipa::path(
+    get,
+    path = "/api/notifications/events/{event_type}/template",
+    tag = "Notifications",
+    params(
+        ("event_type" = String, Path, description = "Event type"),
+    ),
+    responses(
+        (status = 200, body = EventTemplates),
+        (status = 404, description = "Unknown event type")
+    )
+)]
+async fn get_event_template(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsView>,
+    Path(event_type): Path<String>,
+) -> Result<Json<EventTemplates>> {
+    let db = state.get_db().await?;
+    Ok(Json(templates::get(&db, &event_type).await?))
+}
+
+/// Override the subject and body of an event on one channel
+///
+/// Leaving both empty removes the override, so the built-in text is used.
+#[utoipa::path(
+    put,
+    path = "/api/notifications/events/{event_type}/template",
+    tag = "Notifications",
+    params(
+        ("event_type" = String, Path, description = "Event type"),
+    ),
+    request_body = SetTemplateRequest,
+    responses(
+        (status = 200, body = EventTemplates),
+        (status = 400, description = "Invalid channel or unknown template variable"),
+        (status = 404, description = "Unknown event type")
+    )
+)]
+async fn update_event_template(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Path(event_type): Path<String>,
+    Json(req): Json<SetTemplateRequest>,
+) -> Result<Json<EventTemplates>> {
+    let db = state.get_db().await?;
+    Ok(Json(templates::set(&db, &event_type, req).await?))
+}
+
+/// Render an event's template with sample data
+#[utoipa::path(
+    post,
+    path = "/api/notifications/events/{event_type}/template/preview",
+    tag = "Notifications",
+    params(
+        ("event_type" = String, Path, description = "Event type"),
+    ),
+    request_body = PreviewTemplateRequest,
+    responses(
+        (status = 200, body = RenderedTemplate),
+        (status = 400, description = "Invalid channel or unknown template variable"),
+        (status = 404, description = "Unknown event type")
+    )
+)]
+async fn preview_event_template(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Path(event_type): Path<String>,
+    Json(req): Json<PreviewTemplateRequest>,
+) -> Result<Json<RenderedTemplate>> {
+    let db = state.get_db().await?;
+    Ok(Json(templates::preview(&db, &event_type, req).await?))
+}
+
+/// List every known action type with its event configuration
+///
+/// Covers all audit actions, not only the default catalog, so event types
+/// added to the code can be configured before they are enabled by default.
+#[utoipa::path(
+    get,
+    path = "/api/notifications/events/catalog",
+    tag = "Notifications",
+    responses(
+        (status = 200, body = Vec<EventCatalogEntry>)
+    )
+)]
+async fn list_event_catalog(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsView>,
+) -> Result<Json<Vec<EventCatalogEntry>>> {
+    let db = state.get_db().await?;
+    Ok(Json(events::list_catalog(&db).await?))
+}
+
+/// Create or update several event configs at once
+#[utoipa::path(
+    put,
+    path = "/api/notifications/events",
+    tag = "Notifications",
+    request_body = BulkUpdateEventsRequest,
+    responses(
+        (status = 200, body = Vec<EventSettingDto>),
+        (status = 400, description = "Unknown event type or invalid severity")
+    )
+)]
+async fn bulk_update_events(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Json(req): Json<BulkUpdateEventsRequest>,
+) -> Result<Json<Vec<EventSettingDto>>> {
+    let db = state.get_db().await?;
+    let saved = events::bulk_update(&db, req.events).await?;
+    Ok(Json(
+        saved
+            .into_iter()
+            .map(|event| EventSettingDto {
+                event_type: event.event_type,
+                enabled: event.enabled,
+                severity: event.severity,
+            })
+            .collect(),
+    ))
+}
+
+/// Reset event configs to the default catalog with every event disabled
+#[utoipa::path(
+    post,
+    path = "/api/notifications/events/reset",
+    tag = "Notifications",
+    responses(
+        (status = 200, body = Vec<EventCatalogEntry>)
+    )
+)]
+async fn reset_events(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+) -> Result<Json<Vec<EventCatalogEntry>>> {
+    let db = state.get_db().await?;
+    events::reset_catalog(&db).await?;
+    Ok(Json(events::list_catalog(&db).await?))
+}
+
+// ============================================================================
+// Routing Rules
+// ============================================================================
+
+#[utoipa::path(
+    get,
+    path = "/api/notifications/routes",
+    tag = "Notifications",
+    responses(
+        (status = 200, body = Vec<NotificationRouteInfo>)
+    )
+)]
+async fn list_routes(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsView>,
+) -> Result<Json<Vec<NotificationRouteInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(routing::list_routes(&db).await?))
+}
+
+/// Create a routing rule
+///
+/// When any rule matches an event, the matching rules replace the default
+/// delivery (in-app plus the user's own channels) for that event.
+#[utoipa::path(
+    post,
+    path = "/api/notifications/routes",
+    tag = "Notifications",
+    request_body = CreateNotificationRouteRequest,
+    responses(
+        (status = 200, body = NotificationRouteInfo),
+        (status = 400, description = "Invalid routing rule")
+    )
+)]
+async fn create_route(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Json(req): Json<CreateNotificationRouteRequest>,
+) -> Result<Json<NotificationRouteInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        routing::create_route(&db, req, &get_all_event_types()).await?,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/notifications/routes/{id}",
+    tag = "Notifications",
+    params(
+        ("id" = i64, Path, description = "Route ID"),
+    ),
+    responses(
+        (status = 200, body = NotificationRouteInfo),
+        (status = 404, description = "Route not found")
+    )
+)]
+async fn get_route(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsView>,
+    Path(id): Path<i64>,
+) -> Result<Json<NotificationRouteInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(routing::get_route(&db, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/notifications/routes/{id}",
+    tag = "Notifications",
+    params(
+        ("id" = i64, Path, description = "Route ID"),
+    ),
+    request_body = UpdateNotificationRouteRequest,
+    responses(
+        (status = 200, body = NotificationRouteInfo),
+        (status = 400, description = "Invalid routing rule"),
+        (status = 404, description = "Route not found")
+    )
+)]
+async fn update_route(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateNotificationRouteRequest>,
+) -> Result<Json<NotificationRouteInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        routing::update_route(&db, id, req, &get_all_event_types()).await?,
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/notifications/routes/{id}",
+    tag = "Notifications",
+    params(
+        ("id" = i64, Path, description = "Route ID"),
+    ),
+    responses(
+        (status = 200, description = "Route deleted"),
+        (status = 404, description = "Route not found")
+    )
+)]
+async fn delete_route(
+    State(state): State<AppState>,
+    _auth: Authorized<SettingsManage>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    routing::delete_route(&db, id).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
 // ============================================================================
 // User Preferences
 // ============================================================================
@@ -562,6 +914,23 @@ pub struct UserPrefDto {
     pub enabled: bool,
     pub destination: Option<String>,
     pub verified: bool,
+    /// A verification code was sent and has not expired yet
+    pub verification_pending: bool,
+    /// Events this user receives through the channel
+    pub filter: ChannelFilter,
+}
+
+impl UserPrefDto {
+    fn from_pref(pref: user_notification_pref::Model) -> Self {
+        Self {
+            filter: ChannelFilter::from_pref(&pref),
+            verification_pending: verification_pending(&pref, chrono::Utc::now()),
+            channel_type: pref.channel_type,
+            enabled: pref.enabled,
+            destination: pref.destination.map(|d| mask_destination(&d)),
+            verified: pref.verified,
+        }
+    }
 }
 
 #[utoipa::path(
@@ -590,18 +959,15 @@ async fn get_preferences(
             .find(|p| p.channel_type == channel_type.as_str());
 
         if let Some(pref) = existing {
-            result.push(UserPrefDto {
-                channel_type: pref.channel_type.clone(),
-                enabled: pref.enabled,
-                destination: pref.destination.clone().map(|d| mask_destination(&d)),
-                verified: pref.verified,
-            });
+            result.push(UserPrefDto::from_pref(pref.clone()));
         } else {
             result.push(UserPrefDto {
                 channel_type: channel_type.as_str().to_string(),
                 enabled: false,
                 destination: None,
                 verified: false,
+                verification_pending: false,
+                filter: ChannelFilter::default(),
             });
         }
     }
@@ -613,6 +979,7 @@ async fn get_preferences(
 pub struct UpdatePrefRequest {
     pub enabled: Option<bool>,
     pub destination: Option<String>,
+    pub filter: Option<ChannelFilter>,
 }
 
 #[utoipa::path(
@@ -641,6 +1008,10 @@ async fn update_preference(
         )));
     }
 
+    let filter = req
+        .filter
+        .map(|f| f.validate(&get_all_event_types()))
+        .transpose()?;
     let now = chrono::Utc::now();
 
     let existing = user_notification_pref::Entity::find()
@@ -660,18 +1031,32 @@ async fn update_preference(
             if Some(&destination) != existing.destination.as_ref() {
                 active.destination = Set(Some(destination));
                 active.verified = Set(false);
+                active.verification_code_hash = Set(None);
+                active.verification_sent_at = Set(None);
+                active.verification_attempts = Set(0);
             }
         }
+        if let Some(filter) = &filter {
+            let (include, exclude) = filter.event_type_columns();
+            active.min_severity = Set(filter.min_severity.clone());
+            active.include_event_types = Set(include);
+            active.exclude_event_types = Set(exclude);
+        }
         active.updated_at = Set(now);
 
         active.update(&db).await?
     } else {
+        let filter = filter.unwrap_or_default();
+        let (include, exclude) = filter.event_type_columns();
         let new_pref = user_notification_pref::ActiveModel {
             user_id: Set(auth.user_id()),
             channel_type: Set(channel_type.clone()),
             enabled: Set(req.enabled.unwrap_or(false)),
             destination: Set(req.destination),
             verified: Set(false),
+            min_severity: Set(filter.min_severity),
+            include_event_types: Set(include),
+            exclude_event_types: Set(exclude),
             created_at: Set(now),
             updated_at: Set(now),
             ..Default::default()
@@ -679,14 +1064,229 @@ async fn update_preference(
         new_pref.insert(&db).await?
     };
 
-    Ok(Json(UserPrefDto {
-        channel_type: pref.channel_type,
-        enabled: pref.enabled,
-        destination: pref.destination.map(|d| mask_destination(&d)),
-        verified: pref.verified,
+    Ok(Json(UserPrefDto::from_pref(pref)))
+}
+
+/// Send a test message to the current user's own destination
+///
+/// Works before the destination is verified, so users can check a chat ID or
+/// phone number before relying on it. Limited to one test per channel per
+/// minute.
+#[utoipa::path(
+    post,
+    path = "/api/notifications/preferences/{channel_type}/test",
+    tag = "Notifications",
+    params(
+        ("channel_type" = String, Path, description = "Channel type"),
+    ),
+    responses(
+        (status = 200, body = TestChannelResponse),
+        (status = 400, description = "Invalid channel type or no destination set"),
+        (status = 429, description = "A test was sent too recently")
+    )
+)]
+async fn test_preference(
+    State(state): State<AppState>,
+    auth: Authenticated,
+    Path(channel_type): Path<String>,
+) -> Result<Json<TestChannelResponse>> {
+    let db = state.get_db().await?;
+    let (channel, _, destination) =
+        find_pref_with_destination(&db, auth.user_id(), &channel_type).await?;
+
+    let result = state
+        .notification
+        .test_user_destination(auth.user_id(), channel, &destination)
+        .await?;
+
+    Ok(Json(TestChannelResponse {
+        success: result.success,
+        error: result.error,
+    }))
+}
+
+/// Minutes a verification code stays valid
+const VERIFICATION_CODE_TTL_MINUTES: i64 = 10;
+/// Wrong codes accepted before a new code must be requested
+const MAX_VERIFICATION_ATTEMPTS: i32 = 5;
+
+fn verification_pending(
+    pref: &user_notification_pref::Model,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    pref.verification_code_hash.is_some()
+        && pref.verification_sent_at.is_some_and(|sent| {
+            now - sent < chrono::Duration::minutes(VERIFICATION_CODE_TTL_MINUTES)
+        })
+}
+
+/// The current user's preference for a channel, or 400 if the channel type is
+/// unknown or no destination is set
+async fn find_pref_with_destination(
+    db: &sea_orm::DatabaseConnection,
+    user_id: i64,
+    channel_type: &str,
+) -> Result<(ChannelType, user_notification_pref::Model, String)> {
+    let Some(channel) = ChannelType::parse(channel_type) else {
+        return Err(AppError::BadRequest(format!(
+            "Invalid channel type: {}",
+            channel_type
+        )));
+    };
+    let pref = user_notification_pref::Entity::find()
+        .filter(user_notification_pref::Column::UserId.eq(user_id))
+        .filter(user_notification_pref::Column::ChannelType.eq(channel_type))
+        .one(db)
+        .await?;
+    let destination = pref
+        .as_ref()
+        .and_then(|p| p.destination.clone())
+        .filter(|d| !d.is_empty());
+    match (pref, destination) {
+        (Some(pref), Some(destination)) => Ok((channel, pref, destination)),
+        _ => Err(AppError::BadRequest(format!(
+            "No {} destination configured",
+            channel_type
+        ))),
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SendCodeResponse {
+    /// Seconds until the code expires
+    pub expires_in_seconds: i64,
+}
+
+/// Send a 6-digit verification code to the current user's destination
+///
+/// The destination is marked verified once the code is submitted to
+/// `POST /api/notifications/preferences/{channel_type}/verify-code`.
+#[utoipa::path(
+    post,
+    path = "/api/notifications/preferences/{channel_type}/send-code",
+    tag = "Notifications",
+    params(
+        ("channel_type" = String, Path, description = "Channel type"),
+    ),
+    responses(
+        (status = 200, body = SendCodeResponse),
+        (status = 400, description = "Invalid channel type, no destination set or already verified"),
+        (status = 429, description = "A code was sent too recently"),
+        (status = 502, description = "The channel failed to deliver the code")
+    )
+)]
+async fn send_verification_code(
+    State(state): State<AppState>,
+    auth: Authenticated,
+    Path(channel_type): Path<String>,
+) -> Result<Json<SendCodeResponse>> {
+    let db = state.get_db().await?;
+    let (channel, pref, destination) =
+        find_pref_with_destination(&db, auth.user_id(), &channel_type).await?;
+    if pref.verified {
+        return Err(AppError::BadRequest(format!(
+            "{} destination is already verified",
+            channel
+        )));
+    }
+
+    let now = chrono::Utc::now();
+    if let Some(sent) = pref.verification_sent_at {
+        let interval = runtime_config::current().verification_resend_interval_secs;
+        let wait = interval - (now - sent).num_seconds();
+        if wait > 0 {
+            return Err(AppError::TooManyRequests(format!(
+                "Please wait {} seconds before requesting another code",
+                wait
+            )));
+        }
+    }
+
+    let code = generate_verification_code();
+    let result = state
+        .notification
+        .send_verification_code(channel, &destination, &code, VERIFICATION_CODE_TTL_MINUTES)
+        .await;
+    if !result.success {
+        return Err(AppError::BadGateway(result.error.unwrap_or_else(|| {
+            format!("Failed to send {} verification code", channel)
+        })));
+    }
+
+    let mut active: user_notification_pref::ActiveModel = pref.into();
+    active.verification_code_hash = Set(Some(hash_verification_code(&code)?));
+    active.verification_sent_at = Set(Some(now));
+    active.verification_attempts = Set(0);
+    active.updated_at = Set(now);
+    active.update(&db).await?;
+
+    Ok(Json(SendCodeResponse {
+        expires_in_seconds: VERIFICATION_CODE_TTL_MINUTES * 60,
     }))
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct VerifyCodeRequest {
+    pub code: String,
+}
+
+/// Verify the current user's destination with the code sent to it
+#[utoipa::path(
+    post,
+    path = "/api/notifications/preferences/{channel_type}/verify-code",
+    tag = "Notifications",
+    params(
+        ("channel_type" = String, Path, description = "Channel type"),
+    ),
+    request_body = VerifyCodeRequest,
+    responses(
+        (status = 200, body = UserPrefDto),
+        (status = 400, description = "Wrong, expired or missing code"),
+        (status = 429, description = "Too many wrong codes; request a new one")
+    )
+)]
+async fn verify_code(
+    State(state): State<AppState>,
+    auth: Authenticated,
+    Path(channel_type): Path<String>,
+    Json(req): Json<VerifyCodeRequest>,
+) -> Result<Json<UserPrefDto>> {
+    let db = state.get_db().await?;
+    let (_, pref, _) = find_pref_with_destination(&db, auth.user_id(), &channel_type).await?;
+
+    let now = chrono::Utc::now();
+    if !verification_pending(&pref, now) {
+        return Err(AppError::BadRequest(
+            "No pending verification code, request a new one".to_string(),
+        ));
+    }
+    if pref.verification_attempts >= MAX_VERIFICATION_ATTEMPTS {
+        return Err(AppError::TooManyRequests(
+            "Too many wrong codes, request a new one".to_string(),
+        ));
+    }
+
+    let hash = pref.verification_code_hash.clone().unwrap_or_default();
+    let attempts = pref.verification_attempts;
+    let mut active: user_notification_pref::ActiveModel = pref.into();
+    active.updated_at = Set(now);
+    if !verify_verification_code(req.code.trim(), &hash) {
+        active.verification_attempts = Set(attempts + 1);
+        active.update(&db).await?;
+        return Err(AppError::BadRequest(
+            "Invalid verification code".to_string(),
+        ));
+    }
+
+    active.verified = Set(true);
+    active.verification_code_hash = Set(None);
+    active.verification_sent_at = Set(None);
+    active.verification_attempts = Set(0);
+    let pref = active.update(&db).await?;
+
+    Ok(Json(UserPrefDto::from_pref(pref)))
+}
+
 // ============================================================================
 // Admin: Logs
 // ============================================================================
@@ -708,6 +1308,8 @@ pub struct LogDto {
     pub recipient: Option<String>,
     pub status: String,
     pub error_message: Option<String>,
+    /// ID of the failed delivery this one was a fallback for
+    pub fallback_of: Option<i64>,
     pub created_at: String,
 }
 
@@ -768,6 +1370,7 @@ async fn list_logs(
             recipient: l.recipient,
             status: l.status,
             error_message: l.error_message,
+            fallback_of: l.fallback_of,
             created_at: l.created_at.to_rfc3339(),
         })
         .collect();
@@ -775,6 +1378,154 @@ async fn list_logs(
     Ok(Json(LogsResponse { logs: dtos, total }))
 }
 
+// ============================================================================
+// Telegram Webhook
+// ============================================================================
+
+/// Public routes Telegram calls back into (authenticated by the webhook secret)
+pub fn telegram_webhook_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/callback", post(telegram_callback))
+        .with_state(state)
+}
+
+/// The parts of a Telegram update Kubarr acts on
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TelegramUpdate {
+    pub callback_query: Option<TelegramCallbackQuery>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TelegramCallbackQuery {
+    pub id: String,
+    pub from: TelegramUser,
+    pub message: Option<TelegramMessage>,
+    pub data: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TelegramUser {
+    pub id: i64,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TelegramMessage {
+    pub chat: TelegramChat,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct TelegramChat {
+    pub id: i64,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TelegramCallbackResponse {
+    /// "acknowledged", "ignored", "invalid", "forbidden" or "failed"
+    pub outcome: String,
+}
+
+/// Handle an inline button press from a Telegram notification
+///
+/// Telegram must send the channel's `webhook_secret` in the
+/// `X-Telegram-Bot-Api-Secret-Token` header. The button's callback data is
+/// signed for the chat it was sent to, and the Telegram user who pressed it
+/// must have a verified Telegram destination in Kubarr with the permission the
+/// action needs.
+#[utoipa::path(
+    post,
+    path = "/api/notifications/telegram/callback",
+    tag = "Notifications",
+    request_body = TelegramUpdate,
+    responses(
+        (status = 200, body = TelegramCallbackResponse),
+        (status = 401, description = "Missing or invalid webhook secret")
+    )
+)]
+async fn telegram_callback(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(update): Json<TelegramUpdate>,
+) -> Result<Json<TelegramCallbackResponse>> {
+    let secret = headers
+        .get("x-telegram-bot-api-secret-token")
+        .and_then(|v| v.to_str().ok());
+    if !state.notification.verify_telegram_webhook(secret).await {
+        return Err(AppError::Unauthorized(
+            "Invalid Telegram webhook secret".to_string(),
+        ));
+    }
+
+    let respond = |outcome: &str| {
+        Ok(Json(TelegramCallbackResponse {
+            outcome: outcome.to_string(),
+        }))
+    };
+
+    let Some(query) = update.callback_query else {
+        return respond("ignored");
+    };
+    let (Some(message), Some(data)) = (&query.message, &query.data) else {
+        return respond("ignored");
+    };
+    let Some(action) = state
+        .notification
+        .parse_telegram_callback(data, &message.chat.id.to_string())
+        .await
+    else {
+        state
+            .notification
+            .answer_telegram_callback(&query.id, "This button is no longer valid")
+            .await;
+        return respond("invalid");
+    };
+
+    // The presser must be linked to a Kubarr user allowed to take the action
+    let db = state.get_db().await?;
+    let pref = user_notification_pref::Entity::find()
+        .filter(user_notification_pref::Column::ChannelType.eq(ChannelType::Telegram.as_str()))
+        .filter(user_notification_pref::Column::Destination.eq(query.from.id.to_string()))
+        .filter(user_notification_pref::Column::Verified.eq(true))
+        .one(&db)
+        .await?;
+    let permissions = match &pref {
+        Some(pref) => get_user_permissions(&db, pref.user_id).await,
+        None => Vec::new(),
+    };
+
+    match (action, pref) {
+        (TelegramAction::AcknowledgeAlert(alert_id), Some(pref))
+            if permissions.iter().any(|p| p == MonitoringManage::NAME) =>
+        {
+            let request = AcknowledgeAlertRequest {
+                comment: Some("Acknowledged from Telegram".to_string()),
+            };
+            match alerts::acknowledge(&db, alert_id, pref.user_id, request).await {
+                Ok(_) => {
+                    state
+                        .notification
+                        .answer_telegram_callback(&query.id, "Alert acknowledged")
+                        .await;
+                    respond("acknowledged")
+                }
+                Err(e) => {
+                    state
+                        .notification
+                        .answer_telegram_callback(&query.id, &e.to_string())
+                        .await;
+                    respond("failed")
+                }
+            }
+        }
+        _ => {
+            state
+                .notification
+                .answer_telegram_callback(&query.id, "You are not allowed to do this")
+                .await;
+            respond("forbidden")
+        }
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -784,12 +1535,10 @@ fn mask_sensitive_config(config: &serde_json::Value) -> serde_json::Value {
 
     if let Some(obj) = masked.as_object_mut() {
         for (key, value) in obj.iter_mut() {
-            let key_lower = key.to_lowercase();
-            let is_sensitive = key_lower.contains("password")
-                || key_lower.contains("secret")
-                || key_lower.contains("token")
-                || key_lower.contains("api_key");
-            if is_sensitive && value.is_string() && !value.as_str().unwrap_or("").is_empty() {
+            if crypto::is_sensitive_field(key)
+                && value.is_string()
+                && !value.as_str().unwrap_or("").is_empty()
+            {
                 *value = serde_json::Value::String("********".to_string());
             }
         }
@@ -822,13 +1571,36 @@ fn mask_destination(dest: &str) -> String {
     }
 }
 
-fn get_all_event_types() -> Vec<String> {
+/// Severity used for event types that have not been configured yet
+pub(crate) fn default_event_severity(event_type: &str) -> &'static str {
+    match event_type {
+        "metric_anomaly" => "warning",
+        "node_temperature_critical" => "critical",
+        "log_alert_firing" => "warning",
+        "update_failed" => "critical",
+        "backup_failed" => "warning",
+        "storage_quota_warning" => "warning",
+        "storage_quota_exceeded" => "critical",
+        "ip_banned" => "warning",
+        "access_denied" => "warning",
+        "account_locked" => "warning",
+        "refresh_token_reused" => "warning",
+        "role_expiring" => "warning",
+        _ => "info",
+    }
+}
+
+pub(crate) fn get_all_event_types() -> Vec<String> {
     use crate::models::audit_log::AuditAction;
 
     vec![
         AuditAction::Login.to_string(),
         AuditAction::LoginFailed.to_string(),
         AuditAction::Logout.to_string(),
+        AuditAction::IpBanned.to_string(),
+        AuditAction::AccessDenied.to_string(),
+        AuditAction::AccountLocked.to_string(),
+        AuditAction::RefreshTokenReused.to_string(),
         AuditAction::UserCreated.to_string(),
         AuditAction::UserUpdated.to_string(),
         AuditAction::UserDeleted.to_string(),
@@ -839,15 +1611,44 @@ fn get_all_event_types() -> Vec<String> {
         AuditAction::RoleDeleted.to_string(),
         AuditAction::RoleAssigned.to_string(),
         AuditAction::RoleUnassigned.to_string(),
+        AuditAction::RoleExpiring.to_string(),
+        AuditAction::AccessReviewStarted.to_string(),
+        AuditAction::AccessRevoked.to_string(),
+        AuditAction::ScheduleOverrideGranted.to_string(),
+        AuditAction::ScheduleOverrideRevoked.to_string(),
         AuditAction::AppInstalled.to_string(),
         AuditAction::AppUninstalled.to_string(),
         AuditAction::AppRestarted.to_string(),
         AuditAction::AppAccessed.to_string(),
+        AuditAction::AppsSuspended.to_string(),
+        AuditAction::AppInstallRequested.to_string(),
+        AuditAction::AppInstallDenied.to_string(),
+        AuditAction::AppUpgraded.to_string(),
+        AuditAction::AppShellOpened.to_string(),
+        AuditAction::MediaRequested.to_string(),
+        AuditAction::MediaRequestApproved.to_string(),
+        AuditAction::MediaRequestDeclined.to_string(),
+        AuditAction::WanDegraded.to_string(),
+        AuditAction::WanRecovered.to_string(),
+        AuditAction::UptimeMonitorDown.to_string(),
+        AuditAction::UptimeMonitorUp.to_string(),
+        AuditAction::MetricAnomaly.to_string(),
+        AuditAction::NodeTemperatureCritical.to_string(),
+        AuditAction::LogAlertFiring.to_string(),
+        AuditAction::LogAlertResolved.to_string(),
+        AuditAction::AlertAssigned.to_string(),
+        AuditAction::StorageQuotaWarning.to_string(),
+        AuditAction::StorageQuotaExceeded.to_string(),
         AuditAction::TwoFactorEnabled.to_string(),
         AuditAction::TwoFactorDisabled.to_string(),
         AuditAction::PasswordChanged.to_string(),
         AuditAction::SystemSettingChanged.to_string(),
         AuditAction::InviteCreated.to_string(),
         AuditAction::InviteUsed.to_string(),
+        AuditAction::UpdateAvailable.to_string(),
+        AuditAction::UpdateCompleted.to_string(),
+        AuditAction::UpdateFailed.to_string(),
+        AuditAction::BackupRestored.to_string(),
+        AuditAction::BackupFailed.to_string(),
     ]
 }
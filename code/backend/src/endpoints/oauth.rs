@@ -1,6 +1,9 @@
 use axum::{
     extract::{Path, Query, State},
-    http::{header::SET_COOKIE, HeaderMap, HeaderValue},
+    http::{
+        header::{SET_COOKIE, USER_AGENT},
+        HeaderMap,
+    },
     response::{IntoResponse, Redirect, Response},
     routing::{delete, get},
     Json, Router,
@@ -9,12 +12,17 @@ use chrono::Utc;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, Set};
 use serde::{Deserialize, Serialize};
 
-use crate::config::CONFIG;
+use crate::endpoints::auth::audit_sign_in;
+use crate::endpoints::settings::get_setting_bool;
 use crate::error::{AppError, Result};
+use crate::middleware::auth::{session_cookie, session_cookie_name, SESSION_COOKIE_MAX_AGE};
 use crate::middleware::permissions::{Authenticated, Authorized, SettingsManage, SettingsView};
 use crate::models::prelude::*;
 use crate::models::{oauth_account, oauth_provider, user};
-use crate::services::{create_access_token, generate_random_string, hash_password};
+use crate::services::{
+    approvals, create_access_token, crypto, generate_random_string, hash_password, ip_bans, links,
+    outbound,
+};
 use crate::state::AppState;
 
 /// Create OAuth routes
@@ -213,7 +221,7 @@ async fn update_provider(
             model.client_id = Set(Some(client_id));
         }
         if let Some(client_secret) = data.client_secret {
-            model.client_secret = Set(Some(client_secret));
+            model.client_secret = Set(Some(crypto::encrypt(&client_secret)?));
         }
         model.updated_at = Set(now);
         model.update(&db).await?
@@ -229,7 +237,10 @@ async fn update_provider(
             name: Set(name.to_string()),
             enabled: Set(data.enabled.unwrap_or(false)),
             client_id: Set(data.client_id),
-            client_secret: Set(data.client_secret),
+            client_secret: Set(data
+                .client_secret
+                .map(|secret| crypto::encrypt(&secret))
+                .transpose()?),
             created_at: Set(now),
             updated_at: Set(now),
         };
@@ -313,10 +324,7 @@ async fn oauth_login(
         }
     };
 
-    let redirect_uri = format!(
-        "{}/api/oauth/{}/callback",
-        CONFIG.auth.oauth2_issuer_url, provider
-    );
+    let redirect_uri = links::oauth_callback(&provider);
 
     let url = format!(
         "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}",
@@ -345,6 +353,7 @@ async fn oauth_login(
 async fn oauth_callback(
     State(state): State<AppState>,
     Path(provider): Path<String>,
+    request_headers: HeaderMap,
     Query(query): Query<OAuthCallbackQuery>,
 ) -> Result<Response> {
     let db = state.get_db().await?;
@@ -385,11 +394,9 @@ async fn oauth_callback(
     let client_secret = provider_config
         .client_secret
         .ok_or_else(|| AppError::Internal("Provider client_secret not configured".to_string()))?;
+    let client_secret = crypto::decrypt(&client_secret)?;
 
-    let redirect_uri = format!(
-        "{}/api/oauth/{}/callback",
-        CONFIG.auth.oauth2_issuer_url, provider
-    );
+    let redirect_uri = links::oauth_callback(&provider);
 
     // Exchange code for tokens
     let (token_url, userinfo_url) = match provider.as_str() {
@@ -409,7 +416,7 @@ async fn oauth_callback(
         }
     };
 
-    let http_client = reqwest::Client::new();
+    let http_client = outbound::client();
 
     // Exchange code for token
     let token_response = http_client
@@ -582,19 +589,28 @@ async fn oauth_callback(
             let random_password = generate_random_string(32);
             let password_hash = hash_password(&random_password)?;
 
+            let require_approval = get_setting_bool(&db, "registration_require_approval").await?;
+
             let now = Utc::now();
             let new_user = user::ActiveModel {
                 username: Set(final_username),
                 email: Set(email.clone()),
                 hashed_password: Set(password_hash),
                 is_active: Set(true),
-                is_approved: Set(true), // Auto-approve OAuth users
+                is_approved: Set(!require_approval),
                 created_at: Set(now),
                 updated_at: Set(now),
                 ..Default::default()
             };
 
             let created_user = new_user.insert(&db).await?;
+            if require_approval {
+                if let Err(e) =
+                    approvals::notify_pending(&db, &state.notification, &created_user).await
+                {
+                    tracing::warn!("Failed to notify admins of pending registration: {}", e);
+                }
+            }
 
             // Link OAuth account
             let new_oauth = oauth_account::ActiveModel {
@@ -642,18 +658,30 @@ async fn oauth_callback(
         Some(permissions),
         Some(allowed_apps),
     )?;
+    audit_sign_in(
+        &state,
+        found_user.id,
+        &found_user.username,
+        serde_json::json!({ "method": "oauth", "provider": provider }),
+        ip_bans::client_ip(&request_headers),
+        request_headers
+            .get(USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.chars().take(255).collect()),
+    )
+    .await;
 
     // Set session cookie and redirect
-    let cookie = format!(
-        "kubarr_session={}; HttpOnly; SameSite=Lax; Path=/; Max-Age=604800",
-        session_token
-    );
-
     let mut headers = HeaderMap::new();
     headers.insert(
         SET_COOKIE,
-        HeaderValue::from_str(&cookie)
-            .map_err(|e| AppError::Internal(format!("invalid cookie header: {}", e)))?,
+        session_cookie(
+            session_cookie_name(),
+            &session_token,
+            true,
+            SESSION_COOKIE_MAX_AGE,
+            None,
+        ),
     );
 
     Ok((headers, Redirect::to("/")).into_response())
@@ -61,6 +61,12 @@ async fn proxy_app_request(
             app_name
         )));
     }
+    if !check_app_schedule(&state, user.id, &app_name).await {
+        return Err(AppError::Forbidden(format!(
+            "{} is not available at this time",
+            app_name
+        )));
+    }
 
     // Get the app's service endpoint from Kubernetes
     let target_url = get_app_target_url(&state, &app_name, &path).await?;
@@ -101,8 +107,24 @@ async fn check_app_permission(state: &AppState, user_id: i64, app_name: &str) ->
     };
     let permissions = get_user_permissions(&db, user_id).await;
 
-    // Check for app.* wildcard or specific app.{name} permission
-    permissions.contains(&"app.*".to_string()) || permissions.contains(&format!("app.{}", app_name))
+    // Check for app.* wildcard or specific app.{name} permission, and that
+    // the app does not belong to another tenant
+    (permissions.contains(&"app.*".to_string())
+        || permissions.contains(&format!("app.{}", app_name)))
+        && crate::services::tenants::can_see_app(&db, user_id, app_name)
+            .await
+            .unwrap_or(false)
+}
+
+/// Check if the user's app access schedules allow the app right now
+async fn check_app_schedule(state: &AppState, user_id: i64, app_name: &str) -> bool {
+    let db = match state.get_db().await {
+        Ok(db) => db,
+        Err(_) => return false,
+    };
+    crate::services::app_schedules::is_allowed(&db, user_id, app_name, chrono::Utc::now())
+        .await
+        .unwrap_or(false)
 }
 
 /// Get the target URL for an app
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, State},
-    routing::{get, put},
+    routing::{get, post, put},
     Json, Router,
 };
 use chrono::Utc;
@@ -8,9 +8,17 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilte
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, Result};
-use crate::middleware::permissions::{Authorized, RolesManage, RolesView};
+use crate::middleware::permissions::{Authorized, RolesManage, RolesView, ALL_PERMISSIONS};
+use crate::models::audit_log::{AuditAction, ResourceType};
 use crate::models::prelude::*;
 use crate::models::{role, role_app_permission, role_permission};
+use crate::services::access::{self, Evaluation, Subject};
+use crate::services::access_reviews::{self, Decision, ReviewDetail, ReviewSummary};
+use crate::services::app_schedules::{self, ScheduleOwner, ScheduleWindow};
+use crate::services::media_profiles::{self, MediaProfile, SyncResult, SyncTarget};
+use crate::services::provisioning::{self, ProvisioningProfile};
+use crate::services::quotas::{self, QuotaLimits, QuotaOwner};
+use crate::services::{installed_apps, tenants};
 use crate::state::AppState;
 
 /// Create roles routes
@@ -18,11 +26,43 @@ pub fn roles_routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(list_roles).post(create_role))
         .route("/permissions", get(list_all_permissions))
+        .route("/evaluate", post(evaluate_permission))
+        .route(
+            "/reviews",
+            get(list_access_reviews).post(start_access_review),
+        )
+        .route(
+            "/reviews/{review_id}",
+            get(get_access_review).post(decide_access_review),
+        )
         .route(
             "/{role_id}",
             get(get_role).patch(update_role).delete(delete_role),
         )
         .route("/{role_id}/apps", put(set_role_apps))
+        .route(
+            "/{role_id}/quota",
+            get(get_role_quota)
+                .put(set_role_quota)
+                .delete(delete_role_quota),
+        )
+        .route(
+            "/{role_id}/schedules",
+            get(get_role_schedules).put(set_role_schedules),
+        )
+        .route(
+            "/{role_id}/media-profile",
+            get(get_media_profile)
+                .put(set_media_profile)
+                .delete(delete_media_profile),
+        )
+        .route("/{role_id}/media-profile/sync", post(sync_media_profile))
+        .route(
+            "/{role_id}/provisioning",
+            get(get_provisioning_profile)
+                .put(set_provisioning_profile)
+                .delete(delete_provisioning_profile),
+        )
         .route(
             "/{role_id}/permissions",
             get(get_role_permissions).put(set_role_permissions),
@@ -73,6 +113,25 @@ pub struct SetRolePermissions {
     pub permissions: Vec<String>,
 }
 
+/// Give either `user_id` or `role_id`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct EvaluatePermissionRequest {
+    pub user_id: Option<i64>,
+    pub role_id: Option<i64>,
+    /// Permission the action needs, e.g. `apps.restart`
+    pub action: String,
+    /// App the action is performed on, checked against app access, tenants and
+    /// schedules
+    pub app_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct DecideAccessRequest {
+    pub user_id: i64,
+    pub decision: Decision,
+    pub comment: Option<String>,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct PermissionInfo {
     pub key: String,
@@ -353,6 +412,26 @@ async fn set_role_apps(
     Ok(Json(response))
 }
 
+/// Apps that roles can be granted access to; these are the apps the
+/// backend can proxy to
+pub const APP_ACCESS_PERMISSIONS: &[(&str, &str)] = &[
+    ("sonarr", "Access Sonarr TV show manager"),
+    ("radarr", "Access Radarr movie manager"),
+    ("qbittorrent", "Access qBittorrent download client"),
+    ("transmission", "Access Transmission download client"),
+    ("deluge", "Access Deluge download client"),
+    ("rutorrent", "Access ruTorrent web UI"),
+    ("jellyfin", "Access Jellyfin media server"),
+    ("plex", "Access Plex media server"),
+    ("jackett", "Access Jackett indexer proxy"),
+    ("jellyseerr", "Access Jellyseerr request manager"),
+    ("sabnzbd", "Access SABnzbd Usenet client"),
+    ("grafana", "Access Grafana dashboards"),
+    ("victoriametrics", "Access VictoriaMetrics"),
+    ("victorialogs", "Access VictoriaLogs log storage"),
+    ("kubernetes-dashboard", "Access Kubernetes Dashboard"),
+];
+
 /// Get all available permissions with descriptions
 #[utoipa::path(
     get,
@@ -360,7 +439,10 @@ async fn set_role_apps(
     tag = "Roles",
     responses((status = 200, body = Vec<PermissionInfo>))
 )]
-async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<PermissionInfo>>> {
+async fn list_all_permissions(
+    State(state): State<AppState>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<Vec<PermissionInfo>>> {
     let mut permissions = vec![
         // Apps permissions
         PermissionInfo {
@@ -383,6 +465,21 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
             category: "Apps".to_string(),
             description: "Restart application pods".to_string(),
         },
+        PermissionInfo {
+            key: "apps.stop".to_string(),
+            category: "Apps".to_string(),
+            description: "Stop applications and start them again".to_string(),
+        },
+        PermissionInfo {
+            key: "apps.exec".to_string(),
+            category: "Apps".to_string(),
+            description: "Open a shell in application pods".to_string(),
+        },
+        PermissionInfo {
+            key: "apps.request".to_string(),
+            category: "Apps".to_string(),
+            description: "Request app installs for an admin to approve".to_string(),
+        },
         // Storage permissions
         PermissionInfo {
             key: "storage.view".to_string(),
@@ -416,6 +513,11 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
             category: "Monitoring".to_string(),
             description: "View metrics and monitoring data".to_string(),
         },
+        PermissionInfo {
+            key: "monitoring.manage".to_string(),
+            category: "Monitoring".to_string(),
+            description: "Register and manage uptime monitors".to_string(),
+        },
         // Users permissions
         PermissionInfo {
             key: "users.view".to_string(),
@@ -427,6 +529,12 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
             category: "Users".to_string(),
             description: "Create, edit, and delete users".to_string(),
         },
+        // Tenant permissions
+        PermissionInfo {
+            key: "tenants.manage".to_string(),
+            category: "Tenants".to_string(),
+            description: "Manage tenants and see the apps and storage of every tenant".to_string(),
+        },
         // Roles permissions
         PermissionInfo {
             key: "roles.view".to_string(),
@@ -449,6 +557,17 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
             category: "Settings".to_string(),
             description: "Modify system settings".to_string(),
         },
+        // Networking permissions
+        PermissionInfo {
+            key: "networking.view".to_string(),
+            category: "Networking".to_string(),
+            description: "View network topology and bandwidth usage".to_string(),
+        },
+        PermissionInfo {
+            key: "networking.manage".to_string(),
+            category: "Networking".to_string(),
+            description: "Manage bandwidth quotas".to_string(),
+        },
         // VPN permissions
         PermissionInfo {
             key: "vpn.view".to_string(),
@@ -460,29 +579,44 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
             category: "VPN".to_string(),
             description: "Manage VPN providers and assign VPN to apps".to_string(),
         },
+        // Audit permissions
+        PermissionInfo {
+            key: "audit.view".to_string(),
+            category: "Audit".to_string(),
+            description: "View the full audit log".to_string(),
+        },
+        PermissionInfo {
+            key: "audit.view_own_apps".to_string(),
+            category: "Audit".to_string(),
+            description: "View audit entries for the apps and users the role manages".to_string(),
+        },
+        PermissionInfo {
+            key: "audit.manage".to_string(),
+            category: "Audit".to_string(),
+            description: "Clear old audit log entries".to_string(),
+        },
+        // Media request permissions
+        PermissionInfo {
+            key: "requests.manage".to_string(),
+            category: "Requests".to_string(),
+            description: "Approve media requests and manage linked request accounts".to_string(),
+        },
+        // System permissions
+        PermissionInfo {
+            key: "system.manage".to_string(),
+            category: "System".to_string(),
+            description: "Read Kubarr's own logs and diagnostics".to_string(),
+        },
+        // Security permissions
+        PermissionInfo {
+            key: "security.manage".to_string(),
+            category: "Security".to_string(),
+            description: "View failed sign-ins and manage IP bans".to_string(),
+        },
     ];
 
     // Add app access permissions
-    // These are the apps that the backend can proxy to
-    let app_permissions = vec![
-        ("sonarr", "Access Sonarr TV show manager"),
-        ("radarr", "Access Radarr movie manager"),
-        ("qbittorrent", "Access qBittorrent download client"),
-        ("transmission", "Access Transmission download client"),
-        ("deluge", "Access Deluge download client"),
-        ("rutorrent", "Access ruTorrent web UI"),
-        ("jellyfin", "Access Jellyfin media server"),
-        ("plex", "Access Plex media server"),
-        ("jackett", "Access Jackett indexer proxy"),
-        ("jellyseerr", "Access Jellyseerr request manager"),
-        ("sabnzbd", "Access SABnzbd Usenet client"),
-        ("grafana", "Access Grafana dashboards"),
-        ("victoriametrics", "Access VictoriaMetrics"),
-        ("victorialogs", "Access VictoriaLogs log storage"),
-        ("kubernetes-dashboard", "Access Kubernetes Dashboard"),
-    ];
-
-    for (app_name, description) in app_permissions {
+    for (app_name, description) in APP_ACCESS_PERMISSIONS {
         permissions.push(PermissionInfo {
             key: format!("app.{}", app_name),
             category: "App Access".to_string(),
@@ -490,9 +624,592 @@ async fn list_all_permissions(_auth: Authorized<RolesView>) -> Result<Json<Vec<P
         });
     }
 
+    // Cloned instances get their own access permission
+    let db = state.get_db().await?;
+    for clone in installed_apps::list_clones(&db).await? {
+        permissions.push(PermissionInfo {
+            key: format!("app.{}", clone.app_name),
+            category: "App Access".to_string(),
+            description: format!(
+                "Access {} (instance of {})",
+                clone.app_name,
+                clone.cloned_from.unwrap_or_default()
+            ),
+        });
+    }
+
     Ok(Json(permissions))
 }
 
+/// Check whether a user or role would be allowed to perform an action,
+/// showing which roles and permissions the decision rests on
+#[utoipa::path(
+    post,
+    path = "/api/roles/evaluate",
+    tag = "Roles",
+    request_body = EvaluatePermissionRequest,
+    responses(
+        (status = 200, body = Evaluation),
+        (status = 400, description = "Invalid subject or unknown permission"),
+        (status = 404, description = "User or role not found")
+    )
+)]
+async fn evaluate_permission(
+    State(state): State<AppState>,
+    auth: Authorized<RolesView>,
+    Json(data): Json<EvaluatePermissionRequest>,
+) -> Result<Json<Evaluation>> {
+    if !ALL_PERMISSIONS.contains(&data.action.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "Unknown permission: {}",
+            data.action
+        )));
+    }
+    let app_name = data
+        .app_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty());
+
+    let db = state.get_db().await?;
+    let subject = match (data.user_id, data.role_id) {
+        (Some(user_id), None) => {
+            let hidden = tenants::hidden_users(&db, auth.user_id()).await?;
+            let found = User::find_by_id(user_id)
+                .one(&db)
+                .await?
+                .filter(|u| !hidden.contains(&u.id))
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            Subject::User(found)
+        }
+        (None, Some(role_id)) => Subject::Role(
+            Role::find_by_id(role_id)
+                .one(&db)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?,
+        ),
+        _ => {
+            return Err(AppError::BadRequest(
+                "Give either user_id or role_id".to_string(),
+            ))
+        }
+    };
+
+    Ok(Json(
+        access::evaluate(&db, &subject, &data.action, app_name).await?,
+    ))
+}
+
+/// List access reviews, newest first
+#[utoipa::path(
+    get,
+    path = "/api/roles/reviews",
+    tag = "Roles",
+    responses((status = 200, body = Vec<ReviewSummary>))
+)]
+async fn list_access_reviews(
+    State(state): State<AppState>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<Vec<ReviewSummary>>> {
+    let db = state.get_db().await?;
+    Ok(Json(access_reviews::list_reviews(&db).await?))
+}
+
+/// Start an access review of every active user's roles and app access
+#[utoipa::path(
+    post,
+    path = "/api/roles/reviews",
+    tag = "Roles",
+    responses(
+        (status = 200, body = ReviewDetail),
+        (status = 400, description = "A review is already open")
+    )
+)]
+async fn start_access_review(
+    State(state): State<AppState>,
+    auth: Authorized<RolesManage>,
+) -> Result<Json<ReviewDetail>> {
+    let db = state.get_db().await?;
+    let policy = access_reviews::policy(&db).await?;
+    let review =
+        access_reviews::start_review(&db, Some(auth.user_id()), policy.deadline_days, Utc::now())
+            .await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AccessReviewStarted,
+            ResourceType::Role,
+            Some(review.id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({"due_at": review.due_at})),
+            None,
+            None,
+        )
+        .await;
+    access_reviews::announce(
+        &db,
+        &state.notification,
+        &review,
+        Some(&auth.user().username),
+    )
+    .await?;
+
+    Ok(Json(access_reviews::get_review(&db, review.id).await?))
+}
+
+/// Get an access review with every user in it
+#[utoipa::path(
+    get,
+    path = "/api/roles/reviews/{review_id}",
+    tag = "Roles",
+    params(("review_id" = i64, Path, description = "Access review ID")),
+    responses(
+        (status = 200, body = ReviewDetail),
+        (status = 404, description = "Access review not found")
+    )
+)]
+async fn get_access_review(
+    State(state): State<AppState>,
+    Path(review_id): Path<i64>,
+    auth: Authorized<RolesView>,
+) -> Result<Json<ReviewDetail>> {
+    let db = state.get_db().await?;
+    let mut review = access_reviews::get_review(&db, review_id).await?;
+    let hidden = tenants::hidden_users(&db, auth.user_id()).await?;
+    review.items.retain(|i| !hidden.contains(&i.user_id));
+    Ok(Json(review))
+}
+
+/// Confirm or revoke a user's access in a review
+///
+/// Revoking removes all of the user's roles. Confirming a user suspended
+/// for missing the deadline reactivates their account.
+#[utoipa::path(
+    post,
+    path = "/api/roles/reviews/{review_id}",
+    tag = "Roles",
+    params(("review_id" = i64, Path, description = "Access review ID")),
+    request_body = DecideAccessRequest,
+    responses(
+        (status = 200, body = ReviewDetail),
+        (status = 400, description = "Already decided, review closed or own access"),
+        (status = 404, description = "Access review or user not found")
+    )
+)]
+async fn decide_access_review(
+    State(state): State<AppState>,
+    Path(review_id): Path<i64>,
+    auth: Authorized<RolesManage>,
+    Json(data): Json<DecideAccessRequest>,
+) -> Result<Json<ReviewDetail>> {
+    let db = state.get_db().await?;
+    if tenants::hidden_users(&db, auth.user_id())
+        .await?
+        .contains(&data.user_id)
+    {
+        return Err(AppError::NotFound(
+            "User is not part of this review".to_string(),
+        ));
+    }
+    let comment = data.comment.clone();
+    let outcome = access_reviews::decide(
+        &db,
+        review_id,
+        auth.user(),
+        data.user_id,
+        data.decision,
+        data.comment,
+        Utc::now(),
+    )
+    .await?;
+
+    let reviewer = auth.user().username.clone();
+    let action = match data.decision {
+        Decision::Confirm => AuditAction::AccessConfirmed,
+        Decision::Revoke => AuditAction::AccessRevoked,
+    };
+    let _ = state
+        .audit
+        .log_success(
+            action.clone(),
+            ResourceType::User,
+            Some(outcome.user.id.to_string()),
+            Some(auth.user_id()),
+            Some(reviewer.clone()),
+            Some(serde_json::json!({
+                "review_id": review_id,
+                "target_username": outcome.user.username,
+                "removed_roles": outcome.removed_roles,
+                "comment": comment,
+            })),
+            None,
+            None,
+        )
+        .await;
+    if outcome.reactivated {
+        let _ = state
+            .audit
+            .log_success(
+                AuditAction::UserActivated,
+                ResourceType::User,
+                Some(outcome.user.id.to_string()),
+                Some(auth.user_id()),
+                Some(reviewer.clone()),
+                Some(serde_json::json!({
+                    "target_username": outcome.user.username,
+                    "reason": "access_review_confirmed",
+                    "review_id": review_id,
+                })),
+                None,
+                None,
+            )
+            .await;
+    }
+    if data.decision == Decision::Revoke {
+        let _ = state
+            .notification
+            .notify_event(
+                &action,
+                Some(outcome.user.id),
+                Some(&reviewer),
+                Some(&format!(
+                    "{} in access review #{}",
+                    outcome.user.username, review_id
+                )),
+            )
+            .await;
+    }
+
+    Ok(Json(access_reviews::get_review(&db, review_id).await?))
+}
+
+/// Get the resource quota applied to each member of a role
+#[utoipa::path(
+    get,
+    path = "/api/roles/{role_id}/quota",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, body = QuotaLimits),
+        (status = 404, description = "Role not found or no quota configured")
+    )
+)]
+async fn get_role_quota(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<QuotaLimits>> {
+    let db = state.get_db().await?;
+    let quota = quotas::get_quota(&db, QuotaOwner::Role(role_id))
+        .await?
+        .ok_or_else(|| AppError::NotFound("No quota configured".to_string()))?;
+    Ok(Json(quota))
+}
+
+/// Set the resource quota applied to each member of a role
+#[utoipa::path(
+    put,
+    path = "/api/roles/{role_id}/quota",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    request_body = QuotaLimits,
+    responses(
+        (status = 200, body = QuotaLimits),
+        (status = 400, description = "Negative limit"),
+        (status = 404, description = "Role not found")
+    )
+)]
+async fn set_role_quota(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+    Json(limits): Json<QuotaLimits>,
+) -> Result<Json<QuotaLimits>> {
+    let db = state.get_db().await?;
+    // Verify role exists
+    let _ = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    Ok(Json(
+        quotas::set_quota(&db, QuotaOwner::Role(role_id), &limits).await?,
+    ))
+}
+
+/// Remove a role's resource quota, leaving its members unrestricted
+#[utoipa::path(
+    delete,
+    path = "/api/roles/{role_id}/quota",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, description = "Quota removed"),
+        (status = 404, description = "No quota configured")
+    )
+)]
+async fn delete_role_quota(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    if !quotas::delete_quota(&db, QuotaOwner::Role(role_id)).await? {
+        return Err(AppError::NotFound("No quota configured".to_string()));
+    }
+    Ok(Json(serde_json::json!({"message": "Quota removed"})))
+}
+
+/// Get the app access schedules that limit what a role grants
+#[utoipa::path(
+    get,
+    path = "/api/roles/{role_id}/schedules",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses((status = 200, body = Vec<ScheduleWindow>))
+)]
+async fn get_role_schedules(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<Vec<ScheduleWindow>>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        app_schedules::get_schedules(&db, ScheduleOwner::Role(role_id)).await?,
+    ))
+}
+
+/// Replace the app access schedules of a role
+///
+/// Members reach a scheduled app through this role only inside its windows;
+/// an empty list removes the limits.
+#[utoipa::path(
+    put,
+    path = "/api/roles/{role_id}/schedules",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    request_body = Vec<ScheduleWindow>,
+    responses(
+        (status = 200, body = Vec<ScheduleWindow>),
+        (status = 400, description = "Invalid window"),
+        (status = 404, description = "Role not found")
+    )
+)]
+async fn set_role_schedules(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+    Json(windows): Json<Vec<ScheduleWindow>>,
+) -> Result<Json<Vec<ScheduleWindow>>> {
+    let db = state.get_db().await?;
+    // Verify role exists
+    let _ = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    Ok(Json(
+        app_schedules::set_schedules(&db, ScheduleOwner::Role(role_id), &windows).await?,
+    ))
+}
+
+/// Get the media profile of a role
+#[utoipa::path(
+    get,
+    path = "/api/roles/{role_id}/media-profile",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, body = MediaProfile),
+        (status = 404, description = "Role not found or no media profile configured")
+    )
+)]
+async fn get_media_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<MediaProfile>> {
+    let db = state.get_db().await?;
+    let profile = media_profiles::get_profile(&db, role_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No media profile configured".to_string()))?;
+    Ok(Json(profile))
+}
+
+/// Set the media profile of a role
+///
+/// The members' Jellyfin policies are updated in the background.
+#[utoipa::path(
+    put,
+    path = "/api/roles/{role_id}/media-profile",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    request_body = MediaProfile,
+    responses(
+        (status = 200, body = MediaProfile),
+        (status = 400, description = "Invalid limit"),
+        (status = 404, description = "Role not found")
+    )
+)]
+async fn set_media_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+    Json(profile): Json<MediaProfile>,
+) -> Result<Json<MediaProfile>> {
+    let db = state.get_db().await?;
+    // Verify role exists
+    let _ = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    let profile = media_profiles::set_profile(&db, role_id, &profile).await?;
+    media_profiles::spawn_sync(&state, SyncTarget::Role(role_id));
+    Ok(Json(profile))
+}
+
+/// Remove the media profile of a role
+///
+/// Members keep their current Jellyfin policy unless another of their roles
+/// has a profile.
+#[utoipa::path(
+    delete,
+    path = "/api/roles/{role_id}/media-profile",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, description = "Media profile removed"),
+        (status = 404, description = "No media profile configured")
+    )
+)]
+async fn delete_media_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    if !media_profiles::delete_profile(&db, role_id).await? {
+        return Err(AppError::NotFound(
+            "No media profile configured".to_string(),
+        ));
+    }
+    media_profiles::spawn_sync(&state, SyncTarget::Role(role_id));
+    Ok(Json(
+        serde_json::json!({"message": "Media profile removed"}),
+    ))
+}
+
+/// Write the media profiles of a role's members into Jellyfin now
+#[utoipa::path(
+    post,
+    path = "/api/roles/{role_id}/media-profile/sync",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, body = Vec<SyncResult>),
+        (status = 400, description = "Jellyfin is not configured"),
+        (status = 502, description = "Jellyfin returned an error"),
+        (status = 503, description = "Jellyfin could not be reached")
+    )
+)]
+async fn sync_media_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+) -> Result<Json<Vec<SyncResult>>> {
+    let db = state.get_db().await?;
+    // Verify role exists
+    let _ = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    Ok(Json(media_profiles::sync_role(&state, role_id).await?))
+}
+
+/// Get the provisioning profile of a role
+#[utoipa::path(
+    get,
+    path = "/api/roles/{role_id}/provisioning",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, body = ProvisioningProfile),
+        (status = 404, description = "Role not found or no provisioning profile configured")
+    )
+)]
+async fn get_provisioning_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesView>,
+) -> Result<Json<ProvisioningProfile>> {
+    let db = state.get_db().await?;
+    let profile = provisioning::get_profile(&db, role_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No provisioning profile configured".to_string()))?;
+    Ok(Json(profile))
+}
+
+/// Set the provisioning profile of a role
+///
+/// Members approved from now on get accounts in the listed apps.
+#[utoipa::path(
+    put,
+    path = "/api/roles/{role_id}/provisioning",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    request_body = ProvisioningProfile,
+    responses(
+        (status = 200, body = ProvisioningProfile),
+        (status = 400, description = "Unsupported app"),
+        (status = 404, description = "Role not found")
+    )
+)]
+async fn set_provisioning_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+    Json(profile): Json<ProvisioningProfile>,
+) -> Result<Json<ProvisioningProfile>> {
+    let db = state.get_db().await?;
+    // Verify role exists
+    let _ = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    Ok(Json(
+        provisioning::set_profile(&db, role_id, &profile).await?,
+    ))
+}
+
+/// Remove the provisioning profile of a role
+///
+/// Accounts already created are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/roles/{role_id}/provisioning",
+    tag = "Roles",
+    params(("role_id" = i64, Path, description = "Role ID")),
+    responses(
+        (status = 200, description = "Provisioning profile removed"),
+        (status = 404, description = "No provisioning profile configured")
+    )
+)]
+async fn delete_provisioning_profile(
+    State(state): State<AppState>,
+    Path(role_id): Path<i64>,
+    _auth: Authorized<RolesManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    if !provisioning::delete_profile(&db, role_id).await? {
+        return Err(AppError::NotFound(
+            "No provisioning profile configured".to_string(),
+        ));
+    }
+    Ok(Json(
+        serde_json::json!({"message": "Provisioning profile removed"}),
+    ))
+}
+
 /// Get permissions for a specific role
 #[utoipa::path(
     get,
@@ -0,0 +1,243 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Authorized, SecurityManage};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::ip_ban;
+use crate::models::prelude::*;
+use crate::services::ip_bans::{self, ExportFormat, FailedLoginSource};
+use crate::state::AppState;
+
+/// Longest failed sign-in history that can be requested, in hours
+const MAX_HOURS: i64 = 24 * 30;
+
+/// Longest timed manual ban, in minutes (a year)
+const MAX_BAN_MINUTES: i64 = 60 * 24 * 365;
+
+/// Create security routes
+pub fn security_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/bans", get(list_bans).post(create_ban))
+        .route("/bans/export", get(export_bans))
+        .route("/bans/{ban_id}", delete(delete_ban))
+        .route("/failed-logins", get(failed_logins))
+        .with_state(state)
+}
+
+/// Manual ban of an address
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateBanRequest {
+    pub ip_address: String,
+    pub reason: Option<String>,
+    /// Minutes until the ban lifts, up to a year; omit to ban until removed
+    pub duration_minutes: Option<i64>,
+}
+
+/// Format of the exported ban list
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ExportParams {
+    /// `plain` (default), `nginx` or `crowdsec`
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub format: ExportFormat,
+}
+
+/// Period of failed sign-ins to summarize
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct FailedLoginParams {
+    /// Hours to look back (default 24, at most 720)
+    pub hours: Option<i64>,
+}
+
+/// List active IP bans
+#[utoipa::path(
+    get,
+    path = "/api/security/bans",
+    tag = "Security",
+    responses((status = 200, body = Vec<ip_ban::Model>))
+)]
+async fn list_bans(
+    State(state): State<AppState>,
+    _auth: Authorized<SecurityManage>,
+) -> Result<Json<Vec<ip_ban::Model>>> {
+    let db = state.get_db().await?;
+    Ok(Json(ip_bans::list_bans(&db, Utc::now()).await?))
+}
+
+/// Ban an address
+///
+/// Replaces any existing ban on the address. An admin cannot ban the address
+/// they are connecting from.
+#[utoipa::path(
+    post,
+    path = "/api/security/bans",
+    tag = "Security",
+    request_body = CreateBanRequest,
+    responses(
+        (status = 201, body = ip_ban::Model),
+        (status = 400, description = "Invalid address or duration, or the caller's own address")
+    )
+)]
+async fn create_ban(
+    State(state): State<AppState>,
+    auth: Authorized<SecurityManage>,
+    headers: HeaderMap,
+    Json(request): Json<CreateBanRequest>,
+) -> Result<(StatusCode, Json<ip_ban::Model>)> {
+    let db = state.get_db().await?;
+    let ip = ip_bans::parse_ip(&request.ip_address)?;
+    if ip_bans::client_ip(&headers).and_then(|own| ip_bans::parse_ip(&own).ok()) == Some(ip.clone())
+    {
+        return Err(AppError::BadRequest(
+            "You cannot ban the address you are connected from".to_string(),
+        ));
+    }
+    let expires_at = match request.duration_minutes {
+        Some(minutes) if !(1..=MAX_BAN_MINUTES).contains(&minutes) => {
+            return Err(AppError::BadRequest(format!(
+                "duration_minutes must be between 1 and {}",
+                MAX_BAN_MINUTES
+            )))
+        }
+        Some(minutes) => Some(Utc::now() + Duration::minutes(minutes)),
+        None => None,
+    };
+    let reason = request
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+
+    let created = ip_bans::ban(
+        &db,
+        &ip,
+        ip_bans::SOURCE_MANUAL,
+        reason,
+        None,
+        Some(auth.user_id()),
+        expires_at,
+    )
+    .await?;
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::IpBanned,
+            ResourceType::System,
+            Some(created.ip_address.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "source": ip_bans::SOURCE_MANUAL,
+                "reason": created.reason,
+                "expires_at": created.expires_at,
+            })),
+            None,
+            None,
+        )
+        .await;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Lift a ban
+#[utoipa::path(
+    delete,
+    path = "/api/security/bans/{ban_id}",
+    tag = "Security",
+    params(("ban_id" = i64, Path, description = "Ban ID")),
+    responses(
+        (status = 204, description = "Ban lifted"),
+        (status = 404, description = "Ban not found")
+    )
+)]
+async fn delete_ban(
+    State(state): State<AppState>,
+    Path(ban_id): Path<i64>,
+    auth: Authorized<SecurityManage>,
+) -> Result<StatusCode> {
+    let db = state.get_db().await?;
+    let ban = IpBan::find_by_id(ban_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Ban {} not found", ban_id)))?;
+    IpBan::delete_by_id(ban.id).exec(&db).await?;
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::IpUnbanned,
+            ResourceType::System,
+            Some(ban.ip_address.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "source": ban.source })),
+            None,
+            None,
+        )
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Export active bans for ingress-level blocking
+///
+/// `nginx` gives a value for the NGINX ingress
+/// `nginx.ingress.kubernetes.io/denylist-source-range` annotation, `crowdsec`
+/// a JSON file for `cscli decisions import -i bans.json`, and `plain` one
+/// address per line.
+#[utoipa::path(
+    get,
+    path = "/api/security/bans/export",
+    tag = "Security",
+    params(ExportParams),
+    responses(
+        (status = 200, description = "Ban list in the requested format", body = String),
+        (status = 400, description = "Unknown format")
+    )
+)]
+async fn export_bans(
+    State(state): State<AppState>,
+    _auth: Authorized<SecurityManage>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let now = Utc::now();
+    let bans = ip_bans::list_bans(&db, now).await?;
+    Ok((
+        [(header::CONTENT_TYPE, params.format.content_type())],
+        ip_bans::export(&bans, params.format, now),
+    )
+        .into_response())
+}
+
+/// Failed sign-ins per address, most attempts first
+#[utoipa::path(
+    get,
+    path = "/api/security/failed-logins",
+    tag = "Security",
+    params(FailedLoginParams),
+    responses((status = 200, body = Vec<FailedLoginSource>))
+)]
+async fn failed_logins(
+    State(state): State<AppState>,
+    _auth: Authorized<SecurityManage>,
+    Query(params): Query<FailedLoginParams>,
+) -> Result<Json<Vec<FailedLoginSource>>> {
+    let hours = params.hours.unwrap_or(24);
+    if !(1..=MAX_HOURS).contains(&hours) {
+        return Err(AppError::BadRequest(format!(
+            "hours must be between 1 and {}",
+            MAX_HOURS
+        )));
+    }
+    let db = state.get_db().await?;
+    let now = Utc::now();
+    Ok(Json(
+        ip_bans::failed_login_sources(&db, now - Duration::hours(hours), now).await?,
+    ))
+}
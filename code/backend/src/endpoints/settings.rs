@@ -14,11 +14,17 @@ use crate::error::{AppError, Result};
 use crate::middleware::permissions::{Authorized, SettingsManage, SettingsView};
 use crate::models::prelude::*;
 use crate::models::system_setting;
+use crate::services::notification::routing::validate_timezone;
+use crate::services::runtime_config;
+use crate::services::{
+    access_denials, access_reviews, app_schedules, backup, crowdsec, i18n, ip_bans, lockout,
+    log_archive, role_expiry, storage_roots, trash, webdav,
+};
 use crate::state::{AppState, DbConn};
 
 /// Default settings values
-static DEFAULT_SETTINGS: Lazy<HashMap<&'static str, (&'static str, &'static str)>> =
-    Lazy::new(|| {
+static DEFAULT_SETTINGS: Lazy<HashMap<&'static str, (&'static str, &'static str)>> = Lazy::new(
+    || {
         let mut m = HashMap::new();
         m.insert(
             "registration_enabled",
@@ -31,13 +37,438 @@ static DEFAULT_SETTINGS: Lazy<HashMap<&'static str, (&'static str, &'static str)
             "registration_require_approval",
             ("true", "Require admin approval for new registrations"),
         );
+        m.insert(
+            "wan_health_targets",
+            (
+                "1.1.1.1:443,8.8.8.8:443",
+                "Comma-separated host:port targets for WAN latency and packet-loss checks",
+            ),
+        );
+        m.insert(
+            "wan_health_speedtest_enabled",
+            (
+                "false",
+                "Periodically run a download speed test from inside the cluster",
+            ),
+        );
+        m.insert(
+            "wan_health_speedtest_url",
+            (
+                "https://speed.cloudflare.com/__down?bytes=25000000",
+                "File downloaded to measure WAN throughput",
+            ),
+        );
+        m.insert(
+            "energy_price_per_kwh",
+            ("0.25", "Electricity price per kWh used for cost estimates"),
+        );
+        m.insert(
+            "energy_currency",
+            ("EUR", "Currency shown with energy cost estimates"),
+        );
+        m.insert(
+            "energy_default_idle_watts",
+            ("10", "Idle power draw of nodes without a power profile"),
+        );
+        m.insert(
+            "energy_default_max_watts",
+            (
+                "40",
+                "Full-load power draw of nodes without a power profile",
+            ),
+        );
+        m.insert(
+            "energy_power_query",
+            (
+                "",
+                "PromQL returning measured power in watts per node (instance label); empty uses the wattage model",
+            ),
+        );
+        m.insert(
+            "sensor_cpu_warning_celsius",
+            (
+                "80",
+                "CPU temperature at which node sensors report a warning",
+            ),
+        );
+        m.insert(
+            "sensor_cpu_critical_celsius",
+            (
+                "90",
+                "CPU temperature at which a critical notification is sent",
+            ),
+        );
+        m.insert(
+            "sensor_disk_warning_celsius",
+            (
+                "50",
+                "Disk temperature at which node sensors report a warning",
+            ),
+        );
+        m.insert(
+            "sensor_disk_critical_celsius",
+            (
+                "60",
+                "Disk temperature at which a critical notification is sent",
+            ),
+        );
+        m.insert(
+            "anomaly_detection_enabled",
+            (
+                "true",
+                "Flag deviations of app CPU, memory and restarts from their baseline",
+            ),
+        );
+        m.insert(
+            "anomaly_default_sensitivity",
+            (
+                "medium",
+                "Anomaly sensitivity for apps without an override: off, low, medium or high",
+            ),
+        );
+        m.insert(
+            "log_level",
+            (
+                "",
+                "Backend log level (trace, debug, info, warn, error); empty uses KUBARR_LOG_LEVEL",
+            ),
+        );
+        m.insert(
+            "frontend_url",
+            (
+                "",
+                "URL the backend proxies the web UI from; empty uses KUBARR_FRONTEND_URL",
+            ),
+        );
+        m.insert(
+            "external_url",
+            (
+                "",
+                "URL users reach Kubarr at, used for links in notifications, emails and OAuth redirects; empty uses KUBARR_EXTERNAL_URL",
+            ),
+        );
+        m.insert(
+            "victoriametrics_url",
+            (
+                "",
+                "VictoriaMetrics base URL for metrics queries; empty uses the in-cluster service",
+            ),
+        );
+        m.insert(
+            "http_proxy",
+            (
+                "",
+                "Proxy for outbound HTTP requests, e.g. http://proxy.lan:3128; empty uses HTTP_PROXY",
+            ),
+        );
+        m.insert(
+            "https_proxy",
+            (
+                "",
+                "Proxy for outbound HTTPS requests; empty uses HTTPS_PROXY",
+            ),
+        );
+        m.insert(
+            "no_proxy",
+            (
+                "",
+                "Comma-separated hosts, domains (.example.com) and CIDRs reached without a proxy; empty uses NO_PROXY",
+            ),
+        );
+        m.insert(
+            "notification_test_interval_seconds",
+            (
+                "60",
+                "Minimum seconds between a user's test notifications on one channel",
+            ),
+        );
+        m.insert(
+            "app_base_domain",
+            (
+                "",
+                "Domain apps with subdomain routing are served under, e.g. apps.example.com",
+            ),
+        );
+        m.insert(
+            "session_cookie_domain",
+            (
+                "",
+                "Parent domain the session cookie is scoped to; must cover app_base_domain for subdomain routing",
+            ),
+        );
+        m.insert(
+            "verification_resend_interval_seconds",
+            (
+                "60",
+                "Minimum seconds between verification codes sent to one destination",
+            ),
+        );
+        m.insert(
+            "idle_suspend_enabled",
+            (
+                "false",
+                "Scale apps to zero after idle_suspend_days without access; they start again on their next request",
+            ),
+        );
+        m.insert(
+            "idle_suspend_days",
+            ("14", "Days without access before an idle app is suspended"),
+        );
+        m.insert(
+            storage_roots::STORAGE_ROOTS_SETTING,
+            (
+                "",
+                "JSON list of named storage roots with protected folders and per-role permissions; empty serves KUBARR_STORAGE_PATH only",
+            ),
+        );
+        m.insert(
+            backup::BACKUP_SCHEDULE,
+            (
+                "",
+                "Cron expression (UTC) scheduled backups run on, e.g. '0 3 * * *'; empty turns them off",
+            ),
+        );
+        m.insert(
+            backup::BACKUP_RETENTION_COUNT,
+            ("7", "Number of scheduled backups kept in KUBARR_BACKUP_DIR"),
+        );
+        m.insert(
+            trash::TRASH_RETENTION_DAYS,
+            (
+                "30",
+                "Days items deleted from storage stay in the trash; 0 keeps them until the trash is emptied",
+            ),
+        );
+        m.insert(
+            webdav::WEBDAV_ENABLED,
+            (
+                "false",
+                "Serve the storage roots over WebDAV at /dav/ for users signing in with their password",
+            ),
+        );
+        m.insert(
+            log_archive::LOG_ARCHIVE_ENABLED,
+            (
+                "false",
+                "Export each day of the installed apps' logs from VictoriaLogs to the log archive",
+            ),
+        );
+        m.insert(
+            log_archive::LOG_ARCHIVE_RETENTION_DAYS,
+            ("365", "Days log archives are kept; 0 keeps them forever"),
+        );
+        m.insert(
+            ip_bans::IP_BAN_THRESHOLD,
+            (
+                "10",
+                "Failed sign-ins from one IP address that ban it; 0 turns automatic bans off",
+            ),
+        );
+        m.insert(
+            ip_bans::IP_BAN_WINDOW_MINUTES,
+            ("15", "Minutes failed sign-ins are counted over for IP bans"),
+        );
+        m.insert(
+            ip_bans::IP_BAN_DURATION_MINUTES,
+            ("60", "Minutes an automatic IP ban lasts"),
+        );
+        m.insert(
+            lockout::LOCKOUT_THRESHOLD,
+            (
+                "5",
+                "Failed sign-ins in a row that lock an account, and from one IP address that slow it down; 0 turns lockout off",
+            ),
+        );
+        m.insert(
+            lockout::LOCKOUT_DURATION_MINUTES,
+            (
+                "5",
+                "Minutes the first account lock lasts; it doubles with every further failure",
+            ),
+        );
+        m.insert(
+            lockout::LOCKOUT_MAX_MINUTES,
+            ("1440", "Minutes an account lock lasts at most"),
+        );
+        m.insert(
+            access_denials::ACCESS_DENIED_ALERT_THRESHOLD,
+            (
+                "10",
+                "Permission denials of one user within 10 minutes that send an access_denied notification; 0 turns it off",
+            ),
+        );
+        m.insert(
+            access_reviews::ACCESS_REVIEW_INTERVAL_MONTHS,
+            (
+                "0",
+                "Months between automatic access reviews; 0 only allows starting them by hand",
+            ),
+        );
+        m.insert(
+            access_reviews::ACCESS_REVIEW_DEADLINE_DAYS,
+            ("14", "Days reviewers have to decide an access review"),
+        );
+        m.insert(
+            access_reviews::ACCESS_REVIEW_OVERDUE_ACTION,
+            (
+                "flag",
+                "What happens to users left undecided when an access review is due: flag or suspend",
+            ),
+        );
+        m.insert(
+            role_expiry::ROLE_EXPIRY_NOTICE_HOURS,
+            (
+                "72",
+                "Hours before a time-boxed role assignment ends that the user and admins get a role_expiring notification; 0 turns it off",
+            ),
+        );
+        m.insert(
+            crowdsec::CROWDSEC_LAPI_URL,
+            (
+                "",
+                "CrowdSec Local API whose ban decisions are enforced, e.g. http://crowdsec-service.crowdsec:8080; needs KUBARR_CROWDSEC_LAPI_KEY",
+            ),
+        );
+        m.insert(
+            i18n::DEFAULT_LOCALE_SETTING,
+            (
+                "en",
+                "Locale of notifications for users without a locale preference: en, nl or de",
+            ),
+        );
+        m.insert(
+            app_schedules::DEFAULT_TIMEZONE_SETTING,
+            (
+                "UTC",
+                "IANA time zone app access schedules are read in for users without a time zone preference",
+            ),
+        );
         m
-    });
+    },
+);
+
+/// Environment variables read once at startup, listed in the settings schema
+const STATIC_ENV_KEYS: &[(&str, &str)] = &[
+    ("KUBARR_API_PORT", "Port the API listens on"),
+    ("KUBARR_DATABASE_URL", "PostgreSQL connection string"),
+    ("KUBARR_IN_CLUSTER", "Use in-cluster Kubernetes API access"),
+    (
+        "KUBARR_KUBECONFIG_PATH",
+        "Kubeconfig used outside the cluster",
+    ),
+    (
+        "KUBARR_DEFAULT_NAMESPACE",
+        "Default namespace for media applications",
+    ),
+    ("KUBARR_OAUTH2_ENABLED", "Enable the OAuth2 provider"),
+    ("KUBARR_OAUTH2_ISSUER_URL", "OAuth2 issuer URL"),
+    ("KUBARR_CHARTS_DIR", "Directory of bundled Helm charts"),
+    ("KUBARR_CHARTS_SYNC_INTERVAL", "Seconds between chart syncs"),
+    ("KUBARR_HELM_BINARY", "Helm binary used to install apps"),
+    (
+        "KUBARR_STATIC_DIR",
+        "Directory of self-hosted static assets",
+    ),
+    (
+        "KUBARR_AUDIT_INTEGRITY_KEY",
+        "HMAC key for tamper-evident audit chaining",
+    ),
+    (
+        "KUBARR_CORS_ALLOWED_ORIGINS",
+        "Comma-separated origins browsers may call the API from; unset allows any",
+    ),
+    (
+        "KUBARR_AUTH_FAILURE_LOG",
+        "File failed sign-ins are appended to for Fail2ban or CrowdSec",
+    ),
+    (
+        "KUBARR_CROWDSEC_LAPI_KEY",
+        "Bouncer key for the CrowdSec Local API",
+    ),
+    (
+        "KUBARR_ENCRYPTION_KEY",
+        "32-byte key, base64 or hex, that stored provider credentials are encrypted with",
+    ),
+    (
+        "KUBARR_ENCRYPTION_KEY_SECRET",
+        "Kubernetes secret ([namespace/]name) whose encryption-key entry holds the encryption key",
+    ),
+    (
+        "KUBARR_MAX_BODY_SIZE",
+        "Largest request body accepted by the API, e.g. 2m; 0 disables the limit",
+    ),
+    (
+        "KUBARR_MAX_AUTH_BODY_SIZE",
+        "Largest request body accepted by sign-in and setup routes",
+    ),
+    (
+        "KUBARR_MAX_UPLOAD_SIZE",
+        "Largest WebDAV upload or request to a proxied app",
+    ),
+    (
+        "KUBARR_COMPRESSION",
+        "Response encodings offered, br and gzip, or none",
+    ),
+    (
+        "KUBARR_COMPRESSION_MIN_SIZE",
+        "Smallest response in bytes that is compressed",
+    ),
+    (
+        "KUBARR_TLS_CERT_FILE",
+        "PEM certificate chain served when the backend terminates TLS",
+    ),
+    (
+        "KUBARR_TLS_KEY_FILE",
+        "PEM private key of the TLS certificate",
+    ),
+    (
+        "KUBARR_ACME_DOMAINS",
+        "Domains to get a Let's Encrypt certificate for",
+    ),
+    (
+        "KUBARR_ACME_EMAIL",
+        "Contact address given to Let's Encrypt",
+    ),
+    (
+        "KUBARR_ACME_CACHE_DIR",
+        "Where the ACME account and certificates are kept",
+    ),
+    (
+        "KUBARR_ACME_STAGING",
+        "Use Let's Encrypt's staging environment",
+    ),
+    (
+        "KUBARR_HTTP_REDIRECT_PORT",
+        "Plain HTTP port redirecting to HTTPS",
+    ),
+    (
+        "KUBARR_BACKUP_DIR",
+        "Directory scheduled backups are written to",
+    ),
+    (
+        "KUBARR_BACKUP_PASSPHRASE",
+        "Passphrase scheduled backups encrypt secrets with",
+    ),
+];
+
+/// Environment variables a dynamic setting falls back to
+fn env_fallback(key: &str) -> Option<&'static str> {
+    match key {
+        runtime_config::LOG_LEVEL => Some("KUBARR_LOG_LEVEL"),
+        runtime_config::FRONTEND_URL => Some("KUBARR_FRONTEND_URL"),
+        runtime_config::EXTERNAL_URL => Some("KUBARR_EXTERNAL_URL"),
+        runtime_config::HTTP_PROXY => Some("HTTP_PROXY"),
+        runtime_config::HTTPS_PROXY => Some("HTTPS_PROXY"),
+        runtime_config::NO_PROXY => Some("NO_PROXY"),
+        _ => None,
+    }
+}
 
 /// Create settings routes
 pub fn settings_routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(list_settings))
+        .route("/schema", get(settings_schema))
         .route("/{key}", get(get_setting).put(update_setting))
         .with_state(state)
 }
@@ -63,6 +494,30 @@ pub struct SettingsResponse {
     pub settings: HashMap<String, SettingResponse>,
 }
 
+/// When a change takes effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingReload {
+    /// Applied immediately
+    Live,
+    /// Requires a pod restart
+    Restart,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SettingSchema {
+    /// Setting key, or environment variable name for static configuration
+    pub key: String,
+    pub description: String,
+    /// Default value; empty when it comes from the environment
+    pub default: Option<String>,
+    pub reload: SettingReload,
+    /// Whether the value is stored as a system setting (editable via the API)
+    pub editable: bool,
+    /// Environment variable used when the setting is empty
+    pub env_fallback: Option<String>,
+}
+
 // ============================================================================
 // Endpoint Handlers
 // ============================================================================
@@ -114,6 +569,45 @@ async fn list_settings(
     Ok(Json(SettingsResponse { settings }))
 }
 
+/// Describe every configuration key and whether changes need a restart
+#[utoipa::path(
+    get,
+    path = "/api/settings/schema",
+    tag = "Settings",
+    responses(
+        (status = 200, body = Vec<SettingSchema>)
+    )
+)]
+async fn settings_schema(_auth: Authorized<SettingsView>) -> Result<Json<Vec<SettingSchema>>> {
+    let mut schema: Vec<SettingSchema> = DEFAULT_SETTINGS
+        .iter()
+        .map(|(key, (default_value, description))| SettingSchema {
+            key: key.to_string(),
+            description: description.to_string(),
+            default: Some(default_value.to_string()).filter(|d| !d.is_empty()),
+            reload: SettingReload::Live,
+            editable: true,
+            env_fallback: env_fallback(key).map(str::to_string),
+        })
+        .collect();
+    schema.sort_by(|a, b| a.key.cmp(&b.key));
+
+    schema.extend(
+        STATIC_ENV_KEYS
+            .iter()
+            .map(|(key, description)| SettingSchema {
+                key: key.to_string(),
+                description: description.to_string(),
+                default: None,
+                reload: SettingReload::Restart,
+                editable: false,
+                env_fallback: None,
+            }),
+    );
+
+    Ok(Json(schema))
+}
+
 /// Get a specific setting (requires settings.view permission)
 #[utoipa::path(
     get,
@@ -179,6 +673,67 @@ async fn update_setting(
         .get(key.as_str())
         .ok_or_else(|| AppError::BadRequest(format!("Unknown setting key '{}'", key)))?;
 
+    if runtime_config::is_dynamic(&key) {
+        runtime_config::validate(&key, &data.value)?;
+    }
+    if key == storage_roots::STORAGE_ROOTS_SETTING {
+        storage_roots::parse_roots(&data.value)?;
+    }
+    if key == backup::BACKUP_SCHEDULE {
+        backup::parse_schedule(&data.value)?;
+    }
+    if key == backup::BACKUP_RETENTION_COUNT {
+        backup::parse_retention(&data.value)?;
+    }
+    if key == trash::TRASH_RETENTION_DAYS {
+        trash::parse_retention(&data.value)?;
+    }
+    if key == log_archive::LOG_ARCHIVE_RETENTION_DAYS {
+        log_archive::parse_retention(&data.value)?;
+    }
+    if key == i18n::DEFAULT_LOCALE_SETTING {
+        i18n::parse_locale(&data.value)?;
+    }
+    if key == app_schedules::DEFAULT_TIMEZONE_SETTING {
+        validate_timezone(&data.value)?;
+    }
+    if [
+        ip_bans::IP_BAN_THRESHOLD,
+        ip_bans::IP_BAN_WINDOW_MINUTES,
+        ip_bans::IP_BAN_DURATION_MINUTES,
+    ]
+    .contains(&key.as_str())
+    {
+        ip_bans::parse_setting(&key, &data.value)?;
+    }
+    if [
+        lockout::LOCKOUT_THRESHOLD,
+        lockout::LOCKOUT_DURATION_MINUTES,
+        lockout::LOCKOUT_MAX_MINUTES,
+    ]
+    .contains(&key.as_str())
+    {
+        lockout::parse_setting(&key, &data.value)?;
+    }
+    if key == crowdsec::CROWDSEC_LAPI_URL {
+        crowdsec::parse_lapi_url(&data.value)?;
+    }
+    if key == access_denials::ACCESS_DENIED_ALERT_THRESHOLD {
+        access_denials::parse_threshold(&data.value)?;
+    }
+    if key == role_expiry::ROLE_EXPIRY_NOTICE_HOURS {
+        role_expiry::parse_notice_hours(&data.value)?;
+    }
+    if [
+        access_reviews::ACCESS_REVIEW_INTERVAL_MONTHS,
+        access_reviews::ACCESS_REVIEW_DEADLINE_DAYS,
+        access_reviews::ACCESS_REVIEW_OVERDUE_ACTION,
+    ]
+    .contains(&key.as_str())
+    {
+        access_reviews::parse_setting(&key, &data.value)?;
+    }
+
     let now = Utc::now();
 
     // Check if setting exists
@@ -201,6 +756,10 @@ async fn update_setting(
         new_setting.insert(&db).await?
     };
 
+    if runtime_config::is_dynamic(&key) {
+        runtime_config::reload(&db).await?;
+    }
+
     Ok(Json(SettingResponse {
         key: setting.key,
         value: setting.value,
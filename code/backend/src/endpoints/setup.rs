@@ -306,6 +306,7 @@ async fn initialize_setup(
     let user_role_model = user_role::ActiveModel {
         user_id: Set(created_user.id),
         role_id: Set(admin_role.id),
+        ..Default::default()
     };
     user_role_model
         .insert(&db)
@@ -601,7 +602,7 @@ async fn browse_setup_directory(
     }
 
     // Sort alphabetically
-    directories.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    directories.sort_by_key(|d| d.name.to_lowercase());
 
     Ok(Json(BrowseSetupResponse {
         path: host_path,
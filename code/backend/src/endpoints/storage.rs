@@ -1,35 +1,60 @@
 use axum::{
     body::Body,
     extract::{Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
-    Json, Router,
+    routing::{delete, get, post, put},
+    Extension, Json, Router,
 };
+use futures_util::stream::{self, StreamExt};
 use sea_orm::EntityTrait;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio_util::io::ReaderStream;
 
 use crate::error::{AppError, Result};
 use crate::middleware::permissions::{
-    Authorized, StorageDelete, StorageDownload, StorageView, StorageWrite,
+    Authorized, Permission, SettingsManage, StorageDelete, StorageDownload, StorageView,
+    StorageWrite, UsersManage,
 };
+use crate::middleware::AuthenticatedUser;
 use crate::models::prelude::*;
+use crate::services::access::get_user_permissions;
+use crate::services::checksums::{self, ChecksumAlgorithm, ChecksumResult, FileVersion};
+use crate::services::homes::{self, HomeInfo, HomeQuotaOverride};
+use crate::services::previews::{PreviewSize, PREVIEWS};
+use crate::services::shares;
+use crate::services::storage_roots::{self, StorageRoot, StorageRootInfo, DEFAULT_ROOT};
+use crate::services::trash::{self, TrashEntry, TRASH_DIR};
 use crate::state::{AppState, DbConn};
 
-/// Protected top-level folders that cannot be deleted
-const PROTECTED_FOLDERS: &[&str] = &["downloads", "media"];
-
 /// Create storage routes
 pub fn storage_routes(state: AppState) -> Router {
     Router::new()
+        .route("/roots", get(list_roots))
         .route("/browse", get(browse_directory))
         .route("/stats", get(get_storage_stats))
         .route("/file-info", get(get_file_info))
         .route("/mkdir", post(create_directory))
         .route("/delete", delete(delete_path))
         .route("/download", get(download_file))
+        .route("/preview", get(preview_file))
+        .route("/checksum", get(get_checksums).post(request_checksum))
+        .route("/trash", get(list_trash).delete(empty_trash))
+        .route("/trash/{id}", delete(purge_trash_item))
+        .route("/trash/{id}/restore", post(restore_trash_item))
+        .route("/shares", get(get_shares).post(create_share))
+        .route(
+            "/shares/password",
+            put(set_share_password).delete(clear_share_password),
+        )
+        .route("/shares/sync", post(force_share_sync))
+        .route("/shares/{root}", delete(delete_share))
+        .route("/homes", get(list_homes))
+        .route("/homes/scan", post(scan_homes))
+        .route("/homes/{root}/{user_id}", put(set_home_quota))
         .with_state(state)
 }
 
@@ -37,15 +62,100 @@ pub fn storage_routes(state: AppState) -> Router {
 // Request/Response Types
 // ============================================================================
 
+/// Pages with more items than this are streamed instead of serialized at once
+const STREAM_THRESHOLD: usize = 1000;
+
+/// Items serialized per chunk of a streamed listing
+const STREAM_CHUNK_SIZE: usize = 250;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowseSort {
+    #[default]
+    Name,
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct BrowseQuery {
     #[serde(default)]
     pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+    /// Number of entries to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of entries to return (default: all)
+    pub limit: Option<usize>,
+    /// Sort key; directories always come first
+    #[serde(default)]
+    pub sort: BrowseSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Skip per-entry metadata: items only have name, path and type
+    #[serde(default)]
+    pub light: bool,
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct PathQuery {
     pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct DeleteQuery {
+    pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+    /// Delete right away instead of moving to the trash
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PreviewQuery {
+    pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+    #[serde(default)]
+    pub size: PreviewSize,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ChecksumRequest {
+    pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+    #[serde(default)]
+    pub algorithm: ChecksumAlgorithm,
+    /// Checksum to compare the result against, e.g. from a release page
+    pub expected: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ChecksumQuery {
+    pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
+    /// Checksum to compare the stored results against
+    pub expected: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct RootQuery {
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
@@ -54,17 +164,32 @@ pub struct FileInfo {
     pub path: String,
     #[serde(rename = "type")]
     pub file_type: String,
-    pub size: u64,
-    pub modified: String,
-    pub permissions: String,
+    /// Omitted in light listings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Omitted in light listings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// Omitted in light listings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<String>,
 }
 
+/// One page of a directory
+///
+/// `items` is the last field so large pages can be streamed after the rest.
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct DirectoryListing {
+    /// Storage root the listing belongs to
+    pub root: String,
     pub path: String,
     pub parent: Option<String>,
-    pub items: Vec<FileInfo>,
+    /// Number of entries in the directory
     pub total_items: usize,
+    pub offset: usize,
+    /// Offset of the next page, if any
+    pub next_offset: Option<usize>,
+    pub items: Vec<FileInfo>,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -78,26 +203,33 @@ pub struct StorageStats {
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct CreateDirectoryRequest {
     pub path: String,
+    /// Storage root (default: the first root)
+    pub root: Option<String>,
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-/// Get the configured storage path
-async fn get_storage_path(db: &DbConn) -> Result<PathBuf> {
-    // Check if storage is configured in DB
-    let db_path = SystemSetting::find_by_id("storage_path").one(db).await?;
-
-    // Use environment variable for the actual path inside the container
-    let storage_path = std::env::var("KUBARR_STORAGE_PATH").unwrap_or_else(|_| "/data".to_string());
-
-    let path = PathBuf::from(&storage_path);
+/// Get the directory of a storage root
+pub(crate) async fn get_storage_path(db: &DbConn, root: &StorageRoot) -> Result<PathBuf> {
+    let path = root.resolve();
 
     if path.exists() {
         return Ok(path);
     }
 
+    if root.name != DEFAULT_ROOT {
+        return Err(AppError::Internal(format!(
+            "Storage root '{}' is not mounted at {}",
+            root.name,
+            path.display()
+        )));
+    }
+
+    // Check if storage is configured in DB
+    let db_path = SystemSetting::find_by_id("storage_path").one(db).await?;
+
     // If no mount path but DB has a path, storage might not be mounted
     if db_path.is_some() {
         return Err(AppError::Internal(
@@ -111,8 +243,21 @@ async fn get_storage_path(db: &DbConn) -> Result<PathBuf> {
     ))
 }
 
+/// Resolve the storage root a request names and its directory, checking
+/// the caller's access to the root
+async fn storage_root(
+    db: &DbConn,
+    user_id: i64,
+    root: Option<&str>,
+    permission: &str,
+) -> Result<(StorageRoot, PathBuf)> {
+    let root = storage_roots::root_for(db, user_id, root, permission).await?;
+    let path = get_storage_path(db, &root).await?;
+    Ok((root, path))
+}
+
 /// Validate and resolve a requested path to prevent directory traversal
-fn validate_path(requested_path: &str, storage_path: &Path) -> Result<PathBuf> {
+pub(crate) fn validate_path(requested_path: &str, storage_path: &Path) -> Result<PathBuf> {
     let base_path = storage_path
         .canonicalize()
         .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
@@ -133,9 +278,46 @@ fn validate_path(requested_path: &str, storage_path: &Path) -> Result<PathBuf> {
         ));
     }
 
+    // The trash is only reachable through the trash endpoints
+    if resolved
+        .strip_prefix(&base_path)
+        .is_ok_and(trash::is_trash_path)
+    {
+        return Err(AppError::NotFound(format!(
+            "Path not found: {}",
+            requested_path
+        )));
+    }
+
     Ok(resolved)
 }
 
+/// Path of `file` relative to the canonical root directory, as used to key
+/// cached previews and stored checksums
+pub(crate) fn relative_to_root(file: &Path, base_path: &Path) -> Result<String> {
+    Ok(file
+        .strip_prefix(base_path)
+        .map_err(|_| AppError::Internal("Failed to calculate relative path".to_string()))?
+        .to_string_lossy()
+        .replace('\\', "/"))
+}
+
+/// Resolve a file for the preview and checksum endpoints
+fn resolve_file(requested_path: &str, storage_path: &Path) -> Result<(PathBuf, String)> {
+    let base_path = storage_path
+        .canonicalize()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
+    let file_path = validate_path(requested_path, storage_path)?;
+    if file_path.is_dir() {
+        return Err(AppError::BadRequest(format!(
+            "Path is a directory: {}",
+            requested_path
+        )));
+    }
+    let relative = relative_to_root(&file_path, &base_path)?;
+    Ok((file_path, relative))
+}
+
 /// Get information about a file or directory
 fn get_file_info_internal(file_path: &PathBuf, base_path: &PathBuf) -> Result<FileInfo> {
     let metadata = std::fs::metadata(file_path)
@@ -186,23 +368,134 @@ fn get_file_info_internal(file_path: &PathBuf, base_path: &PathBuf) -> Result<Fi
             .to_string()
             .replace('\\', "/"),
         file_type: file_type.to_string(),
-        size,
-        modified,
-        permissions,
+        size: Some(size),
+        modified: Some(modified),
+        permissions: Some(permissions),
     })
 }
 
+/// A directory entry collected for sorting, before per-entry metadata is read
+struct ListEntry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    /// Read only when sorting by size or modification time
+    metadata: Option<std::fs::Metadata>,
+}
+
+impl ListEntry {
+    fn new(entry: std::fs::DirEntry, with_metadata: bool) -> Self {
+        let path = entry.path();
+        // The entry's file type usually comes from the directory itself;
+        // only symlinks need a stat to know whether they point at a directory
+        let is_dir = match entry.file_type() {
+            Ok(t) if t.is_symlink() => path.is_dir(),
+            Ok(t) => t.is_dir(),
+            Err(_) => false,
+        };
+        let metadata = if with_metadata {
+            std::fs::metadata(&path).ok()
+        } else {
+            None
+        };
+        Self {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path,
+            is_dir,
+            metadata,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match &self.metadata {
+            Some(m) if !self.is_dir => m.len(),
+            _ => 0,
+        }
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        self.metadata.as_ref().and_then(|m| m.modified().ok())
+    }
+
+    /// Directories first, then by the sort key, then by name
+    fn compare(&self, other: &Self, sort: BrowseSort, order: SortOrder) -> Ordering {
+        let by_name = || {
+            self.name
+                .to_ascii_lowercase()
+                .cmp(&other.name.to_ascii_lowercase())
+        };
+        let by_key = match sort {
+            BrowseSort::Name => by_name(),
+            BrowseSort::Size => self.size().cmp(&other.size()).then_with(by_name),
+            BrowseSort::Modified => self.modified().cmp(&other.modified()).then_with(by_name),
+        };
+        other.is_dir.cmp(&self.is_dir).then(match order {
+            SortOrder::Asc => by_key,
+            SortOrder::Desc => by_key.reverse(),
+        })
+    }
+
+    /// The entry as listed, or `None` if its metadata cannot be read
+    fn info(&self, base_path: &Path, light: bool) -> Option<FileInfo> {
+        if !light {
+            return get_file_info_internal(&self.path, &base_path.to_path_buf()).ok();
+        }
+        Some(FileInfo {
+            name: self.name.clone(),
+            path: self
+                .path
+                .strip_prefix(base_path)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/"),
+            file_type: if self.is_dir { "directory" } else { "file" }.to_string(),
+            size: None,
+            modified: None,
+            permissions: None,
+        })
+    }
+}
+
 // ============================================================================
 // Endpoint Handlers
 // ============================================================================
 
+/// List the storage roots the current user can see
+#[utoipa::path(
+    get,
+    path = "/api/storage/roots",
+    tag = "Storage",
+    responses(
+        (status = 200, body = Vec<StorageRootInfo>)
+    )
+)]
+async fn list_roots(
+    State(state): State<AppState>,
+    auth: Authorized<StorageView>,
+) -> Result<Json<Vec<StorageRootInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        storage_roots::visible_roots(&db, auth.user_id()).await?,
+    ))
+}
+
 /// Browse a directory in the shared storage
+///
+/// Supports pagination with `offset`/`limit`, sorting, and a light mode
+/// that skips per-entry metadata. Pages of more than 1000 items are
+/// streamed.
 #[utoipa::path(
     get,
     path = "/api/storage/browse",
     tag = "Storage",
     params(
         ("path" = String, Query, description = "Directory path to browse"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+        ("offset" = Option<usize>, Query, description = "Number of entries to skip"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of entries to return (default: all)"),
+        ("sort" = Option<BrowseSort>, Query, description = "Sort by name, size or modified; directories come first"),
+        ("order" = Option<SortOrder>, Query, description = "asc or desc"),
+        ("light" = Option<bool>, Query, description = "Skip per-entry metadata (size, modified, permissions)"),
     ),
     responses(
         (status = 200, body = DirectoryListing)
@@ -211,10 +504,16 @@ fn get_file_info_internal(file_path: &PathBuf, base_path: &PathBuf) -> Result<Fi
 async fn browse_directory(
     State(state): State<AppState>,
     Query(query): Query<BrowseQuery>,
-    _auth: Authorized<StorageView>,
-) -> Result<Json<DirectoryListing>> {
+    auth: Authorized<StorageView>,
+) -> Result<Response> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageView::NAME,
+    )
+    .await?;
     let base_path = storage_path
         .canonicalize()
         .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
@@ -240,34 +539,24 @@ async fn browse_directory(
         )));
     }
 
-    // List directory contents
-    let mut items: Vec<FileInfo> = Vec::new();
-    let entries = std::fs::read_dir(&dir_path)
-        .map_err(|e| AppError::Forbidden(format!("Permission denied: {}", e)))?;
-
-    let mut entries_vec: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-
-    // Sort: directories first, then by name
-    entries_vec.sort_by(|a, b| {
-        let a_is_dir = a.file_type().map(|t| t.is_dir()).unwrap_or(false);
-        let b_is_dir = b.file_type().map(|t| t.is_dir()).unwrap_or(false);
-
-        match (a_is_dir, b_is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a
-                .file_name()
-                .to_ascii_lowercase()
-                .cmp(&b.file_name().to_ascii_lowercase()),
-        }
-    });
-
-    for entry in entries_vec {
-        let entry_path = entry.path();
-        if let Ok(info) = get_file_info_internal(&entry_path, &base_path) {
-            items.push(info);
-        }
-    }
+    // Collect the entries; metadata is only read here when sorting needs it
+    let with_metadata = query.sort != BrowseSort::Name;
+    let mut entries: Vec<ListEntry> = std::fs::read_dir(&dir_path)
+        .map_err(|e| AppError::Forbidden(format!("Permission denied: {}", e)))?
+        .filter_map(|e| e.ok())
+        .filter(|e| dir_path != base_path || e.file_name() != TRASH_DIR)
+        .map(|e| ListEntry::new(e, with_metadata))
+        .collect();
+    entries.sort_by(|a, b| a.compare(b, query.sort, query.order));
+
+    let total_items = entries.len();
+    let page: Vec<ListEntry> = entries
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+    let next_offset =
+        Some(query.offset.saturating_add(page.len())).filter(|next| *next < total_items);
 
     // Calculate relative path and parent
     let relative_path = if dir_path == base_path {
@@ -294,14 +583,54 @@ async fn browse_directory(
         }
     };
 
-    let total_items = items.len();
-
-    Ok(Json(DirectoryListing {
+    let mut listing = DirectoryListing {
+        root: root.name,
         path: relative_path,
         parent,
-        items,
         total_items,
-    }))
+        offset: query.offset,
+        next_offset,
+        items: Vec::new(),
+    };
+
+    if page.len() <= STREAM_THRESHOLD {
+        listing.items = page
+            .iter()
+            .filter_map(|entry| entry.info(&base_path, query.light))
+            .collect();
+        return Ok(Json(listing).into_response());
+    }
+
+    // Stream large pages: the listing without items, minus the closing
+    // `]}`, then the items chunk by chunk, reading metadata as they go
+    let head = serde_json::to_string(&listing)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize listing: {}", e)))?;
+    let head = head.trim_end_matches("]}").to_string();
+    let light = query.light;
+    let mut first = true;
+    let items = stream::iter(page)
+        .ready_chunks(STREAM_CHUNK_SIZE)
+        .map(move |chunk| {
+            let mut buf = String::new();
+            for info in chunk.iter().filter_map(|e| e.info(&base_path, light)) {
+                if !std::mem::take(&mut first) {
+                    buf.push(',');
+                }
+                // FileInfo only holds strings and numbers
+                buf.push_str(&serde_json::to_string(&info).unwrap_or_default());
+            }
+            buf
+        });
+    let body = stream::once(async move { head })
+        .chain(items)
+        .chain(stream::once(async { "]}".to_string() }))
+        .map(Ok::<_, std::io::Error>);
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(body),
+    )
+        .into_response())
 }
 
 /// Get storage usage statistics
@@ -309,16 +638,26 @@ async fn browse_directory(
     get,
     path = "/api/storage/stats",
     tag = "Storage",
+    params(
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+    ),
     responses(
         (status = 200, body = StorageStats)
     )
 )]
 async fn get_storage_stats(
     State(state): State<AppState>,
-    _auth: Authorized<StorageView>,
+    Query(query): Query<RootQuery>,
+    auth: Authorized<StorageView>,
 ) -> Result<Json<StorageStats>> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (_, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageView::NAME,
+    )
+    .await?;
 
     if !storage_path.exists() {
         return Err(AppError::NotFound("Storage path not found".to_string()));
@@ -383,6 +722,7 @@ async fn get_storage_stats(
     tag = "Storage",
     params(
         ("path" = String, Query, description = "File or directory path"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
     ),
     responses(
         (status = 200, body = FileInfo)
@@ -391,10 +731,16 @@ async fn get_storage_stats(
 async fn get_file_info(
     State(state): State<AppState>,
     Query(query): Query<PathQuery>,
-    _auth: Authorized<StorageView>,
+    auth: Authorized<StorageView>,
 ) -> Result<Json<FileInfo>> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (_, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageView::NAME,
+    )
+    .await?;
     let base_path = storage_path
         .canonicalize()
         .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
@@ -424,11 +770,17 @@ async fn get_file_info(
 )]
 async fn create_directory(
     State(state): State<AppState>,
-    _auth: Authorized<StorageWrite>,
+    auth: Authorized<StorageWrite>,
     Json(request): Json<CreateDirectoryRequest>,
 ) -> Result<Json<serde_json::Value>> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        request.root.as_deref(),
+        StorageWrite::NAME,
+    )
+    .await?;
     let base_path = storage_path
         .canonicalize()
         .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
@@ -444,12 +796,20 @@ async fn create_directory(
         ));
     }
 
+    if trash::is_trash_path(Path::new(clean_path)) {
+        return Err(AppError::BadRequest(format!(
+            "{} is reserved for the trash",
+            TRASH_DIR
+        )));
+    }
+
     if dir_path.exists() {
         return Err(AppError::BadRequest(format!(
             "Path already exists: {}",
             request.path
         )));
     }
+    homes::enforce(&db, &root, Path::new(clean_path), 0).await?;
 
     std::fs::create_dir_all(&dir_path)
         .map_err(|e| AppError::Internal(format!("Failed to create directory: {}", e)))?;
@@ -468,12 +828,16 @@ async fn create_directory(
 }
 
 /// Delete a file or empty directory (requires storage.delete permission)
+///
+/// The item is moved to the root's trash unless `permanent` is set.
 #[utoipa::path(
     delete,
     path = "/api/storage/delete",
     tag = "Storage",
     params(
         ("path" = String, Query, description = "Path to delete"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+        ("permanent" = Option<bool>, Query, description = "Delete right away instead of moving to the trash"),
     ),
     responses(
         (status = 200, body = serde_json::Value)
@@ -481,11 +845,17 @@ async fn create_directory(
 )]
 async fn delete_path(
     State(state): State<AppState>,
-    Query(query): Query<PathQuery>,
-    _auth: Authorized<StorageDelete>,
+    Query(query): Query<DeleteQuery>,
+    auth: Authorized<StorageDelete>,
 ) -> Result<Json<serde_json::Value>> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDelete::NAME,
+    )
+    .await?;
     let base_path = storage_path
         .canonicalize()
         .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
@@ -499,23 +869,80 @@ async fn delete_path(
         )));
     }
 
-    // Check if trying to delete the root
+    check_deletable(&root, &base_path, &target_path)?;
+
+    let is_dir = target_path.is_dir();
+    if is_dir {
+        // Only delete empty directories
+        let is_empty = std::fs::read_dir(&target_path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+
+        if !is_empty {
+            return Err(AppError::BadRequest(
+                "Cannot delete non-empty directory".to_string(),
+            ));
+        }
+    }
+
+    let relative_path = relative_to_root(&target_path, &base_path)?;
+    let trashed = if query.permanent {
+        if is_dir {
+            std::fs::remove_dir(&target_path)
+                .map_err(|e| AppError::Internal(format!("Failed to delete directory: {}", e)))?;
+        } else {
+            std::fs::remove_file(&target_path)
+                .map_err(|e| AppError::Internal(format!("Failed to delete file: {}", e)))?;
+        }
+        None
+    } else {
+        Some(trash::move_to_trash(
+            &base_path,
+            &target_path,
+            &relative_path,
+            Some(auth.user().username.clone()),
+        )?)
+    };
+
+    if !is_dir {
+        PREVIEWS.invalidate(&root.name, &relative_path).await;
+        checksums::forget(&db, &root.name, &relative_path).await?;
+    }
+
+    Ok(Json(match trashed {
+        Some(entry) => serde_json::json!({
+            "success": true,
+            "message": format!("Moved to trash: {}", query.path),
+            "trash_id": entry.id
+        }),
+        None => serde_json::json!({
+            "success": true,
+            "message": format!("Deleted: {}", query.path)
+        }),
+    }))
+}
+
+/// Refuse to delete the storage root or one of its protected folders
+pub(crate) fn check_deletable(
+    root: &StorageRoot,
+    base_path: &Path,
+    target_path: &Path,
+) -> Result<()> {
     if target_path == base_path {
         return Err(AppError::Forbidden(
             "Cannot delete the storage root".to_string(),
         ));
     }
 
-    // Check if trying to delete a protected top-level folder
     let relative_path = target_path
-        .strip_prefix(&base_path)
+        .strip_prefix(base_path)
         .map_err(|_| AppError::Internal("Failed to calculate relative path".to_string()))?;
 
     let parts: Vec<_> = relative_path.components().collect();
     if parts.len() == 1 {
         if let Some(std::path::Component::Normal(name)) = parts.first() {
             let name_str = name.to_string_lossy();
-            if PROTECTED_FOLDERS.contains(&name_str.as_ref()) {
+            if root.is_protected(&name_str) {
                 return Err(AppError::Forbidden(format!(
                     "Cannot delete protected folder: {}",
                     name_str
@@ -523,30 +950,7 @@ async fn delete_path(
             }
         }
     }
-
-    if target_path.is_dir() {
-        // Only delete empty directories
-        let is_empty = std::fs::read_dir(&target_path)
-            .map(|mut entries| entries.next().is_none())
-            .unwrap_or(false);
-
-        if !is_empty {
-            return Err(AppError::BadRequest(
-                "Cannot delete non-empty directory".to_string(),
-            ));
-        }
-
-        std::fs::remove_dir(&target_path)
-            .map_err(|e| AppError::Internal(format!("Failed to delete directory: {}", e)))?;
-    } else {
-        std::fs::remove_file(&target_path)
-            .map_err(|e| AppError::Internal(format!("Failed to delete file: {}", e)))?;
-    }
-
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "message": format!("Deleted: {}", query.path)
-    })))
+    Ok(())
 }
 
 /// Download a file from storage
@@ -556,6 +960,7 @@ async fn delete_path(
     tag = "Storage",
     params(
         ("path" = String, Query, description = "File path to download"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
     ),
     responses(
         (status = 200, description = "File download")
@@ -564,10 +969,16 @@ async fn delete_path(
 async fn download_file(
     State(state): State<AppState>,
     Query(query): Query<PathQuery>,
-    _auth: Authorized<StorageDownload>,
+    auth: Authorized<StorageDownload>,
 ) -> Result<Response> {
     let db = state.get_db().await?;
-    let storage_path = get_storage_path(&db).await?;
+    let (_, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDownload::NAME,
+    )
+    .await?;
     let file_path = validate_path(&query.path, &storage_path)?;
 
     if !file_path.exists() {
@@ -609,3 +1020,578 @@ async fn download_file(
     )
         .into_response())
 }
+
+/// Get a thumbnail of an image or a poster frame of a video
+///
+/// Thumbnails are rendered by ffmpeg and cached until the file changes.
+#[utoipa::path(
+    get,
+    path = "/api/storage/preview",
+    tag = "Storage",
+    params(
+        ("path" = String, Query, description = "Image or video to preview"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+        ("size" = Option<PreviewSize>, Query, description = "small (160px), medium (320px, default) or large (640px)"),
+    ),
+    responses(
+        (status = 200, description = "JPEG thumbnail", content_type = "image/jpeg"),
+        (status = 304, description = "Thumbnail unchanged since the ETag in If-None-Match"),
+        (status = 400, description = "Not an image or video"),
+        (status = 503, description = "ffmpeg is not installed")
+    )
+)]
+async fn preview_file(
+    State(state): State<AppState>,
+    Query(query): Query<PreviewQuery>,
+    auth: Authorized<StorageDownload>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDownload::NAME,
+    )
+    .await?;
+    let (file_path, relative_path) = resolve_file(&query.path, &storage_path)?;
+    let preview = PREVIEWS
+        .preview(&root.name, &relative_path, &file_path, query.size)
+        .await?;
+
+    let cache_headers = [
+        (header::ETAG, preview.etag.clone()),
+        (header::CACHE_CONTROL, "private, max-age=86400".to_string()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == preview.etag));
+    if unchanged {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let image = tokio::fs::read(&preview.path)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read preview: {}", e)))?;
+    Ok((
+        StatusCode::OK,
+        cache_headers,
+        [(header::CONTENT_TYPE, "image/jpeg")],
+        image,
+    )
+        .into_response())
+}
+
+/// Compute the checksum of a file
+///
+/// Files up to 64 MiB are hashed right away (200); larger ones are hashed in
+/// the background (202) and their result polled with `GET`. A result for the
+/// same version of the file is reused.
+#[utoipa::path(
+    post,
+    path = "/api/storage/checksum",
+    tag = "Storage",
+    request_body = ChecksumRequest,
+    responses(
+        (status = 200, description = "Checksum computed", body = ChecksumResult),
+        (status = 202, description = "Checksum is being computed in the background", body = ChecksumResult)
+    )
+)]
+async fn request_checksum(
+    State(state): State<AppState>,
+    auth: Authorized<StorageDownload>,
+    Json(request): Json<ChecksumRequest>,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        request.root.as_deref(),
+        StorageDownload::NAME,
+    )
+    .await?;
+    let (file_path, relative_path) = resolve_file(&request.path, &storage_path)?;
+
+    let (record, background) = checksums::request(
+        &db,
+        auth.user_id(),
+        &root.name,
+        &relative_path,
+        &file_path,
+        request.algorithm,
+    )
+    .await?;
+    let current = FileVersion::read(&file_path).ok();
+    let status = if background {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::OK
+    };
+    Ok((
+        status,
+        Json(ChecksumResult::new(
+            record,
+            current,
+            request.expected.as_deref(),
+        )),
+    )
+        .into_response())
+}
+
+/// Get the stored checksums of a file, newest first
+#[utoipa::path(
+    get,
+    path = "/api/storage/checksum",
+    tag = "Storage",
+    params(
+        ("path" = String, Query, description = "File path"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+        ("expected" = Option<String>, Query, description = "Checksum to compare the results against"),
+    ),
+    responses(
+        (status = 200, body = Vec<ChecksumResult>)
+    )
+)]
+async fn get_checksums(
+    State(state): State<AppState>,
+    Query(query): Query<ChecksumQuery>,
+    auth: Authorized<StorageDownload>,
+) -> Result<Json<Vec<ChecksumResult>>> {
+    let db = state.get_db().await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDownload::NAME,
+    )
+    .await?;
+    let (file_path, relative_path) = resolve_file(&query.path, &storage_path)?;
+
+    let current = FileVersion::read(&file_path).ok();
+    Ok(Json(
+        checksums::results(&db, &root.name, &relative_path)
+            .await?
+            .into_iter()
+            .map(|record| ChecksumResult::new(record, current, query.expected.as_deref()))
+            .collect(),
+    ))
+}
+
+/// The canonical directory of a storage root, for the trash endpoints
+async fn root_base(
+    db: &DbConn,
+    user_id: i64,
+    root: Option<&str>,
+    permission: &str,
+) -> Result<PathBuf> {
+    let (_, storage_path) = storage_root(db, user_id, root, permission).await?;
+    storage_path
+        .canonicalize()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))
+}
+
+/// List the trash of a storage root, most recently deleted first
+#[utoipa::path(
+    get,
+    path = "/api/storage/trash",
+    tag = "Storage",
+    params(
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+    ),
+    responses(
+        (status = 200, body = Vec<TrashEntry>)
+    )
+)]
+async fn list_trash(
+    State(state): State<AppState>,
+    Query(query): Query<RootQuery>,
+    auth: Authorized<StorageView>,
+) -> Result<Json<Vec<TrashEntry>>> {
+    let db = state.get_db().await?;
+    let base_path = root_base(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageView::NAME,
+    )
+    .await?;
+    Ok(Json(trash::list(&base_path)?))
+}
+
+/// Restore a trashed item to its original path
+#[utoipa::path(
+    post,
+    path = "/api/storage/trash/{id}/restore",
+    tag = "Storage",
+    params(
+        ("id" = String, Path, description = "Trash item ID"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+    ),
+    responses(
+        (status = 200, body = TrashEntry),
+        (status = 403, description = "The item would not fit in its home folder's quota"),
+        (status = 409, description = "Something already exists at the original path")
+    )
+)]
+async fn restore_trash_item(
+    State(state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(query): Query<RootQuery>,
+    auth: Authorized<StorageWrite>,
+) -> Result<Json<TrashEntry>> {
+    let db = state.get_db().await?;
+    let (root, storage_path) = storage_root(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageWrite::NAME,
+    )
+    .await?;
+    let base_path = storage_path
+        .canonicalize()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
+    let (entry, item) = trash::get(&base_path, &id)?;
+    homes::enforce(
+        &db,
+        &root,
+        Path::new(&entry.original_path),
+        homes::disk_usage(&item),
+    )
+    .await?;
+    Ok(Json(trash::restore(&base_path, &id)?))
+}
+
+/// Permanently delete one trashed item
+#[utoipa::path(
+    delete,
+    path = "/api/storage/trash/{id}",
+    tag = "Storage",
+    params(
+        ("id" = String, Path, description = "Trash item ID"),
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+    ),
+    responses(
+        (status = 200, body = serde_json::Value)
+    )
+)]
+async fn purge_trash_item(
+    State(state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Query(query): Query<RootQuery>,
+    auth: Authorized<StorageDelete>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let base_path = root_base(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDelete::NAME,
+    )
+    .await?;
+    let entry = trash::purge(&base_path, &id)?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Deleted: {}", entry.original_path)
+    })))
+}
+
+/// Permanently delete everything in the trash of a storage root
+#[utoipa::path(
+    delete,
+    path = "/api/storage/trash",
+    tag = "Storage",
+    params(
+        ("root" = Option<String>, Query, description = "Storage root (default: the first root)"),
+    ),
+    responses(
+        (status = 200, body = serde_json::Value)
+    )
+)]
+async fn empty_trash(
+    State(state): State<AppState>,
+    Query(query): Query<RootQuery>,
+    auth: Authorized<StorageDelete>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let base_path = root_base(
+        &db,
+        auth.user_id(),
+        query.root.as_deref(),
+        StorageDelete::NAME,
+    )
+    .await?;
+    let purged = trash::empty(&base_path, None)?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Deleted {} items from the trash", purged),
+        "purged": purged
+    })))
+}
+
+// ============================================================================
+// SMB shares
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateShareRequest {
+    /// Storage root to share
+    pub root: String,
+    /// Serve the share read-only, whatever the users' permissions
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SharePasswordRequest {
+    pub password: String,
+}
+
+/// Share status for a user, listing share users to those who manage settings
+async fn share_status(
+    state: &AppState,
+    db: &DbConn,
+    caller: &crate::models::user::Model,
+    error: Option<String>,
+) -> Result<shares::SharesStatus> {
+    let k8s = state.k8s_api().await;
+    let show_users = get_user_permissions(db, caller.id)
+        .await
+        .iter()
+        .any(|p| p == SettingsManage::NAME);
+    let mut status = shares::status(
+        db,
+        k8s.as_deref(),
+        state.helm().as_ref(),
+        caller,
+        show_users,
+    )
+    .await?;
+    status.error = error;
+    Ok(status)
+}
+
+/// Apply share changes to the Samba release, returning the status with any
+/// sync error instead of failing the change that was already saved
+async fn sync_shares(
+    state: &AppState,
+    db: &DbConn,
+    caller: &crate::models::user::Model,
+    force: bool,
+) -> Result<Json<shares::SharesStatus>> {
+    let error = match state.k8s_api().await {
+        Some(k8s) => shares::sync(db, k8s.as_ref(), state.helm().as_ref(), force)
+            .await
+            .err()
+            .map(|e| e.to_string()),
+        None => Some("Kubernetes client not available".to_string()),
+    };
+    if let Some(error) = &error {
+        tracing::warn!("Failed to sync storage shares: {}", error);
+    }
+    Ok(Json(share_status(state, db, caller, error).await?))
+}
+
+/// Status of the SMB shares and the caller's access to them
+#[utoipa::path(
+    get,
+    path = "/api/storage/shares",
+    tag = "Storage",
+    responses(
+        (status = 200, body = shares::SharesStatus)
+    )
+)]
+async fn get_shares(
+    State(state): State<AppState>,
+    auth: Authorized<StorageView>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    Ok(Json(share_status(&state, &db, auth.user(), None).await?))
+}
+
+/// Share a storage root over SMB
+#[utoipa::path(
+    post,
+    path = "/api/storage/shares",
+    tag = "Storage",
+    request_body = CreateShareRequest,
+    responses(
+        (status = 200, body = shares::SharesStatus),
+        (status = 400, description = "The root is outside the storage path"),
+        (status = 409, description = "The root is already shared")
+    )
+)]
+async fn create_share(
+    State(state): State<AppState>,
+    auth: Authorized<SettingsManage>,
+    Json(request): Json<CreateShareRequest>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    shares::create(&db, &request.root, request.read_only).await?;
+    sync_shares(&state, &db, auth.user(), false).await
+}
+
+/// Stop sharing a storage root
+#[utoipa::path(
+    delete,
+    path = "/api/storage/shares/{root}",
+    tag = "Storage",
+    params(
+        ("root" = String, Path, description = "Shared storage root"),
+    ),
+    responses(
+        (status = 200, body = shares::SharesStatus)
+    )
+)]
+async fn delete_share(
+    State(state): State<AppState>,
+    axum::extract::Path(root): axum::extract::Path<String>,
+    auth: Authorized<SettingsManage>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    shares::remove(&db, &root).await?;
+    sync_shares(&state, &db, auth.user(), false).await
+}
+
+/// Set the caller's password for signing in to shares
+#[utoipa::path(
+    put,
+    path = "/api/storage/shares/password",
+    tag = "Storage",
+    request_body = SharePasswordRequest,
+    responses(
+        (status = 200, body = shares::SharesStatus)
+    )
+)]
+async fn set_share_password(
+    State(state): State<AppState>,
+    auth: Authorized<StorageView>,
+    Json(request): Json<SharePasswordRequest>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    shares::set_password(&db, auth.user_id(), &request.password).await?;
+    sync_shares(&state, &db, auth.user(), false).await
+}
+
+/// Remove the caller's share password
+#[utoipa::path(
+    delete,
+    path = "/api/storage/shares/password",
+    tag = "Storage",
+    responses(
+        (status = 200, body = shares::SharesStatus)
+    )
+)]
+async fn clear_share_password(
+    State(state): State<AppState>,
+    auth: Authorized<StorageView>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    shares::clear_password(&db, auth.user_id()).await?;
+    sync_shares(&state, &db, auth.user(), false).await
+}
+
+/// Redeploy the Samba release with the current shares and users
+#[utoipa::path(
+    post,
+    path = "/api/storage/shares/sync",
+    tag = "Storage",
+    responses(
+        (status = 200, body = shares::SharesStatus)
+    )
+)]
+async fn force_share_sync(
+    State(state): State<AppState>,
+    auth: Authorized<SettingsManage>,
+) -> Result<Json<shares::SharesStatus>> {
+    let db = state.get_db().await?;
+    sync_shares(&state, &db, auth.user(), true).await
+}
+
+// ============================================================================
+// Home folders
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct HomesQuery {
+    /// List every user's homes instead of the caller's (requires users.manage)
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// List home folders with their usage and quotas
+///
+/// Returns the caller's homes; holders of `users.manage` can pass
+/// `all=true` to list everyone's.
+#[utoipa::path(
+    get,
+    path = "/api/storage/homes",
+    tag = "Storage",
+    params(HomesQuery),
+    responses(
+        (status = 200, body = Vec<HomeInfo>),
+        (status = 403, description = "users.manage required for all=true")
+    )
+)]
+async fn list_homes(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthenticatedUser>,
+    Query(query): Query<HomesQuery>,
+) -> Result<Json<Vec<HomeInfo>>> {
+    let db = state.get_db().await?;
+    if query.all && !auth_user.has_permission(UsersManage::NAME) {
+        return Err(AppError::Forbidden(format!(
+            "Permission denied: {} required",
+            UsersManage::NAME
+        )));
+    }
+    let user_id = (!query.all).then_some(auth_user.user.id);
+    Ok(Json(homes::list(&db, user_id).await?))
+}
+
+/// Measure all home folders now instead of waiting for the next scan
+#[utoipa::path(
+    post,
+    path = "/api/storage/homes/scan",
+    tag = "Storage",
+    responses(
+        (status = 200, body = Vec<HomeInfo>)
+    )
+)]
+async fn scan_homes(
+    State(state): State<AppState>,
+    _auth: Authorized<UsersManage>,
+) -> Result<Json<Vec<HomeInfo>>> {
+    let db = state.get_db().await?;
+    homes::scan(&db, &state.notification).await?;
+    Ok(Json(homes::list(&db, None).await?))
+}
+
+/// Override the quotas of one user's home folder; omitted quotas fall back
+/// to the storage root's
+#[utoipa::path(
+    put,
+    path = "/api/storage/homes/{root}/{user_id}",
+    tag = "Storage",
+    params(
+        ("root" = String, Path, description = "Storage root"),
+        ("user_id" = i64, Path, description = "User ID"),
+    ),
+    request_body = HomeQuotaOverride,
+    responses(
+        (status = 200, body = HomeInfo),
+        (status = 400, description = "Negative quota, or soft quota above hard quota"),
+        (status = 404, description = "Root without homes, or user not found")
+    )
+)]
+async fn set_home_quota(
+    State(state): State<AppState>,
+    axum::extract::Path((root, user_id)): axum::extract::Path<(String, i64)>,
+    _auth: Authorized<UsersManage>,
+    Json(quota): Json<HomeQuotaOverride>,
+) -> Result<Json<HomeInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        homes::set_override(&db, &root, user_id, &quota).await?,
+    ))
+}
@@ -0,0 +1,2494 @@
+//! System endpoints and API access metadata
+//!
+//! [`ROUTE_ACCESS`] records what every documented API operation requires: no
+//! session at all, any signed-in user, or a specific permission. The
+//! [`SecurityAddon`] modifier copies it into the OpenAPI document, and
+//! `GET /api/system/permissions-map` serves it to the frontend so it can hide
+//! actions the current user cannot perform.
+//!
+//! The remaining endpoints let admins look into Kubarr itself, starting with
+//! the backend's recent log lines, recorded panics and 5xx spikes, and a
+//! diagnostic bundle for bug reports. The update endpoints report new Kubarr
+//! releases with their changelog and upgrade Kubarr's own Helm release, and the
+//! report endpoints manage the scheduled summary emails sent to admins. The
+//! security report scores the deployment against a checklist. The registry
+//! endpoints set the mirrors and pull credentials app images are pulled with.
+//! The outbound endpoints show which proxy each external destination goes
+//! through and check that it can be reached, and the certificate endpoints
+//! manage the private CAs outbound requests trust. The backup endpoints
+//! download a backup of the database, list and download scheduled backups,
+//! and restore one.
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::Path,
+    extract::Query,
+    extract::State,
+    extract::{DefaultBodyLimit, Multipart},
+    http::header,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post, put},
+    Extension, Json, Router,
+};
+use chrono::Utc;
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::openapi::extensions::ExtensionsBuilder;
+use utoipa::openapi::path::Operation;
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::{Modify, ToSchema};
+
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::middleware::auth::session_cookie_name;
+use crate::middleware::permissions::{
+    AppsDelete, AppsExec, AppsInstall, AppsRequest, AppsRestart, AppsStop, AppsView, AuditManage,
+    AuditView, CloudflareManage, CloudflareView, LogsView, MonitoringManage, MonitoringView,
+    NetworkingManage, NetworkingView, Permission as _, RequestsManage, RolesManage, RolesView,
+    SecurityManage, SettingsManage, SettingsView, StorageDelete, StorageDownload, StorageView,
+    StorageWrite, TenantsManage, UsersManage, UsersResetPassword, UsersView, VpnManage, VpnView,
+};
+use crate::middleware::permissions::{Authorized, SystemManage};
+use crate::middleware::AuthenticatedUser;
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::services::backup::{self, BackupFile, RestoreSummary, MAX_BACKUP_BYTES};
+use crate::services::diagnostics::{self, ErrorReport, ERRORS};
+use crate::services::log_buffer::{self, LogEntry, LOG_BUFFER};
+use crate::services::notification::routing::validate_timezone;
+use crate::services::outbound::{self, ConnectivityTest, OutboundTarget};
+use crate::services::registries::{self, RegistryInfo, RegistryRequest, RegistryTest};
+use crate::services::reports::{
+    self, Report, ReportFrequency, ReportSubscriptionInfo, SendReportRequest, SendReportResult,
+    UpdateReportSubscriptionRequest,
+};
+use crate::services::runtime_config;
+use crate::services::security_report::{self, SecurityReport};
+use crate::services::trust_store::{self, CertificateInfo, CertificateRequest};
+use crate::services::updates::{
+    self, ApplyUpdateRequest, Changelog, UpdateRecord, UpdateStatus, UPDATES,
+};
+use crate::state::AppState;
+
+/// Security scheme name for the session cookie
+pub const COOKIE_SCHEME: &str = "session_cookie";
+
+/// Security scheme name for a session token sent as a bearer token
+pub const BEARER_SCHEME: &str = "bearer";
+
+/// OpenAPI extension holding an operation's required permission
+pub const PERMISSION_EXTENSION: &str = "x-required-permission";
+
+pub fn system_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/permissions-map", get(get_permissions_map))
+        .route("/logs", get(get_system_logs))
+        .route("/logs/download", get(download_system_logs))
+        .route("/diagnostics/errors", get(get_diagnostic_errors))
+        .route("/diagnostics/bundle", post(create_diagnostic_bundle))
+        .route("/backup", post(create_backup))
+        .route("/backups", get(list_backups))
+        .route("/backups/{name}", get(download_backup))
+        .route(
+            "/restore",
+            post(restore_backup).layer(DefaultBodyLimit::max(MAX_BACKUP_BYTES)),
+        )
+        .route("/update", get(get_update_status))
+        .route("/update/apply", post(apply_update))
+        .route("/changelog", get(get_changelog))
+        .route(
+            "/reports/subscription",
+            get(get_report_subscription).put(update_report_subscription),
+        )
+        .route("/reports/preview", get(preview_report))
+        .route("/reports/send", post(send_report))
+        .route("/security-report", get(get_security_report))
+        .route("/registries", get(list_registries).post(create_registry))
+        .route(
+            "/registries/{registry_id}",
+            put(update_registry).delete(delete_registry),
+        )
+        .route("/registries/{registry_id}/test", post(test_registry))
+        .route("/outbound", get(list_outbound_targets))
+        .route("/outbound/{target}/test", post(test_outbound_target))
+        .route(
+            "/certificates",
+            get(list_certificates).post(create_certificates),
+        )
+        .route("/certificates/{certificate_id}", delete(delete_certificate))
+        .with_state(state)
+}
+
+/// What an API operation requires from the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteAccess {
+    /// No session needed
+    Public,
+    /// Any signed-in user
+    Authenticated,
+    /// A signed-in user holding this permission
+    Permission(&'static str),
+}
+
+use RouteAccess::{Authenticated, Permission, Public};
+
+/// Access rules for every operation in the OpenAPI document, as `(method, path, access)`
+///
+/// Handlers that check finer-grained access themselves (own resources, app
+/// access) are listed as [`RouteAccess::Authenticated`].
+pub const ROUTE_ACCESS: &[(&str, &str, RouteAccess)] = &[
+    // Health
+    ("GET", "/api/health", Public),
+    ("GET", "/api/system/health", Public),
+    ("GET", "/api/system/version", Public),
+    ("GET", "/metrics", Permission(MonitoringView::NAME)),
+    ("GET", "/api/system/permissions-map", Authenticated),
+    ("GET", "/api/system/logs", Permission(SystemManage::NAME)),
+    (
+        "GET",
+        "/api/system/logs/download",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/diagnostics/errors",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/diagnostics/bundle",
+        Permission(SystemManage::NAME),
+    ),
+    ("POST", "/api/system/backup", Permission(SystemManage::NAME)),
+    ("GET", "/api/system/backups", Permission(SystemManage::NAME)),
+    (
+        "GET",
+        "/api/system/backups/{name}",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/restore",
+        Permission(SystemManage::NAME),
+    ),
+    ("GET", "/api/system/update", Permission(SystemManage::NAME)),
+    (
+        "POST",
+        "/api/system/update/apply",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/changelog",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/reports/subscription",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/system/reports/subscription",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/reports/preview",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/reports/send",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/security-report",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/registries",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/registries",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/system/registries/{registry_id}",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/system/registries/{registry_id}",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/registries/{registry_id}/test",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/outbound",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/outbound/{target}/test",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/system/certificates",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/system/certificates",
+        Permission(SystemManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/system/certificates/{certificate_id}",
+        Permission(SystemManage::NAME),
+    ),
+    // Auth
+    ("POST", "/auth/login", Public),
+    ("POST", "/auth/logout", Public),
+    ("POST", "/auth/session/refresh", Public),
+    ("GET", "/auth/sessions", Authenticated),
+    ("DELETE", "/auth/sessions/{session_id}", Authenticated),
+    ("GET", "/auth/sessions/revoke-link", Public),
+    ("POST", "/auth/sessions/revoke-link", Public),
+    ("POST", "/auth/2fa/recover", Public),
+    ("POST", "/auth/sessions/{session_id}/switch", Authenticated),
+    ("GET", "/auth/accounts", Authenticated),
+    // Setup
+    ("GET", "/api/setup/required", Public),
+    ("GET", "/api/setup/status", Public),
+    ("POST", "/api/setup/initialize", Public),
+    ("GET", "/api/setup/generate-credentials", Public),
+    ("POST", "/api/setup/validate-path", Public),
+    ("GET", "/api/setup/browse", Public),
+    ("GET", "/api/setup/bootstrap/status", Public),
+    ("POST", "/api/setup/bootstrap/start", Public),
+    ("POST", "/api/setup/bootstrap/retry/{component}", Public),
+    ("GET", "/api/setup/server", Public),
+    ("POST", "/api/setup/server", Public),
+    // Users
+    ("GET", "/api/users", Permission(UsersView::NAME)),
+    ("GET", "/api/users/me", Authenticated),
+    ("PATCH", "/api/users/me", Authenticated),
+    ("DELETE", "/api/users/me", Authenticated),
+    ("GET", "/api/users/me/preferences", Authenticated),
+    ("PATCH", "/api/users/me/preferences", Authenticated),
+    ("GET", "/api/users/pending", Permission(UsersView::NAME)),
+    ("GET", "/api/users/approve-link", Public),
+    ("POST", "/api/users/approve-link", Public),
+    ("GET", "/api/invites/share/{token}", Public),
+    ("POST", "/api/users", Permission(UsersManage::NAME)),
+    ("GET", "/api/users/{user_id}", Permission(UsersView::NAME)),
+    (
+        "PATCH",
+        "/api/users/{user_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/approve",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/reject",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}",
+        Permission(UsersManage::NAME),
+    ),
+    ("GET", "/api/users/invites", Permission(UsersManage::NAME)),
+    ("POST", "/api/users/invites", Permission(UsersManage::NAME)),
+    (
+        "POST",
+        "/api/users/invites/batch",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/invites/{invite_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/invites/{invite_id}/qr",
+        Permission(UsersManage::NAME),
+    ),
+    ("PATCH", "/api/users/me/password", Authenticated),
+    (
+        "PATCH",
+        "/api/users/{user_id}/password",
+        Permission(UsersResetPassword::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/unlock",
+        Permission(UsersManage::NAME),
+    ),
+    ("POST", "/api/users/me/2fa/setup", Authenticated),
+    ("POST", "/api/users/me/2fa/enable", Authenticated),
+    ("POST", "/api/users/me/2fa/disable", Authenticated),
+    ("GET", "/api/users/me/2fa/status", Authenticated),
+    ("GET", "/api/users/me/2fa/qr", Authenticated),
+    ("GET", "/api/users/me/2fa/recovery-codes", Authenticated),
+    ("GET", "/api/users/me/quota", Authenticated),
+    (
+        "GET",
+        "/api/users/{user_id}/quota",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/users/{user_id}/quota",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}/quota",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/users/{user_id}/roles/{role_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}/roles/{role_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/{user_id}/client-certificates",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/client-certificates",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}/client-certificates/{certificate_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/service-accounts",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/service-accounts",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/service-accounts/{account_id}",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "PATCH",
+        "/api/users/service-accounts/{account_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/service-accounts/{account_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/service-accounts/{account_id}/tokens",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/service-accounts/{account_id}/tokens",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/service-accounts/{account_id}/tokens/{token_id}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/{user_id}/accounts",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/users/{user_id}/accounts/{app_name}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}/accounts/{app_name}",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/accounts/discover",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/provision",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/deprovision",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/{user_id}/schedules",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/users/{user_id}/schedules",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/users/{user_id}/schedule-overrides",
+        Permission(UsersView::NAME),
+    ),
+    (
+        "POST",
+        "/api/users/{user_id}/schedule-overrides",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/users/{user_id}/schedule-overrides/{override_id}",
+        Permission(UsersManage::NAME),
+    ),
+    // Roles
+    // Tenants
+    ("GET", "/api/tenants", Authenticated),
+    ("POST", "/api/tenants", Permission(TenantsManage::NAME)),
+    ("GET", "/api/tenants/{tenant_id}", Authenticated),
+    (
+        "PUT",
+        "/api/tenants/{tenant_id}",
+        Permission(TenantsManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/tenants/{tenant_id}",
+        Permission(TenantsManage::NAME),
+    ),
+    ("GET", "/api/tenants/{tenant_id}/members", Authenticated),
+    (
+        "PUT",
+        "/api/tenants/{tenant_id}/members/{user_id}",
+        Authenticated,
+    ),
+    (
+        "DELETE",
+        "/api/tenants/{tenant_id}/members/{user_id}",
+        Authenticated,
+    ),
+    ("GET", "/api/roles", Permission(RolesView::NAME)),
+    ("GET", "/api/roles/{role_id}", Permission(RolesView::NAME)),
+    ("POST", "/api/roles", Permission(RolesManage::NAME)),
+    (
+        "PATCH",
+        "/api/roles/{role_id}",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/roles/{role_id}",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/apps",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/roles/{role_id}/quota",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/quota",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/roles/{role_id}/quota",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/roles/{role_id}/schedules",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/schedules",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/roles/{role_id}/media-profile",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/media-profile",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/roles/{role_id}/media-profile",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/roles/{role_id}/media-profile/sync",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/roles/{role_id}/provisioning",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/provisioning",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/roles/{role_id}/provisioning",
+        Permission(RolesManage::NAME),
+    ),
+    ("GET", "/api/roles/permissions", Permission(RolesView::NAME)),
+    ("POST", "/api/roles/evaluate", Permission(RolesView::NAME)),
+    ("GET", "/api/roles/reviews", Permission(RolesView::NAME)),
+    ("POST", "/api/roles/reviews", Permission(RolesManage::NAME)),
+    (
+        "GET",
+        "/api/roles/reviews/{review_id}",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "POST",
+        "/api/roles/reviews/{review_id}",
+        Permission(RolesManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/roles/{role_id}/permissions",
+        Permission(RolesView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/roles/{role_id}/permissions",
+        Permission(RolesManage::NAME),
+    ),
+    // Settings
+    ("GET", "/api/settings", Permission(SettingsView::NAME)),
+    (
+        "GET",
+        "/api/settings/schema",
+        Permission(SettingsView::NAME),
+    ),
+    ("GET", "/api/settings/{key}", Permission(SettingsView::NAME)),
+    (
+        "PUT",
+        "/api/settings/{key}",
+        Permission(SettingsManage::NAME),
+    ),
+    // Monitoring
+    (
+        "GET",
+        "/api/monitoring/vm/apps",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/vm/cluster",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/vm/cluster/network-history",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/vm/cluster/metrics-history",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/vm/app/{app_name}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/vm/available",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/pods",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/metrics",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/health/{app_name}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/endpoints/{app_name}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/metrics-available",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/apps/{app_name}/transcodes",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/apps/{app_name}/stability",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/auth",
+        Permission(SecurityManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/uptime",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "POST",
+        "/api/monitoring/uptime",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/uptime/{id}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/monitoring/uptime/{id}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/monitoring/uptime/{id}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/monitoring/uptime/{id}/check",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/dashboards",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "POST",
+        "/api/monitoring/dashboards",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/dashboards/{id}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/monitoring/dashboards/{id}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/monitoring/dashboards/{id}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/dashboards/{id}/data",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/nodes/{name}/sensors",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/energy",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/energy/nodes",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/monitoring/energy/nodes/{node_name}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/monitoring/energy/nodes/{node_name}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/anomalies",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/anomalies/sensitivity",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/monitoring/anomalies/sensitivity/{app_name}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/monitoring/anomalies/sensitivity/{app_name}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/alerts",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/log-alerts",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "POST",
+        "/api/monitoring/log-alerts",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/monitoring/log-alerts/{id}",
+        Permission(MonitoringView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/monitoring/log-alerts/{id}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/monitoring/log-alerts/{id}",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/monitoring/log-alerts/{id}/evaluate",
+        Permission(MonitoringManage::NAME),
+    ),
+    // Alerts
+    ("GET", "/api/alerts", Permission(MonitoringView::NAME)),
+    ("GET", "/api/alerts/{id}", Permission(MonitoringView::NAME)),
+    (
+        "POST",
+        "/api/alerts/{id}/ack",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/alerts/{id}/silence",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/alerts/{id}/silence",
+        Permission(MonitoringManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/alerts/{id}/assign",
+        Permission(MonitoringManage::NAME),
+    ),
+    // Networking
+    (
+        "GET",
+        "/api/networking/topology",
+        Permission(NetworkingView::NAME),
+    ),
+    (
+        "GET",
+        "/api/networking/stats",
+        Permission(NetworkingView::NAME),
+    ),
+    (
+        "GET",
+        "/api/networking/usage",
+        Permission(NetworkingView::NAME),
+    ),
+    (
+        "GET",
+        "/api/networking/usage/quotas",
+        Permission(NetworkingView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/networking/usage/quotas/{app_name}",
+        Permission(NetworkingManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/networking/usage/quotas/{app_name}",
+        Permission(NetworkingManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/networking/wan-health",
+        Permission(NetworkingView::NAME),
+    ),
+    // Apps
+    ("GET", "/api/apps/catalog", Permission(AppsView::NAME)),
+    (
+        "GET",
+        "/api/apps/catalog/{app_name}",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/catalog/validate",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/catalog/import-bundle",
+        Permission(SystemManage::NAME),
+    ),
+    ("GET", "/api/apps/catalog/{app_name}/icon", Authenticated),
+    ("GET", "/api/apps/installed", Permission(AppsView::NAME)),
+    ("POST", "/api/apps/install", Permission(AppsInstall::NAME)),
+    ("GET", "/api/apps/requests", Authenticated),
+    ("POST", "/api/apps/requests", Permission(AppsRequest::NAME)),
+    (
+        "POST",
+        "/api/apps/requests/{request_id}/approve",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/requests/{request_id}/deny",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/{app_name}/clone",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/apps/{app_name}",
+        Permission(AppsDelete::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/{app_name}/restart",
+        Permission(AppsRestart::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/{app_name}/upgrade",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/{app_name}/stop",
+        Permission(AppsStop::NAME),
+    ),
+    (
+        "POST",
+        "/api/apps/{app_name}/start",
+        Permission(AppsStop::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/exec",
+        Permission(AppsExec::NAME),
+    ),
+    ("GET", "/api/apps/categories", Permission(AppsView::NAME)),
+    (
+        "GET",
+        "/api/apps/category/{category}",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/health",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/exists",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/status",
+        Permission(AppsView::NAME),
+    ),
+    ("POST", "/api/apps/sync", Permission(AppsInstall::NAME)),
+    ("POST", "/api/apps/{app_name}/access", Authenticated),
+    (
+        "PUT",
+        "/api/apps/{app_name}/metadata",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "PUT",
+        "/api/apps/{app_name}/idle-suspend",
+        Permission(AppsStop::NAME),
+    ),
+    (
+        "PUT",
+        "/api/apps/{app_name}/tenant",
+        Permission(TenantsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/boot-order",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/apps/{app_name}/boot-order",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/routing",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/apps/{app_name}/routing",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/values",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "PUT",
+        "/api/apps/{app_name}/values",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "GET",
+        "/api/apps/{app_name}/native-status",
+        Permission(AppsView::NAME),
+    ),
+    // Storage
+    ("GET", "/api/storage/roots", Permission(StorageView::NAME)),
+    ("GET", "/api/storage/browse", Permission(StorageView::NAME)),
+    ("GET", "/api/storage/stats", Permission(StorageView::NAME)),
+    (
+        "GET",
+        "/api/storage/file-info",
+        Permission(StorageView::NAME),
+    ),
+    ("POST", "/api/storage/mkdir", Permission(StorageWrite::NAME)),
+    (
+        "DELETE",
+        "/api/storage/delete",
+        Permission(StorageDelete::NAME),
+    ),
+    (
+        "GET",
+        "/api/storage/download",
+        Permission(StorageDownload::NAME),
+    ),
+    (
+        "GET",
+        "/api/storage/preview",
+        Permission(StorageDownload::NAME),
+    ),
+    (
+        "POST",
+        "/api/storage/checksum",
+        Permission(StorageDownload::NAME),
+    ),
+    (
+        "GET",
+        "/api/storage/checksum",
+        Permission(StorageDownload::NAME),
+    ),
+    ("GET", "/api/storage/trash", Permission(StorageView::NAME)),
+    (
+        "POST",
+        "/api/storage/trash/{id}/restore",
+        Permission(StorageWrite::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/storage/trash/{id}",
+        Permission(StorageDelete::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/storage/trash",
+        Permission(StorageDelete::NAME),
+    ),
+    ("GET", "/api/storage/shares", Permission(StorageView::NAME)),
+    (
+        "POST",
+        "/api/storage/shares",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/storage/shares/{root}",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/storage/shares/password",
+        Permission(StorageView::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/storage/shares/password",
+        Permission(StorageView::NAME),
+    ),
+    (
+        "POST",
+        "/api/storage/shares/sync",
+        Permission(SettingsManage::NAME),
+    ),
+    ("GET", "/api/storage/homes", Authenticated),
+    (
+        "POST",
+        "/api/storage/homes/scan",
+        Permission(UsersManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/storage/homes/{root}/{user_id}",
+        Permission(UsersManage::NAME),
+    ),
+    // Logs
+    ("GET", "/api/logs/{pod_name}", Permission(LogsView::NAME)),
+    (
+        "GET",
+        "/api/logs/app/{app_name}",
+        Permission(LogsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/logs/raw/{pod_name}",
+        Permission(LogsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/logs/pods/{pod_name}/stream",
+        Permission(LogsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/logs/vlogs/namespaces",
+        Permission(LogsView::NAME),
+    ),
+    ("GET", "/api/logs/vlogs/labels", Permission(LogsView::NAME)),
+    (
+        "GET",
+        "/api/logs/vlogs/label/{label}/values",
+        Permission(LogsView::NAME),
+    ),
+    ("GET", "/api/logs/vlogs/query", Permission(LogsView::NAME)),
+    ("GET", "/api/logs/archive", Permission(LogsView::NAME)),
+    ("GET", "/api/logs/archive/query", Permission(LogsView::NAME)),
+    (
+        "POST",
+        "/api/logs/archive/run",
+        Permission(SettingsManage::NAME),
+    ),
+    // Audit
+    ("GET", "/api/audit", Authenticated),
+    ("GET", "/api/audit/stats", Permission(AuditView::NAME)),
+    (
+        "GET",
+        "/api/audit/stats/timeseries",
+        Permission(AuditView::NAME),
+    ),
+    ("GET", "/api/audit/verify", Permission(AuditView::NAME)),
+    ("POST", "/api/audit/clear", Permission(AuditManage::NAME)),
+    // Security
+    (
+        "GET",
+        "/api/security/bans",
+        Permission(SecurityManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/security/bans",
+        Permission(SecurityManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/security/bans/export",
+        Permission(SecurityManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/security/bans/{ban_id}",
+        Permission(SecurityManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/security/failed-logins",
+        Permission(SecurityManage::NAME),
+    ),
+    // Notifications
+    ("GET", "/api/notifications/inbox", Authenticated),
+    ("GET", "/api/notifications/inbox/count", Authenticated),
+    ("POST", "/api/notifications/inbox/{id}/read", Authenticated),
+    ("POST", "/api/notifications/inbox/read-all", Authenticated),
+    ("DELETE", "/api/notifications/inbox/{id}", Authenticated),
+    (
+        "GET",
+        "/api/notifications/channels",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/channels/{channel_type}",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/notifications/channels/{channel_type}",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/channels/status",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "POST",
+        "/api/notifications/channels/reload",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/notifications/channels/{channel_type}/test",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/events",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/notifications/events/{event_type}",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/events/{event_type}/template",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/notifications/events/{event_type}/template",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/notifications/events/{event_type}/template/preview",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/events/catalog",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/notifications/events",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/notifications/events/reset",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/routes",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "POST",
+        "/api/notifications/routes",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/notifications/routes/{id}",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/notifications/routes/{id}",
+        Permission(SettingsManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/notifications/routes/{id}",
+        Permission(SettingsManage::NAME),
+    ),
+    ("GET", "/api/notifications/preferences", Authenticated),
+    (
+        "PUT",
+        "/api/notifications/preferences/{channel_type}",
+        Authenticated,
+    ),
+    (
+        "POST",
+        "/api/notifications/preferences/{channel_type}/test",
+        Authenticated,
+    ),
+    (
+        "POST",
+        "/api/notifications/preferences/{channel_type}/send-code",
+        Authenticated,
+    ),
+    (
+        "POST",
+        "/api/notifications/preferences/{channel_type}/verify-code",
+        Authenticated,
+    ),
+    (
+        "GET",
+        "/api/notifications/logs",
+        Permission(AuditView::NAME),
+    ),
+    ("POST", "/api/notifications/telegram/callback", Public),
+    // OAuth
+    ("GET", "/api/oauth/available", Authenticated),
+    (
+        "GET",
+        "/api/oauth/providers",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/oauth/providers/{provider}",
+        Permission(SettingsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/oauth/providers/{provider}",
+        Permission(SettingsManage::NAME),
+    ),
+    ("GET", "/api/oauth/{provider}/login", Authenticated),
+    ("GET", "/api/oauth/{provider}/callback", Authenticated),
+    ("GET", "/api/oauth/accounts", Authenticated),
+    ("DELETE", "/api/oauth/accounts/{provider}", Authenticated),
+    ("GET", "/api/oauth/link/{provider}", Authenticated),
+    // VPN
+    ("GET", "/api/vpn/providers", Permission(VpnView::NAME)),
+    ("GET", "/api/vpn/providers/{id}", Permission(VpnView::NAME)),
+    ("POST", "/api/vpn/providers", Permission(VpnManage::NAME)),
+    (
+        "PUT",
+        "/api/vpn/providers/{id}",
+        Permission(VpnManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/vpn/providers/{id}",
+        Permission(VpnManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/vpn/providers/{id}/test",
+        Permission(VpnManage::NAME),
+    ),
+    ("GET", "/api/vpn/apps", Permission(VpnView::NAME)),
+    ("GET", "/api/vpn/apps/{app_name}", Permission(VpnView::NAME)),
+    (
+        "PUT",
+        "/api/vpn/apps/{app_name}",
+        Permission(VpnManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/vpn/apps/{app_name}",
+        Permission(VpnManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/vpn/apps/{app_name}/forwarded-port",
+        Permission(VpnView::NAME),
+    ),
+    (
+        "GET",
+        "/api/vpn/supported-providers",
+        Permission(VpnView::NAME),
+    ),
+    // Cloudflare
+    (
+        "GET",
+        "/api/cloudflare/config",
+        Permission(CloudflareView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/cloudflare/config",
+        Permission(CloudflareManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/cloudflare/config",
+        Permission(CloudflareManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/cloudflare/status",
+        Permission(CloudflareView::NAME),
+    ),
+    (
+        "POST",
+        "/api/cloudflare/validate-token",
+        Permission(CloudflareManage::NAME),
+    ),
+    // Integrations
+    ("GET", "/api/integrations", Permission(AppsView::NAME)),
+    (
+        "GET",
+        "/api/integrations/apps/{app_name}",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "PUT",
+        "/api/integrations/apps/{app_name}",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/integrations/apps/{app_name}",
+        Permission(AppsInstall::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/downloads",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/calendar",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/requests",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/requests/count",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "POST",
+        "/api/integrations/requests",
+        Permission(AppsView::NAME),
+    ),
+    (
+        "POST",
+        "/api/integrations/requests/{request_id}/approve",
+        Permission(RequestsManage::NAME),
+    ),
+    (
+        "POST",
+        "/api/integrations/requests/{request_id}/decline",
+        Permission(RequestsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/requests/users",
+        Permission(RequestsManage::NAME),
+    ),
+    (
+        "GET",
+        "/api/integrations/requests/links",
+        Permission(RequestsManage::NAME),
+    ),
+    (
+        "PUT",
+        "/api/integrations/requests/links/{user_id}",
+        Permission(RequestsManage::NAME),
+    ),
+    (
+        "DELETE",
+        "/api/integrations/requests/links/{user_id}",
+        Permission(RequestsManage::NAME),
+    ),
+];
+
+/// Look up the access rule for an operation
+pub fn route_access(method: &str, path: &str) -> Option<RouteAccess> {
+    ROUTE_ACCESS
+        .iter()
+        .find(|(m, p, _)| m.eq_ignore_ascii_case(method) && *p == path)
+        .map(|(_, _, access)| *access)
+}
+
+/// The operations of a path item with their HTTP methods
+fn operations_mut(
+    item: &mut utoipa::openapi::PathItem,
+) -> [(&'static str, &mut Option<Operation>); 5] {
+    [
+        ("GET", &mut item.get),
+        ("POST", &mut item.post),
+        ("PUT", &mut item.put),
+        ("PATCH", &mut item.patch),
+        ("DELETE", &mut item.delete),
+    ]
+}
+
+/// Adds the session security schemes and per-operation requirements to the OpenAPI document
+pub struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            COOKIE_SCHEME,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                session_cookie_name(),
+                "Session cookie set by /auth/login",
+            ))),
+        );
+        components.add_security_scheme(
+            BEARER_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Session token sent in the Authorization header"))
+                    .build(),
+            ),
+        );
+
+        let signed_in = |scopes: Vec<&str>| {
+            vec![
+                SecurityRequirement::new(COOKIE_SCHEME, scopes.clone()),
+                SecurityRequirement::new(BEARER_SCHEME, scopes),
+            ]
+        };
+        openapi.security = Some(signed_in(Vec::new()));
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            for (method, operation) in operations_mut(item) {
+                let Some(operation) = operation.as_mut() else {
+                    continue;
+                };
+                match route_access(method, path) {
+                    Some(Public) => operation.security = Some(vec![SecurityRequirement::default()]),
+                    Some(Permission(permission)) => {
+                        operation.security = Some(signed_in(vec![permission]));
+                        operation
+                            .extensions
+                            .get_or_insert_with(Default::default)
+                            .merge(
+                                ExtensionsBuilder::new()
+                                    .add(PERMISSION_EXTENSION, permission)
+                                    .build(),
+                            );
+                    }
+                    // Covered by the document-wide requirement
+                    Some(Authenticated) | None => {}
+                }
+            }
+        }
+    }
+}
+
+/// Access rule for one API operation, as seen by the current user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoutePermission {
+    pub method: String,
+    pub path: String,
+    /// Whether the operation works without a session
+    pub public: bool,
+    /// Permission required beyond being signed in
+    pub permission: Option<String>,
+    /// Whether the current user may call the operation
+    pub allowed: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PermissionsMap {
+    pub routes: Vec<RoutePermission>,
+}
+
+/// Get the access rules of all API operations
+#[utoipa::path(
+    get,
+    path = "/api/system/permissions-map",
+    tag = "System",
+    responses((status = 200, body = PermissionsMap))
+)]
+pub async fn get_permissions_map(
+    Extension(auth_user): Extension<AuthenticatedUser>,
+) -> Json<PermissionsMap> {
+    let routes = ROUTE_ACCESS
+        .iter()
+        .map(|(method, path, access)| {
+            let permission = match access {
+                Permission(permission) => Some(permission.to_string()),
+                _ => None,
+            };
+            RoutePermission {
+                method: method.to_string(),
+                path: path.to_string(),
+                public: *access == Public,
+                allowed: permission
+                    .as_deref()
+                    .is_none_or(|p| auth_user.has_permission(p)),
+                permission,
+            }
+        })
+        .collect();
+    Json(PermissionsMap { routes })
+}
+
+/// Lines returned when no limit is given
+const DEFAULT_LOG_LIMIT: usize = 500;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SystemLogsQuery {
+    /// Minimum level: error, warn, info, debug or trace (default all)
+    pub level: Option<String>,
+    /// Number of most recent lines (default 500)
+    pub limit: Option<usize>,
+    /// Keep the response open and stream new lines as server-sent events
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// Get Kubarr's recent backend log lines
+///
+/// With `follow=true` the response is a `text/event-stream` of `log` events,
+/// starting with the recent lines and continuing with new ones.
+#[utoipa::path(
+    get,
+    path = "/api/system/logs",
+    tag = "System",
+    params(SystemLogsQuery),
+    responses(
+        (status = 200, body = Vec<LogEntry>),
+        (status = 400, description = "Invalid level")
+    )
+)]
+async fn get_system_logs(
+    _auth: Authorized<SystemManage>,
+    Query(query): Query<SystemLogsQuery>,
+) -> Result<Response> {
+    let level = match query.level.as_deref().filter(|l| !l.is_empty()) {
+        Some(level) => Some(log_buffer::parse_level(level).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Invalid level '{}', expected error, warn, info, debug or trace",
+                level
+            ))
+        })?),
+        None => None,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LOG_LIMIT)
+        .min(log_buffer::CAPACITY);
+
+    if !query.follow {
+        return Ok(Json(LOG_BUFFER.recent(level, limit)).into_response());
+    }
+
+    // Subscribe before reading the backlog so no line falls in between
+    let live = LOG_BUFFER.subscribe();
+    let backlog = LOG_BUFFER.recent(level, limit);
+    let last_seq = backlog.last().map_or(0, |e| e.seq);
+    let live = stream::unfold(live, move |mut live| async move {
+        loop {
+            match live.recv().await {
+                Ok(entry) if entry.seq > last_seq && log_buffer::at_least(&entry, level) => {
+                    return Some((entry, live));
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = stream::iter(backlog).chain(live).map(|entry| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .event("log")
+                .json_data(&entry)
+                .unwrap_or_default(),
+        )
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Download the whole log buffer as text, e.g. to attach to a bug report
+#[utoipa::path(
+    get,
+    path = "/api/system/logs/download",
+    tag = "System",
+    responses((status = 200, description = "Log lines", content_type = "text/plain"))
+)]
+async fn download_system_logs(_auth: Authorized<SystemManage>) -> Response {
+    let file_name = format!("kubarr-logs-{}.txt", Utc::now().format("%Y%m%d-%H%M%S"));
+    (
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                &format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        LOG_BUFFER.to_text(),
+    )
+        .into_response()
+}
+
+/// Get the panics, 5xx responses and error spikes recorded since startup
+#[utoipa::path(
+    get,
+    path = "/api/system/diagnostics/errors",
+    tag = "System",
+    responses((status = 200, body = ErrorReport))
+)]
+async fn get_diagnostic_errors(_auth: Authorized<SystemManage>) -> Json<ErrorReport> {
+    Json(ERRORS.report())
+}
+
+/// Build a diagnostic bundle to attach to a GitHub issue
+///
+/// A `.tar.gz` with version info, the configuration with secrets redacted,
+/// recent logs, migration status, a health report and the recorded errors.
+#[utoipa::path(
+    post,
+    path = "/api/system/diagnostics/bundle",
+    tag = "System",
+    responses((status = 200, description = "Diagnostic bundle", content_type = "application/gzip"))
+)]
+async fn create_diagnostic_bundle(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+) -> Result<Response> {
+    let bundle = diagnostics::build_bundle(&state).await?;
+    tracing::info!("Diagnostic bundle created by user {}", auth.user_id());
+    let file_name = format!(
+        "kubarr-diagnostics-{}.tar.gz",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    Ok(gzip_download(&file_name, bundle))
+}
+
+fn gzip_download(file_name: &str, data: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "application/gzip"),
+            (
+                header::CONTENT_DISPOSITION,
+                &format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        data,
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBackupRequest {
+    /// Passphrase the backup's secrets are encrypted with; needed to restore it
+    pub passphrase: String,
+}
+
+/// Download a backup of the database
+///
+/// A `.tar.gz` with every table except sessions, including users, settings,
+/// notification channels and provider credentials. Credentials are encrypted
+/// with the passphrase.
+#[utoipa::path(
+    post,
+    path = "/api/system/backup",
+    tag = "System",
+    request_body = CreateBackupRequest,
+    responses(
+        (status = 200, description = "Backup archive", content_type = "application/gzip"),
+        (status = 400, description = "The passphrase is too short")
+    )
+)]
+async fn create_backup(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<CreateBackupRequest>,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let data = backup::create(&db, &request.passphrase).await?;
+    let file_name = backup::file_name(Utc::now());
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::BackupCreated,
+            ResourceType::System,
+            Some(file_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "size_bytes": data.len() })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(gzip_download(&file_name, data))
+}
+
+/// List the scheduled backups in `KUBARR_BACKUP_DIR`, newest first
+#[utoipa::path(
+    get,
+    path = "/api/system/backups",
+    tag = "System",
+    responses((status = 200, body = Vec<BackupFile>))
+)]
+async fn list_backups(_auth: Authorized<SystemManage>) -> Result<Json<Vec<BackupFile>>> {
+    Ok(Json(backup::list_stored().await?))
+}
+
+/// Download a scheduled backup
+#[utoipa::path(
+    get,
+    path = "/api/system/backups/{name}",
+    tag = "System",
+    params(("name" = String, Path, description = "Backup file name")),
+    responses(
+        (status = 200, description = "Backup archive", content_type = "application/gzip"),
+        (status = 404, description = "No such backup")
+    )
+)]
+async fn download_backup(
+    _auth: Authorized<SystemManage>,
+    Path(name): Path<String>,
+) -> Result<Response> {
+    let data = backup::read_stored(&name).await?;
+    Ok(gzip_download(&name, data))
+}
+
+/// Multipart form for a restore
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct RestoreBackupForm {
+    /// Passphrase the backup was made with
+    pub passphrase: String,
+    /// The backup archive
+    #[schema(value_type = String, format = Binary)]
+    pub archive: Vec<u8>,
+}
+
+/// Restore a backup
+///
+/// Replaces everything in the database with the backup's contents, in one
+/// transaction. The backup must come from the same Kubarr version and
+/// database. Everyone is signed out, including the caller.
+#[utoipa::path(
+    post,
+    path = "/api/system/restore",
+    tag = "System",
+    request_body(content = RestoreBackupForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, body = RestoreSummary),
+        (status = 400, description = "Wrong passphrase, or the backup is malformed or from another version")
+    )
+)]
+async fn restore_backup(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    mut multipart: Multipart,
+) -> Result<Json<RestoreSummary>> {
+    let invalid = |e: axum::extract::multipart::MultipartError| {
+        AppError::BadRequest(format!("Invalid restore form: {}", e))
+    };
+    let mut passphrase = None;
+    let mut archive = None;
+    while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+        match field.name() {
+            Some("passphrase") => passphrase = Some(field.text().await.map_err(invalid)?),
+            Some("archive") => archive = Some(field.bytes().await.map_err(invalid)?),
+            _ => {}
+        }
+    }
+    let (Some(passphrase), Some(archive)) = (passphrase, archive) else {
+        return Err(AppError::BadRequest(
+            "The restore form needs 'passphrase' and 'archive' fields".to_string(),
+        ));
+    };
+
+    let db = state.get_db().await?;
+    let user_id = auth.user_id();
+    let username = auth.user().username.clone();
+    let summary = match backup::restore(&db, &archive, &passphrase).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            let _ = state
+                .audit
+                .log_failure(
+                    AuditAction::BackupFailed,
+                    ResourceType::System,
+                    None,
+                    Some(user_id),
+                    Some(username.clone()),
+                    Some(serde_json::json!({ "restore": true })),
+                    None,
+                    None,
+                    &e.to_string(),
+                )
+                .await;
+            return Err(e);
+        }
+    };
+    runtime_config::reload(&db).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::BackupRestored,
+            ResourceType::System,
+            None,
+            Some(user_id),
+            Some(username.clone()),
+            Some(serde_json::json!({
+                "kubarr_version": summary.kubarr_version,
+                "created_at": summary.created_at,
+                "tables": summary.tables,
+                "rows": summary.rows,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let _ = state
+        .notification
+        .notify_event(
+            &AuditAction::BackupRestored,
+            Some(user_id),
+            Some(&username),
+            None,
+        )
+        .await;
+
+    Ok(Json(summary))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UpdateStatusQuery {
+    /// Check the release channel now instead of using the last result
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Get whether a newer Kubarr version is available
+///
+/// The release channel is checked in the background; `refresh=true` checks
+/// it now. A failed check is reported in `check_error`.
+#[utoipa::path(
+    get,
+    path = "/api/system/update",
+    tag = "System",
+    params(UpdateStatusQuery),
+    responses((status = 200, body = UpdateStatus))
+)]
+async fn get_update_status(
+    State(state): State<AppState>,
+    _auth: Authorized<SystemManage>,
+    Query(query): Query<UpdateStatusQuery>,
+) -> Result<Json<UpdateStatus>> {
+    if query.refresh {
+        // The error is kept and returned in the status
+        let _ = UPDATES.check().await;
+    }
+    let db = state.get_db().await?;
+    Ok(Json(updates::status(&db).await?))
+}
+
+/// Get the release notes of the running and the available Kubarr versions
+///
+/// Newer releases carry `warnings` for breaking changes called out in their
+/// notes and for major version bumps. The release channel is checked first
+/// if it has not been yet, or when `refresh=true`.
+#[utoipa::path(
+    get,
+    path = "/api/system/changelog",
+    tag = "System",
+    params(UpdateStatusQuery),
+    responses((status = 200, body = Changelog))
+)]
+async fn get_changelog(
+    _auth: Authorized<SystemManage>,
+    Query(query): Query<UpdateStatusQuery>,
+) -> Result<Json<Changelog>> {
+    if query.refresh || !UPDATES.checked() {
+        // The error is kept and returned in the changelog
+        let _ = UPDATES.check().await;
+    }
+    Ok(Json(updates::changelog()))
+}
+
+/// Upgrade Kubarr to a newer version
+///
+/// Backs up the current Helm release, upgrades it and returns while the new
+/// version rolls out. The release is rolled back if it does not become
+/// healthy; follow progress in `last_update` of `GET /api/system/update`.
+#[utoipa::path(
+    post,
+    path = "/api/system/update/apply",
+    tag = "System",
+    request_body = ApplyUpdateRequest,
+    responses(
+        (status = 200, body = UpdateRecord),
+        (status = 400, description = "No newer version, or Kubarr is not installed with Helm"),
+        (status = 409, description = "An update is already in progress")
+    )
+)]
+async fn apply_update(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    request: Option<Json<ApplyUpdateRequest>>,
+) -> Result<Json<UpdateRecord>> {
+    let db = state.get_db().await?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let record = updates::apply(&state, &db, request, auth.user_id()).await?;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::UpdateStarted,
+            ResourceType::System,
+            Some(CONFIG.updates.release_name.clone()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "from_version": record.from_version,
+                "to_version": record.to_version,
+            })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    Ok(Json(record))
+}
+
+/// Get the caller's scheduled report subscription
+#[utoipa::path(
+    get,
+    path = "/api/system/reports/subscription",
+    tag = "System",
+    responses((status = 200, body = ReportSubscriptionInfo))
+)]
+async fn get_report_subscription(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+) -> Result<Json<ReportSubscriptionInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(reports::subscription(&db, auth.user_id()).await?))
+}
+
+/// Opt in to or out of the scheduled report, or change when it is sent
+///
+/// Weekly reports go out on Mondays and monthly reports on the 1st, at
+/// `send_hour` in `timezone`.
+#[utoipa::path(
+    put,
+    path = "/api/system/reports/subscription",
+    tag = "System",
+    request_body = UpdateReportSubscriptionRequest,
+    responses(
+        (status = 200, body = ReportSubscriptionInfo),
+        (status = 400, description = "Unknown time zone or invalid hour")
+    )
+)]
+async fn update_report_subscription(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<UpdateReportSubscriptionRequest>,
+) -> Result<Json<ReportSubscriptionInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        reports::update_subscription(&db, auth.user_id(), request).await?,
+    ))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportPreviewQuery {
+    /// Period to cover (default: weekly)
+    #[serde(default)]
+    #[param(inline)]
+    pub frequency: ReportFrequency,
+    /// Time zone to show dates in (default: the caller's subscription)
+    pub timezone: Option<String>,
+}
+
+/// Build the report for the period ending now without sending it
+#[utoipa::path(
+    get,
+    path = "/api/system/reports/preview",
+    tag = "System",
+    params(ReportPreviewQuery),
+    responses(
+        (status = 200, body = Report),
+        (status = 400, description = "Unknown time zone")
+    )
+)]
+async fn preview_report(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Query(query): Query<ReportPreviewQuery>,
+) -> Result<Json<Report>> {
+    let db = state.get_db().await?;
+    let timezone = match query.timezone {
+        Some(timezone) => validate_timezone(&timezone)?,
+        None => reports::subscription(&db, auth.user_id()).await?.timezone,
+    };
+    Ok(Json(
+        reports::build(&db, query.frequency, &timezone, chrono::Utc::now()).await?,
+    ))
+}
+
+/// Email the report for the period ending now
+///
+/// Goes to the caller, or with `all_subscribers` to every subscribed admin.
+/// Does not affect when scheduled reports are sent.
+#[utoipa::path(
+    post,
+    path = "/api/system/reports/send",
+    tag = "System",
+    request_body = SendReportRequest,
+    responses((status = 200, body = SendReportResult))
+)]
+async fn send_report(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<SendReportRequest>,
+) -> Result<Json<SendReportResult>> {
+    let db = state.get_db().await?;
+    Ok(Json(
+        reports::send_now(&db, &state.notification, auth.user_id(), request).await?,
+    ))
+}
+
+/// Check the deployment against the security checklist
+///
+/// Covers default credentials, 2FA coverage, role separation, CORS, stored
+/// secrets, audit log integrity, stale sessions and brute-force protection.
+/// Each finding that does not pass comes with a remediation hint.
+#[utoipa::path(
+    get,
+    path = "/api/system/security-report",
+    tag = "System",
+    responses((status = 200, body = SecurityReport))
+)]
+async fn get_security_report(
+    State(state): State<AppState>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<SecurityReport>> {
+    let db = state.get_db().await?;
+    Ok(Json(security_report::build(&db, chrono::Utc::now()).await?))
+}
+
+/// List container registries, without their passwords
+#[utoipa::path(
+    get,
+    path = "/api/system/registries",
+    tag = "System",
+    responses((status = 200, body = Vec<RegistryInfo>))
+)]
+async fn list_registries(
+    State(state): State<AppState>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<Vec<RegistryInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(registries::list(&db).await?))
+}
+
+/// Add a container registry
+///
+/// Apps deployed afterwards pull images from `host` through `mirror`, and
+/// get the credentials in a pull secret.
+#[utoipa::path(
+    post,
+    path = "/api/system/registries",
+    tag = "System",
+    request_body = RegistryRequest,
+    responses(
+        (status = 200, body = RegistryInfo),
+        (status = 400, description = "Invalid host or mirror"),
+        (status = 409, description = "The registry already exists")
+    )
+)]
+async fn create_registry(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<RegistryRequest>,
+) -> Result<Json<RegistryInfo>> {
+    let db = state.get_db().await?;
+    let registry = registries::create(&db, request).await?;
+    audit_registry(&state, &auth, "created", &registry.host).await;
+    Ok(Json(registry))
+}
+
+/// Replace a container registry's settings
+///
+/// Leave out `password` to keep the stored one for the same username.
+#[utoipa::path(
+    put,
+    path = "/api/system/registries/{registry_id}",
+    tag = "System",
+    params(("registry_id" = i64, Path, description = "Registry ID")),
+    request_body = RegistryRequest,
+    responses(
+        (status = 200, body = RegistryInfo),
+        (status = 400, description = "Invalid host or mirror"),
+        (status = 404, description = "Registry not found"),
+        (status = 409, description = "Another registry has the host")
+    )
+)]
+async fn update_registry(
+    State(state): State<AppState>,
+    Path(registry_id): Path<i64>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<RegistryRequest>,
+) -> Result<Json<RegistryInfo>> {
+    let db = state.get_db().await?;
+    let registry = registries::update(&db, registry_id, request).await?;
+    audit_registry(&state, &auth, "updated", &registry.host).await;
+    Ok(Json(registry))
+}
+
+/// Remove a container registry
+#[utoipa::path(
+    delete,
+    path = "/api/system/registries/{registry_id}",
+    tag = "System",
+    params(("registry_id" = i64, Path, description = "Registry ID")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "Registry not found")
+    )
+)]
+async fn delete_registry(
+    State(state): State<AppState>,
+    Path(registry_id): Path<i64>,
+    auth: Authorized<SystemManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let registry = registries::delete(&db, registry_id).await?;
+    audit_registry(&state, &auth, "deleted", &registry.host).await;
+    Ok(Json(serde_json::json!({ "message": "Registry deleted" })))
+}
+
+/// Check that a registry, or its mirror, answers and takes the stored
+/// credentials
+#[utoipa::path(
+    post,
+    path = "/api/system/registries/{registry_id}/test",
+    tag = "System",
+    params(("registry_id" = i64, Path, description = "Registry ID")),
+    responses(
+        (status = 200, body = RegistryTest),
+        (status = 404, description = "Registry not found")
+    )
+)]
+async fn test_registry(
+    State(state): State<AppState>,
+    Path(registry_id): Path<i64>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<RegistryTest>> {
+    let db = state.get_db().await?;
+    Ok(Json(registries::test(&db, registry_id).await?))
+}
+
+/// List the external destinations Kubarr calls, with the proxy each one
+/// goes through
+#[utoipa::path(
+    get,
+    path = "/api/system/outbound",
+    tag = "System",
+    responses((status = 200, body = Vec<OutboundTarget>))
+)]
+async fn list_outbound_targets(
+    State(state): State<AppState>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<Vec<OutboundTarget>>> {
+    Ok(Json(outbound::targets(&state).await?))
+}
+
+/// Check that a destination can be reached through the current proxy
+/// settings
+///
+/// Any HTTP response counts as reachable; the status is reported as is.
+#[utoipa::path(
+    post,
+    path = "/api/system/outbound/{target}/test",
+    tag = "System",
+    params(("target" = String, Path, description = "Target name from the outbound list")),
+    responses(
+        (status = 200, body = ConnectivityTest),
+        (status = 404, description = "Unknown target")
+    )
+)]
+async fn test_outbound_target(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<ConnectivityTest>> {
+    let target = outbound::targets(&state)
+        .await?
+        .into_iter()
+        .find(|target| target.name == name)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown outbound target: {}", name)))?;
+    Ok(Json(outbound::test(target).await))
+}
+
+/// List trusted CA certificates, soonest expiry first
+#[utoipa::path(
+    get,
+    path = "/api/system/certificates",
+    tag = "System",
+    responses((status = 200, body = Vec<CertificateInfo>))
+)]
+async fn list_certificates(
+    State(state): State<AppState>,
+    _auth: Authorized<SystemManage>,
+) -> Result<Json<Vec<CertificateInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(trust_store::list(&db, Utc::now()).await?))
+}
+
+/// Trust the certificates in a PEM upload for outbound requests
+///
+/// A bundle adds each of its certificates under the same name.
+#[utoipa::path(
+    post,
+    path = "/api/system/certificates",
+    tag = "System",
+    request_body = CertificateRequest,
+    responses(
+        (status = 200, body = Vec<CertificateInfo>),
+        (status = 400, description = "No valid certificate, or a private key in the upload"),
+        (status = 409, description = "A certificate is already trusted")
+    )
+)]
+async fn create_certificates(
+    State(state): State<AppState>,
+    auth: Authorized<SystemManage>,
+    Json(request): Json<CertificateRequest>,
+) -> Result<Json<Vec<CertificateInfo>>> {
+    let db = state.get_db().await?;
+    let certificates = trust_store::create(&db, request).await?;
+    for certificate in &certificates {
+        audit_certificate(&state, &auth, "added", &certificate.subject).await;
+    }
+    Ok(Json(certificates))
+}
+
+/// Stop trusting a certificate
+#[utoipa::path(
+    delete,
+    path = "/api/system/certificates/{certificate_id}",
+    tag = "System",
+    params(("certificate_id" = i64, Path, description = "Certificate ID")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "Certificate not found")
+    )
+)]
+async fn delete_certificate(
+    State(state): State<AppState>,
+    Path(certificate_id): Path<i64>,
+    auth: Authorized<SystemManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let certificate = trust_store::delete(&db, certificate_id).await?;
+    audit_certificate(&state, &auth, "removed", &certificate.subject).await;
+    Ok(Json(
+        serde_json::json!({ "message": "Certificate removed" }),
+    ))
+}
+
+async fn audit_certificate(
+    state: &AppState,
+    auth: &Authorized<SystemManage>,
+    change: &str,
+    subject: &str,
+) {
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::SystemSettingChanged,
+            ResourceType::System,
+            Some("certificates".to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "certificate": subject, "change": change })),
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn audit_registry(
+    state: &AppState,
+    auth: &Authorized<SystemManage>,
+    change: &str,
+    host: &str,
+) {
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::SystemSettingChanged,
+            ResourceType::System,
+            Some("registries".to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({ "registry": host, "change": change })),
+            None,
+            None,
+        )
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoints::ApiDoc;
+    use std::collections::BTreeSet;
+    use utoipa::OpenApi;
+
+    fn documented_operations() -> BTreeSet<(String, String)> {
+        let mut openapi = ApiDoc::openapi();
+        let mut ops = BTreeSet::new();
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            for (method, operation) in operations_mut(item) {
+                if operation.is_some() {
+                    ops.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        ops
+    }
+
+    #[test]
+    fn test_route_access_covers_every_documented_operation() {
+        let documented = documented_operations();
+        let listed: BTreeSet<(String, String)> = ROUTE_ACCESS
+            .iter()
+            .map(|(m, p, _)| (m.to_string(), p.to_string()))
+            .collect();
+        assert_eq!(listed.len(), ROUTE_ACCESS.len(), "duplicate entries");
+        let missing: Vec<_> = documented.difference(&listed).collect();
+        let stale: Vec<_> = listed.difference(&documented).collect();
+        assert!(
+            missing.is_empty(),
+            "operations without access rule: {:?}",
+            missing
+        );
+        assert!(
+            stale.is_empty(),
+            "access rules for unknown operations: {:?}",
+            stale
+        );
+    }
+
+    #[test]
+    fn test_openapi_declares_security() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemes = &doc["components"]["securitySchemes"];
+        assert_eq!(schemes[COOKIE_SCHEME]["in"], "cookie");
+        assert_eq!(schemes[BEARER_SCHEME]["scheme"], "bearer");
+
+        let list_users = &doc["paths"]["/api/users"]["get"];
+        assert_eq!(list_users[PERMISSION_EXTENSION], "users.view");
+        assert_eq!(list_users["security"][0][COOKIE_SCHEME][0], "users.view");
+
+        let login = &doc["paths"]["/auth/login"]["post"];
+        assert_eq!(login["security"], serde_json::json!([{}]));
+        assert!(doc["paths"]["/api/users/me"]["get"]
+            .get(PERMISSION_EXTENSION)
+            .is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Authenticated, Authorized, TenantsManage};
+use crate::services::tenants::{
+    self, TenantInfo, TenantMemberInfo, TenantMemberRequest, TenantRequest, TenantScope,
+};
+use crate::state::AppState;
+
+/// Create tenants routes
+pub fn tenants_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_tenants).post(create_tenant))
+        .route(
+            "/{tenant_id}",
+            get(get_tenant).put(update_tenant).delete(delete_tenant),
+        )
+        .route("/{tenant_id}/members", get(list_members))
+        .route(
+            "/{tenant_id}/members/{user_id}",
+            put(set_member).delete(remove_member),
+        )
+        .with_state(state)
+}
+
+/// List tenants
+///
+/// Holders of `tenants.manage` see every tenant, everyone else the tenants
+/// they belong to.
+#[utoipa::path(
+    get,
+    path = "/api/tenants",
+    tag = "Tenants",
+    responses((status = 200, body = Vec<TenantInfo>))
+)]
+async fn list_tenants(
+    State(state): State<AppState>,
+    auth: Authenticated,
+) -> Result<Json<Vec<TenantInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(tenants::list(&db, auth.user_id()).await?))
+}
+
+/// Create a tenant
+#[utoipa::path(
+    post,
+    path = "/api/tenants",
+    tag = "Tenants",
+    request_body = TenantRequest,
+    responses(
+        (status = 201, body = TenantInfo),
+        (status = 400, description = "Invalid name"),
+        (status = 409, description = "A tenant with this name exists")
+    )
+)]
+async fn create_tenant(
+    State(state): State<AppState>,
+    _auth: Authorized<TenantsManage>,
+    Json(request): Json<TenantRequest>,
+) -> Result<(StatusCode, Json<TenantInfo>)> {
+    let db = state.get_db().await?;
+    let tenant = tenants::create(&db, &request).await?;
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
+
+/// Get a tenant the caller belongs to
+#[utoipa::path(
+    get,
+    path = "/api/tenants/{tenant_id}",
+    tag = "Tenants",
+    params(("tenant_id" = i64, Path, description = "Tenant ID")),
+    responses(
+        (status = 200, body = TenantInfo),
+        (status = 404, description = "Tenant not found")
+    )
+)]
+async fn get_tenant(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<i64>,
+    auth: Authenticated,
+) -> Result<Json<TenantInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(tenants::get(&db, auth.user_id(), tenant_id).await?))
+}
+
+/// Rename a tenant or change its description
+#[utoipa::path(
+    put,
+    path = "/api/tenants/{tenant_id}",
+    tag = "Tenants",
+    params(("tenant_id" = i64, Path, description = "Tenant ID")),
+    request_body = TenantRequest,
+    responses(
+        (status = 200, body = TenantInfo),
+        (status = 404, description = "Tenant not found"),
+        (status = 409, description = "A tenant with this name exists")
+    )
+)]
+async fn update_tenant(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<i64>,
+    _auth: Authorized<TenantsManage>,
+    Json(request): Json<TenantRequest>,
+) -> Result<Json<TenantInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(tenants::update(&db, tenant_id, &request).await?))
+}
+
+/// Delete a tenant; its apps become shared
+#[utoipa::path(
+    delete,
+    path = "/api/tenants/{tenant_id}",
+    tag = "Tenants",
+    params(("tenant_id" = i64, Path, description = "Tenant ID")),
+    responses(
+        (status = 204, description = "Tenant deleted"),
+        (status = 404, description = "Tenant not found")
+    )
+)]
+async fn delete_tenant(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<i64>,
+    _auth: Authorized<TenantsManage>,
+) -> Result<StatusCode> {
+    let db = state.get_db().await?;
+    tenants::delete(&db, tenant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the members of a tenant the caller belongs to
+#[utoipa::path(
+    get,
+    path = "/api/tenants/{tenant_id}/members",
+    tag = "Tenants",
+    params(("tenant_id" = i64, Path, description = "Tenant ID")),
+    responses(
+        (status = 200, body = Vec<TenantMemberInfo>),
+        (status = 404, description = "Tenant not found")
+    )
+)]
+async fn list_members(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<i64>,
+    auth: Authenticated,
+) -> Result<Json<Vec<TenantMemberInfo>>> {
+    let db = state.get_db().await?;
+    if !tenants::scope(&db, auth.user_id())
+        .await?
+        .includes(Some(tenant_id))
+    {
+        return Err(AppError::NotFound("Tenant not found".to_string()));
+    }
+    Ok(Json(tenants::members(&db, tenant_id).await?))
+}
+
+/// Add a user to a tenant, or make them its admin or not
+///
+/// Requires `tenants.manage` or being an admin of the tenant.
+#[utoipa::path(
+    put,
+    path = "/api/tenants/{tenant_id}/members/{user_id}",
+    tag = "Tenants",
+    params(
+        ("tenant_id" = i64, Path, description = "Tenant ID"),
+        ("user_id" = i64, Path, description = "User ID"),
+    ),
+    request_body = TenantMemberRequest,
+    responses(
+        (status = 200, body = TenantMemberInfo),
+        (status = 403, description = "Neither tenants.manage nor tenant admin"),
+        (status = 404, description = "Tenant or user not found")
+    )
+)]
+async fn set_member(
+    State(state): State<AppState>,
+    Path((tenant_id, user_id)): Path<(i64, i64)>,
+    auth: Authenticated,
+    Json(request): Json<TenantMemberRequest>,
+) -> Result<Json<TenantMemberInfo>> {
+    let db = state.get_db().await?;
+    tenants::require_admin(&db, auth.user_id(), tenant_id).await?;
+    Ok(Json(
+        tenants::set_member(&db, tenant_id, user_id, &request).await?,
+    ))
+}
+
+/// Remove a user from a tenant
+///
+/// Requires `tenants.manage` or being an admin of the tenant. Tenant admins
+/// cannot remove themselves, so a tenant is not left without an admin by
+/// accident.
+#[utoipa::path(
+    delete,
+    path = "/api/tenants/{tenant_id}/members/{user_id}",
+    tag = "Tenants",
+    params(
+        ("tenant_id" = i64, Path, description = "Tenant ID"),
+        ("user_id" = i64, Path, description = "User ID"),
+    ),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 400, description = "Tenant admins cannot remove themselves"),
+        (status = 403, description = "Neither tenants.manage nor tenant admin"),
+        (status = 404, description = "Tenant not found, or the user is not a member")
+    )
+)]
+async fn remove_member(
+    State(state): State<AppState>,
+    Path((tenant_id, user_id)): Path<(i64, i64)>,
+    auth: Authenticated,
+) -> Result<StatusCode> {
+    let db = state.get_db().await?;
+    tenants::require_admin(&db, auth.user_id(), tenant_id).await?;
+    if user_id == auth.user_id() && tenants::scope(&db, user_id).await? != TenantScope::All {
+        return Err(AppError::BadRequest(
+            "Tenant admins cannot remove themselves".to_string(),
+        ));
+    }
+    if !tenants::remove_member(&db, tenant_id, user_id).await? {
+        return Err(AppError::NotFound(
+            "User is not a member of this tenant".to_string(),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
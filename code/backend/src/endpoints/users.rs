@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+
 use axum::{
     extract::{Path, Query, State},
-    routing::{delete, get, patch, post},
-    Json, Router,
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{delete, get, patch, post, put},
+    Form, Json, Router,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
     QueryOrder, QuerySelect, Set,
@@ -13,11 +17,34 @@ use serde::{Deserialize, Serialize};
 use crate::endpoints::extractors::{get_user_app_access, get_user_permissions};
 use crate::error::{AppError, Result};
 use crate::middleware::{Authenticated, Authorized, UsersManage, UsersResetPassword, UsersView};
+use crate::models::audit_log::{AuditAction, ResourceType};
 use crate::models::prelude::*;
 use crate::models::{invite, role, two_factor_recovery_code, user, user_preferences, user_role};
+use crate::services::access::unexpired_roles;
+use crate::services::account_links::{
+    self, AccountLink, DiscoveryReport, SetAccountLink, SOURCE_MANUAL,
+};
+use crate::services::app_schedules::{self, OverrideInfo, ScheduleOwner, ScheduleWindow};
+use crate::services::approvals;
+use crate::services::client_certs::{self, ClientCertificateInfo, ClientCertificateRequest};
+use crate::services::deprovisioning::{self, DeprovisioningReport};
+use crate::services::i18n;
+use crate::services::invites;
+use crate::services::lockout;
+use crate::services::media_profiles::{self, SyncTarget};
+use crate::services::notification::routing::validate_timezone;
+use crate::services::provisioning::{self, ProvisionRequest, ProvisioningReport};
+use crate::services::qr::{self, QrFormat};
+use crate::services::quotas::{self, QuotaLimits, QuotaOwner, QuotaReport};
+use crate::services::role_expiry;
+use crate::services::service_accounts::{
+    self, CreateServiceAccountRequest, CreateTokenRequest, CreatedToken, ServiceAccountInfo,
+    ServiceAccountTokenInfo, UpdateServiceAccountRequest,
+};
+use crate::services::sessions::revoke_user_sessions;
 use crate::services::{
-    generate_recovery_codes, generate_totp_secret, get_totp_provisioning_uri, hash_password,
-    hash_recovery_code, verify_password, verify_totp,
+    decode_session_token, generate_recovery_codes, generate_totp_secret, get_totp_provisioning_uri,
+    hash_password, hash_recovery_code, verify_password, verify_totp,
 };
 use crate::state::AppState;
 
@@ -36,14 +63,37 @@ pub fn users_routes(state: AppState) -> Router {
             get(get_my_preferences).patch(update_my_preferences),
         )
         .route("/me/password", patch(change_own_password))
+        .route("/me/quota", get(get_my_quota))
         .route("/me/2fa/setup", post(setup_2fa))
         .route("/me/2fa/enable", post(enable_2fa))
         .route("/me/2fa/disable", post(disable_2fa))
         .route("/me/2fa/status", get(get_2fa_status))
+        .route("/me/2fa/qr", get(get_2fa_qr))
         .route("/me/2fa/recovery-codes", get(get_recovery_code_count))
         .route("/pending", get(list_pending_users))
         .route("/invites", get(list_invites).post(create_invite))
+        .route("/invites/batch", post(create_invite_batch))
         .route("/invites/{invite_id}", delete(delete_invite))
+        .route("/invites/{invite_id}/qr", get(get_invite_qr))
+        .route("/accounts/discover", post(discover_account_links))
+        .route(
+            "/service-accounts",
+            get(list_service_accounts).post(create_service_account),
+        )
+        .route(
+            "/service-accounts/{account_id}",
+            get(get_service_account)
+                .patch(update_service_account)
+                .delete(delete_service_account),
+        )
+        .route(
+            "/service-accounts/{account_id}/tokens",
+            get(list_service_account_tokens).post(create_service_account_token),
+        )
+        .route(
+            "/service-accounts/{account_id}/tokens/{token_id}",
+            delete(delete_service_account_token),
+        )
         .route(
             "/{user_id}",
             get(get_user).patch(update_user).delete(delete_user),
@@ -51,6 +101,58 @@ pub fn users_routes(state: AppState) -> Router {
         .route("/{user_id}/approve", post(approve_user))
         .route("/{user_id}/reject", post(reject_user))
         .route("/{user_id}/password", patch(admin_reset_password))
+        .route("/{user_id}/unlock", post(unlock_user))
+        .route(
+            "/{user_id}/quota",
+            get(get_user_quota)
+                .put(set_user_quota)
+                .delete(delete_user_quota),
+        )
+        .route(
+            "/{user_id}/roles/{role_id}",
+            put(assign_user_role).delete(unassign_user_role),
+        )
+        .route(
+            "/{user_id}/schedules",
+            get(get_user_schedules).put(set_user_schedules),
+        )
+        .route(
+            "/{user_id}/schedule-overrides",
+            get(list_schedule_overrides).post(grant_schedule_override),
+        )
+        .route(
+            "/{user_id}/schedule-overrides/{override_id}",
+            delete(revoke_schedule_override),
+        )
+        .route("/{user_id}/accounts", get(list_account_links))
+        .route(
+            "/{user_id}/client-certificates",
+            get(list_client_certificates).post(create_client_certificate),
+        )
+        .route(
+            "/{user_id}/client-certificates/{certificate_id}",
+            delete(delete_client_certificate),
+        )
+        .route("/{user_id}/provision", post(provision_user))
+        .route("/{user_id}/deprovision", post(deprovision_user))
+        .route(
+            "/{user_id}/accounts/{app_name}",
+            put(set_account_link).delete(delete_account_link),
+        )
+        .with_state(state)
+}
+
+/// Public share pages of invite batches (authenticated by the share token)
+pub fn invite_share_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/{token}", get(invite_share_page))
+        .with_state(state)
+}
+
+/// Public routes for emailed approve links (authenticated by the signed token)
+pub fn approve_link_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(approve_link_page).post(approve_link_confirm))
         .with_state(state)
 }
 
@@ -86,16 +188,65 @@ pub struct RoleInfo {
     pub id: i64,
     pub name: String,
     pub description: Option<String>,
+    /// When the assignment ends; `None` keeps it until it is removed
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct AssignRoleRequest {
+    /// When the assignment ends; leave out to keep it until it is removed
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GrantOverrideRequest {
+    /// App to let the user into, or `*` for every app
+    pub app_name: String,
+    /// When the schedules apply again
+    pub expires_at: DateTime<Utc>,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct PreferencesResponse {
     pub theme: String,
+    /// Locale notifications are written in; `None` uses the server default
+    pub locale: Option<String>,
+    /// Whether signing in from a new device sends a "was this you?" alert
+    pub login_alerts: bool,
+    /// Time zone app access schedules are read in; `None` uses the server
+    /// default
+    pub timezone: Option<String>,
+}
+
+impl From<Option<user_preferences::Model>> for PreferencesResponse {
+    fn from(preferences: Option<user_preferences::Model>) -> Self {
+        match preferences {
+            Some(p) => Self {
+                theme: p.theme,
+                locale: p.locale,
+                login_alerts: p.login_alerts,
+                timezone: p.timezone,
+            },
+            None => Self {
+                theme: "system".to_string(),
+                locale: None,
+                login_alerts: true,
+                timezone: None,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdatePreferences {
     pub theme: Option<String>,
+    /// Locale such as `nl`; an empty string goes back to the server default
+    pub locale: Option<String>,
+    pub login_alerts: Option<bool>,
+    /// IANA time zone such as `Europe/Amsterdam`; an empty string goes back
+    /// to the server default
+    pub timezone: Option<String>,
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
@@ -163,10 +314,16 @@ pub struct UserResponse {
     pub is_approved: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Failed sign-ins since the last successful one
+    pub failed_login_attempts: i32,
+    /// Set while sign-ins are refused after too many failures
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
     pub roles: Vec<RoleInfo>,
     pub preferences: PreferencesResponse,
     pub permissions: Vec<String>,
     pub allowed_apps: Vec<String>,
+    /// The user's accounts in managed apps
+    pub accounts: Vec<AccountLink>,
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
@@ -179,6 +336,54 @@ fn default_invite_days() -> i32 {
     7
 }
 
+/// Format of a QR code image
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct QrQuery {
+    /// `png` (default) or `svg`
+    #[serde(default)]
+    pub format: QrFormat,
+}
+
+/// A QR code image response; never cached, since it encodes a secret
+fn qr_response(text: &str, format: QrFormat) -> Result<Response> {
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type()),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        qr::render(text, format)?,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateInviteBatchRequest {
+    /// Shown on the share page and in the invite list
+    pub label: String,
+    /// Number of invites, at most 50
+    pub count: u32,
+    #[serde(default = "default_invite_days")]
+    pub expires_in_days: i32,
+    /// How long the share page stays reachable, at most 720 hours
+    #[serde(default = "default_share_hours")]
+    pub share_expires_in_hours: i64,
+}
+
+fn default_share_hours() -> i64 {
+    24
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct InviteBatchResponse {
+    pub id: i64,
+    pub label: String,
+    /// Public page with a QR code per invite that can still be used
+    pub share_url: String,
+    pub share_expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub invites: Vec<InviteResponse>,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct InviteResponse {
     pub id: i64,
@@ -189,6 +394,9 @@ pub struct InviteResponse {
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Batch the invite was created in, with its label
+    pub batch_id: Option<i64>,
+    pub label: Option<String>,
 }
 
 // ============================================================================
@@ -203,22 +411,20 @@ async fn get_user_with_roles(state: &AppState, user_id: i64) -> Result<UserRespo
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
     // Get user's roles via the junction table
-    let roles: Vec<role::Model> = Role::find()
-        .inner_join(UserRole)
+    let roles: Vec<(user_role::Model, Option<role::Model>)> = UserRole::find()
+        .find_also_related(Role)
         .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
         .all(&db)
         .await?;
 
     // Fetch user preferences (or use defaults)
     let preferences = UserPreferences::find_by_id(user_id).one(&db).await?;
 
-    let theme = preferences
-        .map(|p| p.theme)
-        .unwrap_or_else(|| "system".to_string());
-
     // Get user's permissions and allowed apps
     let permissions = get_user_permissions(&db, user_id).await;
     let allowed_apps = get_user_app_access(&db, user_id).await;
+    let accounts = account_links::list_links(&db, user_id).await?;
 
     Ok(UserResponse {
         id: found_user.id,
@@ -228,17 +434,23 @@ async fn get_user_with_roles(state: &AppState, user_id: i64) -> Result<UserRespo
         is_approved: found_user.is_approved,
         created_at: found_user.created_at,
         updated_at: found_user.updated_at,
+        failed_login_attempts: found_user.failed_login_attempts,
+        locked_until: found_user.locked_until.filter(|until| *until > Utc::now()),
         roles: roles
             .into_iter()
-            .map(|r| RoleInfo {
-                id: r.id,
-                name: r.name,
-                description: r.description,
+            .filter_map(|(assignment, r)| {
+                r.map(|r| RoleInfo {
+                    id: r.id,
+                    name: r.name,
+                    description: r.description,
+                    expires_at: assignment.expires_at,
+                })
             })
             .collect(),
-        preferences: PreferencesResponse { theme },
+        preferences: preferences.into(),
         permissions,
         allowed_apps,
+        accounts,
     })
 }
 
@@ -246,7 +458,7 @@ async fn get_user_with_roles(state: &AppState, user_id: i64) -> Result<UserRespo
 // Endpoint Handlers
 // ============================================================================
 
-/// List all users
+/// List all users, without service accounts
 #[doc = "Requires: users.view"]
 #[utoipa::path(
     get,
@@ -265,7 +477,12 @@ async fn list_users(
     let skip = params.skip.unwrap_or(0);
     let limit = params.limit.unwrap_or(100);
 
-    let users = User::find().offset(skip).limit(limit).all(&db).await?;
+    let users = User::find()
+        .filter(user::Column::IsServiceAccount.eq(false))
+        .offset(skip)
+        .limit(limit)
+        .all(&db)
+        .await?;
 
     let mut responses = Vec::new();
     for u in users {
@@ -437,8 +654,10 @@ async fn delete_own_account(
         }
     }
 
-    // Delete the user (cascade will handle related records)
+    // Deprovision first, then delete the user (cascade will handle related records)
+    let report = deprovisioning::deprovision_user(&state, user_id, false).await?;
     user_record.delete(&db).await?;
+    deprovisioning::audit(&state, AuditAction::UserDeleted, &report, auth.user()).await;
 
     Ok(Json(
         serde_json::json!({"message": "Account deleted successfully"}),
@@ -460,12 +679,7 @@ async fn get_my_preferences(
 ) -> Result<Json<PreferencesResponse>> {
     let db = state.get_db().await?;
     let preferences = UserPreferences::find_by_id(auth.user_id()).one(&db).await?;
-
-    let theme = preferences
-        .map(|p| p.theme)
-        .unwrap_or_else(|| "system".to_string());
-
-    Ok(Json(PreferencesResponse { theme }))
+    Ok(Json(preferences.into()))
 }
 
 /// Update current user's preferences
@@ -492,6 +706,17 @@ async fn update_my_preferences(
             ));
         }
     }
+    // Normalize the locale; an empty one clears the preference
+    let locale = match data.locale.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(tag) => Some(Some(i18n::parse_locale(tag)?.to_string())),
+        None => None,
+    };
+    let timezone = match data.timezone.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(zone) => Some(Some(validate_timezone(zone)?)),
+        None => None,
+    };
 
     let now = Utc::now();
     let user_id = auth.user_id();
@@ -501,9 +726,24 @@ async fn update_my_preferences(
 
     if let Some(existing_prefs) = existing {
         // Update existing preferences
-        if let Some(ref theme) = data.theme {
+        if data.theme.is_some()
+            || locale.is_some()
+            || data.login_alerts.is_some()
+            || timezone.is_some()
+        {
             let mut active_model: user_preferences::ActiveModel = existing_prefs.into();
-            active_model.theme = Set(theme.clone());
+            if let Some(ref theme) = data.theme {
+                active_model.theme = Set(theme.clone());
+            }
+            if let Some(locale) = locale {
+                active_model.locale = Set(locale);
+            }
+            if let Some(login_alerts) = data.login_alerts {
+                active_model.login_alerts = Set(login_alerts);
+            }
+            if let Some(timezone) = timezone {
+                active_model.timezone = Set(timezone);
+            }
             active_model.updated_at = Set(now);
             active_model.update(&db).await?;
         }
@@ -513,6 +753,9 @@ async fn update_my_preferences(
         let new_prefs = user_preferences::ActiveModel {
             user_id: Set(user_id),
             theme: Set(theme.to_string()),
+            locale: Set(locale.flatten()),
+            login_alerts: Set(data.login_alerts.unwrap_or(true)),
+            timezone: Set(timezone.flatten()),
             updated_at: Set(now),
         };
         new_prefs.insert(&db).await?;
@@ -520,12 +763,7 @@ async fn update_my_preferences(
 
     // Return updated preferences
     let preferences = UserPreferences::find_by_id(user_id).one(&db).await?;
-
-    let theme = preferences
-        .map(|p| p.theme)
-        .unwrap_or_else(|| "system".to_string());
-
-    Ok(Json(PreferencesResponse { theme }))
+    Ok(Json(preferences.into()))
 }
 
 /// List pending users
@@ -615,9 +853,11 @@ async fn create_user(
         let user_role_model = user_role::ActiveModel {
             user_id: Set(created_user.id),
             role_id: Set(*role_id),
+            ..Default::default()
         };
         user_role_model.insert(&db).await?;
     }
+    media_profiles::spawn_sync(&state, SyncTarget::Users(vec![created_user.id]));
 
     let response = get_user_with_roles(&state, created_user.id).await?;
     Ok(Json(response))
@@ -658,7 +898,7 @@ async fn get_user(
 async fn update_user(
     State(state): State<AppState>,
     Path(user_id): Path<i64>,
-    _auth: Authorized<UsersManage>,
+    auth: Authorized<UsersManage>,
     Json(data): Json<UpdateUserRequest>,
 ) -> Result<Json<UserResponse>> {
     let db = state.get_db().await?;
@@ -667,6 +907,7 @@ async fn update_user(
         .one(&db)
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let deactivated = existing_user.is_active && data.is_active == Some(false);
 
     let now = Utc::now();
     let mut user_model: user::ActiveModel = existing_user.into();
@@ -684,23 +925,39 @@ async fn update_user(
     user_model.updated_at = Set(now);
 
     user_model.update(&db).await?;
+    if deactivated {
+        deprovisioning::spawn_deactivation(&state, user_id, auth.user().clone());
+    }
 
-    // Update roles if provided
+    // Update roles if provided; kept roles keep their expiry
     if let Some(role_ids) = &data.role_ids {
-        // Delete existing roles
+        // Delete roles no longer listed
         UserRole::delete_many()
             .filter(user_role::Column::UserId.eq(user_id))
+            .filter(user_role::Column::RoleId.is_not_in(role_ids.clone()))
             .exec(&db)
             .await?;
+        let kept: Vec<i64> = UserRole::find()
+            .filter(user_role::Column::UserId.eq(user_id))
+            .all(&db)
+            .await?
+            .iter()
+            .map(|ur| ur.role_id)
+            .collect();
 
         // Add new roles
         for role_id in role_ids {
+            if kept.contains(role_id) {
+                continue;
+            }
             let user_role_model = user_role::ActiveModel {
                 user_id: Set(user_id),
                 role_id: Set(*role_id),
+                ..Default::default()
             };
             user_role_model.insert(&db).await?;
         }
+        media_profiles::spawn_sync(&state, SyncTarget::Users(vec![user_id]));
     }
 
     let response = get_user_with_roles(&state, user_id).await?;
@@ -708,6 +965,9 @@ async fn update_user(
 }
 
 /// Approve a user registration
+///
+/// Accounts in the apps of the user's provisioning profiles are created in
+/// the background.
 #[doc = "Requires: users.manage"]
 #[utoipa::path(
     post,
@@ -729,6 +989,7 @@ async fn approve_user(
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
+    let was_approved = existing_user.is_approved;
     let now = Utc::now();
     let mut user_model: user::ActiveModel = existing_user.into();
     user_model.is_approved = Set(true);
@@ -736,11 +997,198 @@ async fn approve_user(
     user_model.updated_at = Set(now);
 
     user_model.update(&db).await?;
+    if !was_approved {
+        provisioning::spawn_provision(&state, user_id);
+    }
+
+    let response = get_user_with_roles(&state, user_id).await?;
+    Ok(Json(response))
+}
+
+/// Unlock an account locked after too many failed sign-ins
+///
+/// Also clears the user's count of failed sign-ins.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/unlock",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = UserResponse)
+    )
+)]
+async fn unlock_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<UserResponse>> {
+    let db = state.get_db().await?;
+    let existing_user = User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let was_locked = lockout::is_locked(&existing_user, Utc::now());
+    lockout::reset(&db, &existing_user).await?;
+    if was_locked {
+        let _ = state
+            .audit
+            .log_success(
+                AuditAction::UserUpdated,
+                ResourceType::User,
+                Some(user_id.to_string()),
+                Some(auth.user_id()),
+                Some(auth.user().username.clone()),
+                Some(serde_json::json!({
+                    "unlocked": true,
+                    "target_username": existing_user.username,
+                })),
+                None,
+                None,
+            )
+            .await;
+    }
 
     let response = get_user_with_roles(&state, user_id).await?;
     Ok(Json(response))
 }
 
+/// Token of an emailed approve link
+#[derive(Debug, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+pub struct ApproveLinkParams {
+    pub token: String,
+}
+
+/// Page for a failed approve link, with a status matching the error
+fn approve_link_error(err: AppError) -> (StatusCode, Html<String>) {
+    let (status, message) = match err {
+        AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+        AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+        AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+        err => {
+            tracing::error!("Approve link failed: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Something went wrong, please try again".to_string(),
+            )
+        }
+    };
+    (
+        status,
+        Html(approvals::render_page("Approval failed", &message, None)),
+    )
+}
+
+/// Confirmation page for an emailed approve link
+///
+/// Public: the signed token stands in for a session. Nothing changes until
+/// the page's form is submitted.
+#[utoipa::path(
+    get,
+    path = "/api/users/approve-link",
+    tag = "Users",
+    params(ApproveLinkParams),
+    responses(
+        (status = 200, description = "HTML confirmation page", content_type = "text/html"),
+        (status = 401, description = "Invalid or expired link"),
+        (status = 403, description = "The admin may no longer approve users"),
+        (status = 404, description = "The account no longer exists")
+    )
+)]
+async fn approve_link_page(
+    State(state): State<AppState>,
+    Query(params): Query<ApproveLinkParams>,
+) -> Result<(StatusCode, Html<String>)> {
+    let db = state.get_db().await?;
+    Ok(match approvals::resolve_link(&db, &params.token).await {
+        Ok((pending, _)) if pending.is_approved => (
+            StatusCode::OK,
+            Html(approvals::render_page(
+                "Already approved",
+                &format!("{} has already been approved.", pending.username),
+                None,
+            )),
+        ),
+        Ok((pending, _)) => (
+            StatusCode::OK,
+            Html(approvals::render_page(
+                &format!("Approve {}?", pending.username),
+                &format!(
+                    "{} <{}> registered on {} and is waiting for approval.",
+                    pending.username,
+                    pending.email,
+                    pending.created_at.format("%Y-%m-%d")
+                ),
+                Some((&params.token, "Approve")),
+            )),
+        ),
+        Err(e) => approve_link_error(e),
+    })
+}
+
+/// Approve a user through an emailed approve link
+#[utoipa::path(
+    post,
+    path = "/api/users/approve-link",
+    tag = "Users",
+    request_body(content = ApproveLinkParams, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "HTML result page", content_type = "text/html"),
+        (status = 401, description = "Invalid or expired link"),
+        (status = 403, description = "The admin may no longer approve users"),
+        (status = 404, description = "The account no longer exists")
+    )
+)]
+async fn approve_link_confirm(
+    State(state): State<AppState>,
+    Form(params): Form<ApproveLinkParams>,
+) -> Result<(StatusCode, Html<String>)> {
+    let db = state.get_db().await?;
+    let (approved, admin, changed) = match approvals::approve_with_link(&db, &params.token).await {
+        Ok(outcome) => outcome,
+        Err(e) => return Ok(approve_link_error(e)),
+    };
+    if !changed {
+        return Ok((
+            StatusCode::OK,
+            Html(approvals::render_page(
+                "Already approved",
+                &format!("{} has already been approved.", approved.username),
+                None,
+            )),
+        ));
+    }
+
+    provisioning::spawn_provision(&state, approved.id);
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserApproved,
+            ResourceType::User,
+            Some(approved.id.to_string()),
+            Some(admin.id),
+            Some(admin.username.clone()),
+            Some(serde_json::json!({
+                "target_username": approved.username,
+                "method": "approve_link",
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok((
+        StatusCode::OK,
+        Html(approvals::render_page(
+            "Account approved",
+            &format!("{} can now sign in.", approved.username),
+            None,
+        )),
+    ))
+}
+
 /// Reject a user registration
 #[doc = "Requires: users.manage"]
 #[utoipa::path(
@@ -796,9 +1244,15 @@ async fn delete_user(
         .await?
         .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
+    // Deprovision first: deleting the user removes their roles and links
+    let report = deprovisioning::deprovision_user(&state, user_id, false).await?;
     existing_user.delete(&db).await?;
+    deprovisioning::audit(&state, AuditAction::UserDeleted, &report, auth.user()).await;
 
-    Ok(Json(serde_json::json!({"message": "User deleted"})))
+    Ok(Json(serde_json::json!({
+        "message": "User deleted",
+        "deprovisioning": report,
+    })))
 }
 
 /// List all invites
@@ -821,6 +1275,13 @@ async fn list_invites(
         .all(&db)
         .await?;
 
+    let batches: HashMap<i64, String> = InviteBatch::find()
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|b| (b.id, b.label))
+        .collect();
+
     let mut responses = Vec::new();
     for inv in invites {
         let created_by = User::find_by_id(inv.created_by_id).one(&db).await?;
@@ -842,6 +1303,8 @@ async fn list_invites(
             expires_at: inv.expires_at,
             created_at: inv.created_at,
             used_at: inv.used_at,
+            label: inv.batch_id.and_then(|id| batches.get(&id).cloned()),
+            batch_id: inv.batch_id,
         });
     }
 
@@ -895,9 +1358,115 @@ async fn create_invite(
         expires_at: created_invite.expires_at,
         created_at: created_invite.created_at,
         used_at: created_invite.used_at,
+        batch_id: None,
+        label: None,
+    }))
+}
+
+/// QR code of an invite's registration link
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    get,
+    path = "/api/users/invites/{invite_id}/qr",
+    tag = "Users",
+    params(("invite_id" = i64, Path, description = "Invite ID"), QrQuery),
+    responses(
+        (status = 200, description = "QR code image, PNG or SVG", content_type = "image/png"),
+        (status = 404, description = "Invite not found")
+    )
+)]
+async fn get_invite_qr(
+    State(state): State<AppState>,
+    Path(invite_id): Path<i64>,
+    Query(query): Query<QrQuery>,
+    _auth: Authorized<UsersManage>,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let found = Invite::find_by_id(invite_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Invite not found".to_string()))?;
+    qr_response(&invites::register_link(&found.code), query.format)
+}
+
+/// Create a batch of invites with a shared label
+///
+/// The response links to a public share page listing a QR code per invite,
+/// for onboarding a group without handing out links one by one.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/invites/batch",
+    tag = "Users",
+    request_body = CreateInviteBatchRequest,
+    responses(
+        (status = 200, body = InviteBatchResponse),
+        (status = 400, description = "Invalid label, count or share page expiry")
+    )
+)]
+async fn create_invite_batch(
+    State(state): State<AppState>,
+    auth: Authorized<UsersManage>,
+    Json(data): Json<CreateInviteBatchRequest>,
+) -> Result<Json<InviteBatchResponse>> {
+    let db = state.get_db().await?;
+    let (batch, created) = invites::create_batch(
+        &db,
+        auth.user_id(),
+        &data.label,
+        data.count,
+        data.expires_in_days,
+        data.share_expires_in_hours,
+    )
+    .await?;
+
+    Ok(Json(InviteBatchResponse {
+        id: batch.id,
+        share_url: invites::share_link(&batch.share_token),
+        share_expires_at: batch.share_expires_at,
+        created_at: batch.created_at,
+        invites: created
+            .into_iter()
+            .map(|inv| InviteResponse {
+                id: inv.id,
+                code: inv.code,
+                created_by_username: auth.user().username.clone(),
+                used_by_username: None,
+                is_used: inv.is_used,
+                expires_at: inv.expires_at,
+                created_at: inv.created_at,
+                used_at: inv.used_at,
+                batch_id: inv.batch_id,
+                label: Some(batch.label.clone()),
+            })
+            .collect(),
+        label: batch.label,
     }))
 }
 
+/// Share page of an invite batch
+///
+/// Public: the share token stands in for a session. Lists a QR code of the
+/// registration link for each invite that is neither used nor expired.
+#[utoipa::path(
+    get,
+    path = "/api/invites/share/{token}",
+    tag = "Users",
+    params(("token" = String, Path, description = "Share token of the batch")),
+    responses(
+        (status = 200, description = "Printable HTML page", content_type = "text/html"),
+        (status = 404, description = "Unknown or expired share page")
+    )
+)]
+async fn invite_share_page(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Html<String>> {
+    let db = state.get_db().await?;
+    let (batch, usable) = invites::resolve_share(&db, &token, Utc::now()).await?;
+    Ok(Html(invites::render_share_page(&batch, &usable)?))
+}
+
 /// Delete an invite
 #[doc = "Requires: users.manage"]
 #[utoipa::path(
@@ -941,6 +1510,7 @@ async fn delete_invite(
 )]
 async fn change_own_password(
     State(state): State<AppState>,
+    headers: HeaderMap,
     auth: Authenticated,
     Json(data): Json<ChangeOwnPasswordRequest>,
 ) -> Result<Json<serde_json::Value>> {
@@ -972,11 +1542,25 @@ async fn change_own_password(
     let mut user_model: user::ActiveModel = user_record.into();
     user_model.hashed_password = Set(hashed);
     user_model.updated_at = Set(now);
-    user_model.update(&db).await?;
+    let user_record = user_model.update(&db).await?;
+
+    // Sign out everywhere else; the session making the change stays valid
+    let current_session = crate::endpoints::auth::extract_session_token(&headers)
+        .and_then(|token| decode_session_token(&token).ok())
+        .map(|claims| claims.sid);
+    let revoked = after_password_change(
+        &state,
+        &db,
+        &user_record,
+        auth.user(),
+        current_session.as_deref(),
+    )
+    .await?;
 
-    Ok(Json(
-        serde_json::json!({"message": "Password changed successfully"}),
-    ))
+    Ok(Json(serde_json::json!({
+        "message": "Password changed successfully",
+        "sessions_revoked": revoked
+    })))
 }
 
 /// Admin reset password for another user
@@ -1025,11 +1609,77 @@ async fn admin_reset_password(
     let mut user_model: user::ActiveModel = user_record.into();
     user_model.hashed_password = Set(hashed);
     user_model.updated_at = Set(now);
-    user_model.update(&db).await?;
+    let user_record = user_model.update(&db).await?;
 
-    Ok(Json(
-        serde_json::json!({"message": "Password reset successfully"}),
-    ))
+    let revoked = after_password_change(&state, &db, &user_record, auth.user(), None).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "Password reset successfully",
+        "sessions_revoked": revoked
+    })))
+}
+
+/// Revoke the user's sessions except `keep`, audit the change and notify
+/// the user, returning the number of sessions revoked
+///
+/// `actor` is the user who changed the password: the user themselves or an
+/// admin resetting it.
+async fn after_password_change(
+    state: &AppState,
+    db: &sea_orm::DatabaseConnection,
+    target: &user::Model,
+    actor: &user::Model,
+    keep: Option<&str>,
+) -> Result<u64> {
+    let revoked = revoke_user_sessions(db, target.id, keep).await?;
+    let by_admin = actor.id != target.id;
+
+    let _ = state
+        .audit
+        .log(
+            AuditAction::PasswordChanged,
+            ResourceType::User,
+            Some(target.id.to_string()),
+            Some(actor.id),
+            Some(actor.username.clone()),
+            Some(serde_json::json!({
+                "method": if by_admin { "admin_reset" } else { "self" },
+                "target_username": target.username,
+                "sessions_revoked": revoked,
+            })),
+            None,
+            None,
+            true,
+            None,
+        )
+        .await;
+
+    let detail = if by_admin {
+        format!(
+            "reset by {}; {} session(s) signed out",
+            actor.username, revoked
+        )
+    } else {
+        format!("{} other session(s) signed out", revoked)
+    };
+    if let Err(e) = state
+        .notification
+        .notify_security(
+            &AuditAction::PasswordChanged,
+            target.id,
+            &target.username,
+            Some(&detail),
+        )
+        .await
+    {
+        tracing::warn!(
+            "Failed to notify user {} of password change: {}",
+            target.id,
+            e
+        );
+    }
+
+    Ok(revoked)
 }
 
 // ============================================================================
@@ -1042,6 +1692,7 @@ async fn user_requires_2fa(db: &sea_orm::DatabaseConnection, user_id: i64) -> bo
     let roles: Vec<role::Model> = Role::find()
         .inner_join(UserRole)
         .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
         .all(db)
         .await
         .unwrap_or_default();
@@ -1080,7 +1731,7 @@ async fn setup_2fa(
     let secret = generate_totp_secret();
     let account_name = &user_record.email;
 
-    // Get provisioning URI (frontend generates QR code)
+    // Get provisioning URI; GET /me/2fa/qr renders it as a QR code
     let provisioning_uri = get_totp_provisioning_uri(&secret, account_name)?;
 
     // Store the secret (but don't enable yet - user must verify first)
@@ -1096,6 +1747,42 @@ async fn setup_2fa(
     }))
 }
 
+/// QR code of the provisioning URI from 2FA setup
+///
+/// Only available between setup and enabling 2FA, so an enabled secret
+/// cannot be shown again.
+#[utoipa::path(
+    get,
+    path = "/api/users/me/2fa/qr",
+    tag = "Users",
+    params(QrQuery),
+    responses(
+        (status = 200, description = "QR code image, PNG or SVG", content_type = "image/png"),
+        (status = 400, description = "No 2FA setup in progress")
+    )
+)]
+async fn get_2fa_qr(
+    State(state): State<AppState>,
+    Query(query): Query<QrQuery>,
+    auth: Authenticated,
+) -> Result<Response> {
+    let db = state.get_db().await?;
+    let user_record = User::find_by_id(auth.user_id())
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let secret = match (&user_record.totp_secret, user_record.totp_enabled) {
+        (Some(secret), false) => secret,
+        _ => {
+            return Err(AppError::BadRequest(
+                "No 2FA setup in progress. Start one with /api/users/me/2fa/setup.".to_string(),
+            ))
+        }
+    };
+    let provisioning_uri = get_totp_provisioning_uri(secret, &user_record.email)?;
+    qr_response(&provisioning_uri, query.format)
+}
+
 /// Enable 2FA - verify the code and activate
 #[utoipa::path(
     post,
@@ -1288,3 +1975,1042 @@ async fn get_recovery_code_count(
 
     Ok(Json(TwoFactorRecoveryCodesResponse { remaining }))
 }
+
+// ============================================================================
+// Resource Quota Endpoints
+// ============================================================================
+
+/// Get the current user's resource quota and usage
+#[utoipa::path(
+    get,
+    path = "/api/users/me/quota",
+    tag = "Users",
+    responses((status = 200, body = QuotaReport))
+)]
+async fn get_my_quota(
+    State(state): State<AppState>,
+    auth: Authenticated,
+) -> Result<Json<QuotaReport>> {
+    let db = state.get_db().await?;
+    Ok(Json(quotas::report(&db, auth.user_id()).await?))
+}
+
+/// Get a user's effective resource quota and usage
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/quota",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = QuotaReport),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn get_user_quota(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<QuotaReport>> {
+    let db = state.get_db().await?;
+    User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    Ok(Json(quotas::report(&db, user_id).await?))
+}
+
+/// Set a user's own resource quota, overriding their roles' quotas
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/quota",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = QuotaLimits,
+    responses(
+        (status = 200, body = QuotaLimits),
+        (status = 400, description = "Negative limit"),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn set_user_quota(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersManage>,
+    Json(limits): Json<QuotaLimits>,
+) -> Result<Json<QuotaLimits>> {
+    let db = state.get_db().await?;
+    User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    Ok(Json(
+        quotas::set_quota(&db, QuotaOwner::User(user_id), &limits).await?,
+    ))
+}
+
+/// Remove a user's own resource quota, so their roles' quotas apply
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/quota",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "Quota removed"),
+        (status = 404, description = "No quota configured")
+    )
+)]
+async fn delete_user_quota(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    if !quotas::delete_quota(&db, QuotaOwner::User(user_id)).await? {
+        return Err(AppError::NotFound("No quota configured".to_string()));
+    }
+    Ok(Json(serde_json::json!({"message": "Quota removed"})))
+}
+
+/// Whether someone other than `user_id` holds the admin role without an
+/// expiry
+async fn has_other_permanent_admin(
+    db: &sea_orm::DatabaseConnection,
+    admin_role_id: i64,
+    user_id: i64,
+) -> Result<bool> {
+    Ok(UserRole::find()
+        .filter(user_role::Column::RoleId.eq(admin_role_id))
+        .filter(user_role::Column::UserId.ne(user_id))
+        .filter(user_role::Column::ExpiresAt.is_null())
+        .one(db)
+        .await?
+        .is_some())
+}
+
+/// Assign a role to a user, optionally until a given time
+///
+/// Assigning a role the user already has changes its expiry.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/roles/{role_id}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("role_id" = i64, Path, description = "Role ID")
+    ),
+    request_body = AssignRoleRequest,
+    responses(
+        (status = 200, body = UserResponse),
+        (status = 400, description = "Expiry in the past, or no administrator would be left without one"),
+        (status = 404, description = "User or role not found")
+    )
+)]
+async fn assign_user_role(
+    State(state): State<AppState>,
+    Path((user_id, role_id)): Path<(i64, i64)>,
+    auth: Authorized<UsersManage>,
+    Json(data): Json<AssignRoleRequest>,
+) -> Result<Json<UserResponse>> {
+    let db = state.get_db().await?;
+    let target = User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let assigned_role = Role::find_by_id(role_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Role not found".to_string()))?;
+    role_expiry::validate_expiry(data.expires_at, Utc::now())?;
+    if assigned_role.name == "admin"
+        && data.expires_at.is_some()
+        && !has_other_permanent_admin(&db, role_id, user_id).await?
+    {
+        return Err(AppError::BadRequest(
+            "At least one administrator must keep the admin role without an expiry".to_string(),
+        ));
+    }
+
+    match UserRole::find_by_id((user_id, role_id)).one(&db).await? {
+        Some(existing) => {
+            let mut active: user_role::ActiveModel = existing.into();
+            active.expires_at = Set(data.expires_at);
+            active.expiry_notified = Set(false);
+            active.update(&db).await?;
+        }
+        None => {
+            user_role::ActiveModel {
+                user_id: Set(user_id),
+                role_id: Set(role_id),
+                expires_at: Set(data.expires_at),
+                expiry_notified: Set(false),
+            }
+            .insert(&db)
+            .await?;
+        }
+    }
+    media_profiles::spawn_sync(&state, SyncTarget::Users(vec![user_id]));
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::RoleAssigned,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.username,
+                "role_id": role_id,
+                "role_name": assigned_role.name,
+                "expires_at": data.expires_at,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(get_user_with_roles(&state, user_id).await?))
+}
+
+/// Remove a role from a user
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/roles/{role_id}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("role_id" = i64, Path, description = "Role ID")
+    ),
+    responses(
+        (status = 200, body = UserResponse),
+        (status = 400, description = "No administrator would be left"),
+        (status = 404, description = "User does not have the role")
+    )
+)]
+async fn unassign_user_role(
+    State(state): State<AppState>,
+    Path((user_id, role_id)): Path<(i64, i64)>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<UserResponse>> {
+    let db = state.get_db().await?;
+    let assignment = UserRole::find_by_id((user_id, role_id))
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User does not have this role".to_string()))?;
+    let removed_role = Role::find_by_id(role_id).one(&db).await?;
+    if removed_role.as_ref().is_some_and(|r| r.name == "admin")
+        && !has_other_permanent_admin(&db, role_id, user_id).await?
+    {
+        return Err(AppError::BadRequest(
+            "Cannot remove the admin role from the only administrator".to_string(),
+        ));
+    }
+    assignment.delete(&db).await?;
+    media_profiles::spawn_sync(&state, SyncTarget::Users(vec![user_id]));
+
+    let target = User::find_by_id(user_id).one(&db).await?;
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::RoleUnassigned,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.map(|u| u.username),
+                "role_id": role_id,
+                "role_name": removed_role.map(|r| r.name),
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(get_user_with_roles(&state, user_id).await?))
+}
+
+// ============================================================================
+// App Access Schedules
+// ============================================================================
+
+async fn find_user(db: &sea_orm::DatabaseConnection, user_id: i64) -> Result<user::Model> {
+    User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+}
+
+/// Get the app access schedules set on a user
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/schedules",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = Vec<ScheduleWindow>),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn get_user_schedules(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<ScheduleWindow>>> {
+    let db = state.get_db().await?;
+    find_user(&db, user_id).await?;
+    Ok(Json(
+        app_schedules::get_schedules(&db, ScheduleOwner::User(user_id)).await?,
+    ))
+}
+
+/// Replace a user's own app access schedules, overriding their roles'
+///
+/// An empty list removes them, so the roles' schedules apply again.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/schedules",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = Vec<ScheduleWindow>,
+    responses(
+        (status = 200, body = Vec<ScheduleWindow>),
+        (status = 400, description = "Invalid window"),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn set_user_schedules(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersManage>,
+    Json(windows): Json<Vec<ScheduleWindow>>,
+) -> Result<Json<Vec<ScheduleWindow>>> {
+    let db = state.get_db().await?;
+    find_user(&db, user_id).await?;
+    Ok(Json(
+        app_schedules::set_schedules(&db, ScheduleOwner::User(user_id), &windows).await?,
+    ))
+}
+
+/// List a user's schedule overrides that have not expired
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/schedule-overrides",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = Vec<OverrideInfo>),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn list_schedule_overrides(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<OverrideInfo>>> {
+    let db = state.get_db().await?;
+    find_user(&db, user_id).await?;
+    Ok(Json(app_schedules::list_overrides(&db, user_id).await?))
+}
+
+/// Let a user past their app access schedules until a given time
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/schedule-overrides",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = GrantOverrideRequest,
+    responses(
+        (status = 200, body = OverrideInfo),
+        (status = 400, description = "Missing app or expiry in the past"),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn grant_schedule_override(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+    Json(data): Json<GrantOverrideRequest>,
+) -> Result<Json<OverrideInfo>> {
+    let db = state.get_db().await?;
+    let target = find_user(&db, user_id).await?;
+    let granted = app_schedules::grant_override(
+        &db,
+        user_id,
+        &data.app_name,
+        data.expires_at,
+        data.reason,
+        auth.user_id(),
+    )
+    .await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::ScheduleOverrideGranted,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.username,
+                "override_id": granted.id,
+                "app_name": granted.app_name,
+                "expires_at": granted.expires_at,
+                "reason": granted.reason,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let details = format!(
+        "{} until {}",
+        granted.app_name,
+        granted.expires_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    if let Err(e) = state
+        .notification
+        .notify_user_event(
+            &AuditAction::ScheduleOverrideGranted,
+            user_id,
+            Some(&auth.user().username),
+            Some(&details),
+        )
+        .await
+    {
+        tracing::warn!(
+            "Failed to send schedule_override_granted notification: {}",
+            e
+        );
+    }
+
+    Ok(Json(granted))
+}
+
+/// End a schedule override early
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/schedule-overrides/{override_id}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("override_id" = i64, Path, description = "Override ID")
+    ),
+    responses(
+        (status = 200, description = "Override revoked"),
+        (status = 404, description = "Override not found")
+    )
+)]
+async fn revoke_schedule_override(
+    State(state): State<AppState>,
+    Path((user_id, override_id)): Path<(i64, i64)>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let revoked = app_schedules::revoke_override(&db, user_id, override_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Override not found".to_string()))?;
+
+    let target = User::find_by_id(user_id).one(&db).await?;
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::ScheduleOverrideRevoked,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.map(|u| u.username),
+                "override_id": revoked.id,
+                "app_name": revoked.app_name,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({"message": "Override revoked"})))
+}
+
+// ============================================================================
+// Client Certificates
+// ============================================================================
+
+/// List the client certificates a user can authenticate with
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/client-certificates",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = Vec<ClientCertificateInfo>),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn list_client_certificates(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<ClientCertificateInfo>>> {
+    let db = state.get_db().await?;
+    find_user(&db, user_id).await?;
+    Ok(Json(client_certs::list(&db, user_id, Utc::now()).await?))
+}
+
+/// Register a client certificate for a user
+///
+/// Requests whose verified client certificate matches are authenticated as
+/// the user when `KUBARR_CLIENT_CERT_HEADER` is set.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/client-certificates",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = ClientCertificateRequest,
+    responses(
+        (status = 200, body = ClientCertificateInfo),
+        (status = 400, description = "Not exactly one valid, unexpired certificate"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "Certificate already registered")
+    )
+)]
+async fn create_client_certificate(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+    Json(req): Json<ClientCertificateRequest>,
+) -> Result<Json<ClientCertificateInfo>> {
+    let db = state.get_db().await?;
+    let target = find_user(&db, user_id).await?;
+    let certificate = client_certs::create(&db, user_id, req).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.username,
+                "client_certificate_added": certificate.fingerprint,
+                "subject": certificate.subject,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(certificate))
+}
+
+/// Remove a user's client certificate
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/client-certificates/{certificate_id}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("certificate_id" = i64, Path, description = "Certificate ID")
+    ),
+    responses(
+        (status = 200, description = "Certificate removed"),
+        (status = 404, description = "Certificate not found")
+    )
+)]
+async fn delete_client_certificate(
+    State(state): State<AppState>,
+    Path((user_id, certificate_id)): Path<(i64, i64)>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let removed = client_certs::delete(&db, user_id, certificate_id).await?;
+
+    let target = User::find_by_id(user_id).one(&db).await?;
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(user_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": target.map(|u| u.username),
+                "client_certificate_removed": removed.fingerprint,
+                "subject": removed.subject,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({"message": "Certificate removed"})))
+}
+
+// ============================================================================
+// Account Links
+// ============================================================================
+
+/// List a user's accounts in managed apps
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/accounts",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    responses(
+        (status = 200, body = Vec<AccountLink>),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn list_account_links(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<AccountLink>>> {
+    let db = state.get_db().await?;
+    find_user(&db, user_id).await?;
+    Ok(Json(account_links::list_links(&db, user_id).await?))
+}
+
+/// Link a user to their account in a managed app
+///
+/// Replaces the user's link for that app.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/accounts/{app_name}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("app_name" = String, Path, description = "App name")
+    ),
+    request_body = SetAccountLink,
+    responses(
+        (status = 200, body = AccountLink),
+        (status = 400, description = "Invalid account ID"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "Account already linked to another user")
+    )
+)]
+async fn set_account_link(
+    State(state): State<AppState>,
+    Path((user_id, app_name)): Path<(i64, String)>,
+    _auth: Authorized<UsersManage>,
+    Json(req): Json<SetAccountLink>,
+) -> Result<Json<AccountLink>> {
+    let db = state.get_db().await?;
+    let link = account_links::set_link(&db, user_id, &app_name, &req, SOURCE_MANUAL).await?;
+    Ok(Json(link.into()))
+}
+
+/// Remove a user's link to an app account
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/accounts/{app_name}",
+    tag = "Users",
+    params(
+        ("user_id" = i64, Path, description = "User ID"),
+        ("app_name" = String, Path, description = "App name")
+    ),
+    responses(
+        (status = 200, description = "Link removed"),
+        (status = 404, description = "No account linked for this app")
+    )
+)]
+async fn delete_account_link(
+    State(state): State<AppState>,
+    Path((user_id, app_name)): Path<(i64, String)>,
+    _auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    if !account_links::remove_link(&db, user_id, &app_name).await? {
+        return Err(AppError::NotFound(
+            "No account linked for this app".to_string(),
+        ));
+    }
+    Ok(Json(serde_json::json!({"message": "Link removed"})))
+}
+
+/// Link users to the matching accounts in configured apps
+///
+/// Asks Jellyfin, Jellyseerr and Overseerr for their users and links each
+/// account to the user with the same name or email. Existing links are kept.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/accounts/discover",
+    tag = "Users",
+    responses((status = 200, body = DiscoveryReport))
+)]
+async fn discover_account_links(
+    State(state): State<AppState>,
+    _auth: Authorized<UsersManage>,
+) -> Result<Json<DiscoveryReport>> {
+    Ok(Json(account_links::discover(&state).await?))
+}
+
+/// Create or link a user's accounts in the apps of their provisioning
+/// profiles
+///
+/// With `dry_run`, reports what would be done without touching any app.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/provision",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = ProvisionRequest,
+    responses(
+        (status = 200, body = ProvisioningReport),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn provision_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    _auth: Authorized<UsersManage>,
+    Json(req): Json<ProvisionRequest>,
+) -> Result<Json<ProvisioningReport>> {
+    Ok(Json(
+        provisioning::provision_user(&state, user_id, req.dry_run).await?,
+    ))
+}
+
+/// Deprovision a deactivated user
+///
+/// Revokes their sessions, 2FA challenges, unused invites and share
+/// password, and disables or deletes their app accounts as their
+/// provisioning profiles say. This happens by itself when a user is
+/// deactivated or deleted; use it to preview the outcome with `dry_run`, or
+/// to retry after an app failed. Only a dry run is allowed for active users.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/deprovision",
+    tag = "Users",
+    params(("user_id" = i64, Path, description = "User ID")),
+    request_body = ProvisionRequest,
+    responses(
+        (status = 200, body = DeprovisioningReport),
+        (status = 400, description = "The user is still active"),
+        (status = 404, description = "User not found")
+    )
+)]
+async fn deprovision_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+    Json(req): Json<ProvisionRequest>,
+) -> Result<Json<DeprovisioningReport>> {
+    let db = state.get_db().await?;
+    let target = find_user(&db, user_id).await?;
+    if !req.dry_run && target.is_active {
+        return Err(AppError::BadRequest(
+            "Deactivate the user before deprovisioning them".to_string(),
+        ));
+    }
+    let report = deprovisioning::deprovision_user(&state, user_id, req.dry_run).await?;
+    if !req.dry_run {
+        deprovisioning::audit(&state, AuditAction::UserDeactivated, &report, auth.user()).await;
+    }
+    Ok(Json(report))
+}
+
+/// List service accounts
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/service-accounts",
+    tag = "Users",
+    responses((status = 200, body = Vec<ServiceAccountInfo>))
+)]
+async fn list_service_accounts(
+    State(state): State<AppState>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<ServiceAccountInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(service_accounts::list(&db).await?))
+}
+
+/// Create a service account
+///
+/// Service accounts cannot sign in; they call the API with tokens created
+/// for them.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/service-accounts",
+    tag = "Users",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 200, body = ServiceAccountInfo),
+        (status = 400, description = "Invalid name or unknown role"),
+        (status = 409, description = "Name already taken")
+    )
+)]
+async fn create_service_account(
+    State(state): State<AppState>,
+    auth: Authorized<UsersManage>,
+    Json(req): Json<CreateServiceAccountRequest>,
+) -> Result<Json<ServiceAccountInfo>> {
+    let db = state.get_db().await?;
+    let account = service_accounts::create(&db, req).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserCreated,
+            ResourceType::User,
+            Some(account.id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": account.name,
+                "service_account": true,
+                "roles": account.roles.iter().map(|r| &r.name).collect::<Vec<_>>(),
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(account))
+}
+
+/// Get a service account
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/service-accounts/{account_id}",
+    tag = "Users",
+    params(("account_id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, body = ServiceAccountInfo),
+        (status = 404, description = "Service account not found")
+    )
+)]
+async fn get_service_account(
+    State(state): State<AppState>,
+    Path(account_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<ServiceAccountInfo>> {
+    let db = state.get_db().await?;
+    Ok(Json(service_accounts::get(&db, account_id).await?))
+}
+
+/// Activate or deactivate a service account, or replace its roles
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    patch,
+    path = "/api/users/service-accounts/{account_id}",
+    tag = "Users",
+    params(("account_id" = i64, Path, description = "Service account ID")),
+    request_body = UpdateServiceAccountRequest,
+    responses(
+        (status = 200, body = ServiceAccountInfo),
+        (status = 400, description = "Unknown role"),
+        (status = 404, description = "Service account not found")
+    )
+)]
+async fn update_service_account(
+    State(state): State<AppState>,
+    Path(account_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+    Json(req): Json<UpdateServiceAccountRequest>,
+) -> Result<Json<ServiceAccountInfo>> {
+    let db = state.get_db().await?;
+    let mut details = serde_json::json!({
+        "service_account": true,
+        "is_active": req.is_active,
+        "role_ids": req.role_ids,
+    });
+    let account = service_accounts::update(&db, account_id, req).await?;
+
+    details["target_username"] = serde_json::json!(account.name);
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(account_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(details),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(account))
+}
+
+/// Delete a service account and its tokens
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/service-accounts/{account_id}",
+    tag = "Users",
+    params(("account_id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "Service account not found")
+    )
+)]
+async fn delete_service_account(
+    State(state): State<AppState>,
+    Path(account_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let deleted = service_accounts::delete(&db, account_id).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserDeleted,
+            ResourceType::User,
+            Some(account_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": deleted.username,
+                "service_account": true,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(
+        serde_json::json!({"message": "Service account deleted"}),
+    ))
+}
+
+/// List a service account's tokens, without the tokens themselves
+#[doc = "Requires: users.view"]
+#[utoipa::path(
+    get,
+    path = "/api/users/service-accounts/{account_id}/tokens",
+    tag = "Users",
+    params(("account_id" = i64, Path, description = "Service account ID")),
+    responses(
+        (status = 200, body = Vec<ServiceAccountTokenInfo>),
+        (status = 404, description = "Service account not found")
+    )
+)]
+async fn list_service_account_tokens(
+    State(state): State<AppState>,
+    Path(account_id): Path<i64>,
+    _auth: Authorized<UsersView>,
+) -> Result<Json<Vec<ServiceAccountTokenInfo>>> {
+    let db = state.get_db().await?;
+    Ok(Json(service_accounts::list_tokens(&db, account_id).await?))
+}
+
+/// Create a token for a service account
+///
+/// The token is only returned here. Send it as `Authorization: Bearer <token>`.
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    post,
+    path = "/api/users/service-accounts/{account_id}/tokens",
+    tag = "Users",
+    params(("account_id" = i64, Path, description = "Service account ID")),
+    request_body = CreateTokenRequest,
+    responses(
+        (status = 200, body = CreatedToken),
+        (status = 400, description = "Missing name or invalid expiry"),
+        (status = 404, description = "Service account not found")
+    )
+)]
+async fn create_service_account_token(
+    State(state): State<AppState>,
+    Path(account_id): Path<i64>,
+    auth: Authorized<UsersManage>,
+    Json(req): Json<CreateTokenRequest>,
+) -> Result<Json<CreatedToken>> {
+    let db = state.get_db().await?;
+    let account = service_accounts::find(&db, account_id).await?;
+    let created = service_accounts::create_token(&db, account_id, req).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(account_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": account.username,
+                "service_account": true,
+                "token_added": created.info.token_prefix,
+                "expires_at": created.info.expires_at,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(created))
+}
+
+/// Revoke a service account's token
+#[doc = "Requires: users.manage"]
+#[utoipa::path(
+    delete,
+    path = "/api/users/service-accounts/{account_id}/tokens/{token_id}",
+    tag = "Users",
+    params(
+        ("account_id" = i64, Path, description = "Service account ID"),
+        ("token_id" = i64, Path, description = "Token ID")
+    ),
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 404, description = "Token not found")
+    )
+)]
+async fn delete_service_account_token(
+    State(state): State<AppState>,
+    Path((account_id, token_id)): Path<(i64, i64)>,
+    auth: Authorized<UsersManage>,
+) -> Result<Json<serde_json::Value>> {
+    let db = state.get_db().await?;
+    let account = service_accounts::find(&db, account_id).await?;
+    let revoked = service_accounts::delete_token(&db, account_id, token_id).await?;
+
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(account_id.to_string()),
+            Some(auth.user_id()),
+            Some(auth.user().username.clone()),
+            Some(serde_json::json!({
+                "target_username": account.username,
+                "service_account": true,
+                "token_removed": revoked.token_prefix,
+            })),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(Json(serde_json::json!({"message": "Token revoked"})))
+}
@@ -271,12 +271,18 @@ async fn assign_vpn(
     let k8s = state.k8s_client.read().await;
     if let Some(k8s_client) = k8s.as_ref() {
         let catalog = state.catalog.read().await;
-        let deployment_manager = DeploymentManager::with_db(k8s_client, &catalog, &db);
+        let helm = state.helm();
+        let deployment_manager =
+            DeploymentManager::with_db(k8s_client, helm.as_ref(), &catalog, &db);
         let deploy_request = DeploymentRequest {
             app_name: app_name.clone(),
             custom_config: std::collections::HashMap::new(),
+            allow_incompatible_arch: false,
         };
-        match deployment_manager.deploy_app(&deploy_request, None).await {
+        match deployment_manager
+            .redeploy_app(&deploy_request, None, None)
+            .await
+        {
             Ok(status) => {
                 tracing::info!("Redeployed app {} with VPN: {}", app_name, status.message);
             }
@@ -317,12 +323,17 @@ async fn remove_vpn(
 
     // Trigger redeploy to remove VPN sidecar
     let catalog = state.catalog.read().await;
-    let deployment_manager = DeploymentManager::with_db(client, &catalog, &db);
+    let helm = state.helm();
+    let deployment_manager = DeploymentManager::with_db(client, helm.as_ref(), &catalog, &db);
     let deploy_request = DeploymentRequest {
         app_name: app_name.clone(),
         custom_config: std::collections::HashMap::new(),
+        allow_incompatible_arch: false,
     };
-    match deployment_manager.deploy_app(&deploy_request, None).await {
+    match deployment_manager
+        .redeploy_app(&deploy_request, None, None)
+        .await
+    {
         Ok(status) => {
             tracing::info!(
                 "Redeployed app {} without VPN: {}",
@@ -0,0 +1,357 @@
+//! WebDAV gateway to the storage roots
+//!
+//! When the `webdav_enabled` setting is on, `/dav/<root>/` serves a storage
+//! root over WebDAV so it can be mounted from a desktop file manager, and
+//! `/dav/` lists the roots the user can see. Clients sign in with HTTP Basic
+//! auth using their Kubarr username and password; accounts with two-factor
+//! authentication cannot, as Basic auth has no room for a second factor.
+//!
+//! Each root follows the storage permissions of the API: `storage.view`
+//! lists, `storage.download` reads, `storage.write` uploads, creates folders
+//! and copies, and `storage.delete` deletes. Moving needs both write and
+//! delete. Deleted items go to the root's trash, like the storage browser.
+//! Writes into a home folder at its hard quota are refused with
+//! `507 Insufficient Storage`.
+//!
+//! Wrong credentials count as failed sign-ins towards an IP ban.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    routing::any,
+    Router,
+};
+use std::path::{Path, PathBuf};
+
+use dav_server::davpath::DavPath;
+use dav_server::fakels::FakeLs;
+use dav_server::{DavHandler, DavMethod, DavMethodSet};
+
+use crate::endpoints::auth::role_requires_2fa;
+use crate::endpoints::settings::get_setting_bool;
+use crate::endpoints::storage::{
+    check_deletable, get_storage_path, relative_to_root, validate_path,
+};
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    Permission, StorageDelete, StorageDownload, StorageView, StorageWrite,
+};
+use crate::models::audit_log::AuditAction;
+use crate::models::user;
+use crate::services::previews::PREVIEWS;
+use crate::services::storage_roots::{self, StorageRoot, StorageRootInfo};
+use crate::services::webdav::{self, RootFs, WEBDAV_ENABLED};
+use crate::services::{checksums, homes, ip_bans, trash};
+use crate::state::{AppState, DbConn};
+
+/// Path the gateway is served under
+const PREFIX: &str = "/dav";
+
+/// Public routes; requests authenticate with Basic auth themselves
+pub fn webdav_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/dav", any(handle))
+        .route("/dav/", any(handle))
+        .route("/dav/{*path}", any(handle))
+        .with_state(state)
+}
+
+async fn handle(State(state): State<AppState>, request: Request) -> Response {
+    match serve(state, request).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn serve(state: AppState, request: Request) -> Result<Response> {
+    let db = state.get_db().await?;
+    if !get_setting_bool(&db, WEBDAV_ENABLED).await? {
+        return Err(AppError::NotFound("WebDAV is disabled".to_string()));
+    }
+
+    let credentials = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(webdav::parse_basic_auth);
+    let Some((username, password)) = credentials else {
+        return Ok(unauthorized());
+    };
+    let Some(user) = webdav::authenticate(&db, &username, &password).await? else {
+        let user_agent = request
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.chars().take(255).collect::<String>());
+        if let Err(e) = ip_bans::record_failure(
+            &state,
+            AuditAction::LoginFailed,
+            &username,
+            ip_bans::client_ip(request.headers()),
+            user_agent,
+            "webdav",
+        )
+        .await
+        {
+            tracing::warn!("Failed to record failed WebDAV sign-in: {}", e);
+        }
+        return Ok(unauthorized());
+    };
+    if user.totp_enabled || role_requires_2fa(&db, user.id).await {
+        return Err(AppError::Forbidden(
+            "WebDAV is not available to accounts with two-factor authentication".to_string(),
+        ));
+    }
+
+    let roots = storage_roots::visible_roots(&db, user.id).await?;
+    let path = request.uri().path().trim_start_matches(PREFIX);
+    let root_name = path.trim_start_matches('/').split('/').next().unwrap_or("");
+    if root_name.is_empty() {
+        return Ok(list_roots(request.method(), &request, &roots));
+    }
+
+    let not_found = || AppError::NotFound(format!("Storage root '{}' not found", root_name));
+    let info = roots
+        .iter()
+        .find(|r| r.name == root_name)
+        .ok_or_else(not_found)?;
+    let allowed = |permission: &str| info.permissions.iter().any(|p| p == permission);
+    if !allowed(StorageView::NAME) {
+        return Err(AppError::Forbidden(format!(
+            "Permission denied: {} required on storage root '{}'",
+            StorageView::NAME,
+            info.name
+        )));
+    }
+
+    let mut methods = DavMethodSet::none();
+    methods.add(DavMethod::Options);
+    methods.add(DavMethod::PropFind);
+    if allowed(StorageDownload::NAME) {
+        methods.add(DavMethod::Get);
+        methods.add(DavMethod::Head);
+    }
+    if allowed(StorageWrite::NAME) {
+        for method in [
+            DavMethod::Put,
+            DavMethod::MkCol,
+            DavMethod::Copy,
+            DavMethod::PropPatch,
+            DavMethod::Lock,
+            DavMethod::Unlock,
+        ] {
+            methods.add(method);
+        }
+        if allowed(StorageDelete::NAME) {
+            methods.add(DavMethod::Move);
+        }
+    }
+    if allowed(StorageDelete::NAME) {
+        methods.add(DavMethod::Delete);
+    }
+    if let Ok(method) = DavMethod::try_from(request.method()) {
+        if !methods.contains(method) {
+            return Err(AppError::Forbidden(format!(
+                "{} is not allowed on storage root '{}'",
+                request.method(),
+                info.name
+            )));
+        }
+    }
+
+    let root = storage_roots::root_for(&db, user.id, Some(root_name), StorageView::NAME).await?;
+    let base_path = get_storage_path(&db, &root)
+        .await?
+        .canonicalize()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve storage path: {}", e)))?;
+    let prefix = format!("{}/{}", PREFIX, root.name);
+
+    if request.method() == Method::DELETE {
+        let uri_path = request.uri().path().to_string();
+        return delete(&db, &root, &base_path, &prefix, &uri_path, &user).await;
+    }
+    let quota = check_home_quota(
+        &db,
+        &root,
+        &base_path,
+        &prefix,
+        request.method(),
+        request.uri().path(),
+        request.headers(),
+    )
+    .await?;
+    if let Some(response) = quota {
+        return Ok(response);
+    }
+
+    let handler = DavHandler::builder()
+        .filesystem(RootFs::new(base_path, root.protected_folders.clone()))
+        .locksystem(FakeLs::new())
+        .strip_prefix(prefix)
+        .methods(methods)
+        .hide_symlinks(true)
+        .build_handler();
+    Ok(handler.handle(request).await.map(Body::new))
+}
+
+/// Move a file or folder to the root's trash
+async fn delete(
+    db: &DbConn,
+    root: &StorageRoot,
+    base_path: &Path,
+    prefix: &str,
+    uri_path: &str,
+    user: &user::Model,
+) -> Result<Response> {
+    let requested = root_relative(prefix, uri_path)?
+        .to_string_lossy()
+        .to_string();
+
+    let target_path = validate_path(&requested, base_path)?;
+    check_deletable(root, base_path, &target_path)?;
+    let relative_path = relative_to_root(&target_path, base_path)?;
+    let is_dir = target_path.is_dir();
+    trash::move_to_trash(
+        base_path,
+        &target_path,
+        &relative_path,
+        Some(user.username.clone()),
+    )?;
+    if !is_dir {
+        PREVIEWS.invalidate(&root.name, &relative_path).await;
+        checksums::forget(db, &root.name, &relative_path).await?;
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// A request path relative to the root served under `prefix`
+fn root_relative(prefix: &str, uri_path: &str) -> Result<PathBuf> {
+    let mut path =
+        DavPath::new(uri_path).map_err(|_| AppError::BadRequest("Invalid path".to_string()))?;
+    path.set_prefix(prefix)
+        .map_err(|_| AppError::BadRequest("Invalid path".to_string()))?;
+    Ok(path.as_rel_ospath().to_path_buf())
+}
+
+/// A `507 Insufficient Storage` response if the request writes into a home
+/// folder whose hard quota it would break
+async fn check_home_quota(
+    db: &DbConn,
+    root: &StorageRoot,
+    base_path: &Path,
+    prefix: &str,
+    method: &Method,
+    uri_path: &str,
+    headers: &HeaderMap,
+) -> Result<Option<Response>> {
+    let Some(settings) = &root.homes else {
+        return Ok(None);
+    };
+    let (target, incoming) = match method.as_str() {
+        "PUT" => {
+            let length = headers
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            (root_relative(prefix, uri_path)?, length)
+        }
+        "MKCOL" => (root_relative(prefix, uri_path)?, 0),
+        "COPY" | "MOVE" => {
+            let destination = headers
+                .get("Destination")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<axum::http::Uri>().ok())
+                .and_then(|uri| root_relative(prefix, uri.path()).ok());
+            let Some(destination) = destination else {
+                return Ok(None);
+            };
+            let source = root_relative(prefix, uri_path)?;
+            // Moving within one home leaves its usage unchanged
+            let same_home = homes::owner_of(settings, &source)
+                .is_some_and(|owner| homes::owner_of(settings, &destination) == Some(owner));
+            let incoming = if method == "MOVE" && same_home {
+                0
+            } else {
+                homes::disk_usage(&base_path.join(&source))
+            };
+            (destination, incoming)
+        }
+        _ => return Ok(None),
+    };
+    Ok(homes::over_quota(db, root, &target, incoming)
+        .await?
+        .map(|reason| {
+            Response::builder()
+                .status(StatusCode::INSUFFICIENT_STORAGE)
+                .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(Body::from(reason))
+                .unwrap()
+        }))
+}
+
+/// `/dav/` itself: a read-only collection of the visible roots
+fn list_roots(method: &Method, request: &Request, roots: &[StorageRootInfo]) -> Response {
+    const ALLOW: &str = "OPTIONS, PROPFIND";
+    match method.as_str() {
+        "OPTIONS" => Response::builder()
+            .status(StatusCode::OK)
+            .header("DAV", "1, 2")
+            .header(header::ALLOW, ALLOW)
+            .header("MS-Author-Via", "DAV")
+            .body(Body::empty())
+            .unwrap(),
+        "PROPFIND" => {
+            let depth_zero = request
+                .headers()
+                .get("Depth")
+                .is_some_and(|d| d.as_bytes() == b"0");
+            let mut xml = String::from(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#,
+            );
+            push_collection(&mut xml, &format!("{}/", PREFIX), "dav");
+            if !depth_zero {
+                for root in roots {
+                    push_collection(&mut xml, &format!("{}/{}/", PREFIX, root.name), &root.name);
+                }
+            }
+            xml.push_str("</D:multistatus>");
+            Response::builder()
+                .status(StatusCode::MULTI_STATUS)
+                .header(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/xml; charset=utf-8"),
+                )
+                .body(Body::from(xml))
+                .unwrap()
+        }
+        _ => Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(header::ALLOW, ALLOW)
+            .body(Body::empty())
+            .unwrap(),
+    }
+}
+
+/// Root names are limited to `[a-z0-9_-]`, so they need no escaping
+fn push_collection(xml: &mut String, href: &str, name: &str) {
+    xml.push_str(&format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+         <D:displayname>{}</D:displayname><D:resourcetype><D:collection/></D:resourcetype>\
+         </D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        href, name
+    ));
+}
+
+fn unauthorized() -> Response {
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header(
+            header::WWW_AUTHENTICATE,
+            r#"Basic realm="Kubarr", charset="UTF-8""#,
+        )
+        .body(Body::empty())
+        .unwrap()
+}
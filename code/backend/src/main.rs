@@ -1,4 +1,10 @@
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    kubarr::bootstrapper::run().await
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("seed-dev-data") => kubarr::application::dev_seed::run(&args[1..]).await,
+        Some("check-config") => kubarr::config::validation::run_check(),
+        Some("validate-catalog") => kubarr::services::catalog_validation::run_check(&args[1..]),
+        _ => kubarr::bootstrapper::run().await,
+    }
 }
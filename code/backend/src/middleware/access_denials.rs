@@ -0,0 +1,75 @@
+//! Audit of `403` responses on the API
+//!
+//! Runs inside [`super::require_auth`], so every denial has a user. The
+//! [`Authorized`](super::Authorized) extractor notes the permission it
+//! required in [`DeniedPermission`] before rejecting a request.
+
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use super::AuthenticatedUser;
+use crate::services::access_denials::{self, Denial};
+use crate::services::ip_bans;
+use crate::state::AppState;
+
+/// Permission a request was denied for, set by the permission extractor
+#[derive(Debug, Clone, Default)]
+pub struct DeniedPermission(Arc<OnceLock<&'static str>>);
+
+impl DeniedPermission {
+    /// Note the permission that was missing
+    pub fn set(&self, permission: &'static str) {
+        let _ = self.0.set(permission);
+    }
+
+    /// The permission that was missing, if a permission check denied
+    pub fn get(&self) -> Option<&'static str> {
+        self.0.get().copied()
+    }
+}
+
+/// Middleware recording denied requests of signed-in users
+pub async fn record_access_denials(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(user) = req
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .map(|auth| auth.user.clone())
+    else {
+        return next.run(req).await;
+    };
+    let denied = DeniedPermission::default();
+    req.extensions_mut().insert(denied.clone());
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let ip_address = ip_bans::client_ip(req.headers());
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(req).await;
+    if response.status() == StatusCode::FORBIDDEN {
+        let denial = Denial {
+            method,
+            path,
+            permission: denied.get(),
+            ip_address,
+            user_agent,
+        };
+        if let Err(e) = access_denials::record(&state, &user, denial).await {
+            tracing::warn!("Failed to record access denial: {}", e);
+        }
+    }
+    response
+}
@@ -1,11 +1,17 @@
 //! Authentication middleware for API routes
 //!
-//! Requires valid session cookie for all endpoints except `/auth/*`.
+//! Requires a valid session cookie (or the session token as a bearer token)
+//! for all endpoints except `/auth/*`. Without either, a client certificate
+//! forwarded by the ingress is accepted (see [`crate::services::client_certs`]).
+//! Bearer tokens of service accounts are recognized by their prefix (see
+//! [`crate::services::service_accounts`]).
 //! Session tokens contain only a session ID - user data is looked up from the database.
+//! They expire after `KUBARR_ACCESS_TOKEN_TTL` seconds and are renewed with the
+//! session's refresh token (see [`crate::services::refresh_tokens`]).
 
 use axum::{
     extract::{Request, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -13,20 +19,82 @@ use axum::{
 use chrono::Utc;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 
+use crate::config::CONFIG;
 use crate::models::prelude::*;
 use crate::models::{role_app_permission, role_permission, session, user, user_role};
+use crate::services::access::unexpired_roles;
+use crate::services::client_certs;
 use crate::services::security::decode_session_token;
+use crate::services::service_accounts;
 use crate::state::AppState;
 
-/// Base cookie name for session tokens (indexed as kubarr_session_0, kubarr_session_1, etc.)
-pub const SESSION_COOKIE_BASE: &str = "kubarr_session";
-/// Cookie name for active session index
-pub const ACTIVE_SESSION_COOKIE: &str = "kubarr_active";
 /// Maximum number of simultaneous sessions
 pub const MAX_SESSIONS: usize = 5;
 
-/// Legacy cookie name (for backwards compatibility)
-pub const SESSION_COOKIE_NAME: &str = "kubarr_session";
+/// Lifetime of the session cookies in seconds (7 days)
+pub const SESSION_COOKIE_MAX_AGE: i64 = 7 * 24 * 60 * 60;
+
+/// Path the refresh token cookies are sent to
+pub const REFRESH_COOKIE_PATH: &str = "/auth/session";
+
+/// Base name of the session cookies (indexed as kubarr_session_0,
+/// kubarr_session_1, etc.); also the name of the legacy cookie
+pub fn session_cookie_name() -> &'static str {
+    &CONFIG.auth.session_cookie_name
+}
+
+/// Name of the cookie holding the active session slot
+pub fn active_session_cookie_name() -> &'static str {
+    &CONFIG.auth.active_session_cookie_name
+}
+
+/// `Set-Cookie` value for a session cookie with the configured `SameSite`
+/// and `Secure` attributes, expiring after `max_age` seconds (0 clears it)
+///
+/// `domain` scopes the cookie to a parent domain, for the cookie the app
+/// proxy reads on subdomain-routed apps.
+pub fn session_cookie(
+    name: &str,
+    value: &str,
+    http_only: bool,
+    max_age: i64,
+    domain: Option<&str>,
+) -> HeaderValue {
+    cookie_at_path(name, value, http_only, max_age, domain, "/")
+}
+
+/// `Set-Cookie` value for a refresh token cookie, which is only sent to
+/// [`REFRESH_COOKIE_PATH`]
+pub fn refresh_cookie(name: &str, value: &str, max_age: i64) -> HeaderValue {
+    cookie_at_path(name, value, true, max_age, None, REFRESH_COOKIE_PATH)
+}
+
+fn cookie_at_path(
+    name: &str,
+    value: &str,
+    http_only: bool,
+    max_age: i64,
+    domain: Option<&str>,
+    path: &str,
+) -> HeaderValue {
+    let mut cookie = format!("{}={}; ", name, value);
+    if http_only {
+        cookie.push_str("HttpOnly; ");
+    }
+    cookie.push_str(&format!(
+        "SameSite={}; Path={}; Max-Age={}",
+        CONFIG.auth.session_cookie_same_site.as_str(),
+        path,
+        max_age
+    ));
+    if let Some(domain) = domain {
+        cookie.push_str(&format!("; Domain={}", domain));
+    }
+    if CONFIG.auth.session_cookie_secure {
+        cookie.push_str("; Secure");
+    }
+    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+}
 
 /// Authenticated user with permissions, stored in request extensions
 #[derive(Clone, Debug)]
@@ -61,16 +129,22 @@ pub async fn require_auth(State(state): State<AppState>, mut req: Request, next:
         return next.run(req).await;
     }
 
-    // Extract session token from cookie
-    let token = match extract_token(&req) {
-        Some(t) => t,
-        None => {
-            return unauthorized_response("Missing or invalid session");
+    // Extract session token from the Authorization header or cookie, falling
+    // back to a client certificate
+    let result = match extract_token(&req) {
+        Some(token) if token.starts_with(service_accounts::TOKEN_PREFIX) => {
+            authenticate_service_account(&state, &token).await
         }
+        Some(token) => authenticate_session(&state, &token).await,
+        None => match authenticate_client_certificate(&state, req.headers()).await {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err("Missing or invalid session".to_string()),
+            Err(msg) => Err(msg),
+        },
     };
 
     // Validate session and get user with permissions
-    let auth_user = match authenticate_session(&state, &token).await {
+    let auth_user = match result {
         Ok(u) => u,
         Err(msg) => {
             return unauthorized_response(&msg);
@@ -83,8 +157,17 @@ pub async fn require_auth(State(state): State<AppState>, mut req: Request, next:
     next.run(req).await
 }
 
-/// Extract session token from cookie (supports multi-session)
+/// Extract session token from a bearer token or cookie (supports multi-session)
 fn extract_token(req: &Request) -> Option<String> {
+    if let Some(token) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(token.trim().to_string());
+    }
+
     let cookies = req.headers().get(header::COOKIE)?;
     let cookie_str = cookies.to_str().ok()?;
 
@@ -98,22 +181,22 @@ fn extract_token(req: &Request) -> Option<String> {
         let cookie = cookie.trim();
 
         // Check for active session cookie
-        if let Some(value) = cookie.strip_prefix(&format!("{}=", ACTIVE_SESSION_COOKIE)) {
+        if let Some(value) = cookie.strip_prefix(&format!("{}=", active_session_cookie_name())) {
             active_slot = value.parse().ok();
         }
         // Check for indexed session cookies (kubarr_session_0, kubarr_session_1, etc.)
-        else if cookie.starts_with(SESSION_COOKIE_BASE) {
+        else if cookie.starts_with(session_cookie_name()) {
             for i in 0..MAX_SESSIONS {
-                let prefix = format!("{}_{}=", SESSION_COOKIE_BASE, i);
+                let prefix = format!("{}_{}=", session_cookie_name(), i);
                 if let Some(value) = cookie.strip_prefix(&prefix) {
                     session_cookies.insert(i, value.to_string());
                     break;
                 }
             }
             // Also check for legacy cookie (kubarr_session without index)
-            if let Some(value) = cookie.strip_prefix(&format!("{}=", SESSION_COOKIE_NAME)) {
+            if let Some(value) = cookie.strip_prefix(&format!("{}=", session_cookie_name())) {
                 // Only use legacy if it doesn't match an indexed pattern
-                if !cookie.contains(&format!("{}_", SESSION_COOKIE_BASE)) {
+                if !cookie.contains(&format!("{}_", session_cookie_name())) {
                     legacy_token = Some(value.to_string());
                 }
             }
@@ -142,6 +225,9 @@ async fn authenticate_session(state: &AppState, token: &str) -> Result<Authentic
     // Decode and validate the session token
     let claims =
         decode_session_token(token).map_err(|_| "Invalid or expired session".to_string())?;
+    if claims.is_expired(Utc::now()) {
+        return Err("Access token has expired".to_string());
+    }
 
     // Get database connection
     let db = state
@@ -195,6 +281,55 @@ async fn authenticate_session(state: &AppState, token: &str) -> Result<Authentic
     Ok(AuthenticatedUser { user, permissions })
 }
 
+/// Authenticate as the user the request's client certificate is registered
+/// for, or `None` if it carries no certificate
+async fn authenticate_client_certificate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<AuthenticatedUser>, String> {
+    let db = state
+        .get_db()
+        .await
+        .map_err(|_| "Database not available".to_string())?;
+    let Some(user_id) = client_certs::authenticate(&db, headers, Utc::now()).await? else {
+        return Ok(None);
+    };
+
+    let user = User::find_by_id(user_id)
+        .filter(user::Column::IsActive.eq(true))
+        .filter(user::Column::IsApproved.eq(true))
+        .one(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "User not found or inactive".to_string())?;
+    let permissions = fetch_user_permissions(state, user_id).await;
+
+    Ok(Some(AuthenticatedUser { user, permissions }))
+}
+
+/// Authenticate as the service account a bearer token belongs to
+async fn authenticate_service_account(
+    state: &AppState,
+    token: &str,
+) -> Result<AuthenticatedUser, String> {
+    let db = state
+        .get_db()
+        .await
+        .map_err(|_| "Database not available".to_string())?;
+    let user_id = service_accounts::authenticate(&db, token, Utc::now()).await?;
+
+    let user = User::find_by_id(user_id)
+        .filter(user::Column::IsActive.eq(true))
+        .filter(user::Column::IsServiceAccount.eq(true))
+        .one(&db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Service account not found or inactive".to_string())?;
+    let permissions = fetch_user_permissions(state, user_id).await;
+
+    Ok(AuthenticatedUser { user, permissions })
+}
+
 /// Fetch all permissions for a user from their roles
 async fn fetch_user_permissions(state: &AppState, user_id: i64) -> Vec<String> {
     // Get database connection
@@ -206,6 +341,7 @@ async fn fetch_user_permissions(state: &AppState, user_id: i64) -> Vec<String> {
     // Get all role IDs for this user
     let user_roles = UserRole::find()
         .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
         .all(&db)
         .await
         .unwrap_or_default();
@@ -269,6 +405,9 @@ mod tests {
             totp_secret: None,
             totp_enabled: false,
             totp_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            is_service_account: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -344,9 +483,27 @@ mod tests {
 
     #[test]
     fn test_session_cookie_constants() {
-        assert_eq!(SESSION_COOKIE_BASE, "kubarr_session");
-        assert_eq!(SESSION_COOKIE_NAME, "kubarr_session");
-        assert_eq!(ACTIVE_SESSION_COOKIE, "kubarr_active");
-        assert!(MAX_SESSIONS > 0);
+        assert_eq!(session_cookie_name(), "kubarr_session");
+        assert_eq!(active_session_cookie_name(), "kubarr_active");
+        const { assert!(MAX_SESSIONS > 0) };
+    }
+
+    #[test]
+    fn test_session_cookie_attributes() {
+        let cookie = session_cookie("kubarr_session", "abc", true, 60, Some("example.com"));
+        assert_eq!(
+            cookie.to_str().unwrap(),
+            "kubarr_session=abc; HttpOnly; SameSite=Lax; Path=/; Max-Age=60; Domain=example.com"
+        );
+        let cookie = session_cookie("kubarr_active", "0", false, 0, None);
+        assert_eq!(
+            cookie.to_str().unwrap(),
+            "kubarr_active=0; SameSite=Lax; Path=/; Max-Age=0"
+        );
+        let cookie = refresh_cookie("kubarr_session_refresh_0", "sid.abc", 60);
+        assert_eq!(
+            cookie.to_str().unwrap(),
+            "kubarr_session_refresh_0=sid.abc; HttpOnly; SameSite=Lax; Path=/auth/session; Max-Age=60"
+        );
     }
 }
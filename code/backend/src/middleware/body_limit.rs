@@ -0,0 +1,113 @@
+//! Request body size limits
+//!
+//! Every request body is capped before a handler reads it. A declared
+//! `Content-Length` over the route's limit answers `413` straight away;
+//! chunked bodies are counted as they stream in and fail with `413` once they
+//! pass it, so leaving out the length does not get a larger body through.
+//! Sign-in and setup routes get a small limit, WebDAV uploads and proxied
+//! apps a large one and the rest of the API `KUBARR_MAX_BODY_SIZE`.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::Limited;
+
+use crate::config::CONFIG;
+use crate::error::AppError;
+use crate::services::backup::MAX_BACKUP_BYTES;
+use crate::services::catalog_bundle::MAX_BUNDLE_BYTES;
+
+/// Which limit a route's request bodies are held to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyLimit {
+    /// `KUBARR_MAX_AUTH_BODY_SIZE`
+    Auth,
+    /// `KUBARR_MAX_BODY_SIZE`
+    Default,
+    /// `KUBARR_MAX_UPLOAD_SIZE`
+    Upload,
+    /// A limit the route's handler enforces itself as well
+    Fixed(usize),
+}
+
+/// Path prefixes and their limit; the first match wins, and paths outside
+/// the API are proxied apps and the frontend
+const ROUTE_LIMITS: &[(&str, BodyLimit)] = &[
+    ("/api/system/restore", BodyLimit::Fixed(MAX_BACKUP_BYTES)),
+    (
+        "/api/apps/catalog/import-bundle",
+        BodyLimit::Fixed(MAX_BUNDLE_BYTES),
+    ),
+    ("/auth/", BodyLimit::Auth),
+    ("/api/setup/", BodyLimit::Auth),
+    ("/api/", BodyLimit::Default),
+    ("/dav", BodyLimit::Upload),
+];
+
+/// Limit for requests to `path`
+pub fn route_limit(path: &str) -> BodyLimit {
+    ROUTE_LIMITS
+        .iter()
+        .find(|(prefix, _)| path.starts_with(prefix))
+        .map(|(_, limit)| *limit)
+        .unwrap_or(BodyLimit::Upload)
+}
+
+/// Largest body in bytes accepted for `path`, or `None` when unlimited
+fn max_body_size(path: &str) -> Option<usize> {
+    match route_limit(path) {
+        BodyLimit::Auth => CONFIG.server.max_auth_body_size,
+        BodyLimit::Default => CONFIG.server.max_body_size,
+        BodyLimit::Upload => CONFIG.server.max_upload_size,
+        BodyLimit::Fixed(bytes) => Some(bytes),
+    }
+}
+
+/// Middleware refusing request bodies over the route's limit
+pub async fn limit_request_body(req: Request, next: Next) -> Response {
+    let Some(limit) = max_body_size(req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > limit as u64) {
+        return AppError::PayloadTooLarge(format!(
+            "Request body exceeds the limit of {} bytes",
+            limit
+        ))
+        .into_response();
+    }
+
+    let (parts, body) = req.into_parts();
+    let body = Body::new(Limited::new(body, limit));
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_limit() {
+        assert_eq!(route_limit("/auth/login"), BodyLimit::Auth);
+        assert_eq!(route_limit("/api/setup/initialize"), BodyLimit::Auth);
+        assert_eq!(route_limit("/api/settings/theme"), BodyLimit::Default);
+        assert_eq!(
+            route_limit("/api/system/restore"),
+            BodyLimit::Fixed(MAX_BACKUP_BYTES)
+        );
+        assert_eq!(route_limit("/dav/media/film.mkv"), BodyLimit::Upload);
+        assert_eq!(
+            route_limit("/qbittorrent/api/v2/torrents/add"),
+            BodyLimit::Upload
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Response compression
+//!
+//! JSON, text, log exports and the frontend's scripts and styles are
+//! compressed with brotli or gzip when the client accepts it and the body is
+//! at least `KUBARR_COMPRESSION_MIN_SIZE` bytes. Everything else, such as
+//! media and archive downloads, is already compressed or binary and passes
+//! through untouched, as do range responses and event streams.
+
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::predicate::{Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+
+use crate::config::server::ServerConfig;
+
+/// Content types worth compressing, matched on the media type's start
+const COMPRESSIBLE_TYPES: &[&str] = &[
+    "application/json",
+    "application/problem+json",
+    "application/x-ndjson",
+    "application/javascript",
+    "application/xml",
+    "application/yaml",
+    "image/svg+xml",
+    "text/",
+];
+
+/// Whether a response's content type is compressible
+fn is_compressible(status: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+    if status == StatusCode::PARTIAL_CONTENT || headers.contains_key(header::CONTENT_RANGE) {
+        return false;
+    }
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+    let media_type = content_type.to_lowercase();
+    !media_type.starts_with("text/event-stream")
+        && COMPRESSIBLE_TYPES
+            .iter()
+            .any(|prefix| media_type.starts_with(prefix))
+}
+
+/// Layer compressing responses with the encodings `config` enables
+pub fn compression_layer(config: &ServerConfig) -> CompressionLayer<impl Predicate> {
+    let enabled = |algorithm: &str| config.compression.iter().any(|a| a == algorithm);
+    CompressionLayer::new()
+        .br(enabled("br"))
+        .gzip(enabled("gzip"))
+        .compress_when(SizeAbove::new(config.compression_min_size).and(is_compressible))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn compressible(content_type: &str) -> bool {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(content_type).unwrap(),
+        );
+        is_compressible(
+            StatusCode::OK,
+            Version::HTTP_11,
+            &headers,
+            &Extensions::new(),
+        )
+    }
+
+    #[test]
+    fn test_is_compressible() {
+        assert!(compressible("application/json"));
+        assert!(compressible("text/csv; charset=utf-8"));
+        assert!(compressible("application/javascript"));
+        assert!(!compressible("video/x-matroska"));
+        assert!(!compressible("application/gzip"));
+        assert!(!compressible("image/png"));
+        assert!(!compressible("text/event-stream"));
+        assert!(!is_compressible(
+            StatusCode::OK,
+            Version::HTTP_11,
+            &HeaderMap::new(),
+            &Extensions::new()
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+//! Server error tracking middleware
+//!
+//! Feeds every 5xx response into the diagnostics error aggregator, which
+//! logs a warning when they start to pile up.
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use chrono::Utc;
+
+use super::request_id::current_request_id;
+use crate::services::diagnostics::{ServerErrorRecord, ERRORS, SPIKE_THRESHOLD, SPIKE_WINDOW_SECS};
+
+/// Middleware that records 5xx responses
+pub async fn track_server_errors(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let response = next.run(req).await;
+
+    let status = response.status();
+    if status.is_server_error() {
+        let started = ERRORS.record_server_error(ServerErrorRecord {
+            timestamp: Utc::now(),
+            method,
+            path,
+            status: status.as_u16(),
+            request_id: current_request_id(),
+        });
+        if started {
+            tracing::warn!(
+                "Spike of server errors: {} or more 5xx responses within {}s",
+                SPIKE_THRESHOLD,
+                SPIKE_WINDOW_SECS
+            );
+        }
+    }
+    response
+}
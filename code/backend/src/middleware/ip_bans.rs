@@ -0,0 +1,43 @@
+//! IP bans for unauthenticated routes
+//!
+//! Requests from a banned address to the sign-in endpoints, WebDAV, setup
+//! and public links answer `403` before reaching the handler. Addresses
+//! banned by CrowdSec are refused the same way when the bouncer is on.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+
+use crate::error::AppError;
+use crate::services::{crowdsec, ip_bans};
+use crate::state::AppState;
+
+/// Route layer refusing banned addresses
+pub async fn reject_banned_ips(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Some(ip) = ip_bans::client_ip(req.headers()) {
+        let banned = match state.get_db().await {
+            Ok(db) => match ip_bans::active_ban(&db, &ip, Utc::now()).await {
+                Ok(None) => crowdsec::is_banned(&db, &ip).await,
+                Ok(Some(_)) => Ok(true),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        match banned {
+            Ok(false) => {}
+            Ok(true) => {
+                return AppError::Forbidden("Your address is temporarily blocked".to_string())
+                    .into_response()
+            }
+            Err(e) => return e.into_response(),
+        }
+    }
+    next.run(req).await
+}
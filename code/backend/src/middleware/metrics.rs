@@ -0,0 +1,37 @@
+//! Request metrics middleware
+//!
+//! Times every request into the `kubarr_http_request_duration_seconds`
+//! histogram, labelled by route template rather than path so that app names
+//! and IDs do not each start a series of their own.
+
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::telemetry::{METRICS, UNMATCHED_ROUTE};
+
+/// Middleware that records request durations
+pub async fn record_metrics(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let _in_flight = METRICS.request_started();
+    let started = Instant::now();
+
+    let response = next.run(req).await;
+
+    METRICS.observe_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
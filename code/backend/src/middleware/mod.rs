@@ -1,6 +1,22 @@
+pub mod access_denials;
 pub mod auth;
+pub mod body_limit;
+pub mod compression;
+pub mod error_tracking;
+pub mod ip_bans;
+pub mod metrics;
 pub mod permissions;
+pub mod request_id;
+pub mod tenants;
 
+pub use access_denials::{record_access_denials, DeniedPermission};
 pub use auth::require_auth;
 pub use auth::AuthenticatedUser;
+pub use body_limit::limit_request_body;
+pub use compression::compression_layer;
+pub use error_tracking::track_server_errors;
+pub use ip_bans::reject_banned_ips;
+pub use metrics::record_metrics;
 pub use permissions::*;
+pub use request_id::{current_request_id, request_id, REQUEST_ID_HEADER};
+pub use tenants::require_tenant_app;
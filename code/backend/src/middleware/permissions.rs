@@ -17,7 +17,7 @@ use std::marker::PhantomData;
 use axum::{extract::FromRequestParts, http::request::Parts};
 
 use crate::error::AppError;
-use crate::middleware::AuthenticatedUser;
+use crate::middleware::{AuthenticatedUser, DeniedPermission};
 use crate::models::user;
 
 /// Trait for permission marker types
@@ -40,6 +40,9 @@ macro_rules! define_permissions {
                 const NAME: &'static str = $perm;
             }
         )*
+
+        /// Names of all defined permissions
+        pub const ALL_PERMISSIONS: &[&str] = &[$($perm),*];
     };
 }
 
@@ -53,6 +56,10 @@ define_permissions! {
     /// Reset other users' passwords
     UsersResetPassword => "users.reset_password",
 
+    // Tenant management
+    /// Create tenants, manage their members and apps, and see across tenants
+    TenantsManage => "tenants.manage",
+
     // Role management
     /// View roles list and details
     RolesView => "roles.view",
@@ -68,6 +75,12 @@ define_permissions! {
     AppsDelete => "apps.delete",
     /// Restart apps
     AppsRestart => "apps.restart",
+    /// Stop apps and start them again
+    AppsStop => "apps.stop",
+    /// Open a shell in app pods
+    AppsExec => "apps.exec",
+    /// Ask for apps to be installed, pending admin approval
+    AppsRequest => "apps.request",
 
     // Storage management
     /// Browse and view storage
@@ -86,6 +99,8 @@ define_permissions! {
     // Monitoring
     /// View metrics and monitoring data
     MonitoringView => "monitoring.view",
+    /// Register and manage uptime monitors
+    MonitoringManage => "monitoring.manage",
 
     // Settings
     /// View system settings
@@ -98,6 +113,8 @@ define_permissions! {
     AuditView => "audit.view",
     /// Manage audit logs (clear old entries)
     AuditManage => "audit.manage",
+    /// View audit logs for the apps and users the role manages
+    AuditViewOwnApps => "audit.view_own_apps",
 
     // Notifications
     /// View notifications
@@ -108,6 +125,8 @@ define_permissions! {
     // Networking
     /// View network topology
     NetworkingView => "networking.view",
+    /// Manage bandwidth quotas
+    NetworkingManage => "networking.manage",
 
     // VPN
     /// View VPN providers and app VPN configurations
@@ -120,6 +139,18 @@ define_permissions! {
     CloudflareView => "cloudflare.view",
     /// Manage Cloudflare Tunnel (deploy/remove)
     CloudflareManage => "cloudflare.manage",
+
+    // Media requests
+    /// Approve/decline media requests and manage linked request accounts
+    RequestsManage => "requests.manage",
+
+    // System
+    /// Read Kubarr's own logs and diagnostics
+    SystemManage => "system.manage",
+
+    // Security
+    /// View failed sign-ins and manage IP bans
+    SecurityManage => "security.manage",
 }
 
 /// Extractor that requires a specific permission
@@ -168,6 +199,9 @@ where
 
         // Check if user has the required permission
         if !auth_user.has_permission(P::NAME) {
+            if let Some(denied) = parts.extensions.get::<DeniedPermission>() {
+                denied.set(P::NAME);
+            }
             return Err(AppError::Forbidden(format!(
                 "Permission denied: {} required",
                 P::NAME
@@ -229,6 +263,9 @@ mod tests {
             totp_secret: None,
             totp_enabled: false,
             totp_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            is_service_account: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -239,29 +276,46 @@ mod tests {
         assert_eq!(UsersView::NAME, "users.view");
         assert_eq!(UsersManage::NAME, "users.manage");
         assert_eq!(UsersResetPassword::NAME, "users.reset_password");
+        assert_eq!(TenantsManage::NAME, "tenants.manage");
         assert_eq!(RolesView::NAME, "roles.view");
         assert_eq!(RolesManage::NAME, "roles.manage");
         assert_eq!(AppsView::NAME, "apps.view");
         assert_eq!(AppsInstall::NAME, "apps.install");
         assert_eq!(AppsDelete::NAME, "apps.delete");
         assert_eq!(AppsRestart::NAME, "apps.restart");
+        assert_eq!(AppsStop::NAME, "apps.stop");
+        assert_eq!(AppsExec::NAME, "apps.exec");
+        assert_eq!(AppsRequest::NAME, "apps.request");
         assert_eq!(StorageView::NAME, "storage.view");
         assert_eq!(StorageWrite::NAME, "storage.write");
         assert_eq!(StorageDelete::NAME, "storage.delete");
         assert_eq!(StorageDownload::NAME, "storage.download");
         assert_eq!(LogsView::NAME, "logs.view");
         assert_eq!(MonitoringView::NAME, "monitoring.view");
+        assert_eq!(MonitoringManage::NAME, "monitoring.manage");
         assert_eq!(SettingsView::NAME, "settings.view");
         assert_eq!(SettingsManage::NAME, "settings.manage");
         assert_eq!(AuditView::NAME, "audit.view");
         assert_eq!(AuditManage::NAME, "audit.manage");
+        assert_eq!(AuditViewOwnApps::NAME, "audit.view_own_apps");
         assert_eq!(NotificationsView::NAME, "notifications.view");
         assert_eq!(NotificationsManage::NAME, "notifications.manage");
         assert_eq!(NetworkingView::NAME, "networking.view");
+        assert_eq!(NetworkingManage::NAME, "networking.manage");
         assert_eq!(VpnView::NAME, "vpn.view");
         assert_eq!(VpnManage::NAME, "vpn.manage");
         assert_eq!(CloudflareView::NAME, "cloudflare.view");
         assert_eq!(CloudflareManage::NAME, "cloudflare.manage");
+        assert_eq!(RequestsManage::NAME, "requests.manage");
+        assert_eq!(SystemManage::NAME, "system.manage");
+        assert_eq!(SecurityManage::NAME, "security.manage");
+    }
+
+    #[test]
+    fn test_all_permissions() {
+        assert!(ALL_PERMISSIONS.contains(&UsersView::NAME));
+        assert!(ALL_PERMISSIONS.contains(&SecurityManage::NAME));
+        assert!(!ALL_PERMISSIONS.contains(&"app.*"));
     }
 
     #[test]
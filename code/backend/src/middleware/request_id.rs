@@ -0,0 +1,101 @@
+//! Request ID middleware
+//!
+//! Assigns every HTTP request an ID, or keeps the one the client sent in
+//! `X-Request-Id`. The ID is echoed back in the response header, recorded on
+//! a tracing span around the request, included in error response bodies and
+//! stored on audit log rows written while the request is handled, so a failed
+//! UI action can be traced across logs and audit records.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+/// Header carrying the request ID in both directions
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest inbound request ID that is honored
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request currently being handled, if any
+///
+/// Only available on the task handling the request; work spawned onto other
+/// tasks does not inherit it.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Middleware that assigns the request ID and scopes it to the handler
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid_request_id(v))
+        .map(String::from)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    // Always valid: either checked above or a UUID
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        req.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+
+    let mut response = REQUEST_ID
+        .scope(id.clone(), next.run(req).instrument(span))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+/// Inbound IDs must be short, non-empty and limited to characters that are
+/// safe to log and to filter on
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("ui-7f3a2c"));
+        assert!(is_valid_request_id("550e8400-e29b-41d4-a716-446655440000"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id("line\nbreak"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_current_request_id_is_scoped() {
+        assert_eq!(current_request_id(), None);
+        let inside = REQUEST_ID
+            .scope("abc".to_string(), async { current_request_id() })
+            .await;
+        assert_eq!(inside.as_deref(), Some("abc"));
+    }
+}
@@ -0,0 +1,48 @@
+//! Tenant isolation for app routes
+//!
+//! Routes with an `{app_name}` path parameter answer `404` for apps that
+//! belong to a tenant the caller cannot see, as if the app did not exist.
+
+use axum::{
+    extract::{rejection::RawPathParamsRejection, RawPathParams, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::AuthenticatedUser;
+use crate::error::AppError;
+use crate::services::tenants;
+use crate::state::AppState;
+
+/// Route layer hiding apps of other tenants
+pub async fn require_tenant_app(
+    State(state): State<AppState>,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let app_name = params.ok().and_then(|params| {
+        params
+            .iter()
+            .find(|(key, _)| *key == "app_name")
+            .map(|(_, value)| value.to_string())
+    });
+    let user_id = req
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .map(|auth| auth.user.id);
+    if let (Some(app_name), Some(user_id)) = (app_name, user_id) {
+        let visible = match state.get_db().await {
+            Ok(db) => tenants::can_see_app(&db, user_id, &app_name).await,
+            Err(e) => Err(e),
+        };
+        match visible {
+            Ok(true) => {}
+            Ok(false) => {
+                return AppError::NotFound(format!("App '{}' not found", app_name)).into_response()
+            }
+            Err(e) => return e.into_response(),
+        }
+    }
+    next.run(req).await
+}
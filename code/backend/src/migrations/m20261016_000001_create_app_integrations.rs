@@ -0,0 +1,77 @@
+//! Migration: Create app_integrations table
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppIntegrations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppIntegrations::AppName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppIntegrations::CredentialsJson)
+                            .text()
+                            .not_null()
+                            .default("{}"),
+                    )
+                    .col(ColumnDef::new(AppIntegrations::BaseUrl).text().null())
+                    .col(
+                        ColumnDef::new(AppIntegrations::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(AppIntegrations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppIntegrations::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppIntegrations::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "app_integrations"]
+enum AppIntegrations {
+    Table,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "credentials_json"]
+    CredentialsJson,
+    #[iden = "base_url"]
+    BaseUrl,
+    Enabled,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,106 @@
+//! Migration: Create media_account_links table
+//!
+//! Maps Kubarr users to their user accounts in request managers
+//! (Jellyseerr/Overseerr) so requests can be made on their behalf.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MediaAccountLinks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::AppName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::ExternalUserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::ExternalUsername)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaAccountLinks::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MediaAccountLinks::Table, MediaAccountLinks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_media_account_links_user_app")
+                    .table(MediaAccountLinks::Table)
+                    .col(MediaAccountLinks::UserId)
+                    .col(MediaAccountLinks::AppName)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(MediaAccountLinks::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "media_account_links"]
+enum MediaAccountLinks {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "external_user_id"]
+    ExternalUserId,
+    #[iden = "external_username"]
+    ExternalUsername,
+    #[iden = "created_at"]
+    CreatedAt,
+}
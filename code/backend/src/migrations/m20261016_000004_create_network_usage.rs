@@ -0,0 +1,151 @@
+//! Migration: Create network_usage and network_quotas tables
+//!
+//! Stores daily/monthly per-app ingress/egress rollups sampled from
+//! VictoriaMetrics, and optional monthly transfer quotas per app.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NetworkUsage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NetworkUsage::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NetworkUsage::AppName).string().not_null())
+                    .col(ColumnDef::new(NetworkUsage::Period).string().not_null())
+                    .col(
+                        ColumnDef::new(NetworkUsage::PeriodStart)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkUsage::RxBytes)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkUsage::TxBytes)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkUsage::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_network_usage_app_period")
+                    .table(NetworkUsage::Table)
+                    .col(NetworkUsage::AppName)
+                    .col(NetworkUsage::Period)
+                    .col(NetworkUsage::PeriodStart)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NetworkQuotas::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NetworkQuotas::AppName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkQuotas::MonthlyLimitBytes)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkQuotas::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NetworkQuotas::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NetworkQuotas::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NetworkUsage::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "network_usage"]
+enum NetworkUsage {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    Period,
+    #[iden = "period_start"]
+    PeriodStart,
+    #[iden = "rx_bytes"]
+    RxBytes,
+    #[iden = "tx_bytes"]
+    TxBytes,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "network_quotas"]
+enum NetworkQuotas {
+    Table,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "monthly_limit_bytes"]
+    MonthlyLimitBytes,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,97 @@
+//! Migration: Create wan_health_checks table
+//!
+//! History of WAN latency/packet-loss probes and speed tests.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WanHealthChecks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WanHealthChecks::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(WanHealthChecks::Kind).string().not_null())
+                    .col(ColumnDef::new(WanHealthChecks::Target).string().not_null())
+                    .col(ColumnDef::new(WanHealthChecks::LatencyMs).double().null())
+                    .col(
+                        ColumnDef::new(WanHealthChecks::PacketLossPercent)
+                            .double()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(WanHealthChecks::DownloadMbps)
+                            .double()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(WanHealthChecks::Success)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WanHealthChecks::Error).text().null())
+                    .col(
+                        ColumnDef::new(WanHealthChecks::CheckedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_wan_health_checks_kind_checked_at")
+                    .table(WanHealthChecks::Table)
+                    .col(WanHealthChecks::Kind)
+                    .col(WanHealthChecks::CheckedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(WanHealthChecks::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "wan_health_checks"]
+enum WanHealthChecks {
+    Table,
+    Id,
+    Kind,
+    Target,
+    #[iden = "latency_ms"]
+    LatencyMs,
+    #[iden = "packet_loss_percent"]
+    PacketLossPercent,
+    #[iden = "download_mbps"]
+    DownloadMbps,
+    Success,
+    Error,
+    #[iden = "checked_at"]
+    CheckedAt,
+}
@@ -0,0 +1,209 @@
+//! Migration: Create uptime_monitors and uptime_checks tables
+//!
+//! User-defined HTTP/TCP endpoints checked by the uptime worker, and the
+//! result of every check.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UptimeMonitors::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UptimeMonitors::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UptimeMonitors::Name).string().not_null())
+                    .col(ColumnDef::new(UptimeMonitors::Kind).string().not_null())
+                    .col(ColumnDef::new(UptimeMonitors::Target).text().not_null())
+                    .col(
+                        ColumnDef::new(UptimeMonitors::IntervalSeconds)
+                            .integer()
+                            .not_null()
+                            .default(60),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::TimeoutSeconds)
+                            .integer()
+                            .not_null()
+                            .default(10),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::ExpectedStatus)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::Status)
+                            .string()
+                            .not_null()
+                            .default("unknown"),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::LastCheckedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::LastChangeAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::CreatedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeMonitors::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UptimeMonitors::Table, UptimeMonitors::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UptimeChecks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UptimeChecks::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(UptimeChecks::MonitorId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(UptimeChecks::Success).boolean().not_null())
+                    .col(ColumnDef::new(UptimeChecks::ResponseTimeMs).double().null())
+                    .col(ColumnDef::new(UptimeChecks::StatusCode).integer().null())
+                    .col(ColumnDef::new(UptimeChecks::Error).text().null())
+                    .col(
+                        ColumnDef::new(UptimeChecks::CheckedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UptimeChecks::Table, UptimeChecks::MonitorId)
+                            .to(UptimeMonitors::Table, UptimeMonitors::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_uptime_checks_monitor_checked_at")
+                    .table(UptimeChecks::Table)
+                    .col(UptimeChecks::MonitorId)
+                    .col(UptimeChecks::CheckedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(UptimeChecks::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(UptimeMonitors::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "uptime_monitors"]
+enum UptimeMonitors {
+    Table,
+    Id,
+    Name,
+    Kind,
+    Target,
+    #[iden = "interval_seconds"]
+    IntervalSeconds,
+    #[iden = "timeout_seconds"]
+    TimeoutSeconds,
+    #[iden = "expected_status"]
+    ExpectedStatus,
+    Enabled,
+    Status,
+    #[iden = "last_checked_at"]
+    LastCheckedAt,
+    #[iden = "last_change_at"]
+    LastChangeAt,
+    #[iden = "created_by"]
+    CreatedBy,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "uptime_checks"]
+enum UptimeChecks {
+    Table,
+    Id,
+    #[iden = "monitor_id"]
+    MonitorId,
+    Success,
+    #[iden = "response_time_ms"]
+    ResponseTimeMs,
+    #[iden = "status_code"]
+    StatusCode,
+    Error,
+    #[iden = "checked_at"]
+    CheckedAt,
+}
@@ -0,0 +1,56 @@
+//! Migration: Grant the monitoring.manage permission to the admin role
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const PERMISSION: &str = "monitoring.manage";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        use crate::models::prelude::*;
+        use crate::models::{role, role_permission};
+
+        let db = manager.get_connection();
+
+        let Some(admin_role) = Role::find()
+            .filter(role::Column::Name.eq("admin"))
+            .one(db)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let existing = RolePermission::find()
+            .filter(role_permission::Column::RoleId.eq(admin_role.id))
+            .filter(role_permission::Column::Permission.eq(PERMISSION))
+            .one(db)
+            .await?;
+        if existing.is_none() {
+            role_permission::ActiveModel {
+                role_id: Set(admin_role.id),
+                permission: Set(PERMISSION.to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        use crate::models::prelude::*;
+        use crate::models::role_permission;
+
+        let db = manager.get_connection();
+        RolePermission::delete_many()
+            .filter(role_permission::Column::Permission.eq(PERMISSION))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Migration: Create pod_restart_events table
+//!
+//! Container restarts observed by the pod stability sampler, with the
+//! termination reason and exit code of the last restart.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PodRestartEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PodRestartEvents::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PodRestartEvents::AppName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PodRestartEvents::PodName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PodRestartEvents::ContainerName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PodRestartEvents::RestartCount)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PodRestartEvents::Restarts)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PodRestartEvents::Reason).string().null())
+                    .col(ColumnDef::new(PodRestartEvents::ExitCode).integer().null())
+                    .col(
+                        ColumnDef::new(PodRestartEvents::OccurredAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pod_restart_events_app_occurred_at")
+                    .table(PodRestartEvents::Table)
+                    .col(PodRestartEvents::AppName)
+                    .col(PodRestartEvents::OccurredAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pod_restart_events_pod_container")
+                    .table(PodRestartEvents::Table)
+                    .col(PodRestartEvents::PodName)
+                    .col(PodRestartEvents::ContainerName)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(PodRestartEvents::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "pod_restart_events"]
+enum PodRestartEvents {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "pod_name"]
+    PodName,
+    #[iden = "container_name"]
+    ContainerName,
+    #[iden = "restart_count"]
+    RestartCount,
+    Restarts,
+    Reason,
+    #[iden = "exit_code"]
+    ExitCode,
+    #[iden = "occurred_at"]
+    OccurredAt,
+}
@@ -0,0 +1,100 @@
+//! Migration: Create dashboards table
+//!
+//! User-defined dashboards of metric panels, private to their owner or
+//! shared with everyone who can view monitoring.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Dashboards::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Dashboards::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Dashboards::OwnerId).big_integer().not_null())
+                    .col(ColumnDef::new(Dashboards::Name).string().not_null())
+                    .col(ColumnDef::new(Dashboards::Description).text().null())
+                    .col(
+                        ColumnDef::new(Dashboards::Shared)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(Dashboards::PanelsJson).text().not_null())
+                    .col(
+                        ColumnDef::new(Dashboards::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Dashboards::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Dashboards::Table, Dashboards::OwnerId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_dashboards_owner_id")
+                    .table(Dashboards::Table)
+                    .col(Dashboards::OwnerId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(Dashboards::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "dashboards"]
+enum Dashboards {
+    Table,
+    Id,
+    #[iden = "owner_id"]
+    OwnerId,
+    Name,
+    Description,
+    Shared,
+    #[iden = "panels_json"]
+    PanelsJson,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,158 @@
+//! Migration: Create metric_anomalies and anomaly_sensitivities tables
+//!
+//! Deviations of app CPU, memory and restart metrics from their baseline,
+//! and per-app detection sensitivity overrides.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricAnomalies::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MetricAnomalies::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MetricAnomalies::AppName).string().not_null())
+                    .col(ColumnDef::new(MetricAnomalies::Metric).string().not_null())
+                    .col(
+                        ColumnDef::new(MetricAnomalies::Direction)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MetricAnomalies::Value).double().not_null())
+                    .col(
+                        ColumnDef::new(MetricAnomalies::BaselineMean)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MetricAnomalies::BaselineStddev)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MetricAnomalies::ZScore).double().not_null())
+                    .col(
+                        ColumnDef::new(MetricAnomalies::WindowStart)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MetricAnomalies::WindowEnd)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MetricAnomalies::DetectedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_metric_anomalies_app_metric_detected_at")
+                    .table(MetricAnomalies::Table)
+                    .col(MetricAnomalies::AppName)
+                    .col(MetricAnomalies::Metric)
+                    .col(MetricAnomalies::DetectedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AnomalySensitivities::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AnomalySensitivities::AppName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AnomalySensitivities::Sensitivity)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AnomalySensitivities::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AnomalySensitivities::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(MetricAnomalies::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "metric_anomalies"]
+enum MetricAnomalies {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    Metric,
+    Direction,
+    Value,
+    #[iden = "baseline_mean"]
+    BaselineMean,
+    #[iden = "baseline_stddev"]
+    BaselineStddev,
+    #[iden = "z_score"]
+    ZScore,
+    #[iden = "window_start"]
+    WindowStart,
+    #[iden = "window_end"]
+    WindowEnd,
+    #[iden = "detected_at"]
+    DetectedAt,
+}
+
+#[derive(Iden)]
+#[iden = "anomaly_sensitivities"]
+enum AnomalySensitivities {
+    Table,
+    #[iden = "app_name"]
+    AppName,
+    Sensitivity,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,153 @@
+//! Migration: Create energy_usage and node_power_profiles tables
+//!
+//! Stores daily/monthly per-app energy and cost rollups estimated from CPU
+//! utilization, and the idle/max wattage of individual nodes.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EnergyUsage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EnergyUsage::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EnergyUsage::AppName).string().not_null())
+                    .col(ColumnDef::new(EnergyUsage::Period).string().not_null())
+                    .col(ColumnDef::new(EnergyUsage::PeriodStart).string().not_null())
+                    .col(
+                        ColumnDef::new(EnergyUsage::EnergyWh)
+                            .double()
+                            .not_null()
+                            .default(0.0),
+                    )
+                    .col(
+                        ColumnDef::new(EnergyUsage::Cost)
+                            .double()
+                            .not_null()
+                            .default(0.0),
+                    )
+                    .col(
+                        ColumnDef::new(EnergyUsage::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_energy_usage_app_period")
+                    .table(EnergyUsage::Table)
+                    .col(EnergyUsage::AppName)
+                    .col(EnergyUsage::Period)
+                    .col(EnergyUsage::PeriodStart)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NodePowerProfiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NodePowerProfiles::NodeName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NodePowerProfiles::IdleWatts)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodePowerProfiles::MaxWatts)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodePowerProfiles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodePowerProfiles::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NodePowerProfiles::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(EnergyUsage::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "energy_usage"]
+enum EnergyUsage {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    Period,
+    #[iden = "period_start"]
+    PeriodStart,
+    #[iden = "energy_wh"]
+    EnergyWh,
+    Cost,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "node_power_profiles"]
+enum NodePowerProfiles {
+    Table,
+    #[iden = "node_name"]
+    NodeName,
+    #[iden = "idle_watts"]
+    IdleWatts,
+    #[iden = "max_watts"]
+    MaxWatts,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
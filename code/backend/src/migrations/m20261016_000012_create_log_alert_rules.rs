@@ -0,0 +1,152 @@
+//! Migration: Create log_alert_rules table
+//!
+//! LogsQL patterns evaluated against VictoriaLogs on an interval, firing when
+//! the number of matching lines in the window exceeds the threshold.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LogAlertRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LogAlertRules::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LogAlertRules::Name).string().not_null())
+                    .col(ColumnDef::new(LogAlertRules::AppName).string().null())
+                    .col(ColumnDef::new(LogAlertRules::Pattern).text().not_null())
+                    .col(
+                        ColumnDef::new(LogAlertRules::Threshold)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::WindowMinutes)
+                            .integer()
+                            .not_null()
+                            .default(5),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::IntervalSeconds)
+                            .integer()
+                            .not_null()
+                            .default(60),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::Severity)
+                            .string()
+                            .not_null()
+                            .default("warning"),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::State)
+                            .string()
+                            .not_null()
+                            .default("unknown"),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::LastCount)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(LogAlertRules::LastError).text().null())
+                    .col(
+                        ColumnDef::new(LogAlertRules::LastEvaluatedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::FiringSince)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::CreatedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(LogAlertRules::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LogAlertRules::Table, LogAlertRules::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(LogAlertRules::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "log_alert_rules"]
+enum LogAlertRules {
+    Table,
+    Id,
+    Name,
+    #[iden = "app_name"]
+    AppName,
+    Pattern,
+    Threshold,
+    #[iden = "window_minutes"]
+    WindowMinutes,
+    #[iden = "interval_seconds"]
+    IntervalSeconds,
+    Severity,
+    Enabled,
+    State,
+    #[iden = "last_count"]
+    LastCount,
+    #[iden = "last_error"]
+    LastError,
+    #[iden = "last_evaluated_at"]
+    LastEvaluatedAt,
+    #[iden = "firing_since"]
+    FiringSince,
+    #[iden = "created_by"]
+    CreatedBy,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,231 @@
+//! Migration: Create alerts and alert_events tables
+//!
+//! Alerts raised by the monitoring checkers (metric anomalies, log rules,
+//! uptime monitors, WAN health, hardware sensors) with their acknowledgement,
+//! silence and assignment state, plus a per-alert history.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Alerts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Alerts::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Alerts::Fingerprint).string().not_null())
+                    .col(ColumnDef::new(Alerts::Source).string().not_null())
+                    .col(ColumnDef::new(Alerts::Name).string().not_null())
+                    .col(ColumnDef::new(Alerts::AppName).string().null())
+                    .col(ColumnDef::new(Alerts::Severity).string().not_null())
+                    .col(ColumnDef::new(Alerts::Message).text().not_null())
+                    .col(ColumnDef::new(Alerts::Link).string().not_null())
+                    .col(
+                        ColumnDef::new(Alerts::State)
+                            .string()
+                            .not_null()
+                            .default("firing"),
+                    )
+                    .col(
+                        ColumnDef::new(Alerts::StartedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Alerts::LastSeenAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Alerts::ResolvedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(Alerts::AckedBy).big_integer().null())
+                    .col(
+                        ColumnDef::new(Alerts::AckedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Alerts::SilencedUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(Alerts::SilencedBy).big_integer().null())
+                    .col(ColumnDef::new(Alerts::AssignedTo).big_integer().null())
+                    .col(
+                        ColumnDef::new(Alerts::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Alerts::Table, Alerts::AckedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Alerts::Table, Alerts::SilencedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Alerts::Table, Alerts::AssignedTo)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alerts_fingerprint")
+                    .table(Alerts::Table)
+                    .col(Alerts::Fingerprint)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alerts_state")
+                    .table(Alerts::Table)
+                    .col(Alerts::State)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AlertEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AlertEvents::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AlertEvents::AlertId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AlertEvents::Action).string().not_null())
+                    .col(ColumnDef::new(AlertEvents::UserId).big_integer().null())
+                    .col(ColumnDef::new(AlertEvents::Detail).text().null())
+                    .col(
+                        ColumnDef::new(AlertEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AlertEvents::Table, AlertEvents::AlertId)
+                            .to(Alerts::Table, Alerts::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AlertEvents::Table, AlertEvents::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alert_events_alert_id")
+                    .table(AlertEvents::Table)
+                    .col(AlertEvents::AlertId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AlertEvents::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Alerts::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "alerts"]
+enum Alerts {
+    Table,
+    Id,
+    Fingerprint,
+    Source,
+    Name,
+    #[iden = "app_name"]
+    AppName,
+    Severity,
+    Message,
+    Link,
+    State,
+    #[iden = "started_at"]
+    StartedAt,
+    #[iden = "last_seen_at"]
+    LastSeenAt,
+    #[iden = "resolved_at"]
+    ResolvedAt,
+    #[iden = "acked_by"]
+    AckedBy,
+    #[iden = "acked_at"]
+    AckedAt,
+    #[iden = "silenced_until"]
+    SilencedUntil,
+    #[iden = "silenced_by"]
+    SilencedBy,
+    #[iden = "assigned_to"]
+    AssignedTo,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "alert_events"]
+enum AlertEvents {
+    Table,
+    Id,
+    #[iden = "alert_id"]
+    AlertId,
+    Action,
+    #[iden = "user_id"]
+    UserId,
+    Detail,
+    #[iden = "created_at"]
+    CreatedAt,
+}
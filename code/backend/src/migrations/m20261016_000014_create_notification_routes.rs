@@ -0,0 +1,117 @@
+//! Migration: Create notification_routes table
+//!
+//! Routing rules that send events matching an event type, severity and time
+//! window to specific channels and recipients.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationRoutes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationRoutes::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NotificationRoutes::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(NotificationRoutes::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::EventTypes)
+                            .text()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::Severities)
+                            .text()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::StartTime)
+                            .string()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(NotificationRoutes::EndTime).string().null())
+                    .col(
+                        ColumnDef::new(NotificationRoutes::Timezone)
+                            .string()
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::Channels)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::RecipientUserIds)
+                            .text()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationRoutes::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NotificationRoutes::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "notification_routes"]
+enum NotificationRoutes {
+    Table,
+    Id,
+    Name,
+    Enabled,
+    #[iden = "event_types"]
+    EventTypes,
+    Severities,
+    #[iden = "start_time"]
+    StartTime,
+    #[iden = "end_time"]
+    EndTime,
+    Timezone,
+    Channels,
+    #[iden = "recipient_user_ids"]
+    RecipientUserIds,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,59 @@
+//! Migration: Add request_id column to audit_logs table
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .add_column(ColumnDef::new(AuditLogs::RequestId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_request_id")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::RequestId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_logs_request_id")
+                    .table(AuditLogs::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .drop_column(AuditLogs::RequestId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "audit_logs"]
+enum AuditLogs {
+    Table,
+    #[iden = "request_id"]
+    RequestId,
+}
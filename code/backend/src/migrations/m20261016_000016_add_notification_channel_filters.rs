@@ -0,0 +1,73 @@
+//! Migration: Add delivery filters to notification channels and user preferences
+//!
+//! Adds a minimum severity and included/excluded event types (JSON arrays) to
+//! both the global channel configuration and each user's channel preference.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            Alias::new("notification_channels"),
+            Alias::new("user_notification_prefs"),
+        ] {
+            // SQLite only allows one column per ALTER TABLE statement
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table.clone())
+                        .add_column(ColumnDef::new(Filters::MinSeverity).string().null())
+                        .to_owned(),
+                )
+                .await?;
+            for column in [Filters::IncludeEventTypes, Filters::ExcludeEventTypes] {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table.clone())
+                            .add_column(ColumnDef::new(column).text().not_null().default("[]"))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            Alias::new("notification_channels"),
+            Alias::new("user_notification_prefs"),
+        ] {
+            for column in [
+                Filters::MinSeverity,
+                Filters::IncludeEventTypes,
+                Filters::ExcludeEventTypes,
+            ] {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table.clone())
+                            .drop_column(column)
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Filters {
+    #[iden = "min_severity"]
+    MinSeverity,
+    #[iden = "include_event_types"]
+    IncludeEventTypes,
+    #[iden = "exclude_event_types"]
+    ExcludeEventTypes,
+}
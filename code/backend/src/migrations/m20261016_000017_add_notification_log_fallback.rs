@@ -0,0 +1,46 @@
+//! Migration: Add fallback_of column to notification_logs table
+//!
+//! A delivery made because another channel failed points at the log entry of
+//! that failed attempt, so a whole fallback chain can be followed.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationLogs::Table)
+                    .add_column(
+                        ColumnDef::new(NotificationLogs::FallbackOf)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationLogs::Table)
+                    .drop_column(NotificationLogs::FallbackOf)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "notification_logs"]
+enum NotificationLogs {
+    Table,
+    #[iden = "fallback_of"]
+    FallbackOf,
+}
@@ -0,0 +1,83 @@
+//! Migration: Add one-time code verification to user notification preferences
+//!
+//! Stores the hash of the code last sent to a destination, when it was sent
+//! and how many wrong codes have been submitted for it.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserNotificationPrefs::Table)
+                    .add_column(
+                        ColumnDef::new(UserNotificationPrefs::VerificationCodeHash)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserNotificationPrefs::Table)
+                    .add_column(
+                        ColumnDef::new(UserNotificationPrefs::VerificationSentAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserNotificationPrefs::Table)
+                    .add_column(
+                        ColumnDef::new(UserNotificationPrefs::VerificationAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            UserNotificationPrefs::VerificationCodeHash,
+            UserNotificationPrefs::VerificationSentAt,
+            UserNotificationPrefs::VerificationAttempts,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserNotificationPrefs::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "user_notification_prefs"]
+enum UserNotificationPrefs {
+    Table,
+    #[iden = "verification_code_hash"]
+    VerificationCodeHash,
+    #[iden = "verification_sent_at"]
+    VerificationSentAt,
+    #[iden = "verification_attempts"]
+    VerificationAttempts,
+}
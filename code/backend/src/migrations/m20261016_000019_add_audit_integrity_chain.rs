@@ -0,0 +1,59 @@
+//! Migration: Add hash chain columns to audit_logs table
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .add_column(ColumnDef::new(AuditLogs::PrevHash).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .add_column(ColumnDef::new(AuditLogs::IntegrityHash).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .drop_column(AuditLogs::IntegrityHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLogs::Table)
+                    .drop_column(AuditLogs::PrevHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "audit_logs"]
+enum AuditLogs {
+    Table,
+    #[iden = "prev_hash"]
+    PrevHash,
+    #[iden = "integrity_hash"]
+    IntegrityHash,
+}
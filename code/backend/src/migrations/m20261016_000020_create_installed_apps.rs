@@ -0,0 +1,70 @@
+//! Migration: Create installed_apps table
+//!
+//! User-editable metadata (notes and tags) for apps deployed through Helm.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InstalledApps::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InstalledApps::AppName)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(InstalledApps::Notes).text().null())
+                    .col(
+                        ColumnDef::new(InstalledApps::Tags)
+                            .text()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(InstalledApps::InstalledAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InstalledApps::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InstalledApps::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "app_name"]
+    AppName,
+    Notes,
+    Tags,
+    #[iden = "installed_at"]
+    InstalledAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
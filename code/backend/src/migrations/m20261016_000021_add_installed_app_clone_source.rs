@@ -0,0 +1,42 @@
+//! Migration: Add cloned_from column to installed_apps table
+//!
+//! Cloned instances (e.g. `sonarr-4k`) record the catalog app they were
+//! installed from.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(ColumnDef::new(InstalledApps::ClonedFrom).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .drop_column(InstalledApps::ClonedFrom)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "cloned_from"]
+    ClonedFrom,
+}
@@ -0,0 +1,65 @@
+//! Migration: Add routing columns to installed_apps table
+//!
+//! Apps are proxied under `/{app_name}/` by default; apps that misbehave under
+//! a sub-path can instead be served from a dedicated subdomain.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::RoutingMode)
+                            .string()
+                            .not_null()
+                            .default("path"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(ColumnDef::new(InstalledApps::Subdomain).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .drop_column(InstalledApps::Subdomain)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .drop_column(InstalledApps::RoutingMode)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "routing_mode"]
+    RoutingMode,
+    Subdomain,
+}
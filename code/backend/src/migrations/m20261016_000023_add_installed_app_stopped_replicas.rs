@@ -0,0 +1,42 @@
+//! Migration: Add stopped_replicas column to installed_apps table
+//!
+//! Stopping an app scales its deployments to zero; their replica counts are
+//! kept here so starting it again restores them.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(ColumnDef::new(InstalledApps::StoppedReplicas).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .drop_column(InstalledApps::StoppedReplicas)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "stopped_replicas"]
+    StoppedReplicas,
+}
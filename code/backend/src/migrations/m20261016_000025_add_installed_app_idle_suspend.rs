@@ -0,0 +1,83 @@
+//! Migration: Add idle auto-suspend columns to installed_apps table
+//!
+//! Apps without proxied access for a configurable number of days can be
+//! scaled to zero automatically and are woken by their next request.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::LastAccessedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::IdleSuspendExempt)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::SuspendedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            InstalledApps::SuspendedAt,
+            InstalledApps::IdleSuspendExempt,
+            InstalledApps::LastAccessedAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstalledApps::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "last_accessed_at"]
+    LastAccessedAt,
+    #[iden = "idle_suspend_exempt"]
+    IdleSuspendExempt,
+    #[iden = "suspended_at"]
+    SuspendedAt,
+}
@@ -0,0 +1,85 @@
+//! Migration: Add boot ordering columns to installed_apps table
+//!
+//! Apps can declare a boot priority and the apps they depend on. After a
+//! cluster reboot, apps coming up before their prerequisites are held at zero
+//! replicas until those are healthy.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::BootPriority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::DependsOn)
+                            .text()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::BootHeldAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            InstalledApps::BootHeldAt,
+            InstalledApps::DependsOn,
+            InstalledApps::BootPriority,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstalledApps::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "boot_priority"]
+    BootPriority,
+    #[iden = "depends_on"]
+    DependsOn,
+    #[iden = "boot_held_at"]
+    BootHeldAt,
+}
@@ -0,0 +1,113 @@
+//! Migration: Create kubarr_updates table
+//!
+//! Upgrades of Kubarr's own Helm release, with the pre-update backup needed
+//! to roll back and the outcome of the post-update health verification.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(KubarrUpdates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(KubarrUpdates::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(KubarrUpdates::FromVersion)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(KubarrUpdates::ToVersion).string().not_null())
+                    .col(ColumnDef::new(KubarrUpdates::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(KubarrUpdates::PreviousRevision)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(KubarrUpdates::Backup).text().not_null())
+                    .col(ColumnDef::new(KubarrUpdates::Error).text().null())
+                    .col(
+                        ColumnDef::new(KubarrUpdates::StartedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(KubarrUpdates::StartedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(KubarrUpdates::FinishedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(KubarrUpdates::Table, KubarrUpdates::StartedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_kubarr_updates_started_at")
+                    .table(KubarrUpdates::Table)
+                    .col(KubarrUpdates::StartedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(KubarrUpdates::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "kubarr_updates"]
+enum KubarrUpdates {
+    Table,
+    Id,
+    #[iden = "from_version"]
+    FromVersion,
+    #[iden = "to_version"]
+    ToVersion,
+    Status,
+    #[iden = "previous_revision"]
+    PreviousRevision,
+    Backup,
+    Error,
+    #[iden = "started_by"]
+    StartedBy,
+    #[iden = "started_at"]
+    StartedAt,
+    #[iden = "finished_at"]
+    FinishedAt,
+}
@@ -0,0 +1,105 @@
+//! Migration: Create report_subscriptions table
+//!
+//! Admins who opted in to the scheduled summary report, with how often and at
+//! which local time they receive it.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportSubscriptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::UserId)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::Frequency)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::Timezone)
+                            .string()
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::SendHour)
+                            .integer()
+                            .not_null()
+                            .default(8),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::LastSentAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ReportSubscriptions::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportSubscriptions::Table, ReportSubscriptions::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ReportSubscriptions::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "report_subscriptions"]
+enum ReportSubscriptions {
+    Table,
+    #[iden = "user_id"]
+    UserId,
+    Enabled,
+    Frequency,
+    Timezone,
+    #[iden = "send_hour"]
+    SendHour,
+    #[iden = "last_sent_at"]
+    LastSentAt,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
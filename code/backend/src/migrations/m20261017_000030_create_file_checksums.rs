@@ -0,0 +1,112 @@
+//! Migration: Create file_checksums table
+//!
+//! Checksums computed for files in storage, with the file's size and
+//! modification time at the time so stale results can be recognized.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FileChecksums::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FileChecksums::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FileChecksums::Root).string().not_null())
+                    .col(ColumnDef::new(FileChecksums::Path).text().not_null())
+                    .col(ColumnDef::new(FileChecksums::Algorithm).string().not_null())
+                    .col(ColumnDef::new(FileChecksums::Status).string().not_null())
+                    .col(ColumnDef::new(FileChecksums::Checksum).string().null())
+                    .col(ColumnDef::new(FileChecksums::Size).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(FileChecksums::FileModifiedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(FileChecksums::Error).text().null())
+                    .col(
+                        ColumnDef::new(FileChecksums::RequestedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(FileChecksums::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(FileChecksums::CompletedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(FileChecksums::Table, FileChecksums::RequestedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_file_checksums_root_path")
+                    .table(FileChecksums::Table)
+                    .col(FileChecksums::Root)
+                    .col(FileChecksums::Path)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(FileChecksums::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "file_checksums"]
+enum FileChecksums {
+    Table,
+    Id,
+    Root,
+    Path,
+    Algorithm,
+    Status,
+    Checksum,
+    Size,
+    #[iden = "file_modified_at"]
+    FileModifiedAt,
+    Error,
+    #[iden = "requested_by"]
+    RequestedBy,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "completed_at"]
+    CompletedAt,
+}
@@ -0,0 +1,121 @@
+//! Migration: Create storage_shares and share_credentials tables
+//!
+//! Storage roots shared over SMB, and the NT password hashes users set to
+//! sign in to the shares.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StorageShares::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StorageShares::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageShares::Root)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageShares::ReadOnly)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(StorageShares::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareCredentials::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShareCredentials::UserId)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ShareCredentials::NtHash).string().not_null())
+                    .col(
+                        ColumnDef::new(ShareCredentials::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShareCredentials::Table, ShareCredentials::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ShareCredentials::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(StorageShares::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "storage_shares"]
+enum StorageShares {
+    Table,
+    Id,
+    Root,
+    #[iden = "read_only"]
+    ReadOnly,
+    #[iden = "created_at"]
+    CreatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "share_credentials"]
+enum ShareCredentials {
+    Table,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "nt_hash"]
+    NtHash,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,84 @@
+//! Migration: Create log_archives table
+//!
+//! Index of the per-app, per-day log archives exported from VictoriaLogs to
+//! the archive store.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LogArchives::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LogArchives::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LogArchives::AppName).string().not_null())
+                    .col(ColumnDef::new(LogArchives::Day).string().not_null())
+                    .col(ColumnDef::new(LogArchives::Store).string().not_null())
+                    .col(ColumnDef::new(LogArchives::ObjectKey).string().null())
+                    .col(ColumnDef::new(LogArchives::Lines).big_integer().not_null())
+                    .col(ColumnDef::new(LogArchives::Bytes).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(LogArchives::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_log_archives_app_day")
+                    .table(LogArchives::Table)
+                    .col(LogArchives::AppName)
+                    .col(LogArchives::Day)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(LogArchives::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "log_archives"]
+enum LogArchives {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    Day,
+    Store,
+    #[iden = "object_key"]
+    ObjectKey,
+    Lines,
+    Bytes,
+    #[iden = "created_at"]
+    CreatedAt,
+}
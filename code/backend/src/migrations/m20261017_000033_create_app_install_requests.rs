@@ -0,0 +1,131 @@
+//! Migration: Create app_install_requests table
+//!
+//! Installs asked for by users with `apps.request`, waiting for an admin to
+//! approve or deny them.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppInstallRequests::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppInstallRequests::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::AppName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::CustomConfig)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::RequestedBy)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AppInstallRequests::Comment).text().null())
+                    .col(
+                        ColumnDef::new(AppInstallRequests::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::ReviewedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::ReviewComment)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppInstallRequests::ReviewedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppInstallRequests::Table, AppInstallRequests::RequestedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppInstallRequests::Table, AppInstallRequests::ReviewedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_app_install_requests_status")
+                    .table(AppInstallRequests::Table)
+                    .col(AppInstallRequests::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppInstallRequests::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "app_install_requests"]
+enum AppInstallRequests {
+    Table,
+    Id,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "custom_config"]
+    CustomConfig,
+    #[iden = "requested_by"]
+    RequestedBy,
+    Comment,
+    Status,
+    #[iden = "reviewed_by"]
+    ReviewedBy,
+    #[iden = "review_comment"]
+    ReviewComment,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "reviewed_at"]
+    ReviewedAt,
+}
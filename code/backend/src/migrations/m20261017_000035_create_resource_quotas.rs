@@ -0,0 +1,118 @@
+//! Migration: Create resource_quotas table
+//!
+//! A quota caps how many apps a user may install and the CPU, memory and
+//! storage those installs may request. Each row belongs to either a user or
+//! a role; a role's quota applies to each of its members.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+use super::m20260127_000002_create_roles::Roles;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ResourceQuotas::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ResourceQuotas::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::UserId)
+                            .big_integer()
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::RoleId)
+                            .big_integer()
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ResourceQuotas::MaxApps).integer().null())
+                    .col(
+                        ColumnDef::new(ResourceQuotas::MaxCpuMillicores)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::MaxMemoryBytes)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::MaxStorageBytes)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceQuotas::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ResourceQuotas::Table, ResourceQuotas::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ResourceQuotas::Table, ResourceQuotas::RoleId)
+                            .to(Roles::Table, Roles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ResourceQuotas::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "resource_quotas"]
+enum ResourceQuotas {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "role_id"]
+    RoleId,
+    #[iden = "max_apps"]
+    MaxApps,
+    #[iden = "max_cpu_millicores"]
+    MaxCpuMillicores,
+    #[iden = "max_memory_bytes"]
+    MaxMemoryBytes,
+    #[iden = "max_storage_bytes"]
+    MaxStorageBytes,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
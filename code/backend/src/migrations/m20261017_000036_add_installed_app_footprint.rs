@@ -0,0 +1,76 @@
+//! Migration: Add owner and resource footprint columns to installed_apps
+//!
+//! Records who last installed or upgraded an app and what it requested, so
+//! quotas can count a user's apps without asking the cluster.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledApps::Table)
+                    .add_column(
+                        ColumnDef::new(InstalledApps::InstalledBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        for column in [
+            InstalledApps::CpuMillicores,
+            InstalledApps::MemoryBytes,
+            InstalledApps::StorageBytes,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstalledApps::Table)
+                        .add_column(ColumnDef::new(column).big_integer().not_null().default(0))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            InstalledApps::StorageBytes,
+            InstalledApps::MemoryBytes,
+            InstalledApps::CpuMillicores,
+            InstalledApps::InstalledBy,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(InstalledApps::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "installed_apps"]
+enum InstalledApps {
+    Table,
+    #[iden = "installed_by"]
+    InstalledBy,
+    #[iden = "cpu_millicores"]
+    CpuMillicores,
+    #[iden = "memory_bytes"]
+    MemoryBytes,
+    #[iden = "storage_bytes"]
+    StorageBytes,
+}
@@ -0,0 +1,126 @@
+//! Migration: Create storage_homes table
+//!
+//! One row per user home folder under a storage root that has homes
+//! enabled, holding the usage last measured by the scanner, optional
+//! per-user quota overrides and the last quota level the user was warned
+//! about.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StorageHomes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StorageHomes::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StorageHomes::Root).string().not_null())
+                    .col(
+                        ColumnDef::new(StorageHomes::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageHomes::UsedBytes)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(StorageHomes::SoftQuotaBytes)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageHomes::HardQuotaBytes)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(StorageHomes::NotifiedLevel).string().null())
+                    .col(
+                        ColumnDef::new(StorageHomes::ScannedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageHomes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StorageHomes::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StorageHomes::Table, StorageHomes::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_storage_homes_root_user")
+                    .table(StorageHomes::Table)
+                    .col(StorageHomes::Root)
+                    .col(StorageHomes::UserId)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(StorageHomes::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "storage_homes"]
+enum StorageHomes {
+    Table,
+    Id,
+    Root,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "used_bytes"]
+    UsedBytes,
+    #[iden = "soft_quota_bytes"]
+    SoftQuotaBytes,
+    #[iden = "hard_quota_bytes"]
+    HardQuotaBytes,
+    #[iden = "notified_level"]
+    NotifiedLevel,
+    #[iden = "scanned_at"]
+    ScannedAt,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
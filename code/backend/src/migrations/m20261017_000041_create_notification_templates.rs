@@ -0,0 +1,86 @@
+//! Migration: Create notification_templates table
+//!
+//! Admin overrides of the subject and body a notification event is sent with
+//! on one channel. Events without an override use the built-in text.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationTemplates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationTemplates::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationTemplates::EventType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NotificationTemplates::ChannelType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NotificationTemplates::Subject).text().null())
+                    .col(ColumnDef::new(NotificationTemplates::Body).text().null())
+                    .col(
+                        ColumnDef::new(NotificationTemplates::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_templates_event_channel")
+                    .table(NotificationTemplates::Table)
+                    .col(NotificationTemplates::EventType)
+                    .col(NotificationTemplates::ChannelType)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(NotificationTemplates::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "notification_templates"]
+enum NotificationTemplates {
+    Table,
+    Id,
+    #[iden = "event_type"]
+    EventType,
+    #[iden = "channel_type"]
+    ChannelType,
+    Subject,
+    Body,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
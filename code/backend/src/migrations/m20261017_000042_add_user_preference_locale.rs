@@ -0,0 +1,41 @@
+//! Migration: Add locale column to user_preferences table
+//!
+//! Notifications are written in the user's locale; users without one get
+//! the `default_locale` setting.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .add_column(ColumnDef::new(UserPreferences::Locale).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .drop_column(UserPreferences::Locale)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "user_preferences"]
+enum UserPreferences {
+    Table,
+    Locale,
+}
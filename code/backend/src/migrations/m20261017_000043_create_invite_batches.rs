@@ -0,0 +1,88 @@
+//! Migration: Create invite_batches table
+//!
+//! A batch groups invites created together under one label, with a share
+//! page listing them that is reachable through an unguessable token until
+//! it expires.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InviteBatches::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InviteBatches::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(InviteBatches::Label).string().not_null())
+                    .col(
+                        ColumnDef::new(InviteBatches::ShareToken)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(InviteBatches::CreatedById)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InviteBatches::ShareExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(InviteBatches::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(InviteBatches::Table, InviteBatches::CreatedById)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InviteBatches::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "invite_batches"]
+enum InviteBatches {
+    Table,
+    Id,
+    Label,
+    #[iden = "share_token"]
+    ShareToken,
+    #[iden = "created_by_id"]
+    CreatedById,
+    #[iden = "share_expires_at"]
+    ShareExpiresAt,
+    #[iden = "created_at"]
+    CreatedAt,
+}
@@ -0,0 +1,41 @@
+//! Migration: Add batch_id column to invites table
+//!
+//! Invites created one at a time have no batch.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Invites::Table)
+                    .add_column(ColumnDef::new(Invites::BatchId).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Invites::Table)
+                    .drop_column(Invites::BatchId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "invites"]
+enum Invites {
+    Table,
+    #[iden = "batch_id"]
+    BatchId,
+}
@@ -0,0 +1,46 @@
+//! Migration: Add login_alerts column to user_preferences table
+//!
+//! Users get a "was this you?" notification when they sign in from a new
+//! device unless they turn it off.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .add_column(
+                        ColumnDef::new(UserPreferences::LoginAlerts)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .drop_column(UserPreferences::LoginAlerts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "user_preferences"]
+enum UserPreferences {
+    Table,
+    LoginAlerts,
+}
@@ -0,0 +1,84 @@
+//! Migration: Create ip_bans table
+//!
+//! Addresses banned from the sign-in pages and other public routes, either
+//! automatically after too many failed sign-ins or by an admin. A ban
+//! without an expiry lasts until it is removed.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IpBans::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IpBans::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(IpBans::IpAddress)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(IpBans::Source).string().not_null())
+                    .col(ColumnDef::new(IpBans::Reason).string().null())
+                    .col(ColumnDef::new(IpBans::FailedAttempts).integer().null())
+                    .col(ColumnDef::new(IpBans::CreatedById).big_integer().null())
+                    .col(
+                        ColumnDef::new(IpBans::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IpBans::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IpBans::Table, IpBans::CreatedById)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IpBans::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "ip_bans"]
+enum IpBans {
+    Table,
+    Id,
+    #[iden = "ip_address"]
+    IpAddress,
+    Source,
+    Reason,
+    #[iden = "failed_attempts"]
+    FailedAttempts,
+    #[iden = "created_by_id"]
+    CreatedById,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "expires_at"]
+    ExpiresAt,
+}
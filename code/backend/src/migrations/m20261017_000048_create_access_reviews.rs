@@ -0,0 +1,196 @@
+//! Migration: Create access_reviews and access_review_items tables
+//!
+//! An access review lists every active user's roles and app access at the
+//! time it was opened. Admins confirm or revoke each user's access before
+//! the deadline; items left undecided are marked overdue.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessReviews::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessReviews::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AccessReviews::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(AccessReviews::CreatedById)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviews::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviews::DueAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviews::ClosedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccessReviews::Table, AccessReviews::CreatedById)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessReviewItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessReviewItems::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItems::ReviewId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItems::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AccessReviewItems::Roles).text().not_null())
+                    .col(ColumnDef::new(AccessReviewItems::Apps).text().not_null())
+                    .col(
+                        ColumnDef::new(AccessReviewItems::Decision)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItems::Suspended)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItems::DecidedById)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItems::DecidedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(AccessReviewItems::Comment).text().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccessReviewItems::Table, AccessReviewItems::ReviewId)
+                            .to(AccessReviews::Table, AccessReviews::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccessReviewItems::Table, AccessReviewItems::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AccessReviewItems::Table, AccessReviewItems::DecidedById)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_access_review_items_review_user")
+                    .table(AccessReviewItems::Table)
+                    .col(AccessReviewItems::ReviewId)
+                    .col(AccessReviewItems::UserId)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AccessReviewItems::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AccessReviews::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "access_reviews"]
+enum AccessReviews {
+    Table,
+    Id,
+    Status,
+    #[iden = "created_by_id"]
+    CreatedById,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "due_at"]
+    DueAt,
+    #[iden = "closed_at"]
+    ClosedAt,
+}
+
+#[derive(Iden)]
+#[iden = "access_review_items"]
+enum AccessReviewItems {
+    Table,
+    Id,
+    #[iden = "review_id"]
+    ReviewId,
+    #[iden = "user_id"]
+    UserId,
+    Roles,
+    Apps,
+    Decision,
+    Suspended,
+    #[iden = "decided_by_id"]
+    DecidedById,
+    #[iden = "decided_at"]
+    DecidedAt,
+    Comment,
+}
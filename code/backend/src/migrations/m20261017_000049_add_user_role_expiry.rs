@@ -0,0 +1,66 @@
+//! Migration: Add an optional expiry to role assignments
+//!
+//! A role assignment with `expires_at` stops granting anything at that time
+//! and is removed by the role expiry task. `expiry_notified` records that
+//! the user and admins were told about the upcoming expiry.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserRoles::Table)
+                    .add_column(
+                        ColumnDef::new(UserRoles::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserRoles::Table)
+                    .add_column(
+                        ColumnDef::new(UserRoles::ExpiryNotified)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [UserRoles::ExpiresAt, UserRoles::ExpiryNotified] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(UserRoles::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "user_roles"]
+enum UserRoles {
+    Table,
+    #[iden = "expires_at"]
+    ExpiresAt,
+    #[iden = "expiry_notified"]
+    ExpiryNotified,
+}
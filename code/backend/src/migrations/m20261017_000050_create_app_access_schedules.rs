@@ -0,0 +1,213 @@
+//! Migration: Create app access schedule tables
+//!
+//! A schedule limits when a user or the members of a role may reach an app
+//! to time windows on given days. An override lets one user past the
+//! schedules of an app until it expires. Windows are read in the user's time
+//! zone, stored with their preferences.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+use super::m20260127_000002_create_roles::Roles;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppAccessSchedules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::UserId)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::RoleId)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::AppName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AppAccessSchedules::Days).text().not_null())
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::StartTime)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::EndTime)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessSchedules::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppAccessSchedules::Table, AppAccessSchedules::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppAccessSchedules::Table, AppAccessSchedules::RoleId)
+                            .to(Roles::Table, Roles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppAccessOverrides::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::AppName)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AppAccessOverrides::Reason).text().null())
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::GrantedBy)
+                            .big_integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccessOverrides::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppAccessOverrides::Table, AppAccessOverrides::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppAccessOverrides::Table, AppAccessOverrides::GrantedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .add_column(ColumnDef::new(UserPreferences::Timezone).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPreferences::Table)
+                    .drop_column(UserPreferences::Timezone)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppAccessOverrides::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppAccessSchedules::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "app_access_schedules"]
+enum AppAccessSchedules {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "role_id"]
+    RoleId,
+    #[iden = "app_name"]
+    AppName,
+    Days,
+    #[iden = "start_time"]
+    StartTime,
+    #[iden = "end_time"]
+    EndTime,
+    #[iden = "created_at"]
+    CreatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "app_access_overrides"]
+enum AppAccessOverrides {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "app_name"]
+    AppName,
+    Reason,
+    #[iden = "granted_by"]
+    GrantedBy,
+    #[iden = "expires_at"]
+    ExpiresAt,
+    #[iden = "created_at"]
+    CreatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "user_preferences"]
+enum UserPreferences {
+    Table,
+    Timezone,
+}
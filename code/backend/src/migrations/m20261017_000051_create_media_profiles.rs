@@ -0,0 +1,91 @@
+//! Migration: Create media_profiles table
+//!
+//! A media profile says what the members of a role may see in the media
+//! server: the libraries, how many streams at once and the highest parental
+//! rating. Kubarr writes it into the members' Jellyfin user policies.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000002_create_roles::Roles;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MediaProfiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MediaProfiles::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaProfiles::RoleId)
+                            .big_integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(MediaProfiles::Libraries).text().not_null())
+                    .col(ColumnDef::new(MediaProfiles::MaxStreams).integer().null())
+                    .col(
+                        ColumnDef::new(MediaProfiles::MaxParentalRating)
+                            .integer()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaProfiles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MediaProfiles::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MediaProfiles::Table, MediaProfiles::RoleId)
+                            .to(Roles::Table, Roles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(MediaProfiles::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "media_profiles"]
+enum MediaProfiles {
+    Table,
+    Id,
+    #[iden = "role_id"]
+    RoleId,
+    Libraries,
+    #[iden = "max_streams"]
+    MaxStreams,
+    #[iden = "max_parental_rating"]
+    MaxParentalRating,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
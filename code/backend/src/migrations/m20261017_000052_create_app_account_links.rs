@@ -0,0 +1,208 @@
+//! Migration: Create app_account_links table
+//!
+//! Maps Kubarr users to their accounts in managed apps (a Jellyfin user ID,
+//! a qBittorrent username, a Jellyseerr user ID) so per-user features can
+//! find them. Replaces media_account_links, which only held request manager
+//! accounts with numeric IDs; its links are carried over.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+use super::m20261016_000002_create_media_account_links as media_account_links;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AppAccountLinks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AppAccountLinks::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccountLinks::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AppAccountLinks::AppName).string().not_null())
+                    .col(
+                        ColumnDef::new(AppAccountLinks::ExternalId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccountLinks::ExternalUsername)
+                            .string()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(AppAccountLinks::Source).string().not_null())
+                    .col(
+                        ColumnDef::new(AppAccountLinks::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AppAccountLinks::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AppAccountLinks::Table, AppAccountLinks::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_app_account_links_user_app")
+                    .table(AppAccountLinks::Table)
+                    .col(AppAccountLinks::UserId)
+                    .col(AppAccountLinks::AppName)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_app_account_links_app_external")
+                    .table(AppAccountLinks::Table)
+                    .col(AppAccountLinks::AppName)
+                    .col(AppAccountLinks::ExternalId)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Carry over the request manager links
+        if manager.has_table("media_account_links").await? {
+            let insert = Query::insert()
+                .into_table(AppAccountLinks::Table)
+                .columns([
+                    AppAccountLinks::UserId,
+                    AppAccountLinks::AppName,
+                    AppAccountLinks::ExternalId,
+                    AppAccountLinks::ExternalUsername,
+                    AppAccountLinks::Source,
+                    AppAccountLinks::CreatedAt,
+                    AppAccountLinks::UpdatedAt,
+                ])
+                .select_from(
+                    Query::select()
+                        .column(MediaAccountLinks::UserId)
+                        .column(MediaAccountLinks::AppName)
+                        .expr(
+                            Expr::col(MediaAccountLinks::ExternalUserId)
+                                .cast_as(Alias::new("text")),
+                        )
+                        .column(MediaAccountLinks::ExternalUsername)
+                        .expr(Expr::val("manual"))
+                        .column(MediaAccountLinks::CreatedAt)
+                        .column(MediaAccountLinks::CreatedAt)
+                        .from(MediaAccountLinks::Table)
+                        .to_owned(),
+                )
+                .map_err(|e| DbErr::Custom(e.to_string()))?
+                .to_owned();
+            manager.exec_stmt(insert).await?;
+
+            manager
+                .drop_table(Table::drop().table(MediaAccountLinks::Table).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        media_account_links::Migration.up(manager).await?;
+
+        let insert = Query::insert()
+            .into_table(MediaAccountLinks::Table)
+            .columns([
+                MediaAccountLinks::UserId,
+                MediaAccountLinks::AppName,
+                MediaAccountLinks::ExternalUserId,
+                MediaAccountLinks::ExternalUsername,
+                MediaAccountLinks::CreatedAt,
+            ])
+            .select_from(
+                Query::select()
+                    .column(AppAccountLinks::UserId)
+                    .column(AppAccountLinks::AppName)
+                    .expr(Expr::col(AppAccountLinks::ExternalId).cast_as(Alias::new("bigint")))
+                    .column(AppAccountLinks::ExternalUsername)
+                    .column(AppAccountLinks::CreatedAt)
+                    .from(AppAccountLinks::Table)
+                    .and_where(
+                        Expr::col(AppAccountLinks::AppName).is_in(["jellyseerr", "overseerr"]),
+                    )
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Custom(e.to_string()))?
+            .to_owned();
+        manager.exec_stmt(insert).await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AppAccountLinks::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "app_account_links"]
+enum AppAccountLinks {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "external_id"]
+    ExternalId,
+    #[iden = "external_username"]
+    ExternalUsername,
+    Source,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+#[iden = "media_account_links"]
+enum MediaAccountLinks {
+    Table,
+    #[iden = "user_id"]
+    UserId,
+    #[iden = "app_name"]
+    AppName,
+    #[iden = "external_user_id"]
+    ExternalUserId,
+    #[iden = "external_username"]
+    ExternalUsername,
+    #[iden = "created_at"]
+    CreatedAt,
+}
@@ -0,0 +1,81 @@
+//! Migration: Create provisioning_profiles table
+//!
+//! A provisioning profile lists the apps in which members of a role get an
+//! account when an admin approves them. Kubarr creates the accounts through
+//! the app integrations and links them to the user.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000002_create_roles::Roles;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProvisioningProfiles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProvisioningProfiles::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProvisioningProfiles::RoleId)
+                            .big_integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ProvisioningProfiles::Apps).text().not_null())
+                    .col(
+                        ColumnDef::new(ProvisioningProfiles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProvisioningProfiles::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProvisioningProfiles::Table, ProvisioningProfiles::RoleId)
+                            .to(Roles::Table, Roles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ProvisioningProfiles::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "provisioning_profiles"]
+enum ProvisioningProfiles {
+    Table,
+    Id,
+    #[iden = "role_id"]
+    RoleId,
+    Apps,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
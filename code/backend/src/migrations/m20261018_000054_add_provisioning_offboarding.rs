@@ -0,0 +1,47 @@
+//! Migration: Add the deactivation action to provisioning profiles
+//!
+//! `on_deactivate` says what happens to a member's linked app accounts when
+//! the member is deactivated or deleted: "keep", "disable" or "delete".
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProvisioningProfiles::Table)
+                    .add_column(
+                        ColumnDef::new(ProvisioningProfiles::OnDeactivate)
+                            .string()
+                            .not_null()
+                            .default("keep"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProvisioningProfiles::Table)
+                    .drop_column(ProvisioningProfiles::OnDeactivate)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "provisioning_profiles"]
+enum ProvisioningProfiles {
+    Table,
+    #[iden = "on_deactivate"]
+    OnDeactivate,
+}
@@ -0,0 +1,84 @@
+//! Migration: Create registries table
+//!
+//! A registry entry tells Kubarr how to pull images from one container
+//! registry: through a mirror, and with which credentials. Deployments
+//! rewrite chart images to the mirror and get a pull secret with the
+//! credentials.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Registries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Registries::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Registries::Host)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Registries::Mirror).string().null())
+                    .col(ColumnDef::new(Registries::Username).string().null())
+                    .col(ColumnDef::new(Registries::Password).text().null())
+                    .col(
+                        ColumnDef::new(Registries::Insecure)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Registries::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Registries::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(Registries::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "registries"]
+enum Registries {
+    Table,
+    Id,
+    Host,
+    Mirror,
+    Username,
+    Password,
+    Insecure,
+    #[iden = "created_at"]
+    CreatedAt,
+    #[iden = "updated_at"]
+    UpdatedAt,
+}
@@ -0,0 +1,97 @@
+//! Migration: Create trusted_certificates table
+//!
+//! CA certificates uploaded by admins, which Kubarr's outbound HTTP clients
+//! trust on top of the system roots. Validity and names are stored next to
+//! the PEM so expiring certificates can be found with a query.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrustedCertificates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TrustedCertificates::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::Name)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::Subject)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::Issuer)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::Fingerprint)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(TrustedCertificates::Pem).text().not_null())
+                    .col(
+                        ColumnDef::new(TrustedCertificates::NotBefore)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::NotAfter)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrustedCertificates::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(TrustedCertificates::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "trusted_certificates"]
+enum TrustedCertificates {
+    Table,
+    Id,
+    Name,
+    Subject,
+    Issuer,
+    Fingerprint,
+    Pem,
+    #[iden = "not_before"]
+    NotBefore,
+    #[iden = "not_after"]
+    NotAfter,
+    #[iden = "created_at"]
+    CreatedAt,
+}
@@ -0,0 +1,47 @@
+//! Migration: Add a TLS verification toggle to app integrations
+//!
+//! Apps reached over HTTPS with a self-signed certificate can have
+//! verification turned off per integration. It stays on by default.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AppIntegrations::Table)
+                    .add_column(
+                        ColumnDef::new(AppIntegrations::TlsVerify)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AppIntegrations::Table)
+                    .drop_column(AppIntegrations::TlsVerify)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "app_integrations"]
+enum AppIntegrations {
+    Table,
+    #[iden = "tls_verify"]
+    TlsVerify,
+}
@@ -0,0 +1,100 @@
+//! Migration: Create client_certificates table
+//!
+//! Client certificates registered for users, identified by the SHA-256
+//! fingerprint of their DER encoding. A request whose verified client
+//! certificate matches one is authenticated as that user.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClientCertificates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ClientCertificates::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientCertificates::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ClientCertificates::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(ClientCertificates::Subject)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientCertificates::Fingerprint)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientCertificates::NotAfter)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientCertificates::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientCertificates::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClientCertificates::Table, ClientCertificates::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ClientCertificates::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "client_certificates"]
+enum ClientCertificates {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    Name,
+    Subject,
+    Fingerprint,
+    #[iden = "not_after"]
+    NotAfter,
+    #[iden = "last_used_at"]
+    LastUsedAt,
+    #[iden = "created_at"]
+    CreatedAt,
+}
@@ -0,0 +1,67 @@
+//! Migration: Add indexes for authentication analytics to audit_logs table
+//!
+//! Sign-in analytics count entries of a few actions over a time range and
+//! group failures by address; these indexes keep that fast on large logs.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_action_timestamp")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::Action)
+                    .col(AuditLogs::Timestamp)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_ip_address")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::IpAddress)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_logs_ip_address")
+                    .table(AuditLogs::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_logs_action_timestamp")
+                    .table(AuditLogs::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "audit_logs"]
+enum AuditLogs {
+    Table,
+    Action,
+    Timestamp,
+    #[iden = "ip_address"]
+    IpAddress,
+}
@@ -0,0 +1,72 @@
+//! Migration: Add refresh token columns to sessions table
+//!
+//! Each session holds the hash of its current refresh token and of the one
+//! it replaced, so a refresh token presented twice can be told apart from a
+//! request racing the rotation.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only allows one column per ALTER TABLE statement
+        for column in [
+            Sessions::RefreshTokenHash,
+            Sessions::PreviousRefreshTokenHash,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Sessions::Table)
+                        .add_column(ColumnDef::new(column).string().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Sessions::Table)
+                    .add_column(
+                        ColumnDef::new(Sessions::RefreshedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Sessions::RefreshTokenHash,
+            Sessions::PreviousRefreshTokenHash,
+            Sessions::RefreshedAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Sessions::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[iden = "sessions"]
+enum Sessions {
+    Table,
+    #[iden = "refresh_token_hash"]
+    RefreshTokenHash,
+    #[iden = "previous_refresh_token_hash"]
+    PreviousRefreshTokenHash,
+    #[iden = "refreshed_at"]
+    RefreshedAt,
+}
@@ -0,0 +1,136 @@
+//! Migration: Create service accounts
+//!
+//! Marks users that are service accounts, which cannot sign in with a
+//! password, and creates the table of their API tokens. Tokens are stored as
+//! SHA-256 hashes.
+
+use sea_orm_migration::prelude::*;
+
+use super::m20260127_000001_create_users::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServiceAccountUsers::Table)
+                    .add_column(
+                        ColumnDef::new(ServiceAccountUsers::IsServiceAccount)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ServiceAccountTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::UserId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::Name)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::TokenHash)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::TokenPrefix)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::LastUsedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccountTokens::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ServiceAccountTokens::Table, ServiceAccountTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ServiceAccountTokens::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServiceAccountUsers::Table)
+                    .drop_column(ServiceAccountUsers::IsServiceAccount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[iden = "users"]
+enum ServiceAccountUsers {
+    Table,
+    #[iden = "is_service_account"]
+    IsServiceAccount,
+}
+
+#[derive(Iden)]
+#[iden = "service_account_tokens"]
+enum ServiceAccountTokens {
+    Table,
+    Id,
+    #[iden = "user_id"]
+    UserId,
+    Name,
+    #[iden = "token_hash"]
+    TokenHash,
+    #[iden = "token_prefix"]
+    TokenPrefix,
+    #[iden = "expires_at"]
+    ExpiresAt,
+    #[iden = "last_used_at"]
+    LastUsedAt,
+    #[iden = "created_at"]
+    CreatedAt,
+}
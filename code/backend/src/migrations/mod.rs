@@ -26,6 +26,71 @@ mod m20260131_000001_add_port_forwarding;
 mod m20260219_000001_create_two_factor_recovery_codes;
 mod m20260221_000001_create_cloudflare_tunnels;
 mod m20260221_000002_add_cloudflare_api_fields;
+mod m20261016_000001_create_app_integrations;
+mod m20261016_000002_create_media_account_links;
+mod m20261016_000003_grant_requests_manage;
+mod m20261016_000004_create_network_usage;
+mod m20261016_000005_create_wan_health_checks;
+mod m20261016_000006_create_uptime_monitors;
+mod m20261016_000007_grant_monitoring_manage;
+mod m20261016_000008_create_pod_restart_events;
+mod m20261016_000009_create_dashboards;
+mod m20261016_000010_create_metric_anomalies;
+mod m20261016_000011_create_energy_usage;
+mod m20261016_000012_create_log_alert_rules;
+mod m20261016_000013_create_alerts;
+mod m20261016_000014_create_notification_routes;
+mod m20261016_000015_add_audit_request_id;
+mod m20261016_000016_add_notification_channel_filters;
+mod m20261016_000017_add_notification_log_fallback;
+mod m20261016_000018_add_notification_pref_verification;
+mod m20261016_000019_add_audit_integrity_chain;
+mod m20261016_000020_create_installed_apps;
+mod m20261016_000021_add_installed_app_clone_source;
+mod m20261016_000022_add_installed_app_routing;
+mod m20261016_000023_add_installed_app_stopped_replicas;
+mod m20261016_000024_grant_apps_stop;
+mod m20261016_000025_add_installed_app_idle_suspend;
+mod m20261016_000026_add_installed_app_boot_order;
+mod m20261017_000027_grant_system_manage;
+mod m20261017_000028_create_kubarr_updates;
+mod m20261017_000029_create_report_subscriptions;
+mod m20261017_000030_create_file_checksums;
+mod m20261017_000031_create_storage_shares;
+mod m20261017_000032_create_log_archives;
+mod m20261017_000033_create_app_install_requests;
+mod m20261017_000034_grant_apps_request;
+mod m20261017_000035_create_resource_quotas;
+mod m20261017_000036_add_installed_app_footprint;
+mod m20261017_000037_create_storage_homes;
+mod m20261017_000038_create_tenants;
+mod m20261017_000039_add_installed_app_tenant;
+mod m20261017_000040_grant_tenants_manage;
+mod m20261017_000041_create_notification_templates;
+mod m20261017_000042_add_user_preference_locale;
+mod m20261017_000043_create_invite_batches;
+mod m20261017_000044_add_invite_batch;
+mod m20261017_000045_add_user_preference_login_alerts;
+mod m20261017_000046_create_ip_bans;
+mod m20261017_000047_grant_security_manage;
+mod m20261017_000048_create_access_reviews;
+mod m20261017_000049_add_user_role_expiry;
+mod m20261017_000050_create_app_access_schedules;
+mod m20261017_000051_create_media_profiles;
+mod m20261017_000052_create_app_account_links;
+mod m20261017_000053_create_provisioning_profiles;
+mod m20261018_000054_add_provisioning_offboarding;
+mod m20261018_000055_create_registries;
+mod m20261018_000056_create_trusted_certificates;
+mod m20261018_000057_add_integration_tls_verify;
+mod m20261018_000058_add_installed_app_chart_version;
+mod m20261018_000059_create_client_certificates;
+mod m20261018_000060_add_installed_app_value_overrides;
+mod m20261018_000061_add_user_lockout;
+mod m20261018_000062_add_audit_auth_indexes;
+mod m20261018_000063_add_session_refresh_tokens;
+mod m20261018_000064_create_service_accounts;
+mod m20261018_000065_grant_apps_exec;
 
 pub struct Migrator;
 
@@ -59,6 +124,71 @@ impl MigratorTrait for Migrator {
             Box::new(m20260219_000001_create_two_factor_recovery_codes::Migration),
             Box::new(m20260221_000001_create_cloudflare_tunnels::Migration),
             Box::new(m20260221_000002_add_cloudflare_api_fields::Migration),
+            Box::new(m20261016_000001_create_app_integrations::Migration),
+            Box::new(m20261016_000002_create_media_account_links::Migration),
+            Box::new(m20261016_000003_grant_requests_manage::Migration),
+            Box::new(m20261016_000004_create_network_usage::Migration),
+            Box::new(m20261016_000005_create_wan_health_checks::Migration),
+            Box::new(m20261016_000006_create_uptime_monitors::Migration),
+            Box::new(m20261016_000007_grant_monitoring_manage::Migration),
+            Box::new(m20261016_000008_create_pod_restart_events::Migration),
+            Box::new(m20261016_000009_create_dashboards::Migration),
+            Box::new(m20261016_000010_create_metric_anomalies::Migration),
+            Box::new(m20261016_000011_create_energy_usage::Migration),
+            Box::new(m20261016_000012_create_log_alert_rules::Migration),
+            Box::new(m20261016_000013_create_alerts::Migration),
+            Box::new(m20261016_000014_create_notification_routes::Migration),
+            Box::new(m20261016_000015_add_audit_request_id::Migration),
+            Box::new(m20261016_000016_add_notification_channel_filters::Migration),
+            Box::new(m20261016_000017_add_notification_log_fallback::Migration),
+            Box::new(m20261016_000018_add_notification_pref_verification::Migration),
+            Box::new(m20261016_000019_add_audit_integrity_chain::Migration),
+            Box::new(m20261016_000020_create_installed_apps::Migration),
+            Box::new(m20261016_000021_add_installed_app_clone_source::Migration),
+            Box::new(m20261016_000022_add_installed_app_routing::Migration),
+            Box::new(m20261016_000023_add_installed_app_stopped_replicas::Migration),
+            Box::new(m20261016_000024_grant_apps_stop::Migration),
+            Box::new(m20261016_000025_add_installed_app_idle_suspend::Migration),
+            Box::new(m20261016_000026_add_installed_app_boot_order::Migration),
+            Box::new(m20261017_000027_grant_system_manage::Migration),
+            Box::new(m20261017_000028_create_kubarr_updates::Migration),
+            Box::new(m20261017_000029_create_report_subscriptions::Migration),
+            Box::new(m20261017_000030_create_file_checksums::Migration),
+            Box::new(m20261017_000031_create_storage_shares::Migration),
+            Box::new(m20261017_000032_create_log_archives::Migration),
+            Box::new(m20261017_000033_create_app_install_requests::Migration),
+            Box::new(m20261017_000034_grant_apps_request::Migration),
+            Box::new(m20261017_000035_create_resource_quotas::Migration),
+            Box::new(m20261017_000036_add_installed_app_footprint::Migration),
+            Box::new(m20261017_000037_create_storage_homes::Migration),
+            Box::new(m20261017_000038_create_tenants::Migration),
+            Box::new(m20261017_000039_add_installed_app_tenant::Migration),
+            Box::new(m20261017_000040_grant_tenants_manage::Migration),
+            Box::new(m20261017_000041_create_notification_templates::Migration),
+            Box::new(m20261017_000042_add_user_preference_locale::Migration),
+            Box::new(m20261017_000043_create_invite_batches::Migration),
+            Box::new(m20261017_000044_add_invite_batch::Migration),
+            Box::new(m20261017_000045_add_user_preference_login_alerts::Migration),
+            Box::new(m20261017_000046_create_ip_bans::Migration),
+            Box::new(m20261017_000047_grant_security_manage::Migration),
+            Box::new(m20261017_000048_create_access_reviews::Migration),
+            Box::new(m20261017_000049_add_user_role_expiry::Migration),
+            Box::new(m20261017_000050_create_app_access_schedules::Migration),
+            Box::new(m20261017_000051_create_media_profiles::Migration),
+            Box::new(m20261017_000052_create_app_account_links::Migration),
+            Box::new(m20261017_000053_create_provisioning_profiles::Migration),
+            Box::new(m20261018_000054_add_provisioning_offboarding::Migration),
+            Box::new(m20261018_000055_create_registries::Migration),
+            Box::new(m20261018_000056_create_trusted_certificates::Migration),
+            Box::new(m20261018_000057_add_integration_tls_verify::Migration),
+            Box::new(m20261018_000058_add_installed_app_chart_version::Migration),
+            Box::new(m20261018_000059_create_client_certificates::Migration),
+            Box::new(m20261018_000060_add_installed_app_value_overrides::Migration),
+            Box::new(m20261018_000061_add_user_lockout::Migration),
+            Box::new(m20261018_000062_add_audit_auth_indexes::Migration),
+            Box::new(m20261018_000063_add_session_refresh_tokens::Migration),
+            Box::new(m20261018_000064_create_service_accounts::Migration),
+            Box::new(m20261018_000065_grant_apps_exec::Migration),
         ]
     }
 }
@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "access_reviews")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// `open` until every user is decided or the review is past due
+    pub status: String,
+    /// Admin who started the review; `None` for scheduled reviews
+    pub created_by_id: Option<i64>,
+    pub created_at: DateTimeUtc,
+    pub due_at: DateTimeUtc,
+    pub closed_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::access_review_item::Entity")]
+    Items,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::CreatedById",
+        to = "super::user::Column::Id",
+        on_delete = "SetNull"
+    )]
+    CreatedBy,
+}
+
+impl Related<super::access_review_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Items.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
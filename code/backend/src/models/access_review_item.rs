@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "access_review_items")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub review_id: i64,
+    pub user_id: i64,
+    /// JSON array of the user's role names when the review started
+    pub roles: String,
+    /// JSON array of the apps the user could open, `["*"]` for all
+    pub apps: String,
+    /// `pending`, `confirmed`, `revoked` or `overdue`
+    pub decision: String,
+    /// Whether the user was deactivated for missing the deadline
+    pub suspended: bool,
+    pub decided_by_id: Option<i64>,
+    pub decided_at: Option<DateTimeUtc>,
+    pub comment: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::access_review::Entity",
+        from = "Column::ReviewId",
+        to = "super::access_review::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Review,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::access_review::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Review.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "alerts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Identifies the condition across checks, e.g. "uptime:3"
+    pub fingerprint: String,
+    /// "metric", "log", "uptime", "wan" or "sensor"
+    pub source: String,
+    pub name: String,
+    pub app_name: Option<String>,
+    /// "info", "warning" or "critical"
+    pub severity: String,
+    pub message: String,
+    /// UI link to the relevant page
+    pub link: String,
+    /// "firing", "acked", "silenced" or "resolved"
+    pub state: String,
+    pub started_at: DateTimeUtc,
+    /// Last time the checker reported the condition
+    pub last_seen_at: DateTimeUtc,
+    pub resolved_at: Option<DateTimeUtc>,
+    pub acked_by: Option<i64>,
+    pub acked_at: Option<DateTimeUtc>,
+    /// Notifications are suppressed until this time
+    pub silenced_until: Option<DateTimeUtc>,
+    pub silenced_by: Option<i64>,
+    pub assigned_to: Option<i64>,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::alert_event::Entity")]
+    AlertEvent,
+}
+
+impl Related<super::alert_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AlertEvent.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "alert_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub alert_id: i64,
+    /// "fired", "acked", "silenced", "unsilenced", "silence_expired",
+    /// "assigned", "unassigned" or "resolved"
+    pub action: String,
+    /// User who took the action (None for changes made by the checkers)
+    pub user_id: Option<i64>,
+    pub detail: Option<String>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::alert::Entity",
+        from = "Column::AlertId",
+        to = "super::alert::Column::Id"
+    )]
+    Alert,
+}
+
+impl Related<super::alert::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alert.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "app_access_schedules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Set for a user's own schedule, which overrides their roles' schedules
+    pub user_id: Option<i64>,
+    /// Set for a role schedule, limiting the access the role grants
+    pub role_id: Option<i64>,
+    pub app_name: String,
+    /// JSON array of weekdays ("mon" .. "sun") the window opens on
+    pub days: String,
+    /// Start of the window, "HH:MM" in the user's time zone
+    pub start_time: String,
+    /// End of the window, "HH:MM"; may be before the start to wrap past
+    /// midnight
+    pub end_time: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::role::Entity",
+        from = "Column::RoleId",
+        to = "super::role::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Role,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
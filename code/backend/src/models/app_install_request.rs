@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "app_install_requests")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Catalog app to install
+    pub app_name: String,
+    /// Install options as a JSON object of string values
+    #[sea_orm(column_type = "Text")]
+    pub custom_config: String,
+    pub requested_by: i64,
+    /// Why the requester wants the app
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
+    /// `pending`, `approved` or `denied`
+    pub status: String,
+    pub reviewed_by: Option<i64>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub review_comment: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub reviewed_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::RequestedBy",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Requester,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
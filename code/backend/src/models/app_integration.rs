@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "app_integrations")]
+pub struct Model {
+    /// App identifier (e.g., "sonarr", "qbittorrent")
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub app_name: String,
+    /// JSON blob containing the app's API key and/or username/password
+    #[serde(skip_serializing)]
+    pub credentials_json: String,
+    /// Override for the app's API root (None = resolve from the K8s service)
+    pub base_url: Option<String>,
+    pub enabled: bool,
+    /// Whether the app's HTTPS certificate is verified
+    pub tls_verify: bool,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
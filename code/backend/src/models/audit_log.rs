@@ -18,6 +18,14 @@ pub struct Model {
     pub user_agent: Option<String>,
     pub success: bool,
     pub error_message: Option<String>,
+    /// ID of the HTTP request that produced the entry
+    pub request_id: Option<String>,
+    /// Hash of the preceding chained entry (integrity mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// HMAC over `prev_hash` and this entry's contents (integrity mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity_hash: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -26,7 +34,7 @@ pub enum Relation {}
 impl ActiveModelBehavior for ActiveModel {}
 
 // Audit action types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, EnumIter)]
 pub enum AuditAction {
     // Authentication
     Login,
@@ -38,6 +46,13 @@ pub enum AuditAction {
     TwoFactorVerified,
     TwoFactorFailed,
     PasswordChanged,
+    NewDeviceLogin,
+    SessionRevoked,
+    IpBanned,
+    IpUnbanned,
+    AccessDenied,
+    AccountLocked,
+    RefreshTokenReused,
 
     // User management
     UserCreated,
@@ -53,6 +68,12 @@ pub enum AuditAction {
     RoleDeleted,
     RoleAssigned,
     RoleUnassigned,
+    RoleExpiring,
+    AccessReviewStarted,
+    AccessConfirmed,
+    AccessRevoked,
+    ScheduleOverrideGranted,
+    ScheduleOverrideRevoked,
 
     // App management
     AppInstalled,
@@ -62,12 +83,47 @@ pub enum AuditAction {
     AppRestarted,
     AppConfigured,
     AppAccessed,
+    AppsSuspended,
+    AppInstallRequested,
+    AppInstallDenied,
+    AppUpgraded,
+    AppShellOpened,
+    AppShellClosed,
+
+    // Media requests
+    MediaRequested,
+    MediaRequestApproved,
+    MediaRequestDeclined,
+
+    // Network
+    WanDegraded,
+    WanRecovered,
+    UptimeMonitorDown,
+    UptimeMonitorUp,
+
+    // Monitoring
+    MetricAnomaly,
+    NodeTemperatureCritical,
+    LogAlertFiring,
+    LogAlertResolved,
+    AlertAssigned,
+
+    // Storage
+    StorageQuotaWarning,
+    StorageQuotaExceeded,
 
     // System
     SystemSettingChanged,
     InviteCreated,
     InviteUsed,
     InviteDeleted,
+    UpdateAvailable,
+    UpdateStarted,
+    UpdateCompleted,
+    UpdateFailed,
+    BackupCreated,
+    BackupRestored,
+    BackupFailed,
 
     // API access
     ApiAccess,
@@ -85,6 +141,13 @@ impl std::fmt::Display for AuditAction {
             AuditAction::TwoFactorVerified => write!(f, "2fa_verified"),
             AuditAction::TwoFactorFailed => write!(f, "2fa_failed"),
             AuditAction::PasswordChanged => write!(f, "password_changed"),
+            AuditAction::NewDeviceLogin => write!(f, "new_device_login"),
+            AuditAction::SessionRevoked => write!(f, "session_revoked"),
+            AuditAction::IpBanned => write!(f, "ip_banned"),
+            AuditAction::IpUnbanned => write!(f, "ip_unbanned"),
+            AuditAction::AccessDenied => write!(f, "access_denied"),
+            AuditAction::AccountLocked => write!(f, "account_locked"),
+            AuditAction::RefreshTokenReused => write!(f, "refresh_token_reused"),
             AuditAction::UserCreated => write!(f, "user_created"),
             AuditAction::UserUpdated => write!(f, "user_updated"),
             AuditAction::UserDeleted => write!(f, "user_deleted"),
@@ -96,6 +159,12 @@ impl std::fmt::Display for AuditAction {
             AuditAction::RoleDeleted => write!(f, "role_deleted"),
             AuditAction::RoleAssigned => write!(f, "role_assigned"),
             AuditAction::RoleUnassigned => write!(f, "role_unassigned"),
+            AuditAction::RoleExpiring => write!(f, "role_expiring"),
+            AuditAction::AccessReviewStarted => write!(f, "access_review_started"),
+            AuditAction::AccessConfirmed => write!(f, "access_confirmed"),
+            AuditAction::AccessRevoked => write!(f, "access_revoked"),
+            AuditAction::ScheduleOverrideGranted => write!(f, "schedule_override_granted"),
+            AuditAction::ScheduleOverrideRevoked => write!(f, "schedule_override_revoked"),
             AuditAction::AppInstalled => write!(f, "app_installed"),
             AuditAction::AppUninstalled => write!(f, "app_uninstalled"),
             AuditAction::AppStarted => write!(f, "app_started"),
@@ -103,10 +172,37 @@ impl std::fmt::Display for AuditAction {
             AuditAction::AppRestarted => write!(f, "app_restarted"),
             AuditAction::AppConfigured => write!(f, "app_configured"),
             AuditAction::AppAccessed => write!(f, "app_accessed"),
+            AuditAction::AppsSuspended => write!(f, "apps_suspended"),
+            AuditAction::AppInstallRequested => write!(f, "app_install_requested"),
+            AuditAction::AppInstallDenied => write!(f, "app_install_denied"),
+            AuditAction::AppUpgraded => write!(f, "app_upgraded"),
+            AuditAction::AppShellOpened => write!(f, "app_shell_opened"),
+            AuditAction::AppShellClosed => write!(f, "app_shell_closed"),
+            AuditAction::MediaRequested => write!(f, "media_requested"),
+            AuditAction::MediaRequestApproved => write!(f, "media_request_approved"),
+            AuditAction::MediaRequestDeclined => write!(f, "media_request_declined"),
+            AuditAction::WanDegraded => write!(f, "wan_degraded"),
+            AuditAction::WanRecovered => write!(f, "wan_recovered"),
+            AuditAction::UptimeMonitorDown => write!(f, "uptime_monitor_down"),
+            AuditAction::UptimeMonitorUp => write!(f, "uptime_monitor_up"),
+            AuditAction::MetricAnomaly => write!(f, "metric_anomaly"),
+            AuditAction::NodeTemperatureCritical => write!(f, "node_temperature_critical"),
+            AuditAction::LogAlertFiring => write!(f, "log_alert_firing"),
+            AuditAction::LogAlertResolved => write!(f, "log_alert_resolved"),
+            AuditAction::AlertAssigned => write!(f, "alert_assigned"),
+            AuditAction::StorageQuotaWarning => write!(f, "storage_quota_warning"),
+            AuditAction::StorageQuotaExceeded => write!(f, "storage_quota_exceeded"),
             AuditAction::SystemSettingChanged => write!(f, "system_setting_changed"),
             AuditAction::InviteCreated => write!(f, "invite_created"),
             AuditAction::InviteUsed => write!(f, "invite_used"),
             AuditAction::InviteDeleted => write!(f, "invite_deleted"),
+            AuditAction::UpdateAvailable => write!(f, "update_available"),
+            AuditAction::UpdateStarted => write!(f, "update_started"),
+            AuditAction::UpdateCompleted => write!(f, "update_completed"),
+            AuditAction::UpdateFailed => write!(f, "update_failed"),
+            AuditAction::BackupCreated => write!(f, "backup_created"),
+            AuditAction::BackupRestored => write!(f, "backup_restored"),
+            AuditAction::BackupFailed => write!(f, "backup_failed"),
             AuditAction::ApiAccess => write!(f, "api_access"),
         }
     }
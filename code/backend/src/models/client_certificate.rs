@@ -0,0 +1,40 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "client_certificates")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// User a request presenting the certificate is authenticated as
+    pub user_id: i64,
+    /// Label shown in the list, e.g. `backup script`
+    pub name: String,
+    /// e.g. `CN=backup, O=Homelab`
+    pub subject: String,
+    /// SHA-256 of the DER encoding, hex
+    #[sea_orm(unique)]
+    pub fingerprint: String,
+    pub not_after: DateTimeUtc,
+    pub last_used_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
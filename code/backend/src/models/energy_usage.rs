@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "energy_usage")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// App namespace the energy was attributed to ("system" for the rest)
+    pub app_name: String,
+    /// Rollup granularity: "day" or "month"
+    pub period: String,
+    /// Period key: "YYYY-MM-DD" for days, "YYYY-MM" for months (UTC)
+    pub period_start: String,
+    /// Estimated energy in watt-hours
+    pub energy_wh: f64,
+    /// Estimated cost at the electricity price in effect when sampled
+    pub cost: f64,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
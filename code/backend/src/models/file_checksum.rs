@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "file_checksums")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Storage root the file is in
+    pub root: String,
+    /// Path of the file relative to its root
+    pub path: String,
+    /// "sha256" or "blake3"
+    pub algorithm: String,
+    /// "pending", "running", "completed" or "failed"
+    pub status: String,
+    /// Lowercase hex digest, once completed
+    pub checksum: Option<String>,
+    /// File size when the checksum was requested
+    pub size: i64,
+    /// File modification time when the checksum was requested
+    pub file_modified_at: DateTimeUtc,
+    pub error: Option<String>,
+    pub requested_by: Option<i64>,
+    pub created_at: DateTimeUtc,
+    pub completed_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,61 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "installed_apps")]
+pub struct Model {
+    /// App identifier (e.g., "sonarr"); Helm remains the source of truth for
+    /// whether the app is deployed
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub app_name: String,
+    /// Free-form notes entered by users
+    pub notes: Option<String>,
+    /// JSON array of tags, e.g. ["family instance", "testing"]
+    pub tags: String,
+    /// Catalog app a cloned instance was installed from; `None` for apps
+    /// installed under their catalog name
+    pub cloned_from: Option<String>,
+    /// How the proxy serves the app: "path" (`/{app_name}/`) or "subdomain"
+    pub routing_mode: String,
+    /// Subdomain label used in "subdomain" mode; defaults to the app name
+    pub subdomain: Option<String>,
+    /// JSON object of deployment name to replica count, saved while the app
+    /// is stopped (scaled to zero) so starting it restores the counts
+    pub stopped_replicas: Option<String>,
+    /// Last proxied request to the app, recorded at most every few minutes
+    pub last_accessed_at: Option<DateTimeUtc>,
+    /// Never suspend the app for being idle
+    pub idle_suspend_exempt: bool,
+    /// When the app was suspended for being idle; its next request wakes it
+    pub suspended_at: Option<DateTimeUtc>,
+    /// Apps with a lower priority are started first after a reboot
+    pub boot_priority: i32,
+    /// JSON array of apps that must be healthy before this one starts
+    pub depends_on: String,
+    /// When the app was scaled to zero to wait for its prerequisites
+    pub boot_held_at: Option<DateTimeUtc>,
+    /// User who last installed or upgraded the app; quotas count the app
+    /// against them
+    pub installed_by: Option<i64>,
+    /// CPU requested by the app's last install, in millicores
+    pub cpu_millicores: i64,
+    /// Memory requested by the app's last install, in bytes
+    pub memory_bytes: i64,
+    /// Volume sizes requested by the app's last install, in bytes
+    pub storage_bytes: i64,
+    /// Tenant the app belongs to; `None` shares it with every tenant
+    pub tenant_id: Option<i64>,
+    /// Chart version of the app's last install or upgrade; `None` until it
+    /// is read back from Helm for apps installed before it was tracked
+    pub chart_version: Option<String>,
+    /// JSON object of Helm values set on top of the chart's defaults, passed
+    /// to every install and upgrade of the app
+    pub value_overrides: Option<String>,
+    pub installed_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -14,6 +14,8 @@ pub struct Model {
     pub expires_at: Option<DateTimeUtc>,
     pub created_at: DateTimeUtc,
     pub used_at: Option<DateTimeUtc>,
+    /// Batch the invite was created in, if any
+    pub batch_id: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -24,6 +26,18 @@ pub enum Relation {
         to = "super::user::Column::Id"
     )]
     CreatedBy,
+    #[sea_orm(
+        belongs_to = "super::invite_batch::Entity",
+        from = "Column::BatchId",
+        to = "super::invite_batch::Column::Id"
+    )]
+    Batch,
+}
+
+impl Related<super::invite_batch::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Batch.def()
+    }
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "ip_bans")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(unique)]
+    pub ip_address: String,
+    /// `automatic` after too many failed sign-ins, or `manual`
+    pub source: String,
+    pub reason: Option<String>,
+    /// Failed sign-ins in the window that triggered an automatic ban
+    pub failed_attempts: Option<i32>,
+    /// Admin who added a manual ban
+    pub created_by_id: Option<i64>,
+    #[schema(value_type = String)]
+    pub created_at: DateTimeUtc,
+    /// `None` bans until the ban is removed
+    #[schema(value_type = Option<String>)]
+    pub expires_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::CreatedById",
+        to = "super::user::Column::Id",
+        on_delete = "SetNull"
+    )]
+    CreatedBy,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
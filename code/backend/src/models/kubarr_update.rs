@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kubarr_updates")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub from_version: String,
+    pub to_version: String,
+    /// "applying", "verifying", "completed", "rolled_back" or "failed"
+    pub status: String,
+    /// Helm revision to roll back to
+    pub previous_revision: i32,
+    /// JSON snapshot taken before the upgrade (release values, migrations)
+    pub backup: String,
+    pub error: Option<String>,
+    pub started_by: Option<i64>,
+    pub started_at: DateTimeUtc,
+    pub finished_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "log_alert_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub name: String,
+    /// Only count lines of this app's namespace (None = all namespaces)
+    pub app_name: Option<String>,
+    /// LogsQL filter matching the lines to count
+    pub pattern: String,
+    /// The rule fires when more than this many lines match in the window
+    pub threshold: i32,
+    pub window_minutes: i32,
+    pub interval_seconds: i32,
+    /// "info", "warning" or "critical"
+    pub severity: String,
+    pub enabled: bool,
+    /// "ok", "firing" or "unknown"
+    pub state: String,
+    /// Matching lines at the last evaluation
+    pub last_count: Option<i64>,
+    /// Why the last evaluation failed, if it did
+    pub last_error: Option<String>,
+    pub last_evaluated_at: Option<DateTimeUtc>,
+    pub firing_since: Option<DateTimeUtc>,
+    /// User notified when the rule fires or resolves
+    pub created_by: Option<i64>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
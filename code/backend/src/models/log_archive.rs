@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "log_archives")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// App (and namespace) the logs came from
+    pub app_name: String,
+    /// UTC day the archive covers, as `YYYY-MM-DD`
+    pub day: String,
+    /// Store the archive was written to: "local" or "s3"
+    pub store: String,
+    /// Key of the archive in the store; `None` when the day had no logs
+    pub object_key: Option<String>,
+    pub lines: i64,
+    /// Compressed size
+    pub bytes: i64,
+    #[schema(value_type = String)]
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "metric_anomalies")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub app_name: String,
+    /// "cpu", "memory" or "restarts"
+    pub metric: String,
+    /// "above" or "below" the baseline band
+    pub direction: String,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_stddev: f64,
+    pub z_score: f64,
+    /// Time window in which the deviation was observed
+    pub window_start: DateTimeUtc,
+    pub window_end: DateTimeUtc,
+    pub detected_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,52 +1,136 @@
+pub mod access_review;
+pub mod access_review_item;
+pub mod alert;
+pub mod alert_event;
+pub mod anomaly_sensitivity;
+pub mod app_access_override;
+pub mod app_access_schedule;
+pub mod app_account_link;
+pub mod app_install_request;
+pub mod app_integration;
 pub mod app_vpn_config;
 pub mod audit_log;
 pub mod bootstrap_status;
+pub mod client_certificate;
 pub mod cloudflare_tunnel;
+pub mod dashboard;
+pub mod energy_usage;
+pub mod file_checksum;
+pub mod installed_app;
 pub mod invite;
+pub mod invite_batch;
+pub mod ip_ban;
+pub mod kubarr_update;
+pub mod log_alert_rule;
+pub mod log_archive;
+pub mod media_profile;
+pub mod metric_anomaly;
+pub mod network_quota;
+pub mod network_usage;
+pub mod node_power_profile;
 pub mod notification_channel;
 pub mod notification_event;
 pub mod notification_log;
+pub mod notification_route;
+pub mod notification_template;
 pub mod oauth_account;
 pub mod oauth_provider;
 pub mod pending_2fa_challenge;
+pub mod pod_restart_event;
+pub mod provisioning_profile;
+pub mod registry;
+pub mod report_subscription;
+pub mod resource_quota;
 pub mod role;
 pub mod role_app_permission;
 pub mod role_permission;
 pub mod server_config;
+pub mod service_account_token;
 pub mod session;
+pub mod share_credential;
+pub mod storage_home;
+pub mod storage_share;
 pub mod system_setting;
+pub mod tenant;
+pub mod tenant_member;
+pub mod trusted_certificate;
 pub mod two_factor_recovery_code;
+pub mod uptime_check;
+pub mod uptime_monitor;
 pub mod user;
 pub mod user_notification;
 pub mod user_notification_pref;
 pub mod user_preferences;
 pub mod user_role;
 pub mod vpn_provider;
+pub mod wan_health_check;
 
 #[allow(unused_imports)]
 pub mod prelude {
+    pub use super::access_review::{self, Entity as AccessReview};
+    pub use super::access_review_item::{self, Entity as AccessReviewItem};
+    pub use super::alert::{self, Entity as Alert};
+    pub use super::alert_event::{self, Entity as AlertEvent};
+    pub use super::anomaly_sensitivity::{self, Entity as AnomalySensitivity};
+    pub use super::app_access_override::{self, Entity as AppAccessOverride};
+    pub use super::app_access_schedule::{self, Entity as AppAccessSchedule};
+    pub use super::app_account_link::{self, Entity as AppAccountLink};
+    pub use super::app_install_request::{self, Entity as AppInstallRequest};
+    pub use super::app_integration::{self, Entity as AppIntegration};
     pub use super::app_vpn_config::{self, Entity as AppVpnConfig};
     pub use super::audit_log::{self, Entity as AuditLog};
     pub use super::bootstrap_status::{self, Entity as BootstrapStatus};
+    pub use super::client_certificate::{self, Entity as ClientCertificate};
     pub use super::cloudflare_tunnel::{self, Entity as CloudflareTunnel};
+    pub use super::dashboard::{self, Entity as Dashboard};
+    pub use super::energy_usage::{self, Entity as EnergyUsage};
+    pub use super::file_checksum::{self, Entity as FileChecksum};
+    pub use super::installed_app::{self, Entity as InstalledApp};
     pub use super::invite::{self, Entity as Invite};
+    pub use super::invite_batch::{self, Entity as InviteBatch};
+    pub use super::ip_ban::{self, Entity as IpBan};
+    pub use super::kubarr_update::{self, Entity as KubarrUpdate};
+    pub use super::log_alert_rule::{self, Entity as LogAlertRule};
+    pub use super::log_archive::{self, Entity as LogArchive};
+    pub use super::media_profile::{self, Entity as MediaProfile};
+    pub use super::metric_anomaly::{self, Entity as MetricAnomaly};
+    pub use super::network_quota::{self, Entity as NetworkQuota};
+    pub use super::network_usage::{self, Entity as NetworkUsage};
+    pub use super::node_power_profile::{self, Entity as NodePowerProfile};
     pub use super::notification_channel::{self, Entity as NotificationChannel};
     pub use super::notification_event::{self, Entity as NotificationEvent};
     pub use super::notification_log::{self, Entity as NotificationLog};
+    pub use super::notification_route::{self, Entity as NotificationRoute};
+    pub use super::notification_template::{self, Entity as NotificationTemplate};
     pub use super::oauth_account::{self, Entity as OauthAccount};
     pub use super::oauth_provider::{self, Entity as OauthProvider};
     pub use super::pending_2fa_challenge::{self, Entity as Pending2faChallenge};
+    pub use super::pod_restart_event::{self, Entity as PodRestartEvent};
+    pub use super::provisioning_profile::{self, Entity as ProvisioningProfile};
+    pub use super::registry::{self, Entity as Registry};
+    pub use super::report_subscription::{self, Entity as ReportSubscription};
+    pub use super::resource_quota::{self, Entity as ResourceQuota};
     pub use super::role::{self, Entity as Role};
     pub use super::role_app_permission::{self, Entity as RoleAppPermission};
     pub use super::role_permission::{self, Entity as RolePermission};
     pub use super::server_config::{self, Entity as ServerConfig};
+    pub use super::service_account_token::{self, Entity as ServiceAccountToken};
     pub use super::session::{self, Entity as Session};
+    pub use super::share_credential::{self, Entity as ShareCredential};
+    pub use super::storage_home::{self, Entity as StorageHome};
+    pub use super::storage_share::{self, Entity as StorageShare};
     pub use super::system_setting::{self, Entity as SystemSetting};
+    pub use super::tenant::{self, Entity as Tenant};
+    pub use super::tenant_member::{self, Entity as TenantMember};
+    pub use super::trusted_certificate::{self, Entity as TrustedCertificate};
     pub use super::two_factor_recovery_code::{self, Entity as TwoFactorRecoveryCode};
+    pub use super::uptime_check::{self, Entity as UptimeCheck};
+    pub use super::uptime_monitor::{self, Entity as UptimeMonitor};
     pub use super::user::{self, Entity as User};
     pub use super::user_notification::{self, Entity as UserNotification};
     pub use super::user_notification_pref::{self, Entity as UserNotificationPref};
     pub use super::user_preferences::{self, Entity as UserPreferences};
     pub use super::user_role::{self, Entity as UserRole};
     pub use super::vpn_provider::{self, Entity as VpnProvider};
+    pub use super::wan_health_check::{self, Entity as WanHealthCheck};
 }
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "network_usage")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// App namespace the traffic was attributed to
+    pub app_name: String,
+    /// Rollup granularity: "day" or "month"
+    pub period: String,
+    /// Period key: "YYYY-MM-DD" for days, "YYYY-MM" for months (UTC)
+    pub period_start: String,
+    /// Bytes received by the app's pods
+    pub rx_bytes: i64,
+    /// Bytes transmitted by the app's pods
+    pub tx_bytes: i64,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,12 @@ pub struct Model {
     pub channel_type: String,
     pub enabled: bool,
     pub config: String,
+    /// Lowest severity delivered (None = all)
+    pub min_severity: Option<String>,
+    /// JSON array of event types delivered (empty = all)
+    pub include_event_types: String,
+    /// JSON array of event types never delivered
+    pub exclude_event_types: String,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -12,6 +12,8 @@ pub struct Model {
     pub recipient: Option<String>,
     pub status: String,
     pub error_message: Option<String>,
+    /// Log entry of the failed delivery this one stood in for
+    pub fallback_of: Option<i64>,
     pub created_at: DateTimeUtc,
 }
 
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "notification_routes")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    /// JSON array of event types (empty = all events)
+    pub event_types: String,
+    /// JSON array of severities (empty = all severities)
+    pub severities: String,
+    /// Start of the active window, "HH:MM" (None = all day)
+    pub start_time: Option<String>,
+    /// End of the active window, "HH:MM"; may be before the start to wrap
+    /// past midnight
+    pub end_time: Option<String>,
+    /// IANA time zone the window is expressed in
+    pub timezone: String,
+    /// JSON array of channels: "in_app", "email", "telegram", "messagebird"
+    pub channels: String,
+    /// JSON array of user IDs (empty = the event's user, or all admins for
+    /// system events)
+    pub recipient_user_ids: String,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pod_restart_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub app_name: String,
+    pub pod_name: String,
+    pub container_name: String,
+    /// Cumulative restart count reported by Kubernetes when observed
+    pub restart_count: i32,
+    /// Restarts since the previous observation of this container
+    pub restarts: i32,
+    /// Termination reason of the last restart (e.g. "OOMKilled", "Error")
+    pub reason: Option<String>,
+    pub exit_code: Option<i32>,
+    pub occurred_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
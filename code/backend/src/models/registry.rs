@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "registries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Registry host images name, e.g. "docker.io" or "ghcr.io"
+    #[sea_orm(unique)]
+    pub host: String,
+    /// Host and optional path prefix images are pulled from instead
+    pub mirror: Option<String>,
+    pub username: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: Option<String>,
+    /// Whether the registry (or its mirror) is served over plain HTTP
+    pub insecure: bool,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
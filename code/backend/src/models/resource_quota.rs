@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "resource_quotas")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Set for a user's own quota, which overrides their roles' quotas
+    pub user_id: Option<i64>,
+    /// Set for a role quota, applied to each member of the role
+    pub role_id: Option<i64>,
+    /// Apps the user may have installed; `None` is unlimited
+    pub max_apps: Option<i32>,
+    /// Total CPU requests of the user's apps, in millicores
+    pub max_cpu_millicores: Option<i64>,
+    /// Total memory requests of the user's apps, in bytes
+    pub max_memory_bytes: Option<i64>,
+    /// Total volume sizes of the user's apps, in bytes
+    pub max_storage_bytes: Option<i64>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::role::Entity",
+        from = "Column::RoleId",
+        to = "super::role::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Role,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -13,6 +13,13 @@ pub struct Model {
     pub expires_at: DateTimeUtc,
     pub last_accessed_at: DateTimeUtc,
     pub is_revoked: bool,
+    /// SHA-256 of the current refresh token
+    #[serde(skip_serializing)]
+    pub refresh_token_hash: Option<String>,
+    /// SHA-256 of the refresh token the current one replaced
+    #[serde(skip_serializing)]
+    pub previous_refresh_token_hash: Option<String>,
+    pub refreshed_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "storage_homes")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Name of the storage root the home lives in
+    pub root: String,
+    pub user_id: i64,
+    /// Size of the home folder at the last scan
+    pub used_bytes: i64,
+    /// Overrides the root's soft quota for this user
+    pub soft_quota_bytes: Option<i64>,
+    /// Overrides the root's hard quota for this user
+    pub hard_quota_bytes: Option<i64>,
+    /// Quota level (`soft` or `hard`) the user was last notified about
+    pub notified_level: Option<String>,
+    pub scanned_at: Option<DateTimeUtc>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "storage_shares")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Storage root shared over SMB; also the share name
+    #[sea_orm(unique)]
+    pub root: String,
+    /// Nobody can write, whatever their storage permissions
+    pub read_only: bool,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
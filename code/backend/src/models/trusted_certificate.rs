@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "trusted_certificates")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Admin-given label; certificates uploaded together share it
+    pub name: String,
+    pub subject: String,
+    pub issuer: String,
+    /// SHA-256 of the DER encoding, hex
+    #[sea_orm(unique)]
+    pub fingerprint: String,
+    #[sea_orm(column_type = "Text")]
+    pub pem: String,
+    pub not_before: DateTimeUtc,
+    pub not_after: DateTimeUtc,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
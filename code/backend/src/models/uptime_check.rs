@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "uptime_checks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub monitor_id: i64,
+    pub success: bool,
+    pub response_time_ms: Option<f64>,
+    /// HTTP status code (HTTP monitors only)
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub checked_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::uptime_monitor::Entity",
+        from = "Column::MonitorId",
+        to = "super::uptime_monitor::Column::Id"
+    )]
+    UptimeMonitor,
+}
+
+impl Related<super::uptime_monitor::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UptimeMonitor.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
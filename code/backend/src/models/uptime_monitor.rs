@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "uptime_monitors")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub name: String,
+    /// "http" or "tcp"
+    pub kind: String,
+    /// URL for HTTP monitors, host:port for TCP monitors
+    pub target: String,
+    pub interval_seconds: i32,
+    pub timeout_seconds: i32,
+    /// Required HTTP status (None = any status below 400)
+    pub expected_status: Option<i32>,
+    pub enabled: bool,
+    /// "up", "down" or "unknown"
+    pub status: String,
+    pub last_checked_at: Option<DateTimeUtc>,
+    /// When the status last changed between up and down
+    pub last_change_at: Option<DateTimeUtc>,
+    /// User notified when the monitor goes down or comes back up
+    pub created_by: Option<i64>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::uptime_check::Entity")]
+    UptimeCheck,
+}
+
+impl Related<super::uptime_check::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UptimeCheck.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
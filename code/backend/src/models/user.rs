@@ -18,6 +18,12 @@ pub struct Model {
     pub totp_secret: Option<String>,
     pub totp_enabled: bool,
     pub totp_verified_at: Option<DateTimeUtc>,
+    /// Failed sign-ins since the last successful one
+    pub failed_login_attempts: i32,
+    /// Sign-ins are refused until this time after too many failures
+    pub locked_until: Option<DateTimeUtc>,
+    /// Service accounts authenticate with API tokens only, never a password
+    pub is_service_account: bool,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -11,6 +11,18 @@ pub struct Model {
     pub enabled: bool,
     pub destination: Option<String>,
     pub verified: bool,
+    /// Lowest severity delivered (None = all)
+    pub min_severity: Option<String>,
+    /// JSON array of event types delivered (empty = all)
+    pub include_event_types: String,
+    /// JSON array of event types never delivered
+    pub exclude_event_types: String,
+    /// Hash of the verification code last sent to the destination
+    #[serde(skip_serializing)]
+    pub verification_code_hash: Option<String>,
+    pub verification_sent_at: Option<DateTimeUtc>,
+    /// Wrong codes submitted since the last one was sent
+    pub verification_attempts: i32,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -7,6 +7,14 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub user_id: i64,
     pub theme: String,
+    /// Language tag notifications are written in; `None` uses the
+    /// `default_locale` setting
+    pub locale: Option<String>,
+    /// Whether signing in from a new device sends a "was this you?" alert
+    pub login_alerts: bool,
+    /// IANA time zone app access schedules are read in; `None` uses the
+    /// `default_timezone` setting
+    pub timezone: Option<String>,
     pub updated_at: DateTimeUtc,
 }
 
@@ -7,6 +7,10 @@ pub struct Model {
     pub user_id: i64,
     #[sea_orm(primary_key, auto_increment = false)]
     pub role_id: i64,
+    /// When the assignment ends; `None` keeps it until it is removed
+    pub expires_at: Option<DateTimeUtc>,
+    /// Whether the user and admins were told about the upcoming expiry
+    pub expiry_notified: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -42,7 +42,7 @@ pub struct Model {
     pub vpn_type: VpnType,
     /// Service provider (e.g., "nordvpn", "mullvad", "custom")
     pub service_provider: Option<String>,
-    /// JSON blob containing VPN credentials (encrypted later)
+    /// JSON blob containing VPN credentials, encrypted when a key is configured
     #[serde(skip_serializing)]
     pub credentials_json: String,
     pub enabled: bool,
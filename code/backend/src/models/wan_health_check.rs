@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "wan_health_checks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// "latency" or "speedtest"
+    pub kind: String,
+    /// host:port for latency probes, URL for speed tests
+    pub target: String,
+    /// Average round-trip of successful probes
+    pub latency_ms: Option<f64>,
+    pub packet_loss_percent: Option<f64>,
+    pub download_mbps: Option<f64>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub checked_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
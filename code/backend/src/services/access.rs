@@ -0,0 +1,391 @@
+//! Permission and app access lookups
+//!
+//! Resolve what a user's roles grant. Used by the permission extractors and
+//! by services that scope data to a user, such as the audit log. Expired
+//! role assignments are left out. [`evaluate`] explains a decision step by
+//! step for debugging access problems.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::middleware::permissions::{Permission, TenantsManage};
+use crate::models::prelude::*;
+use crate::models::{role, role_app_permission, role_permission, user, user_role};
+use crate::state::DbConn;
+
+/// Role assignments that still grant their role
+///
+/// Assignments past their `expires_at` grant nothing, even before the role
+/// expiry task removes them.
+pub fn unexpired_roles() -> Condition {
+    Condition::any()
+        .add(user_role::Column::ExpiresAt.is_null())
+        .add(user_role::Column::ExpiresAt.gt(Utc::now()))
+}
+
+/// Get all permissions for a user (from all their roles)
+/// Includes app.* permissions based on role_app_permissions
+pub async fn get_user_permissions(db: &DbConn, user_id: i64) -> Vec<String> {
+    // Get all role IDs for this user
+    let user_roles = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    let role_ids: Vec<i64> = user_roles.iter().map(|ur| ur.role_id).collect();
+
+    if role_ids.is_empty() {
+        return vec![];
+    }
+
+    // Get all permissions from all roles
+    let permissions = RolePermission::find()
+        .filter(role_permission::Column::RoleId.is_in(role_ids.clone()))
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    let mut unique_perms: Vec<String> = permissions.iter().map(|p| p.permission.clone()).collect();
+
+    // Get app permissions and convert to app.{name} format
+    let app_permissions = RoleAppPermission::find()
+        .filter(role_app_permission::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    for app_perm in app_permissions {
+        unique_perms.push(format!("app.{}", app_perm.app_name));
+    }
+
+    // Deduplicate and return
+    unique_perms.sort();
+    unique_perms.dedup();
+    unique_perms
+}
+
+/// Get all app names a user has access to
+/// Returns vec!["*"] if user has app.* permission (all apps access)
+pub async fn get_user_app_access(db: &DbConn, user_id: i64) -> Vec<String> {
+    // Get all role IDs for this user
+    let user_roles = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    let role_ids: Vec<i64> = user_roles.iter().map(|ur| ur.role_id).collect();
+
+    if role_ids.is_empty() {
+        return vec![];
+    }
+
+    // Check for app.* wildcard permission
+    let has_wildcard = RolePermission::find()
+        .filter(role_permission::Column::RoleId.is_in(role_ids.clone()))
+        .filter(role_permission::Column::Permission.eq("app.*"))
+        .one(db)
+        .await
+        .map(|r| r.is_some())
+        .unwrap_or(false);
+
+    if has_wildcard {
+        return vec!["*".to_string()];
+    }
+
+    // Get all app permissions from all roles
+    let app_permissions = RoleAppPermission::find()
+        .filter(role_app_permission::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    // Deduplicate and return
+    let mut unique_apps: Vec<String> = app_permissions.iter().map(|p| p.app_name.clone()).collect();
+    unique_apps.sort();
+    unique_apps.dedup();
+    unique_apps
+}
+
+/// Check whether a user may access a specific app (via app.* or a per-app
+/// grant) that does not belong to another tenant, at this time of day
+pub async fn user_has_app_access(db: &DbConn, user_id: i64, app_name: &str) -> bool {
+    let allowed = get_user_app_access(db, user_id).await;
+    allowed.iter().any(|a| a == "*" || a == app_name)
+        && super::tenants::can_see_app(db, user_id, app_name)
+            .await
+            .unwrap_or(false)
+        && super::app_schedules::is_allowed(db, user_id, app_name, Utc::now())
+            .await
+            .unwrap_or(false)
+}
+
+/// Get the names of a user's roles
+pub async fn get_user_role_names(db: &DbConn, user_id: i64) -> Vec<String> {
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|ur| ur.role_id)
+        .collect();
+
+    if role_ids.is_empty() {
+        return vec![];
+    }
+
+    Role::find()
+        .filter(role::Column::Id.is_in(role_ids))
+        .all(db)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| r.name)
+        .collect()
+}
+
+/// Who an evaluation is for
+#[derive(Debug, Clone)]
+pub enum Subject {
+    /// A user, with everything their roles grant and their tenants
+    User(user::Model),
+    /// A single role on its own
+    Role(role::Model),
+}
+
+/// A role grant that satisfied a check
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct RoleGrant {
+    pub role_id: i64,
+    pub role_name: String,
+    /// The granted permission, e.g. `apps.restart`, `app.*` or `app.sonarr`
+    pub permission: String,
+}
+
+/// One check of an evaluation
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct EvaluationStep {
+    /// `account`, `permission`, `app_access`, `tenant` or `schedule`
+    pub check: String,
+    pub passed: bool,
+    pub detail: String,
+    /// Grants that satisfied the check
+    pub granted_by: Vec<RoleGrant>,
+}
+
+/// Outcome of [`evaluate`]
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Evaluation {
+    /// Whether every check passed
+    pub allowed: bool,
+    /// Names of the roles that were considered
+    pub roles: Vec<String>,
+    /// Checks in the order requests go through them; tenant membership is
+    /// only checked for users
+    pub steps: Vec<EvaluationStep>,
+}
+
+impl EvaluationStep {
+    fn new(check: &str, passed: bool, detail: String, granted_by: Vec<RoleGrant>) -> Self {
+        Self {
+            check: check.to_string(),
+            passed,
+            detail,
+            granted_by,
+        }
+    }
+}
+
+/// Every grant of the given roles, with app access in `app.{name}` form
+async fn role_grants(db: &DbConn, roles: &[role::Model]) -> Result<Vec<RoleGrant>> {
+    let names: HashMap<i64, &str> = roles.iter().map(|r| (r.id, r.name.as_str())).collect();
+    let role_ids: Vec<i64> = roles.iter().map(|r| r.id).collect();
+    let grant = |role_id: i64, permission: String| RoleGrant {
+        role_id,
+        role_name: names.get(&role_id).copied().unwrap_or_default().to_string(),
+        permission,
+    };
+
+    let mut grants: Vec<RoleGrant> = RolePermission::find()
+        .filter(role_permission::Column::RoleId.is_in(role_ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| grant(p.role_id, p.permission))
+        .collect();
+    grants.extend(
+        RoleAppPermission::find()
+            .filter(role_app_permission::Column::RoleId.is_in(role_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| grant(p.role_id, format!("app.{}", p.app_name))),
+    );
+    grants.sort_by(|a, b| (&a.role_name, &a.permission).cmp(&(&b.role_name, &b.permission)));
+    Ok(grants)
+}
+
+fn matching(grants: &[RoleGrant], permissions: &[&str]) -> Vec<RoleGrant> {
+    grants
+        .iter()
+        .filter(|g| permissions.contains(&g.permission.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn granted_detail(what: &str, granted_by: &[RoleGrant]) -> String {
+    if granted_by.is_empty() {
+        return format!("No role grants {}", what);
+    }
+    let mut roles: Vec<&str> = granted_by.iter().map(|g| g.role_name.as_str()).collect();
+    roles.dedup();
+    format!("{} is granted by {}", what, roles.join(", "))
+}
+
+/// Explain whether a user or role may perform `action`, optionally on an
+/// app. Users are decided by [`get_user_permissions`] and
+/// [`user_has_app_access`], the same lookups requests go through.
+pub async fn evaluate(
+    db: &DbConn,
+    subject: &Subject,
+    action: &str,
+    app_name: Option<&str>,
+) -> Result<Evaluation> {
+    let roles = match subject {
+        Subject::User(user) => {
+            let role_ids: Vec<i64> = UserRole::find()
+                .filter(user_role::Column::UserId.eq(user.id))
+                .filter(unexpired_roles())
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|ur| ur.role_id)
+                .collect();
+            Role::find()
+                .filter(role::Column::Id.is_in(role_ids))
+                .all(db)
+                .await?
+        }
+        Subject::Role(role) => vec![role.clone()],
+    };
+    let grants = role_grants(db, &roles).await?;
+    let mut steps = Vec::new();
+
+    if let Subject::User(user) = subject {
+        let (passed, detail) = match (user.is_active, user.is_approved) {
+            (true, true) => (true, "The account is active and approved".to_string()),
+            (false, _) => (false, "The account is deactivated".to_string()),
+            (true, false) => (false, "The account is waiting for approval".to_string()),
+        };
+        steps.push(EvaluationStep::new("account", passed, detail, Vec::new()));
+    }
+
+    let granted_by = matching(&grants, &[action]);
+    let passed = match subject {
+        Subject::User(user) => get_user_permissions(db, user.id)
+            .await
+            .iter()
+            .any(|p| p == action),
+        Subject::Role(_) => !granted_by.is_empty(),
+    };
+    let detail = granted_detail(action, &granted_by);
+    steps.push(EvaluationStep::new(
+        "permission",
+        passed,
+        detail,
+        granted_by,
+    ));
+
+    if let Some(app_name) = app_name {
+        let app_permission = format!("app.{}", app_name);
+        let granted_by = matching(&grants, &["app.*", &app_permission]);
+        let passed = match subject {
+            Subject::User(user) => get_user_app_access(db, user.id)
+                .await
+                .iter()
+                .any(|a| a == "*" || a == app_name),
+            Subject::Role(_) => !granted_by.is_empty(),
+        };
+        let detail = granted_detail(&format!("Access to {}", app_name), &granted_by);
+        steps.push(EvaluationStep::new(
+            "app_access",
+            passed,
+            detail,
+            granted_by,
+        ));
+
+        if let Subject::User(user) = subject {
+            steps.push(tenant_step(db, user, app_name, &grants).await?);
+            let passed =
+                super::app_schedules::is_allowed(db, user.id, app_name, Utc::now()).await?;
+            let detail = if passed {
+                format!("{} may be used at this time", app_name)
+            } else {
+                format!("{} is outside its allowed hours", app_name)
+            };
+            steps.push(EvaluationStep::new("schedule", passed, detail, Vec::new()));
+        }
+    }
+
+    let mut role_names: Vec<String> = roles.into_iter().map(|r| r.name).collect();
+    role_names.sort();
+    Ok(Evaluation {
+        allowed: steps.iter().all(|s| s.passed),
+        roles: role_names,
+        steps,
+    })
+}
+
+async fn tenant_step(
+    db: &DbConn,
+    user: &user::Model,
+    app_name: &str,
+    grants: &[RoleGrant],
+) -> Result<EvaluationStep> {
+    let passed = super::tenants::can_see_app(db, user.id, app_name).await?;
+    let tenant = match InstalledApp::find_by_id(app_name).one(db).await? {
+        Some(app) => match app.tenant_id {
+            Some(id) => Tenant::find_by_id(id).one(db).await?,
+            None => None,
+        },
+        None => None,
+    };
+    let Some(tenant) = tenant else {
+        return Ok(EvaluationStep::new(
+            "tenant",
+            passed,
+            format!("{} is shared by all tenants", app_name),
+            Vec::new(),
+        ));
+    };
+
+    let granted_by = matching(grants, &[TenantsManage::NAME]);
+    let detail = if !granted_by.is_empty() {
+        format!(
+            "{} belongs to tenant {}; {} sees every tenant",
+            app_name,
+            tenant.name,
+            TenantsManage::NAME
+        )
+    } else if passed {
+        format!(
+            "{} belongs to tenant {}, which the user is a member of",
+            app_name, tenant.name
+        )
+    } else {
+        format!(
+            "{} belongs to tenant {}, which the user is not a member of",
+            app_name, tenant.name
+        )
+    };
+    Ok(EvaluationStep::new("tenant", passed, detail, granted_by))
+}
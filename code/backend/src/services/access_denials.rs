@@ -0,0 +1,286 @@
+//! Audit of permission denials
+//!
+//! Every `403` answered to a signed-in user on the API is recorded as an
+//! `access_denied` audit entry, with the permission the route requires when
+//! the denial came from a permission check. The same denial (user, method
+//! and path) is written at most once per [`DEDUP_WINDOW`]. When one user is
+//! denied `access_denied_alert_threshold` times within [`ALERT_WINDOW`],
+//! holders of `security.manage` get an `access_denied` notification, at most
+//! once per window, as that usually means probing or a misconfigured role.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use super::access::get_user_permissions;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, SecurityManage};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::user;
+use crate::state::{AppState, DbConn};
+
+/// Denials of one user within [`ALERT_WINDOW`] that send a notification;
+/// 0 turns the notification off
+pub const ACCESS_DENIED_ALERT_THRESHOLD: &str = "access_denied_alert_threshold";
+
+/// How long repeats of the same denial are left out of the audit log
+pub const DEDUP_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Period denials are counted over for the notification
+pub const ALERT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+static DENIALS: Lazy<DenialTracker> = Lazy::new(DenialTracker::default);
+
+/// What to do about one denial
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Observation {
+    /// Whether to write an audit entry
+    pub record: bool,
+    /// Denials of the user within [`ALERT_WINDOW`], when they call for a
+    /// notification
+    pub alert: Option<usize>,
+}
+
+#[derive(Default)]
+struct UserDenials {
+    seen: VecDeque<Instant>,
+    alerted_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    /// (user, method, path) -> when it was last written to the audit log
+    recorded: HashMap<(i64, String, String), Instant>,
+    users: HashMap<i64, UserDenials>,
+}
+
+/// Recent denials, for deduplication and alerting
+#[derive(Default)]
+pub struct DenialTracker {
+    state: Mutex<TrackerState>,
+}
+
+impl DenialTracker {
+    /// Count a denial and decide whether to record and alert
+    pub fn observe(
+        &self,
+        user_id: i64,
+        method: &str,
+        path: &str,
+        threshold: u32,
+        now: Instant,
+    ) -> Observation {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .recorded
+            .retain(|_, at| now.duration_since(*at) < DEDUP_WINDOW);
+        let key = (user_id, method.to_string(), path.to_string());
+        let record = !state.recorded.contains_key(&key);
+        if record {
+            state.recorded.insert(key, now);
+        }
+
+        state.users.retain(|_, denials| {
+            denials
+                .seen
+                .back()
+                .is_some_and(|last| now.duration_since(*last) < ALERT_WINDOW)
+        });
+        let denials = state.users.entry(user_id).or_default();
+        while denials
+            .seen
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= ALERT_WINDOW)
+        {
+            denials.seen.pop_front();
+        }
+        denials.seen.push_back(now);
+        let quiet = denials
+            .alerted_at
+            .is_none_or(|at| now.duration_since(at) >= ALERT_WINDOW);
+        let alert = if threshold > 0 && denials.seen.len() >= threshold as usize && quiet {
+            denials.alerted_at = Some(now);
+            Some(denials.seen.len())
+        } else {
+            None
+        };
+        Observation { record, alert }
+    }
+}
+
+/// Parse the alert threshold setting
+pub fn parse_threshold(value: &str) -> Result<u32> {
+    value.trim().parse::<u32>().map_err(|_| {
+        AppError::BadRequest(format!(
+            "{} must be a whole number of at least 0",
+            ACCESS_DENIED_ALERT_THRESHOLD
+        ))
+    })
+}
+
+async fn alert_threshold(db: &DbConn) -> Result<u32> {
+    Ok(get_setting_value(db, ACCESS_DENIED_ALERT_THRESHOLD)
+        .await?
+        .and_then(|v| parse_threshold(&v).ok())
+        .unwrap_or(10))
+}
+
+/// A denied request
+#[derive(Debug, Clone)]
+pub struct Denial {
+    pub method: String,
+    pub path: String,
+    /// Permission the route requires, if a permission check denied it
+    pub permission: Option<&'static str>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Active users holding `security.manage`, who are told about repeated
+/// denials
+async fn security_admins(db: &DbConn) -> Result<Vec<user::Model>> {
+    let mut admins = Vec::new();
+    for candidate in User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+    {
+        if get_user_permissions(db, candidate.id)
+            .await
+            .iter()
+            .any(|p| p == SecurityManage::NAME)
+        {
+            admins.push(candidate);
+        }
+    }
+    Ok(admins)
+}
+
+/// Audit a denial and tell security admins about repeated ones
+pub async fn record(state: &AppState, user: &user::Model, denial: Denial) -> Result<()> {
+    let db = state.get_db().await?;
+    let threshold = alert_threshold(&db).await?;
+    let observation = DENIALS.observe(
+        user.id,
+        &denial.method,
+        &denial.path,
+        threshold,
+        Instant::now(),
+    );
+
+    if observation.record {
+        state
+            .audit
+            .log_failure(
+                AuditAction::AccessDenied,
+                ResourceType::System,
+                denial.permission.map(str::to_string),
+                Some(user.id),
+                Some(user.username.clone()),
+                Some(serde_json::json!({
+                    "method": denial.method,
+                    "path": denial.path,
+                    "permission": denial.permission,
+                })),
+                denial.ip_address.clone(),
+                denial.user_agent.clone(),
+                "Permission denied",
+            )
+            .await?;
+    }
+
+    if let Some(count) = observation.alert {
+        let details = format!(
+            "{} denied requests within {} minutes, most recently {} {}{}",
+            count,
+            ALERT_WINDOW.as_secs() / 60,
+            denial.method,
+            denial.path,
+            denial
+                .permission
+                .map(|p| format!(" (requires {})", p))
+                .unwrap_or_default()
+        );
+        for admin in security_admins(&db).await? {
+            if admin.id == user.id {
+                continue;
+            }
+            if let Err(e) = state
+                .notification
+                .notify_user_event(
+                    &AuditAction::AccessDenied,
+                    admin.id,
+                    Some(&user.username),
+                    Some(&details),
+                )
+                .await
+            {
+                tracing::warn!("Failed to send access_denied notification: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_are_deduplicated() {
+        let tracker = DenialTracker::default();
+        let start = Instant::now();
+        let observe = |user_id, path, secs| {
+            tracker.observe(user_id, "GET", path, 0, start + Duration::from_secs(secs))
+        };
+        assert!(observe(1, "/api/users", 0).record);
+        assert!(!observe(1, "/api/users", 60).record);
+        assert!(observe(1, "/api/roles", 60).record);
+        assert!(observe(2, "/api/users", 60).record);
+        assert!(observe(1, "/api/users", DEDUP_WINDOW.as_secs()).record);
+    }
+
+    #[test]
+    fn test_alert_once_per_window() {
+        let tracker = DenialTracker::default();
+        let start = Instant::now();
+        let alerts: Vec<Option<usize>> = (0..5)
+            .map(|i| {
+                tracker
+                    .observe(1, "GET", &format!("/api/{}", i), 3, start)
+                    .alert
+            })
+            .collect();
+        assert_eq!(alerts, [None, None, Some(3), None, None]);
+
+        // Once the window has passed, old denials no longer count
+        let later = start + ALERT_WINDOW;
+        assert_eq!(tracker.observe(1, "GET", "/api/x", 3, later).alert, None);
+        assert_eq!(tracker.observe(1, "GET", "/api/y", 3, later).alert, None);
+        assert_eq!(tracker.observe(1, "GET", "/api/z", 3, later).alert, Some(3));
+        // Other users have their own count
+        assert_eq!(tracker.observe(2, "GET", "/api/x", 3, later).alert, None);
+        // 0 turns alerts off
+        let off = DenialTracker::default();
+        for i in 0..20 {
+            assert_eq!(
+                off.observe(1, "GET", &format!("/api/{}", i), 0, start)
+                    .alert,
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!(parse_threshold(" 5 ").unwrap(), 5);
+        assert_eq!(parse_threshold("0").unwrap(), 0);
+        assert!(parse_threshold("-1").is_err());
+        assert!(parse_threshold("often").is_err());
+    }
+}
@@ -0,0 +1,683 @@
+//! Periodic access reviews
+//!
+//! An access review lists every active user with the roles and app access
+//! they hold when it starts. Holders of `roles.manage` confirm or revoke
+//! each user's access before the review is due; revoking removes all of the
+//! user's roles. Nobody reviews their own access.
+//!
+//! With `access_review_interval_months` set, [`AccessReviewTask`] starts a
+//! review that many months after the previous one and notifies reviewers.
+//! Once a review is past due, undecided users are marked `overdue`. With
+//! `access_review_overdue_action` set to `suspend` their accounts are also
+//! deactivated, except for reviewers, until someone confirms their access.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Months, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+
+use super::access::{get_user_app_access, get_user_permissions, get_user_role_names};
+use super::audit::AuditService;
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use super::sessions::revoke_user_sessions;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, RolesManage};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::{access_review, access_review_item, user, user_role};
+
+/// Months between scheduled reviews; 0 only allows manual reviews
+pub const ACCESS_REVIEW_INTERVAL_MONTHS: &str = "access_review_interval_months";
+
+/// Days reviewers have to decide before a review is overdue
+pub const ACCESS_REVIEW_DEADLINE_DAYS: &str = "access_review_deadline_days";
+
+/// What happens to users left undecided: `flag` or `suspend`
+pub const ACCESS_REVIEW_OVERDUE_ACTION: &str = "access_review_overdue_action";
+
+pub const STATUS_OPEN: &str = "open";
+pub const STATUS_CLOSED: &str = "closed";
+
+pub const DECISION_PENDING: &str = "pending";
+pub const DECISION_CONFIRMED: &str = "confirmed";
+pub const DECISION_REVOKED: &str = "revoked";
+pub const DECISION_OVERDUE: &str = "overdue";
+
+/// What happens to users nobody decided on by the deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverdueAction {
+    /// Mark them overdue
+    Flag,
+    /// Mark them overdue and deactivate their accounts
+    Suspend,
+}
+
+/// The configured review schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewPolicy {
+    pub interval_months: u32,
+    pub deadline_days: u32,
+    pub overdue_action: OverdueAction,
+}
+
+fn parse_overdue_action(value: &str) -> Result<OverdueAction> {
+    match value.trim() {
+        "flag" => Ok(OverdueAction::Flag),
+        "suspend" => Ok(OverdueAction::Suspend),
+        _ => Err(AppError::BadRequest(format!(
+            "{} must be 'flag' or 'suspend'",
+            ACCESS_REVIEW_OVERDUE_ACTION
+        ))),
+    }
+}
+
+fn parse_number(key: &str, value: &str) -> Result<u32> {
+    let minimum = if key == ACCESS_REVIEW_INTERVAL_MONTHS {
+        0
+    } else {
+        1
+    };
+    value
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n >= minimum)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a whole number of at least {}",
+                key, minimum
+            ))
+        })
+}
+
+/// Validate one of the review settings
+pub fn parse_setting(key: &str, value: &str) -> Result<()> {
+    if key == ACCESS_REVIEW_OVERDUE_ACTION {
+        parse_overdue_action(value).map(|_| ())
+    } else {
+        parse_number(key, value).map(|_| ())
+    }
+}
+
+/// The configured review schedule; unreadable settings fall back to the
+/// defaults
+pub async fn policy(db: &DatabaseConnection) -> Result<ReviewPolicy> {
+    let read = |key: &'static str| async move { get_setting_value(db, key).await };
+    Ok(ReviewPolicy {
+        interval_months: read(ACCESS_REVIEW_INTERVAL_MONTHS)
+            .await?
+            .and_then(|v| parse_number(ACCESS_REVIEW_INTERVAL_MONTHS, &v).ok())
+            .unwrap_or(0),
+        deadline_days: read(ACCESS_REVIEW_DEADLINE_DAYS)
+            .await?
+            .and_then(|v| parse_number(ACCESS_REVIEW_DEADLINE_DAYS, &v).ok())
+            .unwrap_or(14),
+        overdue_action: read(ACCESS_REVIEW_OVERDUE_ACTION)
+            .await?
+            .and_then(|v| parse_overdue_action(&v).ok())
+            .unwrap_or(OverdueAction::Flag),
+    })
+}
+
+/// When the next scheduled review is due to start, if reviews are scheduled
+pub fn next_review_at(
+    policy: &ReviewPolicy,
+    last_started: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if policy.interval_months == 0 {
+        return None;
+    }
+    Some(match last_started {
+        Some(last) => last
+            .checked_add_months(Months::new(policy.interval_months))
+            .unwrap_or(last),
+        None => now,
+    })
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// One user's access in a review
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ReviewItem {
+    pub user_id: i64,
+    pub username: String,
+    /// Role names when the review started
+    pub roles: Vec<String>,
+    /// Apps the user could open when the review started, `["*"]` for all
+    pub apps: Vec<String>,
+    /// `pending`, `confirmed`, `revoked` or `overdue`
+    pub decision: String,
+    /// Whether the account was deactivated for missing the deadline
+    pub suspended: bool,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+}
+
+/// An access review with its progress
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ReviewSummary {
+    pub id: i64,
+    /// `open` or `closed`
+    pub status: String,
+    /// Admin who started the review; `None` for scheduled reviews
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub total: usize,
+    pub pending: usize,
+    pub overdue: usize,
+}
+
+/// An access review with every user in it
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ReviewDetail {
+    #[serde(flatten)]
+    pub review: ReviewSummary,
+    pub items: Vec<ReviewItem>,
+}
+
+/// A reviewer's decision on one user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Confirm,
+    Revoke,
+}
+
+/// The outcome of a decision
+#[derive(Debug)]
+pub struct DecisionOutcome {
+    pub user: user::Model,
+    /// Names of the roles removed by a revoke
+    pub removed_roles: Vec<String>,
+    /// Whether a confirm reactivated an account suspended by the review
+    pub reactivated: bool,
+    /// Whether the decision closed the review
+    pub closed: bool,
+}
+
+// ============================================================================
+// Reviews
+// ============================================================================
+
+async fn username(db: &DatabaseConnection, user_id: Option<i64>) -> Result<Option<String>> {
+    Ok(match user_id {
+        Some(id) => User::find_by_id(id).one(db).await?.map(|u| u.username),
+        None => None,
+    })
+}
+
+fn parse_list(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+async fn summarize(
+    db: &DatabaseConnection,
+    review: access_review::Model,
+    items: &[access_review_item::Model],
+) -> Result<ReviewSummary> {
+    let count = |decision: &str| items.iter().filter(|i| i.decision == decision).count();
+    Ok(ReviewSummary {
+        created_by: username(db, review.created_by_id).await?,
+        id: review.id,
+        status: review.status,
+        created_at: review.created_at,
+        due_at: review.due_at,
+        closed_at: review.closed_at,
+        total: items.len(),
+        pending: count(DECISION_PENDING),
+        overdue: count(DECISION_OVERDUE),
+    })
+}
+
+async fn review_items(
+    db: &DatabaseConnection,
+    review_id: i64,
+) -> Result<Vec<access_review_item::Model>> {
+    Ok(AccessReviewItem::find()
+        .filter(access_review_item::Column::ReviewId.eq(review_id))
+        .all(db)
+        .await?)
+}
+
+/// The open review, if any
+pub async fn open_review(db: &DatabaseConnection) -> Result<Option<access_review::Model>> {
+    Ok(AccessReview::find()
+        .filter(access_review::Column::Status.eq(STATUS_OPEN))
+        .one(db)
+        .await?)
+}
+
+/// Start a review of every active user's access
+///
+/// Only one review can be open at a time.
+pub async fn start_review(
+    db: &DatabaseConnection,
+    created_by_id: Option<i64>,
+    deadline_days: u32,
+    now: DateTime<Utc>,
+) -> Result<access_review::Model> {
+    if open_review(db).await?.is_some() {
+        return Err(AppError::BadRequest(
+            "An access review is already open".to_string(),
+        ));
+    }
+
+    let review = access_review::ActiveModel {
+        status: Set(STATUS_OPEN.to_string()),
+        created_by_id: Set(created_by_id),
+        created_at: Set(now),
+        due_at: Set(now + chrono::Duration::days(deadline_days as i64)),
+        closed_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    for member in User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+    {
+        let roles = get_user_role_names(db, member.id).await;
+        let apps = get_user_app_access(db, member.id).await;
+        access_review_item::ActiveModel {
+            review_id: Set(review.id),
+            user_id: Set(member.id),
+            roles: Set(serde_json::to_string(&roles)?),
+            apps: Set(serde_json::to_string(&apps)?),
+            decision: Set(DECISION_PENDING.to_string()),
+            suspended: Set(false),
+            decided_by_id: Set(None),
+            decided_at: Set(None),
+            comment: Set(None),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    Ok(review)
+}
+
+/// All reviews, newest first
+pub async fn list_reviews(db: &DatabaseConnection) -> Result<Vec<ReviewSummary>> {
+    let reviews = AccessReview::find()
+        .order_by_desc(access_review::Column::CreatedAt)
+        .order_by_desc(access_review::Column::Id)
+        .all(db)
+        .await?;
+    let mut summaries = Vec::with_capacity(reviews.len());
+    for review in reviews {
+        let items = review_items(db, review.id).await?;
+        summaries.push(summarize(db, review, &items).await?);
+    }
+    Ok(summaries)
+}
+
+/// A review with every user in it, sorted by username
+pub async fn get_review(db: &DatabaseConnection, review_id: i64) -> Result<ReviewDetail> {
+    let review = AccessReview::find_by_id(review_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Access review not found".to_string()))?;
+    let models = review_items(db, review.id).await?;
+
+    let mut items = Vec::with_capacity(models.len());
+    for item in &models {
+        items.push(ReviewItem {
+            user_id: item.user_id,
+            username: username(db, Some(item.user_id)).await?.unwrap_or_default(),
+            roles: parse_list(&item.roles),
+            apps: parse_list(&item.apps),
+            decision: item.decision.clone(),
+            suspended: item.suspended,
+            decided_by: username(db, item.decided_by_id).await?,
+            decided_at: item.decided_at,
+            comment: item.comment.clone(),
+        });
+    }
+    items.sort_by(|a, b| a.username.cmp(&b.username));
+
+    Ok(ReviewDetail {
+        review: summarize(db, review, &models).await?,
+        items,
+    })
+}
+
+async fn close(
+    db: &DatabaseConnection,
+    review: access_review::Model,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut active: access_review::ActiveModel = review.into();
+    active.status = Set(STATUS_CLOSED.to_string());
+    active.closed_at = Set(Some(now));
+    active.update(db).await?;
+    Ok(())
+}
+
+/// Confirm or revoke one user's access
+///
+/// Pending users can be decided while the review is open; overdue users
+/// also after it closed, so a suspended account can be reactivated. The
+/// review closes once nobody is pending.
+pub async fn decide(
+    db: &DatabaseConnection,
+    review_id: i64,
+    reviewer: &user::Model,
+    user_id: i64,
+    decision: Decision,
+    comment: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<DecisionOutcome> {
+    let review = AccessReview::find_by_id(review_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Access review not found".to_string()))?;
+    let item = AccessReviewItem::find()
+        .filter(access_review_item::Column::ReviewId.eq(review.id))
+        .filter(access_review_item::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User is not part of this review".to_string()))?;
+    if user_id == reviewer.id {
+        return Err(AppError::BadRequest(
+            "You cannot review your own access".to_string(),
+        ));
+    }
+    let decidable = match item.decision.as_str() {
+        DECISION_PENDING => review.status == STATUS_OPEN,
+        DECISION_OVERDUE => true,
+        _ => false,
+    };
+    if !decidable {
+        return Err(AppError::BadRequest(format!(
+            "Access of this user is already {}",
+            item.decision
+        )));
+    }
+    let member = User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let mut removed_roles = Vec::new();
+    let mut reactivated = false;
+    match decision {
+        Decision::Confirm => {
+            if item.suspended && !member.is_active {
+                let mut active: user::ActiveModel = member.clone().into();
+                active.is_active = Set(true);
+                active.updated_at = Set(now);
+                active.update(db).await?;
+                reactivated = true;
+            }
+        }
+        Decision::Revoke => {
+            removed_roles = get_user_role_names(db, user_id).await;
+            UserRole::delete_many()
+                .filter(user_role::Column::UserId.eq(user_id))
+                .exec(db)
+                .await?;
+        }
+    }
+
+    let mut active: access_review_item::ActiveModel = item.into();
+    active.decision = Set(match decision {
+        Decision::Confirm => DECISION_CONFIRMED,
+        Decision::Revoke => DECISION_REVOKED,
+    }
+    .to_string());
+    active.decided_by_id = Set(Some(reviewer.id));
+    active.decided_at = Set(Some(now));
+    active.comment = Set(comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty()));
+    active.update(db).await?;
+
+    let mut closed = false;
+    if review.status == STATUS_OPEN
+        && !review_items(db, review.id)
+            .await?
+            .iter()
+            .any(|i| i.decision == DECISION_PENDING)
+    {
+        close(db, review, now).await?;
+        closed = true;
+    }
+
+    Ok(DecisionOutcome {
+        user: member,
+        removed_roles,
+        reactivated,
+        closed,
+    })
+}
+
+/// Whether a user may decide access reviews
+async fn is_reviewer(db: &DatabaseConnection, user_id: i64) -> bool {
+    get_user_permissions(db, user_id)
+        .await
+        .iter()
+        .any(|p| p == RolesManage::NAME)
+}
+
+/// Active users holding `roles.manage`, who are asked to review access
+pub async fn reviewers(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let mut found = Vec::new();
+    for candidate in User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+    {
+        if is_reviewer(db, candidate.id).await {
+            found.push(candidate);
+        }
+    }
+    Ok(found)
+}
+
+/// Mark users left undecided in past-due reviews as overdue and close
+/// those reviews
+///
+/// Returns the users whose accounts were suspended.
+pub async fn process_overdue(
+    db: &DatabaseConnection,
+    action: OverdueAction,
+    now: DateTime<Utc>,
+) -> Result<Vec<(i64, user::Model)>> {
+    let due = AccessReview::find()
+        .filter(access_review::Column::Status.eq(STATUS_OPEN))
+        .filter(access_review::Column::DueAt.lte(now))
+        .all(db)
+        .await?;
+
+    let mut suspended = Vec::new();
+    for review in due {
+        for item in review_items(db, review.id).await? {
+            if item.decision != DECISION_PENDING {
+                continue;
+            }
+            let member = User::find_by_id(item.user_id).one(db).await?;
+            let suspend = match &member {
+                Some(m) => {
+                    action == OverdueAction::Suspend && m.is_active && !is_reviewer(db, m.id).await
+                }
+                None => false,
+            };
+
+            let mut active: access_review_item::ActiveModel = item.into();
+            active.decision = Set(DECISION_OVERDUE.to_string());
+            active.suspended = Set(suspend);
+            active.update(db).await?;
+
+            if let (true, Some(member)) = (suspend, member) {
+                let mut account: user::ActiveModel = member.clone().into();
+                account.is_active = Set(false);
+                account.updated_at = Set(now);
+                account.update(db).await?;
+                revoke_user_sessions(db, member.id, None).await?;
+                suspended.push((review.id, member));
+            }
+        }
+        close(db, review, now).await?;
+    }
+    Ok(suspended)
+}
+
+/// Tell reviewers a review has started
+pub async fn announce(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    review: &access_review::Model,
+    started_by: Option<&str>,
+) -> Result<()> {
+    let details = format!(
+        "Review #{} is due {}",
+        review.id,
+        review.due_at.format("%Y-%m-%d")
+    );
+    for reviewer in reviewers(db).await? {
+        if let Err(e) = notification
+            .notify_user_event(
+                &AuditAction::AccessReviewStarted,
+                reviewer.id,
+                Some(started_by.unwrap_or("system")),
+                Some(&details),
+            )
+            .await
+        {
+            tracing::warn!("Failed to send access_review_started notification: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Handles overdue reviews and starts scheduled ones
+pub struct AccessReviewTask {
+    pub notification: NotificationService,
+    pub audit: AuditService,
+}
+
+#[async_trait]
+impl PeriodicTask for AccessReviewTask {
+    fn name(&self) -> &'static str {
+        "access_review"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let policy = policy(db).await?;
+
+        for (review_id, member) in process_overdue(db, policy.overdue_action, now).await? {
+            tracing::info!(
+                "Suspended {} for missing access review #{}",
+                member.username,
+                review_id
+            );
+            let _ = self
+                .audit
+                .log_success(
+                    AuditAction::UserDeactivated,
+                    ResourceType::User,
+                    Some(member.id.to_string()),
+                    None,
+                    None,
+                    Some(serde_json::json!({
+                        "target_username": member.username,
+                        "reason": "access_review_overdue",
+                        "review_id": review_id,
+                    })),
+                    None,
+                    None,
+                )
+                .await;
+        }
+
+        if open_review(db).await?.is_some() {
+            return Ok(());
+        }
+        let last_started = AccessReview::find()
+            .order_by_desc(access_review::Column::CreatedAt)
+            .one(db)
+            .await?
+            .map(|r| r.created_at);
+        if next_review_at(&policy, last_started, now).is_some_and(|at| at <= now) {
+            let review = start_review(db, None, policy.deadline_days, now).await?;
+            tracing::info!("Started access review #{}", review.id);
+            let _ = self
+                .audit
+                .log_success(
+                    AuditAction::AccessReviewStarted,
+                    ResourceType::Role,
+                    Some(review.id.to_string()),
+                    None,
+                    None,
+                    Some(serde_json::json!({"due_at": review.due_at})),
+                    None,
+                    None,
+                )
+                .await;
+            announce(db, &self.notification, &review, None).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn policy(interval_months: u32) -> ReviewPolicy {
+        ReviewPolicy {
+            interval_months,
+            deadline_days: 14,
+            overdue_action: OverdueAction::Flag,
+        }
+    }
+
+    #[test]
+    fn test_next_review_at() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        assert_eq!(next_review_at(&policy(0), None, now), None);
+        assert_eq!(next_review_at(&policy(3), None, now), Some(now));
+        let last = Utc.with_ymd_and_hms(2026, 5, 31, 9, 0, 0).unwrap();
+        // Month ends clamp to the last day of the month
+        assert_eq!(
+            next_review_at(&policy(3), Some(last), now),
+            Some(Utc.with_ymd_and_hms(2026, 8, 31, 9, 0, 0).unwrap())
+        );
+        assert_eq!(
+            next_review_at(&policy(1), Some(last), now),
+            Some(Utc.with_ymd_and_hms(2026, 6, 30, 9, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        assert!(parse_setting(ACCESS_REVIEW_INTERVAL_MONTHS, "0").is_ok());
+        assert!(parse_setting(ACCESS_REVIEW_INTERVAL_MONTHS, " 6 ").is_ok());
+        assert!(parse_setting(ACCESS_REVIEW_INTERVAL_MONTHS, "-1").is_err());
+        assert!(parse_setting(ACCESS_REVIEW_DEADLINE_DAYS, "0").is_err());
+        assert!(parse_setting(ACCESS_REVIEW_DEADLINE_DAYS, "30").is_ok());
+        assert!(parse_setting(ACCESS_REVIEW_OVERDUE_ACTION, "suspend").is_ok());
+        assert!(parse_setting(ACCESS_REVIEW_OVERDUE_ACTION, "delete").is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! Account links
+//!
+//! Maps Kubarr users to their accounts in managed apps: a Jellyfin user ID,
+//! a qBittorrent username, a Jellyseerr user ID. Per-user features look the
+//! account up here instead of guessing from usernames. Media requests are
+//! made as the linked request manager account, and media profiles are
+//! written to the linked Jellyfin user.
+//!
+//! Admins set links by hand, or [`discover`] asks the configured apps that
+//! can list their users and links accounts whose name or email matches a
+//! Kubarr user. Discovery never replaces an existing link. Accounts Kubarr
+//! creates on approval are linked by [`super::provisioning`].
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{app_account_link, user};
+use crate::services::integrations::requests::seerr_kind;
+use crate::services::integrations::seerr::SeerrAdapter;
+use crate::services::integrations::{build_context, jellyfin};
+use crate::state::{AppState, DbConn};
+
+/// Link set by an admin
+pub const SOURCE_MANUAL: &str = "manual";
+/// Link found by [`discover`]
+pub const SOURCE_AUTO: &str = "auto";
+/// Link to an account Kubarr created (see [`super::provisioning`])
+pub const SOURCE_PROVISIONED: &str = "provisioned";
+
+/// Apps whose users [`discover`] can list
+pub const DISCOVERABLE_APPS: [&str; 3] = ["jellyfin", "jellyseerr", "overseerr"];
+
+/// A user's account in a managed app
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AccountLink {
+    pub app_name: String,
+    /// The account's ID in the app, or its username where the app has no IDs
+    pub external_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_username: Option<String>,
+    /// "manual", "auto" or "provisioned"
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<app_account_link::Model> for AccountLink {
+    fn from(link: app_account_link::Model) -> Self {
+        Self {
+            app_name: link.app_name,
+            external_id: link.external_id,
+            external_username: link.external_username,
+            source: link.source,
+            updated_at: link.updated_at,
+        }
+    }
+}
+
+/// Body for PUT /api/users/{user_id}/accounts/{app_name}
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetAccountLink {
+    /// The account's ID in the app; for qBittorrent, the username
+    pub external_id: String,
+    #[serde(default)]
+    pub external_username: Option<String>,
+}
+
+/// A link made by [`discover`]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DiscoveredLink {
+    pub user_id: i64,
+    pub username: String,
+    pub app_name: String,
+    pub external_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_username: Option<String>,
+}
+
+/// An app [`discover`] could not ask
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DiscoveryError {
+    pub app_name: String,
+    pub error: String,
+}
+
+/// Response for POST /api/users/accounts/discover
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct DiscoveryReport {
+    pub linked: Vec<DiscoveredLink>,
+    pub errors: Vec<DiscoveryError>,
+}
+
+// ============================================================================
+// Links
+// ============================================================================
+
+/// All of a user's account links
+pub async fn list_links(db: &DbConn, user_id: i64) -> Result<Vec<AccountLink>> {
+    Ok(AppAccountLink::find()
+        .filter(app_account_link::Column::UserId.eq(user_id))
+        .order_by_asc(app_account_link::Column::AppName)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// A user's account in an app
+pub async fn find_link(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+) -> Result<Option<app_account_link::Model>> {
+    Ok(AppAccountLink::find()
+        .filter(app_account_link::Column::UserId.eq(user_id))
+        .filter(app_account_link::Column::AppName.eq(app_name))
+        .one(db)
+        .await?)
+}
+
+/// The link of an app account, whoever it belongs to
+pub async fn find_by_external(
+    db: &DbConn,
+    app_name: &str,
+    external_id: &str,
+) -> Result<Option<app_account_link::Model>> {
+    Ok(AppAccountLink::find()
+        .filter(app_account_link::Column::AppName.eq(app_name))
+        .filter(app_account_link::Column::ExternalId.eq(external_id))
+        .one(db)
+        .await?)
+}
+
+/// Link a user to an app account, replacing their link for that app
+///
+/// Each app account can only be linked to one Kubarr user.
+pub async fn set_link(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    link: &SetAccountLink,
+    source: &str,
+) -> Result<app_account_link::Model> {
+    let app_name = app_name.trim();
+    let external_id = link.external_id.trim();
+    if app_name.is_empty() || external_id.is_empty() {
+        return Err(AppError::BadRequest(
+            "app_name and external_id are required".to_string(),
+        ));
+    }
+    if seerr_kind(app_name).is_some() && external_id.parse::<i64>().is_err() {
+        return Err(AppError::BadRequest(format!(
+            "{} user IDs are numbers",
+            app_name
+        )));
+    }
+    User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if find_by_external(db, app_name, external_id)
+        .await?
+        .is_some_and(|l| l.user_id != user_id)
+    {
+        return Err(AppError::Conflict(format!(
+            "{} account {} is already linked to another user",
+            app_name, external_id
+        )));
+    }
+
+    let now = Utc::now();
+    let external_username = link
+        .external_username
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string);
+    Ok(match find_link(db, user_id, app_name).await? {
+        Some(existing) => {
+            let mut model: app_account_link::ActiveModel = existing.into();
+            model.external_id = Set(external_id.to_string());
+            model.external_username = Set(external_username);
+            model.source = Set(source.to_string());
+            model.updated_at = Set(now);
+            model.update(db).await?
+        }
+        None => {
+            app_account_link::ActiveModel {
+                user_id: Set(user_id),
+                app_name: Set(app_name.to_string()),
+                external_id: Set(external_id.to_string()),
+                external_username: Set(external_username),
+                source: Set(source.to_string()),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?
+        }
+    })
+}
+
+/// Remove a user's link for an app; returns whether there was one
+pub async fn remove_link(db: &DbConn, user_id: i64, app_name: &str) -> Result<bool> {
+    let result = AppAccountLink::delete_many()
+        .filter(app_account_link::Column::UserId.eq(user_id))
+        .filter(app_account_link::Column::AppName.eq(app_name))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+// ============================================================================
+// Discovery
+// ============================================================================
+
+/// A user account as listed by an app
+#[derive(Debug, Clone)]
+pub struct ExternalAccount {
+    pub id: String,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// The user accounts of an app that can list them
+pub async fn list_accounts(state: &AppState, app_name: &str) -> Result<Vec<ExternalAccount>> {
+    let ctx = build_context(state, app_name).await?;
+    if let Some(kind) = seerr_kind(app_name) {
+        return Ok(SeerrAdapter::new(kind)
+            .list_users(&ctx)
+            .await?
+            .into_iter()
+            .map(|u| ExternalAccount {
+                id: u.id.to_string(),
+                name: u.display_name.unwrap_or_default(),
+                email: u.email,
+            })
+            .collect());
+    }
+    Ok(jellyfin::fetch_users(&ctx)
+        .await?
+        .into_iter()
+        .map(|u| ExternalAccount {
+            id: u.id,
+            name: u.name,
+            email: None,
+        })
+        .collect())
+}
+
+/// The Kubarr user an app account belongs to: same name ignoring case, or
+/// same email
+pub fn match_account<'a>(
+    account: &ExternalAccount,
+    users: &'a [user::Model],
+) -> Option<&'a user::Model> {
+    users.iter().find(|u| {
+        (!account.name.is_empty() && u.username.eq_ignore_ascii_case(&account.name))
+            || account
+                .email
+                .as_deref()
+                .is_some_and(|e| u.email.eq_ignore_ascii_case(e))
+    })
+}
+
+/// Link users to the matching accounts of every configured app that can
+/// list its users
+pub async fn discover(state: &AppState) -> Result<DiscoveryReport> {
+    let db = state.get_db().await?;
+    let apps: Vec<String> = AppIntegration::find()
+        .all(&db)
+        .await?
+        .into_iter()
+        .filter(|i| i.enabled && DISCOVERABLE_APPS.contains(&i.app_name.as_str()))
+        .map(|i| i.app_name)
+        .collect();
+    let users = User::find().all(&db).await?;
+
+    let mut report = DiscoveryReport::default();
+    for app_name in apps {
+        let accounts = match list_accounts(state, &app_name).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                report.errors.push(DiscoveryError {
+                    app_name,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let existing = AppAccountLink::find()
+            .filter(app_account_link::Column::AppName.eq(&app_name))
+            .all(&db)
+            .await?;
+        let mut linked_users: Vec<i64> = existing.iter().map(|l| l.user_id).collect();
+
+        for account in accounts {
+            if existing.iter().any(|l| l.external_id == account.id) {
+                continue;
+            }
+            let Some(owner) = match_account(&account, &users) else {
+                continue;
+            };
+            if linked_users.contains(&owner.id) {
+                continue;
+            }
+            let link = SetAccountLink {
+                external_id: account.id,
+                external_username: Some(account.name),
+            };
+            let saved = set_link(&db, owner.id, &app_name, &link, SOURCE_AUTO).await?;
+            linked_users.push(owner.id);
+            report.linked.push(DiscoveredLink {
+                user_id: owner.id,
+                username: owner.username.clone(),
+                app_name: saved.app_name,
+                external_id: saved.external_id,
+                external_username: saved.external_username,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: i64, username: &str, email: &str) -> user::Model {
+        user::Model {
+            id,
+            username: username.to_string(),
+            email: email.to_string(),
+            hashed_password: String::new(),
+            is_active: true,
+            is_approved: true,
+            totp_secret: None,
+            totp_enabled: false,
+            totp_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            is_service_account: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_match_account() {
+        let users = vec![
+            user(1, "alice", "alice@example.com"),
+            user(2, "bob", "bob@example.com"),
+        ];
+        let account = |name: &str, email: Option<&str>| ExternalAccount {
+            id: "x".to_string(),
+            name: name.to_string(),
+            email: email.map(str::to_string),
+        };
+
+        assert_eq!(
+            match_account(&account("Alice", None), &users).unwrap().id,
+            1
+        );
+        assert_eq!(
+            match_account(&account("Robert", Some("BOB@example.com")), &users)
+                .unwrap()
+                .id,
+            2
+        );
+        assert!(match_account(&account("", None), &users).is_none());
+        assert!(match_account(&account("carol", Some("carol@example.com")), &users).is_none());
+    }
+}
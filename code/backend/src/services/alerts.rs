@@ -0,0 +1,903 @@
+//! Unified alert management
+//!
+//! The monitoring checkers (anomaly detection, log alert rules, uptime
+//! monitors, WAN health, hardware sensors and trusted certificate expiry)
+//! each decide on their own when
+//! something is wrong. This module turns their conditions into alerts with a
+//! lifecycle: an alert is `firing` when a condition appears, can be
+//! acknowledged, silenced until a given time or assigned to a user, and is
+//! `resolved` once the checker no longer reports the condition. Every change
+//! is recorded in the alert's history.
+//!
+//! A silenced alert suppresses the checker's notifications. The silence
+//! outlives the alert: if the same condition fires again before the silence
+//! expires, the new alert starts out silenced.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use super::anomaly;
+use super::hardware_sensors::{NodeSensors, SensorStatus};
+use super::log_alerts;
+use super::notification::{NotificationAction, NotificationService};
+use super::scheduler::PeriodicTask;
+use super::trust_store::{self, CertificateStatus};
+use super::uptime;
+use super::wan_health::{self, WanStatus};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::{
+    alert, alert_event, log_alert_rule, trusted_certificate, uptime_check, uptime_monitor,
+};
+
+pub const STATE_FIRING: &str = "firing";
+pub const STATE_ACKED: &str = "acked";
+pub const STATE_SILENCED: &str = "silenced";
+pub const STATE_RESOLVED: &str = "resolved";
+
+/// States of alerts that have not resolved yet
+pub const OPEN_STATES: &[&str] = &[STATE_FIRING, STATE_ACKED, STATE_SILENCED];
+
+pub const SOURCE_METRIC: &str = "metric";
+pub const SOURCE_LOG: &str = "log";
+pub const SOURCE_UPTIME: &str = "uptime";
+pub const SOURCE_WAN: &str = "wan";
+pub const SOURCE_SENSOR: &str = "sensor";
+pub const SOURCE_CERTIFICATE: &str = "certificate";
+
+pub const SOURCES: &[&str] = &[
+    SOURCE_METRIC,
+    SOURCE_LOG,
+    SOURCE_UPTIME,
+    SOURCE_WAN,
+    SOURCE_SENSOR,
+    SOURCE_CERTIFICATE,
+];
+
+/// Longest allowed silence (30 days)
+pub const MAX_SILENCE_MINUTES: i64 = 43200;
+
+const DEFAULT_LIST_LIMIT: u64 = 100;
+const MAX_LIST_LIMIT: u64 = 500;
+const SYNC_INTERVAL_SECS: u64 = 60;
+/// Resolved alerts are kept this long
+const RETENTION_DAYS: i64 = 30;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// A condition currently reported by a checker
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertCondition {
+    pub fingerprint: String,
+    pub name: String,
+    pub app_name: Option<String>,
+    pub severity: String,
+    pub message: String,
+    pub link: String,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct AlertListQuery {
+    /// "open" (default: firing, acked or silenced), "all", or a single state
+    pub state: Option<String>,
+    /// Only alerts from this source
+    pub source: Option<String>,
+    /// Only alerts assigned to this user
+    pub assigned_to: Option<i64>,
+    /// Maximum number of alerts (default 100, max 500)
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct AcknowledgeAlertRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SilenceAlertRequest {
+    /// How long to silence the alert (1 minute to 30 days)
+    pub duration_minutes: i64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct AssignAlertRequest {
+    /// User to assign the alert to (None to unassign)
+    pub user_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AlertUser {
+    pub id: i64,
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AlertInfo {
+    pub id: i64,
+    pub fingerprint: String,
+    pub source: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    pub severity: String,
+    pub message: String,
+    pub link: String,
+    pub state: String,
+    pub started_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub acked_by: Option<AlertUser>,
+    pub acked_at: Option<DateTime<Utc>>,
+    pub silenced_until: Option<DateTime<Utc>>,
+    pub silenced_by: Option<AlertUser>,
+    pub assigned_to: Option<AlertUser>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AlertHistoryEntry {
+    pub action: String,
+    /// None for changes made by the checkers
+    pub user: Option<AlertUser>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response for GET /api/alerts/{id}
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AlertDetail {
+    #[serde(flatten)]
+    pub alert: AlertInfo,
+    /// Oldest first
+    pub history: Vec<AlertHistoryEntry>,
+}
+
+/// `"<source>:<key>"`, stable across checks of the same condition
+pub fn fingerprint(source: &str, key: impl std::fmt::Display) -> String {
+    format!("{}:{}", source, key)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn usernames(db: &DatabaseConnection, ids: HashSet<i64>) -> Result<HashMap<i64, String>> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(User::find()
+        .filter(user::Column::Id.is_in(ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|u| (u.id, u.username))
+        .collect())
+}
+
+fn alert_user(names: &HashMap<i64, String>, id: Option<i64>) -> Option<AlertUser> {
+    id.and_then(|id| {
+        names.get(&id).map(|username| AlertUser {
+            id,
+            username: username.clone(),
+        })
+    })
+}
+
+async fn to_infos(db: &DatabaseConnection, alerts: Vec<alert::Model>) -> Result<Vec<AlertInfo>> {
+    let ids = alerts
+        .iter()
+        .flat_map(|a| [a.acked_by, a.silenced_by, a.assigned_to])
+        .flatten()
+        .collect();
+    let names = usernames(db, ids).await?;
+    Ok(alerts
+        .into_iter()
+        .map(|a| AlertInfo {
+            acked_by: alert_user(&names, a.acked_by),
+            silenced_by: alert_user(&names, a.silenced_by),
+            assigned_to: alert_user(&names, a.assigned_to),
+            id: a.id,
+            fingerprint: a.fingerprint,
+            source: a.source,
+            name: a.name,
+            app_name: a.app_name,
+            severity: a.severity,
+            message: a.message,
+            link: a.link,
+            state: a.state,
+            started_at: a.started_at,
+            last_seen_at: a.last_seen_at,
+            resolved_at: a.resolved_at,
+            acked_at: a.acked_at,
+            silenced_until: a.silenced_until,
+        })
+        .collect())
+}
+
+async fn find_alert(db: &DatabaseConnection, id: i64) -> Result<alert::Model> {
+    Alert::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Alert {} not found", id)))
+}
+
+fn ensure_open(alert: &alert::Model) -> Result<()> {
+    if alert.state == STATE_RESOLVED {
+        return Err(AppError::BadRequest(format!(
+            "Alert {} is already resolved",
+            alert.id
+        )));
+    }
+    Ok(())
+}
+
+fn normalize_comment(comment: Option<String>) -> Option<String> {
+    comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+}
+
+async fn record_event(
+    db: &DatabaseConnection,
+    alert_id: i64,
+    action: &str,
+    user_id: Option<i64>,
+    detail: Option<String>,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    alert_event::ActiveModel {
+        alert_id: Set(alert_id),
+        action: Set(action.to_string()),
+        user_id: Set(user_id),
+        detail: Set(detail),
+        created_at: Set(at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// State to return to when a silence ends
+fn unsilenced_state(alert: &alert::Model) -> &'static str {
+    if alert.acked_at.is_some() {
+        STATE_ACKED
+    } else {
+        STATE_FIRING
+    }
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+pub async fn list_alerts(db: &DatabaseConnection, query: AlertListQuery) -> Result<Vec<AlertInfo>> {
+    let mut select = Alert::find();
+    match query.state.as_deref().unwrap_or("open") {
+        "open" => select = select.filter(alert::Column::State.is_in(OPEN_STATES.to_vec())),
+        "all" => {}
+        state if OPEN_STATES.contains(&state) || state == STATE_RESOLVED => {
+            select = select.filter(alert::Column::State.eq(state))
+        }
+        state => {
+            return Err(AppError::BadRequest(format!(
+                "Invalid state '{}', expected open, all, firing, acked, silenced or resolved",
+                state
+            )))
+        }
+    }
+    if let Some(source) = query.source.as_deref() {
+        if !SOURCES.contains(&source) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid source '{}', expected one of: {}",
+                source,
+                SOURCES.join(", ")
+            )));
+        }
+        select = select.filter(alert::Column::Source.eq(source));
+    }
+    if let Some(user_id) = query.assigned_to {
+        select = select.filter(alert::Column::AssignedTo.eq(user_id));
+    }
+
+    let alerts = select
+        .order_by_desc(alert::Column::StartedAt)
+        .order_by_desc(alert::Column::Id)
+        .limit(
+            query
+                .limit
+                .unwrap_or(DEFAULT_LIST_LIMIT)
+                .clamp(1, MAX_LIST_LIMIT),
+        )
+        .all(db)
+        .await?;
+    to_infos(db, alerts).await
+}
+
+pub async fn get_alert(db: &DatabaseConnection, id: i64) -> Result<AlertDetail> {
+    let model = find_alert(db, id).await?;
+    let events = AlertEvent::find()
+        .filter(alert_event::Column::AlertId.eq(id))
+        .order_by_asc(alert_event::Column::CreatedAt)
+        .order_by_asc(alert_event::Column::Id)
+        .all(db)
+        .await?;
+    let ids = events.iter().filter_map(|e| e.user_id).collect();
+    let names = usernames(db, ids).await?;
+    let history = events
+        .into_iter()
+        .map(|e| AlertHistoryEntry {
+            user: alert_user(&names, e.user_id),
+            action: e.action,
+            detail: e.detail,
+            created_at: e.created_at,
+        })
+        .collect();
+
+    let alert = to_infos(db, vec![model]).await?.remove(0);
+    Ok(AlertDetail { alert, history })
+}
+
+/// Whether the condition is silenced, i.e. its notifications are suppressed
+pub async fn is_silenced(
+    db: &DatabaseConnection,
+    fingerprint: &str,
+    now: DateTime<Utc>,
+) -> Result<bool> {
+    Ok(Alert::find()
+        .filter(alert::Column::Fingerprint.eq(fingerprint))
+        .filter(alert::Column::SilencedUntil.gt(now))
+        .one(db)
+        .await?
+        .is_some())
+}
+
+// ============================================================================
+// Actions
+// ============================================================================
+
+pub async fn acknowledge(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+    request: AcknowledgeAlertRequest,
+) -> Result<AlertDetail> {
+    let existing = find_alert(db, id).await?;
+    ensure_open(&existing)?;
+
+    let now = Utc::now();
+    let mut active: alert::ActiveModel = existing.clone().into();
+    if existing.state == STATE_FIRING {
+        active.state = Set(STATE_ACKED.to_string());
+    }
+    active.acked_by = Set(Some(user_id));
+    active.acked_at = Set(Some(now));
+    active.updated_at = Set(now);
+    active.update(db).await?;
+    record_event(
+        db,
+        id,
+        "acked",
+        Some(user_id),
+        normalize_comment(request.comment),
+        now,
+    )
+    .await?;
+    get_alert(db, id).await
+}
+
+pub async fn silence(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+    request: SilenceAlertRequest,
+) -> Result<AlertDetail> {
+    if !(1..=MAX_SILENCE_MINUTES).contains(&request.duration_minutes) {
+        return Err(AppError::BadRequest(format!(
+            "Silence duration must be between 1 and {} minutes",
+            MAX_SILENCE_MINUTES
+        )));
+    }
+    let existing = find_alert(db, id).await?;
+    ensure_open(&existing)?;
+
+    let now = Utc::now();
+    let until = now + chrono::Duration::minutes(request.duration_minutes);
+    let mut active: alert::ActiveModel = existing.into();
+    active.state = Set(STATE_SILENCED.to_string());
+    active.silenced_until = Set(Some(until));
+    active.silenced_by = Set(Some(user_id));
+    active.updated_at = Set(now);
+    active.update(db).await?;
+
+    let detail = match normalize_comment(request.comment) {
+        Some(comment) => format!("until {} — {}", until.to_rfc3339(), comment),
+        None => format!("until {}", until.to_rfc3339()),
+    };
+    record_event(db, id, "silenced", Some(user_id), Some(detail), now).await?;
+    get_alert(db, id).await
+}
+
+pub async fn unsilence(db: &DatabaseConnection, id: i64, user_id: i64) -> Result<AlertDetail> {
+    let existing = find_alert(db, id).await?;
+    if existing.state != STATE_SILENCED {
+        return Err(AppError::BadRequest(format!(
+            "Alert {} is not silenced",
+            existing.id
+        )));
+    }
+
+    let now = Utc::now();
+    let state = unsilenced_state(&existing);
+    let mut active: alert::ActiveModel = existing.into();
+    active.state = Set(state.to_string());
+    active.silenced_until = Set(None);
+    active.silenced_by = Set(None);
+    active.updated_at = Set(now);
+    active.update(db).await?;
+    record_event(db, id, "unsilenced", Some(user_id), None, now).await?;
+    get_alert(db, id).await
+}
+
+/// Assign an alert and notify the assignee (unless they assigned themselves)
+pub async fn assign(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    id: i64,
+    user_id: i64,
+    request: AssignAlertRequest,
+) -> Result<AlertDetail> {
+    let existing = find_alert(db, id).await?;
+    let assignee =
+        match request.user_id {
+            Some(assignee_id) => Some(User::find_by_id(assignee_id).one(db).await?.ok_or_else(
+                || AppError::BadRequest(format!("User {} does not exist", assignee_id)),
+            )?),
+            None => None,
+        };
+    if assignee.as_ref().map(|u| u.id) == existing.assigned_to {
+        return get_alert(db, id).await;
+    }
+
+    let now = Utc::now();
+    let mut active: alert::ActiveModel = existing.clone().into();
+    active.assigned_to = Set(assignee.as_ref().map(|u| u.id));
+    active.updated_at = Set(now);
+    active.update(db).await?;
+
+    match assignee {
+        Some(assignee) => {
+            record_event(
+                db,
+                id,
+                "assigned",
+                Some(user_id),
+                Some(assignee.username.clone()),
+                now,
+            )
+            .await?;
+            if assignee.id != user_id {
+                notification
+                    .notify_event_with_actions(
+                        &AuditAction::AlertAssigned,
+                        Some(assignee.id),
+                        None,
+                        Some(&existing.message),
+                        &[NotificationAction::AcknowledgeAlert(id)],
+                    )
+                    .await?;
+            }
+        }
+        None => record_event(db, id, "unassigned", Some(user_id), None, now).await?,
+    }
+    get_alert(db, id).await
+}
+
+// ============================================================================
+// Reconciliation
+// ============================================================================
+
+/// Bring the alerts of one source in line with the conditions it reports
+///
+/// New conditions fire, known ones are refreshed and open alerts whose
+/// condition is gone resolve. A new alert inherits an unexpired silence from
+/// an earlier alert with the same fingerprint.
+pub async fn reconcile(
+    db: &DatabaseConnection,
+    source: &str,
+    conditions: Vec<AlertCondition>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut open: HashMap<String, alert::Model> = Alert::find()
+        .filter(alert::Column::Source.eq(source))
+        .filter(alert::Column::State.is_in(OPEN_STATES.to_vec()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (a.fingerprint.clone(), a))
+        .collect();
+
+    for condition in conditions {
+        if let Some(existing) = open.remove(&condition.fingerprint) {
+            let mut active: alert::ActiveModel = existing.into();
+            active.name = Set(condition.name);
+            active.app_name = Set(condition.app_name);
+            active.severity = Set(condition.severity);
+            active.message = Set(condition.message);
+            active.link = Set(condition.link);
+            active.last_seen_at = Set(now);
+            active.updated_at = Set(now);
+            active.update(db).await?;
+            continue;
+        }
+
+        let silence = Alert::find()
+            .filter(alert::Column::Fingerprint.eq(&condition.fingerprint))
+            .filter(alert::Column::SilencedUntil.gt(now))
+            .one(db)
+            .await?;
+        let state = if silence.is_some() {
+            STATE_SILENCED
+        } else {
+            STATE_FIRING
+        };
+        let model = alert::ActiveModel {
+            fingerprint: Set(condition.fingerprint),
+            source: Set(source.to_string()),
+            name: Set(condition.name),
+            app_name: Set(condition.app_name),
+            severity: Set(condition.severity),
+            message: Set(condition.message.clone()),
+            link: Set(condition.link),
+            state: Set(state.to_string()),
+            started_at: Set(now),
+            last_seen_at: Set(now),
+            resolved_at: Set(None),
+            acked_by: Set(None),
+            acked_at: Set(None),
+            silenced_until: Set(silence.as_ref().and_then(|s| s.silenced_until)),
+            silenced_by: Set(silence.as_ref().and_then(|s| s.silenced_by)),
+            assigned_to: Set(None),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        record_event(db, model.id, "fired", None, Some(condition.message), now).await?;
+    }
+
+    for (_, gone) in open {
+        let id = gone.id;
+        let mut active: alert::ActiveModel = gone.into();
+        active.state = Set(STATE_RESOLVED.to_string());
+        active.resolved_at = Set(Some(now));
+        active.updated_at = Set(now);
+        active.update(db).await?;
+        record_event(db, id, "resolved", None, None, now).await?;
+    }
+    Ok(())
+}
+
+/// Return silenced alerts whose silence has ended to firing (or acked)
+pub async fn expire_silences(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<()> {
+    let expired = Alert::find()
+        .filter(alert::Column::State.eq(STATE_SILENCED))
+        .filter(alert::Column::SilencedUntil.lte(now))
+        .all(db)
+        .await?;
+    for alert in expired {
+        let id = alert.id;
+        let state = unsilenced_state(&alert);
+        let mut active: alert::ActiveModel = alert.into();
+        active.state = Set(state.to_string());
+        active.updated_at = Set(now);
+        active.update(db).await?;
+        record_event(db, id, "silence_expired", None, None, now).await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+/// Anomalies still within their cooldown, one per app and metric
+pub async fn metric_conditions(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<AlertCondition>> {
+    let mut seen = HashSet::new();
+    // Newest first, so the latest anomaly of each metric wins
+    Ok(anomaly::active_anomalies(db, now)
+        .await?
+        .into_iter()
+        .filter(|a| seen.insert((a.app_name.clone(), a.metric.clone())))
+        .map(|a| AlertCondition {
+            fingerprint: fingerprint(SOURCE_METRIC, format!("{}/{}", a.app_name, a.metric)),
+            name: format!("{} {} anomaly", a.app_name, a.metric),
+            message: a.summary(),
+            app_name: Some(a.app_name),
+            severity: "warning".to_string(),
+            link: a.link,
+        })
+        .collect())
+}
+
+pub async fn log_conditions(db: &DatabaseConnection) -> Result<Vec<AlertCondition>> {
+    Ok(LogAlertRule::find()
+        .filter(log_alert_rule::Column::Enabled.eq(true))
+        .filter(log_alert_rule::Column::State.eq(log_alerts::STATE_FIRING))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|rule| AlertCondition {
+            fingerprint: fingerprint(SOURCE_LOG, rule.id),
+            message: log_alerts::describe(&rule),
+            name: rule.name,
+            app_name: rule.app_name,
+            severity: rule.severity,
+            link: "/logs".to_string(),
+        })
+        .collect())
+}
+
+pub async fn uptime_conditions(db: &DatabaseConnection) -> Result<Vec<AlertCondition>> {
+    let monitors = UptimeMonitor::find()
+        .filter(uptime_monitor::Column::Enabled.eq(true))
+        .filter(uptime_monitor::Column::Status.eq(uptime::STATUS_DOWN))
+        .all(db)
+        .await?;
+
+    let mut conditions = Vec::with_capacity(monitors.len());
+    for monitor in monitors {
+        let error = UptimeCheck::find()
+            .filter(uptime_check::Column::MonitorId.eq(monitor.id))
+            .order_by_desc(uptime_check::Column::CheckedAt)
+            .one(db)
+            .await?
+            .and_then(|c| c.error);
+        let message = match error {
+            Some(error) => format!("{} ({}) is down: {}", monitor.name, monitor.target, error),
+            None => format!("{} ({}) is down", monitor.name, monitor.target),
+        };
+        conditions.push(AlertCondition {
+            fingerprint: fingerprint(SOURCE_UPTIME, monitor.id),
+            name: monitor.name,
+            app_name: None,
+            severity: "critical".to_string(),
+            message,
+            link: "/resources".to_string(),
+        });
+    }
+    Ok(conditions)
+}
+
+pub async fn wan_conditions(db: &DatabaseConnection) -> Result<Vec<AlertCondition>> {
+    let (status, reason) = wan_health::latest_status(db).await?;
+    let (severity, word) = match status {
+        WanStatus::Down => ("critical", "down"),
+        WanStatus::Degraded => ("warning", "degraded"),
+        WanStatus::Healthy | WanStatus::Unknown => return Ok(Vec::new()),
+    };
+    Ok(vec![AlertCondition {
+        fingerprint: fingerprint(SOURCE_WAN, "internet"),
+        name: "WAN connectivity".to_string(),
+        app_name: None,
+        severity: severity.to_string(),
+        message: format!("WAN is {}: {}", word, reason.unwrap_or_default()),
+        link: "/networking".to_string(),
+    }])
+}
+
+/// Trusted certificates that expired or expire soon
+pub async fn certificate_conditions(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<AlertCondition>> {
+    let warn_after = now + chrono::Duration::days(trust_store::EXPIRY_WARNING_DAYS);
+    Ok(TrustedCertificate::find()
+        .filter(trusted_certificate::Column::NotAfter.lte(warn_after))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|cert| {
+            let date = cert.not_after.format("%Y-%m-%d");
+            let (severity, message) = match trust_store::status(cert.not_after, now) {
+                CertificateStatus::Expired => (
+                    "critical",
+                    format!("Trusted certificate {} expired on {}", cert.subject, date),
+                ),
+                _ => (
+                    "warning",
+                    format!("Trusted certificate {} expires on {}", cert.subject, date),
+                ),
+            };
+            AlertCondition {
+                fingerprint: fingerprint(SOURCE_CERTIFICATE, &cert.fingerprint),
+                name: format!("{} certificate", cert.name),
+                app_name: None,
+                severity: severity.to_string(),
+                message,
+                link: "/settings".to_string(),
+            }
+        })
+        .collect())
+}
+
+/// Sensors at or above their warning temperature
+pub fn sensor_conditions(nodes: &[NodeSensors]) -> Vec<AlertCondition> {
+    nodes
+        .iter()
+        .flat_map(|node| {
+            node.sensors
+                .iter()
+                .filter(|s| s.status != SensorStatus::Ok)
+                .map(|s| {
+                    let severity = match s.status {
+                        SensorStatus::Critical => "critical",
+                        _ => "warning",
+                    };
+                    AlertCondition {
+                        fingerprint: sensor_fingerprint(&node.node, &s.chip, &s.sensor),
+                        name: format!("{} {} temperature", node.node, s.display_name()),
+                        app_name: None,
+                        severity: severity.to_string(),
+                        message: format!(
+                            "{} {} at {:.1}°C",
+                            node.node,
+                            s.display_name(),
+                            s.temperature_celsius
+                        ),
+                        link: "/resources".to_string(),
+                    }
+                })
+        })
+        .collect()
+}
+
+pub fn sensor_fingerprint(node: &str, chip: &str, sensor: &str) -> String {
+    fingerprint(SOURCE_SENSOR, format!("{}/{}/{}", node, chip, sensor))
+}
+
+/// Reconcile all sources whose state is stored in the database
+///
+/// Sensor readings are not stored; the sensor checker reconciles them itself.
+pub async fn sync(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<()> {
+    expire_silences(db, now).await?;
+    reconcile(db, SOURCE_METRIC, metric_conditions(db, now).await?, now).await?;
+    reconcile(db, SOURCE_LOG, log_conditions(db).await?, now).await?;
+    reconcile(db, SOURCE_UPTIME, uptime_conditions(db).await?, now).await?;
+    reconcile(db, SOURCE_WAN, wan_conditions(db).await?, now).await?;
+    reconcile(
+        db,
+        SOURCE_CERTIFICATE,
+        certificate_conditions(db, now).await?,
+        now,
+    )
+    .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Keeps alerts in sync with the checkers and prunes old resolved alerts
+pub struct AlertSyncTask;
+
+#[async_trait]
+impl PeriodicTask for AlertSyncTask {
+    fn name(&self) -> &'static str {
+        "alerts"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(SYNC_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        sync(db, now).await?;
+
+        Alert::delete_many()
+            .filter(alert::Column::State.eq(STATE_RESOLVED))
+            .filter(alert::Column::ResolvedAt.lt(now - chrono::Duration::days(RETENTION_DAYS)))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::hardware_sensors::{SensorKind, SensorReading, SensorThresholds};
+
+    fn reading(sensor: &str, temperature: f64, status: SensorStatus) -> SensorReading {
+        SensorReading {
+            chip: "platform_coretemp_0".to_string(),
+            chip_name: Some("coretemp".to_string()),
+            sensor: sensor.to_string(),
+            label: Some("Core 0".to_string()),
+            kind: SensorKind::Cpu,
+            temperature_celsius: temperature,
+            device_critical_celsius: None,
+            status,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(SOURCE_UPTIME, 3), "uptime:3");
+        assert_eq!(
+            sensor_fingerprint("nuc", "nvme_nvme0", "temp1"),
+            "sensor:nuc/nvme_nvme0/temp1"
+        );
+    }
+
+    #[test]
+    fn test_sensor_conditions() {
+        let nodes = vec![NodeSensors {
+            node: "nuc".to_string(),
+            available: true,
+            status: SensorStatus::Critical,
+            hottest_cpu_celsius: Some(95.0),
+            hottest_disk_celsius: None,
+            thresholds: SensorThresholds::default(),
+            sensors: vec![
+                reading("temp2", 95.0, SensorStatus::Critical),
+                reading("temp3", 84.0, SensorStatus::Warning),
+                reading("temp4", 50.0, SensorStatus::Ok),
+            ],
+        }];
+
+        let conditions = sensor_conditions(&nodes);
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(
+            conditions[0].fingerprint,
+            "sensor:nuc/platform_coretemp_0/temp2"
+        );
+        assert_eq!(conditions[0].severity, "critical");
+        assert_eq!(conditions[0].message, "nuc CPU Core 0 at 95.0°C");
+        assert_eq!(conditions[1].severity, "warning");
+    }
+
+    #[test]
+    fn test_unsilenced_state() {
+        let now = Utc::now();
+        let mut alert = alert::Model {
+            id: 1,
+            fingerprint: "wan:internet".to_string(),
+            source: SOURCE_WAN.to_string(),
+            name: "WAN connectivity".to_string(),
+            app_name: None,
+            severity: "critical".to_string(),
+            message: "WAN is down".to_string(),
+            link: "/networking".to_string(),
+            state: STATE_SILENCED.to_string(),
+            started_at: now,
+            last_seen_at: now,
+            resolved_at: None,
+            acked_by: None,
+            acked_at: None,
+            silenced_until: Some(now),
+            silenced_by: None,
+            assigned_to: None,
+            updated_at: now,
+        };
+        assert_eq!(unsilenced_state(&alert), STATE_FIRING);
+        alert.acked_at = Some(now);
+        assert_eq!(unsilenced_state(&alert), STATE_ACKED);
+    }
+}
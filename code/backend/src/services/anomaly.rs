@@ -0,0 +1,697 @@
+//! Anomaly detection on app metrics
+//!
+//! Every 15 minutes the last hour of each app's CPU usage, memory working set
+//! and container restarts is compared against a rolling baseline built from
+//! the hourly values of the preceding week. A value outside the band
+//! `mean ± threshold × stddev` is recorded in `metric_anomalies` and sent as
+//! a `metric_anomaly` notification with a link to the affected time window.
+//!
+//! The threshold depends on the app's sensitivity (`anomaly_sensitivities`,
+//! falling back to the `anomaly_default_sensitivity` setting). Restarts are
+//! only flagged when above the band; a drop in restarts is never a problem.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use super::alerts;
+use super::links;
+use super::network_broadcaster::is_excluded_namespace;
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use super::victoriametrics::query_vm_range;
+use crate::endpoints::settings::{get_setting_bool, get_setting_value};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::{anomaly_sensitivity, metric_anomaly, pod_restart_event};
+
+pub const METRIC_CPU: &str = "cpu";
+pub const METRIC_MEMORY: &str = "memory";
+pub const METRIC_RESTARTS: &str = "restarts";
+
+pub const DIRECTION_ABOVE: &str = "above";
+pub const DIRECTION_BELOW: &str = "below";
+
+/// Length of the baseline (one week of hourly values)
+const BASELINE_HOURS: i64 = 7 * 24;
+/// Hourly values required before an app is analyzed
+const MIN_BASELINE_POINTS: usize = 24;
+const ANALYSIS_INTERVAL_SECS: u64 = 15 * 60;
+/// The same app and metric is not flagged again within this period
+const COOLDOWN_HOURS: i64 = 6;
+const RETENTION_DAYS: i64 = 30;
+
+// ============================================================================
+// Sensitivity
+// ============================================================================
+
+/// How far a value must deviate from the baseline to be flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Sensitivity {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+impl Sensitivity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Sensitivity::Off),
+            "low" => Some(Sensitivity::Low),
+            "medium" => Some(Sensitivity::Medium),
+            "high" => Some(Sensitivity::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sensitivity::Off => "off",
+            Sensitivity::Low => "low",
+            Sensitivity::Medium => "medium",
+            Sensitivity::High => "high",
+        }
+    }
+
+    /// Number of standard deviations outside which a value is anomalous
+    pub fn z_threshold(&self) -> Option<f64> {
+        match self {
+            Sensitivity::Off => None,
+            Sensitivity::Low => Some(4.0),
+            Sensitivity::Medium => Some(3.0),
+            Sensitivity::High => Some(2.0),
+        }
+    }
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// A detected deviation
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MetricAnomalyInfo {
+    pub id: i64,
+    pub app_name: String,
+    /// "cpu" (cores), "memory" (bytes) or "restarts" (per hour)
+    pub metric: String,
+    /// "above" or "below" the baseline band
+    pub direction: String,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_stddev: f64,
+    pub z_score: f64,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub detected_at: DateTime<Utc>,
+    /// UI link to the app's metrics around the affected window
+    pub link: String,
+}
+
+/// Response for GET /api/monitoring/anomalies
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AnomalyReport {
+    pub window_hours: i64,
+    /// Newest first
+    pub anomalies: Vec<MetricAnomalyInfo>,
+}
+
+/// Sensitivity override for one app
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AppSensitivity {
+    pub app_name: String,
+    pub sensitivity: Sensitivity,
+}
+
+/// Response for GET /api/monitoring/anomalies/sensitivity
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SensitivitySettings {
+    pub default_sensitivity: Sensitivity,
+    pub apps: Vec<AppSensitivity>,
+}
+
+// ============================================================================
+// Baselines
+// ============================================================================
+
+/// Rolling mean and standard deviation of a metric
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Baseline {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+/// Compute a baseline; None when there is too little history
+pub fn baseline(values: &[f64]) -> Option<Baseline> {
+    if values.len() < MIN_BASELINE_POINTS {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    Some(Baseline {
+        mean,
+        stddev: variance.sqrt(),
+    })
+}
+
+/// Smallest standard deviation used, so flat series don't flag noise
+fn stddev_floor(metric: &str, mean: f64) -> f64 {
+    // cores, bytes, restarts per hour
+    let absolute: f64 = match metric {
+        METRIC_CPU => 0.05,
+        METRIC_MEMORY => 32.0 * 1024.0 * 1024.0,
+        _ => 0.5,
+    };
+    absolute.max(mean.abs() * 0.1)
+}
+
+/// A value outside the baseline band
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    pub baseline: Baseline,
+    pub z_score: f64,
+    pub direction: &'static str,
+}
+
+/// Compare the current value against the history of a metric
+pub fn detect(
+    metric: &str,
+    history: &[f64],
+    current: f64,
+    sensitivity: Sensitivity,
+) -> Option<Deviation> {
+    let threshold = sensitivity.z_threshold()?;
+    let baseline = baseline(history)?;
+    let stddev = baseline.stddev.max(stddev_floor(metric, baseline.mean));
+    let z_score = (current - baseline.mean) / stddev;
+
+    let direction = if z_score > threshold {
+        DIRECTION_ABOVE
+    } else if z_score < -threshold && metric != METRIC_RESTARTS {
+        DIRECTION_BELOW
+    } else {
+        return None;
+    };
+
+    Some(Deviation {
+        baseline,
+        z_score: (z_score * 100.0).round() / 100.0,
+        direction,
+    })
+}
+
+// ============================================================================
+// Data Collection
+// ============================================================================
+
+/// Hourly values per app, oldest first, as (unix timestamp, value)
+type AppSeries = HashMap<String, Vec<(i64, f64)>>;
+
+/// Read hourly per-namespace series from VictoriaMetrics
+async fn fetch_vm_series(query: &str, now: DateTime<Utc>) -> AppSeries {
+    let end = now.timestamp() as f64;
+    let start = end - (BASELINE_HOURS * 3600) as f64;
+
+    let mut series = AppSeries::new();
+    for result in query_vm_range(query, start, end, "1h").await {
+        let Some(namespace) = result["metric"]["namespace"].as_str() else {
+            continue;
+        };
+        if is_excluded_namespace(namespace) {
+            continue;
+        }
+        let points = result["values"]
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| {
+                        Some((v[0].as_f64()? as i64, v[1].as_str()?.parse::<f64>().ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        series.insert(namespace.to_string(), points);
+    }
+    series
+}
+
+/// Bucket recorded container restarts into hourly counts per app
+///
+/// The last bucket covers the hour up to `now`.
+async fn restart_series(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<AppSeries> {
+    let start = now - chrono::Duration::hours(BASELINE_HOURS + 1);
+    let events = PodRestartEvent::find()
+        .filter(pod_restart_event::Column::OccurredAt.gt(start))
+        .filter(pod_restart_event::Column::OccurredAt.lte(now))
+        .all(db)
+        .await?;
+
+    let buckets = (BASELINE_HOURS + 1) as usize;
+    let mut counts: HashMap<String, Vec<f64>> = HashMap::new();
+    for event in events {
+        let hours_ago = ((now - event.occurred_at).num_seconds() / 3600) as usize;
+        let index = buckets - 1 - hours_ago.min(buckets - 1);
+        counts
+            .entry(event.app_name)
+            .or_insert_with(|| vec![0.0; buckets])[index] += event.restarts as f64;
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|(app, values)| {
+            let points = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let hours_ago = (buckets - 1 - i) as i64;
+                    ((now - chrono::Duration::hours(hours_ago)).timestamp(), v)
+                })
+                .collect();
+            (app, points)
+        })
+        .collect())
+}
+
+// ============================================================================
+// Analysis
+// ============================================================================
+
+/// UI link to an app's metrics for a time window
+pub fn window_link(app_name: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!(
+        "/resources?app={}&from={}&to={}",
+        app_name,
+        start.timestamp(),
+        end.timestamp()
+    )
+}
+
+fn format_value(metric: &str, value: f64) -> String {
+    match metric {
+        METRIC_CPU => format!("{:.2} cores", value),
+        METRIC_MEMORY => format!("{:.0} MiB", value / (1024.0 * 1024.0)),
+        _ => format!("{:.0} restarts/h", value),
+    }
+}
+
+fn metric_label(metric: &str) -> &'static str {
+    match metric {
+        METRIC_CPU => "CPU usage",
+        METRIC_MEMORY => "memory usage",
+        _ => "container restarts",
+    }
+}
+
+/// One-line description, e.g. "sonarr CPU usage is 1.20 cores, above its
+/// baseline of 0.30 cores"
+fn summary(app_name: &str, metric: &str, value: f64, direction: &str, mean: f64) -> String {
+    format!(
+        "{} {} is {}, {} its baseline of {}",
+        app_name,
+        metric_label(metric),
+        format_value(metric, value),
+        direction,
+        format_value(metric, mean)
+    )
+}
+
+impl MetricAnomalyInfo {
+    pub fn summary(&self) -> String {
+        summary(
+            &self.app_name,
+            &self.metric,
+            self.value,
+            &self.direction,
+            self.baseline_mean,
+        )
+    }
+}
+
+fn to_info(model: metric_anomaly::Model) -> MetricAnomalyInfo {
+    MetricAnomalyInfo {
+        link: window_link(&model.app_name, model.window_start, model.window_end),
+        id: model.id,
+        app_name: model.app_name,
+        metric: model.metric,
+        direction: model.direction,
+        value: model.value,
+        baseline_mean: model.baseline_mean,
+        baseline_stddev: model.baseline_stddev,
+        z_score: model.z_score,
+        window_start: model.window_start,
+        window_end: model.window_end,
+        detected_at: model.detected_at,
+    }
+}
+
+async fn default_sensitivity(db: &DatabaseConnection) -> Result<Sensitivity> {
+    Ok(get_setting_value(db, "anomaly_default_sensitivity")
+        .await?
+        .and_then(|v| Sensitivity::parse(&v))
+        .unwrap_or(Sensitivity::Medium))
+}
+
+/// Check one metric of every app and record new anomalies
+async fn analyze_metric(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    metric: &str,
+    series: AppSeries,
+    sensitivities: &HashMap<String, Sensitivity>,
+    default: Sensitivity,
+    now: DateTime<Utc>,
+) -> Result<Vec<metric_anomaly::Model>> {
+    let mut recorded = Vec::new();
+    let cooldown_start = now - chrono::Duration::hours(COOLDOWN_HOURS);
+
+    // Sorted for deterministic notification order
+    let series: BTreeMap<String, Vec<(i64, f64)>> = series.into_iter().collect();
+    for (app_name, points) in series {
+        let Some(((last_ts, current), history)) = points.split_last() else {
+            continue;
+        };
+        let history: Vec<f64> = history.iter().map(|(_, v)| *v).collect();
+        let sensitivity = sensitivities.get(&app_name).copied().unwrap_or(default);
+        let Some(deviation) = detect(metric, &history, *current, sensitivity) else {
+            continue;
+        };
+
+        let recent = MetricAnomaly::find()
+            .filter(metric_anomaly::Column::AppName.eq(&app_name))
+            .filter(metric_anomaly::Column::Metric.eq(metric))
+            .filter(metric_anomaly::Column::DetectedAt.gte(cooldown_start))
+            .one(db)
+            .await?;
+        if recent.is_some() {
+            continue;
+        }
+
+        let window_end = DateTime::from_timestamp(*last_ts, 0).unwrap_or(now);
+        let window_start = window_end - chrono::Duration::hours(1);
+        let model = metric_anomaly::ActiveModel {
+            app_name: Set(app_name.clone()),
+            metric: Set(metric.to_string()),
+            direction: Set(deviation.direction.to_string()),
+            value: Set(*current),
+            baseline_mean: Set(deviation.baseline.mean),
+            baseline_stddev: Set(deviation.baseline.stddev),
+            z_score: Set(deviation.z_score),
+            window_start: Set(window_start),
+            window_end: Set(window_end),
+            detected_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        let detail = format!(
+            "{} (z = {:.1}) — {}",
+            summary(
+                &app_name,
+                metric,
+                *current,
+                deviation.direction,
+                deviation.baseline.mean
+            ),
+            deviation.z_score,
+            links::absolute(&window_link(&app_name, window_start, window_end))
+        );
+        tracing::warn!(app = %app_name, metric, "{}", detail);
+        let fingerprint =
+            alerts::fingerprint(alerts::SOURCE_METRIC, format!("{}/{}", app_name, metric));
+        if !alerts::is_silenced(db, &fingerprint, now).await? {
+            notification
+                .notify_event(&AuditAction::MetricAnomaly, None, None, Some(&detail))
+                .await?;
+        }
+
+        recorded.push(model);
+    }
+
+    Ok(recorded)
+}
+
+/// Run anomaly detection for all apps and return the new anomalies
+pub async fn analyze(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    now: DateTime<Utc>,
+) -> Result<Vec<metric_anomaly::Model>> {
+    if !get_setting_bool(db, "anomaly_detection_enabled").await? {
+        return Ok(Vec::new());
+    }
+
+    let default = default_sensitivity(db).await?;
+    let sensitivities: HashMap<String, Sensitivity> = AnomalySensitivity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|s| Sensitivity::parse(&s.sensitivity).map(|v| (s.app_name, v)))
+        .collect();
+
+    let cpu_query = r#"sum by (namespace) (rate(container_cpu_usage_seconds_total{container!="",container!="POD"}[1h]))"#;
+    let memory_query = r#"sum by (namespace) (avg_over_time(container_memory_working_set_bytes{container!="",container!="POD"}[1h]))"#;
+    let (cpu, memory) = tokio::join!(
+        fetch_vm_series(cpu_query, now),
+        fetch_vm_series(memory_query, now)
+    );
+    let restarts = restart_series(db, now).await?;
+
+    let mut anomalies = Vec::new();
+    for (metric, series) in [
+        (METRIC_CPU, cpu),
+        (METRIC_MEMORY, memory),
+        (METRIC_RESTARTS, restarts),
+    ] {
+        anomalies.extend(
+            analyze_metric(
+                db,
+                notification,
+                metric,
+                series,
+                &sensitivities,
+                default,
+                now,
+            )
+            .await?,
+        );
+    }
+
+    Ok(anomalies)
+}
+
+// ============================================================================
+// Queries and Settings
+// ============================================================================
+
+/// Anomalies detected in the last `hours`, optionally for one app
+pub async fn list_anomalies(
+    db: &DatabaseConnection,
+    hours: i64,
+    app_name: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<AnomalyReport> {
+    let mut query = MetricAnomaly::find()
+        .filter(metric_anomaly::Column::DetectedAt.gte(now - chrono::Duration::hours(hours)));
+    if let Some(app) = app_name {
+        query = query.filter(metric_anomaly::Column::AppName.eq(app));
+    }
+    let anomalies = query
+        .order_by_desc(metric_anomaly::Column::DetectedAt)
+        .order_by_desc(metric_anomaly::Column::Id)
+        .all(db)
+        .await?;
+
+    Ok(AnomalyReport {
+        window_hours: hours,
+        anomalies: anomalies.into_iter().map(to_info).collect(),
+    })
+}
+
+/// Anomalies detected within the cooldown period, i.e. still active
+pub async fn active_anomalies(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<MetricAnomalyInfo>> {
+    Ok(list_anomalies(db, COOLDOWN_HOURS, None, now)
+        .await?
+        .anomalies)
+}
+
+pub async fn sensitivity_settings(db: &DatabaseConnection) -> Result<SensitivitySettings> {
+    let apps = AnomalySensitivity::find()
+        .order_by_asc(anomaly_sensitivity::Column::AppName)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|s| {
+            Sensitivity::parse(&s.sensitivity).map(|sensitivity| AppSensitivity {
+                app_name: s.app_name,
+                sensitivity,
+            })
+        })
+        .collect();
+
+    Ok(SensitivitySettings {
+        default_sensitivity: default_sensitivity(db).await?,
+        apps,
+    })
+}
+
+/// Set an app's sensitivity override
+pub async fn set_sensitivity(
+    db: &DatabaseConnection,
+    app_name: &str,
+    sensitivity: &str,
+) -> Result<AppSensitivity> {
+    let parsed = Sensitivity::parse(sensitivity).ok_or_else(|| {
+        AppError::BadRequest("Sensitivity must be off, low, medium or high".to_string())
+    })?;
+
+    let model = anomaly_sensitivity::ActiveModel {
+        app_name: Set(app_name.to_string()),
+        sensitivity: Set(parsed.as_str().to_string()),
+        updated_at: Set(Utc::now()),
+    };
+    if AnomalySensitivity::find_by_id(app_name)
+        .one(db)
+        .await?
+        .is_some()
+    {
+        model.update(db).await?;
+    } else {
+        model.insert(db).await?;
+    }
+
+    Ok(AppSensitivity {
+        app_name: app_name.to_string(),
+        sensitivity: parsed,
+    })
+}
+
+/// Remove an app's override so the default sensitivity applies
+pub async fn delete_sensitivity(db: &DatabaseConnection, app_name: &str) -> Result<()> {
+    let result = AnomalySensitivity::delete_by_id(app_name).exec(db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No sensitivity override for '{}'",
+            app_name
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Runs anomaly detection and prunes old anomalies
+pub struct AnomalyDetectionTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for AnomalyDetectionTask {
+    fn name(&self) -> &'static str {
+        "anomaly_detection"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(ANALYSIS_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        analyze(db, &self.notification, now).await?;
+
+        MetricAnomaly::delete_many()
+            .filter(
+                metric_anomaly::Column::DetectedAt.lt(now - chrono::Duration::days(RETENTION_DAYS)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitivity_parse_and_thresholds() {
+        assert_eq!(Sensitivity::parse(" High "), Some(Sensitivity::High));
+        assert_eq!(Sensitivity::parse("extreme"), None);
+        assert_eq!(Sensitivity::Off.z_threshold(), None);
+        assert!(Sensitivity::High.z_threshold().unwrap() < Sensitivity::Low.z_threshold().unwrap());
+    }
+
+    #[test]
+    fn test_baseline_requires_history() {
+        assert!(baseline(&[1.0; MIN_BASELINE_POINTS - 1]).is_none());
+
+        let values: Vec<f64> = (0..24)
+            .map(|i| if i % 2 == 0 { 1.0 } else { 3.0 })
+            .collect();
+        let b = baseline(&values).unwrap();
+        assert_eq!(b.mean, 2.0);
+        assert_eq!(b.stddev, 1.0);
+    }
+
+    #[test]
+    fn test_detect_cpu_spike_and_drop() {
+        let history: Vec<f64> = (0..48)
+            .map(|i| if i % 2 == 0 { 0.9 } else { 1.1 })
+            .collect();
+
+        let spike = detect(METRIC_CPU, &history, 2.0, Sensitivity::Medium).unwrap();
+        assert_eq!(spike.direction, DIRECTION_ABOVE);
+        assert!(spike.z_score > 3.0);
+
+        let drop = detect(METRIC_CPU, &history, 0.0, Sensitivity::Medium).unwrap();
+        assert_eq!(drop.direction, DIRECTION_BELOW);
+
+        // Within the band, or detection switched off
+        assert!(detect(METRIC_CPU, &history, 1.2, Sensitivity::Medium).is_none());
+        assert!(detect(METRIC_CPU, &history, 2.0, Sensitivity::Off).is_none());
+    }
+
+    #[test]
+    fn test_detect_sensitivity_changes_threshold() {
+        // Floor of 0.1 (10% of mean) applies: 1.25 is 2.5 stddevs above
+        let history = vec![1.0; 48];
+        assert!(detect(METRIC_CPU, &history, 1.25, Sensitivity::High).is_some());
+        assert!(detect(METRIC_CPU, &history, 1.25, Sensitivity::Medium).is_none());
+    }
+
+    #[test]
+    fn test_detect_restarts_only_above() {
+        let history = vec![0.0; 48];
+        assert!(detect(METRIC_RESTARTS, &history, 3.0, Sensitivity::Medium).is_some());
+        assert!(detect(METRIC_RESTARTS, &history, 1.0, Sensitivity::Medium).is_none());
+
+        let flapping = vec![5.0; 48];
+        assert!(detect(METRIC_RESTARTS, &flapping, 0.0, Sensitivity::High).is_none());
+    }
+
+    #[test]
+    fn test_window_link() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let end = DateTime::from_timestamp(1_700_003_600, 0).unwrap();
+        assert_eq!(
+            window_link("sonarr", start, end),
+            "/resources?app=sonarr&from=1700000000&to=1700003600"
+        );
+    }
+}
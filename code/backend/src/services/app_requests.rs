@@ -0,0 +1,259 @@
+//! App install requests
+//!
+//! Users with `apps.request` ask for a catalog app to be installed, with the
+//! same options as a direct install. Requests wait in a queue until someone
+//! with `apps.install` approves or denies them, optionally with a comment.
+//! Approving a request installs the app; the install is audited under the
+//! requester, with the approving admin in the details. A request whose
+//! install fails stays pending so it can be approved again.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::catalog::AppCatalog;
+use super::deployment::DeploymentRequest;
+use crate::error::{AppError, Result};
+use crate::models::app_install_request;
+use crate::models::prelude::*;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_DENIED: &str = "denied";
+
+/// Longest accepted requester or reviewer comment
+const MAX_COMMENT_LEN: usize = 1000;
+
+/// An install request as shown in the queue
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AppInstallRequestInfo {
+    pub id: i64,
+    pub app_name: String,
+    pub custom_config: HashMap<String, String>,
+    pub requested_by: i64,
+    pub requested_by_username: Option<String>,
+    pub comment: Option<String>,
+    /// `pending`, `approved` or `denied`
+    pub status: String,
+    pub reviewed_by: Option<i64>,
+    pub reviewed_by_username: Option<String>,
+    pub review_comment: Option<String>,
+    #[schema(value_type = String)]
+    pub created_at: DateTime<Utc>,
+    #[schema(value_type = Option<String>)]
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// Ask for an app to be installed
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAppInstallRequest {
+    pub app_name: String,
+    /// Install options, as for `POST /api/apps/install`
+    #[serde(default)]
+    pub custom_config: HashMap<String, String>,
+    /// Why the app is wanted
+    pub comment: Option<String>,
+}
+
+/// Approve or deny a request
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ReviewAppInstallRequest {
+    /// Shown to the requester
+    pub comment: Option<String>,
+}
+
+fn clean_comment(comment: Option<&str>) -> Result<Option<String>> {
+    let comment = comment.map(str::trim).filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_COMMENT_LEN) {
+        return Err(AppError::BadRequest(format!(
+            "Comments are limited to {} characters",
+            MAX_COMMENT_LEN
+        )));
+    }
+    Ok(comment.map(str::to_string))
+}
+
+/// Queue an install request after checking the app can be installed
+///
+/// Installed apps are rejected by the caller, which has the cluster client.
+pub async fn create(
+    db: &DatabaseConnection,
+    catalog: &AppCatalog,
+    requester_id: i64,
+    request: &CreateAppInstallRequest,
+) -> Result<app_install_request::Model> {
+    let app = catalog.get_app(&request.app_name).ok_or_else(|| {
+        AppError::NotFound(format!("App '{}' not found in catalog", request.app_name))
+    })?;
+    if app.is_system {
+        return Err(AppError::Forbidden(format!(
+            "System app '{}' cannot be requested",
+            app.name
+        )));
+    }
+    let comment = clean_comment(request.comment.as_deref())?;
+
+    let queued = AppInstallRequest::find()
+        .filter(app_install_request::Column::AppName.eq(&app.name))
+        .filter(app_install_request::Column::Status.eq(STATUS_PENDING))
+        .one(db)
+        .await?;
+    if queued.is_some() {
+        return Err(AppError::Conflict(format!(
+            "An install request for '{}' is already waiting for approval",
+            app.name
+        )));
+    }
+
+    Ok(app_install_request::ActiveModel {
+        app_name: Set(app.name.clone()),
+        custom_config: Set(serde_json::to_string(&request.custom_config)?),
+        requested_by: Set(requester_id),
+        comment: Set(comment),
+        status: Set(STATUS_PENDING.to_string()),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Requests, newest first, optionally only one user's or with one status
+pub async fn list(
+    db: &DatabaseConnection,
+    requested_by: Option<i64>,
+    status: Option<&str>,
+) -> Result<Vec<AppInstallRequestInfo>> {
+    let mut query = AppInstallRequest::find().order_by_desc(app_install_request::Column::Id);
+    if let Some(user_id) = requested_by {
+        query = query.filter(app_install_request::Column::RequestedBy.eq(user_id));
+    }
+    if let Some(status) = status {
+        if ![STATUS_PENDING, STATUS_APPROVED, STATUS_DENIED].contains(&status) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown status '{}'; expected pending, approved or denied",
+                status
+            )));
+        }
+        query = query.filter(app_install_request::Column::Status.eq(status));
+    }
+
+    let usernames: HashMap<i64, String> = User::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|u| (u.id, u.username))
+        .collect();
+    query
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|request| to_info(request, &usernames))
+        .collect()
+}
+
+fn to_info(
+    request: app_install_request::Model,
+    usernames: &HashMap<i64, String>,
+) -> Result<AppInstallRequestInfo> {
+    Ok(AppInstallRequestInfo {
+        id: request.id,
+        app_name: request.app_name,
+        custom_config: serde_json::from_str(&request.custom_config)?,
+        requested_by: request.requested_by,
+        requested_by_username: usernames.get(&request.requested_by).cloned(),
+        comment: request.comment,
+        status: request.status,
+        reviewed_by: request.reviewed_by,
+        reviewed_by_username: request
+            .reviewed_by
+            .and_then(|id| usernames.get(&id).cloned()),
+        review_comment: request.review_comment,
+        created_at: request.created_at,
+        reviewed_at: request.reviewed_at,
+    })
+}
+
+/// One request with usernames filled in
+pub async fn info(
+    db: &DatabaseConnection,
+    request: app_install_request::Model,
+) -> Result<AppInstallRequestInfo> {
+    let mut usernames = HashMap::new();
+    for id in [Some(request.requested_by), request.reviewed_by]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(user) = User::find_by_id(id).one(db).await? {
+            usernames.insert(id, user.username);
+        }
+    }
+    to_info(request, &usernames)
+}
+
+/// A request that is still waiting for review
+pub async fn pending(db: &DatabaseConnection, id: i64) -> Result<app_install_request::Model> {
+    let request = AppInstallRequest::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Install request {} not found", id)))?;
+    if request.status != STATUS_PENDING {
+        return Err(AppError::Conflict(format!(
+            "Install request {} was already {}",
+            id, request.status
+        )));
+    }
+    Ok(request)
+}
+
+/// The install an approved request performs
+pub fn deployment_request(request: &app_install_request::Model) -> Result<DeploymentRequest> {
+    Ok(DeploymentRequest {
+        app_name: request.app_name.clone(),
+        custom_config: serde_json::from_str(&request.custom_config)?,
+        allow_incompatible_arch: false,
+    })
+}
+
+/// Record the decision on a pending request
+pub async fn review(
+    db: &DatabaseConnection,
+    request: app_install_request::Model,
+    reviewer_id: i64,
+    approved: bool,
+    comment: Option<&str>,
+) -> Result<app_install_request::Model> {
+    let comment = clean_comment(comment)?;
+    let mut active: app_install_request::ActiveModel = request.into();
+    active.status = Set(if approved {
+        STATUS_APPROVED
+    } else {
+        STATUS_DENIED
+    }
+    .to_string());
+    active.reviewed_by = Set(Some(reviewer_id));
+    active.review_comment = Set(comment);
+    active.reviewed_at = Set(Some(Utc::now()));
+    Ok(active.update(db).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_comment() {
+        assert_eq!(clean_comment(None).unwrap(), None);
+        assert_eq!(clean_comment(Some("  ")).unwrap(), None);
+        assert_eq!(
+            clean_comment(Some(" for the kids ")).unwrap().as_deref(),
+            Some("for the kids")
+        );
+        assert!(clean_comment(Some(&"x".repeat(MAX_COMMENT_LEN + 1))).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Per-app proxy routing
+//!
+//! Apps are proxied under `/{app_name}/` by default. Apps that behave badly
+//! under a sub-path (Plex, Home Assistant, ...) can instead be served from a
+//! dedicated subdomain of the `app_base_domain` setting, e.g.
+//! `plex.apps.example.com`. The choice is stored on the app's
+//! `installed_apps` row and passed to its chart as ingress values.
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::CONFIG;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::installed_app;
+use crate::models::prelude::*;
+use crate::state::DbConn;
+
+/// Setting holding the domain subdomain-routed apps are served under
+pub const APP_BASE_DOMAIN: &str = "app_base_domain";
+
+/// Setting holding the parent domain the session cookie is scoped to, so
+/// browsers also send it to app subdomains
+pub const SESSION_COOKIE_DOMAIN: &str = "session_cookie_domain";
+
+/// How the proxy exposes an app
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutingMode {
+    /// Served under `/{app_name}/` on the Kubarr host
+    #[default]
+    Path,
+    /// Served at the root of `{subdomain}.{app_base_domain}`
+    Subdomain,
+}
+
+impl RoutingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoutingMode::Path => "path",
+            RoutingMode::Subdomain => "subdomain",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "subdomain" => RoutingMode::Subdomain,
+            _ => RoutingMode::Path,
+        }
+    }
+}
+
+/// Routing of an installed app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct AppRouting {
+    pub app_name: String,
+    pub mode: RoutingMode,
+    /// Subdomain label, set in subdomain mode
+    pub subdomain: Option<String>,
+    /// Host the app is served on, when subdomain mode is active and
+    /// `app_base_domain` is configured
+    pub host: Option<String>,
+    /// Path prefix the app is served under in path mode
+    pub path_prefix: Option<String>,
+}
+
+impl AppRouting {
+    pub fn new(
+        app_name: &str,
+        mode: RoutingMode,
+        subdomain: Option<String>,
+        domain: Option<&str>,
+    ) -> Self {
+        match mode {
+            RoutingMode::Path => Self {
+                app_name: app_name.to_string(),
+                mode,
+                subdomain: None,
+                host: None,
+                path_prefix: Some(format!("/{}", app_name)),
+            },
+            RoutingMode::Subdomain => {
+                let label = subdomain.unwrap_or_else(|| app_name.to_string());
+                Self {
+                    app_name: app_name.to_string(),
+                    mode,
+                    host: domain.map(|d| format!("{}.{}", label, d)),
+                    subdomain: Some(label),
+                    path_prefix: None,
+                }
+            }
+        }
+    }
+
+    /// Helm `--set` values for the app's ingress
+    pub fn helm_values(&self) -> Vec<String> {
+        match (&self.host, &self.path_prefix) {
+            (Some(host), _) => vec![
+                format!("ingress.host={}", host),
+                "ingress.path=/".to_string(),
+            ],
+            (None, Some(prefix)) => vec![format!("ingress.path={}", prefix)],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateAppRouting {
+    pub mode: RoutingMode,
+    /// Subdomain label for subdomain mode; defaults to the app name
+    pub subdomain: Option<String>,
+}
+
+/// Whether `label` is a valid DNS label
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// The configured `app_base_domain`, if any
+pub async fn base_domain(db: &DbConn) -> Result<Option<String>> {
+    Ok(get_setting_value(db, APP_BASE_DOMAIN)
+        .await?
+        .map(|d| d.trim().trim_matches('.').to_lowercase())
+        .filter(|d| !d.is_empty()))
+}
+
+/// The configured `session_cookie_domain`, falling back to
+/// `KUBARR_SESSION_COOKIE_DOMAIN`
+pub async fn session_cookie_domain(db: &DbConn) -> Result<Option<String>> {
+    Ok(get_setting_value(db, SESSION_COOKIE_DOMAIN)
+        .await?
+        .map(|d| d.trim().trim_matches('.').to_lowercase())
+        .filter(|d| !d.is_empty())
+        .or_else(|| CONFIG.auth.session_cookie_domain.clone()))
+}
+
+/// Whether a cookie scoped to `cookie_domain` is sent to hosts under `domain`
+fn cookie_domain_covers(cookie_domain: &str, domain: &str) -> bool {
+    domain == cookie_domain || domain.ends_with(&format!(".{}", cookie_domain))
+}
+
+/// Routing of an app; apps without a record use path routing
+pub async fn get_routing(db: &DbConn, app_name: &str) -> Result<AppRouting> {
+    let record = InstalledApp::find_by_id(app_name).one(db).await?;
+    let domain = base_domain(db).await?;
+    Ok(match record {
+        Some(r) => AppRouting::new(
+            app_name,
+            RoutingMode::parse(&r.routing_mode),
+            r.subdomain,
+            domain.as_deref(),
+        ),
+        None => AppRouting::new(app_name, RoutingMode::Path, None, domain.as_deref()),
+    })
+}
+
+/// Validate a routing change for an installed app without saving it
+///
+/// Subdomain mode needs `app_base_domain`, and a `session_cookie_domain`
+/// covering it so the session cookie reaches the app's host.
+pub async fn plan_routing(
+    db: &DbConn,
+    app_name: &str,
+    update: UpdateAppRouting,
+) -> Result<AppRouting> {
+    let domain = base_domain(db).await?;
+    let subdomain = match update.mode {
+        RoutingMode::Path => None,
+        RoutingMode::Subdomain => {
+            let Some(domain) = domain.as_deref() else {
+                return Err(AppError::BadRequest(format!(
+                    "Set '{}' before enabling subdomain routing",
+                    APP_BASE_DOMAIN
+                )));
+            };
+            let covered = session_cookie_domain(db)
+                .await?
+                .is_some_and(|cookie_domain| cookie_domain_covers(&cookie_domain, domain));
+            if !covered {
+                return Err(AppError::BadRequest(format!(
+                    "Set '{}' to '{}' or a parent of it before enabling subdomain routing",
+                    SESSION_COOKIE_DOMAIN, domain
+                )));
+            }
+            let label = update
+                .subdomain
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| app_name.to_string());
+            if !is_valid_label(&label) {
+                return Err(AppError::BadRequest(format!(
+                    "'{}' is not a valid subdomain",
+                    label
+                )));
+            }
+            let taken = InstalledApp::find()
+                .filter(installed_app::Column::RoutingMode.eq(RoutingMode::Subdomain.as_str()))
+                .filter(installed_app::Column::AppName.ne(app_name))
+                .all(db)
+                .await?
+                .into_iter()
+                .any(|r| r.subdomain.as_deref().unwrap_or(&r.app_name) == label);
+            if taken {
+                return Err(AppError::Conflict(format!(
+                    "Subdomain '{}' is already used by another app",
+                    label
+                )));
+            }
+            Some(label)
+        }
+    };
+
+    Ok(AppRouting::new(
+        app_name,
+        update.mode,
+        subdomain,
+        domain.as_deref(),
+    ))
+}
+
+/// Save a routing returned by [`plan_routing`]
+pub async fn save_routing(db: &DbConn, routing: &AppRouting) -> Result<()> {
+    let now = Utc::now();
+    match InstalledApp::find_by_id(&routing.app_name).one(db).await? {
+        Some(existing) => {
+            let mut model: installed_app::ActiveModel = existing.into();
+            model.routing_mode = Set(routing.mode.as_str().to_string());
+            model.subdomain = Set(routing.subdomain.clone());
+            model.updated_at = Set(now);
+            model.update(db).await?;
+        }
+        None => {
+            installed_app::ActiveModel {
+                app_name: Set(routing.app_name.clone()),
+                notes: Set(None),
+                tags: Set("[]".to_string()),
+                cloned_from: Set(None),
+                routing_mode: Set(routing.mode.as_str().to_string()),
+                subdomain: Set(routing.subdomain.clone()),
+                stopped_replicas: Set(None),
+                last_accessed_at: Set(None),
+                idle_suspend_exempt: Set(false),
+                suspended_at: Set(None),
+                boot_priority: Set(0),
+                depends_on: Set("[]".to_string()),
+                boot_held_at: Set(None),
+                installed_by: Set(None),
+                cpu_millicores: Set(0),
+                memory_bytes: Set(0),
+                storage_bytes: Set(0),
+                tenant_id: Set(None),
+                chart_version: Set(None),
+                value_overrides: Set(None),
+                installed_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the app served on `host` in subdomain mode
+///
+/// `host` may carry a port, which is ignored.
+pub async fn app_for_host(db: &DbConn, host: &str) -> Result<Option<String>> {
+    let Some(domain) = base_domain(db).await? else {
+        return Ok(None);
+    };
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    let Some(label) = host
+        .strip_suffix(&domain)
+        .and_then(|rest| rest.strip_suffix('.'))
+    else {
+        return Ok(None);
+    };
+
+    Ok(InstalledApp::find()
+        .filter(installed_app::Column::RoutingMode.eq(RoutingMode::Subdomain.as_str()))
+        .all(db)
+        .await?
+        .into_iter()
+        .find(|r| r.subdomain.as_deref().unwrap_or(&r.app_name) == label)
+        .map(|r| r.app_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helm_values() {
+        let path = AppRouting::new("sonarr", RoutingMode::Path, None, Some("apps.lan"));
+        assert_eq!(path.helm_values(), vec!["ingress.path=/sonarr"]);
+
+        let sub = AppRouting::new("plex", RoutingMode::Subdomain, None, Some("apps.lan"));
+        assert_eq!(sub.host.as_deref(), Some("plex.apps.lan"));
+        assert_eq!(
+            sub.helm_values(),
+            vec!["ingress.host=plex.apps.lan", "ingress.path=/"]
+        );
+
+        // Without a base domain there is no host to route
+        let sub = AppRouting::new("plex", RoutingMode::Subdomain, Some("tv".into()), None);
+        assert_eq!(sub.subdomain.as_deref(), Some("tv"));
+        assert!(sub.helm_values().is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_label() {
+        assert!(is_valid_label("home-assistant"));
+        assert!(!is_valid_label("Plex"));
+        assert!(!is_valid_label("-plex"));
+        assert!(!is_valid_label("a.b"));
+        assert!(!is_valid_label(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn test_cookie_domain_covers() {
+        assert!(cookie_domain_covers("example.com", "apps.example.com"));
+        assert!(cookie_domain_covers("apps.example.com", "apps.example.com"));
+        assert!(!cookie_domain_covers("example.com", "badexample.com"));
+        assert!(!cookie_domain_covers("apps.example.com", "example.com"));
+    }
+}
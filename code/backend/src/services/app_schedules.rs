@@ -0,0 +1,447 @@
+//! App access schedules
+//!
+//! A schedule limits when an app can be reached to time windows on given
+//! weekdays, such as Jellyfin from 16:00 to 21:00 for the kids' role.
+//! Schedules are set per user or per role, for one app or for every app
+//! (`*`). Like quotas, a user's own schedules override their roles', and a
+//! role schedule only limits the access that role grants: a user who also
+//! reaches the app through an unscheduled role is not limited. Windows are
+//! read in the user's time zone from their preferences, or the
+//! `default_timezone` setting.
+//!
+//! An override lets one user past the schedules of an app until it expires,
+//! for a one-off late movie night. Overrides are audited.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, NaiveTime, Utc, Weekday};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{
+    app_access_override, app_access_schedule, role_app_permission, role_permission,
+    user_preferences, user_role,
+};
+use crate::services::access::unexpired_roles;
+use crate::services::notification::routing::{in_window, local_datetime};
+use crate::state::DbConn;
+
+/// Setting holding the time zone of users without a preference
+pub const DEFAULT_TIMEZONE_SETTING: &str = "default_timezone";
+
+const TIME_FORMAT: &str = "%H:%M";
+
+/// A window in which an app can be reached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScheduleWindow {
+    /// App the window applies to, or `*` for every app
+    pub app_name: String,
+    /// Weekdays the window opens on, such as `["sat", "sun"]`; empty is
+    /// every day
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Start of the window, "HH:MM"
+    pub start_time: String,
+    /// End of the window, "HH:MM"; before the start to wrap past midnight
+    pub end_time: String,
+}
+
+impl From<&app_access_schedule::Model> for ScheduleWindow {
+    fn from(model: &app_access_schedule::Model) -> Self {
+        Self {
+            app_name: model.app_name.clone(),
+            days: serde_json::from_str(&model.days).unwrap_or_default(),
+            start_time: model.start_time.clone(),
+            end_time: model.end_time.clone(),
+        }
+    }
+}
+
+/// The user or role a schedule belongs to
+#[derive(Debug, Clone, Copy)]
+pub enum ScheduleOwner {
+    User(i64),
+    Role(i64),
+}
+
+impl ScheduleOwner {
+    fn column(self) -> (app_access_schedule::Column, i64) {
+        match self {
+            ScheduleOwner::User(id) => (app_access_schedule::Column::UserId, id),
+            ScheduleOwner::Role(id) => (app_access_schedule::Column::RoleId, id),
+        }
+    }
+}
+
+/// A temporary exception to a user's schedules
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OverrideInfo {
+    pub id: i64,
+    pub user_id: i64,
+    pub app_name: String,
+    pub reason: Option<String>,
+    pub granted_by: Option<i64>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<app_access_override::Model> for OverrideInfo {
+    fn from(model: app_access_override::Model) -> Self {
+        Self {
+            id: model.id,
+            user_id: model.user_id,
+            app_name: model.app_name,
+            reason: model.reason,
+            granted_by: model.granted_by,
+            expires_at: model.expires_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+// ============================================================================
+// Windows
+// ============================================================================
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map_err(|_| AppError::BadRequest(format!("Invalid time '{}', expected HH:MM", value)))
+}
+
+fn parse_day(value: &str) -> Result<Weekday> {
+    value
+        .trim()
+        .parse::<Weekday>()
+        .map_err(|_| AppError::BadRequest(format!("Invalid weekday '{}'", value)))
+}
+
+/// Check a window and normalize its days to `mon` .. `sun`
+pub fn validate_window(window: &ScheduleWindow) -> Result<ScheduleWindow> {
+    let app_name = window.app_name.trim();
+    if app_name.is_empty() {
+        return Err(AppError::BadRequest("app_name is required".to_string()));
+    }
+    let start = parse_time(&window.start_time)?;
+    let end = parse_time(&window.end_time)?;
+    if start == end {
+        return Err(AppError::BadRequest(
+            "A window cannot start and end at the same time".to_string(),
+        ));
+    }
+    let mut days = Vec::new();
+    for day in &window.days {
+        let day = parse_day(day)?.to_string().to_lowercase();
+        if !days.contains(&day) {
+            days.push(day);
+        }
+    }
+    Ok(ScheduleWindow {
+        app_name: app_name.to_string(),
+        days,
+        start_time: start.format(TIME_FORMAT).to_string(),
+        end_time: end.format(TIME_FORMAT).to_string(),
+    })
+}
+
+/// Whether a window is open at the local time `local`
+///
+/// A window that wraps past midnight belongs to the day it opens on, so a
+/// Friday 22:00-02:00 window still admits at 01:00 on Saturday.
+pub fn window_open(window: &ScheduleWindow, local: NaiveDateTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&window.start_time), parse_time(&window.end_time))
+    else {
+        return false;
+    };
+    let time = local.time();
+    if !in_window(time, start, end) {
+        return false;
+    }
+    let opened_on = if start > end && time < end {
+        local.date() - Duration::days(1)
+    } else {
+        local.date()
+    };
+    window.days.is_empty()
+        || window
+            .days
+            .iter()
+            .filter_map(|d| d.parse::<Weekday>().ok())
+            .any(|d| d == opened_on.weekday())
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Time zone a user's schedules are read in
+pub async fn user_timezone(db: &DbConn, user_id: i64) -> Result<String> {
+    let preferred = UserPreferences::find_by_id(user_id)
+        .one(db)
+        .await?
+        .and_then(|p: user_preferences::Model| p.timezone);
+    Ok(match preferred {
+        Some(timezone) => timezone,
+        None => get_setting_value(db, DEFAULT_TIMEZONE_SETTING)
+            .await?
+            .unwrap_or_else(|| "UTC".to_string()),
+    })
+}
+
+fn applies_to(window: &ScheduleWindow, app_name: &str) -> bool {
+    window.app_name == "*" || window.app_name == app_name
+}
+
+/// Schedules of an owner that apply to an app
+async fn windows_for(
+    db: &DbConn,
+    owner: ScheduleOwner,
+    app_name: &str,
+) -> Result<Vec<ScheduleWindow>> {
+    Ok(get_schedules(db, owner)
+        .await?
+        .into_iter()
+        .filter(|w| applies_to(w, app_name))
+        .collect())
+}
+
+/// The user's unexpired override for an app, if any
+pub async fn active_override(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<app_access_override::Model>> {
+    Ok(AppAccessOverride::find()
+        .filter(app_access_override::Column::UserId.eq(user_id))
+        .filter(
+            Condition::any()
+                .add(app_access_override::Column::AppName.eq(app_name))
+                .add(app_access_override::Column::AppName.eq("*")),
+        )
+        .filter(app_access_override::Column::ExpiresAt.gt(now))
+        .one(db)
+        .await?)
+}
+
+/// Whether the schedules let a user reach an app at `now`
+///
+/// Only looks at schedules and overrides; whether the user may use the app
+/// at all is up to their permissions.
+pub async fn is_allowed(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    now: DateTime<Utc>,
+) -> Result<bool> {
+    if active_override(db, user_id, app_name, now).await?.is_some() {
+        return Ok(true);
+    }
+    let local = local_datetime(now, &user_timezone(db, user_id).await?);
+
+    let own = windows_for(db, ScheduleOwner::User(user_id), app_name).await?;
+    if !own.is_empty() {
+        return Ok(own.iter().any(|w| window_open(w, local)));
+    }
+
+    // Roles that grant the app; any one of them letting the user in is enough
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.role_id)
+        .collect();
+    let mut granting: Vec<i64> = RolePermission::find()
+        .filter(role_permission::Column::RoleId.is_in(role_ids.clone()))
+        .filter(role_permission::Column::Permission.eq("app.*"))
+        .all(db)
+        .await?
+        .iter()
+        .map(|p| p.role_id)
+        .collect();
+    granting.extend(
+        RoleAppPermission::find()
+            .filter(role_app_permission::Column::RoleId.is_in(role_ids))
+            .filter(role_app_permission::Column::AppName.eq(app_name))
+            .all(db)
+            .await?
+            .iter()
+            .map(|p| p.role_id),
+    );
+    granting.sort();
+    granting.dedup();
+    if granting.is_empty() {
+        return Ok(true);
+    }
+    for role_id in granting {
+        let windows = windows_for(db, ScheduleOwner::Role(role_id), app_name).await?;
+        if windows.is_empty() || windows.iter().any(|w| window_open(w, local)) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// The schedules set directly on a user or role
+pub async fn get_schedules(db: &DbConn, owner: ScheduleOwner) -> Result<Vec<ScheduleWindow>> {
+    let (column, id) = owner.column();
+    Ok(AppAccessSchedule::find()
+        .filter(column.eq(id))
+        .order_by_asc(app_access_schedule::Column::Id)
+        .all(db)
+        .await?
+        .iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// Replace the schedules of a user or role; an empty list removes them
+pub async fn set_schedules(
+    db: &DbConn,
+    owner: ScheduleOwner,
+    windows: &[ScheduleWindow],
+) -> Result<Vec<ScheduleWindow>> {
+    let windows = windows
+        .iter()
+        .map(validate_window)
+        .collect::<Result<Vec<_>>>()?;
+
+    let (column, id) = owner.column();
+    AppAccessSchedule::delete_many()
+        .filter(column.eq(id))
+        .exec(db)
+        .await?;
+    let now = Utc::now();
+    for window in &windows {
+        app_access_schedule::ActiveModel {
+            user_id: Set(matches!(owner, ScheduleOwner::User(_)).then_some(id)),
+            role_id: Set(matches!(owner, ScheduleOwner::Role(_)).then_some(id)),
+            app_name: Set(window.app_name.clone()),
+            days: Set(serde_json::to_string(&window.days)?),
+            start_time: Set(window.start_time.clone()),
+            end_time: Set(window.end_time.clone()),
+            created_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    get_schedules(db, owner).await
+}
+
+/// A user's overrides that have not expired
+pub async fn list_overrides(db: &DbConn, user_id: i64) -> Result<Vec<OverrideInfo>> {
+    Ok(AppAccessOverride::find()
+        .filter(app_access_override::Column::UserId.eq(user_id))
+        .filter(app_access_override::Column::ExpiresAt.gt(Utc::now()))
+        .order_by_asc(app_access_override::Column::ExpiresAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// Let a user past the schedules of an app until `expires_at`
+pub async fn grant_override(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    expires_at: DateTime<Utc>,
+    reason: Option<String>,
+    granted_by: i64,
+) -> Result<OverrideInfo> {
+    let app_name = app_name.trim();
+    if app_name.is_empty() {
+        return Err(AppError::BadRequest("app_name is required".to_string()));
+    }
+    let now = Utc::now();
+    if expires_at <= now {
+        return Err(AppError::BadRequest(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+    Ok(app_access_override::ActiveModel {
+        user_id: Set(user_id),
+        app_name: Set(app_name.to_string()),
+        reason: Set(reason.filter(|r| !r.trim().is_empty())),
+        granted_by: Set(Some(granted_by)),
+        expires_at: Set(expires_at),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?
+    .into())
+}
+
+/// End an override early; returns it if it existed
+pub async fn revoke_override(
+    db: &DbConn,
+    user_id: i64,
+    override_id: i64,
+) -> Result<Option<OverrideInfo>> {
+    let Some(existing) = AppAccessOverride::find_by_id(override_id)
+        .filter(app_access_override::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    AppAccessOverride::delete_by_id(existing.id)
+        .exec(db)
+        .await?;
+    Ok(Some(existing.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(days: &[&str], start: &str, end: &str) -> ScheduleWindow {
+        ScheduleWindow {
+            app_name: "jellyfin".to_string(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+        }
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_validate_window() {
+        let valid = validate_window(&window(&["Saturday", "sun", "sat"], "9:00", "21:30")).unwrap();
+        assert_eq!(valid.days, vec!["sat", "sun"]);
+        assert_eq!(valid.start_time, "09:00");
+        assert!(validate_window(&window(&["someday"], "09:00", "21:00")).is_err());
+        assert!(validate_window(&window(&[], "25:00", "21:00")).is_err());
+        assert!(validate_window(&window(&[], "21:00", "21:00")).is_err());
+    }
+
+    #[test]
+    fn test_window_open() {
+        // 2026-10-16 is a Friday
+        let evenings = window(&[], "16:00", "21:00");
+        assert!(window_open(&evenings, at("2026-10-16 16:00")));
+        assert!(!window_open(&evenings, at("2026-10-16 21:00")));
+        assert!(!window_open(&evenings, at("2026-10-16 09:00")));
+
+        let friday_night = window(&["fri"], "22:00", "02:00");
+        assert!(window_open(&friday_night, at("2026-10-16 23:00")));
+        assert!(window_open(&friday_night, at("2026-10-17 01:00")));
+        assert!(!window_open(&friday_night, at("2026-10-17 23:00")));
+        assert!(!window_open(&friday_night, at("2026-10-16 01:00")));
+    }
+}
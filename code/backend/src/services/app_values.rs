@@ -0,0 +1,433 @@
+//! Helm value overrides of installed apps
+//!
+//! Users can change the chart values of an installed app, such as resource
+//! limits or environment variables, without reinstalling it. Overrides are
+//! stored with the app's metadata and passed to helm as a values file on
+//! every install, redeploy and upgrade. The `--set` values Kubarr manages
+//! (storage, VPN, routing, registries) still take precedence over them.
+//!
+//! Values whose key looks like a credential are masked in responses. A
+//! masked value sent back unchanged keeps the stored one, so the editor can
+//! round-trip what it was given.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::services::installed_apps;
+use crate::state::DbConn;
+
+/// Placeholder for a masked secret value
+pub const MASK: &str = "********";
+
+/// Values of an installed app's chart
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AppValues {
+    pub app_name: String,
+    /// Chart the app was installed from; differs from `app_name` for clones
+    pub chart: String,
+    /// Values set on top of the chart's defaults, with secrets masked
+    #[schema(value_type = Object)]
+    pub overrides: Value,
+    /// The chart's defaults merged with the overrides, with secrets masked
+    #[schema(value_type = Object)]
+    pub effective: Value,
+    /// JSON schema of the chart's values, if the chart ships one
+    #[schema(value_type = Option<Object>)]
+    pub schema: Option<Value>,
+}
+
+/// Replace the value overrides of an installed app
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateAppValues {
+    /// Values to set on top of the chart's defaults; `null` removes a
+    /// default
+    #[schema(value_type = Object)]
+    pub overrides: Value,
+}
+
+/// Current values of `app_name`, installed from `chart`
+pub async fn get(db: &DbConn, app_name: &str, chart: &str) -> Result<AppValues> {
+    let overrides = installed_apps::value_overrides(db, app_name).await?;
+    describe(app_name, chart, &overrides)
+}
+
+/// Check new overrides of `app_name` against its chart and return them as
+/// they should be stored
+///
+/// Masked secrets are replaced with the stored values they stand for. The
+/// chart's defaults merged with the overrides must satisfy the chart's
+/// values schema, when it has one.
+pub async fn plan(
+    db: &DbConn,
+    app_name: &str,
+    chart: &str,
+    request: UpdateAppValues,
+) -> Result<Value> {
+    let mut overrides = request.overrides;
+    if overrides.is_null() {
+        overrides = Value::Object(Map::new());
+    }
+    if !overrides.is_object() {
+        return Err(AppError::BadRequest(
+            "Value overrides must be an object".to_string(),
+        ));
+    }
+    let stored = installed_apps::value_overrides(db, app_name).await?;
+    restore_masked(&mut overrides, &stored);
+
+    if let Some(schema) = chart_schema(chart)? {
+        let mut effective = chart_defaults(chart)?;
+        merge(&mut effective, &overrides);
+        let errors = validate(&effective, &schema);
+        if !errors.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Values do not match the chart schema: {}",
+                errors.join("; ")
+            )));
+        }
+    }
+    Ok(overrides)
+}
+
+/// Describe `overrides` of `app_name` with secrets masked
+pub fn describe(app_name: &str, chart: &str, overrides: &Value) -> Result<AppValues> {
+    let mut effective = chart_defaults(chart)?;
+    merge(&mut effective, overrides);
+    Ok(AppValues {
+        app_name: app_name.to_string(),
+        chart: chart.to_string(),
+        overrides: mask_secrets(overrides),
+        effective: mask_secrets(&effective),
+        schema: chart_schema(chart)?,
+    })
+}
+
+/// Default values from the chart's values.yaml; an empty object if it has
+/// none
+pub fn chart_defaults(chart: &str) -> Result<Value> {
+    let path = CONFIG.charts.dir.join(chart).join("values.yaml");
+    if !path.exists() {
+        return Ok(Value::Object(Map::new()));
+    }
+    let values: Value = serde_yaml::from_str(&std::fs::read_to_string(&path)?)?;
+    Ok(match values {
+        Value::Null => Value::Object(Map::new()),
+        values => values,
+    })
+}
+
+/// Values schema from the chart's values.schema.json
+pub fn chart_schema(chart: &str) -> Result<Option<Value>> {
+    let path = CONFIG.charts.dir.join(chart).join("values.schema.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&std::fs::read_to_string(
+        &path,
+    )?)?))
+}
+
+/// Merge `overlay` into `base` the way Helm merges user values into chart
+/// defaults: objects are merged key by key, `null` removes a key and
+/// anything else replaces the base value
+pub fn merge(base: &mut Value, overlay: &Value) {
+    let (Some(base), Some(overlay)) = (base.as_object_mut(), overlay.as_object()) else {
+        if !overlay.is_null() {
+            *base = overlay.clone();
+        }
+        return;
+    };
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (_, Value::Null) => {
+                base.remove(key);
+            }
+            (Some(existing), Value::Object(_)) if existing.is_object() => merge(existing, value),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Whether a value's key suggests it holds a credential
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    [
+        "password",
+        "secret",
+        "token",
+        "apikey",
+        "api_key",
+        "credential",
+    ]
+    .iter()
+    .any(|word| key.contains(word))
+}
+
+/// Copy of `value` with non-empty strings under credential-like keys
+/// replaced by [`MASK`]
+pub fn mask_secrets(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let masked = match value {
+                        Value::String(s) if !s.is_empty() && is_secret_key(key) => {
+                            Value::String(MASK.to_string())
+                        }
+                        value => mask_secrets(value),
+                    };
+                    (key.clone(), masked)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(mask_secrets).collect()),
+        value => value.clone(),
+    }
+}
+
+/// Put the stored values back in place of masks sent back unchanged
+///
+/// A mask without a stored override stood for a chart default, which is
+/// kept by dropping the key.
+fn restore_masked(overrides: &mut Value, stored: &Value) {
+    let Some(map) = overrides.as_object_mut() else {
+        return;
+    };
+    let keys: Vec<String> = map.keys().cloned().collect();
+    for key in keys {
+        let previous = stored.get(&key);
+        let Some(value) = map.get_mut(&key) else {
+            continue;
+        };
+        if value.as_str() == Some(MASK) {
+            match previous {
+                Some(previous) => *value = previous.clone(),
+                None => {
+                    map.remove(&key);
+                }
+            }
+        } else if value.is_object() {
+            restore_masked(value, previous.unwrap_or(&Value::Null));
+        }
+    }
+}
+
+/// Check `value` against a JSON schema, returning one message per violation
+///
+/// Covers the keywords charts use for their values: `type`, `properties`,
+/// `additionalProperties`, `required`, `items`, `enum`, `minimum`,
+/// `maximum`, `minLength` and `maxLength`. Other keywords are ignored.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at("", value, schema, &mut errors);
+    errors
+}
+
+fn validate_at(path: &str, value: &Value, schema: &Value, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let location = if path.is_empty() { "values" } else { path };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            errors.push(format!("{}: expected {}", location, types.join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: not one of the allowed values", location));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                errors.push(format!("{}: must be at least {}", location, min));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                errors.push(format!("{}: must be at most {}", location, max));
+            }
+        }
+    }
+
+    if let Some(s) = value.as_str() {
+        let len = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if len < min {
+                errors.push(format!("{}: must be at least {} characters", location, min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if len > max {
+                errors.push(format!("{}: must be at most {} characters", location, max));
+            }
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_at(&format!("{}[{}]", location, i), item, item_schema, errors);
+            }
+        }
+    }
+
+    if let Some(map) = value.as_object() {
+        let child = |key: &str| {
+            if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", path, key)
+            }
+        };
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !map.contains_key(key) {
+                    errors.push(format!("{}: is required", child(key)));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, value) in map {
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate_at(&child(key), value, property, errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        errors.push(format!("{}: is not allowed", child(key)));
+                    }
+                    Some(additional @ Value::Object(_)) => {
+                        validate_at(&child(key), value, additional, errors)
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge() {
+        let mut base = json!({
+            "sonarr": {"resources": {"limits": {"cpu": "1000m", "memory": "1Gi"}}},
+            "persistence": {"config": {"size": "1Gi"}}
+        });
+        merge(
+            &mut base,
+            &json!({
+                "sonarr": {"resources": {"limits": {"memory": "2Gi"}}},
+                "persistence": null
+            }),
+        );
+        assert_eq!(
+            base,
+            json!({"sonarr": {"resources": {"limits": {"cpu": "1000m", "memory": "2Gi"}}}})
+        );
+    }
+
+    #[test]
+    fn test_mask_secrets() {
+        let values = json!({
+            "env": {"TZ": "UTC", "API_KEY": "abc", "WEBUI_PASSWORD": ""},
+            "auth": {"adminPassword": "hunter2"},
+            "tokens": [{"name": "a"}]
+        });
+        assert_eq!(
+            mask_secrets(&values),
+            json!({
+                "env": {"TZ": "UTC", "API_KEY": MASK, "WEBUI_PASSWORD": ""},
+                "auth": {"adminPassword": MASK},
+                "tokens": [{"name": "a"}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_restore_masked() {
+        let stored = json!({"env": {"API_KEY": "abc"}});
+        let mut overrides =
+            json!({"env": {"API_KEY": MASK, "TZ": "UTC"}, "auth": {"adminPassword": MASK}});
+        restore_masked(&mut overrides, &stored);
+        assert_eq!(
+            overrides,
+            json!({"env": {"API_KEY": "abc", "TZ": "UTC"}, "auth": {}})
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "replicas": {"type": "integer", "minimum": 0, "maximum": 3},
+                "resources": {
+                    "type": "object",
+                    "properties": {"limits": {"type": "object", "additionalProperties": {"type": "string"}}}
+                },
+                "logLevel": {"enum": ["info", "debug"]},
+                "strict": {"type": "object", "required": ["name"], "additionalProperties": false,
+                    "properties": {"name": {"type": "string", "minLength": 1}}}
+            }
+        });
+
+        let valid = json!({
+            "replicas": 2,
+            "resources": {"limits": {"cpu": "500m"}},
+            "logLevel": "debug",
+            "strict": {"name": "x"},
+            "extra": true
+        });
+        assert!(validate(&valid, &schema).is_empty());
+
+        let invalid = json!({
+            "replicas": 5,
+            "resources": {"limits": {"cpu": 1}},
+            "logLevel": "trace",
+            "strict": {"other": 1}
+        });
+        assert_eq!(
+            validate(&invalid, &schema),
+            vec![
+                "logLevel: not one of the allowed values",
+                "replicas: must be at most 3",
+                "resources.limits.cpu: expected string",
+                "strict.name: is required",
+                "strict.other: is not allowed",
+            ]
+        );
+    }
+}
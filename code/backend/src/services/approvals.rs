@@ -0,0 +1,392 @@
+//! Registration approvals
+//!
+//! When a new account waits for admin approval, every active user holding
+//! `users.manage` gets a `user_created` notification with a link that
+//! approves the account without signing in. [`ApprovalDigestTask`] emails
+//! the same admins a daily list of the registrations still waiting, again
+//! with a link per account.
+//!
+//! Links are signed for the admin they were sent to, expire after
+//! [`LINK_VALIDITY_DAYS`] days and only work while that admin still holds
+//! `users.manage`. Opening a link shows a confirmation page; the account is
+//! approved once the admin confirms, so mail scanners that prefetch links
+//! cannot approve anyone.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+use super::access::get_user_permissions;
+use super::links;
+use super::maintenance::escape_html;
+use super::notification::NotificationService;
+use super::reports::recipient;
+use super::scheduler::PeriodicTask;
+use super::security::{create_approval_token, decode_approval_token};
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, UsersManage};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::{system_setting, user};
+
+/// Event type digests are logged under in the notification log
+pub const DIGEST_EVENT_TYPE: &str = "approval_digest";
+
+/// Setting recording when the last digest went out
+pub const DIGEST_LAST_SENT: &str = "approval_digest_last_sent";
+
+/// How long an approve link stays valid
+pub const LINK_VALIDITY_DAYS: i64 = 7;
+
+/// Minimum time between two digests
+const DIGEST_INTERVAL_HOURS: i64 = 24;
+
+/// Accounts waiting for approval, oldest first
+pub async fn pending_users(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let mut pending = User::find()
+        .filter(user::Column::IsApproved.eq(false))
+        .all(db)
+        .await?;
+    pending.sort_by_key(|u| u.created_at);
+    Ok(pending)
+}
+
+/// Whether `user` may approve registrations
+async fn is_approver(db: &DatabaseConnection, user: &user::Model) -> bool {
+    user.is_active
+        && user.is_approved
+        && get_user_permissions(db, user.id)
+            .await
+            .iter()
+            .any(|p| p == UsersManage::NAME)
+}
+
+/// Active users who may approve registrations
+pub async fn approvers(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let mut approvers = Vec::new();
+    for user in User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+    {
+        if is_approver(db, &user).await {
+            approvers.push(user);
+        }
+    }
+    Ok(approvers)
+}
+
+/// Link that lets `admin_id` approve `user_id`
+pub fn approve_link(user_id: i64, admin_id: i64) -> Result<String> {
+    let token = create_approval_token(user_id, admin_id, LINK_VALIDITY_DAYS * 24 * 60 * 60)?;
+    Ok(links::absolute(&format!(
+        "/api/users/approve-link?token={}",
+        token
+    )))
+}
+
+/// Tell every approver that `pending` is waiting for approval
+pub async fn notify_pending(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    pending: &user::Model,
+) -> Result<()> {
+    for admin in approvers(db).await? {
+        let details = format!(
+            "{} is awaiting approval. Approve: {}",
+            pending.email,
+            approve_link(pending.id, admin.id)?
+        );
+        notification
+            .notify_user_event(
+                &AuditAction::UserCreated,
+                admin.id,
+                Some(&pending.username),
+                Some(&details),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Plain-text digest of the accounts awaiting approval for one admin
+pub fn render_digest(pending: &[(user::Model, String)], now: DateTime<Utc>) -> String {
+    let mut out = format!(
+        "{} account{} awaiting approval:\n\n",
+        pending.len(),
+        if pending.len() == 1 { " is" } else { "s are" }
+    );
+    for (user, link) in pending {
+        let days = (now - user.created_at).num_days();
+        out.push_str(&format!(
+            "- {} <{}>, waiting {} day{}\n  Approve: {}\n",
+            user.username,
+            user.email,
+            days,
+            if days == 1 { "" } else { "s" },
+            link
+        ));
+    }
+    out.push_str(&format!(
+        "\nLinks are valid for {} days. To reject an account, use the Users page.\n",
+        LINK_VALIDITY_DAYS
+    ));
+    out
+}
+
+/// When the last digest was sent
+async fn last_digest(db: &DatabaseConnection) -> Result<Option<DateTime<Utc>>> {
+    Ok(SystemSetting::find_by_id(DIGEST_LAST_SENT)
+        .one(db)
+        .await?
+        .and_then(|s| DateTime::parse_from_rfc3339(&s.value).ok())
+        .map(|at| at.with_timezone(&Utc)))
+}
+
+async fn record_digest(db: &DatabaseConnection, at: DateTime<Utc>) -> Result<()> {
+    let value = at.to_rfc3339();
+    match SystemSetting::find_by_id(DIGEST_LAST_SENT).one(db).await? {
+        Some(existing) => {
+            let mut active: system_setting::ActiveModel = existing.into();
+            active.value = Set(value);
+            active.updated_at = Set(at);
+            active.update(db).await?;
+        }
+        None => {
+            system_setting::ActiveModel {
+                key: Set(DIGEST_LAST_SENT.to_string()),
+                value: Set(value),
+                description: Set(Some(
+                    "When the pending approvals digest was last sent".into(),
+                )),
+                updated_at: Set(at),
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Email the digest to every approver if accounts are waiting and the last
+/// digest is at least a day old; returns how many emails went out
+pub async fn send_digest(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    if last_digest(db)
+        .await?
+        .is_some_and(|last| now - last < chrono::Duration::hours(DIGEST_INTERVAL_HOURS))
+    {
+        return Ok(0);
+    }
+    let pending = pending_users(db).await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let subject = format!("Kubarr: {} account(s) awaiting approval", pending.len());
+    let mut sent = 0;
+    for admin in approvers(db).await? {
+        let links = pending
+            .iter()
+            .map(|user| Ok((user.clone(), approve_link(user.id, admin.id)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let to = recipient(db, &admin).await?;
+        let result = notification
+            .send_email_to_user(
+                admin.id,
+                &to,
+                &subject,
+                &render_digest(&links, now),
+                DIGEST_EVENT_TYPE,
+            )
+            .await?;
+        match result.success {
+            true => sent += 1,
+            false => tracing::warn!(
+                "Failed to send approvals digest to {}: {}",
+                admin.username,
+                result.error.unwrap_or_default()
+            ),
+        }
+    }
+
+    // Recorded even when delivery failed, so a broken mail setup is not
+    // retried every hour; the failure is in the notification log
+    record_digest(db, now).await?;
+    Ok(sent)
+}
+
+/// The pending account and the admin an approve link is for, after
+/// checking the link is still usable
+pub async fn resolve_link(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<(user::Model, user::Model)> {
+    let claims = decode_approval_token(token).map_err(|_| {
+        AppError::Unauthorized("This approval link is invalid or has expired".to_string())
+    })?;
+    let admin = match User::find_by_id(claims.adm).one(db).await? {
+        Some(admin) if is_approver(db, &admin).await => admin,
+        _ => {
+            return Err(AppError::Forbidden(
+                "This approval link is no longer valid".to_string(),
+            ))
+        }
+    };
+    let pending = User::find_by_id(claims.sub)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("This account no longer exists".to_string()))?;
+    Ok((pending, admin))
+}
+
+/// Approve the account an approve link is for
+///
+/// Returns the account, the admin the link was sent to, and whether the
+/// account was still waiting (a link may be used after someone else
+/// approved the account).
+pub async fn approve_with_link(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<(user::Model, user::Model, bool)> {
+    let (pending, admin) = resolve_link(db, token).await?;
+    if pending.is_approved {
+        return Ok((pending, admin, false));
+    }
+    let mut active: user::ActiveModel = pending.into();
+    active.is_approved = Set(true);
+    active.is_active = Set(true);
+    active.updated_at = Set(Utc::now());
+    let approved = active.update(db).await?;
+    Ok((approved, admin, true))
+}
+
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}} - Kubarr</title>
+<style>
+body { font-family: system-ui, sans-serif; background: #111827; color: #e5e7eb; display: flex; align-items: center; justify-content: center; min-height: 100vh; margin: 0; }
+main { max-width: 28rem; padding: 2rem; text-align: center; }
+h1 { font-size: 1.5rem; margin-bottom: 0.5rem; }
+p { color: #9ca3af; }
+button { background: #2563eb; color: #fff; border: 0; border-radius: 0.375rem; padding: 0.5rem 1rem; font-size: 1rem; cursor: pointer; }
+a { color: #60a5fa; }
+</style>
+</head>
+<body>
+<main>
+<h1>{{title}}</h1>
+<p>{{message}}</p>
+{{form}}
+<p><a href="/">Back to Kubarr</a></p>
+</main>
+</body>
+</html>
+"#;
+
+/// Render a page for a link opened from a notification; with a token and a
+/// button label, the page asks to confirm
+pub fn render_page(title: &str, message: &str, confirm: Option<(&str, &str)>) -> String {
+    let form = match confirm {
+        Some((token, button)) => format!(
+            r#"<form method="post"><input type="hidden" name="token" value="{}"><button type="submit">{}</button></form>"#,
+            escape_html(token),
+            escape_html(button)
+        ),
+        None => String::new(),
+    };
+    PAGE_TEMPLATE
+        .replace("{{title}}", &escape_html(title))
+        .replace("{{message}}", &escape_html(message))
+        .replace("{{form}}", &form)
+}
+
+/// Emails the daily digest of accounts awaiting approval
+pub struct ApprovalDigestTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for ApprovalDigestTask {
+    fn name(&self) -> &'static str {
+        "approval_digest"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let sent = send_digest(db, &self.notification, Utc::now()).await?;
+        if sent > 0 {
+            tracing::info!("Sent approvals digest to {} admin(s)", sent);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn pending(username: &str, created_at: DateTime<Utc>) -> user::Model {
+        user::Model {
+            id: 7,
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            hashed_password: String::new(),
+            is_active: true,
+            is_approved: false,
+            totp_secret: None,
+            totp_enabled: false,
+            totp_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            is_service_account: false,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_render_digest() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 17, 8, 0, 0).unwrap();
+        let body = render_digest(
+            &[
+                (
+                    pending("alice", now - chrono::Duration::days(3)),
+                    "L1".into(),
+                ),
+                (
+                    pending("bob", now - chrono::Duration::hours(30)),
+                    "L2".into(),
+                ),
+            ],
+            now,
+        );
+        assert!(body.starts_with("2 accounts are awaiting approval:\n"));
+        assert!(body.contains("- alice <alice@example.com>, waiting 3 days\n  Approve: L1\n"));
+        assert!(body.contains("- bob <bob@example.com>, waiting 1 day\n  Approve: L2\n"));
+    }
+
+    #[test]
+    fn test_render_page_escapes() {
+        let page = render_page("Approve <b>", "x & y", Some(("a\"b", "Approve")));
+        assert!(page.contains("<h1>Approve &lt;b&gt;</h1>"));
+        assert!(page.contains("<p>x &amp; y</p>"));
+        assert!(page.contains(r#"value="a&quot;b""#));
+        assert!(page.contains("<button type=\"submit\">Approve</button>"));
+
+        let page = render_page("Done", "ok", None);
+        assert!(!page.contains("<form"));
+    }
+}
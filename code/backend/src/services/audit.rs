@@ -1,19 +1,29 @@
+use chrono::SubsecRound;
+use hmac::{Hmac, Mac};
+use sea_orm::sea_query::{Alias, Expr, SimpleExpr};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
-    QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, PaginatorTrait, QueryFilter, QueryOrder, QueryResult, QuerySelect, Set,
 };
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
 use crate::db::DbConn;
-use crate::error::Result;
+use crate::error::{AppError, Result};
+use crate::middleware::current_request_id;
 use crate::models::audit_log::{self, AuditAction, ResourceType};
+use crate::services::access::{get_user_app_access, unexpired_roles};
 
 /// Audit service for logging system events
 #[derive(Clone, Default)]
 pub struct AuditService {
     db: Arc<RwLock<Option<DbConn>>>,
+    /// HMAC key for integrity mode; `None` leaves entries unchained
+    integrity_key: Arc<RwLock<Option<String>>>,
+    /// Serializes chained writes so each entry links to its true predecessor
+    chain_lock: Arc<Mutex<()>>,
 }
 
 impl AuditService {
@@ -25,6 +35,24 @@ impl AuditService {
         *self.db.write().await = Some(db);
     }
 
+    /// Enable integrity mode: every new entry is chained to the previous one
+    pub async fn set_integrity_key(&self, key: Option<String>) {
+        *self.integrity_key.write().await = key.filter(|k| !k.is_empty());
+    }
+
+    /// Whether new entries are being chained
+    pub async fn integrity_enabled(&self) -> bool {
+        self.integrity_key.read().await.is_some()
+    }
+
+    /// Validate the hash chain of all entries
+    pub async fn verify_chain(&self, db: &DbConn) -> Result<AuditChainVerification> {
+        match self.integrity_key.read().await.as_deref() {
+            Some(key) => verify_audit_chain(db, key).await,
+            None => Ok(AuditChainVerification::default()),
+        }
+    }
+
     /// Log an audit event
     #[allow(clippy::too_many_arguments)]
     pub async fn log(
@@ -49,10 +77,11 @@ impl AuditService {
             }
         };
 
-        let now = chrono::Utc::now();
+        // Postgres keeps microseconds; hash exactly what will be read back
+        let now = chrono::Utc::now().trunc_subsecs(6);
         let details_str = details.map(|d| d.to_string());
 
-        let log_entry = audit_log::ActiveModel {
+        let mut log_entry = audit_log::ActiveModel {
             timestamp: Set(now),
             user_id: Set(user_id),
             username: Set(username),
@@ -64,9 +93,27 @@ impl AuditService {
             user_agent: Set(user_agent),
             success: Set(success),
             error_message: Set(error_message),
+            request_id: Set(current_request_id()),
             ..Default::default()
         };
 
+        let integrity_key = self.integrity_key.read().await;
+        let Some(key) = integrity_key.as_deref() else {
+            log_entry.insert(db).await?;
+            return Ok(());
+        };
+
+        let _chain = self.chain_lock.lock().await;
+        let prev_hash = audit_log::Entity::find()
+            .filter(audit_log::Column::IntegrityHash.is_not_null())
+            .order_by_desc(audit_log::Column::Id)
+            .one(db)
+            .await?
+            .and_then(|entry| entry.integrity_hash)
+            .unwrap_or_default();
+        let hash = chain_hash(key, &prev_hash, &log_entry);
+        log_entry.prev_hash = Set(Some(prev_hash));
+        log_entry.integrity_hash = Set(Some(hash));
         log_entry.insert(db).await?;
         Ok(())
     }
@@ -137,10 +184,14 @@ pub struct AuditLogQuery {
     pub user_id: Option<i64>,
     pub action: Option<String>,
     pub resource_type: Option<String>,
+    /// e.g. the permission an `access_denied` entry was denied for
+    pub resource_id: Option<String>,
     pub success: Option<bool>,
     pub from: Option<chrono::DateTime<chrono::Utc>>,
     pub to: Option<chrono::DateTime<chrono::Utc>>,
     pub search: Option<String>,
+    /// Only entries written while handling this request
+    pub request_id: Option<String>,
 }
 
 /// Paginated audit log response
@@ -153,14 +204,140 @@ pub struct AuditLogResponse {
     pub total_pages: u64,
 }
 
+/// The slice of the audit log an app admin may see
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditScope {
+    /// Apps whose `app` entries are visible; `None` means every app
+    pub apps: Option<Vec<String>>,
+    /// Users whose own actions are visible
+    pub user_ids: Vec<i64>,
+}
+
+/// Entries hidden from a user by tenant isolation, on top of their scope
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditExclusion {
+    /// Apps whose `app` entries are hidden
+    pub apps: Vec<String>,
+    /// Users whose actions are hidden
+    pub user_ids: Vec<i64>,
+}
+
+/// Resolve the audit scope of a user holding `audit.view_own_apps`.
+///
+/// The scope covers entries about the apps the user's roles grant, plus
+/// actions by users whose roles grant one of those apps. Users that can
+/// see the whole audit log themselves are never part of someone's scope.
+pub async fn resolve_app_admin_scope(db: &DbConn, user_id: i64) -> Result<AuditScope> {
+    use crate::models::{role_app_permission, role_permission, user_role};
+
+    let apps = get_user_app_access(db, user_id).await;
+    if apps.is_empty() {
+        // Their own actions stay visible even without any app grants
+        return Ok(AuditScope {
+            apps: Some(vec![]),
+            user_ids: vec![user_id],
+        });
+    }
+    let all_apps = apps.iter().any(|a| a == "*");
+
+    // Roles granting at least one app in scope
+    let mut grants = role_app_permission::Entity::find();
+    if !all_apps {
+        grants = grants.filter(role_app_permission::Column::AppName.is_in(apps.clone()));
+    }
+    let mut role_ids: Vec<i64> = grants.all(db).await?.iter().map(|g| g.role_id).collect();
+    if all_apps {
+        let wildcard = role_permission::Entity::find()
+            .filter(role_permission::Column::Permission.eq("app.*"))
+            .all(db)
+            .await?;
+        role_ids.extend(wildcard.iter().map(|p| p.role_id));
+    }
+    role_ids.sort();
+    role_ids.dedup();
+
+    let mut user_ids: Vec<i64> = user_role::Entity::find()
+        .filter(user_role::Column::RoleId.is_in(role_ids))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.user_id)
+        .collect();
+    user_ids.push(user_id);
+    user_ids.sort();
+    user_ids.dedup();
+
+    // Global auditors are out of reach of app admins
+    let auditor_roles: Vec<i64> = role_permission::Entity::find()
+        .filter(role_permission::Column::Permission.eq("audit.view"))
+        .all(db)
+        .await?
+        .iter()
+        .map(|p| p.role_id)
+        .collect();
+    let auditors: Vec<i64> = user_role::Entity::find()
+        .filter(user_role::Column::RoleId.is_in(auditor_roles))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.user_id)
+        .collect();
+    user_ids.retain(|id| *id == user_id || !auditors.contains(id));
+
+    Ok(AuditScope {
+        apps: if all_apps { None } else { Some(apps) },
+        user_ids,
+    })
+}
+
 /// Get audit logs with filtering and pagination
 pub async fn get_audit_logs(db: &DbConn, query: AuditLogQuery) -> Result<AuditLogResponse> {
+    get_scoped_audit_logs(db, query, None, &AuditExclusion::default()).await
+}
+
+/// Get audit logs visible within `scope`, minus the `hidden` entries;
+/// `None` returns every entry
+pub async fn get_scoped_audit_logs(
+    db: &DbConn,
+    query: AuditLogQuery,
+    scope: Option<&AuditScope>,
+    hidden: &AuditExclusion,
+) -> Result<AuditLogResponse> {
     let page = query.page.unwrap_or(1).max(1);
     let per_page = query.per_page.unwrap_or(50).min(100);
     let offset = (page - 1) * per_page;
 
     let mut select = audit_log::Entity::find();
 
+    if let Some(scope) = scope {
+        let mut app_entries = audit_log::Column::ResourceType.eq(ResourceType::App.to_string());
+        if let Some(apps) = &scope.apps {
+            app_entries = app_entries.and(audit_log::Column::ResourceId.is_in(apps.clone()));
+        }
+        select = select.filter(
+            Condition::any()
+                .add(app_entries)
+                .add(audit_log::Column::UserId.is_in(scope.user_ids.clone())),
+        );
+    }
+    if !hidden.apps.is_empty() {
+        select = select.filter(
+            Condition::any()
+                .add(audit_log::Column::ResourceType.ne(ResourceType::App.to_string()))
+                .add(audit_log::Column::ResourceId.is_null())
+                .add(audit_log::Column::ResourceId.is_not_in(hidden.apps.clone())),
+        );
+    }
+    if !hidden.user_ids.is_empty() {
+        select = select.filter(
+            Condition::any()
+                .add(audit_log::Column::UserId.is_null())
+                .add(audit_log::Column::UserId.is_not_in(hidden.user_ids.clone())),
+        );
+    }
+
     // Apply filters
     if let Some(user_id) = query.user_id {
         select = select.filter(audit_log::Column::UserId.eq(user_id));
@@ -174,6 +351,10 @@ pub async fn get_audit_logs(db: &DbConn, query: AuditLogQuery) -> Result<AuditLo
         select = select.filter(audit_log::Column::ResourceType.eq(resource_type.clone()));
     }
 
+    if let Some(resource_id) = &query.resource_id {
+        select = select.filter(audit_log::Column::ResourceId.eq(resource_id.clone()));
+    }
+
     if let Some(success) = query.success {
         select = select.filter(audit_log::Column::Success.eq(success));
     }
@@ -186,6 +367,10 @@ pub async fn get_audit_logs(db: &DbConn, query: AuditLogQuery) -> Result<AuditLo
         select = select.filter(audit_log::Column::Timestamp.lte(to));
     }
 
+    if let Some(request_id) = &query.request_id {
+        select = select.filter(audit_log::Column::RequestId.eq(request_id.clone()));
+    }
+
     if let Some(search) = &query.search {
         let search_pattern = format!("%{}%", search);
         select = select.filter(
@@ -276,22 +461,22 @@ pub async fn get_audit_stats(db: &DbConn) -> Result<AuditStats> {
         .all(db)
         .await?;
 
-    // For top actions, we'll do a simple approach since SeaORM grouping is complex
-    // Fetch all logs to count actions (select all columns to avoid partial model issues)
-    let all_logs = audit_log::Entity::find().all(db).await?;
-
-    let mut action_counts: std::collections::HashMap<String, u64> =
-        std::collections::HashMap::new();
-    for log in all_logs {
-        *action_counts.entry(log.action.clone()).or_insert(0) += 1;
-    }
-
-    let mut top_actions: Vec<ActionCount> = action_counts
+    let top_actions: Vec<ActionCount> = audit_log::Entity::find()
+        .select_only()
+        .column(audit_log::Column::Action)
+        .column_as(audit_log::Column::Id.count(), "count")
+        .group_by(audit_log::Column::Action)
+        .order_by_desc(Expr::col(Alias::new("count")))
+        .limit(10)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
         .into_iter()
-        .map(|(action, count)| ActionCount { action, count })
+        .map(|(action, count)| ActionCount {
+            action,
+            count: count as u64,
+        })
         .collect();
-    top_actions.sort_by(|a, b| b.count.cmp(&a.count));
-    top_actions.truncate(10);
 
     Ok(AuditStats {
         total_events,
@@ -304,6 +489,324 @@ pub async fn get_audit_stats(db: &DbConn) -> Result<AuditStats> {
     })
 }
 
+/// Bucket width of an audit time series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeseriesInterval {
+    #[default]
+    Hour,
+    Day,
+}
+
+impl TimeseriesInterval {
+    pub(crate) fn duration(self) -> chrono::Duration {
+        match self {
+            TimeseriesInterval::Hour => chrono::Duration::hours(1),
+            TimeseriesInterval::Day => chrono::Duration::days(1),
+        }
+    }
+
+    /// SQL expression formatting `timestamp` as the RFC 3339 start of its
+    /// bucket, so both backends return the same bucket keys
+    pub(crate) fn bucket_expr(self, backend: DbBackend) -> SimpleExpr {
+        match (backend, self) {
+            (DbBackend::Postgres, TimeseriesInterval::Hour) => Expr::cust(
+                r#"to_char(date_trunc('hour', "timestamp" AT TIME ZONE 'UTC'), 'YYYY-MM-DD"T"HH24:00:00"Z"')"#,
+            ),
+            (DbBackend::Postgres, TimeseriesInterval::Day) => Expr::cust(
+                r#"to_char(date_trunc('day', "timestamp" AT TIME ZONE 'UTC'), 'YYYY-MM-DD"T"00:00:00"Z"')"#,
+            ),
+            (_, TimeseriesInterval::Hour) => {
+                Expr::cust(r#"strftime('%Y-%m-%dT%H:00:00Z', "timestamp")"#)
+            }
+            (_, TimeseriesInterval::Day) => {
+                Expr::cust(r#"strftime('%Y-%m-%dT00:00:00Z', "timestamp")"#)
+            }
+        }
+    }
+}
+
+/// Most buckets a single time series query may span
+pub const MAX_TIMESERIES_BUCKETS: i64 = 2000;
+
+/// Query parameters for an audit time series
+#[derive(Debug, Clone, Default, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditTimeseriesQuery {
+    /// Bucket width (default hour)
+    #[serde(default)]
+    pub interval: TimeseriesInterval,
+    /// Start of the range (default 7 days before `to`)
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// End of the range (default now)
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    /// Comma-separated dimensions to split counts by: action, success, user
+    /// (default action)
+    pub group_by: Option<String>,
+    pub action: Option<String>,
+    pub success: Option<bool>,
+    pub user_id: Option<i64>,
+    pub resource_type: Option<String>,
+}
+
+/// Event count of one bucket and combination of grouped dimensions
+///
+/// Dimensions that were not grouped by are omitted.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct AuditTimeseriesPoint {
+    /// Start of the bucket (UTC, RFC 3339)
+    pub bucket: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    pub count: i64,
+}
+
+impl FromQueryResult for AuditTimeseriesPoint {
+    fn from_query_result(res: &QueryResult, pre: &str) -> std::result::Result<Self, DbErr> {
+        // Ungrouped dimensions are not selected at all
+        Ok(Self {
+            bucket: res.try_get(pre, "bucket")?,
+            action: res.try_get(pre, "action").ok().flatten(),
+            success: res.try_get(pre, "success").ok().flatten(),
+            user_id: res.try_get(pre, "user_id").ok().flatten(),
+            username: res.try_get(pre, "username").ok().flatten(),
+            count: res.try_get(pre, "count")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AuditTimeseries {
+    pub interval: TimeseriesInterval,
+    pub from: chrono::DateTime<chrono::Utc>,
+    pub to: chrono::DateTime<chrono::Utc>,
+    pub group_by: Vec<String>,
+    /// Non-empty buckets, oldest first
+    pub points: Vec<AuditTimeseriesPoint>,
+}
+
+/// Count audit events per time bucket, aggregated in the database
+pub async fn get_audit_timeseries(
+    db: &DbConn,
+    query: AuditTimeseriesQuery,
+) -> Result<AuditTimeseries> {
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(7));
+    if from >= to {
+        return Err(AppError::BadRequest(
+            "'from' must be before 'to'".to_string(),
+        ));
+    }
+    let buckets = (to - from).num_seconds() / query.interval.duration().num_seconds();
+    if buckets > MAX_TIMESERIES_BUCKETS {
+        return Err(AppError::BadRequest(format!(
+            "Range spans {} buckets, at most {} are allowed; use a larger interval",
+            buckets, MAX_TIMESERIES_BUCKETS
+        )));
+    }
+
+    let mut group_by = Vec::new();
+    for dimension in query.group_by.as_deref().unwrap_or("action").split(',') {
+        let dimension = dimension.trim();
+        if dimension.is_empty() || group_by.iter().any(|d| d == dimension) {
+            continue;
+        }
+        if !["action", "success", "user"].contains(&dimension) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid group_by '{}', expected: action, success, user",
+                dimension
+            )));
+        }
+        group_by.push(dimension.to_string());
+    }
+
+    let bucket = query.interval.bucket_expr(db.get_database_backend());
+    let mut select = audit_log::Entity::find()
+        .select_only()
+        .column_as(bucket.clone(), "bucket")
+        .filter(audit_log::Column::Timestamp.gte(from))
+        .filter(audit_log::Column::Timestamp.lt(to))
+        .group_by(bucket.clone());
+    for dimension in &group_by {
+        let columns: &[audit_log::Column] = match dimension.as_str() {
+            "action" => &[audit_log::Column::Action],
+            "success" => &[audit_log::Column::Success],
+            _ => &[audit_log::Column::UserId, audit_log::Column::Username],
+        };
+        for column in columns {
+            select = select.column(*column).group_by(*column);
+        }
+    }
+    select = select.column_as(audit_log::Column::Id.count(), "count");
+
+    if let Some(action) = &query.action {
+        select = select.filter(audit_log::Column::Action.eq(action.clone()));
+    }
+    if let Some(success) = query.success {
+        select = select.filter(audit_log::Column::Success.eq(success));
+    }
+    if let Some(user_id) = query.user_id {
+        select = select.filter(audit_log::Column::UserId.eq(user_id));
+    }
+    if let Some(resource_type) = &query.resource_type {
+        select = select.filter(audit_log::Column::ResourceType.eq(resource_type.clone()));
+    }
+
+    let mut points = select.into_model::<AuditTimeseriesPoint>().all(db).await?;
+    points.sort_by(|a, b| a.bucket.cmp(&b.bucket).then(b.count.cmp(&a.count)));
+
+    Ok(AuditTimeseries {
+        interval: query.interval,
+        from,
+        to,
+        group_by,
+        points,
+    })
+}
+
+// ============================================================================
+// Integrity chain
+// ============================================================================
+
+/// Entries read per batch while verifying the chain
+const VERIFY_BATCH_SIZE: u64 = 1000;
+
+/// HMAC-SHA256 over the previous entry's hash and this entry's contents
+///
+/// The row id is left out because it is only assigned on insert; ordering is
+/// covered by each entry committing to its predecessor.
+fn chain_hash(key: &str, prev_hash: &str, entry: &audit_log::ActiveModel) -> String {
+    let content = serde_json::json!([
+        entry
+            .timestamp
+            .try_as_ref()
+            .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)),
+        entry.user_id.try_as_ref(),
+        entry.username.try_as_ref(),
+        entry.action.try_as_ref(),
+        entry.resource_type.try_as_ref(),
+        entry.resource_id.try_as_ref(),
+        entry.details.try_as_ref(),
+        entry.ip_address.try_as_ref(),
+        entry.user_agent.try_as_ref(),
+        entry.success.try_as_ref(),
+        entry.error_message.try_as_ref(),
+        entry.request_id.try_as_ref(),
+    ]);
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(prev_hash.as_bytes());
+    mac.update(b"\n");
+    mac.update(content.to_string().as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Why an entry breaks the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainBreakReason {
+    /// The entry's contents no longer match its hash
+    ContentMismatch,
+    /// The entry does not link to the hash of the entry before it
+    PrevHashMismatch,
+    /// An entry written after chaining started carries no hash
+    MissingHash,
+}
+
+/// First entry at which the chain stops validating
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct AuditChainBreak {
+    pub id: i64,
+    #[schema(value_type = String)]
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub reason: ChainBreakReason,
+}
+
+/// Result of validating the audit hash chain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct AuditChainVerification {
+    /// Whether integrity mode is configured on this server
+    pub enabled: bool,
+    /// Chained entries checked, up to the first break
+    pub checked: u64,
+    /// Entries written before integrity mode was enabled
+    pub unchained: u64,
+    pub valid: bool,
+    pub first_broken: Option<AuditChainBreak>,
+}
+
+/// Walk all entries in insertion order and report the first broken link.
+///
+/// Entries from before integrity mode was enabled are skipped. The oldest
+/// chained entry is trusted to link to its stored predecessor hash, so
+/// retention cleanup of old entries does not register as tampering.
+pub async fn verify_audit_chain(db: &DbConn, key: &str) -> Result<AuditChainVerification> {
+    let mut result = AuditChainVerification {
+        enabled: true,
+        valid: true,
+        ..Default::default()
+    };
+    let mut prev_hash: Option<String> = None;
+    let mut last_id = i64::MIN;
+
+    loop {
+        let batch = audit_log::Entity::find()
+            .filter(audit_log::Column::Id.gt(last_id))
+            .order_by_asc(audit_log::Column::Id)
+            .limit(VERIFY_BATCH_SIZE)
+            .all(db)
+            .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        last_id = last.id;
+
+        for entry in batch {
+            let reason = match (&entry.integrity_hash, &prev_hash) {
+                (None, None) => {
+                    result.unchained += 1;
+                    continue;
+                }
+                (None, Some(_)) => Some(ChainBreakReason::MissingHash),
+                (Some(hash), expected_prev) => {
+                    let stored_prev = entry.prev_hash.clone().unwrap_or_default();
+                    if expected_prev.as_ref().is_some_and(|p| *p != stored_prev) {
+                        Some(ChainBreakReason::PrevHashMismatch)
+                    } else {
+                        let active: audit_log::ActiveModel = entry.clone().into();
+                        if chain_hash(key, &stored_prev, &active) != *hash {
+                            Some(ChainBreakReason::ContentMismatch)
+                        } else {
+                            None
+                        }
+                    }
+                }
+            };
+
+            if let Some(reason) = reason {
+                result.valid = false;
+                result.first_broken = Some(AuditChainBreak {
+                    id: entry.id,
+                    timestamp: entry.timestamp,
+                    reason,
+                });
+                return Ok(result);
+            }
+            result.checked += 1;
+            prev_hash = entry.integrity_hash;
+        }
+    }
+
+    Ok(result)
+}
+
 /// Clear old audit logs (retention policy)
 pub async fn clear_old_logs(db: &DbConn, days: i64) -> Result<u64> {
     let cutoff = chrono::Utc::now() - chrono::Duration::days(days);
@@ -0,0 +1,301 @@
+//! Authentication analytics
+//!
+//! Summarizes sign-ins from the audit log: successful and failed sign-ins
+//! per hour or day, the failure ratio, how sign-ins split between password,
+//! recovery code and OAuth, and the addresses with the most failures. 2FA
+//! adoption is the share of active users with an authenticator set up;
+//! service accounts, which never sign in, are not counted.
+//!
+//! Everything is counted with SQL aggregates; the `(action, timestamp)` and
+//! `ip_address` indexes on `audit_logs` keep this fast on large logs.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::{Alias, Expr};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+
+use super::audit::{TimeseriesInterval, MAX_TIMESERIES_BUCKETS};
+use super::ip_bans;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{self, AuditAction};
+use crate::models::prelude::*;
+use crate::models::user;
+
+/// Addresses listed in `top_failing_ips`
+const TOP_FAILING_IPS: u64 = 10;
+
+/// Sign-in method of successful sign-ins audited without one
+const DEFAULT_METHOD: &str = "password";
+
+/// Query parameters for authentication analytics
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuthAnalyticsQuery {
+    /// Bucket width of `buckets` (default hour)
+    #[serde(default)]
+    pub interval: TimeseriesInterval,
+    /// Start of the range (default 7 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (default now)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Sign-ins in one bucket
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SignInBucket {
+    /// Start of the bucket (UTC, RFC 3339)
+    pub bucket: String,
+    pub successful: i64,
+    pub failed: i64,
+}
+
+/// Successful sign-ins with one method
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SignInMethodShare {
+    /// "password", "recovery_code" or "oauth"
+    pub method: String,
+    /// OAuth provider, for OAuth sign-ins
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    pub logins: i64,
+    /// Fraction of all successful sign-ins, 0 to 1
+    pub share: f64,
+}
+
+/// Failed sign-ins from one address
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct FailingAddress {
+    pub ip_address: String,
+    pub failures: i64,
+    /// Whether the address is banned right now
+    pub banned: bool,
+}
+
+/// Active users with 2FA turned on
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct TwoFactorAdoption {
+    pub active_users: u64,
+    pub enabled: u64,
+    /// Fraction of active users with 2FA, 0 to 1
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AuthAnalytics {
+    pub interval: TimeseriesInterval,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub successful_logins: i64,
+    pub failed_logins: i64,
+    /// Failed over all sign-in attempts, 0 to 1
+    pub failure_ratio: f64,
+    /// Non-empty buckets, oldest first
+    pub buckets: Vec<SignInBucket>,
+    /// Most used first
+    pub methods: Vec<SignInMethodShare>,
+    /// Most failures first
+    pub top_failing_ips: Vec<FailingAddress>,
+    pub two_factor: TwoFactorAdoption,
+}
+
+fn ratio(part: i64, total: i64) -> f64 {
+    if total <= 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Method and OAuth provider from the details of a successful sign-in
+fn sign_in_method(details: Option<&str>) -> (String, Option<String>) {
+    let details: serde_json::Value = details
+        .and_then(|d| serde_json::from_str(d).ok())
+        .unwrap_or_default();
+    let method = details["method"]
+        .as_str()
+        .unwrap_or(DEFAULT_METHOD)
+        .to_string();
+    let provider = details["provider"].as_str().map(str::to_string);
+    (method, provider)
+}
+
+/// Summarize sign-ins between `from` and `to`
+pub async fn summarize(
+    db: &DatabaseConnection,
+    query: AuthAnalyticsQuery,
+    now: DateTime<Utc>,
+) -> Result<AuthAnalytics> {
+    let to = query.to.unwrap_or(now);
+    let from = query.from.unwrap_or(to - Duration::days(7));
+    if from >= to {
+        return Err(AppError::BadRequest(
+            "'from' must be before 'to'".to_string(),
+        ));
+    }
+    let buckets = (to - from).num_seconds() / query.interval.duration().num_seconds();
+    if buckets > MAX_TIMESERIES_BUCKETS {
+        return Err(AppError::BadRequest(format!(
+            "Range spans {} buckets, at most {} are allowed; use a larger interval",
+            buckets, MAX_TIMESERIES_BUCKETS
+        )));
+    }
+
+    let login = AuditAction::Login.to_string();
+    let failure_actions = ip_bans::failure_actions();
+    let mut actions = failure_actions.to_vec();
+    actions.push(login.clone());
+    let in_range = || {
+        AuditLog::find()
+            .filter(audit_log::Column::Timestamp.gte(from))
+            .filter(audit_log::Column::Timestamp.lt(to))
+    };
+
+    let bucket = query.interval.bucket_expr(db.get_database_backend());
+    let counts = in_range()
+        .select_only()
+        .column_as(bucket.clone(), "bucket")
+        .column(audit_log::Column::Action)
+        .column_as(audit_log::Column::Id.count(), "count")
+        .filter(audit_log::Column::Action.is_in(actions))
+        .group_by(bucket)
+        .group_by(audit_log::Column::Action)
+        .into_tuple::<(String, String, i64)>()
+        .all(db)
+        .await?;
+    let mut series: Vec<SignInBucket> = Vec::new();
+    for (bucket, action, count) in counts {
+        let entry = match series.iter_mut().position(|b| b.bucket == bucket) {
+            Some(i) => &mut series[i],
+            None => {
+                series.push(SignInBucket {
+                    bucket,
+                    successful: 0,
+                    failed: 0,
+                });
+                series.last_mut().unwrap()
+            }
+        };
+        if action == login {
+            entry.successful += count;
+        } else {
+            entry.failed += count;
+        }
+    }
+    series.sort_by(|a, b| a.bucket.cmp(&b.bucket));
+    let successful_logins: i64 = series.iter().map(|b| b.successful).sum();
+    let failed_logins: i64 = series.iter().map(|b| b.failed).sum();
+
+    // Details of successful sign-ins only hold the method and provider, so
+    // grouping by them yields a handful of rows
+    let by_details = in_range()
+        .select_only()
+        .column(audit_log::Column::Details)
+        .column_as(audit_log::Column::Id.count(), "count")
+        .filter(audit_log::Column::Action.eq(login))
+        .group_by(audit_log::Column::Details)
+        .into_tuple::<(Option<String>, i64)>()
+        .all(db)
+        .await?;
+    let mut methods: Vec<SignInMethodShare> = Vec::new();
+    for (details, count) in by_details {
+        let (method, provider) = sign_in_method(details.as_deref());
+        match methods
+            .iter_mut()
+            .find(|m| m.method == method && m.provider == provider)
+        {
+            Some(share) => share.logins += count,
+            None => methods.push(SignInMethodShare {
+                method,
+                provider,
+                logins: count,
+                share: 0.0,
+            }),
+        }
+    }
+    for share in &mut methods {
+        share.share = ratio(share.logins, successful_logins);
+    }
+    methods.sort_by(|a, b| {
+        b.logins
+            .cmp(&a.logins)
+            .then_with(|| a.method.cmp(&b.method))
+            .then_with(|| a.provider.cmp(&b.provider))
+    });
+
+    let banned: Vec<String> = ip_bans::list_bans(db, now)
+        .await?
+        .into_iter()
+        .map(|b| b.ip_address)
+        .collect();
+    let top_failing_ips = in_range()
+        .select_only()
+        .column(audit_log::Column::IpAddress)
+        .column_as(audit_log::Column::Id.count(), "failures")
+        .filter(audit_log::Column::Action.is_in(failure_actions))
+        .filter(audit_log::Column::IpAddress.is_not_null())
+        .group_by(audit_log::Column::IpAddress)
+        .order_by_desc(Expr::col(Alias::new("failures")))
+        .limit(TOP_FAILING_IPS)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(ip_address, failures)| FailingAddress {
+            banned: banned.contains(&ip_address),
+            ip_address,
+            failures,
+        })
+        .collect();
+
+    let active_users = || {
+        User::find()
+            .filter(user::Column::IsActive.eq(true))
+            .filter(user::Column::IsApproved.eq(true))
+            .filter(user::Column::IsServiceAccount.eq(false))
+    };
+    let active = active_users().count(db).await?;
+    let enabled = active_users()
+        .filter(user::Column::TotpEnabled.eq(true))
+        .count(db)
+        .await?;
+
+    Ok(AuthAnalytics {
+        interval: query.interval,
+        from,
+        to,
+        successful_logins,
+        failed_logins,
+        failure_ratio: ratio(failed_logins, successful_logins + failed_logins),
+        buckets: series,
+        methods,
+        top_failing_ips,
+        two_factor: TwoFactorAdoption {
+            active_users: active,
+            enabled,
+            ratio: ratio(enabled as i64, active as i64),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_in_method() {
+        assert_eq!(
+            sign_in_method(Some(r#"{"method":"oauth","provider":"google"}"#)),
+            ("oauth".to_string(), Some("google".to_string()))
+        );
+        assert_eq!(
+            sign_in_method(Some(r#"{"method":"recovery_code"}"#)),
+            ("recovery_code".to_string(), None)
+        );
+        assert_eq!(sign_in_method(None), ("password".to_string(), None));
+        assert_eq!(ratio(1, 4), 0.25);
+        assert_eq!(ratio(0, 0), 0.0);
+    }
+}
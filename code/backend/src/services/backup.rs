@@ -0,0 +1,1075 @@
+//! Backup and restore of Kubarr's database
+//!
+//! [`create`] packs the whole database into a `.tar.gz`: a `manifest.json`
+//! and one `database/<table>.json` per table holding its rows as a JSON
+//! array. That covers users and roles, system settings, notification
+//! channels, VPN and OAuth providers and everything else Kubarr stores.
+//! Sessions and pending 2FA challenges are left out, and so are the
+//! instance's JWT signing keys.
+//!
+//! Credentials in [`SECRET_COLUMNS`] are encrypted with AES-256-GCM under a
+//! key derived from a passphrase with PBKDF2, so an archive can be kept
+//! off-site without handing out provider passwords and tokens. Restoring it
+//! takes the same passphrase. Credentials encrypted at rest by
+//! [`crypto`](super::crypto) are decrypted first and encrypted again with
+//! this instance's key on restore, so a backup does not depend on the
+//! encryption key of the instance that made it.
+//!
+//! [`restore`] replaces the contents of every table with the backup's in a
+//! single transaction. The backup must come from the same database backend
+//! and the same set of migrations, i.e. the Kubarr version that made it.
+//! Restoring signs everyone out.
+//!
+//! [`BackupTask`] makes a backup whenever the cron expression in
+//! `backup_schedule` matches (in UTC), writes it to `KUBARR_BACKUP_DIR`
+//! encrypted with `KUBARR_BACKUP_PASSPHRASE`, and keeps the newest
+//! `backup_retention_count`. A failed run sends a `backup_failed`
+//! notification.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Datelike, DurationRound, Timelike, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, FromQueryResult, JsonValue, Statement,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use utoipa::ToSchema;
+
+use super::audit::AuditService;
+use super::catalog_bundle::{read_tar, unpack};
+use super::crypto;
+use super::diagnostics::append_tar_entry;
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use super::security::{JWT_PRIVATE_KEY_SETTING, JWT_PUBLIC_KEY_SETTING};
+use crate::config::CONFIG;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{AuditAction, ResourceType};
+
+/// Setting with the cron expression scheduled backups run on
+pub const BACKUP_SCHEDULE: &str = "backup_schedule";
+
+/// Setting with the number of scheduled backups kept
+pub const BACKUP_RETENTION_COUNT: &str = "backup_retention_count";
+
+/// Retention used when the setting is not a number
+const DEFAULT_RETENTION_COUNT: usize = 7;
+
+/// Largest archive accepted for restore
+pub const MAX_BACKUP_BYTES: usize = 512 * 1024 * 1024;
+
+/// Shortest passphrase accepted for encrypting secrets
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Version of the archive layout
+const FORMAT_VERSION: u32 = 1;
+
+/// Directory inside the archive
+const ROOT: &str = "kubarr-backup";
+
+/// Prefix of encrypted values in the archive
+const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// Encrypted into the manifest, so a wrong passphrase is told apart from a
+/// damaged archive
+const PASSPHRASE_CHECK: &str = "kubarr-backup";
+
+const PBKDF2_ROUNDS: u32 = 100_000;
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 12;
+
+/// Rows inserted per statement on restore
+const INSERT_BATCH: usize = 200;
+
+const MIGRATIONS_TABLE: &str = "seaql_migrations";
+
+/// Tables that are neither backed up nor restored; sessions and challenges
+/// are emptied on restore
+const EXCLUDED_TABLES: &[&str] = &[MIGRATIONS_TABLE, "sessions", "pending_2fa_challenges"];
+
+/// Columns holding credentials, encrypted in the archive
+pub const SECRET_COLUMNS: &[(&str, &str)] = &[
+    ("users", "totp_secret"),
+    ("share_credentials", "nt_hash"),
+    ("oauth_providers", "client_secret"),
+    ("oauth_accounts", "access_token"),
+    ("oauth_accounts", "refresh_token"),
+    ("vpn_providers", "credentials_json"),
+    ("notification_channels", "config"),
+    ("cloudflare_tunnels", "tunnel_token"),
+    ("cloudflare_tunnels", "api_token"),
+    ("registries", "password"),
+    ("app_integrations", "credentials_json"),
+];
+
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+type Row = serde_json::Map<String, JsonValue>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    kubarr_version: String,
+    created_at: DateTime<Utc>,
+    /// "sqlite" or "postgres"
+    database: String,
+    /// Applied migrations, oldest first
+    migrations: Vec<String>,
+    /// PBKDF2 salt, hex
+    salt: String,
+    /// [`PASSPHRASE_CHECK`], encrypted
+    check: String,
+    /// Rows per table
+    tables: BTreeMap<String, usize>,
+}
+
+/// Outcome of a restore
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RestoreSummary {
+    /// Kubarr version that made the backup
+    pub kubarr_version: String,
+    pub created_at: DateTime<Utc>,
+    pub tables: usize,
+    pub rows: usize,
+}
+
+/// A scheduled backup in `KUBARR_BACKUP_DIR`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupFile {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Encryption
+// ============================================================================
+
+struct Cipher(Aes256Gcm);
+
+impl Cipher {
+    fn new(passphrase: &str, salt: &[u8]) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+        Self(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::Internal("Failed to encrypt a backup secret".to_string()))?;
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(data)
+        ))
+    }
+
+    /// Decrypt a value written by [`Self::encrypt`]; `None` if the key is
+    /// wrong or the value was altered
+    fn decrypt(&self, value: &str) -> Option<String> {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(value.strip_prefix(ENCRYPTED_PREFIX)?)
+            .ok()?;
+        if data.len() < NONCE_BYTES {
+            return None;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_BYTES);
+        let plaintext = self.0.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+fn is_secret(table: &str, column: &str) -> bool {
+    SECRET_COLUMNS.contains(&(table, column))
+}
+
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::BadRequest(format!(
+            "The backup passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Database access
+// ============================================================================
+
+fn backend_name(backend: DatabaseBackend) -> &'static str {
+    match backend {
+        DatabaseBackend::Sqlite => "sqlite",
+        DatabaseBackend::Postgres => "postgres",
+        DatabaseBackend::MySql => "mysql",
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Rows that belong to the running instance: left out of backups and kept
+/// on restore
+fn instance_rows(table: &str) -> Option<String> {
+    (table == "system_settings").then(|| {
+        format!(
+            "\"key\" IN ('{}', '{}')",
+            JWT_PRIVATE_KEY_SETTING, JWT_PUBLIC_KEY_SETTING
+        )
+    })
+}
+
+/// First column of every row as a string
+async fn strings<C: ConnectionTrait>(
+    db: &C,
+    sql: &str,
+    values: Vec<sea_orm::Value>,
+) -> Result<Vec<String>> {
+    let statement = Statement::from_sql_and_values(db.get_database_backend(), sql, values);
+    db.query_all(statement)
+        .await?
+        .iter()
+        .map(|row| row.try_get_by_index::<String>(0).map_err(Into::into))
+        .collect()
+}
+
+async fn table_names<C: ConnectionTrait>(db: &C) -> Result<Vec<String>> {
+    let sql = match db.get_database_backend() {
+        DatabaseBackend::Sqlite => {
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+        }
+        _ => {
+            "SELECT table_name::text FROM information_schema.tables \
+             WHERE table_schema = current_schema() AND table_type = 'BASE TABLE' ORDER BY table_name"
+        }
+    };
+    strings(db, sql, vec![]).await
+}
+
+async fn column_names<C: ConnectionTrait>(db: &C, table: &str) -> Result<Vec<String>> {
+    let sql = match db.get_database_backend() {
+        DatabaseBackend::Sqlite => "SELECT name FROM pragma_table_info(?) ORDER BY cid",
+        _ => {
+            "SELECT column_name::text FROM information_schema.columns \
+             WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position"
+        }
+    };
+    strings(db, sql, vec![table.into()]).await
+}
+
+/// Foreign keys as (table, referenced table)
+async fn foreign_keys<C: ConnectionTrait>(db: &C) -> Result<Vec<(String, String)>> {
+    let sql = match db.get_database_backend() {
+        DatabaseBackend::Sqlite => {
+            "SELECT m.name, p.\"table\" FROM sqlite_master m \
+             JOIN pragma_foreign_key_list(m.name) p WHERE m.type = 'table'"
+        }
+        _ => {
+            "SELECT cl.relname::text, ref.relname::text FROM pg_constraint c \
+             JOIN pg_class cl ON cl.oid = c.conrelid \
+             JOIN pg_class ref ON ref.oid = c.confrelid \
+             JOIN pg_namespace n ON n.oid = cl.relnamespace \
+             WHERE c.contype = 'f' AND n.nspname = current_schema()"
+        }
+    };
+    let statement = Statement::from_string(db.get_database_backend(), sql);
+    db.query_all(statement)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok((
+                row.try_get_by_index::<String>(0)?,
+                row.try_get_by_index::<String>(1)?,
+            ))
+        })
+        .collect()
+}
+
+async fn applied_migrations<C: ConnectionTrait>(db: &C) -> Result<Vec<String>> {
+    strings(
+        db,
+        &format!(
+            "SELECT version FROM {} ORDER BY version",
+            quote(MIGRATIONS_TABLE)
+        ),
+        vec![],
+    )
+    .await
+}
+
+/// Tables ordered so every table comes after the tables it references;
+/// tables in a reference cycle follow in name order
+fn dependency_order(tables: &[String], foreign_keys: &[(String, String)]) -> Vec<String> {
+    let mut pending: BTreeMap<&str, BTreeSet<&str>> = tables
+        .iter()
+        .map(|t| (t.as_str(), BTreeSet::new()))
+        .collect();
+    for (table, referenced) in foreign_keys {
+        if table != referenced && pending.contains_key(referenced.as_str()) {
+            if let Some(parents) = pending.get_mut(table.as_str()) {
+                parents.insert(referenced.as_str());
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(tables.len());
+    while !pending.is_empty() {
+        let mut ready: Vec<&str> = pending
+            .iter()
+            .filter(|(_, parents)| parents.is_empty())
+            .map(|(table, _)| *table)
+            .collect();
+        if ready.is_empty() {
+            ready = pending.keys().copied().collect();
+        }
+        for table in &ready {
+            pending.remove(table);
+        }
+        for parents in pending.values_mut() {
+            for table in &ready {
+                parents.remove(table);
+            }
+        }
+        order.extend(ready.into_iter().map(str::to_string));
+    }
+    order
+}
+
+async fn dump_table<C: ConnectionTrait>(db: &C, table: &str) -> Result<Vec<Row>> {
+    let mut sql = format!("SELECT * FROM {}", quote(table));
+    if let Some(condition) = instance_rows(table) {
+        sql.push_str(&format!(" WHERE NOT ({})", condition));
+    }
+    let rows = JsonValue::find_by_statement(Statement::from_string(db.get_database_backend(), sql))
+        .all(db)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| match row {
+            JsonValue::Object(row) => Some(row),
+            _ => None,
+        })
+        .collect())
+}
+
+async fn insert_rows<C: ConnectionTrait>(
+    db: &C,
+    table: &str,
+    columns: &[String],
+    rows: &[Row],
+) -> Result<()> {
+    let backend = db.get_database_backend();
+    let names = columns
+        .iter()
+        .map(|c| quote(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = match backend {
+        DatabaseBackend::Sqlite => {
+            let values = columns
+                .iter()
+                .map(|c| {
+                    format!(
+                        "json_extract(value, '$.\"{}\"')",
+                        c.replace('\'', "''").replace('"', "\\\"")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "INSERT INTO {} ({}) SELECT {} FROM json_each(?)",
+                quote(table),
+                names,
+                values
+            )
+        }
+        _ => format!(
+            "INSERT INTO {table} ({names}) SELECT {names} FROM json_populate_recordset(NULL::{table}, $1::json)",
+            table = quote(table),
+            names = names
+        ),
+    };
+    for batch in rows.chunks(INSERT_BATCH) {
+        let json = serde_json::to_string(batch)?;
+        db.execute(Statement::from_sql_and_values(
+            backend,
+            sql.as_str(),
+            vec![json.into()],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+/// Move PostgreSQL sequences past the restored IDs
+async fn reset_sequences<C: ConnectionTrait>(db: &C, tables: &[String]) -> Result<()> {
+    for table in tables {
+        let sequence = db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT pg_get_serial_sequence($1, 'id')",
+                vec![quote(table).into()],
+            ))
+            .await?
+            .and_then(|row| row.try_get_by_index::<Option<String>>(0).ok().flatten());
+        let Some(sequence) = sequence else {
+            continue;
+        };
+        db.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                "SELECT setval($1, COALESCE((SELECT MAX(\"id\") FROM {}), 0) + 1, false)",
+                quote(table)
+            ),
+            vec![sequence.into()],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Backup and restore
+// ============================================================================
+
+/// Back up the database, encrypting secrets with `passphrase`
+pub async fn create(db: &DatabaseConnection, passphrase: &str) -> Result<Vec<u8>> {
+    check_passphrase(passphrase)?;
+    let mut salt = [0u8; SALT_BYTES];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Cipher::new(passphrase, &salt);
+
+    // One transaction for a consistent snapshot
+    let txn = db.begin().await?;
+    let migrations = applied_migrations(&txn).await?;
+    let mut files = Vec::new();
+    let mut tables = BTreeMap::new();
+    for table in table_names(&txn).await? {
+        if EXCLUDED_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+        let mut rows = dump_table(&txn, &table).await?;
+        for row in &mut rows {
+            for (column, value) in row.iter_mut() {
+                if let (true, Some(stored)) = (is_secret(&table, column), value.as_str()) {
+                    let plaintext = crypto::reveal_column(&table, column, stored)?;
+                    *value = JsonValue::String(cipher.encrypt(&plaintext)?);
+                }
+            }
+        }
+        tables.insert(table.clone(), rows.len());
+        let json = serde_json::to_vec(&rows)?;
+        files.push((format!("database/{}.json", table), json));
+    }
+    txn.commit().await?;
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        kubarr_version: CONFIG.version.clone(),
+        created_at: Utc::now(),
+        database: backend_name(db.get_database_backend()).to_string(),
+        migrations,
+        salt: hex::encode(salt),
+        check: cipher.encrypt(PASSPHRASE_CHECK)?,
+        tables,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+    files.insert(0, ("manifest.json".to_string(), manifest));
+
+    let mtime = Utc::now().timestamp().max(0) as u64;
+    let mut tar = Vec::new();
+    for (name, data) in &files {
+        append_tar_entry(&mut tar, &format!("{}/{}", ROOT, name), data, mtime);
+    }
+    // End of archive: two zero blocks
+    tar.extend_from_slice(&[0u8; 1024]);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&tar)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| AppError::Internal(format!("Failed to compress backup: {}", e)))
+}
+
+struct Archive {
+    manifest: Manifest,
+    tables: BTreeMap<String, Vec<Row>>,
+}
+
+fn read_archive(data: &[u8]) -> Result<Archive> {
+    let invalid = |file: &str, e: serde_json::Error| {
+        AppError::BadRequest(format!("Backup file '{}' is not valid: {}", file, e))
+    };
+    let mut manifest: Option<Manifest> = None;
+    let mut tables = BTreeMap::new();
+    for (path, contents) in read_tar(&unpack(data)?)? {
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        match path.as_slice() {
+            [ROOT, "manifest.json"] => {
+                manifest = Some(
+                    serde_json::from_slice(&contents).map_err(|e| invalid("manifest.json", e))?,
+                );
+            }
+            [ROOT, "database", file] => {
+                if let Some(table) = file.strip_suffix(".json") {
+                    let rows = serde_json::from_slice(&contents).map_err(|e| invalid(file, e))?;
+                    tables.insert(table.to_string(), rows);
+                }
+            }
+            _ => {}
+        }
+    }
+    let manifest = manifest.ok_or_else(|| {
+        AppError::BadRequest("Not a Kubarr backup: manifest.json is missing".to_string())
+    })?;
+    if manifest.format_version != FORMAT_VERSION {
+        return Err(AppError::BadRequest(format!(
+            "Backup format {} is not supported",
+            manifest.format_version
+        )));
+    }
+    Ok(Archive { manifest, tables })
+}
+
+/// Replace the database contents with a backup's
+pub async fn restore(
+    db: &DatabaseConnection,
+    data: &[u8],
+    passphrase: &str,
+) -> Result<RestoreSummary> {
+    let Archive {
+        manifest,
+        mut tables,
+    } = read_archive(data)?;
+    let salt = hex::decode(&manifest.salt)
+        .map_err(|_| AppError::BadRequest("Backup manifest has an invalid salt".to_string()))?;
+    let cipher = Cipher::new(passphrase, &salt);
+    if cipher.decrypt(&manifest.check).as_deref() != Some(PASSPHRASE_CHECK) {
+        return Err(AppError::BadRequest(
+            "Wrong passphrase for this backup".to_string(),
+        ));
+    }
+
+    let backend = db.get_database_backend();
+    if manifest.database != backend_name(backend) {
+        return Err(AppError::BadRequest(format!(
+            "Backup was made from a {} database, but Kubarr uses {}",
+            manifest.database,
+            backend_name(backend)
+        )));
+    }
+
+    for (table, rows) in tables.iter_mut() {
+        for row in rows.iter_mut() {
+            for (column, value) in row.iter_mut() {
+                let Some(encrypted) = value.as_str().filter(|_| is_secret(table, column)) else {
+                    continue;
+                };
+                let plaintext = cipher.decrypt(encrypted).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Backup secret {}.{} could not be decrypted",
+                        table, column
+                    ))
+                })?;
+                *value = JsonValue::String(crypto::conceal_column(table, column, &plaintext)?);
+            }
+        }
+    }
+
+    let txn = db.begin().await?;
+    if applied_migrations(&txn).await? != manifest.migrations {
+        return Err(AppError::BadRequest(format!(
+            "Backup was made by Kubarr {} with a different database schema; restore it with that version",
+            manifest.kubarr_version
+        )));
+    }
+    if backend == DatabaseBackend::Sqlite {
+        txn.execute_unprepared("PRAGMA defer_foreign_keys = ON")
+            .await?;
+    }
+
+    let current: Vec<String> = table_names(&txn)
+        .await?
+        .into_iter()
+        .filter(|t| t != MIGRATIONS_TABLE)
+        .collect();
+    let order = dependency_order(&current, &foreign_keys(&txn).await?);
+    for table in order.iter().rev() {
+        let mut sql = format!("DELETE FROM {}", quote(table));
+        if let Some(condition) = instance_rows(table) {
+            sql.push_str(&format!(" WHERE NOT ({})", condition));
+        }
+        txn.execute_unprepared(&sql).await?;
+    }
+
+    let mut restored_tables = 0;
+    let mut restored_rows = 0;
+    for table in &order {
+        let Some(rows) = tables.get(table).filter(|rows| !rows.is_empty()) else {
+            continue;
+        };
+        let columns: Vec<String> = column_names(&txn, table)
+            .await?
+            .into_iter()
+            .filter(|c| rows[0].contains_key(c))
+            .collect();
+        insert_rows(&txn, table, &columns, rows).await?;
+        restored_tables += 1;
+        restored_rows += rows.len();
+    }
+    if backend == DatabaseBackend::Postgres {
+        reset_sequences(&txn, &order).await?;
+    }
+    txn.commit().await?;
+
+    tracing::info!(
+        tables = restored_tables,
+        rows = restored_rows,
+        "Restored backup made by Kubarr {} at {}",
+        manifest.kubarr_version,
+        manifest.created_at
+    );
+    Ok(RestoreSummary {
+        kubarr_version: manifest.kubarr_version,
+        created_at: manifest.created_at,
+        tables: restored_tables,
+        rows: restored_rows,
+    })
+}
+
+// ============================================================================
+// Schedule
+// ============================================================================
+
+/// A five-field cron expression: minute, hour, day of month, month and day
+/// of week (0 or 7 is Sunday). Fields take `*`, numbers, ranges, lists and
+/// `/step`; `@hourly`, `@daily`, `@weekly` and `@monthly` are shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month and day of week are both restricted, so either matches
+    either_day: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(AppError::BadRequest(format!(
+                "{} needs five fields (minute hour day month weekday), got '{}'",
+                BACKUP_SCHEDULE, expression
+            )));
+        };
+        // Sunday is 0 and 7
+        let weekdays = parse_field(weekday, 0, 7)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires in the minute of `at`
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let has = |mask: u64, value: u32| mask & (1 << value) != 0;
+        let day = has(self.days, at.day());
+        let weekday = has(self.weekdays, at.weekday().num_days_from_sunday());
+        let day_matches = if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        has(self.minutes, at.minute())
+            && has(self.hours, at.hour())
+            && has(self.months, at.month())
+            && day_matches
+    }
+
+    /// Whether the schedule fires in a minute after `since`, up to `until`
+    pub fn due_between(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> bool {
+        let minute = chrono::Duration::minutes(1);
+        let Ok(mut at) = since.duration_trunc(minute) else {
+            return false;
+        };
+        // A day at most, should the task have stalled
+        for _ in 0..24 * 60 {
+            at += minute;
+            if at > until {
+                return false;
+            }
+            if self.matches(at) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Bit mask of the values a cron field selects
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let invalid = || AppError::BadRequest(format!("Invalid cron field '{}'", field));
+    let number = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, number(step)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (number(start)?, number(end)?),
+            // `5/15` runs from 5 to the end of the range
+            None if part.contains('/') => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// Parse `backup_schedule`; empty turns scheduled backups off
+pub fn parse_schedule(value: &str) -> Result<Option<Schedule>> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    Schedule::parse(value).map(Some)
+}
+
+/// Parse `backup_retention_count`
+pub fn parse_retention(value: &str) -> Result<usize> {
+    value
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a number of backups of at least 1",
+                BACKUP_RETENTION_COUNT
+            ))
+        })
+}
+
+// ============================================================================
+// Stored backups
+// ============================================================================
+
+const FILE_PREFIX: &str = "kubarr-backup-";
+const FILE_SUFFIX: &str = ".tar.gz";
+
+/// File name of a backup made at `at`
+pub fn file_name(at: DateTime<Utc>) -> String {
+    format!(
+        "{}{}{}",
+        FILE_PREFIX,
+        at.format("%Y%m%d-%H%M%S"),
+        FILE_SUFFIX
+    )
+}
+
+fn is_backup_file(name: &str) -> bool {
+    name.starts_with(FILE_PREFIX)
+        && name.ends_with(FILE_SUFFIX)
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.contains("..")
+}
+
+fn backup_dir() -> PathBuf {
+    PathBuf::from(&CONFIG.backup.dir)
+}
+
+/// Scheduled backups, newest first
+pub async fn list_stored() -> Result<Vec<BackupFile>> {
+    let mut entries = match tokio::fs::read_dir(backup_dir()).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !is_backup_file(&name) {
+            continue;
+        }
+        let metadata = entry.metadata().await?;
+        files.push(BackupFile {
+            name,
+            size_bytes: metadata.len(),
+            created_at: metadata.modified().map(DateTime::from).unwrap_or_default(),
+        });
+    }
+    // Names sort by creation time
+    files.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(files)
+}
+
+/// Read a scheduled backup
+pub async fn read_stored(name: &str) -> Result<Vec<u8>> {
+    let not_found = || AppError::NotFound(format!("Backup '{}' not found", name));
+    if !is_backup_file(name) {
+        return Err(not_found());
+    }
+    tokio::fs::read(backup_dir().join(name))
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => not_found(),
+            _ => e.into(),
+        })
+}
+
+/// Write a backup to `KUBARR_BACKUP_DIR` and delete the ones past retention
+pub async fn run_scheduled(db: &DatabaseConnection) -> Result<BackupFile> {
+    let passphrase = CONFIG.backup.passphrase.as_deref().ok_or_else(|| {
+        AppError::BadRequest(
+            "KUBARR_BACKUP_PASSPHRASE must be set for scheduled backups".to_string(),
+        )
+    })?;
+    let data = create(db, passphrase).await?;
+
+    let created_at = Utc::now();
+    let name = file_name(created_at);
+    let dir = backup_dir();
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(&name);
+    let partial = path.with_extension("partial");
+    tokio::fs::write(&partial, &data).await?;
+    tokio::fs::rename(&partial, &path).await?;
+
+    let keep = match get_setting_value(db, BACKUP_RETENTION_COUNT).await? {
+        Some(value) => parse_retention(&value).unwrap_or(DEFAULT_RETENTION_COUNT),
+        None => DEFAULT_RETENTION_COUNT,
+    };
+    for old in list_stored().await?.into_iter().skip(keep) {
+        if let Err(e) = tokio::fs::remove_file(dir.join(&old.name)).await {
+            tracing::warn!("Failed to delete old backup {}: {}", old.name, e);
+        }
+    }
+
+    Ok(BackupFile {
+        name,
+        size_bytes: data.len() as u64,
+        created_at,
+    })
+}
+
+/// Makes backups on `backup_schedule`
+pub struct BackupTask {
+    notification: NotificationService,
+    audit: AuditService,
+    /// End of the time span already checked against the schedule
+    checked_until: Mutex<DateTime<Utc>>,
+}
+
+impl BackupTask {
+    pub fn new(notification: NotificationService, audit: AuditService) -> Self {
+        Self {
+            notification,
+            audit,
+            checked_until: Mutex::new(Utc::now()),
+        }
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for BackupTask {
+    fn name(&self) -> &'static str {
+        "backup"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let since = std::mem::replace(
+            &mut *self.checked_until.lock().unwrap_or_else(|e| e.into_inner()),
+            now,
+        );
+        let schedule = get_setting_value(db, BACKUP_SCHEDULE)
+            .await?
+            .map(|value| parse_schedule(&value))
+            .transpose()?
+            .flatten();
+        if !schedule.is_some_and(|s| s.due_between(since, now)) {
+            return Ok(());
+        }
+
+        match run_scheduled(db).await {
+            Ok(file) => {
+                tracing::info!("Scheduled backup written to {}", file.name);
+                let _ = self
+                    .audit
+                    .log_success(
+                        AuditAction::BackupCreated,
+                        ResourceType::System,
+                        Some(file.name.clone()),
+                        None,
+                        None,
+                        Some(serde_json::json!({
+                            "scheduled": true,
+                            "size_bytes": file.size_bytes,
+                        })),
+                        None,
+                        None,
+                    )
+                    .await;
+            }
+            Err(e) => {
+                tracing::error!("Scheduled backup failed: {}", e);
+                let _ = self
+                    .audit
+                    .log_failure(
+                        AuditAction::BackupFailed,
+                        ResourceType::System,
+                        None,
+                        None,
+                        None,
+                        Some(serde_json::json!({ "scheduled": true })),
+                        None,
+                        None,
+                        &e.to_string(),
+                    )
+                    .await;
+                self.notification
+                    .notify_event(&AuditAction::BackupFailed, None, None, Some(&e.to_string()))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_schedule_matches() {
+        // 2026-10-18 is a Sunday
+        let daily = Schedule::parse("30 3 * * *").unwrap();
+        assert!(daily.matches(at("2026-10-18T03:30:00Z")));
+        assert!(!daily.matches(at("2026-10-18T03:31:00Z")));
+
+        let weekly = Schedule::parse("@weekly").unwrap();
+        assert!(weekly.matches(at("2026-10-18T00:00:00Z")));
+        assert!(!weekly.matches(at("2026-10-19T00:00:00Z")));
+        assert_eq!(weekly, Schedule::parse("0 0 * * 7").unwrap());
+
+        let stepped = Schedule::parse("*/15 1-5/2 * * 1,3").unwrap();
+        assert!(stepped.matches(at("2026-10-19T03:45:00Z")));
+        assert!(!stepped.matches(at("2026-10-19T02:45:00Z")));
+        assert!(!stepped.matches(at("2026-10-20T03:45:00Z")));
+
+        // With both day fields set, either one matches
+        let either = Schedule::parse("0 0 1 * 0").unwrap();
+        assert!(either.matches(at("2026-10-01T00:00:00Z")));
+        assert!(either.matches(at("2026-10-18T00:00:00Z")));
+        assert!(!either.matches(at("2026-10-19T00:00:00Z")));
+
+        for invalid in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(Schedule::parse(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(parse_schedule(" ").unwrap(), None);
+    }
+
+    #[test]
+    fn test_due_between() {
+        let schedule = Schedule::parse("0 4 * * *").unwrap();
+        assert!(schedule.due_between(at("2026-10-18T03:59:30Z"), at("2026-10-18T04:00:10Z")));
+        assert!(!schedule.due_between(at("2026-10-18T04:00:10Z"), at("2026-10-18T04:01:10Z")));
+        // A run that was missed by a minute is still picked up
+        assert!(schedule.due_between(at("2026-10-18T03:58:50Z"), at("2026-10-18T04:01:50Z")));
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let tables: Vec<String> = ["audit_logs", "roles", "user_roles", "users"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let keys = vec![
+            ("user_roles".to_string(), "users".to_string()),
+            ("user_roles".to_string(), "roles".to_string()),
+            ("users".to_string(), "users".to_string()),
+        ];
+        let order = dependency_order(&tables, &keys);
+        assert_eq!(order, ["audit_logs", "roles", "users", "user_roles"]);
+    }
+
+    #[test]
+    fn test_cipher_roundtrip() {
+        let cipher = Cipher::new("correct horse", b"salt");
+        let encrypted = cipher.encrypt("s3cret").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        assert!(!encrypted.contains("s3cret"));
+        assert_eq!(cipher.decrypt(&encrypted).as_deref(), Some("s3cret"));
+        assert_eq!(
+            Cipher::new("wrong horse", b"salt").decrypt(&encrypted),
+            None
+        );
+    }
+
+    #[test]
+    fn test_backup_file_names() {
+        let name = file_name(at("2026-10-18T04:00:00Z"));
+        assert_eq!(name, "kubarr-backup-20261018-040000.tar.gz");
+        assert!(is_backup_file(&name));
+        assert!(!is_backup_file("kubarr-backup-../../etc/passwd.tar.gz"));
+        assert!(!is_backup_file("other.tar.gz"));
+    }
+}
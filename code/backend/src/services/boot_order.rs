@@ -0,0 +1,414 @@
+//! Dependency-aware app boot ordering
+//!
+//! After a node reboot every pod comes back at once, so an *arr app can be up
+//! long before the download client it talks to. Apps can declare a boot
+//! priority (lower starts first) and the apps they depend on. An app's
+//! prerequisites are its dependencies plus every non-system app with a lower
+//! priority. While an app is still starting and any prerequisite is not
+//! healthy yet, [`reconcile`] scales it to zero and marks it held; once the
+//! prerequisites are running it is started again.
+//!
+//! Running apps are never held, so a prerequisite restarting later does not
+//! take its dependents down. Stopped or suspended prerequisites are ignored:
+//! ordering boot is not a reason to keep an app down indefinitely. System
+//! apps are never held and are only prerequisites when listed explicitly.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::catalog::AppCatalog;
+use super::deployment::DeploymentManager;
+use super::helm;
+use super::installed_apps;
+use super::k8s::K8sApi;
+use super::maintenance::{self, AppRunState};
+use super::scheduler::PeriodicTask;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::state::{SharedCatalog, SharedK8sClient};
+
+/// Lowest and highest accepted boot priority
+pub const PRIORITY_RANGE: std::ops::RangeInclusive<i32> = 0..=100;
+
+/// Maximum number of dependencies per app
+const MAX_DEPENDENCIES: usize = 20;
+
+/// How often boot order is reconciled
+const RECONCILE_INTERVAL_SECS: u64 = 15;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateBootOrder {
+    /// Apps with a lower priority are started first
+    #[serde(default)]
+    pub priority: i32,
+    /// Apps that must be healthy before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// Boot ordering of an installed app and what it is waiting for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BootStatus {
+    pub app_name: String,
+    pub priority: i32,
+    pub depends_on: Vec<String>,
+    /// Scaled to zero until its prerequisites are healthy
+    pub held: bool,
+    pub held_since: Option<DateTime<Utc>>,
+    /// Prerequisites that are not healthy yet
+    pub waiting_for: Vec<String>,
+}
+
+/// Boot settings and run state of one deployed app
+#[derive(Debug, Clone)]
+struct Node {
+    priority: i32,
+    depends_on: Vec<String>,
+    held_since: Option<DateTime<Utc>>,
+    is_system: bool,
+    state: AppRunState,
+}
+
+impl Node {
+    /// Whether dependents still have to wait for this app
+    fn is_pending(&self) -> bool {
+        self.held_since.is_some() || self.state == AppRunState::Starting
+    }
+}
+
+/// Boot settings of the deployed apps, by name
+type Plan = BTreeMap<String, Node>;
+
+/// Apps `app_name` waits for, whether or not they are healthy
+fn prerequisites<'a>(plan: &'a Plan, app_name: &str) -> Vec<&'a str> {
+    let Some(node) = plan.get(app_name) else {
+        return Vec::new();
+    };
+    let mut prerequisites: Vec<&str> = node
+        .depends_on
+        .iter()
+        .filter_map(|dep| plan.get_key_value(dep).map(|(name, _)| name.as_str()))
+        .collect();
+    if !node.is_system {
+        prerequisites.extend(
+            plan.iter()
+                .filter(|(name, other)| {
+                    name.as_str() != app_name && !other.is_system && other.priority < node.priority
+                })
+                .map(|(name, _)| name.as_str()),
+        );
+    }
+    prerequisites.sort_unstable();
+    prerequisites.dedup();
+    prerequisites
+}
+
+/// Prerequisites of `app_name` that are not healthy yet
+fn waiting_for(plan: &Plan, app_name: &str) -> Vec<String> {
+    prerequisites(plan, app_name)
+        .into_iter()
+        .filter(|name| plan.get(*name).is_some_and(Node::is_pending))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Name of an app that is part of a dependency cycle, if any
+fn find_cycle(plan: &Plan) -> Option<String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a>(plan: &'a Plan, app: &'a str, marks: &mut HashMap<&'a str, Mark>) -> bool {
+        match marks.get(app) {
+            Some(Mark::Visiting) => return true,
+            Some(Mark::Done) => return false,
+            None => {}
+        }
+        marks.insert(app, Mark::Visiting);
+        for prerequisite in prerequisites(plan, app) {
+            if visit(plan, prerequisite, marks) {
+                return true;
+            }
+        }
+        marks.insert(app, Mark::Done);
+        false
+    }
+
+    let mut marks = HashMap::new();
+    plan.keys()
+        .find(|app| visit(plan, app, &mut marks))
+        .cloned()
+}
+
+/// Collect the boot settings and run state of the deployed apps
+async fn load_plan(
+    db: &DatabaseConnection,
+    k8s: &dyn K8sApi,
+    manager: &DeploymentManager<'_>,
+    catalog: &AppCatalog,
+    with_state: bool,
+) -> Result<Plan> {
+    let records: HashMap<String, _> = InstalledApp::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|r| (r.app_name.clone(), r))
+        .collect();
+
+    let mut plan = Plan::new();
+    for app_name in manager.get_deployed_apps().await {
+        let record = records.get(&app_name);
+        let state = if with_state {
+            maintenance::app_run_status(k8s, &app_name).await?.state
+        } else {
+            AppRunState::Running
+        };
+        plan.insert(
+            app_name.clone(),
+            Node {
+                priority: record.map(|r| r.boot_priority).unwrap_or_default(),
+                depends_on: record
+                    .map(|r| installed_apps::parse_depends_on(&r.depends_on))
+                    .unwrap_or_default(),
+                held_since: record.and_then(|r| r.boot_held_at),
+                is_system: catalog.get_app(&app_name).is_some_and(|app| app.is_system),
+                state,
+            },
+        );
+    }
+    Ok(plan)
+}
+
+/// Check and normalize a boot order update for `app_name`
+///
+/// Dependencies must be other deployed apps, and the result must not make
+/// any app wait for itself.
+pub async fn validate(
+    db: &DatabaseConnection,
+    k8s: &dyn K8sApi,
+    manager: &DeploymentManager<'_>,
+    catalog: &AppCatalog,
+    app_name: &str,
+    update: UpdateBootOrder,
+) -> Result<(i32, Vec<String>)> {
+    if !PRIORITY_RANGE.contains(&update.priority) {
+        return Err(AppError::BadRequest(format!(
+            "Boot priority must be between {} and {}",
+            PRIORITY_RANGE.start(),
+            PRIORITY_RANGE.end()
+        )));
+    }
+
+    let mut plan = load_plan(db, k8s, manager, catalog, false).await?;
+    let mut depends_on: Vec<String> = Vec::new();
+    for dep in update.depends_on {
+        let dep = dep.trim().to_lowercase();
+        if dep.is_empty() || depends_on.contains(&dep) {
+            continue;
+        }
+        if dep == app_name {
+            return Err(AppError::BadRequest(format!(
+                "App '{}' cannot depend on itself",
+                app_name
+            )));
+        }
+        if !plan.contains_key(&dep) {
+            return Err(AppError::BadRequest(format!(
+                "Dependency '{}' is not installed",
+                dep
+            )));
+        }
+        depends_on.push(dep);
+    }
+    if depends_on.len() > MAX_DEPENDENCIES {
+        return Err(AppError::BadRequest(format!(
+            "An app can depend on at most {} apps",
+            MAX_DEPENDENCIES
+        )));
+    }
+
+    if let Some(node) = plan.get_mut(app_name) {
+        node.priority = update.priority;
+        node.depends_on = depends_on.clone();
+    }
+    if let Some(app) = find_cycle(&plan) {
+        return Err(AppError::BadRequest(format!(
+            "Boot order would make '{}' wait for itself; check priorities and dependencies",
+            app
+        )));
+    }
+    Ok((update.priority, depends_on))
+}
+
+/// Boot ordering of a deployed app, with what it is waiting for
+pub async fn boot_status(
+    db: &DatabaseConnection,
+    k8s: &dyn K8sApi,
+    manager: &DeploymentManager<'_>,
+    catalog: &AppCatalog,
+    app_name: &str,
+) -> Result<BootStatus> {
+    let plan = load_plan(db, k8s, manager, catalog, true).await?;
+    let node = plan
+        .get(app_name)
+        .ok_or_else(|| AppError::NotFound(format!("App '{}' is not installed", app_name)))?;
+    Ok(BootStatus {
+        app_name: app_name.to_string(),
+        priority: node.priority,
+        depends_on: node.depends_on.clone(),
+        held: node.held_since.is_some(),
+        held_since: node.held_since,
+        waiting_for: waiting_for(&plan, app_name),
+    })
+}
+
+/// Apps held and released by one [`reconcile`] pass
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BootChanges {
+    pub held: Vec<String>,
+    pub released: Vec<String>,
+}
+
+/// Hold starting apps whose prerequisites are not healthy and release held
+/// apps whose prerequisites are
+pub async fn reconcile(
+    db: &DatabaseConnection,
+    k8s: &dyn K8sApi,
+    manager: &DeploymentManager<'_>,
+    catalog: &AppCatalog,
+    now: DateTime<Utc>,
+) -> Result<BootChanges> {
+    let mut changes = BootChanges::default();
+
+    // Skip the cluster queries while nobody has configured boot ordering
+    let configured =
+        InstalledApp::find().all(db).await?.into_iter().any(|r| {
+            r.boot_priority != 0 || r.boot_held_at.is_some() || r.depends_on.trim() != "[]"
+        });
+    if !configured {
+        return Ok(changes);
+    }
+
+    let plan = load_plan(db, k8s, manager, catalog, true).await?;
+    for (app_name, node) in &plan {
+        if node.is_system {
+            continue;
+        }
+        let waiting = waiting_for(&plan, app_name);
+        if node.held_since.is_some() {
+            if !waiting.is_empty() {
+                continue;
+            }
+            match manager.start_app(app_name).await {
+                Ok(_) => {
+                    tracing::info!("Prerequisites of '{}' are up, starting it", app_name);
+                    changes.released.push(app_name.clone());
+                }
+                Err(e) => tracing::warn!("Failed to start held app '{}': {}", app_name, e),
+            }
+        } else if node.state == AppRunState::Starting && !waiting.is_empty() {
+            match manager.stop_app(app_name).await {
+                Ok(_) => {
+                    installed_apps::set_boot_held(db, app_name, Some(now)).await?;
+                    tracing::info!("Holding '{}' until {} start", app_name, waiting.join(", "));
+                    changes.held.push(app_name.clone());
+                }
+                Err(e) => tracing::warn!("Failed to hold app '{}': {}", app_name, e),
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Periodically enforces boot order
+pub struct BootOrderTask {
+    pub k8s_client: SharedK8sClient,
+    pub catalog: SharedCatalog,
+}
+
+#[async_trait]
+impl PeriodicTask for BootOrderTask {
+    fn name(&self) -> &'static str {
+        "boot_order"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(RECONCILE_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let k8s_guard = self.k8s_client.read().await;
+        let Some(k8s) = k8s_guard.as_ref() else {
+            return Ok(());
+        };
+        let catalog = self.catalog.read().await;
+        let helm = helm::from_config();
+        let manager = DeploymentManager::with_db(k8s, helm.as_ref(), &catalog, db);
+        reconcile(db, k8s, &manager, &catalog, Utc::now()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(priority: i32, depends_on: &[&str], state: AppRunState) -> Node {
+        Node {
+            priority,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            held_since: None,
+            is_system: false,
+            state,
+        }
+    }
+
+    #[test]
+    fn test_waiting_for_combines_dependencies_and_priority() {
+        let mut plan = Plan::new();
+        plan.insert("qbittorrent".into(), node(0, &[], AppRunState::Starting));
+        plan.insert("prowlarr".into(), node(1, &[], AppRunState::Running));
+        plan.insert("plex".into(), node(0, &[], AppRunState::Stopped));
+        plan.insert(
+            "sonarr".into(),
+            node(2, &["plex", "missing"], AppRunState::Starting),
+        );
+
+        // Stopped and unknown prerequisites don't block; running ones are done
+        assert_eq!(
+            prerequisites(&plan, "sonarr"),
+            vec!["plex", "prowlarr", "qbittorrent"]
+        );
+        assert_eq!(waiting_for(&plan, "sonarr"), vec!["qbittorrent"]);
+        assert_eq!(waiting_for(&plan, "prowlarr"), vec!["qbittorrent"]);
+        assert!(waiting_for(&plan, "qbittorrent").is_empty());
+
+        // Held apps are still pending for their dependents
+        plan.get_mut("plex").unwrap().held_since = Some(Utc::now());
+        assert_eq!(waiting_for(&plan, "sonarr"), vec!["plex", "qbittorrent"]);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let mut plan = Plan::new();
+        plan.insert("a".into(), node(0, &["b"], AppRunState::Running));
+        plan.insert("b".into(), node(0, &[], AppRunState::Running));
+        assert_eq!(find_cycle(&plan), None);
+
+        // b boots after a by priority, but a depends on b
+        plan.get_mut("b").unwrap().priority = 1;
+        assert!(find_cycle(&plan).is_some());
+
+        plan.get_mut("b").unwrap().priority = 0;
+        plan.get_mut("b").unwrap().depends_on = vec!["a".to_string()];
+        assert!(find_cycle(&plan).is_some());
+    }
+}
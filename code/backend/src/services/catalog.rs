@@ -22,6 +22,15 @@ pub struct AppConfig {
     pub is_system: bool,
     pub is_hidden: bool,
     pub is_browseable: bool,
+    /// CPU architectures the app's images are built for (e.g. `amd64`,
+    /// `arm64`); empty when the chart does not say, in which case it is
+    /// assumed to run anywhere
+    #[serde(default)]
+    pub architectures: Vec<String>,
+    /// Chart version from Chart.yaml; installs and upgrades pin the release
+    /// to it. Empty when the chart does not say
+    #[serde(default)]
+    pub version: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
@@ -101,198 +110,13 @@ impl AppCatalog {
             return Ok(None);
         }
 
-        // Parse Chart.yaml
         let chart_content = std::fs::read_to_string(&chart_yaml)?;
-        let chart: serde_yaml::Value = serde_yaml::from_str(&chart_content)?;
-
-        // Get kubarr annotations
-        let annotations = chart
-            .get("annotations")
-            .and_then(|a| a.as_mapping())
-            .cloned()
-            .unwrap_or_default();
-
-        // Skip charts without kubarr category annotation
-        let category =
-            match annotations.get(serde_yaml::Value::String("kubarr.io/category".to_string())) {
-                Some(c) => c.as_str().unwrap_or("other").to_string(),
-                None => return Ok(None),
-            };
-
-        // Parse values.yaml
-        let values: serde_yaml::Value = if values_yaml.exists() {
-            let values_content = std::fs::read_to_string(&values_yaml)?;
-            serde_yaml::from_str(&values_content).unwrap_or(serde_yaml::Value::Null)
+        let values_content = if values_yaml.exists() {
+            Some(std::fs::read_to_string(&values_yaml)?)
         } else {
-            serde_yaml::Value::Null
-        };
-
-        // Get app-specific config
-        let app_values = values
-            .get(chart_name)
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-
-        // Extract image info
-        let image_config = app_values
-            .get("image")
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-        let default_image_repo = format!("linuxserver/{}", chart_name);
-        let image_repo = image_config
-            .get("repository")
-            .and_then(|r| r.as_str())
-            .unwrap_or(&default_image_repo);
-        let image_tag = image_config
-            .get("tag")
-            .and_then(|t| t.as_str())
-            .unwrap_or("latest");
-        let container_image = format!("{}:{}", image_repo, image_tag);
-
-        // Extract port
-        let service_config = app_values
-            .get("service")
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-        let default_port = service_config
-            .get("port")
-            .and_then(|p| p.as_i64())
-            .unwrap_or(8080) as i32;
-
-        // Extract resources
-        let resources_config = app_values
-            .get("resources")
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-        let requests = resources_config
-            .get("requests")
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-        let limits = resources_config
-            .get("limits")
-            .cloned()
-            .unwrap_or(serde_yaml::Value::Null);
-
-        let resource_requirements = ResourceRequirements {
-            cpu_request: requests
-                .get("cpu")
-                .and_then(|c| c.as_str())
-                .unwrap_or("100m")
-                .to_string(),
-            cpu_limit: limits
-                .get("cpu")
-                .and_then(|c| c.as_str())
-                .unwrap_or("1000m")
-                .to_string(),
-            memory_request: requests
-                .get("memory")
-                .and_then(|m| m.as_str())
-                .unwrap_or("256Mi")
-                .to_string(),
-            memory_limit: limits
-                .get("memory")
-                .and_then(|m| m.as_str())
-                .unwrap_or("1Gi")
-                .to_string(),
+            None
         };
-
-        // Extract volumes
-        let mut volumes = Vec::new();
-        if let Some(persistence) = values.get("persistence").and_then(|p| p.as_mapping()) {
-            for (vol_name, vol_config) in persistence {
-                if let (Some(name), Some(config)) = (vol_name.as_str(), vol_config.as_mapping()) {
-                    let enabled = config
-                        .get(serde_yaml::Value::String("enabled".to_string()))
-                        .and_then(|e| e.as_bool())
-                        .unwrap_or(true);
-
-                    if enabled {
-                        volumes.push(VolumeConfig {
-                            name: name.to_string(),
-                            mount_path: config
-                                .get(serde_yaml::Value::String("mountPath".to_string()))
-                                .and_then(|m| m.as_str())
-                                .unwrap_or(&format!("/{}", name))
-                                .to_string(),
-                            size: config
-                                .get(serde_yaml::Value::String("size".to_string()))
-                                .and_then(|s| s.as_str())
-                                .unwrap_or("1Gi")
-                                .to_string(),
-                        });
-                    }
-                }
-            }
-        }
-
-        // Extract environment variables
-        let env_vars: HashMap<String, String> = app_values
-            .get("env")
-            .and_then(|e| e.as_mapping())
-            .map(|m| {
-                m.iter()
-                    .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        // Get display name and other annotations
-        let display_name = annotations
-            .get(serde_yaml::Value::String(
-                "kubarr.io/display-name".to_string(),
-            ))
-            .and_then(|d| d.as_str())
-            .unwrap_or(chart_name)
-            .to_string();
-
-        let icon = annotations
-            .get(serde_yaml::Value::String("kubarr.io/icon".to_string()))
-            .and_then(|i| i.as_str())
-            .unwrap_or("📦")
-            .to_string();
-
-        let is_system = annotations
-            .get(serde_yaml::Value::String("kubarr.io/system".to_string()))
-            .and_then(|s| s.as_str())
-            .map(|s| s.to_lowercase() == "true")
-            .unwrap_or(false);
-
-        let is_hidden = annotations
-            .get(serde_yaml::Value::String("kubarr.io/hidden".to_string()))
-            .and_then(|h| h.as_str())
-            .map(|h| h.to_lowercase() == "true")
-            .unwrap_or(false);
-
-        // Apps are browseable by default unless explicitly set to false
-        let is_browseable = annotations
-            .get(serde_yaml::Value::String(
-                "kubarr.io/browseable".to_string(),
-            ))
-            .and_then(|b| b.as_str())
-            .map(|b| b.to_lowercase() != "false")
-            .unwrap_or(true);
-
-        let description = chart
-            .get("description")
-            .and_then(|d| d.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        Ok(Some(AppConfig {
-            name: chart_name.to_string(),
-            display_name,
-            description,
-            icon,
-            container_image,
-            default_port,
-            resource_requirements,
-            volumes,
-            environment_variables: env_vars,
-            category,
-            is_system,
-            is_hidden,
-            is_browseable,
-        }))
+        parse_chart_content(chart_name, &chart_content, values_content.as_deref())
     }
 
     /// Get all available apps
@@ -344,3 +168,332 @@ impl Default for AppCatalog {
         Self::new()
     }
 }
+
+/// Maximum length of a clone suffix
+pub const MAX_INSTANCE_SUFFIX_LEN: usize = 20;
+
+/// Whether `suffix` can name a cloned instance: lowercase letters, digits and
+/// inner hyphens, so `{app}-{suffix}` stays a valid namespace and release name
+pub fn is_valid_instance_suffix(suffix: &str) -> bool {
+    !suffix.is_empty()
+        && suffix.len() <= MAX_INSTANCE_SUFFIX_LEN
+        && !suffix.starts_with('-')
+        && !suffix.ends_with('-')
+        && suffix
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Name of the `suffix` instance of a catalog app, e.g. `sonarr-4k`
+pub fn instance_name(app_name: &str, suffix: &str) -> String {
+    format!("{}-{}", app_name, suffix)
+}
+
+/// Parse the contents of a chart's Chart.yaml and values.yaml into an AppConfig
+///
+/// Returns `None` for charts without a `kubarr.io/category` annotation.
+pub fn parse_chart_content(
+    chart_name: &str,
+    chart_content: &str,
+    values_content: Option<&str>,
+) -> Result<Option<AppConfig>> {
+    let chart: serde_yaml::Value = serde_yaml::from_str(chart_content)?;
+
+    // Get kubarr annotations
+    let annotations = chart
+        .get("annotations")
+        .and_then(|a| a.as_mapping())
+        .cloned()
+        .unwrap_or_default();
+
+    // Skip charts without kubarr category annotation
+    let category =
+        match annotations.get(serde_yaml::Value::String("kubarr.io/category".to_string())) {
+            Some(c) => c.as_str().unwrap_or("other").to_string(),
+            None => return Ok(None),
+        };
+
+    // Parse values.yaml
+    let values: serde_yaml::Value = values_content
+        .and_then(|content| serde_yaml::from_str(content).ok())
+        .unwrap_or(serde_yaml::Value::Null);
+
+    // Get app-specific config
+    let app_values = values
+        .get(chart_name)
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+
+    // Extract image info
+    let image_config = app_values
+        .get("image")
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+    let default_image_repo = format!("linuxserver/{}", chart_name);
+    let image_repo = image_config
+        .get("repository")
+        .and_then(|r| r.as_str())
+        .unwrap_or(&default_image_repo);
+    let image_tag = image_config
+        .get("tag")
+        .and_then(|t| t.as_str())
+        .unwrap_or("latest");
+    let container_image = format!("{}:{}", image_repo, image_tag);
+
+    // Extract port
+    let service_config = app_values
+        .get("service")
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+    let default_port = service_config
+        .get("port")
+        .and_then(|p| p.as_i64())
+        .unwrap_or(8080) as i32;
+
+    // Extract resources
+    let resources_config = app_values
+        .get("resources")
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+    let requests = resources_config
+        .get("requests")
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+    let limits = resources_config
+        .get("limits")
+        .cloned()
+        .unwrap_or(serde_yaml::Value::Null);
+
+    let resource_requirements = ResourceRequirements {
+        cpu_request: requests
+            .get("cpu")
+            .and_then(|c| c.as_str())
+            .unwrap_or("100m")
+            .to_string(),
+        cpu_limit: limits
+            .get("cpu")
+            .and_then(|c| c.as_str())
+            .unwrap_or("1000m")
+            .to_string(),
+        memory_request: requests
+            .get("memory")
+            .and_then(|m| m.as_str())
+            .unwrap_or("256Mi")
+            .to_string(),
+        memory_limit: limits
+            .get("memory")
+            .and_then(|m| m.as_str())
+            .unwrap_or("1Gi")
+            .to_string(),
+    };
+
+    // Extract volumes
+    let mut volumes = Vec::new();
+    if let Some(persistence) = values.get("persistence").and_then(|p| p.as_mapping()) {
+        for (vol_name, vol_config) in persistence {
+            if let (Some(name), Some(config)) = (vol_name.as_str(), vol_config.as_mapping()) {
+                let enabled = config
+                    .get(serde_yaml::Value::String("enabled".to_string()))
+                    .and_then(|e| e.as_bool())
+                    .unwrap_or(true);
+
+                if enabled {
+                    volumes.push(VolumeConfig {
+                        name: name.to_string(),
+                        mount_path: config
+                            .get(serde_yaml::Value::String("mountPath".to_string()))
+                            .and_then(|m| m.as_str())
+                            .unwrap_or(&format!("/{}", name))
+                            .to_string(),
+                        size: config
+                            .get(serde_yaml::Value::String("size".to_string()))
+                            .and_then(|s| s.as_str())
+                            .unwrap_or("1Gi")
+                            .to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Extract environment variables
+    let env_vars: HashMap<String, String> = app_values
+        .get("env")
+        .and_then(|e| e.as_mapping())
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Get display name and other annotations
+    let display_name = annotations
+        .get(serde_yaml::Value::String(
+            "kubarr.io/display-name".to_string(),
+        ))
+        .and_then(|d| d.as_str())
+        .unwrap_or(chart_name)
+        .to_string();
+
+    let icon = annotations
+        .get(serde_yaml::Value::String("kubarr.io/icon".to_string()))
+        .and_then(|i| i.as_str())
+        .unwrap_or("📦")
+        .to_string();
+
+    let is_system = annotations
+        .get(serde_yaml::Value::String("kubarr.io/system".to_string()))
+        .and_then(|s| s.as_str())
+        .map(|s| s.to_lowercase() == "true")
+        .unwrap_or(false);
+
+    let is_hidden = annotations
+        .get(serde_yaml::Value::String("kubarr.io/hidden".to_string()))
+        .and_then(|h| h.as_str())
+        .map(|h| h.to_lowercase() == "true")
+        .unwrap_or(false);
+
+    // Apps are browseable by default unless explicitly set to false
+    let is_browseable = annotations
+        .get(serde_yaml::Value::String(
+            "kubarr.io/browseable".to_string(),
+        ))
+        .and_then(|b| b.as_str())
+        .map(|b| b.to_lowercase() != "false")
+        .unwrap_or(true);
+
+    let architectures = annotations
+        .get(serde_yaml::Value::String(
+            "kubarr.io/architectures".to_string(),
+        ))
+        .and_then(|a| a.as_str())
+        .map(parse_architectures)
+        .unwrap_or_default();
+
+    let description = chart
+        .get("description")
+        .and_then(|d| d.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let version = match chart.get("version") {
+        Some(serde_yaml::Value::String(v)) => v.clone(),
+        Some(serde_yaml::Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    };
+
+    Ok(Some(AppConfig {
+        name: chart_name.to_string(),
+        display_name,
+        description,
+        icon,
+        container_image,
+        default_port,
+        resource_requirements,
+        volumes,
+        environment_variables: env_vars,
+        category,
+        is_system,
+        is_hidden,
+        is_browseable,
+        architectures,
+        version,
+    }))
+}
+
+/// Parse a comma-separated `kubarr.io/architectures` annotation into
+/// Kubernetes architecture names, mapping `x86_64` and `aarch64` to `amd64`
+/// and `arm64`
+pub fn parse_architectures(value: &str) -> Vec<String> {
+    let mut architectures: Vec<String> = Vec::new();
+    for arch in value.split(',').map(normalize_architecture) {
+        if !arch.is_empty() && !architectures.contains(&arch) {
+            architectures.push(arch);
+        }
+    }
+    architectures
+}
+
+/// Kubernetes name of a CPU architecture, as in the `kubernetes.io/arch`
+/// node label
+pub fn normalize_architecture(arch: &str) -> String {
+    match arch.trim().to_lowercase().as_str() {
+        "x86_64" | "x86-64" | "x64" => "amd64".to_string(),
+        "aarch64" | "arm64v8" => "arm64".to_string(),
+        "armhf" | "armv7" | "armv7l" => "arm".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// How a catalog app fits the architectures of the cluster's nodes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct ArchCompatibility {
+    /// False when no node can run the app's images
+    pub compatible: bool,
+    /// Explains an incompatible app, or one that only runs on some nodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Architecture to pin the app's pods to on a mixed cluster
+    #[serde(skip)]
+    pub node_arch: Option<String>,
+}
+
+impl AppConfig {
+    /// Check the app's architectures against those of the cluster's nodes
+    ///
+    /// Apps without architectures, and clusters whose node architectures are
+    /// unknown (an empty list), are always compatible.
+    pub fn arch_compatibility(&self, node_architectures: &[String]) -> ArchCompatibility {
+        let supported: Vec<&String> = node_architectures
+            .iter()
+            .filter(|arch| self.architectures.contains(arch))
+            .collect();
+        if self.architectures.is_empty()
+            || node_architectures.is_empty()
+            || supported.len() == node_architectures.len()
+        {
+            return ArchCompatibility {
+                compatible: true,
+                warning: None,
+                node_arch: None,
+            };
+        }
+        match supported.as_slice() {
+            [] => ArchCompatibility {
+                compatible: false,
+                warning: Some(format!(
+                    "{} has no images for this cluster: it supports {}, the nodes are {}",
+                    self.display_name,
+                    self.architectures.join(", "),
+                    node_architectures.join(", ")
+                )),
+                node_arch: None,
+            },
+            [arch] => ArchCompatibility {
+                compatible: true,
+                warning: Some(format!(
+                    "{} only runs on {} nodes and is scheduled on those",
+                    self.display_name, arch
+                )),
+                node_arch: Some(arch.to_string()),
+            },
+            _ => {
+                let unsupported: Vec<&str> = node_architectures
+                    .iter()
+                    .filter(|arch| !self.architectures.contains(arch))
+                    .map(String::as_str)
+                    .collect();
+                ArchCompatibility {
+                    compatible: true,
+                    warning: Some(format!(
+                        "{} does not run on {} nodes; set a nodeSelector to keep it off them",
+                        self.display_name,
+                        unsupported.join(", ")
+                    )),
+                    node_arch: None,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Offline catalog bundles
+//!
+//! Clusters without internet access cannot sync charts from GitHub and the
+//! OCI registry, so the catalog is imported as a bundle instead: a `.tar.gz`
+//! (or plain `.tar`) of chart directories laid out like the charts repo,
+//! e.g. `sonarr/Chart.yaml`, `sonarr/values.yaml`, `sonarr/icon.svg`. The
+//! chart directories may also sit under a single top-level directory.
+//!
+//! Every chart is validated with [`validate_catalog_entry`] before anything
+//! is written; a bundle with an invalid chart is rejected as a whole. Valid
+//! charts replace the directories of the same name in the charts directory,
+//! charts missing from the bundle are left alone.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::catalog_validation::{
+    validate_catalog_entry, CatalogEntryRequest, CatalogIssue, CatalogIssueLevel,
+};
+use crate::error::{AppError, Result};
+
+/// Largest bundle accepted for upload
+pub const MAX_BUNDLE_BYTES: usize = 512 * 1024 * 1024;
+
+/// Largest uncompressed archive, guards against decompression bombs
+const MAX_UNPACKED_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+const BLOCK: usize = 512;
+
+/// A chart imported from a bundle
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportedChart {
+    pub name: String,
+    /// Chart version from Chart.yaml
+    pub version: Option<String>,
+    /// Validation warnings; charts with errors are never imported
+    pub issues: Vec<CatalogIssue>,
+}
+
+/// Result of importing a bundle
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BundleImport {
+    pub charts: Vec<ImportedChart>,
+}
+
+/// Files of one chart, keyed by their path inside the chart directory
+type ChartFiles = BTreeMap<String, Vec<u8>>;
+
+/// Validate the charts in a bundle and install them into `charts_dir`
+///
+/// The catalog has to be reloaded afterwards to pick the charts up.
+pub fn import(bundle: &[u8], charts_dir: &Path) -> Result<BundleImport> {
+    let charts = group_charts(read_tar(&unpack(bundle)?)?)?;
+
+    let mut imported = Vec::new();
+    let mut errors = Vec::new();
+    for (name, files) in &charts {
+        let text = |file: &str| -> Result<Option<String>> {
+            files
+                .get(file)
+                .map(|data| {
+                    String::from_utf8(data.clone()).map_err(|_| {
+                        AppError::BadRequest(format!("{}/{} is not valid UTF-8", name, file))
+                    })
+                })
+                .transpose()
+        };
+        let chart_yaml = text("Chart.yaml")?.unwrap_or_default();
+        let validation = validate_catalog_entry(&CatalogEntryRequest {
+            name: name.clone(),
+            chart_yaml: chart_yaml.clone(),
+            values_yaml: text("values.yaml")?,
+            icon_svg: text("icon.svg")?,
+        });
+        errors.extend(
+            validation
+                .issues
+                .iter()
+                .filter(|i| i.level == CatalogIssueLevel::Error)
+                .map(|i| format!("{}: {}: {}", name, i.field, i.message)),
+        );
+        imported.push(ImportedChart {
+            name: name.clone(),
+            version: chart_version(&chart_yaml),
+            // The chart is about to be on disk, so drop the "not found" warning
+            issues: validation
+                .issues
+                .into_iter()
+                .filter(|i| i.level == CatalogIssueLevel::Warning && i.field != "chart")
+                .collect(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Bundle contains invalid charts: {}",
+            errors.join("; ")
+        )));
+    }
+
+    install(&charts, charts_dir)
+        .map_err(|e| AppError::Internal(format!("Failed to install charts: {}", e)))?;
+    Ok(BundleImport { charts: imported })
+}
+
+/// Decompress a gzipped bundle; plain tarballs are passed through
+pub(crate) fn unpack(bundle: &[u8]) -> Result<Vec<u8>> {
+    if !bundle.starts_with(&[0x1f, 0x8b]) {
+        return Ok(bundle.to_vec());
+    }
+    let mut tar = Vec::new();
+    GzDecoder::new(bundle)
+        .take(MAX_UNPACKED_BYTES + 1)
+        .read_to_end(&mut tar)
+        .map_err(|e| AppError::BadRequest(format!("Bundle is not a valid gzip file: {}", e)))?;
+    if tar.len() as u64 > MAX_UNPACKED_BYTES {
+        return Err(AppError::BadRequest(
+            "Bundle is too large once unpacked".to_string(),
+        ));
+    }
+    Ok(tar)
+}
+
+/// Regular files in a ustar/GNU/pax archive, as path components and contents
+///
+/// Directories, links and other special entries are skipped.
+pub(crate) fn read_tar(tar: &[u8]) -> Result<Vec<(Vec<String>, Vec<u8>)>> {
+    let invalid = |message: &str| AppError::BadRequest(format!("Invalid tar archive: {}", message));
+    let mut files = Vec::new();
+    let mut long_name: Option<String> = None;
+    let mut offset = 0;
+
+    while offset + BLOCK <= tar.len() {
+        let header = &tar[offset..offset + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        // The checksum is computed with its own field set to spaces
+        let computed: u64 = header[..148]
+            .iter()
+            .chain(&[b' '; 8])
+            .chain(&header[156..])
+            .map(|&b| u64::from(b))
+            .sum();
+        if read_octal(&header[148..156]) != Some(computed) {
+            return Err(invalid("header checksum mismatch"));
+        }
+        let size = read_octal(&header[124..136]).ok_or_else(|| invalid("bad entry size"))? as usize;
+        let start = offset + BLOCK;
+        let end = start
+            .checked_add(size)
+            .filter(|&end| end <= tar.len())
+            .ok_or_else(|| invalid("truncated entry"))?;
+        let data = &tar[start..end];
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+
+        match header[156] {
+            // GNU long name for the next entry
+            b'L' => long_name = Some(c_string(data)),
+            // pax extended header, only the path is of interest
+            b'x' => {
+                if let Some(path) = pax_path(data) {
+                    long_name = Some(path);
+                }
+            }
+            b'0' | 0 => {
+                let name = match long_name.take() {
+                    Some(name) => name,
+                    None => {
+                        let name = c_string(&header[..100]);
+                        let prefix = c_string(&header[345..500]);
+                        if header[257..262] == *b"ustar" && !prefix.is_empty() {
+                            format!("{}/{}", prefix, name)
+                        } else {
+                            name
+                        }
+                    }
+                };
+                files.push((safe_components(&name)?, data.to_vec()));
+            }
+            _ => long_name = None,
+        }
+    }
+    Ok(files)
+}
+
+/// Split a path into components, rejecting anything that escapes the bundle
+fn safe_components(path: &str) -> Result<Vec<String>> {
+    let components: Vec<String> = path
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .map(str::to_string)
+        .collect();
+    if components.iter().any(|c| c == ".." || c.contains('\\')) {
+        return Err(AppError::BadRequest(format!(
+            "Bundle entry '{}' points outside the bundle",
+            path
+        )));
+    }
+    Ok(components)
+}
+
+/// Group files by chart directory, looking through a single top-level
+/// directory when the charts are not at the root
+fn group_charts(mut files: Vec<(Vec<String>, Vec<u8>)>) -> Result<BTreeMap<String, ChartFiles>> {
+    let is_chart_yaml = |path: &[String]| path.len() == 2 && path[1] == "Chart.yaml";
+    if !files.iter().any(|(path, _)| is_chart_yaml(path)) {
+        let root = files.first().and_then(|(path, _)| path.first().cloned());
+        if let Some(root) = root {
+            if files
+                .iter()
+                .all(|(path, _)| path.len() > 1 && path[0] == root)
+            {
+                for (path, _) in &mut files {
+                    path.remove(0);
+                }
+            }
+        }
+    }
+
+    let mut charts: BTreeMap<String, ChartFiles> = BTreeMap::new();
+    for (path, data) in files {
+        if let [chart, rest @ ..] = path.as_slice() {
+            if !rest.is_empty() {
+                charts
+                    .entry(chart.clone())
+                    .or_default()
+                    .insert(rest.join("/"), data);
+            }
+        }
+    }
+    charts.retain(|_, files| files.contains_key("Chart.yaml"));
+    if charts.is_empty() {
+        return Err(AppError::BadRequest(
+            "Bundle contains no charts".to_string(),
+        ));
+    }
+    Ok(charts)
+}
+
+/// Write the charts to a staging directory, then swap them in one by one
+fn install(charts: &BTreeMap<String, ChartFiles>, charts_dir: &Path) -> std::io::Result<()> {
+    let staging = charts_dir.join(format!(".import-{}", uuid::Uuid::new_v4()));
+    let result = (|| {
+        for (name, files) in charts {
+            for (file, data) in files {
+                let path = staging.join(name).join(file);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, data)?;
+            }
+        }
+        for name in charts.keys() {
+            let target = charts_dir.join(name);
+            if target.exists() {
+                std::fs::remove_dir_all(&target)?;
+            }
+            std::fs::rename(staging.join(name), target)?;
+        }
+        Ok(())
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+fn chart_version(chart_yaml: &str) -> Option<String> {
+    let chart: serde_yaml::Value = serde_yaml::from_str(chart_yaml).ok()?;
+    match chart.get("version")? {
+        serde_yaml::Value::String(v) => Some(v.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// The `path` record of a pax extended header (`<len> path=<value>\n`)
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, kv) = record.split_once(' ')?;
+        kv.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let digits = c_string(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const CHART: &str = r#"apiVersion: v2
+name: sonarr
+version: 1.2.3
+annotations:
+  kubarr.io/category: media
+  kubarr.io/display-name: Sonarr
+"#;
+
+    /// Build a ustar archive the way `tar` does
+    fn tarball(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (name, data) in files {
+            let mut header = [0u8; BLOCK];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+            header[136..147].copy_from_slice(b"00000000000");
+            header[156] = b'0';
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[148..156].fill(b' ');
+            let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+            header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+            tar.extend_from_slice(&header);
+            tar.extend_from_slice(data);
+            tar.extend(std::iter::repeat_n(
+                0u8,
+                (BLOCK - data.len() % BLOCK) % BLOCK,
+            ));
+        }
+        tar.extend_from_slice(&[0u8; 2 * BLOCK]);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&tar).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_import_replaces_charts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sonarr")).unwrap();
+        std::fs::write(dir.path().join("sonarr/stale.yaml"), "old").unwrap();
+        std::fs::create_dir_all(dir.path().join("radarr")).unwrap();
+
+        let bundle = tarball(&[
+            ("bundle/sonarr/Chart.yaml", CHART.as_bytes()),
+            (
+                "bundle/sonarr/templates/deployment.yaml",
+                b"kind: Deployment",
+            ),
+            ("bundle/README.md", b"charts"),
+        ]);
+        let result = import(&bundle, dir.path()).unwrap();
+        assert_eq!(result.charts.len(), 1);
+        assert_eq!(result.charts[0].name, "sonarr");
+        assert_eq!(result.charts[0].version.as_deref(), Some("1.2.3"));
+
+        let sonarr = dir.path().join("sonarr");
+        assert!(sonarr.join("templates/deployment.yaml").exists());
+        assert!(!sonarr.join("stale.yaml").exists());
+        assert!(dir.path().join("radarr").exists());
+        let leftovers = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with('.')
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        let escape = tarball(&[("../evil/Chart.yaml", CHART.as_bytes())]);
+        assert!(import(&escape, dir.path()).is_err());
+
+        let empty = tarball(&[("README.md", b"nothing here")]);
+        assert!(import(&empty, dir.path()).is_err());
+
+        // One invalid chart rejects the whole bundle
+        let invalid = tarball(&[
+            ("sonarr/Chart.yaml", CHART.as_bytes()),
+            ("Bad_Name/Chart.yaml", CHART.as_bytes()),
+        ]);
+        assert!(import(&invalid, dir.path()).is_err());
+        assert!(!dir.path().join("sonarr").exists());
+
+        assert!(import(b"not a bundle", dir.path()).is_err());
+    }
+}
@@ -0,0 +1,576 @@
+//! Validation of app catalog entries
+//!
+//! `parse_chart_content` is lenient: missing or malformed values fall back to
+//! defaults so one bad chart cannot take down the catalog. Contributors need
+//! the opposite, so this pass reports every value the installer would ignore
+//! or reject before the chart is published.
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use utoipa::ToSchema;
+
+use crate::config::CONFIG;
+use crate::endpoints::roles::APP_ACCESS_PERMISSIONS;
+
+use super::catalog::{parse_architectures, parse_chart_content, AppConfig};
+
+/// Boolean annotations, compared against the literal strings "true"/"false"
+const BOOL_ANNOTATIONS: &[&str] = &[
+    "kubarr.io/system",
+    "kubarr.io/hidden",
+    "kubarr.io/browseable",
+];
+
+/// Architectures Kubernetes nodes report, as in the `kubernetes.io/arch` label
+const KNOWN_ARCHITECTURES: &[&str] = &[
+    "amd64", "arm64", "arm", "386", "ppc64le", "s390x", "riscv64",
+];
+
+/// Kubernetes quantity suffixes accepted for CPU, memory and volume sizes
+const QUANTITY_SUFFIXES: &[&str] = &[
+    "Ki", "Mi", "Gi", "Ti", "Pi", "Ei", "m", "k", "M", "G", "T", "P", "E", "",
+];
+
+/// Catalog entry to validate
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CatalogEntryRequest {
+    /// Chart directory name, which becomes the app name
+    pub name: String,
+    /// Contents of Chart.yaml
+    pub chart_yaml: String,
+    /// Contents of values.yaml
+    pub values_yaml: Option<String>,
+    /// Contents of icon.svg
+    pub icon_svg: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogIssueLevel {
+    /// The installer would use a fallback or the app is incomplete
+    Warning,
+    /// The chart would be skipped or fail to install
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CatalogIssue {
+    pub level: CatalogIssueLevel,
+    /// File and path of the offending value, e.g. `values.yaml:sonarr.service.port`
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CatalogValidation {
+    /// True when there are no errors; warnings do not block installation
+    pub valid: bool,
+    /// The app as the catalog would load it
+    pub app: Option<AppConfig>,
+    pub issues: Vec<CatalogIssue>,
+}
+
+#[derive(Default)]
+struct Issues(Vec<CatalogIssue>);
+
+impl Issues {
+    fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(CatalogIssueLevel::Error, field, message);
+    }
+
+    fn warn(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.push(CatalogIssueLevel::Warning, field, message);
+    }
+
+    fn push(
+        &mut self,
+        level: CatalogIssueLevel,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.0.push(CatalogIssue {
+            level,
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// Validate a catalog entry, including chart and icon availability on disk
+pub fn validate_catalog_entry(entry: &CatalogEntryRequest) -> CatalogValidation {
+    let mut issues = Issues::default();
+    let name = entry.name.as_str();
+
+    if !is_dns_label(name) {
+        issues.error(
+            "name",
+            "must be a lowercase DNS label of at most 63 characters",
+        );
+    }
+
+    let parsed = check_chart(&mut issues, name, &entry.chart_yaml);
+    if let Some(values) = &entry.values_yaml {
+        check_values(&mut issues, name, values);
+    }
+
+    // Parse errors were already reported by check_chart
+    let app = parse_chart_content(name, &entry.chart_yaml, entry.values_yaml.as_deref())
+        .unwrap_or_default();
+
+    // The name is joined onto the charts directory, so only look when it is safe
+    if parsed && is_dns_label(name) {
+        check_availability(&mut issues, name, entry.icon_svg.as_deref());
+        check_permission_hint(&mut issues, name);
+    }
+
+    let valid = !issues.0.iter().any(|i| i.level == CatalogIssueLevel::Error);
+    CatalogValidation {
+        valid,
+        app,
+        issues: issues.0,
+    }
+}
+
+/// Returns false when Chart.yaml could not be parsed at all
+fn check_chart(issues: &mut Issues, name: &str, content: &str) -> bool {
+    let chart: Value = match serde_yaml::from_str(content) {
+        Ok(chart) => chart,
+        Err(e) => {
+            issues.error("Chart.yaml", format!("invalid YAML: {}", e));
+            return false;
+        }
+    };
+
+    match chart.get("apiVersion").and_then(Value::as_str) {
+        Some("v2") => {}
+        Some(other) => issues.error(
+            "Chart.yaml:apiVersion",
+            format!("'{}' is not supported, expected v2", other),
+        ),
+        None => issues.error("Chart.yaml:apiVersion", "is required"),
+    }
+
+    match chart.get("name").and_then(Value::as_str) {
+        Some(chart_name) if chart_name == name => {}
+        Some(chart_name) => issues.error(
+            "Chart.yaml:name",
+            format!(
+                "'{}' does not match the chart directory '{}'",
+                chart_name, name
+            ),
+        ),
+        None => issues.error("Chart.yaml:name", "is required"),
+    }
+
+    if chart.get("version").and_then(Value::as_str).is_none() {
+        issues.error("Chart.yaml:version", "is required");
+    }
+
+    if chart.get("description").and_then(Value::as_str).is_none() {
+        issues.warn(
+            "Chart.yaml:description",
+            "is missing, the catalog shows no description",
+        );
+    }
+
+    let annotations = chart.get("annotations").and_then(Value::as_mapping);
+    let annotation = |key: &str| annotations.and_then(|a| a.get(key));
+
+    match annotation("kubarr.io/category") {
+        Some(Value::String(category)) if !category.trim().is_empty() => {}
+        Some(_) => issues.error(
+            "Chart.yaml:annotations.kubarr.io/category",
+            "must be a non-empty string",
+        ),
+        None => issues.error(
+            "Chart.yaml:annotations.kubarr.io/category",
+            "is required, charts without it are not listed in the catalog",
+        ),
+    }
+
+    if annotation("kubarr.io/display-name").is_none() {
+        issues.warn(
+            "Chart.yaml:annotations.kubarr.io/display-name",
+            format!("is missing, '{}' is shown instead", name),
+        );
+    }
+
+    for key in BOOL_ANNOTATIONS {
+        match annotation(key) {
+            None => {}
+            Some(Value::String(s)) if matches!(s.as_str(), "true" | "false") => {}
+            Some(Value::Bool(_)) => issues.error(
+                format!("Chart.yaml:annotations.{}", key),
+                "must be quoted; Helm annotations are strings",
+            ),
+            Some(_) => issues.error(
+                format!("Chart.yaml:annotations.{}", key),
+                "must be \"true\" or \"false\"",
+            ),
+        }
+    }
+
+    let field = "Chart.yaml:annotations.kubarr.io/architectures";
+    match annotation("kubarr.io/architectures") {
+        None => {}
+        Some(Value::String(value)) => {
+            let architectures = parse_architectures(value);
+            if architectures.is_empty() {
+                issues.error(
+                    field,
+                    "must list at least one architecture, e.g. \"amd64,arm64\"",
+                );
+            }
+            for arch in architectures {
+                if !KNOWN_ARCHITECTURES.contains(&arch.as_str()) {
+                    issues.warn(
+                        field,
+                        format!("'{}' is not an architecture Kubernetes nodes report", arch),
+                    );
+                }
+            }
+        }
+        Some(_) => issues.error(
+            field,
+            "must be a comma-separated string, e.g. \"amd64,arm64\"",
+        ),
+    }
+
+    true
+}
+
+fn check_values(issues: &mut Issues, name: &str, content: &str) {
+    let values: Value = match serde_yaml::from_str(content) {
+        Ok(values) => values,
+        Err(e) => {
+            issues.error("values.yaml", format!("invalid YAML: {}", e));
+            return;
+        }
+    };
+
+    match values.get(name) {
+        Some(app) => check_app_values(issues, name, app),
+        None => issues.warn(
+            format!("values.yaml:{}", name),
+            format!(
+                "is missing, the image defaults to linuxserver/{}:latest on port 8080",
+                name
+            ),
+        ),
+    }
+
+    let Some(persistence) = values.get("persistence") else {
+        return;
+    };
+    let Some(persistence) = persistence.as_mapping() else {
+        issues.error("values.yaml:persistence", "must be a mapping of volumes");
+        return;
+    };
+    for (volume, config) in persistence {
+        let volume = volume.as_str().unwrap_or_default();
+        let field = format!("values.yaml:persistence.{}", volume);
+        let Some(config) = config.as_mapping() else {
+            issues.error(field, "must be a mapping");
+            continue;
+        };
+        if let Some(enabled) = config.get("enabled") {
+            if !enabled.is_bool() {
+                issues.error(format!("{}.enabled", field), "must be a boolean");
+            }
+        }
+        match config.get("mountPath") {
+            None => {}
+            Some(Value::String(path)) if path.starts_with('/') => {}
+            Some(_) => issues.error(format!("{}.mountPath", field), "must be an absolute path"),
+        }
+        if let Some(size) = config.get("size") {
+            check_quantity(issues, &format!("{}.size", field), size);
+        }
+    }
+}
+
+fn check_app_values(issues: &mut Issues, name: &str, app: &Value) {
+    let field = |path: &str| format!("values.yaml:{}.{}", name, path);
+
+    for key in ["repository", "tag"] {
+        match app.get("image").and_then(|i| i.get(key)) {
+            None | Some(Value::String(_)) => {}
+            // An unquoted tag such as 1.10 is parsed as a number
+            Some(_) => issues.error(field(&format!("image.{}", key)), "must be a quoted string"),
+        }
+    }
+
+    match app.get("service").and_then(|s| s.get("port")) {
+        None => issues.warn(field("service.port"), "is missing, port 8080 is assumed"),
+        Some(port) => match port.as_i64() {
+            Some(p) if (1..=65535).contains(&p) => {}
+            _ => issues.error(
+                field("service.port"),
+                "must be a number between 1 and 65535",
+            ),
+        },
+    }
+
+    if let Some(resources) = app.get("resources") {
+        for group in ["requests", "limits"] {
+            for resource in ["cpu", "memory"] {
+                if let Some(value) = resources.get(group).and_then(|g| g.get(resource)) {
+                    check_quantity(
+                        issues,
+                        &field(&format!("resources.{}.{}", group, resource)),
+                        value,
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(env) = app.get("env") {
+        match env.as_mapping() {
+            Some(env) => {
+                for (key, value) in env {
+                    if !value.is_string() {
+                        issues.error(
+                            field(&format!("env.{}", key.as_str().unwrap_or_default())),
+                            "must be a quoted string, other values are dropped",
+                        );
+                    }
+                }
+            }
+            None => issues.error(
+                field("env"),
+                "must be a mapping of variable names to values",
+            ),
+        }
+    }
+}
+
+fn check_availability(issues: &mut Issues, name: &str, icon_svg: Option<&str>) {
+    let chart_dir = CONFIG.charts.dir.join(name);
+    if !chart_dir.join("Chart.yaml").exists() {
+        issues.warn(
+            "chart",
+            format!(
+                "not found in {}; publish it to {} before installing",
+                CONFIG.charts.dir.display(),
+                CONFIG.charts.registry
+            ),
+        );
+    }
+
+    match icon_svg {
+        Some(svg) if svg.contains("<svg") => {}
+        Some(_) => issues.error("icon.svg", "is not an SVG document"),
+        None if chart_dir.join("icon.svg").exists() => {}
+        None => issues.warn(
+            "icon.svg",
+            "is missing, the catalog falls back to the emoji icon",
+        ),
+    }
+}
+
+fn check_permission_hint(issues: &mut Issues, name: &str) {
+    if !APP_ACCESS_PERMISSIONS.iter().any(|(app, _)| *app == name) {
+        issues.warn(
+            "permissions",
+            format!(
+                "app.{} is not listed in the role editor, add it to APP_ACCESS_PERMISSIONS",
+                name
+            ),
+        );
+    }
+}
+
+fn check_quantity(issues: &mut Issues, field: &str, value: &Value) {
+    let valid = match value {
+        Value::String(s) => is_quantity(s),
+        Value::Number(n) => n.as_f64().is_some_and(|n| n >= 0.0),
+        _ => false,
+    };
+    if !valid {
+        issues.error(field, "must be a Kubernetes quantity such as 500m or 1Gi");
+    }
+}
+
+fn is_quantity(value: &str) -> bool {
+    QUANTITY_SUFFIXES.iter().any(|suffix| {
+        value
+            .strip_suffix(suffix)
+            .is_some_and(|n| !n.is_empty() && n.parse::<f64>().is_ok_and(|n| n >= 0.0))
+    })
+}
+
+fn is_dns_label(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
+
+/// `kubarr validate-catalog <chart-dir>`: validate a chart directory
+pub fn run_check(args: &[String]) -> anyhow::Result<()> {
+    let Some(dir) = args.first().map(std::path::Path::new) else {
+        anyhow::bail!("usage: kubarr validate-catalog <chart-dir>");
+    };
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("{} is not a chart directory", dir.display()))?;
+
+    let entry = CatalogEntryRequest {
+        name: name.to_string(),
+        chart_yaml: std::fs::read_to_string(dir.join("Chart.yaml"))?,
+        values_yaml: std::fs::read_to_string(dir.join("values.yaml")).ok(),
+        icon_svg: std::fs::read_to_string(dir.join("icon.svg")).ok(),
+    };
+    let result = validate_catalog_entry(&entry);
+    for issue in &result.issues {
+        let label = match issue.level {
+            CatalogIssueLevel::Warning => "warning",
+            CatalogIssueLevel::Error => "error",
+        };
+        println!("{}: {}: {}", label, issue.field, issue.message);
+    }
+    if !result.valid {
+        anyhow::bail!("{} is not a valid catalog entry", name);
+    }
+    println!("{} OK", name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = r#"
+apiVersion: v2
+name: sonarr
+version: 1.0.0
+description: TV show manager
+annotations:
+  kubarr.io/category: media-manager
+  kubarr.io/display-name: Sonarr
+  kubarr.io/browseable: "true"
+"#;
+
+    const VALUES: &str = r#"
+sonarr:
+  image:
+    repository: linuxserver/sonarr
+    tag: "4.0"
+  service:
+    port: 8989
+  resources:
+    requests:
+      cpu: 100m
+      memory: 256Mi
+persistence:
+  config:
+    enabled: true
+    mountPath: /config
+    size: 1Gi
+"#;
+
+    fn entry(name: &str, chart: &str, values: &str) -> CatalogEntryRequest {
+        CatalogEntryRequest {
+            name: name.to_string(),
+            chart_yaml: chart.to_string(),
+            values_yaml: Some(values.to_string()),
+            icon_svg: Some("<svg xmlns=\"http://www.w3.org/2000/svg\"/>".to_string()),
+        }
+    }
+
+    fn error_fields(result: &CatalogValidation) -> Vec<&str> {
+        result
+            .issues
+            .iter()
+            .filter(|i| i.level == CatalogIssueLevel::Error)
+            .map(|i| i.field.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_entry() {
+        let result = validate_catalog_entry(&entry("sonarr", CHART, VALUES));
+        assert!(result.valid, "{:?}", result.issues);
+        let app = result.app.unwrap();
+        assert_eq!(app.container_image, "linuxserver/sonarr:4.0");
+        assert_eq!(app.default_port, 8989);
+    }
+
+    #[test]
+    fn test_invalid_values_are_reported() {
+        let chart = CHART
+            .replace("name: sonarr", "name: radarr")
+            .replace("\"true\"", "true");
+        let values = VALUES
+            .replace("\"4.0\"", "4.0")
+            .replace("8989", "70000")
+            .replace("256Mi", "lots")
+            .replace("/config", "config");
+        let result = validate_catalog_entry(&entry("sonarr", &chart, &values));
+        assert!(!result.valid);
+        assert_eq!(
+            error_fields(&result),
+            vec![
+                "Chart.yaml:name",
+                "Chart.yaml:annotations.kubarr.io/browseable",
+                "values.yaml:sonarr.image.tag",
+                "values.yaml:sonarr.service.port",
+                "values.yaml:sonarr.resources.requests.memory",
+                "values.yaml:persistence.config.mountPath",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_category_and_permission_hint() {
+        let chart = CHART
+            .replace("sonarr", "newapp")
+            .replace("  kubarr.io/category: media-manager\n", "");
+        let result = validate_catalog_entry(&entry("newapp", &chart, ""));
+        assert_eq!(
+            error_fields(&result),
+            vec!["Chart.yaml:annotations.kubarr.io/category"]
+        );
+        assert!(result.app.is_none());
+        assert!(result.issues.iter().any(|i| i.field == "permissions"));
+    }
+
+    #[test]
+    fn test_architectures() {
+        let chart = CHART.replace(
+            "  kubarr.io/browseable: \"true\"\n",
+            "  kubarr.io/architectures: \"x86_64, arm64, sparc\"\n",
+        );
+        let result = validate_catalog_entry(&entry("sonarr", &chart, VALUES));
+        assert!(result.valid, "{:?}", result.issues);
+        assert_eq!(
+            result.app.unwrap().architectures,
+            vec!["amd64", "arm64", "sparc"]
+        );
+        assert!(result
+            .issues
+            .iter()
+            .any(|i| i.level == CatalogIssueLevel::Warning && i.message.contains("'sparc'")));
+
+        let chart = chart.replace("\"x86_64, arm64, sparc\"", "\" , \"");
+        let result = validate_catalog_entry(&entry("sonarr", &chart, VALUES));
+        assert_eq!(
+            error_fields(&result),
+            vec!["Chart.yaml:annotations.kubarr.io/architectures"]
+        );
+    }
+
+    #[test]
+    fn test_quantities() {
+        assert!(is_quantity("500m"));
+        assert!(is_quantity("1.5Gi"));
+        assert!(is_quantity("2"));
+        assert!(!is_quantity("Gi"));
+        assert!(!is_quantity("1GB"));
+    }
+}
@@ -1,7 +1,9 @@
 //! Chart sync service
 //!
 //! Discovers charts from GitHub and pulls them from an OCI registry
-//! so the catalog always reflects the latest published versions.
+//! so the catalog always reflects the latest published versions. In offline
+//! mode (`KUBARR_OFFLINE=true`) nothing is fetched and charts come from
+//! imported bundles instead (see [`super::catalog_bundle`]).
 
 use std::process::Command;
 use std::sync::Arc;
@@ -11,6 +13,7 @@ use async_trait::async_trait;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
 
+use super::outbound::{ProxySettings, SharedClient};
 use crate::config::CONFIG;
 use crate::state::SharedCatalog;
 
@@ -25,24 +28,27 @@ struct GitHubContent {
 /// Shared chart sync service used by both the scheduler and the on-demand endpoint.
 pub struct ChartSyncService {
     catalog: SharedCatalog,
-    client: reqwest::Client,
+    client: SharedClient,
 }
 
 impl ChartSyncService {
-    #[allow(clippy::expect_used)]
     pub fn new(catalog: SharedCatalog) -> Self {
         Self {
             catalog,
-            client: reqwest::Client::builder()
-                .user_agent("kubarr-backend")
-                .timeout(Duration::from_secs(30))
-                .build()
-                .expect("failed to build reqwest client"),
+            client: SharedClient::new(|builder| {
+                builder
+                    .user_agent("kubarr-backend")
+                    .timeout(Duration::from_secs(30))
+            }),
         }
     }
 
     /// Discover chart names from the GitHub repo, pull each from OCI, and reload the catalog.
     pub async fn sync(&self) -> anyhow::Result<()> {
+        if CONFIG.outbound.offline {
+            anyhow::bail!("chart sync is disabled in offline mode, import a chart bundle instead");
+        }
+
         let chart_names = self.discover_charts().await?;
 
         if chart_names.is_empty() {
@@ -75,7 +81,13 @@ impl ChartSyncService {
             CONFIG.charts.repo, CONFIG.charts.git_ref,
         );
 
-        let resp = self.client.get(&url).send().await?.error_for_status()?;
+        let resp = self
+            .client
+            .client()
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?;
 
         let entries: Vec<GitHubContent> = resp.json().await?;
 
@@ -96,6 +108,7 @@ impl ChartSyncService {
 
         let output = Command::new("helm")
             .args(["pull", &chart_ref, "--untar", "--destination", dest])
+            .envs(ProxySettings::current().env_vars())
             .output()?;
 
         if !output.status.success() {
@@ -124,6 +137,9 @@ impl super::scheduler::PeriodicTask for ChartSyncTask {
     }
 
     async fn run(&self, _db: &DatabaseConnection) -> anyhow::Result<()> {
+        if CONFIG.outbound.offline {
+            return Ok(());
+        }
         self.service.sync().await
     }
 }
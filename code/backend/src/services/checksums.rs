@@ -0,0 +1,323 @@
+//! Checksums of files in storage
+//!
+//! Files up to [`INLINE_LIMIT`] are hashed within the request; larger ones
+//! are hashed by a background task while the request returns the pending
+//! record to poll. Results are stored with the file's size and modification
+//! time, so a result for an unchanged file is reused and one for a changed
+//! file is reported as stale.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, Result};
+use crate::models::file_checksum;
+use crate::models::prelude::*;
+
+/// Largest file hashed within the request (64 MiB)
+pub const INLINE_LIMIT: u64 = 64 * 1024 * 1024;
+
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl ChecksumAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Blake3 => "blake3",
+        }
+    }
+}
+
+/// Hash a file, returning the lowercase hex digest
+pub fn hash_file(path: &Path, algorithm: ChecksumAlgorithm) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    match algorithm {
+        ChecksumAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
+            Ok(hex::encode(hasher.finalize()))
+        }
+        ChecksumAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+    }
+}
+
+/// Size and modification time of a file, identifying its current version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileVersion {
+    pub size: i64,
+    pub modified: DateTime<Utc>,
+}
+
+impl FileVersion {
+    pub fn read(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| AppError::Internal(format!("Failed to get file metadata: {}", e)))?;
+        // Stored timestamps keep microseconds at most
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| {
+                DateTime::from_timestamp_micros(DateTime::<Utc>::from(t).timestamp_micros())
+            })
+            .unwrap_or_default();
+        Ok(Self {
+            size: metadata.len() as i64,
+            modified,
+        })
+    }
+
+    fn matches(&self, record: &file_checksum::Model) -> bool {
+        record.size == self.size && record.file_modified_at == self.modified
+    }
+}
+
+/// A stored checksum as returned by the API
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ChecksumResult {
+    pub id: i64,
+    pub root: String,
+    pub path: String,
+    pub algorithm: String,
+    /// "pending", "running", "completed" or "failed"
+    pub status: String,
+    pub checksum: Option<String>,
+    pub size: i64,
+    pub file_modified_at: DateTime<Utc>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The file changed since the checksum was requested
+    pub stale: bool,
+    /// Whether the checksum equals the expected one, when one was given and
+    /// the checksum is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<bool>,
+}
+
+impl ChecksumResult {
+    pub fn new(
+        record: file_checksum::Model,
+        current: Option<FileVersion>,
+        expected: Option<&str>,
+    ) -> Self {
+        let stale = !current.is_some_and(|v| v.matches(&record));
+        let matches = match (expected, &record.checksum) {
+            (Some(expected), Some(checksum)) => {
+                Some(expected.trim().eq_ignore_ascii_case(checksum))
+            }
+            _ => None,
+        };
+        Self {
+            id: record.id,
+            root: record.root,
+            path: record.path,
+            algorithm: record.algorithm,
+            status: record.status,
+            checksum: record.checksum,
+            size: record.size,
+            file_modified_at: record.file_modified_at,
+            error: record.error,
+            created_at: record.created_at,
+            completed_at: record.completed_at,
+            stale,
+            matches,
+        }
+    }
+}
+
+/// Checksum a file, reusing a result for the same version of it
+///
+/// Returns the record and whether it is being computed in the background.
+pub async fn request(
+    db: &DatabaseConnection,
+    user_id: i64,
+    root: &str,
+    relative: &str,
+    file: &Path,
+    algorithm: ChecksumAlgorithm,
+) -> Result<(file_checksum::Model, bool)> {
+    let version = FileVersion::read(file)?;
+    let existing = FileChecksum::find()
+        .filter(file_checksum::Column::Root.eq(root))
+        .filter(file_checksum::Column::Path.eq(relative))
+        .filter(file_checksum::Column::Algorithm.eq(algorithm.as_str()))
+        .filter(file_checksum::Column::Status.ne("failed"))
+        .order_by_desc(file_checksum::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .find(|record| version.matches(record));
+    if let Some(record) = existing {
+        let background = record.status != "completed";
+        return Ok((record, background));
+    }
+
+    let background = version.size as u64 > INLINE_LIMIT;
+    let record = file_checksum::ActiveModel {
+        root: Set(root.to_string()),
+        path: Set(relative.to_string()),
+        algorithm: Set(algorithm.as_str().to_string()),
+        status: Set(if background { "pending" } else { "running" }.to_string()),
+        checksum: Set(None),
+        size: Set(version.size),
+        file_modified_at: Set(version.modified),
+        error: Set(None),
+        requested_by: Set(Some(user_id)),
+        created_at: Set(Utc::now()),
+        completed_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    if background {
+        let db = db.clone();
+        let file = file.to_path_buf();
+        let id = record.id;
+        tokio::spawn(async move {
+            if let Err(e) = run(&db, id, file, algorithm).await {
+                tracing::warn!("Checksum job {} failed: {}", id, e);
+            }
+        });
+        return Ok((record, true));
+    }
+
+    let record = compute(db, record, file.to_path_buf(), algorithm).await?;
+    Ok((record, false))
+}
+
+/// Background job: mark the record running, then compute it
+async fn run(
+    db: &DatabaseConnection,
+    id: i64,
+    file: PathBuf,
+    algorithm: ChecksumAlgorithm,
+) -> Result<()> {
+    let record = FileChecksum::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Checksum {} not found", id)))?;
+    let mut active: file_checksum::ActiveModel = record.into();
+    active.status = Set("running".to_string());
+    let record = active.update(db).await?;
+    compute(db, record, file, algorithm).await?;
+    Ok(())
+}
+
+/// Hash the file and store the outcome on its record
+async fn compute(
+    db: &DatabaseConnection,
+    record: file_checksum::Model,
+    file: PathBuf,
+    algorithm: ChecksumAlgorithm,
+) -> Result<file_checksum::Model> {
+    let outcome = tokio::task::spawn_blocking(move || hash_file(&file, algorithm))
+        .await
+        .map_err(|e| AppError::Internal(format!("Checksum task failed: {}", e)))?;
+
+    let mut active: file_checksum::ActiveModel = record.into();
+    match outcome {
+        Ok(checksum) => {
+            active.status = Set("completed".to_string());
+            active.checksum = Set(Some(checksum));
+        }
+        Err(e) => {
+            active.status = Set("failed".to_string());
+            active.error = Set(Some(e.to_string()));
+        }
+    }
+    active.completed_at = Set(Some(Utc::now()));
+    Ok(active.update(db).await?)
+}
+
+/// Stored checksums of a file, newest first
+pub async fn results(
+    db: &DatabaseConnection,
+    root: &str,
+    relative: &str,
+) -> Result<Vec<file_checksum::Model>> {
+    Ok(FileChecksum::find()
+        .filter(file_checksum::Column::Root.eq(root))
+        .filter(file_checksum::Column::Path.eq(relative))
+        .order_by_desc(file_checksum::Column::Id)
+        .all(db)
+        .await?)
+}
+
+/// Drop the stored checksums of a deleted file
+pub async fn forget(db: &DatabaseConnection, root: &str, relative: &str) -> Result<()> {
+    FileChecksum::delete_many()
+        .filter(file_checksum::Column::Root.eq(root))
+        .filter(file_checksum::Column::Path.eq(relative))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Fail jobs left pending or running by a previous process
+pub async fn fail_interrupted(db: &DatabaseConnection) -> Result<u64> {
+    let result = FileChecksum::update_many()
+        .col_expr(file_checksum::Column::Status, Expr::value("failed"))
+        .col_expr(
+            file_checksum::Column::Error,
+            Expr::value("interrupted by a restart"),
+        )
+        .col_expr(file_checksum::Column::CompletedAt, Expr::value(Utc::now()))
+        .filter(file_checksum::Column::Status.is_in(["pending", "running"]))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("kubarr_checksum_{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            hash_file(&path, ChecksumAlgorithm::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_file(&path, ChecksumAlgorithm::Blake3).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert!(hash_file(&path, ChecksumAlgorithm::Sha256).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Client certificate authentication
+//!
+//! Automation that reaches the API over a network it does not trust can
+//! authenticate with a client certificate instead of a bearer token. The
+//! ingress in front of Kubarr terminates mutual TLS: it verifies the
+//! certificate against its CA and forwards it in the header named by
+//! `KUBARR_CLIENT_CERT_HEADER`, with the result in
+//! `KUBARR_CLIENT_CERT_VERIFY_HEADER`. Kubarr looks the certificate up by
+//! its SHA-256 fingerprint and authenticates the request as the user it is
+//! registered for, usually a dedicated automation user with narrow roles.
+//!
+//! Both headers are trusted as the ingress sets them, so the ingress must
+//! overwrite them on every request. Certificates are accepted as URL-encoded
+//! PEM (ingress-nginx) or base64 DER (Traefik). A session cookie or bearer
+//! token on the same request takes precedence.
+
+use axum::http::HeaderMap;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::models::client_certificate;
+use crate::models::prelude::*;
+use crate::services::trust_store::{self, CertificateStatus};
+use crate::state::DbConn;
+
+/// Verification result the ingress reports for a valid certificate
+const VERIFIED: &str = "SUCCESS";
+
+/// Certificate to register for a user
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ClientCertificateRequest {
+    /// Label shown in the list, e.g. `backup script`
+    pub name: String,
+    /// PEM-encoded client certificate, without its private key
+    pub pem: String,
+}
+
+/// A client certificate registered for a user
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClientCertificateInfo {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub subject: String,
+    /// SHA-256 of the DER encoding, hex
+    pub fingerprint: String,
+    pub not_after: DateTime<Utc>,
+    pub status: CertificateStatus,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ClientCertificateInfo {
+    fn new(model: client_certificate::Model, now: DateTime<Utc>) -> Self {
+        Self {
+            status: trust_store::status(model.not_after, now),
+            id: model.id,
+            user_id: model.user_id,
+            name: model.name,
+            subject: model.subject,
+            fingerprint: model.fingerprint,
+            not_after: model.not_after,
+            last_used_at: model.last_used_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+// ============================================================================
+// Management
+// ============================================================================
+
+/// Certificates registered for a user, newest first
+pub async fn list(
+    db: &DbConn,
+    user_id: i64,
+    now: DateTime<Utc>,
+) -> Result<Vec<ClientCertificateInfo>> {
+    Ok(ClientCertificate::find()
+        .filter(client_certificate::Column::UserId.eq(user_id))
+        .order_by_desc(client_certificate::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|model| ClientCertificateInfo::new(model, now))
+        .collect())
+}
+
+/// Register a certificate for a user
+///
+/// A certificate maps to one user only, so registering it twice is a
+/// conflict.
+pub async fn create(
+    db: &DbConn,
+    user_id: i64,
+    request: ClientCertificateRequest,
+) -> Result<ClientCertificateInfo> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("name is required".to_string()));
+    }
+    let mut certificates = trust_store::parse_pem(&request.pem)?;
+    if certificates.len() != 1 {
+        return Err(AppError::BadRequest(
+            "Upload exactly one client certificate".to_string(),
+        ));
+    }
+    let certificate = certificates.remove(0);
+    let now = Utc::now();
+    if certificate.not_after <= now {
+        return Err(AppError::BadRequest(format!(
+            "Certificate {} expired on {}",
+            certificate.subject,
+            certificate.not_after.format("%Y-%m-%d")
+        )));
+    }
+    let existing = ClientCertificate::find()
+        .filter(client_certificate::Column::Fingerprint.eq(&certificate.fingerprint))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Certificate {} is already registered",
+            certificate.subject
+        )));
+    }
+
+    let model = client_certificate::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.to_string()),
+        subject: Set(certificate.subject),
+        fingerprint: Set(certificate.fingerprint),
+        not_after: Set(certificate.not_after),
+        last_used_at: Set(None),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(ClientCertificateInfo::new(model, now))
+}
+
+/// Remove a user's certificate, returning what was removed
+pub async fn delete(db: &DbConn, user_id: i64, id: i64) -> Result<client_certificate::Model> {
+    let existing = ClientCertificate::find_by_id(id)
+        .filter(client_certificate::Column::UserId.eq(user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Certificate not found".to_string()))?;
+    ClientCertificate::delete_by_id(id).exec(db).await?;
+    Ok(existing)
+}
+
+// ============================================================================
+// Authentication
+// ============================================================================
+
+/// User the request's client certificate is registered for
+///
+/// `Ok(None)` when client certificate auth is off or the request carries no
+/// certificate; an error message when it carries one that cannot be used.
+pub async fn authenticate(
+    db: &DbConn,
+    headers: &HeaderMap,
+    now: DateTime<Utc>,
+) -> std::result::Result<Option<i64>, String> {
+    let Some(header) = CONFIG.auth.client_cert_header.as_deref() else {
+        return Ok(None);
+    };
+    let Some(value) = headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let verified = headers
+        .get(CONFIG.auth.client_cert_verify_header.as_str())
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim() == VERIFIED);
+    if !verified {
+        return Err("Client certificate was not verified".to_string());
+    }
+
+    let fingerprint = fingerprint(value).ok_or_else(|| "Invalid client certificate".to_string())?;
+    let certificate = ClientCertificate::find()
+        .filter(client_certificate::Column::Fingerprint.eq(&fingerprint))
+        .one(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Client certificate is not registered".to_string())?;
+    if certificate.not_after <= now {
+        return Err("Client certificate has expired".to_string());
+    }
+
+    let user_id = certificate.user_id;
+    let mut model: client_certificate::ActiveModel = certificate.into();
+    model.last_used_at = Set(Some(now));
+    if let Err(e) = model.update(db).await {
+        tracing::debug!("Failed to record client certificate use: {}", e);
+    }
+    Ok(Some(user_id))
+}
+
+/// Fingerprint of a forwarded certificate, as URL-encoded PEM or base64 DER
+fn fingerprint(value: &str) -> Option<String> {
+    let value = urlencoding::decode(value.trim()).ok()?;
+    let certificate = if value.contains("-----BEGIN") {
+        trust_store::parse_pem(&value).ok()?.into_iter().next()?
+    } else {
+        // Traefik sends the chain comma-separated, leaf first
+        let leaf: String = value.split(',').next()?.split_whitespace().collect();
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(leaf)
+            .ok()?;
+        trust_store::parse_der(&der).ok()?
+    };
+    Some(certificate.fingerprint)
+}
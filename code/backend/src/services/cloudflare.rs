@@ -16,13 +16,13 @@ use chrono::Utc;
 use k8s_openapi::api::core::v1::Secret;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 use kube::api::{Api, DeleteParams, PostParams};
-use once_cell::sync::Lazy;
 use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, Result};
 use crate::models::cloudflare_tunnel;
 use crate::models::prelude::*;
+use crate::services::outbound::SharedClient;
 use crate::services::K8sClient;
 use crate::state::DbConn;
 
@@ -34,16 +34,11 @@ const CLOUDFLARED_NAMESPACE: &str = "cloudflared";
 const CLOUDFLARED_SECRET_NAME: &str = "cloudflared-tunnel-token";
 const CLOUDFLARED_RELEASE_NAME: &str = "cloudflared";
 const CLOUDFLARED_CHART_PATH: &str = "/app/charts/cloudflared";
-const CF_API_BASE: &str = "https://api.cloudflare.com/client/v4";
+pub(crate) const CF_API_BASE: &str = "https://api.cloudflare.com/client/v4";
 
 // Shared reqwest client for Cloudflare API requests
-#[allow(clippy::expect_used)]
-static CF_HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
-    reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .expect("Failed to build Cloudflare HTTP client")
-});
+static CF_HTTP_CLIENT: SharedClient =
+    SharedClient::new(|builder| builder.timeout(std::time::Duration::from_secs(30)));
 
 // ============================================================================
 // Public Request / Response Types
@@ -154,6 +149,7 @@ impl<T> CfResponse<T> {
 /// Verify that the given API token is valid
 async fn cf_verify_token(token: &str) -> Result<()> {
     let resp = CF_HTTP_CLIENT
+        .client()
         .get(format!("{}/user/tokens/verify", CF_API_BASE))
         .header("Authorization", format!("Bearer {}", token))
         .send()
@@ -177,6 +173,7 @@ async fn cf_get_account(token: &str) -> Result<String> {
     }
 
     let resp = CF_HTTP_CLIENT
+        .client()
         .get(format!("{}/accounts", CF_API_BASE))
         .header("Authorization", format!("Bearer {}", token))
         .send()
@@ -203,6 +200,7 @@ async fn cf_list_zones(token: &str, account_id: &str) -> Result<Vec<ZoneInfo>> {
     }
 
     let resp = CF_HTTP_CLIENT
+        .client()
         .get(format!("{}/zones", CF_API_BASE))
         .header("Authorization", format!("Bearer {}", token))
         .query(&[
@@ -242,6 +240,7 @@ async fn cf_create_tunnel(token: &str, account_id: &str, name: &str) -> Result<(
     let tunnel_secret = base64::engine::general_purpose::STANDARD.encode(raw);
 
     let resp = CF_HTTP_CLIENT
+        .client()
         .post(format!(
             "{}/accounts/{}/cfd_tunnel",
             CF_API_BASE, account_id
@@ -267,6 +266,7 @@ async fn cf_create_tunnel(token: &str, account_id: &str, name: &str) -> Result<(
 /// Retrieve the token for an existing tunnel
 async fn cf_get_tunnel_token(token: &str, account_id: &str, tunnel_id: &str) -> Result<String> {
     let resp = CF_HTTP_CLIENT
+        .client()
         .get(format!(
             "{}/accounts/{}/cfd_tunnel/{}/token",
             CF_API_BASE, account_id, tunnel_id
@@ -292,6 +292,7 @@ async fn cf_configure_ingress(
     hostname: &str,
 ) -> Result<()> {
     let resp = CF_HTTP_CLIENT
+        .client()
         .put(format!(
             "{}/accounts/{}/cfd_tunnel/{}/configurations",
             CF_API_BASE, account_id, tunnel_id
@@ -334,6 +335,7 @@ async fn cf_create_dns_record(
     }
 
     let resp = CF_HTTP_CLIENT
+        .client()
         .post(format!("{}/zones/{}/dns_records", CF_API_BASE, zone_id))
         .header("Authorization", format!("Bearer {}", token))
         .json(&serde_json::json!({
@@ -358,6 +360,7 @@ async fn cf_create_dns_record(
 /// Delete a Cloudflare Tunnel (force=true allows deleting active tunnels)
 async fn cf_delete_tunnel(token: &str, account_id: &str, tunnel_id: &str) -> Result<()> {
     let resp = CF_HTTP_CLIENT
+        .client()
         .delete(format!(
             "{}/accounts/{}/cfd_tunnel/{}",
             CF_API_BASE, account_id, tunnel_id
@@ -380,6 +383,7 @@ async fn cf_delete_tunnel(token: &str, account_id: &str, tunnel_id: &str) -> Res
 /// Delete a DNS record
 async fn cf_delete_dns_record(token: &str, zone_id: &str, record_id: &str) -> Result<()> {
     let resp = CF_HTTP_CLIENT
+        .client()
         .delete(format!(
             "{}/zones/{}/dns_records/{}",
             CF_API_BASE, zone_id, record_id
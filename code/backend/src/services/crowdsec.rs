@@ -0,0 +1,209 @@
+//! CrowdSec and Fail2ban hooks
+//!
+//! With `KUBARR_AUTH_FAILURE_LOG` set, every failed sign-in with a known
+//! client address is appended to that file as one line:
+//!
+//! ```text
+//! 2026-10-18T10:15:00Z kubarr auth failure: user="alice" ip=203.0.113.9 method=password
+//! ```
+//!
+//! so Fail2ban or a CrowdSec agent can watch it. Usernames are quoted with
+//! quotes and control characters replaced.
+//!
+//! With the `crowdsec_lapi_url` setting and `KUBARR_CROWDSEC_LAPI_KEY` (a
+//! bouncer key from `cscli bouncers add`) set, Kubarr also acts as a
+//! bouncer: addresses with a `ban` decision in the Local API are refused like
+//! locally banned ones. Answers are cached per address for a minute. If the
+//! Local API cannot be reached, requests are let through and a warning is
+//! logged.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::CONFIG;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::services::outbound::SharedClient;
+use crate::state::DbConn;
+
+/// Base URL of the CrowdSec Local API; empty turns the bouncer off
+pub const CROWDSEC_LAPI_URL: &str = "crowdsec_lapi_url";
+
+/// How long a Local API answer is reused for an address
+pub const DECISION_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Longest username written to the failure log
+const MAX_USERNAME_LEN: usize = 64;
+
+/// Local API answers: address -> whether it is banned, and when asked
+static DECISIONS: Lazy<Mutex<HashMap<String, (bool, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static LAPI_CLIENT: SharedClient =
+    SharedClient::new(|builder| builder.timeout(Duration::from_secs(2)));
+
+/// A Local API URL from the setting, or an error for anything but http(s)
+pub fn parse_lapi_url(value: &str) -> Result<Option<String>> {
+    let value = value.trim().trim_end_matches('/');
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if !(value.starts_with("http://") || value.starts_with("https://")) {
+        return Err(AppError::BadRequest(
+            "crowdsec_lapi_url must start with http:// or https://".to_string(),
+        ));
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// Line recorded for a failed sign-in
+pub fn auth_failure_line(at: DateTime<Utc>, username: &str, ip: &str, method: &str) -> String {
+    let username: String = username
+        .chars()
+        .take(MAX_USERNAME_LEN)
+        .map(|c| if c == '"' || c.is_control() { '?' } else { c })
+        .collect();
+    format!(
+        "{} kubarr auth failure: user=\"{}\" ip={} method={}\n",
+        at.format("%Y-%m-%dT%H:%M:%SZ"),
+        username,
+        ip,
+        method
+    )
+}
+
+/// Append a failed sign-in to the failure log, if one is configured
+pub async fn log_auth_failure(username: &str, ip: &str, method: &str) {
+    let Some(path) = &CONFIG.security.auth_failure_log else {
+        return;
+    };
+    let line = auth_failure_line(Utc::now(), username, ip, method);
+    let written = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(line.as_bytes()).await
+    }
+    .await;
+    if let Err(e) = written {
+        tracing::warn!("Failed to write to auth failure log {}: {}", path, e);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Decision {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Whether a Local API decisions response holds a ban; the API answers
+/// `null` when there are no decisions
+pub fn parse_decisions(body: &str) -> Result<bool> {
+    let decisions: Option<Vec<Decision>> = serde_json::from_str(body)
+        .map_err(|e| AppError::Internal(format!("Invalid CrowdSec decisions: {}", e)))?;
+    Ok(decisions
+        .unwrap_or_default()
+        .iter()
+        .any(|d| d.kind.eq_ignore_ascii_case("ban")))
+}
+
+/// Ask the Local API whether an address is banned
+async fn fetch_ban(url: &str, key: &str, ip: &str) -> Result<bool> {
+    let response = LAPI_CLIENT
+        .client()
+        .get(format!("{}/v1/decisions", url))
+        .query(&[("ip", ip)])
+        .header("X-Api-Key", key)
+        .send()
+        .await
+        .map_err(|e| AppError::ServiceUnavailable(format!("CrowdSec unreachable: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::ServiceUnavailable(format!(
+            "CrowdSec answered {}",
+            response.status()
+        )));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AppError::ServiceUnavailable(format!("CrowdSec unreachable: {}", e)))?;
+    parse_decisions(&body)
+}
+
+/// Whether CrowdSec has banned an address; false when the bouncer is off or
+/// the Local API cannot be reached
+pub async fn is_banned(db: &DbConn, ip: &str) -> Result<bool> {
+    let Some(key) = &CONFIG.security.crowdsec_lapi_key else {
+        return Ok(false);
+    };
+    let Some(url) = get_setting_value(db, CROWDSEC_LAPI_URL)
+        .await?
+        .and_then(|v| parse_lapi_url(&v).ok().flatten())
+    else {
+        return Ok(false);
+    };
+
+    if let Some((banned, at)) = DECISIONS.lock().unwrap().get(ip) {
+        if at.elapsed() < DECISION_CACHE_TTL {
+            return Ok(*banned);
+        }
+    }
+    match fetch_ban(&url, key, ip).await {
+        Ok(banned) => {
+            let mut decisions = DECISIONS.lock().unwrap();
+            decisions.retain(|_, (_, at)| at.elapsed() < DECISION_CACHE_TTL);
+            decisions.insert(ip.to_string(), (banned, Instant::now()));
+            Ok(banned)
+        }
+        Err(e) => {
+            tracing::warn!("Could not check CrowdSec decisions for {}: {}", ip, e);
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_auth_failure_line() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 18, 10, 15, 0).unwrap();
+        assert_eq!(
+            auth_failure_line(at, "alice", "203.0.113.9", "password"),
+            "2026-10-18T10:15:00Z kubarr auth failure: user=\"alice\" ip=203.0.113.9 method=password\n"
+        );
+        assert!(auth_failure_line(at, "a\"b\nc", "::1", "webdav").contains("user=\"a?b?c\""));
+    }
+
+    #[test]
+    fn test_parse_decisions() {
+        assert!(!parse_decisions("null").unwrap());
+        assert!(!parse_decisions("[]").unwrap());
+        assert!(!parse_decisions(r#"[{"type": "captcha", "value": "1.2.3.4"}]"#).unwrap());
+        assert!(parse_decisions(
+            r#"[{"id": 1, "type": "ban", "scope": "Ip", "value": "1.2.3.4", "duration": "3h59m"}]"#
+        )
+        .unwrap());
+        assert!(parse_decisions("<html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_lapi_url() {
+        assert_eq!(parse_lapi_url(" ").unwrap(), None);
+        assert_eq!(
+            parse_lapi_url("http://crowdsec-service.crowdsec:8080/").unwrap(),
+            Some("http://crowdsec-service.crowdsec:8080".to_string())
+        );
+        assert!(parse_lapi_url("crowdsec:8080").is_err());
+    }
+}
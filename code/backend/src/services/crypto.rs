@@ -0,0 +1,339 @@
+//! Encryption of stored credentials
+//!
+//! With an encryption key configured, the credentials in
+//! [`ENCRYPTED_COLUMNS`] are stored encrypted with AES-256-GCM: OAuth client
+//! secrets, VPN provider credentials, and the passwords, tokens and keys in
+//! notification channel configs, such as the SMTP password. Notification
+//! configs are encrypted per field, so their other settings stay readable.
+//!
+//! The key comes from `KUBARR_ENCRYPTION_KEY`, or from the `encryption-key`
+//! entry of the Kubernetes secret named by `KUBARR_ENCRYPTION_KEY_SECRET`.
+//! It is 32 bytes, written as base64 or hex.
+//!
+//! Encrypted values start with [`ENCRYPTED_PREFIX`], so values stored
+//! before the key was set are still read as plain text; [`encrypt_existing`]
+//! encrypts them on startup. Without a key, values are stored and read as
+//! plain text, but values that were encrypted cannot be read.
+
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::Value as JsonValue;
+
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{notification_channel, oauth_provider, vpn_provider};
+use crate::services::K8sClient;
+use crate::state::DbConn;
+
+/// Marks a stored value as encrypted
+pub const ENCRYPTED_PREFIX: &str = "kbrenc:v1:";
+
+/// Entry of `KUBARR_ENCRYPTION_KEY_SECRET` holding the key
+pub const SECRET_KEY_ENTRY: &str = "encryption-key";
+
+/// Columns stored encrypted, as `(table, column)`
+pub const ENCRYPTED_COLUMNS: &[(&str, &str)] = &[
+    ("oauth_providers", "client_secret"),
+    ("vpn_providers", "credentials_json"),
+    ("notification_channels", "config"),
+];
+
+/// Column whose sensitive JSON fields are encrypted rather than the whole value
+const CONFIG_COLUMN: (&str, &str) = ("notification_channels", "config");
+
+const KEY_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+
+static CIPHER: Lazy<RwLock<Option<Arc<Cipher>>>> = Lazy::new(|| RwLock::new(None));
+
+/// AES-256-GCM under one key
+pub struct Cipher(Aes256Gcm);
+
+impl Cipher {
+    pub fn new(key: &[u8; KEY_BYTES]) -> Self {
+        Self(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::Internal("Failed to encrypt a credential".to_string()))?;
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(data)
+        ))
+    }
+
+    /// Decrypt a value written by [`Self::encrypt`]; `None` if the key is
+    /// wrong or the value was altered
+    pub fn decrypt(&self, value: &str) -> Option<String> {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(value.strip_prefix(ENCRYPTED_PREFIX)?)
+            .ok()?;
+        if data.len() < NONCE_BYTES {
+            return None;
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_BYTES);
+        let plaintext = self.0.decrypt(Nonce::from_slice(nonce), ciphertext).ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+/// Parse a 32-byte key written as base64 or hex
+pub fn parse_key(value: &str) -> std::result::Result<[u8; KEY_BYTES], String> {
+    let value = value.trim();
+    let bytes = if value.len() == KEY_BYTES * 2 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(value).map_err(|e| e.to_string())?
+    } else {
+        base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|_| "is neither base64 nor hex".to_string())?
+    };
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("is {} bytes long, not {}", b.len(), KEY_BYTES))
+}
+
+/// Use `key` for stored credentials; `None` stores them as plain text
+pub fn set_key(key: Option<[u8; KEY_BYTES]>) {
+    *CIPHER.write() = key.map(|key| Arc::new(Cipher::new(&key)));
+}
+
+/// Whether credentials are encrypted when stored
+pub fn enabled() -> bool {
+    CIPHER.read().is_some()
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypt a credential for storage; unchanged without a key or if it is
+/// encrypted already
+pub fn encrypt(plaintext: &str) -> Result<String> {
+    match CIPHER.read().as_ref() {
+        Some(cipher) if !is_encrypted(plaintext) => cipher.encrypt(plaintext),
+        _ => Ok(plaintext.to_string()),
+    }
+}
+
+/// Decrypt a stored credential; plain text is returned as it is
+pub fn decrypt(value: &str) -> Result<String> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+    let cipher = CIPHER.read().clone().ok_or_else(|| {
+        AppError::Internal(
+            "A stored credential is encrypted, but no encryption key is configured".to_string(),
+        )
+    })?;
+    cipher.decrypt(value).ok_or_else(|| {
+        AppError::Internal(
+            "A stored credential could not be decrypted; the encryption key is not the one it was encrypted with"
+                .to_string(),
+        )
+    })
+}
+
+/// Whether a notification config field holds a credential
+pub fn is_sensitive_field(key: &str) -> bool {
+    let key = key.to_lowercase();
+    key.contains("password")
+        || key.contains("secret")
+        || key.contains("token")
+        || key.contains("api_key")
+}
+
+/// Serialize a notification channel config with its credentials encrypted
+pub fn encrypt_config(config: &JsonValue) -> Result<String> {
+    let mut config = config.clone();
+    map_sensitive_fields(&mut config, encrypt)?;
+    Ok(serde_json::to_string(&config)?)
+}
+
+/// Parse a stored notification channel config with its credentials
+/// decrypted; a config that is not JSON reads as empty
+pub fn decrypt_config(stored: &str) -> Result<JsonValue> {
+    let mut config = serde_json::from_str(stored).unwrap_or(serde_json::json!({}));
+    map_sensitive_fields(&mut config, decrypt)?;
+    Ok(config)
+}
+
+fn map_sensitive_fields(config: &mut JsonValue, f: fn(&str) -> Result<String>) -> Result<()> {
+    if let Some(fields) = config.as_object_mut() {
+        for (key, value) in fields.iter_mut() {
+            if let Some(text) = value.as_str().filter(|t| !t.is_empty()) {
+                if is_sensitive_field(key) {
+                    *value = JsonValue::String(f(text)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Encrypt a value of one of [`ENCRYPTED_COLUMNS`] for storage; values of
+/// other columns are returned as they are
+pub fn conceal_column(table: &str, column: &str, value: &str) -> Result<String> {
+    if (table, column) == CONFIG_COLUMN {
+        match serde_json::from_str::<JsonValue>(value) {
+            Ok(config) => encrypt_config(&config),
+            Err(_) => Ok(value.to_string()),
+        }
+    } else if ENCRYPTED_COLUMNS.contains(&(table, column)) {
+        encrypt(value)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Decrypt a stored value of one of [`ENCRYPTED_COLUMNS`]
+pub fn reveal_column(table: &str, column: &str, value: &str) -> Result<String> {
+    if (table, column) == CONFIG_COLUMN {
+        match serde_json::from_str::<JsonValue>(value) {
+            Ok(_) => Ok(serde_json::to_string(&decrypt_config(value)?)?),
+            Err(_) => Ok(value.to_string()),
+        }
+    } else if ENCRYPTED_COLUMNS.contains(&(table, column)) {
+        decrypt(value)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Load the key from `KUBARR_ENCRYPTION_KEY` or `KUBARR_ENCRYPTION_KEY_SECRET`
+///
+/// A key that is configured but cannot be read is an error rather than a
+/// reason to store credentials in plain text.
+pub async fn load_key(k8s: Option<&K8sClient>) -> Result<()> {
+    let security = &CONFIG.security;
+    let key = if let Some(value) = &security.encryption_key {
+        Some(value.clone())
+    } else if let Some(name) = &security.encryption_key_secret {
+        let (namespace, name) = name
+            .split_once('/')
+            .unwrap_or((CONFIG.updates.namespace.as_str(), name.as_str()));
+        let k8s = k8s.ok_or_else(|| {
+            AppError::ServiceUnavailable(
+                "KUBARR_ENCRYPTION_KEY_SECRET needs the Kubernetes client".to_string(),
+            )
+        })?;
+        let secret = k8s.get_secret(namespace, name).await?;
+        let data = secret
+            .data
+            .and_then(|mut data| data.remove(SECRET_KEY_ENTRY))
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Secret {}/{} has no {} entry",
+                    namespace, name, SECRET_KEY_ENTRY
+                ))
+            })?;
+        Some(
+            String::from_utf8(data.0)
+                .map_err(|_| AppError::Internal(format!("{} is not text", SECRET_KEY_ENTRY)))?,
+        )
+    } else {
+        None
+    };
+
+    let key = key
+        .map(|value| parse_key(&value))
+        .transpose()
+        .map_err(|e| AppError::Internal(format!("The encryption key {}", e)))?;
+    set_key(key);
+    Ok(())
+}
+
+/// Encrypt credentials stored before the key was set; returns the rows changed
+pub async fn encrypt_existing(db: &DbConn) -> Result<usize> {
+    if !enabled() {
+        return Ok(0);
+    }
+    let mut changed = 0;
+
+    let providers = OauthProvider::find()
+        .filter(oauth_provider::Column::ClientSecret.is_not_null())
+        .all(db)
+        .await?;
+    for provider in providers {
+        let Some(secret) = provider.client_secret.clone().filter(|s| !is_encrypted(s)) else {
+            continue;
+        };
+        let mut active: oauth_provider::ActiveModel = provider.into();
+        active.client_secret = Set(Some(encrypt(&secret)?));
+        active.update(db).await?;
+        changed += 1;
+    }
+
+    for provider in VpnProvider::find().all(db).await? {
+        if is_encrypted(&provider.credentials_json) {
+            continue;
+        }
+        let credentials = encrypt(&provider.credentials_json)?;
+        let mut active: vpn_provider::ActiveModel = provider.into();
+        active.credentials_json = Set(credentials);
+        active.update(db).await?;
+        changed += 1;
+    }
+
+    for channel in NotificationChannel::find().all(db).await? {
+        let (table, column) = CONFIG_COLUMN;
+        let config = conceal_column(table, column, &channel.config)?;
+        if config == channel.config {
+            continue;
+        }
+        let mut active: notification_channel::ActiveModel = channel.into();
+        active.config = Set(config);
+        active.update(db).await?;
+        changed += 1;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; KEY_BYTES] = [7; KEY_BYTES];
+
+    #[test]
+    fn test_cipher_roundtrip() {
+        let cipher = Cipher::new(&KEY);
+        let encrypted = cipher.encrypt("s3cret").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("s3cret"));
+        assert_eq!(cipher.decrypt(&encrypted).as_deref(), Some("s3cret"));
+        assert_eq!(Cipher::new(&[8; KEY_BYTES]).decrypt(&encrypted), None);
+        assert_eq!(cipher.decrypt("s3cret"), None);
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let base64 = base64::engine::general_purpose::STANDARD.encode(KEY);
+        assert_eq!(parse_key(&base64), Ok(KEY));
+        assert_eq!(parse_key(&format!(" {}\n", hex::encode(KEY))), Ok(KEY));
+        assert!(parse_key("c2hvcnQ=").unwrap_err().contains("5 bytes"));
+        assert!(parse_key("not a key!").is_err());
+    }
+
+    #[test]
+    fn test_sensitive_fields() {
+        assert!(is_sensitive_field("smtp_password"));
+        assert!(is_sensitive_field("bot_token"));
+        assert!(is_sensitive_field("API_KEY"));
+        assert!(!is_sensitive_field("smtp_host"));
+    }
+}
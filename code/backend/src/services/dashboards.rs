@@ -0,0 +1,542 @@
+//! Custom monitoring dashboards
+//!
+//! A dashboard is a named list of panels, each a PromQL query with a chart
+//! type and time range. Dashboards are private to their owner unless shared;
+//! sharing (and editing shared dashboards of other users) requires
+//! `monitoring.manage`. Panel queries are restricted to the subset accepted
+//! by `victoriametrics::validate_query`, both when saved and when run.
+
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::victoriametrics::{query_vm_range, validate_query};
+use crate::error::{AppError, Result};
+use crate::models::dashboard;
+use crate::models::prelude::*;
+
+/// Supported panel chart types
+pub const CHART_TYPES: &[&str] = &["line", "area", "bar", "gauge", "stat"];
+
+/// Most panels a dashboard may hold
+pub const MAX_PANELS: usize = 24;
+
+const MAX_NAME_LENGTH: usize = 100;
+/// Shortest and longest panel time range in seconds (5 minutes to 30 days)
+const MIN_TIME_RANGE_SECS: i64 = 5 * 60;
+const MAX_TIME_RANGE_SECS: i64 = 30 * 24 * 60 * 60;
+/// Points per series returned for a panel
+const POINTS_PER_SERIES: i64 = 240;
+const MIN_STEP_SECS: i64 = 15;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+fn default_chart_type() -> String {
+    "line".to_string()
+}
+
+fn default_time_range() -> String {
+    "1h".to_string()
+}
+
+/// A single metric panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DashboardPanel {
+    pub title: String,
+    /// PromQL query over container, node, kubelet or GPU metrics
+    pub query: String,
+    /// "line", "area", "bar", "gauge" or "stat"
+    #[serde(default = "default_chart_type")]
+    pub chart_type: String,
+    /// Lookback such as "15m", "6h" or "7d" (5m to 30d)
+    #[serde(default = "default_time_range")]
+    pub time_range: String,
+    /// Display unit, e.g. "bytes" or "%"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateDashboardRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Share with all users who can view monitoring (requires monitoring.manage)
+    #[serde(default)]
+    pub shared: bool,
+    #[serde(default)]
+    pub panels: Vec<DashboardPanel>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateDashboardRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub shared: Option<bool>,
+    /// Replaces all panels when present
+    pub panels: Option<Vec<DashboardPanel>>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DashboardResponse {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub shared: bool,
+    pub owner_id: i64,
+    /// Whether the requesting user may modify or delete the dashboard
+    pub can_edit: bool,
+    pub panels: Vec<DashboardPanel>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PanelPoint {
+    pub timestamp: f64,
+    pub value: f64,
+}
+
+/// One time series of a panel, identified by its labels
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PanelSeries {
+    pub labels: BTreeMap<String, String>,
+    pub points: Vec<PanelPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PanelData {
+    pub title: String,
+    pub chart_type: String,
+    pub time_range: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Empty when the query matched nothing or VictoriaMetrics is unavailable
+    pub series: Vec<PanelSeries>,
+}
+
+/// Response for GET /api/monitoring/dashboards/{id}/data
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DashboardData {
+    pub dashboard_id: i64,
+    pub panels: Vec<PanelData>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Parse a panel time range such as "30m", "6h" or "7d" into seconds
+pub fn parse_time_range(range: &str) -> Result<i64> {
+    let invalid = || {
+        AppError::BadRequest(format!(
+            "Invalid time range '{}': use minutes, hours or days between 5m and 30d",
+            range
+        ))
+    };
+
+    let (value, unit_secs) = if let Some(v) = range.strip_suffix('m') {
+        (v, 60)
+    } else if let Some(v) = range.strip_suffix('h') {
+        (v, 60 * 60)
+    } else if let Some(v) = range.strip_suffix('d') {
+        (v, 24 * 60 * 60)
+    } else {
+        return Err(invalid());
+    };
+
+    let secs = value
+        .parse::<i64>()
+        .map_err(|_| invalid())?
+        .saturating_mul(unit_secs);
+    if !(MIN_TIME_RANGE_SECS..=MAX_TIME_RANGE_SECS).contains(&secs) {
+        return Err(invalid());
+    }
+    Ok(secs)
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Name must be between 1 and {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Validate and normalize panels
+pub fn validate_panels(panels: Vec<DashboardPanel>) -> Result<Vec<DashboardPanel>> {
+    if panels.len() > MAX_PANELS {
+        return Err(AppError::BadRequest(format!(
+            "A dashboard can hold at most {} panels",
+            MAX_PANELS
+        )));
+    }
+
+    panels
+        .into_iter()
+        .enumerate()
+        .map(|(index, panel)| {
+            let position = index + 1;
+            let title = panel.title.trim().to_string();
+            if title.is_empty() || title.chars().count() > MAX_NAME_LENGTH {
+                return Err(AppError::BadRequest(format!(
+                    "Panel {}: title must be between 1 and {} characters",
+                    position, MAX_NAME_LENGTH
+                )));
+            }
+            let chart_type = panel.chart_type.trim().to_lowercase();
+            if !CHART_TYPES.contains(&chart_type.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Panel {}: chart type must be one of {}",
+                    position,
+                    CHART_TYPES.join(", ")
+                )));
+            }
+            let time_range = panel.time_range.trim().to_string();
+            parse_time_range(&time_range).map_err(|e| match e {
+                AppError::BadRequest(msg) => {
+                    AppError::BadRequest(format!("Panel {}: {}", position, msg))
+                }
+                other => other,
+            })?;
+            let query = panel.query.trim().to_string();
+            validate_query(&query).map_err(|msg| {
+                AppError::BadRequest(format!("Panel {}: invalid query: {}", position, msg))
+            })?;
+
+            Ok(DashboardPanel {
+                title,
+                query,
+                chart_type,
+                time_range,
+                unit: panel
+                    .unit
+                    .map(|u| u.trim().to_string())
+                    .filter(|u| !u.is_empty()),
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// CRUD
+// ============================================================================
+
+fn can_edit(model: &dashboard::Model, user_id: i64, can_manage: bool) -> bool {
+    model.owner_id == user_id || (model.shared && can_manage)
+}
+
+fn to_response(model: dashboard::Model, user_id: i64, can_manage: bool) -> DashboardResponse {
+    let panels = serde_json::from_str(&model.panels_json).unwrap_or_default();
+    DashboardResponse {
+        can_edit: can_edit(&model, user_id, can_manage),
+        id: model.id,
+        name: model.name,
+        description: model.description,
+        shared: model.shared,
+        owner_id: model.owner_id,
+        panels,
+        created_at: model.created_at,
+        updated_at: model.updated_at,
+    }
+}
+
+fn panels_json(panels: &[DashboardPanel]) -> Result<String> {
+    serde_json::to_string(panels).map_err(|e| AppError::Internal(e.to_string()))
+}
+
+fn require_manage_for_sharing(can_manage: bool) -> Result<()> {
+    if !can_manage {
+        return Err(AppError::Forbidden(
+            "Permission denied: monitoring.manage required to share dashboards".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Find a dashboard visible to the user (own or shared)
+async fn find_visible(db: &DatabaseConnection, id: i64, user_id: i64) -> Result<dashboard::Model> {
+    Dashboard::find_by_id(id)
+        .one(db)
+        .await?
+        .filter(|d| d.owner_id == user_id || d.shared)
+        .ok_or_else(|| AppError::NotFound("Dashboard not found".to_string()))
+}
+
+/// Find a dashboard the user may modify
+async fn find_editable(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<dashboard::Model> {
+    let model = find_visible(db, id, user_id).await?;
+    if !can_edit(&model, user_id, can_manage) {
+        return Err(AppError::Forbidden(
+            "Only the owner can modify this dashboard".to_string(),
+        ));
+    }
+    Ok(model)
+}
+
+/// List the user's own dashboards and all shared dashboards
+pub async fn list_dashboards(
+    db: &DatabaseConnection,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<Vec<DashboardResponse>> {
+    let dashboards = Dashboard::find()
+        .filter(
+            Condition::any()
+                .add(dashboard::Column::OwnerId.eq(user_id))
+                .add(dashboard::Column::Shared.eq(true)),
+        )
+        .order_by_asc(dashboard::Column::Name)
+        .all(db)
+        .await?;
+
+    Ok(dashboards
+        .into_iter()
+        .map(|d| to_response(d, user_id, can_manage))
+        .collect())
+}
+
+pub async fn get_dashboard(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<DashboardResponse> {
+    let model = find_visible(db, id, user_id).await?;
+    Ok(to_response(model, user_id, can_manage))
+}
+
+pub async fn create_dashboard(
+    db: &DatabaseConnection,
+    request: CreateDashboardRequest,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<DashboardResponse> {
+    if request.shared {
+        require_manage_for_sharing(can_manage)?;
+    }
+    let name = validate_name(&request.name)?;
+    let panels = validate_panels(request.panels)?;
+
+    let now = Utc::now();
+    let model = dashboard::ActiveModel {
+        owner_id: Set(user_id),
+        name: Set(name),
+        description: Set(request.description.filter(|d| !d.trim().is_empty())),
+        shared: Set(request.shared),
+        panels_json: Set(panels_json(&panels)?),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(to_response(model, user_id, can_manage))
+}
+
+pub async fn update_dashboard(
+    db: &DatabaseConnection,
+    id: i64,
+    request: UpdateDashboardRequest,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<DashboardResponse> {
+    let existing = find_editable(db, id, user_id, can_manage).await?;
+    if request
+        .shared
+        .is_some_and(|shared| shared != existing.shared)
+    {
+        require_manage_for_sharing(can_manage)?;
+    }
+
+    let mut active: dashboard::ActiveModel = existing.into();
+    if let Some(name) = request.name {
+        active.name = Set(validate_name(&name)?);
+    }
+    if let Some(description) = request.description {
+        active.description = Set(Some(description).filter(|d| !d.trim().is_empty()));
+    }
+    if let Some(shared) = request.shared {
+        active.shared = Set(shared);
+    }
+    if let Some(panels) = request.panels {
+        active.panels_json = Set(panels_json(&validate_panels(panels)?)?);
+    }
+    active.updated_at = Set(Utc::now());
+
+    let model = active.update(db).await?;
+    Ok(to_response(model, user_id, can_manage))
+}
+
+pub async fn delete_dashboard(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+    can_manage: bool,
+) -> Result<()> {
+    find_editable(db, id, user_id, can_manage).await?;
+    Dashboard::delete_by_id(id).exec(db).await?;
+    Ok(())
+}
+
+// ============================================================================
+// Panel Data
+// ============================================================================
+
+/// Convert a range query result into panel series
+fn parse_series(results: Vec<serde_json::Value>) -> Vec<PanelSeries> {
+    results
+        .into_iter()
+        .map(|r| {
+            let labels = r["metric"]
+                .as_object()
+                .map(|m| {
+                    m.iter()
+                        .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let points = r["values"]
+                .as_array()
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| {
+                            Some(PanelPoint {
+                                timestamp: v[0].as_f64()?,
+                                value: v[1].as_str()?.parse().ok()?,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            PanelSeries { labels, points }
+        })
+        .collect()
+}
+
+async fn panel_data(panel: DashboardPanel, now: DateTime<Utc>) -> PanelData {
+    // Stored panels were validated on save; check again in case the
+    // whitelist has since been narrowed
+    let series = match (
+        parse_time_range(&panel.time_range),
+        validate_query(&panel.query),
+    ) {
+        (Ok(range_secs), Ok(())) => {
+            let end = now.timestamp() as f64;
+            let start = end - range_secs as f64;
+            let step = format!("{}s", (range_secs / POINTS_PER_SERIES).max(MIN_STEP_SECS));
+            parse_series(query_vm_range(&panel.query, start, end, &step).await)
+        }
+        _ => Vec::new(),
+    };
+
+    PanelData {
+        title: panel.title,
+        chart_type: panel.chart_type,
+        time_range: panel.time_range,
+        unit: panel.unit,
+        series,
+    }
+}
+
+/// Run all panel queries of a dashboard
+pub async fn dashboard_data(
+    db: &DatabaseConnection,
+    id: i64,
+    user_id: i64,
+) -> Result<DashboardData> {
+    let model = find_visible(db, id, user_id).await?;
+    let panels: Vec<DashboardPanel> = serde_json::from_str(&model.panels_json).unwrap_or_default();
+
+    let now = Utc::now();
+    Ok(DashboardData {
+        dashboard_id: model.id,
+        panels: join_all(panels.into_iter().map(|p| panel_data(p, now))).await,
+        generated_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel(query: &str) -> DashboardPanel {
+        DashboardPanel {
+            title: " CPU ".to_string(),
+            query: query.to_string(),
+            chart_type: "Line".to_string(),
+            time_range: "6h".to_string(),
+            unit: Some(" ".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_time_range() {
+        assert_eq!(parse_time_range("5m").unwrap(), 300);
+        assert_eq!(parse_time_range("6h").unwrap(), 21600);
+        assert_eq!(parse_time_range("30d").unwrap(), 2_592_000);
+        assert!(parse_time_range("1m").is_err());
+        assert!(parse_time_range("31d").is_err());
+        assert!(parse_time_range("1w").is_err());
+        assert!(parse_time_range("h").is_err());
+    }
+
+    #[test]
+    fn test_validate_panels_normalizes() {
+        let panels = validate_panels(vec![panel(
+            "sum(rate(container_cpu_usage_seconds_total[5m]))",
+        )])
+        .unwrap();
+        assert_eq!(panels[0].title, "CPU");
+        assert_eq!(panels[0].chart_type, "line");
+        assert!(panels[0].unit.is_none());
+    }
+
+    #[test]
+    fn test_validate_panels_reports_position() {
+        let err = validate_panels(vec![
+            panel("sum(container_memory_working_set_bytes)"),
+            panel("sum(up)"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("Panel 2"));
+
+        let mut bad_chart = panel("sum(container_memory_working_set_bytes)");
+        bad_chart.chart_type = "pie".to_string();
+        assert!(validate_panels(vec![bad_chart]).is_err());
+
+        let too_many = vec![panel("sum(container_memory_working_set_bytes)"); MAX_PANELS + 1];
+        assert!(validate_panels(too_many).is_err());
+    }
+
+    #[test]
+    fn test_parse_series() {
+        let results = vec![serde_json::json!({
+            "metric": {"namespace": "sonarr"},
+            "values": [[1700000000, "0.5"], [1700000060, "bad"], [1700000120, "1.5"]]
+        })];
+        let series = parse_series(results);
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].labels["namespace"], "sonarr");
+        assert_eq!(series[0].points.len(), 2);
+        assert_eq!(series[0].points[1].value, 1.5);
+    }
+}
@@ -1,18 +1,20 @@
-use std::collections::HashMap;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
-use k8s_openapi::api::apps::v1::{DaemonSet, Deployment};
-use k8s_openapi::api::core::v1::Namespace;
-use kube::api::{Api, DeleteParams, ListParams};
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 
 use crate::config::CONFIG;
 use crate::error::{AppError, Result};
-use crate::services::catalog::AppCatalog;
+use crate::services::app_routing::{self, AppRouting, RoutingMode};
+use crate::services::catalog::{AppCatalog, AppConfig};
+use crate::services::helm::{HelmEngine, HelmRelease};
+use crate::services::installed_apps;
+use crate::services::quotas;
+use crate::services::registries;
+use crate::services::tenants;
 use crate::services::vpn;
-use crate::services::K8sClient;
+use crate::services::K8sApi;
 
 /// Deployment request
 #[derive(Debug, Clone, Deserialize)]
@@ -20,6 +22,9 @@ pub struct DeploymentRequest {
     pub app_name: String,
     #[serde(default)]
     pub custom_config: HashMap<String, String>,
+    /// Install even though no node matches the app's architectures
+    #[serde(default)]
+    pub allow_incompatible_arch: bool,
 }
 
 /// Deployment status response
@@ -34,89 +39,287 @@ pub struct DeploymentStatus {
 
 /// Deployment manager for applications
 pub struct DeploymentManager<'a> {
-    k8s: &'a K8sClient,
+    k8s: &'a dyn K8sApi,
+    helm: &'a dyn HelmEngine,
     catalog: &'a AppCatalog,
     db: Option<&'a DatabaseConnection>,
+    quota_user: Option<i64>,
 }
 
 impl<'a> DeploymentManager<'a> {
-    pub fn new(k8s: &'a K8sClient, catalog: &'a AppCatalog) -> Self {
+    pub fn new(k8s: &'a dyn K8sApi, helm: &'a dyn HelmEngine, catalog: &'a AppCatalog) -> Self {
         Self {
             k8s,
+            helm,
             catalog,
             db: None,
+            quota_user: None,
         }
     }
 
     /// Create with database connection for VPN support
     pub fn with_db(
-        k8s: &'a K8sClient,
+        k8s: &'a dyn K8sApi,
+        helm: &'a dyn HelmEngine,
         catalog: &'a AppCatalog,
         db: &'a DatabaseConnection,
     ) -> Self {
         Self {
             k8s,
+            helm,
             catalog,
             db: Some(db),
+            quota_user: None,
         }
     }
 
+    /// Install on behalf of a user: installs are checked against their
+    /// quota and counted towards it
+    pub fn for_user(mut self, user_id: i64) -> Self {
+        self.quota_user = Some(user_id);
+        self
+    }
+
     /// Get the OCI chart reference for an app
     fn get_chart_ref(&self, app_name: &str) -> String {
         format!("{}/{}", CONFIG.charts.registry, app_name)
     }
 
-    /// Run a Helm command
-    fn run_helm_command(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("helm")
-            .args(args)
-            .output()
-            .map_err(|e| AppError::Internal(format!("Failed to run helm: {}", e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AppError::Internal(format!(
-                "Helm command failed: {}",
-                stderr
-            )));
-        }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    }
-
-    /// Deploy an application using Helm
+    /// Deploy a catalog application using Helm
+    ///
+    /// `request.app_name` must be an exact catalog name; cloned instances are
+    /// installed with [`Self::deploy_clone`].
     pub async fn deploy_app(
         &self,
         request: &DeploymentRequest,
         storage_path: Option<&str>,
     ) -> Result<DeploymentStatus> {
-        // Get app config from catalog
         let app_config = self.catalog.get_app(&request.app_name).ok_or_else(|| {
             AppError::NotFound(format!("App '{}' not found in catalog", request.app_name))
         })?;
+        let routing = self.saved_routing(&request.app_name).await?;
+        self.deploy_instance(
+            app_config,
+            request,
+            storage_path,
+            &routing,
+            &app_config.version,
+        )
+        .await
+    }
+
+    /// Deploy `request.app_name` as a cloned instance of `catalog_app` (e.g.
+    /// `sonarr-4k`), with its own release, namespace, ingress path and
+    /// storage directory
+    pub async fn deploy_clone(
+        &self,
+        catalog_app: &str,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+    ) -> Result<DeploymentStatus> {
+        let app_config = self.catalog.get_app(catalog_app).ok_or_else(|| {
+            AppError::NotFound(format!("App '{}' not found in catalog", catalog_app))
+        })?;
+        let routing = self.saved_routing(&request.app_name).await?;
+        self.deploy_instance(
+            app_config,
+            request,
+            storage_path,
+            &routing,
+            &app_config.version,
+        )
+        .await
+    }
+
+    /// Redeploy an installed app or recorded clone, e.g. after its VPN
+    /// changed
+    ///
+    /// `routing` replaces the saved routing, so a routing change can be
+    /// applied before it is stored. The app keeps its installed chart
+    /// version; see [`Self::upgrade_app`] to move it to the catalog's.
+    pub async fn redeploy_app(
+        &self,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+        routing: Option<&AppRouting>,
+    ) -> Result<DeploymentStatus> {
+        let app_config = self.installed_app_config(&request.app_name).await?;
+        let chart_version = match self.db {
+            Some(db) => installed_apps::chart_version(db, &request.app_name).await?,
+            None => None,
+        }
+        .unwrap_or_else(|| app_config.version.clone());
+        self.redeploy_instance(app_config, request, storage_path, routing, &chart_version)
+            .await
+    }
 
-        let chart_ref = self.get_chart_ref(&request.app_name);
+    /// Redeploy an installed app or recorded clone with the chart version the
+    /// catalog currently offers
+    pub async fn upgrade_app(
+        &self,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+    ) -> Result<DeploymentStatus> {
+        let app_config = self.installed_app_config(&request.app_name).await?;
+        self.redeploy_instance(app_config, request, storage_path, None, &app_config.version)
+            .await
+    }
+
+    /// Replace the Helm value overrides of an installed app or recorded clone
+    /// and roll them out
+    ///
+    /// The app keeps its installed chart version. The previous overrides are
+    /// restored if helm rejects the new ones.
+    pub async fn update_values(
+        &self,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+        overrides: &serde_json::Value,
+    ) -> Result<DeploymentStatus> {
+        let db = self
+            .db
+            .ok_or_else(|| AppError::Internal("Value overrides require a database".to_string()))?;
+        let previous = installed_apps::value_overrides(db, &request.app_name).await?;
+        installed_apps::set_value_overrides(db, &request.app_name, overrides).await?;
+        match self.redeploy_app(request, storage_path, None).await {
+            Ok(status) => Ok(status),
+            Err(e) => {
+                installed_apps::set_value_overrides(db, &request.app_name, &previous).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Catalog entry of an installed app, or of the catalog app a recorded
+    /// clone was installed from
+    async fn installed_app_config(&self, app_name: &str) -> Result<&'a AppConfig> {
+        let catalog_app = match self.db {
+            Some(db) => installed_apps::clone_source(db, app_name).await?,
+            None => None,
+        }
+        .unwrap_or_else(|| app_name.to_string());
+        self.catalog
+            .get_app(&catalog_app)
+            .ok_or_else(|| AppError::NotFound(format!("App '{}' not found in catalog", app_name)))
+    }
+
+    async fn redeploy_instance(
+        &self,
+        app_config: &AppConfig,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+        routing: Option<&AppRouting>,
+        chart_version: &str,
+    ) -> Result<DeploymentStatus> {
+        let routing = match routing {
+            Some(routing) => routing.clone(),
+            None => self.saved_routing(&request.app_name).await?,
+        };
+        // The architecture check already ran when the app was installed
+        let request = DeploymentRequest {
+            allow_incompatible_arch: true,
+            ..request.clone()
+        };
+        self.deploy_instance(app_config, &request, storage_path, &routing, chart_version)
+            .await
+    }
+
+    /// Refuse an app no node can run unless the request overrides it, and
+    /// return the architecture to pin its pods to on a mixed cluster
+    ///
+    /// Node architectures that cannot be read are treated as unknown, so a
+    /// cluster that hides its nodes from Kubarr installs as before.
+    async fn check_architecture(
+        &self,
+        app_config: &AppConfig,
+        request: &DeploymentRequest,
+    ) -> Result<Option<String>> {
+        if app_config.architectures.is_empty() {
+            return Ok(None);
+        }
+        let node_architectures = self.k8s.node_architectures().await.unwrap_or_else(|e| {
+            tracing::debug!("Failed to read node architectures: {}", e);
+            Vec::new()
+        });
+        let compatibility = app_config.arch_compatibility(&node_architectures);
+        if compatibility.compatible {
+            return Ok(compatibility.node_arch);
+        }
+        let warning = compatibility.warning.unwrap_or_default();
+        if !request.allow_incompatible_arch {
+            return Err(AppError::BadRequest(format!(
+                "{}. Set allow_incompatible_arch to install it anyway",
+                warning
+            )));
+        }
+        tracing::warn!(
+            "Installing '{}' despite its architectures: {}",
+            request.app_name,
+            warning
+        );
+        Ok(None)
+    }
+
+    /// Saved routing of an app, or path routing without a database
+    async fn saved_routing(&self, app_name: &str) -> Result<AppRouting> {
+        match self.db {
+            Some(db) => app_routing::get_routing(db, app_name).await,
+            None => Ok(AppRouting::new(app_name, RoutingMode::Path, None, None)),
+        }
+    }
+
+    /// Install or upgrade the `request.app_name` release from `app_config`'s
+    /// chart, pinned to `chart_version` unless it is empty
+    async fn deploy_instance(
+        &self,
+        app_config: &AppConfig,
+        request: &DeploymentRequest,
+        storage_path: Option<&str>,
+        routing: &AppRouting,
+        chart_version: &str,
+    ) -> Result<DeploymentStatus> {
         let namespace = &request.app_name;
+        let is_clone = app_config.name != request.app_name;
+
+        let quota = match (self.db, self.quota_user) {
+            (Some(db), Some(user_id)) => {
+                let footprint = quotas::footprint(app_config, request)?;
+                quotas::check(db, user_id, &request.app_name, &footprint).await?;
+                Some((db, user_id, footprint))
+            }
+            _ => None,
+        };
 
-        // Build helm upgrade --install command
-        let mut helm_args = vec![
-            "upgrade",
-            "--install",
-            &request.app_name,
-            &chart_ref,
-            "-n",
-            namespace,
-            "--create-namespace",
-        ];
+        let node_arch = self.check_architecture(app_config, request).await?;
 
         // Collect --set arguments
         let mut set_args: Vec<String> = Vec::new();
         let mut set_string_args: Vec<String> = Vec::new();
 
+        // Keep the pods of an app built for some architectures of a mixed
+        // cluster on the nodes that can run them
+        if let Some(arch) = node_arch {
+            set_string_args.push(format!("nodeSelector.kubernetes\\.io/arch={}", arch));
+        }
+
         // Add storage configuration
         if let Some(path) = storage_path {
             set_args.push("storage.hostPath.enabled=true".to_string());
             set_args.push(format!("storage.hostPath.rootPath={}", path));
+            if is_clone {
+                set_args.push(format!("storage.hostPath.subPath={}", request.app_name));
+            }
+        }
+
+        // Keep a clone's resources apart from the original instance
+        if is_clone {
+            set_args.push(format!("fullnameOverride={}", request.app_name));
+        }
+
+        // Charts default to `/{app_name}/` path routing; only deviations need
+        // ingress overrides
+        if is_clone || routing.mode == RoutingMode::Subdomain {
+            set_args.extend(routing.helm_values());
         }
 
         // Check for VPN configuration
@@ -156,26 +359,82 @@ impl<'a> DeploymentManager<'a> {
             }
         }
 
+        // Pull the image through its registry mirror, with credentials
+        if let Some(db) = self.db {
+            match registries::deployment_values(db, self.k8s, app_config, namespace).await {
+                Ok(values) => set_args.extend(values),
+                Err(e) => tracing::warn!(
+                    "Failed to apply registry settings to app {}: {}",
+                    request.app_name,
+                    e
+                ),
+            }
+        }
+
         // Add custom config
         for (key, value) in &request.custom_config {
             set_args.push(format!("{}={}", key, value));
         }
 
-        // Add --set arguments
-        for arg in &set_args {
-            helm_args.push("--set");
-            helm_args.push(arg);
+        // Values the user set on top of the chart's defaults
+        let mut values = serde_json::Value::Null;
+        if let Some(db) = self.db {
+            match installed_apps::value_overrides(db, &request.app_name).await {
+                Ok(overrides) if overrides.as_object().is_some_and(|o| !o.is_empty()) => {
+                    values = overrides;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    "Failed to read value overrides of app {}: {}",
+                    request.app_name,
+                    e
+                ),
+            }
         }
 
-        // Add --set-string arguments (for values containing special chars like commas/slashes)
-        for arg in &set_string_args {
-            helm_args.push("--set-string");
-            helm_args.push(arg);
+        let release = HelmRelease {
+            name: request.app_name.clone(),
+            chart: self.get_chart_ref(&app_config.name),
+            namespace: namespace.to_string(),
+            create_namespace: true,
+            set: set_args,
+            set_string: set_string_args,
+            version: (!chart_version.is_empty()).then(|| chart_version.to_string()),
+            values,
+            ..Default::default()
+        };
+        self.helm.upgrade_install(&release).await?;
+
+        if let (Some(db), Some(version)) = (self.db, &release.version) {
+            if let Err(e) = installed_apps::set_chart_version(db, &request.app_name, version).await
+            {
+                tracing::warn!(
+                    "Failed to record chart version of '{}': {}",
+                    request.app_name,
+                    e
+                );
+            }
         }
 
-        // Run helm command
-        let args_str: Vec<&str> = helm_args.iter().map(|s| s.as_ref()).collect();
-        self.run_helm_command(&args_str)?;
+        if let Some((db, user_id, footprint)) = quota {
+            let cloned_from = is_clone.then_some(app_config.name.as_str());
+            if let Err(e) =
+                quotas::record(db, &request.app_name, cloned_from, user_id, &footprint).await
+            {
+                tracing::warn!(
+                    "Failed to record quota usage of '{}': {}",
+                    request.app_name,
+                    e
+                );
+            }
+            if let Err(e) = tenants::claim_installed_app(db, &request.app_name, user_id).await {
+                tracing::warn!(
+                    "Failed to assign '{}' to its installer's tenant: {}",
+                    request.app_name,
+                    e
+                );
+            }
+        }
 
         Ok(DeploymentStatus {
             app_name: request.app_name.clone(),
@@ -191,45 +450,128 @@ impl<'a> DeploymentManager<'a> {
         let namespace = app_name;
 
         // Try to uninstall with Helm
-        let _ = self.run_helm_command(&["uninstall", app_name, "-n", namespace]);
+        let _ = self.helm.uninstall(app_name, namespace).await;
 
         // Delete the namespace
-        let namespaces: Api<Namespace> = Api::all(self.k8s.client().clone());
+        self.k8s.delete_namespace(namespace).await?;
+        Ok(true)
+    }
+
+    /// Stop an app by scaling its deployments to zero
+    ///
+    /// Replica counts are saved to the database, when one is set, so
+    /// [`Self::start_app`] can restore them. Returns the number of
+    /// deployments scaled down.
+    pub async fn stop_app(&self, app_name: &str) -> Result<usize> {
+        if !self.check_namespace_exists(app_name).await {
+            return Err(AppError::NotFound(format!(
+                "App '{}' is not installed",
+                app_name
+            )));
+        }
+        let deployments = self.k8s.list_deployments(app_name).await?;
+        if deployments.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "App '{}' has no deployments to stop",
+                app_name
+            )));
+        }
 
-        match namespaces.delete(namespace, &DeleteParams::default()).await {
-            Ok(_) => Ok(true),
-            Err(kube::Error::Api(ae)) if ae.code == 404 => Ok(true),
-            Err(e) => Err(AppError::Internal(format!(
-                "Failed to delete namespace: {}",
-                e
-            ))),
+        let mut saved = match self.db {
+            Some(db) => installed_apps::stopped_replicas(db, app_name).await?,
+            None => HashMap::new(),
+        };
+        let running: Vec<(String, i32)> = deployments
+            .into_iter()
+            .filter_map(|d| {
+                let replicas = d.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+                Some((d.metadata.name?, replicas))
+            })
+            .filter(|(_, replicas)| *replicas > 0)
+            .collect();
+        saved.extend(running.iter().cloned());
+        // Save before scaling so a partial stop can still be undone
+        if let Some(db) = self.db {
+            installed_apps::set_stopped_replicas(db, app_name, Some(&saved)).await?;
         }
+
+        for (name, _) in &running {
+            self.k8s.scale_deployment(app_name, name, 0).await?;
+        }
+        Ok(running.len())
     }
 
-    /// Get list of deployed app names (excludes hidden/system apps like kubarr itself)
-    pub async fn get_deployed_apps(&self) -> Vec<String> {
-        let namespaces: Api<Namespace> = Api::all(self.k8s.client().clone());
+    /// Start a stopped app, restoring the replica counts saved by
+    /// [`Self::stop_app`]
+    ///
+    /// Deployments without a saved count get one replica. Returns the number
+    /// of deployments scaled up.
+    pub async fn start_app(&self, app_name: &str) -> Result<usize> {
+        if !self.check_namespace_exists(app_name).await {
+            return Err(AppError::NotFound(format!(
+                "App '{}' is not installed",
+                app_name
+            )));
+        }
+        let saved = match self.db {
+            Some(db) => installed_apps::stopped_replicas(db, app_name).await?,
+            None => HashMap::new(),
+        };
+
+        let mut started = 0;
+        for deployment in self.k8s.list_deployments(app_name).await? {
+            let replicas = deployment.spec.as_ref().and_then(|s| s.replicas);
+            let Some(name) = deployment.metadata.name else {
+                continue;
+            };
+            if replicas == Some(0) {
+                let target = saved.get(&name).copied().unwrap_or(1);
+                self.k8s.scale_deployment(app_name, &name, target).await?;
+                started += 1;
+            }
+        }
+
+        if let Some(db) = self.db {
+            installed_apps::set_stopped_replicas(db, app_name, None).await?;
+        }
+        Ok(started)
+    }
 
-        // Get all catalog app names (excluding hidden apps)
-        let catalog_apps: std::collections::HashSet<_> = self
+    /// Get list of deployed app names, including cloned instances recorded
+    /// in the database (excludes hidden/system apps like kubarr itself)
+    pub async fn get_deployed_apps(&self) -> Vec<String> {
+        let mut candidates: HashSet<String> = self
             .catalog
             .get_all_apps()
-            .iter()
+            .into_iter()
             .filter(|app| !app.is_hidden)
             .map(|app| app.name.clone())
             .collect();
+        if let Some(db) = self.db {
+            if let Ok(clones) = installed_apps::list_clones(db).await {
+                let clones: Vec<String> = clones
+                    .into_iter()
+                    .filter(|clone| {
+                        clone
+                            .cloned_from
+                            .as_deref()
+                            .is_some_and(|source| candidates.contains(source))
+                    })
+                    .map(|clone| clone.app_name)
+                    .collect();
+                candidates.extend(clones);
+            }
+        }
 
         let mut deployed_apps = Vec::new();
 
-        if let Ok(ns_list) = namespaces.list(&ListParams::default()).await {
-            for ns in ns_list {
-                if let Some(name) = ns.metadata.name {
-                    if catalog_apps.contains(&name) {
-                        // Check if there are deployments in this namespace
-                        if let Ok(health) = self.check_namespace_health(&name).await {
-                            if health.get("deployments").is_some() {
-                                deployed_apps.push(name);
-                            }
+        if let Ok(ns_list) = self.k8s.list_namespaces().await {
+            for name in ns_list {
+                if candidates.contains(&name) {
+                    // Check if there are deployments in this namespace
+                    if let Ok(health) = self.check_namespace_health(&name).await {
+                        if health.get("deployments").is_some() {
+                            deployed_apps.push(name);
                         }
                     }
                 }
@@ -241,16 +583,13 @@ impl<'a> DeploymentManager<'a> {
 
     /// Check if a namespace exists
     pub async fn check_namespace_exists(&self, namespace: &str) -> bool {
-        let namespaces: Api<Namespace> = Api::all(self.k8s.client().clone());
-        namespaces.get(namespace).await.is_ok()
+        self.k8s.namespace_exists(namespace).await.unwrap_or(false)
     }
 
     /// Check if all deployments in a namespace are healthy
     pub async fn check_namespace_health(&self, namespace: &str) -> Result<serde_json::Value> {
-        let namespaces: Api<Namespace> = Api::all(self.k8s.client().clone());
-
         // Check if namespace exists
-        if namespaces.get(namespace).await.is_err() {
+        if !self.check_namespace_exists(namespace).await {
             return Ok(serde_json::json!({
                 "status": "not_found",
                 "healthy": false,
@@ -258,15 +597,11 @@ impl<'a> DeploymentManager<'a> {
             }));
         }
 
-        // Get deployments
-        let deployments: Api<Deployment> = Api::namespaced(self.k8s.client().clone(), namespace);
-        let deploy_list = deployments.list(&ListParams::default()).await?;
-
-        // Get daemonsets
-        let daemonsets: Api<DaemonSet> = Api::namespaced(self.k8s.client().clone(), namespace);
-        let ds_list = daemonsets.list(&ListParams::default()).await?;
+        // Get deployments and daemonsets
+        let deploy_list = self.k8s.list_deployments(namespace).await?;
+        let ds_list = self.k8s.list_daemonsets(namespace).await?;
 
-        if deploy_list.items.is_empty() && ds_list.items.is_empty() {
+        if deploy_list.is_empty() && ds_list.is_empty() {
             return Ok(serde_json::json!({
                 "status": "no_workloads",
                 "healthy": false,
@@ -278,7 +613,7 @@ impl<'a> DeploymentManager<'a> {
         let mut workload_statuses = Vec::new();
 
         // Check deployments
-        for deploy in &deploy_list.items {
+        for deploy in &deploy_list {
             let name = deploy.metadata.name.clone().unwrap_or_default();
             let spec = deploy.spec.as_ref();
             let status = deploy.status.as_ref();
@@ -304,7 +639,7 @@ impl<'a> DeploymentManager<'a> {
         }
 
         // Check daemonsets
-        for ds in &ds_list.items {
+        for ds in &ds_list {
             let name = ds.metadata.name.clone().unwrap_or_default();
             let status = ds.status.as_ref();
 
@@ -0,0 +1,338 @@
+//! Deprovisioning
+//!
+//! When a user is deactivated or deleted, Kubarr cleans up after them: it
+//! revokes their sessions and pending 2FA challenges, withdraws the unused
+//! invites they created and removes their share password, so nothing they
+//! held keeps working. Their linked app accounts are handled as their roles'
+//! provisioning profiles say (see [`super::provisioning`]): kept, disabled
+//! or deleted. An app in several of the user's profiles gets the least
+//! destructive of their actions. Accounts in apps outside the profiles are
+//! always kept.
+//!
+//! Every run produces a [`DeprovisioningReport`], which is recorded in the
+//! audit log with the deactivation or deletion.
+
+use std::collections::BTreeMap;
+
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::{
+    invite, pending_2fa_challenge, provisioning_profile, session, user, user_role,
+};
+use crate::services::account_links;
+use crate::services::integrations::requests::seerr_kind;
+use crate::services::integrations::seerr::SeerrAdapter;
+use crate::services::integrations::{build_context, jellyfin};
+use crate::services::provisioning::{ordered_apps, OffboardAction, ProvisioningProfile};
+use crate::services::sessions::revoke_user_sessions;
+use crate::services::shares;
+use crate::state::{AppState, DbConn};
+
+/// Outcome of deprovisioning one app account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeprovisionStatus {
+    Disabled,
+    Deleted,
+    /// The profile keeps the account
+    Kept,
+    /// Dry run: the account would be disabled
+    WouldDisable,
+    /// Dry run: the account would be deleted
+    WouldDelete,
+    /// The user has no linked account in the app
+    NoAccount,
+    Failed,
+}
+
+/// Result of deprovisioning one app account
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeprovisionResult {
+    pub app_name: String,
+    pub action: OffboardAction,
+    pub status: DeprovisionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What deprovisioning a user did, or would do in a dry run
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DeprovisioningReport {
+    pub user_id: i64,
+    pub username: String,
+    pub dry_run: bool,
+    pub sessions_revoked: u64,
+    /// Pending 2FA challenges removed
+    pub challenges_revoked: u64,
+    /// Unused invites the user created that were deleted
+    pub invites_revoked: u64,
+    /// Whether the user's share password was removed
+    pub share_access_removed: bool,
+    pub accounts: Vec<DeprovisionResult>,
+}
+
+/// The action for each app in the profiles of any of the user's roles, in
+/// provisioning order
+pub async fn actions_for_user(db: &DbConn, user_id: i64) -> Result<Vec<(String, OffboardAction)>> {
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.role_id)
+        .collect();
+    let profiles: Vec<ProvisioningProfile> = provisioning_profile::Entity::find()
+        .filter(provisioning_profile::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await?
+        .iter()
+        .map(Into::into)
+        .collect();
+    Ok(merge_actions(&profiles))
+}
+
+/// Combine profiles into one action per app, the least destructive wins
+pub fn merge_actions(profiles: &[ProvisioningProfile]) -> Vec<(String, OffboardAction)> {
+    let mut actions: BTreeMap<String, OffboardAction> = BTreeMap::new();
+    for profile in profiles {
+        for app in &profile.apps {
+            actions
+                .entry(app.clone())
+                .and_modify(|a| *a = (*a).min(profile.on_deactivate))
+                .or_insert(profile.on_deactivate);
+        }
+    }
+    let apps: Vec<String> = actions.keys().cloned().collect();
+    ordered_apps(&apps)
+        .into_iter()
+        .map(|app| {
+            let action = actions[&app];
+            (app, action)
+        })
+        .collect()
+}
+
+/// Revoke everything a user holds in Kubarr and handle their app accounts
+///
+/// Call this before deleting a user, since deleting removes their roles and
+/// account links.
+pub async fn deprovision_user(
+    state: &AppState,
+    user_id: i64,
+    dry_run: bool,
+) -> Result<DeprovisioningReport> {
+    let db = state.get_db().await?;
+    let member = User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let mut accounts = Vec::new();
+    for (app_name, action) in actions_for_user(&db, user_id).await? {
+        let result = match deprovision_app(state, user_id, &app_name, action, dry_run).await {
+            Ok(result) => result,
+            Err(e) => DeprovisionResult {
+                app_name,
+                action,
+                status: DeprovisionStatus::Failed,
+                external_id: None,
+                error: Some(e.to_string()),
+            },
+        };
+        accounts.push(result);
+    }
+
+    let challenges =
+        Pending2faChallenge::find().filter(pending_2fa_challenge::Column::UserId.eq(user_id));
+    let invites = Invite::find()
+        .filter(invite::Column::CreatedById.eq(user_id))
+        .filter(invite::Column::IsUsed.eq(false));
+    let has_share_password = shares::has_password(&db, user_id).await?;
+    let (sessions_revoked, challenges_revoked, invites_revoked) = if dry_run {
+        (
+            Session::find()
+                .filter(session::Column::UserId.eq(user_id))
+                .filter(session::Column::IsRevoked.eq(false))
+                .count(&db)
+                .await?,
+            challenges.count(&db).await?,
+            invites.count(&db).await?,
+        )
+    } else {
+        let revoked = revoke_user_sessions(&db, user_id, None).await?;
+        let challenges = Pending2faChallenge::delete_many()
+            .filter(pending_2fa_challenge::Column::UserId.eq(user_id))
+            .exec(&db)
+            .await?
+            .rows_affected;
+        let invites = Invite::delete_many()
+            .filter(invite::Column::CreatedById.eq(user_id))
+            .filter(invite::Column::IsUsed.eq(false))
+            .exec(&db)
+            .await?
+            .rows_affected;
+        if has_share_password {
+            shares::clear_password(&db, user_id).await?;
+        }
+        (revoked, challenges, invites)
+    };
+
+    Ok(DeprovisioningReport {
+        user_id,
+        username: member.username,
+        dry_run,
+        sessions_revoked,
+        challenges_revoked,
+        invites_revoked,
+        share_access_removed: has_share_password,
+        accounts,
+    })
+}
+
+async fn deprovision_app(
+    state: &AppState,
+    user_id: i64,
+    app_name: &str,
+    action: OffboardAction,
+    dry_run: bool,
+) -> Result<DeprovisionResult> {
+    let db = state.get_db().await?;
+    let link = account_links::find_link(&db, user_id, app_name).await?;
+    let result = |status: DeprovisionStatus| DeprovisionResult {
+        app_name: app_name.to_string(),
+        action,
+        status,
+        external_id: link.as_ref().map(|l| l.external_id.clone()),
+        error: None,
+    };
+    let Some(account) = link.as_ref() else {
+        return Ok(result(DeprovisionStatus::NoAccount));
+    };
+    let status = match (action, dry_run) {
+        (OffboardAction::Keep, _) => return Ok(result(DeprovisionStatus::Kept)),
+        (OffboardAction::Disable, true) => return Ok(result(DeprovisionStatus::WouldDisable)),
+        (OffboardAction::Delete, true) => return Ok(result(DeprovisionStatus::WouldDelete)),
+        (OffboardAction::Disable, false) => DeprovisionStatus::Disabled,
+        (OffboardAction::Delete, false) => DeprovisionStatus::Deleted,
+    };
+
+    let ctx = build_context(state, app_name).await?;
+    let external_id = account.external_id.as_str();
+    match seerr_kind(app_name) {
+        Some(kind) => {
+            let adapter = SeerrAdapter::new(kind);
+            let id = external_id
+                .parse::<i64>()
+                .map_err(|_| AppError::BadRequest(format!("Invalid {} user ID", app_name)))?;
+            match action {
+                OffboardAction::Delete => adapter.delete_user(&ctx, id).await?,
+                _ => adapter.clear_permissions(&ctx, id).await?,
+            }
+        }
+        None => match action {
+            OffboardAction::Delete => jellyfin::delete_user(&ctx, external_id).await?,
+            _ => {
+                let mut policy = jellyfin::fetch_users(&ctx)
+                    .await?
+                    .into_iter()
+                    .find(|u| u.id == external_id)
+                    .ok_or_else(|| {
+                        AppError::NotFound(format!("Jellyfin user {} not found", external_id))
+                    })?
+                    .policy;
+                policy.insert("IsDisabled".to_string(), serde_json::Value::Bool(true));
+                jellyfin::update_policy(&ctx, external_id, &policy).await?;
+            }
+        },
+    }
+    if action == OffboardAction::Delete {
+        account_links::remove_link(&db, user_id, app_name).await?;
+    }
+    Ok(result(status))
+}
+
+/// Record a deprovisioning run in the audit log
+pub async fn audit(
+    state: &AppState,
+    action: AuditAction,
+    report: &DeprovisioningReport,
+    actor: &user::Model,
+) {
+    let _ = state
+        .audit
+        .log_success(
+            action,
+            ResourceType::User,
+            Some(report.user_id.to_string()),
+            Some(actor.id),
+            Some(actor.username.clone()),
+            Some(serde_json::json!({
+                "target_username": report.username,
+                "deprovisioning": report,
+            })),
+            None,
+            None,
+        )
+        .await;
+}
+
+/// Deprovision a user who was just deactivated, in the background, and
+/// audit the deactivation with the report
+pub fn spawn_deactivation(state: &AppState, user_id: i64, actor: user::Model) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        match deprovision_user(&state, user_id, false).await {
+            Ok(report) => {
+                for r in report
+                    .accounts
+                    .iter()
+                    .filter(|r| r.status == DeprovisionStatus::Failed)
+                {
+                    tracing::warn!(
+                        "Failed to deprovision {} account of {}: {}",
+                        r.app_name,
+                        report.username,
+                        r.error.as_deref().unwrap_or_default()
+                    );
+                }
+                audit(&state, AuditAction::UserDeactivated, &report, &actor).await;
+            }
+            Err(e) => tracing::warn!("Failed to deprovision user {}: {}", user_id, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(apps: &[&str], on_deactivate: OffboardAction) -> ProvisioningProfile {
+        ProvisioningProfile {
+            apps: apps.iter().map(|a| a.to_string()).collect(),
+            on_deactivate,
+        }
+    }
+
+    #[test]
+    fn test_merge_actions_least_destructive_wins() {
+        let actions = merge_actions(&[
+            profile(&["jellyseerr", "jellyfin"], OffboardAction::Delete),
+            profile(&["jellyfin"], OffboardAction::Disable),
+        ]);
+        assert_eq!(
+            actions,
+            vec![
+                ("jellyfin".to_string(), OffboardAction::Disable),
+                ("jellyseerr".to_string(), OffboardAction::Delete),
+            ]
+        );
+        assert!(merge_actions(&[]).is_empty());
+    }
+}
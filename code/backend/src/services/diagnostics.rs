@@ -0,0 +1,603 @@
+//! Crash reporting and diagnostic bundles
+//!
+//! [`install_panic_hook`] records every backend panic in [`ERRORS`] and, as
+//! release builds abort on panic, also writes it to a crash report file that
+//! the next start picks up. The `track_server_errors` middleware feeds 5xx
+//! responses into the same aggregator, which flags a spike once
+//! [`SPIKE_THRESHOLD`] of them land within [`SPIKE_WINDOW_SECS`].
+//!
+//! [`build_bundle`] assembles a `.tar.gz` for attaching to GitHub issues:
+//! version info, the configuration with secrets redacted, recent logs,
+//! migration status, a health report and the recorded errors.
+
+use std::collections::VecDeque;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use sea_orm_migration::{MigrationStatus, MigratorTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::migrations::Migrator;
+use crate::services::log_buffer::LOG_BUFFER;
+use crate::state::AppState;
+
+/// Most panics and 5xx responses kept in memory
+const MAX_RECORDS: usize = 100;
+
+/// Most spikes kept in memory
+const MAX_SPIKES: usize = 20;
+
+/// 5xx responses within [`SPIKE_WINDOW_SECS`] that count as a spike
+pub const SPIKE_THRESHOLD: usize = 20;
+
+/// Sliding window for spike detection
+pub const SPIKE_WINDOW_SECS: i64 = 60;
+
+/// Crash report written by the panic hook
+const CRASH_REPORT_FILE: &str = "kubarr-crash.json";
+
+/// Replaces secret values in the bundle
+const REDACTED: &str = "[redacted]";
+
+/// Keys whose values are masked in log lines
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "passphrase",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "cookie",
+];
+
+/// The process-wide error aggregator
+pub static ERRORS: Lazy<ErrorAggregator> = Lazy::new(ErrorAggregator::new);
+
+/// A backend panic
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PanicRecord {
+    pub timestamp: DateTime<Utc>,
+    pub thread: String,
+    /// `file:line:column` of the panic, if known
+    pub location: Option<String>,
+    pub message: String,
+    /// Recorded by a previous run of the process
+    #[serde(default)]
+    pub previous_run: bool,
+}
+
+/// A response with a 5xx status
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ServerErrorRecord {
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub request_id: Option<String>,
+}
+
+/// A burst of 5xx responses
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ErrorSpike {
+    pub started_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// 5xx responses counted while the spike lasted
+    pub count: usize,
+}
+
+/// Everything the aggregator has recorded
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ErrorReport {
+    pub panics: Vec<PanicRecord>,
+    pub server_errors: Vec<ServerErrorRecord>,
+    pub spikes: Vec<ErrorSpike>,
+    /// 5xx responses within the current spike window
+    pub server_errors_in_window: usize,
+}
+
+#[derive(Default)]
+struct Records {
+    panics: VecDeque<PanicRecord>,
+    server_errors: VecDeque<ServerErrorRecord>,
+    spikes: VecDeque<ErrorSpike>,
+}
+
+/// Collects panics and 5xx responses
+pub struct ErrorAggregator {
+    records: Mutex<Records>,
+}
+
+impl Default for ErrorAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorAggregator {
+    pub fn new() -> Self {
+        Self {
+            records: Mutex::new(Records::default()),
+        }
+    }
+
+    pub fn record_panic(&self, panic: PanicRecord) {
+        let Ok(mut records) = self.records.lock() else {
+            return;
+        };
+        push_bounded(&mut records.panics, panic, MAX_RECORDS);
+    }
+
+    /// Record a 5xx response, returning true when it starts a spike
+    pub fn record_server_error(&self, error: ServerErrorRecord) -> bool {
+        let Ok(mut records) = self.records.lock() else {
+            return false;
+        };
+        let now = error.timestamp;
+        push_bounded(&mut records.server_errors, error, MAX_RECORDS);
+
+        let in_window = count_in_window(&records.server_errors, now);
+        // A spike lasts until the window has been quiet below the threshold
+        if let Some(spike) = records
+            .spikes
+            .back_mut()
+            .filter(|s| now - s.last_seen <= Duration::seconds(SPIKE_WINDOW_SECS))
+        {
+            spike.last_seen = now;
+            spike.count += 1;
+            return false;
+        }
+        if in_window >= SPIKE_THRESHOLD {
+            let since = now - Duration::seconds(SPIKE_WINDOW_SECS);
+            let started_at = records
+                .server_errors
+                .iter()
+                .find(|e| e.timestamp > since)
+                .map_or(now, |e| e.timestamp);
+            let spike = ErrorSpike {
+                started_at,
+                last_seen: now,
+                count: in_window,
+            };
+            push_bounded(&mut records.spikes, spike, MAX_SPIKES);
+            return true;
+        }
+        false
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        let Ok(records) = self.records.lock() else {
+            return ErrorReport {
+                panics: Vec::new(),
+                server_errors: Vec::new(),
+                spikes: Vec::new(),
+                server_errors_in_window: 0,
+            };
+        };
+        ErrorReport {
+            panics: records.panics.iter().cloned().collect(),
+            server_errors: records.server_errors.iter().cloned().collect(),
+            spikes: records.spikes.iter().cloned().collect(),
+            server_errors_in_window: count_in_window(&records.server_errors, Utc::now()),
+        }
+    }
+}
+
+fn push_bounded<T>(queue: &mut VecDeque<T>, item: T, max: usize) {
+    if queue.len() == max {
+        queue.pop_front();
+    }
+    queue.push_back(item);
+}
+
+fn count_in_window(errors: &VecDeque<ServerErrorRecord>, now: DateTime<Utc>) -> usize {
+    let since = now - Duration::seconds(SPIKE_WINDOW_SECS);
+    errors.iter().filter(|e| e.timestamp > since).count()
+}
+
+fn crash_report_path() -> PathBuf {
+    std::env::temp_dir().join(CRASH_REPORT_FILE)
+}
+
+/// Record panics in [`ERRORS`] and the crash report file
+///
+/// The previous hook still runs, so panics keep going to stderr.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let panic = PanicRecord {
+            timestamp: Utc::now(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string(),
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            message,
+            previous_run: false,
+        };
+        tracing::error!(
+            "Panic in thread '{}' at {}: {}",
+            panic.thread,
+            panic.location.as_deref().unwrap_or("unknown location"),
+            panic.message
+        );
+        if let Ok(json) = serde_json::to_vec(&panic) {
+            let _ = std::fs::write(crash_report_path(), json);
+        }
+        ERRORS.record_panic(panic);
+        previous(info);
+    }));
+}
+
+/// Load the crash report left by a previous run, if any, and remove it
+pub fn load_previous_crash() -> Option<PanicRecord> {
+    let path = crash_report_path();
+    let json = std::fs::read(&path).ok()?;
+    let _ = std::fs::remove_file(&path);
+    let mut panic: PanicRecord = serde_json::from_slice(&json).ok()?;
+    panic.previous_run = true;
+    ERRORS.record_panic(panic.clone());
+    Some(panic)
+}
+
+/// Mask the password in a URL's user info
+pub fn redact_url(url: &str) -> String {
+    let Some(scheme_end) = url.find("://").map(|i| i + 3) else {
+        return url.to_string();
+    };
+    let Some(at) = url[scheme_end..].find('@').map(|i| scheme_end + i) else {
+        return url.to_string();
+    };
+    match url[scheme_end..at].find(':') {
+        Some(colon) => format!(
+            "{}{}{}",
+            &url[..scheme_end + colon + 1],
+            REDACTED,
+            &url[at..]
+        ),
+        None => url.to_string(),
+    }
+}
+
+/// Mask secret values in free text
+///
+/// Covers `key=value` and `key: value` pairs for the keys in [`SECRET_KEYS`],
+/// bearer tokens and passwords in URLs.
+pub fn redact_text(text: &str) -> String {
+    text.lines().map(redact_line).collect::<Vec<_>>().join("\n")
+}
+
+fn redact_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for word in line.split_inclusive(' ') {
+        out.push_str(&redact_word(word));
+    }
+
+    // Values following "key: " or "Bearer " are separate words
+    let words: Vec<&str> = out.split(' ').collect();
+    let mut masked = Vec::with_capacity(words.len());
+    let mut mask_next = false;
+    for word in words {
+        let lower = word.to_lowercase();
+        if mask_next && !word.is_empty() && lower != "bearer" {
+            masked.push(REDACTED.to_string());
+            mask_next = false;
+            continue;
+        }
+        mask_next = lower == "bearer"
+            || lower
+                .strip_suffix(':')
+                .map(|k| is_secret_key(k.trim_matches('"')))
+                .unwrap_or(false);
+        masked.push(word.to_string());
+    }
+    masked.join(" ")
+}
+
+fn redact_word(word: &str) -> String {
+    if word.contains("://") {
+        return redact_url(word);
+    }
+    match word.split_once('=') {
+        Some((key, value)) if is_secret_key(key) && !value.trim().is_empty() => {
+            let trailing = if word.ends_with(' ') { " " } else { "" };
+            format!("{}={}{}", key, REDACTED, trailing)
+        }
+        _ => word.to_string(),
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEYS.iter().any(|s| key.ends_with(s))
+}
+
+/// The startup configuration with secrets redacted
+pub fn redacted_config() -> serde_json::Value {
+    serde_json::json!({
+        "server": {
+            "host": CONFIG.server.host,
+            "port": CONFIG.server.port,
+            "static_dir": CONFIG.server.static_dir,
+            "external_url": redact_url(&CONFIG.server.external_url),
+        },
+        "database": {
+            "database_url": redact_url(&CONFIG.database.database_url),
+        },
+        "kubernetes": {
+            "kubeconfig_path": CONFIG.kubernetes.kubeconfig_path,
+            "in_cluster": CONFIG.kubernetes.in_cluster,
+            "default_namespace": CONFIG.kubernetes.default_namespace,
+        },
+        "auth": {
+            "oauth2_enabled": CONFIG.auth.oauth2_enabled,
+            "oauth2_issuer_url": CONFIG.auth.oauth2_issuer_url,
+        },
+        "audit": {
+            "integrity_key": CONFIG.audit.integrity_key.as_ref().map(|_| REDACTED),
+        },
+        "backup": {
+            "dir": CONFIG.backup.dir,
+            "passphrase": CONFIG.backup.passphrase.as_ref().map(|_| REDACTED),
+        },
+        "charts": {
+            "dir": CONFIG.charts.dir,
+            "repo": redact_url(&CONFIG.charts.repo),
+            "registry": redact_url(&CONFIG.charts.registry),
+            "sync_interval": CONFIG.charts.sync_interval,
+            "git_ref": CONFIG.charts.git_ref,
+        },
+        "outbound": {
+            "offline": CONFIG.outbound.offline,
+        },
+        "helm": {
+            "engine": format!("{:?}", CONFIG.helm.engine),
+            "binary": CONFIG.helm.binary,
+        },
+        "updates": {
+            "repo": CONFIG.updates.repo,
+            "chart": redact_url(&CONFIG.updates.chart),
+            "release_name": CONFIG.updates.release_name,
+            "namespace": CONFIG.updates.namespace,
+            "check_interval": CONFIG.updates.check_interval,
+        },
+        "log_level": CONFIG.log_level,
+        "frontend_url": redact_url(&CONFIG.frontend_url),
+    })
+}
+
+fn version_info() -> serde_json::Value {
+    serde_json::json!({
+        "version": CONFIG.version,
+        "channel": CONFIG.channel,
+        "commit_hash": CONFIG.commit_hash,
+        "build_time": CONFIG.build_time,
+        "generated_at": Utc::now(),
+    })
+}
+
+async fn migration_status(state: &AppState) -> serde_json::Value {
+    let db = match state.get_db().await {
+        Ok(db) => db,
+        Err(e) => return serde_json::json!({ "error": e.to_string() }),
+    };
+    match Migrator::get_migration_with_status(&db).await {
+        Ok(migrations) => {
+            let list: Vec<serde_json::Value> = migrations
+                .iter()
+                .map(|m| serde_json::json!({ "name": m.name(), "status": m.status().to_string() }))
+                .collect();
+            let pending = migrations
+                .iter()
+                .filter(|m| m.status() == MigrationStatus::Pending)
+                .count();
+            serde_json::json!({
+                "total": list.len(),
+                "pending": pending,
+                "migrations": list,
+            })
+        }
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    }
+}
+
+async fn health_report(state: &AppState) -> serde_json::Value {
+    let database = match state.get_db().await {
+        Ok(db) => match db.ping().await {
+            Ok(()) => serde_json::json!({ "status": "ok" }),
+            Err(e) => serde_json::json!({ "status": "error", "error": e.to_string() }),
+        },
+        Err(e) => serde_json::json!({ "status": "unavailable", "error": e.to_string() }),
+    };
+    let kubernetes = match state.k8s_client.read().await.as_ref() {
+        Some(client) => match client.get_server_version().await {
+            Ok(version) => serde_json::json!({ "status": "ok", "server_version": version }),
+            Err(e) => serde_json::json!({ "status": "error", "error": e.to_string() }),
+        },
+        None => serde_json::json!({ "status": "unavailable" }),
+    };
+    let report = ERRORS.report();
+    serde_json::json!({
+        "database": database,
+        "kubernetes": kubernetes,
+        "panics": report.panics.len(),
+        "server_errors_in_window": report.server_errors_in_window,
+        "error_spike": report.server_errors_in_window >= SPIKE_THRESHOLD,
+        "tls": super::tls::status_report(Utc::now()),
+    })
+}
+
+/// Assemble the diagnostic bundle as a gzipped tarball
+pub async fn build_bundle(state: &AppState) -> Result<Vec<u8>> {
+    let json = |value: &serde_json::Value| {
+        serde_json::to_vec_pretty(value).map_err(|e| AppError::Internal(e.to_string()))
+    };
+    let errors =
+        serde_json::to_value(ERRORS.report()).map_err(|e| AppError::Internal(e.to_string()))?;
+    let errors = redact_text(&serde_json::to_string_pretty(&errors).unwrap_or_default());
+
+    let files: Vec<(&str, Vec<u8>)> = vec![
+        ("version.json", json(&version_info())?),
+        ("config.json", json(&redacted_config())?),
+        ("logs.txt", redact_text(&LOG_BUFFER.to_text()).into_bytes()),
+        ("migrations.json", json(&migration_status(state).await)?),
+        ("health.json", json(&health_report(state).await)?),
+        ("errors.json", errors.into_bytes()),
+    ];
+
+    let mtime = Utc::now().timestamp().max(0) as u64;
+    let mut tar = Vec::new();
+    for (name, data) in &files {
+        append_tar_entry(
+            &mut tar,
+            &format!("kubarr-diagnostics/{}", name),
+            data,
+            mtime,
+        );
+    }
+    // End of archive: two zero blocks
+    tar.extend_from_slice(&[0u8; 1024]);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&tar)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| AppError::Internal(format!("Failed to compress bundle: {}", e)))
+}
+
+/// Append one regular file to a ustar archive
+pub(crate) fn append_tar_entry(tar: &mut Vec<u8>, name: &str, data: &[u8], mtime: u64) {
+    let mut header = [0u8; 512];
+    let name = name.as_bytes();
+    header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], data.len() as u64);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field set to spaces
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+    write_octal(&mut header[148..155], checksum);
+
+    tar.extend_from_slice(&header);
+    tar.extend_from_slice(data);
+    let padding = (512 - data.len() % 512) % 512;
+    tar.extend(std::iter::repeat_n(0u8, padding));
+}
+
+/// Zero-padded octal number followed by a NUL
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+    field[..width].copy_from_slice(digits.as_bytes());
+    field[width] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error(timestamp: DateTime<Utc>) -> ServerErrorRecord {
+        ServerErrorRecord {
+            timestamp,
+            method: "GET".to_string(),
+            path: "/api/apps".to_string(),
+            status: 500,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_spike_starts_at_threshold_and_extends() {
+        let errors = ErrorAggregator::new();
+        let start = Utc::now();
+        for i in 0..SPIKE_THRESHOLD - 1 {
+            assert!(
+                !errors.record_server_error(server_error(start + Duration::milliseconds(i as i64)))
+            );
+        }
+        assert!(errors.record_server_error(server_error(start + Duration::seconds(1))));
+        assert!(!errors.record_server_error(server_error(start + Duration::seconds(2))));
+
+        let report = errors.report();
+        assert_eq!(report.spikes.len(), 1);
+        assert_eq!(report.spikes[0].count, SPIKE_THRESHOLD + 1);
+    }
+
+    #[test]
+    fn test_spread_out_errors_are_not_a_spike() {
+        let errors = ErrorAggregator::new();
+        let start = Utc::now();
+        for i in 0..SPIKE_THRESHOLD * 2 {
+            let at = start + Duration::seconds(i as i64 * 10);
+            assert!(!errors.record_server_error(server_error(at)));
+        }
+        assert!(errors.report().spikes.is_empty());
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("postgres://kubarr:hunter2@db:5432/kubarr"),
+            "postgres://kubarr:[redacted]@db:5432/kubarr"
+        );
+        assert_eq!(
+            redact_url("postgres://db:5432/kubarr"),
+            "postgres://db:5432/kubarr"
+        );
+        assert_eq!(redact_url("sqlite::memory:"), "sqlite::memory:");
+    }
+
+    #[test]
+    fn test_redact_text() {
+        let text = "login failed password=hunter2 user=bob\n\
+                    sent Authorization: Bearer abc.def to https://u:p@example.com/x\n\
+                    api_key: xyz kept";
+        let redacted = redact_text(text);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc.def"));
+        assert!(!redacted.contains("u:p@"));
+        assert!(!redacted.contains("xyz"));
+        assert!(redacted.contains("user=bob"));
+        assert!(redacted.contains("kept"));
+    }
+
+    #[test]
+    fn test_tar_entry_layout() {
+        let mut tar = Vec::new();
+        append_tar_entry(&mut tar, "a.txt", b"hello", 0);
+        assert_eq!(tar.len(), 1024);
+        assert_eq!(&tar[..5], b"a.txt");
+        assert_eq!(&tar[124..135], b"00000000005");
+        assert_eq!(&tar[257..262], b"ustar");
+        assert_eq!(&tar[512..517], b"hello");
+
+        // Stored checksum matches the header with the field blanked
+        let mut header = tar[..512].to_vec();
+        let stored =
+            u64::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8).unwrap();
+        header[148..156].fill(b' ');
+        assert_eq!(stored, header.iter().map(|&b| u64::from(b)).sum::<u64>());
+    }
+}
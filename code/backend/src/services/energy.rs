@@ -0,0 +1,688 @@
+//! Energy use and cost estimation
+//!
+//! Every hour the power draw of each node is estimated and spread over the
+//! apps running on it in proportion to their CPU usage. Node power follows a
+//! linear model between the node's idle and full-load wattage
+//! (`node_power_profiles`, falling back to the `energy_default_*_watts`
+//! settings) driven by its CPU utilization. When `energy_power_query` is set,
+//! measured node power from VictoriaMetrics is used instead wherever
+//! available.
+//!
+//! Energy that cannot be attributed to an app (idle capacity, system
+//! namespaces) is booked as [`SYSTEM_APP`], so the rollups add up to the whole
+//! cluster. Samples are priced at `energy_price_per_kwh` when recorded and
+//! added to daily and monthly rollups in `energy_usage`.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use super::network_broadcaster::is_excluded_namespace;
+use super::network_usage::{day_key, month_key, since_key, PERIOD_DAY, PERIOD_MONTH};
+use super::scheduler::PeriodicTask;
+use super::victoriametrics::{query_vm, validate_query};
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{energy_usage, node_power_profile};
+
+/// Bucket for energy not attributable to an app
+pub const SYSTEM_APP: &str = "system";
+
+/// Upper bound accepted for a node's wattage
+pub const MAX_NODE_WATTS: f64 = 10_000.0;
+
+const DEFAULT_PRICE_PER_KWH: f64 = 0.25;
+const DEFAULT_IDLE_WATTS: f64 = 10.0;
+const DEFAULT_MAX_WATTS: f64 = 40.0;
+
+/// Sampling interval; also the lookback window of each `rate()` query
+const SAMPLE_INTERVAL_SECS: u64 = 60 * 60;
+
+// ============================================================================
+// Power model
+// ============================================================================
+
+/// Power draw of a node at idle and at full CPU load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerProfile {
+    pub idle_watts: f64,
+    pub max_watts: f64,
+}
+
+impl PowerProfile {
+    /// Estimated draw at a CPU utilization between 0 and 1
+    pub fn watts(&self, utilization: f64) -> f64 {
+        let utilization = utilization.clamp(0.0, 1.0);
+        self.idle_watts + (self.max_watts - self.idle_watts) * utilization
+    }
+}
+
+/// CPU usage of one node during a sampling window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeSample {
+    /// CPU cores of the node
+    pub cores: f64,
+    /// Cores used by all containers and system processes
+    pub used_cores: f64,
+    /// Cores used per app namespace
+    pub app_cores: HashMap<String, f64>,
+    /// Measured power draw, when a power metrics source is configured
+    pub measured_watts: Option<f64>,
+}
+
+/// Split a node's power draw over its apps by CPU share
+///
+/// The remainder (idle capacity and non-app processes) goes to
+/// [`SYSTEM_APP`], so the result always sums to `node_watts`.
+pub fn attribute(node_watts: f64, sample: &NodeSample) -> HashMap<String, f64> {
+    let mut watts = HashMap::new();
+    let total_cores = sample.used_cores.max(sample.app_cores.values().sum());
+    let mut attributed = 0.0;
+    if total_cores > 0.0 {
+        for (app, cores) in &sample.app_cores {
+            let share = node_watts * cores.max(0.0) / total_cores;
+            if share > 0.0 {
+                watts.insert(app.clone(), share);
+                attributed += share;
+            }
+        }
+    }
+    let remainder = node_watts - attributed;
+    if remainder > 0.0 {
+        *watts.entry(SYSTEM_APP.to_string()).or_insert(0.0) += remainder;
+    }
+    watts
+}
+
+/// Estimate watt-hours per app for one sampling window of `hours`
+pub fn estimate(
+    samples: &HashMap<String, NodeSample>,
+    profiles: &HashMap<String, PowerProfile>,
+    default: PowerProfile,
+    hours: f64,
+) -> HashMap<String, f64> {
+    let mut energy: HashMap<String, f64> = HashMap::new();
+    for (node, sample) in samples {
+        let node_watts = sample.measured_watts.unwrap_or_else(|| {
+            let utilization = if sample.cores > 0.0 {
+                sample.used_cores / sample.cores
+            } else {
+                0.0
+            };
+            profiles
+                .get(node)
+                .copied()
+                .unwrap_or(default)
+                .watts(utilization)
+        });
+        for (app, watts) in attribute(node_watts, sample) {
+            *energy.entry(app).or_insert(0.0) += watts * hours;
+        }
+    }
+    energy
+}
+
+/// Cost of `energy_wh` at a price per kWh
+pub fn cost(energy_wh: f64, price_per_kwh: f64) -> f64 {
+    energy_wh / 1000.0 * price_per_kwh
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+/// Energy settings from `system_settings`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnergySettings {
+    pub price_per_kwh: f64,
+    pub currency: String,
+    pub default_profile: PowerProfile,
+    /// PromQL returning measured watts per node (`instance` label)
+    pub power_query: Option<String>,
+}
+
+async fn setting_f64(db: &DatabaseConnection, key: &str, default: f64) -> Result<f64> {
+    Ok(get_setting_value(db, key)
+        .await?
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+        .unwrap_or(default))
+}
+
+pub async fn energy_settings(db: &DatabaseConnection) -> Result<EnergySettings> {
+    let idle_watts = setting_f64(db, "energy_default_idle_watts", DEFAULT_IDLE_WATTS).await?;
+    let max_watts = setting_f64(db, "energy_default_max_watts", DEFAULT_MAX_WATTS).await?;
+    let currency = get_setting_value(db, "energy_currency")
+        .await?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "EUR".to_string());
+    let power_query = get_setting_value(db, "energy_power_query")
+        .await?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    Ok(EnergySettings {
+        price_per_kwh: setting_f64(db, "energy_price_per_kwh", DEFAULT_PRICE_PER_KWH).await?,
+        currency,
+        default_profile: PowerProfile {
+            idle_watts,
+            max_watts: max_watts.max(idle_watts),
+        },
+        power_query,
+    })
+}
+
+// ============================================================================
+// Node Power Profiles
+// ============================================================================
+
+/// Wattage configured for one node
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NodePowerInfo {
+    pub node_name: String,
+    pub idle_watts: f64,
+    pub max_watts: f64,
+}
+
+impl From<node_power_profile::Model> for NodePowerInfo {
+    fn from(model: node_power_profile::Model) -> Self {
+        Self {
+            node_name: model.node_name,
+            idle_watts: model.idle_watts,
+            max_watts: model.max_watts,
+        }
+    }
+}
+
+/// Response for GET /api/monitoring/energy/nodes
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NodePowerSettings {
+    /// Wattage assumed for nodes without a profile
+    pub default_idle_watts: f64,
+    pub default_max_watts: f64,
+    pub nodes: Vec<NodePowerInfo>,
+}
+
+pub async fn node_power_settings(db: &DatabaseConnection) -> Result<NodePowerSettings> {
+    let settings = energy_settings(db).await?;
+    let nodes = NodePowerProfile::find()
+        .order_by_asc(node_power_profile::Column::NodeName)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(NodePowerSettings {
+        default_idle_watts: settings.default_profile.idle_watts,
+        default_max_watts: settings.default_profile.max_watts,
+        nodes,
+    })
+}
+
+/// Create or update a node's wattage
+pub async fn set_node_power(
+    db: &DatabaseConnection,
+    node_name: &str,
+    idle_watts: f64,
+    max_watts: f64,
+) -> Result<NodePowerInfo> {
+    if !idle_watts.is_finite() || !max_watts.is_finite() || idle_watts < 0.0 {
+        return Err(AppError::BadRequest(
+            "idle_watts must be zero or greater".to_string(),
+        ));
+    }
+    if max_watts <= 0.0 || max_watts > MAX_NODE_WATTS {
+        return Err(AppError::BadRequest(format!(
+            "max_watts must be between 0 and {}",
+            MAX_NODE_WATTS
+        )));
+    }
+    if idle_watts > max_watts {
+        return Err(AppError::BadRequest(
+            "idle_watts cannot exceed max_watts".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let model = match NodePowerProfile::find_by_id(node_name).one(db).await? {
+        Some(existing) => {
+            let mut active: node_power_profile::ActiveModel = existing.into();
+            active.idle_watts = Set(idle_watts);
+            active.max_watts = Set(max_watts);
+            active.updated_at = Set(now);
+            active.update(db).await?
+        }
+        None => {
+            node_power_profile::ActiveModel {
+                node_name: Set(node_name.to_string()),
+                idle_watts: Set(idle_watts),
+                max_watts: Set(max_watts),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    Ok(model.into())
+}
+
+/// Remove a node's wattage so the defaults apply
+pub async fn delete_node_power(db: &DatabaseConnection, node_name: &str) -> Result<()> {
+    let result = NodePowerProfile::delete_by_id(node_name).exec(db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No power profile configured for node '{}'",
+            node_name
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Sampling and rollups
+// ============================================================================
+
+fn result_value(result: &serde_json::Value) -> f64 {
+    result["value"][1]
+        .as_str()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .unwrap_or(0.0)
+        .max(0.0)
+}
+
+/// Read per-node CPU usage (and measured power) over the last sampling window
+pub async fn fetch_node_samples(power_query: Option<&str>) -> HashMap<String, NodeSample> {
+    let window = format!("{}s", SAMPLE_INTERVAL_SECS);
+    let used_query = format!(
+        r#"sum by (instance) (rate(container_cpu_usage_seconds_total{{id="/"}}[{}]))"#,
+        window
+    );
+    let app_query = format!(
+        r#"sum by (instance, namespace) (rate(container_cpu_usage_seconds_total{{container!="",container!="POD"}}[{}]))"#,
+        window
+    );
+    let (cores, used, apps) = tokio::join!(
+        query_vm("sum by (instance) (machine_cpu_cores)"),
+        query_vm(&used_query),
+        query_vm(&app_query)
+    );
+
+    let mut samples: HashMap<String, NodeSample> = HashMap::new();
+    for r in &cores {
+        if let Some(node) = r["metric"]["instance"].as_str() {
+            samples.entry(node.to_string()).or_default().cores = result_value(r);
+        }
+    }
+    for r in &used {
+        if let Some(node) = r["metric"]["instance"].as_str() {
+            samples.entry(node.to_string()).or_default().used_cores = result_value(r);
+        }
+    }
+    for r in &apps {
+        let (Some(node), Some(namespace)) = (
+            r["metric"]["instance"].as_str(),
+            r["metric"]["namespace"].as_str(),
+        ) else {
+            continue;
+        };
+        if is_excluded_namespace(namespace) {
+            continue;
+        }
+        *samples
+            .entry(node.to_string())
+            .or_default()
+            .app_cores
+            .entry(namespace.to_string())
+            .or_insert(0.0) += result_value(r);
+    }
+
+    if let Some(query) = power_query {
+        match validate_query(query) {
+            Ok(()) => {
+                for r in query_vm(query).await {
+                    if let Some(sample) = r["metric"]["instance"]
+                        .as_str()
+                        .and_then(|node| samples.get_mut(node))
+                    {
+                        sample.measured_watts = Some(result_value(&r));
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Ignoring energy_power_query: {}", e),
+        }
+    }
+
+    samples
+}
+
+/// Add energy to one rollup row, creating it if needed
+async fn add_to_rollup(
+    db: &DatabaseConnection,
+    app_name: &str,
+    period: &str,
+    period_start: String,
+    energy_wh: f64,
+    cost: f64,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    let existing = EnergyUsage::find()
+        .filter(energy_usage::Column::AppName.eq(app_name))
+        .filter(energy_usage::Column::Period.eq(period))
+        .filter(energy_usage::Column::PeriodStart.eq(period_start.as_str()))
+        .one(db)
+        .await?;
+
+    match existing {
+        Some(row) => {
+            let total_wh = row.energy_wh + energy_wh;
+            let total_cost = row.cost + cost;
+            let mut active: energy_usage::ActiveModel = row.into();
+            active.energy_wh = Set(total_wh);
+            active.cost = Set(total_cost);
+            active.updated_at = Set(at);
+            active.update(db).await?;
+        }
+        None => {
+            energy_usage::ActiveModel {
+                app_name: Set(app_name.to_string()),
+                period: Set(period.to_string()),
+                period_start: Set(period_start),
+                energy_wh: Set(energy_wh),
+                cost: Set(cost),
+                updated_at: Set(at),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Add per-app watt-hours, priced at `price_per_kwh`, to the daily and monthly
+/// rollups of the period containing `at`
+pub async fn record_energy(
+    db: &DatabaseConnection,
+    energy_wh: &HashMap<String, f64>,
+    price_per_kwh: f64,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    for (app_name, wh) in energy_wh {
+        if *wh <= 0.0 {
+            continue;
+        }
+        let cost = cost(*wh, price_per_kwh);
+        add_to_rollup(db, app_name, PERIOD_DAY, day_key(at), *wh, cost, at).await?;
+        add_to_rollup(db, app_name, PERIOD_MONTH, month_key(at), *wh, cost, at).await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Reporting
+// ============================================================================
+
+/// Energy and cost for a single day or month
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct EnergyUsagePoint {
+    /// "YYYY-MM-DD" for days, "YYYY-MM" for months (UTC)
+    pub period_start: String,
+    pub energy_kwh: f64,
+    pub cost: f64,
+}
+
+/// Estimated energy use of a single app
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AppEnergyUsage {
+    /// App namespace, or "system" for unattributed energy
+    pub app_name: String,
+    /// Energy over the requested range
+    pub energy_kwh: f64,
+    /// Cost over the requested range
+    pub cost: f64,
+    /// Oldest first
+    pub history: Vec<EnergyUsagePoint>,
+}
+
+/// Response for GET /api/monitoring/energy
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EnergyReport {
+    /// "day" or "month"
+    pub period: String,
+    /// First period included in the report
+    pub since: String,
+    pub currency: String,
+    /// Current electricity price; past samples keep the price they were recorded at
+    pub price_per_kwh: f64,
+    /// "model" (wattage profiles) or "metrics" (energy_power_query)
+    pub power_source: String,
+    pub total_kwh: f64,
+    pub total_cost: f64,
+    /// Cluster-wide totals per period, oldest first
+    pub totals: Vec<EnergyUsagePoint>,
+    /// Highest energy use first
+    pub apps: Vec<AppEnergyUsage>,
+    pub generated_at: DateTime<Utc>,
+}
+
+fn round_kwh(wh: f64) -> f64 {
+    wh.round() / 1000.0
+}
+
+fn round_cost(cost: f64) -> f64 {
+    (cost * 100.0).round() / 100.0
+}
+
+/// Build the energy report for the last `count` days or months
+pub async fn energy_report(
+    db: &DatabaseConnection,
+    period: &str,
+    count: u32,
+    now: DateTime<Utc>,
+) -> Result<EnergyReport> {
+    if period != PERIOD_DAY && period != PERIOD_MONTH {
+        return Err(AppError::BadRequest(format!(
+            "Invalid period '{}': expected 'day' or 'month'",
+            period
+        )));
+    }
+
+    let settings = energy_settings(db).await?;
+    let since = since_key(period, count, now);
+    let rows = EnergyUsage::find()
+        .filter(energy_usage::Column::Period.eq(period))
+        .filter(energy_usage::Column::PeriodStart.gte(since.as_str()))
+        .order_by_asc(energy_usage::Column::PeriodStart)
+        .all(db)
+        .await?;
+
+    let mut per_app: BTreeMap<String, Vec<(String, f64, f64)>> = BTreeMap::new();
+    let mut per_period: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+    for row in rows {
+        let total = per_period.entry(row.period_start.clone()).or_default();
+        total.0 += row.energy_wh;
+        total.1 += row.cost;
+        per_app
+            .entry(row.app_name)
+            .or_default()
+            .push((row.period_start, row.energy_wh, row.cost));
+    }
+
+    let point = |period_start: String, wh: f64, cost: f64| EnergyUsagePoint {
+        period_start,
+        energy_kwh: round_kwh(wh),
+        cost: round_cost(cost),
+    };
+
+    let mut apps: Vec<(f64, AppEnergyUsage)> = per_app
+        .into_iter()
+        .map(|(app_name, points)| {
+            let wh: f64 = points.iter().map(|p| p.1).sum();
+            let cost: f64 = points.iter().map(|p| p.2).sum();
+            let usage = AppEnergyUsage {
+                app_name,
+                energy_kwh: round_kwh(wh),
+                cost: round_cost(cost),
+                history: points
+                    .into_iter()
+                    .map(|(start, wh, cost)| point(start, wh, cost))
+                    .collect(),
+            };
+            (wh, usage)
+        })
+        .collect();
+    apps.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let total_wh: f64 = per_period.values().map(|t| t.0).sum();
+    let total_cost: f64 = per_period.values().map(|t| t.1).sum();
+
+    Ok(EnergyReport {
+        period: period.to_string(),
+        since,
+        currency: settings.currency,
+        price_per_kwh: settings.price_per_kwh,
+        power_source: if settings.power_query.is_some() {
+            "metrics"
+        } else {
+            "model"
+        }
+        .to_string(),
+        total_kwh: round_kwh(total_wh),
+        total_cost: round_cost(total_cost),
+        totals: per_period
+            .into_iter()
+            .map(|(start, (wh, cost))| point(start, wh, cost))
+            .collect(),
+        apps: apps.into_iter().map(|(_, usage)| usage).collect(),
+        generated_at: now,
+    })
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Estimates per-app energy use and updates the daily/monthly rollups
+pub struct EnergyUsageTask;
+
+#[async_trait]
+impl PeriodicTask for EnergyUsageTask {
+    fn name(&self) -> &'static str {
+        "energy_usage"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(SAMPLE_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let settings = energy_settings(db).await?;
+        let samples = fetch_node_samples(settings.power_query.as_deref()).await;
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let profiles: HashMap<String, PowerProfile> = NodePowerProfile::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| {
+                (
+                    p.node_name,
+                    PowerProfile {
+                        idle_watts: p.idle_watts,
+                        max_watts: p.max_watts,
+                    },
+                )
+            })
+            .collect();
+
+        let hours = SAMPLE_INTERVAL_SECS as f64 / 3600.0;
+        let energy = estimate(&samples, &profiles, settings.default_profile, hours);
+        record_energy(db, &energy, settings.price_per_kwh, Utc::now()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cores: f64, used: f64, apps: &[(&str, f64)]) -> NodeSample {
+        NodeSample {
+            cores,
+            used_cores: used,
+            app_cores: apps.iter().map(|(a, c)| (a.to_string(), *c)).collect(),
+            measured_watts: None,
+        }
+    }
+
+    #[test]
+    fn test_profile_is_linear_and_clamped() {
+        let profile = PowerProfile {
+            idle_watts: 10.0,
+            max_watts: 50.0,
+        };
+        assert_eq!(profile.watts(0.0), 10.0);
+        assert_eq!(profile.watts(0.5), 30.0);
+        assert_eq!(profile.watts(1.0), 50.0);
+        assert_eq!(profile.watts(1.7), 50.0);
+        assert_eq!(profile.watts(-0.2), 10.0);
+    }
+
+    #[test]
+    fn test_attribute_splits_by_cpu_share() {
+        let watts = attribute(40.0, &sample(4.0, 2.0, &[("plex", 1.5), ("sonarr", 0.25)]));
+        assert_eq!(watts["plex"], 30.0);
+        assert_eq!(watts["sonarr"], 5.0);
+        assert_eq!(watts[SYSTEM_APP], 5.0);
+
+        // An idle node is booked entirely as system
+        let watts = attribute(12.0, &sample(4.0, 0.0, &[]));
+        assert_eq!(watts.len(), 1);
+        assert_eq!(watts[SYSTEM_APP], 12.0);
+    }
+
+    #[test]
+    fn test_estimate_prefers_measured_power() {
+        let default = PowerProfile {
+            idle_watts: 10.0,
+            max_watts: 50.0,
+        };
+        let mut samples = HashMap::new();
+        samples.insert("nuc".to_string(), sample(4.0, 2.0, &[("plex", 2.0)]));
+        let mut measured = sample(8.0, 4.0, &[("plex", 4.0)]);
+        measured.measured_watts = Some(100.0);
+        samples.insert("server".to_string(), measured);
+
+        let profiles = HashMap::from([(
+            "nuc".to_string(),
+            PowerProfile {
+                idle_watts: 6.0,
+                max_watts: 26.0,
+            },
+        )]);
+
+        // nuc: 6 + 20 * 0.5 = 16 W, server: measured 100 W, over two hours
+        let energy = estimate(&samples, &profiles, default, 2.0);
+        assert_eq!(energy["plex"], 232.0);
+        assert!(!energy.contains_key(SYSTEM_APP));
+    }
+
+    #[test]
+    fn test_cost() {
+        assert!((cost(1500.0, 0.3) - 0.45).abs() < 1e-9);
+        assert_eq!(round_kwh(1234.5678), 1.235);
+        assert_eq!(round_cost(0.456), 0.46);
+    }
+}
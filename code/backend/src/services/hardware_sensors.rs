@@ -0,0 +1,601 @@
+//! Node temperature sensors
+//!
+//! Reads the node-exporter hwmon metrics (`node_hwmon_temp_celsius` and
+//! friends) from VictoriaMetrics and classifies each sensor as CPU, disk or
+//! other from its chip name. CPU and disk readings are checked against the
+//! `sensor_*_celsius` thresholds; other sensors only against the critical
+//! temperature the device reports itself.
+//!
+//! A background task raises a `node_temperature_critical` notification when a
+//! sensor becomes critical, so a passively cooled node can be dealt with
+//! before it throttles or shuts down.
+
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use super::alerts;
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use super::victoriametrics::query_vm;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::Result;
+use crate::models::audit_log::AuditAction;
+
+const CHECK_INTERVAL_SECS: u64 = 2 * 60;
+
+/// Chip names of CPU temperature drivers (Intel, AMD, ARM SoCs)
+const CPU_CHIPS: &[&str] = &[
+    "coretemp",
+    "k10temp",
+    "zenpower",
+    "cpu_thermal",
+    "soc_thermal",
+];
+/// Chip names of disk temperature drivers (NVMe, SATA via drivetemp)
+const DISK_CHIPS: &[&str] = &["nvme", "drivetemp"];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorKind {
+    Cpu,
+    Disk,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorStatus {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// Temperature thresholds for CPU and disk sensors
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SensorThresholds {
+    pub cpu_warning_celsius: f64,
+    pub cpu_critical_celsius: f64,
+    pub disk_warning_celsius: f64,
+    pub disk_critical_celsius: f64,
+}
+
+impl Default for SensorThresholds {
+    fn default() -> Self {
+        Self {
+            cpu_warning_celsius: 80.0,
+            cpu_critical_celsius: 90.0,
+            disk_warning_celsius: 50.0,
+            disk_critical_celsius: 60.0,
+        }
+    }
+}
+
+/// A temperature reading as exported by node-exporter
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawSensor {
+    pub chip: String,
+    /// Driver name from `node_hwmon_chip_names`
+    pub chip_name: Option<String>,
+    pub sensor: String,
+    /// Label from `node_hwmon_sensor_label` (e.g. "Package id 0")
+    pub label: Option<String>,
+    pub temperature_celsius: f64,
+    /// Critical temperature reported by the device
+    pub device_critical_celsius: Option<f64>,
+}
+
+/// A classified temperature reading
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SensorReading {
+    pub chip: String,
+    pub chip_name: Option<String>,
+    pub sensor: String,
+    pub label: Option<String>,
+    pub kind: SensorKind,
+    pub temperature_celsius: f64,
+    pub device_critical_celsius: Option<f64>,
+    pub status: SensorStatus,
+}
+
+impl SensorReading {
+    /// Human-readable name, e.g. "CPU Package id 0"
+    pub fn display_name(&self) -> String {
+        let kind = match self.kind {
+            SensorKind::Cpu => "CPU",
+            SensorKind::Disk => "Disk",
+            SensorKind::Other => self.chip_name.as_deref().unwrap_or(&self.chip),
+        };
+        let sensor = self.label.as_deref().unwrap_or(&self.sensor);
+        format!("{} {}", kind, sensor)
+    }
+}
+
+/// Response for GET /api/monitoring/nodes/{name}/sensors
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NodeSensors {
+    pub node: String,
+    /// False when no hwmon metrics were found for the node
+    pub available: bool,
+    /// Worst status of all sensors
+    pub status: SensorStatus,
+    pub hottest_cpu_celsius: Option<f64>,
+    pub hottest_disk_celsius: Option<f64>,
+    pub thresholds: SensorThresholds,
+    /// Hottest first
+    pub sensors: Vec<SensorReading>,
+}
+
+// ============================================================================
+// Classification
+// ============================================================================
+
+/// Determine the sensor kind from the driver name, falling back to the chip
+pub fn classify(chip: &str, chip_name: Option<&str>) -> SensorKind {
+    let name = chip_name.unwrap_or(chip).to_lowercase();
+    if CPU_CHIPS.iter().any(|c| name.contains(c)) {
+        SensorKind::Cpu
+    } else if DISK_CHIPS.iter().any(|c| name.contains(c)) {
+        SensorKind::Disk
+    } else {
+        SensorKind::Other
+    }
+}
+
+fn status_for(temperature: f64, warning: f64, critical: f64) -> SensorStatus {
+    if temperature >= critical {
+        SensorStatus::Critical
+    } else if temperature >= warning {
+        SensorStatus::Warning
+    } else {
+        SensorStatus::Ok
+    }
+}
+
+/// Classify a raw reading and check it against the thresholds
+pub fn evaluate(raw: RawSensor, thresholds: &SensorThresholds) -> SensorReading {
+    let kind = classify(&raw.chip, raw.chip_name.as_deref());
+    let status = match kind {
+        SensorKind::Cpu => status_for(
+            raw.temperature_celsius,
+            thresholds.cpu_warning_celsius,
+            thresholds.cpu_critical_celsius,
+        ),
+        SensorKind::Disk => status_for(
+            raw.temperature_celsius,
+            thresholds.disk_warning_celsius,
+            thresholds.disk_critical_celsius,
+        ),
+        // Without a threshold of our own, warn within 10°C of the device limit
+        SensorKind::Other => match raw.device_critical_celsius.filter(|c| *c > 0.0) {
+            Some(critical) => status_for(raw.temperature_celsius, critical - 10.0, critical),
+            None => SensorStatus::Ok,
+        },
+    };
+
+    SensorReading {
+        chip: raw.chip,
+        chip_name: raw.chip_name,
+        sensor: raw.sensor,
+        label: raw.label,
+        kind,
+        temperature_celsius: (raw.temperature_celsius * 10.0).round() / 10.0,
+        device_critical_celsius: raw.device_critical_celsius,
+        status,
+    }
+}
+
+/// Summarize the readings of one node
+pub fn summarize(
+    node: &str,
+    mut sensors: Vec<SensorReading>,
+    thresholds: SensorThresholds,
+) -> NodeSensors {
+    sensors.sort_by(|a, b| b.temperature_celsius.total_cmp(&a.temperature_celsius));
+    let hottest = |kind: SensorKind| {
+        sensors
+            .iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| s.temperature_celsius)
+            .reduce(f64::max)
+    };
+
+    NodeSensors {
+        node: node.to_string(),
+        available: !sensors.is_empty(),
+        status: sensors
+            .iter()
+            .map(|s| s.status)
+            .max()
+            .unwrap_or(SensorStatus::Ok),
+        hottest_cpu_celsius: hottest(SensorKind::Cpu),
+        hottest_disk_celsius: hottest(SensorKind::Disk),
+        thresholds,
+        sensors,
+    }
+}
+
+/// Keys of sensors that turned critical since the previous check
+///
+/// `critical` holds the keys that were critical last time and is updated in
+/// place, so a sensor is only reported again after it has recovered.
+pub fn newly_critical<'a>(
+    nodes: &'a [NodeSensors],
+    critical: &mut HashSet<String>,
+) -> Vec<(&'a str, &'a SensorReading)> {
+    let mut current = HashSet::new();
+    let mut new = Vec::new();
+    for node in nodes {
+        for sensor in &node.sensors {
+            if sensor.status != SensorStatus::Critical {
+                continue;
+            }
+            let key = format!("{}/{}/{}", node.node, sensor.chip, sensor.sensor);
+            if !critical.contains(&key) {
+                new.push((node.node.as_str(), sensor));
+            }
+            current.insert(key);
+        }
+    }
+    *critical = current;
+    new
+}
+
+// ============================================================================
+// Data Collection
+// ============================================================================
+
+/// Node a series belongs to: the `node` label set by Kubernetes service
+/// discovery, otherwise the host part of `instance`
+fn series_node(metric: &serde_json::Value) -> Option<String> {
+    if let Some(node) = metric["node"]
+        .as_str()
+        .or_else(|| metric["kubernetes_node"].as_str())
+    {
+        return Some(node.to_string());
+    }
+    let instance = metric["instance"].as_str()?;
+    let host = match instance.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => instance,
+    };
+    Some(host.trim_matches(|c| c == '[' || c == ']').to_string())
+}
+
+fn series_value(result: &serde_json::Value) -> Option<f64> {
+    result["value"][1]
+        .as_str()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+}
+
+/// Read all hwmon temperatures, grouped by node
+pub async fn fetch_sensors() -> BTreeMap<String, Vec<RawSensor>> {
+    let (temps, crits, chips, labels) = tokio::join!(
+        query_vm("node_hwmon_temp_celsius"),
+        query_vm("node_hwmon_temp_crit_celsius"),
+        query_vm("node_hwmon_chip_names"),
+        query_vm("node_hwmon_sensor_label"),
+    );
+
+    let key = |metric: &serde_json::Value| {
+        Some((
+            series_node(metric)?,
+            metric["chip"].as_str()?.to_string(),
+            metric["sensor"].as_str().unwrap_or_default().to_string(),
+        ))
+    };
+
+    let chip_names: HashMap<(String, String), String> = chips
+        .iter()
+        .filter_map(|r| {
+            let (node, chip, _) = key(&r["metric"])?;
+            Some(((node, chip), r["metric"]["chip_name"].as_str()?.to_string()))
+        })
+        .collect();
+    let sensor_labels: HashMap<(String, String, String), String> = labels
+        .iter()
+        .filter_map(|r| {
+            Some((
+                key(&r["metric"])?,
+                r["metric"]["label"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    let critical: HashMap<(String, String, String), f64> = crits
+        .iter()
+        .filter_map(|r| Some((key(&r["metric"])?, series_value(r)?)))
+        .collect();
+
+    let mut nodes: BTreeMap<String, Vec<RawSensor>> = BTreeMap::new();
+    for r in &temps {
+        let (Some(k), Some(temperature)) = (key(&r["metric"]), series_value(r)) else {
+            continue;
+        };
+        let (node, chip, sensor) = k.clone();
+        nodes.entry(node.clone()).or_default().push(RawSensor {
+            chip_name: chip_names.get(&(node, chip.clone())).cloned(),
+            label: sensor_labels.get(&k).cloned(),
+            device_critical_celsius: critical.get(&k).copied(),
+            chip,
+            sensor,
+            temperature_celsius: temperature,
+        });
+    }
+    nodes
+}
+
+async fn setting_celsius(db: &DatabaseConnection, key: &str, default: f64) -> Result<f64> {
+    Ok(get_setting_value(db, key)
+        .await?
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .unwrap_or(default))
+}
+
+/// Thresholds from the `sensor_*_celsius` settings
+pub async fn sensor_thresholds(db: &DatabaseConnection) -> Result<SensorThresholds> {
+    let defaults = SensorThresholds::default();
+    Ok(SensorThresholds {
+        cpu_warning_celsius: setting_celsius(
+            db,
+            "sensor_cpu_warning_celsius",
+            defaults.cpu_warning_celsius,
+        )
+        .await?,
+        cpu_critical_celsius: setting_celsius(
+            db,
+            "sensor_cpu_critical_celsius",
+            defaults.cpu_critical_celsius,
+        )
+        .await?,
+        disk_warning_celsius: setting_celsius(
+            db,
+            "sensor_disk_warning_celsius",
+            defaults.disk_warning_celsius,
+        )
+        .await?,
+        disk_critical_celsius: setting_celsius(
+            db,
+            "sensor_disk_critical_celsius",
+            defaults.disk_critical_celsius,
+        )
+        .await?,
+    })
+}
+
+/// Current sensor readings of one node
+pub async fn node_sensors(db: &DatabaseConnection, node: &str) -> Result<NodeSensors> {
+    let thresholds = sensor_thresholds(db).await?;
+    let sensors = fetch_sensors()
+        .await
+        .remove(node)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|raw| evaluate(raw, &thresholds))
+        .collect();
+    Ok(summarize(node, sensors, thresholds))
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Notifies when a node sensor reaches its critical temperature
+pub struct SensorMonitorTask {
+    pub notification: NotificationService,
+    critical: Mutex<HashSet<String>>,
+}
+
+impl SensorMonitorTask {
+    pub fn new(notification: NotificationService) -> Self {
+        Self {
+            notification,
+            critical: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for SensorMonitorTask {
+    fn name(&self) -> &'static str {
+        "hardware_sensors"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let raw = fetch_sensors().await;
+        if raw.is_empty() {
+            return Ok(());
+        }
+
+        let thresholds = sensor_thresholds(db).await?;
+        let nodes: Vec<NodeSensors> = raw
+            .into_iter()
+            .map(|(node, sensors)| {
+                let readings = sensors
+                    .into_iter()
+                    .map(|s| evaluate(s, &thresholds))
+                    .collect();
+                summarize(&node, readings, thresholds)
+            })
+            .collect();
+
+        let now = chrono::Utc::now();
+        alerts::reconcile(
+            db,
+            alerts::SOURCE_SENSOR,
+            alerts::sensor_conditions(&nodes),
+            now,
+        )
+        .await?;
+
+        let mut critical = self.critical.lock().await;
+        for (node, sensor) in newly_critical(&nodes, &mut critical) {
+            let detail = format!(
+                "{} {} at {:.1}°C",
+                node,
+                sensor.display_name(),
+                sensor.temperature_celsius
+            );
+            tracing::warn!(node, "Temperature critical: {}", detail);
+            let fingerprint = alerts::sensor_fingerprint(node, &sensor.chip, &sensor.sensor);
+            if alerts::is_silenced(db, &fingerprint, now).await? {
+                continue;
+            }
+            self.notification
+                .notify_event(
+                    &AuditAction::NodeTemperatureCritical,
+                    None,
+                    None,
+                    Some(&detail),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(chip: &str, chip_name: Option<&str>, temperature: f64) -> RawSensor {
+        RawSensor {
+            chip: chip.to_string(),
+            chip_name: chip_name.map(str::to_string),
+            sensor: "temp1".to_string(),
+            label: None,
+            temperature_celsius: temperature,
+            device_critical_celsius: None,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("platform_coretemp_0", Some("coretemp")),
+            SensorKind::Cpu
+        );
+        assert_eq!(
+            classify("pci0000:00_0000:00:18_3", Some("k10temp")),
+            SensorKind::Cpu
+        );
+        assert_eq!(
+            classify("thermal_thermal_zone0", Some("cpu_thermal")),
+            SensorKind::Cpu
+        );
+        assert_eq!(classify("nvme_nvme0", None), SensorKind::Disk);
+        assert_eq!(
+            classify("scsi_0_0_0_0", Some("drivetemp")),
+            SensorKind::Disk
+        );
+        assert_eq!(classify("acpitz", Some("acpitz")), SensorKind::Other);
+    }
+
+    #[test]
+    fn test_evaluate_thresholds() {
+        let thresholds = SensorThresholds::default();
+        let status = |r: RawSensor| evaluate(r, &thresholds).status;
+
+        assert_eq!(
+            status(raw("coretemp", Some("coretemp"), 65.0)),
+            SensorStatus::Ok
+        );
+        assert_eq!(
+            status(raw("coretemp", Some("coretemp"), 82.0)),
+            SensorStatus::Warning
+        );
+        assert_eq!(
+            status(raw("coretemp", Some("coretemp"), 90.0)),
+            SensorStatus::Critical
+        );
+        assert_eq!(
+            status(raw("nvme_nvme0", Some("nvme"), 55.0)),
+            SensorStatus::Warning
+        );
+        assert_eq!(
+            status(raw("nvme_nvme0", Some("nvme"), 61.0)),
+            SensorStatus::Critical
+        );
+
+        // Other sensors use the device's own limit, if any
+        assert_eq!(status(raw("acpitz", None, 99.0)), SensorStatus::Ok);
+        let mut gpu = raw("amdgpu", Some("amdgpu"), 92.0);
+        gpu.device_critical_celsius = Some(100.0);
+        assert_eq!(status(gpu), SensorStatus::Warning);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let thresholds = SensorThresholds::default();
+        let sensors = vec![
+            evaluate(raw("nvme_nvme0", Some("nvme"), 41.0), &thresholds),
+            evaluate(raw("coretemp", Some("coretemp"), 71.26), &thresholds),
+            evaluate(raw("nvme_nvme1", Some("nvme"), 52.0), &thresholds),
+        ];
+        let summary = summarize("nuc", sensors, thresholds);
+        assert!(summary.available);
+        assert_eq!(summary.status, SensorStatus::Warning);
+        assert_eq!(summary.hottest_cpu_celsius, Some(71.3));
+        assert_eq!(summary.hottest_disk_celsius, Some(52.0));
+        assert_eq!(summary.sensors[0].kind, SensorKind::Cpu);
+
+        let empty = summarize("pi", Vec::new(), thresholds);
+        assert!(!empty.available);
+        assert_eq!(empty.status, SensorStatus::Ok);
+    }
+
+    #[test]
+    fn test_newly_critical_reports_once_until_recovered() {
+        let thresholds = SensorThresholds::default();
+        let node = |temperature: f64| {
+            vec![summarize(
+                "nuc",
+                vec![evaluate(
+                    raw("coretemp", Some("coretemp"), temperature),
+                    &thresholds,
+                )],
+                thresholds,
+            )]
+        };
+        let mut critical = HashSet::new();
+
+        let hot = node(95.0);
+        assert_eq!(newly_critical(&hot, &mut critical).len(), 1);
+        assert!(newly_critical(&hot, &mut critical).is_empty());
+
+        let cool = node(70.0);
+        assert!(newly_critical(&cool, &mut critical).is_empty());
+        assert_eq!(newly_critical(&hot, &mut critical).len(), 1);
+    }
+
+    #[test]
+    fn test_series_node() {
+        let node = |metric: serde_json::Value| series_node(&metric);
+        assert_eq!(
+            node(serde_json::json!({"node": "nuc", "instance": "10.0.0.5:9100"})),
+            Some("nuc".to_string())
+        );
+        assert_eq!(
+            node(serde_json::json!({"instance": "nuc:9100"})),
+            Some("nuc".to_string())
+        );
+        assert_eq!(
+            node(serde_json::json!({"instance": "[fd00::5]:9100"})),
+            Some("fd00::5".to_string())
+        );
+        assert_eq!(node(serde_json::json!({})), None);
+    }
+}
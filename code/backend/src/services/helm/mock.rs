@@ -0,0 +1,204 @@
+//! In-memory Helm engine for tests
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use super::{HelmEngine, HelmRelease, HelmReleaseInfo};
+use crate::error::{AppError, Result};
+
+/// A call made against [`MockHelmEngine`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelmCall {
+    UpgradeInstall(HelmRelease),
+    Uninstall {
+        name: String,
+        namespace: String,
+    },
+    Rollback {
+        name: String,
+        namespace: String,
+        revision: i32,
+    },
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<HelmCall>,
+    failure: Option<String>,
+    /// Installed releases by (namespace, name)
+    releases: HashMap<(String, String), HelmReleaseInfo>,
+}
+
+/// [`HelmEngine`] that records calls instead of running helm
+///
+/// Clones share the same call log, so a test can keep a handle after
+/// injecting the engine into `AppState`.
+#[derive(Clone, Default)]
+pub struct MockHelmEngine {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockHelmEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(clippy::expect_used)]
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock helm lock poisoned")
+    }
+
+    /// Make every subsequent call fail with the given helm error output
+    pub fn fail_with(&self, stderr: &str) {
+        self.state().failure = Some(stderr.to_string());
+    }
+
+    /// Pretend a release is installed, as reported by `release_info`
+    pub fn add_release(&self, name: &str, namespace: &str, info: HelmReleaseInfo) {
+        self.state()
+            .releases
+            .insert((namespace.to_string(), name.to_string()), info);
+    }
+
+    /// Calls made so far, oldest first
+    pub fn calls(&self) -> Vec<HelmCall> {
+        self.state().calls.clone()
+    }
+
+    fn record(&self, call: HelmCall) -> Result<()> {
+        let mut state = self.state();
+        state.calls.push(call);
+        match &state.failure {
+            Some(stderr) => Err(AppError::Internal(format!(
+                "Helm command failed: {}",
+                stderr
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl HelmEngine for MockHelmEngine {
+    async fn upgrade_install(&self, release: &HelmRelease) -> Result<()> {
+        self.record(HelmCall::UpgradeInstall(release.clone()))?;
+        let key = (release.namespace.clone(), release.name.clone());
+        let mut state = self.state();
+        let info = state.releases.entry(key).or_default();
+        info.revision += 1;
+        if release.version.is_some() {
+            info.chart_version = release.version.clone();
+        }
+        Ok(())
+    }
+
+    async fn uninstall(&self, name: &str, namespace: &str) -> Result<()> {
+        self.record(HelmCall::Uninstall {
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+        })?;
+        self.state()
+            .releases
+            .remove(&(namespace.to_string(), name.to_string()));
+        Ok(())
+    }
+
+    async fn release_info(&self, name: &str, namespace: &str) -> Result<Option<HelmReleaseInfo>> {
+        Ok(self
+            .state()
+            .releases
+            .get(&(namespace.to_string(), name.to_string()))
+            .cloned())
+    }
+
+    async fn rollback(&self, name: &str, namespace: &str, revision: i32) -> Result<()> {
+        self.record(HelmCall::Rollback {
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            revision,
+        })?;
+        if let Some(info) = self
+            .state()
+            .releases
+            .get_mut(&(namespace.to_string(), name.to_string()))
+        {
+            info.revision += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_calls() {
+        let helm = MockHelmEngine::new();
+        let release = HelmRelease {
+            name: "sonarr".to_string(),
+            ..Default::default()
+        };
+
+        helm.upgrade_install(&release).await.unwrap();
+        helm.uninstall("sonarr", "sonarr").await.unwrap();
+
+        assert_eq!(
+            helm.calls(),
+            vec![
+                HelmCall::UpgradeInstall(release),
+                HelmCall::Uninstall {
+                    name: "sonarr".to_string(),
+                    namespace: "sonarr".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tracks_release_revisions() {
+        let helm = MockHelmEngine::new();
+        assert!(helm
+            .release_info("kubarr", "kubarr")
+            .await
+            .unwrap()
+            .is_none());
+
+        let release = HelmRelease {
+            name: "kubarr".to_string(),
+            namespace: "kubarr".to_string(),
+            version: Some("0.2.0".to_string()),
+            ..Default::default()
+        };
+        helm.upgrade_install(&release).await.unwrap();
+        helm.upgrade_install(&release).await.unwrap();
+        helm.rollback("kubarr", "kubarr", 1).await.unwrap();
+
+        let info = helm
+            .release_info("kubarr", "kubarr")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.revision, 3);
+        assert_eq!(info.chart_version.as_deref(), Some("0.2.0"));
+
+        helm.uninstall("kubarr", "kubarr").await.unwrap();
+        assert!(helm
+            .release_info("kubarr", "kubarr")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fail_with_records_and_errors() {
+        let helm = MockHelmEngine::new();
+        helm.fail_with("chart not found");
+
+        let err = helm.uninstall("sonarr", "sonarr").await.unwrap_err();
+        assert!(err.to_string().contains("chart not found"));
+        assert_eq!(helm.calls().len(), 1);
+    }
+}
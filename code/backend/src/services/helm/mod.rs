@@ -0,0 +1,353 @@
+//! Helm execution
+//!
+//! App deployments and Kubarr's own upgrades go through the [`HelmEngine`]
+//! trait rather than invoking helm directly. [`SubprocessHelm`] shells out to the helm binary configured
+//! by `KUBARR_HELM_BINARY`; [`MockHelmEngine`] records calls in memory so the
+//! deployment logic can be tested without helm or a cluster.
+//!
+//! `KUBARR_HELM_ENGINE` selects the backend. Only `subprocess` is implemented
+//! so far; `library` is reserved for an embedded engine and rejected by
+//! startup validation.
+
+use std::process::Stdio;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::helm::HelmEngineKind;
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::services::outbound::ProxySettings;
+
+pub mod mock;
+
+pub use mock::{HelmCall, MockHelmEngine};
+
+/// A release to install or upgrade
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HelmRelease {
+    pub name: String,
+    /// Chart reference, e.g. an OCI URL or a local chart directory
+    pub chart: String,
+    pub namespace: String,
+    pub create_namespace: bool,
+    /// `key=value` overrides passed with `--set`
+    pub set: Vec<String>,
+    /// `key=value` overrides passed with `--set-string`, for values Helm's
+    /// `--set` parser would misinterpret (commas, slashes)
+    pub set_string: Vec<String>,
+    /// Chart version to install instead of the latest
+    pub version: Option<String>,
+    /// Keep the values of the current revision on upgrade
+    pub reuse_values: bool,
+    /// Values object passed as a values file; `set` and `set_string` take
+    /// precedence over it. `Null` for none
+    pub values: serde_json::Value,
+}
+
+/// An installed release as reported by `helm status`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HelmReleaseInfo {
+    pub revision: i32,
+    pub chart_version: Option<String>,
+    /// User-supplied values of the current revision
+    pub values: serde_json::Value,
+}
+
+/// Helm operations used by the deployment service
+#[async_trait]
+pub trait HelmEngine: Send + Sync {
+    /// Install a release, or upgrade it if it already exists
+    async fn upgrade_install(&self, release: &HelmRelease) -> Result<()>;
+
+    /// Uninstall a release
+    async fn uninstall(&self, name: &str, namespace: &str) -> Result<()>;
+
+    /// Current revision of a release, or `None` if it is not installed
+    async fn release_info(&self, name: &str, namespace: &str) -> Result<Option<HelmReleaseInfo>>;
+
+    /// Roll a release back to an earlier revision
+    async fn rollback(&self, name: &str, namespace: &str, revision: i32) -> Result<()>;
+}
+
+/// The Helm engine selected by `KUBARR_HELM_ENGINE`
+pub fn from_config() -> Arc<dyn HelmEngine> {
+    match CONFIG.helm.engine {
+        HelmEngineKind::Subprocess => Arc::new(SubprocessHelm::from_config()),
+        HelmEngineKind::Library => {
+            // Validation refuses this at startup; only reachable when the
+            // state is built without bootstrapping
+            tracing::warn!("Library Helm engine is not available, using the helm binary");
+            Arc::new(SubprocessHelm::from_config())
+        }
+    }
+}
+
+/// Helm engine that runs the helm binary
+pub struct SubprocessHelm {
+    binary: String,
+}
+
+impl SubprocessHelm {
+    pub fn new(binary: impl Into<String>) -> Self {
+        Self {
+            binary: binary.into(),
+        }
+    }
+
+    /// Use the binary from `KUBARR_HELM_BINARY` (default `helm`)
+    pub fn from_config() -> Self {
+        Self::new(CONFIG.helm.binary.clone())
+    }
+
+    async fn run(&self, args: &[String]) -> Result<String> {
+        self.run_with_input(args, None).await
+    }
+
+    /// Run helm with `input` written to its stdin
+    async fn run_with_input(&self, args: &[String], input: Option<&[u8]>) -> Result<String> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut child = tokio::process::Command::new(&self.binary)
+            .args(args)
+            .envs(ProxySettings::current().env_vars())
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Internal(format!("Failed to run helm: {}", e)))?;
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin
+                .write_all(input)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to pass values to helm: {}", e)))?;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to run helm: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Internal(format!(
+                "Helm command failed: {}",
+                stderr
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+#[async_trait]
+impl HelmEngine for SubprocessHelm {
+    async fn upgrade_install(&self, release: &HelmRelease) -> Result<()> {
+        // JSON is valid YAML, so the values go to helm as-is
+        let values = match &release.values {
+            serde_json::Value::Null => None,
+            values => Some(serde_json::to_vec(values)?),
+        };
+        self.run_with_input(&upgrade_install_args(release), values.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    async fn uninstall(&self, name: &str, namespace: &str) -> Result<()> {
+        let args = ["uninstall", name, "-n", namespace].map(String::from);
+        self.run(&args).await?;
+        Ok(())
+    }
+
+    async fn release_info(&self, name: &str, namespace: &str) -> Result<Option<HelmReleaseInfo>> {
+        let args = ["status", name, "-n", namespace, "-o", "json"].map(String::from);
+        match self.run(&args).await {
+            Ok(output) => parse_status(&output).map(Some),
+            Err(AppError::Internal(e)) if e.contains("release: not found") => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn rollback(&self, name: &str, namespace: &str, revision: i32) -> Result<()> {
+        let args = ["rollback", name, &revision.to_string(), "-n", namespace].map(String::from);
+        self.run(&args).await?;
+        Ok(())
+    }
+}
+
+/// Parse the JSON printed by `helm status -o json`
+fn parse_status(output: &str) -> Result<HelmReleaseInfo> {
+    let status: serde_json::Value = serde_json::from_str(output)
+        .map_err(|e| AppError::Internal(format!("Unexpected helm status output: {}", e)))?;
+    let revision = status["version"]
+        .as_i64()
+        .ok_or_else(|| AppError::Internal("helm status reported no revision".to_string()))?;
+    Ok(HelmReleaseInfo {
+        revision: revision as i32,
+        chart_version: status["chart"]["metadata"]["version"]
+            .as_str()
+            .map(String::from),
+        values: match &status["config"] {
+            serde_json::Value::Null => serde_json::json!({}),
+            config => config.clone(),
+        },
+    })
+}
+
+/// Command line for `helm upgrade --install`
+fn upgrade_install_args(release: &HelmRelease) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "upgrade",
+        "--install",
+        &release.name,
+        &release.chart,
+        "-n",
+        &release.namespace,
+    ]
+    .map(String::from)
+    .to_vec();
+
+    if release.create_namespace {
+        args.push("--create-namespace".to_string());
+    }
+    if let Some(version) = &release.version {
+        args.push("--version".to_string());
+        args.push(version.clone());
+    }
+    if release.reuse_values {
+        args.push("--reuse-values".to_string());
+    }
+    if !release.values.is_null() {
+        args.push("--values".to_string());
+        args.push("-".to_string());
+    }
+    for value in &release.set {
+        args.push("--set".to_string());
+        args.push(value.clone());
+    }
+    for value in &release.set_string {
+        args.push("--set-string".to_string());
+        args.push(value.clone());
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_install_args() {
+        let release = HelmRelease {
+            name: "qbittorrent".to_string(),
+            chart: "oci://registry/qbittorrent".to_string(),
+            namespace: "qbittorrent".to_string(),
+            create_namespace: true,
+            set: vec!["vpn.enabled=true".to_string()],
+            set_string: vec!["vpn.firewallOutboundSubnets=10.0.0.0/8,192.168.0.0/16".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            upgrade_install_args(&release),
+            vec![
+                "upgrade",
+                "--install",
+                "qbittorrent",
+                "oci://registry/qbittorrent",
+                "-n",
+                "qbittorrent",
+                "--create-namespace",
+                "--set",
+                "vpn.enabled=true",
+                "--set-string",
+                "vpn.firewallOutboundSubnets=10.0.0.0/8,192.168.0.0/16",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_install_args_without_overrides() {
+        let release = HelmRelease {
+            name: "sonarr".to_string(),
+            chart: "/app/charts/sonarr".to_string(),
+            namespace: "media".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            upgrade_install_args(&release),
+            vec![
+                "upgrade",
+                "--install",
+                "sonarr",
+                "/app/charts/sonarr",
+                "-n",
+                "media"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_install_args_pinned_version() {
+        let release = HelmRelease {
+            name: "kubarr".to_string(),
+            chart: "oci://ghcr.io/bmartensnl/kubarr/charts/kubarr".to_string(),
+            namespace: "kubarr".to_string(),
+            version: Some("0.3.0".to_string()),
+            reuse_values: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            upgrade_install_args(&release)[6..],
+            ["--version", "0.3.0", "--reuse-values"]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_install_args_values() {
+        let release = HelmRelease {
+            name: "sonarr".to_string(),
+            chart: "/app/charts/sonarr".to_string(),
+            namespace: "sonarr".to_string(),
+            set: vec!["storage.hostPath.enabled=true".to_string()],
+            values: serde_json::json!({"sonarr": {"env": {"TZ": "Europe/Amsterdam"}}}),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            upgrade_install_args(&release)[6..],
+            ["--values", "-", "--set", "storage.hostPath.enabled=true"]
+        );
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let output = r#"{
+            "name": "kubarr",
+            "version": 4,
+            "chart": {"metadata": {"name": "kubarr", "version": "0.2.1"}},
+            "config": {"backend": {"service": {"type": "NodePort"}}}
+        }"#;
+        let info = parse_status(output).unwrap();
+        assert_eq!(info.revision, 4);
+        assert_eq!(info.chart_version.as_deref(), Some("0.2.1"));
+        assert_eq!(info.values["backend"]["service"]["type"], "NodePort");
+
+        let info = parse_status(r#"{"version": 1, "config": null}"#).unwrap();
+        assert_eq!(info.values, serde_json::json!({}));
+        assert!(parse_status("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subprocess_reports_missing_binary() {
+        let helm = SubprocessHelm::new("/nonexistent/helm");
+        let err = helm.uninstall("sonarr", "sonarr").await.unwrap_err();
+        assert!(err.to_string().contains("Failed to run helm"));
+    }
+}
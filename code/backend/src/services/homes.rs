@@ -0,0 +1,521 @@
+//! Per-user home folders in storage roots
+//!
+//! A root with `homes` configured gives every user who may write to it a
+//! folder at `<homes folder>/<username>`. [`HomeUsageTask`] creates missing
+//! homes, measures each one and records the size in `storage_homes`. A user
+//! is notified once when their home reaches the soft quota
+//! (`storage_quota_warning`) and once when it reaches the hard quota
+//! (`storage_quota_exceeded`); dropping back below re-arms the warning.
+//!
+//! While a home is at its hard quota, creating folders in it, uploading to
+//! it and copying or moving files into it are refused, through the storage
+//! API as well as over WebDAV. Enforcement uses the size measured at the
+//! last scan plus the size of the incoming data, so a home can overshoot by
+//! what was written between two scans. Admins can override the quotas of a
+//! single user.
+
+use std::path::{Component, Path};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use super::access::{get_user_permissions, get_user_role_names};
+use super::notification::NotificationService;
+use super::quotas::format_bytes;
+use super::scheduler::PeriodicTask;
+use super::storage_roots::{self, is_folder_name, HomeFolders, StorageRoot};
+use super::tenants;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, StorageView, StorageWrite};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::{storage_home, user};
+use crate::state::DbConn;
+
+/// `notified_level` of a home at its soft quota
+pub const LEVEL_SOFT: &str = "soft";
+
+/// `notified_level` of a home at its hard quota
+pub const LEVEL_HARD: &str = "hard";
+
+/// A home folder and its usage, as listed by `GET /api/storage/homes`
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct HomeInfo {
+    pub root: String,
+    pub user_id: i64,
+    pub username: String,
+    /// Path of the home relative to its root
+    pub path: String,
+    /// Size at the last scan
+    pub used_bytes: i64,
+    /// Effective soft quota: the user's override or the root's
+    pub soft_quota_bytes: Option<i64>,
+    /// Effective hard quota: the user's override or the root's
+    pub hard_quota_bytes: Option<i64>,
+    /// Whether either quota is overridden for this user
+    pub overridden: bool,
+    pub scanned_at: Option<DateTime<Utc>>,
+}
+
+/// Quotas of one user's home; `None` falls back to the root's quota
+#[derive(Debug, Clone, Default, Deserialize, utoipa::ToSchema)]
+pub struct HomeQuotaOverride {
+    #[serde(default)]
+    pub soft_quota_bytes: Option<i64>,
+    #[serde(default)]
+    pub hard_quota_bytes: Option<i64>,
+}
+
+/// Path of a user's home relative to its root
+pub fn home_path(homes: &HomeFolders, username: &str) -> String {
+    format!("{}/{}", homes.folder, username)
+}
+
+/// Username of the home a path relative to the root lies in, after
+/// resolving `.` and `..` lexically
+pub fn owner_of(homes: &HomeFolders, relative: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            _ => {}
+        }
+    }
+    match parts.as_slice() {
+        [folder, owner, ..] if folder.to_str() == Some(homes.folder.as_str()) => {
+            owner.to_str().map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+/// Total size of the files under `path`, without following symlinks
+pub fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if metadata.is_file() {
+        return metadata.len();
+    }
+    if !metadata.is_dir() {
+        return 0;
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| disk_usage(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// The quota level `used` has reached
+pub fn level(used: i64, soft: Option<i64>, hard: Option<i64>) -> Option<&'static str> {
+    if hard.is_some_and(|hard| used >= hard) {
+        Some(LEVEL_HARD)
+    } else if soft.is_some_and(|soft| used >= soft) {
+        Some(LEVEL_SOFT)
+    } else {
+        None
+    }
+}
+
+fn rank(level: Option<&str>) -> u8 {
+    match level {
+        Some(LEVEL_HARD) => 2,
+        Some(LEVEL_SOFT) => 1,
+        _ => 0,
+    }
+}
+
+/// Soft and hard quota of a home: the user's overrides, else the root's
+fn limits(homes: &HomeFolders, row: Option<&storage_home::Model>) -> (Option<i64>, Option<i64>) {
+    (
+        row.and_then(|r| r.soft_quota_bytes)
+            .or(homes.soft_quota_bytes()),
+        row.and_then(|r| r.hard_quota_bytes)
+            .or(homes.hard_quota_bytes()),
+    )
+}
+
+fn info(
+    root: &StorageRoot,
+    homes: &HomeFolders,
+    user: &user::Model,
+    row: Option<&storage_home::Model>,
+) -> HomeInfo {
+    let (soft_quota_bytes, hard_quota_bytes) = limits(homes, row);
+    HomeInfo {
+        root: root.name.clone(),
+        user_id: user.id,
+        username: user.username.clone(),
+        path: home_path(homes, &user.username),
+        used_bytes: row.map_or(0, |r| r.used_bytes),
+        soft_quota_bytes,
+        hard_quota_bytes,
+        overridden: row
+            .is_some_and(|r| r.soft_quota_bytes.is_some() || r.hard_quota_bytes.is_some()),
+        scanned_at: row.and_then(|r| r.scanned_at),
+    }
+}
+
+async fn find(db: &DbConn, root: &str, user_id: i64) -> Result<Option<storage_home::Model>> {
+    Ok(StorageHome::find()
+        .filter(storage_home::Column::Root.eq(root))
+        .filter(storage_home::Column::UserId.eq(user_id))
+        .one(db)
+        .await?)
+}
+
+/// Whether a user gets a home in `root`: active, in one of the root's
+/// tenants, and allowed to write there
+async fn has_home(db: &DbConn, root: &StorageRoot, user: &user::Model) -> bool {
+    if !user.is_active || !is_folder_name(&user.username) {
+        return false;
+    }
+    let tenants = tenants::scope_names(db, user.id)
+        .await
+        .unwrap_or(Some(Vec::new()));
+    if !root.in_tenants(tenants.as_deref()) {
+        return false;
+    }
+    let roles = get_user_role_names(db, user.id).await;
+    let permissions = get_user_permissions(db, user.id).await;
+    [StorageView::NAME, StorageWrite::NAME]
+        .iter()
+        .all(|p| root.allows(&roles, p) && permissions.iter().any(|granted| granted == p))
+}
+
+/// Roots with homes, each with its homes settings
+async fn home_roots(db: &DbConn) -> Result<Vec<(StorageRoot, HomeFolders)>> {
+    Ok(storage_roots::configured_roots(db)
+        .await?
+        .into_iter()
+        .filter_map(|root| {
+            let homes = root.homes.clone()?;
+            Some((root, homes))
+        })
+        .collect())
+}
+
+/// Homes of one user, or of every user, across all roots with homes
+pub async fn list(db: &DbConn, user_id: Option<i64>) -> Result<Vec<HomeInfo>> {
+    let mut query = User::find().order_by_asc(user::Column::Username);
+    if let Some(user_id) = user_id {
+        query = query.filter(user::Column::Id.eq(user_id));
+    }
+    let users = query.all(db).await?;
+
+    let mut homes = Vec::new();
+    for (root, settings) in home_roots(db).await? {
+        for user in &users {
+            if has_home(db, &root, user).await {
+                let row = find(db, &root.name, user.id).await?;
+                homes.push(info(&root, &settings, user, row.as_ref()));
+            }
+        }
+    }
+    Ok(homes)
+}
+
+/// Override the quotas of one user's home in `root_name`
+pub async fn set_override(
+    db: &DbConn,
+    root_name: &str,
+    user_id: i64,
+    quota: &HomeQuotaOverride,
+) -> Result<HomeInfo> {
+    let (root, homes) = home_roots(db)
+        .await?
+        .into_iter()
+        .find(|(root, _)| root.name == root_name)
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Storage root '{}' has no home folders", root_name))
+        })?;
+    let user = User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    if [quota.soft_quota_bytes, quota.hard_quota_bytes]
+        .iter()
+        .flatten()
+        .any(|bytes| *bytes < 0)
+    {
+        return Err(AppError::BadRequest(
+            "Quotas cannot be negative".to_string(),
+        ));
+    }
+
+    let soft = quota.soft_quota_bytes.or(homes.soft_quota_bytes());
+    let hard = quota.hard_quota_bytes.or(homes.hard_quota_bytes());
+    if let (Some(soft), Some(hard)) = (soft, hard) {
+        if soft > hard {
+            return Err(AppError::BadRequest(
+                "The soft quota cannot exceed the hard quota".to_string(),
+            ));
+        }
+    }
+
+    let now = Utc::now();
+    let row = match find(db, &root.name, user.id).await? {
+        Some(row) => {
+            let mut active: storage_home::ActiveModel = row.into();
+            active.soft_quota_bytes = Set(quota.soft_quota_bytes);
+            active.hard_quota_bytes = Set(quota.hard_quota_bytes);
+            active.updated_at = Set(now);
+            active.update(db).await?
+        }
+        None => {
+            storage_home::ActiveModel {
+                root: Set(root.name.clone()),
+                user_id: Set(user.id),
+                used_bytes: Set(0),
+                soft_quota_bytes: Set(quota.soft_quota_bytes),
+                hard_quota_bytes: Set(quota.hard_quota_bytes),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    Ok(info(&root, &homes, &user, Some(&row)))
+}
+
+// ============================================================================
+// Enforcement
+// ============================================================================
+
+/// Why writing `incoming` bytes to a path relative to `root` would break
+/// the hard quota of the home it lies in, if it would
+pub async fn over_quota(
+    db: &DbConn,
+    root: &StorageRoot,
+    relative: &Path,
+    incoming: u64,
+) -> Result<Option<String>> {
+    let Some(homes) = &root.homes else {
+        return Ok(None);
+    };
+    let Some(owner) = owner_of(homes, relative) else {
+        return Ok(None);
+    };
+    let Some(user) = User::find()
+        .filter(user::Column::Username.eq(&owner))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let row = find(db, &root.name, user.id).await?;
+    let (_, hard) = limits(homes, row.as_ref());
+    let Some(hard) = hard else {
+        return Ok(None);
+    };
+    let used = row.map_or(0, |r| r.used_bytes);
+    let incoming = i64::try_from(incoming).unwrap_or(i64::MAX);
+    let path = home_path(homes, &owner);
+    if used >= hard {
+        Ok(Some(format!(
+            "The home folder {} is over its hard quota: {} of {} used",
+            path,
+            format_bytes(used),
+            format_bytes(hard)
+        )))
+    } else if used.saturating_add(incoming) > hard {
+        Ok(Some(format!(
+            "Writing {} would exceed the hard quota of the home folder {}: {} of {} used",
+            format_bytes(incoming),
+            path,
+            format_bytes(used),
+            format_bytes(hard)
+        )))
+    } else {
+        Ok(None)
+    }
+}
+
+/// [`over_quota`] as an error, for the storage API
+pub async fn enforce(
+    db: &DbConn,
+    root: &StorageRoot,
+    relative: &Path,
+    incoming: u64,
+) -> Result<()> {
+    match over_quota(db, root, relative, incoming).await? {
+        Some(reason) => Err(AppError::Forbidden(reason)),
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// Scanning
+// ============================================================================
+
+/// Create missing homes, measure every home and notify users whose home
+/// reached a new quota level; returns the number of homes scanned
+pub async fn scan(db: &DbConn, notification: &NotificationService) -> Result<usize> {
+    let users = User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?;
+    let mut scanned = 0;
+    for (root, homes) in home_roots(db).await? {
+        let Ok(base) = root.resolve().canonicalize() else {
+            continue;
+        };
+        for user in &users {
+            if !has_home(db, &root, user).await {
+                continue;
+            }
+            let dir = base.join(&homes.folder).join(&user.username);
+            if !dir.exists() {
+                std::fs::create_dir_all(&dir).map_err(|e| {
+                    AppError::Internal(format!("Failed to create home folder: {}", e))
+                })?;
+            }
+            let used = i64::try_from(disk_usage(&dir)).unwrap_or(i64::MAX);
+            record(db, notification, &root, &homes, user, used).await?;
+            scanned += 1;
+        }
+    }
+    Ok(scanned)
+}
+
+/// Store a measured size and notify the user if their home reached a
+/// higher quota level than they were last told about
+async fn record(
+    db: &DbConn,
+    notification: &NotificationService,
+    root: &StorageRoot,
+    homes: &HomeFolders,
+    user: &user::Model,
+    used: i64,
+) -> Result<()> {
+    let now = Utc::now();
+    let row = find(db, &root.name, user.id).await?;
+    let (soft, hard) = limits(homes, row.as_ref());
+    let reached = level(used, soft, hard);
+    let notified = row.as_ref().and_then(|r| r.notified_level.clone());
+
+    match row {
+        Some(row) => {
+            let mut active: storage_home::ActiveModel = row.into();
+            active.used_bytes = Set(used);
+            active.notified_level = Set(reached.map(str::to_string));
+            active.scanned_at = Set(Some(now));
+            active.updated_at = Set(now);
+            active.update(db).await?;
+        }
+        None => {
+            storage_home::ActiveModel {
+                root: Set(root.name.clone()),
+                user_id: Set(user.id),
+                used_bytes: Set(used),
+                notified_level: Set(reached.map(str::to_string)),
+                scanned_at: Set(Some(now)),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+
+    if rank(reached) <= rank(notified.as_deref()) {
+        return Ok(());
+    }
+    let (action, limit) = match reached {
+        Some(LEVEL_HARD) => (AuditAction::StorageQuotaExceeded, hard),
+        _ => (AuditAction::StorageQuotaWarning, soft),
+    };
+    let details = format!(
+        "{} on {} uses {} of {}",
+        home_path(homes, &user.username),
+        root.name,
+        format_bytes(used),
+        format_bytes(limit.unwrap_or_default())
+    );
+    notification
+        .notify_user_event(&action, user.id, Some(&user.username), Some(&details))
+        .await
+}
+
+/// Measures home folders and warns users approaching their quota
+pub struct HomeUsageTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for HomeUsageTask {
+    fn name(&self) -> &'static str {
+        "storage_home_usage"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let scanned = scan(db, &self.notification).await?;
+        tracing::debug!("Measured {} home folders", scanned);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn homes() -> HomeFolders {
+        HomeFolders {
+            folder: "home".to_string(),
+            soft_quota: Some("1Ki".to_string()),
+            hard_quota: Some("2Ki".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_owner_of() {
+        let homes = homes();
+        assert_eq!(
+            owner_of(&homes, Path::new("home/alice/photos/a.jpg")),
+            Some("alice".to_string())
+        );
+        assert_eq!(
+            owner_of(&homes, Path::new("/home/bob/../alice")),
+            Some("alice".to_string())
+        );
+        assert_eq!(owner_of(&homes, Path::new("home")), None);
+        assert_eq!(owner_of(&homes, Path::new("home/alice/../..")), None);
+        assert_eq!(owner_of(&homes, Path::new("media/alice")), None);
+    }
+
+    #[test]
+    fn test_level() {
+        let homes = homes();
+        let (soft, hard) = limits(&homes, None);
+        assert_eq!(level(1023, soft, hard), None);
+        assert_eq!(level(1024, soft, hard), Some(LEVEL_SOFT));
+        assert_eq!(level(4096, soft, hard), Some(LEVEL_HARD));
+        assert_eq!(level(4096, None, None), None);
+        assert!(rank(Some(LEVEL_HARD)) > rank(Some(LEVEL_SOFT)));
+    }
+
+    #[test]
+    fn test_disk_usage() {
+        let dir = std::env::temp_dir().join(format!("kubarr-homes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("a/b")).unwrap();
+        std::fs::write(dir.join("one"), b"12345").unwrap();
+        std::fs::write(dir.join("a/b/two"), b"123").unwrap();
+        assert_eq!(disk_usage(&dir), 8);
+        assert_eq!(disk_usage(&dir.join("missing")), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
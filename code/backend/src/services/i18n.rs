@@ -0,0 +1,254 @@
+//! Locales for text sent to users
+//!
+//! Notifications are rendered in the recipient's locale: the `locale` in
+//! their preferences, or the `default_locale` setting for users who did not
+//! pick one. Besides the translated event text (see
+//! [`crate::services::notification::locales`]), dates and decimal numbers
+//! are formatted the way the locale writes them.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::user_preferences;
+use crate::state::DbConn;
+
+/// Setting holding the locale of users without a preference
+pub const DEFAULT_LOCALE_SETTING: &str = "default_locale";
+
+/// A supported locale
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Nl,
+    De,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Nl => "nl",
+            Locale::De => "de",
+        }
+    }
+
+    /// Parse a language tag such as `nl`, `nl-BE` or `de_DE`
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "nl" => Some(Locale::Nl),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    pub fn all() -> Vec<Locale> {
+        vec![Locale::En, Locale::Nl, Locale::De]
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self {
+            Locale::En => '.',
+            Locale::Nl | Locale::De => ',',
+        }
+    }
+
+    fn group_separator(&self) -> char {
+        match self {
+            Locale::En => ',',
+            Locale::Nl | Locale::De => '.',
+        }
+    }
+
+    fn month_abbreviations(&self) -> [&'static str; 12] {
+        match self {
+            Locale::En => [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            Locale::Nl => [
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            Locale::De => [
+                "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.",
+                "Nov.", "Dez.",
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Parse a locale from a preference or setting, rejecting unsupported ones
+pub fn parse_locale(value: &str) -> Result<Locale> {
+    Locale::parse(value).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Unsupported locale '{}', expected one of: {}",
+            value,
+            Locale::all()
+                .iter()
+                .map(|l| l.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    })
+}
+
+/// Locale of users who did not choose one
+pub async fn default_locale(db: &DbConn) -> Result<Locale> {
+    Ok(get_setting_value(db, DEFAULT_LOCALE_SETTING)
+        .await?
+        .and_then(|v| Locale::parse(&v))
+        .unwrap_or_default())
+}
+
+/// Locale a user's notifications are written in
+pub async fn user_locale(db: &DbConn, user_id: i64) -> Result<Locale> {
+    let preferred = user_preferences::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .and_then(|p| p.locale)
+        .and_then(|l| Locale::parse(&l));
+    match preferred {
+        Some(locale) => Ok(locale),
+        None => default_locale(db).await,
+    }
+}
+
+/// A date and time in UTC, e.g. `Jan 5, 2026, 14:03 UTC` or
+/// `5 jan 2026 14:03 UTC`
+pub fn format_datetime(time: &DateTime<Utc>, locale: Locale) -> String {
+    let month = locale.month_abbreviations()[time.month0() as usize];
+    let (day, year, hour, minute) = (time.day(), time.year(), time.hour(), time.minute());
+    match locale {
+        Locale::En => format!("{month} {day}, {year}, {hour:02}:{minute:02} UTC"),
+        Locale::Nl => format!("{day} {month} {year} {hour:02}:{minute:02} UTC"),
+        Locale::De => format!("{day}. {month} {year}, {hour:02}:{minute:02} UTC"),
+    }
+}
+
+/// A number with `decimals` digits after the separator and grouped
+/// thousands, e.g. `1,234.5` or `1.234,5`
+pub fn format_number(value: f64, decimals: usize, locale: Locale) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = formatted
+        .split_once('.')
+        .map_or((formatted.as_str(), None), |(i, f)| (i, Some(f)));
+
+    let mut out = String::with_capacity(formatted.len() + int_part.len() / 3 + 1);
+    if value.is_sign_negative() && formatted.chars().any(|c| c != '0' && c != '.') {
+        out.push('-');
+    }
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            out.push(locale.group_separator());
+        }
+        out.push(digit);
+    }
+    if let Some(frac) = frac_part {
+        out.push(locale.decimal_separator());
+        out.push_str(frac);
+    }
+    out
+}
+
+/// Rewrite the decimal numbers in a measurement, e.g. `at 85.5°C`, in the
+/// locale's notation
+///
+/// Only numbers with a fractional part that stand on their own are touched,
+/// so versions (`1.2.3`), addresses and integers in links stay as they are.
+pub fn localize_numbers(text: &str, locale: Locale) -> String {
+    if locale == Locale::En {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let standalone = i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '.');
+        if !chars[i].is_ascii_digit() || !standalone {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+            i += 1;
+        }
+        let token: String = chars[start..i].iter().collect();
+        let followed_by_word = i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '/');
+        match token.split_once('.') {
+            Some((int_part, frac))
+                if !int_part.is_empty()
+                    && !frac.is_empty()
+                    && !frac.contains('.')
+                    && !followed_by_word =>
+            {
+                match token.parse::<f64>() {
+                    Ok(value) => out.push_str(&format_number(value, frac.len(), locale)),
+                    Err(_) => out.push_str(&token),
+                }
+            }
+            _ => out.push_str(&token),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("nl"), Some(Locale::Nl));
+        assert_eq!(Locale::parse("de_DE"), Some(Locale::De));
+        assert_eq!(Locale::parse(" EN-gb "), Some(Locale::En));
+        assert_eq!(Locale::parse("fr"), None);
+        assert!(parse_locale("xx").is_err());
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let time = Utc.with_ymd_and_hms(2026, 3, 5, 9, 7, 0).unwrap();
+        assert_eq!(format_datetime(&time, Locale::En), "Mar 5, 2026, 09:07 UTC");
+        assert_eq!(format_datetime(&time, Locale::Nl), "5 mrt 2026 09:07 UTC");
+        assert_eq!(
+            format_datetime(&time, Locale::De),
+            "5. März 2026, 09:07 UTC"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1234567.891, 2, Locale::En), "1,234,567.89");
+        assert_eq!(format_number(1234567.891, 2, Locale::Nl), "1.234.567,89");
+        assert_eq!(format_number(-12.5, 1, Locale::De), "-12,5");
+        assert_eq!(format_number(999.0, 0, Locale::De), "999");
+        assert_eq!(format_number(-0.01, 1, Locale::En), "0.0");
+    }
+
+    #[test]
+    fn test_localize_numbers() {
+        assert_eq!(
+            localize_numbers("node1 CPU at 85.5°C, uses 1.2 GiB of 50 GiB", Locale::Nl),
+            "node1 CPU at 85,5°C, uses 1,2 GiB of 50 GiB"
+        );
+        assert_eq!(
+            localize_numbers("z = 3.2 — /logs?start=1700000000.5x&v=1.2.3", Locale::De),
+            "z = 3,2 — /logs?start=1700000000.5x&v=1.2.3"
+        );
+        assert_eq!(localize_numbers("1234.5 ms", Locale::De), "1.234,5 ms");
+        assert_eq!(localize_numbers("85.5°C", Locale::En), "85.5°C");
+    }
+}
@@ -0,0 +1,213 @@
+//! Idle app auto-suspend
+//!
+//! When `idle_suspend_enabled` is on, apps nobody has used for
+//! `idle_suspend_days` are scaled to zero to free cluster resources. An app's
+//! last activity is the latest of its last proxied request, its most recent
+//! `app_accessed` audit entry (logged when it is opened from the dashboard),
+//! and when it was installed or started. A suspended app is woken by its next
+//! proxied request through the maintenance page. Apps can opt out with
+//! `idle_suspend_exempt`; system apps and apps stopped by hand are left alone.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use super::catalog::AppCatalog;
+use super::deployment::DeploymentManager;
+use super::helm;
+use super::installed_apps;
+use super::k8s::K8sApi;
+use super::maintenance::{self, AppRunState};
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use crate::endpoints::settings::{get_setting_bool, get_setting_value};
+use crate::error::Result;
+use crate::models::audit_log::{self, AuditAction};
+use crate::models::prelude::*;
+use crate::state::{SharedCatalog, SharedK8sClient};
+
+/// Setting enabling the policy
+pub const IDLE_SUSPEND_ENABLED: &str = "idle_suspend_enabled";
+
+/// Setting holding the number of idle days before an app is suspended
+pub const IDLE_SUSPEND_DAYS: &str = "idle_suspend_days";
+
+/// Idle days used when the setting is not a positive number
+const DEFAULT_IDLE_DAYS: i64 = 14;
+
+/// Proxied requests to one app are written to the database at most this often
+const ACCESS_RECORD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How often idle apps are looked for
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// When each app's last proxied request was written to the database
+static LAST_RECORDED: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Note a proxied request to an app
+///
+/// Called on every proxied request, so the database is only written once per
+/// [`ACCESS_RECORD_INTERVAL`] per app.
+pub async fn note_access(db: &DatabaseConnection, app_name: &str) {
+    let due = {
+        let Ok(mut recorded) = LAST_RECORDED.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        match recorded.get(app_name) {
+            Some(last) if now.duration_since(*last) < ACCESS_RECORD_INTERVAL => false,
+            _ => {
+                recorded.insert(app_name.to_string(), now);
+                true
+            }
+        }
+    };
+    if due {
+        if let Err(e) = installed_apps::record_access(db, app_name, Utc::now()).await {
+            tracing::warn!("Failed to record access to app '{}': {}", app_name, e);
+        }
+    }
+}
+
+/// Configured idle days, or `None` while the policy is disabled
+pub async fn idle_days(db: &DatabaseConnection) -> Result<Option<i64>> {
+    if !get_setting_bool(db, IDLE_SUSPEND_ENABLED).await? {
+        return Ok(None);
+    }
+    let days = get_setting_value(db, IDLE_SUSPEND_DAYS)
+        .await?
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|d| *d > 0)
+        .unwrap_or(DEFAULT_IDLE_DAYS);
+    Ok(Some(days))
+}
+
+/// Latest activity recorded for an app, if any
+async fn last_activity(
+    db: &DatabaseConnection,
+    app_name: &str,
+    record: Option<&crate::models::installed_app::Model>,
+) -> Result<Option<DateTime<Utc>>> {
+    let audited = AuditLog::find()
+        .filter(audit_log::Column::ResourceId.eq(app_name))
+        .filter(audit_log::Column::Action.is_in([
+            AuditAction::AppAccessed.to_string(),
+            AuditAction::AppInstalled.to_string(),
+            AuditAction::AppStarted.to_string(),
+        ]))
+        .order_by_desc(audit_log::Column::Timestamp)
+        .one(db)
+        .await?
+        .map(|entry| entry.timestamp);
+
+    Ok([
+        audited,
+        record.and_then(|r| r.last_accessed_at),
+        record.map(|r| r.installed_at),
+    ]
+    .into_iter()
+    .flatten()
+    .max())
+}
+
+/// Suspend deployed apps without activity for the configured number of days
+///
+/// Returns the names of the suspended apps.
+pub async fn suspend_idle_apps(
+    db: &DatabaseConnection,
+    k8s: &dyn K8sApi,
+    manager: &DeploymentManager<'_>,
+    catalog: &AppCatalog,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>> {
+    let Some(days) = idle_days(db).await? else {
+        return Ok(Vec::new());
+    };
+    let cutoff = now - chrono::Duration::days(days);
+
+    let mut suspended = Vec::new();
+    let mut apps = manager.get_deployed_apps().await;
+    apps.sort();
+    for app_name in apps {
+        if catalog.get_app(&app_name).is_some_and(|app| app.is_system) {
+            continue;
+        }
+        let record = InstalledApp::find_by_id(&app_name).one(db).await?;
+        if record
+            .as_ref()
+            .is_some_and(|r| r.idle_suspend_exempt || r.suspended_at.is_some())
+        {
+            continue;
+        }
+        // Stopped by hand; starting it is up to the user
+        if maintenance::app_run_status(k8s, &app_name).await?.state == AppRunState::Stopped {
+            continue;
+        }
+
+        match last_activity(db, &app_name, record.as_ref()).await? {
+            Some(last) if last > cutoff => continue,
+            Some(_) => {}
+            // Nothing known about the app yet; start counting from now
+            None => {
+                installed_apps::record_access(db, &app_name, now).await?;
+                continue;
+            }
+        }
+
+        match manager.stop_app(&app_name).await {
+            Ok(_) => {
+                installed_apps::mark_suspended(db, &app_name, now).await?;
+                tracing::info!("Suspended app '{}' after {} idle days", app_name, days);
+                suspended.push(app_name);
+            }
+            Err(e) => tracing::warn!("Failed to suspend idle app '{}': {}", app_name, e),
+        }
+    }
+    Ok(suspended)
+}
+
+/// Periodically suspends idle apps and notifies about them
+pub struct IdleSuspendTask {
+    pub k8s_client: SharedK8sClient,
+    pub catalog: SharedCatalog,
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for IdleSuspendTask {
+    fn name(&self) -> &'static str {
+        "idle_suspend"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let Some(days) = idle_days(db).await? else {
+            return Ok(());
+        };
+        let k8s_guard = self.k8s_client.read().await;
+        let Some(k8s) = k8s_guard.as_ref() else {
+            return Ok(());
+        };
+        let catalog = self.catalog.read().await;
+        let helm = helm::from_config();
+        let manager = DeploymentManager::with_db(k8s, helm.as_ref(), &catalog, db);
+
+        let suspended = suspend_idle_apps(db, k8s, &manager, &catalog, Utc::now()).await?;
+        if !suspended.is_empty() {
+            let detail = format!("{} (no access for {} days)", suspended.join(", "), days);
+            self.notification
+                .notify_event(&AuditAction::AppsSuspended, None, None, Some(&detail))
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,557 @@
+//! Installed app metadata
+//!
+//! Helm decides which apps are deployed; this table only stores what Kubarr
+//! and its users add on top of a deployment, such as notes, tags and the
+//! installed chart version. Rows are created on
+//! install and removed on uninstall. Apps deployed before the table existed
+//! get a row the first time their metadata is edited.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::installed_app;
+use crate::models::prelude::*;
+use crate::services::app_routing::RoutingMode;
+use crate::services::catalog::AppCatalog;
+use crate::services::updates::Version;
+use crate::state::DbConn;
+
+/// Maximum number of tags per app
+const MAX_TAGS: usize = 20;
+
+/// Maximum length of a single tag
+const MAX_TAG_LEN: usize = 32;
+
+/// Maximum length of the notes field
+const MAX_NOTES_LEN: usize = 2000;
+
+/// An installed app with its user-editable metadata
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct InstalledAppInfo {
+    pub name: String,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    /// Catalog app this instance was cloned from
+    pub cloned_from: Option<String>,
+    /// When Kubarr installed the app; for apps deployed before metadata was
+    /// tracked, when their metadata was first edited
+    pub installed_at: Option<DateTime<Utc>>,
+    /// Excluded from idle auto-suspend
+    pub idle_suspend_exempt: bool,
+    /// Set while the app is suspended for being idle
+    pub suspended_at: Option<DateTime<Utc>>,
+    /// Apps with a lower priority are started first after a reboot
+    pub boot_priority: i32,
+    /// Apps that must be healthy before this one starts
+    pub depends_on: Vec<String>,
+    /// Tenant the app belongs to; `None` if shared
+    pub tenant_id: Option<i64>,
+    /// Chart version of the last install or upgrade, if known
+    pub chart_version: Option<String>,
+    /// Chart version currently offered by the catalog
+    pub latest_version: Option<String>,
+    /// The catalog offers a newer chart than the installed one
+    pub update_available: bool,
+}
+
+impl InstalledAppInfo {
+    fn new(name: String, record: Option<&installed_app::Model>) -> Self {
+        Self {
+            name,
+            notes: record.and_then(|r| r.notes.clone()),
+            tags: record.map(|r| parse_tags(&r.tags)).unwrap_or_default(),
+            cloned_from: record.and_then(|r| r.cloned_from.clone()),
+            installed_at: record.map(|r| r.installed_at),
+            idle_suspend_exempt: record.is_some_and(|r| r.idle_suspend_exempt),
+            suspended_at: record.and_then(|r| r.suspended_at),
+            boot_priority: record.map(|r| r.boot_priority).unwrap_or_default(),
+            depends_on: record
+                .map(|r| parse_depends_on(&r.depends_on))
+                .unwrap_or_default(),
+            tenant_id: record.and_then(|r| r.tenant_id),
+            chart_version: record.and_then(|r| r.chart_version.clone()),
+            latest_version: None,
+            update_available: false,
+        }
+    }
+
+    /// Compare the installed chart against the version `catalog` offers for
+    /// the app, or for the catalog app it was cloned from
+    pub fn with_catalog(mut self, catalog: &AppCatalog) -> Self {
+        let catalog_app = self.cloned_from.as_deref().unwrap_or(&self.name);
+        self.latest_version = catalog
+            .get_app(catalog_app)
+            .map(|app| app.version.clone())
+            .filter(|v| !v.is_empty());
+        self.update_available = match (&self.chart_version, &self.latest_version) {
+            (Some(installed), Some(latest)) => is_newer(latest, installed),
+            _ => false,
+        };
+        self
+    }
+
+    /// Whether the app carries `tag`, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateInstalledAppMetadata {
+    /// Empty or missing clears the notes
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateIdleSuspend {
+    /// Keep the app running however long it goes unused
+    pub exempt: bool,
+}
+
+fn parse_tags(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+/// Whether chart version `candidate` is newer than `installed`
+///
+/// Versions that are not semver are compared for equality only, so any other
+/// version counts as newer.
+pub fn is_newer(candidate: &str, installed: &str) -> bool {
+    match (Version::parse(candidate), Version::parse(installed)) {
+        (Some(candidate), Some(installed)) => candidate > installed,
+        _ => candidate != installed,
+    }
+}
+
+/// Parse the stored `depends_on` JSON array
+pub fn parse_depends_on(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+/// Trim tags, drop case-insensitive duplicates and enforce the limits
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Tag '{}' is longer than {} characters",
+                tag, MAX_TAG_LEN
+            )));
+        }
+        if !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::BadRequest(format!(
+            "An app can have at most {} tags",
+            MAX_TAGS
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Attach stored metadata to the deployed apps reported by Helm
+pub async fn with_metadata(db: &DbConn, app_names: Vec<String>) -> Result<Vec<InstalledAppInfo>> {
+    let records: HashMap<String, installed_app::Model> = InstalledApp::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|r| (r.app_name.clone(), r))
+        .collect();
+
+    Ok(app_names
+        .into_iter()
+        .map(|name| {
+            let record = records.get(&name);
+            InstalledAppInfo::new(name, record)
+        })
+        .collect())
+}
+
+/// Records of cloned instances, ordered by name
+pub async fn list_clones(db: &DbConn) -> Result<Vec<installed_app::Model>> {
+    Ok(InstalledApp::find()
+        .filter(installed_app::Column::ClonedFrom.is_not_null())
+        .order_by_asc(installed_app::Column::AppName)
+        .all(db)
+        .await?)
+}
+
+/// Catalog app a recorded clone was installed from
+pub async fn clone_source(db: &DbConn, app_name: &str) -> Result<Option<String>> {
+    Ok(InstalledApp::find_by_id(app_name)
+        .one(db)
+        .await?
+        .and_then(|r| r.cloned_from))
+}
+
+/// Create the metadata row for a freshly installed app, keeping any existing
+/// notes and tags
+///
+/// `cloned_from` names the catalog app when `app_name` is a cloned instance.
+pub async fn record_install(db: &DbConn, app_name: &str, cloned_from: Option<&str>) -> Result<()> {
+    if InstalledApp::find_by_id(app_name).one(db).await?.is_some() {
+        return Ok(());
+    }
+    let now = Utc::now();
+    installed_app::ActiveModel {
+        app_name: Set(app_name.to_string()),
+        notes: Set(None),
+        tags: Set("[]".to_string()),
+        cloned_from: Set(cloned_from.map(str::to_string)),
+        routing_mode: Set(RoutingMode::Path.as_str().to_string()),
+        subdomain: Set(None),
+        stopped_replicas: Set(None),
+        last_accessed_at: Set(None),
+        idle_suspend_exempt: Set(false),
+        suspended_at: Set(None),
+        boot_priority: Set(0),
+        depends_on: Set("[]".to_string()),
+        boot_held_at: Set(None),
+        installed_by: Set(None),
+        cpu_millicores: Set(0),
+        memory_bytes: Set(0),
+        storage_bytes: Set(0),
+        tenant_id: Set(None),
+        chart_version: Set(None),
+        value_overrides: Set(None),
+        installed_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Drop the metadata of an uninstalled app
+pub async fn forget(db: &DbConn, app_name: &str) -> Result<()> {
+    InstalledApp::delete_by_id(app_name).exec(db).await?;
+    Ok(())
+}
+
+/// Chart version of an app's last install or upgrade, if known
+pub async fn chart_version(db: &DbConn, app_name: &str) -> Result<Option<String>> {
+    Ok(InstalledApp::find_by_id(app_name)
+        .one(db)
+        .await?
+        .and_then(|r| r.chart_version))
+}
+
+/// Record the chart version an app was installed or upgraded with
+pub async fn set_chart_version(db: &DbConn, app_name: &str, version: &str) -> Result<()> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    let version = version.to_string();
+    upsert(db, app_name, existing, |model| {
+        model.chart_version = Set(Some(version));
+    })
+    .await
+}
+
+/// Helm values set on top of the app's chart defaults; an empty object if
+/// none are set
+pub async fn value_overrides(db: &DbConn, app_name: &str) -> Result<serde_json::Value> {
+    let saved = InstalledApp::find_by_id(app_name)
+        .one(db)
+        .await?
+        .and_then(|r| r.value_overrides);
+    Ok(match saved {
+        Some(json) => serde_json::from_str(&json)?,
+        None => serde_json::json!({}),
+    })
+}
+
+/// Save the Helm values set on top of the app's chart defaults
+pub async fn set_value_overrides(
+    db: &DbConn,
+    app_name: &str,
+    overrides: &serde_json::Value,
+) -> Result<()> {
+    let json = serde_json::to_string(overrides)?;
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.value_overrides = Set(Some(json));
+    })
+    .await
+}
+
+/// Replica counts saved when the app was stopped, by deployment name
+pub async fn stopped_replicas(db: &DbConn, app_name: &str) -> Result<HashMap<String, i32>> {
+    let saved = InstalledApp::find_by_id(app_name)
+        .one(db)
+        .await?
+        .and_then(|r| r.stopped_replicas);
+    Ok(match saved {
+        Some(json) => serde_json::from_str(&json)?,
+        None => HashMap::new(),
+    })
+}
+
+/// Save the replica counts of a stopped app, or clear them with `None` once
+/// it is started again, which also ends an idle suspension or boot hold
+pub async fn set_stopped_replicas(
+    db: &DbConn,
+    app_name: &str,
+    replicas: Option<&HashMap<String, i32>>,
+) -> Result<()> {
+    let json = replicas.map(serde_json::to_string).transpose()?;
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    // Nothing to clear for an app without metadata
+    if existing.is_none() && json.is_none() {
+        return Ok(());
+    }
+    let clear = json.is_none();
+    upsert(db, app_name, existing, |model| {
+        model.stopped_replicas = Set(json);
+        if clear {
+            model.suspended_at = Set(None);
+            model.boot_held_at = Set(None);
+        }
+    })
+    .await
+}
+
+/// Mark an app stopped by the idle auto-suspend policy
+pub async fn mark_suspended(db: &DbConn, app_name: &str, at: DateTime<Utc>) -> Result<()> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.suspended_at = Set(Some(at));
+    })
+    .await
+}
+
+/// Whether an app is currently suspended for being idle
+pub async fn is_suspended(db: &DbConn, app_name: &str) -> Result<bool> {
+    Ok(InstalledApp::find_by_id(app_name)
+        .one(db)
+        .await?
+        .is_some_and(|r| r.suspended_at.is_some()))
+}
+
+/// Record a proxied request to an app
+pub async fn record_access(db: &DbConn, app_name: &str, at: DateTime<Utc>) -> Result<()> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.last_accessed_at = Set(Some(at));
+    })
+    .await
+}
+
+/// Exclude an app from idle auto-suspend, or include it again
+pub async fn set_idle_suspend_exempt(
+    db: &DbConn,
+    app_name: &str,
+    exempt: bool,
+) -> Result<InstalledAppInfo> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.idle_suspend_exempt = Set(exempt);
+    })
+    .await?;
+    let record = InstalledApp::find_by_id(app_name).one(db).await?;
+    Ok(InstalledAppInfo::new(app_name.to_string(), record.as_ref()))
+}
+
+/// Assign an app to a tenant, or share it again with `None`
+pub async fn set_tenant(
+    db: &DbConn,
+    app_name: &str,
+    tenant_id: Option<i64>,
+) -> Result<InstalledAppInfo> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.tenant_id = Set(tenant_id);
+    })
+    .await?;
+    let record = InstalledApp::find_by_id(app_name).one(db).await?;
+    Ok(InstalledAppInfo::new(app_name.to_string(), record.as_ref()))
+}
+
+/// Mark an app held at zero replicas until its prerequisites are healthy,
+/// or clear the mark with `None`
+pub async fn set_boot_held(db: &DbConn, app_name: &str, at: Option<DateTime<Utc>>) -> Result<()> {
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    if existing.is_none() && at.is_none() {
+        return Ok(());
+    }
+    upsert(db, app_name, existing, |model| {
+        model.boot_held_at = Set(at);
+    })
+    .await
+}
+
+/// Replace an app's boot priority and dependencies
+///
+/// Callers validate the dependencies; see `boot_order::validate`.
+pub async fn set_boot_order(
+    db: &DbConn,
+    app_name: &str,
+    priority: i32,
+    depends_on: &[String],
+) -> Result<InstalledAppInfo> {
+    let json = serde_json::to_string(depends_on)?;
+    let existing = InstalledApp::find_by_id(app_name).one(db).await?;
+    upsert(db, app_name, existing, |model| {
+        model.boot_priority = Set(priority);
+        model.depends_on = Set(json);
+    })
+    .await?;
+    let record = InstalledApp::find_by_id(app_name).one(db).await?;
+    Ok(InstalledAppInfo::new(app_name.to_string(), record.as_ref()))
+}
+
+/// Apply `change` to an app's row, creating the row for apps deployed before
+/// metadata was tracked
+async fn upsert(
+    db: &DbConn,
+    app_name: &str,
+    existing: Option<installed_app::Model>,
+    change: impl FnOnce(&mut installed_app::ActiveModel),
+) -> Result<()> {
+    let now = Utc::now();
+    match existing {
+        Some(existing) => {
+            let mut model: installed_app::ActiveModel = existing.into();
+            change(&mut model);
+            model.updated_at = Set(now);
+            model.update(db).await?;
+        }
+        None => {
+            let mut model = installed_app::ActiveModel {
+                app_name: Set(app_name.to_string()),
+                notes: Set(None),
+                tags: Set("[]".to_string()),
+                cloned_from: Set(None),
+                routing_mode: Set(RoutingMode::Path.as_str().to_string()),
+                subdomain: Set(None),
+                stopped_replicas: Set(None),
+                last_accessed_at: Set(None),
+                idle_suspend_exempt: Set(false),
+                suspended_at: Set(None),
+                boot_priority: Set(0),
+                depends_on: Set("[]".to_string()),
+                boot_held_at: Set(None),
+                installed_by: Set(None),
+                cpu_millicores: Set(0),
+                memory_bytes: Set(0),
+                storage_bytes: Set(0),
+                tenant_id: Set(None),
+                chart_version: Set(None),
+                value_overrides: Set(None),
+                installed_at: Set(now),
+                updated_at: Set(now),
+            };
+            change(&mut model);
+            model.insert(db).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the notes and tags of an installed app
+pub async fn update_metadata(
+    db: &DbConn,
+    app_name: &str,
+    update: UpdateInstalledAppMetadata,
+) -> Result<InstalledAppInfo> {
+    let notes = update
+        .notes
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    if notes
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NOTES_LEN)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Notes must be at most {} characters",
+            MAX_NOTES_LEN
+        )));
+    }
+    let tags = normalize_tags(update.tags)?;
+    let tags_json = serde_json::to_string(&tags)?;
+    let now = Utc::now();
+
+    let record = match InstalledApp::find_by_id(app_name).one(db).await? {
+        Some(existing) => {
+            let mut model: installed_app::ActiveModel = existing.into();
+            model.notes = Set(notes);
+            model.tags = Set(tags_json);
+            model.updated_at = Set(now);
+            model.update(db).await?
+        }
+        None => {
+            installed_app::ActiveModel {
+                app_name: Set(app_name.to_string()),
+                notes: Set(notes),
+                tags: Set(tags_json),
+                cloned_from: Set(None),
+                routing_mode: Set(RoutingMode::Path.as_str().to_string()),
+                subdomain: Set(None),
+                stopped_replicas: Set(None),
+                last_accessed_at: Set(None),
+                idle_suspend_exempt: Set(false),
+                suspended_at: Set(None),
+                boot_priority: Set(0),
+                depends_on: Set("[]".to_string()),
+                boot_held_at: Set(None),
+                installed_by: Set(None),
+                cpu_millicores: Set(0),
+                memory_bytes: Set(0),
+                storage_bytes: Set(0),
+                tenant_id: Set(None),
+                chart_version: Set(None),
+                value_overrides: Set(None),
+                // Deployed before metadata was tracked; first sighting is the
+                // best estimate
+                installed_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?
+        }
+    };
+
+    Ok(InstalledAppInfo::new(app_name.to_string(), Some(&record)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = normalize_tags(vec![
+            " family instance ".to_string(),
+            "Testing".to_string(),
+            "testing".to_string(),
+            "".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["family instance", "Testing"]);
+
+        assert!(normalize_tags(vec!["x".repeat(MAX_TAG_LEN + 1)]).is_err());
+        assert!(normalize_tags((0..=MAX_TAGS).map(|i| i.to_string()).collect()).is_err());
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("1.10.0", "1.9.2"));
+        assert!(!is_newer("1.9.2", "1.10.0"));
+        assert!(!is_newer("1.2.0", "1.2.0"));
+        assert!(is_newer("1.2.0", "1.2.0-rc.1"));
+        // Not semver: anything different is offered
+        assert!(is_newer("2024.06", "2024.05"));
+        assert!(!is_newer("2024.06", "2024.06"));
+    }
+}
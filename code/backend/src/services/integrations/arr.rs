@@ -0,0 +1,297 @@
+//! Sonarr/Radarr adapter
+//!
+//! Both apps share the same v3 API, authenticated with an `X-Api-Key` header.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::calendar::{CalendarEntry, CalendarMediaKind, ReleaseType};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::Result;
+
+/// Which *arr app the adapter talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrKind {
+    Sonarr,
+    Radarr,
+}
+
+impl ArrKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArrKind::Sonarr => "sonarr",
+            ArrKind::Radarr => "radarr",
+        }
+    }
+}
+
+/// Adapter for Sonarr and Radarr
+pub struct ArrAdapter {
+    kind: ArrKind,
+}
+
+impl ArrAdapter {
+    pub fn new(kind: ArrKind) -> Self {
+        Self { kind }
+    }
+}
+
+/// Response from GET /api/v3/queue/status
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArrQueueStatus {
+    #[serde(default)]
+    pub total_count: u64,
+    #[serde(default)]
+    pub count: u64,
+    #[serde(default)]
+    pub errors: bool,
+    #[serde(default)]
+    pub warnings: bool,
+}
+
+/// Convert a queue status into dashboard metrics
+pub fn queue_metrics(status: &ArrQueueStatus) -> Vec<NativeMetric> {
+    vec![
+        NativeMetric::new("queue_size", "Queue size", status.total_count as f64, None),
+        NativeMetric::new("queue_visible", "Queued items", status.count as f64, None),
+        NativeMetric::new(
+            "queue_errors",
+            "Queue errors",
+            if status.errors { 1.0 } else { 0.0 },
+            None,
+        ),
+        NativeMetric::new(
+            "queue_warnings",
+            "Queue warnings",
+            if status.warnings { 1.0 } else { 0.0 },
+            None,
+        ),
+    ]
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SonarrSeries {
+    #[serde(default)]
+    title: String,
+}
+
+/// An episode from Sonarr's GET /api/v3/calendar
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SonarrEpisode {
+    #[serde(default)]
+    series: Option<SonarrSeries>,
+    #[serde(default)]
+    pub season_number: u32,
+    #[serde(default)]
+    pub episode_number: u32,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub air_date_utc: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub has_file: bool,
+    #[serde(default)]
+    pub monitored: bool,
+}
+
+/// A movie from Radarr's GET /api/v3/calendar
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RadarrMovie {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub in_cinemas: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub digital_release: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub physical_release: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub has_file: bool,
+    #[serde(default)]
+    pub monitored: bool,
+}
+
+/// Convert Sonarr episodes into calendar entries
+pub fn episode_entries(episodes: &[SonarrEpisode]) -> Vec<CalendarEntry> {
+    episodes
+        .iter()
+        .filter_map(|ep| {
+            let date = ep.air_date_utc?;
+            let series = ep
+                .series
+                .as_ref()
+                .map(|s| s.title.clone())
+                .unwrap_or_default();
+            Some(CalendarEntry {
+                app_name: ArrKind::Sonarr.as_str().to_string(),
+                kind: CalendarMediaKind::Episode,
+                release_type: ReleaseType::Airing,
+                title: series,
+                subtitle: Some(format!(
+                    "S{:02}E{:02} - {}",
+                    ep.season_number, ep.episode_number, ep.title
+                )),
+                date,
+                has_file: ep.has_file,
+                monitored: ep.monitored,
+            })
+        })
+        .collect()
+}
+
+/// Convert Radarr movies into calendar entries
+///
+/// Radarr returns a movie if any of its release dates fall in the range, so
+/// only the dates inside `[start, end)` become entries.
+pub fn movie_entries(
+    movies: &[RadarrMovie],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<CalendarEntry> {
+    let mut entries = Vec::new();
+    for movie in movies {
+        let releases = [
+            (ReleaseType::Cinema, movie.in_cinemas),
+            (ReleaseType::Digital, movie.digital_release),
+            (ReleaseType::Physical, movie.physical_release),
+        ];
+        for (release_type, date) in releases {
+            let Some(date) = date.filter(|d| *d >= start && *d < end) else {
+                continue;
+            };
+            entries.push(CalendarEntry {
+                app_name: ArrKind::Radarr.as_str().to_string(),
+                kind: CalendarMediaKind::Movie,
+                release_type,
+                title: movie.title.clone(),
+                subtitle: None,
+                date,
+                has_file: movie.has_file,
+                monitored: movie.monitored,
+            });
+        }
+    }
+    entries
+}
+
+#[async_trait]
+impl IntegrationAdapter for ArrAdapter {
+    fn kind(&self) -> &'static str {
+        self.kind.as_str()
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let app = self.kind.as_str();
+        let api_key = ctx.require_api_key()?;
+
+        let resp = ctx
+            .client()
+            .get(ctx.url("/api/v3/queue/status"))
+            .header("X-Api-Key", api_key)
+            .send()
+            .await
+            .map_err(|e| upstream_error(app, e))?;
+
+        let status: ArrQueueStatus = check_status(app, resp)?
+            .json()
+            .await
+            .map_err(|e| upstream_error(app, e))?;
+
+        Ok(queue_metrics(&status))
+    }
+
+    fn is_calendar_source(&self) -> bool {
+        true
+    }
+
+    async fn calendar(
+        &self,
+        ctx: &IntegrationContext,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEntry>> {
+        let app = self.kind.as_str();
+        let api_key = ctx.require_api_key()?;
+
+        let resp = ctx
+            .client()
+            .get(ctx.url("/api/v3/calendar"))
+            .header("X-Api-Key", api_key)
+            .query(&[
+                ("start", start.to_rfc3339()),
+                ("end", end.to_rfc3339()),
+                ("includeSeries", "true".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| upstream_error(app, e))?;
+        let resp = check_status(app, resp)?;
+
+        match self.kind {
+            ArrKind::Sonarr => {
+                let episodes: Vec<SonarrEpisode> =
+                    resp.json().await.map_err(|e| upstream_error(app, e))?;
+                Ok(episode_entries(&episodes))
+            }
+            ArrKind::Radarr => {
+                let movies: Vec<RadarrMovie> =
+                    resp.json().await.map_err(|e| upstream_error(app, e))?;
+                Ok(movie_entries(&movies, start, end))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_status_parse() {
+        let json = r#"{"totalCount":7,"count":5,"unknownCount":2,"errors":true,"warnings":false}"#;
+        let status: ArrQueueStatus = serde_json::from_str(json).unwrap();
+        let metrics = queue_metrics(&status);
+
+        assert_eq!(metrics[0].key, "queue_size");
+        assert_eq!(metrics[0].value, 7.0);
+        assert_eq!(metrics[1].value, 5.0);
+        assert_eq!(metrics[2].value, 1.0);
+        assert_eq!(metrics[3].value, 0.0);
+    }
+
+    #[test]
+    fn test_episode_entries() {
+        let json = r#"[
+            {"series":{"title":"The Show"},"seasonNumber":1,"episodeNumber":2,"title":"Second","airDateUtc":"2026-10-17T01:00:00Z","hasFile":false,"monitored":true},
+            {"seasonNumber":1,"episodeNumber":3,"title":"TBA"}
+        ]"#;
+        let episodes: Vec<SonarrEpisode> = serde_json::from_str(json).unwrap();
+        let entries = episode_entries(&episodes);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "The Show");
+        assert_eq!(entries[0].subtitle.as_deref(), Some("S01E02 - Second"));
+        assert_eq!(entries[0].release_type, ReleaseType::Airing);
+        assert!(entries[0].monitored);
+    }
+
+    #[test]
+    fn test_movie_entries_only_in_range() {
+        let json = r#"[
+            {"title":"Movie","inCinemas":"2026-09-01T00:00:00Z","digitalRelease":"2026-10-20T00:00:00Z","physicalRelease":"2026-12-01T00:00:00Z","hasFile":false,"monitored":true}
+        ]"#;
+        let movies: Vec<RadarrMovie> = serde_json::from_str(json).unwrap();
+        let start = "2026-10-16T00:00:00Z".parse().unwrap();
+        let end = "2026-10-23T00:00:00Z".parse().unwrap();
+        let entries = movie_entries(&movies, start, end);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].release_type, ReleaseType::Digital);
+        assert_eq!(entries[0].kind, CalendarMediaKind::Movie);
+    }
+}
@@ -0,0 +1,254 @@
+//! Media calendar aggregation
+//!
+//! Combines upcoming episode and movie releases from Sonarr and Radarr into
+//! one date-sorted list. Per-app results are cached in `AppState` so repeated
+//! dashboard loads don't hit the *arr APIs every time.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::future::join_all;
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+
+use super::{adapter_for, build_context};
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::state::AppState;
+
+/// Default number of days shown when no end date is given
+pub const DEFAULT_CALENDAR_DAYS: i64 = 7;
+/// Maximum range a single calendar request may cover
+pub const MAX_CALENDAR_DAYS: i64 = 90;
+
+/// Kind of media in a calendar entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarMediaKind {
+    Episode,
+    Movie,
+}
+
+/// Which release a calendar entry represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseType {
+    /// TV air date
+    Airing,
+    Cinema,
+    Digital,
+    Physical,
+}
+
+/// A single upcoming release
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CalendarEntry {
+    /// App the entry came from (e.g., "sonarr")
+    pub app_name: String,
+    pub kind: CalendarMediaKind,
+    pub release_type: ReleaseType,
+    /// Series or movie title
+    pub title: String,
+    /// Episode details (e.g., "S01E02 - Pilot"); None for movies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    pub date: DateTime<Utc>,
+    /// Whether the media file is already downloaded
+    pub has_file: bool,
+    pub monitored: bool,
+}
+
+/// Per-source status in the aggregated calendar
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CalendarSourceStatus {
+    pub app_name: String,
+    pub available: bool,
+    pub cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response for GET /api/integrations/calendar
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MediaCalendar {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub entries: Vec<CalendarEntry>,
+    pub sources: Vec<CalendarSourceStatus>,
+}
+
+/// Apps whose adapter provides a release calendar
+pub fn calendar_sources() -> Vec<&'static str> {
+    super::supported_apps()
+        .into_iter()
+        .filter(|app| adapter_for(app).is_some_and(|a| a.is_calendar_source()))
+        .collect()
+}
+
+/// Validate and default a requested date range (end is inclusive)
+pub fn resolve_range(
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate)> {
+    let start = start.unwrap_or(today);
+    let end = end.unwrap_or(start + chrono::Duration::days(DEFAULT_CALENDAR_DAYS));
+
+    if end < start {
+        return Err(AppError::BadRequest(
+            "end must not be before start".to_string(),
+        ));
+    }
+    if (end - start).num_days() > MAX_CALENDAR_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "Calendar range may not exceed {} days",
+            MAX_CALENDAR_DAYS
+        )));
+    }
+    Ok((start, end))
+}
+
+/// Convert an inclusive date range to UTC timestamps for the app APIs
+fn range_bounds(start: NaiveDate, end: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let from = start.and_time(chrono::NaiveTime::MIN).and_utc();
+    let to = (end + chrono::Duration::days(1))
+        .and_time(chrono::NaiveTime::MIN)
+        .and_utc();
+    (from, to)
+}
+
+/// Fetch one source's entries, using the integration cache
+async fn fetch_source(
+    state: &AppState,
+    app_name: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> (CalendarSourceStatus, Vec<CalendarEntry>) {
+    let cache_key = format!("{}:calendar:{}:{}", app_name, start, end);
+    if let Some(cached) = state.integration_cache.get(&cache_key).await {
+        if let Ok(entries) = serde_json::from_value::<Vec<CalendarEntry>>(cached) {
+            let status = CalendarSourceStatus {
+                app_name: app_name.to_string(),
+                available: true,
+                cached: true,
+                error: None,
+            };
+            return (status, entries);
+        }
+    }
+
+    let (from, to) = range_bounds(start, end);
+    let result = match adapter_for(app_name) {
+        Some(adapter) => match build_context(state, app_name).await {
+            Ok(ctx) => adapter.calendar(&ctx, from, to).await,
+            Err(e) => Err(e),
+        },
+        None => Ok(Vec::new()),
+    };
+
+    match result {
+        Ok(entries) => {
+            if let Ok(value) = serde_json::to_value(&entries) {
+                state.integration_cache.set(&cache_key, value).await;
+            }
+            let status = CalendarSourceStatus {
+                app_name: app_name.to_string(),
+                available: true,
+                cached: false,
+                error: None,
+            };
+            (status, entries)
+        }
+        Err(e) => {
+            tracing::debug!("Failed to fetch calendar from {}: {}", app_name, e);
+            let status = CalendarSourceStatus {
+                app_name: app_name.to_string(),
+                available: false,
+                cached: false,
+                error: Some(e.to_string()),
+            };
+            (status, Vec::new())
+        }
+    }
+}
+
+/// Build the combined calendar from all enabled calendar-source integrations
+/// the caller can see
+///
+/// `allowed_apps` is the caller's app access list (`["*"]` for all apps).
+pub async fn aggregate_calendar(
+    state: &AppState,
+    allowed_apps: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<MediaCalendar> {
+    let db = state.get_db().await?;
+    let configured = AppIntegration::find().all(&db).await?;
+
+    let sources = calendar_sources();
+    let visible: Vec<String> = configured
+        .into_iter()
+        .filter(|i| i.enabled && sources.contains(&i.app_name.as_str()))
+        .filter(|i| allowed_apps.iter().any(|a| a == "*" || *a == i.app_name))
+        .map(|i| i.app_name)
+        .collect();
+
+    let results = join_all(
+        visible
+            .iter()
+            .map(|app_name| fetch_source(state, app_name, start, end)),
+    )
+    .await;
+
+    let mut entries = Vec::new();
+    let mut statuses = Vec::new();
+    for (status, app_entries) in results {
+        statuses.push(status);
+        entries.extend(app_entries);
+    }
+    entries.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.title.cmp(&b.title)));
+
+    Ok(MediaCalendar {
+        start,
+        end,
+        entries,
+        sources: statuses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_calendar_sources() {
+        let sources = calendar_sources();
+        assert!(sources.contains(&"sonarr"));
+        assert!(sources.contains(&"radarr"));
+        assert!(!sources.contains(&"qbittorrent"));
+    }
+
+    #[test]
+    fn test_resolve_range_defaults() {
+        let today = date("2026-10-16");
+        let (start, end) = resolve_range(None, None, today).unwrap();
+        assert_eq!(start, today);
+        assert_eq!(end, date("2026-10-23"));
+    }
+
+    #[test]
+    fn test_resolve_range_rejects_invalid() {
+        let today = date("2026-10-16");
+        assert!(resolve_range(Some(today), Some(date("2026-10-01")), today).is_err());
+        assert!(resolve_range(Some(today), Some(date("2027-10-01")), today).is_err());
+    }
+
+    #[test]
+    fn test_range_bounds_inclusive_end() {
+        let (from, to) = range_bounds(date("2026-10-16"), date("2026-10-16"));
+        assert_eq!(from.to_rfc3339(), "2026-10-16T00:00:00+00:00");
+        assert_eq!(to.to_rfc3339(), "2026-10-17T00:00:00+00:00");
+    }
+}
@@ -0,0 +1,208 @@
+//! Download queue aggregation
+//!
+//! Merges active downloads from every configured download client
+//! (qBittorrent, SABnzbd, Transmission) into one normalized list.
+
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::EntityTrait;
+use serde::Serialize;
+
+use super::{adapter_for, build_context};
+use crate::error::Result;
+use crate::models::prelude::*;
+use crate::state::AppState;
+
+/// Normalized download state across clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    Downloading,
+    Stalled,
+    Checking,
+    Queued,
+    Paused,
+    Error,
+}
+
+/// A single active download
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DownloadItem {
+    /// App the download belongs to (e.g., "qbittorrent")
+    pub app_name: String,
+    /// Client-specific identifier (torrent hash, nzo_id, ...)
+    pub id: String,
+    pub name: String,
+    pub state: DownloadState,
+    /// Progress from 0.0 to 1.0
+    pub progress: f64,
+    pub size_bytes: u64,
+    /// Download rate in bytes/s
+    pub download_rate: u64,
+    /// Upload rate in bytes/s
+    pub upload_rate: u64,
+    /// Estimated seconds remaining, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// Per-client summary in the aggregated queue
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DownloadClientSummary {
+    pub app_name: String,
+    pub available: bool,
+    pub item_count: usize,
+    pub download_rate: u64,
+    pub upload_rate: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response for GET /api/integrations/downloads
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DownloadQueue {
+    pub items: Vec<DownloadItem>,
+    pub clients: Vec<DownloadClientSummary>,
+    pub total_download_rate: u64,
+    pub total_upload_rate: u64,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Apps whose adapter can list downloads
+pub fn download_clients() -> Vec<&'static str> {
+    super::supported_apps()
+        .into_iter()
+        .filter(|app| adapter_for(app).is_some_and(|a| a.is_download_client()))
+        .collect()
+}
+
+/// Sort items so active downloads come first, then by soonest completion
+fn sort_items(items: &mut [DownloadItem]) {
+    items.sort_by(|a, b| {
+        a.state
+            .cmp(&b.state)
+            .then_with(|| {
+                a.eta_seconds
+                    .unwrap_or(u64::MAX)
+                    .cmp(&b.eta_seconds.unwrap_or(u64::MAX))
+            })
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Fetch and merge downloads from all enabled download-client integrations
+/// the caller can see
+///
+/// `allowed_apps` is the caller's app access list (`["*"]` for all apps).
+/// A failing client is reported in `clients` and doesn't fail the request.
+pub async fn aggregate_downloads(
+    state: &AppState,
+    allowed_apps: &[String],
+) -> Result<DownloadQueue> {
+    let db = state.get_db().await?;
+    let configured = AppIntegration::find().all(&db).await?;
+
+    let clients = download_clients();
+    let visible: Vec<String> = configured
+        .into_iter()
+        .filter(|i| i.enabled && clients.contains(&i.app_name.as_str()))
+        .filter(|i| allowed_apps.iter().any(|a| a == "*" || *a == i.app_name))
+        .map(|i| i.app_name)
+        .collect();
+
+    let results = join_all(visible.iter().map(|app_name| async move {
+        let result = match adapter_for(app_name) {
+            Some(adapter) => match build_context(state, app_name).await {
+                Ok(ctx) => adapter.downloads(&ctx).await,
+                Err(e) => Err(e),
+            },
+            None => Ok(Vec::new()),
+        };
+        (app_name.clone(), result)
+    }))
+    .await;
+
+    let mut items = Vec::new();
+    let mut summaries = Vec::new();
+    for (app_name, result) in results {
+        match result {
+            Ok(app_items) => {
+                summaries.push(DownloadClientSummary {
+                    app_name,
+                    available: true,
+                    item_count: app_items.len(),
+                    download_rate: app_items.iter().map(|i| i.download_rate).sum(),
+                    upload_rate: app_items.iter().map(|i| i.upload_rate).sum(),
+                    error: None,
+                });
+                items.extend(app_items);
+            }
+            Err(e) => {
+                tracing::debug!("Failed to fetch downloads from {}: {}", app_name, e);
+                summaries.push(DownloadClientSummary {
+                    app_name,
+                    available: false,
+                    item_count: 0,
+                    download_rate: 0,
+                    upload_rate: 0,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    sort_items(&mut items);
+
+    Ok(DownloadQueue {
+        total_download_rate: summaries.iter().map(|s| s.download_rate).sum(),
+        total_upload_rate: summaries.iter().map(|s| s.upload_rate).sum(),
+        items,
+        clients: summaries,
+        fetched_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, state: DownloadState, eta: Option<u64>) -> DownloadItem {
+        DownloadItem {
+            app_name: "qbittorrent".to_string(),
+            id: name.to_string(),
+            name: name.to_string(),
+            state,
+            progress: 0.5,
+            size_bytes: 0,
+            download_rate: 0,
+            upload_rate: 0,
+            eta_seconds: eta,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn test_download_clients() {
+        let clients = download_clients();
+        assert!(clients.contains(&"qbittorrent"));
+        assert!(clients.contains(&"sabnzbd"));
+        assert!(clients.contains(&"transmission"));
+        assert!(!clients.contains(&"sonarr"));
+    }
+
+    #[test]
+    fn test_sort_items() {
+        let mut items = vec![
+            item("paused", DownloadState::Paused, None),
+            item("slow", DownloadState::Downloading, Some(600)),
+            item("unknown", DownloadState::Downloading, None),
+            item("fast", DownloadState::Downloading, Some(60)),
+            item("queued", DownloadState::Queued, None),
+        ];
+        sort_items(&mut items);
+        let names: Vec<&str> = items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["fast", "slow", "unknown", "queued", "paused"]);
+    }
+}
@@ -0,0 +1,445 @@
+//! Jellyfin adapter
+//!
+//! Reads active sessions from `/Sessions`, authenticated with an API key
+//! passed in the `X-Emby-Token` header. Also reads users and libraries and
+//! writes user policies, for media profiles (see
+//! [`crate::services::media_profiles`]), and creates and deletes users for
+//! account provisioning.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::transcodes::{PlaybackDecision, PlaybackSession};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::Result;
+
+const APP: &str = "jellyfin";
+
+/// Adapter for Jellyfin
+pub struct JellyfinAdapter;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinMediaStream {
+    #[serde(default, rename = "Type")]
+    pub stream_type: Option<String>,
+    #[serde(default)]
+    pub codec: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinNowPlaying {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub series_name: Option<String>,
+    #[serde(default)]
+    pub media_streams: Vec<JellyfinMediaStream>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinTranscodingInfo {
+    #[serde(default)]
+    pub video_codec: Option<String>,
+    #[serde(default)]
+    pub audio_codec: Option<String>,
+    #[serde(default)]
+    pub is_video_direct: bool,
+    #[serde(default)]
+    pub is_audio_direct: bool,
+    #[serde(default)]
+    pub completion_percentage: Option<f64>,
+    /// e.g. "vaapi", "qsv", "nvenc"; "none" or absent for software
+    #[serde(default)]
+    pub hardware_acceleration_type: Option<String>,
+    #[serde(default)]
+    pub transcode_reasons: Vec<String>,
+}
+
+/// A session entry from GET /Sessions (only the fields we use)
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinSession {
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    pub now_playing_item: Option<JellyfinNowPlaying>,
+    #[serde(default)]
+    pub transcoding_info: Option<JellyfinTranscodingInfo>,
+}
+
+impl JellyfinSession {
+    /// Convert a session with something playing into a playback session
+    pub fn into_playback(self) -> Option<PlaybackSession> {
+        let item = self.now_playing_item?;
+        let title = match (&item.series_name, &item.name) {
+            (Some(series), Some(name)) => format!("{} - {}", series, name),
+            (None, Some(name)) => name.clone(),
+            (Some(series), None) => series.clone(),
+            (None, None) => "Unknown".to_string(),
+        };
+        let source_video_codec = item
+            .media_streams
+            .iter()
+            .find(|s| s.stream_type.as_deref() == Some("Video"))
+            .and_then(|s| s.codec.clone());
+        let source_audio_codec = item
+            .media_streams
+            .iter()
+            .find(|s| s.stream_type.as_deref() == Some("Audio"))
+            .and_then(|s| s.codec.clone());
+
+        let session = match self.transcoding_info {
+            Some(info) => {
+                let decision = if info.is_video_direct && info.is_audio_direct {
+                    PlaybackDecision::DirectStream
+                } else {
+                    PlaybackDecision::Transcode
+                };
+                let hardware_acceleration = info
+                    .hardware_acceleration_type
+                    .filter(|t| !t.is_empty() && !t.eq_ignore_ascii_case("none"))
+                    .filter(|_| decision == PlaybackDecision::Transcode);
+                PlaybackSession {
+                    user: self.user_name,
+                    title,
+                    device: self.device_name,
+                    decision,
+                    source_video_codec,
+                    video_codec: info.video_codec,
+                    audio_codec: info.audio_codec.or(source_audio_codec),
+                    hardware_acceleration,
+                    progress: info.completion_percentage,
+                    reasons: info.transcode_reasons,
+                }
+            }
+            None => PlaybackSession {
+                user: self.user_name,
+                title,
+                device: self.device_name,
+                decision: PlaybackDecision::DirectPlay,
+                video_codec: source_video_codec.clone(),
+                source_video_codec,
+                audio_codec: source_audio_codec,
+                hardware_acceleration: None,
+                progress: None,
+                reasons: Vec::new(),
+            },
+        };
+        Some(session)
+    }
+}
+
+/// Convert sessions into dashboard metrics
+pub fn session_metrics(sessions: &[JellyfinSession]) -> Vec<NativeMetric> {
+    let streams: Vec<&JellyfinSession> = sessions
+        .iter()
+        .filter(|s| s.now_playing_item.is_some())
+        .collect();
+    let transcodes = streams
+        .iter()
+        .filter(|s| s.transcoding_info.is_some())
+        .count();
+
+    vec![
+        NativeMetric::new(
+            "active_streams",
+            "Active streams",
+            streams.len() as f64,
+            None,
+        ),
+        NativeMetric::new("transcodes", "Transcoding", transcodes as f64, None),
+        NativeMetric::new(
+            "direct_streams",
+            "Direct play",
+            (streams.len() - transcodes) as f64,
+            None,
+        ),
+        NativeMetric::new("sessions", "Sessions", sessions.len() as f64, None),
+    ]
+}
+
+/// Fetch all sessions from Jellyfin
+pub(crate) async fn fetch_sessions(ctx: &IntegrationContext) -> Result<Vec<JellyfinSession>> {
+    let api_key = ctx.require_api_key()?;
+
+    let resp = ctx
+        .client()
+        .get(ctx.url("/Sessions"))
+        .header("X-Emby-Token", api_key)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))
+}
+
+// ============================================================================
+// Users and policies
+// ============================================================================
+
+/// A user from GET /Users (only the fields we use)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinUser {
+    pub id: String,
+    pub name: String,
+    /// Kept whole, since Jellyfin replaces the policy with what is posted
+    #[serde(default)]
+    pub policy: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A library from GET /Library/VirtualFolders
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinLibrary {
+    pub name: String,
+    #[serde(default)]
+    pub item_id: Option<String>,
+}
+
+/// What a Kubarr media profile sets in a Jellyfin user policy
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicyLimits {
+    /// IDs of the libraries the user may see; `None` is every library
+    pub library_ids: Option<Vec<String>>,
+    /// Simultaneous streams; `None` is unlimited
+    pub max_streams: Option<i32>,
+    /// Highest parental rating value shown; `None` is no limit
+    pub max_parental_rating: Option<i32>,
+}
+
+/// Write `limits` into a user policy; returns whether anything changed
+pub fn apply_policy_limits(
+    policy: &mut serde_json::Map<String, serde_json::Value>,
+    limits: &PolicyLimits,
+) -> bool {
+    use serde_json::Value;
+
+    let wanted = [
+        (
+            "EnableAllFolders",
+            Value::Bool(limits.library_ids.is_none()),
+        ),
+        (
+            "EnabledFolders",
+            Value::from(limits.library_ids.clone().unwrap_or_default()),
+        ),
+        (
+            "MaxActiveSessions",
+            Value::from(limits.max_streams.unwrap_or(0)),
+        ),
+        (
+            "MaxParentalRating",
+            limits
+                .max_parental_rating
+                .map(Value::from)
+                .unwrap_or(Value::Null),
+        ),
+    ];
+
+    let mut changed = false;
+    for (key, value) in wanted {
+        if policy.get(key) != Some(&value) {
+            policy.insert(key.to_string(), value);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Fetch all users with their policies
+pub async fn fetch_users(ctx: &IntegrationContext) -> Result<Vec<JellyfinUser>> {
+    let api_key = ctx.require_api_key()?;
+    let resp = ctx
+        .client()
+        .get(ctx.url("/Users"))
+        .header("X-Emby-Token", api_key)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))
+}
+
+/// Fetch the libraries
+pub async fn fetch_libraries(ctx: &IntegrationContext) -> Result<Vec<JellyfinLibrary>> {
+    let api_key = ctx.require_api_key()?;
+    let resp = ctx
+        .client()
+        .get(ctx.url("/Library/VirtualFolders"))
+        .header("X-Emby-Token", api_key)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))
+}
+
+/// Replace a user's policy
+pub async fn update_policy(
+    ctx: &IntegrationContext,
+    user_id: &str,
+    policy: &serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    let api_key = ctx.require_api_key()?;
+    let resp = ctx
+        .client()
+        .post(ctx.url(&format!("/Users/{}/Policy", user_id)))
+        .header("X-Emby-Token", api_key)
+        .json(policy)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?;
+    Ok(())
+}
+
+/// Create a user with a password
+pub async fn create_user(
+    ctx: &IntegrationContext,
+    name: &str,
+    password: &str,
+) -> Result<JellyfinUser> {
+    let api_key = ctx.require_api_key()?;
+    let resp = ctx
+        .client()
+        .post(ctx.url("/Users/New"))
+        .header("X-Emby-Token", api_key)
+        .json(&serde_json::json!({"Name": name, "Password": password}))
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))
+}
+
+/// Delete a user
+pub async fn delete_user(ctx: &IntegrationContext, user_id: &str) -> Result<()> {
+    let api_key = ctx.require_api_key()?;
+    let resp = ctx
+        .client()
+        .delete(ctx.url(&format!("/Users/{}", user_id)))
+        .header("X-Emby-Token", api_key)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?;
+    Ok(())
+}
+
+#[async_trait]
+impl IntegrationAdapter for JellyfinAdapter {
+    fn kind(&self) -> &'static str {
+        APP
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let sessions = fetch_sessions(ctx).await?;
+        Ok(session_metrics(&sessions))
+    }
+
+    fn is_media_server(&self) -> bool {
+        true
+    }
+
+    async fn playback_sessions(&self, ctx: &IntegrationContext) -> Result<Vec<PlaybackSession>> {
+        let sessions = fetch_sessions(ctx).await?;
+        Ok(sessions
+            .into_iter()
+            .filter_map(JellyfinSession::into_playback)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_metrics() {
+        let json = r#"[
+            {"UserName":"alice","NowPlayingItem":{"Name":"Movie"},"TranscodingInfo":{"VideoCodec":"h264"}},
+            {"UserName":"bob","NowPlayingItem":{"Name":"Show"}},
+            {"UserName":"carol"}
+        ]"#;
+        let sessions: Vec<JellyfinSession> = serde_json::from_str(json).unwrap();
+        let metrics = session_metrics(&sessions);
+
+        assert_eq!(metrics[0].key, "active_streams");
+        assert_eq!(metrics[0].value, 2.0);
+        assert_eq!(metrics[1].value, 1.0);
+        assert_eq!(metrics[2].value, 1.0);
+        assert_eq!(metrics[3].value, 3.0);
+    }
+
+    #[test]
+    fn test_into_playback() {
+        let json = r#"[
+            {"UserName":"alice","DeviceName":"TV","NowPlayingItem":{"Name":"Pilot","SeriesName":"Show",
+              "MediaStreams":[{"Type":"Video","Codec":"hevc"},{"Type":"Audio","Codec":"eac3"}]},
+             "TranscodingInfo":{"VideoCodec":"h264","AudioCodec":"aac","IsVideoDirect":false,
+              "HardwareAccelerationType":"vaapi","CompletionPercentage":40.0,
+              "TranscodeReasons":["VideoCodecNotSupported"]}},
+            {"UserName":"bob","NowPlayingItem":{"Name":"Movie","MediaStreams":[{"Type":"Video","Codec":"h264"}]},
+             "TranscodingInfo":{"VideoCodec":"h264","IsVideoDirect":false,"HardwareAccelerationType":"none"}},
+            {"UserName":"carol","NowPlayingItem":{"Name":"Clip"}},
+            {"UserName":"dave"}
+        ]"#;
+        let sessions: Vec<JellyfinSession> = serde_json::from_str(json).unwrap();
+        let playback: Vec<PlaybackSession> = sessions
+            .into_iter()
+            .filter_map(JellyfinSession::into_playback)
+            .collect();
+
+        assert_eq!(playback.len(), 3);
+        assert_eq!(playback[0].title, "Show - Pilot");
+        assert_eq!(playback[0].decision, PlaybackDecision::Transcode);
+        assert_eq!(playback[0].source_video_codec.as_deref(), Some("hevc"));
+        assert_eq!(playback[0].hardware_acceleration.as_deref(), Some("vaapi"));
+        assert_eq!(playback[0].reasons, vec!["VideoCodecNotSupported"]);
+        assert!(playback[1].is_transcode());
+        assert!(!playback[1].is_hardware_transcode());
+        assert_eq!(playback[2].decision, PlaybackDecision::DirectPlay);
+    }
+
+    #[test]
+    fn test_apply_policy_limits() {
+        let mut policy: serde_json::Map<String, serde_json::Value> = serde_json::from_str(
+            r#"{"IsAdministrator":false,"EnableAllFolders":true,"EnabledFolders":[],"MaxActiveSessions":0}"#,
+        )
+        .unwrap();
+        let limits = PolicyLimits {
+            library_ids: Some(vec!["kids-movies".to_string()]),
+            max_streams: Some(2),
+            max_parental_rating: Some(10),
+        };
+
+        assert!(apply_policy_limits(&mut policy, &limits));
+        assert_eq!(policy["EnableAllFolders"], false);
+        assert_eq!(policy["EnabledFolders"][0], "kids-movies");
+        assert_eq!(policy["MaxActiveSessions"], 2);
+        assert_eq!(policy["MaxParentalRating"], 10);
+        // Fields Kubarr does not manage are kept
+        assert_eq!(policy["IsAdministrator"], false);
+        assert!(!apply_policy_limits(&mut policy, &limits));
+
+        assert!(apply_policy_limits(&mut policy, &PolicyLimits::default()));
+        assert_eq!(policy["EnableAllFolders"], true);
+        assert_eq!(policy["MaxActiveSessions"], 0);
+        assert!(policy["MaxParentalRating"].is_null());
+    }
+}
@@ -0,0 +1,584 @@
+//! App integration adapters
+//!
+//! Talks to installed apps' own HTTP APIs (Sonarr queue, qBittorrent transfer
+//! rates, Jellyfin sessions, ...) using credentials stored in the
+//! `app_integrations` table, so the dashboard can show app-native widgets
+//! instead of just pod state.
+//!
+//! Add support for a new app by implementing `IntegrationAdapter` and
+//! registering it in `adapter_for`.
+
+mod arr;
+pub mod calendar;
+pub mod downloads;
+pub mod jellyfin;
+mod plex;
+mod qbittorrent;
+pub mod requests;
+mod sabnzbd;
+pub mod seerr;
+pub mod transcodes;
+mod transmission;
+
+pub use arr::{ArrAdapter, ArrKind};
+pub use calendar::{CalendarEntry, MediaCalendar};
+pub use downloads::{DownloadItem, DownloadQueue, DownloadState};
+pub use jellyfin::JellyfinAdapter;
+pub use plex::PlexAdapter;
+pub use qbittorrent::QbittorrentAdapter;
+pub use sabnzbd::SabnzbdAdapter;
+pub use seerr::{SeerrAdapter, SeerrKind};
+pub use transcodes::{AppTranscodes, PlaybackSession};
+pub use transmission::TransmissionAdapter;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+
+use super::outbound::SharedClient;
+use crate::error::{AppError, Result};
+use crate::models::app_integration;
+use crate::models::prelude::*;
+use crate::state::{AppState, DbConn};
+
+// Shared reqwest client for app API requests
+static INTEGRATION_HTTP_CLIENT: SharedClient =
+    SharedClient::new(|builder| builder.timeout(std::time::Duration::from_secs(10)));
+
+// Same, for integrations with TLS verification turned off
+static INSECURE_INTEGRATION_HTTP_CLIENT: SharedClient = SharedClient::new(|builder| {
+    builder
+        .timeout(std::time::Duration::from_secs(10))
+        .danger_accept_invalid_certs(true)
+});
+
+/// Get the shared HTTP client for requests outside an integration
+pub(crate) fn http_client() -> reqwest::Client {
+    INTEGRATION_HTTP_CLIENT.client()
+}
+
+// ============================================================================
+// Adapter Types
+// ============================================================================
+
+/// Credentials used to authenticate against an app's API
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IntegrationCredentials {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+/// Everything an adapter needs to call an app's API
+#[derive(Debug, Clone)]
+pub struct IntegrationContext {
+    /// API root without trailing slash (service URL plus base path)
+    pub base_url: String,
+    pub credentials: IntegrationCredentials,
+    /// Whether the app's HTTPS certificate is verified
+    pub tls_verify: bool,
+}
+
+impl IntegrationContext {
+    /// HTTP client for the app's API
+    pub fn client(&self) -> reqwest::Client {
+        if self.tls_verify {
+            INTEGRATION_HTTP_CLIENT.client()
+        } else {
+            INSECURE_INTEGRATION_HTTP_CLIENT.client()
+        }
+    }
+
+    /// Build a full URL for an API path (path must start with '/')
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Get the API key or fail with a helpful message
+    pub fn require_api_key(&self) -> Result<&str> {
+        self.credentials
+            .api_key
+            .as_deref()
+            .filter(|k| !k.is_empty())
+            .ok_or_else(|| AppError::BadRequest("Integration requires an API key".to_string()))
+    }
+}
+
+/// A single app-native metric for dashboard widgets
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NativeMetric {
+    pub key: String,
+    pub label: String,
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+impl NativeMetric {
+    pub fn new(key: &str, label: &str, value: f64, unit: Option<&str>) -> Self {
+        Self {
+            key: key.to_string(),
+            label: label.to_string(),
+            value,
+            unit: unit.map(|u| u.to_string()),
+        }
+    }
+}
+
+/// Response for GET /api/apps/{app_name}/native-status
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NativeStatus {
+    pub app_name: String,
+    /// Adapter that produced the status (e.g., "sonarr", "qbittorrent")
+    pub integration: String,
+    /// Whether the app's API answered successfully
+    pub available: bool,
+    pub metrics: Vec<NativeMetric>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Trait for app-specific API adapters
+#[async_trait]
+pub trait IntegrationAdapter: Send + Sync {
+    /// Adapter identifier
+    fn kind(&self) -> &'static str;
+
+    /// Fetch app-native status metrics
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>>;
+
+    /// Whether the app is a download client (supports `downloads`)
+    fn is_download_client(&self) -> bool {
+        false
+    }
+
+    /// Fetch incomplete downloads, normalized across clients
+    async fn downloads(&self, _ctx: &IntegrationContext) -> Result<Vec<DownloadItem>> {
+        Err(AppError::BadRequest(format!(
+            "{} is not a download client",
+            self.kind()
+        )))
+    }
+
+    /// Whether the app provides a release calendar (supports `calendar`)
+    fn is_calendar_source(&self) -> bool {
+        false
+    }
+
+    /// Fetch releases between `start` (inclusive) and `end` (exclusive)
+    async fn calendar(
+        &self,
+        _ctx: &IntegrationContext,
+        _start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEntry>> {
+        Err(AppError::BadRequest(format!(
+            "{} does not provide a calendar",
+            self.kind()
+        )))
+    }
+
+    /// Whether the app is a media server (supports `playback_sessions`)
+    fn is_media_server(&self) -> bool {
+        false
+    }
+
+    /// Fetch active playback sessions, including transcode details
+    async fn playback_sessions(&self, _ctx: &IntegrationContext) -> Result<Vec<PlaybackSession>> {
+        Err(AppError::BadRequest(format!(
+            "{} is not a media server",
+            self.kind()
+        )))
+    }
+}
+
+/// Get the adapter for an app, if one exists
+pub fn adapter_for(app_name: &str) -> Option<Box<dyn IntegrationAdapter>> {
+    match app_name {
+        "sonarr" => Some(Box::new(ArrAdapter::new(ArrKind::Sonarr))),
+        "radarr" => Some(Box::new(ArrAdapter::new(ArrKind::Radarr))),
+        "qbittorrent" => Some(Box::new(QbittorrentAdapter)),
+        "jellyfin" => Some(Box::new(JellyfinAdapter)),
+        "plex" => Some(Box::new(PlexAdapter)),
+        "sabnzbd" => Some(Box::new(SabnzbdAdapter)),
+        "transmission" => Some(Box::new(TransmissionAdapter)),
+        "jellyseerr" => Some(Box::new(SeerrAdapter::new(SeerrKind::Jellyseerr))),
+        "overseerr" => Some(Box::new(SeerrAdapter::new(SeerrKind::Overseerr))),
+        _ => None,
+    }
+}
+
+/// Apps that have an integration adapter
+pub fn supported_apps() -> Vec<&'static str> {
+    vec![
+        "jellyfin",
+        "jellyseerr",
+        "overseerr",
+        "plex",
+        "qbittorrent",
+        "radarr",
+        "sabnzbd",
+        "sonarr",
+        "transmission",
+    ]
+}
+
+/// Map an upstream HTTP failure to an AppError
+pub(crate) fn upstream_error(app: &str, e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::ServiceUnavailable(format!("{} API timed out", app))
+    } else if e.is_connect() {
+        AppError::ServiceUnavailable(format!("Failed to connect to {} API: {}", app, e))
+    } else {
+        AppError::BadGateway(format!("{} API error: {}", app, e))
+    }
+}
+
+/// Check an upstream response status, mapping auth failures to a clear message
+pub(crate) fn check_status(app: &str, resp: reqwest::Response) -> Result<reqwest::Response> {
+    let status = resp.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(AppError::BadGateway(format!(
+            "{} rejected the configured credentials",
+            app
+        )));
+    }
+    if !status.is_success() {
+        return Err(AppError::BadGateway(format!(
+            "{} API returned HTTP {}",
+            app, status
+        )));
+    }
+    Ok(resp)
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// Request to configure an app integration
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct UpdateIntegrationRequest {
+    #[serde(default)]
+    pub credentials: Option<IntegrationCredentials>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Turn off to accept the app's self-signed certificate
+    #[serde(default)]
+    pub tls_verify: Option<bool>,
+}
+
+/// Integration configuration (credentials masked)
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct IntegrationResponse {
+    pub app_name: String,
+    pub integration: String,
+    pub enabled: bool,
+    pub base_url: Option<String>,
+    pub tls_verify: bool,
+    pub has_api_key: bool,
+    pub has_username: bool,
+    pub has_password: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IntegrationResponse {
+    fn from_model(model: app_integration::Model) -> Self {
+        let credentials = parse_credentials(&model.credentials_json);
+        let integration = adapter_for(&model.app_name)
+            .map(|a| a.kind().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        Self {
+            app_name: model.app_name,
+            integration,
+            enabled: model.enabled,
+            base_url: model.base_url,
+            tls_verify: model.tls_verify,
+            has_api_key: credentials.api_key.is_some_and(|k| !k.is_empty()),
+            has_username: credentials.username.is_some_and(|u| !u.is_empty()),
+            has_password: credentials.password.is_some_and(|p| !p.is_empty()),
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+fn parse_credentials(json: &str) -> IntegrationCredentials {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+// ============================================================================
+// Integration Configuration Functions
+// ============================================================================
+
+/// List all configured integrations
+pub async fn list_integrations(db: &DbConn) -> Result<Vec<IntegrationResponse>> {
+    let integrations = AppIntegration::find().all(db).await?;
+    Ok(integrations
+        .into_iter()
+        .map(IntegrationResponse::from_model)
+        .collect())
+}
+
+/// Get the integration for an app
+pub async fn get_integration(db: &DbConn, app_name: &str) -> Result<IntegrationResponse> {
+    let model = AppIntegration::find_by_id(app_name)
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("No integration configured for '{}'", app_name))
+        })?;
+    Ok(IntegrationResponse::from_model(model))
+}
+
+/// Create or update the integration for an app
+pub async fn upsert_integration(
+    db: &DbConn,
+    app_name: &str,
+    req: UpdateIntegrationRequest,
+) -> Result<IntegrationResponse> {
+    if adapter_for(app_name).is_none() {
+        return Err(AppError::BadRequest(format!(
+            "No integration adapter available for '{}'",
+            app_name
+        )));
+    }
+
+    if let Some(ref url) = req.base_url {
+        if !url.is_empty() && !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(AppError::BadRequest(
+                "base_url must start with http:// or https://".to_string(),
+            ));
+        }
+    }
+
+    let now = Utc::now();
+    let existing = AppIntegration::find_by_id(app_name).one(db).await?;
+
+    let model = if let Some(existing) = existing {
+        let mut active: app_integration::ActiveModel = existing.into();
+        if let Some(credentials) = req.credentials {
+            active.credentials_json = Set(serde_json::to_string(&credentials)?);
+        }
+        if let Some(base_url) = req.base_url {
+            active.base_url = Set(normalize_base_url(&base_url));
+        }
+        if let Some(enabled) = req.enabled {
+            active.enabled = Set(enabled);
+        }
+        if let Some(tls_verify) = req.tls_verify {
+            active.tls_verify = Set(tls_verify);
+        }
+        active.updated_at = Set(now);
+        active.update(db).await?
+    } else {
+        let credentials = req.credentials.unwrap_or_default();
+        let active = app_integration::ActiveModel {
+            app_name: Set(app_name.to_string()),
+            credentials_json: Set(serde_json::to_string(&credentials)?),
+            base_url: Set(req.base_url.as_deref().and_then(normalize_base_url)),
+            enabled: Set(req.enabled.unwrap_or(true)),
+            tls_verify: Set(req.tls_verify.unwrap_or(true)),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+        active.insert(db).await?
+    };
+
+    Ok(IntegrationResponse::from_model(model))
+}
+
+/// Delete the integration for an app
+pub async fn delete_integration(db: &DbConn, app_name: &str) -> Result<()> {
+    let result = AppIntegration::delete_by_id(app_name).exec(db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No integration configured for '{}'",
+            app_name
+        )));
+    }
+    Ok(())
+}
+
+/// Trim a user-supplied base URL; empty means "resolve from K8s"
+fn normalize_base_url(url: &str) -> Option<String> {
+    let trimmed = url.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+// ============================================================================
+// Status Fetching
+// ============================================================================
+
+/// Resolve the API root for an app from its K8s service (cached)
+async fn resolve_service_url(state: &AppState, app_name: &str) -> Result<String> {
+    let (base_url, base_path) = if let Some(cached) = state.endpoint_cache.get(app_name).await {
+        cached
+    } else {
+        let k8s_guard = state.k8s_client.read().await;
+        let k8s = k8s_guard
+            .as_ref()
+            .ok_or_else(|| AppError::ServiceUnavailable("Kubernetes not available".to_string()))?;
+
+        // Apps are deployed in namespaces named after the app
+        let endpoints = k8s.get_service_endpoints(app_name, app_name).await?;
+        let endpoint = endpoints.first().ok_or_else(|| {
+            AppError::NotFound(format!("App {} not found or not ready", app_name))
+        })?;
+
+        let base_url = format!(
+            "http://{}.{}.svc.cluster.local:{}",
+            endpoint.name, endpoint.namespace, endpoint.port
+        );
+        let base_path = endpoint.base_path.clone();
+
+        state
+            .endpoint_cache
+            .set(app_name, base_url.clone(), base_path.clone())
+            .await;
+
+        (base_url, base_path)
+    };
+
+    Ok(join_base_path(&base_url, base_path.as_deref()))
+}
+
+/// Join a service URL with an optional base path (e.g., "/sonarr")
+fn join_base_path(base_url: &str, base_path: Option<&str>) -> String {
+    match base_path.map(|p| p.trim_matches('/')) {
+        Some(p) if !p.is_empty() => format!("{}/{}", base_url.trim_end_matches('/'), p),
+        _ => base_url.trim_end_matches('/').to_string(),
+    }
+}
+
+/// Build the adapter context for an app from its stored integration
+pub async fn build_context(state: &AppState, app_name: &str) -> Result<IntegrationContext> {
+    let db = state.get_db().await?;
+    let integration = AppIntegration::find_by_id(app_name).one(&db).await?;
+
+    let (credentials, base_url_override, tls_verify) = match integration {
+        Some(i) if !i.enabled => {
+            return Err(AppError::BadRequest(format!(
+                "Integration for '{}' is disabled",
+                app_name
+            )));
+        }
+        Some(i) => (
+            parse_credentials(&i.credentials_json),
+            i.base_url,
+            i.tls_verify,
+        ),
+        None => (IntegrationCredentials::default(), None, true),
+    };
+
+    let base_url = match base_url_override {
+        Some(url) => url,
+        None => resolve_service_url(state, app_name).await?,
+    };
+
+    Ok(IntegrationContext {
+        base_url,
+        credentials,
+        tls_verify,
+    })
+}
+
+/// Fetch app-native status for an installed app
+///
+/// Upstream API failures are reported in the response (`available: false`)
+/// rather than as an HTTP error, so a single broken app doesn't break the
+/// dashboard.
+pub async fn fetch_native_status(state: &AppState, app_name: &str) -> Result<NativeStatus> {
+    let adapter = adapter_for(app_name).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No integration adapter available for '{}'",
+            app_name
+        ))
+    })?;
+
+    let ctx = build_context(state, app_name).await?;
+
+    let (metrics, error) = match adapter.native_status(&ctx).await {
+        Ok(metrics) => (metrics, None),
+        Err(e) => {
+            tracing::debug!("Native status for {} failed: {}", app_name, e);
+            (Vec::new(), Some(e.to_string()))
+        }
+    };
+
+    Ok(NativeStatus {
+        app_name: app_name.to_string(),
+        integration: adapter.kind().to_string(),
+        available: error.is_none(),
+        metrics,
+        error,
+        fetched_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_for_known_apps() {
+        for app in supported_apps() {
+            let adapter = adapter_for(app).expect("adapter must exist");
+            assert_eq!(adapter.kind(), app);
+        }
+    }
+
+    #[test]
+    fn test_adapter_for_unknown_app() {
+        assert!(adapter_for("nginx").is_none());
+    }
+
+    #[test]
+    fn test_join_base_path() {
+        assert_eq!(
+            join_base_path(
+                "http://sonarr.sonarr.svc.cluster.local:8989",
+                Some("/sonarr")
+            ),
+            "http://sonarr.sonarr.svc.cluster.local:8989/sonarr"
+        );
+        assert_eq!(join_base_path("http://a:1/", None), "http://a:1");
+        assert_eq!(join_base_path("http://a:1", Some("/")), "http://a:1");
+    }
+
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(
+            normalize_base_url(" http://nas:8989/ "),
+            Some("http://nas:8989".to_string())
+        );
+        assert_eq!(normalize_base_url(""), None);
+    }
+
+    #[test]
+    fn test_require_api_key() {
+        let mut ctx = IntegrationContext {
+            base_url: "http://a".to_string(),
+            credentials: IntegrationCredentials::default(),
+            tls_verify: true,
+        };
+        assert!(ctx.require_api_key().is_err());
+        ctx.credentials.api_key = Some(String::new());
+        assert!(ctx.require_api_key().is_err());
+        ctx.credentials.api_key = Some("abc".to_string());
+        assert_eq!(ctx.require_api_key().unwrap(), "abc");
+        assert_eq!(ctx.url("/api/v3/queue"), "http://a/api/v3/queue");
+    }
+}
@@ -0,0 +1,236 @@
+//! Plex adapter
+//!
+//! Reads active sessions from `/status/sessions`, authenticated with a Plex
+//! token passed in the `X-Plex-Token` header (stored as the API key).
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::transcodes::{PlaybackDecision, PlaybackSession};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::Result;
+
+const APP: &str = "plex";
+
+/// Adapter for Plex Media Server
+pub struct PlexAdapter;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexSessionsResponse {
+    #[serde(default)]
+    media_container: PlexMediaContainer,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlexMediaContainer {
+    #[serde(default)]
+    metadata: Vec<PlexSession>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PlexNamed {
+    #[serde(default)]
+    title: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlexMedia {
+    #[serde(default)]
+    video_codec: Option<String>,
+    #[serde(default)]
+    audio_codec: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlexTranscodeSession {
+    #[serde(default)]
+    video_decision: Option<String>,
+    #[serde(default)]
+    audio_decision: Option<String>,
+    #[serde(default)]
+    video_codec: Option<String>,
+    #[serde(default)]
+    audio_codec: Option<String>,
+    #[serde(default)]
+    source_video_codec: Option<String>,
+    #[serde(default)]
+    transcode_hw_encoding: Option<String>,
+    #[serde(default)]
+    transcode_hw_decoding: Option<String>,
+    #[serde(default)]
+    progress: Option<f64>,
+}
+
+/// A session entry from GET /status/sessions (only the fields we use)
+#[derive(Debug, Default, Deserialize)]
+struct PlexSession {
+    #[serde(default)]
+    title: String,
+    #[serde(default, rename = "grandparentTitle")]
+    grandparent_title: Option<String>,
+    #[serde(default, rename = "User")]
+    user: Option<PlexNamed>,
+    #[serde(default, rename = "Player")]
+    player: Option<PlexNamed>,
+    #[serde(default, rename = "Media")]
+    media: Vec<PlexMedia>,
+    #[serde(default, rename = "TranscodeSession")]
+    transcode_session: Option<PlexTranscodeSession>,
+}
+
+impl PlexSession {
+    fn into_playback(self) -> PlaybackSession {
+        let title = match &self.grandparent_title {
+            Some(show) => format!("{} - {}", show, self.title),
+            None => self.title.clone(),
+        };
+        let media = self.media.into_iter().next().unwrap_or_default();
+
+        let (decision, video_codec, audio_codec, source, hw, progress) =
+            match self.transcode_session {
+                Some(ts) => {
+                    let decision = if ts.video_decision.as_deref() == Some("transcode")
+                        || ts.audio_decision.as_deref() == Some("transcode")
+                    {
+                        PlaybackDecision::Transcode
+                    } else {
+                        PlaybackDecision::DirectStream
+                    };
+                    let hw = ts
+                        .transcode_hw_encoding
+                        .or(ts.transcode_hw_decoding)
+                        .filter(|s| !s.is_empty());
+                    (
+                        decision,
+                        ts.video_codec,
+                        ts.audio_codec.or(media.audio_codec),
+                        ts.source_video_codec.or(media.video_codec),
+                        hw,
+                        ts.progress,
+                    )
+                }
+                None => (
+                    PlaybackDecision::DirectPlay,
+                    media.video_codec.clone(),
+                    media.audio_codec,
+                    media.video_codec,
+                    None,
+                    None,
+                ),
+            };
+
+        PlaybackSession {
+            user: self.user.and_then(|u| u.title),
+            title,
+            device: self.player.and_then(|p| p.title),
+            decision,
+            source_video_codec: source,
+            video_codec,
+            audio_codec,
+            hardware_acceleration: if decision == PlaybackDecision::Transcode {
+                hw
+            } else {
+                None
+            },
+            progress,
+            reasons: Vec::new(),
+        }
+    }
+}
+
+/// Fetch active sessions from Plex
+async fn fetch_sessions(ctx: &IntegrationContext) -> Result<Vec<PlexSession>> {
+    let token = ctx.require_api_key()?;
+
+    let resp = ctx
+        .client()
+        .get(ctx.url("/status/sessions"))
+        .header("X-Plex-Token", token)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    let body: PlexSessionsResponse = check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    Ok(body.media_container.metadata)
+}
+
+#[async_trait]
+impl IntegrationAdapter for PlexAdapter {
+    fn kind(&self) -> &'static str {
+        APP
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let sessions = self.playback_sessions(ctx).await?;
+        let transcodes = sessions.iter().filter(|s| s.is_transcode()).count();
+        Ok(vec![
+            NativeMetric::new(
+                "active_streams",
+                "Active streams",
+                sessions.len() as f64,
+                None,
+            ),
+            NativeMetric::new("transcodes", "Transcoding", transcodes as f64, None),
+        ])
+    }
+
+    fn is_media_server(&self) -> bool {
+        true
+    }
+
+    async fn playback_sessions(&self, ctx: &IntegrationContext) -> Result<Vec<PlaybackSession>> {
+        let sessions = fetch_sessions(ctx).await?;
+        Ok(sessions
+            .into_iter()
+            .map(PlexSession::into_playback)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sessions() {
+        let json = r#"{"MediaContainer":{"size":2,"Metadata":[
+            {"title":"Pilot","grandparentTitle":"Show","User":{"title":"alice"},"Player":{"title":"Chrome"},
+             "Media":[{"videoCodec":"hevc","audioCodec":"eac3"}],
+             "TranscodeSession":{"videoDecision":"transcode","audioDecision":"copy","videoCodec":"h264",
+               "audioCodec":"eac3","sourceVideoCodec":"hevc","transcodeHwEncoding":"vaapi","progress":12.5}},
+            {"title":"Movie","User":{"title":"bob"},"Media":[{"videoCodec":"h264","audioCodec":"aac"}]}
+        ]}}"#;
+        let body: PlexSessionsResponse = serde_json::from_str(json).unwrap();
+        let sessions: Vec<PlaybackSession> = body
+            .media_container
+            .metadata
+            .into_iter()
+            .map(PlexSession::into_playback)
+            .collect();
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].title, "Show - Pilot");
+        assert_eq!(sessions[0].decision, PlaybackDecision::Transcode);
+        assert_eq!(sessions[0].source_video_codec.as_deref(), Some("hevc"));
+        assert_eq!(sessions[0].video_codec.as_deref(), Some("h264"));
+        assert_eq!(sessions[0].hardware_acceleration.as_deref(), Some("vaapi"));
+        assert_eq!(sessions[1].decision, PlaybackDecision::DirectPlay);
+        assert_eq!(sessions[1].user.as_deref(), Some("bob"));
+        assert!(sessions[1].hardware_acceleration.is_none());
+    }
+
+    #[test]
+    fn test_parse_empty_container() {
+        let body: PlexSessionsResponse =
+            serde_json::from_str(r#"{"MediaContainer":{"size":0}}"#).unwrap();
+        assert!(body.media_container.metadata.is_empty());
+    }
+}
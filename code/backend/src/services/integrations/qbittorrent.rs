@@ -0,0 +1,266 @@
+//! qBittorrent adapter
+//!
+//! Uses the WebUI API v2. Authentication is cookie-based: log in with the
+//! stored username/password and pass the returned `SID` cookie along. When no
+//! credentials are stored the adapter assumes the WebUI allows unauthenticated
+//! access from the cluster network (subnet whitelist).
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::downloads::{DownloadItem, DownloadState};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::{AppError, Result};
+
+const APP: &str = "qbittorrent";
+
+/// Adapter for qBittorrent
+pub struct QbittorrentAdapter;
+
+/// Response from GET /api/v2/transfer/info
+#[derive(Debug, Default, Deserialize)]
+pub struct TransferInfo {
+    /// Download speed in bytes/s
+    #[serde(default)]
+    pub dl_info_speed: u64,
+    /// Upload speed in bytes/s
+    #[serde(default)]
+    pub up_info_speed: u64,
+    #[serde(default)]
+    pub dl_info_data: u64,
+    #[serde(default)]
+    pub up_info_data: u64,
+    #[serde(default)]
+    pub connection_status: String,
+}
+
+/// Convert transfer info into dashboard metrics
+pub fn transfer_metrics(info: &TransferInfo) -> Vec<NativeMetric> {
+    vec![
+        NativeMetric::new(
+            "download_rate",
+            "Download rate",
+            info.dl_info_speed as f64,
+            Some("bytes/s"),
+        ),
+        NativeMetric::new(
+            "upload_rate",
+            "Upload rate",
+            info.up_info_speed as f64,
+            Some("bytes/s"),
+        ),
+        NativeMetric::new(
+            "session_downloaded",
+            "Downloaded this session",
+            info.dl_info_data as f64,
+            Some("bytes"),
+        ),
+        NativeMetric::new(
+            "session_uploaded",
+            "Uploaded this session",
+            info.up_info_data as f64,
+            Some("bytes"),
+        ),
+        NativeMetric::new(
+            "connected",
+            "Connected",
+            if info.connection_status == "connected" {
+                1.0
+            } else {
+                0.0
+            },
+            None,
+        ),
+    ]
+}
+
+/// A torrent from GET /api/v2/torrents/info
+#[derive(Debug, Default, Deserialize)]
+pub struct QbitTorrent {
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub progress: f64,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub dlspeed: u64,
+    #[serde(default)]
+    pub upspeed: u64,
+    /// Seconds remaining (8640000 = infinity)
+    #[serde(default)]
+    pub eta: u64,
+    #[serde(default)]
+    pub category: String,
+}
+
+/// qBittorrent's "infinite" ETA sentinel
+const ETA_INFINITY: u64 = 8_640_000;
+
+fn map_state(state: &str) -> DownloadState {
+    match state {
+        "downloading" | "forcedDL" | "metaDL" | "forcedMetaDL" => DownloadState::Downloading,
+        "stalledDL" => DownloadState::Stalled,
+        "checkingDL" | "checkingUP" | "checkingResumeData" | "allocating" | "moving" => {
+            DownloadState::Checking
+        }
+        "pausedDL" | "stoppedDL" => DownloadState::Paused,
+        "error" | "missingFiles" => DownloadState::Error,
+        _ => DownloadState::Queued,
+    }
+}
+
+/// Convert incomplete torrents into normalized download items
+pub fn torrent_items(torrents: &[QbitTorrent]) -> Vec<DownloadItem> {
+    torrents
+        .iter()
+        .filter(|t| t.progress < 1.0)
+        .map(|t| DownloadItem {
+            app_name: APP.to_string(),
+            id: t.hash.clone(),
+            name: t.name.clone(),
+            state: map_state(&t.state),
+            progress: t.progress,
+            size_bytes: t.size,
+            download_rate: t.dlspeed,
+            upload_rate: t.upspeed,
+            eta_seconds: Some(t.eta).filter(|e| *e < ETA_INFINITY),
+            category: Some(t.category.clone()).filter(|c| !c.is_empty()),
+        })
+        .collect()
+}
+
+/// Extract the SID value from a Set-Cookie header
+pub fn extract_sid(set_cookie: &str) -> Option<String> {
+    set_cookie
+        .split(';')
+        .next()
+        .and_then(|pair| pair.trim().strip_prefix("SID="))
+        .filter(|sid| !sid.is_empty())
+        .map(|sid| sid.to_string())
+}
+
+/// Log in and return the session cookie, if credentials are configured
+pub(crate) async fn login(ctx: &IntegrationContext) -> Result<Option<String>> {
+    let (Some(username), Some(password)) = (
+        ctx.credentials.username.as_deref(),
+        ctx.credentials.password.as_deref(),
+    ) else {
+        return Ok(None);
+    };
+
+    let resp = ctx
+        .client()
+        .post(ctx.url("/api/v2/auth/login"))
+        // qBittorrent rejects logins without a matching Referer (CSRF protection)
+        .header("Referer", &ctx.base_url)
+        .form(&[("username", username), ("password", password)])
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    let sid = resp
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find_map(extract_sid);
+
+    match sid {
+        Some(sid) => Ok(Some(sid)),
+        None => Err(AppError::BadGateway(format!(
+            "{} rejected the configured credentials",
+            APP
+        ))),
+    }
+}
+
+/// Log in (if configured) and GET a JSON API path
+async fn get_json<T: serde::de::DeserializeOwned>(
+    ctx: &IntegrationContext,
+    path: &str,
+) -> Result<T> {
+    let sid = login(ctx).await?;
+
+    let mut req = ctx.client().get(ctx.url(path));
+    if let Some(sid) = sid {
+        req = req.header(reqwest::header::COOKIE, format!("SID={}", sid));
+    }
+
+    let resp = req.send().await.map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))
+}
+
+#[async_trait]
+impl IntegrationAdapter for QbittorrentAdapter {
+    fn kind(&self) -> &'static str {
+        APP
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let info: TransferInfo = get_json(ctx, "/api/v2/transfer/info").await?;
+        Ok(transfer_metrics(&info))
+    }
+
+    fn is_download_client(&self) -> bool {
+        true
+    }
+
+    async fn downloads(&self, ctx: &IntegrationContext) -> Result<Vec<DownloadItem>> {
+        let torrents: Vec<QbitTorrent> = get_json(ctx, "/api/v2/torrents/info").await?;
+        Ok(torrent_items(&torrents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_sid() {
+        assert_eq!(
+            extract_sid("SID=abc123; HttpOnly; path=/; SameSite=Strict"),
+            Some("abc123".to_string())
+        );
+        assert_eq!(extract_sid("other=1; path=/"), None);
+        assert_eq!(extract_sid("SID=; path=/"), None);
+    }
+
+    #[test]
+    fn test_transfer_metrics() {
+        let json = r#"{"dl_info_speed":1024,"up_info_speed":512,"dl_info_data":10,"up_info_data":20,"connection_status":"connected"}"#;
+        let info: TransferInfo = serde_json::from_str(json).unwrap();
+        let metrics = transfer_metrics(&info);
+
+        assert_eq!(metrics[0].key, "download_rate");
+        assert_eq!(metrics[0].value, 1024.0);
+        assert_eq!(metrics[1].value, 512.0);
+        assert_eq!(metrics[4].value, 1.0);
+    }
+
+    #[test]
+    fn test_torrent_items() {
+        let json = r#"[
+            {"hash":"a","name":"Active","state":"downloading","progress":0.5,"size":100,"dlspeed":10,"upspeed":1,"eta":5,"category":"tv"},
+            {"hash":"b","name":"Stuck","state":"stalledDL","progress":0.1,"size":100,"dlspeed":0,"upspeed":0,"eta":8640000,"category":""},
+            {"hash":"c","name":"Seeding","state":"uploading","progress":1.0,"size":100,"dlspeed":0,"upspeed":50,"eta":8640000,"category":""}
+        ]"#;
+        let torrents: Vec<QbitTorrent> = serde_json::from_str(json).unwrap();
+        let items = torrent_items(&torrents);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].state, DownloadState::Downloading);
+        assert_eq!(items[0].eta_seconds, Some(5));
+        assert_eq!(items[0].category.as_deref(), Some("tv"));
+        assert_eq!(items[1].state, DownloadState::Stalled);
+        assert_eq!(items[1].eta_seconds, None);
+        assert!(items[1].category.is_none());
+    }
+}
@@ -0,0 +1,441 @@
+//! Media request passthrough for Jellyseerr/Overseerr
+//!
+//! Kubarr users are mapped to request manager users through their account
+//! links (see [`crate::services::account_links`]). Requests are created upstream on behalf of the
+//! linked account, and requesters are mapped back to Kubarr users so
+//! approvals can be delivered to their notification inbox.
+
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use super::seerr::{
+    request_status_name, SeerrAdapter, SeerrCreateRequest, SeerrKind, SeerrRequest,
+    SeerrRequestCounts, SeerrUser,
+};
+use super::{build_context, IntegrationContext};
+use crate::error::{AppError, Result};
+use crate::models::app_account_link;
+use crate::models::prelude::*;
+use crate::services::account_links::{self, SetAccountLink, SOURCE_MANUAL};
+use crate::state::{AppState, DbConn};
+
+/// Apps that can act as the request manager, in order of preference
+pub const REQUEST_MANAGERS: [&str; 2] = ["jellyseerr", "overseerr"];
+
+/// Maximum number of requests returned in one page
+pub const MAX_REQUESTS_PAGE: u64 = 100;
+
+pub fn seerr_kind(app_name: &str) -> Option<SeerrKind> {
+    match app_name {
+        "jellyseerr" => Some(SeerrKind::Jellyseerr),
+        "overseerr" => Some(SeerrKind::Overseerr),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// Who made a media request
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MediaRequester {
+    pub external_user_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_username: Option<String>,
+    /// Linked Kubarr user, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// A media request as shown in Kubarr
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MediaRequest {
+    pub id: i64,
+    /// Request manager the request lives in
+    pub app_name: String,
+    /// pending, approved, declined, failed, completed
+    pub status: String,
+    /// "movie" or "tv"
+    pub media_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmdb_id: Option<i64>,
+    pub is4k: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested_by: Option<MediaRequester>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Response for GET /api/integrations/requests
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MediaRequestList {
+    pub app_name: String,
+    pub requests: Vec<MediaRequest>,
+}
+
+/// Response for GET /api/integrations/requests/count
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MediaRequestCounts {
+    pub app_name: String,
+    pub counts: SeerrRequestCounts,
+}
+
+/// Body for POST /api/integrations/requests
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateMediaRequest {
+    /// "movie" or "tv"
+    pub media_type: String,
+    /// TMDB ID of the movie or series
+    pub media_id: i64,
+    /// Seasons to request (TV only, default all)
+    #[serde(default)]
+    pub seasons: Option<Vec<u32>>,
+    #[serde(default)]
+    pub is4k: bool,
+}
+
+/// Body for PUT /api/integrations/requests/links/{user_id}
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct LinkAccountRequest {
+    /// Request manager app (default: the active one)
+    #[serde(default)]
+    pub app_name: Option<String>,
+    pub external_user_id: i64,
+    #[serde(default)]
+    pub external_username: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MediaAccountLinkResponse {
+    pub user_id: i64,
+    pub username: String,
+    pub app_name: String,
+    pub external_user_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_username: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// ============================================================================
+// Account Links
+// ============================================================================
+
+/// List all request manager account links
+pub async fn list_links(db: &DbConn) -> Result<Vec<MediaAccountLinkResponse>> {
+    let links = AppAccountLink::find()
+        .filter(app_account_link::Column::AppName.is_in(REQUEST_MANAGERS))
+        .find_also_related(User)
+        .order_by_asc(app_account_link::Column::UserId)
+        .all(db)
+        .await?;
+
+    Ok(links
+        .into_iter()
+        .filter_map(|(link, user)| {
+            Some(MediaAccountLinkResponse {
+                user_id: link.user_id,
+                username: user.map(|u| u.username).unwrap_or_default(),
+                external_user_id: link.external_id.parse().ok()?,
+                app_name: link.app_name,
+                external_username: link.external_username,
+                created_at: link.created_at,
+            })
+        })
+        .collect())
+}
+
+/// The request manager user ID a Kubarr user is linked to
+pub async fn linked_user_id(db: &DbConn, user_id: i64, app_name: &str) -> Result<Option<i64>> {
+    Ok(account_links::find_link(db, user_id, app_name)
+        .await?
+        .and_then(|link| link.external_id.parse().ok()))
+}
+
+/// Link a Kubarr user to a request manager account
+///
+/// Each request manager account can only be linked to one Kubarr user.
+pub async fn set_link(
+    db: &DbConn,
+    user_id: i64,
+    app_name: &str,
+    req: &LinkAccountRequest,
+) -> Result<MediaAccountLinkResponse> {
+    if seerr_kind(app_name).is_none() {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is not a supported request manager",
+            app_name
+        )));
+    }
+
+    let link = account_links::set_link(
+        db,
+        user_id,
+        app_name,
+        &SetAccountLink {
+            external_id: req.external_user_id.to_string(),
+            external_username: req.external_username.clone(),
+        },
+        SOURCE_MANUAL,
+    )
+    .await?;
+    let username = User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .map(|u| u.username)
+        .unwrap_or_default();
+
+    Ok(MediaAccountLinkResponse {
+        user_id: link.user_id,
+        username,
+        app_name: link.app_name,
+        external_user_id: req.external_user_id,
+        external_username: link.external_username,
+        created_at: link.created_at,
+    })
+}
+
+/// Remove all of a user's request manager account links
+pub async fn delete_links(db: &DbConn, user_id: i64) -> Result<()> {
+    let result = AppAccountLink::delete_many()
+        .filter(app_account_link::Column::UserId.eq(user_id))
+        .filter(app_account_link::Column::AppName.is_in(REQUEST_MANAGERS))
+        .exec(db)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(
+            "User has no linked request accounts".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+/// The request manager requests are proxied to
+pub struct RequestManager {
+    pub app_name: String,
+    adapter: SeerrAdapter,
+    ctx: IntegrationContext,
+}
+
+/// Find the first enabled request manager integration
+pub async fn active_manager_name(db: &DbConn) -> Result<String> {
+    let configured = AppIntegration::find().all(db).await?;
+    REQUEST_MANAGERS
+        .iter()
+        .find(|app| configured.iter().any(|i| i.enabled && i.app_name == **app))
+        .map(|app| app.to_string())
+        .ok_or_else(|| {
+            AppError::NotFound("No Jellyseerr or Overseerr integration is configured".to_string())
+        })
+}
+
+/// Resolve the active request manager and its API context
+pub async fn active_manager(state: &AppState) -> Result<RequestManager> {
+    let db = state.get_db().await?;
+    let app_name = active_manager_name(&db).await?;
+    let kind = seerr_kind(&app_name)
+        .ok_or_else(|| AppError::Internal(format!("Unknown request manager '{}'", app_name)))?;
+    let ctx = build_context(state, &app_name).await?;
+    Ok(RequestManager {
+        app_name,
+        adapter: SeerrAdapter::new(kind),
+        ctx,
+    })
+}
+
+/// Map an upstream request to the Kubarr shape, resolving linked users
+async fn to_media_request(db: &DbConn, app_name: &str, req: SeerrRequest) -> Result<MediaRequest> {
+    let requested_by = match req.requested_by {
+        Some(requester) => {
+            let linked = AppAccountLink::find()
+                .filter(app_account_link::Column::AppName.eq(app_name))
+                .filter(app_account_link::Column::ExternalId.eq(requester.id.to_string()))
+                .find_also_related(User)
+                .one(db)
+                .await?;
+            let (user_id, username) = match linked {
+                Some((link, user)) => (Some(link.user_id), user.map(|u| u.username)),
+                None => (None, None),
+            };
+            Some(MediaRequester {
+                external_user_id: requester.id,
+                external_username: requester.display_name,
+                user_id,
+                username,
+            })
+        }
+        None => None,
+    };
+
+    Ok(MediaRequest {
+        id: req.id,
+        app_name: app_name.to_string(),
+        status: request_status_name(req.status).to_string(),
+        media_type: req.media_type,
+        tmdb_id: req.media.and_then(|m| m.tmdb_id),
+        is4k: req.is4k,
+        requested_by,
+        created_at: req.created_at,
+    })
+}
+
+/// List requests from the active request manager
+///
+/// When `requested_by` is set, only that Kubarr user's requests are returned;
+/// a user without a linked account gets an empty list.
+pub async fn list_requests(
+    state: &AppState,
+    requested_by: Option<i64>,
+    filter: &str,
+    take: u64,
+    skip: u64,
+) -> Result<MediaRequestList> {
+    let db = state.get_db().await?;
+    let manager = active_manager(state).await?;
+
+    let external_id = match requested_by {
+        Some(user_id) => match linked_user_id(&db, user_id, &manager.app_name).await? {
+            Some(external_id) => Some(external_id),
+            None => {
+                return Ok(MediaRequestList {
+                    app_name: manager.app_name,
+                    requests: Vec::new(),
+                });
+            }
+        },
+        None => None,
+    };
+
+    let upstream = manager
+        .adapter
+        .list_requests(
+            &manager.ctx,
+            filter,
+            external_id,
+            take.min(MAX_REQUESTS_PAGE),
+            skip,
+        )
+        .await?;
+
+    let mut requests = Vec::with_capacity(upstream.len());
+    for req in upstream {
+        requests.push(to_media_request(&db, &manager.app_name, req).await?);
+    }
+
+    Ok(MediaRequestList {
+        app_name: manager.app_name,
+        requests,
+    })
+}
+
+/// Get request counts from the active request manager
+pub async fn request_counts(state: &AppState) -> Result<MediaRequestCounts> {
+    let manager = active_manager(state).await?;
+    let counts = manager.adapter.request_counts(&manager.ctx).await?;
+    Ok(MediaRequestCounts {
+        app_name: manager.app_name,
+        counts,
+    })
+}
+
+/// Create a request on behalf of a Kubarr user's linked account
+pub async fn create_request(
+    state: &AppState,
+    user_id: i64,
+    req: &CreateMediaRequest,
+) -> Result<MediaRequest> {
+    if req.media_type != "movie" && req.media_type != "tv" {
+        return Err(AppError::BadRequest(
+            "media_type must be 'movie' or 'tv'".to_string(),
+        ));
+    }
+
+    let db = state.get_db().await?;
+    let manager = active_manager(state).await?;
+    let external_id = linked_user_id(&db, user_id, &manager.app_name)
+        .await?
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Your account is not linked to a {} user",
+                manager.app_name
+            ))
+        })?;
+
+    let body = SeerrCreateRequest {
+        media_type: req.media_type.clone(),
+        media_id: req.media_id,
+        seasons: if req.media_type == "tv" {
+            req.seasons.clone()
+        } else {
+            None
+        },
+        is4k: req.is4k,
+        user_id: external_id,
+    };
+    let created = manager.adapter.create_request(&manager.ctx, &body).await?;
+    to_media_request(&db, &manager.app_name, created).await
+}
+
+/// Approve or decline a pending request
+pub async fn set_request_status(
+    state: &AppState,
+    request_id: i64,
+    approve: bool,
+) -> Result<MediaRequest> {
+    let db = state.get_db().await?;
+    let manager = active_manager(state).await?;
+    let updated = manager
+        .adapter
+        .update_request_status(&manager.ctx, request_id, approve)
+        .await?;
+    to_media_request(&db, &manager.app_name, updated).await
+}
+
+/// List users in the active request manager (for linking)
+pub async fn list_external_users(state: &AppState) -> Result<Vec<SeerrUser>> {
+    let manager = active_manager(state).await?;
+    manager.adapter.list_users(&manager.ctx).await
+}
+
+/// Short description of a request for audit/notification details
+pub fn describe_request(req: &MediaRequest) -> String {
+    match req.tmdb_id {
+        Some(tmdb_id) => format!("{} #{} ({})", req.media_type, tmdb_id, req.app_name),
+        None => format!("request #{} ({})", req.id, req.app_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seerr_kind() {
+        assert_eq!(seerr_kind("jellyseerr"), Some(SeerrKind::Jellyseerr));
+        assert_eq!(seerr_kind("overseerr"), Some(SeerrKind::Overseerr));
+        assert_eq!(seerr_kind("sonarr"), None);
+    }
+
+    #[test]
+    fn test_describe_request() {
+        let mut req = MediaRequest {
+            id: 4,
+            app_name: "jellyseerr".to_string(),
+            status: "pending".to_string(),
+            media_type: "movie".to_string(),
+            tmdb_id: Some(603),
+            is4k: false,
+            requested_by: None,
+            created_at: None,
+        };
+        assert_eq!(describe_request(&req), "movie #603 (jellyseerr)");
+        req.tmdb_id = None;
+        assert_eq!(describe_request(&req), "request #4 (jellyseerr)");
+    }
+}
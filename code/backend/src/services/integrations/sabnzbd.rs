@@ -0,0 +1,219 @@
+//! SABnzbd adapter
+//!
+//! Uses the `mode=queue` API, authenticated with an `apikey` query parameter.
+//! SABnzbd reports most numbers as strings, so parsing is lenient.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::downloads::{DownloadItem, DownloadState};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::Result;
+
+const APP: &str = "sabnzbd";
+
+/// Adapter for SABnzbd
+pub struct SabnzbdAdapter;
+
+#[derive(Debug, Default, Deserialize)]
+struct QueueResponse {
+    #[serde(default)]
+    queue: SabQueue,
+}
+
+/// The `queue` object from GET /api?mode=queue
+#[derive(Debug, Default, Deserialize)]
+pub struct SabQueue {
+    #[serde(default)]
+    pub paused: bool,
+    /// Current download rate in KB/s
+    #[serde(default)]
+    pub kbpersec: String,
+    #[serde(default)]
+    pub slots: Vec<SabSlot>,
+}
+
+/// A queue slot (one NZB)
+#[derive(Debug, Default, Deserialize)]
+pub struct SabSlot {
+    #[serde(default)]
+    pub nzo_id: String,
+    #[serde(default)]
+    pub filename: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub mb: String,
+    #[serde(default)]
+    pub percentage: String,
+    #[serde(default)]
+    pub timeleft: String,
+    #[serde(default)]
+    pub cat: String,
+}
+
+/// Parse SABnzbd's "H:MM:SS" / "D:HH:MM:SS" time-left format into seconds
+pub fn parse_timeleft(value: &str) -> Option<u64> {
+    let parts: Vec<u64> = value
+        .split(':')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let multipliers = [1, 60, 3600, 86400];
+    if parts.is_empty() || parts.len() > multipliers.len() {
+        return None;
+    }
+    let seconds = parts
+        .iter()
+        .rev()
+        .zip(multipliers.iter())
+        .map(|(v, m)| v * m)
+        .sum::<u64>();
+    // SABnzbd reports 0:00:00 when the ETA is unknown
+    (seconds > 0).then_some(seconds)
+}
+
+fn map_state(status: &str, queue_paused: bool) -> DownloadState {
+    match status {
+        "Downloading" | "Fetching" | "Grabbing" if !queue_paused => DownloadState::Downloading,
+        "Paused" => DownloadState::Paused,
+        "Checking" | "QuickCheck" | "Verifying" | "Repairing" | "Extracting" => {
+            DownloadState::Checking
+        }
+        "Failed" => DownloadState::Error,
+        _ if queue_paused => DownloadState::Paused,
+        _ => DownloadState::Queued,
+    }
+}
+
+/// Convert the SABnzbd queue into normalized download items
+///
+/// SABnzbd downloads one NZB at a time, so the queue-wide rate is attributed
+/// to the first downloading slot.
+pub fn queue_items(queue: &SabQueue) -> Vec<DownloadItem> {
+    let total_rate = (queue.kbpersec.trim().parse::<f64>().unwrap_or(0.0) * 1024.0) as u64;
+    let mut rate_assigned = false;
+
+    queue
+        .slots
+        .iter()
+        .map(|slot| {
+            let state = map_state(&slot.status, queue.paused);
+            let download_rate = if state == DownloadState::Downloading && !rate_assigned {
+                rate_assigned = true;
+                total_rate
+            } else {
+                0
+            };
+            let size_mb = slot.mb.trim().parse::<f64>().unwrap_or(0.0);
+            DownloadItem {
+                app_name: APP.to_string(),
+                id: slot.nzo_id.clone(),
+                name: slot.filename.clone(),
+                state,
+                progress: slot.percentage.trim().parse::<f64>().unwrap_or(0.0) / 100.0,
+                size_bytes: (size_mb * 1024.0 * 1024.0) as u64,
+                download_rate,
+                upload_rate: 0,
+                eta_seconds: parse_timeleft(&slot.timeleft),
+                category: Some(slot.cat.clone()).filter(|c| !c.is_empty() && c != "*"),
+            }
+        })
+        .collect()
+}
+
+async fn fetch_queue(ctx: &IntegrationContext) -> Result<SabQueue> {
+    let api_key = ctx.require_api_key()?;
+
+    let resp = ctx
+        .client()
+        .get(ctx.url("/api"))
+        .query(&[("mode", "queue"), ("output", "json"), ("apikey", api_key)])
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    let body: QueueResponse = check_status(APP, resp)?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    Ok(body.queue)
+}
+
+#[async_trait]
+impl IntegrationAdapter for SabnzbdAdapter {
+    fn kind(&self) -> &'static str {
+        APP
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let queue = fetch_queue(ctx).await?;
+        let rate = queue.kbpersec.trim().parse::<f64>().unwrap_or(0.0) * 1024.0;
+        Ok(vec![
+            NativeMetric::new("download_rate", "Download rate", rate, Some("bytes/s")),
+            NativeMetric::new("queue_size", "Queue size", queue.slots.len() as f64, None),
+            NativeMetric::new(
+                "paused",
+                "Paused",
+                if queue.paused { 1.0 } else { 0.0 },
+                None,
+            ),
+        ])
+    }
+
+    fn is_download_client(&self) -> bool {
+        true
+    }
+
+    async fn downloads(&self, ctx: &IntegrationContext) -> Result<Vec<DownloadItem>> {
+        let queue = fetch_queue(ctx).await?;
+        Ok(queue_items(&queue))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeleft() {
+        assert_eq!(parse_timeleft("0:05:00"), Some(300));
+        assert_eq!(parse_timeleft("1:02:03:04"), Some(93784));
+        assert_eq!(parse_timeleft("0:00:00"), None);
+        assert_eq!(parse_timeleft("unknown"), None);
+    }
+
+    #[test]
+    fn test_queue_items() {
+        let json = r#"{"queue":{"paused":false,"kbpersec":"1024.0","slots":[
+            {"nzo_id":"a","filename":"Show.S01E01","status":"Downloading","mb":"100.0","percentage":"25","timeleft":"0:01:00","cat":"tv"},
+            {"nzo_id":"b","filename":"Movie","status":"Queued","mb":"2048.0","percentage":"0","timeleft":"0:00:00","cat":"*"}
+        ]}}"#;
+        let body: QueueResponse = serde_json::from_str(json).unwrap();
+        let items = queue_items(&body.queue);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].state, DownloadState::Downloading);
+        assert_eq!(items[0].download_rate, 1024 * 1024);
+        assert_eq!(items[0].progress, 0.25);
+        assert_eq!(items[0].eta_seconds, Some(60));
+        assert_eq!(items[0].category.as_deref(), Some("tv"));
+        assert_eq!(items[1].state, DownloadState::Queued);
+        assert_eq!(items[1].download_rate, 0);
+        assert_eq!(items[1].size_bytes, 2048 * 1024 * 1024);
+        assert!(items[1].category.is_none());
+    }
+
+    #[test]
+    fn test_paused_queue() {
+        let queue = SabQueue {
+            paused: true,
+            kbpersec: "0".to_string(),
+            slots: vec![SabSlot {
+                status: "Downloading".to_string(),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(queue_items(&queue)[0].state, DownloadState::Paused);
+    }
+}
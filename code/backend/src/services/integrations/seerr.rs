@@ -0,0 +1,394 @@
+//! Jellyseerr/Overseerr adapter
+//!
+//! Both apps share the same v1 API, authenticated with an `X-Api-Key` header.
+//! The API key belongs to the admin account, which lets Kubarr create requests
+//! on behalf of other users by passing their `userId`.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::{AppError, Result};
+
+/// Which request manager the adapter talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeerrKind {
+    Jellyseerr,
+    Overseerr,
+}
+
+impl SeerrKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeerrKind::Jellyseerr => "jellyseerr",
+            SeerrKind::Overseerr => "overseerr",
+        }
+    }
+}
+
+/// Adapter for Jellyseerr and Overseerr
+pub struct SeerrAdapter {
+    kind: SeerrKind,
+}
+
+impl SeerrAdapter {
+    pub fn new(kind: SeerrKind) -> Self {
+        Self { kind }
+    }
+}
+
+// ============================================================================
+// API Types
+// ============================================================================
+
+/// Response from GET /api/v1/request/count
+#[derive(Debug, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SeerrRequestCounts {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub movie: u64,
+    #[serde(default)]
+    pub tv: u64,
+    #[serde(default)]
+    pub pending: u64,
+    #[serde(default)]
+    pub approved: u64,
+    #[serde(default)]
+    pub declined: u64,
+    #[serde(default)]
+    pub processing: u64,
+    #[serde(default)]
+    pub available: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeerrUserRef {
+    pub id: i64,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeerrMedia {
+    #[serde(default)]
+    pub tmdb_id: Option<i64>,
+    #[serde(default)]
+    pub tvdb_id: Option<i64>,
+    /// 1 = unknown, 2 = pending, 3 = processing, 4 = partially available, 5 = available
+    #[serde(default)]
+    pub status: i64,
+}
+
+/// A media request from the request list/detail endpoints
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeerrRequest {
+    pub id: i64,
+    /// 1 = pending, 2 = approved, 3 = declined, 4 = failed, 5 = completed
+    #[serde(default)]
+    pub status: i64,
+    #[serde(default, rename = "type")]
+    pub media_type: String,
+    #[serde(default)]
+    pub is4k: bool,
+    #[serde(default)]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub media: Option<SeerrMedia>,
+    #[serde(default)]
+    pub requested_by: Option<SeerrUserRef>,
+    #[serde(default)]
+    pub modified_by: Option<SeerrUserRef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SeerrPage<T> {
+    #[serde(default = "Vec::new")]
+    pub results: Vec<T>,
+}
+
+/// A user from GET /api/v1/user
+#[derive(Debug, Default, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SeerrUser {
+    pub id: i64,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// Body for POST /api/v1/request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeerrCreateRequest {
+    pub media_type: String,
+    pub media_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seasons: Option<Vec<u32>>,
+    pub is4k: bool,
+    pub user_id: i64,
+}
+
+/// Human-readable request status
+pub fn request_status_name(status: i64) -> &'static str {
+    match status {
+        1 => "pending",
+        2 => "approved",
+        3 => "declined",
+        4 => "failed",
+        5 => "completed",
+        _ => "unknown",
+    }
+}
+
+// ============================================================================
+// API Client
+// ============================================================================
+
+impl SeerrAdapter {
+    fn app(&self) -> &'static str {
+        self.kind.as_str()
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        ctx: &IntegrationContext,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        let resp = ctx
+            .client()
+            .get(ctx.url(path))
+            .header("X-Api-Key", ctx.require_api_key()?)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| upstream_error(self.app(), e))?;
+        check_status(self.app(), resp)?
+            .json()
+            .await
+            .map_err(|e| upstream_error(self.app(), e))
+    }
+
+    async fn post<T: serde::de::DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        ctx: &IntegrationContext,
+        path: &str,
+        body: &B,
+    ) -> Result<T> {
+        let resp = ctx
+            .client()
+            .post(ctx.url(path))
+            .header("X-Api-Key", ctx.require_api_key()?)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| upstream_error(self.app(), e))?;
+
+        // Surface validation errors (e.g., "already requested") as 400s
+        if resp.status() == reqwest::StatusCode::BAD_REQUEST
+            || resp.status() == reqwest::StatusCode::CONFLICT
+        {
+            let message = resp
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|v| v["message"].as_str().map(String::from))
+                .unwrap_or_else(|| "Request rejected".to_string());
+            return Err(AppError::BadRequest(format!("{}: {}", self.app(), message)));
+        }
+
+        check_status(self.app(), resp)?
+            .json()
+            .await
+            .map_err(|e| upstream_error(self.app(), e))
+    }
+
+    /// Get request counts
+    pub async fn request_counts(&self, ctx: &IntegrationContext) -> Result<SeerrRequestCounts> {
+        self.get(ctx, "/api/v1/request/count", &[]).await
+    }
+
+    /// List requests, optionally only those made by one user
+    pub async fn list_requests(
+        &self,
+        ctx: &IntegrationContext,
+        filter: &str,
+        requested_by: Option<i64>,
+        take: u64,
+        skip: u64,
+    ) -> Result<Vec<SeerrRequest>> {
+        let mut query = vec![
+            ("take", take.to_string()),
+            ("skip", skip.to_string()),
+            ("filter", filter.to_string()),
+            ("sort", "added".to_string()),
+        ];
+        if let Some(user_id) = requested_by {
+            query.push(("requestedBy", user_id.to_string()));
+        }
+        let page: SeerrPage<SeerrRequest> = self.get(ctx, "/api/v1/request", &query).await?;
+        Ok(page.results)
+    }
+
+    /// Get a single request
+    pub async fn get_request(&self, ctx: &IntegrationContext, id: i64) -> Result<SeerrRequest> {
+        self.get(ctx, &format!("/api/v1/request/{}", id), &[]).await
+    }
+
+    /// Create a request on behalf of a user
+    pub async fn create_request(
+        &self,
+        ctx: &IntegrationContext,
+        body: &SeerrCreateRequest,
+    ) -> Result<SeerrRequest> {
+        self.post(ctx, "/api/v1/request", body).await
+    }
+
+    /// Approve or decline a pending request
+    pub async fn update_request_status(
+        &self,
+        ctx: &IntegrationContext,
+        id: i64,
+        approve: bool,
+    ) -> Result<SeerrRequest> {
+        let action = if approve { "approve" } else { "decline" };
+        self.post(
+            ctx,
+            &format!("/api/v1/request/{}/{}", id, action),
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    /// List users (for account linking)
+    pub async fn list_users(&self, ctx: &IntegrationContext) -> Result<Vec<SeerrUser>> {
+        let page: SeerrPage<SeerrUser> = self
+            .get(ctx, "/api/v1/user", &[("take", "500".to_string())])
+            .await?;
+        Ok(page.results)
+    }
+
+    /// Create a local user; the app applies its default permissions
+    pub async fn create_user(
+        &self,
+        ctx: &IntegrationContext,
+        username: &str,
+        email: &str,
+    ) -> Result<SeerrUser> {
+        self.post(
+            ctx,
+            "/api/v1/user",
+            &serde_json::json!({"username": username, "email": email}),
+        )
+        .await
+    }
+
+    /// Take every permission from a user, which keeps them from requesting
+    pub async fn clear_permissions(&self, ctx: &IntegrationContext, id: i64) -> Result<()> {
+        let _: serde_json::Value = self
+            .post(
+                ctx,
+                &format!("/api/v1/user/{}/settings/permissions", id),
+                &serde_json::json!({"permissions": 0}),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete a user
+    pub async fn delete_user(&self, ctx: &IntegrationContext, id: i64) -> Result<()> {
+        let resp = ctx
+            .client()
+            .delete(ctx.url(&format!("/api/v1/user/{}", id)))
+            .header("X-Api-Key", ctx.require_api_key()?)
+            .send()
+            .await
+            .map_err(|e| upstream_error(self.app(), e))?;
+        check_status(self.app(), resp)?;
+        Ok(())
+    }
+
+    /// Import Jellyfin users, who then sign in with their Jellyfin password
+    pub async fn import_jellyfin_users(
+        &self,
+        ctx: &IntegrationContext,
+        jellyfin_ids: &[String],
+    ) -> Result<Vec<SeerrUser>> {
+        self.post(
+            ctx,
+            "/api/v1/user/import-from-jellyfin",
+            &serde_json::json!({"jellyfinUserIds": jellyfin_ids}),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl IntegrationAdapter for SeerrAdapter {
+    fn kind(&self) -> &'static str {
+        self.app()
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let counts = self.request_counts(ctx).await?;
+        Ok(vec![
+            NativeMetric::new(
+                "requests_pending",
+                "Pending requests",
+                counts.pending as f64,
+                None,
+            ),
+            NativeMetric::new(
+                "requests_processing",
+                "Processing",
+                counts.processing as f64,
+                None,
+            ),
+            NativeMetric::new(
+                "requests_total",
+                "Total requests",
+                counts.total as f64,
+                None,
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_parse() {
+        let json = r#"{"id":12,"status":1,"type":"movie","is4k":false,"createdAt":"2026-10-16T10:00:00.000Z",
+            "media":{"tmdbId":603,"status":2},"requestedBy":{"id":5,"displayName":"alice"}}"#;
+        let req: SeerrRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.id, 12);
+        assert_eq!(request_status_name(req.status), "pending");
+        assert_eq!(req.media_type, "movie");
+        assert_eq!(req.media.unwrap().tmdb_id, Some(603));
+        assert_eq!(req.requested_by.unwrap().id, 5);
+        assert!(req.created_at.is_some());
+    }
+
+    #[test]
+    fn test_create_request_body() {
+        let body = SeerrCreateRequest {
+            media_type: "tv".to_string(),
+            media_id: 1399,
+            seasons: Some(vec![1, 2]),
+            is4k: false,
+            user_id: 7,
+        };
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["mediaType"], "tv");
+        assert_eq!(json["mediaId"], 1399);
+        assert_eq!(json["userId"], 7);
+        assert_eq!(json["seasons"][1], 2);
+    }
+}
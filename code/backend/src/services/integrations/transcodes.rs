@@ -0,0 +1,308 @@
+//! Transcoding load for media servers
+//!
+//! Normalizes active playback sessions from Jellyfin and Plex and derives
+//! capacity alerts by correlating software/hardware transcodes with the load
+//! on the node the media server runs on.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{adapter_for, build_context};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+/// Node CPU usage at which software transcodes raise a warning
+pub const CPU_WARNING_PERCENT: f64 = 85.0;
+/// Node CPU usage at which software transcodes become critical
+pub const CPU_CRITICAL_PERCENT: f64 = 95.0;
+/// GPU utilization at which hardware transcodes raise a warning
+pub const GPU_WARNING_PERCENT: f64 = 90.0;
+
+/// How a stream is being delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackDecision {
+    /// File is sent as-is
+    DirectPlay,
+    /// Container is remuxed, codecs are copied
+    DirectStream,
+    /// Video and/or audio is re-encoded
+    Transcode,
+}
+
+/// An active playback session on a media server
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PlaybackSession {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    pub decision: PlaybackDecision,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_video_codec: Option<String>,
+    /// Codec being encoded to (same as source when not transcoding video)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_codec: Option<String>,
+    /// Hardware acceleration in use (e.g., "vaapi", "nvenc"); None for software
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardware_acceleration: Option<String>,
+    /// Transcode progress from 0 to 100, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    /// Why the server is transcoding (e.g., "VideoCodecNotSupported")
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+impl PlaybackSession {
+    pub fn is_transcode(&self) -> bool {
+        self.decision == PlaybackDecision::Transcode
+    }
+
+    pub fn is_hardware_transcode(&self) -> bool {
+        self.is_transcode() && self.hardware_acceleration.is_some()
+    }
+}
+
+/// Resource usage of the node running the media server
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct NodeLoad {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// CPU used by the media server's containers
+    pub app_cpu_usage_cores: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_cpu_usage_percent: Option<f64>,
+    /// GPU utilization (NVIDIA DCGM exporter), if reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu_utilization_percent: Option<f64>,
+}
+
+/// A capacity problem caused by transcoding
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CapacityAlert {
+    /// "warning" or "critical"
+    pub severity: String,
+    pub message: String,
+}
+
+/// Response for GET /api/monitoring/apps/{app_name}/transcodes
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AppTranscodes {
+    pub app_name: String,
+    pub available: bool,
+    pub active_streams: usize,
+    pub transcodes: usize,
+    pub hardware_transcodes: usize,
+    pub software_transcodes: usize,
+    pub sessions: Vec<PlaybackSession>,
+    pub node_load: NodeLoad,
+    pub capacity_alerts: Vec<CapacityAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Apps whose adapter reports playback sessions
+pub fn media_servers() -> Vec<&'static str> {
+    super::supported_apps()
+        .into_iter()
+        .filter(|app| adapter_for(app).is_some_and(|a| a.is_media_server()))
+        .collect()
+}
+
+/// Fetch playback sessions from a media server
+///
+/// Upstream failures are returned as `Err` so the caller can still report
+/// node load alongside the error.
+pub async fn fetch_sessions(state: &AppState, app_name: &str) -> Result<Vec<PlaybackSession>> {
+    let adapter = adapter_for(app_name)
+        .filter(|a| a.is_media_server())
+        .ok_or_else(|| {
+            AppError::NotFound(format!("'{}' is not a supported media server", app_name))
+        })?;
+    let ctx = build_context(state, app_name).await?;
+    adapter.playback_sessions(&ctx).await
+}
+
+/// Derive capacity alerts from transcode counts and node load
+pub fn capacity_alerts(sessions: &[PlaybackSession], load: &NodeLoad) -> Vec<CapacityAlert> {
+    let software = sessions
+        .iter()
+        .filter(|s| s.is_transcode() && !s.is_hardware_transcode())
+        .count();
+    let hardware = sessions
+        .iter()
+        .filter(|s| s.is_hardware_transcode())
+        .count();
+
+    let mut alerts = Vec::new();
+
+    if let Some(cpu) = load.node_cpu_usage_percent {
+        if software > 0 && cpu >= CPU_WARNING_PERCENT {
+            let severity = if cpu >= CPU_CRITICAL_PERCENT {
+                "critical"
+            } else {
+                "warning"
+            };
+            alerts.push(CapacityAlert {
+                severity: severity.to_string(),
+                message: format!(
+                    "Node CPU at {:.0}% with {} software transcode(s); playback may buffer",
+                    cpu, software
+                ),
+            });
+        }
+    }
+
+    if let Some(gpu) = load.gpu_utilization_percent {
+        if hardware > 0 && gpu >= GPU_WARNING_PERCENT {
+            alerts.push(CapacityAlert {
+                severity: "warning".to_string(),
+                message: format!("GPU at {:.0}% with {} hardware transcode(s)", gpu, hardware),
+            });
+        }
+        if software > 0 && gpu < GPU_WARNING_PERCENT {
+            alerts.push(CapacityAlert {
+                severity: "warning".to_string(),
+                message: format!(
+                    "{} transcode(s) running in software although a GPU is available",
+                    software
+                ),
+            });
+        }
+    }
+
+    alerts
+}
+
+/// Build the transcode report from sessions (or the fetch error) and node load
+pub fn build_report(
+    app_name: &str,
+    sessions: Result<Vec<PlaybackSession>>,
+    node_load: NodeLoad,
+) -> AppTranscodes {
+    let (sessions, error) = match sessions {
+        Ok(sessions) => (sessions, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    let transcodes = sessions.iter().filter(|s| s.is_transcode()).count();
+    let hardware_transcodes = sessions
+        .iter()
+        .filter(|s| s.is_hardware_transcode())
+        .count();
+
+    AppTranscodes {
+        app_name: app_name.to_string(),
+        available: error.is_none(),
+        active_streams: sessions.len(),
+        transcodes,
+        hardware_transcodes,
+        software_transcodes: transcodes - hardware_transcodes,
+        capacity_alerts: capacity_alerts(&sessions, &node_load),
+        sessions,
+        node_load,
+        error,
+        fetched_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(decision: PlaybackDecision, hw: Option<&str>) -> PlaybackSession {
+        PlaybackSession {
+            user: None,
+            title: "Movie".to_string(),
+            device: None,
+            decision,
+            source_video_codec: Some("hevc".to_string()),
+            video_codec: Some("h264".to_string()),
+            audio_codec: None,
+            hardware_acceleration: hw.map(String::from),
+            progress: None,
+            reasons: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_media_servers() {
+        let servers = media_servers();
+        assert!(servers.contains(&"jellyfin"));
+        assert!(servers.contains(&"plex"));
+        assert!(!servers.contains(&"sonarr"));
+    }
+
+    #[test]
+    fn test_build_report_counts() {
+        let sessions = vec![
+            session(PlaybackDecision::DirectPlay, None),
+            session(PlaybackDecision::Transcode, Some("vaapi")),
+            session(PlaybackDecision::Transcode, None),
+        ];
+        let report = build_report("jellyfin", Ok(sessions), NodeLoad::default());
+        assert!(report.available);
+        assert_eq!(report.active_streams, 3);
+        assert_eq!(report.transcodes, 2);
+        assert_eq!(report.hardware_transcodes, 1);
+        assert_eq!(report.software_transcodes, 1);
+        assert!(report.capacity_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_build_report_error() {
+        let report = build_report(
+            "plex",
+            Err(AppError::ServiceUnavailable("down".to_string())),
+            NodeLoad::default(),
+        );
+        assert!(!report.available);
+        assert_eq!(report.active_streams, 0);
+        assert!(report.error.is_some());
+    }
+
+    #[test]
+    fn test_capacity_alerts_software_cpu() {
+        let sessions = vec![session(PlaybackDecision::Transcode, None)];
+        let mut load = NodeLoad {
+            node_cpu_usage_percent: Some(88.0),
+            ..Default::default()
+        };
+        let alerts = capacity_alerts(&sessions, &load);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, "warning");
+
+        load.node_cpu_usage_percent = Some(97.0);
+        assert_eq!(capacity_alerts(&sessions, &load)[0].severity, "critical");
+
+        // Hardware transcodes don't count against CPU
+        let hw = vec![session(PlaybackDecision::Transcode, Some("nvenc"))];
+        assert!(capacity_alerts(&hw, &load).is_empty());
+    }
+
+    #[test]
+    fn test_capacity_alerts_gpu() {
+        let load = NodeLoad {
+            gpu_utilization_percent: Some(95.0),
+            ..Default::default()
+        };
+        let hw = vec![session(PlaybackDecision::Transcode, Some("nvenc"))];
+        let alerts = capacity_alerts(&hw, &load);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].message.contains("GPU at 95%"));
+
+        let idle_gpu = NodeLoad {
+            gpu_utilization_percent: Some(10.0),
+            ..Default::default()
+        };
+        let sw = vec![session(PlaybackDecision::Transcode, None)];
+        let alerts = capacity_alerts(&sw, &idle_gpu);
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].message.contains("in software"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Transmission adapter
+//!
+//! Uses the JSON-RPC API at `/transmission/rpc`. Transmission requires a CSRF
+//! token: the first request returns 409 with an `X-Transmission-Session-Id`
+//! header that must be echoed on a retry. Optional basic auth uses the stored
+//! username/password.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::downloads::{DownloadItem, DownloadState};
+use super::{check_status, upstream_error, IntegrationAdapter, IntegrationContext, NativeMetric};
+use crate::error::{AppError, Result};
+
+const APP: &str = "transmission";
+const SESSION_HEADER: &str = "X-Transmission-Session-Id";
+
+/// Adapter for Transmission
+pub struct TransmissionAdapter;
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: String,
+    #[serde(default)]
+    arguments: Option<T>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TorrentList {
+    #[serde(default)]
+    torrents: Vec<TransmissionTorrent>,
+}
+
+/// A torrent from the `torrent-get` RPC method
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransmissionTorrent {
+    #[serde(default)]
+    pub hash_string: String,
+    #[serde(default)]
+    pub name: String,
+    /// 0 = stopped, 1/2 = checking, 3 = download queued, 4 = downloading,
+    /// 5 = seed queued, 6 = seeding
+    #[serde(default)]
+    pub status: i64,
+    #[serde(default)]
+    pub percent_done: f64,
+    #[serde(default)]
+    pub total_size: u64,
+    #[serde(default)]
+    pub rate_download: u64,
+    #[serde(default)]
+    pub rate_upload: u64,
+    /// Seconds remaining; -1 = not available, -2 = unknown
+    #[serde(default)]
+    pub eta: i64,
+    #[serde(default)]
+    pub error: i64,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+fn map_state(torrent: &TransmissionTorrent) -> DownloadState {
+    if torrent.error != 0 {
+        return DownloadState::Error;
+    }
+    match torrent.status {
+        0 => DownloadState::Paused,
+        1 | 2 => DownloadState::Checking,
+        3 => DownloadState::Queued,
+        4 if torrent.rate_download == 0 => DownloadState::Stalled,
+        _ => DownloadState::Downloading,
+    }
+}
+
+/// Convert incomplete torrents into normalized download items
+pub fn torrent_items(torrents: &[TransmissionTorrent]) -> Vec<DownloadItem> {
+    torrents
+        .iter()
+        .filter(|t| t.percent_done < 1.0)
+        .map(|t| DownloadItem {
+            app_name: APP.to_string(),
+            id: t.hash_string.clone(),
+            name: t.name.clone(),
+            state: map_state(t),
+            progress: t.percent_done,
+            size_bytes: t.total_size,
+            download_rate: t.rate_download,
+            upload_rate: t.rate_upload,
+            eta_seconds: u64::try_from(t.eta).ok(),
+            category: t.labels.first().cloned(),
+        })
+        .collect()
+}
+
+/// Send an RPC request, performing the session-id handshake if needed
+async fn rpc_call(ctx: &IntegrationContext, body: &serde_json::Value) -> Result<reqwest::Response> {
+    let url = ctx.url("/transmission/rpc");
+    let build = |session_id: Option<&str>| {
+        let mut req = ctx.client().post(&url).json(body);
+        if let Some(username) = ctx.credentials.username.as_deref() {
+            req = req.basic_auth(username, ctx.credentials.password.as_deref());
+        }
+        if let Some(id) = session_id {
+            req = req.header(SESSION_HEADER, id);
+        }
+        req
+    };
+
+    let resp = build(None)
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    if resp.status() != reqwest::StatusCode::CONFLICT {
+        return check_status(APP, resp);
+    }
+
+    let session_id = resp
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| AppError::BadGateway(format!("{} did not return a session id", APP)))?
+        .to_string();
+
+    let resp = build(Some(&session_id))
+        .send()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+    check_status(APP, resp)
+}
+
+async fn fetch_torrents(ctx: &IntegrationContext) -> Result<Vec<TransmissionTorrent>> {
+    let body = serde_json::json!({
+        "method": "torrent-get",
+        "arguments": {
+            "fields": [
+                "hashString", "name", "status", "percentDone", "totalSize",
+                "rateDownload", "rateUpload", "eta", "error", "labels"
+            ]
+        }
+    });
+
+    let resp: RpcResponse<TorrentList> = rpc_call(ctx, &body)
+        .await?
+        .json()
+        .await
+        .map_err(|e| upstream_error(APP, e))?;
+
+    if resp.result != "success" {
+        return Err(AppError::BadGateway(format!(
+            "{} RPC failed: {}",
+            APP, resp.result
+        )));
+    }
+
+    Ok(resp.arguments.unwrap_or_default().torrents)
+}
+
+#[async_trait]
+impl IntegrationAdapter for TransmissionAdapter {
+    fn kind(&self) -> &'static str {
+        APP
+    }
+
+    async fn native_status(&self, ctx: &IntegrationContext) -> Result<Vec<NativeMetric>> {
+        let torrents = fetch_torrents(ctx).await?;
+        let download_rate: u64 = torrents.iter().map(|t| t.rate_download).sum();
+        let upload_rate: u64 = torrents.iter().map(|t| t.rate_upload).sum();
+        let active = torrents.iter().filter(|t| t.percent_done < 1.0).count();
+
+        Ok(vec![
+            NativeMetric::new(
+                "download_rate",
+                "Download rate",
+                download_rate as f64,
+                Some("bytes/s"),
+            ),
+            NativeMetric::new(
+                "upload_rate",
+                "Upload rate",
+                upload_rate as f64,
+                Some("bytes/s"),
+            ),
+            NativeMetric::new("active_downloads", "Downloading", active as f64, None),
+            NativeMetric::new("torrents", "Torrents", torrents.len() as f64, None),
+        ])
+    }
+
+    fn is_download_client(&self) -> bool {
+        true
+    }
+
+    async fn downloads(&self, ctx: &IntegrationContext) -> Result<Vec<DownloadItem>> {
+        let torrents = fetch_torrents(ctx).await?;
+        Ok(torrent_items(&torrents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_torrent_items() {
+        let json = r#"{"result":"success","arguments":{"torrents":[
+            {"hashString":"a","name":"Active","status":4,"percentDone":0.5,"totalSize":1000,"rateDownload":100,"rateUpload":5,"eta":30,"error":0,"labels":["tv"]},
+            {"hashString":"b","name":"Stalled","status":4,"percentDone":0.1,"totalSize":1000,"rateDownload":0,"rateUpload":0,"eta":-1,"error":0,"labels":[]},
+            {"hashString":"c","name":"Done","status":6,"percentDone":1.0,"totalSize":1000,"rateDownload":0,"rateUpload":50,"eta":-1,"error":0,"labels":[]},
+            {"hashString":"d","name":"Broken","status":0,"percentDone":0.2,"totalSize":1000,"rateDownload":0,"rateUpload":0,"eta":-2,"error":3,"labels":[]}
+        ]}}"#;
+        let resp: RpcResponse<TorrentList> = serde_json::from_str(json).unwrap();
+        let items = torrent_items(&resp.arguments.unwrap().torrents);
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].state, DownloadState::Downloading);
+        assert_eq!(items[0].eta_seconds, Some(30));
+        assert_eq!(items[0].category.as_deref(), Some("tv"));
+        assert_eq!(items[1].state, DownloadState::Stalled);
+        assert_eq!(items[1].eta_seconds, None);
+        assert_eq!(items[2].state, DownloadState::Error);
+    }
+}
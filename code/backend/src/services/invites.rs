@@ -0,0 +1,227 @@
+//! Invite batches
+//!
+//! An admin onboarding a group creates several invites at once under one
+//! label. The batch gets a share page listing a QR code per invite, which
+//! can be printed or shown on a screen so guests scan their own code instead
+//! of having links copied to them one by one. The page is public but only
+//! reachable through the batch's unguessable token, expires after
+//! `share_expires_in_hours`, and only shows invites that can still be used.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+use super::links;
+use super::maintenance::escape_html;
+use super::qr;
+use super::security::generate_random_string;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{invite, invite_batch};
+
+/// Most invites created in one batch
+pub const MAX_BATCH_SIZE: u32 = 50;
+
+/// Longest a share page stays reachable
+pub const MAX_SHARE_HOURS: i64 = 30 * 24;
+
+const MAX_LABEL_LEN: usize = 100;
+
+/// Registration link for an invite code
+pub fn register_link(code: &str) -> String {
+    links::absolute(&format!("/auth/register?invite={}", code))
+}
+
+/// Link to a batch's share page
+pub fn share_link(token: &str) -> String {
+    links::absolute(&format!("/api/invites/share/{}", token))
+}
+
+/// Create `count` invites under one label, with a share page listing them
+pub async fn create_batch(
+    db: &DatabaseConnection,
+    created_by_id: i64,
+    label: &str,
+    count: u32,
+    expires_in_days: i32,
+    share_expires_in_hours: i64,
+) -> Result<(invite_batch::Model, Vec<invite::Model>)> {
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > MAX_LABEL_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Label must be 1 to {} characters",
+            MAX_LABEL_LEN
+        )));
+    }
+    if count == 0 || count > MAX_BATCH_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "Count must be between 1 and {}",
+            MAX_BATCH_SIZE
+        )));
+    }
+    if !(1..=MAX_SHARE_HOURS).contains(&share_expires_in_hours) {
+        return Err(AppError::BadRequest(format!(
+            "Share page expiry must be between 1 and {} hours",
+            MAX_SHARE_HOURS
+        )));
+    }
+
+    let now = Utc::now();
+    let expires_at = (expires_in_days > 0).then(|| now + Duration::days(expires_in_days as i64));
+    let batch = invite_batch::ActiveModel {
+        label: Set(label.to_string()),
+        share_token: Set(generate_random_string(32)),
+        created_by_id: Set(created_by_id),
+        share_expires_at: Set(now + Duration::hours(share_expires_in_hours)),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    let mut invites = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let created = invite::ActiveModel {
+            code: Set(generate_random_string(32)),
+            created_by_id: Set(created_by_id),
+            expires_at: Set(expires_at),
+            created_at: Set(now),
+            is_used: Set(false),
+            batch_id: Set(Some(batch.id)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        invites.push(created);
+    }
+    Ok((batch, invites))
+}
+
+/// The batch a share token belongs to and its invites that can still be
+/// used, or an error when the page does not exist or has expired
+pub async fn resolve_share(
+    db: &DatabaseConnection,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<(invite_batch::Model, Vec<invite::Model>)> {
+    let batch = InviteBatch::find()
+        .filter(invite_batch::Column::ShareToken.eq(token))
+        .one(db)
+        .await?
+        .filter(|b| b.share_expires_at > now)
+        .ok_or_else(|| {
+            AppError::NotFound("This invite page does not exist or has expired".to_string())
+        })?;
+    let invites = Invite::find()
+        .filter(invite::Column::BatchId.eq(batch.id))
+        .filter(invite::Column::IsUsed.eq(false))
+        .order_by_asc(invite::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|i| i.expires_at.is_none_or(|at| at > now))
+        .collect();
+    Ok((batch, invites))
+}
+
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{{label}} - Kubarr invites</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #111827; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+.hint { color: #6b7280; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(12rem, 1fr)); gap: 1.5rem; margin-top: 1.5rem; }
+.invite { border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 1rem; text-align: center; break-inside: avoid; }
+.invite svg { width: 100%; height: auto; }
+.code { font-family: monospace; font-size: 0.75rem; word-break: break-all; color: #374151; }
+@media print { body { margin: 0; } .hint { display: none; } }
+</style>
+</head>
+<body>
+<h1>{{label}}</h1>
+<p class="hint">{{summary}}</p>
+<div class="grid">
+{{invites}}
+</div>
+</body>
+</html>
+"#;
+
+/// Render a batch's share page: a QR code of the registration link per
+/// invite, with the link for guests who cannot scan
+pub fn render_share_page(batch: &invite_batch::Model, invites: &[invite::Model]) -> Result<String> {
+    let mut cards = String::new();
+    for (i, invite) in invites.iter().enumerate() {
+        let link = register_link(&invite.code);
+        cards.push_str(&format!(
+            r#"<div class="invite"><div>#{}</div>{}<div class="code"><a href="{}">{}</a></div></div>
+"#,
+            i + 1,
+            qr::svg(&link)?,
+            escape_html(&link),
+            escape_html(&invite.code),
+        ));
+    }
+    let summary = match invites.len() {
+        0 => "All invites on this page have been used or have expired.".to_string(),
+        n => format!(
+            "{} invite{} left. Scan a code to create your account; each code works once. This page expires {}.",
+            n,
+            if n == 1 { "" } else { "s" },
+            batch.share_expires_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
+    Ok(PAGE_TEMPLATE
+        .replace("{{label}}", &escape_html(&batch.label))
+        .replace("{{summary}}", &escape_html(&summary))
+        .replace("{{invites}}", &cards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> invite_batch::Model {
+        invite_batch::Model {
+            id: 1,
+            label: "LAN <party>".to_string(),
+            share_token: "token".to_string(),
+            created_by_id: 1,
+            share_expires_at: Utc::now(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn invite(code: &str) -> invite::Model {
+        invite::Model {
+            id: 1,
+            code: code.to_string(),
+            created_by_id: 1,
+            used_by_id: None,
+            is_used: false,
+            expires_at: None,
+            created_at: Utc::now(),
+            used_at: None,
+            batch_id: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_render_share_page() {
+        let page = render_share_page(&batch(), &[invite("abc"), invite("def")]).unwrap();
+        assert!(page.contains("<h1>LAN &lt;party&gt;</h1>"));
+        assert!(page.contains("2 invites left."));
+        assert_eq!(page.matches("<svg").count(), 2);
+        assert!(page.contains("/auth/register?invite=def"));
+
+        let page = render_share_page(&batch(), &[]).unwrap();
+        assert!(page.contains("have been used or have expired"));
+        assert!(!page.contains("<svg"));
+    }
+}
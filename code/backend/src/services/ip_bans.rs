@@ -0,0 +1,510 @@
+//! IP bans
+//!
+//! Failed sign-ins with a password, a 2FA code or a recovery code, on the
+//! sign-in page or over WebDAV, are recorded in the audit log with the
+//! client's address. Once an address reaches `ip_ban_threshold` failures
+//! within `ip_ban_window_minutes`, it is banned for `ip_ban_duration_minutes`
+//! and an `ip_banned` event goes out. Admins with `security.manage` can list
+//! and lift bans, and add manual ones that last until a given time or until
+//! removed.
+//!
+//! Banned addresses get `403 Forbidden` on the unauthenticated routes: the
+//! sign-in endpoints, WebDAV, setup and public links. To block them before
+//! they reach Kubarr, the ban list can be exported as an NGINX ingress
+//! `denylist-source-range` value, a plain list, or CrowdSec decisions.
+//!
+//! Addresses come from `X-Forwarded-For` or `X-Real-IP`, as set by the
+//! ingress in front of Kubarr. Failures are also handed to the CrowdSec and
+//! Fail2ban hooks in [`crowdsec`].
+
+use std::net::IpAddr;
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use super::crowdsec;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::{audit_log, ip_ban};
+use crate::state::AppState;
+
+/// Failed sign-ins from one address that trigger a ban; 0 turns automatic
+/// bans off
+pub const IP_BAN_THRESHOLD: &str = "ip_ban_threshold";
+
+/// Minutes failed sign-ins are counted over
+pub const IP_BAN_WINDOW_MINUTES: &str = "ip_ban_window_minutes";
+
+/// Minutes an automatic ban lasts
+pub const IP_BAN_DURATION_MINUTES: &str = "ip_ban_duration_minutes";
+
+/// Source of bans added after too many failed sign-ins
+pub const SOURCE_AUTOMATIC: &str = "automatic";
+
+/// Source of bans added by an admin
+pub const SOURCE_MANUAL: &str = "manual";
+
+/// Duration given to CrowdSec for bans without an expiry
+const PERMANENT_HOURS: i64 = 10 * 365 * 24;
+
+const MAX_REASON_LEN: usize = 255;
+
+/// Audit actions that count as failed sign-ins
+pub(crate) fn failure_actions() -> [String; 2] {
+    [
+        AuditAction::LoginFailed.to_string(),
+        AuditAction::TwoFactorFailed.to_string(),
+    ]
+}
+
+/// When failed sign-ins lead to a ban
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BanPolicy {
+    pub threshold: u32,
+    pub window: Duration,
+    pub duration: Duration,
+}
+
+/// A whole number setting, or an error naming the setting
+pub fn parse_setting(key: &str, value: &str) -> Result<u32> {
+    let minimum = if key == IP_BAN_THRESHOLD { 0 } else { 1 };
+    value
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n >= minimum)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a whole number of at least {}",
+                key, minimum
+            ))
+        })
+}
+
+/// The configured ban policy; unreadable settings fall back to the defaults
+pub async fn policy(db: &DatabaseConnection) -> Result<BanPolicy> {
+    let read = |key: &'static str, default: u32| async move {
+        Ok::<_, AppError>(
+            get_setting_value(db, key)
+                .await?
+                .and_then(|v| parse_setting(key, &v).ok())
+                .unwrap_or(default),
+        )
+    };
+    Ok(BanPolicy {
+        threshold: read(IP_BAN_THRESHOLD, 10).await?,
+        window: Duration::minutes(read(IP_BAN_WINDOW_MINUTES, 15).await? as i64),
+        duration: Duration::minutes(read(IP_BAN_DURATION_MINUTES, 60).await? as i64),
+    })
+}
+
+/// Client address of a request, as reported by the ingress
+pub fn client_ip(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .or_else(|| headers.get("x-real-ip"))
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.split(',').next().unwrap_or(s).trim().to_string())
+}
+
+/// An address in canonical form, or an error for anything else
+pub fn parse_ip(value: &str) -> Result<String> {
+    value
+        .trim()
+        .parse::<IpAddr>()
+        .map(|ip| ip.to_string())
+        .map_err(|_| AppError::BadRequest(format!("'{}' is not an IP address", value.trim())))
+}
+
+/// Active bans only: without an expiry or expiring after `now`
+fn active(now: DateTime<Utc>) -> Condition {
+    Condition::any()
+        .add(ip_ban::Column::ExpiresAt.is_null())
+        .add(ip_ban::Column::ExpiresAt.gt(now))
+}
+
+/// The active ban on an address, if any
+pub async fn active_ban(
+    db: &DatabaseConnection,
+    ip: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<ip_ban::Model>> {
+    Ok(IpBan::find()
+        .filter(ip_ban::Column::IpAddress.eq(ip))
+        .filter(active(now))
+        .one(db)
+        .await?)
+}
+
+/// Active bans, newest first
+pub async fn list_bans(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<Vec<ip_ban::Model>> {
+    Ok(IpBan::find()
+        .filter(active(now))
+        .order_by_desc(ip_ban::Column::CreatedAt)
+        .all(db)
+        .await?)
+}
+
+/// Ban an address, replacing any earlier ban on it
+pub async fn ban(
+    db: &DatabaseConnection,
+    ip: &str,
+    source: &str,
+    reason: Option<String>,
+    failed_attempts: Option<i32>,
+    created_by_id: Option<i64>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ip_ban::Model> {
+    if reason
+        .as_ref()
+        .is_some_and(|r| r.chars().count() > MAX_REASON_LEN)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Reason must be at most {} characters",
+            MAX_REASON_LEN
+        )));
+    }
+    IpBan::delete_many()
+        .filter(ip_ban::Column::IpAddress.eq(ip))
+        .exec(db)
+        .await?;
+    Ok(ip_ban::ActiveModel {
+        ip_address: Set(ip.to_string()),
+        source: Set(source.to_string()),
+        reason: Set(reason),
+        failed_attempts: Set(failed_attempts),
+        created_by_id: Set(created_by_id),
+        created_at: Set(Utc::now()),
+        expires_at: Set(expires_at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Failed sign-ins from an address since `since`
+pub async fn failed_attempts(
+    db: &DatabaseConnection,
+    ip: &str,
+    since: DateTime<Utc>,
+) -> Result<u64> {
+    Ok(AuditLog::find()
+        .filter(audit_log::Column::Action.is_in(failure_actions()))
+        .filter(audit_log::Column::IpAddress.eq(ip))
+        .filter(audit_log::Column::Timestamp.gte(since))
+        .count(db)
+        .await?)
+}
+
+/// Time of the latest failed sign-in from an address since `since`
+pub async fn last_failure_at(
+    db: &DatabaseConnection,
+    ip: &str,
+    since: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    Ok(AuditLog::find()
+        .filter(audit_log::Column::Action.is_in(failure_actions()))
+        .filter(audit_log::Column::IpAddress.eq(ip))
+        .filter(audit_log::Column::Timestamp.gte(since))
+        .order_by_desc(audit_log::Column::Timestamp)
+        .one(db)
+        .await?
+        .map(|entry| entry.timestamp))
+}
+
+/// Failed sign-ins from one address
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct FailedLoginSource {
+    pub ip_address: String,
+    pub attempts: i64,
+    /// Distinct usernames tried
+    pub usernames: Vec<String>,
+    #[schema(value_type = String)]
+    pub last_attempt_at: DateTime<Utc>,
+    pub banned: bool,
+}
+
+/// Failed sign-ins since `since` per address, most attempts first
+pub async fn failed_login_sources(
+    db: &DatabaseConnection,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<Vec<FailedLoginSource>> {
+    let failures = AuditLog::find()
+        .filter(audit_log::Column::Action.is_in(failure_actions()))
+        .filter(audit_log::Column::IpAddress.is_not_null())
+        .filter(audit_log::Column::Timestamp.gte(since))
+        .order_by_asc(audit_log::Column::Timestamp)
+        .limit(10_000)
+        .all(db)
+        .await?;
+    let banned: Vec<String> = list_bans(db, now)
+        .await?
+        .into_iter()
+        .map(|b| b.ip_address)
+        .collect();
+
+    let mut sources: Vec<FailedLoginSource> = Vec::new();
+    for failure in failures {
+        let Some(ip) = failure.ip_address else {
+            continue;
+        };
+        let source = match sources.iter_mut().position(|s| s.ip_address == ip) {
+            Some(i) => &mut sources[i],
+            None => {
+                sources.push(FailedLoginSource {
+                    banned: banned.contains(&ip),
+                    ip_address: ip,
+                    attempts: 0,
+                    usernames: Vec::new(),
+                    last_attempt_at: failure.timestamp,
+                });
+                sources.last_mut().unwrap()
+            }
+        };
+        source.attempts += 1;
+        source.last_attempt_at = failure.timestamp;
+        if let Some(username) = failure.username {
+            if !source.usernames.contains(&username) {
+                source.usernames.push(username);
+            }
+        }
+    }
+    sources.sort_by(|a, b| {
+        b.attempts
+            .cmp(&a.attempts)
+            .then(b.last_attempt_at.cmp(&a.last_attempt_at))
+    });
+    Ok(sources)
+}
+
+/// Record a failed sign-in and ban the address if it went over the
+/// threshold; returns the new ban
+pub async fn record_failure(
+    state: &AppState,
+    action: AuditAction,
+    username: &str,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    method: &str,
+) -> Result<Option<ip_ban::Model>> {
+    state
+        .audit
+        .log_failure(
+            action,
+            ResourceType::User,
+            None,
+            None,
+            Some(username.chars().take(255).collect()),
+            Some(serde_json::json!({ "method": method })),
+            ip.clone(),
+            user_agent,
+            "Invalid credentials",
+        )
+        .await?;
+
+    let Some(ip) = ip else {
+        return Ok(None);
+    };
+    crowdsec::log_auth_failure(username, &ip, method).await;
+    let db = state.get_db().await?;
+    let policy = policy(&db).await?;
+    let now = Utc::now();
+    if policy.threshold == 0 || active_ban(&db, &ip, now).await?.is_some() {
+        return Ok(None);
+    }
+    let attempts = failed_attempts(&db, &ip, now - policy.window).await?;
+    if attempts < policy.threshold as u64 {
+        return Ok(None);
+    }
+
+    let minutes = policy.window.num_minutes();
+    let created = ban(
+        &db,
+        &ip,
+        SOURCE_AUTOMATIC,
+        Some(format!(
+            "{} failed sign-ins in {} minute{}",
+            attempts,
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        )),
+        Some(attempts as i32),
+        None,
+        Some(now + policy.duration),
+    )
+    .await?;
+    tracing::warn!(
+        ip_address = ip,
+        attempts = attempts,
+        "Banned address after repeated failed sign-ins"
+    );
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::IpBanned,
+            ResourceType::System,
+            Some(created.ip_address.clone()),
+            None,
+            None,
+            Some(serde_json::json!({
+                "source": SOURCE_AUTOMATIC,
+                "failed_attempts": attempts,
+                "expires_at": created.expires_at,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let details = format!(
+        "{} after {} failed sign-ins, until {}",
+        created.ip_address,
+        attempts,
+        (now + policy.duration).format("%Y-%m-%d %H:%M UTC")
+    );
+    if let Err(e) = state
+        .notification
+        .notify_event(&AuditAction::IpBanned, None, None, Some(&details))
+        .await
+    {
+        tracing::warn!("Failed to send ip_banned notification: {}", e);
+    }
+    Ok(Some(created))
+}
+
+/// Format the ban list is exported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One address per line
+    #[default]
+    Plain,
+    /// Comma-separated CIDRs for the NGINX ingress
+    /// `nginx.ingress.kubernetes.io/denylist-source-range` annotation
+    Nginx,
+    /// JSON decisions for `cscli decisions import`
+    Crowdsec,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Crowdsec => "application/json",
+            _ => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// CIDR covering exactly one address
+fn host_cidr(ip: &str) -> String {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("{}/128", ip),
+        _ => format!("{}/32", ip),
+    }
+}
+
+/// Go-style duration CrowdSec accepts, rounded up to whole minutes
+fn crowdsec_duration(remaining: Duration) -> String {
+    let minutes = (remaining.num_seconds() + 59).max(60) / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+/// The ban list in an export format
+pub fn export(bans: &[ip_ban::Model], format: ExportFormat, now: DateTime<Utc>) -> String {
+    match format {
+        ExportFormat::Plain => bans.iter().map(|b| format!("{}\n", b.ip_address)).collect(),
+        ExportFormat::Nginx => bans
+            .iter()
+            .map(|b| host_cidr(&b.ip_address))
+            .collect::<Vec<_>>()
+            .join(","),
+        ExportFormat::Crowdsec => {
+            let decisions: Vec<serde_json::Value> = bans
+                .iter()
+                .map(|b| {
+                    let remaining = b
+                        .expires_at
+                        .map_or(Duration::hours(PERMANENT_HOURS), |at| at - now);
+                    serde_json::json!({
+                        "value": b.ip_address,
+                        "scope": "ip",
+                        "type": "ban",
+                        "duration": crowdsec_duration(remaining),
+                        "origin": "kubarr",
+                        "reason": b.reason.clone().unwrap_or_else(|| format!("kubarr {} ban", b.source)),
+                    })
+                })
+                .collect();
+            serde_json::Value::Array(decisions).to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ban_of(ip: &str, expires_at: Option<DateTime<Utc>>) -> ip_ban::Model {
+        ip_ban::Model {
+            id: 1,
+            ip_address: ip.to_string(),
+            source: SOURCE_MANUAL.to_string(),
+            reason: None,
+            failed_attempts: None,
+            created_by_id: None,
+            created_at: Utc::now(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_ip(" 203.0.113.7 ").unwrap(), "203.0.113.7");
+        assert_eq!(parse_ip("2001:DB8::1").unwrap(), "2001:db8::1");
+        assert!(parse_ip("203.0.113.0/24").is_err());
+        assert_eq!(parse_setting(IP_BAN_THRESHOLD, "0").unwrap(), 0);
+        assert!(parse_setting(IP_BAN_WINDOW_MINUTES, "0").is_err());
+        assert!(parse_setting(IP_BAN_DURATION_MINUTES, "-5").is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        let now = Utc::now();
+        let bans = [
+            ban_of("203.0.113.7", Some(now + Duration::minutes(90))),
+            ban_of("2001:db8::1", None),
+        ];
+        assert_eq!(
+            export(&bans, ExportFormat::Plain, now),
+            "203.0.113.7\n2001:db8::1\n"
+        );
+        assert_eq!(
+            export(&bans, ExportFormat::Nginx, now),
+            "203.0.113.7/32,2001:db8::1/128"
+        );
+        let decisions: serde_json::Value =
+            serde_json::from_str(&export(&bans, ExportFormat::Crowdsec, now)).unwrap();
+        assert_eq!(decisions[0]["value"], "203.0.113.7");
+        assert_eq!(decisions[0]["duration"], "1h30m");
+        assert_eq!(decisions[0]["reason"], "kubarr manual ban");
+        assert_eq!(decisions[1]["duration"], "87600h");
+        assert_eq!(export(&[], ExportFormat::Nginx, now), "");
+    }
+
+    #[test]
+    fn test_crowdsec_duration() {
+        assert_eq!(crowdsec_duration(Duration::seconds(10)), "1m");
+        assert_eq!(crowdsec_duration(Duration::minutes(45)), "45m");
+        assert_eq!(crowdsec_duration(Duration::minutes(120)), "2h");
+    }
+}
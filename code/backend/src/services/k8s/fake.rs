@@ -0,0 +1,390 @@
+//! In-memory Kubernetes fake
+//!
+//! Implements [`K8sApi`] over a small in-memory model of namespaces, workloads,
+//! pods and secrets, and records every mutating call so tests can assert on
+//! what the code under test asked the cluster to do.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use k8s_openapi::api::apps::v1::{
+    DaemonSet, Deployment, DeploymentSpec, DeploymentStatus as K8sDeploymentStatus,
+};
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+
+use super::{K8sApi, PodStatus};
+use crate::error::{AppError, Result};
+
+/// A mutating call made against the fake cluster
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum K8sOperation {
+    DeleteNamespace(String),
+    DeletePod {
+        namespace: String,
+        pod: String,
+    },
+    ScaleDeployment {
+        namespace: String,
+        name: String,
+        replicas: i32,
+    },
+    ReplaceSecret {
+        namespace: String,
+        name: String,
+    },
+    DeleteSecret {
+        namespace: String,
+        name: String,
+    },
+}
+
+#[derive(Default)]
+struct FakeNamespace {
+    deployments: Vec<Deployment>,
+    daemonsets: Vec<DaemonSet>,
+    pods: Vec<PodStatus>,
+    secrets: BTreeMap<String, Secret>,
+}
+
+#[derive(Default)]
+struct FakeCluster {
+    namespaces: BTreeMap<String, FakeNamespace>,
+    node_architectures: Vec<String>,
+    operations: Vec<K8sOperation>,
+}
+
+/// In-memory [`K8sApi`] implementation for tests
+///
+/// Clones share the same cluster, so a test can keep a handle after
+/// injecting the fake into `AppState`.
+#[derive(Clone, Default)]
+pub struct FakeK8s {
+    cluster: Arc<Mutex<FakeCluster>>,
+}
+
+impl FakeK8s {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(clippy::expect_used)]
+    fn cluster(&self) -> std::sync::MutexGuard<'_, FakeCluster> {
+        self.cluster.lock().expect("fake cluster lock poisoned")
+    }
+
+    /// Add an empty namespace
+    pub fn add_namespace(&self, namespace: &str) {
+        self.cluster()
+            .namespaces
+            .entry(namespace.to_string())
+            .or_default();
+    }
+
+    /// Add an app as a single-replica deployment with one pod in a namespace
+    /// of the same name
+    ///
+    /// A ready app has its replica available and its pod running; otherwise
+    /// the deployment reports no ready replicas and the pod is pending.
+    pub fn add_app(&self, app_name: &str, ready: bool) {
+        let ready_replicas = if ready { 1 } else { 0 };
+        let deployment = Deployment {
+            metadata: ObjectMeta {
+                name: Some(app_name.to_string()),
+                namespace: Some(app_name.to_string()),
+                ..Default::default()
+            },
+            spec: Some(DeploymentSpec {
+                replicas: Some(1),
+                ..Default::default()
+            }),
+            status: Some(K8sDeploymentStatus {
+                ready_replicas: Some(ready_replicas),
+                available_replicas: Some(ready_replicas),
+                ..Default::default()
+            }),
+        };
+        let pod = fake_pod(app_name, app_name, ready);
+
+        let mut cluster = self.cluster();
+        let ns = cluster.namespaces.entry(app_name.to_string()).or_default();
+        ns.deployments.push(deployment);
+        ns.pods.push(pod);
+    }
+
+    /// Set the architectures reported for the cluster's nodes; none are
+    /// reported by default
+    pub fn set_node_architectures(&self, architectures: &[&str]) {
+        let mut architectures: Vec<String> = architectures.iter().map(|a| a.to_string()).collect();
+        architectures.sort();
+        architectures.dedup();
+        self.cluster().node_architectures = architectures;
+    }
+
+    /// Mutating calls made so far, oldest first
+    pub fn operations(&self) -> Vec<K8sOperation> {
+        self.cluster().operations.clone()
+    }
+
+    /// Get a secret stored in the fake
+    pub fn secret(&self, namespace: &str, name: &str) -> Option<Secret> {
+        self.cluster()
+            .namespaces
+            .get(namespace)
+            .and_then(|ns| ns.secrets.get(name).cloned())
+    }
+}
+
+/// A pod of `app_name` in `namespace`, running when `ready`
+fn fake_pod(app_name: &str, namespace: &str, ready: bool) -> PodStatus {
+    PodStatus {
+        name: format!("{}-0", app_name),
+        app: app_name.to_string(),
+        namespace: namespace.to_string(),
+        status: if ready { "Running" } else { "Pending" }.to_string(),
+        ready,
+        restart_count: 0,
+        age: "1h".to_string(),
+        node: Some("fake-node".to_string()),
+        ip: None,
+        cpu_usage: None,
+        memory_usage: None,
+    }
+}
+
+#[async_trait]
+impl K8sApi for FakeK8s {
+    async fn namespace_exists(&self, namespace: &str) -> Result<bool> {
+        Ok(self.cluster().namespaces.contains_key(namespace))
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<String>> {
+        Ok(self.cluster().namespaces.keys().cloned().collect())
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<()> {
+        let mut cluster = self.cluster();
+        cluster.namespaces.remove(namespace);
+        cluster
+            .operations
+            .push(K8sOperation::DeleteNamespace(namespace.to_string()));
+        Ok(())
+    }
+
+    async fn list_deployments(&self, namespace: &str) -> Result<Vec<Deployment>> {
+        Ok(self
+            .cluster()
+            .namespaces
+            .get(namespace)
+            .map(|ns| ns.deployments.clone())
+            .unwrap_or_default())
+    }
+
+    async fn list_daemonsets(&self, namespace: &str) -> Result<Vec<DaemonSet>> {
+        Ok(self
+            .cluster()
+            .namespaces
+            .get(namespace)
+            .map(|ns| ns.daemonsets.clone())
+            .unwrap_or_default())
+    }
+
+    async fn get_pod_status(
+        &self,
+        namespace: &str,
+        app_name: Option<&str>,
+    ) -> Result<Vec<PodStatus>> {
+        Ok(self
+            .cluster()
+            .namespaces
+            .get(namespace)
+            .map(|ns| {
+                ns.pods
+                    .iter()
+                    .filter(|p| app_name.is_none_or(|app| p.app == app))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn delete_pod(&self, namespace: &str, pod_name: &str) -> Result<()> {
+        let mut cluster = self.cluster();
+        let ns = cluster
+            .namespaces
+            .get_mut(namespace)
+            .ok_or_else(|| AppError::NotFound(format!("Namespace {} not found", namespace)))?;
+        let before = ns.pods.len();
+        ns.pods.retain(|p| p.name != pod_name);
+        if ns.pods.len() == before {
+            return Err(AppError::NotFound(format!("Pod {} not found", pod_name)));
+        }
+        cluster.operations.push(K8sOperation::DeletePod {
+            namespace: namespace.to_string(),
+            pod: pod_name.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Scaling takes effect immediately: replicas are ready right away and
+    /// the deployment's pod is added or removed to match
+    async fn scale_deployment(&self, namespace: &str, name: &str, replicas: i32) -> Result<()> {
+        let mut cluster = self.cluster();
+        let ns = cluster
+            .namespaces
+            .get_mut(namespace)
+            .ok_or_else(|| AppError::NotFound(format!("Namespace {} not found", namespace)))?;
+        let deployment = ns
+            .deployments
+            .iter_mut()
+            .find(|d| d.metadata.name.as_deref() == Some(name))
+            .ok_or_else(|| AppError::NotFound(format!("Deployment {} not found", name)))?;
+        deployment
+            .spec
+            .get_or_insert_with(Default::default)
+            .replicas = Some(replicas);
+        let status = deployment.status.get_or_insert_with(Default::default);
+        status.ready_replicas = Some(replicas);
+        status.available_replicas = Some(replicas);
+
+        if replicas == 0 {
+            ns.pods.retain(|p| p.app != name);
+        } else if !ns.pods.iter().any(|p| p.app == name) {
+            ns.pods.push(fake_pod(name, namespace, true));
+        }
+        cluster.operations.push(K8sOperation::ScaleDeployment {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            replicas,
+        });
+        Ok(())
+    }
+
+    async fn replace_secret(&self, namespace: &str, secret: Secret) -> Result<()> {
+        let name = secret
+            .metadata
+            .name
+            .clone()
+            .ok_or_else(|| AppError::BadRequest("Secret has no name".to_string()))?;
+        let mut cluster = self.cluster();
+        cluster
+            .namespaces
+            .entry(namespace.to_string())
+            .or_default()
+            .secrets
+            .insert(name.clone(), secret);
+        cluster.operations.push(K8sOperation::ReplaceSecret {
+            namespace: namespace.to_string(),
+            name,
+        });
+        Ok(())
+    }
+
+    async fn delete_secret(&self, namespace: &str, secret_name: &str) -> Result<()> {
+        let mut cluster = self.cluster();
+        if let Some(ns) = cluster.namespaces.get_mut(namespace) {
+            ns.secrets.remove(secret_name);
+        }
+        cluster.operations.push(K8sOperation::DeleteSecret {
+            namespace: namespace.to_string(),
+            name: secret_name.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn node_architectures(&self) -> Result<Vec<String>> {
+        Ok(self.cluster().node_architectures.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_app_creates_namespace_and_workload() {
+        let fake = FakeK8s::new();
+        fake.add_app("sonarr", true);
+
+        assert!(fake.namespace_exists("sonarr").await.unwrap());
+        assert!(!fake.namespace_exists("radarr").await.unwrap());
+        assert_eq!(fake.list_deployments("sonarr").await.unwrap().len(), 1);
+        let pods = fake.get_pod_status("sonarr", Some("sonarr")).await.unwrap();
+        assert_eq!(pods.len(), 1);
+        assert!(pods[0].ready);
+    }
+
+    #[tokio::test]
+    async fn test_delete_pod_records_operation() {
+        let fake = FakeK8s::new();
+        fake.add_app("sonarr", true);
+
+        fake.delete_pod("sonarr", "sonarr-0").await.unwrap();
+        assert!(fake.delete_pod("sonarr", "sonarr-0").await.is_err());
+        assert_eq!(
+            fake.operations(),
+            vec![K8sOperation::DeletePod {
+                namespace: "sonarr".to_string(),
+                pod: "sonarr-0".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scale_deployment_adds_and_removes_pods() {
+        let fake = FakeK8s::new();
+        fake.add_app("sonarr", true);
+
+        fake.scale_deployment("sonarr", "sonarr", 0).await.unwrap();
+        let deployment = &fake.list_deployments("sonarr").await.unwrap()[0];
+        assert_eq!(deployment.spec.as_ref().unwrap().replicas, Some(0));
+        assert!(fake
+            .get_pod_status("sonarr", None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        fake.scale_deployment("sonarr", "sonarr", 1).await.unwrap();
+        let pods = fake.get_pod_status("sonarr", None).await.unwrap();
+        assert_eq!(pods.len(), 1);
+        assert!(pods[0].ready);
+
+        assert!(fake.scale_deployment("sonarr", "radarr", 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replace_secret_overwrites() {
+        let fake = FakeK8s::new();
+        let secret = |value: &str| Secret {
+            metadata: ObjectMeta {
+                name: Some("vpn-qbittorrent".to_string()),
+                ..Default::default()
+            },
+            string_data: Some(BTreeMap::from([("KEY".to_string(), value.to_string())])),
+            ..Default::default()
+        };
+
+        fake.replace_secret("qbittorrent", secret("a"))
+            .await
+            .unwrap();
+        fake.replace_secret("qbittorrent", secret("b"))
+            .await
+            .unwrap();
+
+        let stored = fake.secret("qbittorrent", "vpn-qbittorrent").unwrap();
+        assert_eq!(stored.string_data.unwrap()["KEY"], "b");
+        assert_eq!(fake.operations().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_cluster() {
+        let fake = FakeK8s::new();
+        let handle = fake.clone();
+        fake.delete_namespace("sonarr").await.unwrap();
+        assert_eq!(
+            handle.operations(),
+            vec![K8sOperation::DeleteNamespace("sonarr".to_string())]
+        );
+    }
+}
@@ -1,8 +1,14 @@
 use std::collections::HashMap;
 
-use k8s_openapi::api::core::v1::{Pod, Secret, Service};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment};
+use k8s_openapi::api::core::v1::{Namespace, Node, Pod, Secret, Service};
 use kube::{
-    api::{Api, ListParams},
+    api::{
+        Api, AttachParams, AttachedProcess, DeleteParams, ListParams, Patch, PatchParams,
+        PostParams,
+    },
     config::{Config, KubeConfigOptions, Kubeconfig},
     Client,
 };
@@ -11,7 +17,12 @@ use serde::Deserialize;
 use crate::config::CONFIG;
 use crate::error::{AppError, Result};
 
+pub mod fake;
+
+pub use fake::{FakeK8s, K8sOperation};
+
 /// Kubernetes client manager
+#[derive(Clone)]
 pub struct K8sClient {
     client: Client,
 }
@@ -151,6 +162,26 @@ impl K8sClient {
         Ok(statuses)
     }
 
+    /// Get per-container restart counts and last termination state
+    ///
+    /// Lists pods across all namespaces when `namespace` is `None`.
+    pub async fn get_container_restarts(
+        &self,
+        namespace: Option<&str>,
+    ) -> Result<Vec<ContainerRestartState>> {
+        let pods: Api<Pod> = match namespace {
+            Some(ns) => Api::namespaced(self.client.clone(), ns),
+            None => Api::all(self.client.clone()),
+        };
+
+        let pod_list = pods.list(&ListParams::default()).await?;
+        Ok(pod_list
+            .items
+            .iter()
+            .flat_map(container_restart_states)
+            .collect())
+    }
+
     /// Get pod metrics for a namespace
     pub async fn get_pod_metrics(
         &self,
@@ -314,6 +345,47 @@ impl K8sClient {
         Ok(logs)
     }
 
+    /// Follow a pod's log from its last `tail_lines` lines, each prefixed
+    /// with the time it was written
+    pub async fn follow_pod_logs(
+        &self,
+        pod_name: &str,
+        namespace: &str,
+        container: Option<&str>,
+        tail_lines: i32,
+    ) -> Result<impl futures_util::io::AsyncBufRead + Send + Unpin + 'static> {
+        use kube::api::LogParams;
+
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        let log_params = LogParams {
+            follow: true,
+            timestamps: true,
+            tail_lines: Some(tail_lines as i64),
+            container: container.map(str::to_string),
+            ..Default::default()
+        };
+        Ok(pods.log_stream(pod_name, &log_params).await?)
+    }
+
+    /// Run `command` in a pod's container with a terminal attached
+    ///
+    /// The returned process's stdin, stdout and terminal size are taken by
+    /// the caller; without `container` the pod's default container is used.
+    pub async fn exec_tty(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+        container: Option<&str>,
+        command: Vec<String>,
+    ) -> Result<AttachedProcess> {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        let mut params = AttachParams::interactive_tty();
+        if let Some(c) = container {
+            params = params.container(c);
+        }
+        Ok(pods.exec(pod_name, command, &params).await?)
+    }
+
     /// Get a secret from a namespace
     pub async fn get_secret(&self, namespace: &str, secret_name: &str) -> Result<Secret> {
         let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
@@ -341,6 +413,159 @@ impl K8sClient {
     }
 }
 
+// ============================================================================
+// Cluster API
+// ============================================================================
+
+/// Cluster operations used by app lifecycle management
+///
+/// Implemented by [`K8sClient`] against a real cluster and by [`FakeK8s`]
+/// in memory, so deployment logic and the app endpoints can be exercised
+/// without a cluster. Obtain one through `AppState::k8s_api`.
+#[async_trait]
+pub trait K8sApi: Send + Sync {
+    /// Check whether a namespace exists
+    async fn namespace_exists(&self, namespace: &str) -> Result<bool>;
+
+    /// List the names of all namespaces
+    async fn list_namespaces(&self) -> Result<Vec<String>>;
+
+    /// Delete a namespace; succeeds if it does not exist
+    async fn delete_namespace(&self, namespace: &str) -> Result<()>;
+
+    /// List the deployments in a namespace
+    async fn list_deployments(&self, namespace: &str) -> Result<Vec<Deployment>>;
+
+    /// List the daemonsets in a namespace
+    async fn list_daemonsets(&self, namespace: &str) -> Result<Vec<DaemonSet>>;
+
+    /// Get pod status for a namespace, optionally filtered by app label
+    async fn get_pod_status(
+        &self,
+        namespace: &str,
+        app_name: Option<&str>,
+    ) -> Result<Vec<PodStatus>>;
+
+    /// Delete a pod so its controller recreates it
+    async fn delete_pod(&self, namespace: &str, pod_name: &str) -> Result<()>;
+
+    /// Set the replica count of a deployment
+    async fn scale_deployment(&self, namespace: &str, name: &str, replicas: i32) -> Result<()>;
+
+    /// Create a secret, replacing any existing secret with the same name
+    async fn replace_secret(&self, namespace: &str, secret: Secret) -> Result<()>;
+
+    /// Delete a secret; succeeds if it does not exist
+    async fn delete_secret(&self, namespace: &str, secret_name: &str) -> Result<()>;
+
+    /// The distinct CPU architectures of the cluster's nodes, sorted
+    async fn node_architectures(&self) -> Result<Vec<String>>;
+}
+
+#[async_trait]
+impl K8sApi for K8sClient {
+    async fn namespace_exists(&self, namespace: &str) -> Result<bool> {
+        let namespaces: Api<Namespace> = Api::all(self.client.clone());
+        match namespaces.get(namespace).await {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(ae)) if ae.code == 404 => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<String>> {
+        let namespaces: Api<Namespace> = Api::all(self.client.clone());
+        let ns_list = namespaces.list(&ListParams::default()).await?;
+        Ok(ns_list
+            .items
+            .into_iter()
+            .filter_map(|ns| ns.metadata.name)
+            .collect())
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<()> {
+        let namespaces: Api<Namespace> = Api::all(self.client.clone());
+        match namespaces.delete(namespace, &DeleteParams::default()).await {
+            Ok(_) => Ok(()),
+            Err(kube::Error::Api(ae)) if ae.code == 404 => Ok(()),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to delete namespace: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn list_deployments(&self, namespace: &str) -> Result<Vec<Deployment>> {
+        let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
+        Ok(deployments.list(&ListParams::default()).await?.items)
+    }
+
+    async fn list_daemonsets(&self, namespace: &str) -> Result<Vec<DaemonSet>> {
+        let daemonsets: Api<DaemonSet> = Api::namespaced(self.client.clone(), namespace);
+        Ok(daemonsets.list(&ListParams::default()).await?.items)
+    }
+
+    async fn get_pod_status(
+        &self,
+        namespace: &str,
+        app_name: Option<&str>,
+    ) -> Result<Vec<PodStatus>> {
+        K8sClient::get_pod_status(self, namespace, app_name).await
+    }
+
+    async fn delete_pod(&self, namespace: &str, pod_name: &str) -> Result<()> {
+        let pods: Api<Pod> = Api::namespaced(self.client.clone(), namespace);
+        pods.delete(pod_name, &DeleteParams::default()).await?;
+        Ok(())
+    }
+
+    async fn scale_deployment(&self, namespace: &str, name: &str, replicas: i32) -> Result<()> {
+        let deployments: Api<Deployment> = Api::namespaced(self.client.clone(), namespace);
+        let patch = serde_json::json!({ "spec": { "replicas": replicas } });
+        deployments
+            .patch_scale(name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await?;
+        Ok(())
+    }
+
+    async fn replace_secret(&self, namespace: &str, secret: Secret) -> Result<()> {
+        let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+
+        // Delete any existing secret first (simpler than patch)
+        if let Some(name) = secret.metadata.name.as_deref() {
+            let _ = secrets.delete(name, &DeleteParams::default()).await;
+        }
+
+        secrets.create(&PostParams::default(), &secret).await?;
+        Ok(())
+    }
+
+    async fn delete_secret(&self, namespace: &str, secret_name: &str) -> Result<()> {
+        let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+        match secrets.delete(secret_name, &DeleteParams::default()).await {
+            Ok(_) => Ok(()),
+            Err(kube::Error::Api(ae)) if ae.code == 404 => Ok(()),
+            Err(e) => Err(AppError::Internal(format!(
+                "Failed to delete secret: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn node_architectures(&self) -> Result<Vec<String>> {
+        let nodes: Api<Node> = Api::all(self.client.clone());
+        let architectures: std::collections::BTreeSet<String> = nodes
+            .list(&ListParams::default())
+            .await?
+            .items
+            .into_iter()
+            .filter_map(|node| node.status?.node_info)
+            .map(|info| info.architecture)
+            .collect();
+        Ok(architectures.into_iter().collect())
+    }
+}
+
 // ============================================================================
 // Helper Types
 // ============================================================================
@@ -383,6 +608,22 @@ pub struct ServiceEndpoint {
     pub base_path: Option<String>,
 }
 
+/// Restart state of a single container
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct ContainerRestartState {
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+    pub restart_count: i32,
+    /// Reason of the last termination (e.g. "OOMKilled", "Error")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_termination_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_terminated_at: Option<DateTime<Utc>>,
+}
+
 // Metrics server response types
 #[derive(Debug, Deserialize)]
 struct PodMetricsList {
@@ -428,6 +669,41 @@ fn format_age(total_seconds: i64) -> String {
     }
 }
 
+/// Extract container restart states from a pod's status
+fn container_restart_states(pod: &Pod) -> Vec<ContainerRestartState> {
+    let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+
+    pod.status
+        .as_ref()
+        .and_then(|s| s.container_statuses.as_ref())
+        .map(|statuses| {
+            statuses
+                .iter()
+                .map(|cs| {
+                    let terminated = cs.last_state.as_ref().and_then(|s| s.terminated.as_ref());
+                    ContainerRestartState {
+                        namespace: namespace.clone(),
+                        pod: pod_name.clone(),
+                        container: cs.name.clone(),
+                        restart_count: cs.restart_count,
+                        last_termination_reason: terminated.and_then(|t| t.reason.clone()),
+                        last_exit_code: terminated.map(|t| t.exit_code),
+                        last_terminated_at: terminated
+                            .and_then(|t| t.finished_at.as_ref())
+                            .and_then(|t| {
+                                DateTime::from_timestamp(
+                                    t.0.as_second(),
+                                    t.0.subsec_nanosecond() as u32,
+                                )
+                            }),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn parse_cpu(cpu_str: &str) -> i64 {
     if let Some(s) = cpu_str.strip_suffix('n') {
         s.parse().unwrap_or(0)
@@ -694,4 +970,53 @@ mod tests {
         // 128 * 1024 * 1024 == 134217728 bytes — less than 1 GiB → shown as Mi
         assert_eq!(format_memory(134_217_728), "128Mi");
     }
+
+    // -------------------------------------------------------------------------
+    // container_restart_states tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_container_restart_states_reads_last_termination() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": {"name": "sonarr-abc", "namespace": "sonarr"},
+            "status": {"containerStatuses": [
+                {
+                    "name": "sonarr", "image": "sonarr", "imageID": "", "ready": true,
+                    "restartCount": 3,
+                    "lastState": {"terminated": {
+                        "exitCode": 137, "reason": "OOMKilled",
+                        "finishedAt": "2026-10-10T12:00:00Z"
+                    }}
+                },
+                {"name": "sidecar", "image": "busybox", "imageID": "", "ready": true, "restartCount": 0}
+            ]}
+        }))
+        .unwrap();
+
+        let states = container_restart_states(&pod);
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].namespace, "sonarr");
+        assert_eq!(states[0].pod, "sonarr-abc");
+        assert_eq!(states[0].restart_count, 3);
+        assert_eq!(
+            states[0].last_termination_reason.as_deref(),
+            Some("OOMKilled")
+        );
+        assert_eq!(states[0].last_exit_code, Some(137));
+        assert_eq!(
+            states[0].last_terminated_at.map(|t| t.to_rfc3339()),
+            Some("2026-10-10T12:00:00+00:00".to_string())
+        );
+        assert_eq!(states[1].restart_count, 0);
+        assert!(states[1].last_exit_code.is_none());
+    }
+
+    #[test]
+    fn test_container_restart_states_without_status() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": {"name": "pending", "namespace": "radarr"}
+        }))
+        .unwrap();
+        assert!(container_restart_states(&pod).is_empty());
+    }
 }
@@ -0,0 +1,64 @@
+//! Absolute links
+//!
+//! Links that leave Kubarr, in notifications, emails, QR codes and OAuth
+//! redirects, are built from the external URL: the `external_url` setting,
+//! else `KUBARR_EXTERNAL_URL`, else `KUBARR_OAUTH2_ISSUER_URL`. Kubarr may
+//! be served under a path, e.g. `https://example.com/kubarr`, so paths are
+//! appended to it rather than resolved against its host.
+
+use crate::config::server::DEFAULT_EXTERNAL_URL;
+
+use super::runtime_config;
+
+/// URL users reach Kubarr at, without a trailing slash
+pub fn external_url() -> String {
+    runtime_config::current().external_url.clone()
+}
+
+/// The external URL, unless nothing configures it and it is only the
+/// in-cluster default
+pub fn configured_external_url() -> Option<String> {
+    Some(external_url()).filter(|url| url != DEFAULT_EXTERNAL_URL)
+}
+
+/// Absolute link to a path, or the link itself if it already is one
+pub fn absolute(path: &str) -> String {
+    join(&external_url(), path)
+}
+
+/// Where an OAuth provider sends users back to after they sign in
+pub fn oauth_callback(provider: &str) -> String {
+    absolute(&format!("/api/oauth/{}/callback", provider))
+}
+
+fn join(base: &str, path: &str) -> String {
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return path.to_string();
+    }
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        assert_eq!(
+            join("https://kubarr.example.com", "/auth/register?invite=abc"),
+            "https://kubarr.example.com/auth/register?invite=abc"
+        );
+        assert_eq!(
+            join("https://example.com/kubarr/", "resources?app=sonarr"),
+            "https://example.com/kubarr/resources?app=sonarr"
+        );
+        assert_eq!(
+            join("https://kubarr.example.com", "https://other.example.com/x"),
+            "https://other.example.com/x"
+        );
+    }
+}
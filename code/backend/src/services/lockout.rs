@@ -0,0 +1,277 @@
+//! Account lockout
+//!
+//! Failed sign-ins with a password, a 2FA code or a recovery code are
+//! counted per user. Once a user reaches `lockout_threshold` failures in a
+//! row, their account is locked for `lockout_duration_minutes`; every
+//! further failure after the lock runs out doubles it, up to
+//! `lockout_max_minutes`. Locked accounts get `403 Forbidden` without their
+//! password being checked, the user and the admins get an `account_locked`
+//! event, and a successful sign-in or an admin unlocking the account resets
+//! the count.
+//!
+//! Addresses are slowed down as well: once one has `lockout_threshold`
+//! failed sign-ins within `ip_ban_window_minutes`, it has to wait 2, 4, 8…
+//! seconds (at most five minutes) after its latest failure before trying
+//! again, and gets `429 Too Many Requests` until then. This applies to
+//! unknown usernames too, and slows down guessing well before
+//! [`ip_bans`] bans the address.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+
+use super::ip_bans;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::user;
+use crate::state::AppState;
+
+/// Failed sign-ins in a row that lock an account; 0 turns lockout and the
+/// per-address backoff off
+pub const LOCKOUT_THRESHOLD: &str = "lockout_threshold";
+
+/// Minutes the first lock lasts
+pub const LOCKOUT_DURATION_MINUTES: &str = "lockout_duration_minutes";
+
+/// Minutes a lock lasts at most, however often it doubled
+pub const LOCKOUT_MAX_MINUTES: &str = "lockout_max_minutes";
+
+/// Longest an address has to wait between failed sign-ins
+const MAX_IP_BACKOFF: Duration = Duration::minutes(5);
+
+/// When failed sign-ins lock an account or slow down an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    pub threshold: u32,
+    pub duration: Duration,
+    pub max: Duration,
+}
+
+impl LockoutPolicy {
+    /// How long an account is locked after `failures` failed sign-ins in a
+    /// row, if at all
+    pub fn lock_duration(&self, failures: u32) -> Option<Duration> {
+        if self.threshold == 0 || failures < self.threshold {
+            return None;
+        }
+        let doublings = (failures - self.threshold).min(30);
+        let minutes = self.duration.num_minutes().saturating_mul(1 << doublings);
+        Some(Duration::minutes(minutes.min(self.max.num_minutes())))
+    }
+
+    /// How long an address waits after its latest failed sign-in, given
+    /// its recent failures, if at all
+    pub fn ip_backoff(&self, failures: u64) -> Option<Duration> {
+        if self.threshold == 0 || failures < self.threshold as u64 {
+            return None;
+        }
+        let doublings = (failures - self.threshold as u64 + 1).min(30);
+        Some(Duration::seconds(1 << doublings).min(MAX_IP_BACKOFF))
+    }
+}
+
+/// A whole number setting, or an error naming the setting
+pub fn parse_setting(key: &str, value: &str) -> Result<u32> {
+    let minimum = if key == LOCKOUT_THRESHOLD { 0 } else { 1 };
+    value
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n >= minimum)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a whole number of at least {}",
+                key, minimum
+            ))
+        })
+}
+
+/// The configured lockout policy; unreadable settings fall back to the
+/// defaults
+pub async fn policy(db: &DatabaseConnection) -> Result<LockoutPolicy> {
+    let read = |key: &'static str, default: u32| async move {
+        Ok::<_, AppError>(
+            get_setting_value(db, key)
+                .await?
+                .and_then(|v| parse_setting(key, &v).ok())
+                .unwrap_or(default),
+        )
+    };
+    let duration = Duration::minutes(read(LOCKOUT_DURATION_MINUTES, 5).await? as i64);
+    Ok(LockoutPolicy {
+        threshold: read(LOCKOUT_THRESHOLD, 5).await?,
+        duration,
+        max: Duration::minutes(read(LOCKOUT_MAX_MINUTES, 1440).await? as i64).max(duration),
+    })
+}
+
+/// Whether an account is locked at `now`
+pub fn is_locked(user: &user::Model, now: DateTime<Utc>) -> bool {
+    user.locked_until.is_some_and(|until| until > now)
+}
+
+/// Refuse a locked account
+pub fn check_user(user: &user::Model, now: DateTime<Utc>) -> Result<()> {
+    match user.locked_until.filter(|until| *until > now) {
+        Some(until) => Err(AppError::Forbidden(format!(
+            "Account is locked after too many failed sign-ins; try again after {}",
+            until.format("%Y-%m-%d %H:%M UTC")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Refuse an address that has to wait after its latest failed sign-in
+pub async fn check_ip(db: &DatabaseConnection, ip: Option<&str>, now: DateTime<Utc>) -> Result<()> {
+    let Some(ip) = ip else {
+        return Ok(());
+    };
+    let policy = policy(db).await?;
+    if policy.threshold == 0 {
+        return Ok(());
+    }
+    let since = now - ip_bans::policy(db).await?.window;
+    let failures = ip_bans::failed_attempts(db, ip, since).await?;
+    let Some(backoff) = policy.ip_backoff(failures) else {
+        return Ok(());
+    };
+    let Some(last) = ip_bans::last_failure_at(db, ip, since).await? else {
+        return Ok(());
+    };
+    let wait = (last + backoff - now).num_seconds();
+    if wait > 0 {
+        return Err(AppError::TooManyRequests(format!(
+            "Too many failed sign-ins; try again in {} second{}",
+            wait,
+            if wait == 1 { "" } else { "s" }
+        )));
+    }
+    Ok(())
+}
+
+/// Count a failed sign-in against a user and lock the account if it went
+/// over the threshold; returns until when it is locked
+pub async fn record_failure(state: &AppState, user: &user::Model) -> Result<Option<DateTime<Utc>>> {
+    let db = state.get_db().await?;
+    let policy = policy(&db).await?;
+    let now = Utc::now();
+    let failures = user.failed_login_attempts.saturating_add(1);
+    let locked_until = policy
+        .lock_duration(failures.max(0) as u32)
+        .map(|duration| now + duration);
+
+    let mut model: user::ActiveModel = user.clone().into();
+    model.failed_login_attempts = Set(failures);
+    if locked_until.is_some() {
+        model.locked_until = Set(locked_until);
+    }
+    model.update(&db).await?;
+
+    let Some(until) = locked_until else {
+        return Ok(None);
+    };
+    tracing::warn!(
+        user_id = user.id,
+        username = user.username,
+        failures = failures,
+        "Locked account after repeated failed sign-ins"
+    );
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::AccountLocked,
+            ResourceType::User,
+            Some(user.id.to_string()),
+            Some(user.id),
+            Some(user.username.clone()),
+            Some(serde_json::json!({
+                "failed_attempts": failures,
+                "locked_until": until,
+            })),
+            None,
+            None,
+        )
+        .await;
+    let details = format!(
+        "{} after {} failed sign-ins, until {}",
+        user.username,
+        failures,
+        until.format("%Y-%m-%d %H:%M UTC")
+    );
+    if let Err(e) = state
+        .notification
+        .notify_security(
+            &AuditAction::AccountLocked,
+            user.id,
+            &user.username,
+            Some(&details),
+        )
+        .await
+    {
+        tracing::warn!("Failed to tell user about account lock: {}", e);
+    }
+    if let Err(e) = state
+        .notification
+        .notify_event(
+            &AuditAction::AccountLocked,
+            None,
+            Some(&user.username),
+            Some(&details),
+        )
+        .await
+    {
+        tracing::warn!("Failed to send account_locked notification: {}", e);
+    }
+    Ok(Some(until))
+}
+
+/// Clear a user's failed sign-ins and lock
+pub async fn reset(db: &DatabaseConnection, user: &user::Model) -> Result<user::Model> {
+    if user.failed_login_attempts == 0 && user.locked_until.is_none() {
+        return Ok(user.clone());
+    }
+    let mut model: user::ActiveModel = user.clone().into();
+    model.failed_login_attempts = Set(0);
+    model.locked_until = Set(None);
+    Ok(model.update(db).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_duration() {
+        let policy = LockoutPolicy {
+            threshold: 3,
+            duration: Duration::minutes(5),
+            max: Duration::minutes(30),
+        };
+        assert_eq!(policy.lock_duration(2), None);
+        assert_eq!(policy.lock_duration(3), Some(Duration::minutes(5)));
+        assert_eq!(policy.lock_duration(4), Some(Duration::minutes(10)));
+        assert_eq!(policy.lock_duration(5), Some(Duration::minutes(20)));
+        assert_eq!(policy.lock_duration(6), Some(Duration::minutes(30)));
+        assert_eq!(policy.lock_duration(u32::MAX), Some(Duration::minutes(30)));
+
+        assert_eq!(policy.ip_backoff(2), None);
+        assert_eq!(policy.ip_backoff(3), Some(Duration::seconds(2)));
+        assert_eq!(policy.ip_backoff(5), Some(Duration::seconds(8)));
+        assert_eq!(policy.ip_backoff(100), Some(MAX_IP_BACKOFF));
+
+        let off = LockoutPolicy {
+            threshold: 0,
+            ..policy
+        };
+        assert_eq!(off.lock_duration(100), None);
+        assert_eq!(off.ip_backoff(100), None);
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        assert_eq!(parse_setting(LOCKOUT_THRESHOLD, "0").unwrap(), 0);
+        assert_eq!(parse_setting(LOCKOUT_MAX_MINUTES, " 60 ").unwrap(), 60);
+        assert!(parse_setting(LOCKOUT_DURATION_MINUTES, "0").is_err());
+        assert!(parse_setting(LOCKOUT_THRESHOLD, "-1").is_err());
+    }
+}
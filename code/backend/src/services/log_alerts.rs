@@ -0,0 +1,696 @@
+//! Log-based alert rules evaluated against VictoriaLogs
+//!
+//! A rule counts the log lines matching a LogsQL pattern (optionally limited
+//! to one app's namespace) over a sliding window and fires when the count
+//! exceeds its threshold, e.g. more than 5 "ERROR" lines in 5 minutes. Rules
+//! are evaluated by a background worker on their own interval; the rule's
+//! owner is notified when it starts firing and when it resolves.
+//!
+//! Firing rules are listed at `/api/monitoring/alerts` next to the metric
+//! alerts raised by anomaly detection.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::alerts;
+use super::anomaly;
+use super::notification::NotificationService;
+use super::outbound;
+use super::scheduler::PeriodicTask;
+use crate::endpoints::logs::VICTORIALOGS_URL;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::log_alert_rule;
+use crate::models::prelude::*;
+
+pub const STATE_OK: &str = "ok";
+pub const STATE_FIRING: &str = "firing";
+pub const STATE_UNKNOWN: &str = "unknown";
+
+pub const SEVERITIES: &[&str] = &["info", "warning", "critical"];
+
+/// Shortest allowed evaluation interval
+pub const MIN_INTERVAL_SECONDS: i32 = 30;
+/// Longest allowed evaluation interval (1 day)
+pub const MAX_INTERVAL_SECONDS: i32 = 86400;
+/// Longest allowed window (1 day)
+pub const MAX_WINDOW_MINUTES: i32 = 1440;
+pub const MAX_PATTERN_LENGTH: usize = 500;
+
+const DEFAULT_WINDOW_MINUTES: i32 = 5;
+const DEFAULT_INTERVAL_SECONDS: i32 = 60;
+/// How often the worker looks for due rules
+const WORKER_TICK_SECS: u64 = 15;
+const QUERY_TIMEOUT_SECS: u64 = 30;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateLogAlertRuleRequest {
+    pub name: String,
+    /// Only count lines from this app's namespace
+    pub app_name: Option<String>,
+    /// LogsQL filter, e.g. `ERROR` or `"connection refused"`
+    pub pattern: String,
+    /// Fire when more than this many lines match (default 0)
+    pub threshold: Option<i32>,
+    pub window_minutes: Option<i32>,
+    pub interval_seconds: Option<i32>,
+    /// "info", "warning" (default) or "critical"
+    pub severity: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateLogAlertRuleRequest {
+    pub name: Option<String>,
+    /// Empty string removes the app filter
+    pub app_name: Option<String>,
+    pub pattern: Option<String>,
+    pub threshold: Option<i32>,
+    pub window_minutes: Option<i32>,
+    pub interval_seconds: Option<i32>,
+    pub severity: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// A log alert rule with its current state
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LogAlertRuleInfo {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    pub pattern: String,
+    /// Full LogsQL query sent to VictoriaLogs
+    pub query: String,
+    pub threshold: i32,
+    pub window_minutes: i32,
+    pub interval_seconds: i32,
+    pub severity: String,
+    pub enabled: bool,
+    /// "ok", "firing" or "unknown"
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firing_since: Option<DateTime<Utc>>,
+}
+
+impl From<log_alert_rule::Model> for LogAlertRuleInfo {
+    fn from(rule: log_alert_rule::Model) -> Self {
+        Self {
+            query: build_query(rule.app_name.as_deref(), &rule.pattern),
+            id: rule.id,
+            name: rule.name,
+            app_name: rule.app_name,
+            pattern: rule.pattern,
+            threshold: rule.threshold,
+            window_minutes: rule.window_minutes,
+            interval_seconds: rule.interval_seconds,
+            severity: rule.severity,
+            enabled: rule.enabled,
+            state: rule.state,
+            last_count: rule.last_count,
+            last_error: rule.last_error,
+            last_evaluated_at: rule.last_evaluated_at,
+            firing_since: rule.firing_since,
+        }
+    }
+}
+
+/// An active metric or log alert
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MonitoringAlert {
+    /// "metric" (anomaly detection) or "log" (log alert rule)
+    pub source: String,
+    /// Anomaly ID or rule ID, depending on the source
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    pub severity: String,
+    pub message: String,
+    pub since: DateTime<Utc>,
+    /// UI link to the relevant metrics or logs
+    pub link: String,
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Rule name is required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+/// Normalize an app filter; it is interpolated into LogsQL, so only
+/// Kubernetes namespace names are accepted
+fn validate_app_name(app_name: Option<&str>) -> Result<Option<String>> {
+    let Some(app) = app_name.map(str::trim).filter(|a| !a.is_empty()) else {
+        return Ok(None);
+    };
+    let valid = app.len() <= 63
+        && app
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !app.starts_with('-')
+        && !app.ends_with('-');
+    if !valid {
+        return Err(AppError::BadRequest(format!("Invalid app name '{}'", app)));
+    }
+    Ok(Some(app.to_string()))
+}
+
+/// Check a LogsQL filter; pipes are not allowed since the rule appends its
+/// own `stats` pipe, and parentheses must balance so the pattern cannot
+/// escape the app filter it is combined with
+pub fn validate_pattern(pattern: &str) -> Result<String> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(AppError::BadRequest("Pattern is required".to_string()));
+    }
+    if pattern.len() > MAX_PATTERN_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "Pattern is longer than {} characters",
+            MAX_PATTERN_LENGTH
+        )));
+    }
+
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    for c in pattern.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth = depth.checked_sub(1).ok_or_else(|| {
+                    AppError::BadRequest("Pattern has unbalanced parentheses".to_string())
+                })?
+            }
+            (None, '|') => {
+                return Err(AppError::BadRequest(
+                    "Pipes are not allowed in log alert patterns".to_string(),
+                ))
+            }
+            (None, _) => {}
+        }
+    }
+    if quote.is_some() {
+        return Err(AppError::BadRequest(
+            "Pattern has an unterminated quote".to_string(),
+        ));
+    }
+    if depth > 0 {
+        return Err(AppError::BadRequest(
+            "Pattern has unbalanced parentheses".to_string(),
+        ));
+    }
+    Ok(pattern.to_string())
+}
+
+fn validate_timing(window_minutes: i32, interval_seconds: i32, threshold: i32) -> Result<()> {
+    if !(1..=MAX_WINDOW_MINUTES).contains(&window_minutes) {
+        return Err(AppError::BadRequest(format!(
+            "window_minutes must be between 1 and {}",
+            MAX_WINDOW_MINUTES
+        )));
+    }
+    if !(MIN_INTERVAL_SECONDS..=MAX_INTERVAL_SECONDS).contains(&interval_seconds) {
+        return Err(AppError::BadRequest(format!(
+            "interval_seconds must be between {} and {}",
+            MIN_INTERVAL_SECONDS, MAX_INTERVAL_SECONDS
+        )));
+    }
+    if threshold < 0 {
+        return Err(AppError::BadRequest(
+            "threshold must be zero or greater".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_severity(severity: &str) -> Result<String> {
+    let severity = severity.trim().to_lowercase();
+    if !SEVERITIES.contains(&severity.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid severity '{}': expected info, warning or critical",
+            severity
+        )));
+    }
+    Ok(severity)
+}
+
+// ============================================================================
+// CRUD
+// ============================================================================
+
+async fn find_rule(db: &DatabaseConnection, id: i64) -> Result<log_alert_rule::Model> {
+    LogAlertRule::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Log alert rule {} not found", id)))
+}
+
+pub async fn list_rules(db: &DatabaseConnection) -> Result<Vec<LogAlertRuleInfo>> {
+    Ok(LogAlertRule::find()
+        .order_by_asc(log_alert_rule::Column::Name)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+pub async fn get_rule(db: &DatabaseConnection, id: i64) -> Result<LogAlertRuleInfo> {
+    Ok(find_rule(db, id).await?.into())
+}
+
+/// Create a rule owned by `created_by`
+pub async fn create_rule(
+    db: &DatabaseConnection,
+    request: CreateLogAlertRuleRequest,
+    created_by: i64,
+) -> Result<LogAlertRuleInfo> {
+    let name = validate_name(&request.name)?;
+    let app_name = validate_app_name(request.app_name.as_deref())?;
+    let pattern = validate_pattern(&request.pattern)?;
+    let threshold = request.threshold.unwrap_or(0);
+    let window_minutes = request.window_minutes.unwrap_or(DEFAULT_WINDOW_MINUTES);
+    let interval_seconds = request.interval_seconds.unwrap_or(DEFAULT_INTERVAL_SECONDS);
+    validate_timing(window_minutes, interval_seconds, threshold)?;
+    let severity = validate_severity(request.severity.as_deref().unwrap_or("warning"))?;
+
+    let now = Utc::now();
+    let model = log_alert_rule::ActiveModel {
+        name: Set(name),
+        app_name: Set(app_name),
+        pattern: Set(pattern),
+        threshold: Set(threshold),
+        window_minutes: Set(window_minutes),
+        interval_seconds: Set(interval_seconds),
+        severity: Set(severity),
+        enabled: Set(request.enabled.unwrap_or(true)),
+        state: Set(STATE_UNKNOWN.to_string()),
+        last_count: Set(None),
+        last_error: Set(None),
+        last_evaluated_at: Set(None),
+        firing_since: Set(None),
+        created_by: Set(Some(created_by)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(model.into())
+}
+
+/// Update a rule; changing what it matches resets its state
+pub async fn update_rule(
+    db: &DatabaseConnection,
+    id: i64,
+    request: UpdateLogAlertRuleRequest,
+) -> Result<LogAlertRuleInfo> {
+    let existing = find_rule(db, id).await?;
+
+    let threshold = request.threshold.unwrap_or(existing.threshold);
+    let window_minutes = request.window_minutes.unwrap_or(existing.window_minutes);
+    let interval_seconds = request
+        .interval_seconds
+        .unwrap_or(existing.interval_seconds);
+    validate_timing(window_minutes, interval_seconds, threshold)?;
+
+    let mut active: log_alert_rule::ActiveModel = existing.clone().into();
+    let mut reset = threshold != existing.threshold || window_minutes != existing.window_minutes;
+    if let Some(name) = request.name {
+        active.name = Set(validate_name(&name)?);
+    }
+    if let Some(app_name) = request.app_name {
+        let app_name = validate_app_name(Some(&app_name))?;
+        reset |= app_name != existing.app_name;
+        active.app_name = Set(app_name);
+    }
+    if let Some(pattern) = request.pattern {
+        let pattern = validate_pattern(&pattern)?;
+        reset |= pattern != existing.pattern;
+        active.pattern = Set(pattern);
+    }
+    if let Some(severity) = request.severity {
+        active.severity = Set(validate_severity(&severity)?);
+    }
+    if let Some(enabled) = request.enabled {
+        active.enabled = Set(enabled);
+    }
+    if reset {
+        active.state = Set(STATE_UNKNOWN.to_string());
+        active.last_count = Set(None);
+        active.last_error = Set(None);
+        active.last_evaluated_at = Set(None);
+        active.firing_since = Set(None);
+    }
+    active.threshold = Set(threshold);
+    active.window_minutes = Set(window_minutes);
+    active.interval_seconds = Set(interval_seconds);
+    active.updated_at = Set(Utc::now());
+    Ok(active.update(db).await?.into())
+}
+
+pub async fn delete_rule(db: &DatabaseConnection, id: i64) -> Result<()> {
+    let rule = find_rule(db, id).await?;
+    LogAlertRule::delete_by_id(rule.id).exec(db).await?;
+    Ok(())
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// LogsQL query counting the lines matched by a rule
+pub fn build_query(app_name: Option<&str>, pattern: &str) -> String {
+    match app_name {
+        Some(app) => format!(r#"namespace:="{}" ({}) | stats count() hits"#, app, pattern),
+        None => format!("({}) | stats count() hits", pattern),
+    }
+}
+
+/// Count matching lines over the rule's window
+async fn count_matches(
+    rule: &log_alert_rule::Model,
+    now: DateTime<Utc>,
+) -> std::result::Result<i64, String> {
+    let client = outbound::client_builder()
+        .timeout(Duration::from_secs(QUERY_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let start = now - chrono::Duration::minutes(rule.window_minutes as i64);
+    let query = build_query(rule.app_name.as_deref(), &rule.pattern);
+    let response = client
+        .get(format!("{}/select/logsql/query", VICTORIALOGS_URL))
+        .query(&[
+            ("query", query.as_str()),
+            ("start", start.to_rfc3339().as_str()),
+            ("end", now.to_rfc3339().as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to VictoriaLogs: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("VictoriaLogs returned {}: {}", status, body.trim()));
+    }
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    parse_hits(&text)
+}
+
+/// Read the `hits` value from a `stats count() hits` response (JSON lines)
+pub fn parse_hits(body: &str) -> std::result::Result<i64, String> {
+    let Some(line) = body.lines().find(|l| !l.trim().is_empty()) else {
+        return Ok(0);
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| format!("Invalid VictoriaLogs response: {}", e))?;
+    match &value["hits"] {
+        serde_json::Value::String(s) => s
+            .parse::<i64>()
+            .map_err(|_| format!("Invalid hit count '{}'", s)),
+        serde_json::Value::Number(n) => {
+            n.as_i64().ok_or_else(|| format!("Invalid hit count {}", n))
+        }
+        _ => Err("VictoriaLogs response has no hit count".to_string()),
+    }
+}
+
+/// Store an evaluation result and update the rule's state
+///
+/// Returns the notification to send if the rule started firing or resolved.
+/// A failed query keeps the previous state.
+pub async fn record_evaluation(
+    db: &DatabaseConnection,
+    rule: log_alert_rule::Model,
+    result: std::result::Result<i64, String>,
+    at: DateTime<Utc>,
+) -> Result<(log_alert_rule::Model, Option<AuditAction>)> {
+    let previous = rule.state.clone();
+    let mut active: log_alert_rule::ActiveModel = rule.clone().into();
+    active.last_evaluated_at = Set(Some(at));
+
+    let event = match result {
+        Ok(count) => {
+            let state = if count > rule.threshold as i64 {
+                STATE_FIRING
+            } else {
+                STATE_OK
+            };
+            active.state = Set(state.to_string());
+            active.last_count = Set(Some(count));
+            active.last_error = Set(None);
+            match (previous.as_str(), state) {
+                (STATE_FIRING, STATE_OK) => {
+                    active.firing_since = Set(None);
+                    Some(AuditAction::LogAlertResolved)
+                }
+                (prev, STATE_FIRING) if prev != STATE_FIRING => {
+                    active.firing_since = Set(Some(at));
+                    Some(AuditAction::LogAlertFiring)
+                }
+                _ => None,
+            }
+        }
+        Err(error) => {
+            active.last_error = Set(Some(error));
+            None
+        }
+    };
+
+    Ok((active.update(db).await?, event))
+}
+
+/// Whether a rule should be evaluated at `now`
+pub fn is_due(rule: &log_alert_rule::Model, now: DateTime<Utc>) -> bool {
+    rule.enabled
+        && rule.last_evaluated_at.is_none_or(|last| {
+            now - last >= chrono::Duration::seconds(rule.interval_seconds as i64)
+        })
+}
+
+pub(crate) fn describe(rule: &log_alert_rule::Model) -> String {
+    let scope = rule
+        .app_name
+        .as_deref()
+        .map(|app| format!(" in {}", app))
+        .unwrap_or_default();
+    format!(
+        "{}: {} lines matching {}{} in the last {} min (threshold {})",
+        rule.name,
+        rule.last_count.unwrap_or(0),
+        rule.pattern,
+        scope,
+        rule.window_minutes,
+        rule.threshold
+    )
+}
+
+/// Evaluate a rule, record the result and notify its owner on state changes
+/// (unless its alert is silenced)
+pub async fn run_evaluation(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    rule: log_alert_rule::Model,
+) -> Result<log_alert_rule::Model> {
+    let now = Utc::now();
+    let result = count_matches(&rule, now).await;
+    let (rule, event) = record_evaluation(db, rule, result, now).await?;
+
+    if let Some(action) = event {
+        tracing::info!(rule = %rule.name, state = %rule.state, "Log alert rule changed state");
+        if alerts::is_silenced(db, &alerts::fingerprint(alerts::SOURCE_LOG, rule.id), now).await? {
+            return Ok(rule);
+        }
+        notification
+            .notify_event(&action, rule.created_by, None, Some(&describe(&rule)))
+            .await?;
+    }
+    Ok(rule)
+}
+
+/// Evaluate a rule immediately (used by the "evaluate now" endpoint)
+pub async fn evaluate_now(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    id: i64,
+) -> Result<LogAlertRuleInfo> {
+    let rule = find_rule(db, id).await?;
+    Ok(run_evaluation(db, notification, rule).await?.into())
+}
+
+// ============================================================================
+// Active Alerts
+// ============================================================================
+
+/// Firing log rules and active metric anomalies, newest first
+pub async fn monitoring_alerts(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<MonitoringAlert>> {
+    let mut alerts: Vec<MonitoringAlert> = LogAlertRule::find()
+        .filter(log_alert_rule::Column::Enabled.eq(true))
+        .filter(log_alert_rule::Column::State.eq(STATE_FIRING))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|rule| MonitoringAlert {
+            source: "log".to_string(),
+            id: rule.id,
+            message: describe(&rule),
+            name: rule.name,
+            app_name: rule.app_name,
+            severity: rule.severity,
+            since: rule.firing_since.unwrap_or(rule.updated_at),
+            link: "/logs".to_string(),
+        })
+        .collect();
+
+    alerts.extend(
+        anomaly::active_anomalies(db, now)
+            .await?
+            .into_iter()
+            .map(|a| MonitoringAlert {
+                source: "metric".to_string(),
+                id: a.id,
+                name: format!("{} {} anomaly", a.app_name, a.metric),
+                message: a.summary(),
+                app_name: Some(a.app_name),
+                severity: "warning".to_string(),
+                since: a.detected_at,
+                link: a.link,
+            }),
+    );
+
+    alerts.sort_by_key(|a| std::cmp::Reverse(a.since));
+    Ok(alerts)
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Evaluates all due log alert rules
+pub struct LogAlertTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for LogAlertTask {
+    fn name(&self) -> &'static str {
+        "log_alerts"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(WORKER_TICK_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let due: Vec<log_alert_rule::Model> = LogAlertRule::find()
+            .filter(log_alert_rule::Column::Enabled.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|r| is_due(r, now))
+            .collect();
+
+        let results = join_all(
+            due.into_iter()
+                .map(|rule| run_evaluation(db, &self.notification, rule)),
+        )
+        .await;
+        for result in results {
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "Log alert evaluation failed to record");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_pattern() {
+        assert_eq!(validate_pattern("  ERROR ").unwrap(), "ERROR");
+        assert!(validate_pattern(r#""connection refused" OR timeout"#).is_ok());
+        assert!(validate_pattern(r#"~"err|fail""#).is_ok());
+        assert!(validate_pattern(r#"_msg:"say \"hi\"""#).is_ok());
+
+        assert!(validate_pattern("").is_err());
+        assert!(validate_pattern("ERROR | delete").is_err());
+        assert!(validate_pattern(r#""unterminated"#).is_err());
+        assert!(validate_pattern("(ERROR OR WARN)").is_ok());
+        assert!(validate_pattern("ERROR) OR (*").is_err());
+        assert!(validate_pattern("(ERROR").is_err());
+        assert!(validate_pattern(&"a".repeat(MAX_PATTERN_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_app_name() {
+        assert_eq!(validate_app_name(None).unwrap(), None);
+        assert_eq!(validate_app_name(Some(" ")).unwrap(), None);
+        assert_eq!(
+            validate_app_name(Some("sonarr")).unwrap(),
+            Some("sonarr".to_string())
+        );
+        assert!(validate_app_name(Some(r#"sonarr" or *"#)).is_err());
+        assert!(validate_app_name(Some("Sonarr")).is_err());
+    }
+
+    #[test]
+    fn test_build_query() {
+        assert_eq!(
+            build_query(Some("sonarr"), "ERROR"),
+            r#"namespace:="sonarr" (ERROR) | stats count() hits"#
+        );
+        assert_eq!(
+            build_query(None, "panic OR fatal"),
+            "(panic OR fatal) | stats count() hits"
+        );
+    }
+
+    #[test]
+    fn test_parse_hits() {
+        assert_eq!(parse_hits("{\"hits\":\"12\"}\n"), Ok(12));
+        assert_eq!(parse_hits("{\"hits\":3}"), Ok(3));
+        assert_eq!(parse_hits(""), Ok(0));
+        assert!(parse_hits("{\"other\":1}").is_err());
+        assert!(parse_hits("not json").is_err());
+    }
+}
@@ -0,0 +1,820 @@
+//! Log archives of managed apps
+//!
+//! VictoriaLogs keeps recent logs; the archive keeps history. Once a UTC day
+//! is over, [`LogArchiveTask`] exports that day's logs of each installed app
+//! from VictoriaLogs as gzip-compressed JSON lines, sorted by time, and
+//! records them in the `log_archives` index. VictoriaLogs' own retention can
+//! then stay short without losing older logs.
+//!
+//! Archives go to an S3-compatible bucket when `KUBARR_LOG_ARCHIVE_S3_BUCKET`
+//! is set, and to `KUBARR_LOG_ARCHIVE_DIR` otherwise. Archives older than
+//! `log_archive_retention_days` are deleted from the store and the index.
+
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::outbound;
+use super::scheduler::PeriodicTask;
+use crate::config::CONFIG;
+use crate::endpoints::logs::VICTORIALOGS_URL;
+use crate::endpoints::settings::{get_setting_bool, get_setting_value};
+use crate::error::{AppError, Result};
+use crate::models::log_archive;
+use crate::models::prelude::*;
+use crate::state::DbConn;
+
+/// Setting that turns the daily export on
+pub const LOG_ARCHIVE_ENABLED: &str = "log_archive_enabled";
+
+/// Setting with the number of days archives are kept
+pub const LOG_ARCHIVE_RETENTION_DAYS: &str = "log_archive_retention_days";
+
+/// Retention used when the setting is not a number
+const DEFAULT_RETENTION_DAYS: i64 = 365;
+
+/// Days looked back for days that were not archived yet
+const BACKFILL_DAYS: u64 = 7;
+
+/// Time after midnight UTC before the previous day is archived, so late
+/// logs are included
+const GRACE_MINUTES: i64 = 60;
+
+/// Longest range one archive query may cover
+pub const MAX_QUERY_DAYS: i64 = 31;
+
+const EXPORT_TIMEOUT_SECS: u64 = 600;
+const RUN_INTERVAL_SECS: u64 = 3600;
+
+// ============================================================================
+// Stores
+// ============================================================================
+
+/// Where archives are kept
+#[async_trait]
+pub trait ArchiveStore: Send + Sync {
+    /// "local" or "s3", recorded with each archive
+    fn kind(&self) -> &'static str;
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()>;
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// Delete an archive; succeeds if it does not exist
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Archives in a local directory
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for LocalStore {
+    fn kind(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, data).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        tokio::fs::read(self.dir.join(key)).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                AppError::NotFound(format!("Log archive '{}' not found", key))
+            } else {
+                e.into()
+            }
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.dir.join(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Archives in an S3-compatible bucket, addressed path-style and signed
+/// with AWS Signature Version 4
+pub struct S3Store {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    prefix: String,
+    client: reqwest::Client,
+}
+
+impl S3Store {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+        prefix: &str,
+    ) -> Result<Self> {
+        let client = outbound::client_builder()
+            .timeout(Duration::from_secs(EXPORT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            client,
+        })
+    }
+
+    fn url(&self, key: &str) -> Result<Url> {
+        let object = if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}/{}", self.prefix, key)
+        };
+        Url::parse(&format!("{}/{}/{}", self.endpoint, self.bucket, object))
+            .map_err(|e| AppError::Internal(format!("Invalid S3 endpoint: {}", e)))
+    }
+
+    async fn send(&self, method: Method, key: &str, body: Vec<u8>) -> Result<reqwest::Response> {
+        let url = self.url(key)?;
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = sigv4_authorization(
+            method.as_str(),
+            &url,
+            &payload_hash,
+            &amz_date,
+            &self.region,
+            &self.access_key_id,
+            &self.secret_access_key,
+        );
+        self.client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("Failed to reach S3: {}", e)))
+    }
+}
+
+async fn s3_error(response: reqwest::Response) -> AppError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    AppError::BadGateway(format!("S3 returned {}: {}", status, body.trim()))
+}
+
+#[async_trait]
+impl ArchiveStore for S3Store {
+    fn kind(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let response = self.send(Method::PUT, key, data).await?;
+        if !response.status().is_success() {
+            return Err(s3_error(response).await);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let response = self.send(Method::GET, key, Vec::new()).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound(format!(
+                "Log archive '{}' not found",
+                key
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(s3_error(response).await);
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadGateway(format!("Failed to read from S3: {}", e)))?;
+        Ok(bytes.to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.send(Method::DELETE, key, Vec::new()).await?;
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(s3_error(response).await);
+        }
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Signing key for one day, region and service
+fn sigv4_signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// `Authorization` header for an S3 request without query parameters,
+/// signing the host, `x-amz-content-sha256` and `x-amz-date` headers
+fn sigv4_authorization(
+    method: &str,
+    url: &Url,
+    payload_hash: &str,
+    amz_date: &str,
+    region: &str,
+    access_key_id: &str,
+    secret_access_key: &str,
+) -> String {
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method,
+        url.path(),
+        host,
+        payload_hash,
+        amz_date,
+        signed_headers,
+        payload_hash
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signature = hex::encode(hmac_sha256(
+        &sigv4_signing_key(secret_access_key, date, region, "s3"),
+        &string_to_sign,
+    ));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key_id, scope, signed_headers, signature
+    )
+}
+
+/// The store selected by the `KUBARR_LOG_ARCHIVE_*` environment variables
+pub fn store_from_config() -> Result<Arc<dyn ArchiveStore>> {
+    let config = &CONFIG.log_archive;
+    let Some(bucket) = &config.s3_bucket else {
+        return Ok(Arc::new(LocalStore::new(&config.local_dir)));
+    };
+    let missing = |name: &str| {
+        AppError::ServiceUnavailable(format!("{} must be set to archive logs to S3", name))
+    };
+    let endpoint = config
+        .s3_endpoint
+        .as_deref()
+        .ok_or_else(|| missing("KUBARR_LOG_ARCHIVE_S3_ENDPOINT"))?;
+    let access_key_id = config
+        .s3_access_key_id
+        .as_deref()
+        .ok_or_else(|| missing("KUBARR_LOG_ARCHIVE_S3_ACCESS_KEY_ID"))?;
+    let secret_access_key = config
+        .s3_secret_access_key
+        .as_deref()
+        .ok_or_else(|| missing("KUBARR_LOG_ARCHIVE_S3_SECRET_ACCESS_KEY"))?;
+    Ok(Arc::new(S3Store::new(
+        endpoint,
+        bucket,
+        &config.s3_region,
+        access_key_id,
+        secret_access_key,
+        &config.s3_prefix,
+    )?))
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+/// Outcome of an archive run
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct ArchiveRunSummary {
+    /// App days archived, including days without logs
+    pub archived: usize,
+    /// Archives deleted for being past the retention
+    pub purged: usize,
+    /// App days that could not be archived, with the reason
+    pub failed: Vec<String>,
+}
+
+/// Parse `log_archive_retention_days`
+pub fn parse_retention(value: &str) -> Result<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|days| *days >= 0)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a number of days, or 0 to keep archives forever",
+                LOG_ARCHIVE_RETENTION_DAYS
+            ))
+        })
+}
+
+/// Days archives are kept; 0 keeps them forever
+pub async fn retention_days(db: &DbConn) -> Result<i64> {
+    Ok(get_setting_value(db, LOG_ARCHIVE_RETENTION_DAYS)
+        .await?
+        .and_then(|v| parse_retention(&v).ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+/// Key of an app's archive for a day
+pub fn object_key(app_name: &str, day: NaiveDate) -> String {
+    format!(
+        "{}/{}/{}-{}.ndjson.gz",
+        app_name,
+        day.format("%Y/%m"),
+        app_name,
+        day.format("%Y-%m-%d")
+    )
+}
+
+/// Complete days that may be archived at `now`, oldest first
+pub fn archivable_days(now: DateTime<Utc>) -> Vec<NaiveDate> {
+    let last = (now - chrono::Duration::minutes(GRACE_MINUTES)).date_naive() - Days::new(1);
+    (0..BACKFILL_DAYS)
+        .rev()
+        .map(|back| last - Days::new(back))
+        .collect()
+}
+
+fn day_start(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Fetch an app's logs for a day from VictoriaLogs, compressed, with the
+/// number of lines
+async fn export_day(vlogs_url: &str, app_name: &str, day: NaiveDate) -> Result<(Vec<u8>, i64)> {
+    let client = outbound::client_builder()
+        .timeout(Duration::from_secs(EXPORT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+    let start = day_start(day);
+    let end = day_start(day + Days::new(1)) - chrono::Duration::nanoseconds(1);
+    let query = format!(r#"namespace:="{}" | sort by (_time)"#, app_name);
+
+    let mut response = client
+        .get(format!("{}/select/logsql/query", vlogs_url))
+        .query(&[
+            ("query", query.as_str()),
+            (
+                "start",
+                start
+                    .to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+                    .as_str(),
+            ),
+            (
+                "end",
+                end.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+                    .as_str(),
+            ),
+        ])
+        .send()
+        .await
+        .map_err(|e| {
+            AppError::ServiceUnavailable(format!("Failed to connect to VictoriaLogs: {}", e))
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "VictoriaLogs returned error: {} - {}",
+            status,
+            body.trim()
+        )));
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut lines = 0;
+    let mut ends_with_newline = true;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read VictoriaLogs response: {}", e)))?
+    {
+        if chunk.is_empty() {
+            continue;
+        }
+        lines += chunk.iter().filter(|b| **b == b'\n').count() as i64;
+        ends_with_newline = chunk.ends_with(b"\n");
+        encoder.write_all(&chunk)?;
+    }
+    if !ends_with_newline {
+        encoder.write_all(b"\n")?;
+        lines += 1;
+    }
+    Ok((encoder.finish()?, lines))
+}
+
+/// Archive an app's logs for a day and record it in the index
+pub async fn archive_day(
+    db: &DbConn,
+    store: &dyn ArchiveStore,
+    vlogs_url: &str,
+    app_name: &str,
+    day: NaiveDate,
+) -> Result<log_archive::Model> {
+    let (data, lines) = export_day(vlogs_url, app_name, day).await?;
+    let bytes = data.len() as i64;
+    let key = if lines > 0 {
+        let key = object_key(app_name, day);
+        store.put(&key, data).await?;
+        Some(key)
+    } else {
+        None
+    };
+    Ok(log_archive::ActiveModel {
+        app_name: Set(app_name.to_string()),
+        day: Set(day.format("%Y-%m-%d").to_string()),
+        store: Set(store.kind().to_string()),
+        object_key: Set(key),
+        lines: Set(lines),
+        bytes: Set(if lines > 0 { bytes } else { 0 }),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Archive the complete days not archived yet for every installed app, then
+/// delete archives past the retention
+pub async fn run(
+    db: &DbConn,
+    store: &dyn ArchiveStore,
+    vlogs_url: &str,
+    now: DateTime<Utc>,
+) -> Result<ArchiveRunSummary> {
+    let mut summary = ArchiveRunSummary::default();
+    let days = archivable_days(now);
+    let first = days[0].format("%Y-%m-%d").to_string();
+    let done: HashSet<(String, String)> = LogArchive::find()
+        .filter(log_archive::Column::Day.gte(first))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (a.app_name, a.day))
+        .collect();
+    let apps = InstalledApp::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| a.app_name);
+
+    for app_name in apps {
+        for day in &days {
+            let day_str = day.format("%Y-%m-%d").to_string();
+            if done.contains(&(app_name.clone(), day_str.clone())) {
+                continue;
+            }
+            match archive_day(db, store, vlogs_url, &app_name, *day).await {
+                Ok(_) => summary.archived += 1,
+                Err(e) => summary
+                    .failed
+                    .push(format!("{} {}: {}", app_name, day_str, e)),
+            }
+        }
+    }
+
+    summary.purged = purge(db, store, retention_days(db).await?, now).await?;
+    Ok(summary)
+}
+
+/// Delete archives of days older than `retention_days`; 0 keeps everything
+pub async fn purge(
+    db: &DbConn,
+    store: &dyn ArchiveStore,
+    retention_days: i64,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    let cutoff = (now.date_naive() - Days::new(retention_days as u64))
+        .format("%Y-%m-%d")
+        .to_string();
+    let expired = LogArchive::find()
+        .filter(log_archive::Column::Day.lt(cutoff))
+        .filter(log_archive::Column::Store.eq(store.kind()))
+        .all(db)
+        .await?;
+
+    let mut purged = 0;
+    for archive in expired {
+        if let Some(key) = &archive.object_key {
+            if let Err(e) = store.delete(key).await {
+                tracing::warn!("Failed to delete log archive {}: {}", key, e);
+                continue;
+            }
+        }
+        LogArchive::delete_by_id(archive.id).exec(db).await?;
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+/// Archives, newest day first, optionally for one app and a range of days
+/// (`YYYY-MM-DD`, inclusive)
+pub async fn list(
+    db: &DbConn,
+    app_name: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<log_archive::Model>> {
+    let mut query = LogArchive::find();
+    if let Some(app_name) = app_name {
+        query = query.filter(log_archive::Column::AppName.eq(app_name));
+    }
+    if let Some(from) = from {
+        query = query.filter(log_archive::Column::Day.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(log_archive::Column::Day.lte(to));
+    }
+    Ok(query
+        .order_by_desc(log_archive::Column::Day)
+        .order_by_asc(log_archive::Column::AppName)
+        .all(db)
+        .await?)
+}
+
+/// Archived log lines of an app between `start` (inclusive) and `end`
+/// (exclusive), oldest first, keeping lines whose message contains `filter`
+///
+/// Returns at most `limit` lines, and whether more lines matched.
+pub async fn query(
+    db: &DbConn,
+    store: &dyn ArchiveStore,
+    app_name: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    filter: Option<&str>,
+    limit: usize,
+) -> Result<(Vec<String>, bool)> {
+    if end <= start {
+        return Err(AppError::BadRequest("end must be after start".to_string()));
+    }
+    if end - start > chrono::Duration::days(MAX_QUERY_DAYS) {
+        return Err(AppError::BadRequest(format!(
+            "Archive queries may cover at most {} days",
+            MAX_QUERY_DAYS
+        )));
+    }
+    let archives = LogArchive::find()
+        .filter(log_archive::Column::AppName.eq(app_name))
+        .filter(log_archive::Column::Day.gte(start.format("%Y-%m-%d").to_string()))
+        .filter(log_archive::Column::Day.lte(end.format("%Y-%m-%d").to_string()))
+        .filter(log_archive::Column::Store.eq(store.kind()))
+        .filter(log_archive::Column::ObjectKey.is_not_null())
+        .order_by_asc(log_archive::Column::Day)
+        .all(db)
+        .await?;
+
+    let mut lines = Vec::new();
+    for archive in archives {
+        let Some(key) = archive.object_key else {
+            continue;
+        };
+        let data = store.get(&key).await?;
+        let filter = filter.map(str::to_string);
+        let remaining = limit - lines.len();
+        let (mut matched, more) = tokio::task::spawn_blocking(move || {
+            read_lines(&data, start, end, filter.as_deref(), remaining)
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read log archive: {}", e)))??;
+        lines.append(&mut matched);
+        if more {
+            return Ok((lines, true));
+        }
+    }
+    Ok((lines, false))
+}
+
+/// Lines of a compressed archive within the range and matching the filter,
+/// up to `limit`, and whether more matched
+fn read_lines(
+    data: &[u8],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    filter: Option<&str>,
+    limit: usize,
+) -> Result<(Vec<String>, bool)> {
+    let mut lines = Vec::new();
+    for line in BufReader::new(GzDecoder::new(data)).lines() {
+        let line = line?;
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(time) = entry
+            .get("_time")
+            .and_then(|t| t.as_str())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        else {
+            continue;
+        };
+        let time = time.with_timezone(&Utc);
+        if time < start {
+            continue;
+        }
+        if time >= end {
+            break;
+        }
+        if let Some(filter) = filter {
+            let message = entry.get("_msg").and_then(|m| m.as_str()).unwrap_or("");
+            if !message.contains(filter) {
+                continue;
+            }
+        }
+        if lines.len() == limit {
+            return Ok((lines, true));
+        }
+        lines.push(line);
+    }
+    Ok((lines, false))
+}
+
+/// Archives the previous days' logs when `log_archive_enabled` is on
+pub struct LogArchiveTask;
+
+#[async_trait]
+impl PeriodicTask for LogArchiveTask {
+    fn name(&self) -> &'static str {
+        "log_archive"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(RUN_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        if !get_setting_bool(db, LOG_ARCHIVE_ENABLED).await? {
+            return Ok(());
+        }
+        let store = store_from_config()?;
+        let summary = run(db, store.as_ref(), VICTORIALOGS_URL, Utc::now()).await?;
+        if summary.archived > 0 || summary.purged > 0 {
+            tracing::info!(
+                "Archived {} app log days, purged {} archives",
+                summary.archived,
+                summary.purged
+            );
+        }
+        for failure in &summary.failed {
+            tracing::warn!("Failed to archive logs for {}", failure);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = sigv4_signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_sigv4_authorization() {
+        let url = Url::parse("http://minio.local:9000/logs/kubarr-logs/a.gz").unwrap();
+        let header = sigv4_authorization(
+            "PUT",
+            &url,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            "20261017T120000Z",
+            "us-east-1",
+            "AKIDEXAMPLE",
+            "secret",
+        );
+        assert!(header.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20261017/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        let signature = header.rsplit('=').next().unwrap();
+        assert_eq!(signature.len(), 64);
+    }
+
+    #[test]
+    fn test_archivable_days() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let days = archivable_days(at("2026-10-17T00:30:00Z"));
+        assert_eq!(days.len(), BACKFILL_DAYS as usize);
+        assert_eq!(days.last().unwrap().to_string(), "2026-10-15");
+        let days = archivable_days(at("2026-10-17T01:30:00Z"));
+        assert_eq!(days.last().unwrap().to_string(), "2026-10-16");
+        assert_eq!(days[0].to_string(), "2026-10-10");
+    }
+
+    #[test]
+    fn test_object_key() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+        assert_eq!(
+            object_key("sonarr", day),
+            "sonarr/2026/03/sonarr-2026-03-07.ndjson.gz"
+        );
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for (time, msg) in [
+            ("2026-10-16T09:59:00Z", "before"),
+            ("2026-10-16T10:00:00Z", "ERROR one"),
+            ("2026-10-16T10:30:00Z", "info"),
+            ("2026-10-16T10:45:00Z", "ERROR two"),
+            ("2026-10-16T11:00:00Z", "after"),
+        ] {
+            let line = serde_json::json!({"_time": time, "_msg": msg});
+            writeln!(encoder, "{}", line).unwrap();
+        }
+        let data = encoder.finish().unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let (start, end) = (at("2026-10-16T10:00:00Z"), at("2026-10-16T11:00:00Z"));
+
+        let (lines, more) = read_lines(&data, start, end, None, 10).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(!more);
+        let (lines, more) = read_lines(&data, start, end, Some("ERROR"), 10).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(!more);
+        let (lines, more) = read_lines(&data, start, end, Some("ERROR"), 1).unwrap();
+        assert!(lines[0].contains("ERROR one"));
+        assert!(more);
+    }
+
+    #[test]
+    fn test_parse_retention() {
+        assert_eq!(parse_retention("0").unwrap(), 0);
+        assert_eq!(parse_retention(" 90 ").unwrap(), 90);
+        assert!(parse_retention("-1").is_err());
+        assert!(parse_retention("year").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! In-memory buffer of recent backend log lines
+//!
+//! [`LogBufferLayer`] is installed next to the stdout formatter and copies
+//! every event that passes the log filter into [`LOG_BUFFER`], a ring buffer
+//! holding the last [`CAPACITY`] lines. Admins read it through
+//! `/api/system/logs`, so Kubarr can be debugged without kubectl.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of lines kept in memory
+pub const CAPACITY: usize = 5000;
+
+/// Backlog of live lines a follower may fall behind by before lines are skipped
+const FOLLOW_BACKLOG: usize = 256;
+
+/// The process-wide log buffer
+pub static LOG_BUFFER: Lazy<LogBuffer> = Lazy::new(|| LogBuffer::new(CAPACITY));
+
+/// One captured log line
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LogEntry {
+    /// Increases by one per captured line
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// "error", "warn", "info", "debug" or "trace"
+    pub level: String,
+    pub target: String,
+    /// The message followed by any structured fields
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Plain text form used for downloads
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} {:>5} {}: {}",
+            self.timestamp.to_rfc3339(),
+            self.level.to_uppercase(),
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Parse a level name into a tracing level
+pub fn parse_level(level: &str) -> Option<Level> {
+    match level.trim().to_lowercase().as_str() {
+        "error" => Some(Level::ERROR),
+        "warn" | "warning" => Some(Level::WARN),
+        "info" => Some(Level::INFO),
+        "debug" => Some(Level::DEBUG),
+        "trace" => Some(Level::TRACE),
+        _ => None,
+    }
+}
+
+/// Whether an entry is at least as severe as `min_level`
+pub fn at_least(entry: &LogEntry, min_level: Option<Level>) -> bool {
+    match (min_level, parse_level(&entry.level)) {
+        // More severe levels compare as smaller
+        (Some(min), Some(level)) => level <= min,
+        _ => true,
+    }
+}
+
+struct Lines {
+    entries: VecDeque<LogEntry>,
+    next_seq: u64,
+}
+
+/// Ring buffer of log lines with live subscribers
+pub struct LogBuffer {
+    capacity: usize,
+    lines: Mutex<Lines>,
+    live: broadcast::Sender<LogEntry>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lines: Mutex::new(Lines {
+                entries: VecDeque::with_capacity(capacity),
+                next_seq: 1,
+            }),
+            live: broadcast::channel(FOLLOW_BACKLOG).0,
+        }
+    }
+
+    /// Append a line, dropping the oldest once full
+    pub fn push(&self, timestamp: DateTime<Utc>, level: &Level, target: &str, message: String) {
+        let Ok(mut lines) = self.lines.lock() else {
+            return;
+        };
+        let entry = LogEntry {
+            seq: lines.next_seq,
+            timestamp,
+            level: level.as_str().to_lowercase(),
+            target: target.to_string(),
+            message,
+        };
+        lines.next_seq += 1;
+        if lines.entries.len() == self.capacity {
+            lines.entries.pop_front();
+        }
+        lines.entries.push_back(entry.clone());
+        // No receivers is fine
+        let _ = self.live.send(entry);
+    }
+
+    /// The newest `limit` lines at or above `min_level`, oldest first
+    pub fn recent(&self, min_level: Option<Level>, limit: usize) -> Vec<LogEntry> {
+        let Ok(lines) = self.lines.lock() else {
+            return Vec::new();
+        };
+        let mut recent: Vec<LogEntry> = lines
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| at_least(e, min_level))
+            .take(limit)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Every buffered line as text, oldest first
+    pub fn to_text(&self) -> String {
+        let Ok(lines) = self.lines.lock() else {
+            return String::new();
+        };
+        lines.entries.iter().fold(String::new(), |mut text, entry| {
+            let _ = writeln!(text, "{}", entry.to_line());
+            text
+        })
+    }
+
+    /// Receive lines as they are captured
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.live.subscribe()
+    }
+}
+
+/// Collects an event's message and fields into one line
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Tracing layer feeding [`LOG_BUFFER`]
+pub struct LogBufferLayer;
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        LOG_BUFFER.push(
+            Utc::now(),
+            metadata.level(),
+            metadata.target(),
+            visitor.message + &visitor.fields,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_keeps_newest_lines() {
+        let buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(Utc::now(), &Level::INFO, "kubarr", format!("line {}", i));
+        }
+        let recent = buffer.recent(None, 10);
+        let messages: Vec<&str> = recent.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["line 2", "line 3", "line 4"]);
+        assert_eq!(recent[2].seq, 5);
+        assert_eq!(buffer.recent(None, 1)[0].message, "line 4");
+        assert_eq!(buffer.to_text().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_level_filter() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(Utc::now(), &Level::DEBUG, "kubarr", "debug".to_string());
+        buffer.push(Utc::now(), &Level::WARN, "kubarr", "warn".to_string());
+        buffer.push(Utc::now(), &Level::ERROR, "kubarr", "error".to_string());
+
+        let warn: Vec<String> = buffer
+            .recent(parse_level("warn"), 10)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(warn, ["warn", "error"]);
+        assert_eq!(buffer.recent(parse_level("trace"), 10).len(), 3);
+        assert_eq!(parse_level("loud"), None);
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_new_lines() {
+        let buffer = LogBuffer::new(10);
+        let mut rx = buffer.subscribe();
+        buffer.push(Utc::now(), &Level::INFO, "kubarr", "hello".to_string());
+        let entry = rx.recv().await.unwrap();
+        assert_eq!(entry.message, "hello");
+        assert_eq!(entry.to_line().split_whitespace().nth(1), Some("INFO"));
+    }
+}
@@ -0,0 +1,166 @@
+//! New-device login alerts
+//!
+//! When a user signs in from an address and browser none of their other
+//! sessions used, they get a `new_device_login` notification on their own
+//! channels asking whether it was them. The message carries a link that
+//! signs that session out without signing in, for the case where it was
+//! not.
+//!
+//! Links are signed for the session and its owner and expire with the
+//! session. Opening one shows a confirmation page; the session is revoked
+//! once the user confirms, so mail scanners that prefetch links cannot sign
+//! anyone out. Every confirmed link is recorded in the audit log as
+//! `session_revoked`, including links for sessions that had already ended.
+//!
+//! Alerts are on by default and can be turned off per user with the
+//! `login_alerts` preference. Only recent sessions are compared: revoked and
+//! expired ones are deleted a day after they end, and a user with no
+//! sessions left is not alerted.
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+use super::links;
+use super::notification::NotificationService;
+use super::security::{create_session_revoke_token, decode_session_revoke_token};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::session;
+
+/// Whether `user_id` wants new-device login alerts
+pub async fn enabled(db: &DatabaseConnection, user_id: i64) -> Result<bool> {
+    Ok(UserPreferences::find_by_id(user_id)
+        .one(db)
+        .await?
+        .is_none_or(|p| p.login_alerts))
+}
+
+/// Whether `session` comes from an address and browser none of the user's
+/// other sessions used
+///
+/// A user without other sessions has nothing to compare against, so their
+/// first sign-in is not reported.
+pub async fn is_new_device(db: &DatabaseConnection, session: &session::Model) -> Result<bool> {
+    let others = Session::find()
+        .filter(session::Column::UserId.eq(session.user_id))
+        .filter(session::Column::Id.ne(&session.id))
+        .all(db)
+        .await?;
+    Ok(!others.is_empty()
+        && !others.iter().any(|other| {
+            other.ip_address == session.ip_address && other.user_agent == session.user_agent
+        }))
+}
+
+/// Link that lets the owner of `session` sign it out
+pub fn revoke_link(session: &session::Model) -> Result<String> {
+    let token = create_session_revoke_token(&session.id, session.user_id, session.expires_at)?;
+    Ok(links::absolute(&format!(
+        "/auth/sessions/revoke-link?token={}",
+        token
+    )))
+}
+
+/// Details of a new-device alert: where the session came from and the link
+/// to sign it out
+pub fn alert_details(session: &session::Model, link: &str) -> String {
+    format!(
+        "IP {}, {}. Not you? Sign that session out: {}",
+        session.ip_address.as_deref().unwrap_or("unknown"),
+        session.user_agent.as_deref().unwrap_or("unknown browser"),
+        link
+    )
+}
+
+/// Alert the owner of a new session if it comes from a new device and they
+/// have alerts on; returns whether an alert was sent
+pub async fn check_login(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    username: &str,
+    session: &session::Model,
+) -> Result<bool> {
+    if !enabled(db, session.user_id).await? || !is_new_device(db, session).await? {
+        return Ok(false);
+    }
+    let details = alert_details(session, &revoke_link(session)?);
+    notification
+        .notify_security(
+            &AuditAction::NewDeviceLogin,
+            session.user_id,
+            username,
+            Some(&details),
+        )
+        .await?;
+    Ok(true)
+}
+
+/// The session a revoke link is for, after checking the link is valid
+pub async fn resolve_link(db: &DatabaseConnection, token: &str) -> Result<session::Model> {
+    let claims = decode_session_revoke_token(token).map_err(|_| {
+        AppError::Unauthorized("This sign-out link is invalid or has expired".to_string())
+    })?;
+    Session::find_by_id(&claims.sid)
+        .one(db)
+        .await?
+        .filter(|s| s.user_id == claims.sub)
+        .ok_or_else(|| AppError::NotFound("This session no longer exists".to_string()))
+}
+
+/// Whether a session can still be used
+pub fn is_active(session: &session::Model) -> bool {
+    !session.is_revoked && session.expires_at > Utc::now()
+}
+
+/// Revoke the session a revoke link is for
+///
+/// Returns the session and whether it was still active (the user may have
+/// signed it out already).
+pub async fn revoke_with_link(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<(session::Model, bool)> {
+    let session = resolve_link(db, token).await?;
+    if !is_active(&session) {
+        return Ok((session, false));
+    }
+    let mut active: session::ActiveModel = session.into();
+    active.is_revoked = Set(true);
+    Ok((active.update(db).await?, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_alert_details() {
+        let now = Utc::now();
+        let mut session = session::Model {
+            id: "sid".to_string(),
+            user_id: 1,
+            user_agent: Some("Firefox/131.0".to_string()),
+            ip_address: Some("203.0.113.7".to_string()),
+            created_at: now,
+            expires_at: now + Duration::days(7),
+            last_accessed_at: now,
+            is_revoked: false,
+            refresh_token_hash: None,
+            previous_refresh_token_hash: None,
+            refreshed_at: None,
+        };
+        assert_eq!(
+            alert_details(&session, "https://k/x"),
+            "IP 203.0.113.7, Firefox/131.0. Not you? Sign that session out: https://k/x"
+        );
+        assert!(is_active(&session));
+
+        session.ip_address = None;
+        session.user_agent = None;
+        session.is_revoked = true;
+        assert!(alert_details(&session, "l").starts_with("IP unknown, unknown browser."));
+        assert!(!is_active(&session));
+    }
+}
@@ -0,0 +1,237 @@
+//! Maintenance page for proxied apps
+//!
+//! When the proxy cannot reach an app it answers with a page saying whether
+//! the app is starting or stopped instead of a bare 502. The page polls its
+//! own URL with the [`MAINTENANCE_HEADER`] set to `status`, which the proxy
+//! answers with the app's [`AppRunStatus`] instead of forwarding, and reloads
+//! once the app is running. Users with `apps.restart` can start a stopped app
+//! from the page, which POSTs to the same URL with the header set to `start`.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::Result;
+use crate::services::k8s::K8sApi;
+
+/// Request header the maintenance page uses to talk to the proxy
+pub const MAINTENANCE_HEADER: &str = "x-kubarr-maintenance";
+
+/// Seconds clients should wait before retrying an app that is down
+pub const RETRY_AFTER_SECS: u32 = 5;
+
+/// Whether an app's workloads are up, as seen from the cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AppRunState {
+    /// All workloads report ready replicas
+    Running,
+    /// Workloads are scaled up but not ready yet
+    Starting,
+    /// Every deployment is scaled to zero
+    Stopped,
+    /// The app's namespace or workloads do not exist
+    Missing,
+}
+
+/// Run state of an app, as returned to the maintenance page
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AppRunStatus {
+    pub app_name: String,
+    pub state: AppRunState,
+    pub message: String,
+    /// Whether the requesting user may start the app from the page
+    pub can_start: bool,
+}
+
+/// Work out the run state of an app from its namespace's workloads
+pub async fn app_run_status(k8s: &dyn K8sApi, app_name: &str) -> Result<AppRunStatus> {
+    let status = |state: AppRunState, message: String| AppRunStatus {
+        app_name: app_name.to_string(),
+        state,
+        message,
+        can_start: false,
+    };
+
+    if !k8s.namespace_exists(app_name).await? {
+        return Ok(status(
+            AppRunState::Missing,
+            format!("{} is not installed", app_name),
+        ));
+    }
+    let deployments = k8s.list_deployments(app_name).await?;
+    let daemonsets = k8s.list_daemonsets(app_name).await?;
+    if deployments.is_empty() && daemonsets.is_empty() {
+        return Ok(status(
+            AppRunState::Missing,
+            format!("{} has no workloads", app_name),
+        ));
+    }
+
+    let desired: i32 = deployments
+        .iter()
+        .map(|d| d.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1))
+        .sum();
+    if daemonsets.is_empty() && desired == 0 {
+        return Ok(status(
+            AppRunState::Stopped,
+            format!("{} is stopped", app_name),
+        ));
+    }
+
+    let deployments_ready = deployments.iter().all(|d| {
+        let replicas = d.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+        let ready = d
+            .status
+            .as_ref()
+            .and_then(|s| s.ready_replicas)
+            .unwrap_or(0);
+        ready >= replicas
+    });
+    let daemonsets_ready = daemonsets.iter().all(|ds| {
+        ds.status.as_ref().is_some_and(|s| {
+            s.desired_number_scheduled > 0 && s.number_ready >= s.desired_number_scheduled
+        })
+    });
+
+    Ok(if deployments_ready && daemonsets_ready {
+        status(
+            AppRunState::Running,
+            format!("{} is running but not responding", app_name),
+        )
+    } else {
+        status(AppRunState::Starting, format!("{} is starting", app_name))
+    })
+}
+
+/// Escape text for inclusion in HTML
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{app}} is unavailable - Kubarr</title>
+<style>
+body { font-family: system-ui, sans-serif; background: #111827; color: #e5e7eb; display: flex; align-items: center; justify-content: center; min-height: 100vh; margin: 0; }
+main { max-width: 28rem; padding: 2rem; text-align: center; }
+h1 { font-size: 1.5rem; margin-bottom: 0.5rem; }
+p { color: #9ca3af; }
+button { background: #2563eb; color: #fff; border: 0; border-radius: 0.375rem; padding: 0.5rem 1rem; font-size: 1rem; cursor: pointer; }
+button:disabled { opacity: 0.6; cursor: default; }
+a { color: #60a5fa; }
+</style>
+</head>
+<body>
+<main>
+<h1>{{app}} is unavailable</h1>
+<p id="message">{{message}}</p>
+<p><button id="start" {{start_hidden}}>Start {{app}}</button></p>
+<p><a href="/">Back to Kubarr</a></p>
+</main>
+<script>
+(function () {
+  var header = "{{header}}";
+  var initial = "{{state}}";
+  var message = document.getElementById("message");
+  var button = document.getElementById("start");
+
+  function poll() {
+    fetch(window.location.href, { headers: { [header]: "status" }, credentials: "same-origin" })
+      .then(function (r) { return r.ok ? r.json() : null; })
+      .then(function (status) {
+        if (status) {
+          message.textContent = status.message;
+          button.hidden = !status.can_start;
+          if (status.state === "running" && initial !== "running") {
+            window.location.reload();
+            return;
+          }
+        }
+        setTimeout(poll, {{retry_ms}});
+      })
+      .catch(function () { setTimeout(poll, {{retry_ms}}); });
+  }
+
+  button.addEventListener("click", function () {
+    button.disabled = true;
+    fetch(window.location.href, { method: "POST", headers: { [header]: "start" }, credentials: "same-origin" })
+      .then(function (r) { return r.json(); })
+      .then(function (status) { message.textContent = status.message; })
+      .catch(function () { button.disabled = false; });
+  });
+
+  setTimeout(poll, {{retry_ms}});
+})();
+</script>
+</body>
+</html>
+"#;
+
+/// Render the maintenance page for an app that could not be reached
+pub fn render_page(status: &AppRunStatus) -> String {
+    let state = serde_json::to_value(status.state)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    PAGE_TEMPLATE
+        .replace("{{app}}", &escape_html(&status.app_name))
+        .replace("{{message}}", &escape_html(&status.message))
+        .replace(
+            "{{start_hidden}}",
+            if status.can_start { "" } else { "hidden" },
+        )
+        .replace("{{header}}", MAINTENANCE_HEADER)
+        .replace("{{state}}", &state)
+        .replace("{{retry_ms}}", &(RETRY_AFTER_SECS * 1000).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::k8s::FakeK8s;
+
+    #[tokio::test]
+    async fn test_app_run_status() {
+        let fake = FakeK8s::new();
+        fake.add_app("sonarr", true);
+        fake.add_app("radarr", false);
+        fake.add_namespace("lidarr");
+
+        let state = |app: &'static str| {
+            let fake = fake.clone();
+            async move { app_run_status(&fake, app).await.unwrap().state }
+        };
+        assert_eq!(state("sonarr").await, AppRunState::Running);
+        assert_eq!(state("radarr").await, AppRunState::Starting);
+        assert_eq!(state("lidarr").await, AppRunState::Missing);
+        assert_eq!(state("plex").await, AppRunState::Missing);
+
+        fake.scale_deployment("sonarr", "sonarr", 0).await.unwrap();
+        assert_eq!(state("sonarr").await, AppRunState::Stopped);
+    }
+
+    #[test]
+    fn test_render_page_escapes_and_hides_start() {
+        let mut status = AppRunStatus {
+            app_name: "<script>".to_string(),
+            state: AppRunState::Stopped,
+            message: "stopped".to_string(),
+            can_start: false,
+        };
+        let page = render_page(&status);
+        assert!(page.contains("&lt;script&gt; is unavailable"));
+        assert!(page.contains(r#"<button id="start" hidden>"#));
+        assert!(page.contains(r#"var initial = "stopped";"#));
+        assert!(!page.contains("{{"));
+
+        status.can_start = true;
+        assert!(render_page(&status).contains(r#"<button id="start" >"#));
+    }
+}
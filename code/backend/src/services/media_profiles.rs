@@ -0,0 +1,374 @@
+//! Media profiles
+//!
+//! A media profile says what the members of a role may see in Jellyfin: the
+//! libraries, how many streams they may play at once and the highest
+//! parental rating. Kubarr writes it into the Jellyfin user policy of each
+//! member, so family access is managed in one place and actually restricts
+//! the media server. A user's Jellyfin account is the one in their account
+//! links (see [`super::account_links`]), or else the one with their name.
+//!
+//! A user with several profiled roles gets the most generous of them. Roles
+//! without a profile do not count, and users none of whose roles has a
+//! profile are left as they are in Jellyfin. Members are synced in the
+//! background when their roles change or their role's profile changes, and
+//! on demand with [`sync_role`].
+
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::prelude::{AppIntegration, User, UserRole};
+use crate::models::{media_profile, user_role};
+use crate::services::access::unexpired_roles;
+use crate::services::account_links;
+use crate::services::integrations::build_context;
+use crate::services::integrations::jellyfin::{
+    self, apply_policy_limits, JellyfinLibrary, PolicyLimits,
+};
+use crate::state::{AppState, DbConn};
+
+/// The media server profiles are synced to
+const APP: &str = "jellyfin";
+
+/// What the members of a role may see in the media server
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MediaProfile {
+    /// Names of the libraries members may see; empty is every library
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    /// Streams a member may play at once; `None` is unlimited
+    pub max_streams: Option<i32>,
+    /// Highest parental rating value shown; `None` is no limit
+    pub max_parental_rating: Option<i32>,
+}
+
+impl From<&media_profile::Model> for MediaProfile {
+    fn from(model: &media_profile::Model) -> Self {
+        Self {
+            libraries: serde_json::from_str(&model.libraries).unwrap_or_default(),
+            max_streams: model.max_streams,
+            max_parental_rating: model.max_parental_rating,
+        }
+    }
+}
+
+/// Outcome of syncing one user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    /// The policy was changed
+    Updated,
+    /// The policy already matched
+    Unchanged,
+    /// None of the user's roles has a profile
+    NoProfile,
+    /// The user has no media server account
+    NoAccount,
+    Failed,
+}
+
+/// Result of syncing one user
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncResult {
+    pub user_id: i64,
+    pub username: String,
+    pub status: SyncStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Combine the profiles of a user's roles into the most generous one
+pub fn merge(profiles: &[MediaProfile]) -> MediaProfile {
+    let most = |values: Vec<Option<i32>>| -> Option<i32> {
+        values
+            .iter()
+            .try_fold(i32::MIN, |acc, v| v.map(|v| acc.max(v)))
+    };
+    let libraries = if profiles.iter().any(|p| p.libraries.is_empty()) {
+        Vec::new()
+    } else {
+        let mut all: Vec<String> = profiles.iter().flat_map(|p| p.libraries.clone()).collect();
+        all.sort();
+        all.dedup();
+        all
+    };
+    MediaProfile {
+        libraries,
+        max_streams: most(profiles.iter().map(|p| p.max_streams).collect()),
+        max_parental_rating: most(profiles.iter().map(|p| p.max_parental_rating).collect()),
+    }
+}
+
+/// Turn a profile into policy limits, resolving library names to IDs;
+/// unknown names are left out
+fn limits_for(profile: &MediaProfile, libraries: &[JellyfinLibrary]) -> PolicyLimits {
+    let library_ids = (!profile.libraries.is_empty()).then(|| {
+        libraries
+            .iter()
+            .filter(|l| {
+                profile
+                    .libraries
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(&l.name))
+            })
+            .filter_map(|l| l.item_id.clone())
+            .collect()
+    });
+    PolicyLimits {
+        library_ids,
+        max_streams: profile.max_streams,
+        max_parental_rating: profile.max_parental_rating,
+    }
+}
+
+// ============================================================================
+// Profile management
+// ============================================================================
+
+/// The profile of a role
+pub async fn get_profile(db: &DbConn, role_id: i64) -> Result<Option<MediaProfile>> {
+    Ok(find_row(db, role_id).await?.as_ref().map(Into::into))
+}
+
+async fn find_row(db: &DbConn, role_id: i64) -> Result<Option<media_profile::Model>> {
+    Ok(media_profile::Entity::find()
+        .filter(media_profile::Column::RoleId.eq(role_id))
+        .one(db)
+        .await?)
+}
+
+/// Create or replace the profile of a role
+pub async fn set_profile(
+    db: &DbConn,
+    role_id: i64,
+    profile: &MediaProfile,
+) -> Result<MediaProfile> {
+    if profile.max_streams.is_some_and(|v| v < 1) {
+        return Err(AppError::BadRequest(
+            "max_streams must be at least 1".to_string(),
+        ));
+    }
+    if profile.max_parental_rating.is_some_and(|v| v < 0) {
+        return Err(AppError::BadRequest(
+            "max_parental_rating cannot be negative".to_string(),
+        ));
+    }
+    let mut libraries: Vec<String> = profile
+        .libraries
+        .iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    libraries.dedup();
+
+    let now = chrono::Utc::now();
+    let existing = find_row(db, role_id).await?;
+    let is_new = existing.is_none();
+    let mut model = match existing {
+        Some(existing) => existing.into(),
+        None => media_profile::ActiveModel {
+            role_id: Set(role_id),
+            created_at: Set(now),
+            ..Default::default()
+        },
+    };
+    model.libraries = Set(serde_json::to_string(&libraries)?);
+    model.max_streams = Set(profile.max_streams);
+    model.max_parental_rating = Set(profile.max_parental_rating);
+    model.updated_at = Set(now);
+    let saved = if is_new {
+        model.insert(db).await?
+    } else {
+        model.update(db).await?
+    };
+    Ok((&saved).into())
+}
+
+/// Remove the profile of a role; returns whether one existed
+pub async fn delete_profile(db: &DbConn, role_id: i64) -> Result<bool> {
+    let result = media_profile::Entity::delete_many()
+        .filter(media_profile::Column::RoleId.eq(role_id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// The profile a user gets from their roles, if any of them has one
+pub async fn effective_profile(db: &DbConn, user_id: i64) -> Result<Option<MediaProfile>> {
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.role_id)
+        .collect();
+    let profiles: Vec<MediaProfile> = media_profile::Entity::find()
+        .filter(media_profile::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await?
+        .iter()
+        .map(Into::into)
+        .collect();
+    Ok((!profiles.is_empty()).then(|| merge(&profiles)))
+}
+
+// ============================================================================
+// Syncing
+// ============================================================================
+
+/// Write the profiles of the given users into their media server policies
+pub async fn sync_users(state: &AppState, user_ids: &[i64]) -> Result<Vec<SyncResult>> {
+    let db = state.get_db().await?;
+    let ctx = build_context(state, APP).await?;
+    let accounts = jellyfin::fetch_users(&ctx).await?;
+    let libraries = jellyfin::fetch_libraries(&ctx).await?;
+
+    let mut results = Vec::new();
+    for &user_id in user_ids {
+        let Some(member) = User::find_by_id(user_id).one(&db).await? else {
+            continue;
+        };
+        let result = |status: SyncStatus, error: Option<String>| SyncResult {
+            user_id,
+            username: member.username.clone(),
+            status,
+            error,
+        };
+        let Some(profile) = effective_profile(&db, user_id).await? else {
+            results.push(result(SyncStatus::NoProfile, None));
+            continue;
+        };
+        let account = match account_links::find_link(&db, user_id, APP).await? {
+            Some(link) => accounts.iter().find(|a| a.id == link.external_id),
+            None => accounts
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(&member.username)),
+        };
+        let Some(account) = account else {
+            results.push(result(SyncStatus::NoAccount, None));
+            continue;
+        };
+
+        let mut policy = account.policy.clone();
+        if !apply_policy_limits(&mut policy, &limits_for(&profile, &libraries)) {
+            results.push(result(SyncStatus::Unchanged, None));
+            continue;
+        }
+        match jellyfin::update_policy(&ctx, &account.id, &policy).await {
+            Ok(()) => results.push(result(SyncStatus::Updated, None)),
+            Err(e) => results.push(result(SyncStatus::Failed, Some(e.to_string()))),
+        }
+    }
+    Ok(results)
+}
+
+/// Sync every member of a role
+pub async fn sync_role(state: &AppState, role_id: i64) -> Result<Vec<SyncResult>> {
+    let db = state.get_db().await?;
+    let members: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::RoleId.eq(role_id))
+        .all(&db)
+        .await?
+        .iter()
+        .map(|ur| ur.user_id)
+        .collect();
+    sync_users(state, &members).await
+}
+
+/// What to sync in the background
+pub enum SyncTarget {
+    Users(Vec<i64>),
+    Role(i64),
+}
+
+/// Sync in the background when Jellyfin is set up and any role has a
+/// profile; failures are only logged
+pub fn spawn_sync(state: &AppState, target: SyncTarget) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let Ok(db) = state.get_db().await else {
+            return;
+        };
+        let configured = AppIntegration::find_by_id(APP)
+            .one(&db)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|i| i.enabled);
+        let profiled = media_profile::Entity::find()
+            .one(&db)
+            .await
+            .ok()
+            .flatten()
+            .is_some();
+        if !configured || !profiled {
+            return;
+        }
+
+        let synced = match target {
+            SyncTarget::Users(ids) => sync_users(&state, &ids).await,
+            SyncTarget::Role(id) => sync_role(&state, id).await,
+        };
+        match synced {
+            Ok(results) => {
+                for r in results.iter().filter(|r| r.status == SyncStatus::Failed) {
+                    tracing::warn!(
+                        "Failed to sync media profile of {}: {}",
+                        r.username,
+                        r.error.as_deref().unwrap_or_default()
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("Failed to sync media profiles: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(libraries: &[&str], max_streams: Option<i32>) -> MediaProfile {
+        MediaProfile {
+            libraries: libraries.iter().map(|l| l.to_string()).collect(),
+            max_streams,
+            max_parental_rating: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_merge_takes_most_generous() {
+        let kids = profile(&["Kids"], Some(1));
+        let teens = profile(&["Kids", "Shows"], Some(2));
+        let merged = merge(&[kids.clone(), teens]);
+        assert_eq!(merged.libraries, vec!["Kids", "Shows"]);
+        assert_eq!(merged.max_streams, Some(2));
+        assert_eq!(merged.max_parental_rating, Some(10));
+
+        let everything = merge(&[kids, profile(&[], None)]);
+        assert!(everything.libraries.is_empty());
+        assert_eq!(everything.max_streams, None);
+    }
+
+    #[test]
+    fn test_limits_resolve_library_names() {
+        let libraries = vec![
+            JellyfinLibrary {
+                name: "Kids".to_string(),
+                item_id: Some("lib-kids".to_string()),
+            },
+            JellyfinLibrary {
+                name: "Movies".to_string(),
+                item_id: Some("lib-movies".to_string()),
+            },
+        ];
+        let limits = limits_for(&profile(&["kids", "Gone"], Some(1)), &libraries);
+        assert_eq!(limits.library_ids, Some(vec!["lib-kids".to_string()]));
+        assert_eq!(
+            limits_for(&profile(&[], None), &libraries).library_ids,
+            None
+        );
+    }
+}
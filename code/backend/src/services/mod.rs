@@ -1,17 +1,87 @@
+pub mod access;
+pub mod access_denials;
+pub mod access_reviews;
+pub mod account_links;
+pub mod alerts;
+pub mod anomaly;
+pub mod app_requests;
+pub mod app_routing;
+pub mod app_schedules;
+pub mod app_values;
+pub mod approvals;
 pub mod audit;
+pub mod auth_analytics;
+pub mod backup;
+pub mod boot_order;
 pub mod bootstrap;
 pub mod cadvisor;
 pub mod catalog;
+pub mod catalog_bundle;
+pub mod catalog_validation;
 pub mod chart_sync;
+pub mod checksums;
+pub mod client_certs;
 pub mod cloudflare;
+pub mod crowdsec;
+pub mod crypto;
+pub mod dashboards;
 pub mod deployment;
+pub mod deprovisioning;
+pub mod diagnostics;
+pub mod energy;
+pub mod hardware_sensors;
+pub mod helm;
+pub mod homes;
+pub mod i18n;
+pub mod idle_suspend;
+pub mod installed_apps;
+pub mod integrations;
+pub mod invites;
+pub mod ip_bans;
 pub mod k8s;
+pub mod links;
+pub mod lockout;
+pub mod log_alerts;
+pub mod log_archive;
+pub mod log_buffer;
+pub mod login_alerts;
+pub mod maintenance;
+pub mod media_profiles;
 pub mod network_broadcaster;
+pub mod network_usage;
 pub mod notification;
+pub mod outbound;
+pub mod pod_exec;
+pub mod pod_logs;
+pub mod pod_stability;
+pub mod previews;
+pub mod provisioning;
 pub mod proxy;
+pub mod qr;
+pub mod quotas;
+pub mod refresh_tokens;
+pub mod registries;
+pub mod reports;
+pub mod role_expiry;
+pub mod runtime_config;
 pub mod scheduler;
 pub mod security;
+pub mod security_report;
+pub mod service_accounts;
+pub mod sessions;
+pub mod shares;
+pub mod storage_roots;
+pub mod telemetry;
+pub mod tenants;
+pub mod tls;
+pub mod trash;
+pub mod trust_store;
+pub mod updates;
+pub mod uptime;
+pub mod victoriametrics;
 pub mod vpn;
+pub mod wan_health;
+pub mod webdav;
 
 pub use audit::*;
 pub use bootstrap::BootstrapService;
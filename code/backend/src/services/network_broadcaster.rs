@@ -280,7 +280,7 @@ async fn fetch_and_compute_metrics(state: &AppState) -> Result<NetworkMetricsMes
 }
 
 /// Check if a namespace should be excluded from display
-fn is_excluded_namespace(namespace: &str) -> bool {
+pub(crate) fn is_excluded_namespace(namespace: &str) -> bool {
     namespace.starts_with("kube-")
         || namespace == "local-path-storage"
         || namespace == "default"
@@ -0,0 +1,499 @@
+//! Per-app network bandwidth accounting
+//!
+//! Every hour, ingress/egress byte increases per namespace are read from the
+//! cAdvisor counters in VictoriaMetrics and added to daily and monthly
+//! rollups in `network_usage`. Optional monthly quotas (`network_quotas`)
+//! produce warnings when an app's transfer approaches or exceeds its limit.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use super::network_broadcaster::is_excluded_namespace;
+use super::scheduler::PeriodicTask;
+use super::victoriametrics::query_vm;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{network_quota, network_usage};
+
+/// Rollup granularity for daily rows
+pub const PERIOD_DAY: &str = "day";
+/// Rollup granularity for monthly rows
+pub const PERIOD_MONTH: &str = "month";
+
+/// Share of a quota at which a warning is raised
+pub const QUOTA_WARNING_PERCENT: f64 = 80.0;
+
+/// Sampling interval; also the lookback window of each `increase()` query
+const SAMPLE_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Bytes transferred by an app during one sampling window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficSample {
+    pub rx_bytes: i64,
+    pub tx_bytes: i64,
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Traffic for a single day or month
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NetworkUsagePoint {
+    /// "YYYY-MM-DD" for days, "YYYY-MM" for months (UTC)
+    pub period_start: String,
+    pub rx_bytes: i64,
+    pub tx_bytes: i64,
+}
+
+/// Quota consumption for the current month
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NetworkQuotaStatus {
+    pub monthly_limit_bytes: i64,
+    pub used_bytes: i64,
+    pub used_percent: f64,
+    pub exceeded: bool,
+}
+
+/// Usage of a single app
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AppNetworkUsage {
+    pub app_name: String,
+    /// Received bytes over the requested range
+    pub rx_bytes: i64,
+    /// Transmitted bytes over the requested range
+    pub tx_bytes: i64,
+    pub total_bytes: i64,
+    /// Ingress + egress so far this month (what quotas are checked against)
+    pub current_month_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<NetworkQuotaStatus>,
+    /// Oldest first
+    pub history: Vec<NetworkUsagePoint>,
+}
+
+/// An app approaching or over its quota
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NetworkUsageWarning {
+    pub app_name: String,
+    /// "warning" (over QUOTA_WARNING_PERCENT) or "critical" (quota exceeded)
+    pub severity: String,
+    pub message: String,
+}
+
+/// Response for GET /api/networking/usage
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct NetworkUsageReport {
+    /// "day" or "month"
+    pub period: String,
+    /// First period included in the report
+    pub since: String,
+    pub apps: Vec<AppNetworkUsage>,
+    pub warnings: Vec<NetworkUsageWarning>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Period helpers
+// ============================================================================
+
+/// Period key for the day containing `at`
+pub fn day_key(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d").to_string()
+}
+
+/// Period key for the month containing `at`
+pub fn month_key(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m").to_string()
+}
+
+/// Key of the oldest period when reporting the last `count` periods
+pub(crate) fn since_key(period: &str, count: u32, now: DateTime<Utc>) -> String {
+    let back = count.saturating_sub(1);
+    if period == PERIOD_MONTH {
+        let months = now.year() * 12 + now.month0() as i32 - back as i32;
+        format!(
+            "{:04}-{:02}",
+            months.div_euclid(12),
+            months.rem_euclid(12) + 1
+        )
+    } else {
+        let day = now.date_naive() - chrono::Duration::days(back as i64);
+        day.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// Compute quota consumption for a month's usage
+pub fn quota_status(used_bytes: i64, monthly_limit_bytes: i64) -> NetworkQuotaStatus {
+    let used_percent = if monthly_limit_bytes > 0 {
+        (used_bytes as f64 / monthly_limit_bytes as f64 * 10000.0).round() / 100.0
+    } else {
+        0.0
+    };
+    NetworkQuotaStatus {
+        monthly_limit_bytes,
+        used_bytes,
+        used_percent,
+        exceeded: used_bytes >= monthly_limit_bytes,
+    }
+}
+
+fn quota_warning(app_name: &str, status: &NetworkQuotaStatus) -> Option<NetworkUsageWarning> {
+    let severity = if status.exceeded {
+        "critical"
+    } else if status.used_percent >= QUOTA_WARNING_PERCENT {
+        "warning"
+    } else {
+        return None;
+    };
+    let message = if status.exceeded {
+        format!(
+            "{} exceeded its monthly transfer quota ({:.1}% used)",
+            app_name, status.used_percent
+        )
+    } else {
+        format!(
+            "{} has used {:.1}% of its monthly transfer quota",
+            app_name, status.used_percent
+        )
+    };
+    Some(NetworkUsageWarning {
+        app_name: app_name.to_string(),
+        severity: severity.to_string(),
+        message,
+    })
+}
+
+// ============================================================================
+// Sampling and rollups
+// ============================================================================
+
+/// Read per-namespace byte increases over the last sampling window
+pub async fn fetch_samples() -> HashMap<String, TrafficSample> {
+    let window = format!("{}s", SAMPLE_INTERVAL_SECS);
+    let rx_query = format!(
+        r#"sum by (namespace) (increase(container_network_receive_bytes_total{{interface!="lo"}}[{}]))"#,
+        window
+    );
+    let tx_query = format!(
+        r#"sum by (namespace) (increase(container_network_transmit_bytes_total{{interface!="lo"}}[{}]))"#,
+        window
+    );
+    let (rx, tx) = tokio::join!(query_vm(&rx_query), query_vm(&tx_query));
+
+    let mut samples: HashMap<String, TrafficSample> = HashMap::new();
+    for (results, is_rx) in [(rx, true), (tx, false)] {
+        for r in results {
+            let Some(namespace) = r["metric"]["namespace"].as_str() else {
+                continue;
+            };
+            if is_excluded_namespace(namespace) {
+                continue;
+            }
+            let bytes = r["value"][1]
+                .as_str()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0)
+                .max(0.0) as i64;
+            let sample = samples.entry(namespace.to_string()).or_default();
+            if is_rx {
+                sample.rx_bytes = bytes;
+            } else {
+                sample.tx_bytes = bytes;
+            }
+        }
+    }
+    samples
+}
+
+/// Add a sample to one rollup row, creating it if needed
+async fn add_to_rollup(
+    db: &DatabaseConnection,
+    app_name: &str,
+    period: &str,
+    period_start: String,
+    sample: TrafficSample,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    let existing = NetworkUsage::find()
+        .filter(network_usage::Column::AppName.eq(app_name))
+        .filter(network_usage::Column::Period.eq(period))
+        .filter(network_usage::Column::PeriodStart.eq(period_start.as_str()))
+        .one(db)
+        .await?;
+
+    match existing {
+        Some(row) => {
+            let rx = row.rx_bytes + sample.rx_bytes;
+            let tx = row.tx_bytes + sample.tx_bytes;
+            let mut active: network_usage::ActiveModel = row.into();
+            active.rx_bytes = Set(rx);
+            active.tx_bytes = Set(tx);
+            active.updated_at = Set(at);
+            active.update(db).await?;
+        }
+        None => {
+            network_usage::ActiveModel {
+                app_name: Set(app_name.to_string()),
+                period: Set(period.to_string()),
+                period_start: Set(period_start),
+                rx_bytes: Set(sample.rx_bytes),
+                tx_bytes: Set(sample.tx_bytes),
+                updated_at: Set(at),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Add samples to the daily and monthly rollups of the period containing `at`
+pub async fn record_usage(
+    db: &DatabaseConnection,
+    samples: &HashMap<String, TrafficSample>,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    for (app_name, sample) in samples {
+        if sample.rx_bytes == 0 && sample.tx_bytes == 0 {
+            continue;
+        }
+        add_to_rollup(db, app_name, PERIOD_DAY, day_key(at), *sample, at).await?;
+        add_to_rollup(db, app_name, PERIOD_MONTH, month_key(at), *sample, at).await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Quotas
+// ============================================================================
+
+/// List all configured quotas
+pub async fn list_quotas(db: &DatabaseConnection) -> Result<Vec<network_quota::Model>> {
+    Ok(NetworkQuota::find()
+        .order_by_asc(network_quota::Column::AppName)
+        .all(db)
+        .await?)
+}
+
+/// Create or update an app's monthly quota
+pub async fn set_quota(
+    db: &DatabaseConnection,
+    app_name: &str,
+    monthly_limit_bytes: i64,
+) -> Result<network_quota::Model> {
+    if monthly_limit_bytes <= 0 {
+        return Err(AppError::BadRequest(
+            "monthly_limit_bytes must be greater than zero".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let model = match NetworkQuota::find_by_id(app_name).one(db).await? {
+        Some(existing) => {
+            let mut active: network_quota::ActiveModel = existing.into();
+            active.monthly_limit_bytes = Set(monthly_limit_bytes);
+            active.updated_at = Set(now);
+            active.update(db).await?
+        }
+        None => {
+            network_quota::ActiveModel {
+                app_name: Set(app_name.to_string()),
+                monthly_limit_bytes: Set(monthly_limit_bytes),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    Ok(model)
+}
+
+/// Remove an app's quota
+pub async fn delete_quota(db: &DatabaseConnection, app_name: &str) -> Result<()> {
+    let result = NetworkQuota::delete_by_id(app_name).exec(db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No bandwidth quota configured for '{}'",
+            app_name
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Reporting
+// ============================================================================
+
+/// Build the usage report for the last `count` days or months
+pub async fn usage_report(
+    db: &DatabaseConnection,
+    period: &str,
+    count: u32,
+    now: DateTime<Utc>,
+) -> Result<NetworkUsageReport> {
+    if period != PERIOD_DAY && period != PERIOD_MONTH {
+        return Err(AppError::BadRequest(format!(
+            "Invalid period '{}': expected 'day' or 'month'",
+            period
+        )));
+    }
+
+    let since = since_key(period, count, now);
+    let rows = NetworkUsage::find()
+        .filter(network_usage::Column::Period.eq(period))
+        .filter(network_usage::Column::PeriodStart.gte(since.as_str()))
+        .order_by_asc(network_usage::Column::PeriodStart)
+        .all(db)
+        .await?;
+
+    let current_month = month_key(now);
+    let month_rows = NetworkUsage::find()
+        .filter(network_usage::Column::Period.eq(PERIOD_MONTH))
+        .filter(network_usage::Column::PeriodStart.eq(current_month.as_str()))
+        .all(db)
+        .await?;
+    let month_totals: HashMap<String, i64> = month_rows
+        .into_iter()
+        .map(|r| (r.app_name, r.rx_bytes + r.tx_bytes))
+        .collect();
+
+    let quotas: HashMap<String, i64> = list_quotas(db)
+        .await?
+        .into_iter()
+        .map(|q| (q.app_name, q.monthly_limit_bytes))
+        .collect();
+
+    let mut history: BTreeMap<String, Vec<NetworkUsagePoint>> = BTreeMap::new();
+    for row in rows {
+        history
+            .entry(row.app_name)
+            .or_default()
+            .push(NetworkUsagePoint {
+                period_start: row.period_start,
+                rx_bytes: row.rx_bytes,
+                tx_bytes: row.tx_bytes,
+            });
+    }
+    // Apps with a quota but no recorded traffic are still reported
+    for app_name in quotas.keys() {
+        history.entry(app_name.clone()).or_default();
+    }
+
+    let mut apps = Vec::new();
+    let mut warnings = Vec::new();
+    for (app_name, points) in history {
+        let rx_bytes = points.iter().map(|p| p.rx_bytes).sum();
+        let tx_bytes = points.iter().map(|p| p.tx_bytes).sum();
+        let current_month_bytes = month_totals.get(&app_name).copied().unwrap_or(0);
+        let quota = quotas
+            .get(&app_name)
+            .map(|limit| quota_status(current_month_bytes, *limit));
+        if let Some(warning) = quota.as_ref().and_then(|q| quota_warning(&app_name, q)) {
+            warnings.push(warning);
+        }
+
+        apps.push(AppNetworkUsage {
+            app_name,
+            rx_bytes,
+            tx_bytes,
+            total_bytes: rx_bytes + tx_bytes,
+            current_month_bytes,
+            quota,
+            history: points,
+        });
+    }
+    apps.sort_by_key(|a| std::cmp::Reverse(a.total_bytes));
+
+    Ok(NetworkUsageReport {
+        period: period.to_string(),
+        since,
+        apps,
+        warnings,
+        generated_at: now,
+    })
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Samples per-app traffic and updates the daily/monthly rollups
+pub struct NetworkUsageTask;
+
+#[async_trait]
+impl PeriodicTask for NetworkUsageTask {
+    fn name(&self) -> &'static str {
+        "network_usage"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(SAMPLE_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let samples = fetch_samples().await;
+        if samples.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        record_usage(db, &samples, now).await?;
+
+        let report = usage_report(db, PERIOD_MONTH, 1, now).await?;
+        for warning in report.warnings {
+            tracing::warn!(app = %warning.app_name, "{}", warning.message);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_period_keys() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 5, 23, 59, 0).unwrap();
+        assert_eq!(day_key(at), "2026-03-05");
+        assert_eq!(month_key(at), "2026-03");
+    }
+
+    #[test]
+    fn test_since_key() {
+        let now = Utc.with_ymd_and_hms(2026, 2, 10, 12, 0, 0).unwrap();
+        assert_eq!(since_key(PERIOD_DAY, 1, now), "2026-02-10");
+        assert_eq!(since_key(PERIOD_DAY, 15, now), "2026-01-27");
+        assert_eq!(since_key(PERIOD_MONTH, 1, now), "2026-02");
+        assert_eq!(since_key(PERIOD_MONTH, 3, now), "2025-12");
+        assert_eq!(since_key(PERIOD_MONTH, 14, now), "2025-01");
+    }
+
+    #[test]
+    fn test_quota_status_and_warnings() {
+        let status = quota_status(50, 100);
+        assert_eq!(status.used_percent, 50.0);
+        assert!(!status.exceeded);
+        assert!(quota_warning("sonarr", &status).is_none());
+
+        let status = quota_status(85, 100);
+        let warning = quota_warning("sonarr", &status).unwrap();
+        assert_eq!(warning.severity, "warning");
+
+        let status = quota_status(120, 100);
+        assert!(status.exceeded);
+        let warning = quota_warning("sonarr", &status).unwrap();
+        assert_eq!(warning.severity, "critical");
+        assert!(warning.message.contains("exceeded"));
+    }
+}
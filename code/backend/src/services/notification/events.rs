@@ -0,0 +1,177 @@
+//! Notification event catalog
+//!
+//! Every [`AuditAction`] can be configured as a notification event. A curated
+//! set of them (the default catalog) gets a `notification_events` row at
+//! startup so new actions show up for admins as soon as they land; rows for
+//! actions that no longer exist are removed. Admins can enable, disable and
+//! re-grade events in bulk, or reset the table to the defaults.
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Iterable, QueryFilter, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use super::format_event_title;
+use super::routing::SEVERITIES;
+use crate::endpoints::notifications::{default_event_severity, get_all_event_types};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::notification_event;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct EventConfigUpdate {
+    pub event_type: String,
+    pub enabled: Option<bool>,
+    pub severity: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct BulkUpdateEventsRequest {
+    /// Events to create or update; omitted fields keep their current value
+    pub events: Vec<EventConfigUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct EventCatalogEntry {
+    pub event_type: String,
+    pub title: String,
+    /// Part of the default catalog
+    pub default: bool,
+    /// Has a row in the event table
+    pub configured: bool,
+    pub enabled: bool,
+    pub severity: String,
+    pub default_severity: String,
+}
+
+/// All action types the code knows about, in declaration order
+pub fn known_event_types() -> Vec<String> {
+    AuditAction::iter().map(|a| a.to_string()).collect()
+}
+
+/// Every known action type with its configuration
+pub async fn list_catalog(db: &DatabaseConnection) -> Result<Vec<EventCatalogEntry>> {
+    let defaults = get_all_event_types();
+    let rows = notification_event::Entity::find().all(db).await?;
+    Ok(AuditAction::iter()
+        .map(|action| {
+            let event_type = action.to_string();
+            let row = rows.iter().find(|r| r.event_type == event_type);
+            let default_severity = default_event_severity(&event_type).to_string();
+            EventCatalogEntry {
+                title: format_event_title(&action),
+                default: defaults.contains(&event_type),
+                configured: row.is_some(),
+                enabled: row.is_some_and(|r| r.enabled),
+                severity: row.map_or_else(|| default_severity.clone(), |r| r.severity.clone()),
+                default_severity,
+                event_type,
+            }
+        })
+        .collect())
+}
+
+/// Create or update event configs
+///
+/// Every update is validated before anything is written.
+pub async fn bulk_update(
+    db: &DatabaseConnection,
+    updates: Vec<EventConfigUpdate>,
+) -> Result<Vec<notification_event::Model>> {
+    let known = known_event_types();
+    for update in &updates {
+        if !known.contains(&update.event_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown event type '{}'",
+                update.event_type
+            )));
+        }
+        if let Some(severity) = &update.severity {
+            if !SEVERITIES.contains(&severity.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid severity '{}', expected one of: {}",
+                    severity,
+                    SEVERITIES.join(", ")
+                )));
+            }
+        }
+    }
+
+    let mut saved = Vec::with_capacity(updates.len());
+    for update in updates {
+        let existing = notification_event::Entity::find()
+            .filter(notification_event::Column::EventType.eq(&update.event_type))
+            .one(db)
+            .await?;
+        let event = match existing {
+            Some(existing) => {
+                let mut active: notification_event::ActiveModel = existing.into();
+                if let Some(enabled) = update.enabled {
+                    active.enabled = Set(enabled);
+                }
+                if let Some(severity) = update.severity {
+                    active.severity = Set(severity);
+                }
+                active.update(db).await?
+            }
+            None => {
+                notification_event::ActiveModel {
+                    enabled: Set(update.enabled.unwrap_or(false)),
+                    severity: Set(update
+                        .severity
+                        .unwrap_or_else(|| default_event_severity(&update.event_type).to_string())),
+                    event_type: Set(update.event_type),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await?
+            }
+        };
+        saved.push(event);
+    }
+    Ok(saved)
+}
+
+/// Bring the event table in line with the code
+///
+/// Adds a disabled row for every default event type that has none and drops
+/// rows for action types that no longer exist. Returns the number of rows
+/// added and removed.
+pub async fn sync_catalog(db: &DatabaseConnection) -> Result<(usize, u64)> {
+    let known = known_event_types();
+    let removed = notification_event::Entity::delete_many()
+        .filter(notification_event::Column::EventType.is_not_in(known))
+        .exec(db)
+        .await?
+        .rows_affected;
+
+    let existing: Vec<String> = notification_event::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|e| e.event_type)
+        .collect();
+    let mut added = 0;
+    for event_type in get_all_event_types()
+        .into_iter()
+        .filter(|t| !existing.contains(t))
+    {
+        notification_event::ActiveModel {
+            severity: Set(default_event_severity(&event_type).to_string()),
+            event_type: Set(event_type),
+            enabled: Set(false),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        added += 1;
+    }
+    Ok((added, removed))
+}
+
+/// Reset the event table to the default catalog, with every event disabled
+pub async fn reset_catalog(db: &DatabaseConnection) -> Result<()> {
+    notification_event::Entity::delete_many().exec(db).await?;
+    sync_catalog(db).await?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Per-channel delivery filters
+//!
+//! Both a channel's global configuration and each user's preference for that
+//! channel can restrict what is sent through it: a minimum severity plus
+//! event types to include (empty = all) or exclude. An event goes out through
+//! a user's channel only when it passes both filters.
+
+use serde::{Deserialize, Serialize};
+
+use super::NotificationSeverity;
+use crate::error::{AppError, Result};
+use crate::models::{notification_channel, user_notification_pref};
+
+/// Which events a channel delivers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ChannelFilter {
+    /// Lowest severity delivered: "info", "warning" or "critical" (None = all)
+    #[serde(default)]
+    pub min_severity: Option<String>,
+    /// Event types delivered (empty = all)
+    #[serde(default)]
+    pub include_event_types: Vec<String>,
+    /// Event types never delivered, applied after `include_event_types`
+    #[serde(default)]
+    pub exclude_event_types: Vec<String>,
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+impl ChannelFilter {
+    pub fn from_channel(channel: &notification_channel::Model) -> Self {
+        Self {
+            min_severity: channel.min_severity.clone(),
+            include_event_types: parse_list(&channel.include_event_types),
+            exclude_event_types: parse_list(&channel.exclude_event_types),
+        }
+    }
+
+    pub fn from_pref(pref: &user_notification_pref::Model) -> Self {
+        Self {
+            min_severity: pref.min_severity.clone(),
+            include_event_types: parse_list(&pref.include_event_types),
+            exclude_event_types: parse_list(&pref.exclude_event_types),
+        }
+    }
+
+    /// Whether an event of this type and severity passes the filter
+    pub fn matches(&self, event_type: &str, severity: NotificationSeverity) -> bool {
+        if let Some(min) = &self.min_severity {
+            if severity < NotificationSeverity::parse(min) {
+                return false;
+            }
+        }
+        if !self.include_event_types.is_empty()
+            && !self.include_event_types.iter().any(|e| e == event_type)
+        {
+            return false;
+        }
+        !self.exclude_event_types.iter().any(|e| e == event_type)
+    }
+
+    /// Check the severity and event types, normalizing an empty severity to
+    /// "no minimum"
+    pub fn validate(mut self, known_event_types: &[String]) -> Result<Self> {
+        self.min_severity = self.min_severity.filter(|s| !s.is_empty());
+        if let Some(min) = &self.min_severity {
+            if !super::routing::SEVERITIES.contains(&min.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid severity '{}', expected one of: {}",
+                    min,
+                    super::routing::SEVERITIES.join(", ")
+                )));
+            }
+        }
+        for event_type in self
+            .include_event_types
+            .iter()
+            .chain(&self.exclude_event_types)
+        {
+            if !known_event_types.contains(event_type) {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown event type '{}'",
+                    event_type
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    /// JSON column values for the include and exclude lists
+    pub fn event_type_columns(&self) -> (String, String) {
+        (
+            serde_json::to_string(&self.include_event_types).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&self.exclude_event_types).unwrap_or_else(|_| "[]".to_string()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known() -> Vec<String> {
+        vec!["login".to_string(), "wan_degraded".to_string()]
+    }
+
+    #[test]
+    fn test_default_matches_everything() {
+        let filter = ChannelFilter::default();
+        assert!(filter.matches("login", NotificationSeverity::Info));
+        assert!(filter.matches("wan_degraded", NotificationSeverity::Critical));
+    }
+
+    #[test]
+    fn test_min_severity() {
+        let filter = ChannelFilter {
+            min_severity: Some("warning".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches("login", NotificationSeverity::Info));
+        assert!(filter.matches("login", NotificationSeverity::Warning));
+        assert!(filter.matches("login", NotificationSeverity::Critical));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let filter = ChannelFilter {
+            include_event_types: vec!["login".to_string(), "wan_degraded".to_string()],
+            exclude_event_types: vec!["login".to_string()],
+            ..Default::default()
+        };
+        assert!(!filter.matches("login", NotificationSeverity::Info));
+        assert!(filter.matches("wan_degraded", NotificationSeverity::Info));
+        assert!(!filter.matches("user_created", NotificationSeverity::Info));
+    }
+
+    #[test]
+    fn test_validate() {
+        let filter = ChannelFilter {
+            min_severity: Some(String::new()),
+            include_event_types: vec!["login".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filter.validate(&known()).unwrap().min_severity, None);
+
+        let bad_severity = ChannelFilter {
+            min_severity: Some("urgent".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_severity.validate(&known()).is_err());
+
+        let bad_event = ChannelFilter {
+            exclude_event_types: vec!["nope".to_string()],
+            ..Default::default()
+        };
+        assert!(bad_event.validate(&known()).is_err());
+    }
+}
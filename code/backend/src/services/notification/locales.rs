@@ -0,0 +1,880 @@
+//! Translated event text
+//!
+//! English is the built-in text of [`format_event_title`] and
+//! [`format_event_body`]. Other locales have a title and two body patterns
+//! per event, one used without details and one with; `{user}` and
+//! `{detail}` are filled in when the message is rendered.
+
+use super::{format_event_body, format_event_title};
+use crate::models::audit_log::AuditAction;
+use crate::services::i18n::{localize_numbers, Locale};
+
+/// Title, body without details, body with details
+type EventText = (&'static str, &'static str, &'static str);
+
+/// Title of an event in a locale
+pub fn event_title(action: &AuditAction, locale: Locale) -> String {
+    match text(action, locale) {
+        Some((title, _, _)) => title.to_string(),
+        None => format_event_title(action),
+    }
+}
+
+/// Body of an event in a locale
+pub fn event_body(
+    action: &AuditAction,
+    username: Option<&str>,
+    details: Option<&str>,
+    locale: Locale,
+) -> String {
+    let details = details.map(|d| localize_details(action, d, locale));
+    let Some((_, body, with_detail)) = text(action, locale) else {
+        return format_event_body(action, username, details.as_deref());
+    };
+    let user = username.unwrap_or(match locale {
+        Locale::De => "Unbekannt",
+        _ => "Onbekend",
+    });
+    match details.as_deref().filter(|d| !d.is_empty()) {
+        Some(detail) => with_detail
+            .replace("{user}", user)
+            .replace("{detail}", detail),
+        None => body.replace("{user}", user),
+    }
+}
+
+/// Details of an event in a locale
+///
+/// Only the numbers of measurements are rewritten; other details are names,
+/// versions or links and are kept as they are.
+pub fn localize_details(action: &AuditAction, details: &str, locale: Locale) -> String {
+    match is_measurement(action) {
+        true => localize_numbers(details, locale),
+        false => details.to_string(),
+    }
+}
+
+/// Events whose details are measurements
+fn is_measurement(action: &AuditAction) -> bool {
+    matches!(
+        action,
+        AuditAction::WanDegraded
+            | AuditAction::WanRecovered
+            | AuditAction::MetricAnomaly
+            | AuditAction::NodeTemperatureCritical
+            | AuditAction::StorageQuotaWarning
+            | AuditAction::StorageQuotaExceeded
+    )
+}
+
+fn text(action: &AuditAction, locale: Locale) -> Option<EventText> {
+    match locale {
+        Locale::En => None,
+        Locale::Nl => Some(nl(action)),
+        Locale::De => Some(de(action)),
+    }
+}
+
+fn nl(action: &AuditAction) -> EventText {
+    match action {
+        // Authentication
+        AuditAction::Login => (
+            "Aangemeld",
+            "Gebruiker {user} is aangemeld",
+            "Gebruiker {user} is aangemeld: {detail}",
+        ),
+        AuditAction::LoginFailed => (
+            "Aanmelden mislukt",
+            "Mislukte aanmeldpoging voor gebruiker {user}",
+            "Mislukte aanmeldpoging voor gebruiker {user}: {detail}",
+        ),
+        AuditAction::Logout => (
+            "Afgemeld",
+            "Gebruiker {user} is afgemeld",
+            "Gebruiker {user} is afgemeld: {detail}",
+        ),
+        AuditAction::TokenRefresh => (
+            "Sessie vernieuwd",
+            "Sessietoken vernieuwd voor gebruiker {user}",
+            "Sessietoken vernieuwd voor gebruiker {user}: {detail}",
+        ),
+        AuditAction::TwoFactorEnabled => (
+            "Tweestapsverificatie ingeschakeld",
+            "Gebruiker {user} heeft tweestapsverificatie ingeschakeld",
+            "Gebruiker {user} heeft tweestapsverificatie ingeschakeld: {detail}",
+        ),
+        AuditAction::TwoFactorDisabled => (
+            "Tweestapsverificatie uitgeschakeld",
+            "Gebruiker {user} heeft tweestapsverificatie uitgeschakeld",
+            "Gebruiker {user} heeft tweestapsverificatie uitgeschakeld: {detail}",
+        ),
+        AuditAction::TwoFactorVerified => (
+            "Tweestapsverificatie geslaagd",
+            "Gebruiker {user} heeft een 2FA-code bevestigd",
+            "Gebruiker {user} heeft een 2FA-code bevestigd: {detail}",
+        ),
+        AuditAction::TwoFactorFailed => (
+            "Tweestapsverificatie mislukt",
+            "2FA-verificatie van gebruiker {user} is mislukt",
+            "2FA-verificatie van gebruiker {user} is mislukt: {detail}",
+        ),
+        AuditAction::PasswordChanged => (
+            "Wachtwoord gewijzigd",
+            "Gebruiker {user} heeft het wachtwoord gewijzigd",
+            "Het wachtwoord van {user} is gewijzigd: {detail}",
+        ),
+        AuditAction::NewDeviceLogin => (
+            "Nieuwe aanmelding",
+            "{user} heeft zich aangemeld vanaf een nieuw apparaat",
+            "{user} heeft zich aangemeld vanaf een nieuw apparaat: {detail}",
+        ),
+        AuditAction::SessionRevoked => (
+            "Sessie afgemeld",
+            "Een sessie van {user} is afgemeld",
+            "Een sessie van {user} is afgemeld: {detail}",
+        ),
+        AuditAction::IpBanned => (
+            "IP-adres geblokkeerd",
+            "Een IP-adres is geblokkeerd door {user}",
+            "IP-adres geblokkeerd: {detail}",
+        ),
+        AuditAction::IpUnbanned => (
+            "IP-adres gedeblokkeerd",
+            "Een IP-adres is gedeblokkeerd door {user}",
+            "IP-adres gedeblokkeerd door {user}: {detail}",
+        ),
+        AuditAction::AccessDenied => (
+            "Herhaaldelijk toegang geweigerd",
+            "{user} is herhaaldelijk toegang geweigerd",
+            "{user} is herhaaldelijk toegang geweigerd: {detail}",
+        ),
+        AuditAction::AccountLocked => (
+            "Account vergrendeld",
+            "Een account is vergrendeld na herhaalde mislukte aanmeldingen",
+            "Account vergrendeld: {detail}",
+        ),
+        AuditAction::RefreshTokenReused => (
+            "Sessie ingetrokken",
+            "Een sessie is ingetrokken omdat een vernieuwingstoken opnieuw is gebruikt",
+            "Sessie ingetrokken na hergebruik van een vernieuwingstoken: {detail}",
+        ),
+        // User management
+        AuditAction::UserCreated => (
+            "Gebruiker aangemaakt",
+            "Nieuw gebruikersaccount aangemaakt door {user}",
+            "Nieuw gebruikersaccount aangemaakt door {user}: {detail}",
+        ),
+        AuditAction::UserUpdated => (
+            "Gebruiker bijgewerkt",
+            "Gebruikersaccount bijgewerkt door {user}",
+            "Gebruikersaccount bijgewerkt door {user}: {detail}",
+        ),
+        AuditAction::UserDeleted => (
+            "Gebruiker verwijderd",
+            "Gebruikersaccount verwijderd door {user}",
+            "Gebruikersaccount verwijderd door {user}: {detail}",
+        ),
+        AuditAction::UserApproved => (
+            "Gebruiker goedgekeurd",
+            "Gebruikersaccount goedgekeurd door {user}",
+            "Gebruiker {detail} goedgekeurd door {user}",
+        ),
+        AuditAction::UserDeactivated => (
+            "Gebruiker gedeactiveerd",
+            "Gebruikersaccount gedeactiveerd door {user}",
+            "Gebruiker {detail} gedeactiveerd door {user}",
+        ),
+        AuditAction::UserActivated => (
+            "Gebruiker geactiveerd",
+            "Gebruikersaccount geactiveerd door {user}",
+            "Gebruiker {detail} geactiveerd door {user}",
+        ),
+        // Role management
+        AuditAction::RoleCreated => (
+            "Rol aangemaakt",
+            "Nieuwe rol aangemaakt door {user}",
+            "Nieuwe rol aangemaakt door {user}: {detail}",
+        ),
+        AuditAction::RoleUpdated => (
+            "Rol bijgewerkt",
+            "Rol bijgewerkt door {user}",
+            "Rol bijgewerkt door {user}: {detail}",
+        ),
+        AuditAction::RoleDeleted => (
+            "Rol verwijderd",
+            "Rol verwijderd door {user}",
+            "Rol verwijderd door {user}: {detail}",
+        ),
+        AuditAction::RoleAssigned => (
+            "Rol toegewezen",
+            "Rol toegewezen door {user}",
+            "Rol toegewezen door {user}: {detail}",
+        ),
+        AuditAction::RoleUnassigned => (
+            "Rol ingetrokken",
+            "Rol ingetrokken door {user}",
+            "Rol ingetrokken door {user}: {detail}",
+        ),
+        AuditAction::RoleExpiring => (
+            "Rol verloopt binnenkort",
+            "Een rol van {user} verloopt binnenkort",
+            "Een rol van {user} verloopt binnenkort: {detail}",
+        ),
+        AuditAction::AccessReviewStarted => (
+            "Toegangsbeoordeling gestart",
+            "Toegangsbeoordeling gestart door {user}",
+            "Toegangsbeoordeling gestart door {user}: {detail}",
+        ),
+        AuditAction::AccessConfirmed => (
+            "Toegang bevestigd",
+            "Toegang bevestigd door {user}",
+            "Toegang bevestigd door {user}: {detail}",
+        ),
+        AuditAction::AccessRevoked => (
+            "Toegang ingetrokken",
+            "Toegang ingetrokken door {user}",
+            "Toegang ingetrokken door {user}: {detail}",
+        ),
+        AuditAction::ScheduleOverrideGranted => (
+            "Uitzondering op tijdschema toegestaan",
+            "Uitzondering op tijdschema toegestaan door {user}",
+            "Uitzondering op tijdschema toegestaan door {user}: {detail}",
+        ),
+        AuditAction::ScheduleOverrideRevoked => (
+            "Uitzondering op tijdschema ingetrokken",
+            "Uitzondering op tijdschema ingetrokken door {user}",
+            "Uitzondering op tijdschema ingetrokken door {user}: {detail}",
+        ),
+        // App management
+        AuditAction::AppInstalled => (
+            "App geïnstalleerd",
+            "App geïnstalleerd door {user}",
+            "App geïnstalleerd door {user}: {detail}",
+        ),
+        AuditAction::AppUninstalled => (
+            "App verwijderd",
+            "App verwijderd door {user}",
+            "App verwijderd door {user}: {detail}",
+        ),
+        AuditAction::AppStarted => (
+            "App gestart",
+            "App gestart door {user}",
+            "App gestart door {user}: {detail}",
+        ),
+        AuditAction::AppStopped => (
+            "App gestopt",
+            "App gestopt door {user}",
+            "App gestopt door {user}: {detail}",
+        ),
+        AuditAction::AppRestarted => (
+            "App herstart",
+            "App herstart door {user}",
+            "App herstart door {user}: {detail}",
+        ),
+        AuditAction::AppConfigured => (
+            "App geconfigureerd",
+            "App-configuratie gewijzigd door {user}",
+            "App-configuratie gewijzigd door {user}: {detail}",
+        ),
+        AuditAction::AppAccessed => (
+            "App geopend",
+            "App geopend door {user}",
+            "Gebruiker {user} heeft {detail} geopend",
+        ),
+        AuditAction::AppsSuspended => (
+            "Inactieve apps gepauzeerd",
+            "Inactieve apps zijn naar nul geschaald",
+            "Inactieve apps gepauzeerd: {detail}",
+        ),
+        AuditAction::AppInstallRequested => (
+            "App-installatie aangevraagd",
+            "App-installatie aangevraagd door {user}",
+            "{user} heeft {detail} aangevraagd",
+        ),
+        AuditAction::AppInstallDenied => (
+            "App-installatie geweigerd",
+            "Installatieaanvraag geweigerd door {user}",
+            "Installatieaanvraag voor {detail} geweigerd door {user}",
+        ),
+        AuditAction::AppUpgraded => (
+            "App bijgewerkt",
+            "App bijgewerkt door {user}",
+            "App bijgewerkt door {user}: {detail}",
+        ),
+        AuditAction::AppShellOpened => (
+            "Shell geopend",
+            "Shell in een app-pod geopend door {user}",
+            "Shell in {detail} geopend door {user}",
+        ),
+        AuditAction::AppShellClosed => (
+            "Shell gesloten",
+            "Shell in een app-pod gesloten door {user}",
+            "Shell in {detail} gesloten door {user}",
+        ),
+        // Media requests
+        AuditAction::MediaRequested => (
+            "Media aangevraagd",
+            "Media aangevraagd door {user}",
+            "{user} heeft {detail} aangevraagd",
+        ),
+        AuditAction::MediaRequestApproved => (
+            "Media-aanvraag goedgekeurd",
+            "Media-aanvraag goedgekeurd door {user}",
+            "Aanvraag voor {detail} goedgekeurd door {user}",
+        ),
+        AuditAction::MediaRequestDeclined => (
+            "Media-aanvraag afgewezen",
+            "Media-aanvraag afgewezen door {user}",
+            "Aanvraag voor {detail} afgewezen door {user}",
+        ),
+        // Network
+        AuditAction::WanDegraded => (
+            "WAN-verbinding verslechterd",
+            "De WAN-verbinding is verslechterd",
+            "WAN-verbinding verslechterd: {detail}",
+        ),
+        AuditAction::WanRecovered => (
+            "WAN-verbinding hersteld",
+            "De WAN-verbinding is hersteld",
+            "WAN-verbinding hersteld: {detail}",
+        ),
+        AuditAction::UptimeMonitorDown => (
+            "Monitor offline",
+            "Een uptime-monitor is offline",
+            "Monitor offline: {detail}",
+        ),
+        AuditAction::UptimeMonitorUp => (
+            "Monitor online",
+            "Een uptime-monitor is weer online",
+            "Monitor weer online: {detail}",
+        ),
+        AuditAction::MetricAnomaly => (
+            "Afwijkende meetwaarde",
+            "Een meetwaarde van een app wijkt af van het normale niveau",
+            "Afwijking gedetecteerd: {detail}",
+        ),
+        AuditAction::NodeTemperatureCritical => (
+            "Kritieke temperatuur op node",
+            "Een sensor van een node heeft de kritieke temperatuur overschreden",
+            "Kritieke temperatuur: {detail}",
+        ),
+        AuditAction::LogAlertFiring => (
+            "Logwaarschuwing actief",
+            "Een logwaarschuwingsregel is actief",
+            "Logwaarschuwing actief: {detail}",
+        ),
+        AuditAction::LogAlertResolved => (
+            "Logwaarschuwing opgelost",
+            "Een logwaarschuwingsregel is opgelost",
+            "Logwaarschuwing opgelost: {detail}",
+        ),
+        AuditAction::AlertAssigned => (
+            "Waarschuwing toegewezen",
+            "Er is een waarschuwing aan je toegewezen",
+            "Waarschuwing aan je toegewezen: {detail}",
+        ),
+        // Storage
+        AuditAction::StorageQuotaWarning => (
+            "Opslagquotum bijna bereikt",
+            "Je thuismap nadert het quotum",
+            "Thuismap nadert het quotum: {detail}",
+        ),
+        AuditAction::StorageQuotaExceeded => (
+            "Opslagquotum bereikt",
+            "Je thuismap is vol; uploads worden geweigerd",
+            "Thuismap vol, uploads worden geweigerd: {detail}",
+        ),
+        // System
+        AuditAction::SystemSettingChanged => (
+            "Systeeminstelling gewijzigd",
+            "Systeeminstelling gewijzigd door {user}",
+            "Systeeminstelling gewijzigd door {user}: {detail}",
+        ),
+        AuditAction::InviteCreated => (
+            "Uitnodigingslink aangemaakt",
+            "Uitnodigingslink aangemaakt door {user}",
+            "Uitnodigingslink aangemaakt door {user}: {detail}",
+        ),
+        AuditAction::InviteUsed => (
+            "Uitnodigingslink gebruikt",
+            "Uitnodigingslink gebruikt door {user}",
+            "Uitnodigingslink gebruikt door {user}: {detail}",
+        ),
+        AuditAction::InviteDeleted => (
+            "Uitnodigingslink verwijderd",
+            "Uitnodigingslink verwijderd door {user}",
+            "Uitnodigingslink verwijderd door {user}: {detail}",
+        ),
+        AuditAction::UpdateAvailable => (
+            "Kubarr-update beschikbaar",
+            "Er is een nieuwe versie van Kubarr beschikbaar",
+            "Kubarr {detail} is beschikbaar",
+        ),
+        AuditAction::UpdateStarted => (
+            "Kubarr-update gestart",
+            "Kubarr-update gestart door {user}",
+            "Kubarr-update gestart door {user}: {detail}",
+        ),
+        AuditAction::UpdateCompleted => (
+            "Kubarr bijgewerkt",
+            "Kubarr is bijgewerkt",
+            "Kubarr is bijgewerkt: {detail}",
+        ),
+        AuditAction::UpdateFailed => (
+            "Kubarr-update mislukt",
+            "Een Kubarr-update is mislukt",
+            "Kubarr-update mislukt: {detail}",
+        ),
+        AuditAction::BackupCreated => (
+            "Back-up gemaakt",
+            "Er is een back-up van Kubarr gemaakt",
+            "Back-up gemaakt: {detail}",
+        ),
+        AuditAction::BackupRestored => (
+            "Back-up teruggezet",
+            "Kubarr is teruggezet vanuit een back-up door {user}",
+            "Kubarr is teruggezet vanuit een back-up door {user}: {detail}",
+        ),
+        AuditAction::BackupFailed => (
+            "Back-up mislukt",
+            "Een geplande back-up van Kubarr is mislukt",
+            "Geplande back-up mislukt: {detail}",
+        ),
+        // API
+        AuditAction::ApiAccess => (
+            "API-toegang",
+            "API gebruikt door {user}",
+            "API gebruikt door {user}: {detail}",
+        ),
+    }
+}
+
+fn de(action: &AuditAction) -> EventText {
+    match action {
+        // Authentication
+        AuditAction::Login => (
+            "Angemeldet",
+            "Benutzer {user} hat sich angemeldet",
+            "Benutzer {user} hat sich angemeldet: {detail}",
+        ),
+        AuditAction::LoginFailed => (
+            "Anmeldung fehlgeschlagen",
+            "Fehlgeschlagener Anmeldeversuch für Benutzer {user}",
+            "Fehlgeschlagener Anmeldeversuch für Benutzer {user}: {detail}",
+        ),
+        AuditAction::Logout => (
+            "Abgemeldet",
+            "Benutzer {user} hat sich abgemeldet",
+            "Benutzer {user} hat sich abgemeldet: {detail}",
+        ),
+        AuditAction::TokenRefresh => (
+            "Sitzung erneuert",
+            "Sitzungstoken für Benutzer {user} erneuert",
+            "Sitzungstoken für Benutzer {user} erneuert: {detail}",
+        ),
+        AuditAction::TwoFactorEnabled => (
+            "Zwei-Faktor-Authentifizierung aktiviert",
+            "Benutzer {user} hat die Zwei-Faktor-Authentifizierung aktiviert",
+            "Benutzer {user} hat die Zwei-Faktor-Authentifizierung aktiviert: {detail}",
+        ),
+        AuditAction::TwoFactorDisabled => (
+            "Zwei-Faktor-Authentifizierung deaktiviert",
+            "Benutzer {user} hat die Zwei-Faktor-Authentifizierung deaktiviert",
+            "Benutzer {user} hat die Zwei-Faktor-Authentifizierung deaktiviert: {detail}",
+        ),
+        AuditAction::TwoFactorVerified => (
+            "Zwei-Faktor-Code bestätigt",
+            "Benutzer {user} hat einen 2FA-Code bestätigt",
+            "Benutzer {user} hat einen 2FA-Code bestätigt: {detail}",
+        ),
+        AuditAction::TwoFactorFailed => (
+            "Zwei-Faktor-Prüfung fehlgeschlagen",
+            "2FA-Prüfung von Benutzer {user} fehlgeschlagen",
+            "2FA-Prüfung von Benutzer {user} fehlgeschlagen: {detail}",
+        ),
+        AuditAction::PasswordChanged => (
+            "Passwort geändert",
+            "Benutzer {user} hat das Passwort geändert",
+            "Das Passwort von {user} wurde geändert: {detail}",
+        ),
+        AuditAction::NewDeviceLogin => (
+            "Neue Anmeldung",
+            "{user} hat sich von einem neuen Gerät angemeldet",
+            "{user} hat sich von einem neuen Gerät angemeldet: {detail}",
+        ),
+        AuditAction::SessionRevoked => (
+            "Sitzung abgemeldet",
+            "Eine Sitzung von {user} wurde abgemeldet",
+            "Eine Sitzung von {user} wurde abgemeldet: {detail}",
+        ),
+        AuditAction::IpBanned => (
+            "IP-Adresse gesperrt",
+            "Eine IP-Adresse wurde von {user} gesperrt",
+            "IP-Adresse gesperrt: {detail}",
+        ),
+        AuditAction::IpUnbanned => (
+            "IP-Adresse entsperrt",
+            "Eine IP-Adresse wurde von {user} entsperrt",
+            "IP-Adresse von {user} entsperrt: {detail}",
+        ),
+        AuditAction::AccessDenied => (
+            "Wiederholt Zugriff verweigert",
+            "{user} wurde wiederholt der Zugriff verweigert",
+            "{user} wurde wiederholt der Zugriff verweigert: {detail}",
+        ),
+        AuditAction::AccountLocked => (
+            "Konto gesperrt",
+            "Ein Konto wurde nach wiederholten fehlgeschlagenen Anmeldungen gesperrt",
+            "Konto gesperrt: {detail}",
+        ),
+        AuditAction::RefreshTokenReused => (
+            "Sitzung widerrufen",
+            "Eine Sitzung wurde widerrufen, weil ein Erneuerungstoken erneut verwendet wurde",
+            "Sitzung nach erneuter Verwendung eines Erneuerungstokens widerrufen: {detail}",
+        ),
+        // User management
+        AuditAction::UserCreated => (
+            "Benutzer erstellt",
+            "Neues Benutzerkonto von {user} erstellt",
+            "Neues Benutzerkonto von {user} erstellt: {detail}",
+        ),
+        AuditAction::UserUpdated => (
+            "Benutzer aktualisiert",
+            "Benutzerkonto von {user} aktualisiert",
+            "Benutzerkonto von {user} aktualisiert: {detail}",
+        ),
+        AuditAction::UserDeleted => (
+            "Benutzer gelöscht",
+            "Benutzerkonto von {user} gelöscht",
+            "Benutzerkonto von {user} gelöscht: {detail}",
+        ),
+        AuditAction::UserApproved => (
+            "Benutzer freigegeben",
+            "Benutzerkonto von {user} freigegeben",
+            "Benutzer {detail} von {user} freigegeben",
+        ),
+        AuditAction::UserDeactivated => (
+            "Benutzer deaktiviert",
+            "Benutzerkonto von {user} deaktiviert",
+            "Benutzer {detail} von {user} deaktiviert",
+        ),
+        AuditAction::UserActivated => (
+            "Benutzer aktiviert",
+            "Benutzerkonto von {user} aktiviert",
+            "Benutzer {detail} von {user} aktiviert",
+        ),
+        // Role management
+        AuditAction::RoleCreated => (
+            "Rolle erstellt",
+            "Neue Rolle von {user} erstellt",
+            "Neue Rolle von {user} erstellt: {detail}",
+        ),
+        AuditAction::RoleUpdated => (
+            "Rolle aktualisiert",
+            "Rolle von {user} aktualisiert",
+            "Rolle von {user} aktualisiert: {detail}",
+        ),
+        AuditAction::RoleDeleted => (
+            "Rolle gelöscht",
+            "Rolle von {user} gelöscht",
+            "Rolle von {user} gelöscht: {detail}",
+        ),
+        AuditAction::RoleAssigned => (
+            "Rolle zugewiesen",
+            "Rolle von {user} zugewiesen",
+            "Rolle von {user} zugewiesen: {detail}",
+        ),
+        AuditAction::RoleUnassigned => (
+            "Rolle entzogen",
+            "Rolle von {user} entzogen",
+            "Rolle von {user} entzogen: {detail}",
+        ),
+        AuditAction::RoleExpiring => (
+            "Rolle läuft bald ab",
+            "Eine Rolle von {user} läuft bald ab",
+            "Eine Rolle von {user} läuft bald ab: {detail}",
+        ),
+        AuditAction::AccessReviewStarted => (
+            "Zugriffsprüfung gestartet",
+            "Zugriffsprüfung von {user} gestartet",
+            "Zugriffsprüfung von {user} gestartet: {detail}",
+        ),
+        AuditAction::AccessConfirmed => (
+            "Zugriff bestätigt",
+            "Zugriff von {user} bestätigt",
+            "Zugriff von {user} bestätigt: {detail}",
+        ),
+        AuditAction::AccessRevoked => (
+            "Zugriff entzogen",
+            "Zugriff von {user} entzogen",
+            "Zugriff von {user} entzogen: {detail}",
+        ),
+        AuditAction::ScheduleOverrideGranted => (
+            "Ausnahme vom Zeitplan gewährt",
+            "Ausnahme vom Zeitplan von {user} gewährt",
+            "Ausnahme vom Zeitplan von {user} gewährt: {detail}",
+        ),
+        AuditAction::ScheduleOverrideRevoked => (
+            "Ausnahme vom Zeitplan widerrufen",
+            "Ausnahme vom Zeitplan von {user} widerrufen",
+            "Ausnahme vom Zeitplan von {user} widerrufen: {detail}",
+        ),
+        // App management
+        AuditAction::AppInstalled => (
+            "App installiert",
+            "App von {user} installiert",
+            "App von {user} installiert: {detail}",
+        ),
+        AuditAction::AppUninstalled => (
+            "App deinstalliert",
+            "App von {user} deinstalliert",
+            "App von {user} deinstalliert: {detail}",
+        ),
+        AuditAction::AppStarted => (
+            "App gestartet",
+            "App von {user} gestartet",
+            "App von {user} gestartet: {detail}",
+        ),
+        AuditAction::AppStopped => (
+            "App gestoppt",
+            "App von {user} gestoppt",
+            "App von {user} gestoppt: {detail}",
+        ),
+        AuditAction::AppRestarted => (
+            "App neu gestartet",
+            "App von {user} neu gestartet",
+            "App von {user} neu gestartet: {detail}",
+        ),
+        AuditAction::AppConfigured => (
+            "App konfiguriert",
+            "App-Konfiguration von {user} geändert",
+            "App-Konfiguration von {user} geändert: {detail}",
+        ),
+        AuditAction::AppAccessed => (
+            "App geöffnet",
+            "App von {user} geöffnet",
+            "Benutzer {user} hat {detail} geöffnet",
+        ),
+        AuditAction::AppsSuspended => (
+            "Inaktive Apps pausiert",
+            "Inaktive Apps wurden auf null skaliert",
+            "Inaktive Apps pausiert: {detail}",
+        ),
+        AuditAction::AppInstallRequested => (
+            "App-Installation angefragt",
+            "App-Installation von {user} angefragt",
+            "{user} hat {detail} angefragt",
+        ),
+        AuditAction::AppInstallDenied => (
+            "App-Installation abgelehnt",
+            "Installationsanfrage von {user} abgelehnt",
+            "Installationsanfrage für {detail} von {user} abgelehnt",
+        ),
+        AuditAction::AppUpgraded => (
+            "App aktualisiert",
+            "App von {user} aktualisiert",
+            "App von {user} aktualisiert: {detail}",
+        ),
+        AuditAction::AppShellOpened => (
+            "Shell geöffnet",
+            "Shell in einem App-Pod von {user} geöffnet",
+            "Shell in {detail} von {user} geöffnet",
+        ),
+        AuditAction::AppShellClosed => (
+            "Shell geschlossen",
+            "Shell in einem App-Pod von {user} geschlossen",
+            "Shell in {detail} von {user} geschlossen",
+        ),
+        // Media requests
+        AuditAction::MediaRequested => (
+            "Medien angefragt",
+            "Medien von {user} angefragt",
+            "{user} hat {detail} angefragt",
+        ),
+        AuditAction::MediaRequestApproved => (
+            "Medienanfrage genehmigt",
+            "Medienanfrage von {user} genehmigt",
+            "Anfrage für {detail} von {user} genehmigt",
+        ),
+        AuditAction::MediaRequestDeclined => (
+            "Medienanfrage abgelehnt",
+            "Medienanfrage von {user} abgelehnt",
+            "Anfrage für {detail} von {user} abgelehnt",
+        ),
+        // Network
+        AuditAction::WanDegraded => (
+            "WAN-Verbindung beeinträchtigt",
+            "Die WAN-Verbindung ist beeinträchtigt",
+            "WAN-Verbindung beeinträchtigt: {detail}",
+        ),
+        AuditAction::WanRecovered => (
+            "WAN-Verbindung wiederhergestellt",
+            "Die WAN-Verbindung ist wiederhergestellt",
+            "WAN-Verbindung wiederhergestellt: {detail}",
+        ),
+        AuditAction::UptimeMonitorDown => (
+            "Monitor ausgefallen",
+            "Ein Uptime-Monitor ist ausgefallen",
+            "Monitor ausgefallen: {detail}",
+        ),
+        AuditAction::UptimeMonitorUp => (
+            "Monitor wieder erreichbar",
+            "Ein Uptime-Monitor ist wieder erreichbar",
+            "Monitor wieder erreichbar: {detail}",
+        ),
+        AuditAction::MetricAnomaly => (
+            "Auffälliger Messwert",
+            "Ein Messwert einer App weicht vom Normalwert ab",
+            "Anomalie erkannt: {detail}",
+        ),
+        AuditAction::NodeTemperatureCritical => (
+            "Kritische Node-Temperatur",
+            "Ein Sensor eines Nodes hat die kritische Temperatur überschritten",
+            "Temperatur kritisch: {detail}",
+        ),
+        AuditAction::LogAlertFiring => (
+            "Log-Alarm ausgelöst",
+            "Eine Log-Alarmregel wurde ausgelöst",
+            "Log-Alarm ausgelöst: {detail}",
+        ),
+        AuditAction::LogAlertResolved => (
+            "Log-Alarm behoben",
+            "Eine Log-Alarmregel ist behoben",
+            "Log-Alarm behoben: {detail}",
+        ),
+        AuditAction::AlertAssigned => (
+            "Alarm zugewiesen",
+            "Dir wurde ein Alarm zugewiesen",
+            "Dir zugewiesener Alarm: {detail}",
+        ),
+        // Storage
+        AuditAction::StorageQuotaWarning => (
+            "Speicherkontingent fast erreicht",
+            "Dein Home-Ordner nähert sich dem Kontingent",
+            "Home-Ordner nähert sich dem Kontingent: {detail}",
+        ),
+        AuditAction::StorageQuotaExceeded => (
+            "Speicherkontingent erreicht",
+            "Dein Home-Ordner ist voll; Uploads werden abgelehnt",
+            "Home-Ordner voll, Uploads werden abgelehnt: {detail}",
+        ),
+        // System
+        AuditAction::SystemSettingChanged => (
+            "Systemeinstellung geändert",
+            "Systemeinstellung von {user} geändert",
+            "Systemeinstellung von {user} geändert: {detail}",
+        ),
+        AuditAction::InviteCreated => (
+            "Einladungslink erstellt",
+            "Einladungslink von {user} erstellt",
+            "Einladungslink von {user} erstellt: {detail}",
+        ),
+        AuditAction::InviteUsed => (
+            "Einladungslink verwendet",
+            "Einladungslink von {user} verwendet",
+            "Einladungslink von {user} verwendet: {detail}",
+        ),
+        AuditAction::InviteDeleted => (
+            "Einladungslink gelöscht",
+            "Einladungslink von {user} gelöscht",
+            "Einladungslink von {user} gelöscht: {detail}",
+        ),
+        AuditAction::UpdateAvailable => (
+            "Kubarr-Update verfügbar",
+            "Eine neue Kubarr-Version ist verfügbar",
+            "Kubarr {detail} ist verfügbar",
+        ),
+        AuditAction::UpdateStarted => (
+            "Kubarr-Update gestartet",
+            "Kubarr-Update von {user} gestartet",
+            "Kubarr-Update von {user} gestartet: {detail}",
+        ),
+        AuditAction::UpdateCompleted => (
+            "Kubarr aktualisiert",
+            "Kubarr wurde aktualisiert",
+            "Kubarr wurde aktualisiert: {detail}",
+        ),
+        AuditAction::UpdateFailed => (
+            "Kubarr-Update fehlgeschlagen",
+            "Ein Kubarr-Update ist fehlgeschlagen",
+            "Kubarr-Update fehlgeschlagen: {detail}",
+        ),
+        AuditAction::BackupCreated => (
+            "Sicherung erstellt",
+            "Eine Sicherung von Kubarr wurde erstellt",
+            "Sicherung erstellt: {detail}",
+        ),
+        AuditAction::BackupRestored => (
+            "Sicherung wiederhergestellt",
+            "Kubarr wurde von {user} aus einer Sicherung wiederhergestellt",
+            "Kubarr wurde von {user} aus einer Sicherung wiederhergestellt: {detail}",
+        ),
+        AuditAction::BackupFailed => (
+            "Sicherung fehlgeschlagen",
+            "Eine geplante Sicherung von Kubarr ist fehlgeschlagen",
+            "Geplante Sicherung fehlgeschlagen: {detail}",
+        ),
+        // API
+        AuditAction::ApiAccess => (
+            "API-Zugriff",
+            "API-Zugriff durch {user}",
+            "API-Zugriff durch {user}: {detail}",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::Iterable;
+
+    #[test]
+    fn test_every_event_is_translated() {
+        for action in AuditAction::iter() {
+            for locale in [Locale::Nl, Locale::De] {
+                let (title, body, with_detail) = text(&action, locale).unwrap();
+                assert!(!title.is_empty() && !body.is_empty());
+                assert!(
+                    with_detail.contains("{detail}"),
+                    "{:?} in {} drops the details",
+                    action,
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_body() {
+        assert_eq!(
+            event_body(
+                &AuditAction::AppInstalled,
+                Some("alice"),
+                Some("sonarr"),
+                Locale::Nl
+            ),
+            "App geïnstalleerd door alice: sonarr"
+        );
+        assert_eq!(
+            event_body(&AuditAction::AppInstalled, None, None, Locale::De),
+            "App von Unbekannt installiert"
+        );
+        assert_eq!(
+            event_body(
+                &AuditAction::NodeTemperatureCritical,
+                None,
+                Some("node1 CPU at 91.5°C"),
+                Locale::De
+            ),
+            "Temperatur kritisch: node1 CPU at 91,5°C"
+        );
+        assert_eq!(
+            event_body(&AuditAction::UpdateAvailable, None, Some("1.2"), Locale::Nl),
+            "Kubarr 1.2 is beschikbaar"
+        );
+        assert_eq!(
+            event_body(&AuditAction::Login, Some("bob"), None, Locale::En),
+            format_event_body(&AuditAction::Login, Some("bob"), None)
+        );
+    }
+}
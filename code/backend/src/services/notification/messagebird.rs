@@ -4,6 +4,10 @@ use async_trait::async_trait;
 use serde::Deserialize;
 
 use super::{ChannelType, NotificationMessage, NotificationProvider, SendResult};
+use crate::services::outbound;
+
+/// MessageBird SMS API
+pub(crate) const API_URL: &str = "https://rest.messagebird.com/messages";
 
 #[derive(Debug, Deserialize)]
 pub struct MessageBirdConfig {
@@ -25,12 +29,12 @@ impl MessageBirdProvider {
         Ok(Self {
             api_key: mb_config.api_key,
             originator: mb_config.originator.unwrap_or_else(|| "Kubarr".to_string()),
-            client: reqwest::Client::new(),
+            client: outbound::client(),
         })
     }
 
     async fn send_sms(&self, recipient: &str, body: &str) -> SendResult {
-        let url = "https://rest.messagebird.com/messages";
+        let url = API_URL;
 
         let payload = serde_json::json!({
             "originator": self.originator,
@@ -1,12 +1,19 @@
 #![allow(dead_code)]
 
 mod email;
-mod messagebird;
-mod telegram;
+pub mod events;
+pub mod filter;
+pub mod locales;
+pub(crate) mod messagebird;
+pub mod routing;
+pub mod telegram;
+pub mod templates;
 
 pub use email::EmailProvider;
+pub use filter::ChannelFilter;
 pub use messagebird::MessageBirdProvider;
-pub use telegram::TelegramProvider;
+pub use telegram::{TelegramAction, TelegramProvider};
+pub use templates::EventContent;
 
 use async_trait::async_trait;
 use sea_orm::{
@@ -14,6 +21,7 @@ use sea_orm::{
     QueryOrder, QuerySelect, Set,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -22,6 +30,11 @@ use crate::models::{
     audit_log::AuditAction, notification_channel, notification_event, notification_log,
     user_notification, user_notification_pref,
 };
+use crate::services::crypto;
+use crate::services::i18n;
+use crate::services::runtime_config;
+use crate::services::telemetry::METRICS;
+use crate::services::tenants;
 
 /// Notification channel types
 ///
@@ -71,6 +84,11 @@ impl std::fmt::Display for ChannelType {
     }
 }
 
+type UtcTime = chrono::DateTime<chrono::Utc>;
+
+/// Channels tried, in order, when a critical event fails on a user's channel
+pub const FALLBACK_CHAIN: [ChannelType; 2] = [ChannelType::Email, ChannelType::MessageBird];
+
 /// Notification message to send
 #[derive(Debug, Clone)]
 pub struct NotificationMessage {
@@ -78,10 +96,21 @@ pub struct NotificationMessage {
     pub title: String,
     pub body: String,
     pub severity: NotificationSeverity,
+    /// Follow-up actions providers may offer alongside the message
+    pub actions: Vec<NotificationAction>,
+}
+
+/// Action a recipient can take directly from a notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationAction {
+    /// Acknowledge the alert with this ID
+    AcknowledgeAlert(i64),
+    /// Open the app with this name
+    OpenApp(String),
 }
 
-/// Notification severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Notification severity levels, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NotificationSeverity {
     Info,
@@ -134,6 +163,38 @@ pub struct NotificationService {
     email: Arc<RwLock<Option<EmailProvider>>>,
     telegram: Arc<RwLock<Option<TelegramProvider>>>,
     messagebird: Arc<RwLock<Option<MessageBirdProvider>>>,
+    status: Arc<RwLock<HashMap<ChannelType, ChannelState>>>,
+    /// When each user last sent themselves a test message, per channel
+    user_tests: Arc<RwLock<HashMap<(i64, ChannelType), UtcTime>>>,
+}
+
+/// Reload and delivery history of a channel, kept alongside its provider
+#[derive(Debug, Clone, Default)]
+struct ChannelState {
+    enabled: bool,
+    last_error: Option<String>,
+    last_error_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_reload_at: Option<chrono::DateTime<chrono::Utc>>,
+    fallback_deliveries: u64,
+    last_fallback_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Provider state of a channel, as reported by the channel status endpoint
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ProviderStatus {
+    pub channel_type: String,
+    /// Channel is enabled in its saved configuration
+    pub enabled: bool,
+    /// A provider is loaded and used for delivery
+    pub initialized: bool,
+    /// Most recent configuration or delivery error
+    pub last_error: Option<String>,
+    pub last_error_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_reload_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Deliveries attempted on this channel because another one failed,
+    /// since startup
+    pub fallback_deliveries: u64,
+    pub last_fallback_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl NotificationService {
@@ -143,6 +204,8 @@ impl NotificationService {
             email: Arc::new(RwLock::new(None)),
             telegram: Arc::new(RwLock::new(None)),
             messagebird: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(HashMap::new())),
+            user_tests: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -153,48 +216,180 @@ impl NotificationService {
 
     /// Initialize providers from database configuration
     pub async fn init_providers(&self) -> Result<()> {
-        let db_lock = self.db.read().await;
-        let db = db_lock
-            .as_ref()
-            .ok_or_else(|| AppError::Internal("Database not initialized".to_string()))?;
+        self.reload_providers().await.map(|_| ())
+    }
+
+    /// Rebuild every provider from the saved channel configuration
+    ///
+    /// Providers of disabled or unconfigured channels are dropped, so a
+    /// channel switched off takes effect without a restart. An invalid
+    /// configuration leaves the channel uninitialized and is reported as its
+    /// last error.
+    pub async fn reload_providers(&self) -> Result<Vec<ProviderStatus>> {
+        let channels = {
+            let db_lock = self.db.read().await;
+            let db = db_lock
+                .as_ref()
+                .ok_or_else(|| AppError::Internal("Database not initialized".to_string()))?;
+            notification_channel::Entity::find().all(db).await?
+        };
 
-        // Load channel configurations
-        let channels = notification_channel::Entity::find()
-            .filter(notification_channel::Column::Enabled.eq(true))
-            .all(db)
-            .await?;
+        let now = chrono::Utc::now();
+        for channel_type in ChannelType::all() {
+            let channel = channels
+                .iter()
+                .find(|c| c.channel_type == channel_type.as_str());
+            let enabled = channel.is_some_and(|c| c.enabled);
+
+            let error = match channel.filter(|c| c.enabled) {
+                Some(channel) => match crypto::decrypt_config(&channel.config) {
+                    Ok(config) => self.load_provider(channel_type, &config).await.err(),
+                    Err(e) => Some(e.to_string()),
+                },
+                None => {
+                    self.clear_provider(channel_type).await;
+                    None
+                }
+            };
+
+            match &error {
+                Some(e) => tracing::warn!(
+                    "Failed to initialize {} notification provider: {}",
+                    channel_type,
+                    e
+                ),
+                None if enabled => {
+                    tracing::info!("{} notification provider initialized", channel_type)
+                }
+                None => {}
+            }
+
+            let mut status = self.status.write().await;
+            let entry = status.entry(channel_type).or_default();
+            entry.enabled = enabled;
+            entry.last_reload_at = Some(now);
+            entry.last_error_at = error.as_ref().map(|_| now);
+            entry.last_error = error;
+        }
 
-        for channel in channels {
-            let config: serde_json::Value =
-                serde_json::from_str(&channel.config).unwrap_or(serde_json::json!({}));
+        Ok(self.provider_status().await)
+    }
 
-            match channel.channel_type.as_str() {
-                "email" => {
-                    if let Ok(provider) = EmailProvider::from_config(&config) {
-                        let mut email_lock = self.email.write().await;
-                        *email_lock = Some(provider);
-                        tracing::info!("Email notification provider initialized");
+    /// Build and install the provider for a channel, dropping any previous one
+    async fn load_provider(
+        &self,
+        channel_type: ChannelType,
+        config: &serde_json::Value,
+    ) -> std::result::Result<(), String> {
+        match channel_type {
+            ChannelType::Email => {
+                let provider = EmailProvider::from_config(config);
+                let mut lock = self.email.write().await;
+                match provider {
+                    Ok(provider) => {
+                        *lock = Some(provider);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        *lock = None;
+                        Err(e)
                     }
                 }
-                "telegram" => {
-                    if let Ok(provider) = TelegramProvider::from_config(&config) {
-                        let mut telegram_lock = self.telegram.write().await;
-                        *telegram_lock = Some(provider);
-                        tracing::info!("Telegram notification provider initialized");
+            }
+            ChannelType::Telegram => {
+                let provider = TelegramProvider::from_config(config);
+                let mut lock = self.telegram.write().await;
+                match provider {
+                    Ok(provider) => {
+                        *lock = Some(provider);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        *lock = None;
+                        Err(e)
                     }
                 }
-                "messagebird" => {
-                    if let Ok(provider) = MessageBirdProvider::from_config(&config) {
-                        let mut messagebird_lock = self.messagebird.write().await;
-                        *messagebird_lock = Some(provider);
-                        tracing::info!("MessageBird notification provider initialized");
+            }
+            ChannelType::MessageBird => {
+                let provider = MessageBirdProvider::from_config(config);
+                let mut lock = self.messagebird.write().await;
+                match provider {
+                    Ok(provider) => {
+                        *lock = Some(provider);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        *lock = None;
+                        Err(e)
                     }
                 }
-                _ => {}
             }
         }
+    }
 
-        Ok(())
+    async fn clear_provider(&self, channel_type: ChannelType) {
+        match channel_type {
+            ChannelType::Email => *self.email.write().await = None,
+            ChannelType::Telegram => *self.telegram.write().await = None,
+            ChannelType::MessageBird => *self.messagebird.write().await = None,
+        }
+    }
+
+    async fn provider_initialized(&self, channel_type: ChannelType) -> bool {
+        match channel_type {
+            ChannelType::Email => self.email.read().await.is_some(),
+            ChannelType::Telegram => self.telegram.read().await.is_some(),
+            ChannelType::MessageBird => self.messagebird.read().await.is_some(),
+        }
+    }
+
+    /// Current provider state of every channel type
+    pub async fn provider_status(&self) -> Vec<ProviderStatus> {
+        let mut result = Vec::new();
+        for channel_type in ChannelType::all() {
+            let state = self
+                .status
+                .read()
+                .await
+                .get(&channel_type)
+                .cloned()
+                .unwrap_or_default();
+            result.push(ProviderStatus {
+                channel_type: channel_type.as_str().to_string(),
+                enabled: state.enabled,
+                initialized: self.provider_initialized(channel_type).await,
+                last_error: state.last_error,
+                last_error_at: state.last_error_at,
+                last_reload_at: state.last_reload_at,
+                fallback_deliveries: state.fallback_deliveries,
+                last_fallback_at: state.last_fallback_at,
+            });
+        }
+        result
+    }
+
+    /// Remember the outcome of a delivery for the channel status
+    async fn record_delivery(&self, channel_type: &str, result: &SendResult) {
+        let Some(channel_type) = ChannelType::parse(channel_type) else {
+            return;
+        };
+        if let Some(error) = result.error.as_ref().filter(|_| !result.success) {
+            let mut status = self.status.write().await;
+            let entry = status.entry(channel_type).or_default();
+            entry.last_error = Some(error.clone());
+            entry.last_error_at = Some(chrono::Utc::now());
+        }
+    }
+
+    /// Count a delivery a channel made in place of a failed one
+    async fn record_fallback(&self, channel_type: &str) {
+        let Some(channel_type) = ChannelType::parse(channel_type) else {
+            return;
+        };
+        let mut status = self.status.write().await;
+        let entry = status.entry(channel_type).or_default();
+        entry.fallback_deliveries += 1;
+        entry.last_fallback_at = Some(chrono::Utc::now());
     }
 
     /// Send a notification for an audit event
@@ -204,6 +399,20 @@ impl NotificationService {
         user_id: Option<i64>,
         username: Option<&str>,
         details: Option<&str>,
+    ) -> Result<()> {
+        self.notify_event_with_actions(action, user_id, username, details, &[])
+            .await
+    }
+
+    /// Like [`Self::notify_event`], offering follow-up actions to channels
+    /// that support them (e.g. Telegram buttons)
+    pub async fn notify_event_with_actions(
+        &self,
+        action: &AuditAction,
+        user_id: Option<i64>,
+        username: Option<&str>,
+        details: Option<&str>,
+        actions: &[NotificationAction],
     ) -> Result<()> {
         let db_lock = self.db.read().await;
         let db = match db_lock.as_ref() {
@@ -231,27 +440,151 @@ impl NotificationService {
             return Ok(());
         }
 
-        // Create notification title and body
-        let title = format_event_title(action);
-        let body = format_event_body(action, username, details);
+        // Create notification title and body, per channel when templated
+        let content = EventContent::load(db, action, username, details, severity).await?;
+
+        // Routing rules replace the default delivery when any of them match
+        let routes =
+            routing::matching_routes(db, &event_type, severity.as_str(), chrono::Utc::now())
+                .await?;
+        if !routes.is_empty() {
+            return self
+                .deliver_routed(
+                    db,
+                    &routes,
+                    user_id,
+                    &content,
+                    &event_type,
+                    severity,
+                    actions,
+                )
+                .await;
+        }
 
         // Create in-app notification for all users or specific user
         if let Some(uid) = user_id {
+            let locale = i18n::user_locale(db, uid).await?;
+            let (title, body) = content.for_recipient(routing::CHANNEL_IN_APP, locale);
             self.create_user_notification(db, uid, &title, &body, &event_type, severity)
                 .await?;
         } else {
             // For system-wide events, notify all admin users
             // (simplified: just log for now, can be extended)
-            tracing::debug!("System notification: {} - {}", title, body);
+            tracing::debug!("System notification: {} - {}", content.title, content.body);
         }
 
         // Send external notifications
-        self.send_external_notifications(db, user_id, &title, &body, &event_type, severity)
+        self.send_external_notifications(db, user_id, &content, &event_type, severity, actions)
             .await?;
 
         Ok(())
     }
 
+    /// Tell a user about a security-relevant change to their own account
+    ///
+    /// Unlike [`Self::notify_event`], this does not depend on the event type
+    /// being enabled or routed: the user always gets an in-app notification
+    /// and a message on each verified channel whose filters let it through.
+    pub async fn notify_security(
+        &self,
+        action: &AuditAction,
+        user_id: i64,
+        username: &str,
+        details: Option<&str>,
+    ) -> Result<()> {
+        let db_lock = self.db.read().await;
+        let Some(db) = db_lock.as_ref() else {
+            return Ok(());
+        };
+
+        let event_type = action.to_string();
+        let severity = NotificationSeverity::Warning;
+        let content = EventContent::load(db, action, Some(username), details, severity).await?;
+
+        let locale = i18n::user_locale(db, user_id).await?;
+        let (title, body) = content.for_recipient(routing::CHANNEL_IN_APP, locale);
+        self.create_user_notification(db, user_id, &title, &body, &event_type, severity)
+            .await?;
+        self.send_external_notifications(db, Some(user_id), &content, &event_type, severity, &[])
+            .await
+    }
+
+    /// Send an event to one user who has to act on it, such as an admin
+    /// asked to approve a registration
+    ///
+    /// The event type must be enabled and sets the severity, but routing
+    /// rules do not apply: the message is meant for this user only.
+    pub async fn notify_user_event(
+        &self,
+        action: &AuditAction,
+        user_id: i64,
+        username: Option<&str>,
+        details: Option<&str>,
+    ) -> Result<()> {
+        let db_lock = self.db.read().await;
+        let Some(db) = db_lock.as_ref() else {
+            return Ok(());
+        };
+
+        let event_type = action.to_string();
+        let Some(setting) = notification_event::Entity::find()
+            .filter(notification_event::Column::EventType.eq(&event_type))
+            .one(db)
+            .await?
+            .filter(|s| s.enabled)
+        else {
+            return Ok(());
+        };
+        let severity = NotificationSeverity::parse(&setting.severity);
+
+        let content = EventContent::load(db, action, username, details, severity).await?;
+        let locale = i18n::user_locale(db, user_id).await?;
+        let (title, body) = content.for_recipient(routing::CHANNEL_IN_APP, locale);
+        self.create_user_notification(db, user_id, &title, &body, &event_type, severity)
+            .await?;
+        self.send_external_notifications(db, Some(user_id), &content, &event_type, severity, &[])
+            .await
+    }
+
+    /// Email a message straight to a user and log the attempt
+    ///
+    /// For mail the user asked for directly, such as scheduled reports:
+    /// event settings, routing rules and channel filters do not apply.
+    pub async fn send_email_to_user(
+        &self,
+        user_id: i64,
+        recipient: &str,
+        subject: &str,
+        body: &str,
+        event_type: &str,
+    ) -> Result<SendResult> {
+        let message = NotificationMessage {
+            recipient: recipient.to_string(),
+            title: subject.to_string(),
+            body: body.to_string(),
+            severity: NotificationSeverity::Info,
+            actions: Vec::new(),
+        };
+        let result = self
+            .send_to_channel(ChannelType::Email.as_str(), &message)
+            .await;
+
+        let db_lock = self.db.read().await;
+        if let Some(db) = db_lock.as_ref() {
+            self.log_notification(
+                db,
+                Some(user_id),
+                ChannelType::Email.as_str(),
+                event_type,
+                recipient,
+                &result,
+                None,
+            )
+            .await?;
+        }
+        Ok(result)
+    }
+
     /// Create an in-app notification for a user
     async fn create_user_notification(
         &self,
@@ -277,14 +610,15 @@ impl NotificationService {
     }
 
     /// Send notifications through external channels
+    #[allow(clippy::too_many_arguments)]
     async fn send_external_notifications(
         &self,
         db: &DatabaseConnection,
         user_id: Option<i64>,
-        title: &str,
-        body: &str,
+        content: &EventContent,
         event_type: &str,
         severity: NotificationSeverity,
+        actions: &[NotificationAction],
     ) -> Result<()> {
         // If we have a specific user, check their preferences
         if let Some(uid) = user_id {
@@ -294,24 +628,44 @@ impl NotificationService {
                 .filter(user_notification_pref::Column::Verified.eq(true))
                 .all(db)
                 .await?;
+            let channels = notification_channel::Entity::find().all(db).await?;
+            let locale = i18n::user_locale(db, uid).await?;
 
+            let mut attempted = HashSet::new();
             for pref in prefs {
+                // Both the channel's and the user's filter must let the event through
+                let channel_filter = channels
+                    .iter()
+                    .find(|c| c.channel_type == pref.channel_type)
+                    .map(ChannelFilter::from_channel)
+                    .unwrap_or_default();
+                if !channel_filter.matches(event_type, severity)
+                    || !ChannelFilter::from_pref(&pref).matches(event_type, severity)
+                {
+                    continue;
+                }
+
+                // Skip channels a fallback already used for this event
+                if !attempted.insert((uid, pref.channel_type.clone())) {
+                    continue;
+                }
+
                 if let Some(destination) = &pref.destination {
+                    let (title, body) = content.for_recipient(&pref.channel_type, locale);
                     let message = NotificationMessage {
                         recipient: destination.clone(),
-                        title: title.to_string(),
-                        body: body.to_string(),
+                        title,
+                        body,
                         severity,
+                        actions: actions.to_vec(),
                     };
-
-                    let result = self.send_to_channel(&pref.channel_type, &message).await;
-                    self.log_notification(
+                    self.deliver_to_user(
                         db,
-                        Some(uid),
+                        uid,
                         &pref.channel_type,
+                        &message,
                         event_type,
-                        destination,
-                        &result,
+                        &mut attempted,
                     )
                     .await?;
                 }
@@ -321,11 +675,165 @@ impl NotificationService {
         Ok(())
     }
 
+    /// Deliver an event through the channels and recipients of the matching
+    /// routes, at most once per recipient and channel
+    #[allow(clippy::too_many_arguments)]
+    async fn deliver_routed(
+        &self,
+        db: &DatabaseConnection,
+        routes: &[crate::models::notification_route::Model],
+        user_id: Option<i64>,
+        content: &EventContent,
+        event_type: &str,
+        severity: NotificationSeverity,
+        actions: &[NotificationAction],
+    ) -> Result<()> {
+        let mut delivered = HashSet::new();
+        for route in routes {
+            let (channels, recipients) = routing::route_targets(db, route, user_id).await?;
+            for uid in recipients {
+                // Events caused by a tenant member stay within their tenants
+                if let Some(actor) = user_id {
+                    if !tenants::can_see_user(db, uid, actor).await? {
+                        continue;
+                    }
+                }
+                let locale = i18n::user_locale(db, uid).await?;
+                for channel in &channels {
+                    if !delivered.insert((uid, channel.clone())) {
+                        continue;
+                    }
+                    let (title, body) = content.for_recipient(channel, locale);
+                    if channel == routing::CHANNEL_IN_APP {
+                        self.create_user_notification(db, uid, &title, &body, event_type, severity)
+                            .await?;
+                        continue;
+                    }
+
+                    // External channels need a verified destination for the user
+                    let pref = user_notification_pref::Entity::find()
+                        .filter(user_notification_pref::Column::UserId.eq(uid))
+                        .filter(user_notification_pref::Column::ChannelType.eq(channel.as_str()))
+                        .filter(user_notification_pref::Column::Enabled.eq(true))
+                        .filter(user_notification_pref::Column::Verified.eq(true))
+                        .one(db)
+                        .await?;
+                    let Some(destination) = pref.and_then(|p| p.destination) else {
+                        continue;
+                    };
+                    let message = NotificationMessage {
+                        recipient: destination,
+                        title,
+                        body,
+                        severity,
+                        actions: actions.to_vec(),
+                    };
+                    self.deliver_to_user(db, uid, channel, &message, event_type, &mut delivered)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a message to one of a user's channels and log the attempt
+    ///
+    /// When a critical event fails to go out, the user's other verified
+    /// destinations are tried in [`FALLBACK_CHAIN`] order until one succeeds.
+    /// A fallback may use a channel the user switched off or filtered, since
+    /// the event would otherwise not reach them at all. Every channel used is
+    /// added to `attempted`, which callers share across one event so no
+    /// channel is used twice.
+    async fn deliver_to_user(
+        &self,
+        db: &DatabaseConnection,
+        user_id: i64,
+        channel_type: &str,
+        message: &NotificationMessage,
+        event_type: &str,
+        attempted: &mut HashSet<(i64, String)>,
+    ) -> Result<()> {
+        let result = self.send_to_channel(channel_type, message).await;
+        let mut failed_log_id = self
+            .log_notification(
+                db,
+                Some(user_id),
+                channel_type,
+                event_type,
+                &message.recipient,
+                &result,
+                None,
+            )
+            .await?;
+        if result.success || message.severity != NotificationSeverity::Critical {
+            return Ok(());
+        }
+
+        for fallback_type in FALLBACK_CHAIN {
+            let fallback = fallback_type.as_str();
+            if attempted.contains(&(user_id, fallback.to_string()))
+                || !self.provider_initialized(fallback_type).await
+            {
+                continue;
+            }
+            let pref = user_notification_pref::Entity::find()
+                .filter(user_notification_pref::Column::UserId.eq(user_id))
+                .filter(user_notification_pref::Column::ChannelType.eq(fallback))
+                .filter(user_notification_pref::Column::Verified.eq(true))
+                .one(db)
+                .await?;
+            let Some(destination) = pref.and_then(|p| p.destination) else {
+                continue;
+            };
+            attempted.insert((user_id, fallback.to_string()));
+
+            tracing::info!(
+                "Falling back from {} to {} for user {}",
+                channel_type,
+                fallback,
+                user_id
+            );
+            let fallback_message = NotificationMessage {
+                recipient: destination,
+                ..message.clone()
+            };
+            let result = self.send_to_channel(fallback, &fallback_message).await;
+            self.record_fallback(fallback).await;
+            let log_id = self
+                .log_notification(
+                    db,
+                    Some(user_id),
+                    fallback,
+                    event_type,
+                    &fallback_message.recipient,
+                    &result,
+                    Some(failed_log_id),
+                )
+                .await?;
+            if result.success {
+                break;
+            }
+            failed_log_id = log_id;
+        }
+        Ok(())
+    }
+
     /// Send a message to a specific channel
     async fn send_to_channel(
         &self,
         channel_type: &str,
         message: &NotificationMessage,
+    ) -> SendResult {
+        let result = self.send_with_provider(channel_type, message).await;
+        self.record_delivery(channel_type, &result).await;
+        METRICS.record_notification(channel_type, result.success);
+        result
+    }
+
+    async fn send_with_provider(
+        &self,
+        channel_type: &str,
+        message: &NotificationMessage,
     ) -> SendResult {
         match channel_type {
             "email" => {
@@ -356,6 +864,7 @@ impl NotificationService {
     }
 
     /// Log a notification delivery attempt
+    #[allow(clippy::too_many_arguments)]
     async fn log_notification(
         &self,
         db: &DatabaseConnection,
@@ -364,7 +873,8 @@ impl NotificationService {
         event_type: &str,
         recipient: &str,
         result: &SendResult,
-    ) -> Result<()> {
+        fallback_of: Option<i64>,
+    ) -> Result<i64> {
         let log = notification_log::ActiveModel {
             user_id: Set(user_id),
             channel_type: Set(channel_type.to_string()),
@@ -376,11 +886,11 @@ impl NotificationService {
                 "failed".to_string()
             }),
             error_message: Set(result.error.clone()),
+            fallback_of: Set(fallback_of),
             created_at: Set(chrono::Utc::now()),
             ..Default::default()
         };
-        log.insert(db).await?;
-        Ok(())
+        Ok(log.insert(db).await?.id)
     }
 
     /// Test a notification channel
@@ -413,6 +923,84 @@ impl NotificationService {
         }
     }
 
+    /// Send a user a test message at their own destination, at most once
+    /// per `notification_test_interval_seconds` per channel
+    pub async fn test_user_destination(
+        &self,
+        user_id: i64,
+        channel_type: ChannelType,
+        destination: &str,
+    ) -> Result<SendResult> {
+        let now = chrono::Utc::now();
+        {
+            let mut user_tests = self.user_tests.write().await;
+            if let Some(last) = user_tests.get(&(user_id, channel_type)) {
+                let interval = runtime_config::current().notification_test_interval_secs;
+                let wait = interval - (now - *last).num_seconds();
+                if wait > 0 {
+                    return Err(AppError::TooManyRequests(format!(
+                        "Please wait {} seconds before sending another test",
+                        wait
+                    )));
+                }
+            }
+            user_tests.insert((user_id, channel_type), now);
+        }
+        Ok(self.test_channel(channel_type.as_str(), destination).await)
+    }
+
+    /// Send a destination verification code through a channel
+    pub async fn send_verification_code(
+        &self,
+        channel_type: ChannelType,
+        destination: &str,
+        code: &str,
+        valid_minutes: i64,
+    ) -> SendResult {
+        let message = NotificationMessage {
+            recipient: destination.to_string(),
+            title: "Kubarr verification code".to_string(),
+            body: format!(
+                "Your Kubarr verification code is {}. It expires in {} minutes.",
+                code, valid_minutes
+            ),
+            severity: NotificationSeverity::Info,
+            actions: Vec::new(),
+        };
+        self.send_to_channel(channel_type.as_str(), &message).await
+    }
+
+    /// Whether a Telegram webhook call carries the configured webhook secret
+    pub async fn verify_telegram_webhook(&self, secret: Option<&str>) -> bool {
+        match self.telegram.read().await.as_ref() {
+            Some(provider) => provider.verify_webhook_secret(secret),
+            None => false,
+        }
+    }
+
+    /// Decode the signed callback data of a Telegram button pressed in a chat
+    pub async fn parse_telegram_callback(
+        &self,
+        data: &str,
+        chat_id: &str,
+    ) -> Option<TelegramAction> {
+        self.telegram
+            .read()
+            .await
+            .as_ref()
+            .and_then(|provider| provider.parse_callback(data, chat_id))
+    }
+
+    /// Confirm a Telegram button press to the user who pressed it
+    pub async fn answer_telegram_callback(&self, callback_query_id: &str, text: &str) {
+        if let Some(provider) = self.telegram.read().await.as_ref() {
+            let result = provider.answer_callback(callback_query_id, text).await;
+            if let Some(error) = result.error {
+                tracing::warn!("Failed to answer Telegram callback: {}", error);
+            }
+        }
+    }
+
     /// Get unread notification count for a user
     pub async fn get_unread_count(&self, user_id: i64) -> Result<u64> {
         let db_lock = self.db.read().await;
@@ -526,12 +1114,14 @@ impl Clone for NotificationService {
             email: Arc::clone(&self.email),
             telegram: Arc::clone(&self.telegram),
             messagebird: Arc::clone(&self.messagebird),
+            status: Arc::clone(&self.status),
+            user_tests: Arc::clone(&self.user_tests),
         }
     }
 }
 
 /// Format a human-readable title for an audit event
-fn format_event_title(action: &AuditAction) -> String {
+pub(crate) fn format_event_title(action: &AuditAction) -> String {
     match action {
         // Authentication
         AuditAction::Login => "Login Successful".to_string(),
@@ -543,6 +1133,13 @@ fn format_event_title(action: &AuditAction) -> String {
         AuditAction::TwoFactorVerified => "2FA Verification Successful".to_string(),
         AuditAction::TwoFactorFailed => "2FA Verification Failed".to_string(),
         AuditAction::PasswordChanged => "Password Changed".to_string(),
+        AuditAction::NewDeviceLogin => "New Sign-In".to_string(),
+        AuditAction::SessionRevoked => "Session Signed Out".to_string(),
+        AuditAction::IpBanned => "IP Address Banned".to_string(),
+        AuditAction::IpUnbanned => "IP Address Unbanned".to_string(),
+        AuditAction::AccessDenied => "Repeated Access Denied".to_string(),
+        AuditAction::AccountLocked => "Account Locked".to_string(),
+        AuditAction::RefreshTokenReused => "Session Revoked After Token Reuse".to_string(),
         // User management
         AuditAction::UserCreated => "New User Created".to_string(),
         AuditAction::UserUpdated => "User Updated".to_string(),
@@ -556,6 +1153,12 @@ fn format_event_title(action: &AuditAction) -> String {
         AuditAction::RoleDeleted => "Role Deleted".to_string(),
         AuditAction::RoleAssigned => "Role Assigned".to_string(),
         AuditAction::RoleUnassigned => "Role Unassigned".to_string(),
+        AuditAction::RoleExpiring => "Role Expiring Soon".to_string(),
+        AuditAction::AccessReviewStarted => "Access Review Started".to_string(),
+        AuditAction::AccessConfirmed => "Access Confirmed".to_string(),
+        AuditAction::AccessRevoked => "Access Revoked".to_string(),
+        AuditAction::ScheduleOverrideGranted => "Schedule Exception Granted".to_string(),
+        AuditAction::ScheduleOverrideRevoked => "Schedule Exception Revoked".to_string(),
         // App management
         AuditAction::AppInstalled => "App Installed".to_string(),
         AuditAction::AppUninstalled => "App Uninstalled".to_string(),
@@ -564,18 +1167,48 @@ fn format_event_title(action: &AuditAction) -> String {
         AuditAction::AppRestarted => "App Restarted".to_string(),
         AuditAction::AppConfigured => "App Configured".to_string(),
         AuditAction::AppAccessed => "App Accessed".to_string(),
+        AuditAction::AppsSuspended => "Idle Apps Suspended".to_string(),
+        AuditAction::AppInstallRequested => "App Install Requested".to_string(),
+        AuditAction::AppInstallDenied => "App Install Denied".to_string(),
+        AuditAction::AppUpgraded => "App Upgraded".to_string(),
+        AuditAction::AppShellOpened => "App Shell Opened".to_string(),
+        AuditAction::AppShellClosed => "App Shell Closed".to_string(),
+        // Media requests
+        AuditAction::MediaRequested => "Media Requested".to_string(),
+        AuditAction::MediaRequestApproved => "Media Request Approved".to_string(),
+        AuditAction::MediaRequestDeclined => "Media Request Declined".to_string(),
+        // Network
+        AuditAction::WanDegraded => "WAN Connection Degraded".to_string(),
+        AuditAction::WanRecovered => "WAN Connection Recovered".to_string(),
+        AuditAction::UptimeMonitorDown => "Monitor Down".to_string(),
+        AuditAction::UptimeMonitorUp => "Monitor Up".to_string(),
+        AuditAction::MetricAnomaly => "Metric Anomaly".to_string(),
+        AuditAction::NodeTemperatureCritical => "Node Temperature Critical".to_string(),
+        AuditAction::LogAlertFiring => "Log Alert Firing".to_string(),
+        AuditAction::LogAlertResolved => "Log Alert Resolved".to_string(),
+        AuditAction::AlertAssigned => "Alert Assigned".to_string(),
+        // Storage
+        AuditAction::StorageQuotaWarning => "Storage Quota Warning".to_string(),
+        AuditAction::StorageQuotaExceeded => "Storage Quota Exceeded".to_string(),
         // System
         AuditAction::SystemSettingChanged => "System Setting Changed".to_string(),
         AuditAction::InviteCreated => "Invite Link Created".to_string(),
         AuditAction::InviteUsed => "Invite Link Used".to_string(),
         AuditAction::InviteDeleted => "Invite Link Deleted".to_string(),
+        AuditAction::UpdateAvailable => "Kubarr Update Available".to_string(),
+        AuditAction::UpdateStarted => "Kubarr Update Started".to_string(),
+        AuditAction::UpdateCompleted => "Kubarr Updated".to_string(),
+        AuditAction::UpdateFailed => "Kubarr Update Failed".to_string(),
+        AuditAction::BackupCreated => "Backup Created".to_string(),
+        AuditAction::BackupRestored => "Backup Restored".to_string(),
+        AuditAction::BackupFailed => "Backup Failed".to_string(),
         // API
         AuditAction::ApiAccess => "API Access".to_string(),
     }
 }
 
 /// Format a human-readable body for an audit event
-fn format_event_body(
+pub(crate) fn format_event_body(
     action: &AuditAction,
     username: Option<&str>,
     details: Option<&str>,
@@ -601,7 +1234,62 @@ fn format_event_body(
         AuditAction::TwoFactorFailed => {
             format!("User {} failed 2FA verification", user)
         }
-        AuditAction::PasswordChanged => format!("User {} changed their password", user),
+        AuditAction::PasswordChanged => {
+            if detail.is_empty() {
+                format!("User {} changed their password", user)
+            } else {
+                format!("The password of {} was changed: {}", user, detail)
+            }
+        }
+        AuditAction::NewDeviceLogin => {
+            if detail.is_empty() {
+                format!("{} signed in from a new device", user)
+            } else {
+                format!("{} signed in from a new device: {}", user, detail)
+            }
+        }
+        AuditAction::SessionRevoked => {
+            if detail.is_empty() {
+                format!("A session of {} was signed out", user)
+            } else {
+                format!("A session of {} was signed out: {}", user, detail)
+            }
+        }
+        AuditAction::IpBanned => {
+            if detail.is_empty() {
+                format!("An IP address was banned by {}", user)
+            } else {
+                format!("IP address banned: {}", detail)
+            }
+        }
+        AuditAction::IpUnbanned => {
+            if detail.is_empty() {
+                format!("An IP address was unbanned by {}", user)
+            } else {
+                format!("IP address unbanned by {}: {}", user, detail)
+            }
+        }
+        AuditAction::AccessDenied => {
+            if detail.is_empty() {
+                format!("{} was repeatedly denied access", user)
+            } else {
+                format!("{} was repeatedly denied access: {}", user, detail)
+            }
+        }
+        AuditAction::AccountLocked => {
+            if detail.is_empty() {
+                "An account was locked after repeated failed sign-ins".to_string()
+            } else {
+                format!("Account locked: {}", detail)
+            }
+        }
+        AuditAction::RefreshTokenReused => {
+            if detail.is_empty() {
+                "A session was revoked because its refresh token was used twice".to_string()
+            } else {
+                format!("Session revoked after refresh token reuse: {}", detail)
+            }
+        }
         // User management
         AuditAction::UserCreated => {
             if detail.is_empty() {
@@ -681,6 +1369,48 @@ fn format_event_body(
                 format!("Role unassigned by {}: {}", user, detail)
             }
         }
+        AuditAction::RoleExpiring => {
+            if detail.is_empty() {
+                format!("A role of {} expires soon", user)
+            } else {
+                format!("A role of {} expires soon: {}", user, detail)
+            }
+        }
+        AuditAction::AccessReviewStarted => {
+            if detail.is_empty() {
+                format!("Access review started by {}", user)
+            } else {
+                format!("Access review started by {}: {}", user, detail)
+            }
+        }
+        AuditAction::AccessConfirmed => {
+            if detail.is_empty() {
+                format!("Access confirmed by {}", user)
+            } else {
+                format!("Access confirmed by {}: {}", user, detail)
+            }
+        }
+        AuditAction::AccessRevoked => {
+            if detail.is_empty() {
+                format!("Access revoked by {}", user)
+            } else {
+                format!("Access revoked by {}: {}", user, detail)
+            }
+        }
+        AuditAction::ScheduleOverrideGranted => {
+            if detail.is_empty() {
+                format!("Schedule exception granted by {}", user)
+            } else {
+                format!("Schedule exception granted by {}: {}", user, detail)
+            }
+        }
+        AuditAction::ScheduleOverrideRevoked => {
+            if detail.is_empty() {
+                format!("Schedule exception revoked by {}", user)
+            } else {
+                format!("Schedule exception revoked by {}: {}", user, detail)
+            }
+        }
         // App management
         AuditAction::AppInstalled => {
             if detail.is_empty() {
@@ -731,6 +1461,149 @@ fn format_event_body(
                 format!("User {} accessed {}", user, detail)
             }
         }
+        // Media requests
+        AuditAction::MediaRequested => {
+            if detail.is_empty() {
+                format!("Media requested by {}", user)
+            } else {
+                format!("{} requested {}", user, detail)
+            }
+        }
+        AuditAction::MediaRequestApproved => {
+            if detail.is_empty() {
+                format!("Media request approved by {}", user)
+            } else {
+                format!("Request for {} approved by {}", detail, user)
+            }
+        }
+        AuditAction::MediaRequestDeclined => {
+            if detail.is_empty() {
+                format!("Media request declined by {}", user)
+            } else {
+                format!("Request for {} declined by {}", detail, user)
+            }
+        }
+        // Network
+        AuditAction::WanDegraded => {
+            if detail.is_empty() {
+                "WAN connection degraded".to_string()
+            } else {
+                format!("WAN connection degraded: {}", detail)
+            }
+        }
+        AuditAction::WanRecovered => {
+            if detail.is_empty() {
+                "WAN connection recovered".to_string()
+            } else {
+                format!("WAN connection recovered: {}", detail)
+            }
+        }
+        AuditAction::UptimeMonitorDown => {
+            if detail.is_empty() {
+                "An uptime monitor is down".to_string()
+            } else {
+                format!("Monitor down: {}", detail)
+            }
+        }
+        AuditAction::UptimeMonitorUp => {
+            if detail.is_empty() {
+                "An uptime monitor is back up".to_string()
+            } else {
+                format!("Monitor back up: {}", detail)
+            }
+        }
+        AuditAction::MetricAnomaly => {
+            if detail.is_empty() {
+                "An app metric deviated from its baseline".to_string()
+            } else {
+                format!("Anomaly detected: {}", detail)
+            }
+        }
+        AuditAction::NodeTemperatureCritical => {
+            if detail.is_empty() {
+                "A node sensor exceeded its critical temperature".to_string()
+            } else {
+                format!("Temperature critical: {}", detail)
+            }
+        }
+        AuditAction::AppsSuspended => {
+            if detail.is_empty() {
+                "Idle apps were scaled to zero".to_string()
+            } else {
+                format!("Suspended idle apps: {}", detail)
+            }
+        }
+        AuditAction::AppInstallRequested => {
+            if detail.is_empty() {
+                format!("App install requested by {}", user)
+            } else {
+                format!("{} requested {}", user, detail)
+            }
+        }
+        AuditAction::AppInstallDenied => {
+            if detail.is_empty() {
+                format!("App install request denied by {}", user)
+            } else {
+                format!("Install request for {} denied by {}", detail, user)
+            }
+        }
+        AuditAction::AppUpgraded => {
+            if detail.is_empty() {
+                format!("App upgraded by {}", user)
+            } else {
+                format!("App upgraded by {}: {}", user, detail)
+            }
+        }
+        AuditAction::AppShellOpened => {
+            if detail.is_empty() {
+                format!("Shell opened in an app pod by {}", user)
+            } else {
+                format!("Shell opened in {} by {}", detail, user)
+            }
+        }
+        AuditAction::AppShellClosed => {
+            if detail.is_empty() {
+                format!("Shell in an app pod closed by {}", user)
+            } else {
+                format!("Shell in {} closed by {}", detail, user)
+            }
+        }
+        AuditAction::LogAlertFiring => {
+            if detail.is_empty() {
+                "A log alert rule is firing".to_string()
+            } else {
+                format!("Log alert firing: {}", detail)
+            }
+        }
+        AuditAction::LogAlertResolved => {
+            if detail.is_empty() {
+                "A log alert rule has resolved".to_string()
+            } else {
+                format!("Log alert resolved: {}", detail)
+            }
+        }
+        AuditAction::AlertAssigned => {
+            if detail.is_empty() {
+                "An alert has been assigned to you".to_string()
+            } else {
+                format!("Alert assigned to you: {}", detail)
+            }
+        }
+        // Storage
+        AuditAction::StorageQuotaWarning => {
+            if detail.is_empty() {
+                "Your home folder is nearing its quota".to_string()
+            } else {
+                format!("Home folder nearing its quota: {}", detail)
+            }
+        }
+        AuditAction::StorageQuotaExceeded => {
+            if detail.is_empty() {
+                "Your home folder is full; uploads are refused".to_string()
+            } else {
+                format!("Home folder full, uploads are refused: {}", detail)
+            }
+        }
         // System
         AuditAction::SystemSettingChanged => {
             if detail.is_empty() {
@@ -760,6 +1633,55 @@ fn format_event_body(
                 format!("Invite link deleted by {}: {}", user, detail)
             }
         }
+        AuditAction::UpdateAvailable => {
+            if detail.is_empty() {
+                "A new Kubarr version is available".to_string()
+            } else {
+                format!("Kubarr {} is available", detail)
+            }
+        }
+        AuditAction::UpdateStarted => {
+            if detail.is_empty() {
+                format!("Kubarr update started by {}", user)
+            } else {
+                format!("Kubarr update started by {}: {}", user, detail)
+            }
+        }
+        AuditAction::UpdateCompleted => {
+            if detail.is_empty() {
+                "Kubarr was updated".to_string()
+            } else {
+                format!("Kubarr was updated: {}", detail)
+            }
+        }
+        AuditAction::UpdateFailed => {
+            if detail.is_empty() {
+                "A Kubarr update failed".to_string()
+            } else {
+                format!("Kubarr update failed: {}", detail)
+            }
+        }
+        AuditAction::BackupCreated => {
+            if detail.is_empty() {
+                "A backup of Kubarr was created".to_string()
+            } else {
+                format!("Backup created: {}", detail)
+            }
+        }
+        AuditAction::BackupRestored => {
+            if detail.is_empty() {
+                format!("Kubarr was restored from a backup by {}", user)
+            } else {
+                format!("Kubarr was restored from a backup by {}: {}", user, detail)
+            }
+        }
+        AuditAction::BackupFailed => {
+            if detail.is_empty() {
+                "A scheduled backup of Kubarr failed".to_string()
+            } else {
+                format!("Scheduled backup failed: {}", detail)
+            }
+        }
         // API
         AuditAction::ApiAccess => {
             if detail.is_empty() {
@@ -1019,6 +1941,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_event_title_media_requests() {
+        assert_eq!(
+            format_event_title(&AuditAction::MediaRequested),
+            "Media Requested"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::MediaRequestApproved),
+            "Media Request Approved"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::MediaRequestDeclined),
+            "Media Request Declined"
+        );
+    }
+
+    #[test]
+    fn test_format_event_title_wan() {
+        assert_eq!(
+            format_event_title(&AuditAction::WanDegraded),
+            "WAN Connection Degraded"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::WanRecovered),
+            "WAN Connection Recovered"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::UptimeMonitorDown),
+            "Monitor Down"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::UptimeMonitorUp),
+            "Monitor Up"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::MetricAnomaly),
+            "Metric Anomaly"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::NodeTemperatureCritical),
+            "Node Temperature Critical"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::LogAlertFiring),
+            "Log Alert Firing"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::LogAlertResolved),
+            "Log Alert Resolved"
+        );
+        assert_eq!(
+            format_event_title(&AuditAction::AlertAssigned),
+            "Alert Assigned"
+        );
+    }
+
     #[test]
     fn test_format_event_title_system_setting_changed() {
         assert_eq!(
@@ -1120,6 +2098,16 @@ mod tests {
     fn test_format_event_body_password_changed() {
         let body = format_event_body(&AuditAction::PasswordChanged, Some("alice"), None);
         assert_eq!(body, "User alice changed their password");
+
+        let body = format_event_body(
+            &AuditAction::PasswordChanged,
+            Some("alice"),
+            Some("reset by admin; 2 sessions signed out"),
+        );
+        assert_eq!(
+            body,
+            "The password of alice was changed: reset by admin; 2 sessions signed out"
+        );
     }
 
     // Variants that branch on detail presence (with and without details).
@@ -1354,6 +2342,166 @@ mod tests {
         assert_eq!(body, "User alice accessed sonarr");
     }
 
+    #[test]
+    fn test_format_event_body_media_requested() {
+        let body = format_event_body(
+            &AuditAction::MediaRequested,
+            Some("alice"),
+            Some("movie #603 (jellyseerr)"),
+        );
+        assert_eq!(body, "alice requested movie #603 (jellyseerr)");
+    }
+
+    #[test]
+    fn test_format_event_body_media_request_approved() {
+        let body = format_event_body(
+            &AuditAction::MediaRequestApproved,
+            Some("admin"),
+            Some("movie #603 (jellyseerr)"),
+        );
+        assert_eq!(
+            body,
+            "Request for movie #603 (jellyseerr) approved by admin"
+        );
+
+        let body = format_event_body(&AuditAction::MediaRequestDeclined, Some("admin"), None);
+        assert_eq!(body, "Media request declined by admin");
+    }
+
+    #[test]
+    fn test_format_event_body_wan() {
+        let body = format_event_body(
+            &AuditAction::WanDegraded,
+            None,
+            Some("packet loss 40.0% (threshold 20%)"),
+        );
+        assert_eq!(
+            body,
+            "WAN connection degraded: packet loss 40.0% (threshold 20%)"
+        );
+
+        let body = format_event_body(&AuditAction::WanRecovered, None, None);
+        assert_eq!(body, "WAN connection recovered");
+    }
+
+    #[test]
+    fn test_format_event_body_uptime_monitor() {
+        let body = format_event_body(
+            &AuditAction::UptimeMonitorDown,
+            None,
+            Some("NAS (192.168.1.10:445): Connection refused"),
+        );
+        assert_eq!(
+            body,
+            "Monitor down: NAS (192.168.1.10:445): Connection refused"
+        );
+
+        let body = format_event_body(&AuditAction::UptimeMonitorUp, None, None);
+        assert_eq!(body, "An uptime monitor is back up");
+    }
+
+    #[test]
+    fn test_format_event_body_metric_anomaly() {
+        let body = format_event_body(
+            &AuditAction::MetricAnomaly,
+            None,
+            Some("sonarr CPU is above its baseline"),
+        );
+        assert_eq!(body, "Anomaly detected: sonarr CPU is above its baseline");
+
+        let body = format_event_body(&AuditAction::MetricAnomaly, None, None);
+        assert_eq!(body, "An app metric deviated from its baseline");
+    }
+
+    #[test]
+    fn test_format_event_body_node_temperature_critical() {
+        let body = format_event_body(
+            &AuditAction::NodeTemperatureCritical,
+            None,
+            Some("nuc CPU Package id 0 at 96.0°C"),
+        );
+        assert_eq!(body, "Temperature critical: nuc CPU Package id 0 at 96.0°C");
+
+        let body = format_event_body(&AuditAction::NodeTemperatureCritical, None, None);
+        assert_eq!(body, "A node sensor exceeded its critical temperature");
+    }
+
+    #[test]
+    fn test_format_event_body_app_install_requests() {
+        let body = format_event_body(
+            &AuditAction::AppInstallRequested,
+            Some("bob"),
+            Some("jellyfin"),
+        );
+        assert_eq!(body, "bob requested jellyfin");
+        let body = format_event_body(
+            &AuditAction::AppInstallDenied,
+            Some("admin"),
+            Some("jellyfin"),
+        );
+        assert_eq!(body, "Install request for jellyfin denied by admin");
+    }
+
+    #[test]
+    fn test_format_event_body_apps_suspended() {
+        let body = format_event_body(
+            &AuditAction::AppsSuspended,
+            None,
+            Some("radarr, sonarr (no access for 14 days)"),
+        );
+        assert_eq!(
+            body,
+            "Suspended idle apps: radarr, sonarr (no access for 14 days)"
+        );
+    }
+
+    #[test]
+    fn test_format_event_body_log_alerts() {
+        let body = format_event_body(
+            &AuditAction::LogAlertFiring,
+            None,
+            Some("Sonarr errors: 7 lines matching ERROR"),
+        );
+        assert_eq!(
+            body,
+            "Log alert firing: Sonarr errors: 7 lines matching ERROR"
+        );
+
+        let body = format_event_body(&AuditAction::LogAlertResolved, None, None);
+        assert_eq!(body, "A log alert rule has resolved");
+    }
+
+    #[test]
+    fn test_format_event_body_alert_assigned() {
+        let body = format_event_body(
+            &AuditAction::AlertAssigned,
+            Some("admin"),
+            Some("Plex (http://plex:32400) is down"),
+        );
+        assert_eq!(
+            body,
+            "Alert assigned to you: Plex (http://plex:32400) is down"
+        );
+
+        let body = format_event_body(&AuditAction::AlertAssigned, None, None);
+        assert_eq!(body, "An alert has been assigned to you");
+    }
+
+    #[test]
+    fn test_format_event_body_storage_quota() {
+        let body = format_event_body(
+            &AuditAction::StorageQuotaWarning,
+            Some("alice"),
+            Some("home/alice on data uses 9Gi of 8Gi"),
+        );
+        assert_eq!(
+            body,
+            "Home folder nearing its quota: home/alice on data uses 9Gi of 8Gi"
+        );
+        let body = format_event_body(&AuditAction::StorageQuotaExceeded, None, None);
+        assert_eq!(body, "Your home folder is full; uploads are refused");
+    }
+
     #[test]
     fn test_format_event_body_system_setting_changed_no_detail() {
         let body = format_event_body(&AuditAction::SystemSettingChanged, Some("admin"), None);
@@ -1406,6 +2554,25 @@ mod tests {
         assert_eq!(body, "Invite link deleted by admin: abc123");
     }
 
+    #[test]
+    fn test_format_event_body_updates() {
+        let body = format_event_body(&AuditAction::UpdateAvailable, None, Some("0.3.0"));
+        assert_eq!(body, "Kubarr 0.3.0 is available");
+
+        let body = format_event_body(
+            &AuditAction::UpdateFailed,
+            None,
+            Some("0.2.0 -> 0.3.0 rolled back to revision 4"),
+        );
+        assert_eq!(
+            body,
+            "Kubarr update failed: 0.2.0 -> 0.3.0 rolled back to revision 4"
+        );
+
+        let body = format_event_body(&AuditAction::UpdateCompleted, None, None);
+        assert_eq!(body, "Kubarr was updated");
+    }
+
     #[test]
     fn test_format_event_body_api_access_no_detail() {
         let body = format_event_body(&AuditAction::ApiAccess, Some("alice"), None);
@@ -0,0 +1,565 @@
+//! Notification routing rules
+//!
+//! A route sends events matching its event types, severities and (optional)
+//! daily time window to a set of channels and recipients, e.g. "warnings go
+//! only to the in-app inbox between 22:00 and 07:00" or "critical storage
+//! events always go to Telegram". When at least one route matches an event,
+//! the matching routes decide where it goes; otherwise the event is delivered
+//! the default way (in-app and the user's own verified channels).
+
+use chrono::{DateTime, NaiveDateTime, NaiveTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, JoinType, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, Set,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ChannelType;
+use crate::error::{AppError, Result};
+use crate::models::{notification_route, role, user, user_role};
+
+/// Pseudo-channel for the in-app inbox
+pub const CHANNEL_IN_APP: &str = "in_app";
+
+pub const SEVERITIES: &[&str] = &["info", "warning", "critical"];
+
+const TIME_FORMAT: &str = "%H:%M";
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateNotificationRouteRequest {
+    pub name: String,
+    /// Event types to match (empty or omitted = all events)
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Severities to match (empty or omitted = all severities)
+    #[serde(default)]
+    pub severities: Vec<String>,
+    /// Start of the daily window, "HH:MM" (omit both for all day)
+    pub start_time: Option<String>,
+    /// End of the daily window, "HH:MM"; before the start to wrap midnight
+    pub end_time: Option<String>,
+    /// IANA time zone of the window (default "UTC")
+    pub timezone: Option<String>,
+    /// "in_app", "email", "telegram" and/or "messagebird"
+    pub channels: Vec<String>,
+    /// Users to notify (empty or omitted = the event's user, or all admins)
+    #[serde(default)]
+    pub recipient_user_ids: Vec<i64>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct UpdateNotificationRouteRequest {
+    pub name: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub severities: Option<Vec<String>>,
+    /// Set both times to "" to make the route apply all day
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub timezone: Option<String>,
+    pub channels: Option<Vec<String>>,
+    pub recipient_user_ids: Option<Vec<i64>>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct NotificationRouteInfo {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    pub event_types: Vec<String>,
+    pub severities: Vec<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub timezone: String,
+    pub channels: Vec<String>,
+    pub recipient_user_ids: Vec<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn parse_list<T: serde::de::DeserializeOwned>(value: &str) -> Vec<T> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+impl From<notification_route::Model> for NotificationRouteInfo {
+    fn from(route: notification_route::Model) -> Self {
+        Self {
+            id: route.id,
+            event_types: parse_list(&route.event_types),
+            severities: parse_list(&route.severities),
+            channels: parse_list(&route.channels),
+            recipient_user_ids: parse_list(&route.recipient_user_ids),
+            name: route.name,
+            enabled: route.enabled,
+            start_time: route.start_time,
+            end_time: route.end_time,
+            timezone: route.timezone,
+            created_at: route.created_at,
+            updated_at: route.updated_at,
+        }
+    }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Route name is required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_event_types(event_types: Vec<String>, known: &[String]) -> Result<Vec<String>> {
+    for event_type in &event_types {
+        if !known.contains(event_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown event type '{}'",
+                event_type
+            )));
+        }
+    }
+    Ok(event_types)
+}
+
+fn validate_severities(severities: Vec<String>) -> Result<Vec<String>> {
+    for severity in &severities {
+        if !SEVERITIES.contains(&severity.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Invalid severity '{}', expected one of: {}",
+                severity,
+                SEVERITIES.join(", ")
+            )));
+        }
+    }
+    Ok(severities)
+}
+
+fn validate_channels(channels: Vec<String>) -> Result<Vec<String>> {
+    if channels.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one channel is required".to_string(),
+        ));
+    }
+    for channel in &channels {
+        if channel != CHANNEL_IN_APP && ChannelType::parse(channel).is_none() {
+            return Err(AppError::BadRequest(format!(
+                "Invalid channel '{}', expected in_app, email, telegram or messagebird",
+                channel
+            )));
+        }
+    }
+    Ok(channels)
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, TIME_FORMAT)
+        .map_err(|_| AppError::BadRequest(format!("Invalid time '{}', expected HH:MM", value)))
+}
+
+/// Both times or neither; empty strings count as unset
+fn validate_window(
+    start: Option<String>,
+    end: Option<String>,
+) -> Result<(Option<String>, Option<String>)> {
+    let start = start.filter(|s| !s.trim().is_empty());
+    let end = end.filter(|s| !s.trim().is_empty());
+    match (start, end) {
+        (None, None) => Ok((None, None)),
+        (Some(start), Some(end)) => {
+            let (from, to) = (parse_time(start.trim())?, parse_time(end.trim())?);
+            if from == to {
+                return Err(AppError::BadRequest(
+                    "Start and end time must differ; omit both for all day".to_string(),
+                ));
+            }
+            Ok((
+                Some(from.format(TIME_FORMAT).to_string()),
+                Some(to.format(TIME_FORMAT).to_string()),
+            ))
+        }
+        _ => Err(AppError::BadRequest(
+            "Both start_time and end_time are required for a time window".to_string(),
+        )),
+    }
+}
+
+pub fn validate_timezone(timezone: &str) -> Result<String> {
+    let timezone = timezone.trim();
+    jiff::tz::TimeZone::get(timezone)
+        .map_err(|_| AppError::BadRequest(format!("Unknown time zone '{}'", timezone)))?;
+    Ok(timezone.to_string())
+}
+
+async fn validate_recipients(db: &DatabaseConnection, ids: Vec<i64>) -> Result<Vec<i64>> {
+    for id in &ids {
+        if user::Entity::find_by_id(*id).one(db).await?.is_none() {
+            return Err(AppError::BadRequest(format!("User {} does not exist", id)));
+        }
+    }
+    Ok(ids)
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "[]".to_string())
+}
+
+// ============================================================================
+// CRUD
+// ============================================================================
+
+async fn find_route(db: &DatabaseConnection, id: i64) -> Result<notification_route::Model> {
+    notification_route::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Notification route {} not found", id)))
+}
+
+pub async fn list_routes(db: &DatabaseConnection) -> Result<Vec<NotificationRouteInfo>> {
+    Ok(notification_route::Entity::find()
+        .order_by_asc(notification_route::Column::Name)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+pub async fn get_route(db: &DatabaseConnection, id: i64) -> Result<NotificationRouteInfo> {
+    Ok(find_route(db, id).await?.into())
+}
+
+/// Create a route; `known_event_types` are the event types that can be routed
+pub async fn create_route(
+    db: &DatabaseConnection,
+    request: CreateNotificationRouteRequest,
+    known_event_types: &[String],
+) -> Result<NotificationRouteInfo> {
+    let name = validate_name(&request.name)?;
+    let event_types = validate_event_types(request.event_types, known_event_types)?;
+    let severities = validate_severities(request.severities)?;
+    let (start_time, end_time) = validate_window(request.start_time, request.end_time)?;
+    let timezone = validate_timezone(request.timezone.as_deref().unwrap_or("UTC"))?;
+    let channels = validate_channels(request.channels)?;
+    let recipients = validate_recipients(db, request.recipient_user_ids).await?;
+
+    let now = Utc::now();
+    let model = notification_route::ActiveModel {
+        name: Set(name),
+        enabled: Set(request.enabled.unwrap_or(true)),
+        event_types: Set(to_json(&event_types)),
+        severities: Set(to_json(&severities)),
+        start_time: Set(start_time),
+        end_time: Set(end_time),
+        timezone: Set(timezone),
+        channels: Set(to_json(&channels)),
+        recipient_user_ids: Set(to_json(&recipients)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(model.into())
+}
+
+pub async fn update_route(
+    db: &DatabaseConnection,
+    id: i64,
+    request: UpdateNotificationRouteRequest,
+    known_event_types: &[String],
+) -> Result<NotificationRouteInfo> {
+    let existing = find_route(db, id).await?;
+    let mut active: notification_route::ActiveModel = existing.clone().into();
+
+    if let Some(name) = request.name {
+        active.name = Set(validate_name(&name)?);
+    }
+    if let Some(event_types) = request.event_types {
+        active.event_types = Set(to_json(&validate_event_types(
+            event_types,
+            known_event_types,
+        )?));
+    }
+    if let Some(severities) = request.severities {
+        active.severities = Set(to_json(&validate_severities(severities)?));
+    }
+    if request.start_time.is_some() || request.end_time.is_some() {
+        let (start_time, end_time) = validate_window(
+            request.start_time.or(existing.start_time),
+            request.end_time.or(existing.end_time),
+        )?;
+        active.start_time = Set(start_time);
+        active.end_time = Set(end_time);
+    }
+    if let Some(timezone) = request.timezone {
+        active.timezone = Set(validate_timezone(&timezone)?);
+    }
+    if let Some(channels) = request.channels {
+        active.channels = Set(to_json(&validate_channels(channels)?));
+    }
+    if let Some(recipients) = request.recipient_user_ids {
+        active.recipient_user_ids = Set(to_json(&validate_recipients(db, recipients).await?));
+    }
+    if let Some(enabled) = request.enabled {
+        active.enabled = Set(enabled);
+    }
+    active.updated_at = Set(Utc::now());
+    Ok(active.update(db).await?.into())
+}
+
+pub async fn delete_route(db: &DatabaseConnection, id: i64) -> Result<()> {
+    let route = find_route(db, id).await?;
+    notification_route::Entity::delete_by_id(route.id)
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// Whether `time` falls in the window `[start, end)`, wrapping past midnight
+/// when `end` is before `start`
+pub fn in_window(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// Local date and time of `at` in `timezone` (UTC if the zone is unknown)
+pub fn local_datetime(at: DateTime<Utc>, timezone: &str) -> NaiveDateTime {
+    let offset_seconds = jiff::tz::TimeZone::get(timezone)
+        .ok()
+        .and_then(|tz| {
+            jiff::Timestamp::from_second(at.timestamp())
+                .ok()
+                .map(|ts| tz.to_offset(ts).seconds())
+        })
+        .unwrap_or(0);
+    (at + chrono::Duration::seconds(offset_seconds as i64)).naive_utc()
+}
+
+/// Local wall-clock time of `at` in `timezone` (UTC if the zone is unknown)
+fn local_time(at: DateTime<Utc>, timezone: &str) -> NaiveTime {
+    local_datetime(at, timezone).time()
+}
+
+/// Whether an enabled route applies to an event of `event_type` and
+/// `severity` at `at`
+pub fn route_matches(
+    route: &notification_route::Model,
+    event_type: &str,
+    severity: &str,
+    at: DateTime<Utc>,
+) -> bool {
+    let event_types: Vec<String> = parse_list(&route.event_types);
+    let severities: Vec<String> = parse_list(&route.severities);
+    if !route.enabled
+        || (!event_types.is_empty() && !event_types.iter().any(|e| e == event_type))
+        || (!severities.is_empty() && !severities.iter().any(|s| s == severity))
+    {
+        return false;
+    }
+
+    let window = route
+        .start_time
+        .as_deref()
+        .zip(route.end_time.as_deref())
+        .and_then(|(start, end)| {
+            Some((
+                NaiveTime::parse_from_str(start, TIME_FORMAT).ok()?,
+                NaiveTime::parse_from_str(end, TIME_FORMAT).ok()?,
+            ))
+        });
+    match window {
+        Some((start, end)) => in_window(local_time(at, &route.timezone), start, end),
+        None => true,
+    }
+}
+
+/// Routes that apply to an event at `at`
+pub async fn matching_routes(
+    db: &DatabaseConnection,
+    event_type: &str,
+    severity: &str,
+    at: DateTime<Utc>,
+) -> Result<Vec<notification_route::Model>> {
+    Ok(notification_route::Entity::find()
+        .filter(notification_route::Column::Enabled.eq(true))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|r| route_matches(r, event_type, severity, at))
+        .collect())
+}
+
+/// Channels and recipients of a route; without explicit recipients the
+/// event's user is notified, or all admins for system events
+pub async fn route_targets(
+    db: &DatabaseConnection,
+    route: &notification_route::Model,
+    event_user: Option<i64>,
+) -> Result<(Vec<String>, Vec<i64>)> {
+    let channels: Vec<String> = parse_list(&route.channels);
+    let mut recipients: Vec<i64> = parse_list(&route.recipient_user_ids);
+    if recipients.is_empty() {
+        recipients = match event_user {
+            Some(uid) => vec![uid],
+            None => admin_user_ids(db).await?,
+        };
+    }
+    Ok((channels, recipients))
+}
+
+async fn admin_user_ids(db: &DatabaseConnection) -> Result<Vec<i64>> {
+    Ok(user_role::Entity::find()
+        .join(JoinType::InnerJoin, user_role::Relation::Role.def())
+        .filter(role::Column::Name.eq("admin"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|ur| ur.user_id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, TIME_FORMAT).unwrap()
+    }
+
+    fn route(
+        event_types: &str,
+        severities: &str,
+        window: Option<(&str, &str)>,
+    ) -> notification_route::Model {
+        let now = Utc::now();
+        notification_route::Model {
+            id: 1,
+            name: "Night".to_string(),
+            enabled: true,
+            event_types: event_types.to_string(),
+            severities: severities.to_string(),
+            start_time: window.map(|(s, _)| s.to_string()),
+            end_time: window.map(|(_, e)| e.to_string()),
+            timezone: "UTC".to_string(),
+            channels: r#"["in_app"]"#.to_string(),
+            recipient_user_ids: "[]".to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_in_window() {
+        assert!(in_window(time("09:00"), time("08:00"), time("17:00")));
+        assert!(!in_window(time("17:00"), time("08:00"), time("17:00")));
+        assert!(!in_window(time("07:59"), time("08:00"), time("17:00")));
+
+        // Overnight window wraps midnight
+        assert!(in_window(time("23:30"), time("22:00"), time("07:00")));
+        assert!(in_window(time("03:00"), time("22:00"), time("07:00")));
+        assert!(!in_window(time("12:00"), time("22:00"), time("07:00")));
+    }
+
+    #[test]
+    fn test_route_matches_filters() {
+        let now = Utc::now();
+        let any = route("[]", "[]", None);
+        assert!(route_matches(&any, "login", "info", now));
+
+        let critical = route(r#"["uptime_monitor_down"]"#, r#"["critical"]"#, None);
+        assert!(route_matches(
+            &critical,
+            "uptime_monitor_down",
+            "critical",
+            now
+        ));
+        assert!(!route_matches(
+            &critical,
+            "uptime_monitor_down",
+            "warning",
+            now
+        ));
+        assert!(!route_matches(&critical, "login", "critical", now));
+
+        let mut disabled = any.clone();
+        disabled.enabled = false;
+        assert!(!route_matches(&disabled, "login", "info", now));
+    }
+
+    #[test]
+    fn test_route_matches_window_and_timezone() {
+        let night = route("[]", r#"["warning"]"#, Some(("22:00", "07:00")));
+        assert!(route_matches(
+            &night,
+            "metric_anomaly",
+            "warning",
+            at("2026-10-16T23:00:00Z")
+        ));
+        assert!(!route_matches(
+            &night,
+            "metric_anomaly",
+            "warning",
+            at("2026-10-16T12:00:00Z")
+        ));
+
+        // 21:30 UTC is 23:30 in Amsterdam (CEST, UTC+2)
+        let mut local = night.clone();
+        local.timezone = "Europe/Amsterdam".to_string();
+        assert!(route_matches(
+            &local,
+            "metric_anomaly",
+            "warning",
+            at("2026-10-16T21:30:00Z")
+        ));
+        assert!(!route_matches(
+            &night,
+            "metric_anomaly",
+            "warning",
+            at("2026-10-16T21:30:00Z")
+        ));
+    }
+
+    #[test]
+    fn test_validate_window() {
+        assert_eq!(validate_window(None, None).unwrap(), (None, None));
+        assert_eq!(
+            validate_window(Some("".to_string()), Some(" ".to_string())).unwrap(),
+            (None, None)
+        );
+        assert_eq!(
+            validate_window(Some("7:05".to_string()), Some("22:00".to_string())).unwrap(),
+            (Some("07:05".to_string()), Some("22:00".to_string()))
+        );
+        assert!(validate_window(Some("22:00".to_string()), None).is_err());
+        assert!(validate_window(Some("25:00".to_string()), Some("07:00".to_string())).is_err());
+        assert!(validate_window(Some("07:00".to_string()), Some("07:00".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_validate_channels() {
+        assert!(validate_channels(vec!["in_app".to_string(), "telegram".to_string()]).is_ok());
+        assert!(validate_channels(vec![]).is_err());
+        assert!(validate_channels(vec!["signal".to_string()]).is_err());
+    }
+}
@@ -1,17 +1,80 @@
 #![allow(dead_code)]
 
+//! Telegram provider
+//!
+//! Messages are sent as MarkdownV2. The channel config can route each
+//! severity to a forum topic and attach inline buttons: "Open app" links to
+//! the app under `public_url` (default: Kubarr's external URL, when one is
+//! configured), and "Acknowledge" posts a signed callback to
+//! Kubarr's Telegram webhook (only offered when `webhook_secret` is set).
+//!
+//! ```json
+//! {
+//!   "bot_token": "123:abc",
+//!   "topics": {"warning": 12, "critical": 34},
+//!   "public_url": "https://kubarr.example.com",
+//!   "webhook_secret": "long-random-string"
+//! }
+//! ```
+
+use std::collections::HashMap;
+
 use async_trait::async_trait;
+use hmac::{Hmac, Mac};
 use serde::Deserialize;
+use sha2::Sha256;
+
+use super::{
+    ChannelType, NotificationAction, NotificationMessage, NotificationProvider,
+    NotificationSeverity, SendResult,
+};
+use crate::services::{links, outbound};
 
-use super::{ChannelType, NotificationMessage, NotificationProvider, SendResult};
+pub(crate) const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+/// Prefix of the callback data of an "Acknowledge" button
+const ACK_PREFIX: &str = "ack";
+
+/// Hex characters of the HMAC kept in callback data (Telegram allows 64 bytes)
+const SIGNATURE_LEN: usize = 16;
+
+fn default_true() -> bool {
+    true
+}
 
 #[derive(Debug, Deserialize)]
 pub struct TelegramConfig {
     pub bot_token: String,
+    /// Forum topic (message thread ID) to post each severity in
+    #[serde(default)]
+    pub topics: HashMap<NotificationSeverity, i64>,
+    /// Attach action buttons to messages (default true)
+    #[serde(default = "default_true")]
+    pub buttons: bool,
+    /// Base URL of Kubarr, used for "Open app" buttons
+    #[serde(default)]
+    pub public_url: Option<String>,
+    /// Secret Telegram sends with webhook calls; also signs button callbacks
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+    /// Bot API server (default https://api.telegram.org)
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+/// Action requested by pressing a button
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelegramAction {
+    AcknowledgeAlert(i64),
 }
 
 pub struct TelegramProvider {
     bot_token: String,
+    topics: HashMap<NotificationSeverity, i64>,
+    buttons: bool,
+    public_url: Option<String>,
+    webhook_secret: Option<String>,
+    api_url: String,
     client: reqwest::Client,
 }
 
@@ -22,20 +85,26 @@ impl TelegramProvider {
 
         Ok(Self {
             bot_token: telegram_config.bot_token,
-            client: reqwest::Client::new(),
+            topics: telegram_config.topics,
+            buttons: telegram_config.buttons,
+            public_url: telegram_config
+                .public_url
+                .map(|u| u.trim_end_matches('/').to_string())
+                .filter(|u| !u.is_empty())
+                .or_else(links::configured_external_url),
+            webhook_secret: telegram_config.webhook_secret.filter(|s| !s.is_empty()),
+            api_url: telegram_config
+                .api_url
+                .map(|u| u.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| DEFAULT_API_URL.to_string()),
+            client: outbound::client(),
         })
     }
 
-    async fn send_message(&self, chat_id: &str, text: &str) -> SendResult {
-        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
-
-        let payload = serde_json::json!({
-            "chat_id": chat_id,
-            "text": text,
-            "parse_mode": "HTML"
-        });
+    async fn call(&self, method: &str, payload: &serde_json::Value) -> SendResult {
+        let url = format!("{}/bot{}/{}", self.api_url, self.bot_token, method);
 
-        match self.client.post(&url).json(&payload).send().await {
+        match self.client.post(&url).json(payload).send().await {
             Ok(response) => {
                 if response.status().is_success() {
                     SendResult {
@@ -56,6 +125,100 @@ impl TelegramProvider {
             },
         }
     }
+
+    /// `sendMessage` payload for a notification
+    fn message_payload(&self, message: &NotificationMessage) -> serde_json::Value {
+        let severity_emoji = match message.severity {
+            NotificationSeverity::Info => "ℹ️",
+            NotificationSeverity::Warning => "⚠️",
+            NotificationSeverity::Critical => "🚨",
+        };
+
+        let text = format!(
+            "{} *{}*\n\n{}",
+            severity_emoji,
+            escape_markdown_v2(&message.title),
+            escape_markdown_v2(&message.body)
+        );
+
+        let mut payload = serde_json::json!({
+            "chat_id": message.recipient,
+            "text": text,
+            "parse_mode": "MarkdownV2"
+        });
+        if let Some(topic) = self.topics.get(&message.severity) {
+            payload["message_thread_id"] = serde_json::json!(topic);
+        }
+        let buttons = self.buttons(&message.recipient, &message.actions);
+        if !buttons.is_empty() {
+            payload["reply_markup"] = serde_json::json!({ "inline_keyboard": [buttons] });
+        }
+        payload
+    }
+
+    /// Inline buttons for the actions this provider is configured to offer
+    fn buttons(&self, chat_id: &str, actions: &[NotificationAction]) -> Vec<serde_json::Value> {
+        if !self.buttons {
+            return Vec::new();
+        }
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                NotificationAction::AcknowledgeAlert(alert_id) => {
+                    let secret = self.webhook_secret.as_ref()?;
+                    Some(serde_json::json!({
+                        "text": "Acknowledge",
+                        "callback_data": callback_data(secret, *alert_id, chat_id),
+                    }))
+                }
+                NotificationAction::OpenApp(app_name) => {
+                    let base = self.public_url.as_ref()?;
+                    Some(serde_json::json!({
+                        "text": format!("Open {}", app_name),
+                        "url": format!("{}/{}/", base, app_name),
+                    }))
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a webhook call carries the configured secret
+    pub fn verify_webhook_secret(&self, header: Option<&str>) -> bool {
+        match (&self.webhook_secret, header) {
+            (Some(secret), Some(header)) => {
+                secret.len() == header.len()
+                    && secret
+                        .bytes()
+                        .zip(header.bytes())
+                        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                        == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Decode and verify the callback data of a button pressed in a chat
+    pub fn parse_callback(&self, data: &str, chat_id: &str) -> Option<TelegramAction> {
+        let secret = self.webhook_secret.as_ref()?;
+        let mut parts = data.split(':');
+        let (Some(ACK_PREFIX), Some(id), Some(_), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        let alert_id: i64 = id.parse().ok()?;
+        (callback_data(secret, alert_id, chat_id) == data)
+            .then_some(TelegramAction::AcknowledgeAlert(alert_id))
+    }
+
+    /// Show a short confirmation to the user who pressed a button
+    pub async fn answer_callback(&self, callback_query_id: &str, text: &str) -> SendResult {
+        let payload = serde_json::json!({
+            "callback_query_id": callback_query_id,
+            "text": text,
+        });
+        self.call("answerCallbackQuery", &payload).await
+    }
 }
 
 #[async_trait]
@@ -65,30 +228,185 @@ impl NotificationProvider for TelegramProvider {
     }
 
     async fn send(&self, message: &NotificationMessage) -> SendResult {
-        let severity_emoji = match message.severity {
-            super::NotificationSeverity::Info => "ℹ️",
-            super::NotificationSeverity::Warning => "⚠️",
-            super::NotificationSeverity::Critical => "🚨",
-        };
+        self.call("sendMessage", &self.message_payload(message))
+            .await
+    }
 
-        let text = format!(
-            "{} <b>{}</b>\n\n{}",
-            severity_emoji,
-            html_escape(&message.title),
-            html_escape(&message.body)
+    async fn test(&self, destination: &str) -> SendResult {
+        let text = "✅ *Kubarr Test Notification*\n\nThis is a test notification from Kubarr\\.\n\nIf you received this message, your Telegram notifications are configured correctly\\!";
+        let payload = serde_json::json!({
+            "chat_id": destination,
+            "text": text,
+            "parse_mode": "MarkdownV2"
+        });
+        self.call("sendMessage", &payload).await
+    }
+}
+
+/// Callback data for an "Acknowledge" button, signed for one chat
+///
+/// Format: `ack:<alert id>:<truncated HMAC-SHA256 of "ack:<alert id>:<chat id>">`
+pub fn callback_data(secret: &str, alert_id: i64, chat_id: &str) -> String {
+    let payload = format!("{}:{}", ACK_PREFIX, alert_id);
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}:{}", payload, chat_id).as_bytes());
+    let signature = hex::encode(mac.finalize().into_bytes());
+    format!("{}:{}", payload, &signature[..SIGNATURE_LEN])
+}
+
+/// Escape text for Telegram's MarkdownV2 parse mode
+pub fn escape_markdown_v2(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(
+            c,
+            '_' | '*'
+                | '['
+                | ']'
+                | '('
+                | ')'
+                | '~'
+                | '`'
+                | '>'
+                | '#'
+                | '+'
+                | '-'
+                | '='
+                | '|'
+                | '{'
+                | '}'
+                | '.'
+                | '!'
+                | '\\'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(config: serde_json::Value) -> TelegramProvider {
+        TelegramProvider::from_config(&config).unwrap()
+    }
+
+    fn message(
+        severity: NotificationSeverity,
+        actions: Vec<NotificationAction>,
+    ) -> NotificationMessage {
+        NotificationMessage {
+            recipient: "1001".to_string(),
+            title: "Monitor Down".to_string(),
+            body: "plex.local (10.0.0.5) is down!".to_string(),
+            severity,
+            actions,
+        }
+    }
+
+    #[test]
+    fn test_escape_markdown_v2() {
+        assert_eq!(
+            escape_markdown_v2("v1.2 [beta] (x_y) 50% off!"),
+            "v1\\.2 \\[beta\\] \\(x\\_y\\) 50% off\\!"
         );
+        assert_eq!(escape_markdown_v2("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn test_payload_escapes_and_routes_topic() {
+        let provider = provider(serde_json::json!({
+            "bot_token": "t",
+            "topics": {"critical": 42}
+        }));
 
-        self.send_message(&message.recipient, &text).await
+        let payload = provider.message_payload(&message(NotificationSeverity::Critical, vec![]));
+        assert_eq!(payload["parse_mode"], "MarkdownV2");
+        assert_eq!(payload["message_thread_id"], 42);
+        assert_eq!(
+            payload["text"],
+            "🚨 *Monitor Down*\n\nplex\\.local \\(10\\.0\\.0\\.5\\) is down\\!"
+        );
+        assert!(payload.get("reply_markup").is_none());
+
+        let payload = provider.message_payload(&message(NotificationSeverity::Info, vec![]));
+        assert!(payload.get("message_thread_id").is_none());
     }
 
-    async fn test(&self, destination: &str) -> SendResult {
-        let text = "✅ <b>Kubarr Test Notification</b>\n\nThis is a test notification from Kubarr.\n\nIf you received this message, your Telegram notifications are configured correctly!";
-        self.send_message(destination, text).await
+    #[test]
+    fn test_buttons_need_their_config() {
+        let actions = vec![
+            NotificationAction::AcknowledgeAlert(7),
+            NotificationAction::OpenApp("sonarr".to_string()),
+        ];
+
+        let bare = provider(serde_json::json!({"bot_token": "t"}));
+        let payload =
+            bare.message_payload(&message(NotificationSeverity::Warning, actions.clone()));
+        assert!(payload.get("reply_markup").is_none());
+
+        let full = provider(serde_json::json!({
+            "bot_token": "t",
+            "public_url": "https://kubarr.example.com/",
+            "webhook_secret": "s3cret"
+        }));
+        let payload =
+            full.message_payload(&message(NotificationSeverity::Warning, actions.clone()));
+        let buttons = &payload["reply_markup"]["inline_keyboard"][0];
+        assert_eq!(
+            buttons[0]["callback_data"],
+            callback_data("s3cret", 7, "1001")
+        );
+        assert_eq!(buttons[1]["url"], "https://kubarr.example.com/sonarr/");
+
+        let disabled = provider(serde_json::json!({
+            "bot_token": "t",
+            "buttons": false,
+            "public_url": "https://kubarr.example.com",
+            "webhook_secret": "s3cret"
+        }));
+        let payload = disabled.message_payload(&message(NotificationSeverity::Warning, actions));
+        assert!(payload.get("reply_markup").is_none());
+    }
+
+    #[test]
+    fn test_callback_signature() {
+        let provider = provider(serde_json::json!({"bot_token": "t", "webhook_secret": "s3cret"}));
+        let data = callback_data("s3cret", 7, "1001");
+        assert!(data.len() <= 64);
+
+        assert_eq!(
+            provider.parse_callback(&data, "1001"),
+            Some(TelegramAction::AcknowledgeAlert(7))
+        );
+        // Signed for another chat, tampered with, or signed with another secret
+        assert_eq!(provider.parse_callback(&data, "1002"), None);
+        assert_eq!(
+            provider.parse_callback(&data.replace("ack:7", "ack:8"), "1001"),
+            None
+        );
+        assert_eq!(
+            provider.parse_callback(&callback_data("other", 7, "1001"), "1001"),
+            None
+        );
     }
-}
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
+    #[test]
+    fn test_verify_webhook_secret() {
+        let provider = provider(serde_json::json!({"bot_token": "t", "webhook_secret": "s3cret"}));
+        assert!(provider.verify_webhook_secret(Some("s3cret")));
+        assert!(!provider.verify_webhook_secret(Some("s3cre")));
+        assert!(!provider.verify_webhook_secret(None));
+
+        // An empty secret disables the webhook instead of matching an empty header
+        let no_secret = TelegramProvider::from_config(
+            &serde_json::json!({"bot_token": "t", "webhook_secret": ""}),
+        )
+        .unwrap();
+        assert!(!no_secret.verify_webhook_secret(Some("")));
+    }
 }
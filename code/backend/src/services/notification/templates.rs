@@ -0,0 +1,520 @@
+//! Notification templates
+//!
+//! Admins can override the subject and body an event is sent with, per event
+//! type and channel (`email`, `telegram`, `messagebird` or `in_app`). A
+//! template is plain text with `{{variable}}` placeholders, see
+//! [`VARIABLES`]. Either part can be left unset, in which case the built-in
+//! title or body is used; `{{title}}` and `{{body}}` hold the built-in text so
+//! a template can wrap it instead of replacing it.
+//!
+//! Messages are rendered per recipient: the built-in text, details and
+//! timestamp are in the recipient's locale (see [`crate::services::i18n`]),
+//! while templates themselves are not translated.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Iterable, QueryFilter, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::routing::CHANNEL_IN_APP;
+use super::{format_event_body, format_event_title, locales, ChannelType, NotificationSeverity};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::notification_template;
+use crate::services::i18n::{self, Locale};
+
+/// Variables available to templates, with a description and sample value
+pub const VARIABLES: &[(&str, &str, &str)] = &[
+    ("title", "Built-in title of the event", "App Installed"),
+    (
+        "body",
+        "Built-in message of the event",
+        "App installed by admin: sonarr",
+    ),
+    ("event_type", "Event type", "app_installed"),
+    ("severity", "info, warning or critical", "info"),
+    (
+        "username",
+        "User who caused the event, empty for system events",
+        "admin",
+    ),
+    ("details", "Event details, such as the app name", "sonarr"),
+    (
+        "timestamp",
+        "When the event happened, written in the recipient's locale",
+        "Jan 1, 2026, 12:00 UTC",
+    ),
+    ("locale", "Locale of the recipient: en, nl or de", "en"),
+];
+
+/// Longest subject or body accepted
+const MAX_TEMPLATE_LEN: usize = 4000;
+
+/// A template variable, for the admin UI
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub description: String,
+    pub example: String,
+}
+
+/// The template of an event on one channel
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChannelTemplate {
+    pub channel_type: String,
+    /// Override of the subject, `None` when the built-in title is used
+    pub subject: Option<String>,
+    /// Override of the body, `None` when the built-in body is used
+    pub body: Option<String>,
+    pub updated_at: Option<chrono::DateTime<Utc>>,
+}
+
+/// Templates of an event on every channel
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EventTemplates {
+    pub event_type: String,
+    pub templates: Vec<ChannelTemplate>,
+    pub variables: Vec<TemplateVariable>,
+}
+
+/// Set or clear the template of an event on one channel
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetTemplateRequest {
+    pub channel_type: String,
+    /// `None` or empty falls back to the built-in title
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// `None` or empty falls back to the built-in body
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// Render a template against sample data
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct PreviewTemplateRequest {
+    /// Channel whose saved template is used for parts not given here
+    #[serde(default)]
+    pub channel_type: Option<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Values replacing the sample ones, by variable name
+    #[serde(default)]
+    pub sample: HashMap<String, String>,
+}
+
+/// A rendered notification
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct RenderedTemplate {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Text of one event, with the overrides of each channel
+///
+/// Built once per event and asked for the text of every channel it is
+/// delivered on.
+#[derive(Debug, Clone)]
+pub struct EventContent {
+    pub title: String,
+    pub body: String,
+    action: AuditAction,
+    username: Option<String>,
+    details: Option<String>,
+    time: DateTime<Utc>,
+    variables: HashMap<String, String>,
+    overrides: Vec<notification_template::Model>,
+}
+
+impl EventContent {
+    /// Built-in text only
+    pub fn new(
+        action: &AuditAction,
+        username: Option<&str>,
+        details: Option<&str>,
+        severity: NotificationSeverity,
+    ) -> Self {
+        let title = format_event_title(action);
+        let body = format_event_body(action, username, details);
+        let time = Utc::now();
+        let variables = HashMap::from([
+            ("title".to_string(), title.clone()),
+            ("body".to_string(), body.clone()),
+            ("event_type".to_string(), action.to_string()),
+            ("severity".to_string(), severity.as_str().to_string()),
+            ("username".to_string(), username.unwrap_or("").to_string()),
+            ("details".to_string(), details.unwrap_or("").to_string()),
+            (
+                "timestamp".to_string(),
+                i18n::format_datetime(&time, Locale::En),
+            ),
+            ("locale".to_string(), Locale::En.to_string()),
+        ]);
+        Self {
+            title,
+            body,
+            action: action.clone(),
+            username: username.map(str::to_string),
+            details: details.map(str::to_string),
+            time,
+            variables,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Built-in text plus the event's saved templates
+    pub async fn load(
+        db: &DatabaseConnection,
+        action: &AuditAction,
+        username: Option<&str>,
+        details: Option<&str>,
+        severity: NotificationSeverity,
+    ) -> Result<Self> {
+        let mut content = Self::new(action, username, details, severity);
+        content.overrides = find(db, &action.to_string()).await?;
+        Ok(content)
+    }
+
+    /// Subject and body to send on a channel, in English
+    pub fn for_channel(&self, channel_type: &str) -> (String, String) {
+        self.render_channel(channel_type, &self.title, &self.body, &self.variables)
+    }
+
+    /// Subject and body to send on a channel to a recipient using `locale`
+    pub fn for_recipient(&self, channel_type: &str, locale: Locale) -> (String, String) {
+        if locale == Locale::En {
+            return self.for_channel(channel_type);
+        }
+        let username = self.username.as_deref();
+        let title = locales::event_title(&self.action, locale);
+        let body = locales::event_body(&self.action, username, self.details.as_deref(), locale);
+        let details = self
+            .details
+            .as_deref()
+            .map(|d| locales::localize_details(&self.action, d, locale))
+            .unwrap_or_default();
+
+        let mut variables = self.variables.clone();
+        variables.insert("title".to_string(), title.clone());
+        variables.insert("body".to_string(), body.clone());
+        variables.insert("details".to_string(), details);
+        variables.insert(
+            "timestamp".to_string(),
+            i18n::format_datetime(&self.time, locale),
+        );
+        variables.insert("locale".to_string(), locale.to_string());
+        self.render_channel(channel_type, &title, &body, &variables)
+    }
+
+    fn render_channel(
+        &self,
+        channel_type: &str,
+        title: &str,
+        body: &str,
+        variables: &HashMap<String, String>,
+    ) -> (String, String) {
+        let template = self
+            .overrides
+            .iter()
+            .find(|t| t.channel_type == channel_type);
+        let subject = template
+            .and_then(|t| t.subject.as_deref())
+            .map_or_else(|| title.to_string(), |s| render(s, variables));
+        let body = template
+            .and_then(|t| t.body.as_deref())
+            .map_or_else(|| body.to_string(), |b| render(b, variables));
+        (subject, body)
+    }
+}
+
+/// Channels a template can be set for
+pub fn channel_types() -> Vec<&'static str> {
+    let mut channels: Vec<&'static str> = ChannelType::all().iter().map(|c| c.as_str()).collect();
+    channels.push(CHANNEL_IN_APP);
+    channels
+}
+
+/// Replace every `{{variable}}` with its value
+///
+/// Unknown placeholders are left as they are.
+pub fn render(template: &str, variables: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match variables.get(after[..end].trim()) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Check that a template only uses known variables
+pub fn validate(template: &str) -> Result<()> {
+    if template.len() > MAX_TEMPLATE_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Templates are limited to {} characters",
+            MAX_TEMPLATE_LEN
+        )));
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            return Err(AppError::BadRequest(
+                "Unclosed '{{' in template".to_string(),
+            ));
+        };
+        let name = after[..end].trim();
+        if !VARIABLES.iter().any(|(v, _, _)| *v == name) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown template variable '{}'",
+                name
+            )));
+        }
+        rest = &after[end + 2..];
+    }
+    Ok(())
+}
+
+fn parse_event(event_type: &str) -> Result<AuditAction> {
+    AuditAction::iter()
+        .find(|a| a.to_string() == event_type)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown event type '{}'", event_type)))
+}
+
+fn check_channel(channel_type: &str) -> Result<()> {
+    if channel_types().contains(&channel_type) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "Invalid channel '{}', expected one of: {}",
+            channel_type,
+            channel_types().join(", ")
+        )))
+    }
+}
+
+async fn find(
+    db: &DatabaseConnection,
+    event_type: &str,
+) -> Result<Vec<notification_template::Model>> {
+    Ok(notification_template::Entity::find()
+        .filter(notification_template::Column::EventType.eq(event_type))
+        .all(db)
+        .await?)
+}
+
+/// The templates of an event on every channel
+pub async fn get(db: &DatabaseConnection, event_type: &str) -> Result<EventTemplates> {
+    parse_event(event_type)?;
+    let saved = find(db, event_type).await?;
+    Ok(EventTemplates {
+        event_type: event_type.to_string(),
+        templates: channel_types()
+            .into_iter()
+            .map(|channel| {
+                let template = saved.iter().find(|t| t.channel_type == channel);
+                ChannelTemplate {
+                    channel_type: channel.to_string(),
+                    subject: template.and_then(|t| t.subject.clone()),
+                    body: template.and_then(|t| t.body.clone()),
+                    updated_at: template.map(|t| t.updated_at),
+                }
+            })
+            .collect(),
+        variables: VARIABLES
+            .iter()
+            .map(|(name, description, example)| TemplateVariable {
+                name: name.to_string(),
+                description: description.to_string(),
+                example: example.to_string(),
+            })
+            .collect(),
+    })
+}
+
+/// Set the template of an event on one channel
+///
+/// Clearing both parts removes the override.
+pub async fn set(
+    db: &DatabaseConnection,
+    event_type: &str,
+    request: SetTemplateRequest,
+) -> Result<EventTemplates> {
+    parse_event(event_type)?;
+    check_channel(&request.channel_type)?;
+    let subject = request.subject.filter(|s| !s.trim().is_empty());
+    let body = request.body.filter(|b| !b.trim().is_empty());
+    for template in subject.iter().chain(body.iter()) {
+        validate(template)?;
+    }
+
+    let existing = notification_template::Entity::find()
+        .filter(notification_template::Column::EventType.eq(event_type))
+        .filter(notification_template::Column::ChannelType.eq(&request.channel_type))
+        .one(db)
+        .await?;
+    match (existing, subject.is_none() && body.is_none()) {
+        (Some(existing), true) => {
+            notification_template::Entity::delete_by_id(existing.id)
+                .exec(db)
+                .await?;
+        }
+        (Some(existing), false) => {
+            let mut active: notification_template::ActiveModel = existing.into();
+            active.subject = Set(subject);
+            active.body = Set(body);
+            active.updated_at = Set(Utc::now());
+            active.update(db).await?;
+        }
+        (None, true) => {}
+        (None, false) => {
+            notification_template::ActiveModel {
+                event_type: Set(event_type.to_string()),
+                channel_type: Set(request.channel_type),
+                subject: Set(subject),
+                body: Set(body),
+                updated_at: Set(Utc::now()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    get(db, event_type).await
+}
+
+/// Render an event's template with sample data
+///
+/// Parts not given in the request come from the channel's saved template, or
+/// the built-in text.
+pub async fn preview(
+    db: &DatabaseConnection,
+    event_type: &str,
+    request: PreviewTemplateRequest,
+) -> Result<RenderedTemplate> {
+    let action = parse_event(event_type)?;
+    let sample = |name: &str| {
+        request.sample.get(name).cloned().unwrap_or_else(|| {
+            VARIABLES
+                .iter()
+                .find(|(v, _, _)| *v == name)
+                .map(|(_, _, example)| example.to_string())
+                .unwrap_or_default()
+        })
+    };
+    let username = sample("username");
+    let details = sample("details");
+    let severity = request
+        .sample
+        .get("severity")
+        .map_or(NotificationSeverity::Info, |s| {
+            NotificationSeverity::parse(s)
+        });
+    let mut content = EventContent::new(
+        &action,
+        Some(&username)
+            .filter(|u| !u.is_empty())
+            .map(|u| u.as_str()),
+        Some(&details).filter(|d| !d.is_empty()).map(|d| d.as_str()),
+        severity,
+    );
+    content
+        .variables
+        .insert("timestamp".to_string(), sample("timestamp"));
+    for (name, value) in &request.sample {
+        content.variables.insert(name.clone(), value.clone());
+    }
+
+    let channel = match &request.channel_type {
+        Some(channel) => {
+            check_channel(channel)?;
+            content.overrides = find(db, event_type).await?;
+            channel.clone()
+        }
+        None => String::new(),
+    };
+    for template in request.subject.iter().chain(request.body.iter()) {
+        validate(template)?;
+    }
+    let (subject, body) = content.for_channel(&channel);
+    Ok(RenderedTemplate {
+        subject: request
+            .subject
+            .as_deref()
+            .map_or(subject, |s| render(s, &content.variables)),
+        body: request
+            .body
+            .as_deref()
+            .map_or(body, |b| render(b, &content.variables)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("username".to_string(), "alice".to_string()),
+            ("details".to_string(), "sonarr".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("{{username}} installed {{ details }}", &vars()),
+            "alice installed sonarr"
+        );
+        assert_eq!(render("{{unknown}} and {{", &vars()), "{{unknown}} and {{");
+        assert_eq!(render("no placeholders", &vars()), "no placeholders");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("[Kubarr] {{title}}: {{body}}").is_ok());
+        assert!(validate("{{password}}").is_err());
+        assert!(validate("{{title").is_err());
+        assert!(validate(&"x".repeat(MAX_TEMPLATE_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_for_channel_falls_back_to_built_in() {
+        let mut content = EventContent::new(
+            &AuditAction::AppInstalled,
+            Some("admin"),
+            Some("sonarr"),
+            NotificationSeverity::Info,
+        );
+        content.overrides.push(notification_template::Model {
+            id: 1,
+            event_type: "app_installed".to_string(),
+            channel_type: "email".to_string(),
+            subject: Some("[Kubarr] {{title}}".to_string()),
+            body: None,
+            updated_at: Utc::now(),
+        });
+        let (subject, body) = content.for_channel("email");
+        assert_eq!(subject, "[Kubarr] App Installed");
+        assert_eq!(body, content.body);
+        assert_eq!(
+            content.for_channel("telegram"),
+            (content.title.clone(), content.body.clone())
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Interactive shells in app pods
+//!
+//! `GET /api/apps/{app_name}/exec` upgrades to a WebSocket that is bridged
+//! to a Kubernetes exec session with a terminal attached. Binary frames from
+//! the client are written to the shell's stdin and the terminal's output is
+//! sent back as binary frames. Text frames carry JSON control messages:
+//! `{"type": "resize", "cols": 120, "rows": 40}` resizes the terminal and
+//! `{"type": "stdin", "data": "ls\n"}` is an alternative to binary input.
+//! When the shell exits the server sends `{"type": "exit", "exit_code": 0}`
+//! and closes the socket; closing the socket ends the shell.
+
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket};
+use futures_util::{SinkExt, StreamExt};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
+use kube::api::{AttachedProcess, TerminalSize};
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::k8s::PodStatus;
+use crate::error::{AppError, Result};
+
+/// Command run when the client does not ask for one
+pub const DEFAULT_COMMAND: &str = "/bin/sh";
+
+/// How long to wait for the exit status once the shell's output has ended
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Control message sent by the client in a text frame
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    Resize { cols: u16, rows: u16 },
+    Stdin { data: String },
+}
+
+/// How a session ended, for the audit log
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    /// Exit code of the command, when the cluster reported one
+    pub exit_code: Option<i32>,
+    /// Bytes typed into the shell
+    pub stdin_bytes: u64,
+    /// The client closed the socket before the command exited
+    pub closed_by_client: bool,
+}
+
+/// The command to run, split on whitespace; `/bin/sh` when empty
+pub fn parse_command(command: Option<&str>) -> Vec<String> {
+    let parts: Vec<String> = command
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    if parts.is_empty() {
+        vec![DEFAULT_COMMAND.to_string()]
+    } else {
+        parts
+    }
+}
+
+/// The pod of an app to open a shell in: `requested` if it is one of the
+/// app's pods, otherwise the first running one
+pub fn select_pod(pods: &[PodStatus], requested: Option<&str>) -> Result<String> {
+    match requested {
+        Some(name) => pods
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.name.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Pod '{}' not found", name))),
+        None => pods
+            .iter()
+            .find(|p| p.status == "Running")
+            .map(|p| p.name.clone())
+            .ok_or_else(|| AppError::BadRequest("The app has no running pod".to_string())),
+    }
+}
+
+/// Exit code in an exec status; `0` on success, otherwise the code the
+/// cluster reports as an `ExitCode` cause
+fn exit_code(status: &Status) -> Option<i32> {
+    if status.status.as_deref() == Some("Success") {
+        return Some(0);
+    }
+    status
+        .details
+        .as_ref()?
+        .causes
+        .as_ref()?
+        .iter()
+        .find(|c| c.reason.as_deref() == Some("ExitCode"))
+        .and_then(|c| c.message.as_deref()?.parse().ok())
+}
+
+/// Bridge `socket` to `process` until the command exits or the client
+/// disconnects
+pub async fn run_session(socket: WebSocket, mut process: AttachedProcess) -> SessionSummary {
+    let mut summary = SessionSummary::default();
+    let (Some(mut stdin), Some(mut stdout)) = (process.stdin(), process.stdout()) else {
+        return summary;
+    };
+    let mut resize = process.terminal_size();
+    let status = process.take_status();
+    let (mut sender, mut receiver) = socket.split();
+
+    let mut buf = vec![0u8; 8192];
+    loop {
+        tokio::select! {
+            read = stdout.read(&mut buf) => match read {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let frame = Message::Binary(buf[..n].to_vec().into());
+                    if sender.send(frame).await.is_err() {
+                        summary.closed_by_client = true;
+                        break;
+                    }
+                }
+            },
+            message = receiver.next() => {
+                let input = match message {
+                    Some(Ok(Message::Binary(data))) => data.to_vec(),
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                        Ok(ControlMessage::Stdin { data }) => data.into_bytes(),
+                        Ok(ControlMessage::Resize { cols, rows }) => {
+                            if let Some(resize) = resize.as_mut() {
+                                let size = TerminalSize { width: cols, height: rows };
+                                let _ = resize.send(size).await;
+                            }
+                            continue;
+                        }
+                        Err(_) => continue,
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        summary.closed_by_client = true;
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                };
+                summary.stdin_bytes += input.len() as u64;
+                if stdin.write_all(&input).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    if summary.closed_by_client {
+        process.abort();
+        return summary;
+    }
+
+    if let Some(status) = status {
+        if let Ok(Some(status)) = tokio::time::timeout(STATUS_TIMEOUT, status).await {
+            summary.exit_code = exit_code(&status);
+        }
+    }
+    let exit = serde_json::json!({ "type": "exit", "exit_code": summary.exit_code });
+    let _ = sender.send(Message::Text(exit.to_string().into())).await;
+    let _ = sender.send(Message::Close(None)).await;
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::{StatusCause, StatusDetails};
+
+    fn pod(name: &str, status: &str) -> PodStatus {
+        PodStatus {
+            name: name.to_string(),
+            app: "sonarr".to_string(),
+            namespace: "sonarr".to_string(),
+            status: status.to_string(),
+            ready: status == "Running",
+            restart_count: 0,
+            age: "1h".to_string(),
+            node: None,
+            ip: None,
+            cpu_usage: None,
+            memory_usage: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command(None), vec!["/bin/sh"]);
+        assert_eq!(parse_command(Some("  ")), vec!["/bin/sh"]);
+        assert_eq!(parse_command(Some("bash -l")), vec!["bash", "-l"]);
+    }
+
+    #[test]
+    fn test_select_pod() {
+        let pods = [pod("sonarr-a", "Pending"), pod("sonarr-b", "Running")];
+        assert_eq!(select_pod(&pods, None).unwrap(), "sonarr-b");
+        assert_eq!(select_pod(&pods, Some("sonarr-a")).unwrap(), "sonarr-a");
+        assert!(matches!(
+            select_pod(&pods, Some("postgres-0")),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            select_pod(&pods[..1], None),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_control_messages() {
+        assert_eq!(
+            serde_json::from_str::<ControlMessage>(r#"{"type":"resize","cols":120,"rows":40}"#)
+                .unwrap(),
+            ControlMessage::Resize {
+                cols: 120,
+                rows: 40
+            }
+        );
+        assert!(serde_json::from_str::<ControlMessage>(r#"{"type":"signal"}"#).is_err());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let success = Status {
+            status: Some("Success".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(exit_code(&success), Some(0));
+        let failure = Status {
+            status: Some("Failure".to_string()),
+            details: Some(StatusDetails {
+                causes: Some(vec![StatusCause {
+                    reason: Some("ExitCode".to_string()),
+                    message: Some("130".to_string()),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(exit_code(&failure), Some(130));
+    }
+}
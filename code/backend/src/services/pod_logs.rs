@@ -0,0 +1,250 @@
+//! Following pod logs live
+//!
+//! `GET /api/logs/pods/{pod_name}/stream` reads a pod's log with the
+//! Kubernetes `follow` option and sends each line as a server-sent event.
+//! Lines can be filtered by a minimum severity, which is guessed from the
+//! line itself since apps log in many formats; lines without a recognizable
+//! level, such as stack traces, follow the line before them.
+//!
+//! Lines are handed to the client through a bounded queue. A client that
+//! reads slower than the pod logs does not hold up the log connection: once
+//! [`FOLLOW_BACKLOG`] lines are waiting, newer lines are dropped and the
+//! client is told how many with a `dropped` event when it catches up.
+
+use chrono::{DateTime, Utc};
+use futures_util::io::{AsyncBufRead, AsyncBufReadExt};
+use futures_util::StreamExt;
+use serde::Serialize;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::Level;
+
+/// Lines that may wait for a slow client before newer ones are dropped
+pub const FOLLOW_BACKLOG: usize = 512;
+
+/// One followed log line
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct FollowedLine {
+    /// When the container wrote the line, as reported by Kubernetes
+    pub timestamp: Option<DateTime<Utc>>,
+    pub line: String,
+    pub pod_name: String,
+    pub container: Option<String>,
+    /// "error", "warn", "info", "debug" or "trace", when recognizable
+    pub level: Option<String>,
+}
+
+/// What the follower sends to the client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowEvent {
+    Line(FollowedLine),
+    /// Lines dropped because the client fell behind
+    Dropped(u64),
+}
+
+/// Level names as apps write them, most severe first
+const LEVEL_MARKERS: &[(&str, Level)] = &[
+    ("fatal", Level::ERROR),
+    ("critical", Level::ERROR),
+    ("error", Level::ERROR),
+    ("err", Level::ERROR),
+    ("warning", Level::WARN),
+    ("warn", Level::WARN),
+    ("info", Level::INFO),
+    ("debug", Level::DEBUG),
+    ("trace", Level::TRACE),
+];
+
+/// Guess a line's level from a level word standing on its own, such as
+/// `[Error]`, `|Warn|`, `level=info` or `ERROR:`
+pub fn detect_level(line: &str) -> Option<Level> {
+    let lower = line.to_lowercase();
+    lower
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(12)
+        .find_map(|word| {
+            LEVEL_MARKERS
+                .iter()
+                .find(|(marker, _)| *marker == word)
+                .map(|(_, level)| *level)
+        })
+}
+
+/// Minimum severity filter over consecutive lines
+#[derive(Debug, Clone, Copy)]
+pub struct LevelFilter {
+    min_level: Option<Level>,
+    /// Whether the last line with a level was kept
+    keeping: bool,
+}
+
+impl LevelFilter {
+    pub fn new(min_level: Option<Level>) -> Self {
+        Self {
+            min_level,
+            keeping: true,
+        }
+    }
+
+    /// Whether to send a line with `level`
+    pub fn keep(&mut self, level: Option<Level>) -> bool {
+        let Some(min) = self.min_level else {
+            return true;
+        };
+        if let Some(level) = level {
+            // More severe levels compare as smaller
+            self.keeping = level <= min;
+        }
+        self.keeping
+    }
+}
+
+/// Split the RFC 3339 timestamp Kubernetes puts in front of each line
+fn split_timestamp(raw: &str) -> (Option<DateTime<Utc>>, &str) {
+    match raw.split_once(' ') {
+        Some((stamp, rest)) => match DateTime::parse_from_rfc3339(stamp) {
+            Ok(timestamp) => (Some(timestamp.with_timezone(&Utc)), rest),
+            Err(_) => (None, raw),
+        },
+        None => (None, raw),
+    }
+}
+
+/// Read `reader` line by line into a bounded queue until it ends or the
+/// receiver is dropped
+pub fn follow<R>(
+    reader: R,
+    pod_name: String,
+    container: Option<String>,
+    min_level: Option<Level>,
+) -> mpsc::Receiver<FollowEvent>
+where
+    R: AsyncBufRead + Send + Unpin + 'static,
+{
+    let (tx, rx) = mpsc::channel(FOLLOW_BACKLOG);
+    tokio::spawn(async move {
+        let mut lines = reader.lines();
+        let mut filter = LevelFilter::new(min_level);
+        let mut dropped = 0u64;
+        while let Some(Ok(raw)) = lines.next().await {
+            let (timestamp, line) = split_timestamp(&raw);
+            let level = detect_level(line);
+            if !filter.keep(level) {
+                continue;
+            }
+            if dropped > 0 {
+                match tx.try_send(FollowEvent::Dropped(dropped)) {
+                    Ok(()) => dropped = 0,
+                    Err(TrySendError::Full(_)) => {
+                        dropped += 1;
+                        continue;
+                    }
+                    Err(TrySendError::Closed(_)) => break,
+                }
+            }
+            let event = FollowEvent::Line(FollowedLine {
+                timestamp,
+                line: line.to_string(),
+                pod_name: pod_name.clone(),
+                container: container.clone(),
+                level: level.map(|l| l.as_str().to_lowercase()),
+            });
+            match tx.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => dropped += 1,
+                Err(TrySendError::Closed(_)) => break,
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_level() {
+        assert_eq!(
+            detect_level("[Error] DownloadService: Connection refused"),
+            Some(Level::ERROR)
+        );
+        assert_eq!(
+            detect_level("2024-05-01 10:00:00.0|Warn|RssSync|No indexers"),
+            Some(Level::WARN)
+        );
+        assert_eq!(
+            detect_level(r#"time="..." level=info msg="started""#),
+            Some(Level::INFO)
+        );
+        assert_eq!(detect_level("   at Sonarr.Core.Run()"), None);
+        // Level words inside other words do not count
+        assert_eq!(detect_level("Processing errors.txt"), None);
+    }
+
+    #[test]
+    fn test_level_filter_keeps_continuation_lines() {
+        let mut filter = LevelFilter::new(Some(Level::WARN));
+        assert!(!filter.keep(Some(Level::INFO)));
+        assert!(!filter.keep(None));
+        assert!(filter.keep(Some(Level::ERROR)));
+        assert!(filter.keep(None));
+
+        let mut all = LevelFilter::new(None);
+        assert!(all.keep(Some(Level::TRACE)));
+    }
+
+    #[test]
+    fn test_split_timestamp() {
+        let (timestamp, line) = split_timestamp("2024-05-01T10:00:00.123456789Z [Info] Started");
+        assert!(timestamp.is_some());
+        assert_eq!(line, "[Info] Started");
+        assert_eq!(split_timestamp("no stamp"), (None, "no stamp"));
+    }
+
+    async fn collect(mut rx: mpsc::Receiver<FollowEvent>) -> Vec<FollowEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_follow_filters_lines() {
+        let log = "2024-05-01T10:00:00Z [Info] Started\n\
+                   2024-05-01T10:00:01Z [Error] Failed\n\
+                   2024-05-01T10:00:01Z    at Run()\n";
+        let reader = futures_util::io::Cursor::new(log.as_bytes().to_vec());
+        let events = collect(follow(
+            reader,
+            "sonarr-0".to_string(),
+            None,
+            Some(Level::WARN),
+        ))
+        .await;
+        let lines: Vec<&str> = events
+            .iter()
+            .map(|e| match e {
+                FollowEvent::Line(l) => l.line.as_str(),
+                FollowEvent::Dropped(_) => "",
+            })
+            .collect();
+        assert_eq!(lines, ["[Error] Failed", "   at Run()"]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_drops_lines_for_slow_clients() {
+        let total = FOLLOW_BACKLOG + 10;
+        let log: String = (0..total).map(|i| format!("line {}\n", i)).collect();
+        let reader = futures_util::io::Cursor::new(log.into_bytes());
+        let rx = follow(reader, "sonarr-0".to_string(), None, None);
+
+        // The client reads nothing until the whole log has been read, so the
+        // lines past the backlog are dropped instead of blocking the reader
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let events = collect(rx).await;
+        assert_eq!(events.len(), FOLLOW_BACKLOG);
+        assert!(events.iter().all(|e| matches!(e, FollowEvent::Line(_))));
+    }
+}
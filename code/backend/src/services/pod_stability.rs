@@ -0,0 +1,449 @@
+//! Pod restart and OOM-kill statistics
+//!
+//! Kubernetes only reports a cumulative restart count and the last
+//! termination per container, so a sampler polls all pods every few minutes
+//! and records restarts since the previous observation in
+//! `pod_restart_events`. Statistics are aggregated per app (namespace) over
+//! selectable windows and compared against the preceding window of the same
+//! length to show whether stability is improving.
+//!
+//! When a container restarts several times between two samples, all of those
+//! restarts are attributed to the last termination reason and exit code.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::k8s::ContainerRestartState;
+use super::network_broadcaster::is_excluded_namespace;
+use super::scheduler::PeriodicTask;
+use crate::error::{AppError, Result};
+use crate::models::pod_restart_event;
+use crate::models::prelude::*;
+use crate::state::SharedK8sClient;
+
+/// Termination reason Kubernetes reports for containers killed by the OOM killer
+pub const OOM_REASON: &str = "OOMKilled";
+
+/// Windows reported when none are requested
+pub const DEFAULT_WINDOWS: &[&str] = &["24h", "7d", "30d"];
+
+/// How long restart events are kept
+const RETENTION_DAYS: i64 = 90;
+
+/// Longest selectable window; the preceding window must fit in the retention
+pub const MAX_WINDOW_HOURS: i64 = RETENTION_DAYS * 24 / 2;
+
+/// How often pods are sampled
+const SAMPLE_INTERVAL_SECS: u64 = 5 * 60;
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Direction of an app's restart rate compared to the preceding window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StabilityTrend {
+    Improving,
+    Stable,
+    Worsening,
+}
+
+/// Restarts grouped by exit code and termination reason
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ExitCodeCount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub count: i64,
+}
+
+/// Restart statistics for one window
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct StabilityWindow {
+    /// Window as requested, e.g. "24h" or "7d"
+    pub window: String,
+    pub hours: i64,
+    pub since: DateTime<Utc>,
+    pub restarts: i64,
+    pub oom_kills: i64,
+    /// Restarts in the window of the same length before `since`
+    pub previous_restarts: i64,
+    pub previous_oom_kills: i64,
+    pub trend: StabilityTrend,
+    /// Most frequent first
+    pub exit_codes: Vec<ExitCodeCount>,
+}
+
+/// Response for GET /api/monitoring/apps/{app_name}/stability
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AppStability {
+    pub app_name: String,
+    pub windows: Vec<StabilityWindow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_restart_at: Option<DateTime<Utc>>,
+    /// Current restart state of the app's containers (empty without Kubernetes)
+    pub containers: Vec<ContainerRestartState>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Windows and Trends
+// ============================================================================
+
+/// Parse a window such as "12h" or "7d" into hours
+pub fn parse_window(window: &str) -> Result<i64> {
+    let invalid = || {
+        AppError::BadRequest(format!(
+            "Invalid window '{}': use hours or days, e.g. 24h or 7d",
+            window
+        ))
+    };
+
+    let (value, unit_hours) = if let Some(v) = window.strip_suffix('h') {
+        (v, 1)
+    } else if let Some(v) = window.strip_suffix('d') {
+        (v, 24)
+    } else {
+        return Err(invalid());
+    };
+
+    let hours = value
+        .parse::<i64>()
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(invalid)?
+        .saturating_mul(unit_hours);
+    if hours > MAX_WINDOW_HOURS {
+        return Err(AppError::BadRequest(format!(
+            "Window '{}' exceeds the maximum of {} days",
+            window,
+            MAX_WINDOW_HOURS / 24
+        )));
+    }
+    Ok(hours)
+}
+
+/// Parse a comma-separated list of windows, falling back to the defaults
+pub fn parse_windows(windows: Option<&str>) -> Result<Vec<(String, i64)>> {
+    let requested: Vec<&str> = match windows {
+        Some(list) if !list.trim().is_empty() => list
+            .split(',')
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .collect(),
+        _ => DEFAULT_WINDOWS.to_vec(),
+    };
+
+    requested
+        .into_iter()
+        .map(|w| parse_window(w).map(|hours| (w.to_string(), hours)))
+        .collect()
+}
+
+/// Compare restarts in a window against the preceding window
+pub fn trend(current: i64, previous: i64) -> StabilityTrend {
+    match current.cmp(&previous) {
+        std::cmp::Ordering::Less => StabilityTrend::Improving,
+        std::cmp::Ordering::Equal => StabilityTrend::Stable,
+        std::cmp::Ordering::Greater => StabilityTrend::Worsening,
+    }
+}
+
+fn is_oom(event: &pod_restart_event::Model) -> bool {
+    event.reason.as_deref() == Some(OOM_REASON)
+}
+
+/// Aggregate events into statistics for one window ending at `now`
+pub fn summarize_window(
+    events: &[pod_restart_event::Model],
+    window: &str,
+    hours: i64,
+    now: DateTime<Utc>,
+) -> StabilityWindow {
+    let since = now - chrono::Duration::hours(hours);
+    let previous_since = since - chrono::Duration::hours(hours);
+
+    let mut restarts = 0;
+    let mut oom_kills = 0;
+    let mut previous_restarts = 0;
+    let mut previous_oom_kills = 0;
+    let mut exit_codes: HashMap<(Option<i32>, Option<String>), i64> = HashMap::new();
+
+    for event in events {
+        let count = event.restarts as i64;
+        if event.occurred_at >= since && event.occurred_at <= now {
+            restarts += count;
+            if is_oom(event) {
+                oom_kills += count;
+            }
+            *exit_codes
+                .entry((event.exit_code, event.reason.clone()))
+                .or_default() += count;
+        } else if event.occurred_at >= previous_since && event.occurred_at < since {
+            previous_restarts += count;
+            if is_oom(event) {
+                previous_oom_kills += count;
+            }
+        }
+    }
+
+    let mut exit_codes: Vec<ExitCodeCount> = exit_codes
+        .into_iter()
+        .map(|((exit_code, reason), count)| ExitCodeCount {
+            exit_code,
+            reason,
+            count,
+        })
+        .collect();
+    exit_codes.sort_by(|a, b| b.count.cmp(&a.count).then(a.exit_code.cmp(&b.exit_code)));
+
+    StabilityWindow {
+        window: window.to_string(),
+        hours,
+        since,
+        restarts,
+        oom_kills,
+        previous_restarts,
+        previous_oom_kills,
+        trend: trend(restarts, previous_restarts),
+        exit_codes,
+    }
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Record restarts since the previous observation of each container
+///
+/// A container seen for the first time with a non-zero restart count has all
+/// of its restarts recorded at its last termination time.
+pub async fn record_restarts(
+    db: &DatabaseConnection,
+    states: &[ContainerRestartState],
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let mut recorded = 0;
+
+    for state in states {
+        if state.restart_count <= 0 || is_excluded_namespace(&state.namespace) {
+            continue;
+        }
+
+        let previous = PodRestartEvent::find()
+            .filter(pod_restart_event::Column::AppName.eq(&state.namespace))
+            .filter(pod_restart_event::Column::PodName.eq(&state.pod))
+            .filter(pod_restart_event::Column::ContainerName.eq(&state.container))
+            .order_by_desc(pod_restart_event::Column::Id)
+            .one(db)
+            .await?
+            .map(|e| e.restart_count)
+            .unwrap_or(0);
+
+        let restarts = state.restart_count - previous;
+        if restarts <= 0 {
+            continue;
+        }
+
+        pod_restart_event::ActiveModel {
+            app_name: Set(state.namespace.clone()),
+            pod_name: Set(state.pod.clone()),
+            container_name: Set(state.container.clone()),
+            restart_count: Set(state.restart_count),
+            restarts: Set(restarts),
+            reason: Set(state.last_termination_reason.clone()),
+            exit_code: Set(state.last_exit_code),
+            occurred_at: Set(state.last_terminated_at.unwrap_or(now).min(now)),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        recorded += 1;
+    }
+
+    Ok(recorded)
+}
+
+/// Build the stability report for an app
+pub async fn app_stability(
+    db: &DatabaseConnection,
+    app_name: &str,
+    windows: &[(String, i64)],
+    containers: Vec<ContainerRestartState>,
+    now: DateTime<Utc>,
+) -> Result<AppStability> {
+    let longest = windows.iter().map(|(_, hours)| *hours).max().unwrap_or(0);
+    let earliest = now - chrono::Duration::hours(longest * 2);
+
+    let events = PodRestartEvent::find()
+        .filter(pod_restart_event::Column::AppName.eq(app_name))
+        .filter(pod_restart_event::Column::OccurredAt.gte(earliest))
+        .order_by_asc(pod_restart_event::Column::OccurredAt)
+        .all(db)
+        .await?;
+
+    let last_restart_at = PodRestartEvent::find()
+        .filter(pod_restart_event::Column::AppName.eq(app_name))
+        .order_by_desc(pod_restart_event::Column::OccurredAt)
+        .one(db)
+        .await?
+        .map(|e| e.occurred_at);
+
+    Ok(AppStability {
+        app_name: app_name.to_string(),
+        windows: windows
+            .iter()
+            .map(|(window, hours)| summarize_window(&events, window, *hours, now))
+            .collect(),
+        last_restart_at,
+        containers: containers
+            .into_iter()
+            .filter(|c| c.namespace == app_name)
+            .collect(),
+        generated_at: now,
+    })
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Samples container restart counts and prunes old events
+pub struct PodStabilityTask {
+    pub k8s_client: SharedK8sClient,
+}
+
+#[async_trait]
+impl PeriodicTask for PodStabilityTask {
+    fn name(&self) -> &'static str {
+        "pod_stability"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(SAMPLE_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let states = match self.k8s_client.read().await.as_ref() {
+            Some(client) => client.get_container_restarts(None).await?,
+            None => return Ok(()),
+        };
+
+        let now = Utc::now();
+        let recorded = record_restarts(db, &states, now).await?;
+        if recorded > 0 {
+            tracing::info!(containers = recorded, "Recorded container restarts");
+        }
+
+        PodRestartEvent::delete_many()
+            .filter(
+                pod_restart_event::Column::OccurredAt
+                    .lt(now - chrono::Duration::days(RETENTION_DAYS)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(
+        hours_ago: i64,
+        restarts: i32,
+        reason: &str,
+        exit_code: i32,
+        now: DateTime<Utc>,
+    ) -> pod_restart_event::Model {
+        pod_restart_event::Model {
+            id: 0,
+            app_name: "sonarr".to_string(),
+            pod_name: "sonarr-0".to_string(),
+            container_name: "sonarr".to_string(),
+            restart_count: restarts,
+            restarts,
+            reason: Some(reason.to_string()),
+            exit_code: Some(exit_code),
+            occurred_at: now - chrono::Duration::hours(hours_ago),
+        }
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("12h").unwrap(), 12);
+        assert_eq!(parse_window("7d").unwrap(), 168);
+        assert_eq!(parse_window("45d").unwrap(), MAX_WINDOW_HOURS);
+        assert!(parse_window("46d").is_err());
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("-1d").is_err());
+        assert!(parse_window("7w").is_err());
+        assert!(parse_window("d").is_err());
+    }
+
+    #[test]
+    fn test_parse_windows() {
+        let defaults = parse_windows(None).unwrap();
+        assert_eq!(
+            defaults,
+            vec![
+                ("24h".to_string(), 24),
+                ("7d".to_string(), 168),
+                ("30d".to_string(), 720)
+            ]
+        );
+        assert_eq!(parse_windows(Some("")).unwrap(), defaults);
+        assert_eq!(
+            parse_windows(Some("1h, 2d")).unwrap(),
+            vec![("1h".to_string(), 1), ("2d".to_string(), 48)]
+        );
+        assert!(parse_windows(Some("1h,bad")).is_err());
+    }
+
+    #[test]
+    fn test_trend() {
+        assert_eq!(trend(0, 4), StabilityTrend::Improving);
+        assert_eq!(trend(2, 2), StabilityTrend::Stable);
+        assert_eq!(trend(0, 0), StabilityTrend::Stable);
+        assert_eq!(trend(3, 1), StabilityTrend::Worsening);
+    }
+
+    #[test]
+    fn test_summarize_window() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+        let events = vec![
+            // Previous week: repeated OOM kills
+            event(24 * 10, 4, OOM_REASON, 137, now),
+            event(24 * 8, 2, OOM_REASON, 137, now),
+            // This week: one crash and one OOM kill
+            event(24 * 3, 1, "Error", 1, now),
+            event(2, 1, OOM_REASON, 137, now),
+        ];
+
+        let week = summarize_window(&events, "7d", 168, now);
+        assert_eq!(week.restarts, 2);
+        assert_eq!(week.oom_kills, 1);
+        assert_eq!(week.previous_restarts, 6);
+        assert_eq!(week.previous_oom_kills, 6);
+        assert_eq!(week.trend, StabilityTrend::Improving);
+        assert_eq!(week.exit_codes.len(), 2);
+        assert_eq!(week.exit_codes[0].exit_code, Some(1));
+        assert_eq!(week.exit_codes[1].reason.as_deref(), Some(OOM_REASON));
+
+        let day = summarize_window(&events, "24h", 24, now);
+        assert_eq!(day.restarts, 1);
+        assert_eq!(day.previous_restarts, 0);
+        assert_eq!(day.trend, StabilityTrend::Worsening);
+    }
+}
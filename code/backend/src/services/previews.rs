@@ -0,0 +1,459 @@
+//! Thumbnails for images and videos in storage
+//!
+//! Thumbnails are rendered by ffmpeg as JPEGs and cached on disk under
+//! `KUBARR_PREVIEW_CACHE_DIR`, one directory per file. A cached thumbnail is
+//! keyed by the file's size and modification time, so changing the file
+//! makes the next request render a new one and drop the stale ones.
+//!
+//! ffmpeg and ffprobe run sandboxed: with an empty environment, no stdin,
+//! only the `file` protocol for the input, one thread, a timeout, and at
+//! most [`MAX_CONCURRENT_JOBS`] at a time. A video's poster is the frame at
+//! 10% of its duration.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use crate::config::previews::PreviewsConfig;
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+
+pub static PREVIEWS: Lazy<PreviewGenerator> = Lazy::new(|| PreviewGenerator::new(&CONFIG.previews));
+
+/// ffmpeg/ffprobe runs allowed at the same time
+pub const MAX_CONCURRENT_JOBS: usize = 2;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp", "tif", "tiff"];
+
+const VIDEO_EXTENSIONS: &[&str] = &[
+    "mp4", "m4v", "mkv", "avi", "mov", "webm", "wmv", "mpg", "mpeg", "ts", "flv",
+];
+
+/// Longest seek into a video for its poster frame, in seconds
+const MAX_POSTER_OFFSET: f64 = 300.0;
+
+/// Thumbnail size variant
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewSize {
+    /// 160 pixels
+    Small,
+    /// 320 pixels
+    #[default]
+    Medium,
+    /// 640 pixels
+    Large,
+}
+
+impl PreviewSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+        }
+    }
+
+    /// Longest side of the thumbnail in pixels
+    pub fn pixels(&self) -> u32 {
+        match self {
+            Self::Small => 160,
+            Self::Medium => 320,
+            Self::Large => 640,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Video,
+}
+
+/// The kind of media a file holds, judged by its extension
+pub fn media_kind(path: &Path) -> Option<MediaKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(MediaKind::Image)
+    } else if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+        Some(MediaKind::Video)
+    } else {
+        None
+    }
+}
+
+/// A cached thumbnail
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub path: PathBuf,
+    /// Changes whenever the source file or size variant does
+    pub etag: String,
+}
+
+pub struct PreviewGenerator {
+    ffmpeg: String,
+    ffprobe: String,
+    cache_dir: PathBuf,
+    timeout: Duration,
+    jobs: Semaphore,
+}
+
+impl PreviewGenerator {
+    pub fn new(config: &PreviewsConfig) -> Self {
+        Self {
+            ffmpeg: config.ffmpeg_binary.clone(),
+            ffprobe: config.ffprobe_binary.clone(),
+            cache_dir: PathBuf::from(&config.cache_dir),
+            timeout: Duration::from_secs(config.timeout),
+            jobs: Semaphore::new(MAX_CONCURRENT_JOBS),
+        }
+    }
+
+    /// Cache directory of one file, identified by its storage root and
+    /// path relative to it
+    fn file_dir(&self, root: &str, relative: &str) -> PathBuf {
+        let digest = Sha256::digest(format!("{}/{}", root, relative.trim_matches('/')));
+        self.cache_dir.join(hex::encode(&digest[..16]))
+    }
+
+    /// Where the thumbnail of `file` in its current state is cached
+    pub fn cache_path(
+        &self,
+        root: &str,
+        relative: &str,
+        file: &Path,
+        size: PreviewSize,
+    ) -> Result<PathBuf> {
+        let metadata = std::fs::metadata(file)
+            .map_err(|e| AppError::Internal(format!("Failed to get file metadata: {}", e)))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        Ok(self.file_dir(root, relative).join(format!(
+            "{}-{}-{}.jpg",
+            size.as_str(),
+            modified,
+            metadata.len()
+        )))
+    }
+
+    /// The thumbnail of `file`, rendering it if the cache has none for the
+    /// file's current state
+    pub async fn preview(
+        &self,
+        root: &str,
+        relative: &str,
+        file: &Path,
+        size: PreviewSize,
+    ) -> Result<Preview> {
+        let kind = media_kind(file).ok_or_else(|| {
+            AppError::BadRequest(format!("No preview available for {}", relative))
+        })?;
+        let path = self.cache_path(root, relative, file, size)?;
+        let etag = path
+            .file_stem()
+            .map(|s| format!("\"{}\"", s.to_string_lossy()))
+            .unwrap_or_default();
+
+        if !path.exists() {
+            let _permit =
+                self.jobs.acquire().await.map_err(|_| {
+                    AppError::Internal("Preview generator is shut down".to_string())
+                })?;
+            // Another request may have rendered it while this one waited
+            if !path.exists() {
+                self.render(kind, file, &path, size).await?;
+            }
+        }
+        Ok(Preview { path, etag })
+    }
+
+    /// Render a thumbnail into `target`, replacing the stale thumbnails of
+    /// the same file
+    async fn render(
+        &self,
+        kind: MediaKind,
+        file: &Path,
+        target: &Path,
+        size: PreviewSize,
+    ) -> Result<()> {
+        let dir = target
+            .parent()
+            .ok_or_else(|| AppError::Internal("Invalid preview cache path".to_string()))?;
+        tokio::fs::create_dir_all(dir).await.map_err(|e| {
+            AppError::Internal(format!("Failed to create preview cache directory: {}", e))
+        })?;
+
+        let mut args: Vec<String> = vec!["-nostdin".into(), "-v".into(), "error".into()];
+        if kind == MediaKind::Video {
+            let offset = (self.duration(file).await.unwrap_or(0.0) * 0.1).min(MAX_POSTER_OFFSET);
+            args.extend(["-ss".into(), format!("{:.3}", offset)]);
+        }
+        let pixels = size.pixels();
+        let tmp = dir.join(format!(".{}.tmp.jpg", uuid::Uuid::new_v4()));
+        args.extend([
+            "-protocol_whitelist".into(),
+            "file".into(),
+            "-i".into(),
+            format!("file:{}", file.display()),
+            "-threads".into(),
+            "1".into(),
+            "-frames:v".into(),
+            "1".into(),
+            "-vf".into(),
+            format!(
+                "scale='min({p},iw)':'min({p},ih)':force_original_aspect_ratio=decrease",
+                p = pixels
+            ),
+            "-q:v".into(),
+            "4".into(),
+            "-f".into(),
+            "image2".into(),
+            "-c:v".into(),
+            "mjpeg".into(),
+            "-y".into(),
+            format!("file:{}", tmp.display()),
+        ]);
+
+        let result = self.run(&self.ffmpeg, &args, dir).await;
+        if result.is_err() || !tmp.exists() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            result?;
+            return Err(AppError::Internal(format!(
+                "ffmpeg produced no preview for {}",
+                file.display()
+            )));
+        }
+
+        // Drop thumbnails of earlier versions of the file
+        if let Ok(mut entries) = tokio::fs::read_dir(dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let current = name.ends_with(&suffix(target));
+                if !current && !name.starts_with('.') {
+                    let _ = tokio::fs::remove_file(entry.path()).await;
+                }
+            }
+        }
+
+        tokio::fs::rename(&tmp, target)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to cache preview: {}", e)))
+    }
+
+    /// Duration of a video in seconds, from ffprobe
+    async fn duration(&self, file: &Path) -> Option<f64> {
+        let args = [
+            "-v".to_string(),
+            "error".to_string(),
+            "-protocol_whitelist".to_string(),
+            "file".to_string(),
+            "-show_entries".to_string(),
+            "format=duration".to_string(),
+            "-of".to_string(),
+            "csv=p=0".to_string(),
+            format!("file:{}", file.display()),
+        ];
+        let output = self.run(&self.ffprobe, &args, &self.cache_dir).await.ok()?;
+        output.trim().parse().ok().filter(|d: &f64| d.is_finite())
+    }
+
+    /// Run a sandboxed ffmpeg/ffprobe process and return its stdout
+    async fn run(&self, binary: &str, args: &[String], dir: &Path) -> Result<String> {
+        let child = Command::new(binary)
+            .args(args)
+            .env_clear()
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    AppError::ServiceUnavailable(format!(
+                        "{} is not installed; previews are unavailable",
+                        binary
+                    ))
+                } else {
+                    AppError::Internal(format!("Failed to run {}: {}", binary, e))
+                }
+            })?;
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| AppError::Internal(format!("{} timed out", binary)))?
+            .map_err(|e| AppError::Internal(format!("Failed to run {}: {}", binary, e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Internal(format!(
+                "{} failed: {}",
+                binary,
+                stderr.trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Remove every cached thumbnail of a file, e.g. after it was deleted
+    pub async fn invalidate(&self, root: &str, relative: &str) {
+        let dir = self.file_dir(root, relative);
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove previews of {}: {}", relative, e);
+            }
+        }
+    }
+}
+
+/// The `-<mtime>-<len>.jpg` part of a cache file name, shared by all size
+/// variants of one version of a file
+fn suffix(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match name.find('-') {
+        Some(i) => name[i..].to_string(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_kind() {
+        assert_eq!(media_kind(Path::new("a/b.JPG")), Some(MediaKind::Image));
+        assert_eq!(media_kind(Path::new("movie.mkv")), Some(MediaKind::Video));
+        assert_eq!(media_kind(Path::new("notes.txt")), None);
+        assert_eq!(media_kind(Path::new("README")), None);
+    }
+
+    /// A generator whose "ffmpeg" writes a fixed JPEG to its last argument
+    /// and logs its arguments
+    fn fake_generator(dir: &Path) -> PreviewGenerator {
+        use std::os::unix::fs::PermissionsExt;
+
+        let ffmpeg = dir.join("ffmpeg");
+        let script = format!(
+            "#!/bin/sh\necho \"$@\" >> {log}\nfor last; do :; done\nprintf jpeg > \"${{last#file:}}\"\n",
+            log = dir.join("calls.log").display()
+        );
+        std::fs::write(&ffmpeg, script).unwrap();
+        std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let ffprobe = dir.join("ffprobe");
+        std::fs::write(&ffprobe, "#!/bin/sh\necho 120.0\n").unwrap();
+        std::fs::set_permissions(&ffprobe, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        PreviewGenerator::new(&PreviewsConfig {
+            ffmpeg_binary: ffmpeg.to_string_lossy().to_string(),
+            ffprobe_binary: ffprobe.to_string_lossy().to_string(),
+            cache_dir: dir.join("cache").to_string_lossy().to_string(),
+            timeout: 10,
+        })
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("kubarr_previews_{}_{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_preview_is_cached_and_invalidated_on_change() {
+        let dir = temp_dir("cache");
+        let generator = fake_generator(&dir);
+        let file = dir.join("photo.png");
+        std::fs::write(&file, "v1").unwrap();
+
+        let first = generator
+            .preview("data", "photo.png", &file, PreviewSize::Small)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&first.path).unwrap(), b"jpeg");
+        let again = generator
+            .preview("data", "photo.png", &file, PreviewSize::Small)
+            .await
+            .unwrap();
+        assert_eq!(again.etag, first.etag);
+        let calls = std::fs::read_to_string(dir.join("calls.log")).unwrap();
+        assert_eq!(calls.lines().count(), 1);
+        assert!(calls.contains("min(160,iw)"));
+        assert!(calls.contains("-protocol_whitelist file"));
+
+        // A modified file gets a new thumbnail and the old one is dropped
+        std::fs::write(&file, "version 2").unwrap();
+        let changed = generator
+            .preview("data", "photo.png", &file, PreviewSize::Small)
+            .await
+            .unwrap();
+        assert_ne!(changed.etag, first.etag);
+        assert!(!first.path.exists());
+        assert!(changed.path.exists());
+
+        generator.invalidate("data", "photo.png").await;
+        assert!(!changed.path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_video_poster_seeks_into_video() {
+        let dir = temp_dir("video");
+        let generator = fake_generator(&dir);
+        let file = dir.join("movie.mkv");
+        std::fs::write(&file, "video").unwrap();
+
+        generator
+            .preview("data", "movie.mkv", &file, PreviewSize::Large)
+            .await
+            .unwrap();
+        let calls = std::fs::read_to_string(dir.join("calls.log")).unwrap();
+        assert!(calls.contains("-ss 12.000"), "{}", calls);
+        assert!(calls.contains("min(640,iw)"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_preview_errors() {
+        let dir = temp_dir("errors");
+        let file = dir.join("photo.png");
+        std::fs::write(&file, "png").unwrap();
+
+        let generator = fake_generator(&dir);
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "text").unwrap();
+        assert!(matches!(
+            generator
+                .preview("data", "notes.txt", &text, PreviewSize::Small)
+                .await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let missing = PreviewGenerator::new(&PreviewsConfig {
+            ffmpeg_binary: dir.join("no-ffmpeg").to_string_lossy().to_string(),
+            ffprobe_binary: dir.join("no-ffprobe").to_string_lossy().to_string(),
+            cache_dir: dir.join("cache").to_string_lossy().to_string(),
+            timeout: 10,
+        });
+        assert!(matches!(
+            missing
+                .preview("data", "photo.png", &file, PreviewSize::Small)
+                .await,
+            Err(AppError::ServiceUnavailable(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
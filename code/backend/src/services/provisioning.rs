@@ -0,0 +1,478 @@
+//! App account provisioning
+//!
+//! A provisioning profile lists the apps in which the members of a role get
+//! an account. When an admin approves a user, Kubarr creates the missing
+//! accounts through the app integrations and records them in the user's
+//! account links (see [`super::account_links`]), so a new household member
+//! can sign in to Jellyfin and Jellyseerr without further setup.
+//!
+//! An app account that already has the user's name or email is linked
+//! instead of created. Jellyfin accounts get a random password, which is
+//! emailed to the user. Request manager accounts are imported from the
+//! user's Jellyfin account when they have one, so they sign in with the same
+//! password; otherwise a local account with the user's email is created.
+//!
+//! [`provision_user`] with `dry_run` reports what would be done without
+//! touching any app. What happens to the accounts when the user leaves is
+//! up to [`super::deprovisioning`].
+
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::prelude::{AppIntegration, User, UserRole};
+use crate::models::{provisioning_profile, user, user_role};
+use crate::services::access::unexpired_roles;
+use crate::services::account_links::{
+    self, match_account, SetAccountLink, SOURCE_AUTO, SOURCE_PROVISIONED,
+};
+use crate::services::integrations::requests::seerr_kind;
+use crate::services::integrations::seerr::SeerrAdapter;
+use crate::services::integrations::{build_context, jellyfin};
+use crate::services::reports::recipient;
+use crate::services::security::generate_secure_password;
+use crate::state::{AppState, DbConn};
+
+/// Apps Kubarr can create accounts in, in the order they are provisioned
+pub const PROVISIONABLE_APPS: [&str; 3] = ["jellyfin", "jellyseerr", "overseerr"];
+
+/// Event type credential emails are logged under in the notification log
+pub const CREDENTIALS_EVENT_TYPE: &str = "account_provisioned";
+
+/// Length of generated Jellyfin passwords
+const PASSWORD_LENGTH: usize = 16;
+
+/// What happens to a member's app accounts when they are deactivated or
+/// deleted, from least to most destructive
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OffboardAction {
+    #[default]
+    Keep,
+    /// Jellyfin accounts are disabled; request manager accounts lose all
+    /// permissions
+    Disable,
+    Delete,
+}
+
+impl OffboardAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OffboardAction::Keep => "keep",
+            OffboardAction::Disable => "disable",
+            OffboardAction::Delete => "delete",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "disable" => OffboardAction::Disable,
+            "delete" => OffboardAction::Delete,
+            _ => OffboardAction::Keep,
+        }
+    }
+}
+
+/// The apps members of a role get an account in
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ProvisioningProfile {
+    pub apps: Vec<String>,
+    /// What happens to the accounts when a member is deactivated or deleted
+    #[serde(default)]
+    pub on_deactivate: OffboardAction,
+}
+
+impl From<&provisioning_profile::Model> for ProvisioningProfile {
+    fn from(model: &provisioning_profile::Model) -> Self {
+        Self {
+            apps: serde_json::from_str(&model.apps).unwrap_or_default(),
+            on_deactivate: OffboardAction::parse(&model.on_deactivate),
+        }
+    }
+}
+
+/// Outcome of provisioning one app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisionStatus {
+    /// An account was created and linked
+    Created,
+    /// An existing account with the user's name or email was linked
+    Linked,
+    /// The user already has a linked account
+    AlreadyLinked,
+    /// Dry run: an account would be created
+    WouldCreate,
+    /// Dry run: an existing account would be linked
+    WouldLink,
+    /// The app's integration is missing or disabled
+    NotConfigured,
+    Failed,
+}
+
+/// Result of provisioning one app
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProvisionResult {
+    pub app_name: String,
+    pub status: ProvisionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response for POST /api/users/{user_id}/provision
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProvisioningReport {
+    pub user_id: i64,
+    pub username: String,
+    pub dry_run: bool,
+    pub results: Vec<ProvisionResult>,
+}
+
+/// Body for POST /api/users/{user_id}/provision
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct ProvisionRequest {
+    /// Report what would be done without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// ============================================================================
+// Profile management
+// ============================================================================
+
+/// The profile of a role
+pub async fn get_profile(db: &DbConn, role_id: i64) -> Result<Option<ProvisioningProfile>> {
+    Ok(find_row(db, role_id).await?.as_ref().map(Into::into))
+}
+
+async fn find_row(db: &DbConn, role_id: i64) -> Result<Option<provisioning_profile::Model>> {
+    Ok(provisioning_profile::Entity::find()
+        .filter(provisioning_profile::Column::RoleId.eq(role_id))
+        .one(db)
+        .await?)
+}
+
+/// Create or replace the profile of a role
+pub async fn set_profile(
+    db: &DbConn,
+    role_id: i64,
+    profile: &ProvisioningProfile,
+) -> Result<ProvisioningProfile> {
+    let mut apps: Vec<String> = Vec::new();
+    for app in &profile.apps {
+        let app = app.trim().to_lowercase();
+        if !PROVISIONABLE_APPS.contains(&app.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Cannot provision accounts in '{}'; supported apps are {}",
+                app,
+                PROVISIONABLE_APPS.join(", ")
+            )));
+        }
+        if !apps.contains(&app) {
+            apps.push(app);
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let existing = find_row(db, role_id).await?;
+    let is_new = existing.is_none();
+    let mut model = match existing {
+        Some(existing) => existing.into(),
+        None => provisioning_profile::ActiveModel {
+            role_id: Set(role_id),
+            created_at: Set(now),
+            ..Default::default()
+        },
+    };
+    model.apps = Set(serde_json::to_string(&apps)?);
+    model.on_deactivate = Set(profile.on_deactivate.as_str().to_string());
+    model.updated_at = Set(now);
+    let saved = if is_new {
+        model.insert(db).await?
+    } else {
+        model.update(db).await?
+    };
+    Ok((&saved).into())
+}
+
+/// Remove the profile of a role; returns whether one existed
+pub async fn delete_profile(db: &DbConn, role_id: i64) -> Result<bool> {
+    let result = provisioning_profile::Entity::delete_many()
+        .filter(provisioning_profile::Column::RoleId.eq(role_id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// The apps a user gets an account in from all their roles, in
+/// provisioning order
+pub async fn apps_for_user(db: &DbConn, user_id: i64) -> Result<Vec<String>> {
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .iter()
+        .map(|ur| ur.role_id)
+        .collect();
+    let wanted: Vec<String> = provisioning_profile::Entity::find()
+        .filter(provisioning_profile::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await?
+        .iter()
+        .flat_map(|p| ProvisioningProfile::from(p).apps)
+        .collect();
+    Ok(ordered_apps(&wanted))
+}
+
+/// Deduplicate apps and put them in provisioning order, so a Jellyfin
+/// account exists before request manager accounts are imported from it
+pub fn ordered_apps(apps: &[String]) -> Vec<String> {
+    PROVISIONABLE_APPS
+        .iter()
+        .filter(|app| apps.iter().any(|a| a == *app))
+        .map(|app| app.to_string())
+        .collect()
+}
+
+// ============================================================================
+// Provisioning
+// ============================================================================
+
+/// Create or link the accounts a user's roles ask for
+pub async fn provision_user(
+    state: &AppState,
+    user_id: i64,
+    dry_run: bool,
+) -> Result<ProvisioningReport> {
+    let db = state.get_db().await?;
+    let member = User::find_by_id(user_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let mut results = Vec::new();
+    for app_name in apps_for_user(&db, user_id).await? {
+        let result = match provision_app(state, &member, &app_name, dry_run).await {
+            Ok(result) => result,
+            Err(e) => ProvisionResult {
+                app_name,
+                status: ProvisionStatus::Failed,
+                external_id: None,
+                error: Some(e.to_string()),
+            },
+        };
+        results.push(result);
+    }
+    Ok(ProvisioningReport {
+        user_id,
+        username: member.username,
+        dry_run,
+        results,
+    })
+}
+
+async fn provision_app(
+    state: &AppState,
+    member: &user::Model,
+    app_name: &str,
+    dry_run: bool,
+) -> Result<ProvisionResult> {
+    let db = state.get_db().await?;
+    let result = |status: ProvisionStatus, external_id: Option<String>| ProvisionResult {
+        app_name: app_name.to_string(),
+        status,
+        external_id,
+        error: None,
+    };
+
+    if let Some(link) = account_links::find_link(&db, member.id, app_name).await? {
+        return Ok(result(
+            ProvisionStatus::AlreadyLinked,
+            Some(link.external_id),
+        ));
+    }
+    let configured = AppIntegration::find_by_id(app_name)
+        .one(&db)
+        .await?
+        .is_some_and(|i| i.enabled);
+    if !configured {
+        return Ok(result(ProvisionStatus::NotConfigured, None));
+    }
+
+    // Link an account the user already has
+    let accounts = account_links::list_accounts(state, app_name).await?;
+    if let Some(account) = accounts
+        .into_iter()
+        .find(|a| match_account(a, std::slice::from_ref(member)).is_some())
+    {
+        if account_links::find_by_external(&db, app_name, &account.id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict(format!(
+                "{} account {} is linked to another user",
+                app_name, account.name
+            )));
+        }
+        if dry_run {
+            return Ok(result(ProvisionStatus::WouldLink, Some(account.id)));
+        }
+        let link = SetAccountLink {
+            external_id: account.id,
+            external_username: Some(account.name),
+        };
+        let saved = account_links::set_link(&db, member.id, app_name, &link, SOURCE_AUTO).await?;
+        return Ok(result(ProvisionStatus::Linked, Some(saved.external_id)));
+    }
+
+    if dry_run {
+        return Ok(result(ProvisionStatus::WouldCreate, None));
+    }
+    let ctx = build_context(state, app_name).await?;
+    let link = match seerr_kind(app_name) {
+        Some(kind) => {
+            let adapter = SeerrAdapter::new(kind);
+            let created = match account_links::find_link(&db, member.id, "jellyfin").await? {
+                Some(jellyfin) => adapter
+                    .import_jellyfin_users(&ctx, &[jellyfin.external_id])
+                    .await?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| {
+                        AppError::BadGateway(format!(
+                            "{} did not import the Jellyfin account",
+                            app_name
+                        ))
+                    })?,
+                None => {
+                    adapter
+                        .create_user(&ctx, &member.username, &member.email)
+                        .await?
+                }
+            };
+            SetAccountLink {
+                external_id: created.id.to_string(),
+                external_username: created.display_name,
+            }
+        }
+        None => {
+            let password = generate_secure_password(PASSWORD_LENGTH);
+            let created = jellyfin::create_user(&ctx, &member.username, &password).await?;
+            send_credentials(state, member, &created.name, &password).await;
+            SetAccountLink {
+                external_id: created.id,
+                external_username: Some(created.name),
+            }
+        }
+    };
+    let saved =
+        account_links::set_link(&db, member.id, app_name, &link, SOURCE_PROVISIONED).await?;
+    Ok(result(ProvisionStatus::Created, Some(saved.external_id)))
+}
+
+/// Email a new Jellyfin account's password to its owner; failures are only
+/// logged, since the admin can still reset the password in Jellyfin
+async fn send_credentials(state: &AppState, member: &user::Model, name: &str, password: &str) {
+    let sent = async {
+        let db = state.get_db().await?;
+        let to = recipient(&db, member).await?;
+        state
+            .notification
+            .send_email_to_user(
+                member.id,
+                &to,
+                "Kubarr: your Jellyfin account",
+                &render_credentials(name, password),
+                CREDENTIALS_EVENT_TYPE,
+            )
+            .await
+    }
+    .await;
+    match sent {
+        Ok(result) if result.success => {}
+        Ok(result) => tracing::warn!(
+            "Failed to email Jellyfin password to {}: {}",
+            member.username,
+            result.error.unwrap_or_default()
+        ),
+        Err(e) => tracing::warn!(
+            "Failed to email Jellyfin password to {}: {}",
+            member.username,
+            e
+        ),
+    }
+}
+
+/// Plain-text email with the sign-in details of a new Jellyfin account
+pub fn render_credentials(name: &str, password: &str) -> String {
+    format!(
+        "A Jellyfin account has been created for you.\n\n\
+         Username: {}\nPassword: {}\n\n\
+         Please change the password after signing in.\n",
+        name, password
+    )
+}
+
+/// Provision a newly approved user in the background when any of their
+/// roles has a profile; failures are only logged
+pub fn spawn_provision(state: &AppState, user_id: i64) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let Ok(db) = state.get_db().await else {
+            return;
+        };
+        if !apps_for_user(&db, user_id)
+            .await
+            .is_ok_and(|apps| !apps.is_empty())
+        {
+            return;
+        }
+        match provision_user(&state, user_id, false).await {
+            Ok(report) => {
+                for r in report
+                    .results
+                    .iter()
+                    .filter(|r| r.status == ProvisionStatus::Failed)
+                {
+                    tracing::warn!(
+                        "Failed to provision {} account of {}: {}",
+                        r.app_name,
+                        report.username,
+                        r.error.as_deref().unwrap_or_default()
+                    );
+                }
+            }
+            Err(e) => tracing::warn!("Failed to provision app accounts: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_apps() {
+        let apps = vec![
+            "jellyseerr".to_string(),
+            "jellyfin".to_string(),
+            "jellyseerr".to_string(),
+            "sonarr".to_string(),
+        ];
+        assert_eq!(ordered_apps(&apps), vec!["jellyfin", "jellyseerr"]);
+        assert!(ordered_apps(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_render_credentials() {
+        let body = render_credentials("alice", "s3cret");
+        assert!(body.contains("Username: alice"));
+        assert!(body.contains("Password: s3cret"));
+    }
+}
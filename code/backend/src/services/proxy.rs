@@ -229,7 +229,6 @@ fn tungstenite_to_axum(msg: tungstenite::Message) -> Option<Message> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::http::header as h;
 
     fn headers_with(key: &'static str, value: &str) -> HeaderMap {
         let mut map = HeaderMap::new();
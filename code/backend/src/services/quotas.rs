@@ -0,0 +1,501 @@
+//! Resource quotas
+//!
+//! A quota caps how many apps a user may have installed and the total CPU,
+//! memory and storage those apps request. Quotas are set per user or per
+//! role; a user's own quota overrides their roles', and a role quota applies
+//! to each member separately. A user with several roles gets the most
+//! generous of them, and is unrestricted if any of their roles has no quota.
+//!
+//! The deployment pipeline checks the quota before installing or upgrading
+//! an app for a user, then records the app's footprint against them. The
+//! footprint is the chart's resource requests and volume sizes, including
+//! overrides passed in the install options.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{installed_app, resource_quota, user_role};
+use crate::services::access::unexpired_roles;
+use crate::services::catalog::AppConfig;
+use crate::services::deployment::DeploymentRequest;
+use crate::services::installed_apps;
+use crate::state::DbConn;
+
+/// Limits of a quota; `None` leaves that resource unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct QuotaLimits {
+    pub max_apps: Option<i32>,
+    pub max_cpu_millicores: Option<i64>,
+    pub max_memory_bytes: Option<i64>,
+    pub max_storage_bytes: Option<i64>,
+}
+
+impl From<&resource_quota::Model> for QuotaLimits {
+    fn from(model: &resource_quota::Model) -> Self {
+        Self {
+            max_apps: model.max_apps,
+            max_cpu_millicores: model.max_cpu_millicores,
+            max_memory_bytes: model.max_memory_bytes,
+            max_storage_bytes: model.max_storage_bytes,
+        }
+    }
+}
+
+/// Resources an app requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, ToSchema)]
+pub struct Footprint {
+    pub cpu_millicores: i64,
+    pub memory_bytes: i64,
+    pub storage_bytes: i64,
+}
+
+/// What a user's installed apps add up to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct QuotaUsage {
+    pub apps: i64,
+    pub cpu_millicores: i64,
+    pub memory_bytes: i64,
+    pub storage_bytes: i64,
+}
+
+/// Where a user's quota comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaSource {
+    User,
+    Role,
+}
+
+/// A user's quota and usage
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotaReport {
+    /// Missing when no quota applies
+    pub source: Option<QuotaSource>,
+    pub limits: Option<QuotaLimits>,
+    pub usage: QuotaUsage,
+}
+
+/// The user or role a quota belongs to
+#[derive(Debug, Clone, Copy)]
+pub enum QuotaOwner {
+    User(i64),
+    Role(i64),
+}
+
+impl QuotaOwner {
+    fn column(self) -> (resource_quota::Column, i64) {
+        match self {
+            QuotaOwner::User(id) => (resource_quota::Column::UserId, id),
+            QuotaOwner::Role(id) => (resource_quota::Column::RoleId, id),
+        }
+    }
+}
+
+// ============================================================================
+// Quantities
+// ============================================================================
+
+/// Split a Kubernetes quantity such as `1.5Gi` into its number and suffix
+fn split_quantity(value: &str) -> Option<(f64, &str)> {
+    let value = value.trim();
+    let at = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..at].parse().ok()?;
+    (number >= 0.0).then_some((number, &value[at..]))
+}
+
+/// Parse a CPU quantity (`250m`, `0.5`, `2`) into millicores
+pub fn parse_cpu_millicores(value: &str) -> Option<i64> {
+    let (number, suffix) = split_quantity(value)?;
+    let millicores = match suffix {
+        "m" => number,
+        "" => number * 1000.0,
+        _ => return None,
+    };
+    Some(millicores.round() as i64)
+}
+
+/// Parse a memory or storage quantity (`512Mi`, `1.5Gi`, `1G`) into bytes
+pub fn parse_bytes(value: &str) -> Option<i64> {
+    let (number, suffix) = split_quantity(value)?;
+    let multiplier: f64 = match suffix {
+        "" => 1.0,
+        "k" | "K" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "Ki" => 1024.0,
+        "Mi" => 1024.0 * 1024.0,
+        "Gi" => 1024.0 * 1024.0 * 1024.0,
+        "Ti" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as i64)
+}
+
+/// `1500` -> `1.5 CPU`, `250` -> `250m CPU`
+fn format_cpu(millicores: i64) -> String {
+    if millicores % 100 == 0 && millicores >= 1000 {
+        let cores = format!("{:.1}", millicores as f64 / 1000.0);
+        format!("{} CPU", cores.trim_end_matches(".0"))
+    } else {
+        format!("{}m CPU", millicores)
+    }
+}
+
+/// `1610612736` -> `1.5Gi`
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["", "Ki", "Mi", "Gi", "Ti"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let number = format!("{:.1}", value);
+    format!("{}{}", number.trim_end_matches(".0"), UNITS[unit])
+}
+
+// ============================================================================
+// Footprint and usage
+// ============================================================================
+
+/// Resources installing `request` from `app_config`'s chart will request
+///
+/// Install options override the chart defaults through the same keys the
+/// chart reads: `<chart>.resources.requests.cpu`,
+/// `<chart>.resources.requests.memory` and `persistence.<volume>.size`.
+pub fn footprint(app_config: &AppConfig, request: &DeploymentRequest) -> Result<Footprint> {
+    let option = |key: String| request.custom_config.get(&key).map(String::as_str);
+    let invalid = |key: &str, value: &str| {
+        AppError::BadRequest(format!("'{}' is not a valid quantity for {}", value, key))
+    };
+
+    let cpu_key = format!("{}.resources.requests.cpu", app_config.name);
+    let cpu_millicores = match option(cpu_key.clone()) {
+        Some(value) => parse_cpu_millicores(value).ok_or_else(|| invalid(&cpu_key, value))?,
+        None => parse_cpu_millicores(&app_config.resource_requirements.cpu_request).unwrap_or(0),
+    };
+    let memory_key = format!("{}.resources.requests.memory", app_config.name);
+    let memory_bytes = match option(memory_key.clone()) {
+        Some(value) => parse_bytes(value).ok_or_else(|| invalid(&memory_key, value))?,
+        None => parse_bytes(&app_config.resource_requirements.memory_request).unwrap_or(0),
+    };
+
+    let mut storage_bytes = 0;
+    for volume in &app_config.volumes {
+        let size_key = format!("persistence.{}.size", volume.name);
+        storage_bytes += match option(size_key.clone()) {
+            Some(value) => parse_bytes(value).ok_or_else(|| invalid(&size_key, value))?,
+            None => parse_bytes(&volume.size).unwrap_or(0),
+        };
+    }
+
+    Ok(Footprint {
+        cpu_millicores,
+        memory_bytes,
+        storage_bytes,
+    })
+}
+
+/// Totals of the apps counted against a user, leaving out `except`
+pub async fn usage(db: &DbConn, user_id: i64, except: Option<&str>) -> Result<QuotaUsage> {
+    let apps = InstalledApp::find()
+        .filter(installed_app::Column::InstalledBy.eq(user_id))
+        .all(db)
+        .await?;
+    Ok(apps
+        .iter()
+        .filter(|app| Some(app.app_name.as_str()) != except)
+        .fold(QuotaUsage::default(), |usage, app| QuotaUsage {
+            apps: usage.apps + 1,
+            cpu_millicores: usage.cpu_millicores + app.cpu_millicores,
+            memory_bytes: usage.memory_bytes + app.memory_bytes,
+            storage_bytes: usage.storage_bytes + app.storage_bytes,
+        }))
+}
+
+// ============================================================================
+// Limits
+// ============================================================================
+
+/// Most generous combination of role quotas; `None` beats any limit
+fn combine(quotas: &[QuotaLimits]) -> QuotaLimits {
+    fn widest<T: Ord + Copy>(values: impl Iterator<Item = Option<T>>) -> Option<T> {
+        let mut widest: Option<T> = None;
+        for value in values {
+            let value = value?;
+            widest = Some(widest.map_or(value, |w| w.max(value)));
+        }
+        widest
+    }
+    QuotaLimits {
+        max_apps: widest(quotas.iter().map(|q| q.max_apps)),
+        max_cpu_millicores: widest(quotas.iter().map(|q| q.max_cpu_millicores)),
+        max_memory_bytes: widest(quotas.iter().map(|q| q.max_memory_bytes)),
+        max_storage_bytes: widest(quotas.iter().map(|q| q.max_storage_bytes)),
+    }
+}
+
+/// The quota that applies to a user, if any
+pub async fn effective_limits(
+    db: &DbConn,
+    user_id: i64,
+) -> Result<Option<(QuotaSource, QuotaLimits)>> {
+    if let Some(own) = get_quota(db, QuotaOwner::User(user_id)).await? {
+        return Ok(Some((QuotaSource::User, own)));
+    }
+
+    let role_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|ur| ur.role_id)
+        .collect();
+    if role_ids.is_empty() {
+        return Ok(None);
+    }
+    let role_quotas: HashMap<i64, QuotaLimits> = ResourceQuota::find()
+        .filter(resource_quota::Column::RoleId.is_in(role_ids.clone()))
+        .all(db)
+        .await?
+        .iter()
+        .filter_map(|q| Some((q.role_id?, q.into())))
+        .collect();
+    // A role without a quota leaves its members unrestricted
+    if role_ids.iter().any(|id| !role_quotas.contains_key(id)) {
+        return Ok(None);
+    }
+    let quotas: Vec<QuotaLimits> = role_quotas.into_values().collect();
+    Ok(Some((QuotaSource::Role, combine(&quotas))))
+}
+
+/// A user's quota and current usage
+pub async fn report(db: &DbConn, user_id: i64) -> Result<QuotaReport> {
+    let quota = effective_limits(db, user_id).await?;
+    Ok(QuotaReport {
+        source: quota.as_ref().map(|(source, _)| *source),
+        limits: quota.map(|(_, limits)| limits),
+        usage: usage(db, user_id, None).await?,
+    })
+}
+
+/// Reject installing or upgrading `app_name` if it would take the user over
+/// their quota
+///
+/// An upgraded app's previous footprint is replaced, not added to.
+pub async fn check(db: &DbConn, user_id: i64, app_name: &str, footprint: &Footprint) -> Result<()> {
+    let Some((_, limits)) = effective_limits(db, user_id).await? else {
+        return Ok(());
+    };
+    let used = usage(db, user_id, Some(app_name)).await?;
+
+    if let Some(max_apps) = limits.max_apps {
+        if used.apps + 1 > i64::from(max_apps) {
+            return Err(AppError::Forbidden(format!(
+                "Installing {} would exceed your app quota: {} of {} apps already installed",
+                app_name, used.apps, max_apps
+            )));
+        }
+    }
+    check_limit(
+        app_name,
+        "CPU",
+        footprint.cpu_millicores,
+        used.cpu_millicores,
+        limits.max_cpu_millicores,
+        format_cpu,
+    )?;
+    check_limit(
+        app_name,
+        "memory",
+        footprint.memory_bytes,
+        used.memory_bytes,
+        limits.max_memory_bytes,
+        format_bytes,
+    )?;
+    check_limit(
+        app_name,
+        "storage",
+        footprint.storage_bytes,
+        used.storage_bytes,
+        limits.max_storage_bytes,
+        format_bytes,
+    )
+}
+
+fn check_limit(
+    app_name: &str,
+    label: &str,
+    needed: i64,
+    used: i64,
+    limit: Option<i64>,
+    format: fn(i64) -> String,
+) -> Result<()> {
+    match limit {
+        Some(limit) if used + needed > limit => Err(AppError::Forbidden(format!(
+            "Installing {} would exceed your {} quota: it requests {}, {} of {} is already in use",
+            app_name,
+            label,
+            format(needed),
+            format(used),
+            format(limit)
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Count an installed or upgraded app against the user
+pub async fn record(
+    db: &DbConn,
+    app_name: &str,
+    cloned_from: Option<&str>,
+    user_id: i64,
+    footprint: &Footprint,
+) -> Result<()> {
+    installed_apps::record_install(db, app_name, cloned_from).await?;
+    installed_app::ActiveModel {
+        app_name: Set(app_name.to_string()),
+        installed_by: Set(Some(user_id)),
+        cpu_millicores: Set(footprint.cpu_millicores),
+        memory_bytes: Set(footprint.memory_bytes),
+        storage_bytes: Set(footprint.storage_bytes),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .update(db)
+    .await?;
+    Ok(())
+}
+
+// ============================================================================
+// Quota management
+// ============================================================================
+
+/// The quota set directly on a user or role
+pub async fn get_quota(db: &DbConn, owner: QuotaOwner) -> Result<Option<QuotaLimits>> {
+    let (column, id) = owner.column();
+    Ok(ResourceQuota::find()
+        .filter(column.eq(id))
+        .one(db)
+        .await?
+        .as_ref()
+        .map(Into::into))
+}
+
+/// Create or replace the quota of a user or role
+pub async fn set_quota(
+    db: &DbConn,
+    owner: QuotaOwner,
+    limits: &QuotaLimits,
+) -> Result<QuotaLimits> {
+    let negative = limits.max_apps.is_some_and(|v| v < 0)
+        || [
+            limits.max_cpu_millicores,
+            limits.max_memory_bytes,
+            limits.max_storage_bytes,
+        ]
+        .iter()
+        .any(|v| v.is_some_and(|v| v < 0));
+    if negative {
+        return Err(AppError::BadRequest(
+            "Quota limits cannot be negative".to_string(),
+        ));
+    }
+
+    let (column, id) = owner.column();
+    let now = Utc::now();
+    let existing = ResourceQuota::find().filter(column.eq(id)).one(db).await?;
+    let is_new = existing.is_none();
+    let mut model = match existing {
+        Some(existing) => existing.into(),
+        None => resource_quota::ActiveModel {
+            user_id: Set(matches!(owner, QuotaOwner::User(_)).then_some(id)),
+            role_id: Set(matches!(owner, QuotaOwner::Role(_)).then_some(id)),
+            created_at: Set(now),
+            ..Default::default()
+        },
+    };
+    model.max_apps = Set(limits.max_apps);
+    model.max_cpu_millicores = Set(limits.max_cpu_millicores);
+    model.max_memory_bytes = Set(limits.max_memory_bytes);
+    model.max_storage_bytes = Set(limits.max_storage_bytes);
+    model.updated_at = Set(now);
+    let saved = if is_new {
+        model.insert(db).await?
+    } else {
+        model.update(db).await?
+    };
+    Ok((&saved).into())
+}
+
+/// Remove the quota of a user or role; returns whether one existed
+pub async fn delete_quota(db: &DbConn, owner: QuotaOwner) -> Result<bool> {
+    let (column, id) = owner.column();
+    let result = ResourceQuota::delete_many()
+        .filter(column.eq(id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quantities() {
+        assert_eq!(parse_cpu_millicores("250m"), Some(250));
+        assert_eq!(parse_cpu_millicores("0.5"), Some(500));
+        assert_eq!(parse_cpu_millicores("2"), Some(2000));
+        assert_eq!(parse_cpu_millicores("2Gi"), None);
+        assert_eq!(parse_bytes("512Mi"), Some(512 * 1024 * 1024));
+        assert_eq!(parse_bytes("1.5Gi"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_bytes("1G"), Some(1_000_000_000));
+        assert_eq!(parse_bytes("-1Gi"), None);
+        assert_eq!(parse_bytes("lots"), None);
+    }
+
+    #[test]
+    fn test_format_quantities() {
+        assert_eq!(format_cpu(250), "250m CPU");
+        assert_eq!(format_cpu(1500), "1.5 CPU");
+        assert_eq!(format_cpu(2000), "2 CPU");
+        assert_eq!(format_bytes(512 * 1024 * 1024), "512Mi");
+        assert_eq!(format_bytes(1536 * 1024 * 1024), "1.5Gi");
+    }
+
+    #[test]
+    fn test_combine_takes_most_generous() {
+        let small = QuotaLimits {
+            max_apps: Some(2),
+            max_cpu_millicores: Some(1000),
+            max_memory_bytes: None,
+            max_storage_bytes: Some(10),
+        };
+        let large = QuotaLimits {
+            max_apps: Some(5),
+            max_cpu_millicores: Some(500),
+            max_memory_bytes: Some(100),
+            max_storage_bytes: Some(20),
+        };
+        assert_eq!(
+            combine(&[small, large]),
+            QuotaLimits {
+                max_apps: Some(5),
+                max_cpu_millicores: Some(1000),
+                max_memory_bytes: None,
+                max_storage_bytes: Some(20),
+            }
+        );
+    }
+}
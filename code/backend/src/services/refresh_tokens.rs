@@ -0,0 +1,226 @@
+//! Session refresh tokens
+//!
+//! Session cookies hold short-lived access tokens, valid for
+//! `KUBARR_ACCESS_TOKEN_TTL` seconds (15 minutes by default). Next to each
+//! one is a refresh token, in an HttpOnly cookie only sent to
+//! `/auth/session`, that `POST /auth/session/refresh` trades for a new
+//! access token and a new refresh token. Sessions store only the hash of
+//! their current refresh token and of the one it replaced.
+//!
+//! Every refresh token can be used once. When one that was already
+//! replaced comes back, someone holds a copy: the session, and with it every
+//! token issued for it, is revoked, and the user and the admins get a
+//! `refresh_token_reused` event. The exception is the token replaced in the
+//! last few seconds, which another tab may send while the first refresh is
+//! still on its way back; that gets `409 Conflict` and the session stays.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sha2::{Digest, Sha256};
+
+use super::security::generate_random_string;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::session;
+use crate::state::AppState;
+
+/// Seconds a replaced refresh token is answered with `409 Conflict` instead
+/// of revoking the session
+pub const REUSE_GRACE_SECONDS: i64 = 10;
+
+/// Random bytes in a refresh token
+const SECRET_BYTES: usize = 32;
+
+/// A new refresh token and the hash stored for it
+pub struct IssuedToken {
+    /// `{session_id}.{secret}`, for the refresh cookie
+    pub token: String,
+    pub hash: String,
+}
+
+/// Result of trading in a refresh token
+pub struct Rotation {
+    pub session: session::Model,
+    pub refresh_token: String,
+}
+
+fn hash(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Create a refresh token for a session
+pub fn issue(session_id: &str) -> IssuedToken {
+    let secret = generate_random_string(SECRET_BYTES);
+    IssuedToken {
+        token: format!("{}.{}", session_id, secret),
+        hash: hash(&secret),
+    }
+}
+
+/// Trade a refresh token for a new one, revoking the session if the token
+/// was already used
+pub async fn rotate(
+    state: &AppState,
+    token: &str,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<Rotation> {
+    let (session_id, secret) = token
+        .split_once('.')
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+    let db = state.get_db().await?;
+    let session = Session::find_by_id(session_id)
+        .one(&db)
+        .await?
+        .filter(|s| !s.is_revoked && s.expires_at > now)
+        .ok_or_else(|| AppError::Unauthorized("Session has expired".to_string()))?;
+
+    let presented = hash(secret);
+    if session.refresh_token_hash.as_deref() == Some(presented.as_str()) {
+        let issued = issue(&session.id);
+        // Only one of two racing refreshes gets to replace the token
+        let updated = Session::update_many()
+            .col_expr(
+                session::Column::RefreshTokenHash,
+                Expr::value(issued.hash.clone()),
+            )
+            .col_expr(
+                session::Column::PreviousRefreshTokenHash,
+                Expr::value(presented.clone()),
+            )
+            .col_expr(session::Column::RefreshedAt, Expr::value(now))
+            .filter(session::Column::Id.eq(&session.id))
+            .filter(session::Column::RefreshTokenHash.eq(&presented))
+            .exec(&db)
+            .await?;
+        if updated.rows_affected == 1 {
+            let session = session::Model {
+                refresh_token_hash: Some(issued.hash),
+                previous_refresh_token_hash: Some(presented),
+                refreshed_at: Some(now),
+                ..session
+            };
+            return Ok(Rotation {
+                session,
+                refresh_token: issued.token,
+            });
+        }
+        return Err(refreshed_elsewhere());
+    }
+
+    let just_replaced = session.previous_refresh_token_hash.as_deref() == Some(&presented)
+        && session
+            .refreshed_at
+            .is_some_and(|at| at > now - Duration::seconds(REUSE_GRACE_SECONDS));
+    if just_replaced {
+        return Err(refreshed_elsewhere());
+    }
+
+    revoke_reused(state, &session, ip_address, user_agent).await?;
+    Err(AppError::Unauthorized(
+        "Refresh token was already used; the session has been revoked".to_string(),
+    ))
+}
+
+fn refreshed_elsewhere() -> AppError {
+    AppError::Conflict("Session was just refreshed by another request".to_string())
+}
+
+/// Revoke the session of a reused refresh token and raise the alarm
+async fn revoke_reused(
+    state: &AppState,
+    session: &session::Model,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+) -> Result<()> {
+    let db = state.get_db().await?;
+    Session::update_many()
+        .col_expr(session::Column::IsRevoked, Expr::value(true))
+        .col_expr(
+            session::Column::RefreshTokenHash,
+            Expr::value(Option::<String>::None),
+        )
+        .col_expr(
+            session::Column::PreviousRefreshTokenHash,
+            Expr::value(Option::<String>::None),
+        )
+        .filter(session::Column::Id.eq(&session.id))
+        .exec(&db)
+        .await?;
+
+    let username = User::find_by_id(session.user_id)
+        .one(&db)
+        .await?
+        .map(|u| u.username)
+        .unwrap_or_default();
+    tracing::warn!(
+        user_id = session.user_id,
+        session_id = session.id,
+        "Revoked session after its refresh token was reused"
+    );
+    let _ = state
+        .audit
+        .log_success(
+            AuditAction::RefreshTokenReused,
+            ResourceType::Session,
+            Some(session.id.clone()),
+            Some(session.user_id),
+            Some(username.clone()),
+            Some(serde_json::json!({
+                "session_ip_address": session.ip_address,
+                "session_user_agent": session.user_agent,
+            })),
+            ip_address.clone(),
+            user_agent,
+        )
+        .await;
+    let details = format!(
+        "session of {} from {}, reused from {}",
+        username,
+        session.ip_address.as_deref().unwrap_or("unknown"),
+        ip_address.as_deref().unwrap_or("unknown")
+    );
+    if let Err(e) = state
+        .notification
+        .notify_security(
+            &AuditAction::RefreshTokenReused,
+            session.user_id,
+            &username,
+            Some(&details),
+        )
+        .await
+    {
+        tracing::warn!("Failed to tell user about refresh token reuse: {}", e);
+    }
+    if let Err(e) = state
+        .notification
+        .notify_event(
+            &AuditAction::RefreshTokenReused,
+            None,
+            Some(&username),
+            Some(&details),
+        )
+        .await
+    {
+        tracing::warn!("Failed to send refresh_token_reused notification: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue() {
+        let issued = issue("sid");
+        let (session_id, secret) = issued.token.split_once('.').unwrap();
+        assert_eq!(session_id, "sid");
+        assert_eq!(secret.len(), SECRET_BYTES * 2);
+        assert_eq!(issued.hash, hash(secret));
+        assert_ne!(issue("sid").token, issued.token);
+    }
+}
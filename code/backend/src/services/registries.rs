@@ -0,0 +1,588 @@
+//! Container registries
+//!
+//! Each registry entry describes how images from one registry host are
+//! pulled: through a mirror, for example a pull-through cache on the local
+//! network, and with which credentials. When an app is deployed, its chart
+//! image is rewritten to the mirror of its registry, and the credentials of
+//! all registries are written to a `kubernetes.io/dockerconfigjson` secret in
+//! the app's namespace, which the chart gets as `imagePullSecrets`.
+//!
+//! Images without a registry host, such as `linuxserver/sonarr`, come from
+//! Docker Hub and match the `docker.io` entry.
+
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use chrono::Utc;
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::registry;
+use crate::services::catalog::AppConfig;
+use crate::services::integrations::http_client;
+use crate::services::K8sApi;
+use crate::state::DbConn;
+
+/// Name of the pull secret written to app namespaces
+pub const PULL_SECRET_NAME: &str = "kubarr-registry-credentials";
+
+/// Registry of images that do not name one
+const DOCKER_HUB: &str = "docker.io";
+
+/// Registry to add or replace
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct RegistryRequest {
+    /// Registry host as it appears in image names, e.g. `ghcr.io`
+    pub host: String,
+    /// Host and optional path prefix to pull from instead, e.g.
+    /// `harbor.lan/dockerhub`
+    pub mirror: Option<String>,
+    pub username: Option<String>,
+    /// Leave out to keep the stored password of the same username
+    pub password: Option<String>,
+    /// The registry, or its mirror, is served over plain HTTP
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+/// A registry, without its password
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegistryInfo {
+    pub id: i64,
+    pub host: String,
+    pub mirror: Option<String>,
+    pub username: Option<String>,
+    pub has_password: bool,
+    pub insecure: bool,
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+impl From<registry::Model> for RegistryInfo {
+    fn from(model: registry::Model) -> Self {
+        Self {
+            id: model.id,
+            host: model.host,
+            mirror: model.mirror,
+            username: model.username,
+            has_password: model.password.is_some(),
+            insecure: model.insecure,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+/// Outcome of a connectivity test
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegistryTest {
+    /// The registry API endpoint that was asked
+    pub url: String,
+    pub reachable: bool,
+    /// Whether the stored credentials were accepted; absent without
+    /// credentials
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authenticated: Option<bool>,
+    /// HTTP status of the registry's answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+/// List registries by host
+pub async fn list(db: &DbConn) -> Result<Vec<RegistryInfo>> {
+    Ok(Registry::find()
+        .order_by_asc(registry::Column::Host)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// Add a registry; each host can be added once
+pub async fn create(db: &DbConn, request: RegistryRequest) -> Result<RegistryInfo> {
+    let host = normalize_host(&request.host)?;
+    if find_by_host(db, &host).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Registry '{}' already exists",
+            host
+        )));
+    }
+    let (username, password) = credentials(&request, None)?;
+    let now = Utc::now();
+    let model = registry::ActiveModel {
+        host: Set(host),
+        mirror: Set(request
+            .mirror
+            .as_deref()
+            .map(normalize_mirror)
+            .transpose()?),
+        username: Set(username),
+        password: Set(password),
+        insecure: Set(request.insecure),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(model.into())
+}
+
+/// Replace a registry's settings
+pub async fn update(db: &DbConn, id: i64, request: RegistryRequest) -> Result<RegistryInfo> {
+    let existing = find(db, id).await?;
+    let host = normalize_host(&request.host)?;
+    if let Some(other) = find_by_host(db, &host).await? {
+        if other.id != id {
+            return Err(AppError::Conflict(format!(
+                "Registry '{}' already exists",
+                host
+            )));
+        }
+    }
+    let (username, password) = credentials(&request, Some(&existing))?;
+    let mut model: registry::ActiveModel = existing.into();
+    model.host = Set(host);
+    model.mirror = Set(request
+        .mirror
+        .as_deref()
+        .map(normalize_mirror)
+        .transpose()?);
+    model.username = Set(username);
+    model.password = Set(password);
+    model.insecure = Set(request.insecure);
+    model.updated_at = Set(Utc::now());
+    Ok(model.update(db).await?.into())
+}
+
+/// Remove a registry, returning what was removed
+///
+/// Pull secrets already written to app namespaces are replaced on the apps'
+/// next deployment.
+pub async fn delete(db: &DbConn, id: i64) -> Result<registry::Model> {
+    let existing = find(db, id).await?;
+    Registry::delete_by_id(id).exec(db).await?;
+    Ok(existing)
+}
+
+async fn find(db: &DbConn, id: i64) -> Result<registry::Model> {
+    Registry::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Registry not found".to_string()))
+}
+
+async fn find_by_host(db: &DbConn, host: &str) -> Result<Option<registry::Model>> {
+    Ok(Registry::find()
+        .filter(registry::Column::Host.eq(host))
+        .one(db)
+        .await?)
+}
+
+/// Username and password to store, keeping the stored password when only
+/// the other settings change
+fn credentials(
+    request: &RegistryRequest,
+    existing: Option<&registry::Model>,
+) -> Result<(Option<String>, Option<String>)> {
+    let username = request
+        .username
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty());
+    let Some(username) = username else {
+        if request.password.is_some() {
+            return Err(AppError::BadRequest(
+                "A password needs a username".to_string(),
+            ));
+        }
+        return Ok((None, None));
+    };
+    let password = match &request.password {
+        Some(password) => Some(password.clone()),
+        None => existing
+            .filter(|e| e.username.as_deref() == Some(username))
+            .and_then(|e| e.password.clone()),
+    };
+    Ok((Some(username.to_string()), password))
+}
+
+/// Lowercase `host[:port]`, with Docker Hub's aliases mapped to `docker.io`
+pub fn normalize_host(host: &str) -> Result<String> {
+    let host = host.trim().trim_end_matches('/').to_lowercase();
+    if !is_host(&host) {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is not a registry host; give it without scheme or path, e.g. ghcr.io",
+            host
+        )));
+    }
+    Ok(match host.as_str() {
+        "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            DOCKER_HUB.to_string()
+        }
+        _ => host,
+    })
+}
+
+/// Lowercase `host[:port][/path]` of a mirror
+fn normalize_mirror(mirror: &str) -> Result<String> {
+    let mirror = mirror.trim().trim_end_matches('/').to_lowercase();
+    let (host, path) = mirror.split_once('/').unwrap_or((&mirror, ""));
+    let path_ok = path.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    });
+    if !is_host(host) || (!path.is_empty() && !path_ok) {
+        return Err(AppError::BadRequest(format!(
+            "'{}' is not a mirror; give a host and optional path without scheme, e.g. harbor.lan/dockerhub",
+            mirror
+        )));
+    }
+    Ok(mirror)
+}
+
+fn is_host(host: &str) -> bool {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) => (name, Some(port)),
+        None => (host, None),
+    };
+    let name_ok = !name.is_empty()
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        });
+    name_ok && port.is_none_or(|p| p.parse::<u16>().is_ok())
+}
+
+/// Split an image repository (without tag) into its registry host and path
+///
+/// The first path segment is a registry host when it contains a dot or a
+/// port, or is `localhost`; otherwise the image comes from Docker Hub, where
+/// single-segment names live under `library/`.
+pub fn split_repository(repository: &str) -> (String, String) {
+    match repository.split_once('/') {
+        Some((first, rest))
+            if first.contains('.') || first.contains(':') || first == "localhost" =>
+        {
+            let host = normalize_host(first).unwrap_or_else(|_| first.to_string());
+            (host, rest.to_string())
+        }
+        Some(_) => (DOCKER_HUB.to_string(), repository.to_string()),
+        None => (DOCKER_HUB.to_string(), format!("library/{}", repository)),
+    }
+}
+
+/// An image reference without its tag or digest
+pub fn image_repository(image: &str) -> &str {
+    let image = image.split_once('@').map_or(image, |(name, _)| name);
+    match image.rfind(':') {
+        Some(colon) if !image[colon..].contains('/') => &image[..colon],
+        _ => image,
+    }
+}
+
+/// `repository` pulled through the mirror of its registry, if it has one
+pub fn mirror_repository(repository: &str, registries: &[registry::Model]) -> Option<String> {
+    let (host, path) = split_repository(repository);
+    let mirror = registries
+        .iter()
+        .find(|r| r.host == host)?
+        .mirror
+        .as_deref()?;
+    Some(format!("{}/{}", mirror, path))
+}
+
+/// Host a registry's images are actually pulled from
+fn pull_host(registry: &registry::Model) -> &str {
+    match registry.mirror.as_deref() {
+        Some(mirror) => mirror.split('/').next().unwrap_or(mirror),
+        None => &registry.host,
+    }
+}
+
+/// Docker config with the credentials of every registry that has them
+fn docker_config(registries: &[registry::Model]) -> Option<serde_json::Value> {
+    let mut auths = serde_json::Map::new();
+    for registry in registries {
+        let (Some(username), Some(password)) = (&registry.username, &registry.password) else {
+            continue;
+        };
+        let server = match pull_host(registry) {
+            DOCKER_HUB => "https://index.docker.io/v1/".to_string(),
+            host => host.to_string(),
+        };
+        let auth =
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        auths.insert(
+            server,
+            serde_json::json!({"username": username, "password": password, "auth": auth}),
+        );
+    }
+    (!auths.is_empty()).then(|| serde_json::json!({ "auths": auths }))
+}
+
+/// Helm values that pull an app's image through its registry mirror and
+/// give it the pull secret, which is written to `namespace` first
+pub async fn deployment_values(
+    db: &DbConn,
+    k8s: &dyn K8sApi,
+    app_config: &AppConfig,
+    namespace: &str,
+) -> Result<Vec<String>> {
+    let registries = Registry::find().all(db).await?;
+    let mut values = Vec::new();
+    if registries.is_empty() {
+        return Ok(values);
+    }
+
+    let repository = image_repository(&app_config.container_image);
+    if let Some(mirrored) = mirror_repository(repository, &registries) {
+        values.push(format!("{}.image.repository={}", app_config.name, mirrored));
+    }
+
+    if let Some(config) = docker_config(&registries) {
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some(PULL_SECRET_NAME.to_string()),
+                namespace: Some(namespace.to_string()),
+                labels: Some(BTreeMap::from([(
+                    "app.kubernetes.io/managed-by".to_string(),
+                    "kubarr".to_string(),
+                )])),
+                ..Default::default()
+            },
+            type_: Some("kubernetes.io/dockerconfigjson".to_string()),
+            data: Some(BTreeMap::from([(
+                ".dockerconfigjson".to_string(),
+                k8s_openapi::ByteString(config.to_string().into_bytes()),
+            )])),
+            ..Default::default()
+        };
+        k8s.replace_secret(namespace, secret).await?;
+        values.push(format!("imagePullSecrets[0].name={}", PULL_SECRET_NAME));
+    }
+    Ok(values)
+}
+
+/// Ask a registry's API endpoint whether it is up and takes the stored
+/// credentials
+///
+/// Follows the registry's bearer token challenge when it answers `401`, as
+/// Docker Hub, GHCR and Harbor do.
+pub async fn test(db: &DbConn, id: i64) -> Result<RegistryTest> {
+    let registry = find(db, id).await?;
+    let host = match pull_host(&registry) {
+        DOCKER_HUB => "registry-1.docker.io",
+        host => host,
+    };
+    let scheme = if registry.insecure { "http" } else { "https" };
+    let url = format!("{}://{}/v2/", scheme, host);
+    let credentials = registry
+        .username
+        .as_deref()
+        .zip(registry.password.as_deref());
+
+    let mut request = http_client().get(&url);
+    if let Some((username, password)) = credentials {
+        request = request.basic_auth(username, Some(password));
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return Ok(RegistryTest {
+                url,
+                reachable: false,
+                authenticated: None,
+                status: None,
+                message: format!("Registry could not be reached: {}", e),
+            })
+        }
+    };
+    let status = response.status();
+    let result = |authenticated: Option<bool>, message: &str| RegistryTest {
+        url: url.clone(),
+        reachable: true,
+        authenticated,
+        status: Some(status.as_u16()),
+        message: message.to_string(),
+    };
+
+    if status.is_success() {
+        return Ok(result(credentials.map(|_| true), "Registry is reachable"));
+    }
+    if status != reqwest::StatusCode::UNAUTHORIZED {
+        return Ok(result(None, &format!("Registry answered {}", status)));
+    }
+    let Some((username, password)) = credentials else {
+        return Ok(result(
+            None,
+            "Registry is reachable; it needs credentials or a token for pulls",
+        ));
+    };
+    let challenge = response
+        .headers()
+        .get(reqwest::header::WWW_AUTHENTICATE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(bearer_challenge);
+    let Some((realm, service)) = challenge else {
+        return Ok(result(Some(false), "Registry rejected the credentials"));
+    };
+
+    let mut token_request = http_client()
+        .get(&realm)
+        .basic_auth(username, Some(password));
+    if let Some(service) = service {
+        token_request = token_request.query(&[("service", service)]);
+    }
+    match token_request.send().await {
+        Ok(token) if token.status().is_success() => Ok(result(
+            Some(true),
+            "Registry is reachable and accepts the credentials",
+        )),
+        Ok(token) => Ok(result(
+            Some(false),
+            &format!(
+                "Token service rejected the credentials ({})",
+                token.status()
+            ),
+        )),
+        Err(e) => Ok(result(
+            Some(false),
+            &format!("Token service could not be reached: {}", e),
+        )),
+    }
+}
+
+/// Realm and service of a `Bearer realm="...",service="..."` challenge
+fn bearer_challenge(header: &str) -> Option<(String, Option<String>)> {
+    let params = header.strip_prefix("Bearer ")?;
+    let mut realm = None;
+    let mut service = None;
+    for param in params.split(',') {
+        let Some((key, value)) = param.trim().split_once('=') else {
+            continue;
+        };
+        let value = value.trim_matches('"').to_string();
+        match key {
+            "realm" => realm = Some(value),
+            "service" => service = Some(value),
+            _ => {}
+        }
+    }
+    Some((realm?, service))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(host: &str, mirror: Option<&str>, credentials: bool) -> registry::Model {
+        registry::Model {
+            id: 1,
+            host: host.to_string(),
+            mirror: mirror.map(str::to_string),
+            username: credentials.then(|| "kubarr".to_string()),
+            password: credentials.then(|| "secret".to_string()),
+            insecure: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_split_repository() {
+        assert_eq!(
+            split_repository("linuxserver/sonarr"),
+            ("docker.io".to_string(), "linuxserver/sonarr".to_string())
+        );
+        assert_eq!(
+            split_repository("nginx"),
+            ("docker.io".to_string(), "library/nginx".to_string())
+        );
+        assert_eq!(
+            split_repository("ghcr.io/hotio/sonarr"),
+            ("ghcr.io".to_string(), "hotio/sonarr".to_string())
+        );
+        assert_eq!(
+            split_repository("localhost:5000/app"),
+            ("localhost:5000".to_string(), "app".to_string())
+        );
+        assert_eq!(
+            image_repository("linuxserver/sonarr:4.0"),
+            "linuxserver/sonarr"
+        );
+        assert_eq!(
+            image_repository("registry.lan:5000/app"),
+            "registry.lan:5000/app"
+        );
+        assert_eq!(image_repository("app@sha256:abc"), "app");
+    }
+
+    #[test]
+    fn test_mirror_repository() {
+        let registries = [
+            registry("docker.io", Some("harbor.lan/hub"), false),
+            registry("ghcr.io", None, true),
+        ];
+        assert_eq!(
+            mirror_repository("linuxserver/sonarr", &registries).as_deref(),
+            Some("harbor.lan/hub/linuxserver/sonarr")
+        );
+        assert_eq!(mirror_repository("ghcr.io/hotio/sonarr", &registries), None);
+        assert_eq!(mirror_repository("quay.io/app", &registries), None);
+    }
+
+    #[test]
+    fn test_docker_config_uses_pull_host() {
+        assert!(docker_config(&[registry("ghcr.io", None, false)]).is_none());
+        let config = docker_config(&[
+            registry("docker.io", None, true),
+            registry("ghcr.io", Some("harbor.lan:8443/ghcr"), true),
+        ])
+        .unwrap();
+        let auths = config["auths"].as_object().unwrap();
+        assert_eq!(
+            auths.keys().collect::<Vec<_>>(),
+            vec!["harbor.lan:8443", "https://index.docker.io/v1/"]
+        );
+        assert_eq!(auths["harbor.lan:8443"]["auth"], "a3ViYXJyOnNlY3JldA==");
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host(" GHCR.io/ ").unwrap(), "ghcr.io");
+        assert_eq!(normalize_host("index.docker.io").unwrap(), "docker.io");
+        assert!(normalize_host("https://ghcr.io").is_err());
+        assert!(normalize_host("ghcr.io/hotio").is_err());
+        assert!(normalize_mirror("harbor.lan/hub").is_ok());
+        assert!(normalize_mirror("harbor.lan//hub").is_err());
+    }
+
+    #[test]
+    fn test_bearer_challenge() {
+        assert_eq!(
+            bearer_challenge(
+                r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io""#
+            ),
+            Some((
+                "https://auth.docker.io/token".to_string(),
+                Some("registry.docker.io".to_string())
+            ))
+        );
+        assert_eq!(bearer_challenge(r#"Basic realm="x""#), None);
+    }
+}
@@ -0,0 +1,939 @@
+//! Scheduled summary reports
+//!
+//! Admins opt in through a [`report_subscription`] row to receive a weekly or
+//! monthly summary by email: app health, uptime, storage trend, the most
+//! active users, failed sign-ins and a pending Kubarr update.
+//! [`ReportTask`] sends each subscriber's report at the configured hour in
+//! their own time zone, on Mondays (weekly) or the 1st of the month
+//! (monthly). A report missed while Kubarr was down is sent once it is back.
+//!
+//! Subscribers must still hold `system.manage` when a report goes out;
+//! reports are delivered through the email channel only.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Months, Utc};
+use sea_orm::sea_query::{Alias, Expr};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::access::get_user_permissions;
+use super::notification::routing::validate_timezone;
+use super::notification::{ChannelType, NotificationService};
+use super::scheduler::PeriodicTask;
+use super::updates::{AvailableRelease, UPDATES};
+use super::victoriametrics::query_vm_scalar;
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, SystemManage};
+use crate::models::audit_log::{self, AuditAction};
+use crate::models::prelude::*;
+use crate::models::{report_subscription, user, user_notification_pref};
+
+/// Event type reports are logged under in the notification log
+pub const REPORT_EVENT_TYPE: &str = "scheduled_report";
+
+/// Users and failed sign-in sources listed in a report
+const TOP_ENTRIES: u64 = 5;
+
+/// Local hour reports are sent at unless the subscriber picks another
+const DEFAULT_SEND_HOUR: i32 = 8;
+
+/// How often a report is sent and how far back it looks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFrequency {
+    #[default]
+    Weekly,
+    Monthly,
+}
+
+impl ReportFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFrequency::Weekly => "weekly",
+            ReportFrequency::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "weekly" => Some(ReportFrequency::Weekly),
+            "monthly" => Some(ReportFrequency::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Start of the period a report ending at `to` covers
+    fn start(&self, to: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            ReportFrequency::Weekly => to - chrono::Duration::days(7),
+            ReportFrequency::Monthly => to.checked_sub_months(Months::new(1)).unwrap_or(to),
+        }
+    }
+
+    /// The day a report is due in the week or month of `day`
+    fn anchor(&self, day: jiff::civil::Date) -> jiff::civil::Date {
+        match self {
+            ReportFrequency::Weekly => day
+                .checked_sub(jiff::Span::new().days(day.weekday().to_monday_zero_offset()))
+                .unwrap_or(day),
+            ReportFrequency::Monthly => day.first_of_month(),
+        }
+    }
+
+    /// The anchor day `n` periods after `day`
+    fn shift(&self, day: jiff::civil::Date, n: i64) -> Option<jiff::civil::Date> {
+        let span = match self {
+            ReportFrequency::Weekly => jiff::Span::new().weeks(n),
+            ReportFrequency::Monthly => jiff::Span::new().months(n),
+        };
+        day.checked_add(span).ok()
+    }
+}
+
+/// The last send time at or before `now` and the next one after it, for a
+/// report sent at `send_hour` in `timezone`
+pub fn send_times(
+    frequency: ReportFrequency,
+    timezone: &str,
+    send_hour: i32,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let tz = jiff::tz::TimeZone::get(timezone).ok()?;
+    let hour = i8::try_from(send_hour)
+        .ok()
+        .filter(|h| (0..24).contains(h))?;
+    let today = jiff::Timestamp::from_second(now.timestamp())
+        .ok()?
+        .to_zoned(tz.clone())
+        .date();
+    let anchor = frequency.anchor(today);
+    let send_time = |n: i64| -> Option<DateTime<Utc>> {
+        let day = frequency.shift(anchor, n)?;
+        let at = day.at(hour, 0, 0, 0).to_zoned(tz.clone()).ok()?;
+        DateTime::from_timestamp(at.timestamp().as_second(), 0)
+    };
+
+    let current = send_time(0)?;
+    if current <= now {
+        Some((current, send_time(1)?))
+    } else {
+        Some((send_time(-1)?, current))
+    }
+}
+
+/// Format `at` as local time in `timezone` (UTC if the zone is unknown)
+fn local_time(at: DateTime<Utc>, timezone: &str) -> String {
+    let tz = jiff::tz::TimeZone::get(timezone).unwrap_or(jiff::tz::TimeZone::UTC);
+    jiff::Timestamp::from_second(at.timestamp())
+        .map(|ts| ts.to_zoned(tz).strftime("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| at.format("%Y-%m-%d %H:%M").to_string())
+}
+
+// ============================================================================
+// Report contents
+// ============================================================================
+
+/// Container restarts of an installed app during the report period
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AppHealth {
+    pub app_name: String,
+    pub restarts: i64,
+    /// Restarts caused by the container running out of memory
+    pub oom_kills: i64,
+}
+
+/// Availability of an uptime monitor during the report period
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct MonitorUptime {
+    pub name: String,
+    /// Current status: "up", "down" or "unknown"
+    pub status: String,
+    /// Percentage of successful checks (None without checks)
+    pub availability: Option<f64>,
+}
+
+/// Disk usage of the cluster's root filesystem (None when VictoriaMetrics
+/// has no data)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct StorageTrend {
+    pub used_bytes: Option<f64>,
+    /// Usage at the start of the report period
+    pub used_bytes_before: Option<f64>,
+    pub capacity_bytes: Option<f64>,
+}
+
+/// Audited actions of one user during the report period
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct UserActivity {
+    pub username: String,
+    pub actions: i64,
+}
+
+/// Failed sign-in attempts from one address
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FailedLoginSource {
+    pub ip_address: Option<String>,
+    pub attempts: i64,
+}
+
+/// A summary of Kubarr over a week or month
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Report {
+    pub frequency: ReportFrequency,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Time zone dates in the rendered report are shown in
+    pub timezone: String,
+    pub installed_apps: u64,
+    /// Installed apps whose containers restarted, most restarts first
+    pub app_restarts: Vec<AppHealth>,
+    pub uptime: Vec<MonitorUptime>,
+    pub storage: StorageTrend,
+    pub top_users: Vec<UserActivity>,
+    /// Failed password and two-factor sign-ins
+    pub failed_logins: i64,
+    pub failed_login_sources: Vec<FailedLoginSource>,
+    pub pending_update: Option<AvailableRelease>,
+    /// Email subject
+    pub subject: String,
+    /// Email body (plain text)
+    pub body: String,
+}
+
+/// Gather the report for the period of `frequency` ending at `to`
+pub async fn build(
+    db: &DatabaseConnection,
+    frequency: ReportFrequency,
+    timezone: &str,
+    to: DateTime<Utc>,
+) -> Result<Report> {
+    let from = frequency.start(to);
+
+    let installed_apps = InstalledApp::find().count(db).await?;
+    let restart_events = PodRestartEvent::find()
+        .filter(pod_restart_event::Column::OccurredAt.gte(from))
+        .filter(pod_restart_event::Column::OccurredAt.lt(to))
+        .all(db)
+        .await?;
+    let mut restarts: HashMap<String, AppHealth> = HashMap::new();
+    for event in restart_events {
+        let app = restarts
+            .entry(event.app_name.clone())
+            .or_insert_with(|| AppHealth {
+                app_name: event.app_name.clone(),
+                restarts: 0,
+                oom_kills: 0,
+            });
+        app.restarts += i64::from(event.restarts);
+        if event.reason.as_deref() == Some("OOMKilled") {
+            app.oom_kills += i64::from(event.restarts);
+        }
+    }
+    let mut app_restarts: Vec<AppHealth> = restarts.into_values().collect();
+    app_restarts.sort_by(|a, b| {
+        b.restarts
+            .cmp(&a.restarts)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+
+    let uptime = monitor_uptime(db, from, to).await?;
+    let storage = storage_trend(to - from).await;
+
+    let top_users = AuditLog::find()
+        .select_only()
+        .column(audit_log::Column::Username)
+        .column_as(audit_log::Column::Id.count(), "actions")
+        .filter(audit_log::Column::Timestamp.gte(from))
+        .filter(audit_log::Column::Timestamp.lt(to))
+        .filter(audit_log::Column::Username.is_not_null())
+        .group_by(audit_log::Column::Username)
+        .order_by_desc(Expr::col(Alias::new("actions")))
+        .limit(TOP_ENTRIES)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(username, actions)| UserActivity { username, actions })
+        .collect();
+
+    let failed_actions = [
+        AuditAction::LoginFailed.to_string(),
+        AuditAction::TwoFactorFailed.to_string(),
+    ];
+    let failed_filter = || {
+        AuditLog::find()
+            .filter(audit_log::Column::Timestamp.gte(from))
+            .filter(audit_log::Column::Timestamp.lt(to))
+            .filter(audit_log::Column::Action.is_in(failed_actions.clone()))
+    };
+    let failed_logins = failed_filter().count(db).await? as i64;
+    let failed_login_sources = failed_filter()
+        .select_only()
+        .column(audit_log::Column::IpAddress)
+        .column_as(audit_log::Column::Id.count(), "attempts")
+        .group_by(audit_log::Column::IpAddress)
+        .order_by_desc(Expr::col(Alias::new("attempts")))
+        .limit(TOP_ENTRIES)
+        .into_tuple::<(Option<String>, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(ip_address, attempts)| FailedLoginSource {
+            ip_address,
+            attempts,
+        })
+        .collect();
+
+    let mut report = Report {
+        frequency,
+        from,
+        to,
+        timezone: timezone.to_string(),
+        installed_apps,
+        app_restarts,
+        uptime,
+        storage,
+        top_users,
+        failed_logins,
+        failed_login_sources,
+        pending_update: UPDATES.latest(),
+        subject: String::new(),
+        body: String::new(),
+    };
+    report.subject = format!(
+        "Kubarr {} report: {} to {}",
+        frequency.as_str(),
+        local_time(from, timezone)
+            .split(' ')
+            .next()
+            .unwrap_or_default(),
+        local_time(to, timezone)
+            .split(' ')
+            .next()
+            .unwrap_or_default(),
+    );
+    report.body = render(&report);
+    Ok(report)
+}
+
+async fn monitor_uptime(
+    db: &DatabaseConnection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<MonitorUptime>> {
+    let monitors = UptimeMonitor::find()
+        .filter(uptime_monitor::Column::Enabled.eq(true))
+        .order_by_asc(uptime_monitor::Column::Name)
+        .all(db)
+        .await?;
+    let counts = UptimeCheck::find()
+        .select_only()
+        .column(uptime_check::Column::MonitorId)
+        .column(uptime_check::Column::Success)
+        .column_as(uptime_check::Column::Id.count(), "checks")
+        .filter(uptime_check::Column::CheckedAt.gte(from))
+        .filter(uptime_check::Column::CheckedAt.lt(to))
+        .group_by(uptime_check::Column::MonitorId)
+        .group_by(uptime_check::Column::Success)
+        .into_tuple::<(i64, bool, i64)>()
+        .all(db)
+        .await?;
+
+    Ok(monitors
+        .into_iter()
+        .map(|monitor| {
+            let (mut up, mut total) = (0, 0);
+            for (_, success, checks) in counts.iter().filter(|(id, _, _)| *id == monitor.id) {
+                total += checks;
+                if *success {
+                    up += checks;
+                }
+            }
+            MonitorUptime {
+                name: monitor.name,
+                status: monitor.status,
+                availability: (total > 0).then(|| up as f64 * 100.0 / total as f64),
+            }
+        })
+        .collect())
+}
+
+async fn storage_trend(period: chrono::Duration) -> StorageTrend {
+    const FILESYSTEM: &str = r#"{id="/",device=~"/dev/.*"}"#;
+    StorageTrend {
+        used_bytes: query_vm_scalar(&format!("max(container_fs_usage_bytes{})", FILESYSTEM)).await,
+        used_bytes_before: query_vm_scalar(&format!(
+            "max(container_fs_usage_bytes{} offset {}h)",
+            FILESYSTEM,
+            period.num_hours().max(1)
+        ))
+        .await,
+        capacity_bytes: query_vm_scalar(&format!("max(container_fs_limit_bytes{})", FILESYSTEM))
+            .await,
+    }
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes.abs();
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let sign = if bytes < 0.0 { "-" } else { "" };
+    format!("{}{:.1} {}", sign, value, UNITS[unit])
+}
+
+/// Render the plain-text email body of a report
+fn render(report: &Report) -> String {
+    let mut out = format!(
+        "Kubarr {} report\n{} to {} ({})\n",
+        report.frequency.as_str(),
+        local_time(report.from, &report.timezone),
+        local_time(report.to, &report.timezone),
+        report.timezone
+    );
+
+    out.push_str("\nApps\n");
+    out.push_str(&format!(
+        "  {} installed, {} with restarts\n",
+        report.installed_apps,
+        report.app_restarts.len()
+    ));
+    for app in &report.app_restarts {
+        out.push_str(&format!("  - {}: {} restarts", app.app_name, app.restarts));
+        if app.oom_kills > 0 {
+            out.push_str(&format!(" ({} out of memory)", app.oom_kills));
+        }
+        out.push('\n');
+    }
+
+    if !report.uptime.is_empty() {
+        out.push_str("\nUptime\n");
+        for monitor in &report.uptime {
+            let availability = monitor
+                .availability
+                .map(|a| format!("{:.2}%", a))
+                .unwrap_or_else(|| "no checks".to_string());
+            out.push_str(&format!(
+                "  - {}: {} (now {})\n",
+                monitor.name, availability, monitor.status
+            ));
+        }
+    }
+
+    out.push_str("\nStorage\n");
+    let storage = &report.storage;
+    match (storage.used_bytes, storage.capacity_bytes) {
+        (Some(used), capacity) => {
+            out.push_str(&format!("  {} used", format_bytes(used)));
+            if let Some(capacity) = capacity.filter(|c| *c > 0.0) {
+                out.push_str(&format!(
+                    " of {} ({:.0}%)",
+                    format_bytes(capacity),
+                    used * 100.0 / capacity
+                ));
+            }
+            if let Some(before) = storage.used_bytes_before {
+                let change = used - before;
+                let sign = if change >= 0.0 { "+" } else { "" };
+                out.push_str(&format!(", {}{} this period", sign, format_bytes(change)));
+            }
+            out.push('\n');
+        }
+        (None, _) => out.push_str("  No metrics available\n"),
+    }
+
+    out.push_str("\nMost active users\n");
+    if report.top_users.is_empty() {
+        out.push_str("  No activity\n");
+    }
+    for user in &report.top_users {
+        out.push_str(&format!(
+            "  - {}: {} actions\n",
+            user.username, user.actions
+        ));
+    }
+
+    out.push_str("\nFailed sign-ins\n");
+    out.push_str(&format!("  {} failed attempts\n", report.failed_logins));
+    for source in &report.failed_login_sources {
+        out.push_str(&format!(
+            "  - {}: {}\n",
+            source.ip_address.as_deref().unwrap_or("unknown address"),
+            source.attempts
+        ));
+    }
+
+    out.push_str("\nUpdates\n");
+    match &report.pending_update {
+        Some(release) => out.push_str(&format!(
+            "  Kubarr {} is available (running {}): {}\n",
+            release.version, CONFIG.version, release.url
+        )),
+        None => out.push_str(&format!("  Kubarr {} is up to date\n", CONFIG.version)),
+    }
+    out
+}
+
+// ============================================================================
+// Subscriptions
+// ============================================================================
+
+/// An admin's report subscription
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReportSubscriptionInfo {
+    pub enabled: bool,
+    pub frequency: ReportFrequency,
+    pub timezone: String,
+    /// Local hour of day (0-23) the report is sent at
+    pub send_hour: i32,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    /// When the next report goes out (None while disabled)
+    pub next_send_at: Option<DateTime<Utc>>,
+}
+
+impl From<report_subscription::Model> for ReportSubscriptionInfo {
+    fn from(sub: report_subscription::Model) -> Self {
+        let frequency = ReportFrequency::parse(&sub.frequency).unwrap_or_default();
+        let next_send_at = sub
+            .enabled
+            .then(|| send_times(frequency, &sub.timezone, sub.send_hour, Utc::now()))
+            .flatten()
+            .map(|(_, next)| next);
+        Self {
+            enabled: sub.enabled,
+            frequency,
+            timezone: sub.timezone,
+            send_hour: sub.send_hour,
+            last_sent_at: sub.last_sent_at,
+            next_send_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateReportSubscriptionRequest {
+    pub enabled: Option<bool>,
+    pub frequency: Option<ReportFrequency>,
+    /// IANA time zone, e.g. "Europe/Amsterdam"
+    pub timezone: Option<String>,
+    /// Local hour of day (0-23)
+    pub send_hour: Option<i32>,
+}
+
+/// A user's subscription, or the defaults when they have not opted in
+pub async fn subscription(db: &DatabaseConnection, user_id: i64) -> Result<ReportSubscriptionInfo> {
+    Ok(
+        match ReportSubscription::find_by_id(user_id).one(db).await? {
+            Some(sub) => sub.into(),
+            None => ReportSubscriptionInfo {
+                enabled: false,
+                frequency: ReportFrequency::default(),
+                timezone: "UTC".to_string(),
+                send_hour: DEFAULT_SEND_HOUR,
+                last_sent_at: None,
+                next_send_at: None,
+            },
+        },
+    )
+}
+
+/// Create or change a user's subscription
+pub async fn update_subscription(
+    db: &DatabaseConnection,
+    user_id: i64,
+    request: UpdateReportSubscriptionRequest,
+) -> Result<ReportSubscriptionInfo> {
+    if let Some(hour) = request.send_hour {
+        if !(0..24).contains(&hour) {
+            return Err(AppError::BadRequest(
+                "send_hour must be between 0 and 23".to_string(),
+            ));
+        }
+    }
+    let timezone = request
+        .timezone
+        .as_deref()
+        .map(validate_timezone)
+        .transpose()?;
+
+    let now = Utc::now();
+    let sub = match ReportSubscription::find_by_id(user_id).one(db).await? {
+        Some(existing) => {
+            let mut active: report_subscription::ActiveModel = existing.into();
+            if let Some(enabled) = request.enabled {
+                active.enabled = Set(enabled);
+            }
+            if let Some(frequency) = request.frequency {
+                active.frequency = Set(frequency.as_str().to_string());
+            }
+            if let Some(timezone) = timezone {
+                active.timezone = Set(timezone);
+            }
+            if let Some(hour) = request.send_hour {
+                active.send_hour = Set(hour);
+            }
+            active.updated_at = Set(now);
+            active.update(db).await?
+        }
+        None => {
+            report_subscription::ActiveModel {
+                user_id: Set(user_id),
+                enabled: Set(request.enabled.unwrap_or(true)),
+                frequency: Set(request.frequency.unwrap_or_default().as_str().to_string()),
+                timezone: Set(timezone.unwrap_or_else(|| "UTC".to_string())),
+                send_hour: Set(request.send_hour.unwrap_or(DEFAULT_SEND_HOUR)),
+                last_sent_at: Set(None),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    Ok(sub.into())
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SendReportRequest {
+    #[serde(default)]
+    pub frequency: ReportFrequency,
+    /// Send to every subscribed admin instead of only the caller
+    #[serde(default)]
+    pub all_subscribers: bool,
+}
+
+/// Outcome of sending a report on demand
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct SendReportResult {
+    pub sent: u32,
+    pub failed: u32,
+    /// Why deliveries failed
+    pub errors: Vec<String>,
+}
+
+/// Email address a report for `user` goes to: their verified email
+/// notification destination, or their account email
+pub(crate) async fn recipient(db: &DatabaseConnection, user: &user::Model) -> Result<String> {
+    let pref = UserNotificationPref::find()
+        .filter(user_notification_pref::Column::UserId.eq(user.id))
+        .filter(user_notification_pref::Column::ChannelType.eq(ChannelType::Email.as_str()))
+        .filter(user_notification_pref::Column::Verified.eq(true))
+        .one(db)
+        .await?;
+    Ok(pref
+        .and_then(|p| p.destination)
+        .unwrap_or_else(|| user.email.clone()))
+}
+
+/// The user, if they may still receive reports
+async fn report_recipient(db: &DatabaseConnection, user_id: i64) -> Result<Option<user::Model>> {
+    let Some(user) = User::find_by_id(user_id).one(db).await? else {
+        return Ok(None);
+    };
+    if !user.is_active
+        || !get_user_permissions(db, user_id)
+            .await
+            .iter()
+            .any(|p| p == SystemManage::NAME)
+    {
+        return Ok(None);
+    }
+    Ok(Some(user))
+}
+
+/// Build and email a report to one user
+async fn send_to(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    user: &user::Model,
+    frequency: ReportFrequency,
+    timezone: &str,
+    now: DateTime<Utc>,
+) -> Result<std::result::Result<(), String>> {
+    let report = build(db, frequency, timezone, now).await?;
+    let to = recipient(db, user).await?;
+    let result = notification
+        .send_email_to_user(
+            user.id,
+            &to,
+            &report.subject,
+            &report.body,
+            REPORT_EVENT_TYPE,
+        )
+        .await?;
+    Ok(match result.success {
+        true => Ok(()),
+        false => Err(result
+            .error
+            .unwrap_or_else(|| "Delivery failed".to_string())),
+    })
+}
+
+/// Send a report now, to `user_id` or to every subscribed admin
+pub async fn send_now(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    user_id: i64,
+    request: SendReportRequest,
+) -> Result<SendReportResult> {
+    let user_ids = if request.all_subscribers {
+        ReportSubscription::find()
+            .filter(report_subscription::Column::Enabled.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|sub| sub.user_id)
+            .collect()
+    } else {
+        vec![user_id]
+    };
+
+    let now = Utc::now();
+    let mut result = SendReportResult::default();
+    for id in user_ids {
+        let Some(user) = report_recipient(db, id).await? else {
+            continue;
+        };
+        let timezone = subscription(db, id).await?.timezone;
+        match send_to(db, notification, &user, request.frequency, &timezone, now).await? {
+            Ok(()) => result.sent += 1,
+            Err(e) => {
+                result.failed += 1;
+                result.errors.push(format!("{}: {}", user.username, e));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Whether a subscription's report is due at `now`
+///
+/// Subscriptions only receive reports for send times after they opted in.
+pub fn is_due(sub: &report_subscription::Model, now: DateTime<Utc>) -> bool {
+    let Some(frequency) = ReportFrequency::parse(&sub.frequency) else {
+        return false;
+    };
+    let Some((last, _)) = send_times(frequency, &sub.timezone, sub.send_hour, now) else {
+        return false;
+    };
+    sub.enabled && sub.created_at < last && sub.last_sent_at.is_none_or(|sent| sent < last)
+}
+
+/// Send the reports that are due
+async fn send_due(db: &DatabaseConnection, notification: &NotificationService) -> Result<()> {
+    let now = Utc::now();
+    let subscriptions = ReportSubscription::find()
+        .filter(report_subscription::Column::Enabled.eq(true))
+        .all(db)
+        .await?;
+    for sub in subscriptions.into_iter().filter(|s| is_due(s, now)) {
+        let user_id = sub.user_id;
+        let frequency = ReportFrequency::parse(&sub.frequency).unwrap_or_default();
+        if let Some(user) = report_recipient(db, user_id).await? {
+            match send_to(db, notification, &user, frequency, &sub.timezone, now).await? {
+                Ok(()) => tracing::info!("Sent {} report to {}", frequency.as_str(), user.username),
+                Err(e) => tracing::warn!(
+                    "Failed to send {} report to {}: {}",
+                    frequency.as_str(),
+                    user.username,
+                    e
+                ),
+            }
+        }
+
+        // Marked even when delivery failed, so a broken mail setup is not
+        // retried every few minutes; the failure is in the notification log
+        let mut active: report_subscription::ActiveModel = sub.into();
+        active.last_sent_at = Set(Some(now));
+        active.update(db).await?;
+    }
+    Ok(())
+}
+
+/// Sends scheduled reports to subscribed admins
+pub struct ReportTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for ReportTask {
+    fn name(&self) -> &'static str {
+        "scheduled_reports"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        send_due(db, &self.notification).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn sub(frequency: &str, created_at: DateTime<Utc>) -> report_subscription::Model {
+        report_subscription::Model {
+            user_id: 1,
+            enabled: true,
+            frequency: frequency.to_string(),
+            timezone: "Europe/Amsterdam".to_string(),
+            send_hour: 8,
+            last_sent_at: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_send_times_weekly_in_timezone() {
+        // Wednesday 14 Oct 2026; Amsterdam is UTC+2 until 25 Oct
+        let (last, next) = send_times(
+            ReportFrequency::Weekly,
+            "Europe/Amsterdam",
+            8,
+            at(2026, 10, 14, 12, 0),
+        )
+        .unwrap();
+        assert_eq!(last, at(2026, 10, 12, 6, 0));
+        assert_eq!(next, at(2026, 10, 19, 6, 0));
+
+        // Monday 05:59 UTC is still before 08:00 in Amsterdam
+        let (last, next) = send_times(
+            ReportFrequency::Weekly,
+            "Europe/Amsterdam",
+            8,
+            at(2026, 10, 19, 5, 59),
+        )
+        .unwrap();
+        assert_eq!(last, at(2026, 10, 12, 6, 0));
+        assert_eq!(next, at(2026, 10, 19, 6, 0));
+
+        // After the switch to winter time Monday 08:00 is 07:00 UTC
+        let (_, next) = send_times(
+            ReportFrequency::Weekly,
+            "Europe/Amsterdam",
+            8,
+            at(2026, 10, 28, 0, 0),
+        )
+        .unwrap();
+        assert_eq!(next, at(2026, 11, 2, 7, 0));
+    }
+
+    #[test]
+    fn test_send_times_monthly() {
+        let (last, next) =
+            send_times(ReportFrequency::Monthly, "UTC", 0, at(2026, 10, 17, 9, 0)).unwrap();
+        assert_eq!(last, at(2026, 10, 1, 0, 0));
+        assert_eq!(next, at(2026, 11, 1, 0, 0));
+
+        // Already 1 November in Tokyo
+        let (last, _) = send_times(
+            ReportFrequency::Monthly,
+            "Asia/Tokyo",
+            6,
+            at(2026, 10, 31, 22, 0),
+        )
+        .unwrap();
+        assert_eq!(last, at(2026, 10, 31, 21, 0));
+
+        assert!(send_times(ReportFrequency::Monthly, "Mars/Base", 6, Utc::now()).is_none());
+        assert!(send_times(ReportFrequency::Monthly, "UTC", 24, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_is_due() {
+        let now = at(2026, 10, 14, 12, 0);
+        let mut weekly = sub("weekly", at(2026, 10, 1, 0, 0));
+        assert!(is_due(&weekly, now));
+
+        weekly.last_sent_at = Some(at(2026, 10, 12, 6, 1));
+        assert!(!is_due(&weekly, now));
+
+        // Missed while down: sent late, once
+        weekly.last_sent_at = Some(at(2026, 10, 5, 6, 0));
+        assert!(is_due(&weekly, now));
+
+        // Opted in after this week's report went out
+        let recent = sub("weekly", at(2026, 10, 13, 0, 0));
+        assert!(!is_due(&recent, now));
+
+        let mut disabled = sub("monthly", at(2026, 9, 1, 0, 0));
+        assert!(is_due(&disabled, now));
+        disabled.enabled = false;
+        assert!(!is_due(&disabled, now));
+    }
+
+    #[test]
+    fn test_render() {
+        let report = Report {
+            frequency: ReportFrequency::Weekly,
+            from: at(2026, 10, 5, 6, 0),
+            to: at(2026, 10, 12, 6, 0),
+            timezone: "Europe/Amsterdam".to_string(),
+            installed_apps: 4,
+            app_restarts: vec![AppHealth {
+                app_name: "sonarr".to_string(),
+                restarts: 3,
+                oom_kills: 1,
+            }],
+            uptime: vec![MonitorUptime {
+                name: "Plex".to_string(),
+                status: "up".to_string(),
+                availability: Some(99.5),
+            }],
+            storage: StorageTrend {
+                used_bytes: Some(3.0 * 1024f64.powi(3)),
+                used_bytes_before: Some(2.5 * 1024f64.powi(3)),
+                capacity_bytes: Some(12.0 * 1024f64.powi(3)),
+            },
+            top_users: vec![UserActivity {
+                username: "alice".to_string(),
+                actions: 42,
+            }],
+            failed_logins: 2,
+            failed_login_sources: vec![FailedLoginSource {
+                ip_address: Some("10.0.0.5".to_string()),
+                attempts: 2,
+            }],
+            pending_update: None,
+            subject: String::new(),
+            body: String::new(),
+        };
+        let body = render(&report);
+        assert!(body.starts_with(
+            "Kubarr weekly report\n2026-10-05 08:00 to 2026-10-12 08:00 (Europe/Amsterdam)\n"
+        ));
+        assert!(body.contains(
+            "  4 installed, 1 with restarts\n  - sonarr: 3 restarts (1 out of memory)\n"
+        ));
+        assert!(body.contains("  - Plex: 99.50% (now up)\n"));
+        assert!(body.contains("  3.0 GB used of 12.0 GB (25%), +512.0 MB this period\n"));
+        assert!(body.contains("  - alice: 42 actions\n"));
+        assert!(body.contains("  2 failed attempts\n  - 10.0.0.5: 2\n"));
+        assert!(body.contains("is up to date"));
+    }
+}
@@ -0,0 +1,242 @@
+//! Time-boxed role assignments
+//!
+//! A role assignment can carry an `expires_at`. From that moment the role no
+//! longer grants anything (see [`super::access::unexpired_roles`]), and
+//! [`RoleExpiryTask`] removes the assignment and audits it as
+//! `role_unassigned`. `role_expiry_notice_hours` before the expiry, the user
+//! and every active holder of `users.manage` get a `role_expiring`
+//! notification, once per assignment.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+use super::access::get_user_permissions;
+use super::audit::AuditService;
+use super::notification::NotificationService;
+use super::scheduler::PeriodicTask;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, UsersManage};
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::prelude::*;
+use crate::models::{user, user_role};
+
+/// Hours before a role assignment expires that its user and admins are told;
+/// 0 turns the notice off
+pub const ROLE_EXPIRY_NOTICE_HOURS: &str = "role_expiry_notice_hours";
+
+/// Parse the notice setting
+pub fn parse_notice_hours(value: &str) -> Result<u32> {
+    value.trim().parse::<u32>().map_err(|_| {
+        AppError::BadRequest(format!(
+            "{} must be a whole number of at least 0",
+            ROLE_EXPIRY_NOTICE_HOURS
+        ))
+    })
+}
+
+async fn notice_hours(db: &DatabaseConnection) -> Result<u32> {
+    Ok(get_setting_value(db, ROLE_EXPIRY_NOTICE_HOURS)
+        .await?
+        .and_then(|v| parse_notice_hours(&v).ok())
+        .unwrap_or(72))
+}
+
+/// Check an expiry given for a new or changed role assignment
+pub fn validate_expiry(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Result<()> {
+    match expires_at {
+        Some(at) if at <= now => Err(AppError::BadRequest(
+            "expires_at must be in the future".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Assignments that expire within `notice` and nobody has been told about
+pub async fn expiring(
+    db: &DatabaseConnection,
+    notice: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Result<Vec<user_role::Model>> {
+    Ok(UserRole::find()
+        .filter(user_role::Column::ExpiresAt.gt(now))
+        .filter(user_role::Column::ExpiresAt.lte(now + notice))
+        .filter(user_role::Column::ExpiryNotified.eq(false))
+        .all(db)
+        .await?)
+}
+
+/// Remove assignments past their expiry and return them
+pub async fn remove_expired(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<user_role::Model>> {
+    let expired = UserRole::find()
+        .filter(user_role::Column::ExpiresAt.lte(now))
+        .all(db)
+        .await?;
+    if !expired.is_empty() {
+        UserRole::delete_many()
+            .filter(user_role::Column::ExpiresAt.lte(now))
+            .exec(db)
+            .await?;
+    }
+    Ok(expired)
+}
+
+/// Active users holding `users.manage`, who are told about expiring roles
+async fn user_admins(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let mut admins = Vec::new();
+    for candidate in User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .all(db)
+        .await?
+    {
+        if get_user_permissions(db, candidate.id)
+            .await
+            .iter()
+            .any(|p| p == UsersManage::NAME)
+        {
+            admins.push(candidate);
+        }
+    }
+    Ok(admins)
+}
+
+/// Notifies about upcoming expiries and removes expired role assignments
+pub struct RoleExpiryTask {
+    pub notification: NotificationService,
+    pub audit: AuditService,
+}
+
+impl RoleExpiryTask {
+    async fn send_notices(&self, db: &DatabaseConnection, now: DateTime<Utc>) -> Result<usize> {
+        let hours = notice_hours(db).await?;
+        if hours == 0 {
+            return Ok(0);
+        }
+        let upcoming = expiring(db, chrono::Duration::hours(hours as i64), now).await?;
+        if upcoming.is_empty() {
+            return Ok(0);
+        }
+
+        let admins = user_admins(db).await?;
+        for assignment in &upcoming {
+            let (Some(member), Some(role)) = (
+                User::find_by_id(assignment.user_id).one(db).await?,
+                Role::find_by_id(assignment.role_id).one(db).await?,
+            ) else {
+                continue;
+            };
+            let details = format!(
+                "Role {} expires {}",
+                role.name,
+                assignment
+                    .expires_at
+                    .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                    .unwrap_or_default()
+            );
+            let recipients = std::iter::once(member.id)
+                .chain(admins.iter().map(|a| a.id).filter(|id| *id != member.id));
+            for recipient in recipients {
+                if let Err(e) = self
+                    .notification
+                    .notify_user_event(
+                        &AuditAction::RoleExpiring,
+                        recipient,
+                        Some(&member.username),
+                        Some(&details),
+                    )
+                    .await
+                {
+                    tracing::warn!("Failed to send role_expiring notification: {}", e);
+                }
+            }
+
+            let mut active: user_role::ActiveModel = assignment.clone().into();
+            active.expiry_notified = Set(true);
+            active.update(db).await?;
+        }
+        Ok(upcoming.len())
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for RoleExpiryTask {
+    fn name(&self) -> &'static str {
+        "role_expiry"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(15 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let notified = self.send_notices(db, now).await?;
+        if notified > 0 {
+            tracing::info!("Sent expiry notices for {} role assignment(s)", notified);
+        }
+
+        for assignment in remove_expired(db, now).await? {
+            let username = User::find_by_id(assignment.user_id)
+                .one(db)
+                .await?
+                .map(|u| u.username);
+            let role_name = Role::find_by_id(assignment.role_id)
+                .one(db)
+                .await?
+                .map(|r| r.name);
+            tracing::info!(
+                "Role {} of {} expired",
+                role_name.as_deref().unwrap_or("?"),
+                username.as_deref().unwrap_or("?")
+            );
+            let _ = self
+                .audit
+                .log_success(
+                    AuditAction::RoleUnassigned,
+                    ResourceType::User,
+                    Some(assignment.user_id.to_string()),
+                    None,
+                    None,
+                    Some(serde_json::json!({
+                        "target_username": username,
+                        "role_id": assignment.role_id,
+                        "role_name": role_name,
+                        "reason": "expired",
+                        "expired_at": assignment.expires_at,
+                    })),
+                    None,
+                    None,
+                )
+                .await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_expiry() {
+        let now = Utc::now();
+        assert!(validate_expiry(None, now).is_ok());
+        assert!(validate_expiry(Some(now + chrono::Duration::days(30)), now).is_ok());
+        assert!(validate_expiry(Some(now), now).is_err());
+        assert!(validate_expiry(Some(now - chrono::Duration::hours(1)), now).is_err());
+    }
+
+    #[test]
+    fn test_parse_notice_hours() {
+        assert_eq!(parse_notice_hours(" 24 ").unwrap(), 24);
+        assert_eq!(parse_notice_hours("0").unwrap(), 0);
+        assert!(parse_notice_hours("-1").is_err());
+        assert!(parse_notice_hours("soon").is_err());
+    }
+}
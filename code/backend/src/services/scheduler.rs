@@ -9,7 +9,31 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
 
+use super::access_reviews::AccessReviewTask;
+use super::alerts::AlertSyncTask;
+use super::anomaly::AnomalyDetectionTask;
+use super::approvals::ApprovalDigestTask;
+use super::audit::AuditService;
+use super::backup::BackupTask;
+use super::boot_order::BootOrderTask;
 use super::chart_sync::{ChartSyncService, ChartSyncTask};
+use super::energy::EnergyUsageTask;
+use super::hardware_sensors::SensorMonitorTask;
+use super::homes::HomeUsageTask;
+use super::idle_suspend::IdleSuspendTask;
+use super::log_alerts::LogAlertTask;
+use super::log_archive::LogArchiveTask;
+use super::network_usage::NetworkUsageTask;
+use super::notification::NotificationService;
+use super::pod_stability::PodStabilityTask;
+use super::reports::ReportTask;
+use super::role_expiry::RoleExpiryTask;
+use super::shares::ShareSyncTask;
+use super::trash::TrashPurgeTask;
+use super::updates::UpdateCheckTask;
+use super::uptime::UptimeMonitorTask;
+use super::wan_health::WanHealthTask;
+use crate::state::{SharedCatalog, SharedK8sClient};
 
 /// Trait for periodic background tasks
 #[async_trait]
@@ -25,12 +49,73 @@ pub trait PeriodicTask: Send + Sync {
 }
 
 /// Start all periodic tasks
-pub fn start_scheduler(db: Arc<DatabaseConnection>, chart_sync: Arc<ChartSyncService>) {
+pub fn start_scheduler(
+    db: Arc<DatabaseConnection>,
+    chart_sync: Arc<ChartSyncService>,
+    notification: NotificationService,
+    audit: AuditService,
+    k8s_client: SharedK8sClient,
+    catalog: SharedCatalog,
+) {
     let tasks: Vec<Box<dyn PeriodicTask>> = vec![
         Box::new(SessionCleanupTask),
         Box::new(ChartSyncTask {
             service: chart_sync,
         }),
+        Box::new(NetworkUsageTask),
+        Box::new(EnergyUsageTask),
+        Box::new(WanHealthTask {
+            notification: notification.clone(),
+        }),
+        Box::new(UptimeMonitorTask {
+            notification: notification.clone(),
+        }),
+        Box::new(AnomalyDetectionTask {
+            notification: notification.clone(),
+        }),
+        Box::new(SensorMonitorTask::new(notification.clone())),
+        Box::new(LogAlertTask {
+            notification: notification.clone(),
+        }),
+        Box::new(AlertSyncTask),
+        Box::new(LogArchiveTask),
+        Box::new(UpdateCheckTask {
+            notification: notification.clone(),
+        }),
+        Box::new(ReportTask {
+            notification: notification.clone(),
+        }),
+        Box::new(ApprovalDigestTask {
+            notification: notification.clone(),
+        }),
+        Box::new(AccessReviewTask {
+            notification: notification.clone(),
+            audit: audit.clone(),
+        }),
+        Box::new(RoleExpiryTask {
+            notification: notification.clone(),
+            audit: audit.clone(),
+        }),
+        Box::new(BackupTask::new(notification.clone(), audit)),
+        Box::new(TrashPurgeTask),
+        Box::new(HomeUsageTask {
+            notification: notification.clone(),
+        }),
+        Box::new(ShareSyncTask {
+            k8s_client: k8s_client.clone(),
+        }),
+        Box::new(PodStabilityTask {
+            k8s_client: k8s_client.clone(),
+        }),
+        Box::new(BootOrderTask {
+            k8s_client: k8s_client.clone(),
+            catalog: catalog.clone(),
+        }),
+        Box::new(IdleSuspendTask {
+            k8s_client,
+            catalog,
+            notification,
+        }),
     ];
 
     for task in tasks {
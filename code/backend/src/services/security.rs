@@ -1,5 +1,5 @@
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
@@ -20,8 +20,8 @@ const ACCESS_TOKEN_EXPIRE: i64 = 3600; // 1 hour
 const REFRESH_TOKEN_EXPIRE: i64 = 604800; // 7 days
 
 // Database keys for system_settings
-const JWT_PRIVATE_KEY_SETTING: &str = "jwt_private_key";
-const JWT_PUBLIC_KEY_SETTING: &str = "jwt_public_key";
+pub const JWT_PRIVATE_KEY_SETTING: &str = "jwt_private_key";
+pub const JWT_PUBLIC_KEY_SETTING: &str = "jwt_public_key";
 
 // In-memory key cache
 static PRIVATE_KEY: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
@@ -53,12 +53,45 @@ pub struct Claims {
 }
 
 /// Minimal JWT claims for session tokens (stored in cookie)
-/// Contains only the session ID - expiration is checked in the database
+/// Contains only the session ID and a short expiry - the session itself is
+/// checked in the database, and the token is renewed with a refresh token
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionClaims {
     pub sid: String, // Session ID (UUID)
+    /// Expiry of this access token; tokens issued without one count as expired
+    #[serde(default)]
+    pub exp: i64,
 }
 
+impl SessionClaims {
+    /// Whether the access token is past its expiry
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.exp <= now.timestamp()
+    }
+}
+
+/// JWT claims for an emailed "approve registration" link
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalClaims {
+    pub sub: i64, // Pending user
+    pub adm: i64, // Admin the link was sent to
+    pub typ: String,
+    pub exp: i64,
+}
+
+const APPROVAL_TOKEN_TYPE: &str = "user_approval";
+
+/// JWT claims for a "this wasn't me" session revoke link
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionRevokeClaims {
+    pub sid: String, // Session to revoke
+    pub sub: i64,    // Owner of the session
+    pub typ: String,
+    pub exp: i64,
+}
+
+const SESSION_REVOKE_TOKEN_TYPE: &str = "session_revoke";
+
 /// Initialize JWT keys from database (call once during startup)
 /// Generates new keys if not present and stores them in the database
 pub async fn init_jwt_keys(db: &sea_orm::DatabaseConnection) -> Result<()> {
@@ -281,10 +314,13 @@ pub fn decode_token(token: &str) -> Result<Claims> {
 }
 
 /// Create a minimal session token (JWT containing only session ID)
-/// This is stored in the cookie - expiration and user data are looked up from the database
-pub fn create_session_token(session_id: &str) -> Result<String> {
+/// This is stored in the cookie - user data is looked up from the database.
+/// It expires after `expires_in` seconds, `KUBARR_ACCESS_TOKEN_TTL` by default.
+pub fn create_session_token(session_id: &str, expires_in: Option<i64>) -> Result<String> {
     let claims = SessionClaims {
         sid: session_id.to_string(),
+        exp: (Utc::now() + Duration::seconds(expires_in.unwrap_or(CONFIG.auth.access_token_ttl)))
+            .timestamp(),
     };
 
     let private_key = get_private_key()?;
@@ -296,7 +332,9 @@ pub fn create_session_token(session_id: &str) -> Result<String> {
 }
 
 /// Decode and validate a session token
-/// Returns the session ID if signature is valid - expiration is checked in the database
+/// Returns the claims if the signature is valid, even when the token has
+/// expired, so the session can still be found; callers check
+/// [`SessionClaims::is_expired`] where an expired token must be refused
 pub fn decode_session_token(token: &str) -> Result<SessionClaims> {
     let public_key = get_public_key()?;
     let decoding_key = DecodingKey::from_rsa_pem(public_key.as_bytes())
@@ -311,6 +349,78 @@ pub fn decode_session_token(token: &str) -> Result<SessionClaims> {
     Ok(token_data.claims)
 }
 
+/// Create a token that lets `admin_id` approve `user_id` without signing in
+pub fn create_approval_token(user_id: i64, admin_id: i64, expires_in: i64) -> Result<String> {
+    let claims = ApprovalClaims {
+        sub: user_id,
+        adm: admin_id,
+        typ: APPROVAL_TOKEN_TYPE.to_string(),
+        exp: (Utc::now() + Duration::seconds(expires_in)).timestamp(),
+    };
+
+    let private_key = get_private_key()?;
+    let encoding_key = EncodingKey::from_rsa_pem(private_key.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Invalid private key: {}", e)))?;
+
+    let header = Header::new(jsonwebtoken::Algorithm::RS256);
+    encode(&header, &claims, &encoding_key).map_err(|e| e.into())
+}
+
+/// Decode and validate an approval link token
+pub fn decode_approval_token(token: &str) -> Result<ApprovalClaims> {
+    let public_key = get_public_key()?;
+    let decoding_key = DecodingKey::from_rsa_pem(public_key.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Invalid public key: {}", e)))?;
+
+    let mut validation = Validation::new(jsonwebtoken::Algorithm::RS256);
+    validation.validate_aud = false;
+    validation.leeway = 0;
+
+    let claims = decode::<ApprovalClaims>(token, &decoding_key, &validation)?.claims;
+    if claims.typ != APPROVAL_TOKEN_TYPE {
+        return Err(AppError::Unauthorized("Invalid approval link".to_string()));
+    }
+    Ok(claims)
+}
+
+/// Create a token that lets `user_id` sign out `session_id` without signing in
+pub fn create_session_revoke_token(
+    session_id: &str,
+    user_id: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<String> {
+    let claims = SessionRevokeClaims {
+        sid: session_id.to_string(),
+        sub: user_id,
+        typ: SESSION_REVOKE_TOKEN_TYPE.to_string(),
+        exp: expires_at.timestamp(),
+    };
+
+    let private_key = get_private_key()?;
+    let encoding_key = EncodingKey::from_rsa_pem(private_key.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Invalid private key: {}", e)))?;
+
+    let header = Header::new(jsonwebtoken::Algorithm::RS256);
+    encode(&header, &claims, &encoding_key).map_err(|e| e.into())
+}
+
+/// Decode and validate a session revoke link token
+pub fn decode_session_revoke_token(token: &str) -> Result<SessionRevokeClaims> {
+    let public_key = get_public_key()?;
+    let decoding_key = DecodingKey::from_rsa_pem(public_key.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Invalid public key: {}", e)))?;
+
+    let mut validation = Validation::new(jsonwebtoken::Algorithm::RS256);
+    validation.validate_aud = false;
+    validation.leeway = 0;
+
+    let claims = decode::<SessionRevokeClaims>(token, &decoding_key, &validation)?.claims;
+    if claims.typ != SESSION_REVOKE_TOKEN_TYPE {
+        return Err(AppError::Unauthorized("Invalid revoke link".to_string()));
+    }
+    Ok(claims)
+}
+
 /// Generate a cryptographically secure random string (hex)
 pub fn generate_random_string(length: usize) -> String {
     let mut rng = rand::rng();
@@ -319,7 +429,6 @@ pub fn generate_random_string(length: usize) -> String {
 }
 
 /// Generate a secure random password
-#[allow(dead_code)]
 pub fn generate_secure_password(length: usize) -> String {
     const CHARSET: &[u8] =
         b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%^&*()";
@@ -426,6 +535,27 @@ pub fn verify_recovery_code(code: &str, hash: &str) -> bool {
     bcrypt::verify(code, hash).unwrap_or(false)
 }
 
+// ==========================================================================
+// Verification Code Functions
+// ==========================================================================
+
+/// Generate a 6-digit code for verifying a notification destination
+pub fn generate_verification_code() -> String {
+    let mut rng = rand::rng();
+    format!("{:06}", rng.random_range(0..1_000_000u32))
+}
+
+/// Hash a verification code using bcrypt (cost 8 for performance)
+pub fn hash_verification_code(code: &str) -> Result<String> {
+    bcrypt::hash(code, 8)
+        .map_err(|e| AppError::Internal(format!("Failed to hash verification code: {}", e)))
+}
+
+/// Verify a verification code against its hash
+pub fn verify_verification_code(code: &str, hash: &str) -> bool {
+    bcrypt::verify(code, hash).unwrap_or(false)
+}
+
 /// Get the JWKS (JSON Web Key Set) for the public key
 pub fn get_jwks() -> Result<serde_json::Value> {
     let public_pem = get_public_key()?;
@@ -0,0 +1,558 @@
+//! Security posture report
+//!
+//! [`build`] checks the deployment against a fixed checklist and scores the
+//! result. Each check passes, warns or fails, and carries a remediation hint
+//! when it does not pass. Checks are weighted by severity (high 3, medium 2,
+//! low 1); a warning earns half the weight, and the score is the share of the
+//! total weight earned, from 0 to 100. Reports are built on every request, so
+//! they always reflect the current state.
+//!
+//! Privileged users are active users in a role granting one of
+//! [`PRIVILEGED_PERMISSIONS`].
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::Serialize;
+
+use super::access::unexpired_roles;
+use super::crypto;
+use super::ip_bans;
+use super::security::verify_password;
+use crate::application::dev_seed::DEV_PASSWORD;
+use crate::config::validation::DEFAULT_DATABASE_PASSWORD;
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    Permission, RolesManage, SecurityManage, SettingsManage, SystemManage, UsersManage,
+};
+use crate::models::prelude::*;
+use crate::models::{oauth_provider, role, role_permission, session, user, user_role};
+use crate::state::DbConn;
+
+/// Permissions that can change who has access to what
+pub const PRIVILEGED_PERMISSIONS: &[&str] = &[
+    RolesManage::NAME,
+    UsersManage::NAME,
+    SystemManage::NAME,
+    SettingsManage::NAME,
+    SecurityManage::NAME,
+];
+
+/// Sessions unused for this long count as stale
+const STALE_SESSION_DAYS: i64 = 30;
+
+/// Most usernames listed in a finding
+const MAX_LISTED: usize = 5;
+
+/// Outcome of a check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// How much a check counts towards the score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl FindingSeverity {
+    fn weight(&self) -> u32 {
+        match self {
+            FindingSeverity::Low => 1,
+            FindingSeverity::Medium => 2,
+            FindingSeverity::High => 3,
+        }
+    }
+}
+
+/// Result of one check
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SecurityFinding {
+    /// Stable identifier of the check, e.g. `two_factor_coverage`
+    pub id: String,
+    pub title: String,
+    pub status: FindingStatus,
+    pub severity: FindingSeverity,
+    /// What was found
+    pub detail: String,
+    /// How to fix it; only set when the check did not pass
+    pub remediation: Option<String>,
+}
+
+impl SecurityFinding {
+    fn new(id: &str, title: &str, severity: FindingSeverity) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            status: FindingStatus::Pass,
+            severity,
+            detail: String::new(),
+            remediation: None,
+        }
+    }
+
+    fn pass(mut self, detail: impl Into<String>) -> Self {
+        self.status = FindingStatus::Pass;
+        self.detail = detail.into();
+        self
+    }
+
+    fn flag(
+        mut self,
+        status: FindingStatus,
+        detail: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        self.status = status;
+        self.detail = detail.into();
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+/// Scored checklist results
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SecurityReport {
+    #[schema(value_type = String)]
+    pub generated_at: DateTime<Utc>,
+    /// 0 to 100, higher is better
+    pub score: u32,
+    pub passed: usize,
+    pub warnings: usize,
+    pub failures: usize,
+    /// Share of active users with 2FA, in percent
+    pub two_factor_coverage: f64,
+    pub findings: Vec<SecurityFinding>,
+}
+
+/// Score of a set of findings, from 0 to 100
+pub fn score(findings: &[SecurityFinding]) -> u32 {
+    let total: u32 = findings.iter().map(|f| f.severity.weight() * 2).sum();
+    if total == 0 {
+        return 100;
+    }
+    let earned: u32 = findings
+        .iter()
+        .map(|f| match f.status {
+            FindingStatus::Pass => f.severity.weight() * 2,
+            FindingStatus::Warn => f.severity.weight(),
+            FindingStatus::Fail => 0,
+        })
+        .sum();
+    (earned * 100 + total / 2) / total
+}
+
+/// Usernames for a finding, shortened to [`MAX_LISTED`]
+fn list_names(names: &[String]) -> String {
+    let mut listed = names
+        .iter()
+        .take(MAX_LISTED)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > MAX_LISTED {
+        listed.push_str(&format!(" and {} more", names.len() - MAX_LISTED));
+    }
+    listed
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        100.0
+    } else {
+        (part as f64 * 1000.0 / whole as f64).round() / 10.0
+    }
+}
+
+/// Active users and which of them are privileged
+struct Accounts {
+    active: Vec<user::Model>,
+    privileged: Vec<user::Model>,
+}
+
+async fn accounts(db: &DbConn) -> Result<Accounts> {
+    // Service accounts cannot sign in, so sign-in checks skip them
+    let active = User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .filter(user::Column::IsApproved.eq(true))
+        .filter(user::Column::IsServiceAccount.eq(false))
+        .all(db)
+        .await?;
+    let privileged_roles: Vec<i64> = RolePermission::find()
+        .filter(role_permission::Column::Permission.is_in(PRIVILEGED_PERMISSIONS.to_vec()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| p.role_id)
+        .collect();
+    let privileged_ids: Vec<i64> = UserRole::find()
+        .filter(user_role::Column::RoleId.is_in(privileged_roles))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|ur| ur.user_id)
+        .collect();
+    let privileged = active
+        .iter()
+        .filter(|u| privileged_ids.contains(&u.id))
+        .cloned()
+        .collect();
+    Ok(Accounts { active, privileged })
+}
+
+async fn check_default_credentials(accounts: &Accounts) -> Result<SecurityFinding> {
+    let finding = SecurityFinding::new(
+        "default_credentials",
+        "Default credentials",
+        FindingSeverity::High,
+    );
+    let mut problems = Vec::new();
+    if CONFIG
+        .database
+        .database_url
+        .contains(DEFAULT_DATABASE_PASSWORD)
+    {
+        problems.push("the database uses the default kubarr/kubarr credentials".to_string());
+    }
+
+    let hashes: Vec<(String, String)> = accounts
+        .privileged
+        .iter()
+        .map(|u| (u.username.clone(), u.hashed_password.clone()))
+        .collect();
+    let defaults = tokio::task::spawn_blocking(move || {
+        hashes
+            .into_iter()
+            .filter(|(_, hash)| verify_password(DEV_PASSWORD, hash))
+            .map(|(username, _)| username)
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Password check failed: {}", e)))?;
+    if !defaults.is_empty() {
+        problems.push(format!(
+            "privileged accounts still use the development password: {}",
+            list_names(&defaults)
+        ));
+    }
+
+    Ok(if problems.is_empty() {
+        finding.pass("No default database credentials or development passwords are in use")
+    } else {
+        let mut detail = problems.join("; ");
+        detail[..1].make_ascii_uppercase();
+        finding.flag(
+            FindingStatus::Fail,
+            detail,
+            "Set a unique database password in KUBARR_DATABASE_URL and change the passwords of the listed accounts.",
+        )
+    })
+}
+
+fn check_two_factor(accounts: &Accounts) -> (SecurityFinding, f64) {
+    let finding = SecurityFinding::new(
+        "two_factor_coverage",
+        "Two-factor authentication",
+        FindingSeverity::High,
+    );
+    let enabled = accounts.active.iter().filter(|u| u.totp_enabled).count();
+    let coverage = percent(enabled, accounts.active.len());
+    let summary = format!(
+        "2FA is on for {} of {} active users ({}%)",
+        enabled,
+        accounts.active.len(),
+        coverage
+    );
+    let exposed: Vec<String> = accounts
+        .privileged
+        .iter()
+        .filter(|u| !u.totp_enabled)
+        .map(|u| u.username.clone())
+        .collect();
+    let finding = if !exposed.is_empty() {
+        finding.flag(
+            FindingStatus::Fail,
+            format!(
+                "{}. Privileged accounts without 2FA: {}",
+                summary,
+                list_names(&exposed)
+            ),
+            "Turn on requires_2fa for every role with admin permissions so their users must set up 2FA.",
+        )
+    } else if enabled < accounts.active.len() {
+        finding.flag(
+            FindingStatus::Warn,
+            summary,
+            "Ask the remaining users to enable 2FA on their account page, or turn on requires_2fa for their roles.",
+        )
+    } else {
+        finding.pass(summary)
+    };
+    (finding, coverage)
+}
+
+async fn check_role_separation(db: &DbConn, accounts: &Accounts) -> Result<SecurityFinding> {
+    let finding = SecurityFinding::new(
+        "role_separation",
+        "Role separation",
+        FindingSeverity::Medium,
+    );
+    let escalating_roles: Vec<i64> = RolePermission::find()
+        .filter(role_permission::Column::Permission.eq(RolesManage::NAME))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| p.role_id)
+        .collect();
+    let escalating: Vec<String> = Role::find()
+        .filter(role::Column::Id.is_in(escalating_roles))
+        .filter(role::Column::Name.ne("admin"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+
+    let mut problems = Vec::new();
+    if !escalating.is_empty() {
+        problems.push(format!(
+            "roles besides admin can manage roles and grant themselves any permission: {}",
+            list_names(&escalating)
+        ));
+    }
+    let (privileged, active) = (accounts.privileged.len(), accounts.active.len());
+    if active >= 4 && privileged * 2 > active {
+        problems.push(format!(
+            "{} of {} active users hold admin permissions",
+            privileged, active
+        ));
+    }
+
+    Ok(if problems.is_empty() {
+        finding.pass(format!(
+            "{} of {} active users hold admin permissions",
+            privileged, active
+        ))
+    } else {
+        let mut detail = problems.join("; ");
+        detail[..1].make_ascii_uppercase();
+        finding.flag(
+            FindingStatus::Warn,
+            detail,
+            "Keep roles.manage to the admin role and give everyday users a role without admin permissions.",
+        )
+    })
+}
+
+fn check_cors() -> SecurityFinding {
+    let finding = SecurityFinding::new("cors", "Cross-origin requests", FindingSeverity::Medium);
+    let origins = &CONFIG.server.cors_allowed_origins;
+    if origins.is_empty() {
+        finding.flag(
+            FindingStatus::Warn,
+            "Browsers may call the API from any origin",
+            "Set KUBARR_CORS_ALLOWED_ORIGINS to the address Kubarr is reached on, e.g. https://kubarr.example.com.",
+        )
+    } else {
+        finding.pass(format!("API calls are allowed from {}", origins.join(", ")))
+    }
+}
+
+async fn check_stored_secrets(db: &DbConn) -> Result<SecurityFinding> {
+    let finding = SecurityFinding::new(
+        "stored_secrets",
+        "Secrets stored unencrypted",
+        FindingSeverity::Medium,
+    );
+    // Encrypted credentials are left out once a key is configured
+    let encrypted = crypto::enabled();
+    let mut counts = vec![
+        (AppIntegration::find().count(db).await?, "app integration"),
+        (
+            CloudflareTunnel::find().count(db).await?,
+            "Cloudflare tunnel",
+        ),
+    ];
+    if !encrypted {
+        counts.extend([
+            (
+                NotificationChannel::find().count(db).await?,
+                "notification channel",
+            ),
+            (VpnProvider::find().count(db).await?, "VPN provider"),
+            (
+                OauthProvider::find()
+                    .filter(oauth_provider::Column::ClientSecret.is_not_null())
+                    .count(db)
+                    .await?,
+                "OAuth provider",
+            ),
+        ]);
+    }
+    let stored: Vec<String> = counts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{} {}{}", count, what, if *count == 1 { "" } else { "s" }))
+        .collect();
+    Ok(if stored.is_empty() {
+        finding.pass("No third-party credentials are stored")
+    } else {
+        finding.flag(
+            FindingStatus::Warn,
+            format!(
+                "Credentials of {} are stored in the database in plain text",
+                stored.join(", ")
+            ),
+            if encrypted {
+                "Keep the database volume and its backups encrypted, limit access to the database secret, and remove credentials that are no longer used."
+            } else {
+                "Set KUBARR_ENCRYPTION_KEY to encrypt provider credentials, keep the database volume and its backups encrypted, and remove credentials that are no longer used."
+            },
+        )
+    })
+}
+
+fn check_audit_integrity() -> SecurityFinding {
+    let finding = SecurityFinding::new(
+        "audit_integrity",
+        "Tamper-evident audit log",
+        FindingSeverity::Low,
+    );
+    if CONFIG.audit.integrity_key.is_some() {
+        finding.pass("New audit log entries are chained with an HMAC key")
+    } else {
+        finding.flag(
+            FindingStatus::Warn,
+            "Audit log entries can be changed without detection",
+            "Set KUBARR_AUDIT_INTEGRITY_KEY to a random value of at least 32 characters.",
+        )
+    }
+}
+
+async fn check_stale_sessions(db: &DbConn, now: DateTime<Utc>) -> Result<SecurityFinding> {
+    let finding = SecurityFinding::new("stale_sessions", "Stale sessions", FindingSeverity::Low);
+    let stale = Session::find()
+        .filter(session::Column::IsRevoked.eq(false))
+        .filter(session::Column::ExpiresAt.gt(now))
+        .filter(session::Column::LastAccessedAt.lt(now - Duration::days(STALE_SESSION_DAYS)))
+        .count(db)
+        .await?;
+    Ok(if stale == 0 {
+        finding.pass(format!(
+            "No active session has been unused for {} days",
+            STALE_SESSION_DAYS
+        ))
+    } else {
+        finding.flag(
+            FindingStatus::Warn,
+            format!(
+                "{} active session{} unused for over {} days",
+                stale,
+                if stale == 1 {
+                    " has been"
+                } else {
+                    "s have been"
+                },
+                STALE_SESSION_DAYS
+            ),
+            "Sign out sessions that are no longer used from the account page.",
+        )
+    })
+}
+
+async fn check_ip_bans(db: &DbConn) -> Result<SecurityFinding> {
+    let finding = SecurityFinding::new(
+        "brute_force_protection",
+        "Brute-force protection",
+        FindingSeverity::Low,
+    );
+    let policy = ip_bans::policy(db).await?;
+    Ok(if policy.threshold == 0 {
+        finding.flag(
+            FindingStatus::Warn,
+            "Automatic IP bans are off",
+            "Set ip_ban_threshold to the number of failed sign-ins that bans an address, e.g. 10.",
+        )
+    } else {
+        finding.pass(format!(
+            "Addresses are banned after {} failed sign-ins in {} minutes",
+            policy.threshold,
+            policy.window.num_minutes()
+        ))
+    })
+}
+
+/// Run the checklist
+pub async fn build(db: &DbConn, now: DateTime<Utc>) -> Result<SecurityReport> {
+    let accounts = accounts(db).await?;
+    let (two_factor, two_factor_coverage) = check_two_factor(&accounts);
+    let findings = vec![
+        check_default_credentials(&accounts).await?,
+        two_factor,
+        check_role_separation(db, &accounts).await?,
+        check_cors(),
+        check_stored_secrets(db).await?,
+        check_audit_integrity(),
+        check_stale_sessions(db, now).await?,
+        check_ip_bans(db).await?,
+    ];
+    let count = |status| findings.iter().filter(|f| f.status == status).count();
+    Ok(SecurityReport {
+        generated_at: now,
+        score: score(&findings),
+        passed: count(FindingStatus::Pass),
+        warnings: count(FindingStatus::Warn),
+        failures: count(FindingStatus::Fail),
+        two_factor_coverage,
+        findings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(status: FindingStatus, severity: FindingSeverity) -> SecurityFinding {
+        let mut finding = SecurityFinding::new("check", "Check", severity);
+        finding.status = status;
+        finding
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score(&[]), 100);
+        assert_eq!(
+            score(&[
+                finding(FindingStatus::Pass, FindingSeverity::High),
+                finding(FindingStatus::Fail, FindingSeverity::High),
+            ]),
+            50
+        );
+        // 6 + 2 + 0 of 6 + 4 + 2
+        assert_eq!(
+            score(&[
+                finding(FindingStatus::Pass, FindingSeverity::High),
+                finding(FindingStatus::Warn, FindingSeverity::Medium),
+                finding(FindingStatus::Fail, FindingSeverity::Low),
+            ]),
+            67
+        );
+    }
+
+    #[test]
+    fn test_list_names() {
+        let names: Vec<String> = (1..=7).map(|i| format!("u{}", i)).collect();
+        assert_eq!(list_names(&names[..2]), "u1, u2");
+        assert_eq!(list_names(&names), "u1, u2, u3, u4, u5 and 2 more");
+        assert_eq!(percent(1, 3), 33.3);
+        assert_eq!(percent(0, 0), 100.0);
+    }
+}
@@ -0,0 +1,447 @@
+//! Service accounts
+//!
+//! Automation identities, such as a CI job or a home automation hub, get a
+//! service account instead of a user login. A service account is a user row
+//! flagged with `is_service_account`: it holds roles like any user, but it
+//! has no usable password and cannot sign in, and it is left out of the user
+//! list and of sign-in analytics. It authenticates with API tokens sent as
+//! `Authorization: Bearer kbr_sa_...`.
+//!
+//! A token is shown once, when it is created; only its SHA-256 hash is
+//! stored. Tokens can expire, and deactivating or deleting the account stops
+//! all of them.
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use super::access::unexpired_roles;
+use super::security::generate_random_string;
+use crate::error::{AppError, Result};
+use crate::models::prelude::*;
+use crate::models::{role, service_account_token, user, user_role};
+use crate::state::DbConn;
+
+/// Prefix of service account tokens, which tells them apart from session
+/// tokens
+pub const TOKEN_PREFIX: &str = "kbr_sa_";
+
+/// Random bytes in a token
+const SECRET_BYTES: usize = 32;
+
+/// Characters of the secret kept in `token_prefix`
+const SHOWN_SECRET_CHARS: usize = 8;
+
+/// Longest token lifetime
+const MAX_TOKEN_DAYS: i64 = 3650;
+
+/// Stored instead of a password hash; no password verifies against it
+const NO_PASSWORD: &str = "!";
+
+/// Domain of the placeholder email addresses of service accounts
+const EMAIL_DOMAIN: &str = "service-accounts.invalid";
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateServiceAccountRequest {
+    /// Letters, digits, `-`, `_` and `.`; shared with usernames
+    pub name: String,
+    #[serde(default)]
+    pub role_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateServiceAccountRequest {
+    /// Deactivated accounts' tokens are refused
+    pub is_active: Option<bool>,
+    /// Replaces the account's roles
+    pub role_ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceAccountRole {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceAccountInfo {
+    pub id: i64,
+    pub name: String,
+    pub is_active: bool,
+    pub roles: Vec<ServiceAccountRole>,
+    /// Tokens that have not expired
+    pub active_tokens: u64,
+    /// Last use of any of its tokens
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateTokenRequest {
+    /// Label shown in the list, e.g. `ci`
+    pub name: String,
+    /// Days until the token expires; leave out for a token that does not
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceAccountTokenInfo {
+    pub id: i64,
+    pub name: String,
+    /// Start of the token, to recognize it by
+    pub token_prefix: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<service_account_token::Model> for ServiceAccountTokenInfo {
+    fn from(model: service_account_token::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            token_prefix: model.token_prefix,
+            expires_at: model.expires_at,
+            last_used_at: model.last_used_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// A new token; `token` is not shown again
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CreatedToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ServiceAccountTokenInfo,
+}
+
+fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(AppError::BadRequest(
+            "Service account names are 1-64 letters, digits, '-', '_' or '.'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fail unless every role exists
+async fn check_roles(db: &impl sea_orm::ConnectionTrait, role_ids: &[i64]) -> Result<()> {
+    let mut unique = role_ids.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    let found = Role::find()
+        .filter(role::Column::Id.is_in(unique.clone()))
+        .count(db)
+        .await?;
+    if found != unique.len() as u64 {
+        return Err(AppError::BadRequest("Unknown role".to_string()));
+    }
+    Ok(())
+}
+
+async fn set_roles(
+    db: &impl sea_orm::ConnectionTrait,
+    user_id: i64,
+    role_ids: &[i64],
+) -> Result<()> {
+    check_roles(db, role_ids).await?;
+    UserRole::delete_many()
+        .filter(user_role::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    let mut unique = role_ids.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+    for role_id in unique {
+        user_role::ActiveModel {
+            user_id: Set(user_id),
+            role_id: Set(role_id),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// The service account with this ID
+pub async fn find(db: &DbConn, id: i64) -> Result<user::Model> {
+    User::find_by_id(id)
+        .filter(user::Column::IsServiceAccount.eq(true))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Service account not found".to_string()))
+}
+
+async fn info(db: &DbConn, account: user::Model, now: DateTime<Utc>) -> Result<ServiceAccountInfo> {
+    let roles = UserRole::find()
+        .find_also_related(Role)
+        .filter(user_role::Column::UserId.eq(account.id))
+        .filter(unexpired_roles())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(_, role)| role)
+        .map(|role| ServiceAccountRole {
+            id: role.id,
+            name: role.name,
+        })
+        .collect();
+    let tokens = ServiceAccountToken::find()
+        .filter(service_account_token::Column::UserId.eq(account.id))
+        .all(db)
+        .await?;
+    Ok(ServiceAccountInfo {
+        id: account.id,
+        name: account.username,
+        is_active: account.is_active,
+        roles,
+        active_tokens: tokens
+            .iter()
+            .filter(|t| t.expires_at.is_none_or(|at| at > now))
+            .count() as u64,
+        last_used_at: tokens.iter().filter_map(|t| t.last_used_at).max(),
+        created_at: account.created_at,
+    })
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+/// All service accounts, by name
+pub async fn list(db: &DbConn) -> Result<Vec<ServiceAccountInfo>> {
+    let now = Utc::now();
+    let accounts = User::find()
+        .filter(user::Column::IsServiceAccount.eq(true))
+        .order_by_asc(user::Column::Username)
+        .all(db)
+        .await?;
+    let mut infos = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        infos.push(info(db, account, now).await?);
+    }
+    Ok(infos)
+}
+
+pub async fn get(db: &DbConn, id: i64) -> Result<ServiceAccountInfo> {
+    let account = find(db, id).await?;
+    info(db, account, Utc::now()).await
+}
+
+/// Create a service account with the given roles
+pub async fn create(
+    db: &DbConn,
+    request: CreateServiceAccountRequest,
+) -> Result<ServiceAccountInfo> {
+    let name = request.name.trim();
+    validate_name(name)?;
+    let email = format!("{}@{}", name, EMAIL_DOMAIN);
+    let taken = User::find()
+        .filter(
+            user::Column::Username
+                .eq(name)
+                .or(user::Column::Email.eq(&email)),
+        )
+        .one(db)
+        .await?;
+    if taken.is_some() {
+        return Err(AppError::Conflict(format!(
+            "A user or service account named '{}' already exists",
+            name
+        )));
+    }
+
+    let now = Utc::now();
+    let txn = db.begin().await?;
+    let account = user::ActiveModel {
+        username: Set(name.to_string()),
+        email: Set(email),
+        hashed_password: Set(NO_PASSWORD.to_string()),
+        is_active: Set(true),
+        is_approved: Set(true),
+        is_service_account: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    set_roles(&txn, account.id, &request.role_ids).await?;
+    txn.commit().await?;
+
+    info(db, account, now).await
+}
+
+/// Activate or deactivate a service account, or replace its roles
+pub async fn update(
+    db: &DbConn,
+    id: i64,
+    request: UpdateServiceAccountRequest,
+) -> Result<ServiceAccountInfo> {
+    let account = find(db, id).await?;
+    let txn = db.begin().await?;
+    let mut model: user::ActiveModel = account.into();
+    if let Some(is_active) = request.is_active {
+        model.is_active = Set(is_active);
+    }
+    model.updated_at = Set(Utc::now());
+    let account = model.update(&txn).await?;
+    if let Some(role_ids) = &request.role_ids {
+        set_roles(&txn, id, role_ids).await?;
+    }
+    txn.commit().await?;
+
+    info(db, account, Utc::now()).await
+}
+
+/// Delete a service account with its tokens, returning what was deleted
+pub async fn delete(db: &DbConn, id: i64) -> Result<user::Model> {
+    let account = find(db, id).await?;
+    User::delete_by_id(id).exec(db).await?;
+    Ok(account)
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+/// A service account's tokens, newest first
+pub async fn list_tokens(db: &DbConn, id: i64) -> Result<Vec<ServiceAccountTokenInfo>> {
+    find(db, id).await?;
+    Ok(ServiceAccountToken::find()
+        .filter(service_account_token::Column::UserId.eq(id))
+        .order_by_desc(service_account_token::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+/// Create a token for a service account
+pub async fn create_token(
+    db: &DbConn,
+    id: i64,
+    request: CreateTokenRequest,
+) -> Result<CreatedToken> {
+    find(db, id).await?;
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("name is required".to_string()));
+    }
+    if let Some(days) = request.expires_in_days {
+        if !(1..=MAX_TOKEN_DAYS).contains(&days) {
+            return Err(AppError::BadRequest(format!(
+                "expires_in_days must be between 1 and {}",
+                MAX_TOKEN_DAYS
+            )));
+        }
+    }
+
+    let now = Utc::now();
+    let secret = generate_random_string(SECRET_BYTES);
+    let token = format!("{}{}", TOKEN_PREFIX, secret);
+    let model = service_account_token::ActiveModel {
+        user_id: Set(id),
+        name: Set(name.to_string()),
+        token_hash: Set(hash(&token)),
+        token_prefix: Set(format!("{}{}", TOKEN_PREFIX, &secret[..SHOWN_SECRET_CHARS])),
+        expires_at: Set(request
+            .expires_in_days
+            .map(|days| now + Duration::days(days))),
+        last_used_at: Set(None),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(CreatedToken {
+        token,
+        info: model.into(),
+    })
+}
+
+/// Revoke a service account's token, returning what was revoked
+pub async fn delete_token(
+    db: &DbConn,
+    id: i64,
+    token_id: i64,
+) -> Result<service_account_token::Model> {
+    let existing = ServiceAccountToken::find_by_id(token_id)
+        .filter(service_account_token::Column::UserId.eq(id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Token not found".to_string()))?;
+    ServiceAccountToken::delete_by_id(token_id).exec(db).await?;
+    Ok(existing)
+}
+
+// ============================================================================
+// Authentication
+// ============================================================================
+
+/// Service account a token belongs to; an error message when the token is
+/// unknown or expired
+pub async fn authenticate(
+    db: &DbConn,
+    token: &str,
+    now: DateTime<Utc>,
+) -> std::result::Result<i64, String> {
+    let found = ServiceAccountToken::find()
+        .filter(service_account_token::Column::TokenHash.eq(hash(token)))
+        .one(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Invalid service account token".to_string())?;
+    if found.expires_at.is_some_and(|at| at <= now) {
+        return Err("Service account token has expired".to_string());
+    }
+
+    let user_id = found.user_id;
+    let mut model: service_account_token::ActiveModel = found.into();
+    model.last_used_at = Set(Some(now));
+    if let Err(e) = model.update(db).await {
+        tracing::debug!("Failed to record service account token use: {}", e);
+    }
+    Ok(user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("ci-runner.home_1").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name("name@example.com").is_err());
+        assert!(validate_name(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_no_password_never_verifies() {
+        assert!(!crate::services::security::verify_password("", NO_PASSWORD));
+        assert!(!crate::services::security::verify_password(
+            "!",
+            NO_PASSWORD
+        ));
+    }
+}
@@ -0,0 +1,26 @@
+//! Session store helpers
+//!
+//! Revoked sessions are kept, flagged `is_revoked`, until the session cleanup
+//! task deletes them a day later, so recent revocations stay visible.
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use crate::error::Result;
+use crate::models::prelude::*;
+use crate::models::session;
+use crate::state::DbConn;
+
+/// Revoke every active session of a user except `keep`
+///
+/// Returns the number of sessions revoked.
+pub async fn revoke_user_sessions(db: &DbConn, user_id: i64, keep: Option<&str>) -> Result<u64> {
+    let mut query = Session::update_many()
+        .col_expr(session::Column::IsRevoked, Expr::value(true))
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::IsRevoked.eq(false));
+    if let Some(keep) = keep {
+        query = query.filter(session::Column::Id.ne(keep));
+    }
+    Ok(query.exec(db).await?.rows_affected)
+}
@@ -0,0 +1,721 @@
+//! SMB shares of storage roots
+//!
+//! Shared roots are served by a Samba release Kubarr installs from its
+//! managed chart into the `samba` namespace. The chart mounts the storage
+//! host path (the `storage_path` setting) at `/storage` and reads two files
+//! from the `samba-config` secret: `shares.conf`, the share sections Kubarr
+//! renders, and `smbpasswd`, the share users with their NT password hashes.
+//!
+//! Share users are Kubarr accounts that set a share password. Each one gets
+//! read access to a share when they may view and download its root, and
+//! write access when they may also write and delete there and the share is
+//! not read-only. [`ShareSyncTask`] re-renders the configuration every few
+//! minutes so role and account changes reach Samba, and only upgrades the
+//! release when the rendered configuration changed.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use md4::Md4;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::access::{get_user_permissions, get_user_role_names};
+use super::helm::{self, HelmEngine, HelmRelease};
+use super::k8s::K8sApi;
+use super::scheduler::PeriodicTask;
+use super::storage_roots::{self, StorageRoot};
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    Permission, StorageDelete, StorageDownload, StorageView, StorageWrite,
+};
+use crate::models::prelude::*;
+use crate::models::{share_credential, storage_share, user};
+use crate::state::{DbConn, SharedK8sClient};
+
+pub const SAMBA_NAMESPACE: &str = "samba";
+pub const SAMBA_RELEASE: &str = "samba";
+pub const SAMBA_SECRET: &str = "samba-config";
+const SAMBA_CHART_PATH: &str = "/app/charts/samba";
+
+/// Where the chart mounts the storage host path
+const STORAGE_MOUNT: &str = "/storage";
+
+/// Share users get Unix ids from here up, offset by their Kubarr id
+const UID_BASE: i64 = 10000;
+
+const SYNC_INTERVAL_SECS: u64 = 300;
+
+/// A user's access to a share
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ShareUser {
+    pub username: String,
+    /// "read" or "write"
+    pub access: String,
+    /// Whether they set a share password; users without one cannot sign in
+    pub has_password: bool,
+}
+
+/// A shared root with the users who can access it
+#[derive(Debug, Clone)]
+pub struct PlannedShare {
+    pub share: storage_share::Model,
+    /// `None` when the root is no longer configured or is outside the
+    /// storage host path; such shares are not served
+    pub root: Option<StorageRoot>,
+    pub users: Vec<ShareUser>,
+}
+
+/// Rendered Samba configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedConfig {
+    pub shares_conf: String,
+    pub smbpasswd: String,
+}
+
+impl RenderedConfig {
+    /// Digest of the configuration, passed to the chart so its pods restart
+    /// when it changes
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.shares_conf.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.smbpasswd.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// NT hash of a password (MD4 of its UTF-16LE encoding), uppercase hex
+pub fn nt_hash(password: &str) -> String {
+    let utf16: Vec<u8> = password
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    hex::encode_upper(Md4::digest(&utf16))
+}
+
+/// Whether a root can be shared: it must live under the storage host path
+pub fn is_shareable(root: &StorageRoot) -> bool {
+    !Path::new(&root.path).is_absolute()
+}
+
+/// Access a user with `roles` and `permissions` gets to a share of `root`
+fn access_for(
+    root: &StorageRoot,
+    roles: &[String],
+    permissions: &[String],
+    read_only: bool,
+) -> Option<&'static str> {
+    let has = |permission: &str| {
+        permissions.iter().any(|p| p == permission) && root.allows(roles, permission)
+    };
+    if !has(StorageView::NAME) || !has(StorageDownload::NAME) {
+        return None;
+    }
+    if !read_only && has(StorageWrite::NAME) && has(StorageDelete::NAME) {
+        Some("write")
+    } else {
+        Some("read")
+    }
+}
+
+/// The configured shares with the users of each
+pub async fn plan(db: &DbConn) -> Result<Vec<PlannedShare>> {
+    let shares = StorageShare::find()
+        .order_by_asc(storage_share::Column::Root)
+        .all(db)
+        .await?;
+    if shares.is_empty() {
+        return Ok(Vec::new());
+    }
+    let roots = storage_roots::configured_roots(db).await?;
+    let users = User::find()
+        .filter(user::Column::IsActive.eq(true))
+        .filter(user::Column::IsApproved.eq(true))
+        .order_by_asc(user::Column::Username)
+        .all(db)
+        .await?;
+    let with_password: Vec<i64> = ShareCredential::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| c.user_id)
+        .collect();
+
+    let mut grants = Vec::with_capacity(users.len());
+    for user in users {
+        let roles = get_user_role_names(db, user.id).await;
+        let permissions = get_user_permissions(db, user.id).await;
+        grants.push((user, roles, permissions));
+    }
+
+    Ok(shares
+        .into_iter()
+        .map(|share| {
+            let root = roots
+                .iter()
+                .find(|r| r.name == share.root && is_shareable(r))
+                .cloned();
+            let users = match &root {
+                Some(root) => grants
+                    .iter()
+                    .filter_map(|(user, roles, permissions)| {
+                        access_for(root, roles, permissions, share.read_only).map(|access| {
+                            ShareUser {
+                                username: user.username.clone(),
+                                access: access.to_string(),
+                                has_password: with_password.contains(&user.id),
+                            }
+                        })
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            PlannedShare { share, root, users }
+        })
+        .collect())
+}
+
+/// Render the share sections and password file for the planned shares
+///
+/// Only users with a share password are listed, and shares nobody can sign
+/// in to are left out, as Samba treats an empty user list as everyone.
+pub fn render(
+    planned: &[PlannedShare],
+    credentials: &[(user::Model, share_credential::Model)],
+) -> RenderedConfig {
+    let can_sign_in = |username: &str| credentials.iter().any(|(u, _)| u.username == username);
+
+    let mut shares_conf = String::new();
+    for planned_share in planned {
+        let Some(root) = &planned_share.root else {
+            continue;
+        };
+        let users: Vec<&ShareUser> = planned_share
+            .users
+            .iter()
+            .filter(|u| can_sign_in(&u.username))
+            .collect();
+        if users.is_empty() {
+            continue;
+        }
+        let writers: Vec<&str> = users
+            .iter()
+            .filter(|u| u.access == "write")
+            .map(|u| u.username.as_str())
+            .collect();
+        let path = if root.path.is_empty() {
+            STORAGE_MOUNT.to_string()
+        } else {
+            format!("{}/{}", STORAGE_MOUNT, root.path.trim_matches('/'))
+        };
+        shares_conf.push_str(&format!("[{}]\n", root.name));
+        shares_conf.push_str(&format!("   path = {}\n", path));
+        shares_conf.push_str("   browseable = yes\n");
+        shares_conf.push_str("   read only = yes\n");
+        shares_conf.push_str(&format!(
+            "   valid users = {}\n",
+            users
+                .iter()
+                .map(|u| u.username.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+        if !writers.is_empty() {
+            shares_conf.push_str(&format!("   write list = {}\n", writers.join(" ")));
+        }
+        shares_conf.push_str("   veto files = /.trash/\n\n");
+    }
+
+    let mut smbpasswd = String::new();
+    for (user, credential) in credentials {
+        smbpasswd.push_str(&format!(
+            "{}:{}:XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:{}:[U          ]:LCT-{:08X}:\n",
+            user.username,
+            UID_BASE + user.id,
+            credential.nt_hash,
+            credential.updated_at.timestamp()
+        ));
+    }
+
+    RenderedConfig {
+        shares_conf,
+        smbpasswd,
+    }
+}
+
+/// Share users with a password, and the password entries, ordered by name
+async fn credentials(db: &DbConn) -> Result<Vec<(user::Model, share_credential::Model)>> {
+    let mut credentials: Vec<(user::Model, share_credential::Model)> = ShareCredential::find()
+        .find_also_related(User)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(credential, user)| user.map(|u| (u, credential)))
+        .filter(|(u, _)| u.is_active && u.is_approved)
+        .collect();
+    credentials.sort_by(|a, b| a.0.username.cmp(&b.0.username));
+    Ok(credentials)
+}
+
+/// Checksum of the configuration the installed release was deployed with
+async fn deployed_checksum(helm: &dyn HelmEngine) -> Result<Option<String>> {
+    Ok(helm
+        .release_info(SAMBA_RELEASE, SAMBA_NAMESPACE)
+        .await?
+        .and_then(|info| {
+            info.values
+                .get("configChecksum")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+        }))
+}
+
+/// Bring the Samba release in line with the shares and share users
+///
+/// Installs or upgrades the release when there are shares, and removes it
+/// when there are none. Unless `force` is set, a release already deployed
+/// with the same configuration is left alone. Returns whether anything was
+/// changed.
+pub async fn sync(
+    db: &DbConn,
+    k8s: &dyn K8sApi,
+    helm: &dyn HelmEngine,
+    force: bool,
+) -> Result<bool> {
+    let planned = plan(db).await?;
+    let installed = helm
+        .release_info(SAMBA_RELEASE, SAMBA_NAMESPACE)
+        .await?
+        .is_some();
+
+    if planned.is_empty() {
+        if !installed {
+            return Ok(false);
+        }
+        helm.uninstall(SAMBA_RELEASE, SAMBA_NAMESPACE).await?;
+        k8s.delete_secret(SAMBA_NAMESPACE, SAMBA_SECRET).await?;
+        return Ok(true);
+    }
+
+    let storage_path = get_setting_value(db, "storage_path")
+        .await?
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| AppError::BadRequest("Storage is not configured".to_string()))?;
+
+    let config = render(&planned, &credentials(db).await?);
+    let checksum = config.checksum();
+    if !force && installed && deployed_checksum(helm).await?.as_deref() == Some(&checksum) {
+        return Ok(false);
+    }
+
+    let release = HelmRelease {
+        name: SAMBA_RELEASE.to_string(),
+        chart: SAMBA_CHART_PATH.to_string(),
+        namespace: SAMBA_NAMESPACE.to_string(),
+        create_namespace: true,
+        set: vec![
+            "storage.hostPath.enabled=true".to_string(),
+            format!("storage.hostPath.rootPath={}", storage_path),
+            format!("config.existingSecret={}", SAMBA_SECRET),
+        ],
+        set_string: vec![format!("configChecksum={}", checksum)],
+        ..Default::default()
+    };
+    // The secret goes first so upgraded pods start with it; a first install
+    // has to create the namespace, and its pods wait for the secret
+    if installed {
+        write_secret(k8s, &config).await?;
+        helm.upgrade_install(&release).await?;
+    } else {
+        helm.upgrade_install(&release).await?;
+        write_secret(k8s, &config).await?;
+    }
+    Ok(true)
+}
+
+async fn write_secret(k8s: &dyn K8sApi, config: &RenderedConfig) -> Result<()> {
+    let data = BTreeMap::from([
+        (
+            "shares.conf".to_string(),
+            k8s_openapi::ByteString(config.shares_conf.clone().into_bytes()),
+        ),
+        (
+            "smbpasswd".to_string(),
+            k8s_openapi::ByteString(config.smbpasswd.clone().into_bytes()),
+        ),
+    ]);
+    let secret = Secret {
+        metadata: ObjectMeta {
+            name: Some(SAMBA_SECRET.to_string()),
+            namespace: Some(SAMBA_NAMESPACE.to_string()),
+            labels: Some(BTreeMap::from([(
+                "app.kubernetes.io/managed-by".to_string(),
+                "kubarr".to_string(),
+            )])),
+            ..Default::default()
+        },
+        data: Some(data),
+        ..Default::default()
+    };
+    k8s.replace_secret(SAMBA_NAMESPACE, secret).await
+}
+
+/// Share a storage root
+pub async fn create(db: &DbConn, root: &str, read_only: bool) -> Result<storage_share::Model> {
+    let roots = storage_roots::configured_roots(db).await?;
+    let root = roots
+        .iter()
+        .find(|r| r.name == root)
+        .ok_or_else(|| AppError::NotFound(format!("Storage root '{}' not found", root)))?;
+    if !is_shareable(root) {
+        return Err(AppError::BadRequest(format!(
+            "Storage root '{}' is outside the storage path and cannot be shared",
+            root.name
+        )));
+    }
+    let existing = StorageShare::find()
+        .filter(storage_share::Column::Root.eq(&root.name))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Storage root '{}' is already shared",
+            root.name
+        )));
+    }
+    Ok(storage_share::ActiveModel {
+        root: Set(root.name.clone()),
+        read_only: Set(read_only),
+        created_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Stop sharing a storage root
+pub async fn remove(db: &DbConn, root: &str) -> Result<()> {
+    let result = StorageShare::delete_many()
+        .filter(storage_share::Column::Root.eq(root))
+        .exec(db)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "Storage root '{}' is not shared",
+            root
+        )));
+    }
+    Ok(())
+}
+
+/// Set a user's share password
+pub async fn set_password(db: &DbConn, user_id: i64, password: &str) -> Result<()> {
+    if password.len() < 8 {
+        return Err(AppError::BadRequest(
+            "Share password must be at least 8 characters".to_string(),
+        ));
+    }
+    let hash = nt_hash(password);
+    match ShareCredential::find_by_id(user_id).one(db).await? {
+        Some(existing) => {
+            let mut active: share_credential::ActiveModel = existing.into();
+            active.nt_hash = Set(hash);
+            active.updated_at = Set(Utc::now());
+            active.update(db).await?;
+        }
+        None => {
+            share_credential::ActiveModel {
+                user_id: Set(user_id),
+                nt_hash: Set(hash),
+                updated_at: Set(Utc::now()),
+            }
+            .insert(db)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove a user's share password, so they can no longer sign in to shares
+pub async fn clear_password(db: &DbConn, user_id: i64) -> Result<()> {
+    ShareCredential::delete_by_id(user_id).exec(db).await?;
+    Ok(())
+}
+
+pub async fn has_password(db: &DbConn, user_id: i64) -> Result<bool> {
+    Ok(ShareCredential::find_by_id(user_id)
+        .one(db)
+        .await?
+        .is_some())
+}
+
+/// A share as seen by one user
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ShareInfo {
+    /// Name of the shared storage root
+    pub name: String,
+    pub read_only: bool,
+    /// Whether the root still exists and can be shared
+    pub available: bool,
+    /// The caller's access: "read", "write", or null for none
+    pub access: Option<String>,
+    /// Everyone with access; only shown to users who manage settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<ShareUser>>,
+}
+
+/// Status of the Samba release and the shares
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SharesStatus {
+    /// "not_deployed", "deploying", "running" or "unavailable" (no cluster)
+    pub status: String,
+    pub ready_pods: i32,
+    pub total_pods: i32,
+    /// Whether the deployed configuration matches the current shares and users
+    pub in_sync: bool,
+    /// Error of the last sync, when it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the caller set a share password
+    pub has_password: bool,
+    pub shares: Vec<ShareInfo>,
+}
+
+/// Status of the shares for `caller`, with the user lists when `show_users`
+pub async fn status(
+    db: &DbConn,
+    k8s: Option<&dyn K8sApi>,
+    helm: &dyn HelmEngine,
+    caller: &user::Model,
+    show_users: bool,
+) -> Result<SharesStatus> {
+    let planned = plan(db).await?;
+    let (status, ready_pods, total_pods) = match k8s {
+        None => ("unavailable", 0, 0),
+        Some(k8s) => {
+            let pods = k8s
+                .get_pod_status(SAMBA_NAMESPACE, None)
+                .await
+                .unwrap_or_default();
+            let total = pods.len() as i32;
+            let ready = pods.iter().filter(|p| p.ready).count() as i32;
+            let status = if total == 0 {
+                "not_deployed"
+            } else if ready == total {
+                "running"
+            } else {
+                "deploying"
+            };
+            (status, ready, total)
+        }
+    };
+
+    let in_sync = if planned.is_empty() {
+        helm.release_info(SAMBA_RELEASE, SAMBA_NAMESPACE)
+            .await?
+            .is_none()
+    } else {
+        let config = render(&planned, &credentials(db).await?);
+        deployed_checksum(helm).await?.as_deref() == Some(config.checksum().as_str())
+    };
+
+    let shares = planned
+        .into_iter()
+        .map(|p| ShareInfo {
+            name: p.share.root,
+            read_only: p.share.read_only,
+            available: p.root.is_some(),
+            access: p
+                .users
+                .iter()
+                .find(|u| u.username == caller.username)
+                .map(|u| u.access.clone()),
+            users: show_users.then_some(p.users),
+        })
+        .collect();
+
+    Ok(SharesStatus {
+        status: status.to_string(),
+        ready_pods,
+        total_pods,
+        in_sync,
+        error: None,
+        has_password: has_password(db, caller.id).await?,
+        shares,
+    })
+}
+
+/// Periodically apply account and role changes to the Samba release
+pub struct ShareSyncTask {
+    pub k8s_client: SharedK8sClient,
+}
+
+#[async_trait]
+impl PeriodicTask for ShareSyncTask {
+    fn name(&self) -> &'static str {
+        "share_sync"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(SYNC_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let k8s_guard = self.k8s_client.read().await;
+        let Some(k8s) = k8s_guard.as_ref() else {
+            return Ok(());
+        };
+        let helm = helm::from_config();
+        if sync(db, k8s, helm.as_ref(), false).await? {
+            tracing::info!("Updated the Samba release for storage shares");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(name: &str, path: &str) -> StorageRoot {
+        StorageRoot {
+            name: name.to_string(),
+            path: path.to_string(),
+            protected_folders: Vec::new(),
+            roles: BTreeMap::new(),
+            homes: None,
+            tenants: Vec::new(),
+        }
+    }
+
+    fn user(id: i64, username: &str) -> user::Model {
+        user::Model {
+            id,
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            hashed_password: String::new(),
+            is_active: true,
+            is_approved: true,
+            totp_secret: None,
+            totp_enabled: false,
+            totp_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            is_service_account: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_nt_hash() {
+        // Reference value from the MS-NLMP test vectors
+        assert_eq!(nt_hash("Password"), "A4F49C406510BDCAB6824EE7C30FD852");
+    }
+
+    #[test]
+    fn test_access_for() {
+        let open = root("media", "media");
+        let all = |perms: &[&str]| perms.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let roles = vec!["viewer".to_string()];
+        assert_eq!(
+            access_for(&open, &roles, &all(&["storage.view"]), false),
+            None
+        );
+        assert_eq!(
+            access_for(
+                &open,
+                &roles,
+                &all(&["storage.view", "storage.download"]),
+                false
+            ),
+            Some("read")
+        );
+        let everything = all(&[
+            "storage.view",
+            "storage.download",
+            "storage.write",
+            "storage.delete",
+        ]);
+        assert_eq!(access_for(&open, &roles, &everything, false), Some("write"));
+        assert_eq!(access_for(&open, &roles, &everything, true), Some("read"));
+
+        let mut limited = root("backups", "backups");
+        limited.roles.insert(
+            "viewer".to_string(),
+            vec!["storage.view".to_string(), "storage.download".to_string()],
+        );
+        assert_eq!(
+            access_for(&limited, &roles, &everything, false),
+            Some("read")
+        );
+        assert_eq!(
+            access_for(&limited, &["admin".to_string()], &everything, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let share = |id: i64, root: &str| storage_share::Model {
+            id,
+            root: root.to_string(),
+            read_only: false,
+            created_at: Utc::now(),
+        };
+        let share_user = |username: &str, access: &str| ShareUser {
+            username: username.to_string(),
+            access: access.to_string(),
+            has_password: true,
+        };
+        let planned = vec![
+            PlannedShare {
+                share: share(1, "media"),
+                root: Some(root("media", "media")),
+                users: vec![share_user("alice", "write"), share_user("bob", "read")],
+            },
+            PlannedShare {
+                share: share(2, "data"),
+                root: Some(root("data", "")),
+                users: vec![share_user("carol", "read")],
+            },
+            PlannedShare {
+                share: share(3, "gone"),
+                root: None,
+                users: Vec::new(),
+            },
+        ];
+        let credential = |user_id: i64| share_credential::Model {
+            user_id,
+            nt_hash: "HASH".to_string(),
+            updated_at: chrono::DateTime::from_timestamp(255, 0).unwrap(),
+        };
+        let credentials = vec![
+            (user(1, "alice"), credential(1)),
+            (user(2, "bob"), credential(2)),
+        ];
+
+        let config = render(&planned, &credentials);
+        assert_eq!(
+            config.shares_conf,
+            "[media]\n   path = /storage/media\n   browseable = yes\n   read only = yes\n   \
+             valid users = alice bob\n   write list = alice\n   veto files = /.trash/\n\n"
+        );
+        assert_eq!(
+            config.smbpasswd,
+            "alice:10001:XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:HASH:[U          ]:LCT-000000FF:\n\
+             bob:10002:XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX:HASH:[U          ]:LCT-000000FF:\n"
+        );
+        assert_ne!(config.checksum(), render(&planned, &[]).checksum());
+    }
+}
@@ -0,0 +1,427 @@
+//! Named storage roots for the storage browser
+//!
+//! Without configuration there is one root, `data`, at `KUBARR_STORAGE_PATH`
+//! (default `/data`) with the `downloads` and `media` folders protected. The
+//! `storage_roots` setting replaces it with a JSON list of named roots, e.g.
+//!
+//! ```json
+//! [
+//!   {"name": "media", "path": "media", "protected_folders": ["movies", "tv"]},
+//!   {"name": "backups", "path": "/backups",
+//!    "roles": {"admin": ["storage.view", "storage.download", "storage.delete"]}}
+//! ]
+//! ```
+//!
+//! Relative paths are resolved against `KUBARR_STORAGE_PATH`. A root without
+//! `roles` follows the caller's storage permissions. A root with `roles` is
+//! only visible to users with one of those roles, and then only allows the
+//! storage permissions listed for them; the caller still needs the
+//! permission itself as well. A root with `tenants`, a list of tenant
+//! names, is only visible to members of those tenants; see
+//! [`super::tenants`].
+//!
+//! A root with `homes`, e.g. `{"folder": "home", "soft_quota": "50Gi",
+//! "hard_quota": "60Gi"}`, gives each user a home folder under it; see
+//! [`super::homes`]. The homes folder is protected like `protected_folders`.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use sea_orm::EntityTrait;
+use serde::{Deserialize, Serialize};
+
+use super::access::{get_user_permissions, get_user_role_names};
+use super::quotas;
+use super::tenants;
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{
+    Permission, StorageDelete, StorageDownload, StorageView, StorageWrite,
+};
+use crate::models::prelude::*;
+use crate::state::DbConn;
+
+/// Setting holding the JSON list of storage roots
+pub const STORAGE_ROOTS_SETTING: &str = "storage_roots";
+
+/// Name of the root used when `storage_roots` is empty
+pub const DEFAULT_ROOT: &str = "data";
+
+/// Folders protected in the default root
+const DEFAULT_PROTECTED_FOLDERS: &[&str] = &["downloads", "media"];
+
+/// Permissions a root can grant to a role
+const ROOT_PERMISSIONS: &[&str] = &[
+    StorageView::NAME,
+    StorageDownload::NAME,
+    StorageWrite::NAME,
+    StorageDelete::NAME,
+];
+
+/// A storage root as configured in the `storage_roots` setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageRoot {
+    pub name: String,
+    /// Absolute path, or a path relative to `KUBARR_STORAGE_PATH`
+    pub path: String,
+    /// Top-level folders of the root that cannot be deleted
+    #[serde(default)]
+    pub protected_folders: Vec<String>,
+    /// Storage permissions per role name; empty to follow the caller's own
+    /// storage permissions
+    #[serde(default)]
+    pub roles: BTreeMap<String, Vec<String>>,
+    /// Per-user home folders, if enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homes: Option<HomeFolders>,
+    /// Names of the tenants the root belongs to; empty to share it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tenants: Vec<String>,
+}
+
+/// Home folder settings of a storage root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HomeFolders {
+    /// Top-level folder holding one folder per username
+    #[serde(default = "default_homes_folder")]
+    pub folder: String,
+    /// Usage that triggers a warning, as a quantity such as `50Gi`
+    #[serde(default)]
+    pub soft_quota: Option<String>,
+    /// Usage above which uploads and new folders are refused
+    #[serde(default)]
+    pub hard_quota: Option<String>,
+}
+
+fn default_homes_folder() -> String {
+    "home".to_string()
+}
+
+impl HomeFolders {
+    pub fn soft_quota_bytes(&self) -> Option<i64> {
+        self.soft_quota.as_deref().and_then(quotas::parse_bytes)
+    }
+
+    pub fn hard_quota_bytes(&self) -> Option<i64> {
+        self.hard_quota.as_deref().and_then(quotas::parse_bytes)
+    }
+}
+
+impl StorageRoot {
+    fn default_root() -> Self {
+        Self {
+            name: DEFAULT_ROOT.to_string(),
+            path: String::new(),
+            protected_folders: DEFAULT_PROTECTED_FOLDERS
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            roles: BTreeMap::new(),
+            homes: None,
+            tenants: Vec::new(),
+        }
+    }
+
+    /// Directory the root serves
+    pub fn resolve(&self) -> PathBuf {
+        let path = Path::new(&self.path);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            storage_base().join(path)
+        }
+    }
+
+    /// Whether a user with `roles` may use `permission` on this root
+    pub fn allows(&self, roles: &[String], permission: &str) -> bool {
+        self.roles.is_empty()
+            || roles.iter().any(|role| {
+                self.roles
+                    .get(role)
+                    .is_some_and(|granted| granted.iter().any(|p| p == permission))
+            })
+    }
+
+    /// Whether a user who can see the tenants named in `visible` (`None`:
+    /// every tenant) can see this root
+    pub fn in_tenants(&self, visible: Option<&[String]>) -> bool {
+        self.tenants.is_empty()
+            || visible.is_none_or(|names| self.tenants.iter().any(|t| names.contains(t)))
+    }
+
+    pub fn is_protected(&self, folder: &str) -> bool {
+        self.protected_folders.iter().any(|f| f == folder)
+    }
+}
+
+/// A root visible to the caller, as listed by `GET /api/storage/roots`
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct StorageRootInfo {
+    pub name: String,
+    pub protected_folders: Vec<String>,
+    /// Storage permissions the caller has on this root
+    pub permissions: Vec<String>,
+}
+
+/// `KUBARR_STORAGE_PATH`, where relative roots live
+fn storage_base() -> PathBuf {
+    PathBuf::from(std::env::var("KUBARR_STORAGE_PATH").unwrap_or_else(|_| "/data".to_string()))
+}
+
+/// Parse and validate the `storage_roots` setting; empty means the default
+/// root only
+pub fn parse_roots(value: &str) -> Result<Vec<StorageRoot>> {
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let roots: Vec<StorageRoot> = serde_json::from_str(value)
+        .map_err(|e| AppError::BadRequest(format!("Invalid storage roots: {}", e)))?;
+
+    let mut names = Vec::new();
+    for root in &roots {
+        let valid_name = !root.name.is_empty()
+            && root
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(AppError::BadRequest(format!(
+                "Invalid storage root name '{}': use lowercase letters, digits, '-' and '_'",
+                root.name
+            )));
+        }
+        if names.contains(&root.name) {
+            return Err(AppError::BadRequest(format!(
+                "Duplicate storage root '{}'",
+                root.name
+            )));
+        }
+        names.push(root.name.clone());
+
+        if Path::new(&root.path)
+            .components()
+            .any(|c| c == Component::ParentDir)
+        {
+            return Err(AppError::BadRequest(format!(
+                "Storage root '{}' path must not contain '..'",
+                root.name
+            )));
+        }
+        for folder in &root.protected_folders {
+            if !is_folder_name(folder) {
+                return Err(AppError::BadRequest(format!(
+                    "Protected folder '{}' of storage root '{}' must be a top-level folder name",
+                    folder, root.name
+                )));
+            }
+        }
+        if let Some(homes) = &root.homes {
+            validate_homes(&root.name, homes)?;
+        }
+        for permission in root.roles.values().flatten() {
+            if !ROOT_PERMISSIONS.contains(&permission.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown storage permission '{}' for storage root '{}'",
+                    permission, root.name
+                )));
+            }
+        }
+    }
+    Ok(roots)
+}
+
+/// Whether `folder` is a single path component
+pub fn is_folder_name(folder: &str) -> bool {
+    let mut components = Path::new(folder).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+fn validate_homes(root: &str, homes: &HomeFolders) -> Result<()> {
+    if !is_folder_name(&homes.folder) || homes.folder == super::trash::TRASH_DIR {
+        return Err(AppError::BadRequest(format!(
+            "Homes folder '{}' of storage root '{}' must be a top-level folder name",
+            homes.folder, root
+        )));
+    }
+    for (kind, value, bytes) in [
+        ("soft", &homes.soft_quota, homes.soft_quota_bytes()),
+        ("hard", &homes.hard_quota, homes.hard_quota_bytes()),
+    ] {
+        if let Some(value) = value {
+            if bytes.is_none_or(|b| b < 0) {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid {} quota '{}' for the homes of storage root '{}'",
+                    kind, value, root
+                )));
+            }
+        }
+    }
+    if let (Some(soft), Some(hard)) = (homes.soft_quota_bytes(), homes.hard_quota_bytes()) {
+        if soft > hard {
+            return Err(AppError::BadRequest(format!(
+                "The soft quota of the homes of storage root '{}' exceeds its hard quota",
+                root
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The configured roots, or the default root
+pub async fn configured_roots(db: &DbConn) -> Result<Vec<StorageRoot>> {
+    let setting = SystemSetting::find_by_id(STORAGE_ROOTS_SETTING)
+        .one(db)
+        .await?;
+    let roots = match setting {
+        Some(setting) => parse_roots(&setting.value)
+            .map_err(|e| AppError::Internal(format!("Invalid storage_roots setting: {}", e)))?,
+        None => Vec::new(),
+    };
+    if roots.is_empty() {
+        return Ok(vec![StorageRoot::default_root()]);
+    }
+    Ok(roots
+        .into_iter()
+        .map(|mut root| {
+            if let Some(homes) = &root.homes {
+                if !root.is_protected(&homes.folder) {
+                    root.protected_folders.push(homes.folder.clone());
+                }
+            }
+            root
+        })
+        .collect())
+}
+
+/// The root a request names (default: the first root), if the user may use
+/// `permission` on it
+///
+/// Roots the user cannot see are reported as not found.
+pub async fn root_for(
+    db: &DbConn,
+    user_id: i64,
+    name: Option<&str>,
+    permission: &str,
+) -> Result<StorageRoot> {
+    let roots = configured_roots(db).await?;
+    let root = match name.filter(|n| !n.is_empty()) {
+        Some(name) => roots.into_iter().find(|r| r.name == name),
+        None => roots.into_iter().next(),
+    };
+    let not_found = || {
+        AppError::NotFound(format!(
+            "Storage root '{}' not found",
+            name.unwrap_or_default()
+        ))
+    };
+    let root = root.ok_or_else(not_found)?;
+
+    let roles = get_user_role_names(db, user_id).await;
+    let tenants = tenants::scope_names(db, user_id).await?;
+    if !root.allows(&roles, StorageView::NAME) || !root.in_tenants(tenants.as_deref()) {
+        return Err(not_found());
+    }
+    if !root.allows(&roles, permission) {
+        return Err(AppError::Forbidden(format!(
+            "Permission denied: {} required on storage root '{}'",
+            permission, root.name
+        )));
+    }
+    Ok(root)
+}
+
+/// The roots a user can see, with their permissions on each
+pub async fn visible_roots(db: &DbConn, user_id: i64) -> Result<Vec<StorageRootInfo>> {
+    let roles = get_user_role_names(db, user_id).await;
+    let permissions = get_user_permissions(db, user_id).await;
+    let tenants = tenants::scope_names(db, user_id).await?;
+    Ok(configured_roots(db)
+        .await?
+        .into_iter()
+        .filter(|root| root.allows(&roles, StorageView::NAME))
+        .filter(|root| root.in_tenants(tenants.as_deref()))
+        .map(|root| StorageRootInfo {
+            permissions: ROOT_PERMISSIONS
+                .iter()
+                .filter(|p| permissions.iter().any(|granted| granted == *p))
+                .filter(|p| root.allows(&roles, p))
+                .map(|p| p.to_string())
+                .collect(),
+            name: root.name,
+            protected_folders: root.protected_folders,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_roots() {
+        assert!(parse_roots("").unwrap().is_empty());
+
+        let roots = parse_roots(
+            r#"[
+                {"name": "media", "path": "media", "protected_folders": ["movies"]},
+                {"name": "backups", "path": "/backups", "roles": {"admin": ["storage.view"]}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].protected_folders, vec!["movies"]);
+        assert!(roots[0].roles.is_empty());
+        assert_eq!(roots[1].resolve(), PathBuf::from("/backups"));
+        assert!(roots[0].homes.is_none());
+
+        let roots =
+            parse_roots(r#"[{"name": "users", "path": "users", "homes": {"hard_quota": "1Gi"}}]"#)
+                .unwrap();
+        let homes = roots[0].homes.as_ref().unwrap();
+        assert_eq!(homes.folder, "home");
+        assert_eq!(homes.soft_quota_bytes(), None);
+        assert_eq!(homes.hard_quota_bytes(), Some(1024 * 1024 * 1024));
+
+        for invalid in [
+            "not json",
+            r#"[{"name": "Media", "path": "media"}]"#,
+            r#"[{"name": "a", "path": "a"}, {"name": "a", "path": "b"}]"#,
+            r#"[{"name": "a", "path": "../etc"}]"#,
+            r#"[{"name": "a", "path": "a", "protected_folders": ["x/y"]}]"#,
+            r#"[{"name": "a", "path": "a", "roles": {"viewer": ["apps.view"]}}]"#,
+            r#"[{"name": "a", "path": "a", "homes": {"folder": "a/b"}}]"#,
+            r#"[{"name": "a", "path": "a", "homes": {"soft_quota": "lots"}}]"#,
+            r#"[{"name": "a", "path": "a", "homes": {"soft_quota": "2Gi", "hard_quota": "1Gi"}}]"#,
+        ] {
+            assert!(parse_roots(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_root_allows() {
+        let mut root = StorageRoot::default_root();
+        let viewer = vec!["viewer".to_string()];
+        assert!(root.allows(&viewer, StorageWrite::NAME));
+        assert!(root.is_protected("media"));
+
+        root.roles
+            .insert("viewer".to_string(), vec![StorageView::NAME.to_string()]);
+        assert!(root.allows(&viewer, StorageView::NAME));
+        assert!(!root.allows(&viewer, StorageWrite::NAME));
+        assert!(!root.allows(&["user".to_string()], StorageView::NAME));
+    }
+
+    #[test]
+    fn test_root_in_tenants() {
+        let mut root = StorageRoot::default_root();
+        assert!(root.in_tenants(Some(&[])));
+
+        root.tenants = vec!["smiths".to_string()];
+        assert!(root.in_tenants(None));
+        assert!(root.in_tenants(Some(&["smiths".to_string()])));
+        assert!(!root.in_tenants(Some(&["joneses".to_string()])));
+        assert!(!root.in_tenants(Some(&[])));
+    }
+}
@@ -0,0 +1,368 @@
+//! Kubarr's own operational metrics
+//!
+//! `GET /metrics` serves these in the Prometheus text format, so Kubarr can
+//! be scraped alongside the apps it manages:
+//!
+//! - `kubarr_http_request_duration_seconds`: a histogram per method, route
+//!   template and status, recorded by [`crate::middleware::record_metrics`]
+//! - `kubarr_http_requests_in_flight`
+//! - `kubarr_db_pool_connections` and `kubarr_db_pool_max_connections`
+//! - `kubarr_notifications_sent_total` and
+//!   `kubarr_notification_send_failures_total` per channel
+//! - `kubarr_kubernetes_client_errors_total` per API status code, or
+//!   `none` when the cluster could not be reached
+//!
+//! Everything is kept in memory and starts from zero on restart, as
+//! Prometheus expects of counters.
+
+pub mod prometheus;
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection};
+
+use self::prometheus::{format_bound, Exposition};
+use crate::config::CONFIG;
+use crate::state::AppState;
+
+/// Route label of requests no route matched, such as frontend assets
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Upper bounds of the request duration buckets, in seconds
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    /// Observations at or below each bound of [`DURATION_BUCKETS`]
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            buckets: [0; DURATION_BUCKETS.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+/// Labels of a request duration series
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct NotificationCounts {
+    sent: u64,
+    failed: u64,
+}
+
+/// Connection pool of the database, when one is connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+/// Read the pool statistics of `db`
+pub fn pool_stats(db: &DatabaseConnection) -> Option<PoolStats> {
+    match db.get_database_backend() {
+        DatabaseBackend::Postgres => {
+            let pool = db.get_postgres_connection_pool();
+            Some(PoolStats {
+                size: pool.size(),
+                idle: pool.num_idle() as u32,
+                max: pool.options().get_max_connections(),
+            })
+        }
+        DatabaseBackend::Sqlite => {
+            let pool = db.get_sqlite_connection_pool();
+            Some(PoolStats {
+                size: pool.size(),
+                idle: pool.num_idle() as u32,
+                max: pool.options().get_max_connections(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// In-memory metrics registry
+pub struct Metrics {
+    started_at: DateTime<Utc>,
+    requests: Mutex<BTreeMap<RequestKey, Histogram>>,
+    in_flight: AtomicI64,
+    notifications: Mutex<BTreeMap<String, NotificationCounts>>,
+    kubernetes_errors: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Counts a request as in flight until dropped
+pub struct InFlight<'a>(&'a AtomicI64);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            requests: Mutex::new(BTreeMap::new()),
+            in_flight: AtomicI64::new(0),
+            notifications: Mutex::new(BTreeMap::new()),
+            kubernetes_errors: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn request_started(&self) -> InFlight<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(&self.in_flight)
+    }
+
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        let key = RequestKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        self.requests
+            .lock()
+            .entry(key)
+            .or_insert_with(Histogram::new)
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_notification(&self, channel: &str, success: bool) {
+        let mut notifications = self.notifications.lock();
+        let counts = notifications.entry(channel.to_string()).or_default();
+        if success {
+            counts.sent += 1;
+        } else {
+            counts.failed += 1;
+        }
+    }
+
+    pub fn record_kubernetes_error(&self, error: &kube::Error) {
+        let code = match error {
+            kube::Error::Api(status) => status.code.to_string(),
+            _ => "none".to_string(),
+        };
+        *self.kubernetes_errors.lock().entry(code).or_default() += 1;
+    }
+
+    /// The metrics in the Prometheus text format
+    pub fn render(&self, pool: Option<PoolStats>) -> String {
+        let mut out = Exposition::new();
+
+        out.family("kubarr_build_info", "gauge", "Kubarr's version")
+            .sample(
+                "kubarr_build_info",
+                &[("version", &CONFIG.version), ("channel", &CONFIG.channel)],
+                1.0,
+            );
+        out.family(
+            "kubarr_start_time_seconds",
+            "gauge",
+            "When the backend started, as a Unix timestamp",
+        )
+        .sample(
+            "kubarr_start_time_seconds",
+            &[],
+            self.started_at.timestamp() as f64,
+        );
+
+        out.family(
+            "kubarr_http_requests_in_flight",
+            "gauge",
+            "Requests being handled",
+        )
+        .sample(
+            "kubarr_http_requests_in_flight",
+            &[],
+            self.in_flight.load(Ordering::Relaxed) as f64,
+        );
+
+        const DURATION: &str = "kubarr_http_request_duration_seconds";
+        out.family(DURATION, "histogram", "Time to answer HTTP requests");
+        for (key, histogram) in self.requests.lock().iter() {
+            let status = key.status.to_string();
+            let labels = [
+                ("method", key.method.as_str()),
+                ("route", key.route.as_str()),
+                ("status", status.as_str()),
+            ];
+            for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                let le = format_bound(*bound);
+                let mut bucket_labels = labels.to_vec();
+                bucket_labels.push(("le", &le));
+                out.sample(
+                    &format!("{}_bucket", DURATION),
+                    &bucket_labels,
+                    count as f64,
+                );
+            }
+            let mut inf_labels = labels.to_vec();
+            inf_labels.push(("le", "+Inf"));
+            out.sample(
+                &format!("{}_bucket", DURATION),
+                &inf_labels,
+                histogram.count as f64,
+            )
+            .sample(&format!("{}_sum", DURATION), &labels, histogram.sum)
+            .sample(
+                &format!("{}_count", DURATION),
+                &labels,
+                histogram.count as f64,
+            );
+        }
+
+        if let Some(pool) = pool {
+            out.family(
+                "kubarr_db_pool_connections",
+                "gauge",
+                "Open database connections by state",
+            )
+            .sample(
+                "kubarr_db_pool_connections",
+                &[("state", "idle")],
+                pool.idle as f64,
+            )
+            .sample(
+                "kubarr_db_pool_connections",
+                &[("state", "in_use")],
+                pool.size.saturating_sub(pool.idle) as f64,
+            );
+            out.family(
+                "kubarr_db_pool_max_connections",
+                "gauge",
+                "Largest number of database connections the pool opens",
+            )
+            .sample("kubarr_db_pool_max_connections", &[], pool.max as f64);
+        }
+
+        let notifications = self.notifications.lock();
+        out.family(
+            "kubarr_notifications_sent_total",
+            "counter",
+            "Notifications delivered by channel",
+        );
+        for (channel, counts) in notifications.iter() {
+            out.sample(
+                "kubarr_notifications_sent_total",
+                &[("channel", channel)],
+                counts.sent as f64,
+            );
+        }
+        out.family(
+            "kubarr_notification_send_failures_total",
+            "counter",
+            "Notifications a channel failed to deliver",
+        );
+        for (channel, counts) in notifications.iter() {
+            out.sample(
+                "kubarr_notification_send_failures_total",
+                &[("channel", channel)],
+                counts.failed as f64,
+            );
+        }
+        drop(notifications);
+
+        out.family(
+            "kubarr_kubernetes_client_errors_total",
+            "counter",
+            "Failed Kubernetes API calls by status code; none when no response came",
+        );
+        for (code, count) in self.kubernetes_errors.lock().iter() {
+            out.sample(
+                "kubarr_kubernetes_client_errors_total",
+                &[("code", code)],
+                *count as f64,
+            );
+        }
+
+        out.finish()
+    }
+}
+
+/// The current metrics, including the database pool
+pub async fn scrape(state: &AppState) -> String {
+    let pool = state.get_db().await.ok().and_then(|db| pool_stats(&db));
+    METRICS.render(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::new();
+        histogram.observe(0.003);
+        histogram.observe(0.2);
+        histogram.observe(30.0);
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[5], 2);
+        assert_eq!(histogram.buckets[DURATION_BUCKETS.len() - 1], 2);
+        assert_eq!(histogram.count, 3);
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.observe_request(
+            "GET",
+            "/api/apps/{app_name}",
+            200,
+            Duration::from_millis(30),
+        );
+        metrics.record_notification("telegram", false);
+        metrics.record_notification("email", true);
+        let text = metrics.render(Some(PoolStats {
+            size: 4,
+            idle: 3,
+            max: 10,
+        }));
+
+        assert!(text.contains(
+            "kubarr_http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/apps/{app_name}\",status=\"200\",le=\"0.025\"} 0\n"
+        ));
+        assert!(text.contains(
+            "kubarr_http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/apps/{app_name}\",status=\"200\",le=\"0.05\"} 1\n"
+        ));
+        assert!(text.contains(
+            "kubarr_http_request_duration_seconds_count{method=\"GET\",route=\"/api/apps/{app_name}\",status=\"200\"} 1\n"
+        ));
+        assert!(text.contains("kubarr_db_pool_connections{state=\"in_use\"} 1\n"));
+        assert!(text.contains("kubarr_notification_send_failures_total{channel=\"telegram\"} 1\n"));
+        assert!(text.contains("kubarr_notification_send_failures_total{channel=\"email\"} 0\n"));
+    }
+}
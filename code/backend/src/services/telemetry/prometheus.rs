@@ -0,0 +1,100 @@
+//! Prometheus text exposition format
+//!
+//! Just enough of version 0.0.4 of the format for counters, gauges and
+//! histograms: `# HELP` and `# TYPE` lines followed by one sample per line.
+
+use std::fmt::Write;
+
+/// Content type of the text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Builds the text of a scrape
+#[derive(Debug, Default)]
+pub struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a metric family
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        self
+    }
+
+    /// Add a sample to the current family
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, escape(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+        self
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Format a bucket bound for the `le` label
+pub fn format_bound(bound: f64) -> String {
+    format_value(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposition() {
+        let mut exposition = Exposition::new();
+        exposition
+            .family("kubarr_up", "gauge", "Whether Kubarr is up")
+            .sample("kubarr_up", &[], 1.0)
+            .family("kubarr_errors_total", "counter", "Errors")
+            .sample("kubarr_errors_total", &[("path", "/a\"b\\c")], 2.0)
+            .sample(
+                "kubarr_errors_total",
+                &[("path", "/"), ("le", &format_bound(f64::INFINITY))],
+                0.5,
+            );
+        assert_eq!(
+            exposition.finish(),
+            "# HELP kubarr_up Whether Kubarr is up\n\
+             # TYPE kubarr_up gauge\n\
+             kubarr_up 1\n\
+             # HELP kubarr_errors_total Errors\n\
+             # TYPE kubarr_errors_total counter\n\
+             kubarr_errors_total{path=\"/a\\\"b\\\\c\"} 2\n\
+             kubarr_errors_total{path=\"/\",le=\"+Inf\"} 0.5\n"
+        );
+    }
+}
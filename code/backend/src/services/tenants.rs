@@ -0,0 +1,520 @@
+//! Tenants: groups of users sharing one cluster
+//!
+//! A tenant (a household, a group of friends) owns apps and storage roots.
+//! Users belong to any number of tenants. Apps and roots assigned to a
+//! tenant are only visible to its members; apps and roots without a tenant
+//! are shared by everyone, so a cluster without tenants behaves as before.
+//!
+//! Isolation applies to:
+//! - apps: the installed list, every `/{app_name}/...` route and the app
+//!   proxy hide apps of other tenants
+//! - storage roots: a root whose `tenants` setting lists tenant names is
+//!   only visible to their members
+//! - the audit log: entries about other tenants' apps, and actions by users
+//!   who only belong to other tenants, are hidden
+//! - routed notifications: an event caused by a tenant member only reaches
+//!   recipients who share a tenant with them
+//!
+//! Holders of `tenants.manage` see across tenants and manage them. Members
+//! marked as tenant admins manage their tenant's membership and can read
+//! its audit log.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::access::get_user_permissions;
+use super::audit::AuditScope;
+use super::installed_apps::{self, InstalledAppInfo};
+use crate::error::{AppError, Result};
+use crate::middleware::permissions::{Permission, TenantsManage};
+use crate::models::prelude::*;
+use crate::models::{installed_app, tenant, tenant_member, user};
+use crate::state::DbConn;
+
+/// Tenants whose apps and roots a user can see
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantScope {
+    /// Every tenant, for holders of `tenants.manage`
+    All,
+    /// Only these tenants, the user's own
+    Member(Vec<i64>),
+}
+
+impl TenantScope {
+    /// Whether something owned by `tenant_id` is visible; `None` is shared
+    pub fn includes(&self, tenant_id: Option<i64>) -> bool {
+        match (self, tenant_id) {
+            (_, None) | (TenantScope::All, _) => true,
+            (TenantScope::Member(ids), Some(id)) => ids.contains(&id),
+        }
+    }
+}
+
+/// A tenant, as listed by `GET /api/tenants`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TenantInfo {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub member_count: usize,
+    /// Apps assigned to the tenant
+    pub apps: Vec<String>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+
+/// A member of a tenant
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TenantMemberInfo {
+    pub user_id: i64,
+    pub username: String,
+    pub is_admin: bool,
+    pub joined_at: chrono::DateTime<Utc>,
+}
+
+/// Create or rename a tenant
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct TenantRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Add a user to a tenant, or change whether they are its admin
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct TenantMemberRequest {
+    #[serde(default)]
+    pub is_admin: bool,
+}
+
+/// Assign an app to a tenant
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AssignAppTenant {
+    /// `None` shares the app with every tenant
+    pub tenant_id: Option<i64>,
+}
+
+/// Whether `name` is a valid tenant name: lowercase letters, digits, `-`
+/// and `_`, the same as storage root names
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+async fn memberships(db: &DbConn, user_id: i64) -> Result<Vec<tenant_member::Model>> {
+    Ok(TenantMember::find()
+        .filter(tenant_member::Column::UserId.eq(user_id))
+        .all(db)
+        .await?)
+}
+
+/// The tenants a user can see
+pub async fn scope(db: &DbConn, user_id: i64) -> Result<TenantScope> {
+    let permissions = get_user_permissions(db, user_id).await;
+    if permissions.iter().any(|p| p == TenantsManage::NAME) {
+        return Ok(TenantScope::All);
+    }
+    Ok(TenantScope::Member(
+        memberships(db, user_id)
+            .await?
+            .iter()
+            .map(|m| m.tenant_id)
+            .collect(),
+    ))
+}
+
+/// Names of the tenants a user can see; `None` means every tenant
+pub async fn scope_names(db: &DbConn, user_id: i64) -> Result<Option<Vec<String>>> {
+    match scope(db, user_id).await? {
+        TenantScope::All => Ok(None),
+        TenantScope::Member(ids) => Ok(Some(
+            Tenant::find()
+                .filter(tenant::Column::Id.is_in(ids))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|t| t.name)
+                .collect(),
+        )),
+    }
+}
+
+/// Whether a user can see an app; apps Kubarr has no record of are shared
+pub async fn can_see_app(db: &DbConn, user_id: i64, app_name: &str) -> Result<bool> {
+    let Some(app) = InstalledApp::find_by_id(app_name).one(db).await? else {
+        return Ok(true);
+    };
+    if app.tenant_id.is_none() {
+        return Ok(true);
+    }
+    Ok(scope(db, user_id).await?.includes(app.tenant_id))
+}
+
+/// Installed apps that belong to tenants the user cannot see
+pub async fn hidden_apps(db: &DbConn, user_id: i64) -> Result<Vec<String>> {
+    let scope = scope(db, user_id).await?;
+    if scope == TenantScope::All {
+        return Ok(Vec::new());
+    }
+    Ok(InstalledApp::find()
+        .filter(installed_app::Column::TenantId.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|app| !scope.includes(app.tenant_id))
+        .map(|app| app.app_name)
+        .collect())
+}
+
+/// Users who belong to tenants, none of which the user can see
+pub async fn hidden_users(db: &DbConn, user_id: i64) -> Result<Vec<i64>> {
+    let scope = scope(db, user_id).await?;
+    if scope == TenantScope::All {
+        return Ok(Vec::new());
+    }
+    let members = TenantMember::find().all(db).await?;
+    let visible: HashSet<i64> = members
+        .iter()
+        .filter(|m| scope.includes(Some(m.tenant_id)))
+        .map(|m| m.user_id)
+        .collect();
+    let mut hidden: Vec<i64> = members
+        .iter()
+        .map(|m| m.user_id)
+        .filter(|id| *id != user_id && !visible.contains(id))
+        .collect();
+    hidden.sort();
+    hidden.dedup();
+    Ok(hidden)
+}
+
+/// Whether `recipient` may hear about what `actor` did: true unless the
+/// actor only belongs to tenants the recipient cannot see
+pub async fn can_see_user(db: &DbConn, recipient: i64, actor: i64) -> Result<bool> {
+    if recipient == actor {
+        return Ok(true);
+    }
+    Ok(!hidden_users(db, recipient).await?.contains(&actor))
+}
+
+/// Tenants the user is an admin of
+pub async fn administered(db: &DbConn, user_id: i64) -> Result<Vec<i64>> {
+    Ok(memberships(db, user_id)
+        .await?
+        .into_iter()
+        .filter(|m| m.is_admin)
+        .map(|m| m.tenant_id)
+        .collect())
+}
+
+/// Audit scope of a tenant admin: their tenants' apps and members
+pub async fn admin_audit_scope(db: &DbConn, user_id: i64) -> Result<Option<AuditScope>> {
+    let tenant_ids = administered(db, user_id).await?;
+    if tenant_ids.is_empty() {
+        return Ok(None);
+    }
+    let apps = InstalledApp::find()
+        .filter(installed_app::Column::TenantId.is_in(tenant_ids.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|app| app.app_name)
+        .collect();
+    let mut user_ids: Vec<i64> = TenantMember::find()
+        .filter(tenant_member::Column::TenantId.is_in(tenant_ids))
+        .all(db)
+        .await?
+        .iter()
+        .map(|m| m.user_id)
+        .collect();
+    user_ids.push(user_id);
+    user_ids.sort();
+    user_ids.dedup();
+    Ok(Some(AuditScope {
+        apps: Some(apps),
+        user_ids,
+    }))
+}
+
+/// Require `tenants.manage` or being an admin of `tenant_id`
+pub async fn require_admin(db: &DbConn, user_id: i64, tenant_id: i64) -> Result<()> {
+    if scope(db, user_id).await? == TenantScope::All
+        || administered(db, user_id).await?.contains(&tenant_id)
+    {
+        return Ok(());
+    }
+    Err(AppError::Forbidden(format!(
+        "Permission denied: {} or tenant admin required",
+        TenantsManage::NAME
+    )))
+}
+
+// ============================================================================
+// Tenant management
+// ============================================================================
+
+async fn find(db: &DbConn, id: i64) -> Result<tenant::Model> {
+    Tenant::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Tenant not found".to_string()))
+}
+
+async fn info(db: &DbConn, tenant: tenant::Model) -> Result<TenantInfo> {
+    let member_count = TenantMember::find()
+        .filter(tenant_member::Column::TenantId.eq(tenant.id))
+        .all(db)
+        .await?
+        .len();
+    let apps = InstalledApp::find()
+        .filter(installed_app::Column::TenantId.eq(tenant.id))
+        .order_by_asc(installed_app::Column::AppName)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|app| app.app_name)
+        .collect();
+    Ok(TenantInfo {
+        id: tenant.id,
+        name: tenant.name,
+        description: tenant.description,
+        member_count,
+        apps,
+        created_at: tenant.created_at,
+    })
+}
+
+/// Tenants the user can see
+pub async fn list(db: &DbConn, user_id: i64) -> Result<Vec<TenantInfo>> {
+    let mut query = Tenant::find().order_by_asc(tenant::Column::Name);
+    if let TenantScope::Member(ids) = scope(db, user_id).await? {
+        query = query.filter(tenant::Column::Id.is_in(ids));
+    }
+    let mut tenants = Vec::new();
+    for tenant in query.all(db).await? {
+        tenants.push(info(db, tenant).await?);
+    }
+    Ok(tenants)
+}
+
+/// One tenant, if the user can see it
+pub async fn get(db: &DbConn, user_id: i64, id: i64) -> Result<TenantInfo> {
+    if !scope(db, user_id).await?.includes(Some(id)) {
+        return Err(AppError::NotFound("Tenant not found".to_string()));
+    }
+    info(db, find(db, id).await?).await
+}
+
+async fn check_request(db: &DbConn, request: &TenantRequest, id: Option<i64>) -> Result<()> {
+    if !is_valid_name(&request.name) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid tenant name '{}': use lowercase letters, digits, '-' and '_'",
+            request.name
+        )));
+    }
+    let taken = Tenant::find()
+        .filter(tenant::Column::Name.eq(&request.name))
+        .one(db)
+        .await?
+        .is_some_and(|t| Some(t.id) != id);
+    if taken {
+        return Err(AppError::Conflict(format!(
+            "Tenant '{}' already exists",
+            request.name
+        )));
+    }
+    Ok(())
+}
+
+pub async fn create(db: &DbConn, request: &TenantRequest) -> Result<TenantInfo> {
+    check_request(db, request, None).await?;
+    let now = Utc::now();
+    let tenant = tenant::ActiveModel {
+        name: Set(request.name.clone()),
+        description: Set(request.description.clone()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    info(db, tenant).await
+}
+
+pub async fn update(db: &DbConn, id: i64, request: &TenantRequest) -> Result<TenantInfo> {
+    let tenant = find(db, id).await?;
+    check_request(db, request, Some(id)).await?;
+    let mut active: tenant::ActiveModel = tenant.into();
+    active.name = Set(request.name.clone());
+    active.description = Set(request.description.clone());
+    active.updated_at = Set(Utc::now());
+    let tenant = active.update(db).await?;
+    info(db, tenant).await
+}
+
+/// Delete a tenant; its apps become shared
+pub async fn delete(db: &DbConn, id: i64) -> Result<()> {
+    find(db, id).await?;
+    let txn = db.begin().await?;
+    InstalledApp::update_many()
+        .col_expr(
+            installed_app::Column::TenantId,
+            sea_orm::sea_query::Expr::value(Option::<i64>::None),
+        )
+        .filter(installed_app::Column::TenantId.eq(id))
+        .exec(&txn)
+        .await?;
+    TenantMember::delete_many()
+        .filter(tenant_member::Column::TenantId.eq(id))
+        .exec(&txn)
+        .await?;
+    Tenant::delete_by_id(id).exec(&txn).await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+pub async fn members(db: &DbConn, id: i64) -> Result<Vec<TenantMemberInfo>> {
+    find(db, id).await?;
+    let rows = TenantMember::find()
+        .filter(tenant_member::Column::TenantId.eq(id))
+        .find_also_related(User)
+        .all(db)
+        .await?;
+    let mut members: Vec<TenantMemberInfo> = rows
+        .into_iter()
+        .filter_map(|(member, user)| {
+            let user = user?;
+            Some(TenantMemberInfo {
+                user_id: member.user_id,
+                username: user.username,
+                is_admin: member.is_admin,
+                joined_at: member.created_at,
+            })
+        })
+        .collect();
+    members.sort_by(|a, b| a.username.cmp(&b.username));
+    Ok(members)
+}
+
+/// Add a user to a tenant or update their membership
+pub async fn set_member(
+    db: &DbConn,
+    id: i64,
+    user_id: i64,
+    request: &TenantMemberRequest,
+) -> Result<TenantMemberInfo> {
+    find(db, id).await?;
+    let user: user::Model = User::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let existing = TenantMember::find()
+        .filter(tenant_member::Column::TenantId.eq(id))
+        .filter(tenant_member::Column::UserId.eq(user_id))
+        .one(db)
+        .await?;
+    let member = match existing {
+        Some(member) => {
+            let mut active: tenant_member::ActiveModel = member.into();
+            active.is_admin = Set(request.is_admin);
+            active.update(db).await?
+        }
+        None => {
+            tenant_member::ActiveModel {
+                tenant_id: Set(id),
+                user_id: Set(user_id),
+                is_admin: Set(request.is_admin),
+                created_at: Set(Utc::now()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    Ok(TenantMemberInfo {
+        user_id,
+        username: user.username,
+        is_admin: member.is_admin,
+        joined_at: member.created_at,
+    })
+}
+
+/// Remove a user from a tenant; returns whether they were a member
+pub async fn remove_member(db: &DbConn, id: i64, user_id: i64) -> Result<bool> {
+    find(db, id).await?;
+    let result = TenantMember::delete_many()
+        .filter(tenant_member::Column::TenantId.eq(id))
+        .filter(tenant_member::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+// ============================================================================
+// Apps
+// ============================================================================
+
+/// Assign an app to a tenant, or share it again with `None`
+pub async fn assign_app(
+    db: &DbConn,
+    app_name: &str,
+    tenant_id: Option<i64>,
+) -> Result<InstalledAppInfo> {
+    if let Some(id) = tenant_id {
+        find(db, id).await?;
+    }
+    installed_apps::set_tenant(db, app_name, tenant_id).await
+}
+
+/// Give a newly installed app to its installer's tenant
+///
+/// Only applies when the installer belongs to exactly one tenant and cannot
+/// see across tenants, and the app is not assigned yet.
+pub async fn claim_installed_app(db: &DbConn, app_name: &str, user_id: i64) -> Result<()> {
+    let TenantScope::Member(ids) = scope(db, user_id).await? else {
+        return Ok(());
+    };
+    let [tenant_id] = ids.as_slice() else {
+        return Ok(());
+    };
+    match InstalledApp::find_by_id(app_name).one(db).await? {
+        Some(app) if app.tenant_id.is_none() => {
+            installed_apps::set_tenant(db, app_name, Some(*tenant_id)).await?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_includes() {
+        assert!(TenantScope::All.includes(Some(3)));
+        assert!(TenantScope::All.includes(None));
+        let member = TenantScope::Member(vec![1, 2]);
+        assert!(member.includes(None));
+        assert!(member.includes(Some(2)));
+        assert!(!member.includes(Some(3)));
+        assert!(TenantScope::Member(vec![]).includes(None));
+    }
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("smith-household"));
+        assert!(is_valid_name("lan_party2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("Smiths"));
+        assert!(!is_valid_name("a b"));
+    }
+}
@@ -0,0 +1,355 @@
+//! Trash for files deleted through the storage API
+//!
+//! Deleting moves an item into the `.trash` folder at the top of its storage
+//! root, as `.trash/<id>/item` next to `.trash/<id>/entry.json` with its
+//! original path. Items can be restored to where they were or purged.
+//! [`TrashPurgeTask`] purges items older than `storage_trash_retention_days`
+//! (default 30; 0 keeps them until the trash is emptied). The `.trash`
+//! folder itself is hidden from the storage browser.
+
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+use super::scheduler::PeriodicTask;
+use super::storage_roots;
+use crate::endpoints::settings::get_setting_value;
+use crate::error::{AppError, Result};
+use crate::state::DbConn;
+
+/// Folder at the top of each root that holds trashed items
+pub const TRASH_DIR: &str = ".trash";
+
+/// Setting holding the days trashed items are kept
+pub const TRASH_RETENTION_DAYS: &str = "storage_trash_retention_days";
+
+/// Retention used when the setting is not a number
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+const ENTRY_FILE: &str = "entry.json";
+const ITEM: &str = "item";
+
+/// A trashed file or directory
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TrashEntry {
+    pub id: String,
+    pub name: String,
+    /// Path relative to the storage root the item was deleted from
+    pub original_path: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub size: u64,
+    pub deleted_at: DateTime<Utc>,
+    /// Username of whoever deleted it
+    pub deleted_by: Option<String>,
+}
+
+/// Whether a path relative to a root points into the trash, after resolving
+/// `.` and `..` lexically
+pub fn is_trash_path(relative: &Path) -> bool {
+    let mut parts: Vec<&std::ffi::OsStr> = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::ParentDir => {
+                parts.pop();
+            }
+            _ => {}
+        }
+    }
+    parts.first().is_some_and(|first| *first == TRASH_DIR)
+}
+
+/// The directory of a trashed item; ids are UUIDs so they cannot name
+/// anything outside the trash
+fn entry_dir(base: &Path, id: &str) -> Result<PathBuf> {
+    let not_found = || AppError::NotFound(format!("Trash item not found: {}", id));
+    let id = uuid::Uuid::parse_str(id).map_err(|_| not_found())?;
+    let dir = base.join(TRASH_DIR).join(id.to_string());
+    if !dir.join(ENTRY_FILE).is_file() {
+        return Err(not_found());
+    }
+    Ok(dir)
+}
+
+/// Read an item's metadata; its id is the name of its directory, whatever
+/// the file says
+fn read_entry(dir: &Path) -> Result<TrashEntry> {
+    let data = std::fs::read_to_string(dir.join(ENTRY_FILE))
+        .map_err(|e| AppError::Internal(format!("Failed to read trash entry: {}", e)))?;
+    let mut entry: TrashEntry = serde_json::from_str(&data)
+        .map_err(|e| AppError::Internal(format!("Invalid trash entry: {}", e)))?;
+    entry.id = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(entry)
+}
+
+/// Move `target` (at `relative` in the root at `base`) into the trash
+pub fn move_to_trash(
+    base: &Path,
+    target: &Path,
+    relative: &str,
+    deleted_by: Option<String>,
+) -> Result<TrashEntry> {
+    let metadata = std::fs::metadata(target)
+        .map_err(|e| AppError::Internal(format!("Failed to get file metadata: {}", e)))?;
+    let entry = TrashEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        original_path: relative.to_string(),
+        item_type: if metadata.is_dir() {
+            "directory"
+        } else {
+            "file"
+        }
+        .to_string(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        deleted_at: Utc::now(),
+        deleted_by,
+    };
+
+    let dir = base.join(TRASH_DIR).join(&entry.id);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| AppError::Internal(format!("Failed to create trash folder: {}", e)))?;
+    let json = serde_json::to_string_pretty(&entry)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize trash entry: {}", e)))?;
+    let moved = std::fs::write(dir.join(ENTRY_FILE), json)
+        .and_then(|_| std::fs::rename(target, dir.join(ITEM)));
+    if let Err(e) = moved {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(AppError::Internal(format!(
+            "Failed to move {} to the trash: {}",
+            relative, e
+        )));
+    }
+    Ok(entry)
+}
+
+/// Trashed items of a root, most recently deleted first
+pub fn list(base: &Path) -> Result<Vec<TrashEntry>> {
+    let trash = base.join(TRASH_DIR);
+    if !trash.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<TrashEntry> = std::fs::read_dir(&trash)
+        .map_err(|e| AppError::Internal(format!("Failed to read trash: {}", e)))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| read_entry(&e.path()).ok())
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    Ok(entries)
+}
+
+/// A trashed item's metadata and the path the item itself is kept at
+pub fn get(base: &Path, id: &str) -> Result<(TrashEntry, PathBuf)> {
+    let dir = entry_dir(base, id)?;
+    Ok((read_entry(&dir)?, dir.join(ITEM)))
+}
+
+/// Move a trashed item back to its original path
+pub fn restore(base: &Path, id: &str) -> Result<TrashEntry> {
+    let dir = entry_dir(base, id)?;
+    let entry = read_entry(&dir)?;
+
+    let original = Path::new(&entry.original_path);
+    let escapes = original
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)));
+    if escapes || is_trash_path(original) {
+        return Err(AppError::Internal(format!(
+            "Invalid original path in trash entry {}",
+            id
+        )));
+    }
+    let destination = base.join(original);
+    if destination.exists() {
+        return Err(AppError::Conflict(format!(
+            "Cannot restore: {} already exists",
+            entry.original_path
+        )));
+    }
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::Internal(format!("Failed to create directory: {}", e)))?;
+    }
+    std::fs::rename(dir.join(ITEM), &destination)
+        .map_err(|e| AppError::Internal(format!("Failed to restore: {}", e)))?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(entry)
+}
+
+/// Permanently delete one trashed item
+pub fn purge(base: &Path, id: &str) -> Result<TrashEntry> {
+    let dir = entry_dir(base, id)?;
+    let entry = read_entry(&dir)?;
+    std::fs::remove_dir_all(&dir)
+        .map_err(|e| AppError::Internal(format!("Failed to purge trash item: {}", e)))?;
+    Ok(entry)
+}
+
+/// Permanently delete trashed items deleted before `before`, or all of them
+pub fn empty(base: &Path, before: Option<DateTime<Utc>>) -> Result<usize> {
+    let mut purged = 0;
+    for entry in list(base)? {
+        if before.is_some_and(|before| entry.deleted_at >= before) {
+            continue;
+        }
+        let dir = base.join(TRASH_DIR).join(&entry.id);
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => purged += 1,
+            Err(e) => tracing::warn!("Failed to purge trash item {}: {}", entry.id, e),
+        }
+    }
+    Ok(purged)
+}
+
+/// Parse `storage_trash_retention_days`
+pub fn parse_retention(value: &str) -> Result<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|days| *days >= 0)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "{} must be a number of days, or 0 to keep trashed items",
+                TRASH_RETENTION_DAYS
+            ))
+        })
+}
+
+/// Days trashed items are kept; 0 keeps them until the trash is emptied
+pub async fn retention_days(db: &DbConn) -> Result<i64> {
+    Ok(get_setting_value(db, TRASH_RETENTION_DAYS)
+        .await?
+        .and_then(|v| parse_retention(&v).ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS))
+}
+
+/// Purges trashed items past the retention from every storage root
+pub struct TrashPurgeTask;
+
+#[async_trait]
+impl PeriodicTask for TrashPurgeTask {
+    fn name(&self) -> &'static str {
+        "storage_trash_purge"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(60 * 60)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let days = retention_days(db).await?;
+        if days == 0 {
+            return Ok(());
+        }
+        let before = Utc::now() - chrono::Duration::days(days);
+        for root in storage_roots::configured_roots(db).await? {
+            let Ok(base) = root.resolve().canonicalize() else {
+                continue;
+            };
+            let purged = empty(&base, Some(before))?;
+            if purged > 0 {
+                tracing::info!(
+                    "Purged {} trashed items older than {} days from storage root '{}'",
+                    purged,
+                    days,
+                    root.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("kubarr_trash_{}_{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_is_trash_path() {
+        assert!(is_trash_path(Path::new(".trash")));
+        assert!(is_trash_path(Path::new(".trash/abc/item")));
+        assert!(is_trash_path(Path::new("media/../.trash/x")));
+        assert!(is_trash_path(Path::new("./.trash")));
+        assert!(!is_trash_path(Path::new("media/.trash")));
+        assert!(!is_trash_path(Path::new("")));
+    }
+
+    #[test]
+    fn test_trash_and_restore() {
+        let base = temp_root("restore");
+        std::fs::create_dir(base.join("movies")).unwrap();
+        std::fs::write(base.join("movies/film.mkv"), "film").unwrap();
+
+        let entry = move_to_trash(
+            &base,
+            &base.join("movies/film.mkv"),
+            "movies/film.mkv",
+            Some("alice".to_string()),
+        )
+        .unwrap();
+        assert!(!base.join("movies/film.mkv").exists());
+        assert_eq!(entry.size, 4);
+        assert_eq!(list(&base).unwrap().len(), 1);
+
+        // Restoring recreates missing parents and refuses to overwrite
+        std::fs::remove_dir(base.join("movies")).unwrap();
+        std::fs::create_dir(base.join("movies")).unwrap();
+        std::fs::write(base.join("movies/film.mkv"), "other").unwrap();
+        assert!(matches!(
+            restore(&base, &entry.id),
+            Err(AppError::Conflict(_))
+        ));
+        std::fs::remove_dir_all(base.join("movies")).unwrap();
+        let restored = restore(&base, &entry.id).unwrap();
+        assert_eq!(restored.original_path, "movies/film.mkv");
+        assert_eq!(
+            std::fs::read_to_string(base.join("movies/film.mkv")).unwrap(),
+            "film"
+        );
+        assert!(list(&base).unwrap().is_empty());
+
+        assert!(matches!(
+            restore(&base, "../movies"),
+            Err(AppError::NotFound(_))
+        ));
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_empty_respects_cutoff() {
+        let base = temp_root("empty");
+        for name in ["a.txt", "b.txt"] {
+            std::fs::write(base.join(name), name).unwrap();
+            move_to_trash(&base, &base.join(name), name, None).unwrap();
+        }
+        let cutoff = Utc::now() - chrono::Duration::days(1);
+        assert_eq!(empty(&base, Some(cutoff)).unwrap(), 0);
+        assert_eq!(empty(&base, None).unwrap(), 2);
+        assert!(list(&base).unwrap().is_empty());
+
+        assert_eq!(parse_retention(" 7 ").unwrap(), 7);
+        assert_eq!(parse_retention("0").unwrap(), 0);
+        assert!(parse_retention("-1").is_err());
+        assert!(parse_retention("week").is_err());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
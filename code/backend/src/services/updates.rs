@@ -0,0 +1,1130 @@
+//! Kubarr update checks and self-upgrade
+//!
+//! [`UpdateCheckTask`] polls the GitHub releases of `KUBARR_UPDATE_REPO` and
+//! keeps the newest release the configured channel accepts in [`UPDATES`].
+//! The `stable` channel only takes stable releases; `release` and `dev` also
+//! take release candidates and betas. The release notes of the installed
+//! version and of every newer release are kept for [`changelog`], with
+//! breaking changes flagged.
+//!
+//! [`apply`] upgrades Kubarr's own Helm release to a newer version:
+//!
+//! 1. The current revision, its values and the applied migrations are stored
+//!    as a backup in `kubarr_updates`.
+//! 2. The release is upgraded with `--reuse-values`, pinned to the target
+//!    chart version.
+//! 3. [`verify`] waits for every deployment in the release namespace to roll
+//!    out. If that does not happen within [`VERIFY_TIMEOUT_SECS`] the release
+//!    is rolled back to the backed-up revision.
+//!
+//! The upgrade replaces the pod that started it, so verification is picked up
+//! again by whichever backend is running ([`resume`]). Rolling back restores
+//! the release, not the database: migrations of the new version stay applied.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::apps::v1::Deployment;
+use once_cell::sync::Lazy;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use sea_orm_migration::{MigrationStatus, MigratorTrait};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::helm::HelmRelease;
+use super::notification::NotificationService;
+use super::outbound::SharedClient;
+use super::scheduler::PeriodicTask;
+use crate::config::CONFIG;
+use crate::error::{AppError, Result};
+use crate::migrations::Migrator;
+use crate::models::audit_log::{AuditAction, ResourceType};
+use crate::models::kubarr_update;
+use crate::models::prelude::*;
+use crate::models::system_setting;
+use crate::state::AppState;
+
+/// Seconds a rollout may take before the update is rolled back
+pub const VERIFY_TIMEOUT_SECS: i64 = 10 * 60;
+
+/// How often an update in progress is verified
+const VERIFY_POLL_SECS: u64 = 10;
+
+/// Setting holding the last version an "update available" notification was
+/// sent for
+const NOTIFIED_VERSION_SETTING: &str = "update_notified_version";
+
+/// Releases fetched per check
+const RELEASES_PER_PAGE: u32 = 30;
+
+/// The process-wide result of the last update check
+pub static UPDATES: Lazy<UpdateChecker> = Lazy::new(UpdateChecker::new);
+
+/// A semantic version, e.g. `1.2.3` or `1.3.0-rc.1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers, e.g. `["rc", "1"]`
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    /// Parse a version, with or without a leading `v`; build metadata is ignored
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix('v').unwrap_or(value);
+        let value = value.split('+').next()?;
+        let (core, pre) = match value.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (value, None),
+        };
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        let pre = match pre {
+            Some(pre) => {
+                let ids: Vec<String> = pre.split('.').map(String::from).collect();
+                if ids.iter().any(|id| id.is_empty()) {
+                    return None;
+                }
+                ids
+            }
+            None => Vec::new(),
+        };
+        Some(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.is_prerelease() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A release sorts after its pre-releases
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (a, b) in self.pre.iter().zip(&other.pre) {
+                        let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                            (Ok(a), Ok(b)) => a.cmp(&b),
+                            (Ok(_), Err(_)) => Ordering::Less,
+                            (Err(_), Ok(_)) => Ordering::Greater,
+                            (Err(_), Err(_)) => a.cmp(b),
+                        };
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Whether a release channel offers a version
+pub fn channel_accepts(channel: &str, version: &Version) -> bool {
+    channel != "stable" || !version.is_prerelease()
+}
+
+/// GitHub Releases API entry
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    published_at: Option<DateTime<Utc>>,
+}
+
+/// A published Kubarr release newer than the running version
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AvailableRelease {
+    pub version: String,
+    pub tag: String,
+    /// Release page on GitHub
+    pub url: String,
+    /// Release notes (markdown)
+    pub notes: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub prerelease: bool,
+}
+
+/// Pick the newest release above `current` that `channel` accepts
+fn select_release(
+    releases: Vec<GitHubRelease>,
+    current: &Version,
+    channel: &str,
+) -> Option<AvailableRelease> {
+    releases
+        .into_iter()
+        .filter(|r| !r.draft)
+        .filter_map(|r| Version::parse(&r.tag_name).map(|v| (v, r)))
+        .filter(|(v, r)| {
+            v > current && channel_accepts(channel, v) && (channel != "stable" || !r.prerelease)
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(version, r)| AvailableRelease {
+            version: version.to_string(),
+            tag: r.tag_name,
+            url: r.html_url,
+            notes: r.body,
+            published_at: r.published_at,
+            prerelease: version.is_prerelease() || r.prerelease,
+        })
+}
+
+/// Release notes of one Kubarr version
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ReleaseNotes {
+    pub version: String,
+    pub tag: String,
+    /// Release page on GitHub
+    pub url: String,
+    /// Release notes (markdown)
+    pub notes: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub prerelease: bool,
+    /// Whether upgrading to this version needs manual attention
+    pub breaking: bool,
+    /// Breaking changes called out in the notes, and major version bumps
+    pub warnings: Vec<String>,
+}
+
+/// Breaking changes called out in markdown release notes
+///
+/// Picks up list items under a heading mentioning "breaking", lines marked
+/// `BREAKING CHANGE:` / `BREAKING:` and conventional commit entries such as
+/// `feat!: ...`.
+fn breaking_changes(notes: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    // Level of the "breaking" heading we are under
+    let mut section: Option<usize> = None;
+    for line in notes.lines() {
+        let line = line.trim();
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if level > 0 && line[level..].starts_with(' ') {
+            match section {
+                Some(current) if level > current => {}
+                _ => {
+                    section = line.to_lowercase().contains("breaking").then_some(level);
+                }
+            }
+            continue;
+        }
+
+        let item = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .unwrap_or(line)
+            .trim();
+        if item.is_empty() {
+            continue;
+        }
+        let marked = ["BREAKING CHANGE:", "BREAKING CHANGES:", "BREAKING:"]
+            .iter()
+            .find_map(|marker| item.strip_prefix(marker))
+            .map(str::trim);
+        let conventional = item
+            .split_once(':')
+            .filter(|(kind, _)| {
+                kind.ends_with('!')
+                    && kind.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+                    && !kind.contains(' ')
+            })
+            .map(|_| item);
+        if let Some(warning) = marked.or(conventional) {
+            warnings.push(warning.to_string());
+        } else if section.is_some() && item != line {
+            warnings.push(item.to_string());
+        }
+    }
+    warnings
+}
+
+impl ReleaseNotes {
+    fn new(release: &GitHubRelease, version: &Version, current: &Version) -> Self {
+        let notes = release.body.clone().unwrap_or_default();
+        let mut warnings = breaking_changes(&notes);
+        if version.major > current.major {
+            warnings.insert(
+                0,
+                format!(
+                    "Major version upgrade from {} to {}",
+                    current.major, version.major
+                ),
+            );
+        }
+        Self {
+            version: version.to_string(),
+            tag: release.tag_name.clone(),
+            url: release.html_url.clone(),
+            notes,
+            published_at: release.published_at,
+            prerelease: version.is_prerelease() || release.prerelease,
+            breaking: !warnings.is_empty(),
+            warnings,
+        }
+    }
+}
+
+/// Release notes of the running version and of the newer releases `channel`
+/// accepts, newest first
+fn release_notes(
+    releases: &[GitHubRelease],
+    current: &Version,
+    channel: &str,
+) -> (Option<ReleaseNotes>, Vec<ReleaseNotes>) {
+    let mut installed = None;
+    let mut available: Vec<(Version, ReleaseNotes)> = Vec::new();
+    for release in releases.iter().filter(|r| !r.draft) {
+        let Some(version) = Version::parse(&release.tag_name) else {
+            continue;
+        };
+        if &version == current {
+            // Compared with itself: only warnings from the notes apply
+            installed = Some(ReleaseNotes::new(release, &version, &version));
+        } else if &version > current
+            && channel_accepts(channel, &version)
+            && (channel != "stable" || !release.prerelease)
+        {
+            let notes = ReleaseNotes::new(release, &version, current);
+            available.push((version, notes));
+        }
+    }
+    available.sort_by(|(a, _), (b, _)| b.cmp(a));
+    (installed, available.into_iter().map(|(_, n)| n).collect())
+}
+
+#[derive(Debug, Clone, Default)]
+struct CheckResult {
+    checked_at: Option<DateTime<Utc>>,
+    latest: Option<AvailableRelease>,
+    installed: Option<ReleaseNotes>,
+    available: Vec<ReleaseNotes>,
+    error: Option<String>,
+}
+
+/// Checks GitHub for new Kubarr releases and remembers the last result
+pub struct UpdateChecker {
+    client: SharedClient,
+    last: Mutex<CheckResult>,
+}
+
+impl UpdateChecker {
+    pub fn new() -> Self {
+        Self {
+            client: SharedClient::new(|builder| {
+                builder
+                    .user_agent("kubarr-backend")
+                    .timeout(Duration::from_secs(30))
+            }),
+            last: Mutex::new(CheckResult::default()),
+        }
+    }
+
+    /// Fetch the releases and store the newest one available on this channel,
+    /// along with the release notes of the installed and newer versions
+    pub async fn check(&self) -> Result<Option<AvailableRelease>> {
+        let result = self.fetch().await.and_then(|releases| {
+            let current = current_version()?;
+            let (installed, available) = release_notes(&releases, &current, &CONFIG.channel);
+            let latest = select_release(releases, &current, &CONFIG.channel);
+            Ok((latest, installed, available))
+        });
+        if let Ok(mut last) = self.last.lock() {
+            last.checked_at = Some(Utc::now());
+            match &result {
+                Ok((latest, installed, available)) => {
+                    last.latest = latest.clone();
+                    last.installed = installed.clone();
+                    last.available = available.clone();
+                    last.error = None;
+                }
+                Err(e) => last.error = Some(e.to_string()),
+            }
+        }
+        result.map(|(latest, _, _)| latest)
+    }
+
+    async fn fetch(&self) -> Result<Vec<GitHubRelease>> {
+        if CONFIG.outbound.offline {
+            return Err(AppError::BadRequest(
+                "Update checks are disabled in offline mode".to_string(),
+            ));
+        }
+        let url = format!(
+            "https://api.github.com/repos/{}/releases?per_page={}",
+            CONFIG.updates.repo, RELEASES_PER_PAGE
+        );
+        let releases: Vec<GitHubRelease> = self
+            .client
+            .client()
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(releases)
+    }
+
+    /// The release found by the last successful check
+    pub fn latest(&self) -> Option<AvailableRelease> {
+        self.last.lock().ok().and_then(|last| last.latest.clone())
+    }
+
+    /// Whether the release channel has been checked since startup
+    pub fn checked(&self) -> bool {
+        self.last
+            .lock()
+            .map(|last| last.checked_at.is_some())
+            .unwrap_or(false)
+    }
+
+    fn last(&self) -> CheckResult {
+        self.last
+            .lock()
+            .map(|last| last.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for UpdateChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn current_version() -> Result<Version> {
+    Version::parse(&CONFIG.version).ok_or_else(|| {
+        AppError::Internal(format!("Running version '{}' is invalid", CONFIG.version))
+    })
+}
+
+/// An upgrade of Kubarr itself
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UpdateRecord {
+    pub id: i64,
+    pub from_version: String,
+    pub to_version: String,
+    /// "applying", "verifying", "completed", "rolled_back" or "failed"
+    pub status: String,
+    /// Helm revision the update rolls back to
+    pub previous_revision: i32,
+    pub error: Option<String>,
+    pub started_by: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<kubarr_update::Model> for UpdateRecord {
+    fn from(m: kubarr_update::Model) -> Self {
+        Self {
+            id: m.id,
+            from_version: m.from_version,
+            to_version: m.to_version,
+            status: m.status,
+            previous_revision: m.previous_revision,
+            error: m.error,
+            started_by: m.started_by,
+            started_at: m.started_at,
+            finished_at: m.finished_at,
+        }
+    }
+}
+
+/// Update availability and the state of the last upgrade
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateStatus {
+    pub current_version: String,
+    pub channel: String,
+    pub update_available: bool,
+    pub latest: Option<AvailableRelease>,
+    /// When the release channel was last checked
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Why the last check failed
+    pub check_error: Option<String>,
+    /// Most recent upgrade, in progress or finished
+    pub last_update: Option<UpdateRecord>,
+}
+
+/// What an upgrade would apply
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Changelog {
+    pub current_version: String,
+    pub channel: String,
+    /// Release notes of the running version, if it is a published release
+    pub installed: Option<ReleaseNotes>,
+    /// Newer releases on this channel, newest first
+    pub available: Vec<ReleaseNotes>,
+    /// Whether any of the newer releases has breaking changes
+    pub breaking: bool,
+    /// When the release channel was last checked
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Why the last check failed
+    pub check_error: Option<String>,
+}
+
+/// Release notes from the last check
+pub fn changelog() -> Changelog {
+    let check = UPDATES.last();
+    Changelog {
+        current_version: CONFIG.version.clone(),
+        channel: CONFIG.channel.clone(),
+        breaking: check.available.iter().any(|r| r.breaking),
+        installed: check.installed,
+        available: check.available,
+        checked_at: check.checked_at,
+        check_error: check.error,
+    }
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ApplyUpdateRequest {
+    /// Version to upgrade to (default: the latest available)
+    pub version: Option<String>,
+}
+
+/// Update availability from the last check
+pub async fn status(db: &DatabaseConnection) -> Result<UpdateStatus> {
+    let check = UPDATES.last();
+    let last_update = KubarrUpdate::find()
+        .order_by_desc(kubarr_update::Column::Id)
+        .one(db)
+        .await?
+        .map(UpdateRecord::from);
+    Ok(UpdateStatus {
+        current_version: CONFIG.version.clone(),
+        channel: CONFIG.channel.clone(),
+        update_available: check.latest.is_some(),
+        latest: check.latest,
+        checked_at: check.checked_at,
+        check_error: check.error,
+        last_update,
+    })
+}
+
+/// The upgrade still being applied or verified, if any
+async fn in_progress(db: &DatabaseConnection) -> Result<Option<kubarr_update::Model>> {
+    Ok(KubarrUpdate::find()
+        .filter(kubarr_update::Column::Status.is_in(["applying", "verifying"]))
+        .order_by_desc(kubarr_update::Column::Id)
+        .one(db)
+        .await?)
+}
+
+/// Names of the migrations applied to the database
+async fn applied_migrations(db: &DatabaseConnection) -> Result<Vec<String>> {
+    Ok(Migrator::get_migration_with_status(db)
+        .await?
+        .iter()
+        .filter(|m| m.status() == MigrationStatus::Applied)
+        .map(|m| m.name().to_string())
+        .collect())
+}
+
+/// Back up Kubarr's release and upgrade it to a newer version
+///
+/// Returns once helm has accepted the upgrade; verification continues in
+/// the background.
+pub async fn apply(
+    state: &AppState,
+    db: &DatabaseConnection,
+    request: ApplyUpdateRequest,
+    user_id: i64,
+) -> Result<UpdateRecord> {
+    let current = current_version()?;
+    let target = match request.version {
+        Some(version) => Version::parse(&version)
+            .ok_or_else(|| AppError::BadRequest(format!("Invalid version '{}'", version)))?,
+        None => {
+            let latest = match UPDATES.latest() {
+                Some(latest) => Some(latest),
+                None => UPDATES.check().await?,
+            };
+            let latest =
+                latest.ok_or_else(|| AppError::BadRequest("Kubarr is up to date".to_string()))?;
+            Version::parse(&latest.version).ok_or_else(|| {
+                AppError::Internal(format!("Invalid release version '{}'", latest.version))
+            })?
+        }
+    };
+    if target <= current {
+        return Err(AppError::BadRequest(format!(
+            "Kubarr {} is not newer than the running version {}",
+            target, current
+        )));
+    }
+    if let Some(active) = in_progress(db).await? {
+        return Err(AppError::Conflict(format!(
+            "An update to {} is already in progress",
+            active.to_version
+        )));
+    }
+
+    let name = &CONFIG.updates.release_name;
+    let namespace = &CONFIG.updates.namespace;
+    let helm = state.helm();
+    let release = helm.release_info(name, namespace).await?.ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Kubarr is not installed as Helm release {}/{}",
+            namespace, name
+        ))
+    })?;
+
+    let backup = serde_json::json!({
+        "release": {
+            "name": name,
+            "namespace": namespace,
+            "revision": release.revision,
+            "chart_version": release.chart_version,
+            "values": release.values,
+        },
+        "migrations": applied_migrations(db).await?,
+    });
+    let record = kubarr_update::ActiveModel {
+        from_version: Set(current.to_string()),
+        to_version: Set(target.to_string()),
+        status: Set("applying".to_string()),
+        previous_revision: Set(release.revision),
+        backup: Set(backup.to_string()),
+        error: Set(None),
+        started_by: Set(Some(user_id)),
+        started_at: Set(Utc::now()),
+        finished_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    tracing::info!(
+        "Updating Kubarr from {} to {} (backup of revision {})",
+        current,
+        target,
+        release.revision
+    );
+
+    let upgrade = HelmRelease {
+        name: name.clone(),
+        chart: CONFIG.updates.chart.clone(),
+        namespace: namespace.clone(),
+        version: Some(target.to_string()),
+        reuse_values: true,
+        ..Default::default()
+    };
+    if let Err(e) = helm.upgrade_install(&upgrade).await {
+        finish(state, db, record, "failed", Some(e.to_string())).await?;
+        return Err(e);
+    }
+
+    let mut active: kubarr_update::ActiveModel = record.into();
+    active.status = Set("verifying".to_string());
+    let record = active.update(db).await?;
+
+    let watcher = state.clone();
+    tokio::spawn(async move { watch(watcher).await });
+
+    Ok(record.into())
+}
+
+/// Record how an update ended and tell admins about it
+async fn finish(
+    state: &AppState,
+    db: &DatabaseConnection,
+    record: kubarr_update::Model,
+    status: &str,
+    error: Option<String>,
+) -> Result<UpdateRecord> {
+    let detail = match &error {
+        Some(error) => format!(
+            "{} -> {}: {}",
+            record.from_version, record.to_version, error
+        ),
+        None => format!("{} -> {}", record.from_version, record.to_version),
+    };
+    let mut active: kubarr_update::ActiveModel = record.into();
+    active.status = Set(status.to_string());
+    active.error = Set(error.clone());
+    active.finished_at = Set(Some(Utc::now()));
+    let record = active.update(db).await?;
+
+    let action = if status == "completed" {
+        tracing::info!("Kubarr update completed: {}", detail);
+        AuditAction::UpdateCompleted
+    } else {
+        tracing::error!("Kubarr update {}: {}", status, detail);
+        AuditAction::UpdateFailed
+    };
+    let _ = state
+        .audit
+        .log(
+            action.clone(),
+            ResourceType::System,
+            Some(CONFIG.updates.release_name.clone()),
+            None,
+            None,
+            Some(serde_json::json!({
+                "from_version": record.from_version,
+                "to_version": record.to_version,
+                "status": record.status,
+            })),
+            None,
+            None,
+            error.is_none(),
+            error,
+        )
+        .await;
+    if let Err(e) = state
+        .notification
+        .notify_event(&action, None, None, Some(&detail))
+        .await
+    {
+        tracing::warn!("Failed to send update notification: {}", e);
+    }
+    Ok(record.into())
+}
+
+/// Whether every deployment has rolled out its desired replicas
+fn rolled_out(deployments: &[Deployment]) -> std::result::Result<(), String> {
+    for deployment in deployments {
+        let name = deployment.metadata.name.as_deref().unwrap_or("unknown");
+        let desired = deployment
+            .spec
+            .as_ref()
+            .and_then(|s| s.replicas)
+            .unwrap_or(1);
+        let status = deployment.status.as_ref();
+        let observed = status.and_then(|s| s.observed_generation).unwrap_or(0);
+        if observed < deployment.metadata.generation.unwrap_or(0) {
+            return Err(format!("deployment {} has not picked up the upgrade", name));
+        }
+        let ready = status.and_then(|s| s.ready_replicas).unwrap_or(0);
+        let available = status.and_then(|s| s.available_replicas).unwrap_or(0);
+        let updated = status.and_then(|s| s.updated_replicas).unwrap_or(ready);
+        if ready < desired || available < desired || updated < desired {
+            return Err(format!(
+                "deployment {} has {}/{} replicas ready",
+                name, ready, desired
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check the update in progress once
+///
+/// Completes it when the release namespace has rolled out and, on the new
+/// version, no migrations are pending. Rolls it back once
+/// [`VERIFY_TIMEOUT_SECS`] have passed without that. Returns the update when
+/// it finished.
+pub async fn verify(state: &AppState, db: &DatabaseConnection) -> Result<Option<UpdateRecord>> {
+    let Some(record) = in_progress(db).await? else {
+        return Ok(None);
+    };
+
+    let health = match state.k8s_api().await {
+        Some(k8s) => k8s
+            .list_deployments(&CONFIG.updates.namespace)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|deployments| rolled_out(&deployments)),
+        None => Err("Kubernetes client not available".to_string()),
+    };
+    let health = match health {
+        Ok(()) if record.to_version == CONFIG.version => {
+            match Migrator::get_pending_migrations(db).await {
+                Ok(pending) if pending.is_empty() => Ok(()),
+                Ok(pending) => Err(format!("{} migrations pending", pending.len())),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        health => health,
+    };
+
+    let expired = Utc::now() >= record.started_at + chrono::Duration::seconds(VERIFY_TIMEOUT_SECS);
+    match health {
+        Ok(()) if record.status == "verifying" => {
+            finish(state, db, record, "completed", None).await.map(Some)
+        }
+        // The pod that ran helm went away before recording the outcome
+        Ok(()) if expired => {
+            let error = "interrupted while applying".to_string();
+            finish(state, db, record, "failed", Some(error))
+                .await
+                .map(Some)
+        }
+        Err(reason) if expired => rollback(state, db, record, reason).await.map(Some),
+        Ok(()) => Ok(None),
+        Err(reason) => {
+            tracing::debug!("Kubarr update not healthy yet: {}", reason);
+            Ok(None)
+        }
+    }
+}
+
+/// Roll the release back to the revision backed up before the update
+async fn rollback(
+    state: &AppState,
+    db: &DatabaseConnection,
+    record: kubarr_update::Model,
+    reason: String,
+) -> Result<UpdateRecord> {
+    let revision = record.previous_revision;
+    tracing::warn!(
+        "Kubarr update to {} did not become healthy ({}), rolling back to revision {}",
+        record.to_version,
+        reason,
+        revision
+    );
+    match state
+        .helm()
+        .rollback(
+            &CONFIG.updates.release_name,
+            &CONFIG.updates.namespace,
+            revision,
+        )
+        .await
+    {
+        Ok(()) => {
+            let error = format!("{}; rolled back to revision {}", reason, revision);
+            finish(state, db, record, "rolled_back", Some(error)).await
+        }
+        Err(e) => {
+            let error = format!(
+                "{}; rollback to revision {} failed: {}",
+                reason, revision, e
+            );
+            finish(state, db, record, "failed", Some(error)).await
+        }
+    }
+}
+
+/// Verify the update in progress until it finishes
+async fn watch(state: AppState) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(VERIFY_POLL_SECS));
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let db = match state.get_db().await {
+            Ok(db) => db,
+            Err(_) => continue,
+        };
+        match verify(&state, &db).await {
+            Ok(Some(_)) => return,
+            Ok(None) => match in_progress(&db).await {
+                Ok(None) => return,
+                Ok(Some(_)) => {}
+                Err(e) => tracing::warn!("Failed to load Kubarr update: {}", e),
+            },
+            Err(e) => tracing::warn!("Failed to verify Kubarr update: {}", e),
+        }
+    }
+}
+
+/// Run the first update check and resume verifying an update left in
+/// progress by the previous pod
+pub fn resume(state: AppState) {
+    tokio::spawn(async move {
+        let Ok(db) = state.get_db().await else {
+            return;
+        };
+        if let Err(e) = check_and_notify(&db, &state.notification).await {
+            tracing::warn!("Kubarr update check failed: {}", e);
+        }
+        match in_progress(&db).await {
+            Ok(Some(record)) => {
+                tracing::info!(
+                    "Resuming verification of Kubarr update to {}",
+                    record.to_version
+                );
+                watch(state).await;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load Kubarr update: {}", e),
+        }
+    });
+}
+
+/// Check for a new version and notify admins the first time it is seen
+async fn check_and_notify(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+) -> Result<()> {
+    let Some(latest) = UPDATES.check().await? else {
+        return Ok(());
+    };
+    let notified = SystemSetting::find_by_id(NOTIFIED_VERSION_SETTING)
+        .one(db)
+        .await?;
+    if notified.as_ref().map(|s| s.value.as_str()) == Some(latest.version.as_str()) {
+        return Ok(());
+    }
+
+    tracing::info!("Kubarr {} is available", latest.version);
+    notification
+        .notify_event(
+            &AuditAction::UpdateAvailable,
+            None,
+            None,
+            Some(&latest.version),
+        )
+        .await?;
+
+    let setting = system_setting::ActiveModel {
+        key: Set(NOTIFIED_VERSION_SETTING.to_string()),
+        value: Set(latest.version),
+        description: Set(Some(
+            "Last Kubarr version an update notification was sent for".to_string(),
+        )),
+        updated_at: Set(Utc::now()),
+    };
+    if notified.is_some() {
+        setting.update(db).await?;
+    } else {
+        setting.insert(db).await?;
+    }
+    Ok(())
+}
+
+/// Periodically checks the release channel for new Kubarr versions
+pub struct UpdateCheckTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for UpdateCheckTask {
+    fn name(&self) -> &'static str {
+        "update_check"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(CONFIG.updates.check_interval.max(60))
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        if CONFIG.outbound.offline {
+            return Ok(());
+        }
+        check_and_notify(db, &self.notification).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::apps::v1::{DeploymentSpec, DeploymentStatus};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+
+    fn v(value: &str) -> Version {
+        Version::parse(value).unwrap()
+    }
+
+    fn release(tag: &str, prerelease: bool) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            html_url: format!("https://github.com/bmartensNL/Kubarr/releases/tag/{}", tag),
+            body: None,
+            draft: false,
+            prerelease,
+            published_at: None,
+        }
+    }
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(v("v1.2.3"), v("1.2.3"));
+        assert_eq!(v("1.3.0-rc.1").pre, vec!["rc", "1"]);
+        assert_eq!(v("1.2.3+build.5").to_string(), "1.2.3");
+        assert_eq!(v("1.3.0-beta.2").to_string(), "1.3.0-beta.2");
+        for invalid in ["", "1.2", "1.2.3.4", "a.b.c", "1.2.3-", "1.2.3-rc..1"] {
+            assert!(Version::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_version_order() {
+        let ordered = [
+            "0.9.9",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.10.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_select_release_by_channel() {
+        let releases = || {
+            vec![
+                release("v0.2.0", false),
+                release("v0.3.0-rc.1", true),
+                release("v0.1.0", false),
+                release("nightly", true),
+            ]
+        };
+        let current = v("0.1.0");
+
+        let stable = select_release(releases(), &current, "stable").unwrap();
+        assert_eq!(stable.version, "0.2.0");
+        assert!(!stable.prerelease);
+
+        let rc = select_release(releases(), &current, "release").unwrap();
+        assert_eq!(rc.version, "0.3.0-rc.1");
+        assert_eq!(rc.tag, "v0.3.0-rc.1");
+        assert!(rc.prerelease);
+
+        assert!(select_release(releases(), &v("0.3.0"), "dev").is_none());
+    }
+
+    #[test]
+    fn test_select_release_skips_drafts_and_flagged_prereleases_on_stable() {
+        let mut draft = release("v0.5.0", false);
+        draft.draft = true;
+        let releases = vec![draft, release("v0.4.0", true), release("v0.2.0", false)];
+        let selected = select_release(releases, &v("0.1.0"), "stable").unwrap();
+        assert_eq!(selected.version, "0.2.0");
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let notes = "\
+## What's new
+- Faster dashboards
+- feat(apps)!: app manifests use the v2 format
+
+## ⚠️ Breaking changes
+- `KUBARR_DB_URL` was renamed to `DATABASE_URL`
+### Helm
+* `ingress.host` moved to `ingress.hosts`
+
+## Fixes
+- BREAKING: sessions are signed out after upgrading
+- fix!
+";
+        assert_eq!(
+            breaking_changes(notes),
+            vec![
+                "feat(apps)!: app manifests use the v2 format",
+                "`KUBARR_DB_URL` was renamed to `DATABASE_URL`",
+                "`ingress.host` moved to `ingress.hosts`",
+                "sessions are signed out after upgrading",
+            ]
+        );
+        assert!(breaking_changes("## Fixes\n- Typo in the setup wizard").is_empty());
+    }
+
+    #[test]
+    fn test_release_notes() {
+        let mut major = release("v1.0.0", false);
+        major.body = Some("- Stable API".to_string());
+        let mut installed = release("v0.2.0", false);
+        installed.body = Some("## Breaking changes\n- Old one".to_string());
+        let mut rc = release("v0.4.0-rc.1", true);
+        rc.body = Some("- BREAKING CHANGE: new storage layout".to_string());
+        let releases = vec![
+            release("v0.3.0", false),
+            rc,
+            major,
+            installed,
+            release("v0.1.0", false),
+        ];
+
+        let (installed, available) = release_notes(&releases, &v("0.2.0"), "stable");
+        let installed = installed.unwrap();
+        assert_eq!(installed.version, "0.2.0");
+        assert_eq!(installed.notes, "## Breaking changes\n- Old one");
+        let versions: Vec<_> = available.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.0.0", "0.3.0"]);
+        assert!(available[0].breaking);
+        assert_eq!(
+            available[0].warnings,
+            vec!["Major version upgrade from 0 to 1"]
+        );
+        assert!(!available[1].breaking);
+        assert_eq!(available[1].notes, "");
+
+        let (_, available) = release_notes(&releases, &v("0.3.0"), "release");
+        let versions: Vec<_> = available.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.0.0", "0.4.0-rc.1"]);
+        assert_eq!(available[1].warnings, vec!["new storage layout"]);
+        assert!(available[1].prerelease);
+    }
+
+    fn deployment(name: &str, desired: i32, ready: i32) -> Deployment {
+        Deployment {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            spec: Some(DeploymentSpec {
+                replicas: Some(desired),
+                ..Default::default()
+            }),
+            status: Some(DeploymentStatus {
+                ready_replicas: Some(ready),
+                available_replicas: Some(ready),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_rolled_out() {
+        assert!(rolled_out(&[]).is_ok());
+        assert!(rolled_out(&[deployment("kubarr-backend", 1, 1)]).is_ok());
+        let err = rolled_out(&[
+            deployment("kubarr-backend", 1, 1),
+            deployment("kubarr-frontend", 2, 1),
+        ])
+        .unwrap_err();
+        assert_eq!(err, "deployment kubarr-frontend has 1/2 replicas ready");
+    }
+}
@@ -0,0 +1,719 @@
+//! Uptime monitors for user-defined HTTP and TCP endpoints
+//!
+//! Monitors are checked by a background worker on their own interval. Every
+//! check is stored in `uptime_checks`; the monitor's current status is kept
+//! on the monitor row so up/down transitions can be detected and the
+//! monitor's owner notified.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use super::alerts;
+use super::notification::NotificationService;
+use super::outbound;
+use super::scheduler::PeriodicTask;
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::{uptime_check, uptime_monitor};
+
+pub const KIND_HTTP: &str = "http";
+pub const KIND_TCP: &str = "tcp";
+
+pub const STATUS_UP: &str = "up";
+pub const STATUS_DOWN: &str = "down";
+pub const STATUS_UNKNOWN: &str = "unknown";
+
+/// Shortest allowed check interval
+pub const MIN_INTERVAL_SECONDS: i32 = 30;
+/// Longest allowed check interval (1 day)
+pub const MAX_INTERVAL_SECONDS: i32 = 86400;
+/// Longest allowed check timeout
+pub const MAX_TIMEOUT_SECONDS: i32 = 60;
+
+const DEFAULT_INTERVAL_SECONDS: i32 = 60;
+const DEFAULT_TIMEOUT_SECONDS: i32 = 10;
+/// How often the worker looks for due monitors
+const WORKER_TICK_SECS: u64 = 15;
+const RETENTION_DAYS: i64 = 30;
+/// Number of recent checks included in a monitor's detail view
+const DETAIL_HISTORY_LIMIT: usize = 500;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateUptimeMonitorRequest {
+    pub name: String,
+    /// "http" or "tcp"
+    pub kind: String,
+    /// URL for HTTP monitors, host:port for TCP monitors
+    pub target: String,
+    pub interval_seconds: Option<i32>,
+    pub timeout_seconds: Option<i32>,
+    /// Required HTTP status (default: any status below 400)
+    pub expected_status: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateUptimeMonitorRequest {
+    pub name: Option<String>,
+    pub target: Option<String>,
+    pub interval_seconds: Option<i32>,
+    pub timeout_seconds: Option<i32>,
+    pub expected_status: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+/// Response-time percentiles of successful checks
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ResponseTimeStats {
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// A monitor with its statistics over the requested window
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct UptimeMonitorSummary {
+    pub id: i64,
+    pub name: String,
+    pub kind: String,
+    pub target: String,
+    pub interval_seconds: i32,
+    pub timeout_seconds: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_status: Option<i32>,
+    pub enabled: bool,
+    /// "up", "down" or "unknown"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_change_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub checks: usize,
+    /// Share of successful checks in the window (None without checks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<ResponseTimeStats>,
+}
+
+/// A single check result
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct UptimeCheckPoint {
+    pub checked_at: DateTime<Utc>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<uptime_check::Model> for UptimeCheckPoint {
+    fn from(check: uptime_check::Model) -> Self {
+        Self {
+            checked_at: check.checked_at,
+            success: check.success,
+            response_time_ms: check.response_time_ms,
+            status_code: check.status_code,
+            error: check.error,
+        }
+    }
+}
+
+/// Response for GET /api/monitoring/uptime/{id}
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UptimeMonitorDetail {
+    #[serde(flatten)]
+    pub monitor: UptimeMonitorSummary,
+    /// Oldest first
+    pub history: Vec<UptimeCheckPoint>,
+}
+
+/// Response for GET /api/monitoring/uptime
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UptimeOverview {
+    pub window_hours: i64,
+    pub up: usize,
+    pub down: usize,
+    pub unknown: usize,
+    pub monitors: Vec<UptimeMonitorSummary>,
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+fn validate_target(kind: &str, target: &str) -> Result<()> {
+    match kind {
+        KIND_HTTP => {
+            let url = reqwest::Url::parse(target)
+                .map_err(|e| AppError::BadRequest(format!("Invalid URL '{}': {}", target, e)))?;
+            if url.scheme() != "http" && url.scheme() != "https" {
+                return Err(AppError::BadRequest(
+                    "HTTP monitors require an http:// or https:// URL".to_string(),
+                ));
+            }
+            Ok(())
+        }
+        KIND_TCP => match target.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0) => {
+                Ok(())
+            }
+            _ => Err(AppError::BadRequest(format!(
+                "TCP monitors require a host:port target, got '{}'",
+                target
+            ))),
+        },
+        other => Err(AppError::BadRequest(format!(
+            "Invalid monitor kind '{}': expected 'http' or 'tcp'",
+            other
+        ))),
+    }
+}
+
+fn validate_timing(interval_seconds: i32, timeout_seconds: i32) -> Result<()> {
+    if !(MIN_INTERVAL_SECONDS..=MAX_INTERVAL_SECONDS).contains(&interval_seconds) {
+        return Err(AppError::BadRequest(format!(
+            "interval_seconds must be between {} and {}",
+            MIN_INTERVAL_SECONDS, MAX_INTERVAL_SECONDS
+        )));
+    }
+    if !(1..=MAX_TIMEOUT_SECONDS).contains(&timeout_seconds) || timeout_seconds >= interval_seconds
+    {
+        return Err(AppError::BadRequest(format!(
+            "timeout_seconds must be between 1 and {} and shorter than the interval",
+            MAX_TIMEOUT_SECONDS
+        )));
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Monitor name is required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+// ============================================================================
+// CRUD
+// ============================================================================
+
+async fn find_monitor(db: &DatabaseConnection, id: i64) -> Result<uptime_monitor::Model> {
+    UptimeMonitor::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Uptime monitor {} not found", id)))
+}
+
+/// Register a new monitor owned by `created_by`
+pub async fn create_monitor(
+    db: &DatabaseConnection,
+    request: CreateUptimeMonitorRequest,
+    created_by: i64,
+) -> Result<uptime_monitor::Model> {
+    let name = validate_name(&request.name)?;
+    let kind = request.kind.trim().to_lowercase();
+    let target = request.target.trim().to_string();
+    validate_target(&kind, &target)?;
+    let interval_seconds = request.interval_seconds.unwrap_or(DEFAULT_INTERVAL_SECONDS);
+    let timeout_seconds = request.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS);
+    validate_timing(interval_seconds, timeout_seconds)?;
+
+    let now = Utc::now();
+    let model = uptime_monitor::ActiveModel {
+        name: Set(name),
+        kind: Set(kind),
+        target: Set(target),
+        interval_seconds: Set(interval_seconds),
+        timeout_seconds: Set(timeout_seconds),
+        expected_status: Set(request.expected_status),
+        enabled: Set(request.enabled.unwrap_or(true)),
+        status: Set(STATUS_UNKNOWN.to_string()),
+        last_checked_at: Set(None),
+        last_change_at: Set(None),
+        created_by: Set(Some(created_by)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(model)
+}
+
+/// Update a monitor; changing the target resets its status
+pub async fn update_monitor(
+    db: &DatabaseConnection,
+    id: i64,
+    request: UpdateUptimeMonitorRequest,
+) -> Result<uptime_monitor::Model> {
+    let existing = find_monitor(db, id).await?;
+
+    let interval_seconds = request
+        .interval_seconds
+        .unwrap_or(existing.interval_seconds);
+    let timeout_seconds = request.timeout_seconds.unwrap_or(existing.timeout_seconds);
+    validate_timing(interval_seconds, timeout_seconds)?;
+
+    let target_changed = request
+        .target
+        .as_deref()
+        .is_some_and(|t| t.trim() != existing.target);
+
+    let mut active: uptime_monitor::ActiveModel = existing.clone().into();
+    if let Some(name) = request.name {
+        active.name = Set(validate_name(&name)?);
+    }
+    if let Some(target) = request.target {
+        let target = target.trim().to_string();
+        validate_target(&existing.kind, &target)?;
+        active.target = Set(target);
+    }
+    if target_changed {
+        active.status = Set(STATUS_UNKNOWN.to_string());
+        active.last_change_at = Set(None);
+    }
+    if request.expected_status.is_some() {
+        active.expected_status = Set(request.expected_status);
+    }
+    if let Some(enabled) = request.enabled {
+        active.enabled = Set(enabled);
+    }
+    active.interval_seconds = Set(interval_seconds);
+    active.timeout_seconds = Set(timeout_seconds);
+    active.updated_at = Set(Utc::now());
+    Ok(active.update(db).await?)
+}
+
+/// Delete a monitor and its history
+pub async fn delete_monitor(db: &DatabaseConnection, id: i64) -> Result<()> {
+    let monitor = find_monitor(db, id).await?;
+    UptimeCheck::delete_many()
+        .filter(uptime_check::Column::MonitorId.eq(monitor.id))
+        .exec(db)
+        .await?;
+    UptimeMonitor::delete_by_id(monitor.id).exec(db).await?;
+    Ok(())
+}
+
+// ============================================================================
+// Checking
+// ============================================================================
+
+/// Outcome of a single check
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    pub success: bool,
+    pub response_time_ms: Option<f64>,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl CheckOutcome {
+    fn failed(error: String) -> Self {
+        Self {
+            success: false,
+            response_time_ms: None,
+            status_code: None,
+            error: Some(error),
+        }
+    }
+}
+
+async fn check_http(monitor: &uptime_monitor::Model, timeout: Duration) -> CheckOutcome {
+    let client = match outbound::client_builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => return CheckOutcome::failed(e.to_string()),
+    };
+
+    let start = Instant::now();
+    match client.get(&monitor.target).send().await {
+        Ok(resp) => {
+            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+            let code = resp.status().as_u16() as i32;
+            let success = match monitor.expected_status {
+                Some(expected) => code == expected,
+                None => code < 400,
+            };
+            CheckOutcome {
+                success,
+                response_time_ms: Some(round2(elapsed)),
+                status_code: Some(code),
+                error: (!success).then(|| format!("Unexpected HTTP status {}", code)),
+            }
+        }
+        Err(e) if e.is_timeout() => CheckOutcome::failed("Request timed out".to_string()),
+        Err(e) => CheckOutcome::failed(e.to_string()),
+    }
+}
+
+async fn check_tcp(monitor: &uptime_monitor::Model, timeout: Duration) -> CheckOutcome {
+    let start = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect(&monitor.target)).await {
+        Ok(Ok(_)) => CheckOutcome {
+            success: true,
+            response_time_ms: Some(round2(start.elapsed().as_secs_f64() * 1000.0)),
+            status_code: None,
+            error: None,
+        },
+        Ok(Err(e)) => CheckOutcome::failed(e.to_string()),
+        Err(_) => CheckOutcome::failed("Connection timed out".to_string()),
+    }
+}
+
+/// Check a monitor once (without recording the result)
+pub async fn check_monitor(monitor: &uptime_monitor::Model) -> CheckOutcome {
+    let timeout = Duration::from_secs(monitor.timeout_seconds.max(1) as u64);
+    if monitor.kind == KIND_TCP {
+        check_tcp(monitor, timeout).await
+    } else {
+        check_http(monitor, timeout).await
+    }
+}
+
+/// Store a check result and update the monitor's status
+///
+/// Returns the notification to send if the monitor went down or came back up.
+pub async fn record_check(
+    db: &DatabaseConnection,
+    monitor: uptime_monitor::Model,
+    outcome: CheckOutcome,
+    at: DateTime<Utc>,
+) -> Result<(uptime_monitor::Model, Option<AuditAction>)> {
+    uptime_check::ActiveModel {
+        monitor_id: Set(monitor.id),
+        success: Set(outcome.success),
+        response_time_ms: Set(outcome.response_time_ms),
+        status_code: Set(outcome.status_code),
+        error: Set(outcome.error),
+        checked_at: Set(at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    let status = if outcome.success {
+        STATUS_UP
+    } else {
+        STATUS_DOWN
+    };
+    let event = transition_event(&monitor.status, status);
+    let changed = monitor.status != status;
+
+    let mut active: uptime_monitor::ActiveModel = monitor.into();
+    active.status = Set(status.to_string());
+    active.last_checked_at = Set(Some(at));
+    if changed {
+        active.last_change_at = Set(Some(at));
+    }
+    Ok((active.update(db).await?, event))
+}
+
+/// Notification for a status change; the first check only notifies if down
+pub fn transition_event(previous: &str, current: &str) -> Option<AuditAction> {
+    match (previous, current) {
+        (STATUS_DOWN, STATUS_UP) => Some(AuditAction::UptimeMonitorUp),
+        (prev, STATUS_DOWN) if prev != STATUS_DOWN => Some(AuditAction::UptimeMonitorDown),
+        _ => None,
+    }
+}
+
+/// Whether a monitor should be checked at `now`
+pub fn is_due(monitor: &uptime_monitor::Model, now: DateTime<Utc>) -> bool {
+    monitor.enabled
+        && monitor.last_checked_at.is_none_or(|last| {
+            now - last >= chrono::Duration::seconds(monitor.interval_seconds as i64)
+        })
+}
+
+/// Check a monitor, record the result and notify its owner on status changes
+/// (unless its alert is silenced)
+pub async fn run_check(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    monitor: uptime_monitor::Model,
+) -> Result<uptime_monitor::Model> {
+    let outcome = check_monitor(&monitor).await;
+    let error = outcome.error.clone();
+    let now = Utc::now();
+    let (monitor, event) = record_check(db, monitor, outcome, now).await?;
+
+    if let Some(action) = event {
+        let detail = match &error {
+            Some(error) => format!("{} ({}): {}", monitor.name, monitor.target, error),
+            None => format!("{} ({})", monitor.name, monitor.target),
+        };
+        tracing::info!(monitor = %monitor.name, status = %monitor.status, "Uptime monitor changed status");
+        if alerts::is_silenced(
+            db,
+            &alerts::fingerprint(alerts::SOURCE_UPTIME, monitor.id),
+            now,
+        )
+        .await?
+        {
+            return Ok(monitor);
+        }
+        notification
+            .notify_event(&action, monitor.created_by, None, Some(&detail))
+            .await?;
+    }
+    Ok(monitor)
+}
+
+// ============================================================================
+// Statistics
+// ============================================================================
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Nearest-rank percentile of an ascending slice
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn response_time_stats(checks: &[uptime_check::Model]) -> Option<ResponseTimeStats> {
+    let mut times: Vec<f64> = checks
+        .iter()
+        .filter(|c| c.success)
+        .filter_map(|c| c.response_time_ms)
+        .collect();
+    if times.is_empty() {
+        return None;
+    }
+    times.sort_by(f64::total_cmp);
+    Some(ResponseTimeStats {
+        avg_ms: round2(times.iter().sum::<f64>() / times.len() as f64),
+        p50_ms: percentile(&times, 50.0),
+        p95_ms: percentile(&times, 95.0),
+        p99_ms: percentile(&times, 99.0),
+    })
+}
+
+fn summarize(
+    monitor: uptime_monitor::Model,
+    checks: &[uptime_check::Model],
+) -> UptimeMonitorSummary {
+    let successes = checks.iter().filter(|c| c.success).count();
+    let uptime_percent =
+        (!checks.is_empty()).then(|| round2(successes as f64 / checks.len() as f64 * 100.0));
+    let last_error = checks
+        .last()
+        .filter(|c| !c.success)
+        .and_then(|c| c.error.clone());
+
+    UptimeMonitorSummary {
+        id: monitor.id,
+        name: monitor.name,
+        kind: monitor.kind,
+        target: monitor.target,
+        interval_seconds: monitor.interval_seconds,
+        timeout_seconds: monitor.timeout_seconds,
+        expected_status: monitor.expected_status,
+        enabled: monitor.enabled,
+        status: monitor.status,
+        last_checked_at: monitor.last_checked_at,
+        last_change_at: monitor.last_change_at,
+        last_error,
+        checks: checks.len(),
+        uptime_percent,
+        response_time: response_time_stats(checks),
+    }
+}
+
+async fn checks_since(
+    db: &DatabaseConnection,
+    monitor_id: i64,
+    since: DateTime<Utc>,
+) -> Result<Vec<uptime_check::Model>> {
+    Ok(UptimeCheck::find()
+        .filter(uptime_check::Column::MonitorId.eq(monitor_id))
+        .filter(uptime_check::Column::CheckedAt.gte(since))
+        .order_by_asc(uptime_check::Column::CheckedAt)
+        .all(db)
+        .await?)
+}
+
+/// Status and statistics of all monitors over the last `hours` hours
+pub async fn overview(
+    db: &DatabaseConnection,
+    hours: i64,
+    now: DateTime<Utc>,
+) -> Result<UptimeOverview> {
+    let since = now - chrono::Duration::hours(hours);
+    let monitors = UptimeMonitor::find()
+        .order_by_asc(uptime_monitor::Column::Name)
+        .all(db)
+        .await?;
+
+    let mut summaries = Vec::with_capacity(monitors.len());
+    for monitor in monitors {
+        let checks = checks_since(db, monitor.id, since).await?;
+        summaries.push(summarize(monitor, &checks));
+    }
+
+    let count = |status: &str| summaries.iter().filter(|m| m.status == status).count();
+    Ok(UptimeOverview {
+        window_hours: hours,
+        up: count(STATUS_UP),
+        down: count(STATUS_DOWN),
+        unknown: count(STATUS_UNKNOWN),
+        monitors: summaries,
+    })
+}
+
+/// A monitor with its statistics and check history over the last `hours` hours
+pub async fn monitor_detail(
+    db: &DatabaseConnection,
+    id: i64,
+    hours: i64,
+    now: DateTime<Utc>,
+) -> Result<UptimeMonitorDetail> {
+    let monitor = find_monitor(db, id).await?;
+    let checks = checks_since(db, monitor.id, now - chrono::Duration::hours(hours)).await?;
+    let summary = summarize(monitor, &checks);
+    let skip = checks.len().saturating_sub(DETAIL_HISTORY_LIMIT);
+    Ok(UptimeMonitorDetail {
+        monitor: summary,
+        history: checks.into_iter().skip(skip).map(Into::into).collect(),
+    })
+}
+
+/// Check a monitor immediately (used by the "check now" endpoint)
+pub async fn check_now(
+    db: &DatabaseConnection,
+    notification: &NotificationService,
+    id: i64,
+) -> Result<UptimeMonitorSummary> {
+    let monitor = find_monitor(db, id).await?;
+    let monitor = run_check(db, notification, monitor).await?;
+    let checks = checks_since(db, monitor.id, Utc::now() - chrono::Duration::hours(24)).await?;
+    Ok(summarize(monitor, &checks))
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Checks all due monitors
+pub struct UptimeMonitorTask {
+    pub notification: NotificationService,
+}
+
+#[async_trait]
+impl PeriodicTask for UptimeMonitorTask {
+    fn name(&self) -> &'static str {
+        "uptime_monitors"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(WORKER_TICK_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let due: Vec<uptime_monitor::Model> = UptimeMonitor::find()
+            .filter(uptime_monitor::Column::Enabled.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|m| is_due(m, now))
+            .collect();
+
+        let results = join_all(
+            due.into_iter()
+                .map(|monitor| run_check(db, &self.notification, monitor)),
+        )
+        .await;
+        for result in results {
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "Uptime check failed to record");
+            }
+        }
+
+        UptimeCheck::delete_many()
+            .filter(
+                uptime_check::Column::CheckedAt.lt(now - chrono::Duration::days(RETENTION_DAYS)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_target() {
+        assert!(validate_target(KIND_HTTP, "https://nas.local:5001/").is_ok());
+        assert!(validate_target(KIND_HTTP, "ftp://nas.local").is_err());
+        assert!(validate_target(KIND_HTTP, "not a url").is_err());
+        assert!(validate_target(KIND_TCP, "192.168.1.10:445").is_ok());
+        assert!(validate_target(KIND_TCP, "192.168.1.10").is_err());
+        assert!(validate_target(KIND_TCP, ":80").is_err());
+        assert!(validate_target("icmp", "1.1.1.1").is_err());
+    }
+
+    #[test]
+    fn test_validate_timing() {
+        assert!(validate_timing(60, 10).is_ok());
+        assert!(validate_timing(10, 5).is_err());
+        assert!(validate_timing(60, 0).is_err());
+        assert!(validate_timing(30, 30).is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&values, 50.0), 50.0);
+        assert_eq!(percentile(&values, 95.0), 95.0);
+        assert_eq!(percentile(&values, 99.0), 99.0);
+        assert_eq!(percentile(&[42.0], 99.0), 42.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_transition_event() {
+        assert!(matches!(
+            transition_event(STATUS_UP, STATUS_DOWN),
+            Some(AuditAction::UptimeMonitorDown)
+        ));
+        assert!(matches!(
+            transition_event(STATUS_UNKNOWN, STATUS_DOWN),
+            Some(AuditAction::UptimeMonitorDown)
+        ));
+        assert!(matches!(
+            transition_event(STATUS_DOWN, STATUS_UP),
+            Some(AuditAction::UptimeMonitorUp)
+        ));
+        assert!(transition_event(STATUS_UNKNOWN, STATUS_UP).is_none());
+        assert!(transition_event(STATUS_DOWN, STATUS_DOWN).is_none());
+        assert!(transition_event(STATUS_UP, STATUS_UP).is_none());
+    }
+}
@@ -0,0 +1,364 @@
+//! VictoriaMetrics query helpers
+//!
+//! Thin wrappers around the Prometheus-compatible query API. Failures are
+//! treated as "no data" so callers can degrade gracefully when
+//! VictoriaMetrics is not installed. User-supplied queries (custom dashboard
+//! panels) must pass `validate_query` first.
+
+use super::outbound;
+use super::runtime_config;
+
+/// Default VictoriaMetrics URL (inside cluster); overridable at runtime
+pub const VICTORIAMETRICS_URL: &str =
+    "http://victoriametrics.victoriametrics.svc.cluster.local:8428";
+
+/// VictoriaMetrics base URL currently in effect
+pub fn victoriametrics_url() -> String {
+    runtime_config::current().victoriametrics_url.clone()
+}
+
+/// Run an instant query and return the result vector
+pub async fn query_vm(query: &str) -> Vec<serde_json::Value> {
+    let client = outbound::client();
+    let url = format!("{}/api/v1/query", victoriametrics_url());
+
+    match client
+        .get(&url)
+        .query(&[("query", query)])
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+    {
+        Ok(resp) => {
+            if let Ok(data) = resp.json::<serde_json::Value>().await {
+                if data.get("status") == Some(&serde_json::json!("success")) {
+                    return data["data"]["result"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default();
+                }
+            }
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Run a range query and return the result matrix
+pub async fn query_vm_range(
+    query: &str,
+    start: f64,
+    end: f64,
+    step: &str,
+) -> Vec<serde_json::Value> {
+    let client = outbound::client();
+    let url = format!("{}/api/v1/query_range", victoriametrics_url());
+
+    match client
+        .get(&url)
+        .query(&[
+            ("query", query),
+            ("start", &start.to_string()),
+            ("end", &end.to_string()),
+            ("step", step),
+        ])
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+    {
+        Ok(resp) => {
+            if let Ok(data) = resp.json::<serde_json::Value>().await {
+                if data.get("status") == Some(&serde_json::json!("success")) {
+                    return data["data"]["result"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default();
+                }
+            }
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Run an instant query and return the first sample as a number
+pub async fn query_vm_scalar(query: &str) -> Option<f64> {
+    query_vm(query)
+        .await
+        .first()
+        .and_then(|r| r["value"][1].as_str())
+        .and_then(|v| v.parse::<f64>().ok())
+}
+
+// ============================================================================
+// Query Validation
+// ============================================================================
+
+/// Longest query accepted from users
+pub const MAX_QUERY_LENGTH: usize = 1000;
+
+/// Metric families users may query (cAdvisor, node, kubelet and GPU metrics)
+const ALLOWED_METRIC_PREFIXES: &[&str] = &[
+    "container_",
+    "machine_",
+    "node_",
+    "kube_",
+    "kubelet_",
+    "DCGM_",
+];
+
+/// Functions and aggregation operators users may call
+const ALLOWED_FUNCTIONS: &[&str] = &[
+    "abs",
+    "absent",
+    "avg",
+    "avg_over_time",
+    "bottomk",
+    "ceil",
+    "clamp_max",
+    "clamp_min",
+    "count",
+    "count_over_time",
+    "delta",
+    "deriv",
+    "floor",
+    "histogram_quantile",
+    "increase",
+    "irate",
+    "max",
+    "max_over_time",
+    "min",
+    "min_over_time",
+    "predict_linear",
+    "quantile",
+    "quantile_over_time",
+    "rate",
+    "round",
+    "scalar",
+    "sort",
+    "sort_desc",
+    "stddev",
+    "stddev_over_time",
+    "sum",
+    "sum_over_time",
+    "time",
+    "topk",
+    "vector",
+];
+
+/// Keywords followed by a parenthesised label list
+const LABEL_LIST_KEYWORDS: &[&str] = &[
+    "by",
+    "without",
+    "on",
+    "ignoring",
+    "group_left",
+    "group_right",
+];
+
+/// Other bare keywords and literals
+const KEYWORDS: &[&str] = &["and", "or", "unless", "offset", "bool", "nan", "inf"];
+
+/// Validate a user-supplied PromQL query before it is sent to VictoriaMetrics
+///
+/// Only a restricted subset is accepted: known metric families, a whitelist
+/// of functions, label matchers and range selectors. The check is lexical,
+/// so syntax errors beyond unbalanced brackets are left to VictoriaMetrics.
+pub fn validate_query(query: &str) -> std::result::Result<(), String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Query is empty".to_string());
+    }
+    if query.len() > MAX_QUERY_LENGTH {
+        return Err(format!("Query exceeds {} characters", MAX_QUERY_LENGTH));
+    }
+
+    let chars: Vec<char> = query.chars().collect();
+    let mut depth = 0i32;
+    let mut i = 0;
+    // Whether the last token was an allowed metric name
+    let mut after_metric = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let follows_metric = after_metric;
+        if !c.is_whitespace() {
+            after_metric = false;
+        }
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '"' | '\'' => i = skip_string(&chars, i)?,
+            '{' => {
+                // A bare matcher such as `{job=~".+"}` selects any metric
+                if !follows_metric {
+                    return Err("Label matchers must follow an allowed metric name".to_string());
+                }
+                let start = i;
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("Unterminated label matcher".to_string()),
+                        Some('}') => break,
+                        Some('{') => return Err("Nested label matcher".to_string()),
+                        Some('"') | Some('\'') => i = skip_string(&chars, i)?,
+                        Some(_) => i += 1,
+                    }
+                }
+                i += 1;
+                // Selecting by metric name would bypass the metric whitelist
+                if chars[start..i]
+                    .iter()
+                    .collect::<String>()
+                    .contains("__name__")
+                {
+                    return Err("Matching on __name__ is not allowed".to_string());
+                }
+            }
+            '[' => {
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("Unterminated range selector".to_string()),
+                        Some(']') => break,
+                        Some(c) if c.is_ascii_alphanumeric() || *c == ':' => i += 1,
+                        Some(c) => return Err(format!("Unexpected '{}' in range selector", c)),
+                    }
+                }
+                i += 1;
+            }
+            '(' => {
+                depth += 1;
+                i += 1;
+            }
+            ')' => {
+                depth -= 1;
+                if depth < 0 {
+                    return Err("Unbalanced parentheses".to_string());
+                }
+                i += 1;
+            }
+            ',' | '+' | '-' | '*' | '/' | '%' | '^' | '=' | '!' | '<' | '>' => i += 1,
+            c if c.is_ascii_digit() || c == '.' => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == ':' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == ':')
+                {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                let mut next = i;
+                while next < chars.len() && chars[next].is_whitespace() {
+                    next += 1;
+                }
+                let followed_by_paren = chars.get(next) == Some(&'(');
+
+                if ALLOWED_FUNCTIONS.contains(&ident.as_str()) {
+                    continue;
+                }
+                if LABEL_LIST_KEYWORDS.contains(&ident.as_str()) {
+                    if followed_by_paren {
+                        i = skip_label_list(&chars, next)?;
+                    }
+                    continue;
+                }
+                if KEYWORDS.contains(&ident.to_ascii_lowercase().as_str()) {
+                    continue;
+                }
+                if followed_by_paren {
+                    return Err(format!("Function '{}' is not allowed", ident));
+                }
+                if !ALLOWED_METRIC_PREFIXES
+                    .iter()
+                    .any(|prefix| ident.starts_with(prefix))
+                {
+                    return Err(format!("Metric '{}' is not allowed", ident));
+                }
+                after_metric = true;
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+
+    if depth != 0 {
+        return Err("Unbalanced parentheses".to_string());
+    }
+    Ok(())
+}
+
+/// Skip a quoted string starting at `start`, returning the index after it
+fn skip_string(chars: &[char], start: usize) -> std::result::Result<usize, String> {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return Ok(i + 1),
+            _ => i += 1,
+        }
+    }
+    Err("Unterminated string".to_string())
+}
+
+/// Skip a `(label, label)` list starting at `start`, returning the index after it
+fn skip_label_list(chars: &[char], start: usize) -> std::result::Result<usize, String> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            ')' => return Ok(i + 1),
+            c if c.is_ascii_alphanumeric() || c == '_' || c == ',' || c.is_whitespace() => i += 1,
+            c => return Err(format!("Unexpected '{}' in label list", c)),
+        }
+    }
+    Err("Unterminated label list".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_query_accepts_dashboard_queries() {
+        for query in [
+            r#"sum by (namespace) (rate(container_cpu_usage_seconds_total{container!="",container!="POD"}[5m]))"#,
+            r#"sum(container_memory_working_set_bytes{namespace="sonarr"}) by (pod) / 1024 / 1024"#,
+            "avg(DCGM_FI_DEV_GPU_UTIL)",
+            "topk(5, sum by (namespace) (increase(container_network_receive_bytes_total[1h])))",
+            "sum(machine_memory_bytes) - sum(container_memory_working_set_bytes offset 1d)",
+            "histogram_quantile(0.95, sum by (le) (rate(kubelet_runtime_operations_duration_seconds_bucket[5m])))",
+        ] {
+            assert_eq!(validate_query(query), Ok(()), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_validate_query_rejects_unlisted_metrics_and_functions() {
+        assert!(validate_query("").is_err());
+        assert!(validate_query("kubarr_secret_total").is_err());
+        assert!(validate_query("sum(up)").is_err());
+        assert!(
+            validate_query(r#"label_replace(container_last_seen, "a", "b", "c", "d")"#)
+                .unwrap_err()
+                .contains("label_replace")
+        );
+        assert!(validate_query("count({__name__=~\".+\"})").is_err());
+        assert!(validate_query(r#"sum({namespace="kubarr"})"#).is_err());
+        assert!(validate_query(r#"{job=~".+"}"#).is_err());
+        assert!(validate_query(r#"rate(container_last_seen[5m]){job="x"}"#).is_err());
+        assert!(validate_query(&"a".repeat(MAX_QUERY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_validate_query_rejects_malformed_input() {
+        assert!(validate_query("sum(container_last_seen").is_err());
+        assert!(validate_query("container_last_seen)").is_err());
+        assert!(validate_query(r#"container_last_seen{namespace="x"#).is_err());
+        assert!(validate_query("rate(container_last_seen[5m;])").is_err());
+        assert!(validate_query("container_last_seen; drop").is_err());
+        assert!(validate_query("sum by (namespace; pod) (container_last_seen)").is_err());
+    }
+}
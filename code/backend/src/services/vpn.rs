@@ -17,7 +17,7 @@ use serde::{Deserialize, Serialize};
 use crate::error::{AppError, Result};
 use crate::models::prelude::*;
 use crate::models::{app_vpn_config, vpn_provider};
-use crate::services::K8sClient;
+use crate::services::{crypto, K8sApi, K8sClient};
 use crate::state::DbConn;
 
 // ============================================================================
@@ -228,6 +228,7 @@ pub async fn create_vpn_provider(
     let now = Utc::now();
     let credentials_json = serde_json::to_string(&req.credentials)
         .map_err(|e| AppError::BadRequest(format!("Invalid credentials JSON: {}", e)))?;
+    let credentials_json = crypto::encrypt(&credentials_json)?;
 
     let new_provider = vpn_provider::ActiveModel {
         name: Set(req.name),
@@ -281,7 +282,7 @@ pub async fn update_vpn_provider(
         validate_credentials(&provider.vpn_type, &credentials)?;
         let credentials_json = serde_json::to_string(&credentials)
             .map_err(|e| AppError::BadRequest(format!("Invalid credentials JSON: {}", e)))?;
-        active_model.credentials_json = Set(credentials_json);
+        active_model.credentials_json = Set(crypto::encrypt(&credentials_json)?);
     }
     if let Some(enabled) = req.enabled {
         active_model.enabled = Set(enabled);
@@ -317,7 +318,7 @@ pub async fn update_vpn_provider(
 }
 
 /// Delete a VPN provider
-pub async fn delete_vpn_provider(db: &DbConn, k8s: &K8sClient, id: i64) -> Result<()> {
+pub async fn delete_vpn_provider(db: &DbConn, k8s: &dyn K8sApi, id: i64) -> Result<()> {
     // Get all apps using this provider
     let app_configs = AppVpnConfig::find()
         .filter(app_vpn_config::Column::VpnProviderId.eq(id))
@@ -473,7 +474,7 @@ pub async fn assign_vpn_to_app(
 }
 
 /// Remove VPN from an app
-pub async fn remove_vpn_from_app(db: &DbConn, k8s: &K8sClient, app_name: &str) -> Result<()> {
+pub async fn remove_vpn_from_app(db: &DbConn, k8s: &dyn K8sApi, app_name: &str) -> Result<()> {
     // Delete K8s secret
     if let Err(e) = delete_vpn_secret_for_app(k8s, app_name).await {
         tracing::warn!("Failed to delete VPN secret for app {}: {}", app_name, e);
@@ -491,7 +492,7 @@ pub async fn remove_vpn_from_app(db: &DbConn, k8s: &K8sClient, app_name: &str) -
 
 /// Create or update a K8s secret for an app's VPN configuration
 pub async fn create_vpn_secret_for_app(
-    k8s: &K8sClient,
+    k8s: &dyn K8sApi,
     db: &DbConn,
     app_name: &str,
 ) -> Result<String> {
@@ -510,8 +511,9 @@ pub async fn create_vpn_secret_for_app(
         })?;
 
     // Parse credentials
-    let credentials: serde_json::Value = serde_json::from_str(&provider.credentials_json)
-        .map_err(|e| AppError::Internal(format!("Invalid credentials JSON: {}", e)))?;
+    let credentials: serde_json::Value =
+        serde_json::from_str(&crypto::decrypt(&provider.credentials_json)?)
+            .map_err(|e| AppError::Internal(format!("Invalid credentials JSON: {}", e)))?;
 
     // Build secret data based on VPN type
     let mut secret_data: BTreeMap<String, String> = BTreeMap::new();
@@ -565,33 +567,15 @@ pub async fn create_vpn_secret_for_app(
         ..Default::default()
     };
 
-    // Create or update in K8s
-    let secrets: Api<Secret> = Api::namespaced(k8s.client().clone(), namespace);
-
-    // Try to delete existing secret first (simpler than patch)
-    let _ = secrets.delete(&secret_name, &DeleteParams::default()).await;
-
-    // Create new secret
-    secrets.create(&PostParams::default(), &secret).await?;
+    k8s.replace_secret(namespace, secret).await?;
 
     Ok(secret_name)
 }
 
 /// Delete the VPN secret for an app
-pub async fn delete_vpn_secret_for_app(k8s: &K8sClient, app_name: &str) -> Result<()> {
+pub async fn delete_vpn_secret_for_app(k8s: &dyn K8sApi, app_name: &str) -> Result<()> {
     let secret_name = format!("vpn-{}", app_name);
-    let namespace = app_name;
-
-    let secrets: Api<Secret> = Api::namespaced(k8s.client().clone(), namespace);
-
-    match secrets.delete(&secret_name, &DeleteParams::default()).await {
-        Ok(_) => Ok(()),
-        Err(kube::Error::Api(ae)) if ae.code == 404 => Ok(()), // Not found is OK
-        Err(e) => Err(AppError::Internal(format!(
-            "Failed to delete VPN secret: {}",
-            e
-        ))),
-    }
+    k8s.delete_secret(app_name, &secret_name).await
 }
 
 /// Get VPN deployment config for an app (used by deployment service)
@@ -756,8 +740,9 @@ async fn create_test_vpn_secret(
         .ok_or_else(|| AppError::Internal(format!("VPN provider {} not found", provider_id)))?;
 
     // Parse credentials
-    let credentials: serde_json::Value = serde_json::from_str(&provider.credentials_json)
-        .map_err(|e| AppError::Internal(format!("Invalid credentials JSON: {}", e)))?;
+    let credentials: serde_json::Value =
+        serde_json::from_str(&crypto::decrypt(&provider.credentials_json)?)
+            .map_err(|e| AppError::Internal(format!("Invalid credentials JSON: {}", e)))?;
 
     // Build secret data based on VPN type
     let mut secret_data: BTreeMap<String, String> = BTreeMap::new();
@@ -1163,6 +1148,82 @@ fn build_openvpn_secret_data(
     Ok(())
 }
 
+/// Get list of supported VPN service providers
+pub fn get_supported_providers() -> Vec<SupportedProvider> {
+    vec![
+        SupportedProvider {
+            id: "custom",
+            name: "Custom",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Custom WireGuard or OpenVPN configuration",
+            supports_port_forwarding: true,
+        },
+        SupportedProvider {
+            id: "airvpn",
+            name: "AirVPN",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Privacy-focused VPN with WireGuard and OpenVPN",
+            supports_port_forwarding: true,
+        },
+        SupportedProvider {
+            id: "expressvpn",
+            name: "ExpressVPN",
+            vpn_types: vec!["openvpn"],
+            description: "Fast VPN with servers in 94 countries",
+            supports_port_forwarding: false,
+        },
+        SupportedProvider {
+            id: "ipvanish",
+            name: "IPVanish",
+            vpn_types: vec!["openvpn"],
+            description: "VPN with configurable encryption",
+            supports_port_forwarding: false,
+        },
+        SupportedProvider {
+            id: "mullvad",
+            name: "Mullvad",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Privacy-first VPN, no email required",
+            supports_port_forwarding: false,
+        },
+        SupportedProvider {
+            id: "nordvpn",
+            name: "NordVPN",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Popular VPN with NordLynx (WireGuard)",
+            supports_port_forwarding: false,
+        },
+        SupportedProvider {
+            id: "private_internet_access",
+            name: "Private Internet Access (PIA)",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Proven no-logs VPN",
+            supports_port_forwarding: true,
+        },
+        SupportedProvider {
+            id: "protonvpn",
+            name: "ProtonVPN",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Swiss-based privacy VPN",
+            supports_port_forwarding: true,
+        },
+        SupportedProvider {
+            id: "surfshark",
+            name: "Surfshark",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "Unlimited device connections",
+            supports_port_forwarding: false,
+        },
+        SupportedProvider {
+            id: "windscribe",
+            name: "Windscribe",
+            vpn_types: vec!["wireguard", "openvpn"],
+            description: "VPN with built-in ad blocker",
+            supports_port_forwarding: false,
+        },
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1423,79 +1484,3 @@ mod tests {
         assert!(!data.contains_key("SERVER_HOSTNAMES"));
     }
 }
-
-/// Get list of supported VPN service providers
-pub fn get_supported_providers() -> Vec<SupportedProvider> {
-    vec![
-        SupportedProvider {
-            id: "custom",
-            name: "Custom",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Custom WireGuard or OpenVPN configuration",
-            supports_port_forwarding: true,
-        },
-        SupportedProvider {
-            id: "airvpn",
-            name: "AirVPN",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Privacy-focused VPN with WireGuard and OpenVPN",
-            supports_port_forwarding: true,
-        },
-        SupportedProvider {
-            id: "expressvpn",
-            name: "ExpressVPN",
-            vpn_types: vec!["openvpn"],
-            description: "Fast VPN with servers in 94 countries",
-            supports_port_forwarding: false,
-        },
-        SupportedProvider {
-            id: "ipvanish",
-            name: "IPVanish",
-            vpn_types: vec!["openvpn"],
-            description: "VPN with configurable encryption",
-            supports_port_forwarding: false,
-        },
-        SupportedProvider {
-            id: "mullvad",
-            name: "Mullvad",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Privacy-first VPN, no email required",
-            supports_port_forwarding: false,
-        },
-        SupportedProvider {
-            id: "nordvpn",
-            name: "NordVPN",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Popular VPN with NordLynx (WireGuard)",
-            supports_port_forwarding: false,
-        },
-        SupportedProvider {
-            id: "private_internet_access",
-            name: "Private Internet Access (PIA)",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Proven no-logs VPN",
-            supports_port_forwarding: true,
-        },
-        SupportedProvider {
-            id: "protonvpn",
-            name: "ProtonVPN",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Swiss-based privacy VPN",
-            supports_port_forwarding: true,
-        },
-        SupportedProvider {
-            id: "surfshark",
-            name: "Surfshark",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "Unlimited device connections",
-            supports_port_forwarding: false,
-        },
-        SupportedProvider {
-            id: "windscribe",
-            name: "Windscribe",
-            vpn_types: vec!["wireguard", "openvpn"],
-            description: "VPN with built-in ad blocker",
-            supports_port_forwarding: false,
-        },
-    ]
-}
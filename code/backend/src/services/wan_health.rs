@@ -0,0 +1,636 @@
+//! WAN health checks
+//!
+//! Every few minutes the configured targets (`wan_health_targets` setting)
+//! are probed with TCP connects to measure latency and packet loss. When
+//! `wan_health_speedtest_enabled` is set, a download speed test is run from
+//! inside the cluster every few hours. Results are kept in
+//! `wan_health_checks`, and `wan_degraded`/`wan_recovered` notifications are
+//! sent when the overall status changes.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use super::alerts;
+use super::notification::NotificationService;
+use super::outbound;
+use super::scheduler::PeriodicTask;
+use crate::config::CONFIG;
+use crate::endpoints::settings::{get_setting_bool, get_setting_value};
+use crate::error::{AppError, Result};
+use crate::models::audit_log::AuditAction;
+use crate::models::prelude::*;
+use crate::models::wan_health_check;
+
+/// Check kind for latency/packet-loss probes
+pub const KIND_LATENCY: &str = "latency";
+/// Check kind for download speed tests
+pub const KIND_SPEEDTEST: &str = "speedtest";
+
+/// Average latency at which the connection is considered degraded
+pub const LATENCY_DEGRADED_MS: f64 = 250.0;
+/// Average packet loss at which the connection is considered degraded
+pub const PACKET_LOSS_DEGRADED_PERCENT: f64 = 20.0;
+
+/// Port used for targets configured without one
+const DEFAULT_TARGET_PORT: u16 = 443;
+const CHECK_INTERVAL_SECS: u64 = 5 * 60;
+const PROBES_PER_TARGET: u32 = 5;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const SPEEDTEST_INTERVAL_HOURS: i64 = 6;
+const SPEEDTEST_TIMEOUT: Duration = Duration::from_secs(60);
+const RETENTION_DAYS: i64 = 30;
+
+/// Overall WAN status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WanStatus {
+    Healthy,
+    /// High latency or packet loss
+    Degraded,
+    /// No target reachable
+    Down,
+    /// No checks recorded yet
+    Unknown,
+}
+
+impl WanStatus {
+    fn is_problem(self) -> bool {
+        matches!(self, WanStatus::Degraded | WanStatus::Down)
+    }
+}
+
+/// Result of probing one target
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub target: String,
+    /// Average connect time of successful probes
+    pub latency_ms: Option<f64>,
+    pub packet_loss_percent: f64,
+    pub error: Option<String>,
+}
+
+impl From<&wan_health_check::Model> for ProbeResult {
+    fn from(row: &wan_health_check::Model) -> Self {
+        Self {
+            target: row.target.clone(),
+            latency_ms: row.latency_ms,
+            packet_loss_percent: row.packet_loss_percent.unwrap_or(100.0),
+            error: row.error.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Latest and windowed results for one target
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WanTargetHealth {
+    pub target: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    pub packet_loss_percent: f64,
+    /// Average latency over the requested window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_latency_ms: Option<f64>,
+    /// Average packet loss over the requested window
+    pub avg_packet_loss_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One check round, averaged over all targets
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WanHealthPoint {
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    pub packet_loss_percent: f64,
+    pub status: WanStatus,
+}
+
+/// A speed test run
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SpeedTestResult {
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_mbps: Option<f64>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response for GET /api/networking/wan-health
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WanHealthReport {
+    pub status: WanStatus,
+    /// Why the connection is degraded or down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub targets: Vec<WanTargetHealth>,
+    /// Oldest first
+    pub history: Vec<WanHealthPoint>,
+    /// Oldest first
+    pub speed_tests: Vec<SpeedTestResult>,
+}
+
+// ============================================================================
+// Probing
+// ============================================================================
+
+/// Parse the comma-separated target list, adding the default port if missing
+pub fn parse_targets(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            if t.rsplit_once(':')
+                .is_some_and(|(_, port)| port.parse::<u16>().is_ok())
+            {
+                t.to_string()
+            } else {
+                format!("{}:{}", t, DEFAULT_TARGET_PORT)
+            }
+        })
+        .collect()
+}
+
+/// Measure latency and packet loss to a target with repeated TCP connects
+pub async fn probe_target(target: &str) -> ProbeResult {
+    let mut latencies = Vec::new();
+    let mut error = None;
+
+    for _ in 0..PROBES_PER_TARGET {
+        let start = Instant::now();
+        match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(target)).await {
+            Ok(Ok(_)) => latencies.push(start.elapsed().as_secs_f64() * 1000.0),
+            Ok(Err(e)) => error = Some(e.to_string()),
+            Err(_) => error = Some("Connection timed out".to_string()),
+        }
+    }
+
+    let lost = PROBES_PER_TARGET as usize - latencies.len();
+    ProbeResult {
+        target: target.to_string(),
+        latency_ms: average(&latencies).map(round2),
+        packet_loss_percent: round2(lost as f64 / PROBES_PER_TARGET as f64 * 100.0),
+        error: if latencies.is_empty() { error } else { None },
+    }
+}
+
+/// Download a file and return the throughput in Mbit/s
+pub async fn run_speed_test(url: &str) -> std::result::Result<f64, String> {
+    let client = outbound::client_builder()
+        .timeout(SPEEDTEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    let mut resp = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("Speed test server returned HTTP {}", resp.status()));
+    }
+
+    let mut bytes: u64 = 0;
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        bytes += chunk.len() as u64;
+    }
+
+    let secs = start.elapsed().as_secs_f64();
+    if bytes == 0 || secs <= 0.0 {
+        return Err("Speed test downloaded no data".to_string());
+    }
+    Ok(round2(bytes as f64 * 8.0 / secs / 1_000_000.0))
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Average latency of reachable targets and average packet loss of all targets
+fn summarize(results: &[ProbeResult]) -> (Option<f64>, f64) {
+    let latencies: Vec<f64> = results.iter().filter_map(|r| r.latency_ms).collect();
+    let losses: Vec<f64> = results.iter().map(|r| r.packet_loss_percent).collect();
+    (
+        average(&latencies).map(round2),
+        average(&losses).map(round2).unwrap_or(0.0),
+    )
+}
+
+/// Derive the overall status (and a reason when unhealthy) from one check round
+pub fn evaluate(results: &[ProbeResult]) -> (WanStatus, Option<String>) {
+    if results.is_empty() {
+        return (WanStatus::Unknown, None);
+    }
+    if results.iter().all(|r| r.latency_ms.is_none()) {
+        return (
+            WanStatus::Down,
+            Some(format!("all {} target(s) unreachable", results.len())),
+        );
+    }
+
+    let (latency, loss) = summarize(results);
+    if loss >= PACKET_LOSS_DEGRADED_PERCENT {
+        return (
+            WanStatus::Degraded,
+            Some(format!(
+                "packet loss {:.1}% (threshold {:.0}%)",
+                loss, PACKET_LOSS_DEGRADED_PERCENT
+            )),
+        );
+    }
+    if let Some(latency) = latency.filter(|l| *l >= LATENCY_DEGRADED_MS) {
+        return (
+            WanStatus::Degraded,
+            Some(format!(
+                "latency {:.0} ms (threshold {:.0} ms)",
+                latency, LATENCY_DEGRADED_MS
+            )),
+        );
+    }
+    (WanStatus::Healthy, None)
+}
+
+/// Notification to send when the status changes from `previous` to `current`
+pub fn transition_event(previous: WanStatus, current: WanStatus) -> Option<AuditAction> {
+    if current.is_problem() && previous != current {
+        Some(AuditAction::WanDegraded)
+    } else if current == WanStatus::Healthy && previous.is_problem() {
+        Some(AuditAction::WanRecovered)
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// Store one round of probe results
+pub async fn record_probes(
+    db: &DatabaseConnection,
+    results: &[ProbeResult],
+    at: DateTime<Utc>,
+) -> Result<()> {
+    for result in results {
+        wan_health_check::ActiveModel {
+            kind: Set(KIND_LATENCY.to_string()),
+            target: Set(result.target.clone()),
+            latency_ms: Set(result.latency_ms),
+            packet_loss_percent: Set(Some(result.packet_loss_percent)),
+            download_mbps: Set(None),
+            success: Set(result.latency_ms.is_some()),
+            error: Set(result.error.clone()),
+            checked_at: Set(at),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Store a speed test result
+pub async fn record_speed_test(
+    db: &DatabaseConnection,
+    url: &str,
+    result: std::result::Result<f64, String>,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    let (download_mbps, error) = match result {
+        Ok(mbps) => (Some(mbps), None),
+        Err(e) => (None, Some(e)),
+    };
+    wan_health_check::ActiveModel {
+        kind: Set(KIND_SPEEDTEST.to_string()),
+        target: Set(url.to_string()),
+        latency_ms: Set(None),
+        packet_loss_percent: Set(None),
+        download_mbps: Set(download_mbps),
+        success: Set(error.is_none()),
+        error: Set(error),
+        checked_at: Set(at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Probe rows of the most recent check round
+async fn latest_round(db: &DatabaseConnection) -> Result<Vec<wan_health_check::Model>> {
+    let Some(latest) = WanHealthCheck::find()
+        .filter(wan_health_check::Column::Kind.eq(KIND_LATENCY))
+        .order_by_desc(wan_health_check::Column::CheckedAt)
+        .one(db)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(WanHealthCheck::find()
+        .filter(wan_health_check::Column::Kind.eq(KIND_LATENCY))
+        .filter(wan_health_check::Column::CheckedAt.eq(latest.checked_at))
+        .order_by_asc(wan_health_check::Column::Target)
+        .all(db)
+        .await?)
+}
+
+/// Status of the most recent check round
+pub async fn latest_status(db: &DatabaseConnection) -> Result<(WanStatus, Option<String>)> {
+    let rows = latest_round(db).await?;
+    let results: Vec<ProbeResult> = rows.iter().map(ProbeResult::from).collect();
+    Ok(evaluate(&results))
+}
+
+/// Build the WAN health report for the last `hours` hours
+pub async fn wan_health_report(
+    db: &DatabaseConnection,
+    hours: i64,
+    now: DateTime<Utc>,
+) -> Result<WanHealthReport> {
+    if hours <= 0 {
+        return Err(AppError::BadRequest(
+            "hours must be greater than zero".to_string(),
+        ));
+    }
+    let since = now - chrono::Duration::hours(hours);
+
+    let latest = latest_round(db).await?;
+    let latest_results: Vec<ProbeResult> = latest.iter().map(ProbeResult::from).collect();
+    let (status, reason) = evaluate(&latest_results);
+
+    let rows = WanHealthCheck::find()
+        .filter(wan_health_check::Column::CheckedAt.gte(since))
+        .order_by_asc(wan_health_check::Column::CheckedAt)
+        .all(db)
+        .await?;
+
+    let mut rounds: BTreeMap<DateTime<Utc>, Vec<ProbeResult>> = BTreeMap::new();
+    let mut per_target: BTreeMap<String, Vec<ProbeResult>> = BTreeMap::new();
+    let mut speed_tests = Vec::new();
+    for row in &rows {
+        if row.kind == KIND_SPEEDTEST {
+            speed_tests.push(SpeedTestResult {
+                checked_at: row.checked_at,
+                download_mbps: row.download_mbps,
+                success: row.success,
+                error: row.error.clone(),
+            });
+        } else {
+            let result = ProbeResult::from(row);
+            per_target
+                .entry(row.target.clone())
+                .or_default()
+                .push(result.clone());
+            rounds.entry(row.checked_at).or_default().push(result);
+        }
+    }
+
+    let history = rounds
+        .into_iter()
+        .map(|(checked_at, results)| {
+            let (latency_ms, packet_loss_percent) = summarize(&results);
+            WanHealthPoint {
+                checked_at,
+                latency_ms,
+                packet_loss_percent,
+                status: evaluate(&results).0,
+            }
+        })
+        .collect();
+
+    let targets = latest_results
+        .into_iter()
+        .map(|latest| {
+            let window = per_target
+                .get(&latest.target)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let (avg_latency_ms, avg_packet_loss_percent) = summarize(window);
+            WanTargetHealth {
+                reachable: latest.latency_ms.is_some(),
+                latency_ms: latest.latency_ms,
+                packet_loss_percent: latest.packet_loss_percent,
+                avg_latency_ms,
+                avg_packet_loss_percent,
+                error: latest.error,
+                target: latest.target,
+            }
+        })
+        .collect();
+
+    Ok(WanHealthReport {
+        status,
+        reason,
+        last_checked_at: latest.first().map(|r| r.checked_at),
+        targets,
+        history,
+        speed_tests,
+    })
+}
+
+// ============================================================================
+// Periodic Task
+// ============================================================================
+
+/// Probes WAN targets, runs speed tests and notifies on status changes
+pub struct WanHealthTask {
+    pub notification: NotificationService,
+}
+
+impl WanHealthTask {
+    async fn speed_test_due(&self, db: &DatabaseConnection, now: DateTime<Utc>) -> Result<bool> {
+        let last = WanHealthCheck::find()
+            .filter(wan_health_check::Column::Kind.eq(KIND_SPEEDTEST))
+            .order_by_desc(wan_health_check::Column::CheckedAt)
+            .one(db)
+            .await?;
+        Ok(last.is_none_or(|l| {
+            now - l.checked_at >= chrono::Duration::hours(SPEEDTEST_INTERVAL_HOURS)
+        }))
+    }
+}
+
+#[async_trait]
+impl PeriodicTask for WanHealthTask {
+    fn name(&self) -> &'static str {
+        "wan_health"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(CHECK_INTERVAL_SECS)
+    }
+
+    async fn run(&self, db: &DatabaseConnection) -> anyhow::Result<()> {
+        // There is no internet to probe in offline mode
+        if CONFIG.outbound.offline {
+            return Ok(());
+        }
+        let targets = parse_targets(
+            &get_setting_value(db, "wan_health_targets")
+                .await?
+                .unwrap_or_default(),
+        );
+        if targets.is_empty() {
+            return Ok(());
+        }
+
+        let (previous, _) = latest_status(db).await?;
+        let results = join_all(targets.iter().map(|t| probe_target(t))).await;
+        let now = Utc::now();
+        record_probes(db, &results, now).await?;
+
+        let (status, reason) = evaluate(&results);
+        if let Some(action) = transition_event(previous, status) {
+            let detail = match (&action, reason) {
+                (AuditAction::WanDegraded, Some(reason)) => reason,
+                _ => {
+                    let (latency, loss) = summarize(&results);
+                    format!(
+                        "latency {:.0} ms, packet loss {:.1}%",
+                        latency.unwrap_or(0.0),
+                        loss
+                    )
+                }
+            };
+            tracing::warn!(status = ?status, "WAN status changed: {}", detail);
+            if !alerts::is_silenced(
+                db,
+                &alerts::fingerprint(alerts::SOURCE_WAN, "internet"),
+                now,
+            )
+            .await?
+            {
+                self.notification
+                    .notify_event(&action, None, None, Some(&detail))
+                    .await?;
+            }
+        }
+
+        if get_setting_bool(db, "wan_health_speedtest_enabled").await?
+            && self.speed_test_due(db, now).await?
+        {
+            let url = get_setting_value(db, "wan_health_speedtest_url")
+                .await?
+                .unwrap_or_default();
+            let result = run_speed_test(&url).await;
+            if let Err(e) = &result {
+                tracing::warn!(error = %e, "WAN speed test failed");
+            }
+            record_speed_test(db, &url, result, Utc::now()).await?;
+        }
+
+        WanHealthCheck::delete_many()
+            .filter(
+                wan_health_check::Column::CheckedAt
+                    .lt(now - chrono::Duration::days(RETENTION_DAYS)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(latency_ms: Option<f64>, loss: f64) -> ProbeResult {
+        ProbeResult {
+            target: "1.1.1.1:443".to_string(),
+            latency_ms,
+            packet_loss_percent: loss,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        assert_eq!(
+            parse_targets(" 1.1.1.1:53, example.com ,,[::1]:443"),
+            vec!["1.1.1.1:53", "example.com:443", "[::1]:443"]
+        );
+        assert!(parse_targets("").is_empty());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate(&[]).0, WanStatus::Unknown);
+        assert_eq!(
+            evaluate(&[probe(Some(20.0), 0.0), probe(Some(30.0), 0.0)]).0,
+            WanStatus::Healthy
+        );
+
+        let (status, reason) = evaluate(&[probe(Some(20.0), 40.0), probe(Some(30.0), 20.0)]);
+        assert_eq!(status, WanStatus::Degraded);
+        assert!(reason.unwrap().contains("packet loss 30.0%"));
+
+        let (status, reason) = evaluate(&[probe(Some(400.0), 0.0)]);
+        assert_eq!(status, WanStatus::Degraded);
+        assert!(reason.unwrap().contains("latency 400 ms"));
+
+        let (status, reason) = evaluate(&[probe(None, 100.0), probe(None, 100.0)]);
+        assert_eq!(status, WanStatus::Down);
+        assert_eq!(reason.unwrap(), "all 2 target(s) unreachable");
+    }
+
+    #[test]
+    fn test_transition_event() {
+        use WanStatus::*;
+        assert!(matches!(
+            transition_event(Healthy, Degraded),
+            Some(AuditAction::WanDegraded)
+        ));
+        assert!(matches!(
+            transition_event(Unknown, Down),
+            Some(AuditAction::WanDegraded)
+        ));
+        assert!(matches!(
+            transition_event(Degraded, Down),
+            Some(AuditAction::WanDegraded)
+        ));
+        assert!(matches!(
+            transition_event(Down, Healthy),
+            Some(AuditAction::WanRecovered)
+        ));
+        assert!(transition_event(Degraded, Degraded).is_none());
+        assert!(transition_event(Unknown, Healthy).is_none());
+        assert!(transition_event(Healthy, Healthy).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_probe_unreachable_target() {
+        // Port 1 on localhost refuses connections immediately
+        let result = probe_target("127.0.0.1:1").await;
+        assert!(result.latency_ms.is_none());
+        assert_eq!(result.packet_loss_percent, 100.0);
+        assert!(result.error.is_some());
+    }
+}
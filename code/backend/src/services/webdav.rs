@@ -0,0 +1,304 @@
+//! WebDAV access to the storage roots
+//!
+//! [`RootFs`] wraps dav-server's local filesystem with the storage browser's
+//! rules: the `.trash` folder is hidden, the root and its protected folders
+//! cannot be removed or renamed, and symlinks cannot lead out of the root.
+//! Deletes never reach it; the WebDAV endpoint moves them to the trash.
+//!
+//! Clients sign in with HTTP Basic auth on every request, so a verified
+//! password is remembered for [`CREDENTIAL_TTL`] instead of running bcrypt
+//! each time. Changing the password drops the cached entry.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::Engine;
+use dav_server::davpath::DavPath;
+use dav_server::fs::{
+    DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsStream, OpenOptions,
+    ReadDirMeta,
+};
+use dav_server::localfs::LocalFs;
+use futures_util::{future, StreamExt};
+use once_cell::sync::Lazy;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sha2::{Digest, Sha256};
+
+use super::security::verify_password;
+use super::trash::{self, TRASH_DIR};
+use crate::error::Result;
+use crate::models::prelude::*;
+use crate::models::user;
+use crate::state::DbConn;
+
+/// Setting that turns the WebDAV endpoint on
+pub const WEBDAV_ENABLED: &str = "webdav_enabled";
+
+/// How long a verified password is remembered
+pub const CREDENTIAL_TTL: Duration = Duration::from_secs(300);
+
+/// Verified credentials: digest of user id and password -> password hash it
+/// was checked against, and when
+static VERIFIED: Lazy<Mutex<HashMap<String, (String, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Username and password of a `Basic` authorization header
+pub fn parse_basic_auth(header: &str) -> Option<(String, String)> {
+    let encoded = header.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    Some((username.to_string(), password.to_string()))
+}
+
+/// The active, approved user with this username (or email) and password
+pub async fn authenticate(
+    db: &DbConn,
+    username: &str,
+    password: &str,
+) -> Result<Option<user::Model>> {
+    let found = User::find()
+        .filter(
+            user::Column::Username
+                .eq(username)
+                .or(user::Column::Email.eq(username)),
+        )
+        .filter(user::Column::IsServiceAccount.eq(false))
+        .one(db)
+        .await?;
+    let Some(found) = found.filter(|u| u.is_active && u.is_approved) else {
+        return Ok(None);
+    };
+
+    let key = hex::encode(Sha256::digest(format!("{}:{}", found.id, password)));
+    {
+        let verified = VERIFIED.lock().unwrap();
+        if let Some((hash, at)) = verified.get(&key) {
+            if *hash == found.hashed_password && at.elapsed() < CREDENTIAL_TTL {
+                return Ok(Some(found));
+            }
+        }
+    }
+
+    if !verify_password(password, &found.hashed_password) {
+        return Ok(None);
+    }
+    let mut verified = VERIFIED.lock().unwrap();
+    verified.retain(|_, (_, at)| at.elapsed() < CREDENTIAL_TTL);
+    verified.insert(key, (found.hashed_password.clone(), Instant::now()));
+    Ok(Some(found))
+}
+
+/// A storage root as a dav-server filesystem
+#[derive(Clone)]
+pub struct RootFs {
+    inner: Box<LocalFs>,
+    /// Canonical directory of the root
+    base: PathBuf,
+    protected_folders: Vec<String>,
+}
+
+impl RootFs {
+    pub fn new(base: PathBuf, protected_folders: Vec<String>) -> Box<Self> {
+        Box::new(Self {
+            inner: LocalFs::new(&base, false, false, false),
+            base,
+            protected_folders,
+        })
+    }
+
+    /// Whether a path is outside the trash and, following symlinks as far
+    /// as it exists, inside the root
+    fn reachable(&self, path: &DavPath) -> bool {
+        let relative = path.as_rel_ospath();
+        if trash::is_trash_path(relative) {
+            return false;
+        }
+        let mut existing = self.base.join(relative);
+        loop {
+            match existing.canonicalize() {
+                Ok(resolved) => return resolved.starts_with(&self.base),
+                Err(_) if existing.pop() => continue,
+                Err(_) => return false,
+            }
+        }
+    }
+
+    /// Whether a path is the root itself or one of its protected folders
+    fn is_fixed(&self, path: &DavPath) -> bool {
+        let mut parts = path.as_rel_ospath().components();
+        match (parts.next(), parts.next()) {
+            (None, _) => true,
+            (Some(Component::Normal(name)), None) => self
+                .protected_folders
+                .iter()
+                .any(|folder| name.to_str() == Some(folder.as_str())),
+            _ => false,
+        }
+    }
+
+    /// Error for a path that cannot be used, if any
+    fn check(&self, path: &DavPath, removing: bool) -> Option<FsError> {
+        if !self.reachable(path) {
+            Some(FsError::NotFound)
+        } else if removing && self.is_fixed(path) {
+            Some(FsError::Forbidden)
+        } else {
+            None
+        }
+    }
+}
+
+fn refuse<'a, T: Send + 'a>(error: FsError) -> FsFuture<'a, T> {
+    Box::pin(future::ready(Err(error)))
+}
+
+impl DavFileSystem for RootFs {
+    fn open<'a>(
+        &'a self,
+        path: &'a DavPath,
+        options: OpenOptions,
+    ) -> FsFuture<'a, Box<dyn DavFile>> {
+        match self.check(path, false) {
+            Some(error) => refuse(error),
+            None => self.inner.open(path, options),
+        }
+    }
+
+    fn read_dir<'a>(
+        &'a self,
+        path: &'a DavPath,
+        meta: ReadDirMeta,
+    ) -> FsFuture<'a, FsStream<Box<dyn DavDirEntry>>> {
+        if let Some(error) = self.check(path, false) {
+            return refuse(error);
+        }
+        let at_root = path.as_rel_ospath() == Path::new("");
+        Box::pin(async move {
+            let entries = self.inner.read_dir(path, meta).await?;
+            if !at_root {
+                return Ok(entries);
+            }
+            let entries = entries.filter(|entry| {
+                future::ready(!matches!(entry, Ok(e) if e.name() == TRASH_DIR.as_bytes()))
+            });
+            Ok(Box::pin(entries) as FsStream<Box<dyn DavDirEntry>>)
+        })
+    }
+
+    fn metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, Box<dyn DavMetaData>> {
+        match self.check(path, false) {
+            Some(error) => refuse(error),
+            None => self.inner.metadata(path),
+        }
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, Box<dyn DavMetaData>> {
+        match self.check(path, false) {
+            Some(error) => refuse(error),
+            None => self.inner.symlink_metadata(path),
+        }
+    }
+
+    fn create_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        match self.check(path, false) {
+            Some(_) => refuse(FsError::Forbidden),
+            None => self.inner.create_dir(path),
+        }
+    }
+
+    fn remove_dir<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        match self.check(path, true) {
+            Some(error) => refuse(error),
+            None => self.inner.remove_dir(path),
+        }
+    }
+
+    fn remove_file<'a>(&'a self, path: &'a DavPath) -> FsFuture<'a, ()> {
+        match self.check(path, true) {
+            Some(error) => refuse(error),
+            None => self.inner.remove_file(path),
+        }
+    }
+
+    fn rename<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<'a, ()> {
+        match self.check(from, true).or(self.check(to, true)) {
+            Some(error) => refuse(error),
+            None => self.inner.rename(from, to),
+        }
+    }
+
+    fn copy<'a>(&'a self, from: &'a DavPath, to: &'a DavPath) -> FsFuture<'a, ()> {
+        match self.check(from, false).or(self.check(to, true)) {
+            Some(error) => refuse(error),
+            None => self.inner.copy(from, to),
+        }
+    }
+
+    fn set_accessed<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<'a, ()> {
+        match self.check(path, false) {
+            Some(error) => refuse(error),
+            None => self.inner.set_accessed(path, tm),
+        }
+    }
+
+    fn set_modified<'a>(&'a self, path: &'a DavPath, tm: SystemTime) -> FsFuture<'a, ()> {
+        match self.check(path, false) {
+            Some(error) => refuse(error),
+            None => self.inner.set_modified(path, tm),
+        }
+    }
+
+    fn get_quota(&self) -> FsFuture<'_, (u64, Option<u64>)> {
+        self.inner.get_quota()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_auth() {
+        let header = format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("alice:se:cret")
+        );
+        assert_eq!(
+            parse_basic_auth(&header),
+            Some(("alice".to_string(), "se:cret".to_string()))
+        );
+        assert_eq!(parse_basic_auth("Bearer token"), None);
+        assert_eq!(parse_basic_auth("Basic !!!"), None);
+    }
+
+    #[test]
+    fn test_root_fs_rules() {
+        let base = std::env::temp_dir().join(format!("kubarr_webdav_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base.join("movies")).unwrap();
+        std::fs::create_dir_all(base.join(".trash")).unwrap();
+        let base = base.canonicalize().unwrap();
+        let fs = RootFs::new(base.clone(), vec!["movies".to_string()]);
+        let path = |p: &str| DavPath::new(p).unwrap();
+
+        assert_eq!(fs.check(&path("/movies/new"), false), None);
+        assert_eq!(fs.check(&path("/movies/new"), true), None);
+        assert_eq!(fs.check(&path("/movies"), true), Some(FsError::Forbidden));
+        assert_eq!(fs.check(&path("/"), true), Some(FsError::Forbidden));
+        assert_eq!(fs.check(&path("/.trash/x"), false), Some(FsError::NotFound));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", base.join("escape")).unwrap();
+            assert_eq!(
+                fs.check(&path("/escape/passwd"), false),
+                Some(FsError::NotFound)
+            );
+        }
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
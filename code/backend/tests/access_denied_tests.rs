@@ -0,0 +1,115 @@
+//! Integration tests for the audit of permission denials
+//!
+//! Covers:
+//! - `access_denied` audit entries for `403` responses, deduplicated per
+//!   user and route
+//! - the `resource_id` filter of `GET /api/audit`
+//! - the `access_denied` notification and `access_denied_alert_threshold`
+
+use axum::http::StatusCode;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::user_notification;
+
+#[tokio::test]
+async fn test_access_denials_are_audited() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/access_denied_alert_threshold",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "3"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/access_denied_alert_threshold",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "-1"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    for _ in 0..2 {
+        let (status, _) = env
+            .request("GET", "/api/users", Some(env.cookie("viewer")), None)
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    // Repeats of the same denial are written once
+    let (status, audit) = env
+        .request(
+            "GET",
+            "/api/audit?action=access_denied",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(audit["total"], 1, "{}", audit);
+    let entry = &audit["logs"][0];
+    assert_eq!(entry["username"], "viewer");
+    assert_eq!(entry["resource_id"], "users.view");
+    assert_eq!(entry["success"], false);
+    let details: serde_json::Value =
+        serde_json::from_str(entry["details"].as_str().unwrap()).unwrap();
+    assert_eq!(
+        details,
+        json!({"method": "GET", "path": "/api/users", "permission": "users.view"})
+    );
+
+    // Allowed requests are not recorded
+    let (status, _) = env
+        .request("GET", "/api/users", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The third denial within the window notifies admins
+    let (status, _) = env
+        .request("GET", "/api/roles", Some(env.cookie("viewer")), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let alert = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(env.user("admin").user.id))
+        .filter(user_notification::Column::EventType.eq("access_denied"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("access_denied notification");
+    assert!(
+        alert.message.contains("3 denied requests"),
+        "{}",
+        alert.message
+    );
+    assert!(
+        alert
+            .message
+            .contains("GET /api/roles (requires roles.view)"),
+        "{}",
+        alert.message
+    );
+
+    let (_, by_permission) = env
+        .request(
+            "GET",
+            "/api/audit?action=access_denied&resource_id=roles.view",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(by_permission["total"], 1);
+    assert_eq!(by_permission["logs"][0]["resource_id"], "roles.view");
+}
@@ -0,0 +1,199 @@
+//! Integration tests for access reviews
+//!
+//! Covers:
+//! - starting, listing and deciding reviews under `/api/roles/reviews`
+//! - revoking roles, overdue handling and reactivating suspended users
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::{access_review, user_notification, user_role};
+use kubarr::services::access_reviews::{process_overdue, OverdueAction};
+
+/// Find a user's entry in a review
+fn item<'a>(review: &'a Value, username: &str) -> &'a Value {
+    review["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["username"] == username)
+        .unwrap_or_else(|| panic!("No item for '{}' in {}", username, review))
+}
+
+/// Decide a user's access as the admin
+async fn decide(env: &TestEnv, uri: &str, user_id: i64, decision: &str) -> (StatusCode, Value) {
+    env.request(
+        "POST",
+        uri,
+        Some(env.cookie("admin")),
+        Some(json!({"user_id": user_id, "decision": decision, "comment": "left the family"})),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_access_review_lifecycle() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .with_notifications()
+        .build()
+        .await;
+    let alice_id = env.user("alice").user.id;
+    let bob_id = env.user("bob").user.id;
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/roles/reviews",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, review) = env
+        .request(
+            "POST",
+            "/api/roles/reviews",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", review);
+    assert_eq!(review["status"], "open");
+    assert_eq!(review["pending"], review["total"]);
+    let alice = item(&review, "alice");
+    assert_eq!(alice["roles"], json!(["viewer"]));
+    assert!(alice["apps"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("jellyfin")));
+    assert_eq!(item(&review, "admin")["apps"], json!(["*"]));
+    let review_id = review["id"].as_i64().unwrap();
+    let uri = format!("/api/roles/reviews/{}", review_id);
+
+    // Reviewers are told about it
+    let announced = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(env.user("admin").user.id))
+        .filter(user_notification::Column::EventType.eq("access_review_started"))
+        .one(&env.db)
+        .await
+        .unwrap();
+    assert!(announced.is_some());
+
+    // Only one review can be open
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/roles/reviews",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, list) = env
+        .request("GET", "/api/roles/reviews", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    // Nobody reviews their own access
+    let (status, _) = decide(&env, &uri, env.user("admin").user.id, "confirm").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, decided) = decide(&env, &uri, alice_id, "revoke").await;
+    assert_eq!(status, StatusCode::OK, "{}", decided);
+    let alice = item(&decided, "alice");
+    assert_eq!(alice["decision"], "revoked");
+    assert_eq!(alice["decided_by"], "admin");
+    assert_eq!(alice["comment"], "left the family");
+    let roles = UserRole::find()
+        .filter(user_role::Column::UserId.eq(alice_id))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert!(roles.is_empty());
+    let (status, _) = decide(&env, &uri, alice_id, "confirm").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = decide(&env, &uri, env.user("viewer").user.id, "confirm").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, audit) = env
+        .request(
+            "GET",
+            "/api/audit?action=access_revoked",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(audit["total"], 1);
+    assert_eq!(audit["logs"][0]["resource_id"], alice_id.to_string());
+
+    // Past the deadline, undecided users are suspended except reviewers
+    let open = AccessReview::find_by_id(review_id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: access_review::ActiveModel = open.into();
+    active.due_at = Set(Utc::now() - Duration::hours(1));
+    active.update(&env.db).await.unwrap();
+    let suspended = process_overdue(&env.db, OverdueAction::Suspend, Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(suspended.len(), 1);
+    assert_eq!(suspended[0].1.id, bob_id);
+
+    let (_, closed) = env
+        .request("GET", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(closed["status"], "closed");
+    assert_eq!(closed["overdue"], 2);
+    assert_eq!(item(&closed, "bob")["suspended"], true);
+    assert_eq!(item(&closed, "admin")["suspended"], false);
+    let bob = User::find_by_id(bob_id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!bob.is_active);
+
+    // Confirming an overdue user reactivates them
+    let (status, _) = decide(&env, &uri, bob_id, "confirm").await;
+    assert_eq!(status, StatusCode::OK);
+    let bob = User::find_by_id(bob_id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(bob.is_active);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/access_review_overdue_action",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "delete"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/roles/reviews/9999",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
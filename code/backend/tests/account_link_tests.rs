@@ -0,0 +1,245 @@
+//! Integration tests for account links
+//!
+//! Covers:
+//! - `GET    /api/users/{user_id}/accounts`             — requires users.view
+//! - `PUT    /api/users/{user_id}/accounts/{app_name}`  — requires users.manage
+//! - `DELETE /api/users/{user_id}/accounts/{app_name}`  — requires users.manage
+//! - `POST   /api/users/accounts/discover` against a mock Jellyfin
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+
+/// Serve a Jellyfin with the users `Alice`, `bob` and `stranger`
+async fn spawn_jellyfin() -> String {
+    let router = axum::Router::new().route(
+        "/Users",
+        axum::routing::get(|headers: HeaderMap| async move {
+            if headers.get("x-emby-token").and_then(|k| k.to_str().ok()) != Some("jf-key") {
+                return (StatusCode::UNAUTHORIZED, Json(json!([])));
+            }
+            (
+                StatusCode::OK,
+                Json(json!([
+                    {"Id": "jf-alice", "Name": "Alice", "Policy": {}},
+                    {"Id": "jf-bob", "Name": "bob", "Policy": {}},
+                    {"Id": "jf-stranger", "Name": "stranger", "Policy": {}},
+                ])),
+            )
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_manage_account_links() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .build()
+        .await;
+    let alice = env.user("alice").user.id;
+    let bob = env.user("bob").user.id;
+    let uri = format!("/api/users/{}/accounts/jellyfin", alice);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("bob")),
+            Some(json!({"external_id": "jf-alice"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, link) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "jf-alice", "external_username": "Alice"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", link);
+    assert_eq!(link["external_id"], "jf-alice");
+    assert_eq!(link["source"], "manual");
+
+    // An app account belongs to one user
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/accounts/jellyfin", bob),
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "jf-alice"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Request manager IDs are numbers
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/accounts/jellyseerr", alice),
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "abc"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/accounts/jellyseerr", alice),
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "7"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let apps: Vec<&str> = links
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l["app_name"].as_str().unwrap())
+        .collect();
+    assert_eq!(apps, vec!["jellyfin", "jellyseerr"]);
+
+    let (status, user) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user["accounts"].as_array().unwrap().len(), 2);
+
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/users/99999/accounts/jellyfin",
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "jf-x"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_discover_account_links() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .build()
+        .await;
+    let alice = env.user("alice").user.id;
+    let bob = env.user("bob").user.id;
+    let base_url = spawn_jellyfin().await;
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/integrations/apps/jellyfin",
+            Some(env.cookie("admin")),
+            Some(json!({"base_url": base_url, "credentials": {"api_key": "jf-key"}})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Bob already has a link, which discovery keeps
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/accounts/jellyfin", bob),
+            Some(env.cookie("admin")),
+            Some(json!({"external_id": "jf-other"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/users/accounts/discover",
+            Some(env.cookie("alice")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, report) = env
+        .request(
+            "POST",
+            "/api/users/accounts/discover",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", report);
+    assert_eq!(report["errors"], json!([]));
+    let linked = report["linked"].as_array().unwrap();
+    assert_eq!(linked.len(), 1, "{}", report);
+    assert_eq!(linked[0]["user_id"], alice);
+    assert_eq!(linked[0]["external_id"], "jf-alice");
+
+    let (_, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", bob),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(links[0]["external_id"], "jf-other");
+    assert_eq!(links[0]["source"], "manual");
+    let (_, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(links[0]["source"], "auto");
+
+    // Running again links nothing new
+    let (_, report) = env
+        .request(
+            "POST",
+            "/api/users/accounts/discover",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(report["linked"], json!([]));
+}
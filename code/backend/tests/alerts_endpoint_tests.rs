@@ -0,0 +1,508 @@
+//! Integration tests for the unified alerts API
+//!
+//! Covers endpoints:
+//! - `GET    /api/alerts`              — requires monitoring.view
+//! - `GET    /api/alerts/{id}`         — requires monitoring.view
+//! - `POST   /api/alerts/{id}/ack`     — requires monitoring.manage
+//! - `POST   /api/alerts/{id}/silence` — requires monitoring.manage
+//! - `DELETE /api/alerts/{id}/silence` — requires monitoring.manage
+//! - `PUT    /api/alerts/{id}/assign`  — requires monitoring.manage
+//!
+//! Conditions are created directly in the checkers' tables and turned into
+//! alerts with `services::alerts::sync`.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+use kubarr::models::{alert, log_alert_rule, uptime_monitor};
+use kubarr::services::alerts;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    db: DatabaseConnection,
+    admin: String,
+    viewer: String,
+    viewer_id: i64,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only)
+async fn setup() -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(&db, "al_admin", "al_admin@example.com", "pass123", "admin").await;
+    let viewer = create_test_user_with_role(
+        &db,
+        "al_viewer",
+        "al_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "al_admin", "pass123").await.unwrap();
+    let viewer_token = do_login(app.clone(), "al_viewer", "pass123").await.unwrap();
+    TestContext {
+        app,
+        db,
+        admin,
+        viewer: viewer_token,
+        viewer_id: viewer.id,
+    }
+}
+
+async fn create_down_monitor(db: &DatabaseConnection) -> uptime_monitor::Model {
+    let now = Utc::now();
+    uptime_monitor::ActiveModel {
+        name: Set("Plex".to_string()),
+        kind: Set("http".to_string()),
+        target: Set("http://plex:32400".to_string()),
+        interval_seconds: Set(60),
+        timeout_seconds: Set(10),
+        expected_status: Set(None),
+        enabled: Set(true),
+        status: Set("down".to_string()),
+        last_checked_at: Set(Some(now)),
+        last_change_at: Set(Some(now)),
+        created_by: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+async fn set_monitor_status(
+    db: &DatabaseConnection,
+    monitor: &uptime_monitor::Model,
+    status: &str,
+) {
+    let mut active: uptime_monitor::ActiveModel = monitor.clone().into();
+    active.status = Set(status.to_string());
+    active.update(db).await.unwrap();
+}
+
+async fn create_log_rule(db: &DatabaseConnection, state: &str) -> log_alert_rule::Model {
+    let now = Utc::now();
+    log_alert_rule::ActiveModel {
+        name: Set("Sonarr errors".to_string()),
+        app_name: Set(Some("sonarr".to_string())),
+        pattern: Set("ERROR".to_string()),
+        threshold: Set(5),
+        window_minutes: Set(5),
+        interval_seconds: Set(60),
+        severity: Set("warning".to_string()),
+        enabled: Set(true),
+        state: Set(state.to_string()),
+        last_count: Set(Some(7)),
+        last_error: Set(None),
+        last_evaluated_at: Set(Some(now)),
+        firing_since: Set(Some(now)),
+        created_by: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+async fn set_rule_state(db: &DatabaseConnection, rule: &log_alert_rule::Model, state: &str) {
+    let mut active: log_alert_rule::ActiveModel = rule.clone().into();
+    active.state = Set(state.to_string());
+    active.update(db).await.unwrap();
+}
+
+async fn list(ctx: &TestContext, query: &str) -> Vec<serde_json::Value> {
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/alerts{}", query),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    json.as_array().unwrap().clone()
+}
+
+async fn post(
+    ctx: &TestContext,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    make_request(ctx.app.clone(), "POST", uri, Some(&ctx.admin), Some(body)).await
+}
+
+fn history_actions(json: &serde_json::Value) -> Vec<String> {
+    json["history"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap().to_string())
+        .collect()
+}
+
+// ============================================================================
+// Authentication and permissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_alerts_require_auth() {
+    let ctx = setup().await;
+    let (status, _) = make_request(ctx.app.clone(), "GET", "/api/alerts", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_can_list_but_not_manage() {
+    let ctx = setup().await;
+    create_down_monitor(&ctx.db).await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+
+    let listed = list(&ctx, "").await;
+    assert_eq!(listed.len(), 1);
+    let uri = format!("/api/alerts/{}/ack", listed[0]["id"]);
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "POST",
+        &uri,
+        Some(&ctx.viewer),
+        Some(serde_json::json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Lifecycle
+// ============================================================================
+
+#[tokio::test]
+async fn test_alert_lifecycle() {
+    let ctx = setup().await;
+    let monitor = create_down_monitor(&ctx.db).await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+    // A second sync refreshes the same alert instead of firing a new one
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+
+    let listed = list(&ctx, "").await;
+    assert_eq!(listed.len(), 1);
+    let alert = &listed[0];
+    assert_eq!(alert["source"], "uptime");
+    assert_eq!(alert["fingerprint"], format!("uptime:{}", monitor.id));
+    assert_eq!(alert["state"], "firing");
+    assert_eq!(alert["severity"], "critical");
+    assert_eq!(alert["message"], "Plex (http://plex:32400) is down");
+    let id = alert["id"].as_i64().unwrap();
+
+    let (status, json) = post(
+        &ctx,
+        &format!("/api/alerts/{}/ack", id),
+        serde_json::json!({"comment": "Looking into it"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    assert_eq!(json["state"], "acked");
+    assert_eq!(json["acked_by"]["username"], "al_admin");
+
+    let (status, json) = post(
+        &ctx,
+        &format!("/api/alerts/{}/silence", id),
+        serde_json::json!({"duration_minutes": 60}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["state"], "silenced");
+    assert!(json["silenced_until"].is_string());
+    let fingerprint = json["fingerprint"].as_str().unwrap().to_string();
+    assert!(alerts::is_silenced(&ctx.db, &fingerprint, Utc::now())
+        .await
+        .unwrap());
+
+    // Ending the silence returns to the acknowledged state
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "DELETE",
+        &format!("/api/alerts/{}/silence", id),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["state"], "acked");
+    assert!(!alerts::is_silenced(&ctx.db, &fingerprint, Utc::now())
+        .await
+        .unwrap());
+
+    set_monitor_status(&ctx.db, &monitor, "up").await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+    assert!(list(&ctx, "").await.is_empty());
+
+    let resolved = list(&ctx, "?state=resolved").await;
+    assert_eq!(resolved.len(), 1);
+    assert!(resolved[0]["resolved_at"].is_string());
+
+    let (status, _) = post(
+        &ctx,
+        &format!("/api/alerts/{}/ack", id),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/alerts/{}", id),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(
+        history_actions(&json),
+        vec!["fired", "acked", "silenced", "unsilenced", "resolved"]
+    );
+    assert_eq!(json["history"][1]["detail"], "Looking into it");
+    assert_eq!(json["history"][1]["user"]["username"], "al_admin");
+    assert!(json["history"][0]["user"].is_null());
+}
+
+#[tokio::test]
+async fn test_silence_outlives_resolution_and_expires() {
+    let ctx = setup().await;
+    let rule = create_log_rule(&ctx.db, "firing").await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+
+    let listed = list(&ctx, "?source=log").await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["app_name"], "sonarr");
+    let first = listed[0]["id"].as_i64().unwrap();
+    let (status, _) = post(
+        &ctx,
+        &format!("/api/alerts/{}/silence", first),
+        serde_json::json!({"duration_minutes": 30, "comment": "Known issue"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Resolves, then fires again while the silence is still active
+    set_rule_state(&ctx.db, &rule, "ok").await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+    set_rule_state(&ctx.db, &rule, "firing").await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+
+    let listed = list(&ctx, "").await;
+    assert_eq!(listed.len(), 1);
+    let second = listed[0]["id"].as_i64().unwrap();
+    assert_ne!(first, second);
+    assert_eq!(listed[0]["state"], "silenced");
+    assert_eq!(listed[0]["silenced_by"]["username"], "al_admin");
+
+    // Once the silence ends the alert fires again
+    let model = alert::Entity::find_by_id(second)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: alert::ActiveModel = model.into();
+    active.silenced_until = Set(Some(Utc::now() - chrono::Duration::minutes(1)));
+    active.update(&ctx.db).await.unwrap();
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+
+    let listed = list(&ctx, "?state=firing").await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], second);
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/alerts/{}", second),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(history_actions(&json), vec!["fired", "silence_expired"]);
+}
+
+#[tokio::test]
+async fn test_assign_alert() {
+    let ctx = setup().await;
+    create_down_monitor(&ctx.db).await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+    let id = list(&ctx, "").await[0]["id"].as_i64().unwrap();
+    let uri = format!("/api/alerts/{}/assign", id);
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"user_id": 999999})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"user_id": ctx.viewer_id})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["assigned_to"]["username"], "al_viewer");
+    assert_eq!(json["history"][1]["detail"], "al_viewer");
+
+    let mine = list(&ctx, &format!("?assigned_to={}", ctx.viewer_id)).await;
+    assert_eq!(mine.len(), 1);
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"user_id": null})),
+    )
+    .await;
+    assert!(json["assigned_to"].is_null());
+    assert_eq!(
+        history_actions(&json),
+        vec!["fired", "assigned", "unassigned"]
+    );
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_alert_validation() {
+    let ctx = setup().await;
+    for query in ["?state=bogus", "?source=smtp"] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "GET",
+            &format!("/api/alerts{}", query),
+            Some(&ctx.viewer),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/alerts/999",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    create_down_monitor(&ctx.db).await;
+    alerts::sync(&ctx.db, Utc::now()).await.unwrap();
+    let id = list(&ctx, "").await[0]["id"].as_i64().unwrap();
+    for minutes in [0, alerts::MAX_SILENCE_MINUTES + 1] {
+        let (status, _) = post(
+            &ctx,
+            &format!("/api/alerts/{}/silence", id),
+            serde_json::json!({"duration_minutes": minutes}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "DELETE",
+        &format!("/api/alerts/{}/silence", id),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
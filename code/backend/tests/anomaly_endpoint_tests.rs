@@ -0,0 +1,342 @@
+//! Integration tests for the anomaly detection endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/monitoring/anomalies`                        — requires monitoring.view
+//! - `GET    /api/monitoring/anomalies/sensitivity`            — requires monitoring.view
+//! - `PUT    /api/monitoring/anomalies/sensitivity/{app_name}` — requires monitoring.manage
+//! - `DELETE /api/monitoring/anomalies/sensitivity/{app_name}` — requires monitoring.manage
+//!
+//! Detection is driven through `services::anomaly::analyze` using restart
+//! history; CPU and memory series are empty without VictoriaMetrics.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+use kubarr::services::anomaly;
+use kubarr::state::AppState;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    state: AppState,
+    db: DatabaseConnection,
+    admin: String,
+    viewer: String,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only)
+async fn setup() -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(&db, "an_admin", "an_admin@example.com", "pass123", "admin").await;
+    create_test_user_with_role(
+        &db,
+        "an_viewer",
+        "an_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let app = create_router(state.clone());
+
+    let admin = do_login(app.clone(), "an_admin", "pass123").await.unwrap();
+    let viewer = do_login(app.clone(), "an_viewer", "pass123").await.unwrap();
+    TestContext {
+        app,
+        state,
+        db,
+        admin,
+        viewer,
+    }
+}
+
+/// Record a burst of container restarts for an app
+async fn insert_restarts(db: &DatabaseConnection, app: &str, minutes_ago: i64, restarts: i32) {
+    use kubarr::models::pod_restart_event;
+
+    pod_restart_event::ActiveModel {
+        app_name: Set(app.to_string()),
+        pod_name: Set(format!("{}-0", app)),
+        container_name: Set(app.to_string()),
+        restart_count: Set(restarts),
+        restarts: Set(restarts),
+        reason: Set(Some("Error".to_string())),
+        exit_code: Set(Some(1)),
+        occurred_at: Set(Utc::now() - Duration::minutes(minutes_ago)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+}
+
+// ============================================================================
+// Authentication and permissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_anomalies_require_auth() {
+    let ctx = setup().await;
+    let (status, _) = make_request(ctx.app, "GET", "/api/monitoring/anomalies", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_can_read_but_not_set_sensitivity() {
+    let ctx = setup().await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/anomalies/sensitivity",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["default_sensitivity"], "medium");
+    assert!(json["apps"].as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(
+        ctx.app,
+        "PUT",
+        "/api/monitoring/anomalies/sensitivity/sonarr",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"sensitivity": "high"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Sensitivity settings
+// ============================================================================
+
+#[tokio::test]
+async fn test_sensitivity_override_lifecycle() {
+    let ctx = setup().await;
+    let uri = "/api/monitoring/anomalies/sensitivity/sonarr";
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"sensitivity": "extreme"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["detail"].as_str().unwrap().contains("medium"));
+
+    for value in ["low", "HIGH"] {
+        let (status, json) = make_request(
+            ctx.app.clone(),
+            "PUT",
+            uri,
+            Some(&ctx.admin),
+            Some(serde_json::json!({"sensitivity": value})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+    }
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/anomalies/sensitivity",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(
+        json["apps"],
+        serde_json::json!([{"app_name": "sonarr", "sensitivity": "high"}])
+    );
+
+    let (status, json) = make_request(ctx.app.clone(), "DELETE", uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["success"], true);
+
+    let (status, _) = make_request(ctx.app, "DELETE", uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+#[tokio::test]
+async fn test_restart_burst_is_flagged_once_and_listed() {
+    let ctx = setup().await;
+    insert_restarts(&ctx.db, "sonarr", 10, 5).await;
+
+    let anomalies = anomaly::analyze(&ctx.db, &ctx.state.notification, Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0].app_name, "sonarr");
+    assert_eq!(anomalies[0].metric, "restarts");
+    assert_eq!(anomalies[0].direction, "above");
+
+    // Within the cooldown the same metric is not flagged again
+    insert_restarts(&ctx.db, "sonarr", 5, 4).await;
+    let again = anomaly::analyze(&ctx.db, &ctx.state.notification, Utc::now())
+        .await
+        .unwrap();
+    assert!(again.is_empty());
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/anomalies?hours=1&app=sonarr",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["window_hours"], 1);
+    let listed = json["anomalies"].as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["value"], 5.0);
+    assert!(listed[0]["link"]
+        .as_str()
+        .unwrap()
+        .starts_with("/resources?app=sonarr&from="));
+
+    let (_, json) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/anomalies?app=radarr",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert!(json["anomalies"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sensitivity_off_suppresses_detection() {
+    let ctx = setup().await;
+    insert_restarts(&ctx.db, "sonarr", 10, 5).await;
+    anomaly::set_sensitivity(&ctx.db, "sonarr", "off")
+        .await
+        .unwrap();
+
+    let anomalies = anomaly::analyze(&ctx.db, &ctx.state.notification, Utc::now())
+        .await
+        .unwrap();
+    assert!(anomalies.is_empty());
+}
+
+#[tokio::test]
+async fn test_anomaly_event_defaults_to_warning() {
+    let ctx = setup().await;
+    let (status, json) = make_request(
+        ctx.app,
+        "GET",
+        "/api/notifications/events",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let event = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["event_type"] == "metric_anomaly")
+        .expect("metric_anomaly event listed");
+    assert_eq!(event["severity"], "warning");
+}
+
+#[tokio::test]
+async fn test_invalid_window_rejected() {
+    let ctx = setup().await;
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/anomalies?hours=0",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,199 @@
+//! Integration tests for app install requests
+//!
+//! Covers:
+//! - `POST /api/apps/requests` — queueing a request with `apps.request`
+//! - `GET  /api/apps/requests` — own requests vs. the full queue
+//! - `POST /api/apps/requests/{id}/approve` — installing as the requester
+//! - `POST /api/apps/requests/{id}/deny`
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::models::prelude::*;
+use kubarr::models::{audit_log, role, role_permission};
+use kubarr::services::helm::HelmCall;
+
+/// Admin and a viewer allowed to request installs
+async fn setup() -> TestEnv {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_catalog_app("jellyfin")
+        .with_catalog_app("radarr")
+        .build()
+        .await;
+    let viewer = Role::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    role_permission::ActiveModel {
+        role_id: Set(viewer.id),
+        permission: Set("apps.request".to_string()),
+        ..Default::default()
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+    env
+}
+
+async fn submit(env: &TestEnv, app_name: &str) -> (StatusCode, serde_json::Value) {
+    env.request(
+        "POST",
+        "/api/apps/requests",
+        Some(env.cookie("viewer")),
+        Some(serde_json::json!({
+            "app_name": app_name,
+            "custom_config": {"persistence.size": "5Gi"},
+            "comment": "For the family",
+        })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_approve_installs_as_requester() {
+    let env = setup().await;
+    let (status, request) = submit(&env, "jellyfin").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(request["status"], "pending");
+    assert_eq!(request["requested_by_username"], "viewer");
+    assert_eq!(request["custom_config"]["persistence.size"], "5Gi");
+    assert!(env.helm.calls().is_empty());
+
+    // One pending request per app
+    let (status, _) = submit(&env, "jellyfin").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Requesters cannot review
+    let id = request["id"].as_i64().unwrap();
+    let (status, _) = env
+        .request(
+            "POST",
+            &format!("/api/apps/requests/{}/approve", id),
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, approved) = env
+        .request(
+            "POST",
+            &format!("/api/apps/requests/{}/approve", id),
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"comment": "Enjoy"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(approved["status"], "approved");
+    assert_eq!(approved["reviewed_by_username"], "admin");
+    assert_eq!(approved["review_comment"], "Enjoy");
+    let calls = env.helm.calls();
+    assert_eq!(calls.len(), 1);
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an install, got {:?}", calls[0]);
+    };
+    assert_eq!(release.name, "jellyfin");
+    assert!(InstalledApp::find_by_id("jellyfin")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .is_some());
+
+    let installed = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("app_installed"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("audit entry");
+    assert_eq!(installed.username.as_deref(), Some("viewer"));
+    assert_eq!(installed.resource_id.as_deref(), Some("jellyfin"));
+    let details: serde_json::Value =
+        serde_json::from_str(installed.details.as_deref().unwrap()).unwrap();
+    assert_eq!(details["approved_by"], "admin");
+
+    // A reviewed request cannot be reviewed again
+    let (status, _) = env
+        .request(
+            "POST",
+            &format!("/api/apps/requests/{}/deny", id),
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_deny_and_list() {
+    let env = setup().await;
+    let (_, request) = submit(&env, "radarr").await;
+    let id = request["id"].as_i64().unwrap();
+
+    let (status, denied) = env
+        .request(
+            "POST",
+            &format!("/api/apps/requests/{}/deny", id),
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"comment": "Use sonarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(denied["status"], "denied");
+    assert!(env.helm.calls().is_empty());
+
+    // Denied apps can be requested again
+    let (status, _) = submit(&env, "radarr").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, mine) = env
+        .request(
+            "GET",
+            "/api/apps/requests?status=denied",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mine.as_array().unwrap().len(), 1);
+    assert_eq!(mine[0]["review_comment"], "Use sonarr");
+
+    let (_, queue) = env
+        .request("GET", "/api/apps/requests", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(queue.as_array().unwrap().len(), 2);
+    assert_eq!(queue[0]["status"], "pending");
+}
+
+#[tokio::test]
+async fn test_rejected_requests() {
+    let env = setup().await;
+
+    let (status, _) = submit(&env, "sonarr").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = submit(&env, "plex").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_catalog_app("jellyfin")
+        .build()
+        .await;
+    let (status, _) = submit(&env, "jellyfin").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/requests",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,264 @@
+//! Integration tests for app access schedules
+//!
+//! Covers:
+//! - `GET`/`PUT /api/roles/{role_id}/schedules` and
+//!   `/api/users/{user_id}/schedules`, read in the user's time zone
+//! - enforcement on app routes and in permission evaluation
+//! - audited overrides under `/api/users/{user_id}/schedule-overrides`
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::models::prelude::*;
+use kubarr::models::user_notification;
+use kubarr::services::app_schedules;
+
+/// A window of two hours around now in a zone `offset_hours` ahead of UTC
+fn window(app_name: &str, offset_hours: i64) -> Value {
+    let local = Utc::now() + Duration::hours(offset_hours);
+    json!({
+        "app_name": app_name,
+        "start_time": (local - Duration::hours(1)).format("%H:%M").to_string(),
+        "end_time": (local + Duration::hours(1)).format("%H:%M").to_string(),
+    })
+}
+
+async fn allowed(env: &TestEnv, username: &str) -> bool {
+    app_schedules::is_allowed(&env.db, env.user(username).user.id, "jellyfin", Utc::now())
+        .await
+        .unwrap()
+}
+
+async fn role_id(env: &TestEnv, name: &str) -> i64 {
+    let (_, roles) = env
+        .request("GET", "/api/roles", Some(env.cookie("admin")), None)
+        .await;
+    roles
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == name)
+        .unwrap()["id"]
+        .as_i64()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_role_schedules_limit_app_access() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("kid", "viewer")
+        .with_app("jellyfin", AppStatus::Running)
+        .build()
+        .await;
+    let kid = env.user("kid").user.id;
+    let uri = format!("/api/roles/{}/schedules", role_id(&env, "viewer").await);
+    assert!(allowed(&env, "kid").await);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("kid")),
+            Some(json!([window("jellyfin", 0)])),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!([{"app_name": "jellyfin", "days": ["someday"], "start_time": "16:00", "end_time": "21:00"}])),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Open in Tokyo, closed in UTC
+    let (status, schedules) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!([window("jellyfin", 9)])),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", schedules);
+    assert_eq!(schedules.as_array().unwrap().len(), 1);
+    assert!(!allowed(&env, "kid").await);
+    // The admin reaches the app through an unscheduled role
+    assert!(allowed(&env, "admin").await);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/jellyfin/native-status",
+            Some(env.cookie("kid")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, evaluation) = env
+        .request(
+            "POST",
+            "/api/roles/evaluate",
+            Some(env.cookie("admin")),
+            Some(json!({"user_id": kid, "action": "apps.view", "app_name": "jellyfin"})),
+        )
+        .await;
+    assert_eq!(evaluation["allowed"], false);
+    let step = evaluation["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["check"] == "schedule")
+        .unwrap();
+    assert_eq!(step["passed"], false);
+
+    let (status, _) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("kid")),
+            Some(json!({"timezone": "Middle/Earth"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, preferences) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("kid")),
+            Some(json!({"timezone": "Asia/Tokyo"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preferences["timezone"], "Asia/Tokyo");
+    assert!(allowed(&env, "kid").await);
+
+    // The user's own schedules override the role's
+    let user_uri = format!("/api/users/{}/schedules", kid);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &user_uri,
+            Some(env.cookie("admin")),
+            Some(json!([window("*", 3)])),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!allowed(&env, "kid").await);
+    let (status, _) = env
+        .request("PUT", &user_uri, Some(env.cookie("admin")), Some(json!([])))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(allowed(&env, "kid").await);
+
+    let (status, _) = env
+        .request("PUT", &uri, Some(env.cookie("admin")), Some(json!([])))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, schedules) = env
+        .request("GET", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(schedules, json!([]));
+}
+
+#[tokio::test]
+async fn test_schedule_overrides() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("kid", "viewer")
+        .with_app("jellyfin", AppStatus::Running)
+        .with_notifications()
+        .build()
+        .await;
+    let kid = env.user("kid").user.id;
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/schedules", kid),
+            Some(env.cookie("admin")),
+            Some(json!([window("jellyfin", 6)])),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!allowed(&env, "kid").await);
+
+    let uri = format!("/api/users/{}/schedule-overrides", kid);
+    let (status, _) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"app_name": "jellyfin", "expires_at": Utc::now() - Duration::hours(1)})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("kid")),
+            Some(json!({"app_name": "jellyfin", "expires_at": Utc::now() + Duration::hours(2)})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, granted) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({
+                "app_name": "jellyfin",
+                "expires_at": Utc::now() + Duration::hours(2),
+                "reason": "movie night",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", granted);
+    assert_eq!(granted["reason"], "movie night");
+    assert!(allowed(&env, "kid").await);
+    let (_, overrides) = env
+        .request("GET", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+
+    // The user is told
+    let notice = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(kid))
+        .filter(user_notification::Column::EventType.eq("schedule_override_granted"))
+        .one(&env.db)
+        .await
+        .unwrap();
+    assert!(notice.is_some());
+
+    let revoke = format!("{}/{}", uri, granted["id"]);
+    let (status, _) = env
+        .request("DELETE", &revoke, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!allowed(&env, "kid").await);
+    let (status, _) = env
+        .request("DELETE", &revoke, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for action in ["schedule_override_granted", "schedule_override_revoked"] {
+        let (_, audit) = env
+            .request(
+                "GET",
+                &format!("/api/audit?action={}", action),
+                Some(env.cookie("admin")),
+                None,
+            )
+            .await;
+        assert_eq!(audit["total"], 1, "{}", action);
+        assert_eq!(audit["logs"][0]["resource_id"], kid.to_string());
+    }
+}
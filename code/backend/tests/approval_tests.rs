@@ -0,0 +1,199 @@
+//! Integration tests for registration approvals
+//!
+//! Covers:
+//! - notifying approvers (users holding `users.manage`) of a pending account
+//! - `GET /api/users/approve-link`  — confirmation page for a signed link
+//! - `POST /api/users/approve-link` — approving through the link
+//! - the daily digest of accounts still awaiting approval
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::{self, DEV_PASSWORD};
+use kubarr::models::prelude::*;
+use kubarr::models::{notification_log, user, user_notification};
+use kubarr::services::approvals::{self, DIGEST_EVENT_TYPE};
+
+/// Send a request and return the status and body as text
+async fn send(env: &TestEnv, method: &str, uri: &str, form: Option<&str>) -> (StatusCode, String) {
+    let mut request = Request::builder().uri(uri).method(method);
+    if form.is_some() {
+        request = request.header("content-type", "application/x-www-form-urlencoded");
+    }
+    let body = form.map(|f| Body::from(f.to_string())).unwrap_or_default();
+    let response = env
+        .router
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Admin and viewer, plus `carol` waiting for approval
+async fn setup() -> (TestEnv, user::Model) {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    let carol = dev_seed::create_user(&env.db, "carol", DEV_PASSWORD, "viewer", false)
+        .await
+        .unwrap();
+    (env, carol)
+}
+
+/// The approve link token in the admin's `user_created` notification
+async fn link_token(env: &TestEnv) -> String {
+    let admin = env.user("admin").user.id;
+    let notification = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(admin))
+        .filter(user_notification::Column::EventType.eq("user_created"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("approver notification");
+    let (_, token) = notification
+        .message
+        .split_once("/api/users/approve-link?token=")
+        .expect("approve link in message");
+    token.split_whitespace().next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_approve_through_link() {
+    let (env, carol) = setup().await;
+    approvals::notify_pending(&env.db, &env.state.notification, &carol)
+        .await
+        .unwrap();
+
+    // Only users holding users.manage are told
+    let viewer = env.user("viewer").user.id;
+    let viewer_notifications = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(viewer))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert!(viewer_notifications.is_empty());
+    let token = link_token(&env).await;
+
+    // Opening the link only asks for confirmation
+    let uri = format!("/api/users/approve-link?token={}", token);
+    let (status, page) = send(&env, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Approve carol?"));
+    assert!(page.contains(r#"<form method="post">"#));
+    let carol_now = User::find_by_id(carol.id).one(&env.db).await.unwrap();
+    assert!(!carol_now.unwrap().is_approved);
+
+    let form = format!("token={}", token);
+    let (status, page) = send(&env, "POST", "/api/users/approve-link", Some(&form)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("carol can now sign in"));
+    let carol_now = User::find_by_id(carol.id).one(&env.db).await.unwrap();
+    assert!(carol_now.unwrap().is_approved);
+    assert!(env.login("carol", DEV_PASSWORD).await.is_some());
+
+    let approved = AuditLog::find()
+        .filter(kubarr::models::audit_log::Column::Action.eq("user_approved"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("audit entry");
+    assert_eq!(approved.username.as_deref(), Some("admin"));
+
+    // Using the link again is harmless
+    let (status, page) = send(&env, "POST", "/api/users/approve-link", Some(&form)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("already been approved"));
+}
+
+#[tokio::test]
+async fn test_rejected_links() {
+    let (env, carol) = setup().await;
+
+    let (status, _) = send(&env, "GET", "/api/users/approve-link?token=garbage", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A link sent to someone who may not approve users does not work
+    let viewer = env.user("viewer").user.id;
+    let link = approvals::approve_link(carol.id, viewer).unwrap();
+    let (_, token) = link.split_once("token=").unwrap();
+    let (status, page) = send(
+        &env,
+        "POST",
+        "/api/users/approve-link",
+        Some(&format!("token={}", token)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(page.contains("no longer valid"));
+    let carol_now = User::find_by_id(carol.id).one(&env.db).await.unwrap();
+    assert!(!carol_now.unwrap().is_approved);
+
+    // Nor one for an account that was rejected in the meantime
+    let admin = env.user("admin").user.id;
+    let link = approvals::approve_link(carol.id, admin).unwrap();
+    User::delete_by_id(carol.id).exec(&env.db).await.unwrap();
+    let (status, _) = send(&env, "GET", &link[link.find("/api/").unwrap()..], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_daily_digest() {
+    let (env, _) = setup().await;
+    let now = Utc::now();
+
+    // No mail provider is configured, so the attempt is logged as failed
+    let sent = approvals::send_digest(&env.db, &env.state.notification, now)
+        .await
+        .unwrap();
+    assert_eq!(sent, 0);
+    let logged = NotificationLog::find()
+        .filter(notification_log::Column::EventType.eq(DIGEST_EVENT_TYPE))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0].user_id, Some(env.user("admin").user.id));
+
+    // At most one digest a day
+    approvals::send_digest(&env.db, &env.state.notification, now + Duration::hours(23))
+        .await
+        .unwrap();
+    approvals::send_digest(&env.db, &env.state.notification, now + Duration::hours(25))
+        .await
+        .unwrap();
+    let logged = NotificationLog::find()
+        .filter(notification_log::Column::EventType.eq(DIGEST_EVENT_TYPE))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(logged.len(), 2);
+}
+
+#[tokio::test]
+async fn test_no_digest_without_pending_accounts() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let sent = approvals::send_digest(&env.db, &env.state.notification, Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(sent, 0);
+    let logged = NotificationLog::find()
+        .filter(notification_log::Column::EventType.eq(DIGEST_EVENT_TYPE))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert!(logged.is_empty());
+}
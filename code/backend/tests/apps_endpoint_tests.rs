@@ -4,6 +4,7 @@
 //! - `GET  /api/apps/catalog`           — requires apps.view
 //! - `GET  /api/apps/catalog/{name}`    — requires apps.view
 //! - `GET  /api/apps/catalog/{name}/icon` — requires apps.view
+//! - `POST /api/apps/catalog/validate`  — requires apps.install
 //! - `GET  /api/apps/installed`         — requires apps.view
 //! - `POST /api/apps/install`           — requires apps.install
 //! - `POST /api/apps/sync`              — requires apps.install
@@ -15,6 +16,10 @@
 //! - `GET  /api/apps/{name}/exists`     — requires apps.view
 //! - `GET  /api/apps/{name}/status`     — requires apps.view
 //! - `POST /api/apps/{name}/access`     — requires Authenticated
+//! - `PUT  /api/apps/{name}/metadata`   — requires apps.install
+//! - `POST /api/apps/{name}/clone`      — requires apps.install
+//! - `GET  /api/apps/{name}/routing`    — requires apps.view
+//! - `PUT  /api/apps/{name}/routing`    — requires apps.install
 
 use axum::{
     body::Body,
@@ -286,10 +291,62 @@ async fn test_sync_requires_apps_install_permission() {
     );
 }
 
+#[tokio::test]
+async fn test_validate_catalog_requires_apps_install_permission() {
+    let (app, cookie) = make_viewer("viewer_validate", "viewer_validate@test.com").await;
+    let (status, _) = make_request(
+        app,
+        "POST",
+        "/api/apps/catalog/validate",
+        Some(&cookie),
+        Some(serde_json::json!({"name": "sonarr", "chart_yaml": ""})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
 // ============================================================================
 // Functional tests with admin auth
 // ============================================================================
 
+#[tokio::test]
+async fn test_validate_catalog_entry_reports_issues() {
+    let (app, cookie) = make_admin("admin_validate", "admin_validate@test.com").await;
+    let chart = "apiVersion: v2\nname: sonarr\nversion: 1.0.0\nannotations:\n  kubarr.io/category: media-manager\n  kubarr.io/hidden: yes\n";
+    let values = "sonarr:\n  service:\n    port: 0\n";
+    let (status, body) = make_request(
+        app,
+        "POST",
+        "/api/apps/catalog/validate",
+        Some(&cookie),
+        Some(serde_json::json!({
+            "name": "sonarr",
+            "chart_yaml": chart,
+            "values_yaml": values,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let result: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(result["valid"], false);
+    assert_eq!(result["app"]["category"], "media-manager");
+    let errors: Vec<&str> = result["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|i| i["level"] == "error")
+        .map(|i| i["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "Chart.yaml:annotations.kubarr.io/hidden",
+            "values.yaml:sonarr.service.port"
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_list_catalog_returns_empty_without_charts() {
     let (app, cookie) = make_admin("admin_catalog", "admin_catalog@test.com").await;
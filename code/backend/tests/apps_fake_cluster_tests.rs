@@ -0,0 +1,1457 @@
+//! Integration tests for the app lifecycle endpoints against `FakeK8s` and
+//! `MockHelmEngine`
+//!
+//! Complements `apps_endpoint_tests.rs`, which only covers the no-cluster
+//! paths. Each test builds a `TestEnv` whose apps are registered in an
+//! in-memory cluster, so install, restart, delete and status flows run
+//! deterministically and their cluster and Helm calls can be asserted.
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::config::CONFIG;
+use kubarr::models::prelude::InstalledApp;
+use kubarr::models::system_setting;
+use kubarr::services::catalog::{AppCatalog, AppConfig};
+use kubarr::services::helm::{HelmCall, HelmRelease, HelmReleaseInfo};
+use kubarr::services::k8s::K8sOperation;
+
+#[tokio::test]
+async fn test_status_reflects_cluster_state() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Failed)
+        .with_app("lidarr", AppStatus::Stopped)
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    let cookie = env.cookie("viewer");
+
+    let cases = [
+        ("sonarr", "installed"),
+        ("radarr", "installing"),
+        ("lidarr", "idle"),
+        ("prowlarr", "idle"),
+    ];
+    for (app, expected) in cases {
+        let (status, body) = env
+            .request(
+                "GET",
+                &format!("/api/apps/{}/status", app),
+                Some(cookie),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["state"], expected, "unexpected state for {}", app);
+    }
+}
+
+#[tokio::test]
+async fn test_health_and_exists() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Failed)
+        .build()
+        .await;
+    let cookie = env.cookie("viewer");
+
+    let (status, body) = env
+        .request("GET", "/api/apps/sonarr/health", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["healthy"], true);
+    assert_eq!(body["deployments"][0]["ready_replicas"], 1);
+
+    let (_, body) = env
+        .request("GET", "/api/apps/radarr/health", Some(cookie), None)
+        .await;
+    assert_eq!(body["healthy"], false);
+    assert_eq!(body["status"], "unhealthy");
+
+    let (_, body) = env
+        .request("GET", "/api/apps/sonarr/exists", Some(cookie), None)
+        .await;
+    assert_eq!(body["exists"], true);
+    let (_, body) = env
+        .request("GET", "/api/apps/bazarr/exists", Some(cookie), None)
+        .await;
+    assert_eq!(body["exists"], false);
+}
+
+#[tokio::test]
+async fn test_installed_lists_apps_with_workloads() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Failed)
+        .with_app("lidarr", AppStatus::Stopped)
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/apps/installed",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let mut installed: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    installed.sort();
+    assert_eq!(installed, vec!["radarr", "sonarr"]);
+}
+
+#[tokio::test]
+async fn test_installed_app_notes_and_tags() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Running)
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/metadata",
+            Some(cookie),
+            Some(serde_json::json!({
+                "notes": "Shared with the family",
+                "tags": ["Family instance", "family instance", " 4k "]
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tags"], serde_json::json!(["Family instance", "4k"]));
+
+    // Apps that are not deployed cannot be annotated
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/prowlarr/metadata",
+            Some(cookie),
+            Some(serde_json::json!({"tags": ["testing"]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/apps/installed?details=true",
+            Some(cookie),
+            None,
+        )
+        .await;
+    let mut apps = body.as_array().unwrap().clone();
+    apps.sort_by_key(|a| a["name"].as_str().unwrap().to_string());
+    assert_eq!(apps[0]["name"], "radarr");
+    assert_eq!(apps[0]["tags"], serde_json::json!([]));
+    assert!(apps[0]["installed_at"].is_null());
+    assert_eq!(apps[1]["notes"], "Shared with the family");
+
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/apps/installed?tag=family%20INSTANCE",
+            Some(cookie),
+            None,
+        )
+        .await;
+    assert_eq!(body, serde_json::json!(["sonarr"]));
+
+    // Uninstalling drops the metadata
+    env.request("DELETE", "/api/apps/sonarr", Some(cookie), None)
+        .await;
+    assert!(InstalledApp::find_by_id("sonarr")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Installing creates a fresh record
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(cookie),
+            Some(serde_json::json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let record = InstalledApp::find_by_id("prowlarr")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("install must record the app");
+    assert_eq!(record.tags, "[]");
+}
+
+#[tokio::test]
+async fn test_clone_installs_suffixed_instance() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/apps/sonarr/clone",
+            Some(cookie),
+            Some(serde_json::json!({"suffix": "4k"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["app_name"], "sonarr-4k");
+    assert_eq!(body["namespace"], "sonarr-4k");
+    assert_eq!(
+        env.helm.calls(),
+        vec![HelmCall::UpgradeInstall(HelmRelease {
+            name: "sonarr-4k".to_string(),
+            chart: format!("{}/sonarr", CONFIG.charts.registry),
+            namespace: "sonarr-4k".to_string(),
+            create_namespace: true,
+            set: vec![
+                "fullnameOverride=sonarr-4k".to_string(),
+                "ingress.path=/sonarr-4k".to_string(),
+            ],
+            set_string: vec![],
+            ..Default::default()
+        })]
+    );
+    let record = InstalledApp::find_by_id("sonarr-4k")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("clone must be recorded");
+    assert_eq!(record.cloned_from.as_deref(), Some("sonarr"));
+
+    // Once running, the clone is listed as a distinct installed app
+    env.k8s.add_app("sonarr-4k", true);
+    let (_, body) = env
+        .request("GET", "/api/apps/installed", Some(cookie), None)
+        .await;
+    let mut installed: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    installed.sort();
+    assert_eq!(installed, vec!["sonarr", "sonarr-4k"]);
+
+    let (_, body) = env
+        .request("GET", "/api/roles/permissions", Some(cookie), None)
+        .await;
+    assert!(body
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["key"] == "app.sonarr-4k"));
+
+    // Cloning onto an existing instance is rejected
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/sonarr/clone",
+            Some(cookie),
+            Some(serde_json::json!({"suffix": "4k"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_clones_must_go_through_clone_endpoint() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    // Install only takes catalog names, so it cannot skip the clone checks
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(cookie),
+            Some(serde_json::json!({"app_name": "sonarr-4k"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(env.helm.calls().is_empty());
+
+    // Namespaces that merely look like clones are not installed apps
+    env.k8s.add_app("sonarr-manual", true);
+    let (_, body) = env
+        .request("GET", "/api/apps/installed", Some(cookie), None)
+        .await;
+    assert_eq!(body, serde_json::json!(["sonarr"]));
+}
+
+#[tokio::test]
+async fn test_clone_rejects_invalid_suffix() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("sonarr")
+        .build()
+        .await;
+
+    for suffix in ["", "4K", "-4k", "a/b"] {
+        let (status, _) = env
+            .request(
+                "POST",
+                "/api/apps/sonarr/clone",
+                Some(env.cookie("admin")),
+                Some(serde_json::json!({ "suffix": suffix })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "suffix {:?}", suffix);
+    }
+    assert!(env.helm.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_subdomain_routing_redeploys_ingress() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("plex", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+    let set_routing = |body: serde_json::Value| {
+        env.request("PUT", "/api/apps/plex/routing", Some(cookie), Some(body))
+    };
+
+    // Subdomains need a base domain to live under
+    let (status, _) = set_routing(serde_json::json!({"mode": "subdomain"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    set_setting(&env, "app_base_domain", "apps.example.com").await;
+
+    // ... and a session cookie that reaches it
+    let (status, _) = set_routing(serde_json::json!({"mode": "subdomain"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(env.helm.calls().is_empty());
+
+    set_setting(&env, "session_cookie_domain", "example.com").await;
+
+    let (status, body) = set_routing(serde_json::json!({"mode": "subdomain"})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["host"], "plex.apps.example.com");
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(
+        release.set,
+        vec![
+            "ingress.host=plex.apps.example.com".to_string(),
+            "ingress.path=/".to_string(),
+        ]
+    );
+
+    let (_, body) = env
+        .request("GET", "/api/apps/plex/routing", Some(cookie), None)
+        .await;
+    assert_eq!(body["mode"], "subdomain");
+    assert_eq!(body["subdomain"], "plex");
+
+    let (status, _) =
+        set_routing(serde_json::json!({"mode": "subdomain", "subdomain": "Bad_Name"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Back to path routing: the chart defaults apply again
+    let (status, body) = set_routing(serde_json::json!({"mode": "path"})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["path_prefix"], "/plex");
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[1] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert!(release.set.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_routing_redeploy_keeps_old_routing() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("plex", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+    set_setting(&env, "app_base_domain", "apps.example.com").await;
+    set_setting(&env, "session_cookie_domain", "example.com").await;
+    env.helm.fail_with("Error: UPGRADE FAILED");
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/plex/routing",
+            Some(cookie),
+            Some(serde_json::json!({"mode": "subdomain"})),
+        )
+        .await;
+    assert!(status.is_server_error(), "got {}", status);
+
+    let (_, body) = env
+        .request("GET", "/api/apps/plex/routing", Some(cookie), None)
+        .await;
+    assert_eq!(body["mode"], "path");
+    assert!(InstalledApp::find_by_id("plex")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .is_none_or(|r| r.routing_mode == "path"));
+}
+
+#[tokio::test]
+async fn test_subdomain_host_is_dispatched_before_kubarr_routes() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("plex", AppStatus::Running)
+        .build()
+        .await;
+    set_setting(&env, "app_base_domain", "apps.example.com").await;
+    set_setting(&env, "session_cookie_domain", "example.com").await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/plex/routing",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"mode": "subdomain"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let health = |host: &'static str| {
+        env.router.clone().oneshot(
+            Request::builder()
+                .uri("/api/health")
+                .header("Host", host)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    // Kubarr's own host still serves Kubarr
+    assert_eq!(
+        health("kubarr.example.com").await.unwrap().status(),
+        StatusCode::OK
+    );
+    // On the app's host, /api/* belongs to the app and needs a session
+    assert_eq!(
+        health("plex.apps.example.com").await.unwrap().status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_maintenance_page_polls_and_starts_stopped_app() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use kubarr::services::k8s::K8sApi;
+    use tower::ServiceExt;
+
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    env.k8s
+        .scale_deployment("sonarr", "sonarr", 0)
+        .await
+        .unwrap();
+
+    let send = |method: &'static str, user: &'static str, action: &'static str| {
+        env.router.clone().oneshot(
+            Request::builder()
+                .method(method)
+                .uri("/sonarr/")
+                // The app proxy reads the unindexed session cookie
+                .header(
+                    "Cookie",
+                    env.cookie(user)
+                        .replacen("kubarr_session_0=", "kubarr_session=", 1),
+                )
+                .header("x-kubarr-maintenance", action)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    let json = |response: axum::response::Response| async move {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+    };
+
+    // Polls are answered by Kubarr, not forwarded to the app
+    let response = send("GET", "viewer", "status").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let status = json(response).await;
+    assert_eq!(status["state"], "stopped");
+    assert_eq!(status["can_start"], false);
+
+    let status = json(send("GET", "admin", "status").await.unwrap()).await;
+    assert_eq!(status["can_start"], true);
+
+    // Starting needs apps.restart
+    let response = send("POST", "viewer", "start").await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = send("POST", "admin", "start").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json(response).await["state"], "running");
+    assert_eq!(
+        env.k8s.operations().last(),
+        Some(&K8sOperation::ScaleDeployment {
+            namespace: "sonarr".to_string(),
+            name: "sonarr".to_string(),
+            replicas: 1,
+        })
+    );
+}
+
+async fn set_setting(env: &TestEnv, key: &str, value: &str) {
+    system_setting::ActiveModel {
+        key: Set(key.to_string()),
+        value: Set(value.to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_restart_deletes_app_pods() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/apps/sonarr/restart",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["message"], "Restarted 1 pod(s) for app 'sonarr'");
+    assert_eq!(
+        env.k8s.operations(),
+        vec![K8sOperation::DeletePod {
+            namespace: "sonarr".to_string(),
+            pod: "sonarr-0".to_string(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_restart_requires_permission() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/sonarr/restart",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(env.k8s.operations().is_empty());
+}
+
+#[tokio::test]
+async fn test_stop_and_start_preserve_replicas() {
+    use kubarr::models::audit_log;
+    use kubarr::services::k8s::K8sApi;
+    use sea_orm::{ColumnTrait, QueryFilter};
+
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    env.k8s
+        .scale_deployment("sonarr", "sonarr", 3)
+        .await
+        .unwrap();
+    let cookie = env.cookie("admin");
+
+    let (status, body) = env
+        .request("POST", "/api/apps/sonarr/stop", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["state"], "stopped");
+
+    let (_, body) = env
+        .request("GET", "/api/apps/sonarr/status", Some(cookie), None)
+        .await;
+    assert_eq!(body["state"], "stopped");
+
+    let (status, _) = env
+        .request("POST", "/api/apps/sonarr/start", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        env.k8s.operations().last(),
+        Some(&K8sOperation::ScaleDeployment {
+            namespace: "sonarr".to_string(),
+            name: "sonarr".to_string(),
+            replicas: 3,
+        })
+    );
+    let (_, body) = env
+        .request("GET", "/api/apps/sonarr/status", Some(cookie), None)
+        .await;
+    assert_eq!(body["state"], "installed");
+
+    for action in ["app_stopped", "app_started"] {
+        let logged = audit_log::Entity::find()
+            .filter(audit_log::Column::Action.eq(action))
+            .filter(audit_log::Column::ResourceId.eq("sonarr"))
+            .one(&env.db)
+            .await
+            .unwrap();
+        assert!(logged.is_some(), "missing {} audit entry", action);
+    }
+}
+
+#[tokio::test]
+async fn test_stop_requires_permission() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+
+    for action in ["stop", "start"] {
+        let (status, _) = env
+            .request(
+                "POST",
+                &format!("/api/apps/sonarr/{}", action),
+                Some(env.cookie("viewer")),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+    assert!(env.k8s.operations().is_empty());
+}
+
+#[tokio::test]
+async fn test_idle_apps_are_suspended_and_woken_by_next_request() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use kubarr::models::audit_log;
+    use kubarr::services::deployment::DeploymentManager;
+    use kubarr::services::idle_suspend::suspend_idle_apps;
+    use kubarr::services::installed_apps;
+    use sea_orm::{ColumnTrait, IntoActiveModel, QueryFilter};
+    use tower::ServiceExt;
+
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Running)
+        .build()
+        .await;
+    set_setting(&env, "idle_suspend_enabled", "true").await;
+    set_setting(&env, "idle_suspend_days", "7").await;
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/apps/radarr/idle-suspend",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({ "exempt": true })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["idle_suspend_exempt"], true);
+
+    let now = chrono::Utc::now();
+    let long_ago = now - chrono::Duration::days(30);
+    for app in ["sonarr", "radarr"] {
+        installed_apps::record_access(&env.db, app, long_ago)
+            .await
+            .unwrap();
+        let mut record = InstalledApp::find_by_id(app)
+            .one(&env.db)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_active_model();
+        record.installed_at = Set(long_ago);
+        record.update(&env.db).await.unwrap();
+    }
+
+    let suspend = || async {
+        let catalog = env.state.catalog.read().await;
+        let manager = DeploymentManager::with_db(&env.k8s, &env.helm, &catalog, &env.db);
+        suspend_idle_apps(&env.db, &env.k8s, &manager, &catalog, now)
+            .await
+            .unwrap()
+    };
+    assert_eq!(suspend().await, vec!["sonarr".to_string()]);
+    assert!(installed_apps::is_suspended(&env.db, "sonarr")
+        .await
+        .unwrap());
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/status",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(body["state"], "stopped");
+
+    // Any user with access wakes a suspended app, without apps.restart
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/sonarr/")
+                .header(
+                    "Cookie",
+                    env.cookie("viewer")
+                        .replacen("kubarr_session_0=", "kubarr_session=", 1),
+                )
+                .header("x-kubarr-maintenance", "status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let status: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(status["state"], "running");
+    assert_eq!(
+        env.k8s.operations().last(),
+        Some(&K8sOperation::ScaleDeployment {
+            namespace: "sonarr".to_string(),
+            name: "sonarr".to_string(),
+            replicas: 1,
+        })
+    );
+    assert!(!installed_apps::is_suspended(&env.db, "sonarr")
+        .await
+        .unwrap());
+    let woken = audit_log::Entity::find()
+        .filter(audit_log::Column::Action.eq("app_started"))
+        .filter(audit_log::Column::ResourceId.eq("sonarr"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(woken.details.unwrap().contains("idle_wake"));
+
+    // Waking counts as activity
+    assert!(suspend().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_boot_order_holds_apps_until_dependencies_are_up() {
+    use kubarr::services::boot_order;
+    use kubarr::services::deployment::DeploymentManager;
+    use kubarr::services::k8s::K8sApi;
+
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("qbittorrent", AppStatus::Failed)
+        .with_app("radarr", AppStatus::Failed)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+    let put = |app: &str, body: serde_json::Value| {
+        let uri = format!("/api/apps/{}/boot-order", app);
+        let env = &env;
+        async move { env.request("PUT", &uri, Some(cookie), Some(body)).await }
+    };
+
+    let (status, body) = put(
+        "radarr",
+        serde_json::json!({ "depends_on": ["qbittorrent", "qbittorrent"] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["depends_on"], serde_json::json!(["qbittorrent"]));
+
+    let (status, _) = put("radarr", serde_json::json!({ "depends_on": ["plex"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // qbittorrent booting after radarr contradicts radarr depending on it
+    let (status, _) = put("qbittorrent", serde_json::json!({ "priority": 1 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let reconcile = || async {
+        let catalog = env.state.catalog.read().await;
+        let manager = DeploymentManager::with_db(&env.k8s, &env.helm, &catalog, &env.db);
+        boot_order::reconcile(&env.db, &env.k8s, &manager, &catalog, chrono::Utc::now())
+            .await
+            .unwrap()
+    };
+
+    // radarr came up before qbittorrent is ready
+    let changes = reconcile().await;
+    assert_eq!(changes.held, vec!["radarr".to_string()]);
+    let (_, body) = env
+        .request("GET", "/api/apps/radarr/status", Some(cookie), None)
+        .await;
+    assert_eq!(body["state"], "waiting");
+    assert_eq!(
+        body["boot"]["waiting_for"],
+        serde_json::json!(["qbittorrent"])
+    );
+
+    env.k8s
+        .scale_deployment("qbittorrent", "qbittorrent", 1)
+        .await
+        .unwrap();
+    let changes = reconcile().await;
+    assert_eq!(changes.released, vec!["radarr".to_string()]);
+    assert_eq!(
+        env.k8s.operations().last(),
+        Some(&K8sOperation::ScaleDeployment {
+            namespace: "radarr".to_string(),
+            name: "radarr".to_string(),
+            replicas: 1,
+        })
+    );
+    let (_, body) = env
+        .request("GET", "/api/apps/radarr/boot-order", Some(cookie), None)
+        .await;
+    assert_eq!(body["held"], false);
+    assert_eq!(body["waiting_for"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_delete_removes_namespace() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, body) = env
+        .request("DELETE", "/api/apps/sonarr", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "deleting");
+    assert_eq!(
+        env.helm.calls(),
+        vec![HelmCall::Uninstall {
+            name: "sonarr".to_string(),
+            namespace: "sonarr".to_string(),
+        }]
+    );
+    assert_eq!(
+        env.k8s.operations(),
+        vec![K8sOperation::DeleteNamespace("sonarr".to_string())]
+    );
+
+    let (_, body) = env
+        .request("GET", "/api/apps/sonarr/status", Some(cookie), None)
+        .await;
+    assert_eq!(body["state"], "idle");
+}
+
+#[tokio::test]
+async fn test_install_unknown_app_does_not_touch_cluster() {
+    let env = TestEnv::builder().with_admin().build().await;
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "notinthecatalog"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(env.k8s.operations().is_empty());
+    assert!(env.helm.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_install_runs_helm_upgrade() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({
+                "app_name": "prowlarr",
+                "custom_config": {"replicaCount": "2"}
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "installing");
+    assert_eq!(body["namespace"], "prowlarr");
+
+    assert_eq!(
+        env.helm.calls(),
+        vec![HelmCall::UpgradeInstall(HelmRelease {
+            name: "prowlarr".to_string(),
+            chart: format!("{}/prowlarr", CONFIG.charts.registry),
+            namespace: "prowlarr".to_string(),
+            create_namespace: true,
+            set: vec!["replicaCount=2".to_string()],
+            set_string: vec![],
+            ..Default::default()
+        })]
+    );
+}
+
+#[tokio::test]
+async fn test_install_passes_storage_path() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    system_setting::ActiveModel {
+        key: Set("storage_path".to_string()),
+        value: Set("/mnt/media".to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(
+        release.set,
+        vec![
+            "storage.hostPath.enabled=true".to_string(),
+            "storage.hostPath.rootPath=/mnt/media".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_install_reports_helm_failure() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    env.helm.fail_with("Error: chart not found");
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body["detail"].as_str().unwrap().contains("chart not found"));
+}
+
+/// Limit catalog apps to the given architectures
+async fn set_architectures(env: &TestEnv, apps: &[(&str, &[&str])]) {
+    let mut catalog = env.state.catalog.write().await;
+    let mut entries: std::collections::HashMap<String, AppConfig> = catalog
+        .get_all_apps()
+        .into_iter()
+        .map(|app| (app.name.clone(), app.clone()))
+        .collect();
+    for (name, architectures) in apps {
+        entries.get_mut(*name).unwrap().architectures =
+            architectures.iter().map(|a| a.to_string()).collect();
+    }
+    *catalog = AppCatalog::with_apps(entries);
+}
+
+#[tokio::test]
+async fn test_catalog_flags_apps_by_node_architecture() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_catalog_app("prowlarr")
+        .with_catalog_app("sonarr")
+        .with_catalog_app("radarr")
+        .build()
+        .await;
+    set_architectures(&env, &[("prowlarr", &["amd64"]), ("sonarr", &["arm64"])]).await;
+    let cookie = env.cookie("viewer");
+
+    // Unknown node architectures flag nothing
+    let (status, body) = env
+        .request("GET", "/api/apps/catalog", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|app| app["compatible"] == true && app.get("warning").is_none()));
+
+    env.k8s.set_node_architectures(&["arm64"]);
+    let (_, body) = env
+        .request("GET", "/api/apps/catalog", Some(cookie), None)
+        .await;
+    let prowlarr = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|app| app["name"] == "prowlarr")
+        .unwrap();
+    assert_eq!(prowlarr["compatible"], false);
+    assert_eq!(prowlarr["architectures"], serde_json::json!(["amd64"]));
+    assert!(prowlarr["warning"]
+        .as_str()
+        .unwrap()
+        .contains("the nodes are arm64"));
+
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/apps/catalog?compatible_only=true",
+            Some(cookie),
+            None,
+        )
+        .await;
+    let mut names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|app| app["name"].as_str().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["radarr", "sonarr"]);
+}
+
+#[tokio::test]
+async fn test_install_refuses_incompatible_architecture_without_override() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    set_architectures(&env, &[("prowlarr", &["amd64"])]).await;
+    env.k8s.set_node_architectures(&["arm64"]);
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["detail"]
+        .as_str()
+        .unwrap()
+        .contains("allow_incompatible_arch"));
+    assert!(env.helm.calls().is_empty());
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "prowlarr", "allow_incompatible_arch": true})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(env.helm.calls().len(), 1);
+}
+
+#[tokio::test]
+async fn test_install_pins_app_to_supported_nodes_of_mixed_cluster() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    set_architectures(&env, &[("prowlarr", &["arm64"])]).await;
+    env.k8s.set_node_architectures(&["amd64", "arm64"]);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(
+        release.set_string,
+        vec!["nodeSelector.kubernetes\\.io/arch=arm64".to_string()]
+    );
+}
+
+async fn set_chart_version(env: &TestEnv, app: &str, version: &str) {
+    let mut catalog = env.state.catalog.write().await;
+    let mut entries: std::collections::HashMap<String, AppConfig> = catalog
+        .get_all_apps()
+        .into_iter()
+        .map(|app| (app.name.clone(), app.clone()))
+        .collect();
+    entries.get_mut(app).unwrap().version = version.to_string();
+    *catalog = AppCatalog::with_apps(entries);
+}
+
+async fn installed_details(env: &TestEnv, app: &str) -> serde_json::Value {
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/apps/installed?details=true",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    body.as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["name"] == app)
+        .cloned()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_upgrade_moves_app_to_catalog_chart_version() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    set_chart_version(&env, "sonarr", "1.0.0").await;
+    let cookie = env.cookie("admin");
+
+    // Reinstalling pins the release to the catalog's chart and records it
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(cookie),
+            Some(serde_json::json!({"app_name": "sonarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(release.version.as_deref(), Some("1.0.0"));
+
+    let app = installed_details(&env, "sonarr").await;
+    assert_eq!(app["chart_version"], "1.0.0");
+    assert_eq!(app["latest_version"], "1.0.0");
+    assert_eq!(app["update_available"], false);
+
+    let (status, _) = env
+        .request("POST", "/api/apps/sonarr/upgrade", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    set_chart_version(&env, "sonarr", "1.1.0").await;
+    let app = installed_details(&env, "sonarr").await;
+    assert_eq!(app["chart_version"], "1.0.0");
+    assert_eq!(app["latest_version"], "1.1.0");
+    assert_eq!(app["update_available"], true);
+
+    let (status, body) = env
+        .request("POST", "/api/apps/sonarr/upgrade", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "upgrading");
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = calls.last().unwrap() else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(release.version.as_deref(), Some("1.1.0"));
+
+    let app = installed_details(&env, "sonarr").await;
+    assert_eq!(app["chart_version"], "1.1.0");
+    assert_eq!(app["update_available"], false);
+}
+
+#[tokio::test]
+async fn test_untracked_release_version_is_read_from_helm() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    set_chart_version(&env, "sonarr", "1.0.0").await;
+    env.helm.add_release(
+        "sonarr",
+        "sonarr",
+        HelmReleaseInfo {
+            revision: 3,
+            chart_version: Some("0.9.2".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let app = installed_details(&env, "sonarr").await;
+    assert_eq!(app["chart_version"], "0.9.2");
+    assert_eq!(app["update_available"], true);
+    let record = InstalledApp::find_by_id("sonarr")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.chart_version.as_deref(), Some("0.9.2"));
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/sonarr/upgrade",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(env.helm.calls().is_empty());
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/radarr/upgrade",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_value_overrides_roll_out_with_secrets_masked() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+    let overrides = serde_json::json!({
+        "sonarr": {
+            "resources": {"limits": {"memory": "2Gi"}},
+            "env": {"TZ": "Europe/Amsterdam", "API_KEY": "abc123"}
+        }
+    });
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/values",
+            Some(cookie),
+            Some(serde_json::json!({ "overrides": overrides })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["overrides"]["sonarr"]["env"]["API_KEY"], "********");
+    assert_eq!(body["effective"]["sonarr"]["env"]["TZ"], "Europe/Amsterdam");
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(release.values, overrides);
+
+    // The masked value sent back keeps the stored secret
+    let (_, body) = env
+        .request("GET", "/api/apps/sonarr/values", Some(cookie), None)
+        .await;
+    let mut edited = body["overrides"].clone();
+    edited["sonarr"]["env"]["TZ"] = "UTC".into();
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/values",
+            Some(cookie),
+            Some(serde_json::json!({ "overrides": edited })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = calls.last().unwrap() else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(release.values["sonarr"]["env"]["API_KEY"], "abc123");
+    assert_eq!(release.values["sonarr"]["env"]["TZ"], "UTC");
+
+    // Later redeploys keep the overrides
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(cookie),
+            Some(serde_json::json!({"app_name": "sonarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = calls.last().unwrap() else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(release.values["sonarr"]["env"]["TZ"], "UTC");
+}
+
+#[tokio::test]
+async fn test_rejected_value_overrides_are_not_kept() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/values",
+            Some(cookie),
+            Some(serde_json::json!({ "overrides": ["not", "an", "object"] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/values",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    env.helm
+        .fail_with("values don't meet the specifications of the schema");
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/values",
+            Some(cookie),
+            Some(serde_json::json!({ "overrides": {"replicas": "many"} })),
+        )
+        .await;
+    assert!(status.is_server_error());
+    let (status, body) = env
+        .request("GET", "/api/apps/sonarr/values", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["overrides"], serde_json::json!({}));
+}
+
+#[tokio::test]
+async fn test_exec_checks_permission_and_pod() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Failed)
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/exec",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Only the app's own pods, and only running ones by default
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/exec?pod=postgres-0",
+            Some(cookie),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = env
+        .request("GET", "/api/apps/radarr/exec", Some(cookie), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A plain request for a valid pod is refused as not a WebSocket upgrade
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/exec?pod=sonarr-0",
+            Some(cookie),
+            None,
+        )
+        .await;
+    assert!(status.is_client_error(), "{}", status);
+    assert_ne!(status, StatusCode::FORBIDDEN);
+    assert_ne!(status, StatusCode::NOT_FOUND);
+}
@@ -2,7 +2,10 @@
 //!
 //! Covers:
 //! - `GET /api/audit` — list audit logs (requires audit.view)
+//! - `GET /api/audit` — scoped listing for app admins (requires audit.view_own_apps)
 //! - `GET /api/audit/stats` — audit statistics (requires audit.view)
+//! - `GET /api/audit/stats/timeseries` — bucketed event counts (requires audit.view)
+//! - `GET /api/audit/verify` — hash chain validation (requires audit.view)
 //! - Audit logs are generated by login activity
 
 use axum::{
@@ -10,10 +13,14 @@ use axum::{
     http::{header, Request, StatusCode},
 };
 use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, Set};
 use tower::util::ServiceExt;
 
 mod common;
-use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use common::{
+    build_test_app_state_with_db, create_test_db_with_seed, create_test_user,
+    create_test_user_with_role,
+};
 
 use kubarr::endpoints::create_router;
 
@@ -251,6 +258,305 @@ async fn test_get_audit_stats_as_admin() {
     );
 }
 
+#[tokio::test]
+async fn test_get_audit_timeseries_as_admin() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "audittsadmin",
+        "auditts@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "audittsviewer",
+        "audittsviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    // A failed login within the default range (last 7 days, hourly)
+    kubarr::models::audit_log::ActiveModel {
+        timestamp: Set(chrono::Utc::now() - chrono::Duration::hours(2)),
+        username: Set(Some("audittsadmin".to_string())),
+        action: Set("login_failed".to_string()),
+        resource_type: Set("session".to_string()),
+        success: Set(false),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "audittsadmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/audit/stats/timeseries?action=login_failed&group_by=success,user",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["interval"], "hour");
+    let points = json["points"].as_array().unwrap();
+    assert_eq!(points.len(), 1, "Body: {}", body);
+    assert_eq!(points[0]["count"], 1);
+    assert_eq!(points[0]["success"], false);
+    assert!(points[0].get("action").is_none());
+
+    let (status, _) = authenticated_get(
+        create_router(state.clone()),
+        "/api/audit/stats/timeseries?interval=week",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, viewer) = do_login(create_router(state.clone()), "audittsviewer", "password123").await;
+    let (status, _) = authenticated_get(
+        create_router(state),
+        "/api/audit/stats/timeseries",
+        &viewer.unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// GET /api/audit/verify — hash chain validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_verify_audit_log() {
+    use kubarr::models::audit_log::{AuditAction, ResourceType};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "auditverify",
+        "auditverify@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "auditverifyviewer",
+        "auditverifyviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "auditverify", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) =
+        authenticated_get(create_router(state.clone()), "/api/audit/verify", &cookie).await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["enabled"], false);
+
+    state
+        .audit
+        .set_integrity_key(Some("endpoint-test-key".to_string()))
+        .await;
+    for _ in 0..3 {
+        state
+            .audit
+            .log_success(
+                AuditAction::SystemSettingChanged,
+                ResourceType::System,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    let (status, body) =
+        authenticated_get(create_router(state.clone()), "/api/audit/verify", &cookie).await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["enabled"], true);
+    assert_eq!(json["valid"], true, "Body: {}", body);
+    assert_eq!(json["checked"], 3);
+    assert!(json["first_broken"].is_null());
+
+    let (_, viewer) = do_login(
+        create_router(state.clone()),
+        "auditverifyviewer",
+        "password123",
+    )
+    .await;
+    let (status, _) =
+        authenticated_get(create_router(state), "/api/audit/verify", &viewer.unwrap()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// GET /api/audit — app admins only see their apps and users
+// ============================================================================
+
+#[tokio::test]
+async fn test_get_audit_logs_scoped_to_app_admin() {
+    use kubarr::models::{role, role_app_permission, role_permission, user_role};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    let app_admin_role = role::ActiveModel {
+        name: Set("download_admin".to_string()),
+        description: Set(Some("Manages the download clients".to_string())),
+        is_system: Set(false),
+        requires_2fa: Set(false),
+        created_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    role_permission::ActiveModel {
+        role_id: Set(app_admin_role.id),
+        permission: Set("audit.view_own_apps".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    role_app_permission::ActiveModel {
+        role_id: Set(app_admin_role.id),
+        app_name: Set("qbittorrent".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let app_admin =
+        create_test_user(&db, "dladmin", "dladmin@example.com", "password123", true).await;
+    user_role::ActiveModel {
+        user_id: Set(app_admin.id),
+        role_id: Set(app_admin_role.id),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let managed = create_test_user_with_role(
+        &db,
+        "dluser",
+        "dluser@example.com",
+        "password123",
+        "downloader",
+    )
+    .await;
+    let outsider = create_test_user_with_role(
+        &db,
+        "viewuser",
+        "viewuser@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let auditor = create_test_user_with_role(
+        &db,
+        "rootadmin",
+        "rootadmin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+
+    let entries = [
+        ("scoped_app", "app", Some("qbittorrent"), None),
+        ("scoped_app", "app", Some("jellyfin"), None),
+        ("scoped_user", "user", None, Some(managed.id)),
+        ("scoped_user", "user", None, Some(outsider.id)),
+        ("scoped_user", "user", None, Some(auditor.id)),
+    ];
+    for (action, resource_type, resource_id, user_id) in entries {
+        kubarr::models::audit_log::ActiveModel {
+            timestamp: Set(chrono::Utc::now()),
+            user_id: Set(user_id),
+            action: Set(action.to_string()),
+            resource_type: Set(resource_type.to_string()),
+            resource_id: Set(resource_id.map(str::to_string)),
+            success: Set(true),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "dladmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/audit?search=scoped_&per_page=100",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["total"], 2, "Body: {}", body);
+    let logs = json["logs"].as_array().unwrap();
+    assert!(logs
+        .iter()
+        .any(|l| l["resource_id"] == "qbittorrent" && l["user_id"].is_null()));
+    assert!(logs.iter().any(|l| l["user_id"] == managed.id));
+
+    // Global auditors still see everything
+    let (_, admin_cookie) =
+        do_login(create_router(state.clone()), "rootadmin", "password123").await;
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/audit?search=scoped_&per_page=100",
+        &admin_cookie.unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["total"], 5, "Body: {}", body);
+
+    // Without either permission the log stays closed
+    let (_, viewer_cookie) =
+        do_login(create_router(state.clone()), "viewuser", "password123").await;
+    let (status, _) =
+        authenticated_get(create_router(state), "/api/audit", &viewer_cookie.unwrap()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_app_admin_scope_without_apps_keeps_own_actions() {
+    use kubarr::services::audit::resolve_app_admin_scope;
+
+    let db = create_test_db_with_seed().await;
+    let user = create_test_user(&db, "noapps", "noapps@example.com", "password123", true).await;
+
+    let scope = resolve_app_admin_scope(&db, user.id).await.unwrap();
+    assert_eq!(scope.apps, Some(vec![]));
+    assert_eq!(scope.user_ids, vec![user.id]);
+}
+
 // ============================================================================
 // GET /api/audit — audit log response shape after login activity
 // ============================================================================
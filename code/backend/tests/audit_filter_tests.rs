@@ -63,10 +63,12 @@ async fn test_get_audit_logs_filter_by_resource_type() {
             user_id: None,
             action: None,
             resource_type: Some("user".to_string()),
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -102,10 +104,12 @@ async fn test_get_audit_logs_filter_by_from() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: Some(two_days_ago),
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -140,10 +144,12 @@ async fn test_get_audit_logs_filter_by_to() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: Some(two_days_ago),
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -188,10 +194,12 @@ async fn test_get_audit_logs_filter_by_from_and_to() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: Some(five_days_ago),
             to: Some(two_days_ago),
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -232,10 +240,12 @@ async fn test_get_audit_logs_filter_by_search_matches_username() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: Some("alice".to_string()),
+            request_id: None,
         },
     )
     .await
@@ -262,10 +272,12 @@ async fn test_get_audit_logs_filter_by_search_matches_action() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: Some("app_install".to_string()),
+            request_id: None,
         },
     )
     .await
@@ -309,10 +321,12 @@ async fn test_get_audit_logs_filter_by_search_matches_resource_id() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: Some("abc-123".to_string()),
+            request_id: None,
         },
     )
     .await
@@ -340,10 +354,12 @@ async fn test_get_audit_logs_search_no_match_returns_empty() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: Some("zzz-nonexistent-zzz".to_string()),
+            request_id: None,
         },
     )
     .await
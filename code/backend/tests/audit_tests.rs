@@ -5,16 +5,19 @@
 //! - `get_audit_logs` — empty table, pagination (limit/offset), and all three filter axes
 //!   (user_id, action, success)
 //! - `get_audit_stats` — correct counts for total/success/failure/today/week
+//! - `get_audit_timeseries` — hourly/daily buckets, grouping dimensions and filters
 //! - `clear_old_logs` — retention-policy deletion by age
+//! - `AuditService::verify_chain` — integrity-mode hash chaining and tamper detection
 
 mod common;
 use common::create_test_db;
 
 use kubarr::models::audit_log::{self, AuditAction, ResourceType};
 use kubarr::services::audit::{
-    clear_old_logs, get_audit_logs, get_audit_stats, AuditLogQuery, AuditService,
+    clear_old_logs, get_audit_logs, get_audit_stats, get_audit_timeseries, AuditLogQuery,
+    AuditService, AuditTimeseriesPoint, AuditTimeseriesQuery, ChainBreakReason, TimeseriesInterval,
 };
-use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait, IntoActiveModel, QueryOrder, Set};
 
 // ---------------------------------------------------------------------------
 // Helper: build a minimal AuditService backed by `db`
@@ -155,10 +158,12 @@ async fn test_get_audit_logs_empty() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -201,10 +206,12 @@ async fn test_get_audit_logs_pagination() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -224,10 +231,12 @@ async fn test_get_audit_logs_pagination() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -244,10 +253,12 @@ async fn test_get_audit_logs_pagination() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -285,10 +296,12 @@ async fn test_get_audit_logs_filter_by_user() {
             user_id: Some(10),
             action: None,
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -323,10 +336,12 @@ async fn test_get_audit_logs_filter_by_action() {
             user_id: None,
             action: Some("login".to_string()),
             resource_type: None,
+            resource_id: None,
             success: None,
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -365,10 +380,12 @@ async fn test_get_audit_logs_filter_by_success() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: Some(true),
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -389,10 +406,12 @@ async fn test_get_audit_logs_filter_by_success() {
             user_id: None,
             action: None,
             resource_type: None,
+            resource_id: None,
             success: Some(false),
             from: None,
             to: None,
             search: None,
+            request_id: None,
         },
     )
     .await
@@ -507,3 +526,229 @@ async fn test_clear_old_logs() {
         );
     }
 }
+
+// ---------------------------------------------------------------------------
+// 10. get_audit_timeseries buckets events by hour or day
+// ---------------------------------------------------------------------------
+
+fn at(rfc3339: &str) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .unwrap()
+        .with_timezone(&chrono::Utc)
+}
+
+#[tokio::test]
+async fn test_get_audit_timeseries_buckets_and_groups() {
+    let db = create_test_db().await;
+    insert_log(
+        &db,
+        "login_failed",
+        "session",
+        Some(1),
+        false,
+        at("2026-10-10T10:05:00Z"),
+    )
+    .await;
+    insert_log(
+        &db,
+        "login_failed",
+        "session",
+        Some(1),
+        false,
+        at("2026-10-10T10:55:00Z"),
+    )
+    .await;
+    insert_log(
+        &db,
+        "login_failed",
+        "session",
+        Some(2),
+        false,
+        at("2026-10-10T12:30:00Z"),
+    )
+    .await;
+    insert_log(
+        &db,
+        "login",
+        "session",
+        Some(1),
+        true,
+        at("2026-10-11T09:00:00Z"),
+    )
+    .await;
+    // Outside the range
+    insert_log(
+        &db,
+        "login_failed",
+        "session",
+        Some(1),
+        false,
+        at("2026-10-01T10:00:00Z"),
+    )
+    .await;
+
+    let range = AuditTimeseriesQuery {
+        from: Some(at("2026-10-10T00:00:00Z")),
+        to: Some(at("2026-10-12T00:00:00Z")),
+        ..Default::default()
+    };
+
+    // Failed logins per hour
+    let hourly = get_audit_timeseries(
+        &db,
+        AuditTimeseriesQuery {
+            action: Some("login_failed".to_string()),
+            ..range.clone()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(hourly.group_by, vec!["action"]);
+    let points: Vec<(&str, i64)> = hourly
+        .points
+        .iter()
+        .map(|p| (p.bucket.as_str(), p.count))
+        .collect();
+    assert_eq!(
+        points,
+        vec![("2026-10-10T10:00:00Z", 2), ("2026-10-10T12:00:00Z", 1)]
+    );
+
+    // Per day, split by outcome and user
+    let daily = get_audit_timeseries(
+        &db,
+        AuditTimeseriesQuery {
+            interval: TimeseriesInterval::Day,
+            group_by: Some("success,user".to_string()),
+            ..range.clone()
+        },
+    )
+    .await
+    .unwrap();
+    let point = |bucket: &str, success: bool, user_id: i64, count: i64| AuditTimeseriesPoint {
+        bucket: bucket.to_string(),
+        action: None,
+        success: Some(success),
+        user_id: Some(user_id),
+        username: Some(format!("user_{}", user_id)),
+        count,
+    };
+    assert_eq!(
+        daily.points,
+        vec![
+            point("2026-10-10T00:00:00Z", false, 1, 2),
+            point("2026-10-10T00:00:00Z", false, 2, 1),
+            point("2026-10-11T00:00:00Z", true, 1, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_audit_timeseries_rejects_invalid_queries() {
+    let db = create_test_db().await;
+
+    let bad_group = AuditTimeseriesQuery {
+        group_by: Some("action,ip".to_string()),
+        ..Default::default()
+    };
+    assert!(get_audit_timeseries(&db, bad_group).await.is_err());
+
+    let reversed = AuditTimeseriesQuery {
+        from: Some(at("2026-10-12T00:00:00Z")),
+        to: Some(at("2026-10-10T00:00:00Z")),
+        ..Default::default()
+    };
+    assert!(get_audit_timeseries(&db, reversed).await.is_err());
+
+    // A year of hourly buckets is too many
+    let too_long = AuditTimeseriesQuery {
+        from: Some(at("2025-10-10T00:00:00Z")),
+        to: Some(at("2026-10-10T00:00:00Z")),
+        ..Default::default()
+    };
+    assert!(get_audit_timeseries(&db, too_long).await.is_err());
+}
+
+// ---------------------------------------------------------------------------
+// 11. Integrity mode chains entries and pinpoints the first tampered row
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn test_verify_chain_detects_tampering() {
+    let db = create_test_db().await;
+    let (svc, db) = make_service(db).await;
+
+    // Disabled by default
+    let report = svc.verify_chain(&db).await.unwrap();
+    assert!(!report.enabled);
+
+    // Rows from before integrity mode are reported but not checked
+    insert_log(&db, "login", "session", Some(1), true, chrono::Utc::now()).await;
+    svc.set_integrity_key(Some("test-integrity-key".to_string()))
+        .await;
+    for i in 0..4 {
+        svc.log_success(
+            AuditAction::UserUpdated,
+            ResourceType::User,
+            Some(i.to_string()),
+            Some(1),
+            Some("alice".to_string()),
+            Some(serde_json::json!({ "step": i })),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    let report = svc.verify_chain(&db).await.unwrap();
+    assert!(report.enabled);
+    assert!(report.valid, "{:?}", report);
+    assert_eq!(report.checked, 4);
+    assert_eq!(report.unchained, 1);
+
+    let rows = audit_log::Entity::find()
+        .order_by_asc(audit_log::Column::Id)
+        .all(&db)
+        .await
+        .unwrap();
+    assert!(rows[0].integrity_hash.is_none());
+    assert_eq!(rows[1].prev_hash.as_deref(), Some(""));
+    assert_eq!(rows[2].prev_hash, rows[1].integrity_hash);
+
+    // Editing an entry breaks its own hash
+    let mut edited = rows[2].clone().into_active_model();
+    edited.success = Set(false);
+    edited.update(&db).await.unwrap();
+    let report = svc.verify_chain(&db).await.unwrap();
+    assert!(!report.valid);
+    let broken = report.first_broken.unwrap();
+    assert_eq!(broken.id, rows[2].id);
+    assert_eq!(broken.reason, ChainBreakReason::ContentMismatch);
+    assert_eq!(report.checked, 1);
+
+    // Restoring it and deleting another entry breaks the link after the gap
+    rows[2]
+        .clone()
+        .into_active_model()
+        .reset_all()
+        .update(&db)
+        .await
+        .unwrap();
+    audit_log::Entity::delete_by_id(rows[3].id)
+        .exec(&db)
+        .await
+        .unwrap();
+    let report = svc.verify_chain(&db).await.unwrap();
+    let broken = report.first_broken.unwrap();
+    assert_eq!(broken.id, rows[4].id);
+    assert_eq!(broken.reason, ChainBreakReason::PrevHashMismatch);
+
+    // A different key cannot vouch for the chain
+    svc.set_integrity_key(Some("other-key".to_string())).await;
+    let report = svc.verify_chain(&db).await.unwrap();
+    assert_eq!(
+        report.first_broken.unwrap().reason,
+        ChainBreakReason::ContentMismatch
+    );
+}
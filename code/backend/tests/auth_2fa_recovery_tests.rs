@@ -148,6 +148,7 @@ async fn test_login_role_requires_2fa_not_enabled_returns_400() {
     let user_role_model = user_role::ActiveModel {
         user_id: Set(user.id),
         role_id: Set(created_role.id),
+        ..Default::default()
     };
     user_role_model.insert(&db).await.unwrap();
 
@@ -0,0 +1,137 @@
+//! Integration tests for authentication analytics
+//!
+//! Covers:
+//! - successful sign-ins being audited with their method
+//! - `GET /api/monitoring/auth`: sign-ins per bucket, failure ratio, sign-in
+//!   methods, top failing addresses and 2FA adoption
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::prelude::*;
+use kubarr::models::user;
+
+/// Sign in from an address and return the status
+async fn login_as(env: &TestEnv, username: &str, password: &str, ip: &str) -> StatusCode {
+    let body = json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("x-forwarded-for", ip)
+        .body(Body::from(body))
+        .unwrap();
+    env.router.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_auth_analytics() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+
+    for _ in 0..3 {
+        assert_eq!(
+            login_as(&env, "viewer", DEV_PASSWORD, "198.51.100.30").await,
+            StatusCode::OK
+        );
+    }
+    for _ in 0..2 {
+        assert_eq!(
+            login_as(&env, "nobody", "wrong", "203.0.113.30").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        login_as(&env, "viewer", "wrong", "203.0.113.31").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let viewer = User::find_by_id(env.user("viewer").user.id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut viewer: user::ActiveModel = viewer.into();
+    viewer.totp_enabled = Set(true);
+    viewer.update(&env.db).await.unwrap();
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/monitoring/auth",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/monitoring/auth",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["interval"], "hour");
+    // The test environment signs in the admin and the viewer once each
+    assert_eq!(body["successful_logins"], 5);
+    assert_eq!(body["failed_logins"], 3);
+    assert_eq!(body["failure_ratio"], 0.375);
+    let buckets = body["buckets"].as_array().unwrap();
+    let total = |key: &str| {
+        buckets
+            .iter()
+            .map(|b| b[key].as_i64().unwrap())
+            .sum::<i64>()
+    };
+    assert_eq!(total("successful"), 5);
+    assert_eq!(total("failed"), 3);
+
+    let methods = body["methods"].as_array().unwrap();
+    assert_eq!(methods.len(), 1);
+    assert_eq!(methods[0]["method"], "password");
+    assert_eq!(methods[0]["share"], 1.0);
+
+    let ips = body["top_failing_ips"].as_array().unwrap();
+    assert_eq!(ips.len(), 2);
+    assert_eq!(ips[0]["ip_address"], "203.0.113.30");
+    assert_eq!(ips[0]["failures"], 2);
+    assert_eq!(ips[1]["ip_address"], "203.0.113.31");
+
+    assert_eq!(body["two_factor"]["active_users"], 2);
+    assert_eq!(body["two_factor"]["enabled"], 1);
+    assert_eq!(body["two_factor"]["ratio"], 0.5);
+
+    // Daily buckets and explicit ranges
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/monitoring/auth?interval=day&from=2020-01-01T00:00:00Z&to=2020-01-08T00:00:00Z",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["successful_logins"], 0);
+    assert_eq!(body["failure_ratio"], 0.0);
+    assert!(body["buckets"].as_array().unwrap().is_empty());
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/monitoring/auth?from=2020-01-01T00:00:00Z&to=2021-01-01T00:00:00Z",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
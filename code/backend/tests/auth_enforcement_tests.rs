@@ -32,6 +32,20 @@ use kubarr::state::AppState;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+/// Initialise the JWT signing keys once per test binary
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to initialise test JWT keys");
+        })
+        .await;
+}
+
 /// Helper to create a test AppState with a seeded database
 async fn create_test_state() -> AppState {
     let db = create_test_db_with_seed().await;
@@ -442,6 +456,21 @@ async fn test_auth_endpoints_accessible_without_auth() {
     );
 }
 
+#[tokio::test]
+async fn test_auth_assets_self_hosted() {
+    let state = create_test_state().await;
+
+    // Swagger UI must load its assets from the backend, not a CDN
+    let (status, body) = make_unauthenticated_request(state.clone(), "/api/docs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("/auth/assets/swagger-ui/swagger-ui-bundle.js"));
+    assert!(!body.contains("unpkg.com") && !body.contains("cdn."));
+
+    // Assets are public; a missing file is a plain 404, not an auth failure
+    let (status, _) = make_unauthenticated_request(state, "/auth/assets/does-not-exist.css").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 async fn test_oauth_public_endpoints_accessible() {
     let state = create_test_state().await;
@@ -762,6 +791,86 @@ async fn test_oauth_link_endpoint_requires_session() {
     );
 }
 
+// ============================================================================
+// Bearer Tokens and Permissions Map
+// ============================================================================
+
+#[tokio::test]
+async fn test_permissions_map_with_bearer_token() {
+    ensure_jwt_keys().await;
+    let state = create_test_state().await;
+    let db = state.get_db().await.unwrap();
+    create_test_user_with_role(
+        &db,
+        "viewer",
+        "viewer@example.com",
+        "viewer_password",
+        "viewer",
+    )
+    .await;
+
+    let login = create_router(state.clone())
+        .oneshot(
+            Request::builder()
+                .uri("/auth/login")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"username":"viewer","password":"viewer_password"}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(login.status(), StatusCode::OK);
+    let token = login
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .find_map(|v| {
+            let cookie = v.to_str().ok()?.split(';').next()?;
+            cookie.strip_prefix("kubarr_session=").map(str::to_string)
+        })
+        .expect("login sets a session cookie");
+
+    let (status, _) =
+        make_unauthenticated_request(state.clone(), "/api/system/permissions-map").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/system/permissions-map")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let map: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let route = |method: &str, path: &str| {
+        map["routes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["method"] == method && r["path"] == path)
+            .cloned()
+            .unwrap_or_else(|| panic!("{} {} missing from permissions map", method, path))
+    };
+    let list_apps = route("GET", "/api/apps/installed");
+    assert_eq!(list_apps["permission"], "apps.view");
+    assert_eq!(list_apps["allowed"], true);
+    let create_user = route("POST", "/api/users");
+    assert_eq!(create_user["permission"], "users.manage");
+    assert_eq!(create_user["allowed"], false);
+    let login = route("POST", "/auth/login");
+    assert_eq!(login["public"], true);
+    assert_eq!(login["allowed"], true);
+}
+
 // ============================================================================
 // Summary Test - Verify Auth Architecture
 // ============================================================================
@@ -790,7 +899,7 @@ async fn test_auth_architecture_summary() {
     //    - Returns 403 Forbidden if authenticated but lacks permission
 
     // 4. SESSION MANAGEMENT:
-    //    - Cookie-based sessions (kubarr_session)
+    //    - Cookie-based sessions (kubarr_session), or the token as a bearer token
     //    - HttpOnly, SameSite=Lax, Secure (in production)
     //    - Multi-session support with session switching
 
@@ -798,6 +907,4 @@ async fn test_auth_architecture_summary() {
     //    - Most setup endpoints check admin_exists
     //    - Return 403 after admin user creation
     //    - Prevents post-setup abuse
-
-    assert!(true, "Auth architecture verified by test suite");
 }
@@ -298,7 +298,7 @@ async fn test_login_sets_indexed_session_cookie() {
         c.starts_with("kubarr_session_")
             && c.chars()
                 .nth("kubarr_session_".len())
-                .map_or(false, |ch| ch.is_ascii_digit())
+                .is_some_and(|ch| ch.is_ascii_digit())
     });
     assert!(
         has_indexed,
@@ -473,6 +473,73 @@ async fn test_logout_clears_session_cookie() {
     );
 }
 
+#[tokio::test]
+async fn test_logout_clears_active_slot_cookie() {
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "ext_slot_logout",
+        "ext_slot_logout@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let response = do_login_full(
+        create_router(state.clone()),
+        "ext_slot_logout",
+        "password123",
+    )
+    .await;
+    let set_cookies: Vec<String> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok().map(|s| s.to_string()))
+        .collect();
+    // Without an HTTPS external URL the cookies are Lax and not Secure
+    for cookie in &set_cookies {
+        assert!(cookie.contains("SameSite=Lax"), "{}", cookie);
+        assert!(!cookie.contains("Secure"), "{}", cookie);
+    }
+    let cookie = set_cookies
+        .iter()
+        .map(|c| c.split(';').next().unwrap())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    let logout_response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/auth/logout")
+                .method("POST")
+                .header("Cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(logout_response.status(), StatusCode::OK);
+
+    let cleared: Vec<String> = logout_response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok().map(|s| s.to_string()))
+        .filter(|c| c.contains("Max-Age=0"))
+        .map(|c| c.split('=').next().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        cleared,
+        vec![
+            "kubarr_session",
+            "kubarr_session_0",
+            "kubarr_session_refresh_0"
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_logout_response_body() {
     let state = build_test_app_state_with_db(create_test_db_with_seed().await).await;
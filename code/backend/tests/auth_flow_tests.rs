@@ -449,7 +449,7 @@ async fn test_revoke_current_session_returns_bad_request() {
     let response = create_router(state)
         .oneshot(
             Request::builder()
-                .uri(&format!("/auth/sessions/{}", current_id))
+                .uri(format!("/auth/sessions/{}", current_id))
                 .method("DELETE")
                 .header("Cookie", &cookie)
                 .body(Body::empty())
@@ -0,0 +1,335 @@
+//! Integration tests for backup and restore
+//!
+//! Covers:
+//! - `POST /api/system/backup`          — archive with encrypted secrets
+//! - `POST /api/system/restore`         — restoring it, wrong passphrases
+//! - `GET /api/system/backups[/{name}]` — scheduled backups and retention
+//! - validation of the `backup_schedule` and `backup_retention_count` settings
+
+use std::io::Read;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::{audit_log, notification_channel, system_setting, user};
+use kubarr::services::backup;
+
+const PASSPHRASE: &str = "correct horse battery";
+
+/// Backup directory and passphrase; `CONFIG` reads them once, so every test
+/// sets the same values
+fn backup_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("kubarr_backup_tests_{}", std::process::id()));
+    std::env::set_var("KUBARR_BACKUP_DIR", &dir);
+    std::env::set_var("KUBARR_BACKUP_PASSPHRASE", PASSPHRASE);
+    dir
+}
+
+async fn create_backup(env: &TestEnv, passphrase: &str) -> (StatusCode, Vec<u8>) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/system/backup")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("Cookie", env.cookie("admin"))
+                .body(Body::from(json!({ "passphrase": passphrase }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, bytes.to_vec())
+}
+
+async fn restore(
+    env: &TestEnv,
+    cookie: &str,
+    archive: &[u8],
+    passphrase: &str,
+) -> (StatusCode, Value) {
+    let boundary = "kubarr-test-boundary";
+    let mut body = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"passphrase\"\r\n\r\n{p}\r\n\
+         --{b}\r\nContent-Disposition: form-data; name=\"archive\"; filename=\"backup.tar.gz\"\r\n\
+         Content-Type: application/gzip\r\n\r\n",
+        b = boundary,
+        p = passphrase
+    )
+    .into_bytes();
+    body.extend_from_slice(archive);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/system/restore")
+                .method("POST")
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .header("Cookie", cookie)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn add_channel(env: &TestEnv, token: &str) {
+    notification_channel::ActiveModel {
+        channel_type: Set("telegram".to_string()),
+        enabled: Set(true),
+        config: Set(json!({ "bot_token": token, "chat_id": "42" }).to_string()),
+        min_severity: Set(None),
+        include_event_types: Set("[]".to_string()),
+        exclude_event_types: Set("[]".to_string()),
+        created_at: Set(Utc::now()),
+        updated_at: Set(Utc::now()),
+        ..Default::default()
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_backup_and_restore_roundtrip() {
+    backup_dir();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    add_channel(&env, "bot-secret-token").await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/backup_retention_count",
+            Some(env.cookie("admin")),
+            Some(json!({ "value": "3" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, archive) = create_backup(&env, PASSPHRASE).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut tar = Vec::new();
+    flate2::read::GzDecoder::new(archive.as_slice())
+        .read_to_end(&mut tar)
+        .unwrap();
+    let tar = String::from_utf8_lossy(&tar);
+    assert!(tar.contains("kubarr-backup/manifest.json"));
+    assert!(tar.contains("kubarr-backup/database/users.json"));
+    assert!(!tar.contains("bot-secret-token"), "secrets are encrypted");
+
+    // Change things after the backup
+    notification_channel::Entity::delete_many()
+        .exec(&env.db)
+        .await
+        .unwrap();
+    user::Entity::delete_many()
+        .filter(user::Column::Username.eq("viewer"))
+        .exec(&env.db)
+        .await
+        .unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/backup_retention_count",
+            Some(env.cookie("admin")),
+            Some(json!({ "value": "5" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = restore(&env, env.cookie("admin"), &archive, PASSPHRASE).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["kubarr_version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["rows"].as_u64().unwrap() > 0);
+
+    let channels = notification_channel::Entity::find()
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(channels.len(), 1);
+    assert!(channels[0].config.contains("bot-secret-token"));
+    assert!(user::Entity::find()
+        .filter(user::Column::Username.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .is_some());
+    let retention = system_setting::Entity::find_by_id("backup_retention_count")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(retention.value, "3");
+
+    // Restoring signs everyone out
+    let (status, _) = env
+        .request("GET", "/api/users/me", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let cookie = env.login("viewer", DEV_PASSWORD).await;
+    assert!(cookie.is_some(), "restored users can sign in");
+
+    let restored = audit_log::Entity::find()
+        .filter(audit_log::Column::Action.eq("backup_restored"))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(restored.len(), 1);
+}
+
+#[tokio::test]
+async fn test_restore_rejects_wrong_passphrase_and_bad_archives() {
+    backup_dir();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    add_channel(&env, "bot-secret-token").await;
+
+    let (status, _) = create_backup(&env, "short").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, archive) = create_backup(&env, PASSPHRASE).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = restore(&env, env.cookie("admin"), &archive, "wrong passphrase").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["detail"].as_str().unwrap().contains("passphrase"));
+
+    let (status, _) = restore(&env, env.cookie("admin"), b"not a backup", PASSPHRASE).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = restore(&env, env.cookie("viewer"), &archive, PASSPHRASE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Nothing was touched and the caller is still signed in
+    let (status, _) = env
+        .request("GET", "/api/users/me", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        notification_channel::Entity::find()
+            .all(&env.db)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_scheduled_backups_and_retention() {
+    let dir = backup_dir();
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for old in [
+        "kubarr-backup-20200101-000000.tar.gz",
+        "kubarr-backup-20200102-000000.tar.gz",
+    ] {
+        std::fs::write(dir.join(old), b"old").unwrap();
+    }
+    let env = TestEnv::builder().with_admin().build().await;
+
+    for (key, value) in [
+        ("backup_schedule", "every night"),
+        ("backup_schedule", "0 3 * * 8"),
+        ("backup_retention_count", "0"),
+    ] {
+        let (status, _) = env
+            .request(
+                "PUT",
+                &format!("/api/settings/{}", key),
+                Some(env.cookie("admin")),
+                Some(json!({ "value": value })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} = {}", key, value);
+    }
+    for (key, value) in [
+        ("backup_schedule", "0 3 * * *"),
+        ("backup_retention_count", "2"),
+    ] {
+        let (status, _) = env
+            .request(
+                "PUT",
+                &format!("/api/settings/{}", key),
+                Some(env.cookie("admin")),
+                Some(json!({ "value": value })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{} = {}", key, value);
+    }
+
+    let file = backup::run_scheduled(&env.db).await.unwrap();
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/system/backups",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [file.name.as_str(), "kubarr-backup-20200102-000000.tar.gz"]
+    );
+
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/system/backups/{}", file.name))
+                .header("Cookie", env.cookie("admin"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let archive = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(archive.len() as u64, file.size_bytes);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/system/backups/kubarr-backup-x.tar.gz",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The scheduled backup restores with the configured passphrase
+    let (status, body) = restore(&env, env.cookie("admin"), &archive, PASSPHRASE).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
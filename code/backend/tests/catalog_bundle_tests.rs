@@ -0,0 +1,141 @@
+//! Integration tests for air-gapped mode
+//!
+//! Covers:
+//! - `POST /api/apps/catalog/import-bundle` — requires system.manage, rejects
+//!   bundles with invalid charts, imports valid ones into the catalog
+//! - `KUBARR_OFFLINE=true` refusing on-demand chart sync and being reported
+//!   by `GET /api/system/version`
+
+use std::io::Write;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::Value;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::audit_log;
+
+const CHART: &str = r#"apiVersion: v2
+name: sonarr
+version: 4.0.1
+annotations:
+  kubarr.io/category: media
+  kubarr.io/display-name: Sonarr
+"#;
+
+/// Charts directory and offline mode; `CONFIG` reads them once, so they are
+/// set before anything else in the test
+fn configure() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("kubarr_charts_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("KUBARR_CHARTS_DIR", &dir);
+    std::env::set_var("KUBARR_OFFLINE", "true");
+    dir
+}
+
+/// Gzipped ustar archive of the given files
+fn bundle(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut tar = Vec::new();
+    for (name, data) in files {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+        tar.extend_from_slice(&header);
+        tar.extend_from_slice(data.as_bytes());
+        tar.extend(std::iter::repeat_n(0u8, (512 - data.len() % 512) % 512));
+    }
+    tar.extend_from_slice(&[0u8; 1024]);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(&tar).unwrap();
+    encoder.finish().unwrap()
+}
+
+async fn import(env: &TestEnv, cookie: &str, bundle: Vec<u8>) -> (StatusCode, Value) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/apps/catalog/import-bundle")
+                .method("POST")
+                .header("content-type", "application/gzip")
+                .header("Cookie", cookie)
+                .body(Body::from(bundle))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+#[tokio::test]
+async fn test_offline_bundle_import() {
+    let charts_dir = configure();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let admin = env.cookie("admin");
+
+    let (_, version) = env.request("GET", "/api/system/version", None, None).await;
+    assert_eq!(version["offline"], true);
+    let (status, body) = env
+        .request("POST", "/api/apps/sync", Some(admin), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+    let valid = bundle(&[
+        ("kubarr-bundle/sonarr/Chart.yaml", CHART),
+        ("kubarr-bundle/sonarr/icon.svg", "<svg></svg>"),
+    ]);
+    let (status, _) = import(&env, env.cookie("viewer"), valid.clone()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = import(
+        &env,
+        admin,
+        bundle(&[
+            ("kubarr-bundle/sonarr/Chart.yaml", CHART),
+            ("kubarr-bundle/Radarr/Chart.yaml", CHART),
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.to_string().contains("Radarr"), "{}", body);
+    assert!(!charts_dir.join("sonarr").exists());
+
+    let (status, body) = import(&env, admin, valid).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["charts"][0]["name"], "sonarr");
+    assert_eq!(body["charts"][0]["version"], "4.0.1");
+
+    let (status, app) = env
+        .request("GET", "/api/apps/catalog/sonarr", Some(admin), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(app["display_name"], "Sonarr");
+    assert!(charts_dir.join("sonarr/icon.svg").exists());
+
+    let entry = audit_log::Entity::find()
+        .filter(audit_log::Column::ResourceId.eq("catalog"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("the import should be audited");
+    assert!(entry.details.unwrap().contains("bundle_imported"));
+}
@@ -10,7 +10,6 @@
 //! before any test runs via a once_cell::sync::Lazy static.
 
 use std::fs;
-use std::path::PathBuf;
 
 use once_cell::sync::Lazy;
 use tempfile::TempDir;
@@ -82,11 +81,12 @@ fn create_chart_in(
     let chart_dir = parent.join(name);
     fs::create_dir_all(&chart_dir).expect("create chart dir");
 
+    let display_name = format!("{} App", name);
     let chart_yaml = format!(
         "apiVersion: v2\nname: {name}\ndescription: A test app\nversion: 1.0.0\nannotations:\n  kubarr.io/category: {category}\n  kubarr.io/display-name: \"{display_name}\"\n  kubarr.io/icon: \"📦\"\n{extra}",
         name = name,
         category = category,
-        display_name = format!("{} App", name),
+        display_name = display_name,
         extra = extra_annotations,
     );
     fs::write(chart_dir.join("Chart.yaml"), chart_yaml).expect("write Chart.yaml");
@@ -220,6 +220,8 @@ fn catalog_with_apps_correctly_represents_loaded_data() {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     };
     apps.insert("sonarr".to_string(), config);
 
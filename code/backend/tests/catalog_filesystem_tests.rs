@@ -40,6 +40,8 @@ fn make_app(name: &str, category: &str) -> AppConfig {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     }
 }
 
@@ -496,6 +498,8 @@ fn test_app_config_with_environment_variables() {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     };
 
     assert_eq!(app.environment_variables.len(), 3);
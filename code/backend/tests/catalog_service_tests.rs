@@ -6,6 +6,7 @@
 //! - AppCatalog::with_apps() (test constructor)
 //! - get_all_apps() / get_app() / get_apps_by_category()
 //! - app_exists() / get_categories()
+//! - is_valid_instance_suffix() (cloned app instances)
 //! - reload() (with empty charts dir)
 //! - AppCatalog::new() (with non-existent charts dir → empty catalog)
 //! - DeploymentRequest deserialization
@@ -13,7 +14,9 @@
 
 use std::collections::HashMap;
 
-use kubarr::services::catalog::{AppCatalog, AppConfig, ResourceRequirements, VolumeConfig};
+use kubarr::services::catalog::{
+    is_valid_instance_suffix, AppCatalog, AppConfig, ResourceRequirements, VolumeConfig,
+};
 use kubarr::services::deployment::{DeploymentRequest, DeploymentStatus};
 
 // ============================================================================
@@ -40,6 +43,8 @@ fn make_app(name: &str, category: &str) -> AppConfig {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     }
 }
 
@@ -221,6 +226,21 @@ fn get_all_apps_returns_reference_to_configs() {
     assert_eq!(apps[0].category, "media");
 }
 
+// ============================================================================
+// Cloned instances
+// ============================================================================
+
+#[test]
+fn instance_suffix_validation() {
+    assert!(is_valid_instance_suffix("4k"));
+    assert!(is_valid_instance_suffix("family-2"));
+    assert!(!is_valid_instance_suffix(""));
+    assert!(!is_valid_instance_suffix("4K"));
+    assert!(!is_valid_instance_suffix("-4k"));
+    assert!(!is_valid_instance_suffix("4k-"));
+    assert!(!is_valid_instance_suffix(&"a".repeat(21)));
+}
+
 // ============================================================================
 // reload (with non-existent charts dir → stays empty)
 // ============================================================================
@@ -433,6 +453,8 @@ fn app_config_with_volumes_and_env() {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     };
 
     assert_eq!(app.volumes.len(), 2);
@@ -27,6 +27,8 @@ fn test_app_config_serialization() {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     };
 
     let json = serde_json::to_string(&config).unwrap();
@@ -101,6 +103,8 @@ fn test_app_catalog_get_app() {
             is_system: false,
             is_hidden: false,
             is_browseable: true,
+            architectures: vec![],
+            version: String::new(),
         },
     );
 
@@ -136,6 +140,8 @@ fn test_app_catalog_get_apps_by_category() {
             is_system: false,
             is_hidden: false,
             is_browseable: true,
+            architectures: vec![],
+            version: String::new(),
         },
     );
 
@@ -160,6 +166,8 @@ fn test_app_catalog_get_apps_by_category() {
             is_system: false,
             is_hidden: false,
             is_browseable: true,
+            architectures: vec![],
+            version: String::new(),
         },
     );
 
@@ -197,6 +205,8 @@ fn make_app(name: &str, category: &str) -> AppConfig {
         is_system: false,
         is_hidden: false,
         is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
     }
 }
 
@@ -306,6 +316,8 @@ fn test_app_with_volumes_and_env() {
         is_system: true,
         is_hidden: true,
         is_browseable: false,
+        architectures: vec![],
+        version: String::new(),
     };
 
     assert_eq!(app.volumes.len(), 2);
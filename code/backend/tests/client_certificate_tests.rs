@@ -0,0 +1,224 @@
+//! Integration tests for client certificate authentication
+//!
+//! Covers:
+//! - `GET/POST /api/users/{user_id}/client-certificates` and
+//!   `DELETE /api/users/{user_id}/client-certificates/{certificate_id}` —
+//!   validation, duplicates and permissions
+//! - requests carrying a verified client certificate from the ingress being
+//!   authenticated as the user it is registered for, as PEM or base64 DER
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+
+/// `CN=backup-bot, O=Homelab`, valid from 2026-10-18 to 2036-10-15
+const BOT_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBojCCAUmgAwIBAgIUJgfDaGjPBM12UZUq+1WNzEMic3swCgYIKoZIzj0EAwIw
+JzETMBEGA1UEAwwKYmFja3VwLWJvdDEQMA4GA1UECgwHSG9tZWxhYjAeFw0yNjEw
+MTgxNzA3MjJaFw0zNjEwMTUxNzA3MjJaMCcxEzARBgNVBAMMCmJhY2t1cC1ib3Qx
+EDAOBgNVBAoMB0hvbWVsYWIwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAATZvoQs
+Tpuz/XThHq2tcdOJDLWM7JoAfYblwbZWWikICEUgWPQW9bhFRNSLD2reNPs6waqI
+/aj2+V5fUMzJE/gjo1MwUTAdBgNVHQ4EFgQUkPhRC0sLmD00S4a8k34bMQEvpccw
+HwYDVR0jBBgwFoAUkPhRC0sLmD00S4a8k34bMQEvpccwDwYDVR0TAQH/BAUwAwEB
+/zAKBggqhkjOPQQDAgNHADBEAiB/PDaiaw36LmVzJNJzepu8YHJqBLsQQPY3co4t
+hOedxQIgAVB5yDTOu10yEb5CC6j/eezUL0zQUIB8dYB2ABnG8jU=
+-----END CERTIFICATE-----
+";
+
+/// `CN=stranger`, never registered
+const STRANGER_CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBezCCASGgAwIBAgIUatcOQhXANJ4J1haiqOVyp3JdIL4wCgYIKoZIzj0EAwIw
+EzERMA8GA1UEAwwIc3RyYW5nZXIwHhcNMjYxMDE4MTcwNzIyWhcNMzYxMDE1MTcw
+NzIyWjATMREwDwYDVQQDDAhzdHJhbmdlcjBZMBMGByqGSM49AgEGCCqGSM49AwEH
+A0IABGPEwUvG+tPKnLMwFPzTvz2k4c9lHaPWet4U84iWBxsPB7hVrbVjSWYWuebr
+40W/q+8pedkBe4Et+xeK9WEET3ujUzBRMB0GA1UdDgQWBBTAMGHQsoNOpQbUKsvn
+xphCxCiszTAfBgNVHSMEGDAWgBTAMGHQsoNOpQbUKsvnxphCxCiszTAPBgNVHRMB
+Af8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIQDIC8V6fGysexgVXEL6JRwK3HJS
+dAMaj7+ZD8YS20951wIgH4jB1LlvBiGGuebwSHAolfyDSyQMy+uuA7UWUut0mI0=
+-----END CERTIFICATE-----
+";
+
+const BOT_FINGERPRINT: &str = "d0afe4f7dbf3bfba81b9f6332079ea07569e49464e9c030b79166c75ec91fcac";
+
+/// `CONFIG` reads the header names once, so they are set before anything
+/// else in the test
+fn configure() {
+    std::env::set_var("KUBARR_CLIENT_CERT_HEADER", "ssl-client-cert");
+}
+
+/// `GET /api/users/me` with the headers the ingress would add
+async fn whoami(env: &TestEnv, headers: &[(&str, &str)]) -> (StatusCode, Value) {
+    let mut builder = Request::builder().uri("/api/users/me").method("GET");
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let response = env
+        .router
+        .clone()
+        .oneshot(builder.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn register(env: &TestEnv, user_id: i64, pem: &str) -> (StatusCode, Value) {
+    env.request(
+        "POST",
+        &format!("/api/users/{}/client-certificates", user_id),
+        Some(env.cookie("admin")),
+        Some(json!({ "name": "backup script", "pem": pem })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_register_client_certificates() {
+    configure();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let viewer_id = env.user("viewer").user.id;
+
+    let (status, body) = register(&env, viewer_id, BOT_CERT).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["subject"], "CN=backup-bot, O=Homelab");
+    assert_eq!(body["fingerprint"], BOT_FINGERPRINT);
+    assert_eq!(body["status"], "valid");
+    assert!(body["last_used_at"].is_null());
+
+    // One certificate maps to one user
+    let (status, _) = register(&env, env.user("admin").user.id, BOT_CERT).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = register(&env, viewer_id, &format!("{}{}", STRANGER_CERT, BOT_CERT)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = register(&env, viewer_id, "not a certificate").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = register(&env, 9999, STRANGER_CERT).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            &format!("/api/users/{}/client-certificates", viewer_id),
+            Some(env.cookie("viewer")),
+            Some(json!({ "name": "mine", "pem": STRANGER_CERT })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/client-certificates", viewer_id),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_verified_client_certificate_authenticates_its_user() {
+    configure();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let viewer_id = env.user("viewer").user.id;
+    let (_, created) = register(&env, viewer_id, BOT_CERT).await;
+
+    let pem = urlencoding::encode(BOT_CERT).into_owned();
+    let (status, body) = whoami(
+        &env,
+        &[("ssl-client-cert", &pem), ("ssl-client-verify", "SUCCESS")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["username"], "viewer");
+
+    // Traefik forwards base64 DER
+    let der: String = BOT_CERT
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = urlencoding::encode(&der).into_owned();
+    let (status, body) = whoami(
+        &env,
+        &[("ssl-client-cert", &der), ("ssl-client-verify", "SUCCESS")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["username"], "viewer");
+
+    let (_, list) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/client-certificates", viewer_id),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert!(!list[0]["last_used_at"].is_null());
+
+    // The ingress did not verify it
+    let (status, _) = whoami(
+        &env,
+        &[
+            ("ssl-client-cert", &pem),
+            ("ssl-client-verify", "FAILED:expired"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = whoami(&env, &[("ssl-client-cert", &pem)]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let stranger = urlencoding::encode(STRANGER_CERT).into_owned();
+    let (status, _) = whoami(
+        &env,
+        &[
+            ("ssl-client-cert", &stranger),
+            ("ssl-client-verify", "SUCCESS"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // A session takes precedence over the certificate
+    let (status, body) = whoami(
+        &env,
+        &[
+            ("cookie", env.cookie("admin")),
+            ("ssl-client-cert", &pem),
+            ("ssl-client-verify", "SUCCESS"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["username"], "admin");
+
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!(
+                "/api/users/{}/client-certificates/{}",
+                viewer_id, created["id"]
+            ),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = whoami(
+        &env,
+        &[("ssl-client-cert", &pem), ("ssl-client-verify", "SUCCESS")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -85,7 +85,7 @@ async fn authenticated_request(
     let content_type = "application/json";
     let body_bytes = body.map(|b| b.to_string()).unwrap_or_default();
 
-    let mut builder = Request::builder()
+    let builder = Request::builder()
         .uri(uri)
         .method(method)
         .header("Cookie", cookie)
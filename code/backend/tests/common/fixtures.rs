@@ -0,0 +1,333 @@
+//! Fixture builder for integration tests.
+//!
+//! Replaces hand-rolled user/role setup with a declarative builder:
+//!
+//! ```ignore
+//! let env = TestEnv::builder()
+//!     .with_admin()
+//!     .with_viewer()
+//!     .with_app("sonarr", AppStatus::Running)
+//!     .with_notifications()
+//!     .build()
+//!     .await;
+//! let (status, body) = env.request("GET", "/api/alerts", Some(env.cookie("admin")), None).await;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use tower::util::ServiceExt;
+
+use kubarr::application::dev_seed::{self, DEV_PASSWORD};
+use kubarr::endpoints::create_router;
+use kubarr::models::{role, role_app_permission, user};
+use kubarr::services::catalog::{AppCatalog, AppConfig, ResourceRequirements};
+use kubarr::services::helm::MockHelmEngine;
+use kubarr::services::k8s::FakeK8s;
+use kubarr::state::AppState;
+
+use super::{build_test_app_state_with_db, create_test_db_with_seed};
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+/// State of an app registered with [`TestEnvBuilder::with_app`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppStatus {
+    /// Healthy deployment with a running pod
+    Running,
+    /// Namespace exists but has no workloads
+    Stopped,
+    /// Deployment with no ready replicas and a pending pod
+    Failed,
+}
+
+/// An app known to the test environment
+#[derive(Debug, Clone)]
+pub struct TestApp {
+    pub name: String,
+    pub status: AppStatus,
+}
+
+/// A seeded user with a live session
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub user: user::Model,
+    pub role: String,
+    pub cookie: String,
+}
+
+/// Builder for [`TestEnv`]
+#[derive(Default)]
+pub struct TestEnvBuilder {
+    users: Vec<(String, String)>,
+    apps: Vec<TestApp>,
+    catalog_apps: Vec<String>,
+    notifications: bool,
+}
+
+impl TestEnvBuilder {
+    /// Add a user named `admin` with the admin role
+    pub fn with_admin(self) -> Self {
+        self.with_user("admin", "admin")
+    }
+
+    /// Add a user named `viewer` with the viewer role
+    pub fn with_viewer(self) -> Self {
+        self.with_user("viewer", "viewer")
+    }
+
+    /// Add a user with an arbitrary role
+    pub fn with_user(mut self, username: &str, role: &str) -> Self {
+        self.users.push((username.to_string(), role.to_string()));
+        self
+    }
+
+    /// Register an installed app in the catalog and the fake cluster; every
+    /// role used by the environment's users is granted access to it
+    pub fn with_app(mut self, name: &str, status: AppStatus) -> Self {
+        self.apps.push(TestApp {
+            name: name.to_string(),
+            status,
+        });
+        self
+    }
+
+    /// Register an app in the catalog without installing it
+    pub fn with_catalog_app(mut self, name: &str) -> Self {
+        self.catalog_apps.push(name.to_string());
+        self
+    }
+
+    /// Enable notifications for every event type
+    pub fn with_notifications(mut self) -> Self {
+        self.notifications = true;
+        self
+    }
+
+    pub async fn build(self) -> TestEnv {
+        ensure_jwt_keys().await;
+        let db = create_test_db_with_seed().await;
+
+        let mut created = Vec::with_capacity(self.users.len());
+        for (username, role) in &self.users {
+            let user = dev_seed::create_user(&db, username, DEV_PASSWORD, role, true)
+                .await
+                .expect("Failed to create fixture user");
+            created.push((user, role.clone()));
+        }
+
+        for app in &self.apps {
+            for (_, role_name) in &created {
+                grant_app(&db, role_name, &app.name).await;
+            }
+        }
+
+        if self.notifications {
+            dev_seed::enable_notification_events(&db)
+                .await
+                .expect("Failed to enable notification events");
+        }
+
+        let k8s = FakeK8s::new();
+        let mut catalog = HashMap::new();
+        for name in self
+            .catalog_apps
+            .iter()
+            .chain(self.apps.iter().map(|a| &a.name))
+        {
+            catalog.insert(name.clone(), catalog_entry(name));
+        }
+        for app in &self.apps {
+            match app.status {
+                AppStatus::Running => k8s.add_app(&app.name, true),
+                AppStatus::Failed => k8s.add_app(&app.name, false),
+                AppStatus::Stopped => k8s.add_namespace(&app.name),
+            }
+        }
+
+        let helm = MockHelmEngine::new();
+        let state = build_test_app_state_with_db(db.clone())
+            .await
+            .with_k8s_api(Arc::new(k8s.clone()))
+            .with_helm_engine(Arc::new(helm.clone()));
+        *state.catalog.write().await = AppCatalog::with_apps(catalog);
+        let router = create_router(state.clone());
+
+        let mut users = HashMap::new();
+        for (user, role) in created {
+            let cookie = login(router.clone(), &user.username, DEV_PASSWORD)
+                .await
+                .unwrap_or_else(|| panic!("Fixture user '{}' could not log in", user.username));
+            users.insert(user.username.clone(), TestUser { user, role, cookie });
+        }
+
+        TestEnv {
+            db,
+            state,
+            router,
+            k8s,
+            helm,
+            users,
+            apps: self.apps,
+        }
+    }
+}
+
+/// A router backed by a fresh database, a fake cluster and a mock Helm
+/// engine, with fixture data
+pub struct TestEnv {
+    pub db: DatabaseConnection,
+    pub state: AppState,
+    pub router: axum::Router,
+    pub k8s: FakeK8s,
+    pub helm: MockHelmEngine,
+    pub users: HashMap<String, TestUser>,
+    pub apps: Vec<TestApp>,
+}
+
+impl TestEnv {
+    pub fn builder() -> TestEnvBuilder {
+        TestEnvBuilder::default()
+    }
+
+    /// Look up a fixture user, panicking if it was not added to the builder
+    pub fn user(&self, username: &str) -> &TestUser {
+        self.users
+            .get(username)
+            .unwrap_or_else(|| panic!("No fixture user '{}'", username))
+    }
+
+    /// Log in through the router, returning the session cookie
+    pub async fn login(&self, username: &str, password: &str) -> Option<String> {
+        login(self.router.clone(), username, password).await
+    }
+
+    /// Session cookie for a fixture user
+    pub fn cookie(&self, username: &str) -> &str {
+        &self.user(username).cookie
+    }
+
+    /// Send a JSON request and return the status and parsed body
+    pub async fn request(
+        &self,
+        method: &str,
+        uri: &str,
+        cookie: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut builder = Request::builder()
+            .uri(uri)
+            .method(method)
+            .header("content-type", "application/json");
+        if let Some(c) = cookie {
+            builder = builder.header("Cookie", c);
+        }
+        let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+        let response = self
+            .router
+            .clone()
+            .oneshot(builder.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (status, json)
+    }
+}
+
+fn catalog_entry(name: &str) -> AppConfig {
+    AppConfig {
+        name: name.to_string(),
+        display_name: name.to_string(),
+        description: format!("{} test app", name),
+        icon: String::new(),
+        container_image: format!("{}:latest", name),
+        default_port: 8080,
+        resource_requirements: ResourceRequirements {
+            cpu_request: "100m".to_string(),
+            cpu_limit: "1000m".to_string(),
+            memory_request: "256Mi".to_string(),
+            memory_limit: "1Gi".to_string(),
+        },
+        volumes: vec![],
+        environment_variables: HashMap::new(),
+        category: "media".to_string(),
+        is_system: false,
+        is_hidden: false,
+        is_browseable: true,
+        architectures: vec![],
+        version: String::new(),
+    }
+}
+
+async fn grant_app(db: &DatabaseConnection, role_name: &str, app_name: &str) {
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let role = role::Entity::find()
+        .filter(role::Column::Name.eq(role_name))
+        .one(db)
+        .await
+        .unwrap()
+        .expect("Role not found");
+
+    let existing = role_app_permission::Entity::find()
+        .filter(role_app_permission::Column::RoleId.eq(role.id))
+        .filter(role_app_permission::Column::AppName.eq(app_name))
+        .one(db)
+        .await
+        .unwrap();
+    if existing.is_none() {
+        role_app_permission::ActiveModel {
+            role_id: Set(role.id),
+            app_name: Set(app_name.to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+}
+
+async fn login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
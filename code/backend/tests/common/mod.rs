@@ -5,6 +5,8 @@
 
 #![allow(dead_code)]
 
+pub mod fixtures;
+
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -303,6 +305,7 @@ pub async fn create_test_user_with_role(
     let user_role = user_role::ActiveModel {
         user_id: Set(user.id),
         role_id: Set(role.id),
+        ..Default::default()
     };
     user_role.insert(db).await.unwrap();
 
@@ -0,0 +1,74 @@
+//! Integration tests for response compression
+//!
+//! Covers:
+//! - brotli and gzip for JSON when the client accepts them
+//! - no compression without `Accept-Encoding` or below the minimum size
+
+use std::io::Read;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+
+/// GET `uri`, returning the status, `Content-Encoding` and raw body
+async fn get(
+    env: &TestEnv,
+    uri: &str,
+    accept: Option<&str>,
+) -> (StatusCode, Option<String>, Vec<u8>) {
+    let mut builder = Request::builder().uri(uri);
+    if let Some(accept) = accept {
+        builder = builder.header(header::ACCEPT_ENCODING, accept);
+    }
+    let response = env
+        .router
+        .clone()
+        .oneshot(builder.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let encoding = response
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, encoding, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn test_json_responses_are_compressed() {
+    let env = TestEnv::builder().build().await;
+
+    let (status, encoding, plain) = get(&env, "/api/openapi.json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(encoding, None);
+    serde_json::from_slice::<serde_json::Value>(&plain).unwrap();
+
+    let (_, encoding, gzipped) = get(&env, "/api/openapi.json", Some("gzip")).await;
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+    assert!(gzipped.len() < plain.len() / 4);
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(gzipped.as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, plain);
+
+    let (_, encoding, _) = get(&env, "/api/openapi.json", Some("gzip, deflate, br")).await;
+    assert_eq!(encoding.as_deref(), Some("br"));
+}
+
+#[tokio::test]
+async fn test_small_responses_are_not_compressed() {
+    let env = TestEnv::builder().build().await;
+
+    let (status, encoding, body) = get(&env, "/api/health", Some("gzip, br")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(encoding, None);
+    assert_eq!(body, b"OK");
+}
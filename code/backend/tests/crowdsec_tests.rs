@@ -0,0 +1,157 @@
+//! Integration tests for the CrowdSec and Fail2ban hooks
+//!
+//! Covers:
+//! - failed sign-ins written to `KUBARR_AUTH_FAILURE_LOG`
+//! - enforcing ban decisions from a mock CrowdSec Local API, and the
+//!   `crowdsec_lapi_url` setting
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{HeaderMap, Request, StatusCode},
+};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+
+/// Failure log and bouncer key; `CONFIG` reads them once, so they are set
+/// before anything else in the test
+fn configure() -> std::path::PathBuf {
+    let log = std::env::temp_dir().join(format!("kubarr_auth_failures_{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+    std::env::set_var("KUBARR_AUTH_FAILURE_LOG", &log);
+    std::env::set_var("KUBARR_CROWDSEC_LAPI_KEY", "bouncer-key");
+    log
+}
+
+/// Serve a Local API that bans 203.0.113.66 for callers with the bouncer
+/// key, recording the addresses it is asked about
+async fn spawn_lapi() -> (String, Arc<Mutex<Vec<String>>>) {
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let recorded = asked.clone();
+    let router = axum::Router::new().route(
+        "/v1/decisions",
+        axum::routing::get(
+            move |headers: HeaderMap,
+                  Query(params): Query<std::collections::HashMap<String, String>>| {
+                let recorded = recorded.clone();
+                async move {
+                    if headers.get("x-api-key").and_then(|k| k.to_str().ok()) != Some("bouncer-key")
+                    {
+                        return (StatusCode::FORBIDDEN, String::new());
+                    }
+                    let ip = params.get("ip").cloned().unwrap_or_default();
+                    recorded.lock().unwrap().push(ip.clone());
+                    let body = if ip == "203.0.113.66" {
+                        json!([{
+                            "id": 1,
+                            "origin": "crowdsec",
+                            "type": "ban",
+                            "scope": "Ip",
+                            "value": ip,
+                            "duration": "3h59m",
+                            "scenario": "crowdsecurity/http-probing",
+                        }])
+                        .to_string()
+                    } else {
+                        "null".to_string()
+                    };
+                    (StatusCode::OK, body)
+                }
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}", addr), asked)
+}
+
+/// Sign in as the viewer from an address and return the status
+async fn login_from(env: &TestEnv, ip: &str, password: &str) -> StatusCode {
+    let body = json!({"username": "viewer", "password": password}).to_string();
+    env.router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/auth/login")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", ip)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_crowdsec_hooks() {
+    let log = configure();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+
+    // Failed sign-ins are logged in the documented format
+    assert_eq!(
+        login_from(&env, "198.51.100.23", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    let lines = std::fs::read_to_string(&log).unwrap();
+    let line = lines.lines().last().unwrap();
+    assert!(
+        line.ends_with(r#" kubarr auth failure: user="viewer" ip=198.51.100.23 method=password"#),
+        "{}",
+        line
+    );
+
+    // Decisions are not checked until the Local API is configured
+    assert_eq!(
+        login_from(&env, "203.0.113.66", DEV_PASSWORD).await,
+        StatusCode::OK
+    );
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/crowdsec_lapi_url",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "crowdsec:8080"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (lapi_url, asked) = spawn_lapi().await;
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/settings/crowdsec_lapi_url",
+            Some(env.cookie("admin")),
+            Some(json!({"value": lapi_url})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    assert_eq!(
+        login_from(&env, "203.0.113.66", DEV_PASSWORD).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        login_from(&env, "198.51.100.24", DEV_PASSWORD).await,
+        StatusCode::OK
+    );
+    // Answers are cached per address
+    assert_eq!(
+        login_from(&env, "203.0.113.66", DEV_PASSWORD).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        *asked.lock().unwrap(),
+        ["203.0.113.66".to_string(), "198.51.100.24".to_string()]
+    );
+}
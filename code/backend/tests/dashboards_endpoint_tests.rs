@@ -0,0 +1,335 @@
+//! Integration tests for the custom dashboard endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/monitoring/dashboards`           — own and shared dashboards
+//! - `POST   /api/monitoring/dashboards`           — create (sharing needs monitoring.manage)
+//! - `GET    /api/monitoring/dashboards/{id}`      — get a visible dashboard
+//! - `PUT    /api/monitoring/dashboards/{id}`      — owner, or monitoring.manage for shared
+//! - `DELETE /api/monitoring/dashboards/{id}`      — owner, or monitoring.manage for shared
+//! - `GET    /api/monitoring/dashboards/{id}/data` — run panel queries
+//!
+//! VictoriaMetrics is absent in tests, so panel data comes back without series.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    admin: String,
+    viewer: String,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only)
+async fn setup() -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "dash_admin",
+        "dash_admin@example.com",
+        "pass123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "dash_viewer",
+        "dash_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "dash_admin", "pass123")
+        .await
+        .unwrap();
+    let viewer = do_login(app.clone(), "dash_viewer", "pass123")
+        .await
+        .unwrap();
+    TestContext { app, admin, viewer }
+}
+
+fn cpu_panel() -> serde_json::Value {
+    serde_json::json!({
+        "title": "CPU by app",
+        "query": "sum by (namespace) (rate(container_cpu_usage_seconds_total[5m]))",
+        "chart_type": "area",
+        "time_range": "6h"
+    })
+}
+
+async fn create_dashboard(ctx: &TestContext, cookie: &str, body: serde_json::Value) -> i64 {
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/monitoring/dashboards",
+        Some(cookie),
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create failed: {}", json);
+    json["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_dashboards_require_auth() {
+    let ctx = setup().await;
+    let (status, _) = make_request(ctx.app, "GET", "/api/monitoring/dashboards", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_creates_private_dashboard() {
+    let ctx = setup().await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/monitoring/dashboards",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": " My apps ", "panels": [cpu_panel()]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "My apps");
+    assert_eq!(json["shared"], false);
+    assert_eq!(json["can_edit"], true);
+    assert_eq!(json["panels"][0]["chart_type"], "area");
+    let id = json["id"].as_i64().unwrap();
+
+    // Private dashboards are invisible to other users, even admins
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/monitoring/dashboards/{}", id),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, json) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/dashboards",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert!(json.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_create_dashboard_validation() {
+    let ctx = setup().await;
+
+    for body in [
+        serde_json::json!({"name": "  "}),
+        serde_json::json!({"name": "x", "panels": [{"title": "x", "query": "sum(up)"}]}),
+        serde_json::json!({"name": "x", "panels": [{"title": "x", "query": "count({__name__=~\".+\"})"}]}),
+        serde_json::json!({"name": "x", "panels": [{"title": "x", "query": "container_last_seen", "chart_type": "pie"}]}),
+        serde_json::json!({"name": "x", "panels": [{"title": "x", "query": "container_last_seen", "time_range": "90d"}]}),
+    ] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "POST",
+            "/api/monitoring/dashboards",
+            Some(&ctx.viewer),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "body: {}", body);
+    }
+
+    let (_, json) = make_request(
+        ctx.app,
+        "POST",
+        "/api/monitoring/dashboards",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": "x", "panels": [cpu_panel(), {"title": "Bad", "query": "secret_metric"}]})),
+    )
+    .await;
+    assert!(json["detail"].to_string().contains("Panel 2"), "{}", json);
+}
+
+#[tokio::test]
+async fn test_shared_dashboards_require_manage() {
+    let ctx = setup().await;
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/monitoring/dashboards",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": "Team", "shared": true})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let id = create_dashboard(
+        &ctx,
+        &ctx.admin,
+        serde_json::json!({"name": "Cluster", "shared": true, "panels": [cpu_panel()]}),
+    )
+    .await;
+    let uri = format!("/api/monitoring/dashboards/{}", id);
+
+    // Viewers see shared dashboards read-only
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/dashboards",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(json[0]["name"], "Cluster");
+    assert_eq!(json[0]["can_edit"], false);
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": "Mine now"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_request(ctx.app.clone(), "DELETE", &uri, Some(&ctx.viewer), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"shared": false, "panels": []})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["shared"], false);
+    assert!(json["panels"].as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(ctx.app, "GET", &uri, Some(&ctx.viewer), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_dashboard_data_and_delete() {
+    let ctx = setup().await;
+    let id = create_dashboard(
+        &ctx,
+        &ctx.viewer,
+        serde_json::json!({"name": "Memory", "panels": [cpu_panel(), {
+            "title": "Memory",
+            "query": "sum(container_memory_working_set_bytes)",
+            "unit": "bytes"
+        }]}),
+    )
+    .await;
+    let uri = format!("/api/monitoring/dashboards/{}", id);
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("{}/data", uri),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["dashboard_id"], id);
+    let panels = json["panels"].as_array().unwrap();
+    assert_eq!(panels.len(), 2);
+    assert_eq!(panels[1]["title"], "Memory");
+    assert_eq!(panels[1]["chart_type"], "line");
+    assert_eq!(panels[1]["time_range"], "1h");
+    assert_eq!(panels[1]["unit"], "bytes");
+    assert!(panels[1]["series"].as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(ctx.app.clone(), "DELETE", &uri, Some(&ctx.viewer), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(ctx.app, "GET", &uri, Some(&ctx.viewer), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,297 @@
+//! Integration tests for deprovisioning
+//!
+//! Covers:
+//! - `POST /api/users/{user_id}/deprovision` dry runs and the active-user guard
+//! - deactivating a user disables their linked Jellyfin account and revokes
+//!   their sessions, in the background
+//! - deleting a user deletes their linked Jellyseerr account and returns
+//!   the report
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{extract::Path, Json};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::{audit_log, role};
+
+type Calls = Arc<Mutex<Vec<String>>>;
+
+/// Serve a Jellyfin and a Jellyseerr that record the changes made to them
+async fn spawn_apps(calls: Calls) -> String {
+    let policy_calls = calls.clone();
+    let delete_calls = calls.clone();
+    let permission_calls = calls;
+    let router = axum::Router::new()
+        .route(
+            "/Users",
+            axum::routing::get(|| async {
+                Json(json!([
+                    {"Id": "jf-alice", "Name": "alice", "Policy": {"IsAdministrator": false}}
+                ]))
+            }),
+        )
+        .route(
+            "/Users/{id}/Policy",
+            axum::routing::post(move |Path(id): Path<String>, Json(policy): Json<Value>| {
+                let calls = policy_calls.clone();
+                async move {
+                    assert_eq!(policy["IsAdministrator"], false);
+                    calls
+                        .lock()
+                        .unwrap()
+                        .push(format!("policy {} disabled={}", id, policy["IsDisabled"]));
+                }
+            }),
+        )
+        .route(
+            "/api/v1/user/{id}",
+            axum::routing::delete(move |Path(id): Path<String>| {
+                let calls = delete_calls.clone();
+                async move {
+                    calls.lock().unwrap().push(format!("delete {}", id));
+                }
+            }),
+        )
+        .route(
+            "/api/v1/user/{id}/settings/permissions",
+            axum::routing::post(move |Path(id): Path<String>| {
+                let calls = permission_calls.clone();
+                async move {
+                    calls.lock().unwrap().push(format!("permissions {}", id));
+                    Json(json!({"permissions": 0}))
+                }
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// Point both integrations at the mock, link alice's accounts and give
+/// viewers a profile that disables Jellyfin and deletes Jellyseerr accounts
+async fn configure(env: &TestEnv, calls: Calls) {
+    let base_url = spawn_apps(calls).await;
+    for app in ["jellyfin", "jellyseerr"] {
+        let (status, body) = env
+            .request(
+                "PUT",
+                &format!("/api/integrations/apps/{}", app),
+                Some(env.cookie("admin")),
+                Some(json!({"base_url": base_url, "credentials": {"api_key": "key"}})),
+            )
+            .await;
+        assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+    }
+    let alice = env.user("alice").user.id;
+    for (app, id) in [("jellyfin", "jf-alice"), ("jellyseerr", "7")] {
+        let (status, _) = env
+            .request(
+                "PUT",
+                &format!("/api/users/{}/accounts/{}", alice, app),
+                Some(env.cookie("admin")),
+                Some(json!({"external_id": id})),
+            )
+            .await;
+        assert_eq!(status, axum::http::StatusCode::OK);
+    }
+
+    let viewer = role::Entity::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/provisioning", viewer.id),
+            Some(env.cookie("admin")),
+            Some(json!({"apps": ["jellyfin"], "on_deactivate": "disable"})),
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK);
+    let admin = role::Entity::find()
+        .filter(role::Column::Name.eq("admin"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/provisioning", admin.id),
+            Some(env.cookie("admin")),
+            Some(json!({"apps": ["jellyseerr"], "on_deactivate": "delete"})),
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK);
+}
+
+fn account_statuses(report: &Value) -> Vec<(String, String)> {
+    report["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["app_name"].as_str().unwrap().to_string(),
+                r["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_dry_run_and_active_guard() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .build()
+        .await;
+    let calls = Calls::default();
+    configure(&env, calls.clone()).await;
+    let alice = env.user("alice").user.id;
+    let uri = format!("/api/users/{}/deprovision", alice);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("alice")),
+            Some(json!({"dry_run": true})),
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::FORBIDDEN);
+
+    let (status, report) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"dry_run": true})),
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK, "{}", report);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        account_statuses(&report),
+        vec![("jellyfin".to_string(), "would_disable".to_string())]
+    );
+    assert!(report["sessions_revoked"].as_u64().unwrap() >= 1);
+    assert!(calls.lock().unwrap().is_empty());
+
+    // Alice's session still works after the dry run
+    let (status, _) = env
+        .request("GET", "/api/users/me", Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK);
+
+    let (status, _) = env
+        .request("POST", &uri, Some(env.cookie("admin")), Some(json!({})))
+        .await;
+    assert_eq!(status, axum::http::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deactivation_disables_accounts() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .build()
+        .await;
+    let calls = Calls::default();
+    configure(&env, calls.clone()).await;
+    let alice = env.user("alice").user.id;
+
+    let (status, _) = env
+        .request(
+            "PATCH",
+            &format!("/api/users/{}", alice),
+            Some(env.cookie("admin")),
+            Some(json!({"is_active": false})),
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK);
+
+    let mut entry = None;
+    for _ in 0..50 {
+        entry = audit_log::Entity::find()
+            .filter(audit_log::Column::Action.eq("user_deactivated"))
+            .one(&env.db)
+            .await
+            .unwrap();
+        if entry.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let entry = entry.expect("deactivation should be audited");
+    let details: Value = serde_json::from_str(entry.details.as_deref().unwrap()).unwrap();
+    let report = &details["deprovisioning"];
+    assert_eq!(
+        account_statuses(report),
+        vec![("jellyfin".to_string(), "disabled".to_string())]
+    );
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["policy jf-alice disabled=true".to_string()]
+    );
+
+    let (status, _) = env
+        .request("GET", "/api/users/me", Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(status, axum::http::StatusCode::UNAUTHORIZED);
+
+    // Disabled accounts stay linked
+    let (_, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(links.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_deletion_deletes_accounts() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "admin")
+        .build()
+        .await;
+    let calls = Calls::default();
+    configure(&env, calls.clone()).await;
+    let alice = env.user("alice").user.id;
+
+    let (status, body) = env
+        .request(
+            "DELETE",
+            &format!("/api/users/{}", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+    assert_eq!(
+        account_statuses(&body["deprovisioning"]),
+        vec![("jellyseerr".to_string(), "deleted".to_string())]
+    );
+    assert_eq!(*calls.lock().unwrap(), vec!["delete 7".to_string()]);
+
+    let entry = audit_log::Entity::find()
+        .filter(audit_log::Column::Action.eq("user_deleted"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("deletion should be audited");
+    assert_eq!(entry.resource_id, Some(alice.to_string()));
+}
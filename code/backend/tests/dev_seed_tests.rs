@@ -0,0 +1,138 @@
+//! Integration tests for the `seed-dev-data` subcommand and the `TestEnv`
+//! fixture builder in `tests/common/fixtures.rs`.
+
+use axum::http::StatusCode;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::application::dev_seed::{self, DEV_PASSWORD};
+use kubarr::models::{audit_log, role, role_app_permission, user, user_notification};
+
+#[tokio::test]
+async fn test_builder_creates_logged_in_users() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+
+    assert_eq!(env.user("admin").role, "admin");
+    assert_eq!(env.user("viewer").role, "viewer");
+
+    let (status, body) = env
+        .request("GET", "/api/users/me", Some(env.cookie("viewer")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["username"], "viewer");
+
+    let (status, _) = env
+        .request("GET", "/api/users", Some(env.cookie("viewer")), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request("GET", "/api/users", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_builder_grants_apps_to_user_roles() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Stopped)
+        .build()
+        .await;
+
+    assert_eq!(env.apps.len(), 2);
+    assert_eq!(env.apps[1].status, AppStatus::Stopped);
+
+    let viewer = role::Entity::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let apps: Vec<String> = role_app_permission::Entity::find()
+        .filter(role_app_permission::Column::RoleId.eq(viewer.id))
+        .all(&env.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.app_name)
+        .collect();
+    assert!(apps.contains(&"sonarr".to_string()));
+    assert!(apps.contains(&"radarr".to_string()));
+}
+
+#[tokio::test]
+async fn test_builder_enables_notifications() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_notifications()
+        .build()
+        .await;
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/notifications/events",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let events = body.as_array().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.iter().all(|e| e["enabled"] == true));
+}
+
+#[tokio::test]
+async fn test_seed_dev_data_populates_database() {
+    let env = TestEnv::builder().build().await;
+
+    let summary = dev_seed::seed_dev_data(&env.db)
+        .await
+        .unwrap()
+        .expect("Fresh database should be seeded");
+    assert_eq!(summary.users, 4);
+    assert!(summary.audit_logs > 0);
+    assert!(summary.notifications > 0);
+
+    let audit_count = audit_log::Entity::find().count(&env.db).await.unwrap();
+    assert_eq!(audit_count as usize, summary.audit_logs);
+
+    // Pending users get no history
+    let pending = user::Entity::find()
+        .filter(user::Column::IsApproved.eq(false))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let pending_logs = audit_log::Entity::find()
+        .filter(audit_log::Column::UserId.eq(pending.id))
+        .count(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(pending_logs, 0);
+
+    // Inboxes have a mix of read and unread entries
+    let unread = user_notification::Entity::find()
+        .filter(user_notification::Column::Read.eq(false))
+        .count(&env.db)
+        .await
+        .unwrap();
+    assert!(unread > 0);
+    assert!((unread as usize) < summary.notifications);
+
+    let cookie = env.login("dev-admin", DEV_PASSWORD).await;
+    assert!(cookie.is_some());
+}
+
+#[tokio::test]
+async fn test_seed_dev_data_is_idempotent() {
+    let env = TestEnv::builder().build().await;
+
+    assert!(dev_seed::seed_dev_data(&env.db).await.unwrap().is_some());
+    let users = user::Entity::find().count(&env.db).await.unwrap();
+
+    assert!(dev_seed::seed_dev_data(&env.db).await.unwrap().is_none());
+    assert_eq!(user::Entity::find().count(&env.db).await.unwrap(), users);
+}
@@ -0,0 +1,186 @@
+//! Integration tests for `src/services/crypto.rs`
+//!
+//! Covers:
+//! - OAuth client secrets and SMTP passwords saved through the API are
+//!   encrypted in the database and still used by the services
+//! - rows stored before the key was set are encrypted by `encrypt_existing`
+//! - backups of encrypted columns reveal and conceal them again
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+
+use kubarr::models::prelude::*;
+use kubarr::models::vpn_provider::VpnType;
+use kubarr::models::{oauth_provider, vpn_provider};
+use kubarr::services::crypto;
+
+/// Every test uses the same key, so tests running in parallel agree on it
+const KEY: [u8; 32] = [42; 32];
+
+#[tokio::test]
+async fn test_credentials_saved_through_the_api_are_encrypted() {
+    crypto::set_key(Some(KEY));
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_notifications()
+        .build()
+        .await;
+    let cookie = env.cookie("admin");
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/oauth/providers/google",
+            Some(cookie),
+            Some(json!({"client_id": "kubarr", "client_secret": "oauth-s3cret"})),
+        )
+        .await;
+    assert!(status.is_success(), "{}", body);
+    assert_eq!(body["has_secret"], true);
+    let stored = OauthProvider::find_by_id("google")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .client_secret
+        .unwrap();
+    assert!(crypto::is_encrypted(&stored));
+    assert_eq!(crypto::decrypt(&stored).unwrap(), "oauth-s3cret");
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/notifications/channels/email",
+            Some(cookie),
+            Some(json!({
+                "enabled": true,
+                "config": {
+                    "smtp_host": "smtp.example.com",
+                    "smtp_port": 587,
+                    "username": "kubarr",
+                    "password": "smtp-s3cret",
+                    "from_address": "kubarr@example.com"
+                }
+            })),
+        )
+        .await;
+    assert!(status.is_success(), "{}", body);
+    assert_eq!(body["config"]["password"], "********");
+    let channel = NotificationChannel::find()
+        .all(&env.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|c| c.channel_type == "email")
+        .unwrap();
+    assert!(!channel.config.contains("smtp-s3cret"));
+    assert!(channel.config.contains("smtp.example.com"));
+    assert_eq!(
+        crypto::decrypt_config(&channel.config).unwrap()["password"],
+        "smtp-s3cret"
+    );
+
+    // The provider was loaded from the decrypted config
+    let (_, status) = env
+        .request(
+            "GET",
+            "/api/notifications/channels/status",
+            Some(cookie),
+            None,
+        )
+        .await;
+    let email = status
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["channel_type"] == "email")
+        .unwrap();
+    assert_eq!(email["initialized"], true, "{}", email);
+}
+
+#[tokio::test]
+async fn test_existing_credentials_are_encrypted_on_startup() {
+    crypto::set_key(Some(KEY));
+    let env = TestEnv::builder().build().await;
+    let now = Utc::now();
+
+    // Providers are seeded without a secret
+    let provider = OauthProvider::find_by_id("microsoft")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut provider: oauth_provider::ActiveModel = provider.into();
+    provider.client_secret = Set(Some("plain-secret".to_string()));
+    provider.update(&env.db).await.unwrap();
+    let vpn = vpn_provider::ActiveModel {
+        name: Set("Mullvad".to_string()),
+        vpn_type: Set(VpnType::WireGuard),
+        service_provider: Set(Some("mullvad".to_string())),
+        credentials_json: Set(r#"{"private_key":"wg-key"}"#.to_string()),
+        enabled: Set(true),
+        kill_switch: Set(true),
+        firewall_outbound_subnets: Set(String::new()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+
+    let changed = crypto::encrypt_existing(&env.db).await.unwrap();
+    assert!(changed >= 2, "{}", changed);
+    assert_eq!(crypto::encrypt_existing(&env.db).await.unwrap(), 0);
+
+    let stored = VpnProvider::find_by_id(vpn.id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .credentials_json;
+    assert!(crypto::is_encrypted(&stored));
+    assert_eq!(
+        crypto::decrypt(&stored).unwrap(),
+        r#"{"private_key":"wg-key"}"#
+    );
+    let secret = OauthProvider::find_by_id("microsoft")
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .client_secret
+        .unwrap();
+    assert_eq!(crypto::decrypt(&secret).unwrap(), "plain-secret");
+}
+
+#[test]
+fn test_columns_round_trip_for_backups() {
+    crypto::set_key(Some(KEY));
+    let config = r#"{"bot_token":"123:abc","chat_id":"42"}"#;
+
+    let concealed = crypto::conceal_column("notification_channels", "config", config).unwrap();
+    assert!(!concealed.contains("123:abc"));
+    assert!(concealed.contains(r#""chat_id":"42""#));
+    let revealed = crypto::reveal_column("notification_channels", "config", &concealed).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&revealed).unwrap(),
+        serde_json::from_str::<serde_json::Value>(config).unwrap()
+    );
+
+    let secret = crypto::conceal_column("oauth_providers", "client_secret", "s3cret").unwrap();
+    assert!(crypto::is_encrypted(&secret));
+    assert_eq!(
+        crypto::reveal_column("oauth_providers", "client_secret", &secret).unwrap(),
+        "s3cret"
+    );
+    // Other columns are left alone
+    assert_eq!(
+        crypto::conceal_column("registries", "password", "s3cret").unwrap(),
+        "s3cret"
+    );
+}
@@ -0,0 +1,328 @@
+//! Integration tests for the energy and cost estimation endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/monitoring/energy`                   — requires monitoring.view
+//! - `GET    /api/monitoring/energy/nodes`             — requires monitoring.view
+//! - `PUT    /api/monitoring/energy/nodes/{node_name}` — requires monitoring.manage
+//! - `DELETE /api/monitoring/energy/nodes/{node_name}` — requires monitoring.manage
+//!
+//! Rollups are written through `services::energy::record_energy`, as the
+//! hourly sampler would after estimating per-app watt-hours.
+
+use std::collections::HashMap;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use sea_orm::DatabaseConnection;
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+use kubarr::services::energy;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    db: DatabaseConnection,
+    admin: String,
+    viewer: String,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only)
+async fn setup() -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(&db, "en_admin", "en_admin@example.com", "pass123", "admin").await;
+    create_test_user_with_role(
+        &db,
+        "en_viewer",
+        "en_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "en_admin", "pass123").await.unwrap();
+    let viewer = do_login(app.clone(), "en_viewer", "pass123").await.unwrap();
+    TestContext {
+        app,
+        db,
+        admin,
+        viewer,
+    }
+}
+
+// ============================================================================
+// Authentication and permissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_energy_requires_auth() {
+    let ctx = setup().await;
+    let (status, _) = make_request(ctx.app, "GET", "/api/monitoring/energy", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_can_read_but_not_set_node_power() {
+    let ctx = setup().await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/energy/nodes",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["default_idle_watts"], 10.0);
+    assert_eq!(json["default_max_watts"], 40.0);
+    assert!(json["nodes"].as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(
+        ctx.app,
+        "PUT",
+        "/api/monitoring/energy/nodes/nuc",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"idle_watts": 6.0, "max_watts": 28.0})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Node power profiles
+// ============================================================================
+
+#[tokio::test]
+async fn test_node_power_profile_lifecycle() {
+    let ctx = setup().await;
+    let uri = "/api/monitoring/energy/nodes/nuc";
+
+    for body in [
+        serde_json::json!({"idle_watts": -1.0, "max_watts": 28.0}),
+        serde_json::json!({"idle_watts": 6.0, "max_watts": 0.0}),
+        serde_json::json!({"idle_watts": 30.0, "max_watts": 28.0}),
+        serde_json::json!({"idle_watts": 6.0, "max_watts": 50000.0}),
+    ] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "PUT",
+            uri,
+            Some(&ctx.admin),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    for max_watts in [25.0, 28.0] {
+        let (status, json) = make_request(
+            ctx.app.clone(),
+            "PUT",
+            uri,
+            Some(&ctx.admin),
+            Some(serde_json::json!({"idle_watts": 6.0, "max_watts": max_watts})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+    }
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/energy/nodes",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(
+        json["nodes"],
+        serde_json::json!([{"node_name": "nuc", "idle_watts": 6.0, "max_watts": 28.0}])
+    );
+
+    let (status, json) = make_request(ctx.app.clone(), "DELETE", uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["success"], true);
+
+    let (status, _) = make_request(ctx.app, "DELETE", uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Reporting
+// ============================================================================
+
+#[tokio::test]
+async fn test_energy_report_rolls_up_by_month() {
+    let ctx = setup().await;
+    let now = Utc::now();
+
+    // Two samples this month at different prices, one well over a year ago
+    let sample = HashMap::from([("plex".to_string(), 2000.0), ("system".to_string(), 500.0)]);
+    energy::record_energy(&ctx.db, &sample, 0.25, now)
+        .await
+        .unwrap();
+    let sample = HashMap::from([("plex".to_string(), 1000.0)]);
+    energy::record_energy(&ctx.db, &sample, 0.5, now)
+        .await
+        .unwrap();
+    energy::record_energy(&ctx.db, &sample, 0.25, now - Duration::days(500))
+        .await
+        .unwrap();
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/energy",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", json);
+    assert_eq!(json["period"], "month");
+    assert_eq!(json["currency"], "EUR");
+    assert_eq!(json["power_source"], "model");
+    assert_eq!(json["total_kwh"], 3.5);
+    assert_eq!(json["total_cost"], 1.13);
+
+    let apps = json["apps"].as_array().unwrap();
+    assert_eq!(apps.len(), 2);
+    assert_eq!(apps[0]["app_name"], "plex");
+    assert_eq!(apps[0]["energy_kwh"], 3.0);
+    assert_eq!(apps[0]["cost"], 1.0);
+    assert_eq!(apps[0]["history"].as_array().unwrap().len(), 1);
+    assert_eq!(apps[1]["app_name"], "system");
+
+    // The daily view sees the same samples
+    let (status, json) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/energy?period=day&count=1",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["totals"].as_array().unwrap().len(), 1);
+    assert_eq!(json["total_kwh"], 3.5);
+}
+
+#[tokio::test]
+async fn test_energy_report_uses_price_settings() {
+    let ctx = setup().await;
+
+    for (key, value) in [("energy_price_per_kwh", "0.4"), ("energy_currency", "USD")] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "PUT",
+            &format!("/api/settings/{}", key),
+            Some(&ctx.admin),
+            Some(serde_json::json!({ "value": value })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, json) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/energy",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(json["price_per_kwh"], 0.4);
+    assert_eq!(json["currency"], "USD");
+    assert!(json["apps"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_energy_invalid_period_rejected() {
+    let ctx = setup().await;
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/monitoring/energy?period=week",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
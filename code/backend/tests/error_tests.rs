@@ -151,6 +151,15 @@ async fn test_bad_gateway_error() {
     assert!(body.contains("upstream timeout"));
 }
 
+#[tokio::test]
+async fn test_too_many_requests_error() {
+    let error = AppError::TooManyRequests("slow down".to_string());
+    let response = error.into_response();
+    let (status, body) = get_response_body(response).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body.contains("slow down"));
+}
+
 #[tokio::test]
 async fn test_database_error_response() {
     let db_err = sea_orm::DbErr::Custom("test db error".to_string());
@@ -0,0 +1,648 @@
+//! Integration tests for the app integration endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/integrations`                  — requires apps.view
+//! - `GET    /api/integrations/apps/{app_name}`  — requires apps.view
+//! - `PUT    /api/integrations/apps/{app_name}`  — requires apps.install
+//! - `DELETE /api/integrations/apps/{app_name}`  — requires apps.install
+//! - `GET    /api/integrations/downloads`        — requires apps.view, filtered by app access
+//! - `GET    /api/integrations/calendar`         — requires apps.view, filtered by app access
+//! - `GET    /api/apps/{app_name}/native-status` — requires apps.view + app access
+//!
+//! Without K8s, native status is only reachable when a base URL override is
+//! configured; the upstream app is unreachable so the response reports
+//! `available: false`.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+/// Create a router and log in a user with the given role
+async fn setup(username: &str, role: &str) -> (axum::Router, String) {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        username,
+        &format!("{}@example.com", username),
+        "pass123",
+        role,
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+    let cookie = do_login(app.clone(), username, "pass123")
+        .await
+        .expect("login must succeed");
+    (app, cookie)
+}
+
+// ============================================================================
+// Authentication / authorization
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_integrations_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let (status, _) = make_request(app, "GET", "/api/integrations", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_native_status_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let (status, _) = make_request(app, "GET", "/api/apps/sonarr/native-status", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_cannot_configure_integration() {
+    let (app, cookie) = setup("int_viewer1", "viewer").await;
+
+    let (status, _) = make_request(
+        app,
+        "PUT",
+        "/api/integrations/apps/sonarr",
+        Some(&cookie),
+        Some(serde_json::json!({"credentials": {"api_key": "abc"}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_viewer_native_status_without_app_access_forbidden() {
+    // Seeded viewer role only has access to jellyfin/jellyseerr
+    let (app, cookie) = setup("int_viewer2", "viewer").await;
+
+    let (status, _) = make_request(
+        app,
+        "GET",
+        "/api/apps/sonarr/native-status",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_integrations_includes_supported_apps() {
+    let (app, cookie) = setup("int_admin1", "admin").await;
+
+    let (status, body) = make_request(app, "GET", "/api/integrations", Some(&cookie), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let supported: Vec<&str> = body["supported_apps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|v| v.as_str())
+        .collect();
+    for app in ["sonarr", "radarr", "qbittorrent", "jellyfin"] {
+        assert!(supported.contains(&app), "missing {}", app);
+    }
+    assert_eq!(body["integrations"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_configure_integration_masks_credentials() {
+    let (app, cookie) = setup("int_admin2", "admin").await;
+
+    let (status, body) = make_request(
+        app.clone(),
+        "PUT",
+        "/api/integrations/apps/sonarr",
+        Some(&cookie),
+        Some(serde_json::json!({
+            "credentials": {"api_key": "super-secret-key"},
+            "base_url": "http://sonarr.local:8989/"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["integration"], "sonarr");
+    assert_eq!(body["has_api_key"], true);
+    assert_eq!(body["has_password"], false);
+    assert_eq!(body["base_url"], "http://sonarr.local:8989");
+    assert!(!body.to_string().contains("super-secret-key"));
+
+    let (status, body) = make_request(
+        app.clone(),
+        "GET",
+        "/api/integrations/apps/sonarr",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["tls_verify"], true);
+    assert!(!body.to_string().contains("super-secret-key"));
+
+    let (status, body) = make_request(
+        app,
+        "PUT",
+        "/api/integrations/apps/sonarr",
+        Some(&cookie),
+        Some(serde_json::json!({"tls_verify": false})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tls_verify"], false);
+    assert_eq!(body["has_api_key"], true);
+}
+
+#[tokio::test]
+async fn test_configure_unsupported_app_rejected() {
+    let (app, cookie) = setup("int_admin3", "admin").await;
+
+    let (status, _) = make_request(
+        app,
+        "PUT",
+        "/api/integrations/apps/nginx",
+        Some(&cookie),
+        Some(serde_json::json!({"credentials": {"api_key": "abc"}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_configure_invalid_base_url_rejected() {
+    let (app, cookie) = setup("int_admin4", "admin").await;
+
+    let (status, _) = make_request(
+        app,
+        "PUT",
+        "/api/integrations/apps/radarr",
+        Some(&cookie),
+        Some(serde_json::json!({"base_url": "ftp://radarr"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_delete_integration() {
+    let (app, cookie) = setup("int_admin5", "admin").await;
+
+    let (status, _) = make_request(
+        app.clone(),
+        "PUT",
+        "/api/integrations/apps/radarr",
+        Some(&cookie),
+        Some(serde_json::json!({"credentials": {"api_key": "abc"}})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(
+        app.clone(),
+        "DELETE",
+        "/api/integrations/apps/radarr",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(
+        app,
+        "GET",
+        "/api/integrations/apps/radarr",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Native status
+// ============================================================================
+
+#[tokio::test]
+async fn test_native_status_unknown_adapter_returns_404() {
+    let (app, cookie) = setup("int_admin6", "admin").await;
+
+    let (status, _) = make_request(
+        app,
+        "GET",
+        "/api/apps/nginx/native-status",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_native_status_without_k8s_returns_503() {
+    let (app, cookie) = setup("int_admin7", "admin").await;
+
+    let (status, _) = make_request(
+        app,
+        "GET",
+        "/api/apps/sonarr/native-status",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_native_status_unreachable_app_reports_unavailable() {
+    let (app, cookie) = setup("int_admin8", "admin").await;
+
+    let (status, _) = make_request(
+        app.clone(),
+        "PUT",
+        "/api/integrations/apps/sonarr",
+        Some(&cookie),
+        Some(serde_json::json!({
+            "credentials": {"api_key": "abc"},
+            "base_url": "http://127.0.0.1:1"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/apps/sonarr/native-status",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["app_name"], "sonarr");
+    assert_eq!(body["integration"], "sonarr");
+    assert_eq!(body["available"], false);
+    assert!(body["error"].is_string());
+}
+
+// ============================================================================
+// Download queue
+// ============================================================================
+
+#[tokio::test]
+async fn test_downloads_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let (status, _) = make_request(app, "GET", "/api/integrations/downloads", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_downloads_empty_without_integrations() {
+    let (app, cookie) = setup("int_admin9", "admin").await;
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/downloads",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["items"].as_array().unwrap().len(), 0);
+    assert_eq!(body["clients"].as_array().unwrap().len(), 0);
+    assert_eq!(body["total_download_rate"], 0);
+}
+
+#[tokio::test]
+async fn test_downloads_reports_unreachable_client() {
+    let (app, cookie) = setup("int_admin10", "admin").await;
+
+    for client in ["qbittorrent", "sonarr"] {
+        let (status, _) = make_request(
+            app.clone(),
+            "PUT",
+            &format!("/api/integrations/apps/{}", client),
+            Some(&cookie),
+            Some(serde_json::json!({
+                "credentials": {"api_key": "abc"},
+                "base_url": "http://127.0.0.1:1"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/downloads",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Sonarr is not a download client and must not appear
+    let clients = body["clients"].as_array().unwrap();
+    assert_eq!(clients.len(), 1);
+    assert_eq!(clients[0]["app_name"], "qbittorrent");
+    assert_eq!(clients[0]["available"], false);
+    assert!(clients[0]["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_downloads_filtered_by_app_access() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "int_admin11",
+        "int_admin11@example.com",
+        "pass123",
+        "admin",
+    )
+    .await;
+    // Seeded viewer role has no access to qbittorrent
+    create_test_user_with_role(
+        &db,
+        "int_viewer3",
+        "int_viewer3@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "int_admin11", "pass123")
+        .await
+        .unwrap();
+    let viewer = do_login(app.clone(), "int_viewer3", "pass123")
+        .await
+        .unwrap();
+
+    let (status, _) = make_request(
+        app.clone(),
+        "PUT",
+        "/api/integrations/apps/qbittorrent",
+        Some(&admin),
+        Some(serde_json::json!({"base_url": "http://127.0.0.1:1"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/downloads",
+        Some(&viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["clients"].as_array().unwrap().len(), 0);
+}
+
+// ============================================================================
+// Calendar
+// ============================================================================
+
+#[tokio::test]
+async fn test_calendar_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let (status, _) = make_request(app, "GET", "/api/integrations/calendar", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_calendar_default_range_empty() {
+    let (app, cookie) = setup("int_admin12", "admin").await;
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/calendar",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["entries"].as_array().unwrap().len(), 0);
+    assert_eq!(body["sources"].as_array().unwrap().len(), 0);
+    assert!(body["start"].is_string());
+    assert!(body["end"].is_string());
+}
+
+#[tokio::test]
+async fn test_calendar_rejects_invalid_range() {
+    let (app, cookie) = setup("int_admin13", "admin").await;
+
+    let (status, _) = make_request(
+        app.clone(),
+        "GET",
+        "/api/integrations/calendar?start=2026-10-16&end=2026-10-01",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = make_request(
+        app,
+        "GET",
+        "/api/integrations/calendar?start=2026-01-01&end=2026-12-31",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_calendar_reports_unreachable_sources() {
+    let (app, cookie) = setup("int_admin14", "admin").await;
+
+    for source in ["sonarr", "radarr", "qbittorrent"] {
+        let (status, _) = make_request(
+            app.clone(),
+            "PUT",
+            &format!("/api/integrations/apps/{}", source),
+            Some(&cookie),
+            Some(serde_json::json!({
+                "credentials": {"api_key": "abc"},
+                "base_url": "http://127.0.0.1:1"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/calendar?start=2026-10-16&end=2026-10-20",
+        Some(&cookie),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["start"], "2026-10-16");
+    assert_eq!(body["end"], "2026-10-20");
+
+    let mut sources: Vec<&str> = body["sources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| {
+            assert_eq!(s["available"], false);
+            s["app_name"].as_str().unwrap()
+        })
+        .collect();
+    sources.sort();
+    assert_eq!(sources, vec!["radarr", "sonarr"]);
+}
+
+#[tokio::test]
+async fn test_calendar_filtered_by_app_access() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "int_admin15",
+        "int_admin15@example.com",
+        "pass123",
+        "admin",
+    )
+    .await;
+    // Seeded viewer role has no access to sonarr
+    create_test_user_with_role(
+        &db,
+        "int_viewer4",
+        "int_viewer4@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "int_admin15", "pass123")
+        .await
+        .unwrap();
+    let viewer = do_login(app.clone(), "int_viewer4", "pass123")
+        .await
+        .unwrap();
+
+    let (status, _) = make_request(
+        app.clone(),
+        "PUT",
+        "/api/integrations/apps/sonarr",
+        Some(&admin),
+        Some(serde_json::json!({"base_url": "http://127.0.0.1:1"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = make_request(
+        app,
+        "GET",
+        "/api/integrations/calendar",
+        Some(&viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["sources"].as_array().unwrap().len(), 0);
+}
@@ -0,0 +1,149 @@
+//! Integration tests for invite batches
+//!
+//! Covers:
+//! - `POST /api/users/invites/batch` — creating several invites under a label
+//! - `GET /api/invites/share/{token}` — the public share page of a batch
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::{invite, invite_batch};
+
+/// Open a page without a session and return the status and body as text
+async fn open(env: &TestEnv, uri: &str) -> (StatusCode, String) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Path of the share page in a batch response
+fn share_path(body: &serde_json::Value) -> String {
+    let url = body["share_url"].as_str().unwrap();
+    let (_, token) = url.split_once("/api/invites/share/").unwrap();
+    format!("/api/invites/share/{}", token)
+}
+
+#[tokio::test]
+async fn test_create_batch() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/users/invites/batch",
+            Some(env.cookie("admin")),
+            Some(json!({"label": "LAN party", "count": 3})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["label"], "LAN party");
+    let invites = body["invites"].as_array().unwrap();
+    assert_eq!(invites.len(), 3);
+    assert!(invites.iter().all(|i| i["label"] == "LAN party"));
+    assert!(!body["share_expires_at"].is_null());
+
+    let (_, listed) = env
+        .request("GET", "/api/users/invites", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(
+        listed
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|i| i["batch_id"] == body["id"])
+            .count(),
+        3
+    );
+
+    for bad in [
+        json!({"label": "x", "count": 0}),
+        json!({"label": "x", "count": 51}),
+        json!({"label": " ", "count": 2}),
+        json!({"label": "x", "count": 2, "share_expires_in_hours": 0}),
+    ] {
+        let (status, _) = env
+            .request(
+                "POST",
+                "/api/users/invites/batch",
+                Some(env.cookie("admin")),
+                Some(bad),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/users/invites/batch",
+            Some(env.cookie("viewer")),
+            Some(json!({"label": "x", "count": 2})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_share_page() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let (_, body) = env
+        .request(
+            "POST",
+            "/api/users/invites/batch",
+            Some(env.cookie("admin")),
+            Some(json!({"label": "LAN party", "count": 2})),
+        )
+        .await;
+    let path = share_path(&body);
+
+    let (status, page) = open(&env, &path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("<h1>LAN party</h1>"));
+    assert_eq!(page.matches("<svg").count(), 2);
+    let first = body["invites"][0]["code"].as_str().unwrap();
+    assert!(page.contains(&format!("/auth/register?invite={}", first)));
+
+    // Used invites drop off the page
+    let used = Invite::find_by_id(body["invites"][0]["id"].as_i64().unwrap())
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut used: invite::ActiveModel = used.into();
+    used.is_used = Set(true);
+    used.update(&env.db).await.unwrap();
+    let (_, page) = open(&env, &path).await;
+    assert_eq!(page.matches("<svg").count(), 1);
+    assert!(!page.contains(first));
+
+    let (status, _) = open(&env, "/api/invites/share/not-a-token").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Expired pages are gone
+    let batch = InviteBatch::find_by_id(body["id"].as_i64().unwrap())
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut batch: invite_batch::ActiveModel = batch.into();
+    batch.share_expires_at = Set(Utc::now() - Duration::minutes(1));
+    batch.update(&env.db).await.unwrap();
+    let (status, page) = open(&env, &path).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(!page.contains("<svg"));
+}
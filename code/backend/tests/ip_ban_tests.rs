@@ -0,0 +1,249 @@
+//! Integration tests for IP bans
+//!
+//! Covers:
+//! - automatic bans after repeated failed sign-ins, and the `ip_ban_*`
+//!   settings
+//! - `GET/POST /api/security/bans` and `DELETE /api/security/bans/{ban_id}`
+//! - `GET /api/security/bans/export` and `GET /api/security/failed-logins`
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::audit_log;
+use kubarr::models::prelude::*;
+
+/// Sign in from an address and return the status
+async fn login_from(env: &TestEnv, ip: &str, password: &str) -> StatusCode {
+    let body = json!({"username": "viewer", "password": password}).to_string();
+    env.router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/auth/login")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", ip)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+/// Fetch an endpoint as the admin and return the status and body as text
+async fn fetch(env: &TestEnv, uri: &str) -> (StatusCode, String) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("Cookie", env.cookie("admin"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+#[tokio::test]
+async fn test_automatic_ban() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/settings/ip_ban_threshold",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "3"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    for _ in 0..2 {
+        assert_eq!(
+            login_from(&env, "203.0.113.9", "wrong").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    // Below the threshold the right password still works
+    assert_eq!(
+        login_from(&env, "203.0.113.9", DEV_PASSWORD).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        login_from(&env, "203.0.113.9", "wrong").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        login_from(&env, "203.0.113.9", DEV_PASSWORD).await,
+        StatusCode::FORBIDDEN
+    );
+    // Other addresses are not affected
+    assert_eq!(
+        login_from(&env, "198.51.100.4", DEV_PASSWORD).await,
+        StatusCode::OK
+    );
+
+    let failures = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("login_failed"))
+        .filter(audit_log::Column::IpAddress.eq("203.0.113.9"))
+        .count(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(failures, 3);
+    let banned = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("ip_banned"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(banned.resource_id.as_deref(), Some("203.0.113.9"));
+
+    let (status, bans) = env
+        .request("GET", "/api/security/bans", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bans.as_array().unwrap().len(), 1);
+    assert_eq!(bans[0]["ip_address"], "203.0.113.9");
+    assert_eq!(bans[0]["source"], "automatic");
+    assert_eq!(bans[0]["failed_attempts"], 3);
+    assert!(bans[0]["expires_at"].is_string());
+
+    let (status, sources) = env
+        .request(
+            "GET",
+            "/api/security/failed-logins?hours=1",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sources[0]["ip_address"], "203.0.113.9");
+    assert_eq!(sources[0]["attempts"], 3);
+    assert_eq!(sources[0]["usernames"], json!(["viewer"]));
+    assert_eq!(sources[0]["banned"], true);
+
+    // Lifting the ban lets the address sign in again
+    let uri = format!("/api/security/bans/{}", bans[0]["id"]);
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        login_from(&env, "203.0.113.9", DEV_PASSWORD).await,
+        StatusCode::OK
+    );
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/ip_ban_window_minutes",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "0"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_manual_bans_and_export() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/security/bans",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    for invalid in [
+        json!({"ip_address": "203.0.113.0/24"}),
+        json!({"ip_address": "203.0.113.5", "duration_minutes": 0}),
+    ] {
+        let (status, _) = env
+            .request(
+                "POST",
+                "/api/security/bans",
+                Some(env.cookie("admin")),
+                Some(invalid),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, ban) = env
+        .request(
+            "POST",
+            "/api/security/bans",
+            Some(env.cookie("admin")),
+            Some(json!({"ip_address": "203.0.113.5", "reason": "Scanner", "duration_minutes": 90})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", ban);
+    assert_eq!(ban["source"], "manual");
+    assert_eq!(ban["created_by_id"], env.user("admin").user.id);
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/security/bans",
+            Some(env.cookie("admin")),
+            Some(json!({"ip_address": "2001:DB8::1"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        login_from(&env, "2001:db8::1", DEV_PASSWORD).await,
+        StatusCode::FORBIDDEN
+    );
+
+    let (status, plain) = fetch(&env, "/api/security/bans/export").await;
+    assert_eq!(status, StatusCode::OK);
+    let mut lines: Vec<&str> = plain.lines().collect();
+    lines.sort();
+    assert_eq!(lines, ["2001:db8::1", "203.0.113.5"]);
+
+    let (_, nginx) = fetch(&env, "/api/security/bans/export?format=nginx").await;
+    let mut ranges: Vec<&str> = nginx.split(',').collect();
+    ranges.sort();
+    assert_eq!(ranges, ["2001:db8::1/128", "203.0.113.5/32"]);
+
+    let (_, crowdsec) = fetch(&env, "/api/security/bans/export?format=crowdsec").await;
+    let decisions: serde_json::Value = serde_json::from_str(&crowdsec).unwrap();
+    let scanner = decisions
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["value"] == "203.0.113.5")
+        .unwrap();
+    assert_eq!(scanner["type"], "ban");
+    assert_eq!(scanner["scope"], "ip");
+    assert_eq!(scanner["duration"], "1h30m");
+    assert_eq!(scanner["reason"], "Scanner");
+
+    let (status, _) = fetch(&env, "/api/security/bans/export?format=iptables").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
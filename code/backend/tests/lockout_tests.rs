@@ -0,0 +1,184 @@
+//! Integration tests for account lockout
+//!
+//! Covers:
+//! - locking an account after repeated failed sign-ins, and the `lockout_*`
+//!   settings
+//! - the per-address backoff answering `429 Too Many Requests`
+//! - lockout state in `GET /api/users/{user_id}` and
+//!   `POST /api/users/{user_id}/unlock`
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::prelude::*;
+use kubarr::models::{audit_log, user_notification};
+
+/// Sign in, from an address if given, and return the status
+async fn login_as(env: &TestEnv, username: &str, password: &str, ip: Option<&str>) -> StatusCode {
+    let body = json!({"username": username, "password": password}).to_string();
+    let mut builder = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json");
+    if let Some(ip) = ip {
+        builder = builder.header("x-forwarded-for", ip);
+    }
+    env.router
+        .clone()
+        .oneshot(builder.body(Body::from(body)).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+/// Change a setting as the admin
+async fn set_setting(env: &TestEnv, key: &str, value: &str) -> StatusCode {
+    env.request(
+        "PUT",
+        &format!("/api/settings/{}", key),
+        Some(env.cookie("admin")),
+        Some(json!({ "value": value })),
+    )
+    .await
+    .0
+}
+
+#[tokio::test]
+async fn test_account_lockout() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    assert_eq!(
+        set_setting(&env, "lockout_threshold", "3").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        set_setting(&env, "lockout_duration_minutes", "0").await,
+        StatusCode::BAD_REQUEST
+    );
+    let viewer_id = env.user("viewer").user.id;
+    let user_uri = format!("/api/users/{}", viewer_id);
+
+    // A successful sign-in resets the count
+    for _ in 0..2 {
+        assert_eq!(
+            login_as(&env, "viewer", "wrong", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, None).await,
+        StatusCode::OK
+    );
+    let (status, body) = env
+        .request("GET", &user_uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["failed_login_attempts"], 0);
+    assert!(body["locked_until"].is_null());
+
+    for _ in 0..3 {
+        assert_eq!(
+            login_as(&env, "viewer", "wrong", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    // Locked accounts are refused even with the right password
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, None).await,
+        StatusCode::FORBIDDEN
+    );
+    let (_, body) = env
+        .request("GET", &user_uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(body["failed_login_attempts"], 3);
+    assert!(body["locked_until"].is_string());
+
+    let locked = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("account_locked"))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(locked.len(), 1);
+    assert_eq!(locked[0].resource_id, Some(viewer_id.to_string()));
+    let details: serde_json::Value =
+        serde_json::from_str(locked[0].details.as_deref().unwrap()).unwrap();
+    assert_eq!(details["failed_attempts"], 3);
+    let notified = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(viewer_id))
+        .filter(user_notification::Column::EventType.eq("account_locked"))
+        .one(&env.db)
+        .await
+        .unwrap();
+    assert!(notified.is_some());
+
+    // Only admins can unlock
+    let unlock_uri = format!("{}/unlock", user_uri);
+    let (status, _) = env
+        .request("POST", &unlock_uri, Some(env.cookie("viewer")), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = env
+        .request("POST", &unlock_uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["failed_login_attempts"], 0);
+    assert!(body["locked_until"].is_null());
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, None).await,
+        StatusCode::OK
+    );
+
+    // With lockout off, failures never lock
+    assert_eq!(
+        set_setting(&env, "lockout_threshold", "0").await,
+        StatusCode::OK
+    );
+    for _ in 0..4 {
+        assert_eq!(
+            login_as(&env, "viewer", "wrong", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, None).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_address_backoff() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    assert_eq!(
+        set_setting(&env, "lockout_threshold", "3").await,
+        StatusCode::OK
+    );
+
+    // Unknown usernames count towards the address's failures
+    for _ in 0..3 {
+        assert_eq!(
+            login_as(&env, "nobody", "wrong", Some("203.0.113.20")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, Some("203.0.113.20")).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    // Other addresses are not affected
+    assert_eq!(
+        login_as(&env, "viewer", DEV_PASSWORD, Some("198.51.100.20")).await,
+        StatusCode::OK
+    );
+}
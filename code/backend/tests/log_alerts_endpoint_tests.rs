@@ -0,0 +1,424 @@
+//! Integration tests for log alert rules and the monitoring alerts list
+//!
+//! Covers endpoints:
+//! - `GET    /api/monitoring/alerts`                  — requires monitoring.view
+//! - `GET    /api/monitoring/log-alerts`              — requires monitoring.view
+//! - `POST   /api/monitoring/log-alerts`              — requires monitoring.manage
+//! - `GET    /api/monitoring/log-alerts/{id}`         — requires monitoring.view
+//! - `PUT    /api/monitoring/log-alerts/{id}`         — requires monitoring.manage
+//! - `DELETE /api/monitoring/log-alerts/{id}`         — requires monitoring.manage
+//! - `POST   /api/monitoring/log-alerts/{id}/evaluate` — requires monitoring.manage
+//!
+//! VictoriaLogs is not available in tests; state transitions are driven
+//! through `services::log_alerts::record_evaluation`.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+use kubarr::models::log_alert_rule;
+use kubarr::services::log_alerts;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    db: DatabaseConnection,
+    admin: String,
+    viewer: String,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only)
+async fn setup() -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(&db, "la_admin", "la_admin@example.com", "pass123", "admin").await;
+    create_test_user_with_role(
+        &db,
+        "la_viewer",
+        "la_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "la_admin", "pass123").await.unwrap();
+    let viewer = do_login(app.clone(), "la_viewer", "pass123").await.unwrap();
+    TestContext {
+        app,
+        db,
+        admin,
+        viewer,
+    }
+}
+
+async fn create_rule(ctx: &TestContext, body: serde_json::Value) -> i64 {
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/monitoring/log-alerts",
+        Some(&ctx.admin),
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create failed: {}", json);
+    assert_eq!(json["state"], "unknown");
+    json["id"].as_i64().unwrap()
+}
+
+async fn load_rule(db: &DatabaseConnection, id: i64) -> log_alert_rule::Model {
+    log_alert_rule::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn alerts(ctx: &TestContext) -> Vec<serde_json::Value> {
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/alerts",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    json.as_array().unwrap().clone()
+}
+
+// ============================================================================
+// Authentication and permissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_alerts_require_auth() {
+    let ctx = setup().await;
+    for uri in ["/api/monitoring/alerts", "/api/monitoring/log-alerts"] {
+        let (status, _) = make_request(ctx.app.clone(), "GET", uri, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_viewer_can_list_but_not_manage_rules() {
+    let ctx = setup().await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/log-alerts",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json.as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(
+        ctx.app,
+        "POST",
+        "/api/monitoring/log-alerts",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": "Errors", "pattern": "ERROR"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_rule_validation() {
+    let ctx = setup().await;
+
+    for body in [
+        serde_json::json!({"name": " ", "pattern": "ERROR"}),
+        serde_json::json!({"name": "Errors", "pattern": ""}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR | delete"}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR) OR (*"}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR", "app_name": "sonarr\" or *"}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR", "severity": "page"}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR", "window_minutes": 0}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR", "interval_seconds": 5}),
+        serde_json::json!({"name": "Errors", "pattern": "ERROR", "threshold": -1}),
+    ] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "POST",
+            "/api/monitoring/log-alerts",
+            Some(&ctx.admin),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+}
+
+// ============================================================================
+// Evaluation and alerts
+// ============================================================================
+
+#[tokio::test]
+async fn test_rule_fires_and_resolves() {
+    let ctx = setup().await;
+    let id = create_rule(
+        &ctx,
+        serde_json::json!({
+            "name": "Sonarr errors",
+            "app_name": "sonarr",
+            "pattern": "ERROR",
+            "threshold": 5,
+            "severity": "critical"
+        }),
+    )
+    .await;
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/monitoring/log-alerts/{}", id),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(
+        json["query"],
+        r#"namespace:="sonarr" (ERROR) | stats count() hits"#
+    );
+    assert_eq!(json["window_minutes"], 5);
+    assert!(alerts(&ctx).await.is_empty());
+
+    // At the threshold the rule is ok; above it, it fires once
+    let rule = load_rule(&ctx.db, id).await;
+    let (rule, event) = log_alerts::record_evaluation(&ctx.db, rule, Ok(5), Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(rule.state, "ok");
+    assert!(event.is_none());
+
+    let (rule, event) = log_alerts::record_evaluation(&ctx.db, rule, Ok(7), Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(rule.state, "firing");
+    assert_eq!(
+        event.map(|e| e.to_string()),
+        Some("log_alert_firing".to_string())
+    );
+    let (rule, event) = log_alerts::record_evaluation(&ctx.db, rule, Ok(9), Utc::now())
+        .await
+        .unwrap();
+    assert!(event.is_none());
+
+    let listed = alerts(&ctx).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["source"], "log");
+    assert_eq!(listed[0]["id"], id);
+    assert_eq!(listed[0]["severity"], "critical");
+    assert_eq!(listed[0]["app_name"], "sonarr");
+    assert!(listed[0]["message"].as_str().unwrap().contains("9 lines"));
+
+    // A failed query keeps the state
+    let (rule, event) =
+        log_alerts::record_evaluation(&ctx.db, rule, Err("timeout".to_string()), Utc::now())
+            .await
+            .unwrap();
+    assert_eq!(rule.state, "firing");
+    assert_eq!(rule.last_error.as_deref(), Some("timeout"));
+    assert!(event.is_none());
+
+    let (rule, event) = log_alerts::record_evaluation(&ctx.db, rule, Ok(0), Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(rule.state, "ok");
+    assert_eq!(
+        event.map(|e| e.to_string()),
+        Some("log_alert_resolved".to_string())
+    );
+    assert!(rule.firing_since.is_none());
+    assert!(alerts(&ctx).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_evaluate_without_victorialogs_records_error() {
+    let ctx = setup().await;
+    let id = create_rule(
+        &ctx,
+        serde_json::json!({"name": "Panics", "pattern": "panic"}),
+    )
+    .await;
+
+    let (status, json) = make_request(
+        ctx.app,
+        "POST",
+        &format!("/api/monitoring/log-alerts/{}/evaluate", id),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["state"], "unknown");
+    assert!(json["last_error"].is_string());
+    assert!(json["last_evaluated_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_metric_anomalies_listed_as_alerts() {
+    use kubarr::models::metric_anomaly;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let ctx = setup().await;
+    let now = Utc::now();
+    metric_anomaly::ActiveModel {
+        app_name: Set("plex".to_string()),
+        metric: Set("restarts".to_string()),
+        direction: Set("above".to_string()),
+        value: Set(6.0),
+        baseline_mean: Set(0.0),
+        baseline_stddev: Set(0.0),
+        z_score: Set(12.0),
+        window_start: Set(now - chrono::Duration::hours(1)),
+        window_end: Set(now),
+        detected_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await
+    .unwrap();
+
+    let listed = alerts(&ctx).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["source"], "metric");
+    assert_eq!(listed[0]["severity"], "warning");
+    assert_eq!(
+        listed[0]["message"],
+        "plex container restarts is 6 restarts/h, above its baseline of 0 restarts/h"
+    );
+    assert!(listed[0]["link"]
+        .as_str()
+        .unwrap()
+        .starts_with("/resources?app=plex"));
+}
+
+#[tokio::test]
+async fn test_update_resets_state_and_delete() {
+    let ctx = setup().await;
+    let id = create_rule(
+        &ctx,
+        serde_json::json!({"name": "Errors", "pattern": "ERROR"}),
+    )
+    .await;
+    let rule = load_rule(&ctx.db, id).await;
+    log_alerts::record_evaluation(&ctx.db, rule, Ok(3), Utc::now())
+        .await
+        .unwrap();
+
+    // Renaming keeps the state, changing the pattern resets it
+    let uri = format!("/api/monitoring/log-alerts/{}", id);
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"name": "All errors"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["state"], "firing");
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"pattern": "ERROR OR FATAL", "app_name": "radarr"})),
+    )
+    .await;
+    assert_eq!(json["state"], "unknown");
+    assert_eq!(json["app_name"], "radarr");
+    assert!(json.get("last_count").is_none());
+
+    let (status, _) = make_request(ctx.app.clone(), "DELETE", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = make_request(ctx.app, "GET", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
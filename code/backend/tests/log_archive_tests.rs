@@ -0,0 +1,239 @@
+//! Integration tests for the log archive
+//!
+//! Covers:
+//! - exporting complete days of installed apps' logs from a mock
+//!   VictoriaLogs into a local archive store, and the retention purge
+//! - `GET /api/logs/archive`        — the archive index
+//! - `GET /api/logs/archive/query`  — reading archived ranges
+//! - `POST /api/logs/archive/run`   — permission check
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::Query;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::LogArchive;
+use kubarr::models::system_setting;
+use kubarr::services::installed_apps;
+use kubarr::services::log_archive::{self, LocalStore};
+
+/// Archive directory shared by the tests in this file; `CONFIG` reads it
+/// once, so every test sets the same value
+fn archive_dir() -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join("kubarr_log_archive_tests");
+    std::env::set_var("KUBARR_LOG_ARCHIVE_DIR", &dir);
+    dir
+}
+
+fn at(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Serve a VictoriaLogs query API with logs for `app` on 2026-10-15 only,
+/// recording the queries it receives
+async fn spawn_victorialogs(app: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let queries = Arc::new(Mutex::new(Vec::new()));
+    let recorded = queries.clone();
+    let router = axum::Router::new().route(
+        "/select/logsql/query",
+        axum::routing::get(
+            move |Query(params): Query<std::collections::HashMap<String, String>>| {
+                let recorded = recorded.clone();
+                async move {
+                    let query = params.get("query").cloned().unwrap_or_default();
+                    let start = params.get("start").cloned().unwrap_or_default();
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push(format!("{} {}", query, start));
+                    if !query.contains(&format!(r#"namespace:="{}""#, app))
+                        || !start.starts_with("2026-10-15")
+                    {
+                        return String::new();
+                    }
+                    [
+                        ("2026-10-15T08:00:00Z", "starting up"),
+                        ("2026-10-15T09:00:00Z", "ERROR disk full"),
+                        ("2026-10-15T23:00:00Z", "ERROR still full"),
+                    ]
+                    .iter()
+                    .map(|(time, msg)| {
+                        serde_json::json!({
+                            "_time": time,
+                            "_msg": msg,
+                            "namespace": app,
+                            "pod": format!("{}-0", app),
+                            "container": app,
+                        })
+                        .to_string()
+                            + "\n"
+                    })
+                    .collect::<String>()
+                }
+            },
+        ),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}", addr), queries)
+}
+
+#[tokio::test]
+async fn test_archive_run_and_query() {
+    let dir = archive_dir();
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    installed_apps::record_install(&env.db, "archive-sonarr", None)
+        .await
+        .unwrap();
+    let (vlogs_url, queries) = spawn_victorialogs("archive-sonarr").await;
+    let store = LocalStore::new(&dir);
+    let now = at("2026-10-17T02:00:00Z");
+
+    let summary = log_archive::run(&env.db, &store, &vlogs_url, now)
+        .await
+        .unwrap();
+    assert!(summary.failed.is_empty(), "failures: {:?}", summary.failed);
+    assert_eq!(summary.archived, 7);
+    assert_eq!(queries.lock().unwrap().len(), 7);
+    assert!(queries.lock().unwrap()[0].contains("sort by (_time)"));
+
+    let archives = LogArchive::find().all(&env.db).await.unwrap();
+    let with_logs: Vec<_> = archives.iter().filter(|a| a.lines > 0).collect();
+    assert_eq!(archives.len(), 7);
+    assert_eq!(with_logs.len(), 1);
+    assert_eq!(with_logs[0].day, "2026-10-15");
+    assert_eq!(with_logs[0].store, "local");
+    let key = with_logs[0].object_key.clone().unwrap();
+    assert_eq!(
+        key,
+        "archive-sonarr/2026/10/archive-sonarr-2026-10-15.ndjson.gz"
+    );
+    assert!(dir.join(&key).is_file());
+
+    // Days already archived are not exported again
+    let summary = log_archive::run(&env.db, &store, &vlogs_url, now)
+        .await
+        .unwrap();
+    assert_eq!(summary.archived, 0);
+    assert_eq!(queries.lock().unwrap().len(), 7);
+
+    let viewer = env.cookie("viewer");
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/logs/archive?app=archive-sonarr&from=2026-10-15&to=2026-10-16",
+            Some(viewer),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let listed = body.as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0]["day"], "2026-10-16");
+    assert_eq!(listed[1]["lines"], 3);
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/logs/archive/query?app=archive-sonarr&start=2026-10-15T08:30:00Z\
+             &end=2026-10-16T00:00:00Z&filter=ERROR&limit=1",
+            Some(viewer),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_entries"], 1);
+    assert_eq!(body["truncated"], true);
+    let stream = &body["streams"][0];
+    assert_eq!(stream["labels"]["pod"], "archive-sonarr-0");
+    assert_eq!(stream["entries"][0]["line"], "ERROR disk full");
+    assert_eq!(stream["entries"][0]["timestamp"], "2026-10-15T09:00:00Z");
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/logs/archive/query?app=archive-sonarr&start=2026-10-01T00:00:00Z\
+             &end=2026-11-15T00:00:00Z",
+            Some(viewer),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Retention removes old archives from the index and the store
+    system_setting::ActiveModel {
+        key: Set(log_archive::LOG_ARCHIVE_RETENTION_DAYS.to_string()),
+        value: Set("1".to_string()),
+        description: Set(None),
+        updated_at: Set(Utc::now()),
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+    let summary = log_archive::run(&env.db, &store, &vlogs_url, now)
+        .await
+        .unwrap();
+    assert_eq!(summary.purged, 6);
+    let archives = LogArchive::find().all(&env.db).await.unwrap();
+    assert_eq!(archives.len(), 1);
+    assert_eq!(archives[0].day, "2026-10-16");
+    assert!(!dir.join(&key).exists());
+}
+
+#[tokio::test]
+async fn test_unreachable_victorialogs_is_retried() {
+    let dir = archive_dir();
+    let env = TestEnv::builder().build().await;
+    installed_apps::record_install(&env.db, "archive-radarr", None)
+        .await
+        .unwrap();
+    let store = LocalStore::new(&dir);
+
+    let summary = log_archive::run(
+        &env.db,
+        &store,
+        "http://127.0.0.1:1",
+        at("2026-10-17T02:00:00Z"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.archived, 0);
+    assert_eq!(summary.failed.len(), 7);
+    assert!(summary.failed[0].starts_with("archive-radarr 2026-10-10:"));
+    assert!(LogArchive::find().all(&env.db).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_archive_permissions() {
+    archive_dir();
+    let env = TestEnv::builder().with_viewer().build().await;
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/logs/archive/run",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/logs/archive/query?app=x&start=yesterday&end=today",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,189 @@
+//! Integration tests for new-device login alerts
+//!
+//! Covers:
+//! - the `new_device_login` notification sent on a login from a new device
+//! - the `login_alerts` field of `PATCH /api/users/me/preferences`
+//! - `GET/POST /auth/sessions/revoke-link` — the alert's sign-out link
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::prelude::*;
+use kubarr::models::{audit_log, session, user_notification};
+
+/// Log in from a browser and address, returning the new session
+async fn login_from(env: &TestEnv, username: &str, ip: &str, agent: &str) -> session::Model {
+    let body = json!({"username": username, "password": DEV_PASSWORD}).to_string();
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/auth/login")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", ip)
+                .header(header::USER_AGENT, agent)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    Session::find()
+        .filter(session::Column::UserId.eq(env.user(username).user.id))
+        .order_by_desc(session::Column::CreatedAt)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// New-device alerts a user has received, newest first
+async fn alerts(env: &TestEnv, username: &str) -> Vec<user_notification::Model> {
+    UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(env.user(username).user.id))
+        .filter(user_notification::Column::EventType.eq("new_device_login"))
+        .order_by_desc(user_notification::Column::Id)
+        .all(&env.db)
+        .await
+        .unwrap()
+}
+
+/// Path and query of the sign-out link in an alert
+fn revoke_path(alert: &user_notification::Model) -> String {
+    let (_, link) = alert
+        .message
+        .split_once("/auth/sessions/revoke-link")
+        .unwrap();
+    format!("/auth/sessions/revoke-link{}", link.trim())
+}
+
+/// Send a request without a session and return the status and body as text
+async fn open(env: &TestEnv, request: Request<Body>) -> (StatusCode, String) {
+    let response = env.router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Submit the confirmation form of a sign-out link
+async fn confirm(env: &TestEnv, path: &str) -> (StatusCode, String) {
+    let (_, token) = path.split_once("?token=").unwrap();
+    open(
+        env,
+        Request::builder()
+            .uri("/auth/sessions/revoke-link")
+            .method("POST")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(format!("token={}", token)))
+            .unwrap(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_new_device_alert() {
+    let env = TestEnv::builder().with_viewer().build().await;
+    // The fixture login is the user's first session, which is not reported
+    assert!(alerts(&env, "viewer").await.is_empty());
+
+    let session = login_from(&env, "viewer", "203.0.113.7", "Firefox/131.0").await;
+    let sent = alerts(&env, "viewer").await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].title, "New Sign-In");
+    assert!(sent[0].message.contains("IP 203.0.113.7, Firefox/131.0"));
+    assert!(revoke_path(&sent[0]).contains("?token="));
+    assert_eq!(session.ip_address.as_deref(), Some("203.0.113.7"));
+
+    // The same device again is known
+    login_from(&env, "viewer", "203.0.113.7", "Firefox/131.0").await;
+    assert_eq!(alerts(&env, "viewer").await.len(), 1);
+
+    // Users can turn alerts off
+    let (status, body) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("viewer")),
+            Some(json!({"login_alerts": false})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["login_alerts"], false);
+    login_from(&env, "viewer", "198.51.100.2", "Safari/18.0").await;
+    assert_eq!(alerts(&env, "viewer").await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_revoke_link() {
+    let env = TestEnv::builder().with_viewer().build().await;
+    let session = login_from(&env, "viewer", "203.0.113.7", "Firefox/131.0").await;
+    let path = revoke_path(&alerts(&env, "viewer").await[0]);
+
+    // Opening the link only asks for confirmation
+    let (status, page) = open(
+        &env,
+        Request::builder().uri(&path).body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Sign this session out?"));
+    assert!(page.contains("<button type=\"submit\">Sign out</button>"));
+    let unchanged = Session::find_by_id(&session.id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!unchanged.is_revoked);
+
+    let (status, page) = confirm(&env, &path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Session signed out"));
+    let revoked = Session::find_by_id(&session.id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(revoked.is_revoked);
+
+    // Using the link again is recorded too
+    let (status, page) = confirm(&env, &path).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Already signed out"));
+    let outcomes: Vec<String> = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("session_revoked"))
+        .order_by_asc(audit_log::Column::Id)
+        .all(&env.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            assert_eq!(entry.resource_id.as_deref(), Some(session.id.as_str()));
+            assert_eq!(entry.username.as_deref(), Some("viewer"));
+            let details: serde_json::Value =
+                serde_json::from_str(entry.details.as_deref().unwrap()).unwrap();
+            details["outcome"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(outcomes, ["revoked", "already_signed_out"]);
+
+    let (status, _) = open(
+        &env,
+        Request::builder()
+            .uri("/auth/sessions/revoke-link?token=not-a-token")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
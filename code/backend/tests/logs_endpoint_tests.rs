@@ -9,6 +9,7 @@
 //! - `GET /api/logs/{pod_name}`                     — requires logs.view
 //! - `GET /api/logs/app/{app_name}`                 — requires logs.view
 //! - `GET /api/logs/raw/{pod_name}`                 — requires logs.view
+//! - `GET /api/logs/pods/{pod_name}/stream`         — requires logs.view
 //! - `GET /api/logs/vlogs/namespaces`               — requires logs.view (makes HTTP to VictoriaLogs)
 //! - `GET /api/logs/vlogs/labels`                   — requires logs.view (makes HTTP to VictoriaLogs)
 //! - `GET /api/logs/vlogs/label/{label}/values`     — requires logs.view (makes HTTP to VictoriaLogs)
@@ -429,6 +430,92 @@ async fn test_get_raw_pod_logs_with_logs_view_returns_error_without_k8s() {
     );
 }
 
+// ============================================================================
+// GET /api/logs/pods/{pod_name}/stream — live pod logs (requires K8s)
+// ============================================================================
+
+#[tokio::test]
+async fn test_stream_pod_logs_without_logs_view_returns_403() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "stream_logs_no_perm",
+        "stream_logs_no_perm@example.com",
+        "password123",
+        "downloader",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "stream_logs_no_perm",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, _) = authenticated_get(
+        create_router(state),
+        "/api/logs/pods/my-pod-456/stream",
+        &cookie,
+    )
+    .await;
+
+    assert_eq!(
+        status,
+        StatusCode::FORBIDDEN,
+        "User without logs.view must get 403 on GET /api/logs/pods/{{pod_name}}/stream"
+    );
+}
+
+#[tokio::test]
+async fn test_stream_pod_logs_validates_level_and_needs_k8s() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "stream_logs_admin",
+        "stream_logs_admin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "stream_logs_admin",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/logs/pods/my-pod-456/stream?level=loud",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Invalid level 'loud'"), "body: {}", body);
+
+    let (status, _) = authenticated_get(
+        create_router(state),
+        "/api/logs/pods/my-pod-456/stream?level=warn&container=app",
+        &cookie,
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "GET /api/logs/pods/{{pod_name}}/stream without K8s must return 500"
+    );
+}
+
 // ============================================================================
 // GET /api/logs/vlogs/namespaces — VictoriaLogs namespaces
 // ============================================================================
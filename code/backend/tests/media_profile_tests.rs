@@ -0,0 +1,253 @@
+//! Integration tests for media profiles
+//!
+//! Covers:
+//! - `GET`/`PUT`/`DELETE /api/roles/{role_id}/media-profile`
+//! - `POST /api/roles/{role_id}/media-profile/sync` against a mock Jellyfin
+//! - background syncs when a profile or a user's roles change
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+
+type Policies = Arc<Mutex<HashMap<String, Value>>>;
+
+fn authorized(headers: &HeaderMap) -> bool {
+    headers.get("x-emby-token").and_then(|k| k.to_str().ok()) == Some("jf-key")
+}
+
+/// Serve a Jellyfin with the users `Kid` and `admin` and two libraries,
+/// keeping the policies posted to it
+async fn spawn_jellyfin() -> (String, Policies) {
+    let policies: Policies = Arc::new(Mutex::new(HashMap::from([
+        (
+            "jf-kid".to_string(),
+            json!({"IsAdministrator": false, "EnableAllFolders": true}),
+        ),
+        (
+            "jf-admin".to_string(),
+            json!({"IsAdministrator": true, "EnableAllFolders": true}),
+        ),
+    ])));
+    let users = policies.clone();
+    let posted = policies.clone();
+    let router = axum::Router::new()
+        .route(
+            "/Users",
+            axum::routing::get(move |headers: HeaderMap| {
+                let users = users.clone();
+                async move {
+                    if !authorized(&headers) {
+                        return (StatusCode::UNAUTHORIZED, Json(json!([])));
+                    }
+                    let users = users.lock().unwrap();
+                    let list = [("jf-kid", "Kid"), ("jf-admin", "admin")]
+                        .iter()
+                        .map(|(id, name)| json!({"Id": id, "Name": name, "Policy": users[*id]}))
+                        .collect();
+                    (StatusCode::OK, Json(Value::Array(list)))
+                }
+            }),
+        )
+        .route(
+            "/Library/VirtualFolders",
+            axum::routing::get(|| async {
+                Json(json!([
+                    {"Name": "Movies", "ItemId": "lib-movies"},
+                    {"Name": "Kids", "ItemId": "lib-kids"},
+                ]))
+            }),
+        )
+        .route(
+            "/Users/{id}/Policy",
+            axum::routing::post(
+                move |headers: HeaderMap, Path(id): Path<String>, Json(policy): Json<Value>| {
+                    let posted = posted.clone();
+                    async move {
+                        if !authorized(&headers) {
+                            return StatusCode::UNAUTHORIZED;
+                        }
+                        posted.lock().unwrap().insert(id, policy);
+                        StatusCode::NO_CONTENT
+                    }
+                },
+            ),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    (format!("http://{}", addr), policies)
+}
+
+async fn role_id(env: &TestEnv, name: &str) -> i64 {
+    let (_, roles) = env
+        .request("GET", "/api/roles", Some(env.cookie("admin")), None)
+        .await;
+    roles
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == name)
+        .unwrap()["id"]
+        .as_i64()
+        .unwrap()
+}
+
+/// Wait for a background sync to set `key` of the kid's policy to `value`
+async fn wait_for_policy(policies: &Policies, key: &str, value: Value) {
+    for _ in 0..50 {
+        if policies.lock().unwrap()["jf-kid"][key] == value {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!(
+        "{} never became {}: {}",
+        key,
+        value,
+        policies.lock().unwrap()["jf-kid"]
+    );
+}
+
+#[tokio::test]
+async fn test_media_profiles_sync_to_jellyfin() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("kid", "viewer")
+        .with_user("nobody", "viewer")
+        .build()
+        .await;
+    let (base_url, policies) = spawn_jellyfin().await;
+    let viewer = role_id(&env, "viewer").await;
+    let uri = format!("/api/roles/{}/media-profile", viewer);
+
+    // Without Jellyfin set up there is nothing to sync to
+    let (status, _) = env
+        .request(
+            "POST",
+            &format!("{}/sync", uri),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_ne!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/integrations/apps/jellyfin",
+            Some(env.cookie("admin")),
+            Some(json!({"base_url": base_url, "credentials": {"api_key": "jf-key"}})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request("GET", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let profile = json!({"libraries": ["Kids"], "max_streams": 1, "max_parental_rating": 10});
+    let (status, _) = env
+        .request("PUT", &uri, Some(env.cookie("kid")), Some(profile.clone()))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"libraries": [], "max_streams": 0})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, saved) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(profile.clone()),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", saved);
+    assert_eq!(saved, profile);
+    wait_for_policy(&policies, "MaxActiveSessions", json!(1)).await;
+    {
+        let policies = policies.lock().unwrap();
+        let kid = &policies["jf-kid"];
+        assert_eq!(kid["EnableAllFolders"], false);
+        assert_eq!(kid["EnabledFolders"], json!(["lib-kids"]));
+        assert_eq!(kid["MaxParentalRating"], 10);
+        // Settings Kubarr does not manage are kept
+        assert_eq!(kid["IsAdministrator"], false);
+        // The admin holds no profiled role
+        assert_eq!(policies["jf-admin"]["EnableAllFolders"], true);
+    }
+
+    let (status, results) = env
+        .request(
+            "POST",
+            &format!("{}/sync", uri),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", results);
+    let status_of = |username: &str| {
+        results
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|r| r["username"] == username)
+            .unwrap()["status"]
+            .clone()
+    };
+    assert_eq!(status_of("kid"), "unchanged");
+    assert_eq!(status_of("nobody"), "no_account");
+
+    // A role with an unlimited profile lifts the limits
+    let downloader = role_id(&env, "downloader").await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/media-profile", downloader),
+            Some(env.cookie("admin")),
+            Some(json!({"libraries": []})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!(
+                "/api/users/{}/roles/{}",
+                env.user("kid").user.id,
+                downloader
+            ),
+            Some(env.cookie("admin")),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    wait_for_policy(&policies, "EnableAllFolders", json!(true)).await;
+    wait_for_policy(&policies, "MaxActiveSessions", json!(0)).await;
+
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,430 @@
+//! Integration tests for the media request passthrough endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/integrations/requests`                      — apps.view + request manager access
+//! - `POST   /api/integrations/requests`                      — apps.view + linked account
+//! - `GET    /api/integrations/requests/count`                — apps.view + request manager access
+//! - `POST   /api/integrations/requests/{id}/approve|decline` — requires requests.manage
+//! - `GET    /api/integrations/requests/links`                — requires requests.manage
+//! - `PUT    /api/integrations/requests/links/{user_id}`      — requires requests.manage
+//! - `DELETE /api/integrations/requests/links/{user_id}`      — requires requests.manage
+//!
+//! Jellyseerr is configured with an unreachable base URL, so calls that reach
+//! the upstream API fail with 503.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    admin: String,
+    viewer: String,
+    viewer_id: i64,
+    downloader: String,
+    downloader_id: i64,
+}
+
+/// Create a router with an admin, a viewer (has jellyseerr access) and a
+/// downloader (no jellyseerr access), optionally configuring Jellyseerr
+async fn setup(configure_jellyseerr: bool) -> TestContext {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "req_admin",
+        "req_admin@example.com",
+        "pass123",
+        "admin",
+    )
+    .await;
+    let viewer = create_test_user_with_role(
+        &db,
+        "req_viewer",
+        "req_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let downloader = create_test_user_with_role(
+        &db,
+        "req_downloader",
+        "req_downloader@example.com",
+        "pass123",
+        "downloader",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "req_admin", "pass123").await.unwrap();
+    let viewer_cookie = do_login(app.clone(), "req_viewer", "pass123")
+        .await
+        .unwrap();
+    let downloader_cookie = do_login(app.clone(), "req_downloader", "pass123")
+        .await
+        .unwrap();
+
+    if configure_jellyseerr {
+        let (status, _) = make_request(
+            app.clone(),
+            "PUT",
+            "/api/integrations/apps/jellyseerr",
+            Some(&admin),
+            Some(serde_json::json!({
+                "base_url": "http://127.0.0.1:1",
+                "credentials": {"api_key": "k"}
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    TestContext {
+        app,
+        admin,
+        viewer: viewer_cookie,
+        viewer_id: viewer.id,
+        downloader: downloader_cookie,
+        downloader_id: downloader.id,
+    }
+}
+
+// ============================================================================
+// Authentication / authorization
+// ============================================================================
+
+#[tokio::test]
+async fn test_list_requests_requires_auth() {
+    let ctx = setup(false).await;
+    let (status, _) = make_request(ctx.app, "GET", "/api/integrations/requests", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_list_requests_without_request_manager_returns_404() {
+    let ctx = setup(false).await;
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/integrations/requests",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_list_requests_without_app_access_forbidden() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/integrations/requests",
+        Some(&ctx.downloader),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_viewer_cannot_list_all_requests() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/integrations/requests?all=true",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_viewer_cannot_approve_or_manage_links() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/integrations/requests/1/approve",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = make_request(
+        ctx.app,
+        "GET",
+        "/api/integrations/requests/links",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+#[tokio::test]
+async fn test_unlinked_user_sees_no_requests() {
+    let ctx = setup(true).await;
+    let (status, body) = make_request(
+        ctx.app,
+        "GET",
+        "/api/integrations/requests",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["app_name"], "jellyseerr");
+    assert_eq!(body["requests"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_create_request_requires_linked_account() {
+    let ctx = setup(true).await;
+    let (status, body) = make_request(
+        ctx.app,
+        "POST",
+        "/api/integrations/requests",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"media_type": "movie", "media_id": 603})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.to_string().contains("not linked"));
+}
+
+#[tokio::test]
+async fn test_create_request_rejects_invalid_media_type() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app,
+        "POST",
+        "/api/integrations/requests",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"media_type": "music", "media_id": 1})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_linked_request_with_unreachable_manager_returns_503() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &format!("/api/integrations/requests/links/{}", ctx.viewer_id),
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 5})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/integrations/requests",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"media_type": "tv", "media_id": 1399, "seasons": [1]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    let (status, _) = make_request(
+        ctx.app,
+        "POST",
+        "/api/integrations/requests/12/approve",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+// ============================================================================
+// Account links
+// ============================================================================
+
+#[tokio::test]
+async fn test_account_link_lifecycle() {
+    let ctx = setup(true).await;
+    let uri = format!("/api/integrations/requests/links/{}", ctx.viewer_id);
+
+    let (status, body) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 5, "external_username": "viewer"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["app_name"], "jellyseerr");
+    assert_eq!(body["username"], "req_viewer");
+    assert_eq!(body["external_user_id"], 5);
+
+    // Re-linking updates the existing link
+    let (status, body) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 6})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["external_user_id"], 6);
+
+    let (status, body) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/integrations/requests/links",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let links = body.as_array().unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["user_id"], ctx.viewer_id);
+
+    let (status, _) = make_request(ctx.app.clone(), "DELETE", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(ctx.app, "DELETE", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_external_account_cannot_be_linked_twice() {
+    let ctx = setup(true).await;
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &format!("/api/integrations/requests/links/{}", ctx.viewer_id),
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 5})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(
+        ctx.app,
+        "PUT",
+        &format!("/api/integrations/requests/links/{}", ctx.downloader_id),
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 5})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_link_validation() {
+    let ctx = setup(true).await;
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        "/api/integrations/requests/links/99999",
+        Some(&ctx.admin),
+        Some(serde_json::json!({"external_user_id": 5})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = make_request(
+        ctx.app,
+        "PUT",
+        &format!("/api/integrations/requests/links/{}", ctx.viewer_id),
+        Some(&ctx.admin),
+        Some(serde_json::json!({"app_name": "sonarr", "external_user_id": 5})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
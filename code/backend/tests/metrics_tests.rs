@@ -0,0 +1,84 @@
+//! Integration tests for `GET /metrics`
+//!
+//! Covers:
+//! - 401 without a session and 403 without `monitoring.view`
+//! - the Prometheus content type
+//! - request durations labelled by route template rather than path
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+
+use kubarr::services::telemetry::prometheus::CONTENT_TYPE;
+
+/// GET `uri`, returning the status, `Content-Type` and body
+async fn get(env: &TestEnv, uri: &str, cookie: Option<&str>) -> (StatusCode, String, String) {
+    let mut builder = Request::builder().uri(uri);
+    if let Some(cookie) = cookie {
+        builder = builder.header(header::COOKIE, cookie);
+    }
+    let response = env
+        .router
+        .clone()
+        .oneshot(builder.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )
+}
+
+#[tokio::test]
+async fn test_metrics_require_monitoring_view() {
+    let env = TestEnv::builder()
+        .with_viewer()
+        .with_user("dl", "downloader")
+        .build()
+        .await;
+
+    let (status, _, _) = get(&env, "/metrics", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _, _) = get(&env, "/metrics", Some(env.cookie("dl"))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, content_type, _) = get(&env, "/metrics", Some(env.cookie("viewer"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, CONTENT_TYPE);
+}
+
+#[tokio::test]
+async fn test_metrics_label_requests_by_route() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let cookie = env.cookie("admin");
+
+    let (status, _, _) = get(&env, "/api/users/424242", Some(cookie)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, _, text) = get(&env, "/metrics", Some(cookie)).await;
+    assert!(
+        text.contains(
+            "kubarr_http_request_duration_seconds_count{method=\"GET\",route=\"/api/users/{user_id}\",status=\"404\"}"
+        ),
+        "{}",
+        text
+    );
+    assert!(!text.contains("/api/users/424242"));
+    assert!(text.contains("# TYPE kubarr_db_pool_connections gauge"));
+    assert!(text.contains("kubarr_kubernetes_client_errors_total"));
+}
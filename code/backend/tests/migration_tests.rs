@@ -51,6 +51,17 @@ async fn cleanup_postgres_tables(db: &DatabaseConnection) {
         "notification_channels",
         "audit_logs",
         "invites",
+        "ip_bans",
+        "media_profiles",
+        "provisioning_profiles",
+        "registries",
+        "trusted_certificates",
+        "client_certificates",
+        "service_account_tokens",
+        "app_access_overrides",
+        "app_access_schedules",
+        "access_review_items",
+        "access_reviews",
         "user_preferences",
         "system_settings",
         "pending_2fa_challenges",
@@ -58,6 +69,24 @@ async fn cleanup_postgres_tables(db: &DatabaseConnection) {
         "oauth_accounts",
         "vpn_providers",
         "app_vpn_configs",
+        "app_integrations",
+        "app_account_links",
+        "media_account_links",
+        "network_usage",
+        "network_quotas",
+        "wan_health_checks",
+        "uptime_checks",
+        "uptime_monitors",
+        "pod_restart_events",
+        "dashboards",
+        "metric_anomalies",
+        "anomaly_sensitivities",
+        "energy_usage",
+        "node_power_profiles",
+        "log_alert_rules",
+        "alert_events",
+        "alerts",
+        "notification_routes",
         "role_permissions",
         "role_app_permissions",
         "user_roles",
@@ -314,12 +343,18 @@ async fn all_tables_created_impl(db: &DatabaseConnection) {
     let tables = get_table_names(db).await;
 
     let expected_tables = [
+        "app_account_links",
+        "app_integrations",
         "app_vpn_configs",
         "audit_logs",
         "bootstrap_status",
+        "invite_batches",
         "invites",
+        "network_quotas",
+        "network_usage",
         "notification_channels",
         "notification_events",
+        "notification_templates",
         "notification_logs",
         "oauth_accounts",
         "oauth_providers",
@@ -336,8 +371,44 @@ async fn all_tables_created_impl(db: &DatabaseConnection) {
         "user_roles",
         "users",
         "vpn_providers",
+        "wan_health_checks",
         "two_factor_recovery_codes",
         "cloudflare_tunnels",
+        "uptime_checks",
+        "uptime_monitors",
+        "pod_restart_events",
+        "dashboards",
+        "metric_anomalies",
+        "anomaly_sensitivities",
+        "energy_usage",
+        "node_power_profiles",
+        "log_alert_rules",
+        "alerts",
+        "alert_events",
+        "notification_routes",
+        "installed_apps",
+        "kubarr_updates",
+        "report_subscriptions",
+        "file_checksums",
+        "storage_shares",
+        "share_credentials",
+        "log_archives",
+        "app_install_requests",
+        "resource_quotas",
+        "storage_homes",
+        "tenants",
+        "tenant_members",
+        "ip_bans",
+        "access_reviews",
+        "access_review_items",
+        "app_access_schedules",
+        "app_access_overrides",
+        "media_profiles",
+        "provisioning_profiles",
+        "registries",
+        "trusted_certificates",
+        "client_certificates",
+        "service_account_tokens",
     ];
 
     for table in expected_tables {
@@ -384,6 +455,8 @@ async fn users_table_structure_impl(db: &DatabaseConnection) {
         "totp_verified_at",
         "created_at",
         "updated_at",
+        "failed_login_attempts",
+        "locked_until",
     ];
 
     for col in expected_columns {
@@ -879,7 +952,7 @@ async fn migration_count_impl(db: &DatabaseConnection) {
         .expect("Failed to query migrations");
 
     let count: i64 = result[0].try_get("", "cnt").unwrap();
-    assert_eq!(count, 26, "Should have exactly 26 migrations applied");
+    assert_eq!(count, 91, "Should have exactly 91 migrations applied");
 }
 
 test_both_databases!(test_migration_count, migration_count_impl);
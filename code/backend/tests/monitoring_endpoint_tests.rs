@@ -12,6 +12,8 @@
 //! - `GET /api/monitoring/health/{app_name}`    — app health (via K8s)
 //! - `GET /api/monitoring/endpoints/{app_name}` — service endpoints (via K8s)
 //! - `GET /api/monitoring/metrics-available`    — metrics-server probe
+//! - `GET /api/monitoring/apps/{app_name}/transcodes` — media server transcode load
+//! - `GET /api/monitoring/apps/{app_name}/stability`  — restart/OOM statistics
 //!
 //! Strategy: VictoriaMetrics and the Kubernetes API server are absent in the
 //! test environment.  The VM helpers silently return empty vectors on network
@@ -1251,3 +1253,309 @@ async fn test_vm_cluster_response_shape_when_vm_unreachable() {
         "Response must include storage_usage_percent"
     );
 }
+
+// ============================================================================
+// Transcode monitoring
+// ============================================================================
+
+#[tokio::test]
+async fn test_transcodes_requires_auth() {
+    ensure_jwt_keys().await;
+    let state = build_test_app_state_with_db(create_test_db_with_seed().await).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/jellyfin/transcodes")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_transcodes_unknown_media_server_returns_404() {
+    let (state, cookie) = setup_authenticated_state().await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/sonarr/transcodes")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_transcodes_reports_unavailable_server() {
+    use http_body_util::BodyExt;
+
+    let (state, cookie) = setup_authenticated_state().await;
+
+    // Without K8s or a base URL override the server can't be reached, but the
+    // endpoint still returns a report with node load and the error
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/jellyfin/transcodes")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["app_name"], "jellyfin");
+    assert_eq!(json["available"], false);
+    assert_eq!(json["transcodes"], 0);
+    assert!(json["error"].is_string());
+    assert!(json["node_load"].is_object());
+    assert!(json["capacity_alerts"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_transcodes_without_app_access_forbidden() {
+    ensure_jwt_keys().await;
+
+    // Seeded viewer role has monitoring.view but no access to Plex
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "monuser",
+        "monuser@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let cookie = login_as_admin(create_router(state.clone())).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/plex/transcodes")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Pod stability
+// ============================================================================
+
+#[tokio::test]
+async fn test_stability_requires_auth() {
+    ensure_jwt_keys().await;
+    let state = build_test_app_state_with_db(create_test_db_with_seed().await).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/sonarr/stability")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_stability_invalid_window_returns_400() {
+    let (state, cookie) = setup_authenticated_state().await;
+
+    for uri in [
+        "/api/monitoring/apps/sonarr/stability?windows=7w",
+        "/api/monitoring/apps/sonarr/stability?windows=24h,90d",
+    ] {
+        let response = create_router(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header("cookie", &cookie)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_stability_aggregates_restarts_with_trend() {
+    use chrono::{Duration, Utc};
+    use http_body_util::BodyExt;
+    use kubarr::models::pod_restart_event;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let (state, cookie) = setup_authenticated_state().await;
+    let db = state.get_db().await.unwrap();
+
+    // OOM kills the week before a fix, a single crash afterwards, and an
+    // unrelated app that must not be counted
+    let now = Utc::now();
+    for (app, hours_ago, restarts, reason, exit_code) in [
+        ("sonarr", 24 * 9, 3, "OOMKilled", 137),
+        ("sonarr", 24 * 8, 2, "OOMKilled", 137),
+        ("sonarr", 24 * 3, 1, "Error", 1),
+        ("radarr", 1, 7, "Error", 2),
+    ] {
+        pod_restart_event::ActiveModel {
+            app_name: Set(app.to_string()),
+            pod_name: Set(format!("{}-0", app)),
+            container_name: Set(app.to_string()),
+            restart_count: Set(restarts),
+            restarts: Set(restarts),
+            reason: Set(Some(reason.to_string())),
+            exit_code: Set(Some(exit_code)),
+            occurred_at: Set(now - Duration::hours(hours_ago)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/sonarr/stability?windows=24h,7d")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["app_name"], "sonarr");
+    assert!(json["containers"].as_array().unwrap().is_empty());
+    assert!(json["last_restart_at"].is_string());
+
+    let windows = json["windows"].as_array().unwrap();
+    assert_eq!(windows.len(), 2);
+
+    assert_eq!(windows[0]["window"], "24h");
+    assert_eq!(windows[0]["restarts"], 0);
+    assert_eq!(windows[0]["previous_restarts"], 0);
+    assert_eq!(windows[0]["trend"], "stable");
+
+    assert_eq!(windows[1]["window"], "7d");
+    assert_eq!(windows[1]["hours"], 168);
+    assert_eq!(windows[1]["restarts"], 1);
+    assert_eq!(windows[1]["oom_kills"], 0);
+    assert_eq!(windows[1]["previous_restarts"], 5);
+    assert_eq!(windows[1]["previous_oom_kills"], 5);
+    assert_eq!(windows[1]["trend"], "improving");
+    assert_eq!(windows[1]["exit_codes"][0]["exit_code"], 1);
+    assert_eq!(windows[1]["exit_codes"][0]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_stability_without_app_access_forbidden() {
+    ensure_jwt_keys().await;
+
+    // Seeded viewer role has monitoring.view but no access to Sonarr
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "monuser",
+        "monuser@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let cookie = login_as_admin(create_router(state.clone())).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/apps/sonarr/stability")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_node_sensors_requires_auth() {
+    let state = build_test_app_state_with_db(create_test_db_with_seed().await).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/nodes/nuc/sensors")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_node_sensors_unavailable_reports_thresholds() {
+    use http_body_util::BodyExt;
+    use kubarr::models::system_setting;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let (state, cookie) = setup_authenticated_state().await;
+    let db = state.get_db().await.unwrap();
+    system_setting::ActiveModel {
+        key: Set("sensor_disk_critical_celsius".to_string()),
+        value: Set("55".to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/monitoring/nodes/nuc/sensors")
+                .header("cookie", &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // No node-exporter in tests: the node is reported without readings
+    assert_eq!(response.status(), StatusCode::OK);
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(json["node"], "nuc");
+    assert_eq!(json["available"], false);
+    assert_eq!(json["status"], "ok");
+    assert!(json["sensors"].as_array().unwrap().is_empty());
+    assert_eq!(json["thresholds"]["cpu_critical_celsius"], 90.0);
+    assert_eq!(json["thresholds"]["disk_critical_celsius"], 55.0);
+}
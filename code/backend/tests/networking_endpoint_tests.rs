@@ -3,6 +3,9 @@
 //! Covers endpoints under `/api/networking`:
 //! - `GET /api/networking/topology`  — network topology (requires networking.view)
 //! - `GET /api/networking/stats`     — per-app stats (requires networking.view)
+//! - `GET /api/networking/usage`     — bandwidth rollups and quota warnings
+//! - `PUT/DELETE /api/networking/usage/quotas/{app}` — quotas (requires networking.manage)
+//! - `GET /api/networking/wan-health` — WAN latency/packet loss/speed test history
 //!
 //! When K8s client is None (which it always is in tests), both topology and
 //! stats degrade gracefully, returning empty collections. This lets us cover
@@ -85,6 +88,30 @@ async fn authenticated_get(app: axum::Router, uri: &str, cookie: &str) -> (Statu
     (status, String::from_utf8_lossy(&body).to_string())
 }
 
+async fn authenticated_json(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Cookie", cookie)
+        .header("content-type", "application/json")
+        .body(match body {
+            Some(b) => Body::from(b.to_string()),
+            None => Body::empty(),
+        })
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
 // ============================================================================
 // Unauthenticated access tests
 // ============================================================================
@@ -328,3 +355,366 @@ async fn test_networking_ws_endpoint_exists() {
     // Without auth, should return 401
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+// ============================================================================
+// Bandwidth accounting
+// ============================================================================
+
+async fn usage_setup(
+    username: &str,
+) -> (kubarr::state::AppState, sea_orm::DatabaseConnection, String) {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        username,
+        &format!("{}@test.com", username),
+        "pass123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let (_, cookie) = do_login(create_router(state.clone()), username, "pass123").await;
+    (state, db, cookie.expect("admin login must succeed"))
+}
+
+#[tokio::test]
+async fn test_usage_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/networking/usage")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_usage_and_quotas_require_networking_permissions() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "usageviewer",
+        "usageviewer@test.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let (_, cookie) = do_login(create_router(state.clone()), "usageviewer", "pass123").await;
+    let cookie = cookie.expect("viewer login must succeed");
+
+    let (status, _) = authenticated_get(
+        create_router(state.clone()),
+        "/api/networking/usage",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = authenticated_json(
+        create_router(state),
+        "PUT",
+        "/api/networking/usage/quotas/qbittorrent",
+        &cookie,
+        Some(serde_json::json!({"monthly_limit_bytes": 1000})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_usage_empty_report() {
+    let (state, _db, cookie) = usage_setup("usageadmin1").await;
+
+    let (status, json) = authenticated_json(
+        create_router(state),
+        "GET",
+        "/api/networking/usage",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["period"], "day");
+    assert!(json["apps"].as_array().unwrap().is_empty());
+    assert!(json["warnings"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_usage_invalid_period_returns_400() {
+    let (state, _db, cookie) = usage_setup("usageadmin2").await;
+
+    let (status, _) = authenticated_json(
+        create_router(state),
+        "GET",
+        "/api/networking/usage?period=week",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_usage_rollups_and_quota_warnings() {
+    use kubarr::services::network_usage::{record_usage, TrafficSample};
+    use std::collections::HashMap;
+
+    let (state, db, cookie) = usage_setup("usageadmin3").await;
+
+    let mut samples = HashMap::new();
+    samples.insert(
+        "qbittorrent".to_string(),
+        TrafficSample {
+            rx_bytes: 900,
+            tx_bytes: 300,
+        },
+    );
+    samples.insert(
+        "sonarr".to_string(),
+        TrafficSample {
+            rx_bytes: 10,
+            tx_bytes: 5,
+        },
+    );
+    let now = chrono::Utc::now();
+    record_usage(&db, &samples, now).await.unwrap();
+    record_usage(&db, &samples, now).await.unwrap();
+
+    let (status, _) = authenticated_json(
+        create_router(state.clone()),
+        "PUT",
+        "/api/networking/usage/quotas/qbittorrent",
+        &cookie,
+        Some(serde_json::json!({"monthly_limit_bytes": 2000})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, json) = authenticated_json(
+        create_router(state.clone()),
+        "GET",
+        "/api/networking/usage",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let apps = json["apps"].as_array().unwrap();
+    assert_eq!(apps.len(), 2);
+    // Sorted by total traffic, samples accumulate into the same day
+    assert_eq!(apps[0]["app_name"], "qbittorrent");
+    assert_eq!(apps[0]["rx_bytes"], 1800);
+    assert_eq!(apps[0]["tx_bytes"], 600);
+    assert_eq!(apps[0]["history"].as_array().unwrap().len(), 1);
+    assert_eq!(apps[0]["current_month_bytes"], 2400);
+    assert_eq!(apps[0]["quota"]["exceeded"], true);
+    assert!(apps[1].get("quota").is_none());
+
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["app_name"], "qbittorrent");
+    assert_eq!(warnings[0]["severity"], "critical");
+
+    let (status, json) = authenticated_json(
+        create_router(state),
+        "GET",
+        "/api/networking/usage?period=month",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["period"], "month");
+    assert_eq!(json["apps"][0]["total_bytes"], 2400);
+}
+
+#[tokio::test]
+async fn test_quota_crud() {
+    let (state, _db, cookie) = usage_setup("usageadmin4").await;
+    let uri = "/api/networking/usage/quotas/radarr";
+
+    let (status, _) = authenticated_json(
+        create_router(state.clone()),
+        "PUT",
+        uri,
+        &cookie,
+        Some(serde_json::json!({"monthly_limit_bytes": 0})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = authenticated_json(
+        create_router(state.clone()),
+        "PUT",
+        uri,
+        &cookie,
+        Some(serde_json::json!({"monthly_limit_bytes": 5000})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["monthly_limit_bytes"], 5000);
+
+    // Quota without traffic still shows up in the report
+    let (_, json) = authenticated_json(
+        create_router(state.clone()),
+        "GET",
+        "/api/networking/usage",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(json["apps"][0]["app_name"], "radarr");
+    assert_eq!(json["apps"][0]["quota"]["used_percent"], 0.0);
+
+    let (status, json) = authenticated_json(
+        create_router(state.clone()),
+        "GET",
+        "/api/networking/usage/quotas",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 1);
+
+    let (status, _) =
+        authenticated_json(create_router(state.clone()), "DELETE", uri, &cookie, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = authenticated_json(create_router(state), "DELETE", uri, &cookie, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// WAN health
+// ============================================================================
+
+#[tokio::test]
+async fn test_wan_health_requires_auth() {
+    let db = create_test_db_with_seed().await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/networking/wan-health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_wan_health_unknown_without_checks() {
+    let (state, _db, cookie) = usage_setup("wanadmin1").await;
+
+    let (status, json) = authenticated_json(
+        create_router(state),
+        "GET",
+        "/api/networking/wan-health",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["status"], "unknown");
+    assert!(json["targets"].as_array().unwrap().is_empty());
+    assert!(json["history"].as_array().unwrap().is_empty());
+    assert!(json["speed_tests"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_wan_health_reports_latest_round_and_history() {
+    use kubarr::services::wan_health::{record_probes, record_speed_test, ProbeResult};
+
+    let (state, db, cookie) = usage_setup("wanadmin2").await;
+    let probe = |target: &str, latency_ms: Option<f64>, loss: f64| ProbeResult {
+        target: target.to_string(),
+        latency_ms,
+        packet_loss_percent: loss,
+        error: latency_ms
+            .is_none()
+            .then(|| "Connection refused".to_string()),
+    };
+
+    let now = chrono::Utc::now();
+    let earlier = now - chrono::Duration::minutes(5);
+    record_probes(
+        &db,
+        &[
+            probe("1.1.1.1:443", Some(12.0), 0.0),
+            probe("8.8.8.8:443", Some(18.0), 0.0),
+        ],
+        earlier,
+    )
+    .await
+    .unwrap();
+    record_probes(
+        &db,
+        &[
+            probe("1.1.1.1:443", Some(20.0), 40.0),
+            probe("8.8.8.8:443", None, 100.0),
+        ],
+        now,
+    )
+    .await
+    .unwrap();
+    record_speed_test(&db, "https://speed.example/file", Ok(250.5), now)
+        .await
+        .unwrap();
+
+    let (status, json) = authenticated_json(
+        create_router(state.clone()),
+        "GET",
+        "/api/networking/wan-health",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["status"], "degraded");
+    assert!(json["reason"]
+        .as_str()
+        .unwrap()
+        .contains("packet loss 70.0%"));
+
+    let targets = json["targets"].as_array().unwrap();
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0]["target"], "1.1.1.1:443");
+    assert_eq!(targets[0]["reachable"], true);
+    assert_eq!(targets[0]["avg_latency_ms"], 16.0);
+    assert_eq!(targets[1]["reachable"], false);
+    assert_eq!(targets[1]["error"], "Connection refused");
+
+    let history = json["history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["status"], "healthy");
+    assert_eq!(history[1]["status"], "degraded");
+
+    let speed_tests = json["speed_tests"].as_array().unwrap();
+    assert_eq!(speed_tests.len(), 1);
+    assert_eq!(speed_tests[0]["download_mbps"], 250.5);
+
+    let (status, _) = authenticated_json(
+        create_router(state),
+        "GET",
+        "/api/networking/wan-health?hours=0",
+        &cookie,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
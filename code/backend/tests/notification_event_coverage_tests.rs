@@ -42,6 +42,13 @@ async fn setup_all_events(db: &sea_orm::DatabaseConnection) {
         "2fa_verified",
         "2fa_failed",
         "password_changed",
+        "new_device_login",
+        "session_revoked",
+        "ip_banned",
+        "ip_unbanned",
+        "access_denied",
+        "account_locked",
+        "refresh_token_reused",
         "user_created",
         "user_updated",
         "user_deleted",
@@ -53,6 +60,12 @@ async fn setup_all_events(db: &sea_orm::DatabaseConnection) {
         "role_deleted",
         "role_assigned",
         "role_unassigned",
+        "role_expiring",
+        "access_review_started",
+        "access_confirmed",
+        "access_revoked",
+        "schedule_override_granted",
+        "schedule_override_revoked",
         "app_installed",
         "app_uninstalled",
         "app_started",
@@ -60,10 +73,37 @@ async fn setup_all_events(db: &sea_orm::DatabaseConnection) {
         "app_restarted",
         "app_configured",
         "app_accessed",
+        "apps_suspended",
+        "app_install_requested",
+        "app_install_denied",
+        "app_upgraded",
+        "app_shell_opened",
+        "app_shell_closed",
+        "media_requested",
+        "media_request_approved",
+        "media_request_declined",
+        "wan_degraded",
+        "wan_recovered",
+        "uptime_monitor_down",
+        "uptime_monitor_up",
+        "metric_anomaly",
+        "node_temperature_critical",
+        "log_alert_firing",
+        "log_alert_resolved",
+        "alert_assigned",
+        "storage_quota_warning",
+        "storage_quota_exceeded",
         "system_setting_changed",
         "invite_created",
         "invite_used",
         "invite_deleted",
+        "update_available",
+        "update_started",
+        "update_completed",
+        "update_failed",
+        "backup_created",
+        "backup_restored",
+        "backup_failed",
         "api_access",
     ];
 
@@ -97,6 +137,13 @@ async fn test_notify_event_all_actions_without_details() {
         AuditAction::TwoFactorVerified,
         AuditAction::TwoFactorFailed,
         AuditAction::PasswordChanged,
+        AuditAction::NewDeviceLogin,
+        AuditAction::SessionRevoked,
+        AuditAction::IpBanned,
+        AuditAction::IpUnbanned,
+        AuditAction::AccessDenied,
+        AuditAction::AccountLocked,
+        AuditAction::RefreshTokenReused,
         AuditAction::UserCreated,
         AuditAction::UserUpdated,
         AuditAction::UserDeleted,
@@ -108,6 +155,12 @@ async fn test_notify_event_all_actions_without_details() {
         AuditAction::RoleDeleted,
         AuditAction::RoleAssigned,
         AuditAction::RoleUnassigned,
+        AuditAction::RoleExpiring,
+        AuditAction::AccessReviewStarted,
+        AuditAction::AccessConfirmed,
+        AuditAction::AccessRevoked,
+        AuditAction::ScheduleOverrideGranted,
+        AuditAction::ScheduleOverrideRevoked,
         AuditAction::AppInstalled,
         AuditAction::AppUninstalled,
         AuditAction::AppStarted,
@@ -115,10 +168,37 @@ async fn test_notify_event_all_actions_without_details() {
         AuditAction::AppRestarted,
         AuditAction::AppConfigured,
         AuditAction::AppAccessed,
+        AuditAction::AppsSuspended,
+        AuditAction::AppInstallRequested,
+        AuditAction::AppInstallDenied,
+        AuditAction::AppUpgraded,
+        AuditAction::AppShellOpened,
+        AuditAction::AppShellClosed,
+        AuditAction::MediaRequested,
+        AuditAction::MediaRequestApproved,
+        AuditAction::MediaRequestDeclined,
+        AuditAction::WanDegraded,
+        AuditAction::WanRecovered,
+        AuditAction::UptimeMonitorDown,
+        AuditAction::UptimeMonitorUp,
+        AuditAction::MetricAnomaly,
+        AuditAction::NodeTemperatureCritical,
+        AuditAction::LogAlertFiring,
+        AuditAction::LogAlertResolved,
+        AuditAction::AlertAssigned,
+        AuditAction::StorageQuotaWarning,
+        AuditAction::StorageQuotaExceeded,
         AuditAction::SystemSettingChanged,
         AuditAction::InviteCreated,
         AuditAction::InviteUsed,
         AuditAction::InviteDeleted,
+        AuditAction::UpdateAvailable,
+        AuditAction::UpdateStarted,
+        AuditAction::UpdateCompleted,
+        AuditAction::UpdateFailed,
+        AuditAction::BackupCreated,
+        AuditAction::BackupRestored,
+        AuditAction::BackupFailed,
         AuditAction::ApiAccess,
     ];
 
@@ -155,6 +235,13 @@ async fn test_notify_event_all_actions_with_details() {
         AuditAction::TwoFactorVerified,
         AuditAction::TwoFactorFailed,
         AuditAction::PasswordChanged,
+        AuditAction::NewDeviceLogin,
+        AuditAction::SessionRevoked,
+        AuditAction::IpBanned,
+        AuditAction::IpUnbanned,
+        AuditAction::AccessDenied,
+        AuditAction::AccountLocked,
+        AuditAction::RefreshTokenReused,
         AuditAction::UserCreated,
         AuditAction::UserUpdated,
         AuditAction::UserDeleted,
@@ -166,6 +253,12 @@ async fn test_notify_event_all_actions_with_details() {
         AuditAction::RoleDeleted,
         AuditAction::RoleAssigned,
         AuditAction::RoleUnassigned,
+        AuditAction::RoleExpiring,
+        AuditAction::AccessReviewStarted,
+        AuditAction::AccessConfirmed,
+        AuditAction::AccessRevoked,
+        AuditAction::ScheduleOverrideGranted,
+        AuditAction::ScheduleOverrideRevoked,
         AuditAction::AppInstalled,
         AuditAction::AppUninstalled,
         AuditAction::AppStarted,
@@ -173,10 +266,37 @@ async fn test_notify_event_all_actions_with_details() {
         AuditAction::AppRestarted,
         AuditAction::AppConfigured,
         AuditAction::AppAccessed,
+        AuditAction::AppsSuspended,
+        AuditAction::AppInstallRequested,
+        AuditAction::AppInstallDenied,
+        AuditAction::AppUpgraded,
+        AuditAction::AppShellOpened,
+        AuditAction::AppShellClosed,
+        AuditAction::MediaRequested,
+        AuditAction::MediaRequestApproved,
+        AuditAction::MediaRequestDeclined,
+        AuditAction::WanDegraded,
+        AuditAction::WanRecovered,
+        AuditAction::UptimeMonitorDown,
+        AuditAction::UptimeMonitorUp,
+        AuditAction::MetricAnomaly,
+        AuditAction::NodeTemperatureCritical,
+        AuditAction::LogAlertFiring,
+        AuditAction::LogAlertResolved,
+        AuditAction::AlertAssigned,
+        AuditAction::StorageQuotaWarning,
+        AuditAction::StorageQuotaExceeded,
         AuditAction::SystemSettingChanged,
         AuditAction::InviteCreated,
         AuditAction::InviteUsed,
         AuditAction::InviteDeleted,
+        AuditAction::UpdateAvailable,
+        AuditAction::UpdateStarted,
+        AuditAction::UpdateCompleted,
+        AuditAction::UpdateFailed,
+        AuditAction::BackupCreated,
+        AuditAction::BackupRestored,
+        AuditAction::BackupFailed,
         AuditAction::ApiAccess,
     ];
 
@@ -0,0 +1,166 @@
+//! Integration tests for notifications in the recipient's locale
+//!
+//! Covers:
+//! - the `locale` field of `PATCH /api/users/me/preferences`
+//! - the `default_locale` setting
+//! - in-app notifications rendered in the recipient's locale
+
+use axum::http::StatusCode;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::audit_log::AuditAction;
+use kubarr::models::user_notification::{self, Entity as UserNotification};
+
+async fn setup() -> TestEnv {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    env.request(
+        "PUT",
+        "/api/notifications/events/app_installed",
+        Some(env.cookie("admin")),
+        Some(json!({"enabled": true})),
+    )
+    .await;
+    env
+}
+
+/// Newest in-app notification of a user, as (title, message)
+async fn notify(env: &TestEnv, username: &str) -> (String, String) {
+    let user_id = env.user(username).user.id;
+    env.state
+        .notification
+        .notify_event(
+            &AuditAction::AppInstalled,
+            Some(user_id),
+            Some(username),
+            Some("sonarr"),
+        )
+        .await
+        .unwrap();
+    let notification = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(user_id))
+        .order_by_desc(user_notification::Column::Id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("in-app notification");
+    (notification.title, notification.message)
+}
+
+#[tokio::test]
+async fn test_locale_preference() {
+    let env = setup().await;
+
+    let (status, body) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("viewer")),
+            Some(json!({"locale": "nl-BE"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["locale"], "nl");
+
+    let (status, _) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("viewer")),
+            Some(json!({"locale": "klingon"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Changing the theme keeps the locale
+    let (_, body) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("viewer")),
+            Some(json!({"theme": "dark"})),
+        )
+        .await;
+    assert_eq!(body["locale"], "nl");
+
+    let (_, body) = env
+        .request(
+            "PATCH",
+            "/api/users/me/preferences",
+            Some(env.cookie("viewer")),
+            Some(json!({"locale": ""})),
+        )
+        .await;
+    assert!(body["locale"].is_null());
+}
+
+#[tokio::test]
+async fn test_notifications_use_recipient_locale() {
+    let env = setup().await;
+    env.request(
+        "PATCH",
+        "/api/users/me/preferences",
+        Some(env.cookie("viewer")),
+        Some(json!({"locale": "nl"})),
+    )
+    .await;
+
+    assert_eq!(
+        notify(&env, "viewer").await,
+        (
+            "App geïnstalleerd".to_string(),
+            "App geïnstalleerd door viewer: sonarr".to_string()
+        )
+    );
+    // Users without a preference get the default locale
+    assert_eq!(notify(&env, "admin").await.0, "App Installed");
+}
+
+#[tokio::test]
+async fn test_default_locale_setting() {
+    let env = setup().await;
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/default_locale",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "fr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/settings/default_locale",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "de"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        notify(&env, "admin").await,
+        (
+            "App installiert".to_string(),
+            "App von admin installiert: sonarr".to_string()
+        )
+    );
+
+    // A preference wins over the default
+    env.request(
+        "PATCH",
+        "/api/users/me/preferences",
+        Some(env.cookie("admin")),
+        Some(json!({"locale": "en"})),
+    )
+    .await;
+    assert_eq!(notify(&env, "admin").await.0, "App Installed");
+}
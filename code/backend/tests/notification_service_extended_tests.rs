@@ -4,6 +4,8 @@
 //! - `notify_event` pipeline — no-op when DB missing, no-op when event not configured,
 //!   no-op when event disabled, creates in-app notification when event is enabled
 //! - `init_providers` — initialises from DB, no-op when no channels exist
+//! - Channel and user preference delivery filters in `send_external_notifications`
+//! - Fallback chain for critical events whose primary channel fails
 //! - `test_channel` — returns error when channel not configured
 //! - `send_to_channel` (via test_channel) — all three channel types
 //! - `NotificationService::default()` — uses same code path as `new()`
@@ -17,7 +19,6 @@ use common::{create_test_db, create_test_user};
 use kubarr::models::{audit_log::AuditAction, notification_event, user_notification};
 use kubarr::services::notification::NotificationService;
 use sea_orm::{ActiveModelTrait, Set};
-use std::sync::Arc;
 
 // ---------------------------------------------------------------------------
 // Helper: build a NotificationService backed by `db`
@@ -93,7 +94,6 @@ fn test_notification_service_new_and_default_are_equivalent() {
 
 #[test]
 fn test_notification_service_clone_shares_db_arc() {
-    use std::sync::Arc;
     let svc = NotificationService::new();
     let cloned = svc.clone();
     // Both point to the same Arc — verified by constructing and comparing via
@@ -750,3 +750,176 @@ async fn test_notify_event_with_user_prefs_covers_external_path() {
     let count = svc.get_unread_count(user.id).await.unwrap();
     assert_eq!(count, 1, "In-app notification must be created");
 }
+
+// ===========================================================================
+// 21. send_external_notifications — channel and user preference filters
+// ===========================================================================
+
+#[tokio::test]
+async fn test_notify_event_applies_channel_and_pref_filters() {
+    use kubarr::models::{notification_channel, notification_log, user_notification_pref};
+    use sea_orm::{EntityTrait, PaginatorTrait};
+
+    let db = create_test_db().await;
+    let user = create_test_user(&db, "notify_filter", "nf@example.com", "pw", true).await;
+    enable_event(&db, "login", "info").await;
+    enable_event(&db, "login_failed", "warning").await;
+    enable_event(&db, "wan_degraded", "critical").await;
+
+    let now = chrono::Utc::now();
+    // Globally, email only carries warnings and above
+    notification_channel::ActiveModel {
+        channel_type: Set("email".to_string()),
+        enabled: Set(true),
+        config: Set("{}".to_string()),
+        min_severity: Set(Some("warning".to_string())),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    // This user additionally opted out of failed logins
+    user_notification_pref::ActiveModel {
+        user_id: Set(user.id),
+        channel_type: Set("email".to_string()),
+        enabled: Set(true),
+        destination: Set(Some("nf@example.com".to_string())),
+        verified: Set(true),
+        exclude_event_types: Set(r#"["login_failed"]"#.to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let (svc, db) = make_service(db).await;
+    for action in [
+        AuditAction::Login,
+        AuditAction::LoginFailed,
+        AuditAction::WanDegraded,
+    ] {
+        svc.notify_event(&action, Some(user.id), Some("notify_filter"), None)
+            .await
+            .unwrap();
+    }
+
+    // Only the critical event passes both filters; the attempt is logged
+    // (and fails, since no provider is configured)
+    let logs = notification_log::Entity::find().all(&db).await.unwrap();
+    assert_eq!(logs.len(), 1, "Only one event must reach the channel");
+    assert_eq!(logs[0].event_type, "wan_degraded");
+
+    // Filters only affect external channels, not the in-app inbox
+    let inbox = user_notification::Entity::find().count(&db).await.unwrap();
+    assert_eq!(inbox, 3);
+}
+
+// ===========================================================================
+// 22. Fallback chain — critical events move on to the user's next channel
+// ===========================================================================
+
+#[tokio::test]
+async fn test_critical_event_falls_back_to_next_channel() {
+    use kubarr::models::{notification_channel, notification_log, user_notification_pref};
+    use sea_orm::{EntityTrait, QueryOrder};
+
+    let db = create_test_db().await;
+    let user = create_test_user(&db, "notify_fallback", "nfb@example.com", "pw", true).await;
+    enable_event(&db, "login", "info").await;
+    enable_event(&db, "wan_degraded", "critical").await;
+
+    // Both providers load, but nothing listens on port 9, so every send fails
+    let now = chrono::Utc::now();
+    for (channel_type, config) in [
+        (
+            "telegram",
+            serde_json::json!({"bot_token": "123:abc", "api_url": "http://127.0.0.1:9"}),
+        ),
+        (
+            "email",
+            serde_json::json!({
+                "smtp_host": "127.0.0.1",
+                "smtp_port": 9,
+                "username": "",
+                "password": "",
+                "from_address": "kubarr@example.com",
+                "use_tls": false
+            }),
+        ),
+    ] {
+        notification_channel::ActiveModel {
+            channel_type: Set(channel_type.to_string()),
+            enabled: Set(true),
+            config: Set(config.to_string()),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+    // Telegram is the user's only enabled channel; email is a verified backup
+    for (channel_type, destination, enabled) in [
+        ("telegram", "12345", true),
+        ("email", "nfb@example.com", false),
+    ] {
+        user_notification_pref::ActiveModel {
+            user_id: Set(user.id),
+            channel_type: Set(channel_type.to_string()),
+            enabled: Set(enabled),
+            destination: Set(Some(destination.to_string())),
+            verified: Set(true),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+
+    let (svc, db) = make_service(db).await;
+    svc.init_providers().await.unwrap();
+
+    // A failed info event is not retried elsewhere
+    svc.notify_event(&AuditAction::Login, Some(user.id), None, None)
+        .await
+        .unwrap();
+    let logs = notification_log::Entity::find().all(&db).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].channel_type, "telegram");
+
+    // A failed critical event goes on to email, linked to the failed attempt
+    svc.notify_event(&AuditAction::WanDegraded, Some(user.id), None, None)
+        .await
+        .unwrap();
+    let logs = notification_log::Entity::find()
+        .order_by_asc(notification_log::Column::Id)
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 3);
+    let (primary, fallback) = (&logs[1], &logs[2]);
+    assert_eq!(primary.channel_type, "telegram");
+    assert_eq!(primary.status, "failed");
+    assert_eq!(primary.fallback_of, None);
+    assert_eq!(fallback.channel_type, "email");
+    assert_eq!(fallback.event_type, "wan_degraded");
+    assert_eq!(fallback.fallback_of, Some(primary.id));
+
+    // MessageBird is not configured, so the chain ends at email
+    let status = svc.provider_status().await;
+    let email = status.iter().find(|s| s.channel_type == "email").unwrap();
+    assert_eq!(email.fallback_deliveries, 1);
+    assert!(email.last_fallback_at.is_some());
+    let telegram = status
+        .iter()
+        .find(|s| s.channel_type == "telegram")
+        .unwrap();
+    assert_eq!(telegram.fallback_deliveries, 0);
+}
@@ -0,0 +1,237 @@
+//! Integration tests for notification template overrides
+//!
+//! Covers:
+//! - `GET/PUT /api/notifications/events/{event_type}/template`
+//! - `POST /api/notifications/events/{event_type}/template/preview`
+//! - delivery using the override, and the built-in text once it is cleared
+
+use axum::http::StatusCode;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::audit_log::AuditAction;
+use kubarr::models::user_notification::{self, Entity as UserNotification};
+
+const TEMPLATE: &str = "/api/notifications/events/app_installed/template";
+
+async fn setup() -> TestEnv {
+    TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await
+}
+
+/// Newest in-app notification of the viewer, as (title, message)
+async fn notify(env: &TestEnv) -> (String, String) {
+    let viewer = env.user("viewer").user.id;
+    env.state
+        .notification
+        .notify_event(
+            &AuditAction::AppInstalled,
+            Some(viewer),
+            Some("viewer"),
+            Some("sonarr"),
+        )
+        .await
+        .unwrap();
+    let notification = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(viewer))
+        .order_by_desc(user_notification::Column::Id)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("in-app notification");
+    (notification.title, notification.message)
+}
+
+#[tokio::test]
+async fn test_get_template_lists_channels_and_variables() {
+    let env = setup().await;
+
+    let (status, body) = env
+        .request("GET", TEMPLATE, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let channels: Vec<&str> = body["templates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["channel_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(channels, vec!["email", "telegram", "messagebird", "in_app"]);
+    assert!(body["templates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|t| t["subject"].is_null() && t["body"].is_null()));
+    assert!(body["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v["name"] == "username"));
+
+    let (status, _) = env
+        .request("GET", TEMPLATE, Some(env.cookie("viewer")), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/notifications/events/no_such_event/template",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invalid_templates_are_rejected() {
+    let env = setup().await;
+
+    for bad in [
+        json!({"channel_type": "email", "subject": "{{password}}"}),
+        json!({"channel_type": "email", "body": "Hi {{username"}),
+        json!({"channel_type": "pigeon", "subject": "{{title}}"}),
+    ] {
+        let (status, _) = env
+            .request("PUT", TEMPLATE, Some(env.cookie("admin")), Some(bad))
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, _) = env
+        .request(
+            "PUT",
+            TEMPLATE,
+            Some(env.cookie("viewer")),
+            Some(json!({"channel_type": "email", "subject": "{{title}}"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_preview_renders_sample_data() {
+    let env = setup().await;
+
+    // Without a template the built-in text is rendered
+    let (status, body) = env
+        .request(
+            "POST",
+            &format!("{}/preview", TEMPLATE),
+            Some(env.cookie("admin")),
+            Some(json!({"channel_type": "email"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["subject"], "App Installed");
+    assert_eq!(body["body"], "App installed by admin: sonarr");
+
+    // A draft, with a sample value replaced
+    let (status, body) = env
+        .request(
+            "POST",
+            &format!("{}/preview", TEMPLATE),
+            Some(env.cookie("admin")),
+            Some(json!({
+                "subject": "[Home] {{title}}",
+                "body": "{{details}} by {{username}} ({{severity}})",
+                "sample": {"details": "radarr"}
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["subject"], "[Home] App Installed");
+    assert_eq!(body["body"], "radarr by admin (info)");
+}
+
+#[tokio::test]
+async fn test_override_is_used_and_cleared() {
+    let env = setup().await;
+    env.request(
+        "PUT",
+        "/api/notifications/events/app_installed",
+        Some(env.cookie("admin")),
+        Some(json!({"enabled": true})),
+    )
+    .await;
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            TEMPLATE,
+            Some(env.cookie("admin")),
+            Some(json!({
+                "channel_type": "in_app",
+                "subject": "[Home] {{ title }}",
+                "body": "{{username}} added {{details}}"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let in_app = body["templates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["channel_type"] == "in_app")
+        .unwrap()
+        .clone();
+    assert_eq!(in_app["subject"], "[Home] {{ title }}");
+    assert!(!in_app["updated_at"].is_null());
+
+    assert_eq!(
+        notify(&env).await,
+        (
+            "[Home] App Installed".to_string(),
+            "viewer added sonarr".to_string()
+        )
+    );
+
+    // The saved template is what the preview falls back to
+    let (_, body) = env
+        .request(
+            "POST",
+            &format!("{}/preview", TEMPLATE),
+            Some(env.cookie("admin")),
+            Some(json!({"channel_type": "in_app"})),
+        )
+        .await;
+    assert_eq!(body["body"], "admin added sonarr");
+
+    // Only the subject: the body falls back to the built-in one
+    env.request(
+        "PUT",
+        TEMPLATE,
+        Some(env.cookie("admin")),
+        Some(json!({"channel_type": "in_app", "subject": "{{title}}!", "body": ""})),
+    )
+    .await;
+    assert_eq!(
+        notify(&env).await,
+        (
+            "App Installed!".to_string(),
+            "App installed by viewer: sonarr".to_string()
+        )
+    );
+
+    // Clearing both removes the override
+    let (status, body) = env
+        .request(
+            "PUT",
+            TEMPLATE,
+            Some(env.cookie("admin")),
+            Some(json!({"channel_type": "in_app"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["templates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|t| t["updated_at"].is_null()));
+    assert_eq!(notify(&env).await.0, "App Installed");
+}
@@ -1,11 +1,15 @@
 //! Notifications endpoint integration tests
 //!
 //! Covers all endpoints under `/api/notifications`:
-//! - Channels (CRUD + test): `settings.view` / `settings.manage` required
-//! - Events (list + update): `settings.view` / `settings.manage` required
-//! - User preferences (list + upsert): any authenticated user
+//! - Channels (CRUD + test + status/reload): `settings.view` / `settings.manage` required
+//! - Events (list + update, catalog + bulk update + reset): `settings.view` /
+//!   `settings.manage` required
+//! - Routing rules (CRUD): `settings.view` / `settings.manage` required
+//! - User preferences (list + upsert + test + code verification, including delivery
+//!   filters): any authenticated user
 //! - User inbox (list, mark-read, mark-all-read, delete): any authenticated user
 //! - Notification logs (list): `audit.view` required
+//! - Telegram button callbacks: public, authenticated by the webhook secret
 //!
 //! All operations are DB-only — no Kubernetes client is required.
 
@@ -685,6 +689,194 @@ async fn test_test_channel_returns_200_with_error_when_unconfigured() {
     );
 }
 
+// ============================================================================
+// GET /api/notifications/channels/status, POST /api/notifications/channels/reload
+// ============================================================================
+
+/// Find a channel's entry in a provider status response
+fn channel_entry<'a>(status: &'a serde_json::Value, channel_type: &str) -> &'a serde_json::Value {
+    status
+        .as_array()
+        .expect("Status response must be an array")
+        .iter()
+        .find(|c| c["channel_type"] == channel_type)
+        .expect("Status must list every channel type")
+}
+
+#[tokio::test]
+async fn test_channel_status_requires_settings_view() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "statusviewer",
+        "statusviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "statusviewer", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, _) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/channels/status",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = authenticated_post(
+        create_router(state),
+        "/api/notifications/channels/reload",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_update_channel_reloads_providers() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(&db, "reloadadmin", "reloadadmin@example.com", "password123")
+        .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "reloadadmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    // Nothing is initialized before any channel is configured
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/channels/status",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+    assert_eq!(channel_entry(&json, "telegram")["initialized"], false);
+
+    // Enabling a valid channel initializes its provider without a restart
+    let enable_body = serde_json::json!({
+        "enabled": true,
+        "config": {"bot_token": "123:abc"}
+    })
+    .to_string();
+    authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/channels/telegram",
+        &cookie,
+        &enable_body,
+    )
+    .await;
+    let (_, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/channels/status",
+        &cookie,
+    )
+    .await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let telegram = channel_entry(&json, "telegram");
+    assert_eq!(telegram["enabled"], true);
+    assert_eq!(
+        telegram["initialized"], true,
+        "Telegram must be initialized after update. Body: {}",
+        body
+    );
+    assert!(telegram["last_error"].is_null());
+    assert!(telegram["last_reload_at"].is_string());
+
+    // Disabling it drops the provider again
+    let disable_body = serde_json::json!({"enabled": false}).to_string();
+    authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/channels/telegram",
+        &cookie,
+        &disable_body,
+    )
+    .await;
+    let (_, body) = authenticated_get(
+        create_router(state),
+        "/api/notifications/channels/status",
+        &cookie,
+    )
+    .await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        channel_entry(&json, "telegram")["initialized"],
+        false,
+        "Disabled channel must not keep its provider. Body: {}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_reload_reports_invalid_channel_config() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(
+        &db,
+        "badconfigadmin",
+        "badconfigadmin@example.com",
+        "password123",
+    )
+    .await;
+
+    // Written directly so no reload has happened yet
+    let now = chrono::Utc::now();
+    kubarr::models::notification_channel::ActiveModel {
+        channel_type: Set("telegram".to_string()),
+        enabled: Set(true),
+        config: Set("{}".to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "badconfigadmin",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) = authenticated_post(
+        create_router(state),
+        "/api/notifications/channels/reload",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let telegram = channel_entry(&json, "telegram");
+    assert_eq!(telegram["enabled"], true);
+    assert_eq!(telegram["initialized"], false);
+    assert!(
+        telegram["last_error"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("Invalid Telegram config"),
+        "Invalid config must be reported. Body: {}",
+        body
+    );
+    assert!(telegram["last_error_at"].is_string());
+}
+
 // ============================================================================
 // GET /api/notifications/events — list events
 // ============================================================================
@@ -946,6 +1138,151 @@ async fn test_update_event_idempotent_second_call_updates() {
     );
 }
 
+// ============================================================================
+// Event catalog: GET /events/catalog, PUT /events, POST /events/reset
+// ============================================================================
+
+#[tokio::test]
+async fn test_event_catalog_bulk_update_and_reset() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(
+        &db,
+        "catalogadmin",
+        "catalogadmin@example.com",
+        "password123",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let (_, cookie) = do_login(create_router(state.clone()), "catalogadmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let catalog = |body: &str| -> Vec<serde_json::Value> {
+        serde_json::from_str::<serde_json::Value>(body)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .clone()
+    };
+    let entry = |entries: &[serde_json::Value], event_type: &str| {
+        entries
+            .iter()
+            .find(|e| e["event_type"] == event_type)
+            .cloned()
+            .unwrap_or_else(|| panic!("{} missing from catalog", event_type))
+    };
+
+    // Every audit action is listed, including ones outside the default catalog
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/events/catalog",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let entries = catalog(&body);
+    let stopped = entry(&entries, "app_stopped");
+    assert_eq!(stopped["default"], false);
+    assert_eq!(stopped["configured"], false);
+    assert_eq!(entry(&entries, "login")["default"], true);
+
+    // Bulk create and enable, including a non-default action type
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/events",
+        &cookie,
+        r#"{"events":[{"event_type":"app_stopped","enabled":true,"severity":"warning"},{"event_type":"login","enabled":true}]}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    assert_eq!(catalog(&body).len(), 2);
+
+    let (_, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/events/catalog",
+        &cookie,
+    )
+    .await;
+    let entries = catalog(&body);
+    let stopped = entry(&entries, "app_stopped");
+    assert_eq!(stopped["configured"], true);
+    assert_eq!(stopped["enabled"], true);
+    assert_eq!(stopped["severity"], "warning");
+    assert_eq!(entry(&entries, "login")["enabled"], true);
+
+    // Unknown types and severities are rejected before anything is written
+    for bad in [
+        r#"{"events":[{"event_type":"logout","enabled":true},{"event_type":"no_such_event","enabled":true}]}"#,
+        r#"{"events":[{"event_type":"logout","severity":"loud"}]}"#,
+    ] {
+        let (status, _) = authenticated_put(
+            create_router(state.clone()),
+            "/api/notifications/events",
+            &cookie,
+            bad,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // Reset disables everything and drops non-default configs
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/events/reset",
+        &cookie,
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let entries = catalog(&body);
+    assert!(entries.iter().all(|e| e["enabled"] == false));
+    assert_eq!(entry(&entries, "app_stopped")["configured"], false);
+    assert_eq!(entry(&entries, "login")["configured"], true);
+    assert_eq!(entry(&entries, "logout")["configured"], true);
+}
+
+#[tokio::test]
+async fn test_event_catalog_sync_adds_defaults_and_drops_unknown_rows() {
+    use kubarr::models::notification_event;
+    use kubarr::services::notification::events::sync_catalog;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    let db = create_test_db_with_seed().await;
+    notification_event::ActiveModel {
+        event_type: Set("removed_action".to_string()),
+        enabled: Set(true),
+        severity: Set("info".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    notification_event::ActiveModel {
+        event_type: Set("login".to_string()),
+        enabled: Set(true),
+        severity: Set("critical".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let (added, removed) = sync_catalog(&db).await.unwrap();
+    assert!(added > 0);
+    assert_eq!(removed, 1);
+
+    let login = notification_event::Entity::find()
+        .filter(notification_event::Column::EventType.eq("login"))
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(login.enabled, "sync must keep existing configs");
+    assert_eq!(login.severity, "critical");
+
+    assert_eq!(sync_catalog(&db).await.unwrap(), (0, 0));
+}
+
 // ============================================================================
 // GET /api/notifications/preferences
 // ============================================================================
@@ -1146,76 +1483,424 @@ async fn test_update_preference_invalid_channel_type_returns_400() {
     .await;
     let state = build_test_app_state_with_db(db).await;
 
-    let (_, cookie) = do_login(
-        create_router(state.clone()),
-        "invalidprefuser",
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "invalidprefuser",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, body) = authenticated_put(
+        create_router(state),
+        "/api/notifications/preferences/smoke_signal",
+        &cookie,
+        r#"{"enabled":true}"#,
+    )
+    .await;
+
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "Invalid channel type in preferences must return 400. Body: {}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_update_preference_changing_destination_resets_verified() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "resetverfuser",
+        "resetverfuser@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "resetverfuser", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    // First: set destination
+    let body1 =
+        serde_json::json!({"enabled": true, "destination": "first@example.com"}).to_string();
+    let (s1, _) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/preferences/email",
+        &cookie,
+        &body1,
+    )
+    .await;
+    assert_eq!(s1, StatusCode::OK);
+
+    // Second: change destination — verified must reset to false
+    let body2 = serde_json::json!({"destination": "second@example.com"}).to_string();
+    let (status, body) = authenticated_put(
+        create_router(state),
+        "/api/notifications/preferences/email",
+        &cookie,
+        &body2,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        json["verified"], false,
+        "Changing destination must reset verified=false"
+    );
+}
+
+#[tokio::test]
+async fn test_channel_and_preference_filters_round_trip() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(&db, "filteradmin", "filteradmin@example.com", "password123")
+        .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(create_router(state.clone()), "filteradmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let filter = serde_json::json!({
+        "min_severity": "warning",
+        "include_event_types": ["wan_degraded", "login_failed"],
+        "exclude_event_types": ["login_failed"]
+    });
+
+    let channel_body = serde_json::json!({"filter": filter}).to_string();
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/channels/email",
+        &cookie,
+        &channel_body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["filter"], filter);
+
+    let pref_body = serde_json::json!({
+        "enabled": true,
+        "filter": {"min_severity": "critical"}
+    })
+    .to_string();
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/preferences/telegram",
+        &cookie,
+        &pref_body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+
+    let (_, body) = authenticated_get(
+        create_router(state),
+        "/api/notifications/preferences",
+        &cookie,
+    )
+    .await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let telegram = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["channel_type"] == "telegram")
+        .unwrap();
+    assert_eq!(telegram["filter"]["min_severity"], "critical");
+    assert_eq!(
+        telegram["filter"]["include_event_types"],
+        serde_json::json!([])
+    );
+    let email = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["channel_type"] == "email")
+        .unwrap();
+    assert!(email["filter"]["min_severity"].is_null());
+}
+
+#[tokio::test]
+async fn test_invalid_filters_return_400() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(
+        &db,
+        "badfilteradmin",
+        "badfilteradmin@example.com",
+        "password123",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "badfilteradmin",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let bad_severity = serde_json::json!({"filter": {"min_severity": "urgent"}}).to_string();
+    let (status, _) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/channels/email",
+        &cookie,
+        &bad_severity,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let bad_event =
+        serde_json::json!({"filter": {"exclude_event_types": ["not_an_event"]}}).to_string();
+    let (status, body) = authenticated_put(
+        create_router(state),
+        "/api/notifications/preferences/email",
+        &cookie,
+        &bad_event,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("not_an_event"), "Body: {}", body);
+}
+
+#[tokio::test]
+async fn test_preference_test_sends_to_own_destination_and_is_rate_limited() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "preftestuser",
+        "preftestuser@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let (_, cookie) = do_login(create_router(state.clone()), "preftestuser", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    // Nothing to test before a destination is set
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/preferences/telegram/test",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "Body: {}", body);
+
+    let (status, _) = authenticated_put(
+        create_router(state.clone()),
+        "/api/notifications/preferences/telegram",
+        &cookie,
+        r#"{"destination": "12345"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Unverified destinations can be tested; the channel itself is not set up
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/preferences/telegram/test",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["success"], false);
+    assert!(json["error"].as_str().unwrap().contains("not configured"));
+
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/preferences/telegram/test",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "Body: {}", body);
+
+    let (status, _) = authenticated_post(
+        create_router(state),
+        "/api/notifications/preferences/pigeon/test",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// Store a verification code for a user's channel as if it had just been sent
+async fn set_pending_code(
+    db: &sea_orm::DatabaseConnection,
+    username: &str,
+    channel_type: &str,
+    code: &str,
+    sent_at: chrono::DateTime<chrono::Utc>,
+) {
+    use kubarr::models::{user, user_notification_pref};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    let user = user::Entity::find()
+        .filter(user::Column::Username.eq(username))
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    let pref = user_notification_pref::Entity::find()
+        .filter(user_notification_pref::Column::UserId.eq(user.id))
+        .filter(user_notification_pref::Column::ChannelType.eq(channel_type))
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: user_notification_pref::ActiveModel = pref.into();
+    active.verification_code_hash = Set(Some(
+        kubarr::services::security::hash_verification_code(code).unwrap(),
+    ));
+    active.verification_sent_at = Set(Some(sent_at));
+    active.update(db).await.unwrap();
+}
+
+/// Log in a viewer with an unverified MessageBird destination
+async fn setup_code_verification(
+    username: &str,
+) -> (sea_orm::DatabaseConnection, axum::Router, String) {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        username,
+        &format!("{}@example.com", username),
         "password123",
+        "viewer",
     )
     .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+    let app = create_router(state);
+    let (_, cookie) = do_login(app.clone(), username, "password123").await;
     let cookie = cookie.expect("Login must set a session cookie");
 
-    let (status, body) = authenticated_put(
-        create_router(state),
-        "/api/notifications/preferences/smoke_signal",
+    let (status, _) = authenticated_put(
+        app.clone(),
+        "/api/notifications/preferences/messagebird",
         &cookie,
-        r#"{"enabled":true}"#,
+        r#"{"enabled": true, "destination": "+31612345678"}"#,
     )
     .await;
-
-    assert_eq!(
-        status,
-        StatusCode::BAD_REQUEST,
-        "Invalid channel type in preferences must return 400. Body: {}",
-        body
-    );
+    assert_eq!(status, StatusCode::OK);
+    (db, app, cookie)
 }
 
 #[tokio::test]
-async fn test_update_preference_changing_destination_resets_verified() {
-    ensure_jwt_keys().await;
+async fn test_verify_code_marks_destination_verified() {
+    let (db, app, cookie) = setup_code_verification("codeuser").await;
 
-    let db = create_test_db_with_seed().await;
-    create_test_user_with_role(
-        &db,
-        "resetverfuser",
-        "resetverfuser@example.com",
-        "password123",
-        "admin",
+    // MessageBird is not set up, so the code cannot be delivered
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/notifications/preferences/messagebird/send-code",
+        &cookie,
+        "{}",
     )
     .await;
-    let state = build_test_app_state_with_db(db).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY, "Body: {}", body);
+    let (status, _) = authenticated_post(
+        app.clone(),
+        "/api/notifications/preferences/messagebird/verify-code",
+        &cookie,
+        r#"{"code": "123456"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "No code was stored");
 
-    let (_, cookie) = do_login(create_router(state.clone()), "resetverfuser", "password123").await;
-    let cookie = cookie.expect("Login must set a session cookie");
+    set_pending_code(&db, "codeuser", "messagebird", "123456", chrono::Utc::now()).await;
+    let (_, body) = authenticated_get(app.clone(), "/api/notifications/preferences", &cookie).await;
+    let prefs: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let pref = prefs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["channel_type"] == "messagebird")
+        .unwrap();
+    assert_eq!(pref["verification_pending"], true);
 
-    // First: set destination
-    let body1 =
-        serde_json::json!({"enabled": true, "destination": "first@example.com"}).to_string();
-    let (s1, _) = authenticated_put(
-        create_router(state.clone()),
-        "/api/notifications/preferences/email",
+    let (status, _) = authenticated_post(
+        app.clone(),
+        "/api/notifications/preferences/messagebird/verify-code",
         &cookie,
-        &body1,
+        r#"{"code": "654321"}"#,
     )
     .await;
-    assert_eq!(s1, StatusCode::OK);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
 
-    // Second: change destination — verified must reset to false
-    let body2 = serde_json::json!({"destination": "second@example.com"}).to_string();
-    let (status, body) = authenticated_put(
-        create_router(state),
-        "/api/notifications/preferences/email",
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/notifications/preferences/messagebird/verify-code",
         &cookie,
-        &body2,
+        r#"{"code": "123456"}"#,
     )
     .await;
-
     assert_eq!(status, StatusCode::OK, "Body: {}", body);
     let json: serde_json::Value = serde_json::from_str(&body).unwrap();
-    assert_eq!(
-        json["verified"], false,
-        "Changing destination must reset verified=false"
-    );
+    assert_eq!(json["verified"], true);
+    assert_eq!(json["verification_pending"], false);
+
+    let (status, _) = authenticated_post(
+        app,
+        "/api/notifications/preferences/messagebird/send-code",
+        &cookie,
+        "{}",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "Already verified");
+}
+
+#[tokio::test]
+async fn test_verify_code_rejects_expired_and_guessed_codes() {
+    let (db, app, cookie) = setup_code_verification("codeguess").await;
+    let verify = |code: &'static str| {
+        let app = app.clone();
+        let cookie = cookie.clone();
+        async move {
+            authenticated_post(
+                app,
+                "/api/notifications/preferences/messagebird/verify-code",
+                &cookie,
+                &serde_json::json!({ "code": code }).to_string(),
+            )
+            .await
+            .0
+        }
+    };
+
+    let expired = chrono::Utc::now() - chrono::Duration::minutes(11);
+    set_pending_code(&db, "codeguess", "messagebird", "123456", expired).await;
+    assert_eq!(verify("123456").await, StatusCode::BAD_REQUEST);
+
+    set_pending_code(
+        &db,
+        "codeguess",
+        "messagebird",
+        "123456",
+        chrono::Utc::now(),
+    )
+    .await;
+    for _ in 0..5 {
+        assert_eq!(verify("000000").await, StatusCode::BAD_REQUEST);
+    }
+    // The right code no longer helps once the attempts are used up
+    assert_eq!(verify("123456").await, StatusCode::TOO_MANY_REQUESTS);
 }
 
 // ============================================================================
@@ -1750,3 +2435,405 @@ async fn test_list_logs_viewer_without_audit_view_returns_403() {
         "Viewer without audit.view must get 403 on GET /api/notifications/logs"
     );
 }
+
+// ============================================================================
+// Routing rules
+// ============================================================================
+
+#[tokio::test]
+async fn test_routes_require_settings_permissions() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "viewerroutes",
+        "viewerroutes@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let request = Request::builder()
+        .uri("/api/notifications/routes")
+        .method("GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = create_router(state.clone()).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let (_, cookie) = do_login(create_router(state.clone()), "viewerroutes", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+    let (status, _) = authenticated_post(
+        create_router(state),
+        "/api/notifications/routes",
+        &cookie,
+        r#"{"name": "All", "channels": ["in_app"]}"#,
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::FORBIDDEN,
+        "Viewer without settings.manage must get 403 on POST /api/notifications/routes"
+    );
+}
+
+#[tokio::test]
+async fn test_route_crud_and_validation() {
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(&db, "routesadmin", "routesadmin@example.com", "password123")
+        .await;
+    let state = build_test_app_state_with_db(db).await;
+    let (_, cookie) = do_login(create_router(state.clone()), "routesadmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    for invalid in [
+        r#"{"name": "x", "channels": []}"#,
+        r#"{"name": "x", "channels": ["signal"]}"#,
+        r#"{"name": "x", "channels": ["in_app"], "event_types": ["no_such_event"]}"#,
+        r#"{"name": "x", "channels": ["in_app"], "severities": ["urgent"]}"#,
+        r#"{"name": "x", "channels": ["in_app"], "start_time": "22:00"}"#,
+        r#"{"name": "x", "channels": ["in_app"], "start_time": "22:00", "end_time": "24:30"}"#,
+        r#"{"name": "x", "channels": ["in_app"], "timezone": "Mars/Olympus_Mons"}"#,
+        r#"{"name": "x", "channels": ["in_app"], "recipient_user_ids": [999999]}"#,
+    ] {
+        let (status, body) = authenticated_post(
+            create_router(state.clone()),
+            "/api/notifications/routes",
+            &cookie,
+            invalid,
+        )
+        .await;
+        assert_eq!(
+            status,
+            StatusCode::BAD_REQUEST,
+            "{} must be rejected. Body: {}",
+            invalid,
+            body
+        );
+    }
+
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/routes",
+        &cookie,
+        r#"{
+            "name": "Overnight warnings",
+            "severities": ["warning"],
+            "start_time": "22:00",
+            "end_time": "7:00",
+            "timezone": "Europe/Amsterdam",
+            "channels": ["in_app"]
+        }"#,
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "Create route failed. Body: {}",
+        body
+    );
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["end_time"], "07:00");
+    assert_eq!(json["event_types"], serde_json::json!([]));
+    let id = json["id"].as_i64().unwrap();
+    let uri = format!("/api/notifications/routes/{}", id);
+
+    // Clearing both times makes the route apply all day
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        &uri,
+        &cookie,
+        r#"{"start_time": "", "end_time": "", "channels": ["in_app", "telegram"]}"#,
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "Update route failed. Body: {}",
+        body
+    );
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["start_time"].is_null());
+    assert_eq!(json["channels"], serde_json::json!(["in_app", "telegram"]));
+
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/notifications/routes",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+
+    let (status, _) = authenticated_delete(create_router(state.clone()), &uri, &cookie).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = authenticated_get(create_router(state), &uri, &cookie).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_matching_route_replaces_default_delivery() {
+    use kubarr::models::audit_log::AuditAction;
+    use kubarr::models::{notification_event, notification_log, user_notification};
+    use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    setup_admin_with_settings_perms(&db, "routeadmin", "routeadmin@example.com", "password123")
+        .await;
+    let oncall =
+        create_test_user_with_role(&db, "oncall", "oncall@example.com", "password123", "viewer")
+            .await;
+    kubarr::models::user_notification_pref::ActiveModel {
+        user_id: Set(oncall.id),
+        channel_type: Set("telegram".to_string()),
+        enabled: Set(true),
+        destination: Set(Some("123456789".to_string())),
+        verified: Set(true),
+        created_at: Set(chrono::Utc::now()),
+        updated_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    notification_event::ActiveModel {
+        event_type: Set("uptime_monitor_down".to_string()),
+        enabled: Set(true),
+        severity: Set("critical".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let state = build_test_app_state_with_db(db.clone()).await;
+
+    // Without routes a system event only reaches the log
+    state
+        .notification
+        .notify_event(&AuditAction::UptimeMonitorDown, None, None, Some("Plex"))
+        .await
+        .unwrap();
+    assert_eq!(
+        user_notification::Entity::find().count(&db).await.unwrap(),
+        0
+    );
+
+    let (_, cookie) = do_login(create_router(state.clone()), "routeadmin", "password123").await;
+    let cookie = cookie.expect("Login must set a session cookie");
+    let body = serde_json::json!({
+        "name": "Critical to on-call",
+        "event_types": ["uptime_monitor_down"],
+        "severities": ["critical"],
+        "channels": ["in_app", "telegram"],
+        "recipient_user_ids": [oncall.id]
+    })
+    .to_string();
+    let (status, body) = authenticated_post(
+        create_router(state.clone()),
+        "/api/notifications/routes",
+        &cookie,
+        &body,
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "Create route failed. Body: {}",
+        body
+    );
+
+    state
+        .notification
+        .notify_event(&AuditAction::UptimeMonitorDown, None, None, Some("Plex"))
+        .await
+        .unwrap();
+
+    let inbox = user_notification::Entity::find().all(&db).await.unwrap();
+    assert_eq!(
+        inbox.len(),
+        1,
+        "Only the route's recipient gets an in-app notification"
+    );
+    assert_eq!(inbox[0].user_id, oncall.id);
+    assert_eq!(inbox[0].severity, "critical");
+
+    // Telegram is not configured in tests, so the attempt is logged as failed
+    let logs = notification_log::Entity::find()
+        .filter(notification_log::Column::ChannelType.eq("telegram"))
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].user_id, Some(oncall.id));
+    assert_eq!(logs[0].event_type, "uptime_monitor_down");
+    assert_eq!(logs[0].status, "failed");
+}
+
+// ============================================================================
+// POST /api/notifications/telegram/callback — Telegram inline buttons
+// ============================================================================
+
+const TELEGRAM_SECRET: &str = "hook-secret";
+const TELEGRAM_CHAT: &str = "12345";
+
+/// Router with a Telegram channel that has a webhook secret, an admin whose
+/// verified Telegram destination is `TELEGRAM_CHAT`, and one firing alert
+async fn setup_telegram_webhook() -> (axum::Router, sea_orm::DatabaseConnection, i64, i64) {
+    use kubarr::models::{alert, notification_channel, uptime_monitor, user_notification_pref};
+    use sea_orm::EntityTrait;
+
+    let db = create_test_db_with_seed().await;
+    let admin = create_test_user_with_role(
+        &db,
+        "tgadmin",
+        "tgadmin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+
+    let now = chrono::Utc::now();
+    notification_channel::ActiveModel {
+        channel_type: Set("telegram".to_string()),
+        enabled: Set(true),
+        // Nothing listens here, so answering callbacks fails fast
+        config: Set(serde_json::json!({
+            "bot_token": "123:abc",
+            "webhook_secret": TELEGRAM_SECRET,
+            "api_url": "http://127.0.0.1:9"
+        })
+        .to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    user_notification_pref::ActiveModel {
+        user_id: Set(admin.id),
+        channel_type: Set("telegram".to_string()),
+        enabled: Set(true),
+        destination: Set(Some(TELEGRAM_CHAT.to_string())),
+        verified: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    uptime_monitor::ActiveModel {
+        name: Set("Plex".to_string()),
+        kind: Set("http".to_string()),
+        target: Set("http://plex:32400".to_string()),
+        interval_seconds: Set(60),
+        timeout_seconds: Set(10),
+        enabled: Set(true),
+        status: Set("down".to_string()),
+        last_checked_at: Set(Some(now)),
+        last_change_at: Set(Some(now)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    kubarr::services::alerts::sync(&db, now).await.unwrap();
+    let alert = alert::Entity::find().one(&db).await.unwrap().unwrap();
+
+    let state = build_test_app_state_with_db(db.clone()).await;
+    state.notification.reload_providers().await.unwrap();
+    (create_router(state), db, admin.id, alert.id)
+}
+
+async fn post_telegram_callback(
+    app: axum::Router,
+    secret: &str,
+    from: i64,
+    data: &str,
+) -> (StatusCode, serde_json::Value) {
+    let update = serde_json::json!({
+        "update_id": 1,
+        "callback_query": {
+            "id": "cb-1",
+            "from": {"id": from},
+            "message": {"message_id": 7, "chat": {"id": TELEGRAM_CHAT.parse::<i64>().unwrap()}},
+            "data": data
+        }
+    });
+    let request = Request::builder()
+        .uri("/api/notifications/telegram/callback")
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("x-telegram-bot-api-secret-token", secret)
+        .body(Body::from(update.to_string()))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+    )
+}
+
+async fn alert_state(db: &sea_orm::DatabaseConnection, id: i64) -> kubarr::models::alert::Model {
+    use sea_orm::EntityTrait;
+    kubarr::models::alert::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_telegram_callback_acknowledges_alert() {
+    use kubarr::services::notification::telegram::callback_data;
+
+    let (app, db, admin_id, alert_id) = setup_telegram_webhook().await;
+    let data = callback_data(TELEGRAM_SECRET, alert_id, TELEGRAM_CHAT);
+
+    let (status, body) =
+        post_telegram_callback(app, TELEGRAM_SECRET, TELEGRAM_CHAT.parse().unwrap(), &data).await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    assert_eq!(body["outcome"], "acknowledged");
+
+    let alert = alert_state(&db, alert_id).await;
+    assert_eq!(alert.state, "acked");
+    assert_eq!(alert.acked_by, Some(admin_id));
+}
+
+#[tokio::test]
+async fn test_telegram_callback_rejects_bad_secret_signature_and_unknown_user() {
+    use kubarr::services::notification::telegram::callback_data;
+
+    let (app, db, _, alert_id) = setup_telegram_webhook().await;
+    let data = callback_data(TELEGRAM_SECRET, alert_id, TELEGRAM_CHAT);
+    let chat: i64 = TELEGRAM_CHAT.parse().unwrap();
+
+    let (status, _) = post_telegram_callback(app.clone(), "wrong", chat, &data).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Signed with another secret
+    let forged = callback_data("other-secret", alert_id, TELEGRAM_CHAT);
+    let (status, body) = post_telegram_callback(app.clone(), TELEGRAM_SECRET, chat, &forged).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["outcome"], "invalid");
+
+    // Pressed by a Telegram user not linked to any Kubarr account
+    let (status, body) = post_telegram_callback(app, TELEGRAM_SECRET, 999, &data).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["outcome"], "forbidden");
+
+    assert_eq!(alert_state(&db, alert_id).await.state, "firing");
+}
@@ -0,0 +1,206 @@
+//! Integration tests for the permission evaluator
+//!
+//! Covers:
+//! - `POST /api/roles/evaluate` for users and roles, with the matched grants
+//!   of each check, tenant membership and input validation
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::services::installed_apps;
+
+async fn evaluate(env: &TestEnv, body: Value) -> (StatusCode, Value) {
+    env.request(
+        "POST",
+        "/api/roles/evaluate",
+        Some(env.cookie("admin")),
+        Some(body),
+    )
+    .await
+}
+
+/// Find a step of an evaluation by check name
+fn step<'a>(evaluation: &'a Value, check: &str) -> &'a Value {
+    evaluation["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["check"] == check)
+        .unwrap_or_else(|| panic!("No step '{}' in {}", check, evaluation))
+}
+
+#[tokio::test]
+async fn test_evaluate_user_and_role() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let viewer_id = env.user("viewer").user.id;
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/roles/evaluate",
+            Some(env.cookie("viewer")),
+            Some(json!({"user_id": viewer_id, "action": "apps.view"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The viewer role grants jellyfin but not sonarr
+    let (status, allowed) = evaluate(
+        &env,
+        json!({"user_id": viewer_id, "action": "apps.view", "app_name": "jellyfin"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", allowed);
+    assert_eq!(allowed["allowed"], true);
+    assert_eq!(allowed["roles"], json!(["viewer"]));
+    let checks: Vec<&str> = allowed["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["check"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        checks,
+        ["account", "permission", "app_access", "tenant", "schedule"]
+    );
+    assert_eq!(
+        step(&allowed, "permission")["granted_by"][0]["permission"],
+        "apps.view"
+    );
+    assert_eq!(
+        step(&allowed, "app_access")["granted_by"][0]["permission"],
+        "app.jellyfin"
+    );
+
+    let (_, denied) = evaluate(
+        &env,
+        json!({"user_id": viewer_id, "action": "apps.restart", "app_name": "sonarr"}),
+    )
+    .await;
+    assert_eq!(denied["allowed"], false);
+    let permission = step(&denied, "permission");
+    assert_eq!(permission["passed"], false);
+    assert_eq!(permission["detail"], "No role grants apps.restart");
+    assert_eq!(step(&denied, "app_access")["passed"], false);
+
+    // Roles are evaluated on their own, without account, tenant or schedule
+    // checks
+    let (_, roles) = env
+        .request("GET", "/api/roles", Some(env.cookie("admin")), None)
+        .await;
+    let admin_role = roles
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == "admin")
+        .unwrap()["id"]
+        .clone();
+    let (_, role) = evaluate(
+        &env,
+        json!({"role_id": admin_role, "action": "apps.restart", "app_name": "sonarr"}),
+    )
+    .await;
+    assert_eq!(role["allowed"], true);
+    assert_eq!(role["steps"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        step(&role, "app_access")["granted_by"][0]["permission"],
+        "app.*"
+    );
+
+    for (invalid, expected) in [
+        (
+            json!({"user_id": viewer_id, "role_id": admin_role, "action": "apps.view"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (json!({"action": "apps.view"}), StatusCode::BAD_REQUEST),
+        (
+            json!({"user_id": viewer_id, "action": "apps.fly"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({"user_id": 9999, "action": "apps.view"}),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            json!({"role_id": 9999, "action": "apps.view"}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let (status, _) = evaluate(&env, invalid).await;
+        assert_eq!(status, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_evaluate_tenant_membership() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .with_app("jellyfin", AppStatus::Running)
+        .build()
+        .await;
+    let (_, tenant) = env
+        .request(
+            "POST",
+            "/api/tenants",
+            Some(env.cookie("admin")),
+            Some(json!({"name": "smiths"})),
+        )
+        .await;
+    let tenant_id = tenant["id"].as_i64().unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!(
+                "/api/tenants/{}/members/{}",
+                tenant_id,
+                env.user("alice").user.id
+            ),
+            Some(env.cookie("admin")),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    installed_apps::record_install(&env.db, "jellyfin", None)
+        .await
+        .unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/jellyfin/tenant",
+            Some(env.cookie("admin")),
+            Some(json!({"tenant_id": tenant_id})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, alice) = evaluate(
+        &env,
+        json!({"user_id": env.user("alice").user.id, "action": "apps.view", "app_name": "jellyfin"}),
+    )
+    .await;
+    assert_eq!(alice["allowed"], true);
+    assert_eq!(
+        step(&alice, "tenant")["detail"],
+        "jellyfin belongs to tenant smiths, which the user is a member of"
+    );
+
+    let (_, bob) = evaluate(
+        &env,
+        json!({"user_id": env.user("bob").user.id, "action": "apps.view", "app_name": "jellyfin"}),
+    )
+    .await;
+    assert_eq!(bob["allowed"], false);
+    assert_eq!(step(&bob, "app_access")["passed"], true);
+    assert_eq!(step(&bob, "tenant")["passed"], false);
+
+    let (_, admin) = evaluate(
+        &env,
+        json!({"user_id": env.user("admin").user.id, "action": "apps.view", "app_name": "jellyfin"}),
+    )
+    .await;
+    assert_eq!(admin["allowed"], true);
+}
@@ -0,0 +1,355 @@
+//! Integration tests for app account provisioning
+//!
+//! Covers:
+//! - `GET/PUT/DELETE /api/roles/{role_id}/provisioning` — requires roles.view / roles.manage
+//! - `POST /api/users/{user_id}/provision` against a mock Jellyfin and
+//!   Jellyseerr, with and without `dry_run`
+//! - provisioning in the background when a user is approved
+
+use std::time::Duration;
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::{role, user};
+
+/// Serve a Jellyfin that has the user `bob` and creates any other
+async fn spawn_jellyfin() -> String {
+    let router = axum::Router::new()
+        .route(
+            "/Users",
+            axum::routing::get(|| async {
+                Json(json!([{"Id": "jf-bob", "Name": "bob", "Policy": {}}]))
+            }),
+        )
+        .route(
+            "/Users/New",
+            axum::routing::post(|headers: HeaderMap, Json(body): Json<Value>| async move {
+                if headers.get("x-emby-token").and_then(|k| k.to_str().ok()) != Some("jf-key") {
+                    return (StatusCode::UNAUTHORIZED, Json(json!({})));
+                }
+                let name = body["Name"].as_str().unwrap_or_default().to_string();
+                assert!(body["Password"].as_str().is_some_and(|p| p.len() >= 16));
+                (
+                    StatusCode::OK,
+                    Json(json!({"Id": format!("jf-{}", name), "Name": name, "Policy": {}})),
+                )
+            }),
+        );
+    serve(router).await
+}
+
+/// Serve a Jellyseerr without users that imports Jellyfin accounts
+async fn spawn_jellyseerr() -> String {
+    let router = axum::Router::new()
+        .route(
+            "/api/v1/user",
+            axum::routing::get(|| async { Json(json!({"results": []})) }),
+        )
+        .route(
+            "/api/v1/user/import-from-jellyfin",
+            axum::routing::post(|Json(body): Json<Value>| async move {
+                let users: Vec<Value> = body["jellyfinUserIds"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|id| {
+                        let id = id.as_str().unwrap();
+                        json!({
+                            "id": if id == "jf-alice" { 42 } else { 43 },
+                            "displayName": id.trim_start_matches("jf-"),
+                        })
+                    })
+                    .collect();
+                Json(json!(users))
+            }),
+        );
+    serve(router).await
+}
+
+async fn serve(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+async fn viewer_role_id(env: &TestEnv) -> i64 {
+    role::Entity::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .id
+}
+
+/// Set up both integrations and give viewers Jellyfin and Jellyseerr accounts
+async fn configure(env: &TestEnv) {
+    for (app, base_url, key) in [
+        ("jellyfin", spawn_jellyfin().await, "jf-key"),
+        ("jellyseerr", spawn_jellyseerr().await, "js-key"),
+    ] {
+        let (status, body) = env
+            .request(
+                "PUT",
+                &format!("/api/integrations/apps/{}", app),
+                Some(env.cookie("admin")),
+                Some(json!({"base_url": base_url, "credentials": {"api_key": key}})),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    let (status, body) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/provisioning", viewer_role_id(env).await),
+            Some(env.cookie("admin")),
+            Some(json!({"apps": ["jellyseerr", "jellyfin"]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+fn statuses(report: &Value) -> Vec<(String, String)> {
+    report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["app_name"].as_str().unwrap().to_string(),
+                r["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pair(app: &str, status: &str) -> (String, String) {
+    (app.to_string(), status.to_string())
+}
+
+#[tokio::test]
+async fn test_manage_provisioning_profile() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .build()
+        .await;
+    let uri = format!("/api/roles/{}/provisioning", viewer_role_id(&env).await);
+
+    let (status, _) = env
+        .request("GET", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("alice")),
+            Some(json!({"apps": ["jellyfin"]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"apps": ["sonarr"]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, profile) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"apps": ["Jellyfin", "jellyfin", "overseerr"]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", profile);
+    assert_eq!(profile["apps"], json!(["jellyfin", "overseerr"]));
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/roles/99999/provisioning",
+            Some(env.cookie("admin")),
+            Some(json!({"apps": []})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_provision_user() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .build()
+        .await;
+    let alice = env.user("alice").user.id;
+    let bob = env.user("bob").user.id;
+    configure(&env).await;
+    let uri = format!("/api/users/{}/provision", alice);
+
+    let (status, _) = env
+        .request("POST", &uri, Some(env.cookie("bob")), Some(json!({})))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A dry run changes nothing
+    let (status, report) = env
+        .request(
+            "POST",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"dry_run": true})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", report);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        statuses(&report),
+        vec![
+            pair("jellyfin", "would_create"),
+            pair("jellyseerr", "would_create")
+        ]
+    );
+    let (_, report) = env
+        .request(
+            "POST",
+            &format!("/api/users/{}/provision", bob),
+            Some(env.cookie("admin")),
+            Some(json!({"dry_run": true})),
+        )
+        .await;
+    assert_eq!(statuses(&report)[0], pair("jellyfin", "would_link"));
+    let (_, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(links, json!([]));
+
+    // Jellyseerr imports the Jellyfin account created just before
+    let (status, report) = env
+        .request("POST", &uri, Some(env.cookie("admin")), Some(json!({})))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", report);
+    assert_eq!(
+        statuses(&report),
+        vec![pair("jellyfin", "created"), pair("jellyseerr", "created")]
+    );
+    let (_, links) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/accounts", alice),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(links[0]["app_name"], "jellyfin");
+    assert_eq!(links[0]["external_id"], "jf-alice");
+    assert_eq!(links[0]["source"], "provisioned");
+    assert_eq!(links[1]["app_name"], "jellyseerr");
+    assert_eq!(links[1]["external_id"], "42");
+
+    // Running again finds the links
+    let (_, report) = env
+        .request("POST", &uri, Some(env.cookie("admin")), Some(json!({})))
+        .await;
+    assert_eq!(
+        statuses(&report),
+        vec![
+            pair("jellyfin", "already_linked"),
+            pair("jellyseerr", "already_linked")
+        ]
+    );
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/users/99999/provision",
+            Some(env.cookie("admin")),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_approval_provisions_in_background() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("bob", "viewer")
+        .build()
+        .await;
+    let bob = env.user("bob").user.id;
+    configure(&env).await;
+
+    let mut pending: user::ActiveModel = user::Entity::find_by_id(bob)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .into();
+    pending.is_approved = Set(false);
+    pending.update(&env.db).await.unwrap();
+
+    let (status, _) = env
+        .request(
+            "POST",
+            &format!("/api/users/{}/approve", bob),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut links = json!([]);
+    for _ in 0..50 {
+        (_, links) = env
+            .request(
+                "GET",
+                &format!("/api/users/{}/accounts", bob),
+                Some(env.cookie("admin")),
+                None,
+            )
+            .await;
+        if links.as_array().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    // Bob's existing Jellyfin account is linked rather than created
+    assert_eq!(links[0]["external_id"], "jf-bob", "{}", links);
+    assert_eq!(links[0]["source"], "auto");
+    assert_eq!(links[1]["external_id"], "43");
+    assert_eq!(links[1]["source"], "provisioned");
+}
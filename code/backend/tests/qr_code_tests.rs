@@ -0,0 +1,107 @@
+//! Integration tests for QR code images
+//!
+//! Covers:
+//! - `GET /api/users/invites/{invite_id}/qr` — an invite's registration link
+//! - `GET /api/users/me/2fa/qr`              — the provisioning URI during 2FA setup
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::user;
+
+/// Fetch an image and return the status, content type and bytes
+async fn fetch(env: &TestEnv, uri: &str, username: &str) -> (StatusCode, String, Vec<u8>) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("Cookie", env.cookie(username))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, content_type, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn test_invite_qr() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let (_, invite) = env
+        .request(
+            "POST",
+            "/api/users/invites",
+            Some(env.cookie("admin")),
+            Some(json!({})),
+        )
+        .await;
+    let uri = format!("/api/users/invites/{}/qr", invite["id"]);
+
+    let (status, content_type, body) = fetch(&env, &uri, "admin").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/png");
+    assert!(body.starts_with(b"\x89PNG"));
+
+    let (status, content_type, body) = fetch(&env, &format!("{}?format=svg", uri), "admin").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/svg+xml");
+    assert!(String::from_utf8(body).unwrap().starts_with("<svg"));
+
+    let (status, _, _) = fetch(&env, &format!("{}?format=gif", uri), "admin").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _, _) = fetch(&env, "/api/users/invites/9999/qr", "admin").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = fetch(&env, &uri, "viewer").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_2fa_qr_only_during_setup() {
+    let env = TestEnv::builder().with_viewer().build().await;
+
+    let (status, _, _) = fetch(&env, "/api/users/me/2fa/qr", "viewer").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/users/me/2fa/setup",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, content_type, _) = fetch(&env, "/api/users/me/2fa/qr?format=svg", "viewer").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/svg+xml");
+
+    // Once enabled the secret is not shown again
+    user::ActiveModel {
+        id: Set(env.user("viewer").user.id),
+        totp_enabled: Set(true),
+        ..Default::default()
+    }
+    .update(&env.db)
+    .await
+    .unwrap();
+    let (status, _, _) = fetch(&env, "/api/users/me/2fa/qr", "viewer").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,246 @@
+//! Integration tests for resource quotas
+//!
+//! Covers:
+//! - `PUT/GET/DELETE /api/roles/{role_id}/quota` and `/api/users/{user_id}/quota`
+//! - `GET /api/users/me/quota` — effective quota and usage
+//! - enforcement when installing and upgrading apps
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::{role, role_permission};
+
+const MI: i64 = 1024 * 1024;
+
+/// Admin plus a viewer allowed to install apps, and the viewer role's ID
+async fn setup() -> (TestEnv, i64) {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_catalog_app("jellyfin")
+        .with_catalog_app("radarr")
+        .build()
+        .await;
+    let viewer = Role::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    role_permission::ActiveModel {
+        role_id: Set(viewer.id),
+        permission: Set("apps.install".to_string()),
+        ..Default::default()
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+    (env, viewer.id)
+}
+
+async fn install(
+    env: &TestEnv,
+    app_name: &str,
+    custom_config: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    env.request(
+        "POST",
+        "/api/apps/install",
+        Some(env.cookie("viewer")),
+        Some(json!({"app_name": app_name, "custom_config": custom_config})),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_role_quota_limits_installs_and_upgrades() {
+    let (env, viewer_role) = setup().await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/quota", viewer_role),
+            Some(env.cookie("admin")),
+            Some(json!({"max_apps": 1, "max_memory_bytes": 512 * MI})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = install(&env, "jellyfin", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, report) = env
+        .request(
+            "GET",
+            "/api/users/me/quota",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["source"], "role");
+    assert_eq!(report["limits"]["max_apps"], 1);
+    assert_eq!(report["usage"]["apps"], 1);
+    assert_eq!(report["usage"]["cpu_millicores"], 100);
+    assert_eq!(report["usage"]["memory_bytes"], 256 * MI);
+
+    let (status, body) = install(&env, "radarr", json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(
+        body["detail"]
+            .as_str()
+            .unwrap()
+            .contains("would exceed your app quota: 1 of 1 apps already installed"),
+        "{}",
+        body
+    );
+
+    // Upgrading replaces the app's footprint instead of adding to it
+    let (status, _) = install(
+        &env,
+        "jellyfin",
+        json!({"jellyfin.resources.requests.memory": "384Mi"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = install(
+        &env,
+        "jellyfin",
+        json!({"jellyfin.resources.requests.memory": "1Gi"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(
+        body["detail"]
+            .as_str()
+            .unwrap()
+            .contains("memory quota: it requests 1Gi, 0 of 512Mi is already in use"),
+        "{}",
+        body
+    );
+    let (_, report) = env
+        .request(
+            "GET",
+            "/api/users/me/quota",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(report["usage"]["memory_bytes"], 384 * MI);
+
+    let (status, _) = install(
+        &env,
+        "jellyfin",
+        json!({"jellyfin.resources.requests.memory": "plenty"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_user_quota_overrides_role_quota() {
+    let (env, viewer_role) = setup().await;
+    let viewer = env.user("viewer").user.id;
+    let admin = env.cookie("admin");
+    env.request(
+        "PUT",
+        &format!("/api/roles/{}/quota", viewer_role),
+        Some(admin),
+        Some(json!({"max_apps": 0})),
+    )
+    .await;
+    let (status, body) = install(&env, "jellyfin", json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/users/{}/quota", viewer),
+            Some(admin),
+            Some(json!({"max_apps": 2})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, report) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}/quota", viewer),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(report["source"], "user");
+    let (status, _) = install(&env, "jellyfin", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/users/{}/quota", viewer),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = install(&env, "radarr", json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Without any quota, installs are unrestricted
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/roles/{}/quota", viewer_role),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request(
+            "GET",
+            &format!("/api/roles/{}/quota", viewer_role),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = install(&env, "radarr", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, report) = env
+        .request(
+            "GET",
+            "/api/users/me/quota",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert!(report["source"].is_null());
+    assert_eq!(report["usage"]["apps"], 2);
+}
+
+#[tokio::test]
+async fn test_quota_management_requires_permissions() {
+    let (env, viewer_role) = setup().await;
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/quota", viewer_role),
+            Some(env.cookie("viewer")),
+            Some(json!({"max_apps": 10})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &format!("/api/roles/{}/quota", viewer_role),
+            Some(env.cookie("admin")),
+            Some(json!({"max_apps": -1})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
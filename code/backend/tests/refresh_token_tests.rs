@@ -0,0 +1,177 @@
+//! Integration tests for session refresh tokens
+//!
+//! Covers:
+//! - `POST /auth/session/refresh` renewing the access token and rotating the
+//!   refresh token
+//! - expired access tokens being refused until they are renewed
+//! - a reused refresh token revoking the session, except right after the
+//!   rotation
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::prelude::*;
+use kubarr::models::{audit_log, session, user_notification};
+use kubarr::services::create_session_token;
+
+const ACCESS_COOKIE: &str = "kubarr_session_0=";
+const REFRESH_COOKIE: &str = "kubarr_session_refresh_0=";
+
+/// Value of the cookie starting with `prefix` among the response's cookies
+fn set_cookie(response: &axum::response::Response, prefix: &str) -> Option<String> {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|c| c.starts_with(prefix) && !c.contains("Max-Age=0"))
+        .map(|c| c.split(';').next().unwrap().to_string())
+}
+
+/// Sign the viewer in, returning the access and refresh cookies
+async fn sign_in(env: &TestEnv) -> (String, String) {
+    let body = json!({"username": "viewer", "password": DEV_PASSWORD}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = env.router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    (
+        set_cookie(&response, ACCESS_COOKIE).unwrap(),
+        set_cookie(&response, REFRESH_COOKIE).unwrap(),
+    )
+}
+
+/// Refresh with a refresh cookie, returning the status and new cookies
+async fn refresh(env: &TestEnv, cookie: &str) -> (StatusCode, Option<(String, String)>) {
+    let request = Request::builder()
+        .uri("/auth/session/refresh")
+        .method("POST")
+        .header("Cookie", cookie)
+        .body(Body::empty())
+        .unwrap();
+    let response = env.router.clone().oneshot(request).await.unwrap();
+    let cookies = set_cookie(&response, ACCESS_COOKIE).zip(set_cookie(&response, REFRESH_COOKIE));
+    (response.status(), cookies)
+}
+
+async fn me(env: &TestEnv, cookie: &str) -> StatusCode {
+    env.request("GET", "/api/users/me", Some(cookie), None)
+        .await
+        .0
+}
+
+async fn viewer_session(env: &TestEnv, access_cookie: &str) -> session::Model {
+    let token = access_cookie.strip_prefix(ACCESS_COOKIE).unwrap();
+    let claims = kubarr::services::decode_session_token(token).unwrap();
+    Session::find_by_id(claims.sid)
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_refresh_rotation() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    let (access, first) = sign_in(&env).await;
+    assert_eq!(me(&env, &access).await, StatusCode::OK);
+    let session = viewer_session(&env, &access).await;
+    assert!(session.refresh_token_hash.is_some());
+
+    let (status, cookies) = refresh(&env, &first).await;
+    assert_eq!(status, StatusCode::OK);
+    let (access, second) = cookies.unwrap();
+    assert_ne!(first, second);
+    assert_eq!(me(&env, &access).await, StatusCode::OK);
+
+    // Another tab sending the replaced token right away does not end the
+    // session
+    assert_eq!(refresh(&env, &first).await.0, StatusCode::CONFLICT);
+    assert_eq!(me(&env, &access).await, StatusCode::OK);
+
+    let (status, cookies) = refresh(&env, &second).await;
+    assert_eq!(status, StatusCode::OK);
+    let (access, third) = cookies.unwrap();
+
+    // Reusing an older token revokes the whole session
+    assert_eq!(refresh(&env, &first).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(me(&env, &access).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(refresh(&env, &third).await.0, StatusCode::UNAUTHORIZED);
+    assert!(viewer_session(&env, &access).await.is_revoked);
+
+    let viewer_id = env.user("viewer").user.id;
+    let reused = AuditLog::find()
+        .filter(audit_log::Column::Action.eq("refresh_token_reused"))
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert_eq!(reused.len(), 1);
+    assert_eq!(reused[0].user_id, Some(viewer_id));
+    let notified = UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(viewer_id))
+        .filter(user_notification::Column::EventType.eq("refresh_token_reused"))
+        .one(&env.db)
+        .await
+        .unwrap();
+    assert!(notified.is_some());
+}
+
+#[tokio::test]
+async fn test_replaced_token_after_grace() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let (access, first) = sign_in(&env).await;
+    let (status, cookies) = refresh(&env, &first).await;
+    assert_eq!(status, StatusCode::OK);
+    let (access_after, _) = cookies.unwrap();
+
+    let session = viewer_session(&env, &access).await;
+    let mut model: session::ActiveModel = session.into();
+    model.refreshed_at = Set(Some(Utc::now() - Duration::minutes(1)));
+    model.update(&env.db).await.unwrap();
+
+    assert_eq!(refresh(&env, &first).await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(me(&env, &access_after).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_expired_access_token() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let (access, refresh_cookie) = sign_in(&env).await;
+    let session = viewer_session(&env, &access).await;
+
+    let expired = format!(
+        "{}{}",
+        ACCESS_COOKIE,
+        create_session_token(&session.id, Some(-1)).unwrap()
+    );
+    assert_eq!(me(&env, &expired).await, StatusCode::UNAUTHORIZED);
+
+    let (status, cookies) = refresh(&env, &refresh_cookie).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me(&env, &cookies.unwrap().0).await, StatusCode::OK);
+
+    // Without a refresh token there is nothing to renew
+    assert_eq!(refresh(&env, "").await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        refresh(&env, "kubarr_session_refresh_0=nonsense").await.0,
+        StatusCode::UNAUTHORIZED
+    );
+}
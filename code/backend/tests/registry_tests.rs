@@ -0,0 +1,253 @@
+//! Integration tests for container registries
+//!
+//! Covers:
+//! - `GET/POST /api/system/registries` and `PUT/DELETE /api/system/registries/{registry_id}`
+//!   — requires system.manage, never returns passwords
+//! - installs pulling through a registry mirror with a pull secret
+//! - `POST /api/system/registries/{registry_id}/test` against a mock registry
+//!   with a bearer token challenge
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::services::helm::HelmCall;
+use kubarr::services::k8s::K8sOperation;
+
+/// Serve a registry whose token service accepts `kubarr:secret`
+async fn spawn_registry() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let realm = format!("http://{}/token", addr);
+    let router = axum::Router::new()
+        .route(
+            "/v2/",
+            axum::routing::get(move || async move {
+                let challenge = format!(r#"Bearer realm="{}",service="test""#, realm);
+                (
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, challenge)],
+                )
+            }),
+        )
+        .route(
+            "/token",
+            axum::routing::get(|headers: HeaderMap| async move {
+                // "kubarr:secret"
+                let expected = "Basic a3ViYXJyOnNlY3JldA==";
+                match headers.get(header::AUTHORIZATION) {
+                    Some(auth) if auth == expected => {
+                        axum::Json(json!({"token": "t"})).into_response()
+                    }
+                    _ => StatusCode::UNAUTHORIZED.into_response(),
+                }
+            }),
+        );
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    addr.to_string()
+}
+
+#[tokio::test]
+async fn test_manage_registries() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let admin = env.cookie("admin");
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/system/registries",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/system/registries",
+            Some(admin),
+            Some(json!({"host": "https://ghcr.io"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, created) = env
+        .request(
+            "POST",
+            "/api/system/registries",
+            Some(admin),
+            Some(json!({
+                "host": "GHCR.io",
+                "mirror": "harbor.lan/ghcr",
+                "username": "kubarr",
+                "password": "secret"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    assert_eq!(created["host"], "ghcr.io");
+    assert_eq!(created["has_password"], true);
+    assert!(created.get("password").is_none());
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/system/registries",
+            Some(admin),
+            Some(json!({"host": "ghcr.io"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Leaving out the password keeps it
+    let uri = format!("/api/system/registries/{}", created["id"]);
+    let (status, updated) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(admin),
+            Some(json!({"host": "ghcr.io", "username": "kubarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", updated);
+    assert_eq!(updated["mirror"], Value::Null);
+    assert_eq!(updated["has_password"], true);
+
+    let (_, list) = env
+        .request("GET", "/api/system/registries", Some(admin), None)
+        .await;
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    let (status, _) = env.request("DELETE", &uri, Some(admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env.request("DELETE", &uri, Some(admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_install_uses_mirror_and_pull_secret() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_catalog_app("prowlarr")
+        .build()
+        .await;
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/system/registries",
+            Some(env.cookie("admin")),
+            Some(json!({
+                "host": "docker.io",
+                "mirror": "harbor.lan/hub",
+                "username": "kubarr",
+                "password": "secret"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/apps/install",
+            Some(env.cookie("admin")),
+            Some(json!({"app_name": "prowlarr"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let calls = env.helm.calls();
+    let HelmCall::UpgradeInstall(release) = &calls[0] else {
+        panic!("expected an upgrade, got {:?}", calls);
+    };
+    assert_eq!(
+        release.set,
+        vec![
+            "prowlarr.image.repository=harbor.lan/hub/library/prowlarr".to_string(),
+            "imagePullSecrets[0].name=kubarr-registry-credentials".to_string(),
+        ]
+    );
+    assert!(env.k8s.operations().contains(&K8sOperation::ReplaceSecret {
+        namespace: "prowlarr".to_string(),
+        name: "kubarr-registry-credentials".to_string(),
+    }));
+    let secret = env
+        .k8s
+        .secret("prowlarr", "kubarr-registry-credentials")
+        .unwrap();
+    assert_eq!(
+        secret.type_.as_deref(),
+        Some("kubernetes.io/dockerconfigjson")
+    );
+    let config: Value =
+        serde_json::from_slice(&secret.data.unwrap()[".dockerconfigjson"].0).unwrap();
+    assert_eq!(config["auths"]["harbor.lan"]["username"], "kubarr");
+}
+
+#[tokio::test]
+async fn test_registry_connectivity() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let admin = env.cookie("admin");
+    let host = spawn_registry().await;
+
+    let mut ids = Vec::new();
+    for (name, password) in [("registry.test", "secret"), ("other.test", "wrong")] {
+        let (status, created) = env
+            .request(
+                "POST",
+                "/api/system/registries",
+                Some(admin),
+                Some(json!({
+                    "host": name,
+                    "mirror": host,
+                    "username": "kubarr",
+                    "password": password,
+                    "insecure": true
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["id"].as_i64().unwrap());
+    }
+
+    let (status, result) = env
+        .request(
+            "POST",
+            &format!("/api/system/registries/{}/test", ids[0]),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["url"], format!("http://{}/v2/", host));
+    assert_eq!(result["reachable"], true);
+    assert_eq!(result["authenticated"], true, "{}", result);
+
+    let (_, result) = env
+        .request(
+            "POST",
+            &format!("/api/system/registries/{}/test", ids[1]),
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(result["reachable"], true);
+    assert_eq!(result["authenticated"], false);
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/system/registries/99999/test",
+            Some(admin),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
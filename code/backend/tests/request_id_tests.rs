@@ -0,0 +1,146 @@
+//! Request ID propagation tests
+//!
+//! Covers:
+//! - `X-Request-Id` is generated, or honored when valid, and echoed back
+//! - Error responses carry the request ID
+//! - Audit rows record the request ID and `GET /api/audit?request_id=` filters on it
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+
+/// Send a request, optionally with an inbound request ID, and return the
+/// status, the response request ID and the parsed body
+async fn send(
+    env: &TestEnv,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    request_id: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, Option<String>, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    if let Some(id) = request_id {
+        builder = builder.header("X-Request-Id", id);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = env
+        .router
+        .clone()
+        .oneshot(builder.body(body).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let id = response
+        .headers()
+        .get("x-request-id")
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, id, json)
+}
+
+#[tokio::test]
+async fn test_request_id_is_generated() {
+    let env = TestEnv::builder().build().await;
+
+    let (status, first, _) = send(&env, "GET", "/api/health", None, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, second, _) = send(&env, "GET", "/api/health", None, None, None).await;
+
+    let first = first.expect("response should carry a request ID");
+    assert!(uuid::Uuid::parse_str(&first).is_ok());
+    assert_ne!(Some(first), second);
+}
+
+#[tokio::test]
+async fn test_inbound_request_id_is_honored() {
+    let env = TestEnv::builder().build().await;
+
+    let (_, id, _) = send(&env, "GET", "/api/health", None, Some("ui-7f3a2c"), None).await;
+    assert_eq!(id.as_deref(), Some("ui-7f3a2c"));
+
+    // Unsafe IDs are replaced rather than echoed
+    let (_, id, _) = send(&env, "GET", "/api/health", None, Some("bad id"), None).await;
+    let id = id.unwrap();
+    assert_ne!(id, "bad id");
+    assert!(uuid::Uuid::parse_str(&id).is_ok());
+}
+
+#[tokio::test]
+async fn test_error_response_includes_request_id() {
+    let env = TestEnv::builder().with_viewer().build().await;
+
+    let (status, id, body) = send(
+        &env,
+        "GET",
+        "/api/users",
+        Some(env.cookie("viewer")),
+        Some("ui-forbidden"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(id.as_deref(), Some("ui-forbidden"));
+    assert_eq!(body["request_id"], "ui-forbidden");
+    assert!(body["detail"].is_string());
+}
+
+#[tokio::test]
+async fn test_audit_entries_filterable_by_request_id() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app("sonarr", AppStatus::Running)
+        .build()
+        .await;
+
+    let (status, _, _) = send(
+        &env,
+        "POST",
+        "/api/apps/sonarr/access",
+        Some(env.cookie("viewer")),
+        Some("ui-open-42"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/audit?request_id=ui-open-42",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 1);
+    let log = &body["logs"][0];
+    assert_eq!(log["action"], "app_accessed");
+    assert_eq!(log["username"], "viewer");
+    assert_eq!(log["request_id"], "ui-open-42");
+
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/audit?request_id=unknown",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(body["total"], 0);
+}
@@ -0,0 +1,206 @@
+//! Integration tests for time-boxed role assignments
+//!
+//! Covers:
+//! - `PUT`/`DELETE /api/users/{user_id}/roles/{role_id}` with and without an
+//!   expiry, and the last-administrator guard
+//! - expired assignments granting nothing before they are cleaned up
+//! - `RoleExpiryTask` notices and cleanup
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::{user_notification, user_role};
+use kubarr::services::role_expiry::RoleExpiryTask;
+use kubarr::services::scheduler::PeriodicTask;
+
+async fn role_id(env: &TestEnv, name: &str) -> i64 {
+    let (_, roles) = env
+        .request("GET", "/api/roles", Some(env.cookie("admin")), None)
+        .await;
+    roles
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == name)
+        .unwrap_or_else(|| panic!("No role '{}'", name))["id"]
+        .as_i64()
+        .unwrap()
+}
+
+fn role<'a>(user: &'a Value, name: &str) -> Option<&'a Value> {
+    user["roles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == name)
+}
+
+async fn expiring_notices(env: &TestEnv, user_id: i64) -> u64 {
+    UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(user_id))
+        .filter(user_notification::Column::EventType.eq("role_expiring"))
+        .count(&env.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_time_boxed_role_assignment() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("friend", "viewer")
+        .with_notifications()
+        .build()
+        .await;
+    let friend = env.user("friend").user.id;
+    let admin = env.user("admin").user.id;
+    let downloader = role_id(&env, "downloader").await;
+    let uri = format!("/api/users/{}/roles/{}", friend, downloader);
+    let task = RoleExpiryTask {
+        notification: env.state.notification.clone(),
+        audit: env.state.audit.clone(),
+    };
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"expires_at": Utc::now() - Duration::hours(1)})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let expires_at = Utc::now() + Duration::days(30);
+    let (status, user) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"expires_at": expires_at})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", user);
+    assert!(role(&user, "downloader").unwrap()["expires_at"].is_string());
+    assert!(role(&user, "viewer").unwrap()["expires_at"].is_null());
+
+    // Replacing the role list keeps the expiry of roles that stay
+    let (status, user) = env
+        .request(
+            "PATCH",
+            &format!("/api/users/{}", friend),
+            Some(env.cookie("admin")),
+            Some(json!({"role_ids": [role_id(&env, "viewer").await, downloader]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(role(&user, "downloader").unwrap()["expires_at"].is_string());
+
+    // Far from the expiry nobody is told
+    task.run(&env.db).await.unwrap();
+    assert_eq!(expiring_notices(&env, friend).await, 0);
+
+    // Within the notice period the user and admins are told, once
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"expires_at": Utc::now() + Duration::hours(2)})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    task.run(&env.db).await.unwrap();
+    task.run(&env.db).await.unwrap();
+    assert_eq!(expiring_notices(&env, friend).await, 1);
+    assert_eq!(expiring_notices(&env, admin).await, 1);
+
+    // Once expired the role grants nothing, even before cleanup
+    UserRole::update_many()
+        .col_expr(
+            user_role::Column::ExpiresAt,
+            sea_orm::sea_query::Expr::value(Utc::now() - Duration::minutes(1)),
+        )
+        .filter(user_role::Column::UserId.eq(friend))
+        .filter(user_role::Column::RoleId.eq(downloader))
+        .exec(&env.db)
+        .await
+        .unwrap();
+    let (_, user) = env
+        .request(
+            "GET",
+            &format!("/api/users/{}", friend),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert!(role(&user, "downloader").is_none(), "{}", user);
+
+    task.run(&env.db).await.unwrap();
+    assert!(UserRole::find_by_id((friend, downloader))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .is_none());
+    let (_, audit) = env
+        .request(
+            "GET",
+            "/api/audit?action=role_unassigned",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(audit["total"], 1);
+    assert!(audit["logs"][0]["details"]
+        .as_str()
+        .unwrap()
+        .contains("\"reason\":\"expired\""));
+
+    let viewer_uri = format!(
+        "/api/users/{}/roles/{}",
+        friend,
+        role_id(&env, "viewer").await
+    );
+    let (status, _) = env
+        .request("DELETE", &viewer_uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request("DELETE", &viewer_uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_last_admin_keeps_permanent_role() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let admin = env.user("admin").user.id;
+    let admin_role = role_id(&env, "admin").await;
+    let uri = format!("/api/users/{}/roles/{}", admin, admin_role);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"expires_at": Utc::now() + Duration::days(1)})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = env
+        .request("DELETE", &uri, Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let assignment = UserRole::find_by_id((admin, admin_role))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(assignment.expires_at.is_none());
+}
@@ -5,6 +5,7 @@
 //! - `GET /api/roles/{id}/permissions`   — list permissions granted to a specific role
 //! - `PUT /api/roles/{id}/permissions`   — set/replace permissions for a role
 //! - `PUT /api/roles/{id}/apps`          — set app permissions for a role
+//!
 //! Also adds coverage for edge cases on already-tested endpoints:
 //! - Duplicate role name returns 400
 //! - Renaming a system role is blocked
@@ -91,6 +91,9 @@ fn make_user_model(id: i64, username: &str, email: &str) -> user::Model {
         totp_secret: None,
         totp_enabled: false,
         totp_verified_at: None,
+        failed_login_attempts: 0,
+        locked_until: None,
+        is_service_account: false,
         created_at: now,
         updated_at: now,
     }
@@ -174,6 +177,9 @@ fn test_user_response_inactive_not_approved() {
         totp_secret: None,
         totp_enabled: false,
         totp_verified_at: None,
+        failed_login_attempts: 0,
+        locked_until: None,
+        is_service_account: false,
         created_at: now,
         updated_at: now,
     };
@@ -0,0 +1,126 @@
+//! Integration tests for the security posture report
+//!
+//! Covers:
+//! - `GET /api/system/security-report` checks, scoring and access control
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::prelude::*;
+use kubarr::models::{session, user};
+
+/// Find a check in a report by id
+fn finding<'a>(report: &'a Value, id: &str) -> &'a Value {
+    report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["id"] == id)
+        .unwrap_or_else(|| panic!("No finding '{}'", id))
+}
+
+#[tokio::test]
+async fn test_security_report() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/system/security-report",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, report) = env
+        .request(
+            "GET",
+            "/api/system/security-report",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", report);
+    let ids: Vec<&str> = report["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "default_credentials",
+            "two_factor_coverage",
+            "role_separation",
+            "cors",
+            "stored_secrets",
+            "audit_integrity",
+            "stale_sessions",
+            "brute_force_protection",
+        ]
+    );
+    // The fixture accounts use the development password and have no 2FA
+    let defaults = finding(&report, "default_credentials");
+    assert_eq!(defaults["status"], "fail");
+    assert!(defaults["detail"].as_str().unwrap().contains("admin"));
+    assert_eq!(finding(&report, "two_factor_coverage")["status"], "fail");
+    assert_eq!(report["two_factor_coverage"], 0.0);
+    assert_eq!(finding(&report, "role_separation")["status"], "pass");
+    assert_eq!(finding(&report, "stale_sessions")["status"], "pass");
+    assert_eq!(finding(&report, "brute_force_protection")["status"], "pass");
+    assert!(finding(&report, "cors")["remediation"].is_string());
+    assert!(finding(&report, "role_separation")["remediation"].is_null());
+    assert!(report["score"].as_u64().unwrap() < 100);
+
+    // The report is rebuilt on every request
+    let admin = env.user("admin").user.clone();
+    let mut active: user::ActiveModel = admin.into();
+    active.totp_enabled = Set(true);
+    active.update(&env.db).await.unwrap();
+    let viewer_id = env.user("viewer").user.id;
+    Session::update_many()
+        .col_expr(
+            session::Column::LastAccessedAt,
+            sea_orm::sea_query::Expr::value(Utc::now() - Duration::days(45)),
+        )
+        .filter(session::Column::UserId.eq(viewer_id))
+        .exec(&env.db)
+        .await
+        .unwrap();
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/settings/ip_ban_threshold",
+            Some(env.cookie("admin")),
+            Some(json!({"value": "0"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, refreshed) = env
+        .request(
+            "GET",
+            "/api/system/security-report",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(finding(&refreshed, "two_factor_coverage")["status"], "warn");
+    assert_eq!(refreshed["two_factor_coverage"], 50.0);
+    let stale = finding(&refreshed, "stale_sessions");
+    assert_eq!(stale["status"], "warn");
+    assert!(stale["detail"]
+        .as_str()
+        .unwrap()
+        .starts_with("1 active session"));
+    assert_eq!(
+        finding(&refreshed, "brute_force_protection")["status"],
+        "warn"
+    );
+    assert!(refreshed["score"].as_u64() > report["score"].as_u64());
+}
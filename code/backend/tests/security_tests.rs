@@ -357,7 +357,7 @@ async fn test_session_token_roundtrip() {
 
     let session_id = "sess-abc-123";
 
-    let token = create_session_token(session_id).expect("create_session_token must not fail");
+    let token = create_session_token(session_id, None).expect("create_session_token must not fail");
 
     let claims =
         decode_session_token(&token).expect("decode_session_token must succeed for a fresh token");
@@ -366,6 +366,12 @@ async fn test_session_token_roundtrip() {
         claims.sid, session_id,
         "Decoded session ID must match the original"
     );
+    assert!(!claims.is_expired(chrono::Utc::now()));
+
+    // Expired access tokens still decode, so their session can be found
+    let token = create_session_token(session_id, Some(-1)).unwrap();
+    let claims = decode_session_token(&token).expect("expired tokens must still decode");
+    assert!(claims.is_expired(chrono::Utc::now()));
 }
 
 // ==========================================================================
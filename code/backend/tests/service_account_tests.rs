@@ -0,0 +1,287 @@
+//! Integration tests for service accounts
+//!
+//! Covers:
+//! - `GET/POST /api/users/service-accounts` and
+//!   `GET/PATCH/DELETE /api/users/service-accounts/{account_id}` — validation,
+//!   duplicates, roles and permissions
+//! - `GET/POST /api/users/service-accounts/{account_id}/tokens` and
+//!   `DELETE .../tokens/{token_id}` — tokens authenticating as the account
+//!   until they expire, are revoked or the account is deactivated
+//! - service accounts being left out of the user list, password sign-in and
+//!   sign-in analytics
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::{role, service_account_token};
+
+/// Send a request with a bearer token
+async fn with_token(env: &TestEnv, method: &str, uri: &str, token: &str) -> (StatusCode, Value) {
+    let response = env
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header("Authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+async fn role_id(env: &TestEnv, name: &str) -> i64 {
+    role::Entity::find()
+        .filter(role::Column::Name.eq(name))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .expect("Role not found")
+        .id
+}
+
+/// Create a service account with the viewer role and a token for it
+async fn account_with_token(env: &TestEnv, name: &str) -> (i64, Value) {
+    let viewer = role_id(env, "viewer").await;
+    let (status, account) = env
+        .request(
+            "POST",
+            "/api/users/service-accounts",
+            Some(env.cookie("admin")),
+            Some(json!({ "name": name, "role_ids": [viewer] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", account);
+    let id = account["id"].as_i64().unwrap();
+    let (status, token) = env
+        .request(
+            "POST",
+            &format!("/api/users/service-accounts/{}/tokens", id),
+            Some(env.cookie("admin")),
+            Some(json!({ "name": "ci", "expires_in_days": 30 })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", token);
+    (id, token)
+}
+
+#[tokio::test]
+async fn test_manage_service_accounts() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let viewer = role_id(&env, "viewer").await;
+
+    for (name, role_ids, expected) in [
+        ("has space", vec![viewer], StatusCode::BAD_REQUEST),
+        ("ci-bot", vec![9999], StatusCode::BAD_REQUEST),
+        ("admin", vec![viewer], StatusCode::CONFLICT),
+    ] {
+        let (status, _) = env
+            .request(
+                "POST",
+                "/api/users/service-accounts",
+                Some(env.cookie("admin")),
+                Some(json!({ "name": name, "role_ids": role_ids })),
+            )
+            .await;
+        assert_eq!(status, expected, "{}", name);
+    }
+
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/users/service-accounts",
+            Some(env.cookie("viewer")),
+            Some(json!({ "name": "ci-bot" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let analytics = || {
+        env.request(
+            "GET",
+            "/api/monitoring/auth",
+            Some(env.cookie("admin")),
+            None,
+        )
+    };
+    let (_, before) = analytics().await;
+
+    let (id, _) = account_with_token(&env, "ci-bot").await;
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/users/service-accounts",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let accounts = body.as_array().unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0]["name"], "ci-bot");
+    assert_eq!(accounts[0]["roles"][0]["name"], "viewer");
+    assert_eq!(accounts[0]["active_tokens"], 1);
+
+    // Not a person: left out of the user list and the 2FA figures
+    let (_, users) = env
+        .request("GET", "/api/users", Some(env.cookie("admin")), None)
+        .await;
+    let usernames: Vec<&str> = users
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["username"].as_str().unwrap())
+        .collect();
+    assert!(!usernames.contains(&"ci-bot"), "{:?}", usernames);
+    let (_, after) = analytics().await;
+    assert_eq!(
+        after["two_factor"]["active_users"],
+        before["two_factor"]["active_users"]
+    );
+
+    // A human user cannot be managed as a service account
+    let admin_id = env.user("admin").user.id;
+    let (status, _) = env
+        .request(
+            "GET",
+            &format!("/api/users/service-accounts/{}", admin_id),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = env
+        .request(
+            "PATCH",
+            &format!("/api/users/service-accounts/{}", id),
+            Some(env.cookie("admin")),
+            Some(json!({ "role_ids": [] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["roles"], json!([]));
+
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/users/service-accounts/{}", id),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let remaining = service_account_token::Entity::find()
+        .all(&env.db)
+        .await
+        .unwrap();
+    assert!(remaining.is_empty(), "tokens are deleted with the account");
+}
+
+#[tokio::test]
+async fn test_service_account_tokens_authenticate() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let (id, created) = account_with_token(&env, "ci-bot").await;
+    let token = created["token"].as_str().unwrap();
+    assert!(token.starts_with("kbr_sa_"));
+    assert!(token.starts_with(created["token_prefix"].as_str().unwrap()));
+
+    let (status, me) = with_token(&env, "GET", "/api/users/me", token).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me["username"], "ci-bot");
+    // Its roles apply: a viewer cannot manage users
+    let (status, _) = with_token(&env, "GET", "/api/users/invites", token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The token is never shown again
+    let (_, tokens) = env
+        .request(
+            "GET",
+            &format!("/api/users/service-accounts/{}/tokens", id),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert!(tokens[0].get("token").is_none());
+    assert!(tokens[0]["last_used_at"].is_string());
+
+    // No password sign-in
+    let (status, _) = env
+        .request(
+            "POST",
+            "/auth/login",
+            None,
+            Some(json!({ "username": "ci-bot", "password": "!" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Deactivated accounts' tokens are refused
+    for (is_active, expected) in [(false, StatusCode::UNAUTHORIZED), (true, StatusCode::OK)] {
+        env.request(
+            "PATCH",
+            &format!("/api/users/service-accounts/{}", id),
+            Some(env.cookie("admin")),
+            Some(json!({ "is_active": is_active })),
+        )
+        .await;
+        let (status, _) = with_token(&env, "GET", "/api/users/me", token).await;
+        assert_eq!(status, expected);
+    }
+
+    // Expired tokens are refused
+    let stored = service_account_token::Entity::find_by_id(created["id"].as_i64().unwrap())
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut expired: service_account_token::ActiveModel = stored.clone().into();
+    expired.expires_at = Set(Some(Utc::now() - Duration::minutes(1)));
+    expired.update(&env.db).await.unwrap();
+    let (status, body) = with_token(&env, "GET", "/api/users/me", token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["detail"], "Service account token has expired");
+
+    // Revoked tokens are refused
+    let (_, second) = env
+        .request(
+            "POST",
+            &format!("/api/users/service-accounts/{}/tokens", id),
+            Some(env.cookie("admin")),
+            Some(json!({ "name": "backup" })),
+        )
+        .await;
+    let second_token = second["token"].as_str().unwrap();
+    assert!(second["expires_at"].is_null());
+    let (status, _) = with_token(&env, "GET", "/api/users/me", second_token).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/users/service-accounts/{}/tokens/{}", id, second["id"]),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = with_token(&env, "GET", "/api/users/me", second_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = with_token(&env, "GET", "/api/users/me", "kbr_sa_unknown").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
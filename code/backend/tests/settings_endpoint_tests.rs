@@ -4,6 +4,8 @@
 //! - `GET /api/settings` — list settings (requires settings.view)
 //! - `GET /api/settings/{key}` — get a specific setting (requires settings.view)
 //! - `PUT /api/settings/{key}` — update a setting (requires settings.manage)
+//! - `GET /api/settings/schema` — static vs runtime-reloadable keys
+//! - Runtime-reloadable settings are validated and applied without a restart
 //! - Permission enforcement: viewer role cannot access settings
 
 use axum::{
@@ -484,3 +486,153 @@ async fn test_viewer_cannot_update_settings() {
         body
     );
 }
+
+// ============================================================================
+// Runtime-reloadable settings
+// ============================================================================
+
+#[tokio::test]
+async fn test_runtime_settings_apply_without_restart() {
+    use kubarr::services::runtime_config;
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "runtimesettingadmin",
+        "runtimesetting@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "runtimesettingadmin",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    // The schema marks which keys need a restart
+    let (status, body) = authenticated_get(
+        create_router(state.clone()),
+        "/api/settings/schema",
+        &cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let schema: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    let entry = |key: &str| schema.iter().find(|e| e["key"] == key).cloned().unwrap();
+    assert_eq!(entry("log_level")["reload"], "live");
+    assert_eq!(entry("log_level")["env_fallback"], "KUBARR_LOG_LEVEL");
+    assert_eq!(entry("KUBARR_API_PORT")["reload"], "restart");
+    assert_eq!(entry("KUBARR_API_PORT")["editable"], false);
+
+    // Invalid values are rejected before they are stored
+    let (status, _) = authenticated_put(
+        create_router(state.clone()),
+        "/api/settings/victoriametrics_url",
+        &cookie,
+        &serde_json::json!({ "value": "not a url" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let changes = runtime_config::subscribe();
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        "/api/settings/victoriametrics_url",
+        &cookie,
+        &serde_json::json!({ "value": "http://vm.monitoring:8428" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    assert!(changes.has_changed().unwrap());
+    assert_eq!(
+        runtime_config::current().victoriametrics_url,
+        "http://vm.monitoring:8428"
+    );
+
+    // Clearing the setting falls back to the default
+    let (status, _) = authenticated_put(
+        create_router(state),
+        "/api/settings/victoriametrics_url",
+        &cookie,
+        &serde_json::json!({ "value": "" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        runtime_config::current().victoriametrics_url,
+        kubarr::services::victoriametrics::VICTORIAMETRICS_URL
+    );
+}
+
+#[tokio::test]
+async fn test_external_url_setting_changes_links() {
+    use kubarr::services::{invites, links};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "externalurladmin",
+        "externalurl@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+
+    let (_, cookie) = do_login(
+        create_router(state.clone()),
+        "externalurladmin",
+        "password123",
+    )
+    .await;
+    let cookie = cookie.expect("Login must set a session cookie");
+
+    let (status, _) = authenticated_put(
+        create_router(state.clone()),
+        "/api/settings/external_url",
+        &cookie,
+        &serde_json::json!({ "value": "https://kubarr.example.com/?x=1" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = authenticated_put(
+        create_router(state.clone()),
+        "/api/settings/external_url",
+        &cookie,
+        &serde_json::json!({ "value": "https://kubarr.example.com/" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    assert_eq!(
+        invites::register_link("abc"),
+        "https://kubarr.example.com/auth/register?invite=abc"
+    );
+    assert_eq!(
+        links::oauth_callback("google"),
+        "https://kubarr.example.com/api/oauth/google/callback"
+    );
+
+    // Clearing the setting falls back to the environment
+    let (status, _) = authenticated_put(
+        create_router(state),
+        "/api/settings/external_url",
+        &cookie,
+        &serde_json::json!({ "value": "" }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        links::external_url(),
+        kubarr::config::CONFIG.server.external_url
+    );
+}
@@ -7,9 +7,7 @@
 //! - `get_user_app_access()` — app access list and wildcard handling
 
 mod common;
-use common::{
-    create_test_db, create_test_db_with_seed, create_test_user, create_test_user_with_role,
-};
+use common::{create_test_db_with_seed, create_test_user, create_test_user_with_role};
 
 use kubarr::endpoints::extractors::{get_user_app_access, get_user_permissions};
 use kubarr::endpoints::settings::{get_setting_bool, get_setting_value};
@@ -307,6 +305,7 @@ async fn test_get_user_app_access_wildcard_returns_star() {
     let user_role_model = user_role::ActiveModel {
         user_id: Set(user.id),
         role_id: Set(created_role.id),
+        ..Default::default()
     };
     user_role_model.insert(&db).await.unwrap();
 
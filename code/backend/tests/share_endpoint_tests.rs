@@ -0,0 +1,239 @@
+//! Integration tests for the SMB share endpoints against `FakeK8s` and
+//! `MockHelmEngine`
+//!
+//! Covers:
+//! - `GET /api/storage/shares`               — release status and access
+//! - `POST /api/storage/shares`              — sharing a root deploys Samba
+//! - `DELETE /api/storage/shares/{root}`     — unsharing the last root removes it
+//! - `PUT|DELETE /api/storage/shares/password` — share users and their hashes
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, Set};
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::models::system_setting;
+use kubarr::services::helm::HelmCall;
+use kubarr::services::shares::{nt_hash, SAMBA_NAMESPACE, SAMBA_RELEASE, SAMBA_SECRET};
+
+async fn set_setting(env: &TestEnv, key: &str, value: &str) {
+    system_setting::ActiveModel {
+        key: Set(key.to_string()),
+        value: Set(value.to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+}
+
+/// Admin and viewer with a `media` root under the storage path and an
+/// `external` root outside it
+async fn setup() -> TestEnv {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    set_setting(&env, "storage_path", "/mnt/data").await;
+    let roots = serde_json::json!([
+        {"name": "media", "path": "media"},
+        {"name": "external", "path": "/mnt/external"}
+    ]);
+    set_setting(&env, "storage_roots", &roots.to_string()).await;
+    env
+}
+
+/// Contents of a key of the Samba config secret
+fn secret_file(env: &TestEnv, key: &str) -> String {
+    let secret = env
+        .k8s
+        .secret(SAMBA_NAMESPACE, SAMBA_SECRET)
+        .expect("samba config secret");
+    let data = secret.data.unwrap();
+    String::from_utf8(data[key].0.clone()).unwrap()
+}
+
+#[tokio::test]
+async fn test_share_lifecycle() {
+    let env = setup().await;
+    let admin = env.cookie("admin");
+    let viewer = env.cookie("viewer");
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/storage/shares/password",
+            Some(admin),
+            Some(serde_json::json!({"password": "adminshare"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["has_password"], true);
+    assert!(env.helm.calls().is_empty(), "nothing is shared yet");
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/storage/shares",
+            Some(admin),
+            Some(serde_json::json!({"root": "media"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("error").is_none(), "unexpected error: {}", body);
+    assert_eq!(body["shares"][0]["name"], "media");
+    assert_eq!(body["shares"][0]["access"], "write");
+    let users = body["shares"][0]["users"].as_array().unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0]["username"], "admin");
+    assert_eq!(users[0]["has_password"], true);
+    assert_eq!(users[1]["username"], "viewer");
+    assert_eq!(users[1]["access"], "read");
+    assert_eq!(users[1]["has_password"], false);
+
+    let calls = env.helm.calls();
+    let [HelmCall::UpgradeInstall(release)] = calls.as_slice() else {
+        panic!("expected one install, got {:?}", calls);
+    };
+    assert_eq!(release.name, SAMBA_RELEASE);
+    assert_eq!(release.namespace, SAMBA_NAMESPACE);
+    assert!(release
+        .set
+        .contains(&"storage.hostPath.rootPath=/mnt/data".to_string()));
+    assert!(release.set_string[0].starts_with("configChecksum="));
+
+    let shares_conf = secret_file(&env, "shares.conf");
+    assert!(shares_conf.contains("[media]\n   path = /storage/media\n"));
+    assert!(shares_conf.contains("valid users = admin\n"));
+    assert!(shares_conf.contains("write list = admin\n"));
+    let smbpasswd = secret_file(&env, "smbpasswd");
+    assert!(smbpasswd.starts_with("admin:"));
+    assert!(smbpasswd.contains(&nt_hash("adminshare")));
+    assert!(!smbpasswd.contains("viewer"));
+
+    // A viewer sees their own access but not the other users
+    let (status, body) = env
+        .request("GET", "/api/storage/shares", Some(viewer), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["has_password"], false);
+    assert_eq!(body["shares"][0]["access"], "read");
+    assert!(body["shares"][0].get("users").is_none());
+
+    // Setting a password makes the viewer a read-only share user
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/storage/shares/password",
+            Some(viewer),
+            Some(serde_json::json!({"password": "viewershare"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let shares_conf = secret_file(&env, "shares.conf");
+    assert!(shares_conf.contains("valid users = admin viewer\n"));
+    assert!(shares_conf.contains("write list = admin\n"));
+    assert!(secret_file(&env, "smbpasswd").contains("viewer:"));
+
+    let (status, _) = env
+        .request("DELETE", "/api/storage/shares/password", Some(viewer), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!secret_file(&env, "smbpasswd").contains("viewer:"));
+
+    // Unsharing the last root removes the release and its secret
+    let (status, body) = env
+        .request("DELETE", "/api/storage/shares/media", Some(admin), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["shares"], serde_json::json!([]));
+    assert!(matches!(
+        env.helm.calls().last(),
+        Some(HelmCall::Uninstall { name, .. }) if name == SAMBA_RELEASE
+    ));
+    assert!(env.k8s.secret(SAMBA_NAMESPACE, SAMBA_SECRET).is_none());
+}
+
+#[tokio::test]
+async fn test_share_validation() {
+    let env = setup().await;
+    let admin = env.cookie("admin");
+    let viewer = env.cookie("viewer");
+
+    let create = |cookie, root: &str| {
+        env.request(
+            "POST",
+            "/api/storage/shares",
+            Some(cookie),
+            Some(serde_json::json!({"root": root, "read_only": true})),
+        )
+    };
+    assert_eq!(create(viewer, "media").await.0, StatusCode::FORBIDDEN);
+    assert_eq!(create(admin, "missing").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(create(admin, "external").await.0, StatusCode::BAD_REQUEST);
+
+    let (status, body) = create(admin, "media").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["shares"][0]["read_only"], true);
+    assert_eq!(body["shares"][0]["access"], "read");
+    assert_eq!(create(admin, "media").await.0, StatusCode::CONFLICT);
+
+    let (status, _) = env
+        .request("DELETE", "/api/storage/shares/external", Some(admin), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            "/api/storage/shares/password",
+            Some(viewer),
+            Some(serde_json::json!({"password": "short"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["detail"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("at least 8 characters"));
+}
+
+#[tokio::test]
+async fn test_sync_failure_is_reported() {
+    let env = setup().await;
+    let admin = env.cookie("admin");
+    env.request(
+        "PUT",
+        "/api/storage/shares/password",
+        Some(admin),
+        Some(serde_json::json!({"password": "adminshare"})),
+    )
+    .await;
+    env.helm.fail_with("chart not found");
+
+    // The share is saved even though the release could not be deployed
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/storage/shares",
+            Some(admin),
+            Some(serde_json::json!({"root": "media"})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["error"].as_str().unwrap().contains("chart not found"));
+    assert_eq!(body["shares"][0]["name"], "media");
+    assert_eq!(body["in_sync"], false);
+
+    let (status, _) = env
+        .request("POST", "/api/storage/shares/sync", Some(admin), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/storage/shares/sync",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
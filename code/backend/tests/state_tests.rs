@@ -278,6 +278,46 @@ async fn test_endpoint_cache_overwrite() {
     assert_eq!(path.as_deref(), Some("/new"));
 }
 
+// ============================================================================
+// IntegrationCache
+// ============================================================================
+
+#[tokio::test]
+async fn test_integration_cache_set_and_get() {
+    use kubarr::state::IntegrationCache;
+    let cache = IntegrationCache::new(60);
+    assert!(cache.get("sonarr:calendar").await.is_none());
+    cache
+        .set("sonarr:calendar", serde_json::json!([{"title": "Pilot"}]))
+        .await;
+    let value = cache.get("sonarr:calendar").await.unwrap();
+    assert_eq!(value[0]["title"], "Pilot");
+}
+
+#[tokio::test]
+async fn test_integration_cache_expired_entry_not_returned() {
+    use kubarr::state::IntegrationCache;
+    let cache = IntegrationCache::new(0);
+    cache.set("radarr:calendar", serde_json::json!([])).await;
+    assert!(
+        cache.get("radarr:calendar").await.is_none(),
+        "Entry with zero TTL must be expired"
+    );
+}
+
+#[tokio::test]
+async fn test_integration_cache_invalidate_app() {
+    use kubarr::state::IntegrationCache;
+    let cache = IntegrationCache::new(60);
+    cache.set("sonarr:calendar:a", serde_json::json!(1)).await;
+    cache.set("sonarr:calendar:b", serde_json::json!(2)).await;
+    cache.set("radarr:calendar:a", serde_json::json!(3)).await;
+    cache.invalidate_app("sonarr").await;
+    assert!(cache.get("sonarr:calendar:a").await.is_none());
+    assert!(cache.get("sonarr:calendar:b").await.is_none());
+    assert!(cache.get("radarr:calendar:a").await.is_some());
+}
+
 // ============================================================================
 // NetworkMetricsCache async methods (get / add_sample)
 // ============================================================================
@@ -2,18 +2,34 @@
 //!
 //! Covers endpoints under `/api/storage`:
 //!
+//! - `GET  /api/storage/roots`     — storage roots visible to the caller (requires storage.view)
 //! - `GET  /api/storage/browse`    — list directory contents (requires storage.view)
 //! - `GET  /api/storage/stats`     — disk usage statistics (requires storage.view)
 //! - `GET  /api/storage/file-info` — file/directory metadata (requires storage.view)
 //! - `POST /api/storage/mkdir`     — create directory (requires storage.write)
-//! - `DELETE /api/storage/delete`  — delete file or empty dir (requires storage.delete)
+//! - `DELETE /api/storage/delete`  — move a file or empty dir to the trash, or delete it
+//!   with `permanent=true` (requires storage.delete)
 //! - `GET  /api/storage/download`  — stream file download (requires storage.download)
+//! - `GET  /api/storage/preview`   — cached image/video thumbnail (requires storage.download)
+//! - `POST /api/storage/checksum`  — compute a SHA-256/BLAKE3 checksum (requires storage.download)
+//! - `GET  /api/storage/checksum`  — stored checksums of a file (requires storage.download)
+//! - `GET  /api/storage/trash`     — list trashed items (requires storage.view)
+//! - `POST /api/storage/trash/{id}/restore` — restore an item (requires storage.write)
+//! - `DELETE /api/storage/trash[/{id}]` — purge one item or all (requires storage.delete)
 //!
 //! Storage path is controlled by the `KUBARR_STORAGE_PATH` environment variable.
 //! Each test that touches the filesystem creates its own unique temp directory and
 //! sets that env var before calling the endpoint.  Because env vars are
 //! process-global, a static Mutex serialises all storage tests.
 //!
+//! Preview tests never run ffmpeg: they seed the thumbnail cache directly.
+//!
+//! Browse supports `offset`/`limit`, `sort`/`order` and `light`; pages over
+//! 1000 items are streamed.
+//!
+//! Tests built on named storage roots (the `storage_roots` setting) use absolute
+//! root paths, so they do not depend on `KUBARR_STORAGE_PATH`.
+//!
 //! Auth checks:
 //! - Unauthenticated access → 401
 //! - Viewer role (has storage.view / storage.download but NOT storage.write or
@@ -1962,3 +1978,842 @@ async fn test_delete_path_traversal_rejected() {
         status
     );
 }
+
+// ============================================================================
+// Named storage roots
+// ============================================================================
+
+/// Configure a `media` root open to everyone with storage permissions and a
+/// `backups` root only admins see, without write access
+async fn roots_setup(
+    media: &std::path::Path,
+    backups: &std::path::Path,
+) -> (axum::Router, String, String, sea_orm::DatabaseConnection) {
+    use sea_orm::{ActiveModelTrait, Set};
+
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    let roots = serde_json::json!([
+        {
+            "name": "media",
+            "path": media.to_str().unwrap(),
+            "protected_folders": ["movies"]
+        },
+        {
+            "name": "backups",
+            "path": backups.to_str().unwrap(),
+            "roles": { "admin": ["storage.view", "storage.download", "storage.delete"] }
+        }
+    ]);
+    kubarr::models::system_setting::ActiveModel {
+        key: Set("storage_roots".to_string()),
+        value: Set(roots.to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    create_test_user_with_role(
+        &db,
+        "rootsadmin",
+        "rootsadmin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "rootsviewer",
+        "rootsviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let app = create_router(build_test_app_state_with_db(db.clone()).await);
+    let (_, admin) = do_login(app.clone(), "rootsadmin", "password123").await;
+    let (_, viewer) = do_login(app.clone(), "rootsviewer", "password123").await;
+    (app, admin.unwrap(), viewer.unwrap(), db)
+}
+
+#[tokio::test]
+async fn test_default_storage_root() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "defaultrootviewer",
+        "defaultrootviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let app = create_router(build_test_app_state_with_db(db).await);
+    let (_, cookie) = do_login(app.clone(), "defaultrootviewer", "password123").await;
+
+    let (status, body) = authenticated_get(app, "/api/storage/roots", &cookie.unwrap()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let roots: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        roots,
+        serde_json::json!([{
+            "name": "data",
+            "protected_folders": ["downloads", "media"],
+            "permissions": ["storage.view", "storage.download"]
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_storage_roots_visibility_per_role() {
+    let media = make_temp_dir("roots_media");
+    let backups = make_temp_dir("roots_backups");
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    std::fs::write(backups.path().join("db.sql"), "backup").unwrap();
+    let (app, admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) = authenticated_get(app.clone(), "/api/storage/roots", &viewer).await;
+    assert_eq!(status, StatusCode::OK);
+    let roots: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(roots.as_array().unwrap().len(), 1);
+    assert_eq!(roots[0]["name"], "media");
+
+    let (_, body) = authenticated_get(app.clone(), "/api/storage/roots", &admin).await;
+    let roots: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(roots[1]["name"], "backups");
+    assert_eq!(
+        roots[1]["permissions"],
+        serde_json::json!(["storage.view", "storage.download", "storage.delete"])
+    );
+
+    // Without a root the first one is used
+    let (status, body) = authenticated_get(app.clone(), "/api/storage/browse", &viewer).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let listing: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(listing["root"], "media");
+    assert_eq!(listing["items"][0]["name"], "movies");
+
+    // Hidden and unknown roots look the same
+    for uri in [
+        "/api/storage/browse?root=backups",
+        "/api/storage/file-info?root=backups&path=db.sql",
+        "/api/storage/browse?root=nope",
+    ] {
+        let (status, _) = authenticated_get(app.clone(), uri, &viewer).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+    }
+
+    let (status, body) =
+        authenticated_get(app.clone(), "/api/storage/browse?root=backups", &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    let listing: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(listing["root"], "backups");
+    assert_eq!(listing["items"][0]["name"], "db.sql");
+    let (status, _) =
+        authenticated_get(app.clone(), "/api/storage/stats?root=backups", &admin).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_storage_root_permissions_and_protected_folders() {
+    let media = make_temp_dir("roots_perm_media");
+    let backups = make_temp_dir("roots_perm_backups");
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    std::fs::create_dir(media.path().join("downloads")).unwrap();
+    std::fs::write(backups.path().join("old.sql"), "backup").unwrap();
+    let (app, admin, _viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    // The backups root does not grant storage.write to admins
+    let (status, _) = authenticated_post(
+        app.clone(),
+        "/api/storage/mkdir",
+        &admin,
+        r#"{"path": "new", "root": "backups"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(!backups.path().join("new").exists());
+
+    let (status, _) = authenticated_post(
+        app.clone(),
+        "/api/storage/mkdir",
+        &admin,
+        r#"{"path": "new", "root": "media"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(media.path().join("new").is_dir());
+
+    // Protected folders are per root
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?root=media&path=movies",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?root=media&path=downloads",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?root=backups&path=old.sql",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!backups.path().join("old.sql").exists());
+}
+
+#[tokio::test]
+async fn test_storage_roots_setting_is_validated() {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "rootssettings",
+        "rootssettings@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let app = create_router(build_test_app_state_with_db(db).await);
+    let (_, cookie) = do_login(app.clone(), "rootssettings", "password123").await;
+    let cookie = cookie.unwrap();
+
+    let put = |value: serde_json::Value| {
+        let app = app.clone();
+        let cookie = cookie.clone();
+        async move {
+            let request = Request::builder()
+                .uri("/api/settings/storage_roots")
+                .method("PUT")
+                .header("Cookie", cookie)
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({ "value": value }).to_string(),
+                ))
+                .unwrap();
+            app.oneshot(request).await.unwrap().status()
+        }
+    };
+
+    let invalid = r#"[{"name": "media", "path": "../etc"}]"#;
+    assert_eq!(put(invalid.into()).await, StatusCode::BAD_REQUEST);
+    let valid = r#"[{"name": "media", "path": "media", "roles": {"viewer": ["storage.view"]}}]"#;
+    assert_eq!(put(valid.into()).await, StatusCode::OK);
+    assert_eq!(put("".into()).await, StatusCode::OK);
+}
+
+// ============================================================================
+// GET /api/storage/browse — pagination, sorting and streaming
+// ============================================================================
+
+#[tokio::test]
+async fn test_browse_pagination_and_sorting() {
+    let media = make_temp_dir("browse_pages");
+    let backups = make_temp_dir("browse_pages_backups");
+    std::fs::create_dir(media.path().join("zdir")).unwrap();
+    std::fs::write(media.path().join("a.txt"), "123").unwrap();
+    std::fs::write(media.path().join("B.txt"), "1").unwrap();
+    std::fs::write(media.path().join("c.txt"), "12").unwrap();
+    let (app, _admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let names = |body: &str| -> Vec<String> {
+        let json: serde_json::Value = serde_json::from_str(body).unwrap();
+        json["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, body) =
+        authenticated_get(app.clone(), "/api/storage/browse?offset=1&limit=2", &viewer).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["total_items"], 4);
+    assert_eq!(json["offset"], 1);
+    assert_eq!(json["next_offset"], 3);
+    assert_eq!(names(&body), vec!["a.txt", "B.txt"]);
+
+    let (_, body) =
+        authenticated_get(app.clone(), "/api/storage/browse?offset=3&limit=2", &viewer).await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["next_offset"].is_null());
+    assert_eq!(names(&body), vec!["c.txt"]);
+
+    // Directories stay first whatever the sort
+    let (_, body) = authenticated_get(
+        app.clone(),
+        "/api/storage/browse?sort=size&order=desc",
+        &viewer,
+    )
+    .await;
+    assert_eq!(names(&body), vec!["zdir", "a.txt", "c.txt", "B.txt"]);
+    let (_, body) = authenticated_get(
+        app.clone(),
+        "/api/storage/browse?sort=name&order=desc",
+        &viewer,
+    )
+    .await;
+    assert_eq!(names(&body), vec!["zdir", "c.txt", "B.txt", "a.txt"]);
+
+    let (status, _) =
+        authenticated_get(app.clone(), "/api/storage/browse?sort=owner", &viewer).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_browse_light_mode_skips_metadata() {
+    let media = make_temp_dir("browse_light");
+    let backups = make_temp_dir("browse_light_backups");
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    std::fs::write(media.path().join("file.txt"), "data").unwrap();
+    let (app, _admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) =
+        authenticated_get(app.clone(), "/api/storage/browse?light=true", &viewer).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        json["items"],
+        serde_json::json!([
+            {"name": "movies", "path": "movies", "type": "directory"},
+            {"name": "file.txt", "path": "file.txt", "type": "file"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_browse_streams_large_listings() {
+    let media = make_temp_dir("browse_stream");
+    let backups = make_temp_dir("browse_stream_backups");
+    for i in 0..1500 {
+        std::fs::write(media.path().join(format!("file{:04}.txt", i)), "x").unwrap();
+    }
+    let (app, _admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    for uri in ["/api/storage/browse", "/api/storage/browse?light=true"] {
+        let (status, body) = authenticated_get(app.clone(), uri, &viewer).await;
+        assert_eq!(status, StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["root"], "media");
+        assert_eq!(json["total_items"], 1500);
+        assert!(json["next_offset"].is_null());
+        let items = json["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1500);
+        assert_eq!(items[0]["name"], "file0000.txt");
+        assert_eq!(items[1499]["name"], "file1499.txt");
+    }
+
+    let (_, body) = authenticated_get(
+        app.clone(),
+        "/api/storage/browse?offset=1400&limit=1000",
+        &viewer,
+    )
+    .await;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["items"].as_array().unwrap().len(), 100);
+    assert_eq!(json["items"][0]["size"], 1);
+}
+
+// ============================================================================
+// GET /api/storage/preview
+// ============================================================================
+
+#[tokio::test]
+async fn test_preview_serves_cached_thumbnail() {
+    use kubarr::services::previews::{PreviewSize, PREVIEWS};
+
+    let media = make_temp_dir("preview_media");
+    let backups = make_temp_dir("preview_backups");
+    let file = media.path().join("preview_cached.png");
+    std::fs::write(&file, "png").unwrap();
+    let (app, admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    // Seed the cache as ffmpeg would
+    let cached = PREVIEWS
+        .cache_path("media", "preview_cached.png", &file, PreviewSize::Small)
+        .unwrap();
+    std::fs::create_dir_all(cached.parent().unwrap()).unwrap();
+    std::fs::write(&cached, "thumbnail").unwrap();
+
+    let get = |if_none_match: Option<String>| {
+        let app = app.clone();
+        let viewer = viewer.clone();
+        async move {
+            let mut request = Request::builder()
+                .uri("/api/storage/preview?path=preview_cached.png&size=small")
+                .header("Cookie", viewer);
+            if let Some(etag) = if_none_match {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            app.oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+        }
+    };
+
+    let response = get(None).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "image/jpeg");
+    let etag = response.headers()[header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"thumbnail");
+
+    let response = get(Some(etag)).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    // Modifying the file misses the cache
+    std::fs::write(&file, "modified png").unwrap();
+    let response = get(None).await;
+    assert_ne!(response.status(), StatusCode::OK);
+
+    // Deleting the file drops its thumbnails
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?path=preview_cached.png",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!cached.exists());
+}
+
+#[tokio::test]
+async fn test_preview_rejects_unsupported_paths() {
+    let media = make_temp_dir("preview_reject_media");
+    let backups = make_temp_dir("preview_reject_backups");
+    std::fs::write(media.path().join("notes.txt"), "text").unwrap();
+    std::fs::create_dir(media.path().join("photos.jpg")).unwrap();
+    std::fs::write(backups.path().join("dump.png"), "png").unwrap();
+    let (app, _admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    for (uri, expected) in [
+        (
+            "/api/storage/preview?path=notes.txt",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/api/storage/preview?path=photos.jpg",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/api/storage/preview?path=missing.png",
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            "/api/storage/preview?path=dump.png&root=backups",
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            "/api/storage/preview?path=notes.txt&size=huge",
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let (status, _) = authenticated_get(app.clone(), uri, &viewer).await;
+        assert_eq!(status, expected, "{}", uri);
+    }
+
+    let (status, _) = authenticated_get(
+        create_router(build_test_app_state_with_db(create_test_db_with_seed().await).await),
+        "/api/storage/preview?path=notes.txt",
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+// ============================================================================
+// /api/storage/checksum
+// ============================================================================
+
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+#[tokio::test]
+async fn test_checksum_small_file() {
+    let media = make_temp_dir("checksum_media");
+    let backups = make_temp_dir("checksum_backups");
+    let file = media.path().join("release.iso");
+    std::fs::write(&file, "abc").unwrap();
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    let (app, admin, viewer, db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/storage/checksum",
+        &viewer,
+        &format!(
+            r#"{{"path": "release.iso", "expected": "{}"}}"#,
+            ABC_SHA256.to_uppercase()
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let first: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(first["status"], "completed");
+    assert_eq!(first["algorithm"], "sha256");
+    assert_eq!(first["checksum"], ABC_SHA256);
+    assert_eq!(first["root"], "media");
+    assert_eq!(first["size"], 3);
+    assert_eq!(first["stale"], false);
+    assert_eq!(first["matches"], true);
+
+    // The same version of the file reuses the result
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/storage/checksum",
+        &viewer,
+        r#"{"path": "release.iso", "expected": "deadbeef"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let again: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(again["id"], first["id"]);
+    assert_eq!(again["matches"], false);
+
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/storage/checksum",
+        &viewer,
+        r#"{"path": "release.iso", "algorithm": "blake3"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let blake3: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        blake3["checksum"],
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+    );
+    assert!(blake3.get("matches").is_none());
+
+    let (status, body) = authenticated_get(
+        app.clone(),
+        &format!(
+            "/api/storage/checksum?path=release.iso&expected={}",
+            ABC_SHA256
+        ),
+        &viewer,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let results: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(results.as_array().unwrap().len(), 2);
+    assert_eq!(results[0]["algorithm"], "blake3");
+    assert_eq!(results[0]["matches"], false);
+    assert_eq!(results[1]["matches"], true);
+
+    // Changing the file makes the stored results stale
+    std::fs::write(&file, "abcd").unwrap();
+    let (_, body) = authenticated_get(
+        app.clone(),
+        "/api/storage/checksum?path=release.iso",
+        &viewer,
+    )
+    .await;
+    let results: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(results[0]["stale"], true);
+    let (_, body) = authenticated_post(
+        app.clone(),
+        "/api/storage/checksum",
+        &viewer,
+        r#"{"path": "release.iso"}"#,
+    )
+    .await;
+    let changed: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_ne!(changed["id"], first["id"]);
+    assert_ne!(changed["checksum"], ABC_SHA256);
+
+    for (body, expected) in [
+        (r#"{"path": "movies"}"#, StatusCode::BAD_REQUEST),
+        (r#"{"path": "missing.iso"}"#, StatusCode::NOT_FOUND),
+        (
+            r#"{"path": "release.iso", "algorithm": "md5"}"#,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            r#"{"path": "release.iso", "root": "backups"}"#,
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let (status, _) =
+            authenticated_post(app.clone(), "/api/storage/checksum", &viewer, body).await;
+        assert_eq!(status, expected, "{}", body);
+    }
+
+    // Deleting the file drops its checksums
+    let (status, _) =
+        authenticated_delete(app.clone(), "/api/storage/delete?path=release.iso", &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    use sea_orm::{EntityTrait, PaginatorTrait};
+    let remaining = kubarr::models::file_checksum::Entity::find()
+        .count(&db)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn test_checksum_large_file_runs_in_background() {
+    let media = make_temp_dir("checksum_large_media");
+    let backups = make_temp_dir("checksum_large_backups");
+    let file = std::fs::File::create(media.path().join("big.img")).unwrap();
+    file.set_len(kubarr::services::checksums::INLINE_LIMIT + 1)
+        .unwrap();
+    let (app, _admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) = authenticated_post(
+        app.clone(),
+        "/api/storage/checksum",
+        &viewer,
+        r#"{"path": "big.img", "algorithm": "blake3"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{}", body);
+    let job: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(job["checksum"].is_null());
+
+    let mut result = serde_json::Value::Null;
+    for _ in 0..300 {
+        let (_, body) =
+            authenticated_get(app.clone(), "/api/storage/checksum?path=big.img", &viewer).await;
+        result = serde_json::from_str::<serde_json::Value>(&body).unwrap()[0].clone();
+        if result["status"] == "completed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(result["id"], job["id"]);
+    assert_eq!(result["status"], "completed", "{}", result);
+    assert_eq!(result["checksum"].as_str().unwrap().len(), 64);
+}
+
+// ============================================================================
+// /api/storage/trash
+// ============================================================================
+
+#[tokio::test]
+async fn test_delete_moves_to_trash_and_restore() {
+    let media = make_temp_dir("trash_media");
+    let backups = make_temp_dir("trash_backups");
+    std::fs::create_dir(media.path().join("shows")).unwrap();
+    std::fs::write(media.path().join("shows/episode.mkv"), "episode").unwrap();
+    let (app, admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?path=shows/episode.mkv",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let deleted: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let id = deleted["trash_id"].as_str().unwrap().to_string();
+    assert!(!media.path().join("shows/episode.mkv").exists());
+
+    // Viewers can see the trash
+    let (status, body) = authenticated_get(app.clone(), "/api/storage/trash", &viewer).await;
+    assert_eq!(status, StatusCode::OK);
+    let items: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(items.as_array().unwrap().len(), 1);
+    assert_eq!(items[0]["id"], id.as_str());
+    assert_eq!(items[0]["name"], "episode.mkv");
+    assert_eq!(items[0]["original_path"], "shows/episode.mkv");
+    assert_eq!(items[0]["type"], "file");
+    assert_eq!(items[0]["size"], 7);
+    assert_eq!(items[0]["deleted_by"], "rootsadmin");
+
+    // The trash folder is hidden from the other endpoints
+    let (_, body) = authenticated_get(app.clone(), "/api/storage/browse", &viewer).await;
+    let listing: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(listing["items"].as_array().unwrap().len(), 1);
+    for uri in [
+        "/api/storage/browse?path=.trash".to_string(),
+        format!("/api/storage/download?path=.trash/{}/item", id),
+        format!("/api/storage/file-info?path=shows/../.trash/{}", id),
+    ] {
+        let (status, _) = authenticated_get(app.clone(), &uri, &admin).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+    }
+    let (status, _) = authenticated_post(
+        app.clone(),
+        "/api/storage/mkdir",
+        &admin,
+        r#"{"path": ".trash/new"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Restoring needs storage.write and recreates missing folders
+    let restore = format!("/api/storage/trash/{}/restore", id);
+    let (status, _) = authenticated_post(app.clone(), &restore, &viewer, "").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    std::fs::remove_dir(media.path().join("shows")).unwrap();
+    let (status, body) = authenticated_post(app.clone(), &restore, &admin, "").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        std::fs::read_to_string(media.path().join("shows/episode.mkv")).unwrap(),
+        "episode"
+    );
+    let (status, _) = authenticated_post(app.clone(), &restore, &admin, "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Restoring over an existing file is refused
+    let (_, body) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?path=shows/episode.mkv",
+        &admin,
+    )
+    .await;
+    let id: serde_json::Value = serde_json::from_str(&body).unwrap();
+    std::fs::write(media.path().join("shows/episode.mkv"), "new").unwrap();
+    let (status, _) = authenticated_post(
+        app.clone(),
+        &format!(
+            "/api/storage/trash/{}/restore",
+            id["trash_id"].as_str().unwrap()
+        ),
+        &admin,
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_permanent_delete_and_purging_the_trash() {
+    let media = make_temp_dir("trash_purge_media");
+    let backups = make_temp_dir("trash_purge_backups");
+    for name in ["a.txt", "b.txt", "c.txt", "gone.txt"] {
+        std::fs::write(media.path().join(name), name).unwrap();
+    }
+    let (app, admin, viewer, _db) = roots_setup(media.path(), backups.path()).await;
+
+    let (status, body) = authenticated_delete(
+        app.clone(),
+        "/api/storage/delete?path=gone.txt&permanent=true",
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.contains("trash_id"));
+    assert!(!media.path().join("gone.txt").exists());
+
+    let mut ids = Vec::new();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        let (_, body) = authenticated_delete(
+            app.clone(),
+            &format!("/api/storage/delete?path={}", name),
+            &admin,
+        )
+        .await;
+        let deleted: serde_json::Value = serde_json::from_str(&body).unwrap();
+        ids.push(deleted["trash_id"].as_str().unwrap().to_string());
+    }
+    let trash_count = |app: axum::Router| {
+        let admin = admin.clone();
+        async move {
+            let (_, body) = authenticated_get(app, "/api/storage/trash", &admin).await;
+            serde_json::from_str::<serde_json::Value>(&body)
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len()
+        }
+    };
+    assert_eq!(trash_count(app.clone()).await, 3);
+
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        &format!("/api/storage/trash/{}", ids[0]),
+        &viewer,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = authenticated_delete(
+        app.clone(),
+        &format!("/api/storage/trash/{}", ids[0]),
+        &admin,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(trash_count(app.clone()).await, 2);
+    let (status, _) =
+        authenticated_delete(app.clone(), "/api/storage/trash/not-an-id", &admin).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = authenticated_delete(app.clone(), "/api/storage/trash", &admin).await;
+    assert_eq!(status, StatusCode::OK);
+    let emptied: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(emptied["purged"], 2);
+    assert_eq!(trash_count(app.clone()).await, 0);
+}
+
+#[tokio::test]
+async fn test_trash_purge_task_applies_retention() {
+    use kubarr::services::scheduler::PeriodicTask;
+    use kubarr::services::trash::TrashPurgeTask;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let media = make_temp_dir("trash_retention_media");
+    let backups = make_temp_dir("trash_retention_backups");
+    std::fs::write(media.path().join("old.txt"), "old").unwrap();
+    std::fs::write(media.path().join("new.txt"), "new").unwrap();
+    let (app, admin, _viewer, db) = roots_setup(media.path(), backups.path()).await;
+
+    let mut old_id = String::new();
+    for name in ["old.txt", "new.txt"] {
+        let (_, body) = authenticated_delete(
+            app.clone(),
+            &format!("/api/storage/delete?path={}", name),
+            &admin,
+        )
+        .await;
+        let deleted: serde_json::Value = serde_json::from_str(&body).unwrap();
+        if name == "old.txt" {
+            old_id = deleted["trash_id"].as_str().unwrap().to_string();
+        }
+    }
+    // Backdate the first item past the retention
+    let entry_file = media.path().join(".trash").join(&old_id).join("entry.json");
+    let mut entry: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&entry_file).unwrap()).unwrap();
+    entry["deleted_at"] = serde_json::json!((chrono::Utc::now() - chrono::Duration::days(8)));
+    std::fs::write(&entry_file, entry.to_string()).unwrap();
+
+    kubarr::models::system_setting::ActiveModel {
+        key: Set("storage_trash_retention_days".to_string()),
+        value: Set("7".to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    TrashPurgeTask.run(&db).await.unwrap();
+
+    let (_, body) = authenticated_get(app.clone(), "/api/storage/trash", &admin).await;
+    let items: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(items.as_array().unwrap().len(), 1);
+    assert_eq!(items[0]["name"], "new.txt");
+
+    let request = Request::builder()
+        .uri("/api/settings/storage_trash_retention_days")
+        .method("PUT")
+        .header("Cookie", &admin)
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"value": "-1"}"#))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
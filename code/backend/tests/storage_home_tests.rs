@@ -0,0 +1,305 @@
+//! Integration tests for home folders in storage roots
+//!
+//! Covers:
+//! - `POST /api/storage/homes/scan` — creating and measuring homes, with
+//!   notifications at the soft and hard quota
+//! - `GET  /api/storage/homes` — own homes vs. everyone's
+//! - `PUT  /api/storage/homes/{root}/{user_id}` — per-user quota overrides
+//! - hard quota enforcement on `POST /api/storage/mkdir` and over WebDAV
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use base64::Engine;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+use tower::util::ServiceExt;
+
+mod common;
+use common::fixtures::TestEnv;
+use kubarr::application::dev_seed::DEV_PASSWORD;
+use kubarr::models::prelude::*;
+use kubarr::models::{role, role_permission, system_setting, user_notification};
+
+/// A `files` root with homes (soft quota 100 bytes, hard quota 200 bytes),
+/// WebDAV enabled, and a viewer allowed to write
+async fn setup(dir: &std::path::Path) -> TestEnv {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_notifications()
+        .build()
+        .await;
+    let roots = json!([{
+        "name": "files",
+        "path": dir.to_str().unwrap(),
+        "homes": {"soft_quota": "100", "hard_quota": "200"}
+    }]);
+    for (key, value) in [
+        ("storage_roots", roots.to_string()),
+        ("webdav_enabled", "true".to_string()),
+    ] {
+        system_setting::ActiveModel {
+            key: Set(key.to_string()),
+            value: Set(value),
+            description: Set(None),
+            updated_at: Set(chrono::Utc::now()),
+        }
+        .insert(&env.db)
+        .await
+        .unwrap();
+    }
+    let viewer = Role::find()
+        .filter(role::Column::Name.eq("viewer"))
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    role_permission::ActiveModel {
+        role_id: Set(viewer.id),
+        permission: Set("storage.write".to_string()),
+        ..Default::default()
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+    env
+}
+
+async fn scan(env: &TestEnv) -> serde_json::Value {
+    let (status, homes) = env
+        .request(
+            "POST",
+            "/api/storage/homes/scan",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    homes
+}
+
+async fn notifications(env: &TestEnv, event_type: &str) -> usize {
+    UserNotification::find()
+        .filter(user_notification::Column::UserId.eq(env.user("viewer").user.id))
+        .filter(user_notification::Column::EventType.eq(event_type))
+        .all(&env.db)
+        .await
+        .unwrap()
+        .len()
+}
+
+async fn mkdir(env: &TestEnv, path: &str) -> (StatusCode, serde_json::Value) {
+    env.request(
+        "POST",
+        "/api/storage/mkdir",
+        Some(env.cookie("viewer")),
+        Some(json!({"path": path, "root": "files"})),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_scan_warns_and_enforces_hard_quota() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = setup(dir.path()).await;
+
+    let homes = scan(&env).await;
+    let paths: Vec<&str> = homes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["home/admin", "home/viewer"]);
+    assert!(dir.path().join("home/viewer").is_dir());
+
+    std::fs::write(dir.path().join("home/viewer/a.bin"), [0u8; 150]).unwrap();
+    scan(&env).await;
+    scan(&env).await;
+    assert_eq!(notifications(&env, "storage_quota_warning").await, 1);
+    let (status, _) = mkdir(&env, "home/viewer/photos").await;
+    assert_eq!(status, StatusCode::OK);
+
+    std::fs::write(dir.path().join("home/viewer/b.bin"), [0u8; 100]).unwrap();
+    scan(&env).await;
+    assert_eq!(notifications(&env, "storage_quota_exceeded").await, 1);
+
+    let (status, mine) = env
+        .request(
+            "GET",
+            "/api/storage/homes",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mine.as_array().unwrap().len(), 1);
+    assert_eq!(mine[0]["used_bytes"], 250);
+    assert_eq!(mine[0]["hard_quota_bytes"], 200);
+
+    let (status, body) = mkdir(&env, "home/viewer/music").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(
+        body["detail"]
+            .as_str()
+            .unwrap()
+            .contains("home/viewer is over its hard quota"),
+        "{}",
+        body
+    );
+    // Other folders are not affected
+    let (status, _) = mkdir(&env, "shared").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = mkdir(&env, "home/admin/music").await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The homes folder itself cannot be deleted
+    let (status, _) = env
+        .request(
+            "DELETE",
+            "/api/storage/delete?path=home&root=files",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_quota_override() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = setup(dir.path()).await;
+    let viewer = env.user("viewer").user.id;
+    scan(&env).await;
+    std::fs::write(dir.path().join("home/viewer/a.bin"), [0u8; 250]).unwrap();
+    scan(&env).await;
+
+    let uri = format!("/api/storage/homes/files/{}", viewer);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("viewer")),
+            Some(json!({"hard_quota_bytes": 1000})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"hard_quota_bytes": 50})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, mine) = env
+        .request(
+            "GET",
+            "/api/storage/homes",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(mine[0]["hard_quota_bytes"], 200);
+
+    let (status, home) = env
+        .request(
+            "PUT",
+            &uri,
+            Some(env.cookie("admin")),
+            Some(json!({"hard_quota_bytes": 1000})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(home["overridden"], true);
+    assert_eq!(home["soft_quota_bytes"], 100);
+    assert_eq!(home["hard_quota_bytes"], 1000);
+    let (status, _) = mkdir(&env, "home/viewer/photos").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/storage/homes/missing/1",
+            Some(env.cookie("admin")),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/storage/homes?all=true",
+            Some(env.cookie("viewer")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (_, all) = env
+        .request(
+            "GET",
+            "/api/storage/homes?all=true",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(all.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_webdav_uploads_respect_hard_quota() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = setup(dir.path()).await;
+    scan(&env).await;
+    std::fs::write(dir.path().join("home/viewer/a.bin"), [0u8; 150]).unwrap();
+    scan(&env).await;
+
+    let auth = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!("viewer:{}", DEV_PASSWORD))
+    );
+    let put = |path: &str, size: usize| {
+        Request::builder()
+            .method("PUT")
+            .uri(format!("/dav/files/{}", path))
+            .header(header::AUTHORIZATION, &auth)
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::from(vec![0u8; size]))
+            .unwrap()
+    };
+
+    let response = env
+        .router
+        .clone()
+        .oneshot(put("home/viewer/big.bin", 100))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::INSUFFICIENT_STORAGE);
+    assert!(!dir.path().join("home/viewer/big.bin").exists());
+
+    let response = env
+        .router
+        .clone()
+        .oneshot(put("home/viewer/small.bin", 10))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // Copying a.bin within the home would double its usage
+    let copy = Request::builder()
+        .method("COPY")
+        .uri("/dav/files/home/viewer/a.bin")
+        .header(header::AUTHORIZATION, &auth)
+        .header(
+            "Destination",
+            "http://localhost/dav/files/home/viewer/c.bin",
+        )
+        .body(Body::empty())
+        .unwrap();
+    let response = env.router.clone().oneshot(copy).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INSUFFICIENT_STORAGE);
+}
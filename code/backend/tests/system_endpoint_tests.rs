@@ -0,0 +1,722 @@
+//! System endpoint integration tests
+//!
+//! Covers the endpoints under `/api/system` that look into Kubarr itself:
+//! - Backend logs (recent lines, level filter, SSE follow, download):
+//!   `system.manage` required
+//! - Diagnostics (recorded errors, bundle): `system.manage` required
+//! - Updates (availability, applying an upgrade of Kubarr's own release):
+//!   `system.manage` required
+//! - Scheduled reports (subscription, preview, sending): `system.manage`
+//!   required
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use std::io::Read;
+use tower::util::ServiceExt;
+use tracing::Level;
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+
+use kubarr::config::CONFIG;
+use kubarr::endpoints::create_router;
+use kubarr::models::prelude::KubarrUpdate;
+use kubarr::models::{audit_log, kubarr_update};
+use kubarr::services::helm::{HelmCall, HelmRelease, HelmReleaseInfo};
+use kubarr::services::log_buffer::LOG_BUFFER;
+use kubarr::services::updates;
+use kubarr::state::AppState;
+
+// ============================================================================
+// JWT key initialization
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to initialise test JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Build a state with an admin and a viewer, returning their session cookies
+///
+/// The admin role is granted `system.manage` by migration.
+async fn setup() -> (AppState, String, String) {
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "sysadmin",
+        "sysadmin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "sysviewer",
+        "sysviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let admin = login(&state, "sysadmin").await;
+    let viewer = login(&state, "sysviewer").await;
+    (state, admin, viewer)
+}
+
+async fn login(state: &AppState, username: &str) -> String {
+    let body = serde_json::json!({ "username": username, "password": "password123" });
+    let response = create_router(state.clone())
+        .oneshot(
+            Request::builder()
+                .uri("/auth/login")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let cookie = v.to_str().ok()?.split(';').next()?;
+            cookie
+                .starts_with("kubarr_session=")
+                .then(|| cookie.to_string())
+        })
+        .expect("Login must set a session cookie")
+}
+
+async fn get(state: &AppState, uri: &str, cookie: &str) -> axum::response::Response {
+    create_router(state.clone())
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("Cookie", cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn post(state: &AppState, uri: &str, cookie: &str) -> axum::response::Response {
+    create_router(state.clone())
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Cookie", cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn body_string(response: axum::response::Response) -> String {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+// ============================================================================
+// GET /api/system/logs
+// ============================================================================
+
+#[tokio::test]
+async fn test_system_logs_require_system_manage() {
+    let (state, _, viewer) = setup().await;
+    for uri in ["/api/system/logs", "/api/system/logs/download"] {
+        let response = get(&state, uri, &viewer).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_system_logs_filter_by_level() {
+    let (state, admin, _) = setup().await;
+    let marker = format!("level-filter-{}", uuid::Uuid::new_v4());
+    LOG_BUFFER.push(
+        Utc::now(),
+        &Level::DEBUG,
+        "kubarr::test",
+        format!("{} debug", marker),
+    );
+    LOG_BUFFER.push(
+        Utc::now(),
+        &Level::ERROR,
+        "kubarr::test",
+        format!("{} error", marker),
+    );
+
+    let response = get(&state, "/api/system/logs?level=warn", &admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(&body_string(response).await).unwrap();
+    let ours: Vec<&serde_json::Value> = entries
+        .iter()
+        .filter(|e| e["message"].as_str().unwrap().starts_with(&marker))
+        .collect();
+    assert_eq!(ours.len(), 1);
+    assert_eq!(ours[0]["level"], "error");
+    assert_eq!(ours[0]["target"], "kubarr::test");
+
+    let response = get(&state, "/api/system/logs?level=loud", &admin).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_system_logs_follow_streams_events() {
+    let (state, admin, _) = setup().await;
+    let response = get(&state, "/api/system/logs?follow=true&limit=1", &admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+
+    // Logged after the stream was opened, so it arrives as a live line
+    let marker = format!("follow-{}", uuid::Uuid::new_v4());
+    LOG_BUFFER.push(Utc::now(), &Level::WARN, "kubarr::test", marker.clone());
+
+    let mut body = response.into_body();
+    let mut received = String::new();
+    while !received.contains(&marker) {
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), body.frame())
+            .await
+            .expect("follow stream must send new lines")
+            .unwrap()
+            .unwrap();
+        if let Ok(data) = frame.into_data() {
+            received.push_str(&String::from_utf8_lossy(&data));
+        }
+    }
+    assert!(received.starts_with("event: log"));
+}
+
+#[tokio::test]
+async fn test_system_logs_download() {
+    let (state, admin, _) = setup().await;
+    let marker = format!("download-{}", uuid::Uuid::new_v4());
+    LOG_BUFFER.push(Utc::now(), &Level::INFO, "kubarr::test", marker.clone());
+
+    let response = get(&state, "/api/system/logs/download", &admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let disposition = response.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(disposition.starts_with("attachment; filename=\"kubarr-logs-"));
+    let text = body_string(response).await;
+    assert!(text
+        .lines()
+        .any(|l| l.ends_with(&format!("INFO kubarr::test: {}", marker))));
+}
+
+// ============================================================================
+// /api/system/diagnostics
+// ============================================================================
+
+/// Unpack a gzipped tarball into (name, contents) pairs
+fn untar_gz(bytes: &[u8]) -> Vec<(String, String)> {
+    let mut tar = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut tar)
+        .expect("bundle must be gzip");
+    let mut files = Vec::new();
+    let mut offset = 0;
+    while offset + 512 <= tar.len() && tar[offset] != 0 {
+        let header = &tar[offset..offset + 512];
+        let name_end = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let name = String::from_utf8_lossy(&header[..name_end]).to_string();
+        let size_field = String::from_utf8_lossy(&header[124..135]).to_string();
+        let size = usize::from_str_radix(&size_field, 8).unwrap();
+        let data = &tar[offset + 512..offset + 512 + size];
+        files.push((name, String::from_utf8_lossy(data).to_string()));
+        offset += 512 + size.div_ceil(512) * 512;
+    }
+    files
+}
+
+#[tokio::test]
+async fn test_diagnostics_require_system_manage() {
+    let (state, _, viewer) = setup().await;
+    let response = get(&state, "/api/system/diagnostics/errors", &viewer).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = post(&state, "/api/system/diagnostics/bundle", &viewer).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_diagnostic_errors_report() {
+    let (state, admin, _) = setup().await;
+    let response = get(&state, "/api/system/diagnostics/errors", &admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_str(&body_string(response).await).unwrap();
+    assert!(report["panics"].is_array());
+    assert!(report["server_errors"].is_array());
+    assert!(report["spikes"].is_array());
+}
+
+#[tokio::test]
+async fn test_diagnostic_bundle_contents_are_sanitized() {
+    let (state, admin, _) = setup().await;
+    let marker = format!("bundle-{}", uuid::Uuid::new_v4());
+    LOG_BUFFER.push(
+        Utc::now(),
+        &Level::WARN,
+        "kubarr::test",
+        format!("{} password=hunter2", marker),
+    );
+
+    let response = post(&state, "/api/system/diagnostics/bundle", &admin).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/gzip");
+    assert!(response.headers()[header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .contains("kubarr-diagnostics-"));
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let files = untar_gz(&bytes);
+    let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "kubarr-diagnostics/version.json",
+            "kubarr-diagnostics/config.json",
+            "kubarr-diagnostics/logs.txt",
+            "kubarr-diagnostics/migrations.json",
+            "kubarr-diagnostics/health.json",
+            "kubarr-diagnostics/errors.json",
+        ]
+    );
+
+    let file = |name: &str| &files.iter().find(|(n, _)| n.ends_with(name)).unwrap().1;
+    let logs = file("logs.txt");
+    assert!(logs.contains(&format!("{} password=[redacted]", marker)));
+    assert!(!logs.contains("hunter2"));
+
+    let migrations: serde_json::Value = serde_json::from_str(file("migrations.json")).unwrap();
+    assert_eq!(migrations["pending"], 0);
+    assert!(migrations["total"].as_u64().unwrap() > 0);
+
+    let health: serde_json::Value = serde_json::from_str(file("health.json")).unwrap();
+    assert_eq!(health["database"]["status"], "ok");
+
+    let version: serde_json::Value = serde_json::from_str(file("version.json")).unwrap();
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+}
+
+// ============================================================================
+// /api/system/update
+// ============================================================================
+
+/// An environment where Kubarr runs as Helm release revision 3
+async fn update_env(status: AppStatus) -> TestEnv {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_viewer()
+        .with_app(&CONFIG.updates.namespace, status)
+        .build()
+        .await;
+    env.helm.add_release(
+        &CONFIG.updates.release_name,
+        &CONFIG.updates.namespace,
+        HelmReleaseInfo {
+            revision: 3,
+            chart_version: Some(CONFIG.version.clone()),
+            values: serde_json::json!({ "frontend": { "service": { "type": "NodePort" } } }),
+        },
+    );
+    env
+}
+
+#[tokio::test]
+async fn test_update_requires_system_manage() {
+    let env = update_env(AppStatus::Running).await;
+    let viewer = Some(env.cookie("viewer"));
+    let (status, _) = env.request("GET", "/api/system/update", viewer, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request("POST", "/api/system/update/apply", viewer, None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request("GET", "/api/system/changelog", viewer, None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(env.helm.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_update_status_reports_running_version() {
+    let env = update_env(AppStatus::Running).await;
+    let (status, body) = env
+        .request("GET", "/api/system/update", Some(env.cookie("admin")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["current_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(body["channel"], CONFIG.channel.as_str());
+    assert!(body["last_update"].is_null());
+}
+
+#[tokio::test]
+async fn test_apply_update_upgrades_own_release() {
+    let env = update_env(AppStatus::Running).await;
+    let admin = Some(env.cookie("admin"));
+
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/system/update/apply",
+            admin,
+            Some(serde_json::json!({ "version": "v99.0.0" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "verifying");
+    assert_eq!(body["from_version"], CONFIG.version.as_str());
+    assert_eq!(body["to_version"], "99.0.0");
+    assert_eq!(body["previous_revision"], 3);
+    assert_eq!(
+        env.helm.calls(),
+        vec![HelmCall::UpgradeInstall(HelmRelease {
+            name: CONFIG.updates.release_name.clone(),
+            chart: CONFIG.updates.chart.clone(),
+            namespace: CONFIG.updates.namespace.clone(),
+            version: Some("99.0.0".to_string()),
+            reuse_values: true,
+            ..Default::default()
+        })]
+    );
+
+    let record = KubarrUpdate::find_by_id(body["id"].as_i64().unwrap())
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let backup: serde_json::Value = serde_json::from_str(&record.backup).unwrap();
+    assert_eq!(backup["release"]["revision"], 3);
+    assert_eq!(
+        backup["release"]["values"]["frontend"]["service"]["type"],
+        "NodePort"
+    );
+    assert!(!backup["migrations"].as_array().unwrap().is_empty());
+
+    // Only one update at a time
+    let (status, _) = env
+        .request(
+            "POST",
+            "/api/system/update/apply",
+            admin,
+            Some(serde_json::json!({ "version": "99.0.1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // The release namespace has rolled out
+    let finished = updates::verify(&env.state, &env.db)
+        .await
+        .unwrap()
+        .expect("update must finish");
+    assert_eq!(finished.status, "completed");
+    assert!(finished.finished_at.is_some());
+
+    let (_, body) = env.request("GET", "/api/system/update", admin, None).await;
+    assert_eq!(body["last_update"]["status"], "completed");
+}
+
+#[tokio::test]
+async fn test_apply_update_rejections() {
+    let env = update_env(AppStatus::Running).await;
+    let admin = Some(env.cookie("admin"));
+
+    for version in ["0.0.1", CONFIG.version.as_str(), "latest"] {
+        let (status, _) = env
+            .request(
+                "POST",
+                "/api/system/update/apply",
+                admin,
+                Some(serde_json::json!({ "version": version })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", version);
+    }
+
+    let env = TestEnv::builder().with_admin().build().await;
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/system/update/apply",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({ "version": "99.0.0" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["detail"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("not installed as Helm release"));
+    assert!(env.helm.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_update_rolled_back_when_rollout_fails() {
+    let env = update_env(AppStatus::Failed).await;
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/system/update/apply",
+            Some(env.cookie("admin")),
+            Some(serde_json::json!({ "version": "99.0.0" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Still within the verification window
+    assert!(updates::verify(&env.state, &env.db)
+        .await
+        .unwrap()
+        .is_none());
+
+    let record = KubarrUpdate::find_by_id(body["id"].as_i64().unwrap())
+        .one(&env.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut active: kubarr_update::ActiveModel = record.into();
+    active.started_at =
+        Set(Utc::now() - chrono::Duration::seconds(updates::VERIFY_TIMEOUT_SECS + 1));
+    active.update(&env.db).await.unwrap();
+
+    let finished = updates::verify(&env.state, &env.db)
+        .await
+        .unwrap()
+        .expect("update must finish");
+    assert_eq!(finished.status, "rolled_back");
+    assert!(finished
+        .error
+        .unwrap()
+        .contains("rolled back to revision 3"));
+    assert_eq!(
+        env.helm.calls().last(),
+        Some(&HelmCall::Rollback {
+            name: CONFIG.updates.release_name.clone(),
+            namespace: CONFIG.updates.namespace.clone(),
+            revision: 3,
+        })
+    );
+}
+
+// ============================================================================
+// Scheduled reports
+// ============================================================================
+
+#[tokio::test]
+async fn test_reports_require_system_manage() {
+    let env = TestEnv::builder().with_admin().with_viewer().build().await;
+    let viewer = Some(env.cookie("viewer"));
+    for (method, uri, body) in [
+        ("GET", "/api/system/reports/subscription", None),
+        (
+            "PUT",
+            "/api/system/reports/subscription",
+            Some(serde_json::json!({ "enabled": true })),
+        ),
+        ("GET", "/api/system/reports/preview", None),
+        (
+            "POST",
+            "/api/system/reports/send",
+            Some(serde_json::json!({})),
+        ),
+    ] {
+        let (status, _) = env.request(method, uri, viewer, body).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{} {}", method, uri);
+    }
+}
+
+#[tokio::test]
+async fn test_report_subscription_lifecycle() {
+    let env = TestEnv::builder().with_admin().build().await;
+    let admin = Some(env.cookie("admin"));
+    let uri = "/api/system/reports/subscription";
+
+    let (status, body) = env.request("GET", uri, admin, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], false);
+    assert_eq!(body["frequency"], "weekly");
+    assert!(body["next_send_at"].is_null());
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            uri,
+            admin,
+            Some(serde_json::json!({
+                "frequency": "monthly",
+                "timezone": "Europe/Amsterdam",
+                "send_hour": 7
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], true);
+    assert_eq!(body["frequency"], "monthly");
+    assert_eq!(body["timezone"], "Europe/Amsterdam");
+    assert_eq!(body["send_hour"], 7);
+    assert!(body["next_send_at"].is_string());
+
+    for invalid in [
+        serde_json::json!({ "timezone": "Mars/Base" }),
+        serde_json::json!({ "send_hour": 24 }),
+    ] {
+        let (status, _) = env.request("PUT", uri, admin, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (status, body) = env
+        .request(
+            "PUT",
+            uri,
+            admin,
+            Some(serde_json::json!({ "enabled": false })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["enabled"], false);
+    assert_eq!(body["timezone"], "Europe/Amsterdam");
+}
+
+#[tokio::test]
+async fn test_report_preview_summarizes_period() {
+    let env = TestEnv::builder().with_admin().build().await;
+    for (ip, age_days) in [
+        ("10.0.0.5", 1),
+        ("10.0.0.5", 2),
+        ("10.0.0.9", 3),
+        ("10.0.0.9", 10),
+    ] {
+        audit_log::ActiveModel {
+            timestamp: Set(Utc::now() - chrono::Duration::days(age_days)),
+            username: Set(Some("mallory".to_string())),
+            action: Set("login_failed".to_string()),
+            resource_type: Set("user".to_string()),
+            ip_address: Set(Some(ip.to_string())),
+            success: Set(false),
+            ..Default::default()
+        }
+        .insert(&env.db)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = env
+        .request(
+            "GET",
+            "/api/system/reports/preview?frequency=weekly&timezone=Europe/Amsterdam",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["frequency"], "weekly");
+    assert_eq!(body["timezone"], "Europe/Amsterdam");
+    // The entry from 10 days ago is outside the week
+    assert_eq!(body["failed_logins"], 3);
+    assert_eq!(body["failed_login_sources"][0]["ip_address"], "10.0.0.5");
+    assert_eq!(body["failed_login_sources"][0]["attempts"], 2);
+    assert_eq!(body["top_users"][0]["username"], "mallory");
+    assert_eq!(body["top_users"][0]["actions"], 3);
+    assert!(body["subject"]
+        .as_str()
+        .unwrap()
+        .starts_with("Kubarr weekly report"));
+    let text = body["body"].as_str().unwrap();
+    assert!(text.contains("3 failed attempts"));
+    assert!(text.contains("- mallory: 3 actions"));
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/system/reports/preview?timezone=Mars/Base",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_send_report_reports_delivery_failures() {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("other", "admin")
+        .with_viewer()
+        .build()
+        .await;
+    for user in ["other", "viewer"] {
+        kubarr::models::report_subscription::ActiveModel {
+            user_id: Set(env.user(user).user.id),
+            enabled: Set(true),
+            frequency: Set("weekly".to_string()),
+            timezone: Set("UTC".to_string()),
+            send_hour: Set(8),
+            last_sent_at: Set(None),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+        }
+        .insert(&env.db)
+        .await
+        .unwrap();
+    }
+    let admin = Some(env.cookie("admin"));
+
+    // No email channel is configured
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/system/reports/send",
+            admin,
+            Some(serde_json::json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["sent"], 0);
+    assert_eq!(body["failed"], 1);
+    assert!(body["errors"][0].as_str().unwrap().starts_with("admin:"));
+
+    // The viewer's subscription is skipped: they cannot manage the system
+    let (status, body) = env
+        .request(
+            "POST",
+            "/api/system/reports/send",
+            admin,
+            Some(serde_json::json!({ "frequency": "monthly", "all_subscribers": true })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["failed"], 1);
+    assert!(body["errors"][0].as_str().unwrap().starts_with("other:"));
+}
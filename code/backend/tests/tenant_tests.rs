@@ -0,0 +1,332 @@
+//! Integration tests for tenants
+//!
+//! Covers:
+//! - `POST /api/tenants` and membership management by `tenants.manage`
+//!   holders and tenant admins
+//! - app isolation: the installed list and `/{app_name}/...` routes
+//! - storage roots restricted to tenants
+//! - audit log visibility across tenants
+
+use axum::http::StatusCode;
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::json;
+
+mod common;
+use common::fixtures::{AppStatus, TestEnv};
+use kubarr::models::audit_log::{AuditAction, ResourceType};
+use kubarr::models::system_setting;
+use kubarr::services::installed_apps;
+
+/// Admin, and viewers `alice` in tenant `smiths` and `bob` in `joneses`;
+/// `sonarr` belongs to the smiths and `radarr` is shared. Returns the
+/// tenant IDs.
+async fn setup() -> (TestEnv, i64, i64) {
+    let env = TestEnv::builder()
+        .with_admin()
+        .with_user("alice", "viewer")
+        .with_user("bob", "viewer")
+        .with_app("sonarr", AppStatus::Running)
+        .with_app("radarr", AppStatus::Running)
+        .build()
+        .await;
+
+    let mut ids = Vec::new();
+    for (name, member) in [("smiths", "alice"), ("joneses", "bob")] {
+        let (status, tenant) = env
+            .request(
+                "POST",
+                "/api/tenants",
+                Some(env.cookie("admin")),
+                Some(json!({"name": name})),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = tenant["id"].as_i64().unwrap();
+        let (status, _) = env
+            .request(
+                "PUT",
+                &format!("/api/tenants/{}/members/{}", id, env.user(member).user.id),
+                Some(env.cookie("admin")),
+                Some(json!({})),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        ids.push(id);
+    }
+
+    for app in ["sonarr", "radarr"] {
+        installed_apps::record_install(&env.db, app, None)
+            .await
+            .unwrap();
+    }
+    let (status, app) = env
+        .request(
+            "PUT",
+            "/api/apps/sonarr/tenant",
+            Some(env.cookie("admin")),
+            Some(json!({"tenant_id": ids[0]})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(app["tenant_id"], ids[0]);
+
+    (env, ids[0], ids[1])
+}
+
+async fn installed(env: &TestEnv, username: &str) -> Vec<String> {
+    let (status, apps) = env
+        .request(
+            "GET",
+            "/api/apps/installed",
+            Some(env.cookie(username)),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let mut apps: Vec<String> = serde_json::from_value(apps).unwrap();
+    apps.sort();
+    apps
+}
+
+#[tokio::test]
+async fn test_apps_are_isolated_per_tenant() {
+    let (env, smiths, _) = setup().await;
+
+    assert_eq!(installed(&env, "alice").await, vec!["radarr", "sonarr"]);
+    assert_eq!(installed(&env, "bob").await, vec!["radarr"]);
+    assert_eq!(installed(&env, "admin").await, vec!["radarr", "sonarr"]);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/exists",
+            Some(env.cookie("bob")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/apps/sonarr/exists",
+            Some(env.cookie("alice")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only tenants.manage holders assign apps
+    let (status, _) = env
+        .request(
+            "PUT",
+            "/api/apps/radarr/tenant",
+            Some(env.cookie("alice")),
+            Some(json!({"tenant_id": smiths})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Deleting the tenant shares its apps again
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/tenants/{}", smiths),
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(installed(&env, "bob").await, vec!["radarr", "sonarr"]);
+}
+
+#[tokio::test]
+async fn test_tenant_admins_manage_members() {
+    let (env, smiths, joneses) = setup().await;
+    let alice = env.user("alice").user.id;
+    let bob = env.user("bob").user.id;
+
+    let (_, tenants) = env
+        .request("GET", "/api/tenants", Some(env.cookie("bob")), None)
+        .await;
+    assert_eq!(tenants.as_array().unwrap().len(), 1);
+    assert_eq!(tenants[0]["name"], "joneses");
+    let (status, _) = env
+        .request(
+            "GET",
+            &format!("/api/tenants/{}/members", smiths),
+            Some(env.cookie("bob")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A plain member cannot add people
+    let add_bob = format!("/api/tenants/{}/members/{}", smiths, bob);
+    let (status, _) = env
+        .request("PUT", &add_bob, Some(env.cookie("alice")), Some(json!({})))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, member) = env
+        .request(
+            "PUT",
+            &format!("/api/tenants/{}/members/{}", smiths, alice),
+            Some(env.cookie("admin")),
+            Some(json!({"is_admin": true})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(member["is_admin"], true);
+
+    let (status, _) = env
+        .request("PUT", &add_bob, Some(env.cookie("alice")), Some(json!({})))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(installed(&env, "bob").await, vec!["radarr", "sonarr"]);
+
+    // Only their own tenant
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/tenants/{}/members/{}", joneses, bob),
+            Some(env.cookie("alice")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = env
+        .request(
+            "DELETE",
+            &format!("/api/tenants/{}/members/{}", smiths, alice),
+            Some(env.cookie("alice")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = env
+        .request("DELETE", &add_bob, Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, members) = env
+        .request(
+            "GET",
+            &format!("/api/tenants/{}/members", smiths),
+            Some(env.cookie("alice")),
+            None,
+        )
+        .await;
+    assert_eq!(members.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_tenant_admin_audit_scope() {
+    let (env, smiths, _) = setup().await;
+    let alice = env.user("alice").user.id;
+    let bob = env.user("bob").user.id;
+
+    for (user, name, app) in [(alice, "alice", "sonarr"), (bob, "bob", "radarr")] {
+        env.state
+            .audit
+            .log(
+                AuditAction::AppRestarted,
+                ResourceType::App,
+                Some(app.to_string()),
+                Some(user),
+                Some(name.to_string()),
+                None,
+                None,
+                None,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    // Without audit.view or tenant admin rights the log stays closed
+    let (status, _) = env
+        .request("GET", "/api/audit", Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    env.request(
+        "PUT",
+        &format!("/api/tenants/{}/members/{}", smiths, alice),
+        Some(env.cookie("admin")),
+        Some(json!({"is_admin": true})),
+    )
+    .await;
+    let (status, body) = env
+        .request("GET", "/api/audit", Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    // Their members' entries and entries about the tenant's apps, not bob's.
+    // Alice's sign-in and her own denied request above are among them.
+    let logs = body["logs"].as_array().unwrap();
+    assert!(logs.iter().any(|l| l["user_id"] == alice));
+    assert!(logs.iter().all(|l| l["user_id"] != bob), "{}", body);
+    assert!(
+        logs.iter()
+            .filter(|l| l["action"] != "access_denied" && l["action"] != "login")
+            .all(|l| l["resource_id"] == "sonarr"),
+        "{}",
+        body
+    );
+
+    let (_, body) = env
+        .request(
+            "GET",
+            "/api/audit?action=app_restarted",
+            Some(env.cookie("admin")),
+            None,
+        )
+        .await;
+    assert_eq!(body["total"], 2);
+}
+
+#[tokio::test]
+async fn test_storage_roots_restricted_to_tenants() {
+    let (env, _, _) = setup().await;
+    let dir = tempfile::tempdir().unwrap();
+    let roots = json!([
+        {"name": "shared", "path": dir.path().to_str().unwrap()},
+        {"name": "smiths", "path": dir.path().to_str().unwrap(), "tenants": ["smiths"]}
+    ]);
+    system_setting::ActiveModel {
+        key: Set("storage_roots".to_string()),
+        value: Set(roots.to_string()),
+        description: Set(None),
+        updated_at: Set(chrono::Utc::now()),
+    }
+    .insert(&env.db)
+    .await
+    .unwrap();
+
+    let names = |roots: serde_json::Value| -> Vec<String> {
+        roots
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let (_, roots) = env
+        .request("GET", "/api/storage/roots", Some(env.cookie("alice")), None)
+        .await;
+    assert_eq!(names(roots), vec!["shared", "smiths"]);
+    let (_, roots) = env
+        .request("GET", "/api/storage/roots", Some(env.cookie("bob")), None)
+        .await;
+    assert_eq!(names(roots), vec!["shared"]);
+
+    let (status, _) = env
+        .request(
+            "GET",
+            "/api/storage/browse?root=smiths",
+            Some(env.cookie("bob")),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
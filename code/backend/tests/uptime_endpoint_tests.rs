@@ -0,0 +1,401 @@
+//! Integration tests for the uptime monitor endpoints
+//!
+//! Covers endpoints:
+//! - `GET    /api/monitoring/uptime`            — requires monitoring.view
+//! - `POST   /api/monitoring/uptime`            — requires monitoring.manage
+//! - `GET    /api/monitoring/uptime/{id}`       — requires monitoring.view
+//! - `PUT    /api/monitoring/uptime/{id}`       — requires monitoring.manage
+//! - `DELETE /api/monitoring/uptime/{id}`       — requires monitoring.manage
+//! - `POST   /api/monitoring/uptime/{id}/check` — requires monitoring.manage
+//!
+//! Checks run against local listeners (up) and a closed localhost port (down).
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+use kubarr::endpoints::create_router;
+
+// ============================================================================
+// JWT key initialization (once per test binary)
+// ============================================================================
+
+static JWT_INIT: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+async fn ensure_jwt_keys() {
+    JWT_INIT
+        .get_or_init(|| async {
+            let db = create_test_db_with_seed().await;
+            kubarr::services::init_jwt_keys(&db)
+                .await
+                .expect("Failed to init JWT keys");
+        })
+        .await;
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn do_login(app: axum::Router, username: &str, password: &str) -> Option<String> {
+    let body = serde_json::json!({"username": username, "password": password}).to_string();
+    let request = Request::builder()
+        .uri("/auth/login")
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .find_map(|v| {
+            let s = v.to_str().ok()?;
+            if (s.starts_with("kubarr_session_0=")
+                || (s.starts_with("kubarr_session=") && !s.contains("kubarr_session_")))
+                && !s.contains("Max-Age=0")
+            {
+                Some(s.split(';').next().unwrap().to_string())
+            } else {
+                None
+            }
+        })
+}
+
+async fn make_request(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    cookie: Option<&str>,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("content-type", "application/json");
+    if let Some(c) = cookie {
+        builder = builder.header("Cookie", c);
+    }
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_default();
+    let response = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+struct TestContext {
+    app: axum::Router,
+    admin: String,
+    viewer: String,
+}
+
+/// Create a router with an admin and a viewer (monitoring.view only), with
+/// down/up notifications enabled
+async fn setup() -> TestContext {
+    use kubarr::models::notification_event;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    ensure_jwt_keys().await;
+    let db = create_test_db_with_seed().await;
+    for event_type in ["uptime_monitor_down", "uptime_monitor_up"] {
+        notification_event::ActiveModel {
+            event_type: Set(event_type.to_string()),
+            enabled: Set(true),
+            severity: Set("warning".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+    create_test_user_with_role(&db, "up_admin", "up_admin@example.com", "pass123", "admin").await;
+    create_test_user_with_role(
+        &db,
+        "up_viewer",
+        "up_viewer@example.com",
+        "pass123",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db).await;
+    let app = create_router(state);
+
+    let admin = do_login(app.clone(), "up_admin", "pass123").await.unwrap();
+    let viewer = do_login(app.clone(), "up_viewer", "pass123").await.unwrap();
+    TestContext { app, admin, viewer }
+}
+
+/// Serve a tiny HTTP app on a random local port and return its address
+async fn spawn_http_server() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = axum::Router::new().route("/", axum::routing::get(|| async { "ok" }));
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    addr.to_string()
+}
+
+async fn create_monitor(ctx: &TestContext, body: serde_json::Value) -> i64 {
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "POST",
+        "/api/monitoring/uptime",
+        Some(&ctx.admin),
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create failed: {}", json);
+    assert_eq!(json["status"], "unknown");
+    json["id"].as_i64().unwrap()
+}
+
+// ============================================================================
+// Authentication and permissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_uptime_requires_auth() {
+    let ctx = setup().await;
+    let (status, _) = make_request(ctx.app, "GET", "/api/monitoring/uptime", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_viewer_can_list_but_not_manage() {
+    let ctx = setup().await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/uptime",
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["window_hours"], 24);
+    assert!(json["monitors"].as_array().unwrap().is_empty());
+
+    let (status, _) = make_request(
+        ctx.app,
+        "POST",
+        "/api/monitoring/uptime",
+        Some(&ctx.viewer),
+        Some(serde_json::json!({"name": "NAS", "kind": "tcp", "target": "127.0.0.1:445"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_create_monitor_validation() {
+    let ctx = setup().await;
+
+    for body in [
+        serde_json::json!({"name": "x", "kind": "icmp", "target": "1.1.1.1"}),
+        serde_json::json!({"name": "x", "kind": "tcp", "target": "nas.local"}),
+        serde_json::json!({"name": "x", "kind": "http", "target": "nas.local"}),
+        serde_json::json!({"name": " ", "kind": "tcp", "target": "nas.local:22"}),
+        serde_json::json!({"name": "x", "kind": "tcp", "target": "nas.local:22", "interval_seconds": 5}),
+        serde_json::json!({"name": "x", "kind": "tcp", "target": "nas.local:22", "timeout_seconds": 120}),
+    ] {
+        let (status, _) = make_request(
+            ctx.app.clone(),
+            "POST",
+            "/api/monitoring/uptime",
+            Some(&ctx.admin),
+            Some(body.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "body: {}", body);
+    }
+}
+
+// ============================================================================
+// Checks, statistics and notifications
+// ============================================================================
+
+#[tokio::test]
+async fn test_tcp_monitor_down_notifies_creator() {
+    let ctx = setup().await;
+    let id = create_monitor(
+        &ctx,
+        serde_json::json!({"name": "NAS", "kind": "tcp", "target": "127.0.0.1:1"}),
+    )
+    .await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "POST",
+        &format!("/api/monitoring/uptime/{}/check", id),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["status"], "down");
+    assert_eq!(json["checks"], 1);
+    assert_eq!(json["uptime_percent"], 0.0);
+    assert!(json["last_error"].is_string());
+    assert!(json.get("response_time").is_none());
+
+    let (_, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        "/api/monitoring/uptime",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    assert_eq!(json["down"], 1);
+    assert_eq!(json["up"], 0);
+
+    let (_, inbox) = make_request(
+        ctx.app,
+        "GET",
+        "/api/notifications/inbox",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    let titles: Vec<&str> = inbox["notifications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["title"].as_str())
+        .collect();
+    assert_eq!(titles, vec!["Monitor Down"]);
+}
+
+#[tokio::test]
+async fn test_http_monitor_up_with_percentiles_and_recovery() {
+    let ctx = setup().await;
+    let addr = spawn_http_server().await;
+    let id = create_monitor(
+        &ctx,
+        serde_json::json!({
+            "name": "Homepage",
+            "kind": "http",
+            "target": format!("http://{}/", addr),
+            "expected_status": 204
+        }),
+    )
+    .await;
+    let check_uri = format!("/api/monitoring/uptime/{}/check", id);
+
+    // Server answers 200 but 204 is expected
+    let (_, json) = make_request(ctx.app.clone(), "POST", &check_uri, Some(&ctx.admin), None).await;
+    assert_eq!(json["status"], "down");
+    assert_eq!(json["last_error"], "Unexpected HTTP status 200");
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &format!("/api/monitoring/uptime/{}", id),
+        Some(&ctx.admin),
+        Some(serde_json::json!({"expected_status": 200})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for _ in 0..3 {
+        let (status, json) =
+            make_request(ctx.app.clone(), "POST", &check_uri, Some(&ctx.admin), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["status"], "up");
+    }
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "GET",
+        &format!("/api/monitoring/uptime/{}?hours=1", id),
+        Some(&ctx.viewer),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["checks"], 4);
+    assert_eq!(json["uptime_percent"], 75.0);
+    assert!(json["response_time"]["p95_ms"].as_f64().unwrap() >= 0.0);
+    let history = json["history"].as_array().unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0]["success"], false);
+    assert_eq!(history[3]["status_code"], 200);
+
+    let (_, inbox) = make_request(
+        ctx.app,
+        "GET",
+        "/api/notifications/inbox",
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+    let mut titles: Vec<&str> = inbox["notifications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["title"].as_str())
+        .collect();
+    titles.sort();
+    assert_eq!(titles, vec!["Monitor Down", "Monitor Up"]);
+}
+
+#[tokio::test]
+async fn test_update_target_resets_status_and_delete() {
+    let ctx = setup().await;
+    let id = create_monitor(
+        &ctx,
+        serde_json::json!({"name": "SSH", "kind": "tcp", "target": "127.0.0.1:1"}),
+    )
+    .await;
+    let uri = format!("/api/monitoring/uptime/{}", id);
+
+    make_request(
+        ctx.app.clone(),
+        "POST",
+        &format!("{}/check", uri),
+        Some(&ctx.admin),
+        None,
+    )
+    .await;
+
+    let (status, json) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"target": "127.0.0.1:2", "enabled": false})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["status"], "unknown");
+    assert_eq!(json["enabled"], false);
+
+    let (status, _) = make_request(
+        ctx.app.clone(),
+        "PUT",
+        &uri,
+        Some(&ctx.admin),
+        Some(serde_json::json!({"target": "no-port"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = make_request(ctx.app.clone(), "DELETE", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = make_request(ctx.app, "GET", &uri, Some(&ctx.admin), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
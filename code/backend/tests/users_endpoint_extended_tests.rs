@@ -1355,6 +1355,79 @@ async fn test_change_own_password_too_short() {
     );
 }
 
+#[tokio::test]
+async fn test_change_own_password_revokes_other_sessions() {
+    use kubarr::models::{audit_log, user_notification};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    let user = create_test_user_with_role(
+        &db,
+        "revokeuser",
+        "revokeuser@example.com",
+        "oldpassword",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+
+    let (_, current) = do_login(create_router(state.clone()), "revokeuser", "oldpassword").await;
+    let current = current.expect("Login must set a session cookie");
+    let (_, other) = do_login(create_router(state.clone()), "revokeuser", "oldpassword").await;
+    let other = other.expect("Login must set a session cookie");
+
+    let pw_body = serde_json::json!({
+        "current_password": "oldpassword",
+        "new_password": "newpassword123"
+    })
+    .to_string();
+    let (status, body) = authenticated_patch(
+        create_router(state.clone()),
+        "/api/users/me/password",
+        &current,
+        &pw_body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["sessions_revoked"], 1);
+
+    let (status, _) =
+        authenticated_get(create_router(state.clone()), "/api/users/me", &other).await;
+    assert_eq!(
+        status,
+        StatusCode::UNAUTHORIZED,
+        "Other sessions must be signed out after a password change"
+    );
+    let (status, _) = authenticated_get(create_router(state), "/api/users/me", &current).await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "The session that changed the password must stay valid"
+    );
+
+    let entry = audit_log::Entity::find()
+        .filter(audit_log::Column::Action.eq("password_changed"))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("Password change must be audited");
+    let details: serde_json::Value = serde_json::from_str(&entry.details.unwrap()).unwrap();
+    assert_eq!(details["method"], "self");
+    assert_eq!(details["sessions_revoked"], 1);
+
+    // Security notices are sent even though the event type isn't enabled
+    let notice = user_notification::Entity::find()
+        .filter(user_notification::Column::UserId.eq(user.id))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("User must be notified of the password change");
+    assert_eq!(notice.event_type.as_deref(), Some("password_changed"));
+}
+
 // ============================================================================
 // PATCH /api/users/{id}/password — admin reset user password
 // ============================================================================
@@ -1535,6 +1608,71 @@ async fn test_admin_reset_password_nonexistent_user() {
     );
 }
 
+#[tokio::test]
+async fn test_admin_reset_password_revokes_all_sessions() {
+    use kubarr::models::audit_log;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    ensure_jwt_keys().await;
+
+    let db = create_test_db_with_seed().await;
+    create_test_user_with_role(
+        &db,
+        "revokeadmin",
+        "revokeadmin@example.com",
+        "adminpassword",
+        "admin",
+    )
+    .await;
+    let target = create_test_user_with_role(
+        &db,
+        "revoketarget",
+        "revoketarget@example.com",
+        "oldpassword",
+        "viewer",
+    )
+    .await;
+    let state = build_test_app_state_with_db(db.clone()).await;
+
+    let (_, admin) = do_login(create_router(state.clone()), "revokeadmin", "adminpassword").await;
+    let admin = admin.expect("Login must set a session cookie");
+    let (_, target_cookie) =
+        do_login(create_router(state.clone()), "revoketarget", "oldpassword").await;
+    let target_cookie = target_cookie.expect("Login must set a session cookie");
+
+    let uri = format!("/api/users/{}/password", target.id);
+    let pw_body = serde_json::json!({ "new_password": "newlyset_password" }).to_string();
+    let (status, body) =
+        authenticated_patch(create_router(state.clone()), &uri, &admin, &pw_body).await;
+    assert_eq!(status, StatusCode::OK, "Body: {}", body);
+
+    let (status, _) = authenticated_get(
+        create_router(state.clone()),
+        "/api/users/me",
+        &target_cookie,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = authenticated_get(create_router(state), "/api/users/me", &admin).await;
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "The admin's own session is untouched"
+    );
+
+    let entry = audit_log::Entity::find()
+        .filter(audit_log::Column::Action.eq("password_changed"))
+        .filter(audit_log::Column::ResourceId.eq(target.id.to_string()))
+        .one(&db)
+        .await
+        .unwrap()
+        .expect("Password reset must be audited");
+    assert_eq!(entry.username.as_deref(), Some("revokeadmin"));
+    let details: serde_json::Value = serde_json::from_str(&entry.details.unwrap()).unwrap();
+    assert_eq!(details["method"], "admin_reset");
+    assert_eq!(details["sessions_revoked"], 1);
+}
+
 // ============================================================================
 // GET /api/users/me/2fa/status — additional cases
 // ============================================================================
@@ -12,9 +12,8 @@ use common::create_test_db_with_seed;
 
 use kubarr::models::vpn_provider::VpnType;
 use kubarr::services::vpn::{
-    get_supported_providers, AppVpnConfigResponse, AssignVpnRequest, CreateVpnProviderRequest,
-    OpenVpnCredentials, SupportedProvider, UpdateVpnProviderRequest, VpnCredentials,
-    VpnProviderResponse, VpnTestResult, WireGuardCredentials,
+    get_supported_providers, AssignVpnRequest, CreateVpnProviderRequest, OpenVpnCredentials,
+    UpdateVpnProviderRequest, VpnCredentials, VpnTestResult, WireGuardCredentials,
 };
 
 // ============================================================================
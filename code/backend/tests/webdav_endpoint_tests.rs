@@ -0,0 +1,351 @@
+//! Integration tests for the WebDAV gateway
+//!
+//! Covers:
+//! - `/dav/`          — roots visible to the user (OPTIONS, PROPFIND)
+//! - `/dav/<root>/…`  — WebDAV access to a storage root with Basic auth,
+//!   following the user's storage permissions on that root
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use base64::Engine;
+use http_body_util::BodyExt;
+use sea_orm::{ActiveModelTrait, Set};
+use tower::util::ServiceExt;
+
+mod common;
+use common::{build_test_app_state_with_db, create_test_db_with_seed, create_test_user_with_role};
+
+use kubarr::endpoints::create_router;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn make_temp_dir(label: &str) -> tempfile::TempDir {
+    tempfile::Builder::new()
+        .prefix(&format!("kubarr_webdav_{}_", label))
+        .tempdir()
+        .unwrap()
+}
+
+fn basic(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password))
+    )
+}
+
+/// Send a WebDAV request and return (status, headers, body)
+async fn dav(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    auth: Option<&str>,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> (StatusCode, axum::http::HeaderMap, String) {
+    let mut request = Request::builder().uri(uri).method(method);
+    if let Some(auth) = auth {
+        request = request.header(header::AUTHORIZATION, auth);
+    }
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// A `media` root with a protected `movies` folder and an admin-only,
+/// read-only `backups` root, with WebDAV enabled unless `enabled` is false
+async fn setup(
+    media: &std::path::Path,
+    backups: &std::path::Path,
+    enabled: bool,
+) -> (axum::Router, sea_orm::DatabaseConnection) {
+    let db = create_test_db_with_seed().await;
+    let roots = serde_json::json!([
+        {"name": "media", "path": media.to_str().unwrap(), "protected_folders": ["movies"]},
+        {
+            "name": "backups",
+            "path": backups.to_str().unwrap(),
+            "roles": { "admin": ["storage.view", "storage.download"] }
+        }
+    ]);
+    for (key, value) in [
+        ("storage_roots", roots.to_string()),
+        ("webdav_enabled", enabled.to_string()),
+    ] {
+        kubarr::models::system_setting::ActiveModel {
+            key: Set(key.to_string()),
+            value: Set(value),
+            description: Set(None),
+            updated_at: Set(chrono::Utc::now()),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+    create_test_user_with_role(
+        &db,
+        "davadmin",
+        "davadmin@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    create_test_user_with_role(
+        &db,
+        "davviewer",
+        "davviewer@example.com",
+        "password123",
+        "viewer",
+    )
+    .await;
+    let app = create_router(build_test_app_state_with_db(db.clone()).await);
+    (app, db)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_webdav_disabled_by_default_setting() {
+    let media = make_temp_dir("disabled_media");
+    let backups = make_temp_dir("disabled_backups");
+    let (app, _db) = setup(media.path(), backups.path(), false).await;
+
+    let admin = basic("davadmin", "password123");
+    let (status, _, _) = dav(&app, "PROPFIND", "/dav/media/", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_webdav_requires_basic_auth() {
+    let media = make_temp_dir("auth_media");
+    let backups = make_temp_dir("auth_backups");
+    let (app, db) = setup(media.path(), backups.path(), true).await;
+
+    let (status, headers, _) = dav(&app, "PROPFIND", "/dav/", None, &[], "").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(headers[header::WWW_AUTHENTICATE]
+        .to_str()
+        .unwrap()
+        .starts_with("Basic"));
+
+    let wrong = basic("davadmin", "wrong");
+    let (status, _, _) = dav(&app, "PROPFIND", "/dav/", Some(&wrong), &[], "").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Signing in by email works too
+    let by_email = basic("davadmin@example.com", "password123");
+    let (status, _, _) = dav(&app, "PROPFIND", "/dav/", Some(&by_email), &[], "").await;
+    assert_eq!(status, StatusCode::MULTI_STATUS);
+
+    // Accounts with two-factor authentication are refused
+    let user = create_test_user_with_role(
+        &db,
+        "davtotp",
+        "davtotp@example.com",
+        "password123",
+        "admin",
+    )
+    .await;
+    let mut active: kubarr::models::user::ActiveModel = user.into();
+    active.totp_enabled = Set(true);
+    active.update(&db).await.unwrap();
+    let totp = basic("davtotp", "password123");
+    let (status, _, _) = dav(&app, "PROPFIND", "/dav/", Some(&totp), &[], "").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_webdav_lists_visible_roots() {
+    let media = make_temp_dir("roots_media");
+    let backups = make_temp_dir("roots_backups");
+    let (app, _db) = setup(media.path(), backups.path(), true).await;
+
+    let admin = basic("davadmin", "password123");
+    let viewer = basic("davviewer", "password123");
+    let (status, _, body) = dav(&app, "PROPFIND", "/dav/", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::MULTI_STATUS);
+    assert!(body.contains("<D:href>/dav/media/</D:href>"));
+    assert!(body.contains("<D:href>/dav/backups/</D:href>"));
+
+    let (_, _, body) = dav(&app, "PROPFIND", "/dav/", Some(&viewer), &[], "").await;
+    assert!(body.contains("<D:href>/dav/media/</D:href>"));
+    assert!(!body.contains("backups"));
+    let (status, _, _) = dav(&app, "PROPFIND", "/dav/backups/", Some(&viewer), &[], "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, _, body) = dav(
+        &app,
+        "PROPFIND",
+        "/dav/",
+        Some(&admin),
+        &[("Depth", "0")],
+        "",
+    )
+    .await;
+    assert!(!body.contains("/dav/media/"));
+
+    let (status, headers, _) = dav(&app, "OPTIONS", "/dav/", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["DAV"], "1, 2");
+    let (status, _, _) = dav(&app, "PUT", "/dav/", Some(&admin), &[], "x").await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn test_webdav_browse_and_read() {
+    let media = make_temp_dir("read_media");
+    let backups = make_temp_dir("read_backups");
+    std::fs::create_dir(media.path().join("shows")).unwrap();
+    std::fs::write(media.path().join("shows/episode.mkv"), "episode").unwrap();
+    std::fs::create_dir_all(media.path().join(".trash/hidden")).unwrap();
+    std::fs::write(media.path().join(".trash/hidden/entry.json"), "{}").unwrap();
+    let (app, _db) = setup(media.path(), backups.path(), true).await;
+
+    let viewer = basic("davviewer", "password123");
+    let (status, _, body) = dav(
+        &app,
+        "PROPFIND",
+        "/dav/media/",
+        Some(&viewer),
+        &[("Depth", "1")],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::MULTI_STATUS);
+    assert!(body.contains("/dav/media/shows/"));
+    assert!(!body.contains(".trash"));
+
+    let (status, _, body) = dav(
+        &app,
+        "GET",
+        "/dav/media/shows/episode.mkv",
+        Some(&viewer),
+        &[],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "episode");
+
+    let (status, _, _) = dav(
+        &app,
+        "GET",
+        "/dav/media/.trash/hidden/entry.json",
+        Some(&viewer),
+        &[],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_webdav_write_permissions() {
+    let media = make_temp_dir("write_media");
+    let backups = make_temp_dir("write_backups");
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    let (app, _db) = setup(media.path(), backups.path(), true).await;
+
+    let admin = basic("davadmin", "password123");
+    let viewer = basic("davviewer", "password123");
+
+    let (status, _, _) = dav(&app, "PUT", "/dav/media/new.txt", Some(&viewer), &[], "x").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(!media.path().join("new.txt").exists());
+
+    let (status, _, _) = dav(&app, "PUT", "/dav/media/new.txt", Some(&admin), &[], "new").await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(
+        std::fs::read_to_string(media.path().join("new.txt")).unwrap(),
+        "new"
+    );
+
+    let (status, _, _) = dav(&app, "MKCOL", "/dav/media/shows", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(media.path().join("shows").is_dir());
+    let (status, _, _) = dav(&app, "MKCOL", "/dav/media/.trash", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _, _) = dav(
+        &app,
+        "MOVE",
+        "/dav/media/new.txt",
+        Some(&admin),
+        &[("Destination", "/dav/media/shows/moved.txt")],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(media.path().join("shows/moved.txt").exists());
+
+    // Protected folders cannot be moved away
+    let (status, _, _) = dav(
+        &app,
+        "MOVE",
+        "/dav/media/movies",
+        Some(&admin),
+        &[("Destination", "/dav/media/films")],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(media.path().join("movies").is_dir());
+
+    // The backups root only grants admins view and download
+    let (status, _, _) = dav(&app, "PUT", "/dav/backups/x.txt", Some(&admin), &[], "x").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_webdav_delete_moves_to_trash() {
+    let media = make_temp_dir("delete_media");
+    let backups = make_temp_dir("delete_backups");
+    std::fs::create_dir_all(media.path().join("shows/season 1")).unwrap();
+    std::fs::write(media.path().join("shows/season 1/episode.mkv"), "episode").unwrap();
+    std::fs::create_dir(media.path().join("movies")).unwrap();
+    let (app, _db) = setup(media.path(), backups.path(), true).await;
+
+    let admin = basic("davadmin", "password123");
+    let viewer = basic("davviewer", "password123");
+
+    let (status, _, _) = dav(&app, "DELETE", "/dav/media/shows", Some(&viewer), &[], "").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _, _) = dav(&app, "DELETE", "/dav/media/movies", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(media.path().join("movies").is_dir());
+
+    // Non-empty folders go to the trash whole
+    let (status, _, _) = dav(
+        &app,
+        "DELETE",
+        "/dav/media/shows/season%201",
+        Some(&admin),
+        &[],
+        "",
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(!media.path().join("shows/season 1").exists());
+    let trashed = kubarr::services::trash::list(&media.path().canonicalize().unwrap()).unwrap();
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].original_path, "shows/season 1");
+    assert_eq!(trashed[0].deleted_by.as_deref(), Some("davadmin"));
+
+    let (status, _, _) = dav(&app, "DELETE", "/dav/media/missing", Some(&admin), &[], "").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -56,4 +56,31 @@ describe('apiClient', () => {
     // Should NOT have redirected
     expect(window.location.href).not.toBe('/login');
   });
+
+  it('refreshes the session and retries the request once on 401', async () => {
+    vi.resetModules();
+    window.location.pathname = '/dashboard';
+
+    const { default: apiClient } = await import('../client');
+    const postSpy = vi.spyOn(axios, 'post').mockResolvedValue({ data: {} });
+    apiClient.defaults.adapter = async (config) => ({
+      data: 'retried',
+      status: 200,
+      statusText: 'OK',
+      headers: {},
+      config,
+    });
+
+    const error = {
+      response: { status: 401, data: { detail: 'Access token has expired' } },
+      config: { url: '/users/me', headers: {} },
+      message: 'Request failed with status code 401',
+    };
+
+    const interceptor = (apiClient.interceptors.response as unknown as { handlers: Array<{ rejected: (err: unknown) => unknown }> }).handlers[0];
+    await expect(interceptor.rejected(error)).resolves.toMatchObject({ data: 'retried' });
+
+    expect(postSpy).toHaveBeenCalledWith('/auth/session/refresh', null, { withCredentials: true });
+    postSpy.mockRestore();
+  });
 });
@@ -1,19 +1,120 @@
 import apiClient from './client';
-import type { AppConfig, DeploymentRequest, DeploymentStatus } from '../types';
+import type { AppConfig, CatalogApp, DeploymentRequest, DeploymentStatus } from '../types';
 
 // Export type for convenience
 export type App = AppConfig;
 
 // Named exports for direct usage
-export const getCatalog = async (): Promise<AppConfig[]> => {
-  const response = await apiClient.get<AppConfig[]>('/apps/catalog');
+export const getCatalog = async (): Promise<CatalogApp[]> => {
+  const response = await apiClient.get<CatalogApp[]>('/apps/catalog');
   return response.data;
 };
 
+export interface CatalogEntry {
+  name: string;
+  chart_yaml: string;
+  values_yaml?: string;
+  icon_svg?: string;
+}
+
+export interface CatalogIssue {
+  level: 'warning' | 'error';
+  field: string;
+  message: string;
+}
+
+export interface CatalogValidation {
+  valid: boolean;
+  app: AppConfig | null;
+  issues: CatalogIssue[];
+}
+
+export interface ImportedChart {
+  name: string;
+  version: string | null;
+  issues: CatalogIssue[];
+}
+
+export interface BundleImport {
+  charts: ImportedChart[];
+}
+
+export interface InstalledAppInfo {
+  name: string;
+  notes: string | null;
+  tags: string[];
+  cloned_from: string | null;
+  installed_at: string | null;
+  idle_suspend_exempt: boolean;
+  suspended_at: string | null;
+  boot_priority: number;
+  depends_on: string[];
+  tenant_id: number | null;
+  chart_version: string | null;
+  latest_version: string | null;
+  update_available: boolean;
+}
+
+export interface BootStatus {
+  app_name: string;
+  priority: number;
+  depends_on: string[];
+  held: boolean;
+  held_since: string | null;
+  waiting_for: string[];
+}
+
+export interface BootOrderUpdate {
+  priority: number;
+  depends_on: string[];
+}
+
+export interface AppValues {
+  app_name: string;
+  chart: string;
+  overrides: Record<string, unknown>;
+  effective: Record<string, unknown>;
+  schema: Record<string, unknown> | null;
+}
+
+export interface InstalledAppMetadata {
+  notes?: string | null;
+  tags: string[];
+}
+
+export interface CloneAppRequest {
+  suffix: string;
+  custom_config?: Record<string, string>;
+  allow_incompatible_arch?: boolean;
+}
+
+export interface AppInstallRequest {
+  id: number;
+  app_name: string;
+  custom_config: Record<string, string>;
+  requested_by: number;
+  requested_by_username: string | null;
+  comment: string | null;
+  status: 'pending' | 'approved' | 'denied';
+  reviewed_by: number | null;
+  reviewed_by_username: string | null;
+  review_comment: string | null;
+  created_at: string;
+  reviewed_at: string | null;
+}
+
+export interface CreateAppInstallRequest {
+  app_name: string;
+  custom_config?: Record<string, string>;
+  comment?: string;
+}
+
 export const appsApi = {
-  // Get all apps in catalog
-  getCatalog: async (): Promise<AppConfig[]> => {
-    const response = await apiClient.get<AppConfig[]>('/apps/catalog');
+  // Get all apps in catalog, flagged by node architecture
+  getCatalog: async (compatibleOnly = false): Promise<CatalogApp[]> => {
+    const response = await apiClient.get<CatalogApp[]>('/apps/catalog', {
+      params: compatibleOnly ? { compatible_only: true } : undefined,
+    });
     return response.data;
   },
 
@@ -23,18 +124,101 @@ export const appsApi = {
     return response.data;
   },
 
+  // Validate a catalog entry before publishing it
+  validateCatalogEntry: async (entry: CatalogEntry): Promise<CatalogValidation> => {
+    const response = await apiClient.post<CatalogValidation>('/apps/catalog/validate', entry);
+    return response.data;
+  },
+
+  // Import an offline catalog bundle (.tar.gz of chart directories)
+  importCatalogBundle: async (bundle: Blob): Promise<BundleImport> => {
+    const response = await apiClient.post<BundleImport>('/apps/catalog/import-bundle', bundle, {
+      headers: { 'Content-Type': 'application/gzip' },
+    });
+    return response.data;
+  },
+
   // Get installed apps
   getInstalled: async (): Promise<string[]> => {
     const response = await apiClient.get<string[]>('/apps/installed');
     return response.data;
   },
 
+  // Get installed apps with notes and tags, optionally filtered by tag
+  getInstalledDetails: async (tag?: string): Promise<InstalledAppInfo[]> => {
+    const response = await apiClient.get<InstalledAppInfo[]>('/apps/installed', {
+      params: { details: true, tag },
+    });
+    return response.data;
+  },
+
+  // Update notes and tags of an installed app
+  updateMetadata: async (appName: string, metadata: InstalledAppMetadata): Promise<InstalledAppInfo> => {
+    const response = await apiClient.put<InstalledAppInfo>(`/apps/${appName}/metadata`, metadata);
+    return response.data;
+  },
+
+  // Keep an app running however long it goes unused
+  setIdleSuspendExempt: async (appName: string, exempt: boolean): Promise<InstalledAppInfo> => {
+    const response = await apiClient.put<InstalledAppInfo>(`/apps/${appName}/idle-suspend`, { exempt });
+    return response.data;
+  },
+
+  // Boot priority and dependencies, with what the app is waiting for
+  getBootOrder: async (appName: string): Promise<BootStatus> => {
+    const response = await apiClient.get<BootStatus>(`/apps/${appName}/boot-order`);
+    return response.data;
+  },
+
+  // Set the order an app starts in after a reboot
+  updateBootOrder: async (appName: string, update: BootOrderUpdate): Promise<InstalledAppInfo> => {
+    const response = await apiClient.put<InstalledAppInfo>(`/apps/${appName}/boot-order`, update);
+    return response.data;
+  },
+
+  // Assign an app to a tenant; null shares it with every tenant
+  setTenant: async (appName: string, tenantId: number | null): Promise<InstalledAppInfo> => {
+    const response = await apiClient.put<InstalledAppInfo>(`/apps/${appName}/tenant`, { tenant_id: tenantId });
+    return response.data;
+  },
+
   // Install app
   install: async (request: DeploymentRequest): Promise<DeploymentStatus> => {
     const response = await apiClient.post<DeploymentStatus>('/apps/install', request);
     return response.data;
   },
 
+  // Install another instance of a catalog app, e.g. sonarr-4k
+  clone: async (appName: string, request: CloneAppRequest): Promise<DeploymentStatus> => {
+    const response = await apiClient.post<DeploymentStatus>(`/apps/${appName}/clone`, request);
+    return response.data;
+  },
+
+  // Install requests: own requests, or the whole queue for apps.install holders
+  listRequests: async (status?: AppInstallRequest['status']): Promise<AppInstallRequest[]> => {
+    const response = await apiClient.get<AppInstallRequest[]>('/apps/requests', {
+      params: status ? { status } : undefined,
+    });
+    return response.data;
+  },
+
+  // Ask an admin to install an app
+  requestInstall: async (request: CreateAppInstallRequest): Promise<AppInstallRequest> => {
+    const response = await apiClient.post<AppInstallRequest>('/apps/requests', request);
+    return response.data;
+  },
+
+  // Approve a request, which installs the app
+  approveRequest: async (id: number, comment?: string): Promise<AppInstallRequest> => {
+    const response = await apiClient.post<AppInstallRequest>(`/apps/requests/${id}/approve`, { comment });
+    return response.data;
+  },
+
+  denyRequest: async (id: number, comment?: string): Promise<AppInstallRequest> => {
+    const response = await apiClient.post<AppInstallRequest>(`/apps/requests/${id}/deny`, { comment });
+    return response.data;
+  },
+
   // Delete app
   delete: async (appName: string): Promise<{success: boolean, message: string, status: string}> => {
     const response = await apiClient.delete(`/apps/${appName}`);
@@ -54,7 +238,7 @@ export const appsApi = {
   },
 
   // Get app status
-  getStatus: async (appName: string): Promise<{state: string, message: string}> => {
+  getStatus: async (appName: string): Promise<{state: string, message: string, boot?: BootStatus}> => {
     const response = await apiClient.get(`/apps/${appName}/status`);
     return response.data;
   },
@@ -66,6 +250,47 @@ export const appsApi = {
     });
   },
 
+  // WebSocket URL of a shell in one of the app's pods
+  execUrl: (appName: string, options: { pod?: string; container?: string; command?: string } = {}): string => {
+    const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+    const params = new URLSearchParams();
+    Object.entries(options).forEach(([key, value]) => {
+      if (value) params.set(key, value);
+    });
+    const query = params.toString();
+    return `${protocol}//${window.location.host}/api/apps/${appName}/exec${query ? `?${query}` : ''}`;
+  },
+
+  // Upgrade an installed app to the catalog's chart version
+  upgrade: async (appName: string): Promise<DeploymentStatus> => {
+    const response = await apiClient.post<DeploymentStatus>(`/apps/${appName}/upgrade`);
+    return response.data;
+  },
+
+  // Get the Helm values of an installed app, secrets masked
+  getValues: async (appName: string): Promise<AppValues> => {
+    const response = await apiClient.get<AppValues>(`/apps/${appName}/values`);
+    return response.data;
+  },
+
+  // Replace the Helm value overrides of an installed app and roll them out
+  updateValues: async (appName: string, overrides: Record<string, unknown>): Promise<AppValues> => {
+    const response = await apiClient.put<AppValues>(`/apps/${appName}/values`, { overrides });
+    return response.data;
+  },
+
+  // Stop app (scale to zero)
+  stop: async (appName: string): Promise<{success: boolean, message: string, state: string}> => {
+    const response = await apiClient.post(`/apps/${appName}/stop`);
+    return response.data;
+  },
+
+  // Start a stopped app, restoring its replicas
+  start: async (appName: string): Promise<{success: boolean, message: string, state: string}> => {
+    const response = await apiClient.post(`/apps/${appName}/start`);
+    return response.data;
+  },
+
   // Get categories
   getCategories: async (): Promise<string[]> => {
     const response = await apiClient.get<string[]>('/apps/categories');
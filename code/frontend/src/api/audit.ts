@@ -13,6 +13,9 @@ export interface AuditLog {
   user_agent: string | null;
   success: boolean;
   error_message: string | null;
+  request_id: string | null;
+  prev_hash?: string;
+  integrity_hash?: string;
 }
 
 export interface AuditLogResponse {
@@ -29,10 +32,13 @@ export interface AuditLogQuery {
   user_id?: number;
   action?: string;
   resource_type?: string;
+  // e.g. the permission of access_denied entries
+  resource_id?: string;
   success?: boolean;
   from?: string;
   to?: string;
   search?: string;
+  request_id?: string;
 }
 
 export interface ActionCount {
@@ -50,6 +56,49 @@ export interface AuditStats {
   recent_failures: AuditLog[];
 }
 
+export interface AuditTimeseriesQuery {
+  interval?: 'hour' | 'day';
+  from?: string;
+  to?: string;
+  // Comma-separated: action, success, user
+  group_by?: string;
+  action?: string;
+  success?: boolean;
+  user_id?: number;
+  resource_type?: string;
+}
+
+export interface AuditTimeseriesPoint {
+  bucket: string;
+  action?: string;
+  success?: boolean;
+  user_id?: number;
+  username?: string;
+  count: number;
+}
+
+export interface AuditTimeseries {
+  interval: 'hour' | 'day';
+  from: string;
+  to: string;
+  group_by: string[];
+  points: AuditTimeseriesPoint[];
+}
+
+export interface AuditChainBreak {
+  id: number;
+  timestamp: string;
+  reason: 'content_mismatch' | 'prev_hash_mismatch' | 'missing_hash';
+}
+
+export interface AuditChainVerification {
+  enabled: boolean;
+  checked: number;
+  unchained: number;
+  valid: boolean;
+  first_broken: AuditChainBreak | null;
+}
+
 export interface ClearLogsResponse {
   deleted: number;
   message: string;
@@ -72,6 +121,22 @@ export const auditApi = {
     return response.data;
   },
 
+  getTimeseries: async (query: AuditTimeseriesQuery = {}): Promise<AuditTimeseries> => {
+    const params = new URLSearchParams();
+    Object.entries(query).forEach(([key, value]) => {
+      if (value !== undefined && value !== null && value !== '') {
+        params.append(key, String(value));
+      }
+    });
+    const response = await apiClient.get(`/audit/stats/timeseries?${params.toString()}`);
+    return response.data;
+  },
+
+  verify: async (): Promise<AuditChainVerification> => {
+    const response = await apiClient.get('/audit/verify');
+    return response.data;
+  },
+
   clearOldLogs: async (days: number = 90): Promise<ClearLogsResponse> => {
     const response = await apiClient.post('/audit/clear', { days });
     return response.data;
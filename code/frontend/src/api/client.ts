@@ -9,10 +9,36 @@ const apiClient = axios.create({
   withCredentials: true, // Include cookies for OAuth2-Proxy authentication
 });
 
+// Refresh in flight, shared by requests that fail at the same time
+let refreshing: Promise<unknown> | null = null;
+
+// Renew the session's access token with its refresh token cookie
+function refreshSession() {
+  if (!refreshing) {
+    refreshing = axios
+      .post('/auth/session/refresh', null, { withCredentials: true })
+      .finally(() => {
+        refreshing = null;
+      });
+  }
+  return refreshing;
+}
+
 // Response interceptor
 apiClient.interceptors.response.use(
   (response) => response,
-  (error) => {
+  async (error) => {
+    // An expired access token is renewed once before giving up
+    const config = error.config;
+    if (error.response?.status === 401 && config && !config._retried) {
+      config._retried = true;
+      try {
+        await refreshSession();
+        return apiClient(config);
+      } catch {
+        // Fall through to the login redirect
+      }
+    }
     console.error('API Error:', error.response?.data || error.message);
     // If we get a 401 and we're not already on login page, redirect to login
     if (error.response?.status === 401 && window.location.pathname !== '/login') {
@@ -24,6 +24,50 @@ export interface LokiQueryParams {
   direction?: 'forward' | 'backward'
 }
 
+export interface LogArchive {
+  id: number
+  app_name: string
+  day: string
+  store: 'local' | 's3'
+  object_key: string | null
+  lines: number
+  bytes: number
+  created_at: string
+}
+
+export interface ArchiveQueryParams {
+  app: string
+  start: string
+  end: string
+  filter?: string
+  limit?: number
+}
+
+export interface ArchiveQueryResponse extends LokiQueryResponse {
+  truncated: boolean
+}
+
+export interface ArchiveRunSummary {
+  archived: number
+  purged: number
+  failed: string[]
+}
+
+export interface PodLogLine {
+  timestamp: string | null
+  line: string
+  pod_name: string
+  container: string | null
+  level: 'error' | 'warn' | 'info' | 'debug' | 'trace' | null
+}
+
+export interface PodLogStreamParams {
+  namespace?: string
+  container?: string
+  level?: 'error' | 'warn' | 'info' | 'debug' | 'trace'
+  tail?: number
+}
+
 export const logsApi = {
   /**
    * Get all available labels from VictoriaLogs
@@ -64,4 +108,42 @@ export const logsApi = {
     })
     return response.data
   },
+
+  /**
+   * List archived app log days, newest first
+   */
+  listArchives: async (params: { app?: string; from?: string; to?: string } = {}): Promise<LogArchive[]> => {
+    const response = await apiClient.get<LogArchive[]>('/logs/archive', { params })
+    return response.data
+  },
+
+  /**
+   * Read an app's archived logs for a time range
+   */
+  queryArchive: async (params: ArchiveQueryParams): Promise<ArchiveQueryResponse> => {
+    const response = await apiClient.get<ArchiveQueryResponse>('/logs/archive/query', {
+      params: { ...params, limit: params.limit || 1000 },
+    })
+    return response.data
+  },
+
+  /**
+   * Archive pending days and apply the retention now
+   */
+  runArchive: async (): Promise<ArchiveRunSummary> => {
+    const response = await apiClient.post<ArchiveRunSummary>('/logs/archive/run')
+    return response.data
+  },
+
+  /**
+   * Server-sent events URL following a pod's logs, for an EventSource
+   */
+  podLogStreamUrl: (podName: string, params: PodLogStreamParams = {}): string => {
+    const query = new URLSearchParams()
+    Object.entries(params).forEach(([key, value]) => {
+      if (value !== undefined && value !== '') query.set(key, String(value))
+    })
+    const suffix = query.toString()
+    return `${apiClient.defaults.baseURL}/logs/pods/${podName}/stream${suffix ? `?${suffix}` : ''}`
+  },
 }
@@ -88,6 +88,49 @@ export interface ClusterMetricsHistory {
   container_series: TimeSeriesPoint[];
 }
 
+export interface AuthAnalyticsQuery {
+  interval?: 'hour' | 'day';
+  from?: string;
+  to?: string;
+}
+
+export interface SignInBucket {
+  bucket: string;
+  successful: number;
+  failed: number;
+}
+
+export interface SignInMethodShare {
+  // password, recovery_code or oauth
+  method: string;
+  provider?: string;
+  logins: number;
+  share: number;
+}
+
+export interface FailingAddress {
+  ip_address: string;
+  failures: number;
+  banned: boolean;
+}
+
+export interface AuthAnalytics {
+  interval: 'hour' | 'day';
+  from: string;
+  to: string;
+  successful_logins: number;
+  failed_logins: number;
+  failure_ratio: number;
+  buckets: SignInBucket[];
+  methods: SignInMethodShare[];
+  top_failing_ips: FailingAddress[];
+  two_factor: {
+    active_users: number;
+    enabled: number;
+    ratio: number;
+  };
+}
+
 export const monitoringApi = {
   // Get pod status
   getPodStatus: async (namespace: string = 'media', app?: string): Promise<PodStatus[]> => {
@@ -168,4 +211,12 @@ export const monitoringApi = {
     });
     return response.data;
   },
+
+  // Get sign-in analytics (requires security.manage)
+  getAuthAnalytics: async (query: AuthAnalyticsQuery = {}): Promise<AuthAnalytics> => {
+    const response = await apiClient.get<AuthAnalytics>('/monitoring/auth', {
+      params: query,
+    });
+    return response.data;
+  },
 };
@@ -24,10 +24,17 @@ export interface UnreadCountResponse {
   count: number;
 }
 
+export interface ChannelFilter {
+  min_severity: string | null;
+  include_event_types: string[];
+  exclude_event_types: string[];
+}
+
 export interface NotificationChannel {
   channel_type: string;
   enabled: boolean;
   config: Record<string, unknown>;
+  filter: ChannelFilter;
   created_at: string;
   updated_at: string;
 }
@@ -38,11 +45,68 @@ export interface NotificationEvent {
   severity: string;
 }
 
+export interface EventCatalogEntry {
+  event_type: string;
+  title: string;
+  // Part of the default catalog
+  default: boolean;
+  // Has a saved configuration
+  configured: boolean;
+  enabled: boolean;
+  severity: string;
+  default_severity: string;
+}
+
+export interface EventConfigUpdate {
+  event_type: string;
+  enabled?: boolean;
+  severity?: string;
+}
+
+export interface TemplateVariable {
+  name: string;
+  description: string;
+  example: string;
+}
+
+export interface ChannelTemplate {
+  channel_type: string;
+  subject: string | null;
+  body: string | null;
+  updated_at: string | null;
+}
+
+export interface EventTemplates {
+  event_type: string;
+  templates: ChannelTemplate[];
+  variables: TemplateVariable[];
+}
+
+export interface SetTemplateRequest {
+  channel_type: string;
+  subject?: string | null;
+  body?: string | null;
+}
+
+export interface PreviewTemplateRequest {
+  channel_type?: string;
+  subject?: string;
+  body?: string;
+  sample?: Record<string, string>;
+}
+
+export interface RenderedTemplate {
+  subject: string;
+  body: string;
+}
+
 export interface UserNotificationPref {
   channel_type: string;
   enabled: boolean;
   destination: string | null;
   verified: boolean;
+  verification_pending: boolean;
+  filter: ChannelFilter;
 }
 
 export interface NotificationLog {
@@ -53,6 +117,7 @@ export interface NotificationLog {
   recipient: string | null;
   status: string;
   error_message: string | null;
+  fallback_of: number | null;
   created_at: string;
 }
 
@@ -66,6 +131,17 @@ export interface TestChannelResponse {
   error: string | null;
 }
 
+export interface ProviderStatus {
+  channel_type: string;
+  enabled: boolean;
+  initialized: boolean;
+  last_error: string | null;
+  last_error_at: string | null;
+  last_reload_at: string | null;
+  fallback_deliveries: number;
+  last_fallback_at: string | null;
+}
+
 // ============================================================================
 // User Inbox API
 // ============================================================================
@@ -117,7 +193,11 @@ export const notificationsApi = {
   // Update channel configuration (admin)
   updateChannel: async (
     channelType: string,
-    data: { enabled?: boolean; config?: Record<string, unknown> }
+    data: {
+      enabled?: boolean;
+      config?: Record<string, unknown>;
+      filter?: Partial<ChannelFilter>;
+    }
   ): Promise<NotificationChannel> => {
     const response = await apiClient.put(`/notifications/channels/${channelType}`, data);
     return response.data;
@@ -131,6 +211,18 @@ export const notificationsApi = {
     return response.data;
   },
 
+  // Get provider initialization status of every channel (admin)
+  getChannelStatus: async (): Promise<ProviderStatus[]> => {
+    const response = await apiClient.get('/notifications/channels/status');
+    return response.data;
+  },
+
+  // Reload providers from the saved channel configuration (admin)
+  reloadChannels: async (): Promise<ProviderStatus[]> => {
+    const response = await apiClient.post('/notifications/channels/reload');
+    return response.data;
+  },
+
   // ============================================================================
   // Admin: Event Settings
   // ============================================================================
@@ -150,6 +242,42 @@ export const notificationsApi = {
     return response.data;
   },
 
+  // Get every known event type with its configuration (admin)
+  getEventCatalog: async (): Promise<EventCatalogEntry[]> => {
+    const response = await apiClient.get('/notifications/events/catalog');
+    return response.data;
+  },
+
+  // Create or update several event settings at once (admin)
+  updateEvents: async (events: EventConfigUpdate[]): Promise<NotificationEvent[]> => {
+    const response = await apiClient.put('/notifications/events', { events });
+    return response.data;
+  },
+
+  // Reset event settings to the defaults, all disabled (admin)
+  resetEvents: async (): Promise<EventCatalogEntry[]> => {
+    const response = await apiClient.post('/notifications/events/reset');
+    return response.data;
+  },
+
+  // Get the templates of an event on every channel, with their variables (admin)
+  getEventTemplate: async (eventType: string): Promise<EventTemplates> => {
+    const response = await apiClient.get(`/notifications/events/${eventType}/template`);
+    return response.data;
+  },
+
+  // Override an event's subject and body on one channel; empty clears it (admin)
+  updateEventTemplate: async (eventType: string, data: SetTemplateRequest): Promise<EventTemplates> => {
+    const response = await apiClient.put(`/notifications/events/${eventType}/template`, data);
+    return response.data;
+  },
+
+  // Render an event's template with sample data (admin)
+  previewEventTemplate: async (eventType: string, data: PreviewTemplateRequest): Promise<RenderedTemplate> => {
+    const response = await apiClient.post(`/notifications/events/${eventType}/template/preview`, data);
+    return response.data;
+  },
+
   // ============================================================================
   // User Preferences
   // ============================================================================
@@ -163,12 +291,32 @@ export const notificationsApi = {
   // Update user's notification preference for a channel
   updatePreference: async (
     channelType: string,
-    data: { enabled?: boolean; destination?: string }
+    data: { enabled?: boolean; destination?: string; filter?: Partial<ChannelFilter> }
   ): Promise<UserNotificationPref> => {
     const response = await apiClient.put(`/notifications/preferences/${channelType}`, data);
     return response.data;
   },
 
+  // Send a test message to the current user's own destination (rate limited)
+  testPreference: async (channelType: string): Promise<TestChannelResponse> => {
+    const response = await apiClient.post(`/notifications/preferences/${channelType}/test`);
+    return response.data;
+  },
+
+  // Send a one-time code to the current user's destination
+  sendVerificationCode: async (channelType: string): Promise<{ expires_in_seconds: number }> => {
+    const response = await apiClient.post(`/notifications/preferences/${channelType}/send-code`);
+    return response.data;
+  },
+
+  // Verify the current user's destination with the code sent to it
+  verifyCode: async (channelType: string, code: string): Promise<UserNotificationPref> => {
+    const response = await apiClient.post(`/notifications/preferences/${channelType}/verify-code`, {
+      code,
+    });
+    return response.data;
+  },
+
   // ============================================================================
   // Admin: Logs
   // ============================================================================
@@ -1,4 +1,5 @@
 import apiClient from './client';
+import type { QuotaLimits, ScheduleWindow } from './users';
 
 export interface Role {
   id: number;
@@ -21,6 +22,8 @@ export interface RoleInfo {
   id: number;
   name: string;
   description: string | null;
+  // When the assignment ends; null keeps it until it is removed
+  expires_at?: string | null;
 }
 
 export interface CreateRoleRequest {
@@ -44,6 +47,92 @@ export interface SetRolePermissionsRequest {
   permissions: string[];
 }
 
+// Give either user_id or role_id
+export interface EvaluatePermissionRequest {
+  user_id?: number;
+  role_id?: number;
+  // Permission the action needs, e.g. 'apps.restart'
+  action: string;
+  app_name?: string;
+}
+
+export interface RoleGrant {
+  role_id: number;
+  role_name: string;
+  permission: string;
+}
+
+export interface EvaluationStep {
+  check: 'account' | 'permission' | 'app_access' | 'tenant';
+  passed: boolean;
+  detail: string;
+  granted_by: RoleGrant[];
+}
+
+export interface PermissionEvaluation {
+  allowed: boolean;
+  roles: string[];
+  steps: EvaluationStep[];
+}
+
+export type ReviewDecision = 'pending' | 'confirmed' | 'revoked' | 'overdue';
+
+export interface AccessReviewItem {
+  user_id: number;
+  username: string;
+  roles: string[];
+  // ['*'] for all apps
+  apps: string[];
+  decision: ReviewDecision;
+  suspended: boolean;
+  decided_by: string | null;
+  decided_at: string | null;
+  comment: string | null;
+}
+
+export interface AccessReview {
+  id: number;
+  status: 'open' | 'closed';
+  created_by: string | null;
+  created_at: string;
+  due_at: string;
+  closed_at: string | null;
+  total: number;
+  pending: number;
+  overdue: number;
+}
+
+export interface AccessReviewDetail extends AccessReview {
+  items: AccessReviewItem[];
+}
+
+export interface DecideAccessRequest {
+  user_id: number;
+  decision: 'confirm' | 'revoke';
+  comment?: string;
+}
+
+export interface MediaProfile {
+  // Library names members may see; empty is every library
+  libraries: string[];
+  max_streams?: number | null;
+  max_parental_rating?: number | null;
+}
+
+// Apps members get an account in when they are approved
+export interface ProvisioningProfile {
+  apps: string[];
+  // What happens to members' accounts when they are deactivated or deleted
+  on_deactivate?: 'keep' | 'disable' | 'delete';
+}
+
+export interface MediaProfileSyncResult {
+  user_id: number;
+  username: string;
+  status: 'updated' | 'unchanged' | 'no_profile' | 'no_account' | 'failed';
+  error?: string;
+}
+
 /**
  * Get all roles
  */
@@ -100,6 +189,46 @@ export const getPermissions = async (): Promise<PermissionInfo[]> => {
   return response.data;
 };
 
+/**
+ * Check whether a user or role would be allowed to perform an action, and why
+ */
+export const evaluatePermission = async (data: EvaluatePermissionRequest): Promise<PermissionEvaluation> => {
+  const response = await apiClient.post<PermissionEvaluation>('/roles/evaluate', data);
+  return response.data;
+};
+
+/**
+ * List access reviews, newest first
+ */
+export const getAccessReviews = async (): Promise<AccessReview[]> => {
+  const response = await apiClient.get<AccessReview[]>('/roles/reviews');
+  return response.data;
+};
+
+/**
+ * Start an access review of every active user
+ */
+export const startAccessReview = async (): Promise<AccessReviewDetail> => {
+  const response = await apiClient.post<AccessReviewDetail>('/roles/reviews');
+  return response.data;
+};
+
+/**
+ * Get an access review with every user in it
+ */
+export const getAccessReview = async (reviewId: number): Promise<AccessReviewDetail> => {
+  const response = await apiClient.get<AccessReviewDetail>(`/roles/reviews/${reviewId}`);
+  return response.data;
+};
+
+/**
+ * Confirm or revoke a user's access in a review
+ */
+export const decideAccess = async (reviewId: number, data: DecideAccessRequest): Promise<AccessReviewDetail> => {
+  const response = await apiClient.post<AccessReviewDetail>(`/roles/reviews/${reviewId}`, data);
+  return response.data;
+};
+
 /**
  * Get permissions for a specific role
  */
@@ -115,3 +244,99 @@ export const setRolePermissions = async (roleId: number, data: SetRolePermission
   const response = await apiClient.put<Role>(`/roles/${roleId}/permissions`, data);
   return response.data;
 };
+
+/**
+ * Get the quota applied to each member of a role
+ */
+export const getRoleQuota = async (roleId: number): Promise<QuotaLimits> => {
+  const response = await apiClient.get<QuotaLimits>(`/roles/${roleId}/quota`);
+  return response.data;
+};
+
+/**
+ * Set the quota applied to each member of a role
+ */
+export const setRoleQuota = async (roleId: number, limits: QuotaLimits): Promise<QuotaLimits> => {
+  const response = await apiClient.put<QuotaLimits>(`/roles/${roleId}/quota`, limits);
+  return response.data;
+};
+
+/**
+ * Remove a role's quota
+ */
+export const deleteRoleQuota = async (roleId: number): Promise<void> => {
+  await apiClient.delete(`/roles/${roleId}/quota`);
+};
+
+/**
+ * Get the app access schedules of a role
+ */
+export const getRoleSchedules = async (roleId: number): Promise<ScheduleWindow[]> => {
+  const response = await apiClient.get<ScheduleWindow[]>(`/roles/${roleId}/schedules`);
+  return response.data;
+};
+
+/**
+ * Replace the app access schedules of a role; an empty list removes them
+ */
+export const setRoleSchedules = async (roleId: number, windows: ScheduleWindow[]): Promise<ScheduleWindow[]> => {
+  const response = await apiClient.put<ScheduleWindow[]>(`/roles/${roleId}/schedules`, windows);
+  return response.data;
+};
+
+/**
+ * Get the media profile of a role
+ */
+export const getMediaProfile = async (roleId: number): Promise<MediaProfile> => {
+  const response = await apiClient.get<MediaProfile>(`/roles/${roleId}/media-profile`);
+  return response.data;
+};
+
+/**
+ * Set the media profile of a role; members are synced to Jellyfin in the background
+ */
+export const setMediaProfile = async (roleId: number, profile: MediaProfile): Promise<MediaProfile> => {
+  const response = await apiClient.put<MediaProfile>(`/roles/${roleId}/media-profile`, profile);
+  return response.data;
+};
+
+/**
+ * Remove the media profile of a role
+ */
+export const deleteMediaProfile = async (roleId: number): Promise<void> => {
+  await apiClient.delete(`/roles/${roleId}/media-profile`);
+};
+
+/**
+ * Sync the media profiles of a role's members to Jellyfin now
+ */
+export const syncMediaProfile = async (roleId: number): Promise<MediaProfileSyncResult[]> => {
+  const response = await apiClient.post<MediaProfileSyncResult[]>(`/roles/${roleId}/media-profile/sync`);
+  return response.data;
+};
+
+/**
+ * Get the provisioning profile of a role
+ */
+export const getProvisioningProfile = async (roleId: number): Promise<ProvisioningProfile> => {
+  const response = await apiClient.get<ProvisioningProfile>(`/roles/${roleId}/provisioning`);
+  return response.data;
+};
+
+/**
+ * Set the apps members of a role get an account in on approval
+ */
+export const setProvisioningProfile = async (
+  roleId: number,
+  profile: ProvisioningProfile
+): Promise<ProvisioningProfile> => {
+  const response = await apiClient.put<ProvisioningProfile>(`/roles/${roleId}/provisioning`, profile);
+  return response.data;
+};
+
+/**
+ * Remove the provisioning profile of a role
+ */
+export const deleteProvisioningProfile = async (roleId: number): Promise<void> => {
+  await apiClient.delete(`/roles/${roleId}/provisioning`);
+};
@@ -35,6 +35,34 @@ export interface ActiveSession {
   last_activity: string;
 }
 
+export interface IpBan {
+  id: number;
+  ip_address: string;
+  source: 'automatic' | 'manual';
+  reason: string | null;
+  failed_attempts: number | null;
+  created_by_id: number | null;
+  created_at: string;
+  expires_at: string | null;
+}
+
+export interface CreateIpBanRequest {
+  ip_address: string;
+  reason?: string;
+  /** Omit to ban until removed */
+  duration_minutes?: number;
+}
+
+export interface FailedLoginSource {
+  ip_address: string;
+  attempts: number;
+  usernames: string[];
+  last_attempt_at: string;
+  banned: boolean;
+}
+
+export type IpBanExportFormat = 'plain' | 'nginx' | 'crowdsec';
+
 export const securityApi = {
   /**
    * Get security overview with stats and recent events
@@ -104,4 +132,48 @@ export const securityApi = {
     });
     return response.logs;
   },
+
+  /**
+   * Get active IP bans
+   */
+  getIpBans: async (): Promise<IpBan[]> => {
+    const response = await apiClient.get<IpBan[]>('/security/bans');
+    return response.data;
+  },
+
+  /**
+   * Ban an IP address
+   */
+  createIpBan: async (data: CreateIpBanRequest): Promise<IpBan> => {
+    const response = await apiClient.post<IpBan>('/security/bans', data);
+    return response.data;
+  },
+
+  /**
+   * Lift an IP ban
+   */
+  deleteIpBan: async (banId: number): Promise<void> => {
+    await apiClient.delete(`/security/bans/${banId}`);
+  },
+
+  /**
+   * Export the ban list for ingress-level blocking
+   */
+  exportIpBans: async (format: IpBanExportFormat = 'plain'): Promise<string> => {
+    const response = await apiClient.get<string>('/security/bans/export', {
+      params: { format },
+      responseType: 'text',
+    });
+    return response.data;
+  },
+
+  /**
+   * Get failed sign-ins per IP address
+   */
+  getFailedLogins: async (hours: number = 24): Promise<FailedLoginSource[]> => {
+    const response = await apiClient.get<FailedLoginSource[]>('/security/failed-logins', {
+      params: { hours },
+    });
+    return response.data;
+  },
 };
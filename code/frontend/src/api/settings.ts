@@ -24,3 +24,18 @@ export const updateSetting = async (key: string, value: string): Promise<Setting
   const response = await apiClient.put<Setting>(`/settings/${key}`, { value });
   return response.data;
 };
+
+export interface SettingSchema {
+  key: string;
+  description: string;
+  default: string | null;
+  // 'live' settings apply immediately; 'restart' keys are environment variables
+  reload: 'live' | 'restart';
+  editable: boolean;
+  env_fallback: string | null;
+}
+
+export const getSettingsSchema = async (): Promise<SettingSchema[]> => {
+  const response = await apiClient.get<SettingSchema[]>('/settings/schema');
+  return response.data;
+};
@@ -4,18 +4,40 @@ export interface FileInfo {
   name: string;
   path: string;
   type: 'file' | 'directory';
-  size: number;
-  modified: string;
-  permissions: string;
+  // Omitted in light listings
+  size?: number;
+  modified?: string;
+  permissions?: string;
 }
 
 export interface DirectoryListing {
+  root: string;
   path: string;
   parent: string | null;
-  items: FileInfo[];
   total_items: number;
+  offset: number;
+  next_offset: number | null;
+  items: FileInfo[];
 }
 
+export interface BrowseOptions {
+  root?: string;
+  offset?: number;
+  limit?: number;
+  sort?: 'name' | 'size' | 'modified';
+  order?: 'asc' | 'desc';
+  // Skip size, modification time and permissions of each entry
+  light?: boolean;
+}
+
+export interface StorageRoot {
+  name: string;
+  protected_folders: string[];
+  permissions: string[];
+}
+
+export type PreviewSize = 'small' | 'medium' | 'large';
+
 export interface StorageStats {
   total_bytes: number;
   used_bytes: number;
@@ -23,49 +45,253 @@ export interface StorageStats {
   usage_percent: number;
 }
 
+export type ChecksumAlgorithm = 'sha256' | 'blake3';
+
+export interface ChecksumResult {
+  id: number;
+  root: string;
+  path: string;
+  algorithm: ChecksumAlgorithm;
+  status: 'pending' | 'running' | 'completed' | 'failed';
+  checksum: string | null;
+  size: number;
+  file_modified_at: string;
+  error: string | null;
+  created_at: string;
+  completed_at: string | null;
+  // The file changed since the checksum was requested
+  stale: boolean;
+  // Present when an expected checksum was given and the result is known
+  matches?: boolean;
+}
+
+export interface TrashEntry {
+  id: string;
+  name: string;
+  original_path: string;
+  type: 'file' | 'directory';
+  size: number;
+  deleted_at: string;
+  deleted_by: string | null;
+}
+
+export interface ShareUser {
+  username: string;
+  access: 'read' | 'write';
+  has_password: boolean;
+}
+
+export interface ShareInfo {
+  name: string;
+  read_only: boolean;
+  available: boolean;
+  access: 'read' | 'write' | null;
+  users?: ShareUser[];
+}
+
+export interface SharesStatus {
+  status: 'not_deployed' | 'deploying' | 'running' | 'unavailable';
+  ready_pods: number;
+  total_pods: number;
+  in_sync: boolean;
+  error?: string;
+  has_password: boolean;
+  shares: ShareInfo[];
+}
+
+export interface HomeInfo {
+  root: string;
+  user_id: number;
+  username: string;
+  // Path of the home relative to its root, e.g. "home/alice"
+  path: string;
+  // Size at the last scan
+  used_bytes: number;
+  soft_quota_bytes: number | null;
+  hard_quota_bytes: number | null;
+  // Whether the quotas are overridden for this user
+  overridden: boolean;
+  scanned_at: string | null;
+}
+
+export interface HomeQuotaOverride {
+  soft_quota_bytes?: number | null;
+  hard_quota_bytes?: number | null;
+}
+
 export const storageApi = {
-  // Browse a directory
-  browse: async (path: string = ''): Promise<DirectoryListing> => {
+  // List the storage roots the current user can see
+  getRoots: async (): Promise<StorageRoot[]> => {
+    const response = await apiClient.get<StorageRoot[]>('/storage/roots');
+    return response.data;
+  },
+
+  // Browse a directory, optionally one page at a time
+  browse: async (path: string = '', options: BrowseOptions = {}): Promise<DirectoryListing> => {
     const response = await apiClient.get<DirectoryListing>('/storage/browse', {
-      params: { path },
+      params: { path, ...options },
     });
     return response.data;
   },
 
   // Get storage statistics
-  getStats: async (): Promise<StorageStats> => {
-    const response = await apiClient.get<StorageStats>('/storage/stats');
+  getStats: async (root?: string): Promise<StorageStats> => {
+    const response = await apiClient.get<StorageStats>('/storage/stats', {
+      params: { root },
+    });
     return response.data;
   },
 
   // Get file or directory info
-  getFileInfo: async (path: string): Promise<FileInfo> => {
+  getFileInfo: async (path: string, root?: string): Promise<FileInfo> => {
     const response = await apiClient.get<FileInfo>('/storage/file-info', {
-      params: { path },
+      params: { path, root },
     });
     return response.data;
   },
 
   // Create a new directory
-  createDirectory: async (path: string): Promise<{ success: boolean; message: string }> => {
+  createDirectory: async (path: string, root?: string): Promise<{ success: boolean; message: string }> => {
     const response = await apiClient.post<{ success: boolean; message: string }>('/storage/mkdir', {
       path,
+      root,
     });
     return response.data;
   },
 
   // Delete a file or empty directory
-  deletePath: async (path: string): Promise<{ success: boolean; message: string }> => {
-    const response = await apiClient.delete<{ success: boolean; message: string }>('/storage/delete', {
-      params: { path },
+  // Move to the trash, or delete right away when permanent
+  deletePath: async (
+    path: string,
+    root?: string,
+    permanent?: boolean
+  ): Promise<{ success: boolean; message: string; trash_id?: string }> => {
+    const response = await apiClient.delete<{ success: boolean; message: string; trash_id?: string }>(
+      '/storage/delete',
+      { params: { path, root, permanent } }
+    );
+    return response.data;
+  },
+
+  // List items in the trash, newest first
+  listTrash: async (root?: string): Promise<TrashEntry[]> => {
+    const response = await apiClient.get<TrashEntry[]>('/storage/trash', { params: { root } });
+    return response.data;
+  },
+
+  restoreTrashItem: async (id: string, root?: string): Promise<{ success: boolean; message: string }> => {
+    const response = await apiClient.post<{ success: boolean; message: string }>(
+      `/storage/trash/${id}/restore`,
+      null,
+      { params: { root } }
+    );
+    return response.data;
+  },
+
+  purgeTrashItem: async (id: string, root?: string): Promise<{ success: boolean; message: string }> => {
+    const response = await apiClient.delete<{ success: boolean; message: string }>(`/storage/trash/${id}`, {
+      params: { root },
+    });
+    return response.data;
+  },
+
+  emptyTrash: async (root?: string): Promise<{ success: boolean; message: string; purged: number }> => {
+    const response = await apiClient.delete<{ success: boolean; message: string; purged: number }>(
+      '/storage/trash',
+      { params: { root } }
+    );
+    return response.data;
+  },
+
+  // SMB shares of storage roots and the caller's access to them
+  getShares: async (): Promise<SharesStatus> => {
+    const response = await apiClient.get<SharesStatus>('/storage/shares');
+    return response.data;
+  },
+
+  createShare: async (root: string, readOnly: boolean = false): Promise<SharesStatus> => {
+    const response = await apiClient.post<SharesStatus>('/storage/shares', {
+      root,
+      read_only: readOnly,
+    });
+    return response.data;
+  },
+
+  deleteShare: async (root: string): Promise<SharesStatus> => {
+    const response = await apiClient.delete<SharesStatus>(
+      `/storage/shares/${encodeURIComponent(root)}`
+    );
+    return response.data;
+  },
+
+  // Set or remove the password used to sign in to shares
+  setSharePassword: async (password: string): Promise<SharesStatus> => {
+    const response = await apiClient.put<SharesStatus>('/storage/shares/password', { password });
+    return response.data;
+  },
+
+  clearSharePassword: async (): Promise<SharesStatus> => {
+    const response = await apiClient.delete<SharesStatus>('/storage/shares/password');
+    return response.data;
+  },
+
+  syncShares: async (): Promise<SharesStatus> => {
+    const response = await apiClient.post<SharesStatus>('/storage/shares/sync');
+    return response.data;
+  },
+
+  // The caller's home folders, or everyone's with `all` (requires users.manage)
+  getHomes: async (all: boolean = false): Promise<HomeInfo[]> => {
+    const response = await apiClient.get<HomeInfo[]>('/storage/homes', { params: { all } });
+    return response.data;
+  },
+
+  scanHomes: async (): Promise<HomeInfo[]> => {
+    const response = await apiClient.post<HomeInfo[]>('/storage/homes/scan');
+    return response.data;
+  },
+
+  setHomeQuota: async (root: string, userId: number, quota: HomeQuotaOverride): Promise<HomeInfo> => {
+    const response = await apiClient.put<HomeInfo>(`/storage/homes/${encodeURIComponent(root)}/${userId}`, quota);
+    return response.data;
+  },
+
+  // Compute a file checksum; large files are hashed in the background
+  requestChecksum: async (
+    path: string,
+    algorithm: ChecksumAlgorithm = 'sha256',
+    expected?: string,
+    root?: string
+  ): Promise<ChecksumResult> => {
+    const response = await apiClient.post<ChecksumResult>('/storage/checksum', {
+      path,
+      algorithm,
+      expected,
+      root,
     });
     return response.data;
   },
 
+  // Get the stored checksums of a file, newest first
+  getChecksums: async (path: string, expected?: string, root?: string): Promise<ChecksumResult[]> => {
+    const response = await apiClient.get<ChecksumResult[]>('/storage/checksum', {
+      params: { path, expected, root },
+    });
+    return response.data;
+  },
+
+  // Get thumbnail URL for an image or video
+  getPreviewUrl: (path: string, size: PreviewSize = 'medium', root?: string): string => {
+    const baseUrl = apiClient.defaults.baseURL || '/api';
+    const rootParam = root ? `&root=${encodeURIComponent(root)}` : '';
+    return `${baseUrl}/storage/preview?path=${encodeURIComponent(path)}&size=${size}${rootParam}`;
+  },
+
   // Get download URL for a file
-  getDownloadUrl: (path: string): string => {
+  getDownloadUrl: (path: string, root?: string): string => {
     const baseUrl = apiClient.defaults.baseURL || '/api';
-    return `${baseUrl}/storage/download?path=${encodeURIComponent(path)}`;
+    const rootParam = root ? `&root=${encodeURIComponent(root)}` : '';
+    return `${baseUrl}/storage/download?path=${encodeURIComponent(path)}${rootParam}`;
   },
 };
 
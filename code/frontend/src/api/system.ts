@@ -0,0 +1,462 @@
+import apiClient from './client';
+
+export interface RoutePermission {
+  method: string;
+  path: string;
+  // Works without a session
+  public: boolean;
+  // Permission required beyond being signed in
+  permission: string | null;
+  // Whether the current user may call the route
+  allowed: boolean;
+}
+
+export interface PermissionsMap {
+  routes: RoutePermission[];
+}
+
+/**
+ * Get the access rules of all API routes for the current user
+ */
+export const getPermissionsMap = async (): Promise<PermissionsMap> => {
+  const response = await apiClient.get<PermissionsMap>('/system/permissions-map');
+  return response.data;
+};
+
+/**
+ * Whether the current user may call a route, e.g. canCall(map, 'POST', '/api/users').
+ * Routes missing from the map are assumed to be allowed.
+ */
+export const canCall = (map: PermissionsMap, method: string, path: string): boolean => {
+  const route = map.routes.find((r) => r.method === method.toUpperCase() && r.path === path);
+  return route?.allowed ?? true;
+};
+
+export interface SystemLogEntry {
+  seq: number;
+  timestamp: string;
+  level: 'error' | 'warn' | 'info' | 'debug' | 'trace';
+  target: string;
+  message: string;
+}
+
+export type SystemLogLevel = SystemLogEntry['level'];
+
+/**
+ * Get Kubarr's recent backend log lines (requires system.manage)
+ */
+export const getSystemLogs = async (level?: SystemLogLevel, limit?: number): Promise<SystemLogEntry[]> => {
+  const response = await apiClient.get<SystemLogEntry[]>('/system/logs', { params: { level, limit } });
+  return response.data;
+};
+
+/**
+ * Follow backend log lines as they are written. Returns a function that stops following.
+ */
+export const followSystemLogs = (
+  onEntry: (entry: SystemLogEntry) => void,
+  level?: SystemLogLevel
+): (() => void) => {
+  const params = new URLSearchParams({ follow: 'true' });
+  if (level) params.set('level', level);
+  const source = new EventSource(`${apiClient.defaults.baseURL}/system/logs?${params}`, {
+    withCredentials: true,
+  });
+  source.addEventListener('log', (event) => onEntry(JSON.parse((event as MessageEvent).data)));
+  return () => source.close();
+};
+
+/**
+ * URL downloading the whole backend log buffer as text
+ */
+export const systemLogsDownloadUrl = (): string => `${apiClient.defaults.baseURL}/system/logs/download`;
+
+export interface AvailableRelease {
+  version: string;
+  tag: string;
+  url: string;
+  // Release notes (markdown)
+  notes: string | null;
+  published_at: string | null;
+  prerelease: boolean;
+}
+
+export type UpdateState = 'applying' | 'verifying' | 'completed' | 'rolled_back' | 'failed';
+
+export interface UpdateRecord {
+  id: number;
+  from_version: string;
+  to_version: string;
+  status: UpdateState;
+  previous_revision: number;
+  error: string | null;
+  started_by: number | null;
+  started_at: string;
+  finished_at: string | null;
+}
+
+export interface UpdateStatus {
+  current_version: string;
+  channel: string;
+  update_available: boolean;
+  latest: AvailableRelease | null;
+  checked_at: string | null;
+  check_error: string | null;
+  last_update: UpdateRecord | null;
+}
+
+/**
+ * Get whether a newer Kubarr version is available (requires system.manage)
+ */
+export const getUpdateStatus = async (refresh = false): Promise<UpdateStatus> => {
+  const response = await apiClient.get<UpdateStatus>('/system/update', { params: { refresh } });
+  return response.data;
+};
+
+/**
+ * Upgrade Kubarr to a version, by default the latest available
+ */
+export const applyUpdate = async (version?: string): Promise<UpdateRecord> => {
+  const response = await apiClient.post<UpdateRecord>('/system/update/apply', { version });
+  return response.data;
+};
+
+export interface ReleaseNotes {
+  version: string;
+  tag: string;
+  url: string;
+  // Release notes (markdown)
+  notes: string;
+  published_at: string | null;
+  prerelease: boolean;
+  // Whether upgrading to this version needs manual attention
+  breaking: boolean;
+  warnings: string[];
+}
+
+export interface Changelog {
+  current_version: string;
+  channel: string;
+  installed: ReleaseNotes | null;
+  // Newer releases, newest first
+  available: ReleaseNotes[];
+  breaking: boolean;
+  checked_at: string | null;
+  check_error: string | null;
+}
+
+/**
+ * Get the release notes of the running and available Kubarr versions (requires system.manage)
+ */
+export const getChangelog = async (refresh = false): Promise<Changelog> => {
+  const response = await apiClient.get<Changelog>('/system/changelog', { params: { refresh } });
+  return response.data;
+};
+
+export type ReportFrequency = 'weekly' | 'monthly';
+
+export interface ReportSubscription {
+  enabled: boolean;
+  frequency: ReportFrequency;
+  timezone: string;
+  // Local hour of day (0-23)
+  send_hour: number;
+  last_sent_at: string | null;
+  next_send_at: string | null;
+}
+
+export interface Report {
+  frequency: ReportFrequency;
+  from: string;
+  to: string;
+  timezone: string;
+  installed_apps: number;
+  app_restarts: { app_name: string; restarts: number; oom_kills: number }[];
+  uptime: { name: string; status: string; availability: number | null }[];
+  storage: {
+    used_bytes: number | null;
+    used_bytes_before: number | null;
+    capacity_bytes: number | null;
+  };
+  top_users: { username: string; actions: number }[];
+  failed_logins: number;
+  failed_login_sources: { ip_address: string | null; attempts: number }[];
+  pending_update: AvailableRelease | null;
+  subject: string;
+  // Plain-text email body
+  body: string;
+}
+
+export interface SendReportResult {
+  sent: number;
+  failed: number;
+  errors: string[];
+}
+
+/**
+ * Get the current admin's scheduled report subscription
+ */
+export const getReportSubscription = async (): Promise<ReportSubscription> => {
+  const response = await apiClient.get<ReportSubscription>('/system/reports/subscription');
+  return response.data;
+};
+
+/**
+ * Opt in to or out of the scheduled report, or change when it is sent
+ */
+export const updateReportSubscription = async (
+  data: Partial<Pick<ReportSubscription, 'enabled' | 'frequency' | 'timezone' | 'send_hour'>>
+): Promise<ReportSubscription> => {
+  const response = await apiClient.put<ReportSubscription>('/system/reports/subscription', data);
+  return response.data;
+};
+
+/**
+ * Build a report for the period ending now without sending it
+ */
+export const previewReport = async (frequency?: ReportFrequency, timezone?: string): Promise<Report> => {
+  const response = await apiClient.get<Report>('/system/reports/preview', { params: { frequency, timezone } });
+  return response.data;
+};
+
+/**
+ * Email a report now, to the current admin or to every subscriber
+ */
+export const sendReport = async (frequency?: ReportFrequency, allSubscribers = false): Promise<SendReportResult> => {
+  const response = await apiClient.post<SendReportResult>('/system/reports/send', {
+    frequency,
+    all_subscribers: allSubscribers,
+  });
+  return response.data;
+};
+
+export type SecurityFindingStatus = 'pass' | 'warn' | 'fail';
+
+export interface SecurityFinding {
+  id: string;
+  title: string;
+  status: SecurityFindingStatus;
+  severity: 'low' | 'medium' | 'high';
+  detail: string;
+  // Only set when the check did not pass
+  remediation: string | null;
+}
+
+export interface SecurityReport {
+  generated_at: string;
+  // 0-100, weighted by severity
+  score: number;
+  passed: number;
+  warnings: number;
+  failures: number;
+  two_factor_coverage: number;
+  findings: SecurityFinding[];
+}
+
+/**
+ * Check the deployment against the security checklist (requires system.manage)
+ */
+export const getSecurityReport = async (): Promise<SecurityReport> => {
+  const response = await apiClient.get<SecurityReport>('/system/security-report');
+  return response.data;
+};
+
+export interface Registry {
+  id: number;
+  // Registry host as it appears in image names, e.g. ghcr.io
+  host: string;
+  // Host and optional path prefix images are pulled from instead
+  mirror: string | null;
+  username: string | null;
+  has_password: boolean;
+  insecure: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface RegistryRequest {
+  host: string;
+  mirror?: string | null;
+  username?: string | null;
+  // Leave out to keep the stored password of the same username
+  password?: string;
+  insecure?: boolean;
+}
+
+export interface RegistryTest {
+  url: string;
+  reachable: boolean;
+  // Absent when no credentials are stored
+  authenticated?: boolean;
+  status?: number;
+  message: string;
+}
+
+/**
+ * List container registries, without their passwords (requires system.manage)
+ */
+export const getRegistries = async (): Promise<Registry[]> => {
+  const response = await apiClient.get<Registry[]>('/system/registries');
+  return response.data;
+};
+
+/**
+ * Add a container registry mirror or pull credentials
+ */
+export const createRegistry = async (data: RegistryRequest): Promise<Registry> => {
+  const response = await apiClient.post<Registry>('/system/registries', data);
+  return response.data;
+};
+
+/**
+ * Replace a container registry's settings
+ */
+export const updateRegistry = async (id: number, data: RegistryRequest): Promise<Registry> => {
+  const response = await apiClient.put<Registry>(`/system/registries/${id}`, data);
+  return response.data;
+};
+
+/**
+ * Remove a container registry
+ */
+export const deleteRegistry = async (id: number): Promise<void> => {
+  await apiClient.delete(`/system/registries/${id}`);
+};
+
+/**
+ * Check that a registry answers and accepts the stored credentials
+ */
+export const testRegistry = async (id: number): Promise<RegistryTest> => {
+  const response = await apiClient.post<RegistryTest>(`/system/registries/${id}/test`);
+  return response.data;
+};
+
+export interface OutboundTarget {
+  // e.g. charts, telegram or an integration's app name
+  name: string;
+  url: string;
+  // Proxy requests go through, without credentials; null is direct
+  proxy: string | null;
+}
+
+export interface ConnectivityTest extends OutboundTarget {
+  // Whether any HTTP response came back
+  reachable: boolean;
+  status?: number;
+  latency_ms?: number;
+  error?: string;
+}
+
+/**
+ * List the external destinations Kubarr calls and the proxy each goes through
+ */
+export const getOutboundTargets = async (): Promise<OutboundTarget[]> => {
+  const response = await apiClient.get<OutboundTarget[]>('/system/outbound');
+  return response.data;
+};
+
+/**
+ * Check that a destination can be reached through the current proxy settings
+ */
+export const testOutboundTarget = async (name: string): Promise<ConnectivityTest> => {
+  const response = await apiClient.post<ConnectivityTest>(
+    `/system/outbound/${encodeURIComponent(name)}/test`
+  );
+  return response.data;
+};
+
+export interface TrustedCertificate {
+  id: number;
+  name: string;
+  // e.g. CN=Homelab CA, O=Homelab
+  subject: string;
+  issuer: string;
+  // SHA-256 of the certificate, hex
+  fingerprint: string;
+  not_before: string;
+  not_after: string;
+  // expiring: within 30 days
+  status: 'valid' | 'expiring' | 'expired';
+  created_at: string;
+}
+
+/**
+ * List trusted CA certificates, soonest expiry first (requires system.manage)
+ */
+export const getCertificates = async (): Promise<TrustedCertificate[]> => {
+  const response = await apiClient.get<TrustedCertificate[]>('/system/certificates');
+  return response.data;
+};
+
+/**
+ * Trust the certificates in a PEM file or bundle for outbound requests
+ */
+export const createCertificates = async (
+  name: string,
+  pem: string
+): Promise<TrustedCertificate[]> => {
+  const response = await apiClient.post<TrustedCertificate[]>('/system/certificates', {
+    name,
+    pem,
+  });
+  return response.data;
+};
+
+/**
+ * Stop trusting a certificate
+ */
+export const deleteCertificate = async (id: number): Promise<void> => {
+  await apiClient.delete(`/system/certificates/${id}`);
+};
+
+export interface BackupFile {
+  name: string;
+  size_bytes: number;
+  created_at: string;
+}
+
+export interface RestoreSummary {
+  // Kubarr version that made the backup
+  kubarr_version: string;
+  created_at: string;
+  tables: number;
+  rows: number;
+}
+
+/**
+ * Download a backup of the database, with secrets encrypted by the passphrase
+ * (requires system.manage)
+ */
+export const createBackup = async (passphrase: string): Promise<Blob> => {
+  const response = await apiClient.post<Blob>(
+    '/system/backup',
+    { passphrase },
+    { responseType: 'blob', timeout: 0 }
+  );
+  return response.data;
+};
+
+/**
+ * List the scheduled backups, newest first
+ */
+export const getBackups = async (): Promise<BackupFile[]> => {
+  const response = await apiClient.get<BackupFile[]>('/system/backups');
+  return response.data;
+};
+
+export const backupDownloadUrl = (name: string): string =>
+  `${apiClient.defaults.baseURL}/system/backups/${encodeURIComponent(name)}`;
+
+/**
+ * Replace the database with a backup's contents; signs everyone out
+ */
+export const restoreBackup = async (archive: File, passphrase: string): Promise<RestoreSummary> => {
+  const form = new FormData();
+  form.append('passphrase', passphrase);
+  form.append('archive', archive);
+  const response = await apiClient.post<RestoreSummary>('/system/restore', form, {
+    headers: { 'Content-Type': 'multipart/form-data' },
+    timeout: 0,
+  });
+  return response.data;
+};
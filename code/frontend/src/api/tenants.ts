@@ -0,0 +1,84 @@
+import apiClient from './client';
+
+export interface Tenant {
+  id: number;
+  name: string;
+  description: string | null;
+  member_count: number;
+  apps: string[];
+  created_at: string;
+}
+
+export interface TenantMember {
+  user_id: number;
+  username: string;
+  is_admin: boolean;
+  joined_at: string;
+}
+
+export interface TenantRequest {
+  name: string;
+  description?: string;
+}
+
+/**
+ * Get the tenants visible to the current user
+ */
+export const getTenants = async (): Promise<Tenant[]> => {
+  const response = await apiClient.get<Tenant[]>('/tenants');
+  return response.data;
+};
+
+/**
+ * Get tenant by ID
+ */
+export const getTenant = async (tenantId: number): Promise<Tenant> => {
+  const response = await apiClient.get<Tenant>(`/tenants/${tenantId}`);
+  return response.data;
+};
+
+/**
+ * Create a tenant
+ */
+export const createTenant = async (data: TenantRequest): Promise<Tenant> => {
+  const response = await apiClient.post<Tenant>('/tenants', data);
+  return response.data;
+};
+
+/**
+ * Rename a tenant or change its description
+ */
+export const updateTenant = async (tenantId: number, data: TenantRequest): Promise<Tenant> => {
+  const response = await apiClient.put<Tenant>(`/tenants/${tenantId}`, data);
+  return response.data;
+};
+
+/**
+ * Delete a tenant; its apps become shared
+ */
+export const deleteTenant = async (tenantId: number): Promise<void> => {
+  await apiClient.delete(`/tenants/${tenantId}`);
+};
+
+/**
+ * Get the members of a tenant
+ */
+export const getTenantMembers = async (tenantId: number): Promise<TenantMember[]> => {
+  const response = await apiClient.get<TenantMember[]>(`/tenants/${tenantId}/members`);
+  return response.data;
+};
+
+/**
+ * Add a user to a tenant, or change whether they are its admin
+ */
+export const setTenantMember = async (tenantId: number, userId: number, isAdmin = false): Promise<TenantMember> => {
+  const response = await apiClient.put<TenantMember>(`/tenants/${tenantId}/members/${userId}`, { is_admin: isAdmin });
+  return response.data;
+};
+
+/**
+ * Remove a user from a tenant
+ */
+export const removeTenantMember = async (tenantId: number, userId: number): Promise<void> => {
+  await apiClient.delete(`/tenants/${tenantId}/members/${userId}`);
+};
@@ -3,8 +3,16 @@ import { RoleInfo } from './roles';
 
 export type Theme = 'system' | 'light' | 'dark';
 
+export type Locale = 'en' | 'nl' | 'de';
+
 export interface UserPreferences {
   theme: Theme;
+  /** Locale of notifications; null uses the server default */
+  locale?: Locale | null;
+  /** Whether a sign-in from a new device sends a "was this you?" alert */
+  login_alerts?: boolean;
+  /** Time zone app access schedules are read in; null uses the server default */
+  timezone?: string | null;
 }
 
 export interface User {
@@ -15,10 +23,103 @@ export interface User {
   is_approved: boolean;
   created_at: string;
   updated_at: string;
+  // Failed sign-ins since the last successful one
+  failed_login_attempts: number;
+  // Set while sign-ins are refused after too many failures
+  locked_until?: string | null;
   roles: RoleInfo[];
   preferences: UserPreferences;
   permissions: string[];
   allowed_apps: string[];
+  accounts: AccountLink[];
+}
+
+// A user's account in a managed app
+export interface AccountLink {
+  app_name: string;
+  // Account ID in the app, or the username for apps without IDs (qBittorrent)
+  external_id: string;
+  external_username?: string;
+  source: 'manual' | 'auto' | 'provisioned';
+  updated_at: string;
+}
+
+// A client certificate a user authenticates with through the ingress
+export interface ClientCertificate {
+  id: number;
+  user_id: number;
+  name: string;
+  subject: string;
+  fingerprint: string;
+  not_after: string;
+  status: 'valid' | 'expiring' | 'expired';
+  last_used_at: string | null;
+  created_at: string;
+}
+
+// A non-human account for automation, authenticated with API tokens
+export interface ServiceAccount {
+  id: number;
+  name: string;
+  is_active: boolean;
+  roles: { id: number; name: string }[];
+  active_tokens: number;
+  last_used_at: string | null;
+  created_at: string;
+}
+
+export interface ServiceAccountToken {
+  id: number;
+  name: string;
+  token_prefix: string;
+  expires_at: string | null;
+  last_used_at: string | null;
+  created_at: string;
+}
+
+// Returned once on creation; the token itself is never shown again
+export interface CreatedServiceAccountToken extends ServiceAccountToken {
+  token: string;
+}
+
+export interface AccountDiscoveryReport {
+  linked: {
+    user_id: number;
+    username: string;
+    app_name: string;
+    external_id: string;
+    external_username?: string;
+  }[];
+  errors: { app_name: string; error: string }[];
+}
+
+export interface ProvisioningReport {
+  user_id: number;
+  username: string;
+  dry_run: boolean;
+  results: {
+    app_name: string;
+    status: 'created' | 'linked' | 'already_linked' | 'would_create' | 'would_link' | 'not_configured' | 'failed';
+    external_id?: string;
+    error?: string;
+  }[];
+}
+
+export interface DeprovisioningReport {
+  user_id: number;
+  username: string;
+  dry_run: boolean;
+  sessions_revoked: number;
+  challenges_revoked: number;
+  invites_revoked: number;
+  share_access_removed: boolean;
+  accounts: {
+    app_name: string;
+    action: 'keep' | 'disable' | 'delete';
+    status: 'disabled' | 'deleted' | 'kept' | 'would_disable' | 'would_delete' | 'no_account' | 'failed';
+    external_id?: string;
+    error?: string;
+  }[];
 }
 
 export interface CreateUserRequest {
@@ -83,6 +184,22 @@ export const updateUser = async (userId: number, userData: UpdateUserRequest): P
   return response.data;
 };
 
+/**
+ * Assign a role to a user, optionally until a given time (admin only)
+ */
+export const assignUserRole = async (userId: number, roleId: number, expiresAt?: string | null): Promise<User> => {
+  const response = await apiClient.put<User>(`/users/${userId}/roles/${roleId}`, { expires_at: expiresAt ?? null });
+  return response.data;
+};
+
+/**
+ * Remove a role from a user (admin only)
+ */
+export const unassignUserRole = async (userId: number, roleId: number): Promise<User> => {
+  const response = await apiClient.delete<User>(`/users/${userId}/roles/${roleId}`);
+  return response.data;
+};
+
 /**
  * Approve user registration (admin only)
  */
@@ -102,8 +219,12 @@ export const rejectUser = async (userId: number): Promise<{ message: string }> =
 /**
  * Delete user (admin only)
  */
-export const deleteUser = async (userId: number): Promise<{ message: string }> => {
-  const response = await apiClient.delete<{ message: string }>(`/users/${userId}`);
+export const deleteUser = async (
+  userId: number
+): Promise<{ message: string; deprovisioning: DeprovisioningReport }> => {
+  const response = await apiClient.delete<{ message: string; deprovisioning: DeprovisioningReport }>(
+    `/users/${userId}`
+  );
   return response.data;
 };
 
@@ -117,12 +238,31 @@ export interface Invite {
   expires_at: string | null;
   created_at: string;
   used_at: string | null;
+  batch_id: number | null;
+  label: string | null;
 }
 
 export interface CreateInviteRequest {
   expires_in_days?: number;
 }
 
+export interface CreateInviteBatchRequest {
+  label: string;
+  count: number;
+  expires_in_days?: number;
+  share_expires_in_hours?: number;
+}
+
+export interface InviteBatch {
+  id: number;
+  label: string;
+  /** Public page with a QR code per invite that can still be used */
+  share_url: string;
+  share_expires_at: string;
+  created_at: string;
+  invites: Invite[];
+}
+
 /**
  * Get all invites (admin only)
  */
@@ -139,6 +279,22 @@ export const createInvite = async (data?: CreateInviteRequest): Promise<Invite>
   return response.data;
 };
 
+/**
+ * Create a batch of invites with a shared label and share page (admin only)
+ */
+export const createInviteBatch = async (data: CreateInviteBatchRequest): Promise<InviteBatch> => {
+  const response = await apiClient.post<InviteBatch>('/users/invites/batch', data);
+  return response.data;
+};
+
+/**
+ * URL of an invite's QR code image (admin only)
+ */
+export const getInviteQrUrl = (inviteId: number, format: 'png' | 'svg' = 'svg'): string => {
+  const baseUrl = apiClient.defaults.baseURL || '/api';
+  return `${baseUrl}/users/invites/${inviteId}/qr?format=${format}`;
+};
+
 /**
  * Delete an invite (admin only)
  */
@@ -166,6 +322,11 @@ export const updateOwnProfile = async (data: UpdateOwnProfileRequest): Promise<U
 
 export interface UpdatePreferencesRequest {
   theme?: Theme;
+  /** Empty string clears the preference */
+  locale?: Locale | '';
+  login_alerts?: boolean;
+  /** IANA time zone such as Europe/Amsterdam; empty string clears it */
+  timezone?: string;
 }
 
 /**
@@ -197,19 +358,33 @@ export interface AdminResetPasswordRequest {
   new_password: string;
 }
 
+export interface PasswordChangeResponse {
+  message: string;
+  // Sessions signed out by the change
+  sessions_revoked: number;
+}
+
 /**
  * Change own password (requires current password)
  */
-export const changeOwnPassword = async (data: ChangeOwnPasswordRequest): Promise<{ message: string }> => {
-  const response = await apiClient.patch<{ message: string }>('/users/me/password', data);
+export const changeOwnPassword = async (data: ChangeOwnPasswordRequest): Promise<PasswordChangeResponse> => {
+  const response = await apiClient.patch<PasswordChangeResponse>('/users/me/password', data);
   return response.data;
 };
 
 /**
  * Admin reset password for another user (requires users.manage permission)
  */
-export const adminResetPassword = async (userId: number, data: AdminResetPasswordRequest): Promise<{ message: string }> => {
-  const response = await apiClient.patch<{ message: string }>(`/users/${userId}/password`, data);
+export const adminResetPassword = async (userId: number, data: AdminResetPasswordRequest): Promise<PasswordChangeResponse> => {
+  const response = await apiClient.patch<PasswordChangeResponse>(`/users/${userId}/password`, data);
+  return response.data;
+};
+
+/**
+ * Unlock an account locked after too many failed sign-ins (requires users.manage permission)
+ */
+export const unlockUser = async (userId: number): Promise<User> => {
+  const response = await apiClient.post<User>(`/users/${userId}/unlock`);
   return response.data;
 };
 
@@ -269,6 +444,14 @@ export const disable2FA = async (password: string): Promise<{ message: string }>
   return response.data;
 };
 
+/**
+ * URL of the QR code image for a 2FA setup in progress
+ */
+export const get2FAQrUrl = (format: 'png' | 'svg' = 'svg'): string => {
+  const baseUrl = apiClient.defaults.baseURL || '/api';
+  return `${baseUrl}/users/me/2fa/qr?format=${format}`;
+};
+
 /**
  * Get 2FA status
  */
@@ -292,3 +475,274 @@ export const deleteOwnAccount = async (data: DeleteOwnAccountRequest): Promise<{
   const response = await apiClient.delete<{ message: string }>('/users/me', { data });
   return response.data;
 };
+
+// ============================================================================
+// Resource Quota API
+// ============================================================================
+
+export interface QuotaLimits {
+  max_apps: number | null;
+  max_cpu_millicores: number | null;
+  max_memory_bytes: number | null;
+  max_storage_bytes: number | null;
+}
+
+export interface QuotaReport {
+  source: 'user' | 'role' | null;
+  limits: QuotaLimits | null;
+  usage: {
+    apps: number;
+    cpu_millicores: number;
+    memory_bytes: number;
+    storage_bytes: number;
+  };
+}
+
+/**
+ * Get the current user's quota and usage
+ */
+export const getMyQuota = async (): Promise<QuotaReport> => {
+  const response = await apiClient.get<QuotaReport>('/users/me/quota');
+  return response.data;
+};
+
+/**
+ * Get a user's effective quota and usage (admin only)
+ */
+export const getUserQuota = async (userId: number): Promise<QuotaReport> => {
+  const response = await apiClient.get<QuotaReport>(`/users/${userId}/quota`);
+  return response.data;
+};
+
+/**
+ * Set a user's own quota, overriding their roles' quotas (admin only)
+ */
+export const setUserQuota = async (userId: number, limits: QuotaLimits): Promise<QuotaLimits> => {
+  const response = await apiClient.put<QuotaLimits>(`/users/${userId}/quota`, limits);
+  return response.data;
+};
+
+/**
+ * Remove a user's own quota (admin only)
+ */
+export const deleteUserQuota = async (userId: number): Promise<void> => {
+  await apiClient.delete(`/users/${userId}/quota`);
+};
+
+// ============================================================================
+// App Access Schedule API
+// ============================================================================
+
+export interface ScheduleWindow {
+  /** App the window applies to, or '*' for every app */
+  app_name: string;
+  /** Weekdays such as 'sat' and 'sun'; empty is every day */
+  days: string[];
+  /** "HH:MM" in the user's time zone */
+  start_time: string;
+  /** "HH:MM"; before the start to run past midnight */
+  end_time: string;
+}
+
+export interface ScheduleOverride {
+  id: number;
+  user_id: number;
+  app_name: string;
+  reason: string | null;
+  granted_by: number | null;
+  expires_at: string;
+  created_at: string;
+}
+
+/**
+ * Get the schedules set on a user (admin only)
+ */
+export const getUserSchedules = async (userId: number): Promise<ScheduleWindow[]> => {
+  const response = await apiClient.get<ScheduleWindow[]>(`/users/${userId}/schedules`);
+  return response.data;
+};
+
+/**
+ * Replace a user's own schedules, overriding their roles' (admin only)
+ */
+export const setUserSchedules = async (userId: number, windows: ScheduleWindow[]): Promise<ScheduleWindow[]> => {
+  const response = await apiClient.put<ScheduleWindow[]>(`/users/${userId}/schedules`, windows);
+  return response.data;
+};
+
+/**
+ * List a user's running schedule overrides (admin only)
+ */
+export const getScheduleOverrides = async (userId: number): Promise<ScheduleOverride[]> => {
+  const response = await apiClient.get<ScheduleOverride[]>(`/users/${userId}/schedule-overrides`);
+  return response.data;
+};
+
+/**
+ * Let a user past their schedules for an app until a given time (admin only)
+ */
+export const grantScheduleOverride = async (
+  userId: number,
+  data: { app_name: string; expires_at: string; reason?: string }
+): Promise<ScheduleOverride> => {
+  const response = await apiClient.post<ScheduleOverride>(`/users/${userId}/schedule-overrides`, data);
+  return response.data;
+};
+
+/**
+ * End a schedule override early (admin only)
+ */
+export const revokeScheduleOverride = async (userId: number, overrideId: number): Promise<void> => {
+  await apiClient.delete(`/users/${userId}/schedule-overrides/${overrideId}`);
+};
+
+/**
+ * List the client certificates a user can authenticate with
+ */
+export const getClientCertificates = async (userId: number): Promise<ClientCertificate[]> => {
+  const response = await apiClient.get<ClientCertificate[]>(`/users/${userId}/client-certificates`);
+  return response.data;
+};
+
+/**
+ * Register a PEM client certificate for a user (admin only)
+ */
+export const createClientCertificate = async (
+  userId: number,
+  data: { name: string; pem: string }
+): Promise<ClientCertificate> => {
+  const response = await apiClient.post<ClientCertificate>(`/users/${userId}/client-certificates`, data);
+  return response.data;
+};
+
+/**
+ * Remove a user's client certificate (admin only)
+ */
+export const deleteClientCertificate = async (userId: number, certificateId: number): Promise<void> => {
+  await apiClient.delete(`/users/${userId}/client-certificates/${certificateId}`);
+};
+
+/**
+ * List service accounts
+ */
+export const getServiceAccounts = async (): Promise<ServiceAccount[]> => {
+  const response = await apiClient.get<ServiceAccount[]>('/users/service-accounts');
+  return response.data;
+};
+
+/**
+ * Create a service account with the given roles (admin only)
+ */
+export const createServiceAccount = async (data: {
+  name: string;
+  role_ids?: number[];
+}): Promise<ServiceAccount> => {
+  const response = await apiClient.post<ServiceAccount>('/users/service-accounts', data);
+  return response.data;
+};
+
+/**
+ * Get a service account
+ */
+export const getServiceAccount = async (accountId: number): Promise<ServiceAccount> => {
+  const response = await apiClient.get<ServiceAccount>(`/users/service-accounts/${accountId}`);
+  return response.data;
+};
+
+/**
+ * Activate or deactivate a service account or replace its roles (admin only)
+ */
+export const updateServiceAccount = async (
+  accountId: number,
+  data: { is_active?: boolean; role_ids?: number[] }
+): Promise<ServiceAccount> => {
+  const response = await apiClient.patch<ServiceAccount>(`/users/service-accounts/${accountId}`, data);
+  return response.data;
+};
+
+/**
+ * Delete a service account and its tokens (admin only)
+ */
+export const deleteServiceAccount = async (accountId: number): Promise<void> => {
+  await apiClient.delete(`/users/service-accounts/${accountId}`);
+};
+
+/**
+ * List a service account's API tokens
+ */
+export const getServiceAccountTokens = async (accountId: number): Promise<ServiceAccountToken[]> => {
+  const response = await apiClient.get<ServiceAccountToken[]>(`/users/service-accounts/${accountId}/tokens`);
+  return response.data;
+};
+
+/**
+ * Issue an API token for a service account (admin only)
+ */
+export const createServiceAccountToken = async (
+  accountId: number,
+  data: { name: string; expires_in_days?: number }
+): Promise<CreatedServiceAccountToken> => {
+  const response = await apiClient.post<CreatedServiceAccountToken>(
+    `/users/service-accounts/${accountId}/tokens`,
+    data
+  );
+  return response.data;
+};
+
+/**
+ * Revoke a service account's API token (admin only)
+ */
+export const deleteServiceAccountToken = async (accountId: number, tokenId: number): Promise<void> => {
+  await apiClient.delete(`/users/service-accounts/${accountId}/tokens/${tokenId}`);
+};
+
+/**
+ * List a user's accounts in managed apps
+ */
+export const getAccountLinks = async (userId: number): Promise<AccountLink[]> => {
+  const response = await apiClient.get<AccountLink[]>(`/users/${userId}/accounts`);
+  return response.data;
+};
+
+/**
+ * Link a user to their account in an app (admin only)
+ */
+export const setAccountLink = async (
+  userId: number,
+  appName: string,
+  data: { external_id: string; external_username?: string }
+): Promise<AccountLink> => {
+  const response = await apiClient.put<AccountLink>(`/users/${userId}/accounts/${appName}`, data);
+  return response.data;
+};
+
+/**
+ * Remove a user's link to an app account (admin only)
+ */
+export const deleteAccountLink = async (userId: number, appName: string): Promise<void> => {
+  await apiClient.delete(`/users/${userId}/accounts/${appName}`);
+};
+
+/**
+ * Link users to matching accounts in Jellyfin, Jellyseerr and Overseerr (admin only)
+ */
+export const discoverAccountLinks = async (): Promise<AccountDiscoveryReport> => {
+  const response = await apiClient.post<AccountDiscoveryReport>('/users/accounts/discover');
+  return response.data;
+};
+
+/**
+ * Create or link a user's app accounts from their roles' provisioning profiles (admin only)
+ */
+export const provisionUser = async (userId: number, dryRun = false): Promise<ProvisioningReport> => {
+  const response = await apiClient.post<ProvisioningReport>(`/users/${userId}/provision`, { dry_run: dryRun });
+  return response.data;
+};
+
+/**
+ * Deprovision a deactivated user, or preview it for any user with dryRun (admin only)
+ */
+export const deprovisionUser = async (userId: number, dryRun = false): Promise<DeprovisioningReport> => {
+  const response = await apiClient.post<DeprovisioningReport>(`/users/${userId}/deprovision`, { dry_run: dryRun });
+  return response.data;
+};
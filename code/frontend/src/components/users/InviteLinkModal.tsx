@@ -1,6 +1,5 @@
 import React, { useState } from 'react';
-import { QRCodeSVG } from 'qrcode.react';
-import { Invite } from '../../api/users';
+import { Invite, getInviteQrUrl } from '../../api/users';
 
 interface InviteLinkModalProps {
   invite: Invite;
@@ -76,11 +75,11 @@ const InviteLinkModal: React.FC<InviteLinkModalProps> = ({ invite, onClose }) =>
         <div className="flex flex-col items-center">
           <label className="block text-sm font-medium text-gray-300 mb-3">QR Code</label>
           <div className="bg-white p-4 rounded-lg">
-            <QRCodeSVG
-              value={inviteUrl}
-              size={180}
-              level="M"
-              includeMargin={false}
+            <img
+              src={getInviteQrUrl(invite.id)}
+              alt="Invite QR code"
+              width={180}
+              height={180}
             />
           </div>
           <p className="text-gray-500 text-xs mt-2">Scan to open the registration page</p>
@@ -3,7 +3,7 @@ import { useQueryClient } from '@tanstack/react-query'
 import { monitoringApi, ClusterMetrics } from '../api/monitoring'
 import { appsApi } from '../api/apps'
 import { preloadIcons } from '../components/AppIcon'
-import type { AppConfig, PodStatus } from '../types'
+import type { CatalogApp, PodStatus } from '../types'
 import { getPrecached, clearPrecache } from '../utils/precache'
 
 interface AppStatusInfo {
@@ -19,7 +19,7 @@ interface MonitoringState {
   metricsAvailable: boolean | null
 
   // Apps data
-  catalog: AppConfig[]
+  catalog: CatalogApp[]
   catalogLoading: boolean
   installedApps: string[]
   appStatuses: Record<string, AppStatusInfo>
@@ -36,7 +36,7 @@ export function MonitoringProvider({ children }: { children: ReactNode }) {
 
   // Check for precached data on initial mount
   const precachedMetrics = getPrecached<ClusterMetrics>('clusterMetrics')
-  const precachedCatalog = getPrecached<AppConfig[]>('catalog')
+  const precachedCatalog = getPrecached<CatalogApp[]>('catalog')
   const precachedInstalled = getPrecached<string[]>('installed')
 
   // Cluster metrics state - initialize from precache if available
@@ -45,7 +45,7 @@ export function MonitoringProvider({ children }: { children: ReactNode }) {
   const [metricsAvailable, setMetricsAvailable] = useState<boolean | null>(precachedMetrics ? true : null)
 
   // Apps state - initialize from precache if available
-  const [catalog, setCatalog] = useState<AppConfig[]>(precachedCatalog || [])
+  const [catalog, setCatalog] = useState<CatalogApp[]>(precachedCatalog || [])
   const [catalogLoading, setCatalogLoading] = useState(!precachedCatalog)
   const [installedApps, setInstalledApps] = useState<string[]>(precachedInstalled || [])
   const [appStatuses, setAppStatuses] = useState<Record<string, AppStatusInfo>>({})
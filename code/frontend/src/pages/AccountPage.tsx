@@ -2,9 +2,8 @@ import { useState, useEffect } from 'react'
 import { useAuth } from '../contexts/AuthContext'
 import { useTheme } from '../contexts/ThemeContext'
 import { User, Mail, Shield, Sun, Moon, Monitor, Check, Key, Smartphone, AlertTriangle, Eye, EyeOff, Loader2, Link2, Unlink, Palette, Info, Clock, Globe, Trash2, History, Pencil, X } from 'lucide-react'
-import { QRCodeSVG } from 'qrcode.react'
 import type { Theme, TwoFactorStatusResponse, TwoFactorSetupResponse } from '../api/users'
-import { changeOwnPassword, get2FAStatus, getRecoveryCodeCount, setup2FA, enable2FA, disable2FA, updateOwnProfile, deleteOwnAccount } from '../api/users'
+import { changeOwnPassword, get2FAStatus, get2FAQrUrl, getRecoveryCodeCount, setup2FA, enable2FA, disable2FA, updateOwnProfile, deleteOwnAccount, updateMyPreferences } from '../api/users'
 import { oauthApi, type LinkedAccount, type AvailableProvider } from '../api/oauth'
 import { getSessions, revokeSession, type SessionInfo } from '../api/auth'
 import { auditApi, type AuditLog } from '../api/audit'
@@ -57,6 +56,7 @@ export default function AccountPage() {
   const [sessions, setSessions] = useState<SessionInfo[]>([])
   const [loadingSessions, setLoadingSessions] = useState(true)
   const [revokingSession, setRevokingSession] = useState<string | null>(null)
+  const [savingLoginAlerts, setSavingLoginAlerts] = useState(false)
 
   // Audit log state
   const [auditLogs, setAuditLogs] = useState<AuditLog[]>([])
@@ -158,8 +158,12 @@ export default function AccountPage() {
 
     setChangingPassword(true)
     try {
-      await changeOwnPassword({ current_password: currentPassword, new_password: newPassword })
-      setPasswordSuccess('Password changed successfully')
+      const result = await changeOwnPassword({ current_password: currentPassword, new_password: newPassword })
+      setPasswordSuccess(
+        result.sessions_revoked > 0
+          ? `Password changed successfully. ${result.sessions_revoked} other session(s) were signed out.`
+          : 'Password changed successfully'
+      )
       setCurrentPassword('')
       setNewPassword('')
       setConfirmPassword('')
@@ -277,6 +281,19 @@ export default function AccountPage() {
     }
   }
 
+  // Toggle "was this you?" alerts for new-device sign-ins
+  const handleToggleLoginAlerts = async (enabled: boolean) => {
+    setSavingLoginAlerts(true)
+    try {
+      await updateMyPreferences({ login_alerts: enabled })
+      await checkAuth()
+    } catch (err) {
+      console.error('Failed to update login alerts:', err)
+    } finally {
+      setSavingLoginAlerts(false)
+    }
+  }
+
   // Format user agent for display
   const formatUserAgent = (ua: string | null): string => {
     if (!ua) return 'Unknown device'
@@ -814,6 +831,23 @@ export default function AccountPage() {
             Active Sessions
           </h3>
 
+          <div className="flex items-start mb-4">
+            <input
+              type="checkbox"
+              id="login-alerts"
+              className="h-4 w-4 mt-0.5 rounded text-blue-600 focus:ring-2 focus:ring-blue-500"
+              checked={user?.preferences.login_alerts ?? true}
+              disabled={savingLoginAlerts}
+              onChange={e => handleToggleLoginAlerts(e.target.checked)}
+            />
+            <label htmlFor="login-alerts" className="ml-2 text-sm text-gray-700 dark:text-gray-300">
+              Alert me when my account signs in from a new device
+              <span className="block text-xs text-gray-500 dark:text-gray-400">
+                The alert includes a link to sign that session out if it wasn't you.
+              </span>
+            </label>
+          </div>
+
           {loadingSessions ? (
             <div className="flex items-center gap-2 text-gray-500">
               <Loader2 size={16} className="animate-spin" />
@@ -1045,10 +1079,11 @@ export default function AccountPage() {
                     </p>
                     <div className="flex flex-col sm:flex-row gap-6 items-start">
                       <div className="bg-white p-3 rounded-lg">
-                        <QRCodeSVG
-                          value={setupData.provisioning_uri}
-                          size={180}
-                          level="M"
+                        <img
+                          src={get2FAQrUrl()}
+                          alt="Authenticator setup QR code"
+                          width={180}
+                          height={180}
                         />
                       </div>
                       <div className="flex-1 space-y-3">
@@ -375,6 +375,7 @@ function AppDetailPanel({
   const { hasPermission } = useAuth()
   const canViewVpn = hasPermission('vpn.view')
   const canManageVpn = hasPermission('vpn.manage')
+  const canStopApps = hasPermission('apps.stop')
   const queryClient = useQueryClient()
   const [selectedProviderId, setSelectedProviderId] = useState<number | null>(null)
   const [killSwitchOverride, setKillSwitchOverride] = useState<boolean | null>(null)
@@ -436,6 +437,25 @@ function AppDetailPanel({
     },
   })
 
+  // Stopped apps are scaled to zero, which pod health alone can't tell apart
+  // from a broken app
+  const { data: runStatus } = useQuery({
+    queryKey: ['app-status', app?.name],
+    queryFn: () => appsApi.getStatus(app!.name),
+    enabled: !!app && isInstalled && !app.is_system,
+    refetchInterval: 10000,
+  })
+  const isStopped = runStatus?.state === 'stopped'
+  const isWaiting = runStatus?.state === 'waiting'
+
+  const powerMutation = useMutation({
+    mutationFn: ({ appName, start }: { appName: string; start: boolean }) =>
+      start ? appsApi.start(appName) : appsApi.stop(appName),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ['app-status', app?.name] })
+    },
+  })
+
   const enabledProviders = useMemo(() => vpnProviders?.filter((p: VpnProvider) => p.enabled) || [], [vpnProviders])
 
   if (!app) return null
@@ -509,6 +529,26 @@ function AppDetailPanel({
                       Open
                     </button>
                   )}
+                  {canStopApps && !app.is_system && isInstalled && effectiveState === 'installed' && (
+                    <button
+                      onClick={() => powerMutation.mutate({ appName: app.name, start: isStopped })}
+                      disabled={powerMutation.isPending}
+                      className="bg-gray-100 dark:bg-gray-800 hover:bg-gray-200 dark:hover:bg-gray-700 disabled:cursor-not-allowed text-gray-600 dark:text-gray-300 text-sm font-semibold py-2 px-4 rounded-xl transition-colors flex items-center gap-1.5"
+                    >
+                      {isStopped ? (
+                        <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M14.752 11.168l-3.197-2.132A1 1 0 0010 9.87v4.263a1 1 0 001.555.832l3.197-2.132a1 1 0 000-1.664z" />
+                          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 12a9 9 0 11-18 0 9 9 0 0118 0z" />
+                        </svg>
+                      ) : (
+                        <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 12a9 9 0 11-18 0 9 9 0 0118 0z" />
+                          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M9 10a1 1 0 011-1h4a1 1 0 011 1v4a1 1 0 01-1 1h-4a1 1 0 01-1-1v-4z" />
+                        </svg>
+                      )}
+                      {isStopped ? 'Start' : 'Stop'}
+                    </button>
+                  )}
                   {!app.is_system && isInstalled && effectiveState === 'installed' && (
                     <button
                       onClick={onDelete}
@@ -556,7 +596,19 @@ function AppDetailPanel({
                     System App
                   </span>
                 )}
-                {!app.is_system && isInstalled && (
+                {!app.is_system && isInstalled && isStopped && (
+                  <span className="inline-flex items-center gap-1.5 bg-gray-500/20 text-gray-600 dark:text-gray-400 text-sm px-3 py-1 rounded-full">
+                    <span className="w-2 h-2 rounded-full bg-gray-500"></span>
+                    Stopped
+                  </span>
+                )}
+                {!app.is_system && isInstalled && isWaiting && (
+                  <span className="inline-flex items-center gap-1.5 bg-blue-500/20 text-blue-600 dark:text-blue-400 text-sm px-3 py-1 rounded-full">
+                    <span className="w-2 h-2 rounded-full bg-blue-500 animate-pulse"></span>
+                    {runStatus?.message}
+                  </span>
+                )}
+                {!app.is_system && isInstalled && !isStopped && !isWaiting && (
                   <span className={`inline-flex items-center gap-1.5 text-sm px-3 py-1 rounded-full ${
                     isHealthy
                       ? 'bg-green-500/20 text-green-600 dark:text-green-400'
@@ -966,19 +1018,29 @@ export default function AppsPage() {
   }
 
   const installMutation = useMutation({
-    mutationFn: (appName: string) => {
+    mutationFn: ({ appName, allowIncompatibleArch }: { appName: string; allowIncompatibleArch: boolean }) => {
       setOperationState(appName, 'installing')
-      return appsApi.install({ app_name: appName, namespace: appName })
+      return appsApi.install({ app_name: appName, namespace: appName, allow_incompatible_arch: allowIncompatibleArch })
     },
-    onSuccess: (_data, appName) => {
+    onSuccess: (_data, { appName }) => {
       pollHealth(appName)
     },
-    onError: (error: any, appName) => {
+    onError: (error: any, { appName }) => {
       setOperationState(appName, 'error', error.response?.data?.detail || error.message)
       showToast(`Failed to install ${appName}: ${error.response?.data?.detail || error.message}`, 'error')
     },
   })
 
+  // Apps no node of the cluster can run are only installed on confirmation
+  const handleInstall = (appName: string) => {
+    const app = catalog.find(a => a.name === appName)
+    const incompatible = app?.compatible === false
+    if (incompatible && !window.confirm(`${app?.warning}. Install anyway?`)) {
+      return
+    }
+    installMutation.mutate({ appName, allowIncompatibleArch: incompatible })
+  }
+
   const deleteMutation = useMutation({
     mutationFn: (appName: string) => {
       setOperationState(appName, 'deleting')
@@ -1153,7 +1215,7 @@ export default function AppsPage() {
                         isHealthy={isHealthy}
                         effectiveState={effectiveState}
                         isSelected={selectedApp?.name === app.name}
-                        onInstall={() => installMutation.mutate(app.name)}
+                        onInstall={() => handleInstall(app.name)}
                         onDelete={() => deleteMutation.mutate(app.name)}
                         onOpen={() => handleOpen(app)}
                         onClick={() => setSelectedApp(app)}
@@ -1177,7 +1239,7 @@ export default function AppsPage() {
             isInstalled={isInstalled}
             isHealthy={isHealthy}
             effectiveState={effectiveState}
-            onInstall={() => installMutation.mutate(selectedApp.name)}
+            onInstall={() => handleInstall(selectedApp.name)}
             onDelete={() => deleteMutation.mutate(selectedApp.name)}
             onOpen={() => handleOpen(selectedApp)}
             isOperationPending={installMutation.isPending || deleteMutation.isPending}
@@ -445,6 +445,8 @@ const SettingsPage: React.FC = () => {
       case 'telegram':
         return [
           { key: 'bot_token', label: 'Bot Token', type: 'password', placeholder: '123456:ABC-DEF...' },
+          { key: 'public_url', label: 'Public URL', type: 'url', placeholder: 'https://kubarr.example.com' },
+          { key: 'webhook_secret', label: 'Webhook Secret', type: 'password', placeholder: '********' },
         ];
       case 'messagebird':
         return [
@@ -18,7 +18,7 @@ export default function StoragePage() {
   // Fetch storage stats
   const { data: stats, isLoading: statsLoading } = useQuery({
     queryKey: ['storage', 'stats'],
-    queryFn: storageApi.getStats,
+    queryFn: () => storageApi.getStats(),
     refetchInterval: 30000, // Refresh every 30 seconds
   })
 
@@ -264,10 +264,10 @@ export default function StoragePage() {
                     </div>
                   </td>
                   <td className="px-4 py-3 text-gray-500 dark:text-gray-400">
-                    {item.type === 'directory' ? '-' : formatBytes(item.size)}
+                    {item.type === 'directory' ? '-' : formatBytes(item.size ?? 0)}
                   </td>
-                  <td className="px-4 py-3 text-gray-500 dark:text-gray-400">{formatDate(item.modified)}</td>
-                  <td className="px-4 py-3 text-gray-500 dark:text-gray-400 font-mono">{item.permissions}</td>
+                  <td className="px-4 py-3 text-gray-500 dark:text-gray-400">{item.modified ? formatDate(item.modified) : '-'}</td>
+                  <td className="px-4 py-3 text-gray-500 dark:text-gray-400 font-mono">{item.permissions ?? '-'}</td>
                 </tr>
               ))}
             </tbody>
@@ -12,10 +12,12 @@ export function createMockUser(overrides: Partial<User> = {}): User {
     is_approved: true,
     created_at: '2024-01-01T00:00:00Z',
     updated_at: '2024-01-01T00:00:00Z',
+    failed_login_attempts: 0,
     roles: [{ id: 2, name: 'user', description: 'Regular user' }],
     preferences: { theme: 'system' },
     permissions: ['apps.view'],
     allowed_apps: ['sonarr', 'radarr'],
+    accounts: [],
     ...overrides,
   };
 }
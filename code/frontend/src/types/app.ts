@@ -13,6 +13,14 @@ export interface AppConfig {
   is_system: boolean;
   is_hidden: boolean;
   is_browseable: boolean;
+  /** Empty when the app runs on any architecture */
+  architectures: string[];
+}
+
+/** A catalog app with its fit for the cluster's node architectures */
+export interface CatalogApp extends AppConfig {
+  compatible: boolean;
+  warning?: string;
 }
 
 export interface ResourceRequirements {
@@ -33,6 +41,8 @@ export interface DeploymentRequest {
   app_name: string;
   namespace?: string;
   custom_config?: Record<string, any>;
+  /** Install even though no node matches the app's architectures */
+  allow_incompatible_arch?: boolean;
 }
 
 export interface DeploymentStatus {